@@ -0,0 +1,281 @@
+//! C ABI over [`JsonSimulation`].
+//!
+//! Callers own an opaque `RtsSimulation *` created by
+//! [`rts_simulation_new`] and released with [`rts_simulation_free`]. Strings
+//! returned by this module are heap-allocated and must be released with
+//! [`rts_string_free`]. Functions that can fail return null or a negative
+//! status; the reason is available from [`rts_last_error`] on the same thread.
+//!
+//! From Python:
+//!
+//! ```python
+//! lib = ctypes.CDLL("librts_bindings.so")
+//! lib.rts_simulation_new.restype = ctypes.c_void_p
+//! lib.rts_simulation_run.restype = ctypes.c_void_p
+//! sim = lib.rts_simulation_new(setup_json.encode())
+//! report = lib.rts_simulation_run(ctypes.c_void_p(sim), ctypes.c_uint64(600))
+//! print(ctypes.string_at(report).decode())
+//! lib.rts_string_free(ctypes.c_void_p(report))
+//! lib.rts_simulation_free(ctypes.c_void_p(sim))
+//! ```
+
+#![allow(unsafe_code)]
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+use crate::json::{BindingError, JsonSimulation};
+
+/// Opaque simulation handle.
+pub struct RtsSimulation(JsonSimulation);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl Into<Vec<u8>>) {
+    let message = CString::new(message).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+fn record<T>(result: Result<T, BindingError>) -> Option<T> {
+    result.map_err(|e| set_last_error(e.to_string())).ok()
+}
+
+/// Borrow a caller-provided C string as UTF-8.
+///
+/// # Safety
+///
+/// `s` must be null or point to a NUL-terminated string.
+unsafe fn read_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        set_last_error("null string argument");
+        return None;
+    }
+    match CStr::from_ptr(s).to_str() {
+        Ok(s) => Some(s),
+        Err(e) => {
+            set_last_error(e.to_string());
+            None
+        }
+    }
+}
+
+/// Borrow a caller's handle, recording an error if it is null.
+fn live<T>(handle: Option<T>) -> Option<T> {
+    if handle.is_none() {
+        set_last_error("null simulation handle");
+    }
+    handle
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    CString::new(s).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Version of the `rts_core::api` data formats these bindings speak.
+#[no_mangle]
+pub extern "C" fn rts_api_version() -> u32 {
+    rts_core::api::API_VERSION
+}
+
+/// Message describing the last failed call on this thread, or null.
+///
+/// The pointer stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn rts_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Create a simulation from a JSON `MatchSetup`. Returns null on error.
+///
+/// # Safety
+///
+/// `setup_json` must be null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rts_simulation_new(setup_json: *const c_char) -> *mut RtsSimulation {
+    read_str(setup_json)
+        .and_then(|setup| record(JsonSimulation::from_setup_json(setup)))
+        .map_or(ptr::null_mut(), |sim| {
+            Box::into_raw(Box::new(RtsSimulation(sim)))
+        })
+}
+
+/// Release a simulation. Passing null is a no-op.
+///
+/// # Safety
+///
+/// `sim` must be null or a handle from [`rts_simulation_new`] that has not
+/// already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn rts_simulation_free(sim: *mut RtsSimulation) {
+    if !sim.is_null() {
+        drop(Box::from_raw(sim));
+    }
+}
+
+/// Replace an entity's orders with a JSON `ApiCommand`.
+///
+/// Returns 0 on success and -1 on error.
+///
+/// # Safety
+///
+/// `sim` must be a live handle and `command_json` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rts_simulation_submit(
+    sim: *mut RtsSimulation,
+    entity: u64,
+    command_json: *const c_char,
+) -> i32 {
+    let Some(sim) = sim.as_mut() else {
+        set_last_error("null simulation handle");
+        return -1;
+    };
+    read_str(command_json)
+        .and_then(|command| record(sim.0.submit_json(entity, command)))
+        .map_or(-1, |()| 0)
+}
+
+/// Advance one tick and return the `TickReport` as JSON (free with
+/// [`rts_string_free`]). Returns null on a null handle.
+///
+/// # Safety
+///
+/// `sim` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn rts_simulation_tick(sim: *mut RtsSimulation) -> *mut c_char {
+    live(sim.as_mut()).map_or(ptr::null_mut(), |sim| into_c_string(sim.0.tick_json()))
+}
+
+/// Advance up to `ticks` ticks, stopping early once the match is decided.
+/// Returns one `TickReport` covering every tick run as JSON (free with
+/// [`rts_string_free`]), or null on a null handle.
+///
+/// # Safety
+///
+/// `sim` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn rts_simulation_run(sim: *mut RtsSimulation, ticks: u64) -> *mut c_char {
+    live(sim.as_mut()).map_or(ptr::null_mut(), |sim| into_c_string(sim.0.run_json(ticks)))
+}
+
+/// Current `StateSnapshot` as JSON (free with [`rts_string_free`]), or
+/// null on a null handle.
+///
+/// # Safety
+///
+/// `sim` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn rts_simulation_snapshot(sim: *const RtsSimulation) -> *mut c_char {
+    live(sim.as_ref()).map_or(ptr::null_mut(), |sim| into_c_string(sim.0.snapshot_json()))
+}
+
+/// Current tick.
+///
+/// # Safety
+///
+/// `sim` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn rts_simulation_current_tick(sim: *const RtsSimulation) -> u64 {
+    sim.as_ref().map_or(0, |sim| sim.0.current_tick())
+}
+
+/// Deterministic state hash.
+///
+/// # Safety
+///
+/// `sim` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn rts_simulation_state_hash(sim: *const RtsSimulation) -> u64 {
+    sim.as_ref().map_or(0, |sim| sim.0.state_hash())
+}
+
+/// Winning faction as its `FactionId` declaration index, or -1 while the
+/// match is undecided.
+///
+/// # Safety
+///
+/// `sim` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn rts_simulation_winner(sim: *const RtsSimulation) -> i32 {
+    sim.as_ref()
+        .and_then(|sim| sim.0.winner())
+        .map_or(-1, |faction| faction as i32)
+}
+
+/// Release a string returned by this library. Passing null is a no-op.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by this library that has not
+/// already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn rts_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take_string(s: *mut c_char) -> String {
+        assert!(!s.is_null());
+        // SAFETY: non-null strings come from `into_c_string`.
+        unsafe {
+            let owned = CStr::from_ptr(s).to_string_lossy().into_owned();
+            rts_string_free(s);
+            owned
+        }
+    }
+
+    #[test]
+    fn test_lifecycle_through_c_abi() {
+        let setup = CString::new(r#"{ "units": [] }"#).unwrap();
+        // SAFETY: all pointers are created in this test and freed once.
+        unsafe {
+            let sim = rts_simulation_new(setup.as_ptr());
+            assert!(!sim.is_null());
+
+            let report = take_string(rts_simulation_tick(sim));
+            assert!(report.contains("\"tick\":1"));
+            assert_eq!(rts_simulation_current_tick(sim), 1);
+            assert_eq!(rts_simulation_winner(sim), -1);
+
+            let snapshot = take_string(rts_simulation_snapshot(sim));
+            assert!(snapshot.contains("\"entities\":[]"));
+
+            rts_simulation_free(sim);
+        }
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        let bad = CString::new("{").unwrap();
+        let stop = CString::new(r#""Stop""#).unwrap();
+        let empty = CString::new("{}").unwrap();
+        // SAFETY: all pointers are created in this test and freed once.
+        unsafe {
+            assert!(rts_simulation_new(bad.as_ptr()).is_null());
+            assert!(!rts_last_error().is_null());
+
+            let sim = rts_simulation_new(empty.as_ptr());
+            assert_eq!(rts_simulation_submit(sim, 42, stop.as_ptr()), -1);
+            let message = CStr::from_ptr(rts_last_error()).to_string_lossy();
+            assert!(message.contains("42"));
+            rts_simulation_free(sim);
+
+            let null_handle = |string: *mut c_char| {
+                assert!(string.is_null());
+                let message = CStr::from_ptr(rts_last_error()).to_string_lossy();
+                assert_eq!(message, "null simulation handle");
+                set_last_error("");
+            };
+            null_handle(rts_simulation_tick(ptr::null_mut()));
+            null_handle(rts_simulation_run(ptr::null_mut(), 10));
+            null_handle(rts_simulation_snapshot(ptr::null()));
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! JSON boundary shared by the C and WebAssembly front-ends.
+//!
+//! Everything crossing the language boundary is either a scalar or a JSON
+//! document matching the serde form of the `rts_core::api` types. Keeping the
+//! conversion here means the `ffi` and `wasm` modules are thin shims and the
+//! behaviour can be tested without either feature enabled.
+
+use rts_core::api::{ApiCommand, EmbeddedSimulation, MatchSetup};
+use rts_core::components::EntityId;
+use rts_core::error::GameError;
+use rts_core::factions::FactionId;
+use thiserror::Error;
+
+/// Errors surfaced to foreign callers.
+#[derive(Error, Debug)]
+pub enum BindingError {
+    /// Input was not valid JSON for the expected type.
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The simulation rejected the request.
+    #[error(transparent)]
+    Game(#[from] GameError),
+}
+
+/// Result alias for binding operations.
+pub type Result<T> = std::result::Result<T, BindingError>;
+
+/// An [`EmbeddedSimulation`] with a string-in, string-out interface.
+#[derive(Debug)]
+pub struct JsonSimulation {
+    inner: EmbeddedSimulation,
+}
+
+impl JsonSimulation {
+    /// Build a match from a JSON-encoded [`MatchSetup`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is malformed or the setup is rejected.
+    pub fn from_setup_json(setup: &str) -> Result<Self> {
+        let setup: MatchSetup = serde_json::from_str(setup)?;
+        Ok(Self {
+            inner: EmbeddedSimulation::new(&setup)?,
+        })
+    }
+
+    /// Restore a match from bytes produced by [`Self::save_state`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are not a valid saved state.
+    pub fn load_state(bytes: &[u8]) -> Result<Self> {
+        Ok(Self {
+            inner: EmbeddedSimulation::load_state(bytes)?,
+        })
+    }
+
+    /// Replace an entity's orders with a JSON-encoded [`ApiCommand`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is malformed or the entity does not exist.
+    pub fn submit_json(&mut self, entity: EntityId, command: &str) -> Result<()> {
+        let command: ApiCommand = serde_json::from_str(command)?;
+        self.inner.submit(entity, command)?;
+        Ok(())
+    }
+
+    /// Advance one tick and return the [`rts_core::api::TickReport`] as JSON.
+    pub fn tick_json(&mut self) -> String {
+        to_json(&self.inner.tick())
+    }
+
+    /// Advance up to `ticks` ticks and return one report covering them all
+    /// (see [`EmbeddedSimulation::run`]) as JSON.
+    pub fn run_json(&mut self, ticks: u64) -> String {
+        to_json(&self.inner.run(ticks))
+    }
+
+    /// Current [`rts_core::api::StateSnapshot`] as JSON.
+    #[must_use]
+    pub fn snapshot_json(&self) -> String {
+        to_json(&self.inner.snapshot())
+    }
+
+    /// Serialize the full simulation state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    pub fn save_state(&self) -> Result<Vec<u8>> {
+        Ok(self.inner.save_state()?)
+    }
+
+    /// Current tick.
+    #[must_use]
+    pub const fn current_tick(&self) -> u64 {
+        self.inner.current_tick()
+    }
+
+    /// Deterministic state hash, for desync checks across languages.
+    #[must_use]
+    pub fn state_hash(&self) -> u64 {
+        self.inner.state_hash()
+    }
+
+    /// Winning faction, if the match is decided.
+    #[must_use]
+    pub const fn winner(&self) -> Option<FactionId> {
+        self.inner.winner()
+    }
+
+    /// Borrow the wrapped facade.
+    #[must_use]
+    pub const fn inner(&self) -> &EmbeddedSimulation {
+        &self.inner
+    }
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> String {
+    // The api types only contain plain data, so serialization cannot fail.
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rts_core::api::{StateSnapshot, TickReport};
+    use rts_core::map_generation::MapConfig;
+
+    const SETUP: &str = r#"{
+        "units": [
+            { "faction": "Continuity", "position": { "x": 429496729600, "y": 429496729600 },
+              "health": 100, "is_depot": true },
+            { "faction": "Collegium", "position": { "x": 644245094400, "y": 429496729600 },
+              "health": 50, "is_depot": true },
+            { "faction": "Continuity", "position": { "x": 472446402560, "y": 429496729600 },
+              "health": 100, "speed": 8589934592,
+              "combat": { "damage": 25, "range": 214748364800, "cooldown": 1 } }
+        ]
+    }"#;
+
+    #[test]
+    fn test_setup_from_json() {
+        let sim = JsonSimulation::from_setup_json(SETUP).unwrap();
+        let snapshot: StateSnapshot = serde_json::from_str(&sim.snapshot_json()).unwrap();
+        assert_eq!(snapshot.entities.len(), 3);
+        assert_eq!(snapshot.entities[1].health, Some(50));
+    }
+
+    #[test]
+    fn test_submit_json_and_run() {
+        let mut sim = JsonSimulation::from_setup_json(SETUP).unwrap();
+        sim.submit_json(3, r#"{ "Attack": 2 }"#).unwrap();
+
+        let report: TickReport = serde_json::from_str(&sim.run_json(200)).unwrap();
+        assert_eq!(report.winner, Some(FactionId::Continuity));
+        assert_eq!(report.deaths, vec![2]);
+        assert_eq!(sim.winner(), Some(FactionId::Continuity));
+    }
+
+    #[test]
+    fn test_invalid_json_reports_error() {
+        assert!(matches!(
+            JsonSimulation::from_setup_json("{ not json"),
+            Err(BindingError::Json(_))
+        ));
+
+        let mut sim = JsonSimulation::from_setup_json(SETUP).unwrap();
+        assert!(matches!(
+            sim.submit_json(99, r#""Stop""#),
+            Err(BindingError::Game(GameError::EntityNotFound(99)))
+        ));
+    }
+
+    #[test]
+    fn test_save_load_preserves_hash() {
+        let mut sim = JsonSimulation::from_setup_json(SETUP).unwrap();
+        sim.run_json(10);
+
+        let restored = JsonSimulation::load_state(&sim.save_state().unwrap()).unwrap();
+        assert_eq!(restored.state_hash(), sim.state_hash());
+        assert_eq!(restored.current_tick(), 10);
+    }
+
+    #[test]
+    fn test_save_load_keeps_map() {
+        let setup = MatchSetup::new(Vec::new()).with_map(MapConfig::small().with_seed(7));
+        let sim = JsonSimulation::from_setup_json(&serde_json::to_string(&setup).unwrap()).unwrap();
+        let restored = JsonSimulation::load_state(&sim.save_state().unwrap()).unwrap();
+
+        let blocked = |sim: &JsonSimulation| {
+            let grid = sim.inner().inner().nav_grid();
+            (0..64)
+                .flat_map(|y| (0..64).map(move |x| (x, y)))
+                .filter(|&(x, y)| !grid.is_walkable(x, y))
+                .count()
+        };
+        assert!(blocked(&sim) > 0);
+        assert_eq!(blocked(&restored), blocked(&sim));
+    }
+}
@@ -0,0 +1,34 @@
+//! Foreign-language bindings for the deterministic simulation.
+//!
+//! Wraps [`rts_core::api`] so the simulation can be driven from outside Rust:
+//!
+//! - `ffi` feature: `extern "C"` functions over an opaque `RtsSimulation`
+//!   handle, for C, C++ or Python (`ctypes`/`cffi`). Generate the header with
+//!   `cbindgen --config cbindgen.toml --crate rts_bindings`.
+//! - `wasm` feature: a `wasm-bindgen` class for browser-based replay viewers,
+//!   built with `cargo build -p rts_bindings --target wasm32-unknown-unknown
+//!   --features wasm`.
+//!
+//! Both front-ends exchange match setups, commands and snapshots as JSON using
+//! the serde representation of the `rts_core::api` types, so they stay in
+//! lock-step with [`rts_core::api::API_VERSION`].
+//!
+//! `rts_core` keeps `forbid(unsafe_code)`. The raw-pointer handling the C ABI
+//! needs lives only in the [`ffi`] module, and without the `ffi` feature this
+//! crate denies unsafe code too. It is denied rather than forbidden under
+//! `wasm` alone so the `wasm` module can allow the glue `wasm-bindgen`
+//! generates, and forbidden when neither feature is on.
+
+#![cfg_attr(not(any(feature = "ffi", feature = "wasm")), forbid(unsafe_code))]
+#![cfg_attr(all(feature = "wasm", not(feature = "ffi")), deny(unsafe_code))]
+#![warn(missing_docs)]
+
+pub mod json;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use json::{BindingError, JsonSimulation};
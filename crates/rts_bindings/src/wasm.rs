@@ -0,0 +1,94 @@
+//! WebAssembly exports over [`JsonSimulation`].
+//!
+//! ```js
+//! import init, { Simulation } from "./rts_bindings.js";
+//! await init();
+//! const sim = new Simulation(JSON.stringify(setup));
+//! const report = JSON.parse(sim.run(600n));
+//! ```
+
+// wasm-bindgen expands to unsafe glue code.
+#![allow(unsafe_code)]
+
+use wasm_bindgen::prelude::*;
+
+use crate::json::JsonSimulation;
+
+/// Simulation handle exported to JavaScript as `Simulation`.
+#[wasm_bindgen(js_name = Simulation)]
+pub struct WasmSimulation(JsonSimulation);
+
+#[wasm_bindgen(js_class = Simulation)]
+impl WasmSimulation {
+    /// Create a simulation from a JSON `MatchSetup`.
+    ///
+    /// # Errors
+    ///
+    /// Throws if the setup is malformed or rejected.
+    #[wasm_bindgen(constructor)]
+    pub fn new(setup_json: &str) -> Result<WasmSimulation, JsError> {
+        Ok(Self(JsonSimulation::from_setup_json(setup_json)?))
+    }
+
+    /// Restore a simulation from bytes produced by `saveState`.
+    ///
+    /// # Errors
+    ///
+    /// Throws if the bytes are not a valid saved state.
+    #[wasm_bindgen(js_name = loadState)]
+    pub fn load_state(bytes: &[u8]) -> Result<WasmSimulation, JsError> {
+        Ok(Self(JsonSimulation::load_state(bytes)?))
+    }
+
+    /// Replace an entity's orders with a JSON `ApiCommand`.
+    ///
+    /// # Errors
+    ///
+    /// Throws if the command is malformed or the entity does not exist.
+    pub fn submit(&mut self, entity: u64, command_json: &str) -> Result<(), JsError> {
+        Ok(self.0.submit_json(entity, command_json)?)
+    }
+
+    /// Advance one tick; returns the `TickReport` as JSON.
+    pub fn tick(&mut self) -> String {
+        self.0.tick_json()
+    }
+
+    /// Advance up to `ticks` ticks; returns the aggregated `TickReport` as JSON.
+    pub fn run(&mut self, ticks: u64) -> String {
+        self.0.run_json(ticks)
+    }
+
+    /// Current `StateSnapshot` as JSON.
+    pub fn snapshot(&self) -> String {
+        self.0.snapshot_json()
+    }
+
+    /// Serialize the full simulation state.
+    ///
+    /// # Errors
+    ///
+    /// Throws if serialization fails.
+    #[wasm_bindgen(js_name = saveState)]
+    pub fn save_state(&self) -> Result<Vec<u8>, JsError> {
+        Ok(self.0.save_state()?)
+    }
+
+    /// Current tick.
+    #[wasm_bindgen(getter, js_name = currentTick)]
+    pub fn current_tick(&self) -> u64 {
+        self.0.current_tick()
+    }
+
+    /// Deterministic state hash, comparable with native runs.
+    #[wasm_bindgen(getter, js_name = stateHash)]
+    pub fn state_hash(&self) -> u64 {
+        self.0.state_hash()
+    }
+
+    /// Short name of the winning faction, if decided.
+    #[wasm_bindgen(getter)]
+    pub fn winner(&self) -> Option<String> {
+        self.0.winner().map(|f| f.short_name().to_string())
+    }
+}
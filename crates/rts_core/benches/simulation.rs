@@ -5,15 +5,64 @@
 // Benchmark binaries don't need docs on macro-generated functions
 #![allow(missing_docs)]
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+use rts_core::components::{CombatStats, Command, FactionMember};
+use rts_core::factions::FactionId;
+use rts_core::math::{Fixed, Vec2Fixed};
+use rts_core::simulation::{EntitySpawnParams, Simulation};
+
+/// Units per side in the large battle.
+const UNITS_PER_SIDE: i32 = 2500;
+
+/// Two armies of [`UNITS_PER_SIDE`] units marching into each other.
+fn battle() -> Simulation {
+    let mut sim = Simulation::new();
+    for (faction, x, toward) in [
+        (FactionId::Continuity, 0, 400),
+        (FactionId::Collegium, 400, 0),
+    ] {
+        for i in 0..UNITS_PER_SIDE {
+            let position = Vec2Fixed::new(
+                Fixed::from_num(x + (i % 50) * 2),
+                Fixed::from_num((i / 50) * 2),
+            );
+            let id = sim.spawn_entity(EntitySpawnParams {
+                position: Some(position),
+                health: Some(100),
+                movement: Some(Fixed::from_num(1)),
+                combat_stats: Some(CombatStats::new(5, Fixed::from_num(10), 20)),
+                faction: Some(FactionMember::new(faction, 0)),
+                ..Default::default()
+            });
+            let target = Vec2Fixed::new(Fixed::from_num(toward), position.y);
+            sim.apply_command(id, Command::AttackMove(target))
+                .expect("units accept orders");
+        }
+    }
+    sim
+}
 
 /// Runs simulation benchmarks for the rts_core crate.
 pub fn simulation_benchmark(c: &mut Criterion) {
-    c.bench_function("placeholder", |b| {
-        b.iter(|| {
-            // TODO: Add actual simulation benchmarks
-            black_box(1 + 1)
-        })
+    let mut engaged = battle();
+    for _ in 0..100 {
+        engaged.tick();
+    }
+
+    c.bench_function("tick_5k_marching", |b| {
+        b.iter_batched(
+            battle,
+            |mut sim| black_box(sim.tick()),
+            BatchSize::LargeInput,
+        )
+    });
+    c.bench_function("tick_5k_engaged", |b| {
+        b.iter_batched(
+            || engaged.clone(),
+            |mut sim| black_box(sim.tick()),
+            BatchSize::LargeInput,
+        )
     });
 }
 
@@ -0,0 +1,41 @@
+//! Minimal example of embedding the simulation through the stable API.
+//!
+//! Sets up a one-sided skirmish, orders a unit to attack the enemy depot,
+//! and prints a RON snapshot once the match is decided.
+//!
+//! ```bash
+//! cargo run -p rts_core --example embed_simulation
+//! ```
+
+use rts_core::api::{world_pos, ApiCommand, EmbeddedSimulation, MatchSetup, UnitSpawn};
+use rts_core::factions::FactionId;
+use rts_core::math::Fixed;
+
+fn main() -> rts_core::error::Result<()> {
+    let setup = MatchSetup::new(vec![
+        UnitSpawn::depot(FactionId::Continuity, world_pos(100, 100), 500),
+        UnitSpawn::depot(FactionId::Collegium, world_pos(400, 100), 200),
+        UnitSpawn::unit(FactionId::Continuity, world_pos(120, 100), 100)
+            .with_speed(Fixed::from_num(4))
+            .with_combat(20, Fixed::from_num(40), 10),
+    ]);
+
+    let mut game = EmbeddedSimulation::new(&setup)?;
+
+    let snapshot = game.snapshot();
+    let attacker = snapshot.entities[2].id;
+    let enemy_depot = snapshot.entities[1].id;
+    game.submit(attacker, ApiCommand::Attack(enemy_depot))?;
+
+    let report = game.run(2_000);
+    println!(
+        "tick {} winner {:?}",
+        report.tick,
+        report.winner.map(|f| f.short_name())
+    );
+
+    let snapshot = ron::ser::to_string_pretty(&game.snapshot(), Default::default())
+        .map_err(|e| rts_core::error::GameError::InvalidState(e.to_string()))?;
+    println!("{snapshot}");
+    Ok(())
+}
@@ -0,0 +1,150 @@
+//! Unit abilities: active skills such as heals, EMP bursts and shields.
+//!
+//! Units spawned with [`AbilityData`] carry an [`Abilities`] component that
+//! tracks each ability's cooldown. Ordering
+//! [`Command::UseAbility`](crate::components::Command::UseAbility) makes the
+//! unit close to the ability's range; each tick the
+//! [`Simulation`](crate::simulation::Simulation):
+//! - expires [`Shield`]s whose time is up,
+//! - fires every ordered ability that is in range and off cooldown, on the
+//!   caster's allies (heals, shields) or enemies (damage) at the target,
+//! - reports each use as an [`AbilityUsed`] event.
+//!
+//! The simulation does not hold player resources: the game layer checks an
+//! ability's cost before ordering it and pays it when the ability is used.
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::{EntityId, Health};
+use crate::data::AbilityData;
+use crate::factions::FactionId;
+use crate::math::Vec2Fixed;
+
+/// What a [`Command::UseAbility`](crate::components::Command::UseAbility)
+/// is aimed at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AbilityTarget {
+    /// A position on the map.
+    Point(Vec2Fixed),
+    /// Another entity.
+    Entity(EntityId),
+    /// The unit using the ability.
+    Caster,
+}
+
+/// An ability a unit has and when it can next be used.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbilitySlot {
+    /// The ability.
+    pub ability: AbilityData,
+    /// Tick from which the ability can be used again.
+    pub ready_at: u64,
+}
+
+/// The abilities a unit can use.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Abilities {
+    /// One slot per ability, in data order.
+    pub slots: Vec<AbilitySlot>,
+}
+
+impl Abilities {
+    /// Create an abilities component with every ability ready.
+    #[must_use]
+    pub fn new(abilities: impl IntoIterator<Item = AbilityData>) -> Self {
+        Self {
+            slots: abilities
+                .into_iter()
+                .map(|ability| AbilitySlot {
+                    ability,
+                    ready_at: 0,
+                })
+                .collect(),
+        }
+    }
+
+    /// Look up an ability's slot.
+    #[must_use]
+    pub fn get(&self, ability_id: &str) -> Option<&AbilitySlot> {
+        self.slots.iter().find(|slot| slot.ability.id == ability_id)
+    }
+
+    /// Look up an ability's slot mutably.
+    pub fn get_mut(&mut self, ability_id: &str) -> Option<&mut AbilitySlot> {
+        self.slots
+            .iter_mut()
+            .find(|slot| slot.ability.id == ability_id)
+    }
+
+    /// Whether `ability_id` can be used on `tick`.
+    #[must_use]
+    pub fn is_ready(&self, ability_id: &str, tick: u64) -> bool {
+        self.get(ability_id)
+            .is_some_and(|slot| slot.ready_at <= tick)
+    }
+}
+
+/// Temporary health that absorbs damage until it expires.
+///
+/// A shield raises the entity's maximum and current health by `amount`.
+/// When it expires the maximum drops back and current health is capped at
+/// it, so whatever damage the shield soaked up is gone with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shield {
+    /// Health the shield added.
+    pub amount: u32,
+    /// Tick the shield expires on.
+    pub expires_at: u64,
+}
+
+impl Shield {
+    /// Raise `health` by `amount` until `expires_at`.
+    #[must_use]
+    pub fn raise(health: &mut Health, amount: u32, expires_at: u64) -> Self {
+        health.max += amount;
+        health.current += amount;
+        Self { amount, expires_at }
+    }
+
+    /// Take the shield's health back off `health`.
+    pub fn lower(self, health: &mut Health) {
+        health.max = health.max.saturating_sub(self.amount).max(1);
+        health.current = health.current.min(health.max);
+    }
+}
+
+/// An ability was used.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbilityUsed {
+    /// The unit that used it.
+    pub caster: EntityId,
+    /// Faction of the caster, who pays the cost.
+    pub faction: Option<FactionId>,
+    /// The ability used.
+    pub ability_id: String,
+    /// Feedstock the ability costs.
+    pub cost: u32,
+    /// Entities healed, damaged or shielded, in ID order.
+    pub affected: Vec<EntityId>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shield_absorbs_damage_then_expires() {
+        let mut health = Health::new(100);
+        let shield = Shield::raise(&mut health, 50, 10);
+        assert_eq!((health.current, health.max), (150, 150));
+
+        health.apply_damage(30);
+        shield.lower(&mut health);
+        assert_eq!((health.current, health.max), (100, 100));
+
+        let shield = Shield::raise(&mut health, 50, 10);
+        health.apply_damage(70);
+        shield.lower(&mut health);
+        assert_eq!((health.current, health.max), (80, 100));
+    }
+}
@@ -0,0 +1,716 @@
+//! Stable embedding API for the simulation.
+//!
+//! This module is the supported entry point for third parties that want to
+//! embed the deterministic simulation (tournament tools, AI research harnesses,
+//! replay viewers) without tracking the internals of [`crate::simulation`].
+//!
+//! # Stability
+//!
+//! The types defined in this module follow semver: structs and enums are
+//! `#[non_exhaustive]`, structs are built through their constructors and
+//! builder methods, and new fields come with `#[serde(default)]`. The rest
+//! of the crate may change between minor versions.
+//!
+//! Fields also carry a few core types: [`FactionId`], [`EntityId`], the
+//! fixed-point [`Vec2Fixed`] and [`Fixed`], [`MapConfig`] and scenario
+//! [`Trigger`]s. Their serde form is part of this API, and [`API_VERSION`]
+//! is bumped whenever it, or the shape of a snapshot or command, changes
+//! incompatibly. Their Rust API is not covered.
+//!
+//! # Scenarios
+//!
+//! A [`MatchSetup`] is a whole scenario: the map, the entities at tick 0
+//! and scripted [`Trigger`]s. [`EmbeddedSimulation::from_scenario_file`]
+//! loads one from a RON file. Fired triggers are reported in each
+//! [`TickReport`]; carrying out their actions is left to the embedder.
+//!
+//! # Example
+//!
+//! ```
+//! use rts_core::api::{world_pos, ApiCommand, EmbeddedSimulation, MatchSetup, UnitSpawn};
+//! use rts_core::factions::FactionId;
+//!
+//! let setup = MatchSetup::new(vec![UnitSpawn::unit(
+//!     FactionId::Continuity,
+//!     world_pos(0, 0),
+//!     100,
+//! )]);
+//! let mut game = EmbeddedSimulation::new(&setup).unwrap();
+//!
+//! let unit = game.snapshot().entities[0].id;
+//! game.submit(unit, ApiCommand::MoveTo(world_pos(50, 0))).unwrap();
+//! game.run(10);
+//!
+//! let snapshot = game.snapshot();
+//! assert_eq!(snapshot.tick, 10);
+//! assert!(snapshot.entities[0].position.x > rts_core::math::Fixed::ZERO);
+//! ```
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::{CombatStats, Command, EntityId, FactionMember};
+use crate::error::{GameError, Result};
+use crate::factions::FactionId;
+use crate::map_generation::{generate_map, GeneratedMap, MapConfig};
+use crate::math::{fixed_serde, Fixed, Vec2Fixed};
+use crate::pathfinding::NavGrid;
+use crate::simulation::{EntityRef, EntitySpawnParams, Simulation};
+use crate::triggers::{ScenarioScript, Trigger, TriggerFired};
+
+/// Version of the embedding API surface.
+///
+/// Included in every [`StateSnapshot`] so consumers can detect mismatches.
+pub const API_VERSION: u32 = 1;
+
+/// Build a world position from whole world units.
+#[must_use]
+pub fn world_pos(x: i32, y: i32) -> Vec2Fixed {
+    Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y))
+}
+
+/// Combat capabilities for a spawned unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct UnitCombat {
+    /// Damage per attack.
+    pub damage: u32,
+    /// Attack range in world units.
+    #[serde(with = "fixed_serde")]
+    pub range: Fixed,
+    /// Ticks between attacks.
+    pub cooldown: u32,
+}
+
+impl UnitCombat {
+    /// Combat stats dealing `damage` within `range`, every `cooldown` ticks.
+    #[must_use]
+    pub const fn new(damage: u32, range: Fixed, cooldown: u32) -> Self {
+        Self {
+            damage,
+            range,
+            cooldown,
+        }
+    }
+}
+
+/// An entity to place in the world at match start (or later via
+/// [`EmbeddedSimulation::spawn`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct UnitSpawn {
+    /// Owning faction, or `None` for neutral entities.
+    pub faction: Option<FactionId>,
+    /// Spawn position.
+    pub position: Vec2Fixed,
+    /// Maximum (and starting) health.
+    pub health: u32,
+    /// Movement speed per tick; zero for static entities.
+    #[serde(default, with = "fixed_serde")]
+    pub speed: Fixed,
+    /// Combat stats; `None` for non-combatants.
+    #[serde(default)]
+    pub combat: Option<UnitCombat>,
+    /// Whether this entity is a depot (losing all depots loses the match).
+    #[serde(default)]
+    pub is_depot: bool,
+}
+
+impl UnitSpawn {
+    /// A static, unarmed entity owned by `faction`, or neutral if `None`.
+    #[must_use]
+    pub fn new(faction: Option<FactionId>, position: Vec2Fixed, health: u32) -> Self {
+        Self {
+            faction,
+            position,
+            health,
+            speed: Fixed::ZERO,
+            combat: None,
+            is_depot: false,
+        }
+    }
+
+    /// A mobile unit with default speed and no weapons.
+    #[must_use]
+    pub fn unit(faction: FactionId, position: Vec2Fixed, health: u32) -> Self {
+        Self::new(Some(faction), position, health).with_speed(Fixed::from_num(2))
+    }
+
+    /// A static depot building.
+    #[must_use]
+    pub fn depot(faction: FactionId, position: Vec2Fixed, health: u32) -> Self {
+        Self {
+            is_depot: true,
+            ..Self::new(Some(faction), position, health)
+        }
+    }
+
+    /// Builder method to set movement speed.
+    #[must_use]
+    pub fn with_speed(mut self, speed: Fixed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Builder method to set combat stats.
+    #[must_use]
+    pub fn with_combat(mut self, damage: u32, range: Fixed, cooldown: u32) -> Self {
+        self.combat = Some(UnitCombat::new(damage, range, cooldown));
+        self
+    }
+}
+
+/// Everything needed to start a match: a scenario.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct MatchSetup {
+    /// Procedural map to generate. `None` uses the default open grid.
+    #[serde(default)]
+    pub map: Option<MapConfig>,
+    /// Entities present at tick 0, spawned in order.
+    #[serde(default)]
+    pub units: Vec<UnitSpawn>,
+    /// Scripted events, reported in the [`TickReport`] of the tick they
+    /// fire on.
+    #[serde(default)]
+    pub triggers: Vec<Trigger>,
+}
+
+impl MatchSetup {
+    /// A match on the default open grid starting with `units`.
+    #[must_use]
+    pub fn new(units: Vec<UnitSpawn>) -> Self {
+        Self {
+            units,
+            ..Default::default()
+        }
+    }
+
+    /// Builder method to play on a generated map.
+    #[must_use]
+    pub fn with_map(mut self, map: MapConfig) -> Self {
+        self.map = Some(map);
+        self
+    }
+
+    /// Builder method to script the match with triggers.
+    #[must_use]
+    pub fn with_triggers(mut self, triggers: Vec<Trigger>) -> Self {
+        self.triggers = triggers;
+        self
+    }
+
+    /// Parse a scenario written in RON.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GameError::InvalidState`] if the text is not a valid setup.
+    pub fn from_ron_str(ron: &str) -> Result<Self> {
+        ron::from_str(ron)
+            .map_err(|e| GameError::InvalidState(format!("Failed to parse scenario: {}", e)))
+    }
+}
+
+/// Orders accepted through the embedding API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum ApiCommand {
+    /// Move to a position.
+    MoveTo(Vec2Fixed),
+    /// Attack a specific entity.
+    Attack(EntityId),
+    /// Attack a specific entity even if it is friendly.
+    ForceAttack(EntityId),
+    /// Shell a ground position with a splash weapon.
+    AttackGround(Vec2Fixed),
+    /// Move to a position, engaging enemies along the way.
+    AttackMove(Vec2Fixed),
+    /// Patrol between the current position and a target.
+    Patrol(Vec2Fixed),
+    /// Hold position.
+    HoldPosition,
+    /// Stop all actions.
+    Stop,
+}
+
+impl From<ApiCommand> for Command {
+    fn from(command: ApiCommand) -> Self {
+        match command {
+            ApiCommand::MoveTo(pos) => Self::MoveTo(pos),
+            ApiCommand::Attack(target) => Self::Attack(target),
+            ApiCommand::ForceAttack(target) => Self::ForceAttack(target),
+            ApiCommand::AttackGround(pos) => Self::AttackGround(pos),
+            ApiCommand::AttackMove(pos) => Self::AttackMove(pos),
+            ApiCommand::Patrol(pos) => Self::Patrol(pos),
+            ApiCommand::HoldPosition => Self::HoldPosition,
+            ApiCommand::Stop => Self::Stop,
+        }
+    }
+}
+
+/// Plain view of one entity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct EntitySnapshot {
+    /// Entity identifier.
+    pub id: EntityId,
+    /// Owning faction, if any.
+    pub faction: Option<FactionId>,
+    /// World position.
+    pub position: Vec2Fixed,
+    /// Current health, if damageable.
+    pub health: Option<u32>,
+    /// Maximum health, if damageable.
+    pub max_health: Option<u32>,
+    /// Whether this entity is a depot.
+    pub is_depot: bool,
+    /// Whether this entity is a projectile in flight.
+    pub is_projectile: bool,
+    /// Debug label such as `"collegium/security_team#12"`, if assigned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug_name: Option<String>,
+}
+
+impl EntitySnapshot {
+    /// View of a simulation entity, or None if it has no position.
+    #[must_use]
+    pub fn from_entity(entity: EntityRef<'_>) -> Option<Self> {
+        Some(Self {
+            id: entity.id,
+            faction: entity.faction.map(|f| f.faction),
+            position: entity.position?.value,
+            health: entity.health.map(|h| h.current),
+            max_health: entity.health.map(|h| h.max),
+            is_depot: entity.depot.is_some(),
+            is_projectile: entity.projectile.is_some(),
+            debug_name: entity.debug_name.as_ref().map(|n| n.0.clone()),
+        })
+    }
+}
+
+/// Plain, serializable view of the whole simulation at one tick.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct StateSnapshot {
+    /// [`API_VERSION`] that produced this snapshot.
+    pub api_version: u32,
+    /// Simulation tick.
+    pub tick: u64,
+    /// Determinism hash of the full state.
+    pub state_hash: u64,
+    /// Positioned entities in ascending ID order.
+    pub entities: Vec<EntitySnapshot>,
+}
+
+impl StateSnapshot {
+    /// Snapshot of `entities` at `tick`, stamped with [`API_VERSION`].
+    #[must_use]
+    pub fn new(tick: u64, state_hash: u64, entities: Vec<EntitySnapshot>) -> Self {
+        Self {
+            api_version: API_VERSION,
+            tick,
+            state_hash,
+            entities,
+        }
+    }
+}
+
+/// Summary of what happened during one tick, or during every tick of an
+/// [`EmbeddedSimulation::run`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct TickReport {
+    /// Tick number after advancing.
+    pub tick: u64,
+    /// Total damage dealt.
+    pub damage_dealt: u32,
+    /// Entities that died, in order.
+    pub deaths: Vec<EntityId>,
+    /// Winning faction, once the match has been decided.
+    pub winner: Option<FactionId>,
+    /// Scenario triggers that fired, in order.
+    #[serde(default)]
+    pub triggers: Vec<TriggerFired>,
+}
+
+/// What [`EmbeddedSimulation::save_state`] writes: the simulation, the
+/// navigation grid its serialized form leaves out, and the facade's own
+/// record of the outcome.
+#[derive(Serialize, Deserialize)]
+struct SavedState {
+    state: Vec<u8>,
+    nav_grid: NavGrid,
+    winner: Option<FactionId>,
+}
+
+/// A simulation wrapped behind the stable embedding API.
+#[derive(Debug, Clone)]
+pub struct EmbeddedSimulation {
+    sim: Simulation,
+    winner: Option<FactionId>,
+}
+
+impl EmbeddedSimulation {
+    /// Create a simulation from a match setup.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GameError::InvalidState`] if the map configuration has a
+    /// zero dimension.
+    pub fn new(setup: &MatchSetup) -> Result<Self> {
+        let mut game = match &setup.map {
+            Some(config) => {
+                if config.width == 0 || config.height == 0 || config.cell_size == 0 {
+                    return Err(GameError::InvalidState(
+                        "Map dimensions must be positive".to_string(),
+                    ));
+                }
+                Self::from_map(&generate_map(config.clone()))
+            }
+            None => Self {
+                sim: Simulation::new(),
+                winner: None,
+            },
+        };
+
+        for spawn in &setup.units {
+            game.spawn(spawn);
+        }
+        if !setup.triggers.is_empty() {
+            game.sim
+                .set_script(ScenarioScript::new(setup.triggers.clone()));
+        }
+        Ok(game)
+    }
+
+    /// Create a simulation from a scenario file: a [`MatchSetup`] in RON.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GameError::InvalidState`] if the file cannot be read or
+    /// parsed, or the setup is rejected by [`new`](Self::new).
+    pub fn from_scenario_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let ron = std::fs::read_to_string(path).map_err(|e| {
+            GameError::InvalidState(format!("Failed to read scenario {}: {}", path.display(), e))
+        })?;
+        Self::new(&MatchSetup::from_ron_str(&ron)?)
+    }
+
+    /// Create an empty simulation whose navigation grid matches a generated map.
+    #[must_use]
+    pub fn from_map(map: &GeneratedMap) -> Self {
+        let mut sim = Simulation::with_nav_grid(
+            map.config.width,
+            map.config.height,
+            Fixed::from_num(map.config.cell_size),
+        );
+        for y in 0..map.config.height {
+            for x in 0..map.config.width {
+                if let Some(cell) = map.get_cell(x, y) {
+                    sim.nav_grid_mut().set_cell(x, y, cell.cell_type);
+                    sim.nav_grid_mut().set_elevation(x, y, cell.elevation);
+                }
+            }
+        }
+        Self { sim, winner: None }
+    }
+
+    /// Wrap a simulation set up elsewhere, such as by a host loading its
+    /// own scenario format.
+    ///
+    /// Like [`inner`](Self::inner), the argument's type is **not** covered
+    /// by the stability guarantees of this module.
+    #[must_use]
+    pub fn from_simulation(sim: Simulation) -> Self {
+        Self { sim, winner: None }
+    }
+
+    /// Spawn an entity and return its ID.
+    pub fn spawn(&mut self, spawn: &UnitSpawn) -> EntityId {
+        self.sim.spawn_entity(EntitySpawnParams {
+            position: Some(spawn.position),
+            health: Some(spawn.health),
+            movement: (spawn.speed > Fixed::ZERO).then_some(spawn.speed),
+            combat_stats: spawn
+                .combat
+                .map(|c| CombatStats::new(c.damage, c.range, c.cooldown)),
+            faction: spawn.faction.map(|f| FactionMember::new(f, 0)),
+            is_depot: spawn.is_depot,
+            ..Default::default()
+        })
+    }
+
+    /// Replace an entity's orders with a new command.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entity doesn't exist or can't take orders.
+    pub fn submit(&mut self, entity: EntityId, command: ApiCommand) -> Result<()> {
+        self.sim.apply_command(entity, command.into())
+    }
+
+    /// Append a command to an entity's order queue.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entity doesn't exist or can't take orders.
+    pub fn queue(&mut self, entity: EntityId, command: ApiCommand) -> Result<()> {
+        self.sim.queue_command(entity, command.into())
+    }
+
+    /// Advance the simulation by one tick.
+    pub fn tick(&mut self) -> TickReport {
+        let events = self.sim.tick();
+        if self.winner.is_none() {
+            self.winner = events.game_end;
+        }
+        TickReport {
+            tick: self.sim.get_tick(),
+            damage_dealt: events.damage_events.iter().map(|e| e.damage).sum(),
+            deaths: events.deaths,
+            winner: self.winner,
+            triggers: events.triggers,
+        }
+    }
+
+    /// Advance the simulation by `ticks` ticks, stopping early once a winner
+    /// is decided. Returns one report covering every tick run: the damage
+    /// summed, every death in order, and the tick and winner at the end.
+    pub fn run(&mut self, ticks: u64) -> TickReport {
+        let mut report = TickReport {
+            tick: self.sim.get_tick(),
+            winner: self.winner,
+            ..Default::default()
+        };
+        for _ in 0..ticks {
+            let tick = self.tick();
+            report.tick = tick.tick;
+            report.damage_dealt = report.damage_dealt.saturating_add(tick.damage_dealt);
+            report.deaths.extend(tick.deaths);
+            report.winner = tick.winner;
+            report.triggers.extend(tick.triggers);
+            if report.winner.is_some() {
+                break;
+            }
+        }
+        report
+    }
+
+    /// Current simulation tick.
+    #[must_use]
+    pub const fn current_tick(&self) -> u64 {
+        self.sim.get_tick()
+    }
+
+    /// Winning faction, once decided.
+    #[must_use]
+    pub const fn winner(&self) -> Option<FactionId> {
+        self.winner
+    }
+
+    /// Determinism hash of the current state.
+    #[must_use]
+    pub fn state_hash(&self) -> u64 {
+        self.sim.state_hash()
+    }
+
+    /// Take a plain snapshot of every positioned entity.
+    #[must_use]
+    pub fn snapshot(&self) -> StateSnapshot {
+        let entities = self
+            .sim
+            .entities()
+            .sorted_ids()
+            .into_iter()
+            .filter_map(|id| EntitySnapshot::from_entity(self.sim.get_entity(id)?))
+            .collect();
+
+        StateSnapshot::new(self.sim.get_tick(), self.sim.state_hash(), entities)
+    }
+
+    /// Serialize the full simulation state and the winner, if decided
+    /// (for saves or network sync).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    pub fn save_state(&self) -> Result<Vec<u8>> {
+        let saved = SavedState {
+            state: self.sim.serialize()?,
+            nav_grid: self.sim.nav_grid().clone(),
+            winner: self.winner,
+        };
+        bincode::serialize(&saved)
+            .map_err(|e| GameError::InvalidState(format!("Failed to save state: {}", e)))
+    }
+
+    /// Restore a simulation from bytes produced by [`save_state`](Self::save_state).
+    /// A decided match stays decided.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are not a valid saved state.
+    pub fn load_state(bytes: &[u8]) -> Result<Self> {
+        let saved: SavedState = bincode::deserialize(bytes)
+            .map_err(|e| GameError::InvalidState(format!("Failed to load state: {}", e)))?;
+        let mut sim = Simulation::deserialize(&saved.state)?;
+        *sim.nav_grid_mut() = saved.nav_grid;
+        Ok(Self {
+            sim,
+            winner: saved.winner,
+        })
+    }
+
+    /// Access the wrapped simulation.
+    ///
+    /// The returned type is **not** covered by the stability guarantees of
+    /// this module.
+    #[must_use]
+    pub fn inner(&self) -> &Simulation {
+        &self.sim
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::CellType;
+
+    fn duel_setup() -> MatchSetup {
+        MatchSetup::new(vec![
+            UnitSpawn::depot(FactionId::Continuity, world_pos(0, 0), 50),
+            UnitSpawn::depot(FactionId::Collegium, world_pos(40, 0), 50),
+            UnitSpawn::unit(FactionId::Continuity, world_pos(10, 0), 100).with_combat(
+                25,
+                Fixed::from_num(50),
+                1,
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_setup_spawns_units_in_order() {
+        let game = EmbeddedSimulation::new(&duel_setup()).unwrap();
+        let snapshot = game.snapshot();
+
+        assert_eq!(snapshot.api_version, API_VERSION);
+        assert_eq!(snapshot.entities.len(), 3);
+        assert!(snapshot.entities[0].is_depot);
+        assert_eq!(snapshot.entities[2].faction, Some(FactionId::Continuity));
+    }
+
+    #[test]
+    fn test_attack_decides_winner() {
+        let mut game = EmbeddedSimulation::new(&duel_setup()).unwrap();
+        game.submit(3, ApiCommand::Attack(2)).unwrap();
+
+        let report = game.run(200);
+        assert_eq!(report.winner, Some(FactionId::Continuity));
+        assert_eq!(game.winner(), Some(FactionId::Continuity));
+        // Both hits on the depot count, not only the last tick's
+        assert!(report.damage_dealt >= 50);
+        assert_eq!(report.deaths, vec![2]);
+    }
+
+    #[test]
+    fn test_submit_to_missing_entity_fails() {
+        let mut game = EmbeddedSimulation::new(&MatchSetup::default()).unwrap();
+        assert!(game.submit(99, ApiCommand::Stop).is_err());
+    }
+
+    #[test]
+    fn test_map_setup_sizes_nav_grid() {
+        let setup = MatchSetup::new(Vec::new()).with_map(MapConfig::small().with_seed(7));
+        let game = EmbeddedSimulation::new(&setup).unwrap();
+        assert!(game.inner().nav_grid().in_bounds(63, 63));
+        assert!(!game.inner().nav_grid().in_bounds(64, 0));
+    }
+
+    #[test]
+    fn test_save_state_keeps_map() {
+        let setup = MatchSetup::new(Vec::new()).with_map(MapConfig::small().with_seed(7));
+        let game = EmbeddedSimulation::new(&setup).unwrap();
+        let restored = EmbeddedSimulation::load_state(&game.save_state().unwrap()).unwrap();
+
+        let terrain = |game: &EmbeddedSimulation| {
+            let grid = game.inner().nav_grid();
+            let mut cells = Vec::new();
+            for y in 0..64 {
+                for x in 0..64 {
+                    cells.push((grid.get_cell(x, y), grid.elevation(x, y)));
+                }
+            }
+            cells
+        };
+        let before = terrain(&game);
+        assert!(before
+            .iter()
+            .any(|(cell, _)| cell == &Some(CellType::Blocked)));
+        assert_eq!(before, terrain(&restored));
+        assert_eq!(game.state_hash(), restored.state_hash());
+    }
+
+    #[test]
+    fn test_snapshot_serde_roundtrip() {
+        let game = EmbeddedSimulation::new(&duel_setup()).unwrap();
+        let snapshot = game.snapshot();
+        let json = ron::to_string(&snapshot).unwrap();
+        let restored: StateSnapshot = ron::from_str(&json).unwrap();
+        assert_eq!(snapshot, restored);
+    }
+
+    #[test]
+    fn test_embedded_simulation_deterministic() {
+        let run = || {
+            let mut game = EmbeddedSimulation::new(&duel_setup()).unwrap();
+            game.submit(3, ApiCommand::MoveTo(world_pos(30, 30)))
+                .unwrap();
+            game.run(50);
+            game.state_hash()
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn test_save_and_load_state() {
+        let mut game = EmbeddedSimulation::new(&duel_setup()).unwrap();
+        game.run(5);
+        let bytes = game.save_state().unwrap();
+        let restored = EmbeddedSimulation::load_state(&bytes).unwrap();
+        assert_eq!(restored.state_hash(), game.state_hash());
+        assert_eq!(restored.winner(), None);
+
+        // A finished match loads finished
+        game.submit(3, ApiCommand::Attack(2)).unwrap();
+        game.run(200);
+        let restored = EmbeddedSimulation::load_state(&game.save_state().unwrap()).unwrap();
+        assert_eq!(restored.winner(), Some(FactionId::Continuity));
+    }
+
+    #[test]
+    fn test_scenario_file_sets_up_and_scripts_the_match() {
+        let scenario = r#"(
+            units: [
+                (faction: Some(Continuity), position: (x: 0, y: 0), health: 50, is_depot: true),
+                (faction: Some(Collegium), position: (x: 171798691840, y: 0), health: 50, is_depot: true),
+            ],
+            triggers: [
+                (name: "briefing", condition: AtTick(2), actions: [Message("Hold the line")]),
+            ],
+        )"#;
+        let path =
+            std::env::temp_dir().join(format!("rts_api_scenario_{}.ron", std::process::id()));
+        std::fs::write(&path, scenario).unwrap();
+        let mut game = EmbeddedSimulation::from_scenario_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(game.snapshot().entities.len(), 2);
+        let report = game.run(5);
+        assert_eq!(report.triggers.len(), 1);
+        assert_eq!(report.triggers[0].trigger, "briefing");
+
+        assert!(EmbeddedSimulation::from_scenario_file("no/such/scenario.ron").is_err());
+    }
+}
@@ -0,0 +1,563 @@
+//! Compressed, rotating autosaves for long games.
+//!
+//! An [`Autosave`] captures the serialized [`Simulation`] with its
+//! navigation grid, any commands that were issued but not yet applied, and
+//! an opaque blob of state owned by the
+//! host (AI executors, economy bookkeeping, ...). Restoring all three and
+//! continuing produces exactly the same game as never stopping.
+//!
+//! Files are a small uncompressed header (magic, format version, tick)
+//! followed by zstd-compressed bincode. [`AutosaveRotation`] cycles through a
+//! fixed number of slot files so disk use stays bounded, and
+//! [`latest_autosave`] finds the most recent slot to resume from.
+//!
+//! Only available with the `autosave` feature.
+
+use std::path::{Path, PathBuf};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::components::{Command, EntityId};
+use crate::error::{GameError, Result};
+use crate::fingerprint::Fingerprint;
+use crate::pathfinding::NavGrid;
+use crate::simulation::Simulation;
+
+/// Autosave format version for compatibility.
+pub const AUTOSAVE_VERSION: u32 = 4;
+
+/// File magic identifying an autosave.
+const MAGIC: [u8; 4] = *b"PSAS";
+
+/// Header length: magic + version (u32) + tick (u64).
+const HEADER_LEN: usize = 16;
+
+/// zstd level: favour speed, autosaves happen mid-game.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// File extension for autosave slots.
+pub const AUTOSAVE_EXTENSION: &str = "autosave";
+
+/// A command that had been issued but not yet applied when a save was taken.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingCommand {
+    /// Target entity.
+    pub entity: EntityId,
+    /// The command.
+    pub command: Command,
+    /// Append to the entity's queue instead of replacing it.
+    pub queued: bool,
+}
+
+impl PendingCommand {
+    /// Apply the command to a simulation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entity does not exist.
+    pub fn apply(&self, sim: &mut Simulation) -> Result<()> {
+        if self.queued {
+            sim.queue_command(self.entity, self.command.clone())
+        } else {
+            sim.apply_command(self.entity, self.command.clone())
+        }
+    }
+}
+
+/// A resumable point-in-time capture of a game.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Autosave {
+    /// Autosave format version.
+    pub version: u32,
+    /// Game or scenario identifier.
+    pub label: String,
+    /// Simulation tick at capture.
+    pub tick: u64,
+    /// Simulation state hash at capture, checked on restore.
+    pub state_hash: u64,
+    /// Engine, data and map the game was running on.
+    pub fingerprint: Fingerprint,
+    /// Serialized simulation.
+    pub simulation: Vec<u8>,
+    /// Navigation grid, which the serialized simulation leaves out; it
+    /// holds the buildings and terrain units path around.
+    pub nav_grid: NavGrid,
+    /// Commands issued but not yet applied, in issue order.
+    pub command_backlog: Vec<PendingCommand>,
+    /// Host-defined state, serialized with bincode.
+    pub host_state: Vec<u8>,
+}
+
+impl Autosave {
+    /// Capture a simulation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the simulation cannot be serialized.
+    pub fn capture(label: impl Into<String>, sim: &Simulation) -> Result<Self> {
+        Ok(Self {
+            version: AUTOSAVE_VERSION,
+            label: label.into(),
+            tick: sim.get_tick(),
+            state_hash: sim.state_hash(),
+            fingerprint: Fingerprint::engine(),
+            simulation: sim.serialize()?,
+            nav_grid: sim.nav_grid().clone(),
+            command_backlog: Vec::new(),
+            host_state: Vec::new(),
+        })
+    }
+
+    /// Attach unapplied commands.
+    #[must_use]
+    pub fn with_backlog(mut self, commands: Vec<PendingCommand>) -> Self {
+        self.command_backlog = commands;
+        self
+    }
+
+    /// Attach the session fingerprint.
+    #[must_use]
+    pub fn with_fingerprint(mut self, fingerprint: Fingerprint) -> Self {
+        self.fingerprint = fingerprint;
+        self
+    }
+
+    /// Check the save was taken with the local data and map.
+    ///
+    /// The engine is already checked when the save is decoded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming each component that differs.
+    pub fn check_fingerprint(&self, local: &Fingerprint) -> Result<()> {
+        local.check(&self.fingerprint)
+    }
+
+    /// Attach host state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state cannot be serialized.
+    pub fn with_host_state<T: Serialize>(mut self, state: &T) -> Result<Self> {
+        self.host_state = bincode::serialize(state).map_err(|e| {
+            GameError::InvalidState(format!("Failed to serialize autosave host state: {}", e))
+        })?;
+        Ok(self)
+    }
+
+    /// Decode the host state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the blob does not decode as `T`.
+    pub fn host_state<T: DeserializeOwned>(&self) -> Result<T> {
+        bincode::deserialize(&self.host_state).map_err(|e| {
+            GameError::InvalidState(format!("Failed to deserialize autosave host state: {}", e))
+        })
+    }
+
+    /// Rebuild the simulation and its navigation grid, verifying it matches
+    /// the captured hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state does not deserialize or hashes differently.
+    pub fn restore_simulation(&self) -> Result<Simulation> {
+        let mut sim = Simulation::deserialize(&self.simulation)?;
+        *sim.nav_grid_mut() = self.nav_grid.clone();
+        let hash = sim.state_hash();
+        if hash != self.state_hash {
+            return Err(GameError::DesyncDetected {
+                tick: self.tick,
+                local_hash: hash,
+                remote_hash: self.state_hash,
+            });
+        }
+        Ok(sim)
+    }
+
+    /// Encode as header + compressed payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization or compression fails.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let payload = bincode::serialize(self)
+            .map_err(|e| GameError::InvalidState(format!("Failed to serialize autosave: {}", e)))?;
+        let compressed = zstd::bulk::compress(&payload, COMPRESSION_LEVEL)
+            .map_err(|e| GameError::InvalidState(format!("Failed to compress autosave: {}", e)))?;
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + compressed.len());
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        bytes.extend_from_slice(&self.tick.to_le_bytes());
+        bytes.extend_from_slice(&compressed);
+        Ok(bytes)
+    }
+
+    /// Decode bytes produced by [`to_bytes`](Self::to_bytes).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on a bad header, version mismatch, corrupt payload
+    /// or a save taken by a different engine version.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        read_header(bytes)?;
+        let payload = zstd::stream::decode_all(&bytes[HEADER_LEN..]).map_err(|e| {
+            GameError::InvalidState(format!("Failed to decompress autosave: {}", e))
+        })?;
+        let save: Self = bincode::deserialize(&payload).map_err(|e| {
+            GameError::InvalidState(format!("Failed to deserialize autosave: {}", e))
+        })?;
+        Fingerprint::engine().check(&save.fingerprint)?;
+        Ok(save)
+    }
+
+    /// Write to a file atomically (via a temporary file and rename).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding or writing fails.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let bytes = self.to_bytes()?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, bytes)
+            .and_then(|()| std::fs::rename(&tmp, path))
+            .map_err(|e| GameError::InvalidState(format!("Failed to write autosave: {}", e)))
+    }
+
+    /// Read from a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading or decoding fails.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let bytes = std::fs::read(path.as_ref())
+            .map_err(|e| GameError::InvalidState(format!("Failed to read autosave: {}", e)))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Validate the header and return the tick it records.
+fn read_header(bytes: &[u8]) -> Result<u64> {
+    if bytes.len() < HEADER_LEN || bytes[..4] != MAGIC {
+        return Err(GameError::InvalidState("Not an autosave file".to_string()));
+    }
+    let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    if version != AUTOSAVE_VERSION {
+        return Err(GameError::InvalidState(format!(
+            "Autosave version mismatch: expected {}, got {}",
+            AUTOSAVE_VERSION, version
+        )));
+    }
+    let mut tick = [0u8; 8];
+    tick.copy_from_slice(&bytes[8..HEADER_LEN]);
+    Ok(u64::from_le_bytes(tick))
+}
+
+/// Where and how often to autosave.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutosaveConfig {
+    /// Directory holding the slot files.
+    pub dir: PathBuf,
+    /// Slot file name prefix (one rotation per prefix).
+    pub prefix: String,
+    /// Ticks between autosaves.
+    pub interval_ticks: u64,
+    /// Number of slot files to rotate through.
+    pub slots: u32,
+}
+
+impl AutosaveConfig {
+    /// Autosave into `dir` every `interval_ticks`, keeping three slots.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>, interval_ticks: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            prefix: "autosave".to_string(),
+            interval_ticks,
+            slots: 3,
+        }
+    }
+
+    /// Set the slot file prefix.
+    #[must_use]
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Set the number of rotating slots.
+    #[must_use]
+    pub fn with_slots(mut self, slots: u32) -> Self {
+        self.slots = slots;
+        self
+    }
+
+    /// Path of a slot file.
+    #[must_use]
+    pub fn slot_path(&self, slot: u32) -> PathBuf {
+        self.dir
+            .join(format!("{}-{}.{}", self.prefix, slot, AUTOSAVE_EXTENSION))
+    }
+}
+
+/// Writes autosaves round-robin over a fixed set of slot files.
+#[derive(Debug, Clone)]
+pub struct AutosaveRotation {
+    config: AutosaveConfig,
+    next_slot: u32,
+    last_tick: Option<u64>,
+}
+
+impl AutosaveRotation {
+    /// Create a rotation. The first write goes to slot 0.
+    #[must_use]
+    pub fn new(config: AutosaveConfig) -> Self {
+        Self {
+            config,
+            next_slot: 0,
+            last_tick: None,
+        }
+    }
+
+    /// Configuration in use.
+    #[must_use]
+    pub fn config(&self) -> &AutosaveConfig {
+        &self.config
+    }
+
+    /// Whether an autosave should be taken at `tick`.
+    #[must_use]
+    pub fn is_due(&self, tick: u64) -> bool {
+        self.config.interval_ticks > 0
+            && tick > 0
+            && tick % self.config.interval_ticks == 0
+            && self.last_tick != Some(tick)
+    }
+
+    /// Write an autosave to the next slot and return its path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created or the write fails.
+    pub fn write(&mut self, save: &Autosave) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.config.dir).map_err(|e| {
+            GameError::InvalidState(format!("Failed to create autosave directory: {}", e))
+        })?;
+        let path = self.config.slot_path(self.next_slot);
+        save.save(&path)?;
+        self.next_slot = (self.next_slot + 1) % self.config.slots.max(1);
+        self.last_tick = Some(save.tick);
+        Ok(path)
+    }
+}
+
+/// Find the slot file with the highest tick in `dir`, if any.
+///
+/// With a `prefix`, only that rotation's slots are considered. Files that
+/// are not valid autosaves are skipped. Only the header is
+/// inspected, so this is cheap even for large saves.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be read.
+pub fn latest_autosave<P: AsRef<Path>>(dir: P, prefix: Option<&str>) -> Result<Option<PathBuf>> {
+    let entries = std::fs::read_dir(dir.as_ref()).map_err(|e| {
+        GameError::InvalidState(format!("Failed to read autosave directory: {}", e))
+    })?;
+
+    let mut best: Option<(u64, PathBuf)> = None;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let matches = path.extension().is_some_and(|e| e == AUTOSAVE_EXTENSION)
+            && prefix.map_or(true, |prefix| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(&format!("{}-", prefix)))
+            });
+        if !matches {
+            continue;
+        }
+        let Ok(bytes) = read_prefix(&path) else {
+            continue;
+        };
+        let Ok(tick) = read_header(&bytes) else {
+            continue;
+        };
+        if best.as_ref().map_or(true, |(t, _)| tick > *t) {
+            best = Some((tick, path));
+        }
+    }
+    Ok(best.map(|(_, path)| path))
+}
+
+fn read_prefix(path: &Path) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+    let mut header = vec![0u8; HEADER_LEN];
+    std::fs::File::open(path)?.read_exact(&mut header)?;
+    Ok(header)
+}
+
+/// Resolve a `--resume-from` argument: a file is used as-is, a directory
+/// resolves to its newest autosave.
+///
+/// # Errors
+///
+/// Returns an error if the path does not exist or a directory holds no
+/// autosaves.
+pub fn resolve_resume_path<P: AsRef<Path>>(path: P, prefix: Option<&str>) -> Result<PathBuf> {
+    let path = path.as_ref();
+    if path.is_file() {
+        return Ok(path.to_path_buf());
+    }
+    if path.is_dir() {
+        return latest_autosave(path, prefix)?.ok_or_else(|| {
+            GameError::InvalidState(format!("No autosaves found in {}", path.display()))
+        });
+    }
+    Err(GameError::InvalidState(format!(
+        "Autosave path does not exist: {}",
+        path.display()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::FactionMember;
+    use crate::factions::FactionId;
+    use crate::math::{Fixed, Vec2Fixed};
+    use crate::simulation::EntitySpawnParams;
+
+    fn sample_sim() -> Simulation {
+        let mut sim = Simulation::new();
+        let unit = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(10), Fixed::from_num(10))),
+            health: Some(100),
+            movement: Some(Fixed::from_num(2)),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            ..Default::default()
+        });
+        sim.apply_command(
+            unit,
+            Command::MoveTo(Vec2Fixed::new(Fixed::from_num(400), Fixed::from_num(10))),
+        )
+        .unwrap();
+        sim
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let mut sim = sample_sim();
+        for _ in 0..30 {
+            sim.tick();
+        }
+        let save = Autosave::capture("test", &sim)
+            .unwrap()
+            .with_backlog(vec![PendingCommand {
+                entity: 1,
+                command: Command::Stop,
+                queued: true,
+            }])
+            .with_host_state(&(7u32, "ai".to_string()))
+            .unwrap();
+
+        let loaded = Autosave::from_bytes(&save.to_bytes().unwrap()).unwrap();
+        assert_eq!(loaded.tick, 30);
+        assert_eq!(loaded.command_backlog, save.command_backlog);
+        assert_eq!(loaded.host_state::<(u32, String)>().unwrap().1, "ai");
+        assert_eq!(
+            loaded.restore_simulation().unwrap().state_hash(),
+            sim.state_hash()
+        );
+    }
+
+    #[test]
+    fn test_resume_matches_uninterrupted() {
+        let mut uninterrupted = sample_sim();
+        for _ in 0..50 {
+            uninterrupted.tick();
+        }
+        let save = Autosave::capture("test", &uninterrupted).unwrap();
+        for _ in 0..100 {
+            uninterrupted.tick();
+        }
+
+        let mut resumed = Autosave::from_bytes(&save.to_bytes().unwrap())
+            .unwrap()
+            .restore_simulation()
+            .unwrap();
+        for _ in 0..100 {
+            resumed.tick();
+        }
+        assert_eq!(resumed.state_hash(), uninterrupted.state_hash());
+    }
+
+    #[test]
+    fn test_rejects_garbage() {
+        assert!(Autosave::from_bytes(b"nope").is_err());
+        let mut bytes = Autosave::capture("test", &sample_sim())
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        bytes[4] = 99; // version
+        assert!(Autosave::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_rejects_other_engine_and_data() {
+        let local = Fingerprint::engine().with_map("crossroads").unwrap();
+        let save = Autosave::capture("test", &sample_sim())
+            .unwrap()
+            .with_fingerprint(local);
+        let loaded = Autosave::from_bytes(&save.to_bytes().unwrap()).unwrap();
+        assert!(loaded.check_fingerprint(&local).is_ok());
+        let other_map = Fingerprint::engine().with_map("highlands").unwrap();
+        assert!(matches!(
+            loaded.check_fingerprint(&other_map),
+            Err(GameError::IncompatibleFingerprint(_))
+        ));
+
+        let future = save.with_fingerprint(Fingerprint {
+            sim_version: crate::fingerprint::SIM_VERSION + 1,
+            ..local
+        });
+        let err = Autosave::from_bytes(&future.to_bytes().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("engine"), "{err}");
+    }
+
+    #[test]
+    fn test_rotation_and_latest() {
+        let dir = std::env::temp_dir().join(format!("rts_autosave_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = AutosaveConfig::new(&dir, 10).with_slots(2);
+        let mut rotation = AutosaveRotation::new(config.clone());
+
+        assert!(!rotation.is_due(0));
+        assert!(!rotation.is_due(5));
+        assert!(rotation.is_due(10));
+
+        let mut sim = sample_sim();
+        for _ in 0..3 {
+            for _ in 0..10 {
+                sim.tick();
+            }
+            rotation
+                .write(&Autosave::capture("test", &sim).unwrap())
+                .unwrap();
+        }
+        assert!(!rotation.is_due(30));
+
+        // Three writes over two slots: slot 0 was overwritten by tick 30.
+        let latest = latest_autosave(&dir, Some("autosave")).unwrap().unwrap();
+        assert_eq!(latest, config.slot_path(0));
+        assert_eq!(Autosave::load(&latest).unwrap().tick, 30);
+        assert_eq!(resolve_resume_path(&dir, None).unwrap(), latest);
+        assert!(latest_autosave(&dir, Some("other")).unwrap().is_none());
+        assert!(!config.slot_path(2).exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
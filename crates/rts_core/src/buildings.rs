@@ -11,6 +11,7 @@ use crate::components::{EntityId, Position};
 use crate::math::{fixed_serde, Fixed, Vec2Fixed};
 use crate::pathfinding::{CellType, NavGrid};
 use crate::production::Building;
+use crate::research::TechProfile;
 
 // ============================================================================
 // Placement Grid
@@ -461,6 +462,85 @@ pub fn construction_system(
     events
 }
 
+/// Tags that let a unit put up buildings.
+pub const BUILDER_TAGS: [&str; 2] = ["builder", "harvester"];
+
+/// How far beyond the edge of a site a worker can build from.
+pub const CONSTRUCTION_RANGE: Fixed = Fixed::const_from_int(16);
+
+/// How many cells out from the requested spot to look for free ground.
+pub const SITE_SEARCH_RADIUS: u32 = 12;
+
+/// Whether a unit with `profile` can construct buildings.
+#[must_use]
+pub fn is_builder(profile: Option<&TechProfile>) -> bool {
+    profile.is_some_and(|p| p.tags.iter().any(|t| BUILDER_TAGS.contains(&t.as_str())))
+}
+
+/// The ground a structure put up by workers stands on, and what it cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConstructionSite {
+    /// Corner cell of the footprint.
+    pub origin: (u32, u32),
+    /// Cells the structure covers.
+    pub footprint: BuildingFootprint,
+    /// Feedstock paid to start construction.
+    pub cost: u32,
+}
+
+impl ConstructionSite {
+    /// Feedstock returned if construction is cancelled: the share of the
+    /// cost not yet built.
+    #[must_use]
+    pub fn refund(&self, building: &Building) -> u32 {
+        if building.is_construction_complete() {
+            return 0;
+        }
+        let remaining = building
+            .construction_total
+            .saturating_sub(building.construction_progress);
+        let refund = u64::from(self.cost) * u64::from(remaining)
+            / u64::from(building.construction_total.max(1));
+        u32::try_from(refund).unwrap_or(self.cost)
+    }
+
+    /// How close to the centre of the site a worker must be to build.
+    #[must_use]
+    pub fn reach(&self, cell_size: Fixed) -> Fixed {
+        let longest = self.footprint.width.max(self.footprint.height);
+        Fixed::from_num(longest) * cell_size / 2 + CONSTRUCTION_RANGE
+    }
+}
+
+/// Corner cell of `footprint` when centred on `center`, if it lies on the
+/// grid.
+#[must_use]
+pub fn footprint_origin(
+    grid: &PlacementGrid,
+    center: Vec2Fixed,
+    footprint: &BuildingFootprint,
+) -> Option<(u32, u32)> {
+    let half = Vec2Fixed::new(
+        Fixed::from_num(footprint.width) * grid.cell_size() / 2,
+        Fixed::from_num(footprint.height) * grid.cell_size() / 2,
+    );
+    grid.world_to_grid(center - half + Vec2Fixed::new(grid.cell_size() / 2, grid.cell_size() / 2))
+}
+
+/// Centre of `footprint` when its corner cell is `origin`.
+#[must_use]
+pub fn footprint_center(
+    grid: &PlacementGrid,
+    origin: (u32, u32),
+    footprint: &BuildingFootprint,
+) -> Vec2Fixed {
+    grid.grid_to_world(origin.0, origin.1)
+        + Vec2Fixed::new(
+            Fixed::from_num(footprint.width) * grid.cell_size() / 2,
+            Fixed::from_num(footprint.height) * grid.cell_size() / 2,
+        )
+}
+
 // ============================================================================
 // Placement Preview (Ghost Building)
 // ============================================================================
@@ -913,6 +993,32 @@ mod tests {
         assert!(events.is_empty());
     }
 
+    #[test]
+    fn test_construction_site_footprint_and_refund() {
+        let grid = PlacementGrid::new(10, 10, fixed(32));
+        let footprint = BuildingFootprint::new(2, 3);
+        let origin = footprint_origin(&grid, vec2(100, 100), &footprint).unwrap();
+        assert_eq!(origin, (2, 2));
+        assert_eq!(footprint_center(&grid, origin, &footprint), vec2(96, 112));
+        assert_eq!(
+            footprint_origin(&grid, vec2(96, 112), &footprint),
+            Some(origin)
+        );
+
+        let site = ConstructionSite {
+            origin,
+            footprint,
+            cost: 200,
+        };
+        let mut building = Building::new(BuildingTypeId::new(1), 10);
+        assert_eq!(site.refund(&building), 200);
+        for _ in 0..4 {
+            building.tick_construction();
+        }
+        assert_eq!(site.refund(&building), 120);
+        assert_eq!(site.reach(fixed(32)), fixed(64));
+    }
+
     // ------------------------------------------------------------------------
     // PlacementPreview Tests
     // ------------------------------------------------------------------------
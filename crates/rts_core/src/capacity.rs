@@ -0,0 +1,121 @@
+//! Entity capacity limits and graceful degradation.
+//!
+//! A [`Simulation`](crate::simulation::Simulation) can be given an
+//! [`EntityCapacity`]. Once the entity count reaches its pressure threshold,
+//! each tick:
+//! - cosmetic entities are culled, expired ones first and then the oldest,
+//!   until the count is back under the threshold,
+//! - projectiles draw from a fixed pool; shots fired while the pool is empty
+//!   land instantly instead of spawning a projectile entity,
+//! - a [`CapacityPressure`] event tells consumers how close they are.
+//!
+//! None of this fails. Consumers still decide what to do if the count ever
+//! reaches the hard limit, but they hear about it first.
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::EntityId;
+
+/// Default share of the limit at which degradation starts (percent).
+pub const DEFAULT_PRESSURE_PCT: u8 = 80;
+
+/// Default number of projectiles allowed in flight under pressure.
+pub const DEFAULT_PROJECTILE_POOL: usize = 256;
+
+/// Entity limit and the degradation policy applied as it is approached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityCapacity {
+    /// Hard entity limit enforced by the consumer.
+    pub max_entities: usize,
+    /// Share of `max_entities` at which pressure handling starts (1-100).
+    pub pressure_pct: u8,
+    /// Projectiles allowed in flight while under pressure.
+    pub projectile_pool: usize,
+}
+
+impl EntityCapacity {
+    /// Create a capacity policy with default thresholds.
+    #[must_use]
+    pub const fn new(max_entities: usize) -> Self {
+        Self {
+            max_entities,
+            pressure_pct: DEFAULT_PRESSURE_PCT,
+            projectile_pool: DEFAULT_PROJECTILE_POOL,
+        }
+    }
+
+    /// Builder method to set the pressure threshold (clamped to 1-100).
+    #[must_use]
+    pub const fn with_pressure_pct(mut self, pct: u8) -> Self {
+        self.pressure_pct = if pct == 0 {
+            1
+        } else if pct > 100 {
+            100
+        } else {
+            pct
+        };
+        self
+    }
+
+    /// Builder method to set the projectile pool size.
+    #[must_use]
+    pub const fn with_projectile_pool(mut self, pool: usize) -> Self {
+        self.projectile_pool = pool;
+        self
+    }
+
+    /// Entity count at which pressure handling starts.
+    #[must_use]
+    pub const fn pressure_threshold(&self) -> usize {
+        self.max_entities * self.pressure_pct as usize / 100
+    }
+}
+
+/// Report emitted on every tick that starts under capacity pressure.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct CapacityPressure {
+    /// Entity count before culling.
+    pub entities: usize,
+    /// Hard entity limit.
+    pub max_entities: usize,
+    /// Cosmetic entities removed to relieve pressure.
+    pub culled: Vec<EntityId>,
+}
+
+impl CapacityPressure {
+    /// Entities left after culling.
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.entities.saturating_sub(self.culled.len())
+    }
+
+    /// Entities that can still be added before the hard limit.
+    #[must_use]
+    pub fn headroom(&self) -> usize {
+        self.max_entities.saturating_sub(self.remaining())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pressure_threshold() {
+        let capacity = EntityCapacity::new(1000);
+        assert_eq!(capacity.pressure_threshold(), 800);
+        assert_eq!(capacity.with_pressure_pct(0).pressure_threshold(), 10);
+        assert_eq!(capacity.with_pressure_pct(150).pressure_threshold(), 1000);
+    }
+
+    #[test]
+    fn test_pressure_headroom() {
+        let pressure = CapacityPressure {
+            entities: 90,
+            max_entities: 100,
+            culled: vec![1, 2, 3],
+        };
+        assert_eq!(pressure.remaining(), 87);
+        assert_eq!(pressure.headroom(), 13);
+    }
+}
@@ -6,11 +6,14 @@
 //! - Resistance cap at 75% prevents invulnerability
 //! - Size class tracking modifiers
 //! - Damage type effectiveness matrix
+//! - Splash damage with falloff around the point of impact
+//! - High-ground bonuses for ground units firing between elevation levels
 
 use serde::{Deserialize, Serialize};
 
-use crate::components::{ArmorType, DamageType};
-use crate::math::Fixed;
+use crate::components::{ArmorType, DamageType, EntityId, MovementDomain};
+use crate::math::{fixed_serde, fixed_sqrt, Fixed, Vec2Fixed};
+use crate::rng::SimRng;
 
 /// Weapon size class affects tracking vs target size.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
@@ -189,6 +192,9 @@ pub const MAX_RESISTANCE: u8 = 75;
 /// Minimum damage floor - attacks always deal at least 1 damage (unless immune).
 pub const MIN_DAMAGE: u32 = 1;
 
+/// Largest allowed per-shot damage variance (±50%).
+pub const MAX_DAMAGE_VARIANCE: u8 = 50;
+
 /// Combat stats for resistance-based damage calculation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ResistanceStats {
@@ -233,6 +239,137 @@ impl Default for ResistanceStats {
     }
 }
 
+/// Which movement domains a weapon can hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum WeaponTargets {
+    /// Ground, hover and air units alike.
+    #[default]
+    Any,
+    /// Ground and hover units only (e.g. artillery, bombs).
+    GroundOnly,
+    /// Air and hover units only (anti-air).
+    AirOnly,
+}
+
+impl WeaponTargets {
+    /// Check if the weapon can hit a unit moving in `domain`.
+    #[must_use]
+    pub const fn can_hit(self, domain: MovementDomain) -> bool {
+        match (self, domain) {
+            (Self::Any, _) | (_, MovementDomain::Hover) => true,
+            (Self::GroundOnly, domain) => matches!(domain, MovementDomain::Ground),
+            (Self::AirOnly, domain) => matches!(domain, MovementDomain::Air),
+        }
+    }
+
+    /// Domains reachable by either of two weapons.
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::GroundOnly, Self::GroundOnly) => Self::GroundOnly,
+            (Self::AirOnly, Self::AirOnly) => Self::AirOnly,
+            _ => Self::Any,
+        }
+    }
+}
+
+/// How splash damage fades from the centre of a blast to its edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum SplashFalloff {
+    /// Full damage anywhere inside the radius.
+    #[default]
+    Flat,
+    /// Damage drops evenly with distance, to nothing at the edge.
+    Linear,
+    /// Damage holds up near the centre and drops off sharply at the edge.
+    Quadratic,
+}
+
+/// Area damage around a weapon's point of impact.
+///
+/// A radius of zero means the weapon only hits its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct Splash {
+    /// Blast radius around the point of impact.
+    #[serde(with = "fixed_serde")]
+    pub radius: Fixed,
+    /// How damage fades towards the edge of the blast.
+    #[serde(default)]
+    pub falloff: SplashFalloff,
+    /// Whether the blast also hurts the attacker's allies.
+    #[serde(default)]
+    pub friendly_fire: bool,
+}
+
+impl Splash {
+    /// No splash: single-target damage only.
+    pub const NONE: Self = Self {
+        radius: Fixed::ZERO,
+        falloff: SplashFalloff::Flat,
+        friendly_fire: false,
+    };
+
+    /// Create a flat splash of the given radius that spares allies.
+    #[must_use]
+    pub const fn new(radius: Fixed) -> Self {
+        Self {
+            radius,
+            falloff: SplashFalloff::Flat,
+            friendly_fire: false,
+        }
+    }
+
+    /// Builder method to set the falloff curve.
+    #[must_use]
+    pub const fn with_falloff(mut self, falloff: SplashFalloff) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    /// Builder method to set whether allies are hit.
+    #[must_use]
+    pub const fn with_friendly_fire(mut self, friendly_fire: bool) -> Self {
+        self.friendly_fire = friendly_fire;
+        self
+    }
+
+    /// Check if the weapon damages an area rather than a single target.
+    #[must_use]
+    pub fn is_area(&self) -> bool {
+        self.radius > Fixed::ZERO
+    }
+
+    /// Base damage dealt `distance_sq` (squared) from the centre of the
+    /// blast, before resistances, or None outside the radius.
+    ///
+    /// Anything inside the blast takes at least [`MIN_DAMAGE`].
+    #[must_use]
+    pub fn damage_at(&self, damage: u32, distance_sq: Fixed) -> Option<u32> {
+        let radius_sq = self.radius * self.radius;
+        if !self.is_area() || distance_sq > radius_sq {
+            return None;
+        }
+        let remaining = match self.falloff {
+            SplashFalloff::Flat => Fixed::ONE,
+            SplashFalloff::Linear => Fixed::ONE - fixed_sqrt(distance_sq) / self.radius,
+            SplashFalloff::Quadratic => Fixed::ONE - distance_sq / radius_sq,
+        };
+        let scaled = (Fixed::from_num(damage) * remaining.max(Fixed::ZERO)).to_num::<u32>();
+        Some(scaled.max(MIN_DAMAGE))
+    }
+}
+
+/// A splash weapon's shot went off.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SplashEvent {
+    /// The entity that fired the shot.
+    pub attacker: EntityId,
+    /// Centre of the blast.
+    pub center: Vec2Fixed,
+    /// Every entity damaged, including any direct target, in ID order.
+    pub affected: Vec<EntityId>,
+}
+
 /// Weapon stats for armor penetration.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct WeaponStats {
@@ -244,6 +381,12 @@ pub struct WeaponStats {
     pub weapon_size: WeaponSize,
     /// Armor penetration percentage (0-100).
     pub armor_penetration: u8,
+    /// Spread of base damage per shot, as ± percent (0 = fixed damage).
+    #[serde(default)]
+    pub damage_variance: u8,
+    /// Area damage around the point of impact.
+    #[serde(default)]
+    pub splash: Splash,
 }
 
 impl WeaponStats {
@@ -255,6 +398,8 @@ impl WeaponStats {
             damage_type,
             weapon_size: WeaponSize::Medium,
             armor_penetration: 0,
+            damage_variance: 0,
+            splash: Splash::NONE,
         }
     }
 
@@ -272,6 +417,38 @@ impl WeaponStats {
         self
     }
 
+    /// Builder method to set damage variance (capped at [`MAX_DAMAGE_VARIANCE`]).
+    #[must_use]
+    pub const fn with_variance(mut self, percent: u8) -> Self {
+        self.damage_variance = if percent > MAX_DAMAGE_VARIANCE {
+            MAX_DAMAGE_VARIANCE
+        } else {
+            percent
+        };
+        self
+    }
+
+    /// Builder method to set splash damage.
+    #[must_use]
+    pub const fn with_splash(mut self, splash: Splash) -> Self {
+        self.splash = splash;
+        self
+    }
+
+    /// Roll this shot's base damage within the weapon's variance.
+    ///
+    /// Draws from `rng` only when the weapon actually has a spread, so
+    /// fixed-damage weapons leave the generator untouched.
+    #[must_use]
+    pub fn with_rolled_damage(mut self, rng: &mut SimRng) -> Self {
+        let spread = (u64::from(self.damage) * u64::from(self.damage_variance) / 100) as u32;
+        if spread > 0 {
+            self.damage =
+                rng.range_inclusive(self.damage - spread, self.damage.saturating_add(spread));
+        }
+        self
+    }
+
     /// Get armor penetration as a fixed-point fraction.
     #[must_use]
     pub fn penetration_fraction(&self) -> Fixed {
@@ -285,6 +462,59 @@ impl Default for WeaponStats {
     }
 }
 
+/// How elevation changes shots between ground units on different levels.
+///
+/// Firing down from higher ground deals extra damage; firing up at higher
+/// ground sometimes misses. Aircraft, and shots at them, ignore elevation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HighGroundRules {
+    /// Extra damage on shots fired down at a lower level, in percent.
+    pub damage_bonus_pct: u32,
+    /// Chance that a shot fired up at a higher level misses, in percent.
+    pub miss_chance_pct: u8,
+}
+
+impl Default for HighGroundRules {
+    fn default() -> Self {
+        Self {
+            damage_bonus_pct: 25,
+            miss_chance_pct: 30,
+        }
+    }
+}
+
+impl HighGroundRules {
+    /// No bonus and no misses: elevation does not affect combat.
+    pub const NONE: Self = Self {
+        damage_bonus_pct: 0,
+        miss_chance_pct: 0,
+    };
+
+    /// Apply these rules to a shot from elevation level `from` at level
+    /// `to`, returning the shot as fired or None if it misses.
+    ///
+    /// Only shots fired uphill draw from `rng`.
+    pub fn apply(
+        &self,
+        mut weapon: WeaponStats,
+        from: u8,
+        to: u8,
+        rng: &mut SimRng,
+    ) -> Option<WeaponStats> {
+        if from > to {
+            weapon.damage = (u64::from(weapon.damage) * u64::from(100 + self.damage_bonus_pct)
+                / 100)
+                .min(u64::from(u32::MAX)) as u32;
+        } else if from < to
+            && self.miss_chance_pct > 0
+            && rng.range_inclusive(1, 100) <= u32::from(self.miss_chance_pct)
+        {
+            return None;
+        }
+        Some(weapon)
+    }
+}
+
 /// Calculate damage using resistance-based formula.
 ///
 /// Formula:
@@ -366,6 +596,25 @@ pub fn convert_flat_armor_to_resistance(flat_armor: u32, armor_class: ArmorClass
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_splash_falloff_curves() {
+        let radius = Fixed::from_num(10);
+        let at = |falloff, distance: i32| {
+            Splash::new(radius)
+                .with_falloff(falloff)
+                .damage_at(100, Fixed::from_num(distance * distance))
+        };
+
+        assert_eq!(at(SplashFalloff::Flat, 0), Some(100));
+        assert_eq!(at(SplashFalloff::Flat, 8), Some(100));
+        assert_eq!(at(SplashFalloff::Linear, 0), Some(100));
+        assert_eq!(at(SplashFalloff::Linear, 5), Some(50));
+        assert_eq!(at(SplashFalloff::Quadratic, 5), Some(75));
+        assert_eq!(at(SplashFalloff::Linear, 10), Some(MIN_DAMAGE));
+        assert_eq!(at(SplashFalloff::Flat, 11), None);
+        assert_eq!(Splash::NONE.damage_at(100, Fixed::ZERO), None);
+    }
+
     #[test]
     fn test_weapon_size_tracking() {
         // Light weapons are effective vs light
@@ -533,4 +782,49 @@ mod tests {
             assert_eq!(dmg1, dmg2);
         }
     }
+
+    #[test]
+    fn test_high_ground_adds_damage_and_misses_uphill() {
+        let rules = HighGroundRules::default();
+        let weapon = WeaponStats::new(40, ExtendedDamageType::Kinetic);
+        let mut rng = SimRng::new(7);
+
+        let level = rules.apply(weapon, 1, 1, &mut rng).unwrap();
+        assert_eq!(level.damage, 40);
+        let down = rules.apply(weapon, 2, 1, &mut rng).unwrap();
+        assert_eq!(down.damage, 50);
+
+        let untouched = rng;
+        assert!(HighGroundRules::NONE
+            .apply(weapon, 0, 1, &mut rng)
+            .is_some());
+        assert_eq!(rng, untouched);
+
+        let misses = (0..1000)
+            .filter(|_| rules.apply(weapon, 0, 1, &mut rng).is_none())
+            .count();
+        assert!((250..350).contains(&misses), "{misses} misses");
+    }
+
+    #[test]
+    fn test_rolled_damage_stays_within_variance() {
+        let weapon = WeaponStats::new(100, ExtendedDamageType::Kinetic).with_variance(10);
+        let mut rng = SimRng::new(3);
+        let rolls: Vec<u32> = (0..100)
+            .map(|_| weapon.with_rolled_damage(&mut rng).damage)
+            .collect();
+        assert!(rolls.iter().all(|d| (90..=110).contains(d)));
+        assert!(rolls.iter().any(|&d| d != 100));
+
+        // Same seed, same rolls
+        let mut replay = SimRng::new(3);
+        assert_eq!(weapon.with_rolled_damage(&mut replay).damage, rolls[0]);
+
+        // Fixed-damage weapons never draw
+        let mut untouched = SimRng::new(3);
+        let fixed = WeaponStats::new(100, ExtendedDamageType::Kinetic);
+        assert_eq!(fixed.with_rolled_damage(&mut untouched).damage, 100);
+        assert_eq!(untouched, SimRng::new(3));
+        assert_eq!(fixed.with_variance(90).damage_variance, MAX_DAMAGE_VARIANCE);
+    }
 }
@@ -7,7 +7,11 @@ use std::collections::VecDeque;
 
 use serde::{Deserialize, Serialize};
 
-use crate::combat::{ArmorClass, ExtendedDamageType, ResistanceStats, WeaponSize, WeaponStats};
+use crate::abilities::AbilityTarget;
+use crate::combat::{
+    ArmorClass, ExtendedDamageType, ResistanceStats, Splash, SplashFalloff, WeaponSize,
+    WeaponStats, WeaponTargets,
+};
 use crate::factions::FactionId;
 use crate::math::{fixed_serde, Fixed, Vec2Fixed};
 
@@ -110,6 +114,20 @@ impl ArmorType {
     }
 }
 
+/// Human-readable label for logs and inspectors, e.g.
+/// `"collegium/security_team#12"`.
+///
+/// Assigned deterministically at spawn from faction, kind and a per-kind
+/// counter. Purely diagnostic: it is never part of the state hash.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DebugName(pub String);
+
+impl std::fmt::Display for DebugName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 /// Position component in world space.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Position {
@@ -245,8 +263,15 @@ pub struct Selected;
 pub enum Command {
     /// Move to a target position.
     MoveTo(Vec2Fixed),
-    /// Attack a specific entity.
+    /// Attack a specific entity. Rejected for same-faction targets; use
+    /// [`Command::ForceAttack`] for those.
     Attack(EntityId),
+    /// Attack a specific entity regardless of allegiance (forced fire), e.g.
+    /// to clear your own walls.
+    ForceAttack(EntityId),
+    /// Shell a location with a splash weapon, with or without a visible
+    /// target. Persists until replaced, like [`Command::HoldPosition`].
+    AttackGround(Vec2Fixed),
     /// Attack-move to a position (engage enemies along the way).
     AttackMove(Vec2Fixed),
     /// Hold position and engage nearby enemies.
@@ -259,6 +284,46 @@ pub enum Command {
     Follow(EntityId),
     /// Guard another unit (attack anything that attacks it).
     Guard(EntityId),
+    /// Use one of the unit's abilities, closing to range first and waiting
+    /// out its cooldown.
+    UseAbility {
+        /// ID of the ability.
+        ability_id: String,
+        /// What it is aimed at.
+        target: AbilityTarget,
+    },
+    /// Board a friendly transport, closing on it first.
+    Load(EntityId),
+    /// Carry passengers to a position and set them all down there.
+    Unload(Vec2Fixed),
+    /// Restore a damaged ally's health, closing on it first.
+    Repair(EntityId),
+    /// Work on a friendly construction site until it is finished, closing
+    /// on it first.
+    Construct(EntityId),
+    /// Set where a production building sends the units it makes. Takes
+    /// effect at once rather than waiting its turn in the queue.
+    SetRallyPoint(Vec2Fixed),
+}
+
+impl Command {
+    /// The point this command leads to, for commands aimed at a location.
+    #[must_use]
+    pub fn destination(&self) -> Option<Vec2Fixed> {
+        match self {
+            Self::MoveTo(point)
+            | Self::AttackMove(point)
+            | Self::AttackGround(point)
+            | Self::Patrol(point)
+            | Self::Unload(point)
+            | Self::SetRallyPoint(point) => Some(*point),
+            Self::UseAbility {
+                target: AbilityTarget::Point(point),
+                ..
+            } => Some(*point),
+            _ => None,
+        }
+    }
 }
 
 /// Queue of commands for a unit to execute.
@@ -280,7 +345,8 @@ impl CommandQueue {
         }
     }
 
-    /// Add a command to the back of the queue.
+    /// Add a command to the back of the queue, to be carried out once
+    /// those ahead of it are done (shift-queueing).
     pub fn push(&mut self, command: Command) {
         self.commands.push_back(command);
     }
@@ -318,6 +384,12 @@ impl CommandQueue {
     pub fn len(&self) -> usize {
         self.commands.len()
     }
+
+    /// The points the queued commands lead to, in the order they will be
+    /// visited, for drawing a unit's waypoint path.
+    pub fn waypoints(&self) -> impl Iterator<Item = Vec2Fixed> + '_ {
+        self.commands.iter().filter_map(Command::destination)
+    }
 }
 
 /// Component for tracking the current attack target.
@@ -373,6 +445,28 @@ impl Default for AttackTarget {
     }
 }
 
+/// The layer a unit moves in, which decides how it paths and what can
+/// shoot at it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum MovementDomain {
+    /// Walks or drives around obstacles on the navigation grid.
+    #[default]
+    Ground,
+    /// Flies straight over obstacles; only weapons that can hit air reach it.
+    Air,
+    /// Skims low over the ground: paths like a ground unit, but both
+    /// ground and anti-air weapons can hit it.
+    Hover,
+}
+
+impl MovementDomain {
+    /// Check if units in this domain ignore navigation grid obstacles.
+    #[must_use]
+    pub const fn ignores_terrain(self) -> bool {
+        matches!(self, Self::Air)
+    }
+}
+
 /// Movement component for mobile units.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Movement {
@@ -381,6 +475,9 @@ pub struct Movement {
     pub speed: Fixed,
     /// Current movement target (if any).
     pub target: Option<Vec2Fixed>,
+    /// Layer the unit moves in.
+    #[serde(default)]
+    pub domain: MovementDomain,
 }
 
 /// Component tracking patrol behavior between two points.
@@ -442,11 +539,320 @@ impl Health {
     /// Get health as a percentage (0-100).
     #[must_use]
     pub fn percentage(&self) -> u32 {
-        if self.max == 0 {
-            0
+        (self.current * 100).checked_div(self.max).unwrap_or(0)
+    }
+}
+
+/// Ammunition component for units that run on limited rounds.
+///
+/// Only present when a scenario enables logistics. Each shot spends one
+/// round; a unit with an empty magazine cannot fire until resupplied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ammunition {
+    /// Rounds remaining.
+    pub current: u32,
+    /// Magazine capacity.
+    pub max: u32,
+}
+
+impl Ammunition {
+    /// Create a full magazine.
+    #[must_use]
+    pub const fn new(max: u32) -> Self {
+        Self { current: max, max }
+    }
+
+    /// Check if no rounds remain.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.current == 0
+    }
+
+    /// Check if the magazine is full.
+    #[must_use]
+    pub const fn is_full(&self) -> bool {
+        self.current >= self.max
+    }
+
+    /// Spend one round. Returns `false` if the magazine was empty.
+    pub fn spend(&mut self) -> bool {
+        if self.current == 0 {
+            return false;
+        }
+        self.current -= 1;
+        true
+    }
+
+    /// Refill by up to `amount` rounds, returning the rounds actually added.
+    pub fn refill(&mut self, amount: u32) -> u32 {
+        let headroom = self.max.saturating_sub(self.current);
+        let actual = amount.min(headroom);
+        self.current += actual;
+        actual
+    }
+
+    /// Get remaining ammunition as a percentage (0-100).
+    #[must_use]
+    pub fn percentage(&self) -> u32 {
+        (self.current * 100).checked_div(self.max).unwrap_or(0)
+    }
+}
+
+/// Resupply source component (depots and supply units).
+///
+/// Refills the ammunition of friendly units within `radius` by `rate`
+/// rounds per tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Resupply {
+    /// Distance within which friendly units are resupplied.
+    #[serde(with = "fixed_serde")]
+    pub radius: Fixed,
+    /// Rounds restored per tick to each unit in range.
+    pub rate: u32,
+}
+
+impl Resupply {
+    /// Create a resupply source.
+    #[must_use]
+    pub const fn new(radius: Fixed, rate: u32) -> Self {
+        Self { radius, rate }
+    }
+}
+
+/// Health regeneration component.
+///
+/// Once the entity has gone `delay` ticks without losing health, it
+/// recovers `amount` health every `interval` ticks, scaled by its faction's
+/// [`FactionId::regen_modifier_pct`]. Any health loss restarts the delay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Regeneration {
+    /// Health restored per interval, before faction modifiers.
+    pub amount: u32,
+    /// Ticks between heals.
+    pub interval: u32,
+    /// Ticks without damage before recovery starts.
+    pub delay: u32,
+    /// Ticks since health last dropped.
+    pub idle_ticks: u32,
+    /// Health on the previous tick, to notice damage from any source.
+    pub last_health: u32,
+}
+
+impl Regeneration {
+    /// Create a regeneration component.
+    #[must_use]
+    pub const fn new(amount: u32, interval: u32, delay: u32) -> Self {
+        Self {
+            amount,
+            interval: if interval == 0 { 1 } else { interval },
+            delay,
+            idle_ticks: 0,
+            last_health: 0,
+        }
+    }
+
+    /// Whether the entity has been out of combat long enough to recover.
+    #[must_use]
+    pub const fn is_recovering(&self) -> bool {
+        self.idle_ticks >= self.delay
+    }
+
+    /// Health restored per interval for an entity of `faction`.
+    #[must_use]
+    pub fn amount_for(&self, faction: Option<FactionId>) -> u32 {
+        let pct = faction.map_or(100, |f| f.regen_modifier_pct());
+        (u64::from(self.amount) * u64::from(pct) / 100) as u32
+    }
+
+    /// Health restored over `ticks` ticks without damage, delay included.
+    #[must_use]
+    pub fn healed_over(&self, ticks: u64, faction: Option<FactionId>) -> u64 {
+        let heals = ticks.saturating_sub(u64::from(self.delay)) / u64::from(self.interval.max(1));
+        heals * u64::from(self.amount_for(faction))
+    }
+
+    /// Advance one tick and heal `health` if due, returning the amount healed.
+    pub fn tick(&mut self, health: &mut Health, faction: Option<FactionId>) -> u32 {
+        if health.current < self.last_health {
+            self.idle_ticks = 0;
         } else {
-            (self.current * 100) / self.max
+            self.idle_ticks = self.idle_ticks.saturating_add(1);
         }
+
+        let since_delay = self.idle_ticks.saturating_sub(self.delay);
+        let due = since_delay > 0 && since_delay % self.interval.max(1) == 0;
+        let healed = if due && !health.is_dead() {
+            health.heal(self.amount_for(faction))
+        } else {
+            0
+        };
+        self.last_health = health.current;
+        healed
+    }
+}
+
+/// Marker for entities hidden from enemies until detected.
+///
+/// A stealthed entity inside an enemy's vision range stays invisible to
+/// that faction unless it is also within range of one of its [`Detector`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stealth;
+
+/// Reveals enemy [`Stealth`] units within `range`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Detector {
+    /// Distance within which stealthed enemies are revealed.
+    #[serde(with = "fixed_serde")]
+    pub range: Fixed,
+}
+
+impl Detector {
+    /// Create a detector.
+    #[must_use]
+    pub const fn new(range: Fixed) -> Self {
+        Self { range }
+    }
+}
+
+/// Body size for local avoidance.
+///
+/// Moving units with a collider are pushed apart each tick until their
+/// circles of `radius` no longer overlap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Collider {
+    /// Distance from the unit's position to the edge of its body.
+    #[serde(with = "fixed_serde")]
+    pub radius: Fixed,
+}
+
+impl Collider {
+    /// Create a collider.
+    #[must_use]
+    pub const fn new(radius: Fixed) -> Self {
+        Self { radius }
+    }
+}
+
+/// Kind of purely visual entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CosmeticKind {
+    /// What is left of a destroyed unit or building.
+    WreckRemnant,
+    /// Scattered debris from impacts and explosions.
+    Debris,
+}
+
+/// Marker for entities with no gameplay effect.
+///
+/// Cosmetic entities are the first to go when the simulation nears its
+/// entity capacity (see [`crate::capacity`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cosmetic {
+    /// What the entity represents.
+    pub kind: CosmeticKind,
+    /// Tick after which the entity is no longer needed, if any.
+    pub expires_at: Option<u64>,
+}
+
+impl Cosmetic {
+    /// Create a cosmetic marker that never expires.
+    #[must_use]
+    pub const fn new(kind: CosmeticKind) -> Self {
+        Self {
+            kind,
+            expires_at: None,
+        }
+    }
+
+    /// Builder method to set the expiry tick.
+    #[must_use]
+    pub const fn with_expiry(mut self, tick: u64) -> Self {
+        self.expires_at = Some(tick);
+        self
+    }
+
+    /// Check if the entity has outlived its expiry at `tick`.
+    #[must_use]
+    pub const fn is_expired(&self, tick: u64) -> bool {
+        match self.expires_at {
+            Some(expiry) => tick >= expiry,
+            None => false,
+        }
+    }
+}
+
+/// Number of facings an idle unit can turn between (45 degrees apart).
+pub const IDLE_FACINGS: u8 = 8;
+
+/// What an idle unit does while it has no orders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IdleMode {
+    /// Turn to a new facing now and then, without moving.
+    Turn,
+    /// Drift slowly to nearby points within a leash of where it went idle.
+    Wander,
+}
+
+/// Decorative idle behavior so units without orders don't look frozen.
+///
+/// Purely cosmetic: it only acts while the unit has no commands and no
+/// attack target, draws its rolls from the entity ID and tick rather than
+/// the simulation RNG, and target acquisition uses [`Self::anchor`] rather
+/// than the wandered position, so it never changes who fights whom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdleBehavior {
+    /// Kind of idle behavior.
+    pub mode: IdleMode,
+    /// Maximum distance from the anchor when wandering.
+    #[serde(with = "fixed_serde")]
+    pub leash: Fixed,
+    /// Ticks between idle actions.
+    pub interval: u32,
+    /// Where the unit went idle. `None` while it has orders.
+    pub anchor: Option<Vec2Fixed>,
+    /// Point the unit is wandering towards, if any.
+    pub wander_target: Option<Vec2Fixed>,
+    /// Current facing, `0..IDLE_FACINGS` clockwise from east.
+    pub facing: u8,
+    /// Ticks spent idle since the last action.
+    pub timer: u32,
+}
+
+impl IdleBehavior {
+    /// Create an idle behavior that only turns in place.
+    #[must_use]
+    pub const fn turn(interval: u32) -> Self {
+        Self {
+            mode: IdleMode::Turn,
+            leash: Fixed::ZERO,
+            interval: if interval == 0 { 1 } else { interval },
+            anchor: None,
+            wander_target: None,
+            facing: 0,
+            timer: 0,
+        }
+    }
+
+    /// Create an idle behavior that wanders within `leash` of its anchor.
+    #[must_use]
+    pub const fn wander(leash: Fixed, interval: u32) -> Self {
+        let mut idle = Self::turn(interval);
+        idle.mode = IdleMode::Wander;
+        idle.leash = leash;
+        idle
+    }
+
+    /// Whether the unit is currently idling.
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        self.anchor.is_some()
+    }
+
+    /// Stop idling because the unit received orders or a target.
+    pub fn interrupt(&mut self) {
+        self.anchor = None;
+        self.wander_target = None;
+        self.timer = 0;
     }
 }
 
@@ -503,6 +909,23 @@ pub struct CombatStats {
     /// Weapon size class affects tracking vs different armor classes.
     #[serde(default)]
     pub weapon_size: WeaponSize,
+    /// Blast radius around each shot's point of impact (0 = single target,
+    /// and cannot attack ground).
+    #[serde(default, with = "fixed_serde")]
+    pub splash_radius: Fixed,
+    /// How splash damage fades towards the edge of the blast.
+    #[serde(default)]
+    pub splash_falloff: SplashFalloff,
+    /// Whether splash from attacks on units also hurts allies. Ground
+    /// attacks hit everything in the blast regardless.
+    #[serde(default)]
+    pub friendly_fire: bool,
+    /// Per-shot damage spread as ± percent (0 = off).
+    #[serde(default)]
+    pub damage_variance: u8,
+    /// Movement domains the weapon can hit.
+    #[serde(default)]
+    pub targets: WeaponTargets,
 }
 
 impl CombatStats {
@@ -523,6 +946,11 @@ impl CombatStats {
             resistance: 0,
             armor_penetration: 0,
             weapon_size: WeaponSize::Medium,
+            splash_radius: Fixed::ZERO,
+            splash_falloff: SplashFalloff::Flat,
+            friendly_fire: false,
+            damage_variance: 0,
+            targets: WeaponTargets::Any,
         }
     }
 
@@ -579,6 +1007,55 @@ impl CombatStats {
         self
     }
 
+    /// Builder method to set the blast radius of each shot.
+    #[must_use]
+    pub fn with_splash_radius(mut self, radius: Fixed) -> Self {
+        self.splash_radius = radius;
+        self
+    }
+
+    /// Builder method to set how splash damage falls off.
+    #[must_use]
+    pub const fn with_splash_falloff(mut self, falloff: SplashFalloff) -> Self {
+        self.splash_falloff = falloff;
+        self
+    }
+
+    /// Builder method to set whether splash hurts allies.
+    #[must_use]
+    pub const fn with_friendly_fire(mut self, friendly_fire: bool) -> Self {
+        self.friendly_fire = friendly_fire;
+        self
+    }
+
+    /// The splash each shot makes on impact.
+    #[must_use]
+    pub fn splash(&self) -> Splash {
+        Splash::new(self.splash_radius)
+            .with_falloff(self.splash_falloff)
+            .with_friendly_fire(self.friendly_fire)
+    }
+
+    /// Builder method to set per-shot damage variance (± percent).
+    #[must_use]
+    pub const fn with_damage_variance(mut self, percent: u8) -> Self {
+        self.damage_variance = percent;
+        self
+    }
+
+    /// Builder method to set which movement domains the weapon can hit.
+    #[must_use]
+    pub const fn with_targets(mut self, targets: WeaponTargets) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    /// Check if this weapon can shell a location ([`Command::AttackGround`]).
+    #[must_use]
+    pub fn can_attack_ground(&self) -> bool {
+        self.splash_radius > Fixed::ZERO
+    }
+
     /// Check if this unit uses projectiles (non-instant attacks).
     #[must_use]
     pub fn uses_projectiles(&self) -> bool {
@@ -612,6 +1089,8 @@ impl CombatStats {
         )
         .with_size(self.weapon_size)
         .with_penetration(self.armor_penetration)
+        .with_variance(self.damage_variance)
+        .with_splash(self.splash())
     }
 
     /// Convert to ResistanceStats for resistance-based damage calculation.
@@ -649,8 +1128,204 @@ impl Default for CombatStats {
             resistance: 0,
             armor_penetration: 0,
             weapon_size: WeaponSize::Medium,
+            splash_radius: Fixed::ZERO,
+            splash_falloff: SplashFalloff::Flat,
+            friendly_fire: false,
+            damage_variance: 0,
+            targets: WeaponTargets::Any,
+        }
+    }
+}
+
+/// One of several weapons mounted on a unit.
+///
+/// Holds the attack half of [`CombatStats`]; the unit's armor stays on its
+/// combat stats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Weapon {
+    /// Base damage per shot.
+    pub damage: u32,
+    /// Type of damage dealt.
+    pub damage_type: DamageType,
+    /// Reach in world units.
+    #[serde(with = "fixed_serde")]
+    pub range: Fixed,
+    /// Ticks between shots.
+    pub attack_cooldown: u32,
+    /// Ticks until the weapon can fire again.
+    pub cooldown_remaining: u32,
+    /// Projectile speed (0 = instant/hitscan).
+    #[serde(with = "fixed_serde")]
+    pub projectile_speed: Fixed,
+    /// Armor penetration percentage (0-100).
+    pub armor_penetration: u8,
+    /// Size class, for tracking against armor classes.
+    pub weapon_size: WeaponSize,
+    /// Blast radius around each shot's point of impact.
+    #[serde(with = "fixed_serde")]
+    pub splash_radius: Fixed,
+    /// How splash damage falls off.
+    pub splash_falloff: SplashFalloff,
+    /// Whether splash also hurts allies.
+    pub friendly_fire: bool,
+    /// Per-shot damage spread as ± percent.
+    pub damage_variance: u8,
+    /// Movement domains the weapon can hit.
+    pub targets: WeaponTargets,
+}
+
+impl Weapon {
+    /// The weapon described by a unit's combat stats.
+    #[must_use]
+    pub const fn from_stats(stats: &CombatStats) -> Self {
+        Self {
+            damage: stats.damage,
+            damage_type: stats.damage_type,
+            range: stats.range,
+            attack_cooldown: stats.attack_cooldown,
+            cooldown_remaining: stats.cooldown_remaining,
+            projectile_speed: stats.projectile_speed,
+            armor_penetration: stats.armor_penetration,
+            weapon_size: stats.weapon_size,
+            splash_radius: stats.splash_radius,
+            splash_falloff: stats.splash_falloff,
+            friendly_fire: stats.friendly_fire,
+            damage_variance: stats.damage_variance,
+            targets: stats.targets,
+        }
+    }
+
+    /// `stats` with this weapon in hand, keeping its armor.
+    #[must_use]
+    pub const fn arm(&self, mut stats: CombatStats) -> CombatStats {
+        stats.damage = self.damage;
+        stats.damage_type = self.damage_type;
+        stats.range = self.range;
+        stats.attack_cooldown = self.attack_cooldown;
+        stats.cooldown_remaining = self.cooldown_remaining;
+        stats.projectile_speed = self.projectile_speed;
+        stats.armor_penetration = self.armor_penetration;
+        stats.weapon_size = self.weapon_size;
+        stats.splash_radius = self.splash_radius;
+        stats.splash_falloff = self.splash_falloff;
+        stats.friendly_fire = self.friendly_fire;
+        stats.damage_variance = self.damage_variance;
+        stats.targets = self.targets;
+        stats
+    }
+}
+
+/// Weapons of a unit that carries more than one, e.g. a tank's main cannon
+/// and machine gun.
+///
+/// The first weapon is the main one. The unit's [`CombatStats`] hold the
+/// main weapon, widened to the longest range and every domain any weapon
+/// can hit so that targeting considers everything the unit can engage;
+/// combat arms the best weapon for each shot (see [`Armament::choose`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Armament {
+    /// Mounted weapons, main weapon first.
+    pub weapons: Vec<Weapon>,
+}
+
+impl Armament {
+    /// Mount `extras` beside the main weapon in `stats`.
+    #[must_use]
+    pub fn new(stats: &CombatStats, extras: impl IntoIterator<Item = Weapon>) -> Self {
+        let weapons = std::iter::once(Weapon::from_stats(stats))
+            .chain(extras)
+            .collect();
+        Self { weapons }
+    }
+
+    /// `stats` holding the main weapon, with the reach of all of them.
+    #[must_use]
+    pub fn summarize(&self, stats: CombatStats) -> CombatStats {
+        let Some(main) = self.weapons.first() else {
+            return stats;
+        };
+        let mut summary = main.arm(stats);
+        for weapon in &self.weapons[1..] {
+            summary.range = summary.range.max(weapon.range);
+            summary.targets = summary.targets.union(weapon.targets);
+        }
+        summary
+    }
+
+    /// Longest range of the weapons that can shell the ground, if any.
+    #[must_use]
+    pub fn ground_range(&self) -> Option<Fixed> {
+        self.weapons
+            .iter()
+            .filter(|w| w.splash_radius > Fixed::ZERO)
+            .map(|w| w.range)
+            .max()
+    }
+
+    /// Index of the best weapon against a target `distance_sq` away:
+    /// `target` is its domain and armor, or None to shell the ground.
+    ///
+    /// Prefers weapons that can hit the target and are in range, then those
+    /// ready to fire, then the most damage per tick after the target's
+    /// resistance; ties go to the earlier weapon. Falls back to the main
+    /// weapon when none can hit the target at all.
+    #[must_use]
+    pub fn choose(
+        &self,
+        holder: &CombatStats,
+        target: Option<(MovementDomain, ResistanceStats)>,
+        distance_sq: Fixed,
+    ) -> usize {
+        let resistance = target.map(|(_, r)| r).unwrap_or_default();
+        let mut best: Option<(usize, (bool, bool, u64, u64))> = None;
+        for (index, weapon) in self.weapons.iter().enumerate() {
+            let usable = match target {
+                Some((domain, _)) => weapon.targets.can_hit(domain),
+                None => weapon.splash_radius > Fixed::ZERO,
+            };
+            if !usable {
+                continue;
+            }
+            let damage = crate::combat::calculate_resistance_damage(
+                &weapon.arm(*holder).to_weapon_stats(),
+                &resistance,
+            );
+            let score = (
+                distance_sq <= weapon.range * weapon.range,
+                weapon.cooldown_remaining == 0,
+                u64::from(damage),
+                u64::from(weapon.attack_cooldown.max(1)),
+            );
+            if best.map_or(true, |(_, b)| outranks(score, b)) {
+                best = Some((index, score));
+            }
+        }
+        best.map_or(0, |(index, _)| index)
+    }
+
+    /// Count down the cooldowns of every weapon but the armed one.
+    pub fn tick_idle_cooldowns(&mut self, armed: usize) {
+        for (index, weapon) in self.weapons.iter_mut().enumerate() {
+            if index != armed && weapon.cooldown_remaining > 0 {
+                weapon.cooldown_remaining -= 1;
+            }
         }
     }
+
+    /// Change every weapon, e.g. for a researched upgrade.
+    pub fn for_each(&mut self, change: impl FnMut(&mut Weapon)) {
+        self.weapons.iter_mut().for_each(change);
+    }
+}
+
+/// Whether a weapon score `(in range, ready, damage, cooldown)` beats
+/// another, comparing damage per tick without dividing.
+fn outranks(a: (bool, bool, u64, u64), b: (bool, bool, u64, u64)) -> bool {
+    let (a_reach, a_ready, a_damage, a_cooldown) = a;
+    let (b_reach, b_ready, b_damage, b_cooldown) = b;
+    (a_reach, a_ready) > (b_reach, b_ready)
+        || ((a_reach, a_ready) == (b_reach, b_ready)
+            && a_damage * b_cooldown > b_damage * a_cooldown)
 }
 
 // ============================================================================
@@ -671,6 +1346,9 @@ pub struct Projectile {
     /// Travel speed per tick.
     #[serde(with = "fixed_serde")]
     pub speed: Fixed,
+    /// Blast around the target on impact.
+    #[serde(default)]
+    pub splash: Splash,
 }
 
 impl Projectile {
@@ -689,8 +1367,16 @@ impl Projectile {
             damage,
             damage_type,
             speed,
+            splash: Splash::NONE,
         }
     }
+
+    /// Builder method to set the blast on impact.
+    #[must_use]
+    pub const fn with_splash(mut self, splash: Splash) -> Self {
+        self.splash = splash;
+        self
+    }
 }
 
 /// Marker component for buildings.
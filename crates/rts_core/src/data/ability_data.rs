@@ -0,0 +1,100 @@
+//! Ability data structures for data-driven active skills.
+
+use serde::{Deserialize, Serialize};
+
+use crate::math::{fixed_serde, Fixed};
+
+/// What an ability is aimed at.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AbilityTargetType {
+    /// A position on the map.
+    Point,
+    /// Another entity.
+    Entity,
+    /// The unit using the ability.
+    Caster,
+}
+
+/// What an ability does to the entities it affects.
+///
+/// Heals and shields affect the caster's allies, damage its enemies.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AbilityEffect {
+    /// Restore health.
+    Heal {
+        /// Health restored.
+        amount: u32,
+    },
+
+    /// Deal damage that ignores armor (e.g. an EMP burst).
+    Damage {
+        /// Damage dealt.
+        amount: u32,
+    },
+
+    /// Absorb incoming damage for a while.
+    Shield {
+        /// Damage absorbed.
+        amount: u32,
+        /// Ticks the shield lasts.
+        duration: u32,
+    },
+}
+
+/// Data-driven ability definition.
+///
+/// # Example RON
+///
+/// ```ron
+/// AbilityData(
+///     id: "field_repair",
+///     name: "ability.field_repair.name",
+///     cooldown: 200,
+///     cost: 25,
+///     target: Entity,
+///     range: 21474836480,  // Fixed-point for 5.0
+///     effect: Heal(amount: 40),
+/// )
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AbilityData {
+    /// Unique string identifier, used by [`Command::UseAbility`](crate::components::Command::UseAbility).
+    pub id: String,
+
+    /// Localization key for the ability's display name.
+    pub name: String,
+
+    /// Ticks before the ability can be used again.
+    pub cooldown: u32,
+
+    /// Feedstock paid each time the ability is used.
+    #[serde(default)]
+    pub cost: u32,
+
+    /// What the ability is aimed at.
+    pub target: AbilityTargetType,
+
+    /// How close the caster must be to its target (ignored for
+    /// [`AbilityTargetType::Caster`]).
+    #[serde(default, with = "fixed_serde")]
+    pub range: Fixed,
+
+    /// Radius around the target that is affected (0 = the target alone).
+    #[serde(default, with = "fixed_serde")]
+    pub radius: Fixed,
+
+    /// What the ability does.
+    pub effect: AbilityEffect,
+}
+
+impl AbilityData {
+    /// Whether the effect lands on the caster's allies rather than its
+    /// enemies.
+    #[must_use]
+    pub const fn is_friendly(&self) -> bool {
+        matches!(
+            self.effect,
+            AbilityEffect::Heal { .. } | AbilityEffect::Shield { .. }
+        )
+    }
+}
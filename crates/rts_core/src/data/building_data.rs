@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::buildings::BuildingFootprint;
+use crate::economy::ResourceAmounts;
 use crate::math::Fixed;
 
 /// Data-driven building definition.
@@ -23,6 +25,7 @@ use crate::math::Fixed;
 ///     tech_required: [],
 ///     provides_tech: [],
 ///     tier: 1,
+///     footprint: (width: 3, height: 3),
 /// )
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +42,10 @@ pub struct BuildingData {
     /// Feedstock cost to construct this building.
     pub cost: i32,
 
+    /// Cost in rarer resources, paid alongside `cost`.
+    #[serde(default, skip_serializing_if = "ResourceAmounts::is_empty")]
+    pub extra_cost: ResourceAmounts,
+
     /// Construction time in simulation ticks.
     pub build_time: u32,
 
@@ -84,6 +91,18 @@ pub struct BuildingData {
     /// Whether this building is the faction's main base.
     #[serde(default)]
     pub is_main_base: bool,
+
+    /// Whether this building is a market where its owner can trade.
+    #[serde(default)]
+    pub is_market: bool,
+
+    /// Supply this building adds to its owner's cap once constructed.
+    #[serde(default)]
+    pub supply_provided: u32,
+
+    /// Cells the building covers on the placement grid.
+    #[serde(default = "default_footprint")]
+    pub footprint: BuildingFootprint,
 }
 
 /// Default tier for buildings without explicit tier.
@@ -91,6 +110,11 @@ const fn default_tier() -> u8 {
     1
 }
 
+/// Default footprint for buildings without an explicit one.
+const fn default_footprint() -> BuildingFootprint {
+    BuildingFootprint::square(2)
+}
+
 /// Default to true for targetable.
 const fn default_true() -> bool {
     true
@@ -158,6 +182,7 @@ mod tests {
             name: "building.test.name".to_string(),
             description: "building.test.desc".to_string(),
             cost: 150,
+            extra_cost: ResourceAmounts::new(),
             build_time: 180,
             health: 500,
             produces: vec!["security_team".to_string(), "patrol_vehicle".to_string()],
@@ -170,6 +195,9 @@ mod tests {
             tags: vec!["production".to_string()],
             is_harvester: false,
             is_main_base: false,
+            is_market: false,
+            supply_provided: 0,
+            footprint: BuildingFootprint::square(2),
         }
     }
 
@@ -5,7 +5,9 @@ use serde::{Deserialize, Serialize};
 use super::building_data::BuildingData;
 use super::tech_data::TechData;
 use super::unit_data::UnitData;
-use crate::factions::FactionId;
+use crate::economy::{ResourceAmounts, ResourceKind, UpkeepModel};
+use crate::factions::{FactionId, MechanicData};
+use crate::market::MarketRules;
 
 /// Complete faction data definition.
 ///
@@ -60,9 +62,45 @@ pub struct FactionData {
     #[serde(default)]
     pub starting_buildings: Vec<StartingEntity>,
 
+    /// Named starting presets scenarios can pick from.
+    #[serde(default)]
+    pub starting_presets: Vec<StartingPreset>,
+
     /// Starting feedstock amount.
     #[serde(default = "default_starting_feedstock")]
     pub starting_feedstock: i32,
+
+    /// Faction-unique rules, such as buildings that can only be deployed once.
+    #[serde(default)]
+    pub mechanics: Vec<MechanicData>,
+
+    /// What a large army costs the faction (none by default), e.g.
+    ///
+    /// ```ron
+    /// upkeep: (
+    ///     tiers: [(above_supply: 60, income_cut: 20)],
+    ///     drain: Some((
+    ///         resource: "legitimacy",
+    ///         capacity: 1000,
+    ///         above_supply: 80,
+    ///         per_supply: 2,
+    ///         recovery: 10,
+    ///     )),
+    /// ),
+    /// ```
+    #[serde(default)]
+    pub upkeep: UpkeepModel,
+
+    /// Exchange rates at the faction's markets (the defaults if omitted), e.g.
+    ///
+    /// ```ron
+    /// market: (
+    ///     rates: [(from: Feedstock, to: Exotics, give: 4, get: 1)],
+    ///     window_ticks: 600,
+    /// ),
+    /// ```
+    #[serde(default)]
+    pub market: MarketRules,
 }
 
 /// Default primary color (blue-ish).
@@ -95,6 +133,21 @@ pub struct StartingEntity {
     pub offset_y: i32,
 }
 
+/// A named set of starting units and buildings (e.g. "standard", "fast-expand").
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartingPreset {
+    /// Name scenarios refer to the preset by.
+    pub name: String,
+
+    /// Starting units for this preset.
+    #[serde(default)]
+    pub starting_units: Vec<StartingEntity>,
+
+    /// Starting buildings for this preset.
+    #[serde(default)]
+    pub starting_buildings: Vec<StartingEntity>,
+}
+
 impl FactionData {
     /// Find a unit by its ID.
     #[must_use]
@@ -114,6 +167,12 @@ impl FactionData {
         self.technologies.iter().find(|t| t.id == id)
     }
 
+    /// Find a starting preset by name.
+    #[must_use]
+    pub fn get_starting_preset(&self, name: &str) -> Option<&StartingPreset> {
+        self.starting_presets.iter().find(|p| p.name == name)
+    }
+
     /// Get all units at a specific tier.
     pub fn units_at_tier(&self, tier: u8) -> impl Iterator<Item = &UnitData> {
         self.units.iter().filter(move |u| u.tier == tier)
@@ -140,6 +199,8 @@ impl FactionData {
     /// - Unit references in buildings are valid
     /// - Tech prerequisites exist
     /// - Building references in units are valid
+    /// - Starting entities and presets reference known units and buildings
+    /// - Mechanics reference known buildings
     ///
     /// Returns a list of validation errors.
     #[must_use]
@@ -148,6 +209,7 @@ impl FactionData {
 
         // Check building production references
         for building in &self.buildings {
+            check_extra_cost("Building", &building.id, &building.extra_cost, &mut errors);
             for unit_id in &building.produces {
                 if self.get_unit(unit_id).is_none() {
                     errors.push(format!(
@@ -170,6 +232,7 @@ impl FactionData {
 
         // Check unit production location references
         for unit in &self.units {
+            check_extra_cost("Unit", &unit.id, &unit.extra_cost, &mut errors);
             for building_id in &unit.produced_at {
                 if self.get_building(building_id).is_none() {
                     errors.push(format!(
@@ -188,6 +251,22 @@ impl FactionData {
                     ));
                 }
             }
+
+            // Extra weapons mount beside the main one
+            if !unit.weapons.is_empty() && unit.combat.is_none() {
+                errors.push(format!(
+                    "Unit '{}' has weapons but no combat stats",
+                    unit.id
+                ));
+            }
+            for (i, weapon) in unit.weapons.iter().enumerate() {
+                if unit.weapons[..i].iter().any(|w| w.id == weapon.id) {
+                    errors.push(format!(
+                        "Unit '{}' has weapon '{}' twice",
+                        unit.id, weapon.id
+                    ));
+                }
+            }
         }
 
         // Check tech prerequisites
@@ -227,13 +306,111 @@ impl FactionData {
             }
         }
 
+        // Check starting presets
+        for (i, preset) in self.starting_presets.iter().enumerate() {
+            if self.starting_presets[..i]
+                .iter()
+                .any(|p| p.name == preset.name)
+            {
+                errors.push(format!(
+                    "Starting preset '{}' is defined twice",
+                    preset.name
+                ));
+            }
+            for entity in &preset.starting_units {
+                if self.get_unit(&entity.type_id).is_none() {
+                    errors.push(format!(
+                        "Starting preset '{}' has unknown unit '{}'",
+                        preset.name, entity.type_id
+                    ));
+                }
+            }
+            for entity in &preset.starting_buildings {
+                if self.get_building(&entity.type_id).is_none() {
+                    errors.push(format!(
+                        "Starting preset '{}' has unknown building '{}'",
+                        preset.name, entity.type_id
+                    ));
+                }
+            }
+        }
+
+        // Check upkeep
+        for tier in &self.upkeep.tiers {
+            if tier.income_cut > 100 {
+                errors.push(format!(
+                    "Upkeep above {} supply cuts income by {}%",
+                    tier.above_supply, tier.income_cut
+                ));
+            }
+        }
+        if let Some(drain) = &self.upkeep.drain {
+            if drain.exhausted_income_cut > 100 {
+                errors.push(format!(
+                    "Upkeep resource '{}' cuts income by {}% when used up",
+                    drain.resource, drain.exhausted_income_cut
+                ));
+            }
+            if drain.interval_ticks == 0 {
+                errors.push(format!(
+                    "Upkeep resource '{}' drains every 0 ticks",
+                    drain.resource
+                ));
+            }
+        }
+
+        // Check market
+        for rate in &self.market.rates {
+            if rate.from == rate.to {
+                errors.push(format!("Market trades {} for itself", rate.from));
+            } else if rate.give == 0 || rate.get == 0 {
+                errors.push(format!(
+                    "Market rate {} for {} is {}:{}",
+                    rate.from, rate.to, rate.give, rate.get
+                ));
+            }
+        }
+        if self.market.max_slippage_percent > 100 {
+            errors.push(format!(
+                "Market slippage capped at {}%",
+                self.market.max_slippage_percent
+            ));
+        }
+
+        // Check mechanics
+        for mechanic in &self.mechanics {
+            if let MechanicData::DeployOnce(deploy) = mechanic {
+                if self.get_building(&deploy.building).is_none() {
+                    errors.push(format!(
+                        "Deploy-once mechanic names unknown building '{}'",
+                        deploy.building
+                    ));
+                }
+            }
+        }
+
         errors
     }
 }
 
+/// Check that an `extra_cost` lists only rarer resources, each above zero.
+fn check_extra_cost(what: &str, id: &str, cost: &ResourceAmounts, errors: &mut Vec<String>) {
+    for (kind, amount) in cost.iter() {
+        if kind == ResourceKind::Feedstock {
+            errors.push(format!(
+                "{what} '{id}' lists feedstock in extra_cost; put it in cost"
+            ));
+        } else if amount < 0 {
+            errors.push(format!("{what} '{id}' has a negative {kind} cost"));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::buildings::BuildingFootprint;
+    use crate::market::ExchangeRate;
 
     fn create_test_faction_data() -> FactionData {
         FactionData {
@@ -245,20 +422,29 @@ mod tests {
                 name: "unit.security_team.name".to_string(),
                 description: "unit.security_team.desc".to_string(),
                 cost: 50,
+                extra_cost: Default::default(),
                 build_time: 120,
                 health: 80,
                 speed: crate::math::Fixed::from_num(10),
+                domain: crate::components::MovementDomain::Ground,
                 combat: None,
+                weapons: vec![],
                 tech_required: vec![],
                 tier: 1,
                 produced_at: vec!["training_center".to_string()],
                 tags: vec!["infantry".to_string()],
+                regen: None,
+                supply: None,
+                abilities: vec![],
+                cargo: None,
+                repair: None,
             }],
             buildings: vec![BuildingData {
                 id: "training_center".to_string(),
                 name: "building.training_center.name".to_string(),
                 description: "building.training_center.desc".to_string(),
                 cost: 150,
+                extra_cost: Default::default(),
                 build_time: 180,
                 health: 500,
                 produces: vec!["security_team".to_string()],
@@ -271,6 +457,9 @@ mod tests {
                 tags: vec!["production".to_string()],
                 is_harvester: false,
                 is_main_base: false,
+                is_market: false,
+                supply_provided: 0,
+                footprint: BuildingFootprint::square(2),
             }],
             technologies: vec![],
             primary_color: [0, 50, 150],
@@ -281,7 +470,19 @@ mod tests {
                 offset_x: 0,
                 offset_y: 0,
             }],
+            starting_presets: vec![StartingPreset {
+                name: "standard".to_string(),
+                starting_units: vec![StartingEntity {
+                    type_id: "security_team".to_string(),
+                    offset_x: 5,
+                    offset_y: 0,
+                }],
+                starting_buildings: vec![],
+            }],
             starting_feedstock: 500,
+            mechanics: vec![],
+            upkeep: Default::default(),
+            market: Default::default(),
         }
     }
 
@@ -317,4 +518,78 @@ mod tests {
         assert!(!errors.is_empty());
         assert!(errors[0].contains("unknown unit"));
     }
+
+    #[test]
+    fn test_validate_starting_presets() {
+        let mut faction = create_test_faction_data();
+        assert!(faction.get_starting_preset("standard").is_some());
+
+        faction.starting_presets[0]
+            .starting_buildings
+            .push(StartingEntity {
+                type_id: "unknown_building".to_string(),
+                offset_x: 0,
+                offset_y: 0,
+            });
+        let duplicate = faction.starting_presets[0].clone();
+        faction.starting_presets.push(duplicate);
+
+        let errors = faction.validate();
+        assert!(errors.iter().any(|e| e.contains("defined twice")));
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("unknown building 'unknown_building'")));
+    }
+    #[test]
+    fn test_validate_weapons() {
+        let mut faction = create_test_faction_data();
+        let weapon = crate::data::WeaponData {
+            id: "coax_mg".to_string(),
+            damage: 6,
+            damage_type: crate::components::DamageType::Kinetic,
+            range: crate::math::Fixed::from_num(6),
+            attack_cooldown: 10,
+            projectile_speed: crate::math::Fixed::ZERO,
+            splash_radius: crate::math::Fixed::ZERO,
+            splash_falloff: crate::combat::SplashFalloff::Flat,
+            friendly_fire: false,
+            damage_variance: 0,
+            targets: crate::combat::WeaponTargets::Any,
+        };
+        faction.units[0].weapons = vec![weapon.clone(), weapon];
+
+        let errors = faction.validate();
+        assert!(errors.iter().any(|e| e.contains("no combat stats")));
+        assert!(errors.iter().any(|e| e.contains("weapon 'coax_mg' twice")));
+    }
+
+    #[test]
+    fn test_validate_extra_costs() {
+        let mut faction = create_test_faction_data();
+        faction.units[0].extra_cost = ResourceAmounts::new().with(ResourceKind::Exotics, 20);
+        assert!(faction.validate().is_empty());
+        assert_eq!(faction.units[0].feedstock_value(), 50 + 20 * 4);
+
+        faction.units[0].extra_cost.add(ResourceKind::Feedstock, 10);
+        faction.buildings[0].extra_cost = ResourceAmounts::new().with(ResourceKind::Cores, -1);
+        let errors = faction.validate();
+        assert!(errors.iter().any(|e| e.contains("feedstock in extra_cost")));
+        assert!(errors.iter().any(|e| e.contains("negative cores cost")));
+    }
+
+    #[test]
+    fn test_validate_market_rates() {
+        let mut faction = create_test_faction_data();
+        assert!(faction.validate().is_empty());
+
+        faction.market.rates = vec![
+            ExchangeRate::new(ResourceKind::Cores, ResourceKind::Cores, 1, 1),
+            ExchangeRate::new(ResourceKind::Feedstock, ResourceKind::Exotics, 0, 1),
+        ];
+        faction.market.max_slippage_percent = 150;
+        let errors = faction.validate();
+        assert!(errors.iter().any(|e| e.contains("trades cores for itself")));
+        assert!(errors.iter().any(|e| e.contains("is 0:1")));
+        assert!(errors.iter().any(|e| e.contains("capped at 150%")));
+    }
 }
@@ -7,12 +7,14 @@
 //! **Note:** This module contains no IO - it only defines data types.
 //! File loading is handled by `rts_game`.
 
+mod ability_data;
 mod building_data;
 mod faction_data;
 mod tech_data;
 mod unit_data;
 
+pub use ability_data::{AbilityData, AbilityEffect, AbilityTargetType};
 pub use building_data::BuildingData;
-pub use faction_data::{FactionData, StartingEntity};
+pub use faction_data::{FactionData, StartingEntity, StartingPreset};
 pub use tech_data::{TechData, TechEffect, TechEffectType};
-pub use unit_data::{CombatStats, UnitData};
+pub use unit_data::{CargoData, CombatStats, RegenStats, RepairStats, UnitData, WeaponData};
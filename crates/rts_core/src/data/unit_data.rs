@@ -2,7 +2,13 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::ability_data::AbilityData;
+use crate::combat::{SplashFalloff, WeaponTargets};
+use crate::components::{self, DamageType, MovementDomain, Weapon};
+use crate::economy::{ResourceAmounts, HEAVY_UNIT_SUPPLY, LIGHT_UNIT_SUPPLY};
 use crate::math::{fixed_serde, Fixed};
+use crate::repair::RepairKind;
+use crate::salvage::{salvage_rate_for_tier, salvage_value};
 
 /// Combat statistics for a unit.
 ///
@@ -23,6 +29,144 @@ pub struct CombatStats {
     /// Armor value that reduces incoming damage.
     #[serde(default)]
     pub armor: u32,
+
+    /// Blast radius around each shot's point of impact (0 = single
+    /// target, and cannot attack ground).
+    #[serde(default, with = "fixed_serde")]
+    pub splash_radius: Fixed,
+
+    /// How splash damage fades towards the edge of the blast.
+    #[serde(default)]
+    pub splash_falloff: SplashFalloff,
+
+    /// Whether splash also hurts the unit's allies.
+    #[serde(default)]
+    pub friendly_fire: bool,
+
+    /// Per-shot damage spread as ± percent (0 = fixed damage).
+    #[serde(default)]
+    pub damage_variance: u8,
+
+    /// Movement domains the weapon can hit (e.g. `GroundOnly` artillery).
+    #[serde(default)]
+    pub targets: WeaponTargets,
+}
+
+/// A weapon a unit carries beside the main one in its [`CombatStats`],
+/// such as a tank's coaxial machine gun.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WeaponData {
+    /// Identifier, unique among the unit's weapons.
+    pub id: String,
+
+    /// Base damage per shot.
+    pub damage: u32,
+
+    /// Type of damage dealt.
+    #[serde(default)]
+    pub damage_type: DamageType,
+
+    /// Reach in game units.
+    #[serde(with = "fixed_serde")]
+    pub range: Fixed,
+
+    /// Cooldown between shots in ticks.
+    pub attack_cooldown: u32,
+
+    /// Projectile speed (0 = hits instantly).
+    #[serde(default, with = "fixed_serde")]
+    pub projectile_speed: Fixed,
+
+    /// Blast radius around each shot's point of impact (0 = single target).
+    #[serde(default, with = "fixed_serde")]
+    pub splash_radius: Fixed,
+
+    /// How splash damage fades towards the edge of the blast.
+    #[serde(default)]
+    pub splash_falloff: SplashFalloff,
+
+    /// Whether splash also hurts the unit's allies.
+    #[serde(default)]
+    pub friendly_fire: bool,
+
+    /// Per-shot damage spread as ± percent (0 = fixed damage).
+    #[serde(default)]
+    pub damage_variance: u8,
+
+    /// Movement domains the weapon can hit.
+    #[serde(default)]
+    pub targets: WeaponTargets,
+}
+
+impl WeaponData {
+    /// The simulation weapon this data describes.
+    #[must_use]
+    pub fn weapon(&self) -> Weapon {
+        let stats = components::CombatStats::new(self.damage, self.range, self.attack_cooldown)
+            .with_damage_type(self.damage_type)
+            .with_projectile_speed(self.projectile_speed)
+            .with_splash_radius(self.splash_radius)
+            .with_splash_falloff(self.splash_falloff)
+            .with_friendly_fire(self.friendly_fire)
+            .with_damage_variance(self.damage_variance)
+            .with_targets(self.targets);
+        Weapon::from_stats(&stats)
+    }
+}
+
+/// Out-of-combat health regeneration for a unit kind.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RegenStats {
+    /// Health restored per interval.
+    pub amount: u32,
+
+    /// Ticks between heals.
+    #[serde(default = "default_regen_interval")]
+    pub interval_ticks: u32,
+
+    /// Ticks without taking damage before regeneration starts.
+    #[serde(default = "default_regen_delay")]
+    pub delay_ticks: u32,
+}
+
+/// Repair or healing a unit can give its allies.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RepairStats {
+    /// What the unit can mend.
+    #[serde(default)]
+    pub kind: RepairKind,
+
+    /// Health restored per repair.
+    pub amount: u32,
+
+    /// Ticks between repairs.
+    #[serde(default = "default_regen_interval")]
+    pub interval_ticks: u32,
+
+    /// Feedstock each repair costs.
+    #[serde(default)]
+    pub cost: u32,
+}
+
+/// Hold of a transport unit.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CargoData {
+    /// Most passengers carried at once.
+    pub capacity: u32,
+
+    /// Units carrying one of these tags may board (empty = any unit).
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Default regeneration interval (one second).
+const fn default_regen_interval() -> u32 {
+    60
+}
+
+/// Default out-of-combat delay (five seconds).
+const fn default_regen_delay() -> u32 {
+    300
 }
 
 /// Data-driven unit definition.
@@ -67,6 +211,11 @@ pub struct UnitData {
     /// Feedstock cost to produce this unit.
     pub cost: u32,
 
+    /// Cost in rarer resources, paid alongside `cost`, e.g.
+    /// `{Exotics: 40}`.
+    #[serde(default, skip_serializing_if = "ResourceAmounts::is_empty")]
+    pub extra_cost: ResourceAmounts,
+
     /// Production time in simulation ticks.
     pub build_time: u32,
 
@@ -77,10 +226,20 @@ pub struct UnitData {
     #[serde(with = "fixed_serde")]
     pub speed: Fixed,
 
+    /// Layer the unit moves in: aircraft fly over obstacles and can only be
+    /// hit by weapons that reach the air.
+    #[serde(default)]
+    pub domain: MovementDomain,
+
     /// Combat statistics (None for non-combat units).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub combat: Option<CombatStats>,
 
+    /// Weapons carried beside the main one in `combat`; in battle the unit
+    /// fires whichever suits the target best. Needs `combat`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weapons: Vec<WeaponData>,
+
     /// Technologies required to produce this unit.
     #[serde(default)]
     pub tech_required: Vec<String>,
@@ -96,6 +255,27 @@ pub struct UnitData {
     /// Tags for categorization and targeting (e.g., "infantry", "vehicle", "air").
     #[serde(default)]
     pub tags: Vec<String>,
+
+    /// Out-of-combat health regeneration (None = no regeneration).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regen: Option<RegenStats>,
+
+    /// Supply taken while queued or alive (None = inferred from tags, see
+    /// [`UnitData::supply_cost`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supply: Option<u32>,
+
+    /// Active abilities the unit can use.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub abilities: Vec<AbilityData>,
+
+    /// Passenger hold, for transports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cargo: Option<CargoData>,
+
+    /// Repair or healing the unit gives its allies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repair: Option<RepairStats>,
 }
 
 /// Default tier for units without explicit tier.
@@ -121,11 +301,51 @@ impl UnitData {
     pub fn is_combatant(&self) -> bool {
         self.combat.is_some()
     }
+
+    /// Supply this unit takes.
+    ///
+    /// Uses the explicit `supply` value if set. Otherwise harvesters,
+    /// workers, ranged and heavy units cost [`HEAVY_UNIT_SUPPLY`] and
+    /// everything else [`LIGHT_UNIT_SUPPLY`].
+    #[must_use]
+    pub fn supply_cost(&self) -> u32 {
+        self.supply.unwrap_or_else(|| {
+            let heavy = ["harvester", "worker", "ranged", "ranger", "heavy"]
+                .iter()
+                .any(|tag| self.has_tag(tag));
+            if heavy {
+                HEAVY_UNIT_SUPPLY
+            } else {
+                LIGHT_UNIT_SUPPLY
+            }
+        })
+    }
+
+    /// Whole cost, rarer resources included, in feedstock terms (see
+    /// [`crate::economy::ResourceKind::feedstock_value`]).
+    #[must_use]
+    pub fn feedstock_value(&self) -> i64 {
+        i64::from(self.cost) + self.extra_cost.feedstock_value()
+    }
+
+    /// Value of the wreck this unit leaves, a share of its cost.
+    #[must_use]
+    pub const fn salvage_value(&self) -> u32 {
+        salvage_value(self.cost)
+    }
+
+    /// Salvage this unit collects per tick, if it is tagged `battleline`.
+    #[must_use]
+    pub fn salvage_rate(&self) -> Option<u32> {
+        self.has_tag("battleline")
+            .then(|| salvage_rate_for_tier(u32::from(self.tier)))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::economy::ResourceKind;
 
     fn create_test_unit() -> UnitData {
         UnitData {
@@ -133,19 +353,32 @@ mod tests {
             name: "unit.test.name".to_string(),
             description: "unit.test.desc".to_string(),
             cost: 100,
+            extra_cost: ResourceAmounts::new(),
             build_time: 60,
             health: 100,
             speed: Fixed::from_num(5),
+            domain: MovementDomain::Ground,
             combat: Some(CombatStats {
                 damage: 10,
                 range: Fixed::from_num(3),
                 attack_cooldown: 30,
                 armor: 5,
+                splash_radius: Fixed::ZERO,
+                splash_falloff: SplashFalloff::Flat,
+                friendly_fire: false,
+                damage_variance: 0,
+                targets: WeaponTargets::Any,
             }),
+            weapons: vec![],
             tech_required: vec!["enhanced_training".to_string()],
             tier: 1,
             produced_at: vec!["training_center".to_string()],
             tags: vec!["infantry".to_string()],
+            regen: None,
+            supply: None,
+            abilities: vec![],
+            cargo: None,
+            repair: None,
         }
     }
 
@@ -163,6 +396,80 @@ mod tests {
         assert!(!unit.has_tag("vehicle"));
     }
 
+    #[test]
+    fn test_regen_defaults() {
+        let unit: UnitData = ron::from_str(
+            r#"(
+                id: "mender",
+                name: "unit.test.name",
+                description: "unit.test.desc",
+                cost: 50,
+                build_time: 60,
+                health: 80,
+                speed: 42949672960,
+                regen: Some((amount: 2)),
+            )"#,
+        )
+        .unwrap();
+        let regen = unit.regen.unwrap();
+        assert_eq!(regen.interval_ticks, 60);
+        assert_eq!(regen.delay_ticks, 300);
+    }
+
+    #[test]
+    fn test_extra_cost_parses_as_a_map() {
+        let unit: UnitData = ron::from_str(
+            r#"(
+                id: "lancer",
+                name: "unit.test.name",
+                description: "unit.test.desc",
+                cost: 100,
+                extra_cost: {Exotics: 40, Cores: 1},
+                build_time: 60,
+                health: 80,
+                speed: 42949672960,
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(unit.extra_cost.get(ResourceKind::Exotics), 40);
+        assert_eq!(unit.extra_cost.get(ResourceKind::Cores), 1);
+        assert_eq!(unit.feedstock_value(), 100 + 40 * 4 + 10);
+    }
+
+    #[test]
+    fn test_weapons_parse_with_defaults() {
+        let unit: UnitData = ron::from_str(
+            r#"(
+                id: "battle_tank",
+                name: "unit.test.name",
+                description: "unit.test.desc",
+                cost: 300,
+                build_time: 600,
+                health: 500,
+                speed: 21474836480,
+                combat: Some((damage: 60, range: 34359738368, attack_cooldown: 90)),
+                weapons: [
+                    (
+                        id: "coax_mg",
+                        damage: 6,
+                        range: 25769803776,
+                        attack_cooldown: 10,
+                        targets: Any,
+                    ),
+                ],
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(unit.weapons.len(), 1);
+
+        let weapon = unit.weapons[0].weapon();
+        assert_eq!(weapon.damage, 6);
+        assert_eq!(weapon.range, Fixed::from_num(6));
+        assert_eq!(weapon.damage_type, DamageType::Kinetic);
+        assert_eq!(weapon.projectile_speed, Fixed::ZERO);
+        assert_eq!(weapon.cooldown_remaining, 0);
+    }
+
     #[test]
     fn test_is_combatant() {
         let mut unit = create_test_unit();
@@ -171,4 +478,17 @@ mod tests {
         unit.combat = None;
         assert!(!unit.is_combatant());
     }
+
+    #[test]
+    fn test_supply_cost() {
+        let mut unit = create_test_unit();
+        assert_eq!(unit.supply_cost(), LIGHT_UNIT_SUPPLY);
+
+        unit.tags.push("heavy".to_string());
+        assert_eq!(unit.supply_cost(), HEAVY_UNIT_SUPPLY);
+
+        // Explicit supply overrides the tag rule
+        unit.supply = Some(4);
+        assert_eq!(unit.supply_cost(), 4);
+    }
 }
@@ -0,0 +1,228 @@
+//! AI difficulty levels and the handicaps behind them.
+//!
+//! A [`Handicap`] makes a computer player weaker or stronger without
+//! changing how it plays: it scales the AI's income, delays its reaction
+//! to enemies it sees, shifts how eagerly it attacks and throttles how
+//! often it acts. [`Difficulty`] names the preset handicaps offered in
+//! skirmish setup; both the game client's AI and the headless runner apply
+//! them, and either can take a custom handicap per AI player instead.
+//!
+//! All math is integer, in percent where a handicap scales something.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::TICK_RATE;
+
+/// Preset AI strength for skirmish.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    /// Slow to react, poorer and hesitant to attack.
+    Easy,
+    /// The AI as designed, with no handicap.
+    #[default]
+    Normal,
+    /// Richer and quicker to attack.
+    Hard,
+    /// Well ahead on income and attacks early and often.
+    Brutal,
+}
+
+impl Difficulty {
+    /// Every difficulty, easiest first.
+    pub const ALL: [Self; 4] = [Self::Easy, Self::Normal, Self::Hard, Self::Brutal];
+
+    /// Name used in config files and on the command line.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+            Self::Brutal => "brutal",
+        }
+    }
+
+    /// Difficulty by [`name`](Self::name), ignoring case.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The handicap this difficulty plays with.
+    #[must_use]
+    pub const fn handicap(self) -> Handicap {
+        match self {
+            Self::Easy => Handicap {
+                income_percent: 70,
+                reaction_delay_ticks: 3 * TICK_RATE,
+                aggression_bias_percent: -20,
+                attack_delay_percent: 150,
+                max_apm: 40,
+            },
+            Self::Normal => Handicap::NONE,
+            Self::Hard => Handicap {
+                income_percent: 120,
+                reaction_delay_ticks: 0,
+                aggression_bias_percent: 10,
+                attack_delay_percent: 85,
+                max_apm: 0,
+            },
+            Self::Brutal => Handicap {
+                income_percent: 150,
+                reaction_delay_ticks: 0,
+                aggression_bias_percent: 20,
+                attack_delay_percent: 70,
+                max_apm: 0,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How much weaker or stronger an AI player is than designed.
+///
+/// The default is no handicap. Fields left out of a config file keep their
+/// default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Handicap {
+    /// Percent of gathered income the AI keeps (100 = all of it; above 100
+    /// is a bonus).
+    pub income_percent: u32,
+    /// Ticks between the AI first seeing an enemy and reacting to it.
+    pub reaction_delay_ticks: u32,
+    /// Percentage points added to the AI's aggression, which stays within
+    /// 0-100.
+    pub aggression_bias_percent: i32,
+    /// Percent of its planned wait before the first attack and between
+    /// attacks (100 = as planned; lower attacks sooner).
+    pub attack_delay_percent: u32,
+    /// Most times per minute the AI may act (0 = every tick).
+    pub max_apm: u32,
+}
+
+impl Handicap {
+    /// No handicap: the AI as designed.
+    pub const NONE: Self = Self {
+        income_percent: 100,
+        reaction_delay_ticks: 0,
+        aggression_bias_percent: 0,
+        attack_delay_percent: 100,
+        max_apm: 0,
+    };
+
+    /// Income the AI keeps of `amount` gathered.
+    #[must_use]
+    pub fn income(&self, amount: i64) -> i64 {
+        amount * i64::from(self.income_percent) / 100
+    }
+
+    /// The AI's aggression percent given its planned `aggression_percent`.
+    #[must_use]
+    pub fn aggression(&self, aggression_percent: u32) -> u32 {
+        (i64::from(aggression_percent) + i64::from(self.aggression_bias_percent)).clamp(0, 100)
+            as u32
+    }
+
+    /// How long the AI waits before an attack planned after `ticks`.
+    #[must_use]
+    pub fn attack_delay(&self, ticks: u64) -> u64 {
+        ticks.saturating_mul(u64::from(self.attack_delay_percent)) / 100
+    }
+
+    /// Ticks between the AI's actions under its APM throttle.
+    #[must_use]
+    pub fn action_interval(&self) -> u64 {
+        if self.max_apm == 0 {
+            return 1;
+        }
+        (u64::from(TICK_RATE) * 60 / u64::from(self.max_apm)).max(1)
+    }
+
+    /// Whether the APM throttle lets the AI act at `tick`.
+    #[must_use]
+    pub fn may_act(&self, tick: u64) -> bool {
+        tick % self.action_interval() == 0
+    }
+}
+
+impl Default for Handicap {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// Tracks how long enemies have been in sight, so an AI with a reaction
+/// delay only responds once they have been there long enough.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Reaction {
+    /// Tick enemies came into sight, while they stay in sight.
+    spotted_at: Option<u64>,
+}
+
+impl Reaction {
+    /// Note whether enemies are in sight at `tick` and return whether the
+    /// AI reacts to them yet.
+    pub fn react(&mut self, in_sight: bool, tick: u64, handicap: &Handicap) -> bool {
+        if !in_sight {
+            self.spotted_at = None;
+            return false;
+        }
+        let spotted_at = *self.spotted_at.get_or_insert(tick);
+        tick >= spotted_at + u64::from(handicap.reaction_delay_ticks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_difficulties_order_from_weak_to_strong() {
+        let handicaps: Vec<Handicap> = Difficulty::ALL.iter().map(|d| d.handicap()).collect();
+        for pair in handicaps.windows(2) {
+            assert!(pair[0].income_percent < pair[1].income_percent);
+            assert!(pair[0].attack_delay_percent > pair[1].attack_delay_percent);
+        }
+        assert_eq!(Difficulty::Normal.handicap(), Handicap::default());
+        assert_eq!(Difficulty::from_name("HARD"), Some(Difficulty::Hard));
+        assert_eq!(Difficulty::from_name("impossible"), None);
+    }
+
+    #[test]
+    fn test_handicap_scales_income_attacks_and_actions() {
+        let easy = Difficulty::Easy.handicap();
+        assert_eq!(easy.income(100), 70);
+        assert_eq!(easy.attack_delay(1000), 1500);
+        assert_eq!(easy.aggression(10), 0);
+        assert_eq!(easy.aggression(90), 70);
+        assert_eq!(Difficulty::Brutal.handicap().aggression(90), 100);
+        assert_eq!(easy.action_interval(), 30);
+        assert!(easy.may_act(60));
+        assert!(!easy.may_act(61));
+        assert!(Handicap::NONE.may_act(61));
+    }
+
+    #[test]
+    fn test_reaction_waits_out_the_delay() {
+        let easy = Difficulty::Easy.handicap();
+        let mut reaction = Reaction::default();
+        assert!(!reaction.react(true, 100, &easy));
+        assert!(!reaction.react(true, 159, &easy));
+        assert!(reaction.react(true, 160, &easy));
+        // Losing sight starts the wait over
+        assert!(!reaction.react(false, 161, &easy));
+        assert!(!reaction.react(true, 162, &easy));
+        assert!(Reaction::default().react(true, 0, &Handicap::NONE));
+    }
+}
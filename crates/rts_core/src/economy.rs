@@ -1,14 +1,18 @@
 //! Economy and resource management system.
 //!
 //! Implements the Feedstock-based economy where harvesters gather
-//! resources from nodes and deposit them at depots.
+//! resources from nodes and deposit them at depots. Rarer resources
+//! ([`ResourceKind`]) come from their own nodes and pay for advanced units
+//! and buildings alongside feedstock.
 //!
 //! All calculations use integer math for deterministic simulation.
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::components::EntityId;
-use crate::math::Vec2Fixed;
+use crate::math::{Fixed, Vec2Fixed};
 
 /// Feedstock - the primary resource gathered by harvesters.
 ///
@@ -55,15 +59,172 @@ impl std::ops::SubAssign for Feedstock {
     }
 }
 
+/// A resource players gather and spend.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub enum ResourceKind {
+    /// Raw matter for fabrication; pays for almost everything.
+    #[default]
+    Feedstock,
+    /// Rare elements from contested field nodes, for tier 2.
+    Exotics,
+    /// Pre-collapse relics from a few rare deposits, for tier 3.
+    Cores,
+}
+
+impl ResourceKind {
+    /// Every resource, feedstock first.
+    pub const ALL: [Self; 3] = [Self::Feedstock, Self::Exotics, Self::Cores];
+
+    /// Rough worth of one unit of this resource in feedstock, for comparing
+    /// mixed costs.
+    #[must_use]
+    pub const fn feedstock_value(self) -> i64 {
+        match self {
+            Self::Feedstock => 1,
+            Self::Exotics => 4,
+            Self::Cores => 10,
+        }
+    }
+
+    /// Lowercase name, as used in reports.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Feedstock => "feedstock",
+            Self::Exotics => "exotics",
+            Self::Cores => "cores",
+        }
+    }
+}
+
+impl std::fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Amounts of several resources, such as a cost or a stockpile.
+///
+/// Written in data as a map, e.g. `{Exotics: 50, Cores: 1}`. Resources not
+/// listed count as zero.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ResourceAmounts(BTreeMap<ResourceKind, i32>);
+
+impl ResourceAmounts {
+    /// No resources at all.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+
+    /// Add `amount` of `kind`.
+    #[must_use]
+    pub fn with(mut self, kind: ResourceKind, amount: i32) -> Self {
+        self.add(kind, amount);
+        self
+    }
+
+    /// Amount of `kind`.
+    #[must_use]
+    pub fn get(&self, kind: ResourceKind) -> i32 {
+        self.0.get(&kind).copied().unwrap_or(0)
+    }
+
+    /// Add `amount` (which may be negative) of `kind`.
+    pub fn add(&mut self, kind: ResourceKind, amount: i32) {
+        let total = self.get(kind).saturating_add(amount);
+        if total == 0 {
+            self.0.remove(&kind);
+        } else {
+            self.0.insert(kind, total);
+        }
+    }
+
+    /// Check if every amount is zero.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Non-zero amounts, in [`ResourceKind`] order.
+    pub fn iter(&self) -> impl Iterator<Item = (ResourceKind, i32)> + '_ {
+        self.0.iter().map(|(&kind, &amount)| (kind, amount))
+    }
+
+    /// First resource these holdings have less of than `cost` asks for.
+    #[must_use]
+    pub fn shortfall(&self, cost: &Self) -> Option<ResourceKind> {
+        cost.iter()
+            .find(|&(kind, amount)| self.get(kind) < amount)
+            .map(|(kind, _)| kind)
+    }
+
+    /// Take `cost` out of these holdings if every part of it is there.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first resource that falls short; nothing is taken then.
+    pub fn spend(&mut self, cost: &Self) -> Result<(), ResourceKind> {
+        if let Some(kind) = self.shortfall(cost) {
+            return Err(kind);
+        }
+        for (kind, amount) in cost.iter() {
+            self.add(kind, -amount);
+        }
+        Ok(())
+    }
+
+    /// Put `amounts` into these holdings.
+    pub fn deposit(&mut self, amounts: &Self) {
+        for (kind, amount) in amounts.iter() {
+            self.add(kind, amount);
+        }
+    }
+
+    /// These amounts scaled to `percent`, rounding down.
+    #[must_use]
+    pub fn percent(&self, percent: i32) -> Self {
+        let mut scaled = Self::new();
+        for (kind, amount) in self.iter() {
+            scaled.add(kind, amount * percent / 100);
+        }
+        scaled
+    }
+
+    /// Total worth in feedstock (see [`ResourceKind::feedstock_value`]).
+    #[must_use]
+    pub fn feedstock_value(&self) -> i64 {
+        self.iter()
+            .map(|(kind, amount)| i64::from(amount) * kind.feedstock_value())
+            .sum()
+    }
+}
+
+impl FromIterator<(ResourceKind, i32)> for ResourceAmounts {
+    fn from_iter<I: IntoIterator<Item = (ResourceKind, i32)>>(iter: I) -> Self {
+        let mut amounts = Self::new();
+        for (kind, amount) in iter {
+            amounts.add(kind, amount);
+        }
+        amounts
+    }
+}
+
 /// A resource node that harvesters can gather from.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ResourceNode {
     /// Position in world space.
     pub position: Vec2Fixed,
-    /// Remaining feedstock in this node.
+    /// Remaining resource in this node.
     pub remaining: i32,
     /// Amount gathered per harvest action.
     pub gather_rate: i32,
+    /// Resource the node yields.
+    #[serde(default)]
+    pub kind: ResourceKind,
 }
 
 impl ResourceNode {
@@ -74,9 +235,17 @@ impl ResourceNode {
             position,
             remaining,
             gather_rate,
+            kind: ResourceKind::Feedstock,
         }
     }
 
+    /// Set the resource the node yields.
+    #[must_use]
+    pub const fn with_kind(mut self, kind: ResourceKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
     /// Check if this node is depleted.
     #[must_use]
     pub const fn is_depleted(&self) -> bool {
@@ -93,8 +262,17 @@ impl ResourceNode {
     }
 }
 
+/// Hard limit on a player's supply cap, however many buildings provide it.
+pub const MAX_SUPPLY: u32 = 200;
+
+/// Supply cost of light units (infantry).
+pub const LIGHT_UNIT_SUPPLY: u32 = 1;
+
+/// Supply cost of heavy or specialised units (rangers, harvesters, vehicles).
+pub const HEAVY_UNIT_SUPPLY: u32 = 2;
+
 /// Player economy state.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct PlayerEconomy {
     /// Current feedstock stockpile.
     pub feedstock: i32,
@@ -102,6 +280,16 @@ pub struct PlayerEconomy {
     pub storage_capacity: i32,
     /// Calculated income rate from active harvesters (per tick estimation).
     pub income_rate: i32,
+    /// Supply taken by living and queued units.
+    #[serde(default)]
+    pub supply_used: u32,
+    /// Supply provided by buildings, before the [`MAX_SUPPLY`] limit.
+    #[serde(default)]
+    pub supply_provided: u32,
+    /// Stockpiles of resources other than feedstock; these have no
+    /// storage limit.
+    #[serde(default)]
+    pub reserves: ResourceAmounts,
 }
 
 impl PlayerEconomy {
@@ -112,9 +300,84 @@ impl PlayerEconomy {
             feedstock,
             storage_capacity,
             income_rate: 0,
+            supply_used: 0,
+            supply_provided: 0,
+            reserves: ResourceAmounts::new(),
+        }
+    }
+
+    /// Set the stockpiles of resources other than feedstock.
+    #[must_use]
+    pub fn with_reserves(mut self, reserves: ResourceAmounts) -> Self {
+        self.reserves = reserves;
+        self
+    }
+
+    /// Amount held of `kind`.
+    #[must_use]
+    pub fn amount(&self, kind: ResourceKind) -> i32 {
+        match kind {
+            ResourceKind::Feedstock => self.feedstock,
+            _ => self.reserves.get(kind),
+        }
+    }
+
+    /// Set the supply provided by buildings.
+    #[must_use]
+    pub const fn with_supply_provided(mut self, supply: u32) -> Self {
+        self.supply_provided = supply;
+        self
+    }
+
+    /// Usable supply cap: what buildings provide, limited to [`MAX_SUPPLY`].
+    #[must_use]
+    pub const fn supply_cap(&self) -> u32 {
+        if self.supply_provided < MAX_SUPPLY {
+            self.supply_provided
+        } else {
+            MAX_SUPPLY
+        }
+    }
+
+    /// Supply still free under the cap.
+    #[must_use]
+    pub const fn available_supply(&self) -> u32 {
+        self.supply_cap().saturating_sub(self.supply_used)
+    }
+
+    /// Check if a unit costing `supply` fits under the cap.
+    #[must_use]
+    pub const fn has_supply(&self, supply: u32) -> bool {
+        supply <= self.available_supply()
+    }
+
+    /// Take supply for a unit if it fits under the cap.
+    ///
+    /// Returns true if the supply was reserved.
+    pub fn reserve_supply(&mut self, supply: u32) -> bool {
+        if self.has_supply(supply) {
+            self.supply_used += supply;
+            true
+        } else {
+            false
         }
     }
 
+    /// Give back supply when a unit dies or its production is cancelled.
+    pub fn release_supply(&mut self, supply: u32) {
+        self.supply_used = self.supply_used.saturating_sub(supply);
+    }
+
+    /// Add supply from a completed building.
+    pub fn add_supply_provided(&mut self, supply: u32) {
+        self.supply_provided += supply;
+    }
+
+    /// Remove supply when a building that provided it is lost.
+    pub fn remove_supply_provided(&mut self, supply: u32) {
+        self.supply_provided = self.supply_provided.saturating_sub(supply);
+    }
+
     /// Check available storage space.
     #[must_use]
     pub const fn available_storage(&self) -> i32 {
@@ -148,6 +411,205 @@ impl PlayerEconomy {
     pub const fn can_afford(&self, cost: i32) -> bool {
         self.feedstock >= cost
     }
+
+    /// First resource the player is short of for `feedstock` plus `extra`.
+    #[must_use]
+    pub fn shortfall(&self, feedstock: i32, extra: &ResourceAmounts) -> Option<ResourceKind> {
+        let feedstock = feedstock.saturating_add(extra.get(ResourceKind::Feedstock));
+        if !self.can_afford(feedstock) {
+            return Some(ResourceKind::Feedstock);
+        }
+        extra
+            .iter()
+            .filter(|(kind, _)| *kind != ResourceKind::Feedstock)
+            .find(|&(kind, amount)| self.amount(kind) < amount)
+            .map(|(kind, _)| kind)
+    }
+
+    /// Pay `feedstock` plus `extra` if the player has all of it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first resource that falls short; nothing is paid then.
+    pub fn spend_all(
+        &mut self,
+        feedstock: i32,
+        extra: &ResourceAmounts,
+    ) -> Result<(), ResourceKind> {
+        if let Some(kind) = self.shortfall(feedstock, extra) {
+            return Err(kind);
+        }
+        self.feedstock -= feedstock;
+        for (kind, amount) in extra.iter() {
+            match kind {
+                ResourceKind::Feedstock => self.feedstock -= amount,
+                _ => self.reserves.add(kind, -amount),
+            }
+        }
+        Ok(())
+    }
+
+    /// Give back `feedstock` plus `extra`. Refunded feedstock may exceed
+    /// the storage limit.
+    pub fn refund_all(&mut self, feedstock: i32, extra: &ResourceAmounts) {
+        self.feedstock += feedstock;
+        for (kind, amount) in extra.iter() {
+            match kind {
+                ResourceKind::Feedstock => self.feedstock += amount,
+                _ => self.reserves.add(kind, amount),
+            }
+        }
+    }
+
+    /// Deposit `amount` of `kind`; only feedstock is held to the storage
+    /// limit.
+    ///
+    /// Returns the actual amount deposited.
+    pub fn deposit_kind(&mut self, kind: ResourceKind, amount: i32) -> i32 {
+        match kind {
+            ResourceKind::Feedstock => self.deposit(amount),
+            _ => {
+                self.reserves.add(kind, amount);
+                amount
+            }
+        }
+    }
+}
+
+/// Income cut a faction takes while its army is above a supply threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpkeepTier {
+    /// Supply in use above which the cut applies.
+    pub above_supply: u32,
+    /// Percent of income withheld (0-100).
+    pub income_cut: u8,
+}
+
+/// A second resource an oversized army drains, such as "legitimacy" or
+/// "energy".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpkeepDrain {
+    /// Name of the drained resource.
+    pub resource: String,
+    /// Amount held at the start of a match, and at most.
+    pub capacity: u32,
+    /// Supply the army can take before the drain starts.
+    pub above_supply: u32,
+    /// Drained each interval per supply above the threshold.
+    pub per_supply: u32,
+    /// Restored each interval while the army is at or under the threshold.
+    #[serde(default)]
+    pub recovery: u32,
+    /// Ticks between drains.
+    #[serde(default = "default_upkeep_interval")]
+    pub interval_ticks: u32,
+    /// Percent of income withheld while the resource is used up.
+    #[serde(default)]
+    pub exhausted_income_cut: u8,
+}
+
+/// Default upkeep interval (one second).
+const fn default_upkeep_interval() -> u32 {
+    60
+}
+
+/// How a faction pays for a large army. The default charges nothing.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct UpkeepModel {
+    /// Income cuts by army size; past several thresholds, the largest cut
+    /// applies.
+    #[serde(default)]
+    pub tiers: Vec<UpkeepTier>,
+    /// Second resource the army drains.
+    #[serde(default)]
+    pub drain: Option<UpkeepDrain>,
+}
+
+impl UpkeepModel {
+    /// Check if the model charges no upkeep at all.
+    #[must_use]
+    pub fn is_free(&self) -> bool {
+        self.tiers.is_empty() && self.drain.is_none()
+    }
+
+    /// Percent of income withheld from a player in the `upkeep` state:
+    /// the cut for their army size plus any cut for an exhausted drain.
+    #[must_use]
+    pub fn income_cut(&self, upkeep: &Upkeep) -> u8 {
+        let tier = self
+            .tiers
+            .iter()
+            .filter(|t| upkeep.supply > t.above_supply)
+            .map(|t| t.income_cut)
+            .max()
+            .unwrap_or(0);
+        let exhausted = self
+            .drain
+            .as_ref()
+            .filter(|_| upkeep.reserve == 0)
+            .map_or(0, |d| d.exhausted_income_cut);
+        tier.saturating_add(exhausted).min(100)
+    }
+}
+
+/// A player's running upkeep under an [`UpkeepModel`]: what their army
+/// costs now and what it has cost so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Upkeep {
+    /// Supply the player's units took at the last tick.
+    pub supply: u32,
+    /// Drained resource left (0 without a drain).
+    pub reserve: u32,
+    /// Income withheld so far.
+    pub income_lost: i64,
+    /// Ticks spent with an income cut in force.
+    pub ticks_taxed: u64,
+    /// Ticks spent with the drained resource used up.
+    pub ticks_exhausted: u64,
+}
+
+impl Upkeep {
+    /// Start a match with a full reserve.
+    #[must_use]
+    pub fn new(model: &UpkeepModel) -> Self {
+        Self {
+            reserve: model.drain.as_ref().map_or(0, |d| d.capacity),
+            ..Self::default()
+        }
+    }
+
+    /// Advance one tick with `supply` in use, draining or restoring the
+    /// reserve on each interval.
+    pub fn tick(&mut self, model: &UpkeepModel, tick: u64, supply: u32) {
+        self.supply = supply;
+        if let Some(drain) = &model.drain {
+            if tick % u64::from(drain.interval_ticks.max(1)) == 0 {
+                if supply > drain.above_supply {
+                    let cost = (supply - drain.above_supply).saturating_mul(drain.per_supply);
+                    self.reserve = self.reserve.saturating_sub(cost);
+                } else {
+                    self.reserve = self
+                        .reserve
+                        .saturating_add(drain.recovery)
+                        .min(drain.capacity);
+                }
+            }
+            if self.reserve == 0 {
+                self.ticks_exhausted += 1;
+            }
+        }
+        if model.income_cut(self) > 0 {
+            self.ticks_taxed += 1;
+        }
+    }
+
+    /// Income left of `gross` after upkeep, recording what was withheld.
+    pub fn collect(&mut self, model: &UpkeepModel, gross: i64) -> i64 {
+        let cut = i64::from(model.income_cut(self));
+        let net = gross * (100 - cut) / 100;
+        self.income_lost += gross - net;
+        net
+    }
 }
 
 /// State of a harvester unit.
@@ -182,6 +644,13 @@ pub struct Harvester {
     pub gather_rate: i32,
     /// Current state of the harvester.
     pub state: HarvesterState,
+    /// Resource in the current load.
+    #[serde(default)]
+    pub load_kind: ResourceKind,
+    /// Resource to look for first when picking a node (None = nearest of
+    /// any kind).
+    #[serde(default)]
+    pub wanted: Option<ResourceKind>,
 }
 
 impl Harvester {
@@ -193,6 +662,8 @@ impl Harvester {
             current_load: 0,
             gather_rate,
             state: HarvesterState::Idle,
+            load_kind: ResourceKind::Feedstock,
+            wanted: None,
         }
     }
 
@@ -256,6 +727,8 @@ pub enum EconomyEvent {
         harvester: EntityId,
         /// The depot entity.
         depot: EntityId,
+        /// Resource deposited.
+        kind: ResourceKind,
         /// Amount deposited.
         amount: i32,
     },
@@ -266,9 +739,8 @@ pub enum EconomyEvent {
     },
 }
 
-/// Distance threshold for interaction (squared, to avoid sqrt).
-/// Units within this distance can gather/deposit.
-const INTERACTION_DISTANCE_SQ: i64 = 4; // 2 units squared
+/// Default distance within which harvesters can gather/deposit.
+pub const DEFAULT_INTERACTION_RANGE: i32 = 2;
 
 /// Process the economy system for one tick.
 ///
@@ -290,15 +762,40 @@ pub fn economy_system(
     nodes: &mut [(EntityId, &mut ResourceNode, &Vec2Fixed)],
     depots: &[(EntityId, &Vec2Fixed)],
     player_economy: &mut PlayerEconomy,
+) -> Vec<EconomyEvent> {
+    economy_system_in_range(
+        harvesters,
+        nodes,
+        depots,
+        player_economy,
+        Fixed::from_num(DEFAULT_INTERACTION_RANGE),
+    )
+}
+
+/// Process the economy system for one tick, with harvesters gathering and
+/// depositing within `range` of a node or depot.
+///
+/// Use this when units cannot reach the exact centre of a node or depot,
+/// e.g. when local avoidance keeps them apart.
+pub fn economy_system_in_range(
+    harvesters: &mut [(EntityId, &mut Harvester, &Vec2Fixed)],
+    nodes: &mut [(EntityId, &mut ResourceNode, &Vec2Fixed)],
+    depots: &[(EntityId, &Vec2Fixed)],
+    player_economy: &mut PlayerEconomy,
+    range: Fixed,
 ) -> Vec<EconomyEvent> {
     let mut events = Vec::new();
+    let range_sq = range * range;
+    let is_within_range = |a: Vec2Fixed, b: Vec2Fixed| a.distance_squared(b) <= range_sq;
 
     for (harvester_id, harvester, harvester_pos) in harvesters.iter_mut() {
         match harvester.state {
             HarvesterState::Idle => {
                 // Auto-assign to nearest non-depleted node if empty
                 if harvester.is_empty() {
-                    if let Some(node_id) = find_nearest_node(**harvester_pos, nodes) {
+                    if let Some(node_id) =
+                        find_nearest_node(**harvester_pos, nodes, harvester.wanted)
+                    {
                         harvester.state = HarvesterState::MovingToNode(node_id);
                     }
                 } else {
@@ -333,8 +830,10 @@ pub fn economy_system(
                     if node.is_depleted() {
                         events.push(EconomyEvent::NodeDepleted { node: node_id });
                         harvester.state = HarvesterState::Idle;
-                    } else if harvester.is_full() {
-                        // Full, need to return to depot
+                    } else if harvester.is_full()
+                        || (!harvester.is_empty() && harvester.load_kind != node.kind)
+                    {
+                        // Full, or carrying another resource: return to depot
                         if let Some(depot_id) = find_nearest_depot(**harvester_pos, depots) {
                             harvester.state = HarvesterState::Returning(depot_id);
                         } else {
@@ -344,6 +843,7 @@ pub fn economy_system(
                         // Gather resources
                         let to_gather = harvester.gather_rate.min(harvester.available_capacity());
                         let gathered = node.extract(to_gather);
+                        harvester.load_kind = node.kind;
                         harvester.load(gathered);
 
                         if gathered > 0 {
@@ -398,12 +898,13 @@ pub fn economy_system(
                     .find(|(_, pos)| is_within_range(**harvester_pos, **pos))
                 {
                     let load = harvester.unload();
-                    let deposited = player_economy.deposit(load);
+                    let deposited = player_economy.deposit_kind(harvester.load_kind, load);
 
                     if deposited > 0 {
                         events.push(EconomyEvent::ResourceDeposited {
                             harvester: *harvester_id,
                             depot: *depot_id,
+                            kind: harvester.load_kind,
                             amount: deposited,
                         });
                     }
@@ -412,7 +913,9 @@ pub fn economy_system(
                     // This encourages building more storage
 
                     // Go back to gathering
-                    if let Some(node_id) = find_nearest_node(**harvester_pos, nodes) {
+                    if let Some(node_id) =
+                        find_nearest_node(**harvester_pos, nodes, harvester.wanted)
+                    {
                         harvester.state = HarvesterState::MovingToNode(node_id);
                     } else {
                         harvester.state = HarvesterState::Idle;
@@ -440,19 +943,27 @@ pub fn economy_system(
     events
 }
 
-/// Find the nearest non-depleted resource node.
+/// Find the nearest non-depleted resource node, of the `wanted` kind if
+/// any is left.
 fn find_nearest_node(
     pos: Vec2Fixed,
     nodes: &[(EntityId, &mut ResourceNode, &Vec2Fixed)],
+    wanted: Option<ResourceKind>,
 ) -> Option<EntityId> {
-    nodes
-        .iter()
-        .filter(|(_, node, _)| !node.is_depleted())
-        .min_by_key(|(_, _, node_pos)| {
-            let dist = pos.distance_squared(**node_pos);
-            dist.to_bits()
-        })
-        .map(|(id, _, _)| *id)
+    let nearest = |kind: Option<ResourceKind>| {
+        nodes
+            .iter()
+            .filter(|(_, node, _)| !node.is_depleted())
+            .filter(|(_, node, _)| kind.map_or(true, |k| node.kind == k))
+            .min_by_key(|(_, _, node_pos)| {
+                let dist = pos.distance_squared(**node_pos);
+                dist.to_bits()
+            })
+            .map(|(id, _, _)| *id)
+    };
+    wanted
+        .and_then(|k| nearest(Some(k)))
+        .or_else(|| nearest(None))
 }
 
 /// Find the nearest depot.
@@ -466,12 +977,6 @@ fn find_nearest_depot(pos: Vec2Fixed, depots: &[(EntityId, &Vec2Fixed)]) -> Opti
         .map(|(id, _)| *id)
 }
 
-/// Check if two positions are within interaction range.
-fn is_within_range(a: Vec2Fixed, b: Vec2Fixed) -> bool {
-    let dist_sq = a.distance_squared(b);
-    dist_sq.to_bits() <= INTERACTION_DISTANCE_SQ * (1i64 << 32) // Account for Fixed scaling
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -514,6 +1019,69 @@ mod tests {
         assert!(node.is_depleted());
     }
 
+    fn upkeep_model() -> UpkeepModel {
+        UpkeepModel {
+            tiers: vec![
+                UpkeepTier {
+                    above_supply: 20,
+                    income_cut: 10,
+                },
+                UpkeepTier {
+                    above_supply: 40,
+                    income_cut: 30,
+                },
+            ],
+            drain: Some(UpkeepDrain {
+                resource: "legitimacy".to_string(),
+                capacity: 100,
+                above_supply: 30,
+                per_supply: 5,
+                recovery: 20,
+                interval_ticks: 60,
+                exhausted_income_cut: 50,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_upkeep_tiers_cut_income_by_army_size() {
+        let model = upkeep_model();
+        let mut upkeep = Upkeep::new(&model);
+        assert_eq!(upkeep.reserve, 100);
+
+        upkeep.tick(&model, 1, 20);
+        assert_eq!(upkeep.collect(&model, 100), 100);
+        upkeep.tick(&model, 2, 25);
+        assert_eq!(upkeep.collect(&model, 100), 90);
+        // Past both thresholds the larger cut applies
+        upkeep.tick(&model, 3, 45);
+        assert_eq!(upkeep.collect(&model, 100), 70);
+        assert_eq!(upkeep.income_lost, 40);
+        assert_eq!(upkeep.ticks_taxed, 2);
+        assert!(UpkeepModel::default().is_free());
+    }
+
+    #[test]
+    fn test_upkeep_drain_exhausts_and_recovers() {
+        let model = upkeep_model();
+        let mut upkeep = Upkeep::new(&model);
+
+        // 10 supply over the drain threshold costs 50 a second
+        upkeep.tick(&model, 60, 40);
+        assert_eq!(upkeep.reserve, 50);
+        upkeep.tick(&model, 61, 40);
+        assert_eq!(upkeep.reserve, 50, "drains only on the interval");
+        upkeep.tick(&model, 120, 40);
+        assert_eq!(upkeep.reserve, 0);
+        assert_eq!(upkeep.ticks_exhausted, 1);
+        // Tier cut and exhaustion cut stack
+        assert_eq!(model.income_cut(&upkeep), 60);
+
+        upkeep.tick(&model, 180, 10);
+        assert_eq!(upkeep.reserve, 20);
+        assert_eq!(upkeep.collect(&model, 100), 100);
+    }
+
     #[test]
     fn test_player_economy_deposit() {
         let mut economy = PlayerEconomy::new(0, 100);
@@ -541,6 +1109,86 @@ mod tests {
         assert_eq!(economy.feedstock, 50); // Unchanged
     }
 
+    #[test]
+    fn test_resource_amounts_spend_all_or_nothing() {
+        let mut held = ResourceAmounts::new()
+            .with(ResourceKind::Exotics, 50)
+            .with(ResourceKind::Cores, 1);
+        let cost = ResourceAmounts::new()
+            .with(ResourceKind::Exotics, 20)
+            .with(ResourceKind::Cores, 2);
+
+        assert_eq!(held.spend(&cost), Err(ResourceKind::Cores));
+        assert_eq!(held.get(ResourceKind::Exotics), 50);
+
+        held.add(ResourceKind::Cores, 1);
+        assert!(held.spend(&cost).is_ok());
+        assert_eq!(held.get(ResourceKind::Exotics), 30);
+        assert_eq!(held.get(ResourceKind::Cores), 0);
+        assert_eq!(cost.feedstock_value(), 20 * 4 + 2 * 10);
+    }
+
+    #[test]
+    fn test_harvesters_bring_back_the_kind_they_gathered() {
+        let mut harvester = Harvester::new(20, 20);
+        harvester.wanted = Some(ResourceKind::Exotics);
+        let mut economy = PlayerEconomy::new(0, 1000);
+        let mut feedstock = ResourceNode::new(pos(0, 0), 1000, 20);
+        let mut exotics = ResourceNode::new(pos(5, 0), 1000, 20).with_kind(ResourceKind::Exotics);
+        let (home, feedstock_pos, exotics_pos) = (pos(0, 0), pos(0, 0), pos(5, 0));
+
+        // Skips the nearer feedstock node for the exotics it wants
+        let mut nodes = vec![
+            (10u64, &mut feedstock, &feedstock_pos),
+            (11u64, &mut exotics, &exotics_pos),
+        ];
+        let depots = vec![(1u64, &home)];
+        let mut harvesters = vec![(0u64, &mut harvester, &home)];
+        economy_system(&mut harvesters, &mut nodes, &depots, &mut economy);
+        assert_eq!(harvesters[0].1.state, HarvesterState::MovingToNode(11));
+
+        let mut harvesters = vec![(0u64, &mut harvester, &exotics_pos)];
+        economy_system(&mut harvesters, &mut nodes, &depots, &mut economy);
+        economy_system(&mut harvesters, &mut nodes, &depots, &mut economy);
+        assert_eq!(harvesters[0].1.load_kind, ResourceKind::Exotics);
+
+        harvester.state = HarvesterState::Depositing;
+        let mut harvesters = vec![(0u64, &mut harvester, &home)];
+        let events = economy_system(&mut harvesters, &mut nodes, &depots, &mut economy);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            EconomyEvent::ResourceDeposited {
+                kind: ResourceKind::Exotics,
+                amount: 20,
+                ..
+            }
+        )));
+        assert_eq!(economy.feedstock, 0);
+        assert_eq!(economy.amount(ResourceKind::Exotics), 20);
+    }
+
+    #[test]
+    fn test_player_economy_supply() {
+        let mut economy = PlayerEconomy::new(100, 200).with_supply_provided(3);
+        assert_eq!(economy.supply_cap(), 3);
+
+        assert!(economy.reserve_supply(HEAVY_UNIT_SUPPLY));
+        assert!(!economy.reserve_supply(HEAVY_UNIT_SUPPLY));
+        assert!(economy.reserve_supply(LIGHT_UNIT_SUPPLY));
+        assert_eq!(economy.available_supply(), 0);
+
+        economy.release_supply(HEAVY_UNIT_SUPPLY);
+        assert_eq!(economy.supply_used, 1);
+
+        // Buildings lost below current usage leave no free supply
+        economy.remove_supply_provided(3);
+        assert_eq!(economy.available_supply(), 0);
+        assert!(!economy.has_supply(1));
+
+        economy.add_supply_provided(MAX_SUPPLY + 50);
+        assert_eq!(economy.supply_cap(), MAX_SUPPLY);
+    }
+
     #[test]
     fn test_harvester_load_unload() {
         let mut harvester = Harvester::new(100, 10);
@@ -616,7 +1264,8 @@ mod tests {
             EconomyEvent::ResourceDeposited {
                 harvester: 0,
                 depot: 1,
-                amount: 50
+                amount: 50,
+                ..
             }
         )));
 
@@ -624,6 +1273,41 @@ mod tests {
         assert_eq!(economy.feedstock, 50);
     }
 
+    #[test]
+    fn test_economy_system_in_range() {
+        let mut harvester = Harvester::new(100, 10);
+        harvester.current_load = 50;
+        harvester.state = HarvesterState::Returning(1);
+
+        let mut economy = PlayerEconomy::new(0, 1000);
+        let harvester_pos = pos(10, 0);
+        let depot_pos = pos(0, 0);
+        let mut nodes: Vec<(EntityId, &mut ResourceNode, &Vec2Fixed)> = vec![];
+        let depots = vec![(1u64, &depot_pos)];
+
+        // Too far for the default range
+        let mut harvesters = vec![(0u64, &mut harvester, &harvester_pos)];
+        economy_system(&mut harvesters, &mut nodes, &depots, &mut economy);
+        assert_eq!(harvesters[0].1.state, HarvesterState::Returning(1));
+
+        economy_system_in_range(
+            &mut harvesters,
+            &mut nodes,
+            &depots,
+            &mut economy,
+            Fixed::from_num(12),
+        );
+        assert_eq!(harvesters[0].1.state, HarvesterState::Depositing);
+        economy_system_in_range(
+            &mut harvesters,
+            &mut nodes,
+            &depots,
+            &mut economy,
+            Fixed::from_num(12),
+        );
+        assert_eq!(economy.feedstock, 50);
+    }
+
     #[test]
     fn test_economy_system_node_depleted() {
         let mut harvester = Harvester::new(100, 10);
@@ -673,7 +1357,8 @@ mod tests {
             EconomyEvent::ResourceDeposited {
                 harvester: 0,
                 depot: 1,
-                amount: 20
+                amount: 20,
+                ..
             }
         )));
 
@@ -2,6 +2,8 @@
 
 use thiserror::Error;
 
+use crate::research::ResearchError;
+
 /// Result type alias using [`GameError`].
 pub type Result<T> = std::result::Result<T, GameError>;
 
@@ -37,6 +39,14 @@ pub enum GameError {
     #[error("Tech requirement not met: {0}")]
     TechRequirementNotMet(String),
 
+    /// Research could not be queued or cancelled.
+    #[error("Research failed: {0}")]
+    Research(#[from] ResearchError),
+
+    /// A faction mechanic forbids the action.
+    #[error("Forbidden by faction rules: {0}")]
+    FactionRule(String),
+
     /// Insufficient resources.
     #[error("Insufficient resources: need {required} {resource}, have {available}")]
     InsufficientResources {
@@ -62,4 +72,8 @@ pub enum GameError {
         /// Remote simulation hash.
         remote_hash: u64,
     },
+
+    /// Peer, replay or save was made by an incompatible engine or data set.
+    #[error("Incompatible game version: {0} differ")]
+    IncompatibleFingerprint(String),
 }
@@ -1,9 +1,21 @@
-//! Faction definitions and identifiers.
+//! Faction definitions, identifiers and faction-unique mechanics.
+//!
+//! Beyond their data stats, factions play differently through
+//! [`FactionMechanic`]s: deterministic rules the simulation consults when
+//! entities spawn, die and are placed. Faction data lists each faction's
+//! rules as [`MechanicData`], and the simulation keeps them per faction in
+//! a [`FactionMechanics`] registry. A new rule is a new [`MechanicData`]
+//! variant whose settings implement [`FactionMechanic`].
+
+use std::collections::{BTreeMap, BTreeSet};
 
 use serde::{Deserialize, Serialize};
 
+use crate::math::Fixed;
+use crate::simulation::Entity;
+
 /// Unique identifier for factions.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum FactionId {
     /// The Continuity Authority - bureaucratic stability through governance.
     Continuity,
@@ -18,6 +30,36 @@ pub enum FactionId {
 }
 
 impl FactionId {
+    /// Every faction, in declaration order.
+    pub const ALL: [Self; 5] = [
+        Self::Continuity,
+        Self::Collegium,
+        Self::Tinkers,
+        Self::BioSovereigns,
+        Self::Zephyr,
+    ];
+
+    /// Parse a faction from its short name, ignoring case, spaces, dashes
+    /// and underscores (`"continuity"`, `"Bio-Sovereigns"`,
+    /// `"bio_sovereigns"`). `"sculptors"`, the Bio-Sovereigns data file
+    /// name, is accepted too.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        let key: String = name
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '_'))
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        match key.as_str() {
+            "continuity" => Some(Self::Continuity),
+            "collegium" => Some(Self::Collegium),
+            "tinkers" => Some(Self::Tinkers),
+            "biosovereigns" | "sculptors" => Some(Self::BioSovereigns),
+            "zephyr" => Some(Self::Zephyr),
+            _ => None,
+        }
+    }
+
     /// Get the display name for this faction.
     #[must_use]
     pub const fn display_name(&self) -> &'static str {
@@ -41,4 +83,243 @@ impl FactionId {
             Self::Zephyr => "Zephyr",
         }
     }
+
+    /// Health regeneration rate as a percentage of unit data (100 = as listed).
+    ///
+    /// Bio-Sovereign bodies are engineered to mend, so they recover faster.
+    #[must_use]
+    pub const fn regen_modifier_pct(&self) -> u32 {
+        match self {
+            Self::BioSovereigns => 150,
+            Self::Continuity | Self::Collegium | Self::Tinkers | Self::Zephyr => 100,
+        }
+    }
+}
+
+/// A faction-unique rule the simulation applies deterministically.
+///
+/// Every hook has a default that leaves the simulation unchanged, so a
+/// mechanic only implements the hooks it needs.
+pub trait FactionMechanic {
+    /// Adjust a newly spawned entity of the faction.
+    fn on_spawn(&self, _entity: &mut Entity) {}
+
+    /// Whether a destroyed entity of the faction comes back, and how.
+    fn rebuild(&self, _dead: &Entity) -> Option<RebuildPlan> {
+        None
+    }
+
+    /// Whether the faction may place a building of `kind`, given every
+    /// kind it has fielded so far.
+    fn permits_placement(&self, _kind: &str, _fielded: &BTreeSet<String>) -> bool {
+        true
+    }
+}
+
+/// When and how a destroyed structure returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RebuildPlan {
+    /// Ticks after destruction until the structure is back.
+    pub delay: u32,
+    /// Health it returns with, as a percentage of its maximum.
+    pub health_percent: u32,
+}
+
+/// Faction rule as written in faction data.
+///
+/// # Example RON
+///
+/// ```ron
+/// mechanics: [
+///     DeployOnce((building: "administration_center")),
+///     Mobility((tags: ["air"], percent: 15)),
+/// ]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MechanicData {
+    /// A building the faction can only ever deploy once.
+    DeployOnce(DeployOnce),
+    /// Destroyed structures rebuild themselves.
+    Rebuild(Rebuild),
+    /// Units move faster.
+    Mobility(Mobility),
+}
+
+impl MechanicData {
+    /// The rule these settings describe.
+    #[must_use]
+    pub fn mechanic(&self) -> &dyn FactionMechanic {
+        match self {
+            Self::DeployOnce(m) => m,
+            Self::Rebuild(m) => m,
+            Self::Mobility(m) => m,
+        }
+    }
+}
+
+/// A building that cannot be placed again once the faction has had one,
+/// such as the Continuity's headquarters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeployOnce {
+    /// Building data ID.
+    pub building: String,
+}
+
+impl FactionMechanic for DeployOnce {
+    fn permits_placement(&self, kind: &str, fielded: &BTreeSet<String>) -> bool {
+        kind != self.building || !fielded.contains(kind)
+    }
+}
+
+/// Destroyed structures return after a delay, as the Collegium's do.
+///
+/// Only structures with a [`TechProfile`](crate::research::TechProfile)
+/// carrying one of `tags` are rebuilt; with no tags, every profiled
+/// structure is.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rebuild {
+    /// Tags of the structures rebuilt.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Ticks until a destroyed structure returns.
+    pub delay: u32,
+    /// Health it returns with, as a percentage of its maximum.
+    pub health_percent: u32,
+}
+
+impl FactionMechanic for Rebuild {
+    fn rebuild(&self, dead: &Entity) -> Option<RebuildPlan> {
+        let profile = dead.tech_profile.as_ref()?;
+        let is_structure = dead.health.is_some() && dead.movement.is_none();
+        let tagged = self.tags.is_empty() || self.tags.iter().any(|t| profile.tags.contains(t));
+        (is_structure && tagged).then_some(RebuildPlan {
+            delay: self.delay,
+            health_percent: self.health_percent,
+        })
+    }
+}
+
+/// Units move faster, as the Zephyr Guild's do.
+///
+/// Applies to units whose [`TechProfile`](crate::research::TechProfile)
+/// carries one of `tags`, or to every unit with no tags.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mobility {
+    /// Tags of the units sped up.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Speed bonus in percent.
+    pub percent: i32,
+}
+
+impl FactionMechanic for Mobility {
+    fn on_spawn(&self, entity: &mut Entity) {
+        let tagged = self.tags.is_empty()
+            || entity
+                .tech_profile
+                .as_ref()
+                .is_some_and(|p| self.tags.iter().any(|t| p.tags.contains(t)));
+        if let (true, Some(movement)) = (tagged, entity.movement.as_mut()) {
+            movement.speed =
+                movement.speed * Fixed::from_num(100 + self.percent) / Fixed::from_num(100);
+        }
+    }
+}
+
+/// Each faction's mechanics.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FactionMechanics {
+    by_faction: BTreeMap<FactionId, Vec<MechanicData>>,
+}
+
+impl FactionMechanics {
+    /// Replace a faction's mechanics.
+    pub fn set(&mut self, faction: FactionId, mechanics: Vec<MechanicData>) {
+        if mechanics.is_empty() {
+            self.by_faction.remove(&faction);
+        } else {
+            self.by_faction.insert(faction, mechanics);
+        }
+    }
+
+    /// A faction's mechanics as written in its data.
+    #[must_use]
+    pub fn get(&self, faction: FactionId) -> &[MechanicData] {
+        self.by_faction.get(&faction).map_or(&[], Vec::as_slice)
+    }
+
+    /// A faction's mechanics, in data order.
+    pub fn for_faction(&self, faction: FactionId) -> impl Iterator<Item = &dyn FactionMechanic> {
+        self.get(faction).iter().map(MechanicData::mechanic)
+    }
+
+    /// Whether no faction has any mechanics.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.by_faction.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{Movement, MovementDomain};
+    use crate::research::TechProfile;
+
+    #[test]
+    fn test_mechanics_follow_their_settings() {
+        let mut mechanics = FactionMechanics::default();
+        mechanics.set(
+            FactionId::Zephyr,
+            vec![
+                MechanicData::DeployOnce(DeployOnce {
+                    building: "sky_platform".to_string(),
+                }),
+                MechanicData::Mobility(Mobility {
+                    tags: vec!["air".to_string()],
+                    percent: 50,
+                }),
+            ],
+        );
+        assert_eq!(mechanics.for_faction(FactionId::Continuity).count(), 0);
+
+        let mut fielded = BTreeSet::new();
+        let permits = |fielded: &BTreeSet<String>| {
+            mechanics
+                .for_faction(FactionId::Zephyr)
+                .all(|m| m.permits_placement("sky_platform", fielded))
+        };
+        assert!(permits(&fielded));
+        fielded.insert("sky_platform".to_string());
+        assert!(!permits(&fielded));
+
+        let mut unit = Entity::new(1);
+        unit.movement = Some(Movement {
+            speed: Fixed::from_num(2),
+            target: None,
+            domain: MovementDomain::Air,
+        });
+        unit.tech_profile = Some(TechProfile::new("cloudrunner", vec!["air".to_string()]));
+        let mut walker = unit.clone();
+        walker.tech_profile = Some(TechProfile::new("boarding_crew", Vec::new()));
+        for m in mechanics.for_faction(FactionId::Zephyr) {
+            m.on_spawn(&mut unit);
+            m.on_spawn(&mut walker);
+        }
+        assert_eq!(unit.movement.unwrap().speed, Fixed::from_num(3));
+        assert_eq!(walker.movement.unwrap().speed, Fixed::from_num(2));
+    }
+
+    #[test]
+    fn test_from_name_round_trips_short_names() {
+        for faction in FactionId::ALL {
+            assert_eq!(FactionId::from_name(faction.short_name()), Some(faction));
+        }
+        assert_eq!(
+            FactionId::from_name("bio_sovereigns"),
+            Some(FactionId::BioSovereigns)
+        );
+        assert_eq!(FactionId::from_name("ZEPHYR"), Some(FactionId::Zephyr));
+        assert_eq!(FactionId::from_name("pirates"), None);
+    }
 }
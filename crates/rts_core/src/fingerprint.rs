@@ -0,0 +1,290 @@
+//! Engine/data fingerprints for cross-version compatibility checks.
+//!
+//! Lockstep play and replays only work when every participant runs the same
+//! simulation rules on the same data. A [`Fingerprint`] condenses the three
+//! things that can silently differ between builds:
+//!
+//! - the engine, as [`SIM_VERSION`], bumped whenever simulation behaviour
+//!   changes in a way that alters outcomes;
+//! - the faction data in play (units, buildings, techs);
+//! - the map or scenario being played.
+//!
+//! Hosts compute one at session start, exchange it during the network
+//! handshake, and embed it in replays and saves. [`Fingerprint::check`]
+//! refuses a mismatch with an error naming each component that differs.
+//!
+//! Hashes are FNV-1a over bincode bytes, so they are stable across
+//! processes, platforms and Rust versions (unlike `DefaultHasher`).
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::FactionData;
+use crate::error::{GameError, Result};
+
+/// Simulation rules version.
+///
+/// Bump this whenever a change to the simulation makes the same commands
+/// produce a different game, so old replays and peers are rejected instead
+/// of desyncing.
+pub const SIM_VERSION: u32 = 2;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A part of a [`Fingerprint`] that can differ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FingerprintComponent {
+    /// Simulation rules ([`SIM_VERSION`]).
+    Engine,
+    /// Faction data.
+    FactionData,
+    /// Map or scenario.
+    Map,
+}
+
+impl fmt::Display for FingerprintComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Engine => "engine",
+            Self::FactionData => "faction data",
+            Self::Map => "map",
+        })
+    }
+}
+
+/// Identity of the engine, data and map a game runs on.
+///
+/// The data and map components are optional: a host that has no faction
+/// data loaded (a relay server, say) leaves them unset, and unset
+/// components are not compared. The engine component is always present.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Fingerprint {
+    /// Simulation rules version.
+    pub sim_version: u32,
+    /// Hash of the faction data in play.
+    pub data: Option<u64>,
+    /// Hash of the map or scenario.
+    pub map: Option<u64>,
+}
+
+impl Default for Fingerprint {
+    fn default() -> Self {
+        Self::engine()
+    }
+}
+
+impl Fingerprint {
+    /// Fingerprint of this build's engine only.
+    #[must_use]
+    pub const fn engine() -> Self {
+        Self {
+            sim_version: SIM_VERSION,
+            data: None,
+            map: None,
+        }
+    }
+
+    /// Builder method to fingerprint the faction data in play.
+    ///
+    /// Order does not matter: each faction is hashed on its own and the
+    /// hashes are combined sorted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a faction cannot be serialized.
+    pub fn with_factions<'a>(
+        mut self,
+        factions: impl IntoIterator<Item = &'a FactionData>,
+    ) -> Result<Self> {
+        let mut hashes = factions
+            .into_iter()
+            .map(hash_serialized)
+            .collect::<Result<Vec<_>>>()?;
+        hashes.sort_unstable();
+        let bytes: Vec<u8> = hashes.iter().flat_map(|h| h.to_le_bytes()).collect();
+        self.data = Some(fnv1a(&bytes));
+        Ok(self)
+    }
+
+    /// Builder method to fingerprint the map (a map name, generator config
+    /// or whole scenario).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the map cannot be serialized.
+    pub fn with_map<M: Serialize + ?Sized>(mut self, map: &M) -> Result<Self> {
+        self.map = Some(hash_serialized(map)?);
+        Ok(self)
+    }
+
+    /// Builder method to fingerprint a map by name, for hosts that pick
+    /// maps from a named pool.
+    #[must_use]
+    pub fn with_map_name(mut self, name: &str) -> Self {
+        self.map = Some(fnv1a(name.as_bytes()));
+        self
+    }
+
+    /// Components that differ from `other`.
+    ///
+    /// Data and map are only compared when both sides set them.
+    #[must_use]
+    pub fn differences(&self, other: &Self) -> Vec<FingerprintComponent> {
+        let differs =
+            |a: Option<u64>, b: Option<u64>| matches!((a, b), (Some(a), Some(b)) if a != b);
+        let mut differing = Vec::new();
+        if self.sim_version != other.sim_version {
+            differing.push(FingerprintComponent::Engine);
+        }
+        if differs(self.data, other.data) {
+            differing.push(FingerprintComponent::FactionData);
+        }
+        if differs(self.map, other.map) {
+            differing.push(FingerprintComponent::Map);
+        }
+        differing
+    }
+
+    /// Whether the two fingerprints agree on every component both set.
+    #[must_use]
+    pub fn is_compatible(&self, other: &Self) -> bool {
+        self.differences(other).is_empty()
+    }
+
+    /// Check that `other` (a peer, replay or save) matches this host.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GameError::IncompatibleFingerprint`] listing every
+    /// differing component with both values.
+    pub fn check(&self, other: &Self) -> Result<()> {
+        let differing = self.differences(other);
+        if differing.is_empty() {
+            return Ok(());
+        }
+        let details = differing
+            .iter()
+            .map(|component| {
+                let (local, remote) = match component {
+                    FingerprintComponent::Engine => (
+                        format!("v{}", self.sim_version),
+                        format!("v{}", other.sim_version),
+                    ),
+                    FingerprintComponent::FactionData => {
+                        (show_hash(self.data), show_hash(other.data))
+                    }
+                    FingerprintComponent::Map => (show_hash(self.map), show_hash(other.map)),
+                };
+                format!("{component} (local {local}, remote {remote})")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        Err(GameError::IncompatibleFingerprint(details))
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "engine v{} / data {} / map {}",
+            self.sim_version,
+            show_hash(self.data),
+            show_hash(self.map)
+        )
+    }
+}
+
+fn show_hash(hash: Option<u64>) -> String {
+    hash.map_or_else(|| "unset".to_string(), |h| format!("{h:016x}"))
+}
+
+/// Stable hash of a value's bincode encoding.
+fn hash_serialized<T: Serialize + ?Sized>(value: &T) -> Result<u64> {
+    let bytes = bincode::serialize(value).map_err(|e| {
+        GameError::InvalidState(format!("Failed to serialize for fingerprint: {}", e))
+    })?;
+    Ok(fnv1a(&bytes))
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::factions::FactionId;
+
+    fn faction(id: FactionId, name: &str) -> FactionData {
+        FactionData {
+            id,
+            display_name: name.to_string(),
+            description: String::new(),
+            units: Vec::new(),
+            buildings: Vec::new(),
+            technologies: Vec::new(),
+            primary_color: [0; 3],
+            secondary_color: [0; 3],
+            starting_units: Vec::new(),
+            starting_buildings: Vec::new(),
+            starting_presets: Vec::new(),
+            starting_feedstock: 0,
+            mechanics: vec![],
+            upkeep: Default::default(),
+            market: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable_and_order_independent() {
+        let a = faction(FactionId::Continuity, "Continuity");
+        let b = faction(FactionId::Collegium, "Collegium");
+        let forward = Fingerprint::engine()
+            .with_factions([&a, &b])
+            .unwrap()
+            .with_map("crossroads")
+            .unwrap();
+        let backward = Fingerprint::engine()
+            .with_factions([&b, &a])
+            .unwrap()
+            .with_map("crossroads")
+            .unwrap();
+        assert_eq!(forward, backward);
+        assert!(forward.check(&backward).is_ok());
+        // Pinned so accidental changes to the hashing show up here
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_mismatch_names_each_differing_component() {
+        let local = Fingerprint::engine()
+            .with_factions([&faction(FactionId::Continuity, "Continuity")])
+            .unwrap()
+            .with_map_name("crossroads");
+        let remote = Fingerprint {
+            sim_version: SIM_VERSION + 1,
+            ..local
+        }
+        .with_map_name("highlands");
+
+        assert_eq!(
+            local.differences(&remote),
+            vec![FingerprintComponent::Engine, FingerprintComponent::Map]
+        );
+        let message = local.check(&remote).unwrap_err().to_string();
+        assert!(
+            message.contains("engine (local v2, remote v3)"),
+            "{message}"
+        );
+        assert!(message.contains("map"), "{message}");
+        assert!(!message.contains("faction data"), "{message}");
+
+        // Unset components are not compared
+        assert!(Fingerprint::engine().check(&local).is_ok());
+    }
+}
@@ -0,0 +1,366 @@
+//! Fog of war.
+//!
+//! Each faction has a [`FogGrid`] over the same cells as the navigation
+//! grid. A cell starts [`CellVisibility::Unexplored`], is
+//! [`CellVisibility::Visible`] while any of the faction's units can see its
+//! centre, and drops back to [`CellVisibility::Explored`] once they cannot.
+//! The simulation recomputes what is visible every tick from each unit's
+//! sight range (see [`sight_range`]), and enemy queries such as
+//! [`Simulation::get_visible_enemies_for`](crate::simulation::Simulation::get_visible_enemies_for)
+//! only report enemies standing in visible cells.
+//!
+//! Ground units cannot see up cliffs: cells on a higher elevation level
+//! than the viewer stay hidden from it, while aircraft see every level.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::factions::FactionId;
+use crate::math::{fixed_serde, Fixed, Vec2Fixed};
+use crate::pathfinding::NavGrid;
+use crate::player_facade::DEFAULT_VISION_MULTIPLIER;
+use crate::simulation::EntityRef;
+
+/// Sight range of units with neither a vision range nor a weapon.
+pub const DEFAULT_SIGHT_RANGE: i32 = 100;
+
+/// How much a faction knows about one cell.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CellVisibility {
+    /// Never seen.
+    #[default]
+    Unexplored,
+    /// Seen before but not currently in sight.
+    Explored,
+    /// In sight of at least one unit.
+    Visible,
+}
+
+/// Sight range of an entity: its vision range if set, otherwise twice its
+/// attack range, otherwise [`DEFAULT_SIGHT_RANGE`].
+#[must_use]
+pub fn sight_range(entity: EntityRef<'_>) -> Fixed {
+    entity
+        .vision_range
+        .or_else(|| {
+            entity
+                .combat_stats
+                .map(|s| s.range * Fixed::from_num(DEFAULT_VISION_MULTIPLIER))
+        })
+        .unwrap_or(Fixed::from_num(DEFAULT_SIGHT_RANGE))
+}
+
+/// One faction's view of the map.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FogGrid {
+    width: u32,
+    height: u32,
+    /// Cell states in row-major order.
+    cells: Vec<CellVisibility>,
+}
+
+impl FogGrid {
+    /// Create a fully unexplored grid.
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![CellVisibility::Unexplored; (width as usize) * (height as usize)],
+        }
+    }
+
+    /// Width in cells.
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in cells.
+    #[must_use]
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// State of a cell, or None if out of bounds.
+    #[must_use]
+    pub fn get(&self, x: u32, y: u32) -> Option<CellVisibility> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.cells
+            .get((y as usize) * (self.width as usize) + (x as usize))
+            .copied()
+    }
+
+    /// Every cell state in row-major order, e.g. for building a texture.
+    #[must_use]
+    pub fn cells(&self) -> &[CellVisibility] {
+        &self.cells
+    }
+
+    /// Turn every visible cell into an explored one.
+    fn fade(&mut self) {
+        for cell in &mut self.cells {
+            if *cell == CellVisibility::Visible {
+                *cell = CellVisibility::Explored;
+            }
+        }
+    }
+}
+
+/// Fog of war for every faction, on a grid of square cells.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FogOfWar {
+    width: u32,
+    height: u32,
+    #[serde(with = "fixed_serde")]
+    cell_size: Fixed,
+    /// Grids of factions that have had a unit on the map.
+    grids: BTreeMap<FactionId, FogGrid>,
+}
+
+impl FogOfWar {
+    /// Create fog over `width` by `height` cells of `cell_size` world units.
+    #[must_use]
+    pub fn new(width: u32, height: u32, cell_size: Fixed) -> Self {
+        Self {
+            width,
+            height,
+            cell_size,
+            grids: BTreeMap::new(),
+        }
+    }
+
+    /// Width in cells.
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in cells.
+    #[must_use]
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Size of each cell in world units.
+    #[must_use]
+    pub const fn cell_size(&self) -> Fixed {
+        self.cell_size
+    }
+
+    /// A faction's grid, or None if it has never had a unit on the map.
+    #[must_use]
+    pub fn grid(&self, faction: FactionId) -> Option<&FogGrid> {
+        self.grids.get(&faction)
+    }
+
+    /// Cell containing a world position, or None if outside the fog.
+    #[must_use]
+    pub fn cell_at(&self, pos: Vec2Fixed) -> Option<(u32, u32)> {
+        if pos.x < Fixed::ZERO || pos.y < Fixed::ZERO || self.cell_size <= Fixed::ZERO {
+            return None;
+        }
+        let x = (pos.x / self.cell_size).to_num::<i64>();
+        let y = (pos.y / self.cell_size).to_num::<i64>();
+        (x < i64::from(self.width) && y < i64::from(self.height)).then_some((x as u32, y as u32))
+    }
+
+    /// What a faction knows about the cell at a world position, or None if
+    /// the position is outside the fog.
+    #[must_use]
+    pub fn visibility(&self, faction: FactionId, pos: Vec2Fixed) -> Option<CellVisibility> {
+        let (x, y) = self.cell_at(pos)?;
+        Some(
+            self.grids
+                .get(&faction)
+                .and_then(|grid| grid.get(x, y))
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Recompute what every faction can see from its units' positions,
+    /// sight ranges and elevation levels. A viewer with a level only sees
+    /// cells of `terrain` at or below it; one without sees every level.
+    /// Cells no longer in sight stay explored.
+    pub fn update(
+        &mut self,
+        viewers: &[(FactionId, Vec2Fixed, Fixed, Option<u8>)],
+        terrain: &NavGrid,
+    ) {
+        for grid in self.grids.values_mut() {
+            grid.fade();
+        }
+        for &(faction, pos, range, level) in viewers {
+            self.reveal_where(faction, pos, range, |centre| {
+                level.map_or(true, |level| terrain.elevation_at(centre) <= level)
+            });
+        }
+    }
+
+    /// Mark the cells whose centres lie within `range` of `pos`, and the
+    /// cell containing `pos`, visible to a faction.
+    pub fn reveal(&mut self, faction: FactionId, pos: Vec2Fixed, range: Fixed) {
+        self.reveal_where(faction, pos, range, |_| true);
+    }
+
+    /// [`reveal`](Self::reveal) only the cells whose centres pass `visible`.
+    fn reveal_where(
+        &mut self,
+        faction: FactionId,
+        pos: Vec2Fixed,
+        range: Fixed,
+        visible: impl Fn(Vec2Fixed) -> bool,
+    ) {
+        if self.width == 0 || self.height == 0 || self.cell_size <= Fixed::ZERO {
+            return;
+        }
+        let (width, height, cell_size) = (self.width, self.height, self.cell_size);
+        let own_cell = self.cell_at(pos);
+        let grid = self
+            .grids
+            .entry(faction)
+            .or_insert_with(|| FogGrid::new(width, height));
+
+        let to_cell = |coord: Fixed, limit: u32| -> u32 {
+            if coord <= Fixed::ZERO {
+                0
+            } else {
+                (coord / cell_size)
+                    .to_num::<u64>()
+                    .min(u64::from(limit - 1)) as u32
+            }
+        };
+        let (min_x, max_x) = (to_cell(pos.x - range, width), to_cell(pos.x + range, width));
+        let (min_y, max_y) = (
+            to_cell(pos.y - range, height),
+            to_cell(pos.y + range, height),
+        );
+        let range_sq = range * range;
+        let half = cell_size / Fixed::from_num(2);
+
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let centre = Vec2Fixed::new(
+                    Fixed::from_num(x) * cell_size + half,
+                    Fixed::from_num(y) * cell_size + half,
+                );
+                let in_sight = centre.distance_squared(pos) <= range_sq && visible(centre);
+                if in_sight || own_cell == Some((x, y)) {
+                    let index = (y as usize) * (width as usize) + (x as usize);
+                    grid.cells[index] = CellVisibility::Visible;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: i32, y: i32) -> Vec2Fixed {
+        Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y))
+    }
+
+    #[test]
+    fn test_reveal_fades_to_explored() {
+        let mut fog = FogOfWar::new(16, 16, Fixed::from_num(10));
+        let flat = NavGrid::new(16, 16, Fixed::from_num(10));
+        let us = FactionId::Continuity;
+        assert_eq!(
+            fog.visibility(us, at(55, 55)),
+            Some(CellVisibility::Unexplored)
+        );
+        assert_eq!(fog.visibility(us, at(-1, 5)), None);
+
+        fog.update(&[(us, at(55, 55), Fixed::from_num(20), Some(0))], &flat);
+        assert_eq!(
+            fog.visibility(us, at(55, 55)),
+            Some(CellVisibility::Visible)
+        );
+        assert_eq!(
+            fog.visibility(us, at(71, 55)),
+            Some(CellVisibility::Visible)
+        );
+        assert_eq!(
+            fog.visibility(us, at(85, 55)),
+            Some(CellVisibility::Unexplored)
+        );
+        // Corner cell centre (75, 75) is ~28 away
+        assert_eq!(
+            fog.visibility(us, at(75, 75)),
+            Some(CellVisibility::Unexplored)
+        );
+        assert_eq!(
+            fog.visibility(FactionId::Collegium, at(55, 55)),
+            Some(CellVisibility::Unexplored)
+        );
+
+        fog.update(&[(us, at(150, 150), Fixed::from_num(5), Some(0))], &flat);
+        assert_eq!(
+            fog.visibility(us, at(55, 55)),
+            Some(CellVisibility::Explored)
+        );
+        // Sight smaller than a cell still reveals the unit's own cell
+        assert_eq!(
+            fog.visibility(us, at(151, 151)),
+            Some(CellVisibility::Visible)
+        );
+
+        let grid = fog.grid(us).unwrap();
+        assert_eq!(grid.cells().len(), 256);
+        assert_eq!(
+            grid.cells()
+                .iter()
+                .filter(|&&c| c == CellVisibility::Visible)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_low_ground_cannot_see_up_cliffs() {
+        let mut fog = FogOfWar::new(16, 16, Fixed::from_num(10));
+        let mut terrain = NavGrid::new(16, 16, Fixed::from_num(10));
+        // Plateau east of x = 60
+        for y in 0..16 {
+            for x in 6..16 {
+                terrain.set_elevation(x, y, 1);
+            }
+        }
+        let (low, high, air) = (
+            FactionId::Continuity,
+            FactionId::Collegium,
+            FactionId::Tinkers,
+        );
+        let range = Fixed::from_num(30);
+        fog.update(
+            &[
+                (low, at(45, 55), range, Some(0)),
+                (high, at(65, 55), range, Some(1)),
+                (air, at(45, 55), range, None),
+            ],
+            &terrain,
+        );
+
+        assert_eq!(
+            fog.visibility(low, at(35, 55)),
+            Some(CellVisibility::Visible)
+        );
+        assert_eq!(
+            fog.visibility(low, at(65, 55)),
+            Some(CellVisibility::Unexplored)
+        );
+        assert_eq!(
+            fog.visibility(high, at(45, 55)),
+            Some(CellVisibility::Visible)
+        );
+        assert_eq!(
+            fog.visibility(air, at(65, 55)),
+            Some(CellVisibility::Visible)
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! Influence maps: where each side's fighting strength lies.
+//!
+//! An [`InfluenceMap`] divides the map into square regions and sums the
+//! strength of a faction's armed units in each, alongside the strength of
+//! the enemies it can see. A unit weighs fully on its own region and
+//! partly on the regions around it, so a region's numbers say who could
+//! fight there, not only who stands in it. AIs read the map to time
+//! attacks and pick the least defended targets; debugging views draw it
+//! as an overlay.
+//!
+//! Only what the faction can see counts: enemies under fog add nothing.
+//! All math is integer.
+
+use serde::{Deserialize, Serialize};
+
+use crate::factions::FactionId;
+use crate::math::{Fixed, Vec2Fixed};
+use crate::simulation::{EntityRef, Simulation};
+
+/// Side length of an influence region, in world units.
+pub const INFLUENCE_CELL_SIZE: u32 = 64;
+
+/// Share of a unit's strength felt in each region next to its own, in
+/// percent.
+pub const INFLUENCE_SPREAD_PERCENT: i64 = 50;
+
+/// Who holds a region of an influence map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Control {
+    /// Neither side has strength there.
+    Empty,
+    /// Only the faction the map is for.
+    Own,
+    /// Only its enemies.
+    Enemy,
+    /// Both sides.
+    Contested,
+}
+
+/// Fighting strength by region, as one faction sees it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InfluenceMap {
+    /// Tick the map was computed.
+    pub tick: u64,
+    cell_size: u32,
+    width: u32,
+    height: u32,
+    own: Vec<i64>,
+    enemy: Vec<i64>,
+    own_strength: i64,
+    enemy_strength: i64,
+}
+
+impl InfluenceMap {
+    /// An empty map covering `map_size` world units in regions of
+    /// `cell_size`.
+    #[must_use]
+    pub fn new(map_size: (u32, u32), cell_size: u32, tick: u64) -> Self {
+        let cell_size = cell_size.max(1);
+        let width = map_size.0.div_ceil(cell_size).max(1);
+        let height = map_size.1.div_ceil(cell_size).max(1);
+        let cells = (width * height) as usize;
+        Self {
+            tick,
+            cell_size,
+            width,
+            height,
+            own: vec![0; cells],
+            enemy: vec![0; cells],
+            own_strength: 0,
+            enemy_strength: 0,
+        }
+    }
+
+    /// The map for `faction` now: its own armed units, and the armed
+    /// enemies it can see.
+    #[must_use]
+    pub fn for_faction(sim: &Simulation, faction: FactionId, map_size: (u32, u32)) -> Self {
+        let mut map = Self::new(map_size, INFLUENCE_CELL_SIZE, sim.get_tick());
+        for id in sim.entities().sorted_ids() {
+            let Some(entity) = sim.get_entity(id) else {
+                continue;
+            };
+            if entity.faction.is_some_and(|f| f.faction == faction) {
+                if let Some(position) = entity.position {
+                    map.add(position.value, strength(entity), true);
+                }
+            }
+        }
+        for enemy in sim.get_visible_enemies_for(faction) {
+            if let Some(entity) = sim.get_entity(enemy.id) {
+                map.add(enemy.position, strength(entity), false);
+            }
+        }
+        map
+    }
+
+    /// Whether the map covers no ground, as before it is first computed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.own.is_empty()
+    }
+
+    /// Regions across and down.
+    #[must_use]
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Side length of a region, in world units.
+    #[must_use]
+    pub fn cell_size(&self) -> u32 {
+        self.cell_size
+    }
+
+    /// Add a unit's strength at `position`, to our side if `own`.
+    ///
+    /// Positions off the map count at its edge.
+    pub fn add(&mut self, position: Vec2Fixed, strength: i64, own: bool) {
+        if strength <= 0 || self.is_empty() {
+            return;
+        }
+        let (cx, cy) = self.clamped_cell(position);
+        let spread = strength * INFLUENCE_SPREAD_PERCENT / 100;
+        for dy in -1..=1_i64 {
+            for dx in -1..=1_i64 {
+                let (x, y) = (i64::from(cx) + dx, i64::from(cy) + dy);
+                if x < 0 || y < 0 || x >= i64::from(self.width) || y >= i64::from(self.height) {
+                    continue;
+                }
+                let index = (y * i64::from(self.width) + x) as usize;
+                let amount = if dx == 0 && dy == 0 { strength } else { spread };
+                let cells = if own { &mut self.own } else { &mut self.enemy };
+                cells[index] += amount;
+            }
+        }
+        if own {
+            self.own_strength += strength;
+        } else {
+            self.enemy_strength += strength;
+        }
+    }
+
+    /// Region holding `position`, if it is on the map.
+    #[must_use]
+    pub fn cell_at(&self, position: Vec2Fixed) -> Option<(u32, u32)> {
+        if position.x < Fixed::ZERO || position.y < Fixed::ZERO {
+            return None;
+        }
+        let cell = Fixed::from_num(self.cell_size);
+        let x = (position.x / cell).to_num::<u32>();
+        let y = (position.y / cell).to_num::<u32>();
+        (x < self.width && y < self.height).then_some((x, y))
+    }
+
+    /// Centre of a region, in world units.
+    #[must_use]
+    pub fn cell_center(&self, (x, y): (u32, u32)) -> Vec2Fixed {
+        let half = Fixed::from_num(self.cell_size) / 2;
+        Vec2Fixed::new(
+            Fixed::from_num(x * self.cell_size) + half,
+            Fixed::from_num(y * self.cell_size) + half,
+        )
+    }
+
+    /// Our strength felt in a region.
+    #[must_use]
+    pub fn own_at(&self, cell: (u32, u32)) -> i64 {
+        self.index(cell).map_or(0, |i| self.own[i])
+    }
+
+    /// Enemy strength felt in a region.
+    #[must_use]
+    pub fn enemy_at(&self, cell: (u32, u32)) -> i64 {
+        self.index(cell).map_or(0, |i| self.enemy[i])
+    }
+
+    /// Enemy strength felt at `position`, counting it at the map's edge if
+    /// it is off the map.
+    #[must_use]
+    pub fn enemy_near(&self, position: Vec2Fixed) -> i64 {
+        if self.is_empty() {
+            return 0;
+        }
+        self.enemy_at(self.clamped_cell(position))
+    }
+
+    /// Who holds a region.
+    #[must_use]
+    pub fn control(&self, cell: (u32, u32)) -> Control {
+        match (self.own_at(cell) > 0, self.enemy_at(cell) > 0) {
+            (false, false) => Control::Empty,
+            (true, false) => Control::Own,
+            (false, true) => Control::Enemy,
+            (true, true) => Control::Contested,
+        }
+    }
+
+    /// Regions both sides have strength in, row by row.
+    #[must_use]
+    pub fn contested(&self) -> Vec<(u32, u32)> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .filter(|&cell| self.control(cell) == Control::Contested)
+            .collect()
+    }
+
+    /// Total strength of our armed units.
+    #[must_use]
+    pub fn own_strength(&self) -> i64 {
+        self.own_strength
+    }
+
+    /// Total strength of the armed enemies in sight.
+    #[must_use]
+    pub fn enemy_strength(&self) -> i64 {
+        self.enemy_strength
+    }
+
+    fn index(&self, (x, y): (u32, u32)) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| (y * self.width + x) as usize)
+    }
+
+    fn clamped_cell(&self, position: Vec2Fixed) -> (u32, u32) {
+        let cell = Fixed::from_num(self.cell_size);
+        let clamp = |value: Fixed, cells: u32| {
+            (value.max(Fixed::ZERO) / cell)
+                .to_num::<u32>()
+                .min(cells - 1)
+        };
+        (
+            clamp(position.x, self.width),
+            clamp(position.y, self.height),
+        )
+    }
+}
+
+/// Fighting strength of an entity: its damage, scaled by the share of its
+/// health left. Unarmed entities have none.
+#[must_use]
+pub fn strength(entity: EntityRef<'_>) -> i64 {
+    let Some(damage) = entity
+        .combat_stats
+        .as_ref()
+        .map(|c| i64::from(c.damage))
+        .filter(|&d| d > 0)
+    else {
+        return 0;
+    };
+    match entity.health {
+        Some(health) if health.max > 0 => {
+            (damage * i64::from(health.current) / i64::from(health.max)).max(1)
+        }
+        _ => damage,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: i32, y: i32) -> Vec2Fixed {
+        Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y))
+    }
+
+    #[test]
+    fn test_strength_spreads_to_neighbouring_regions() {
+        let mut map = InfluenceMap::new((256, 256), 64, 0);
+        assert_eq!(map.size(), (4, 4));
+        map.add(at(100, 100), 10, true);
+        assert_eq!(map.own_at((1, 1)), 10);
+        assert_eq!(map.own_at((0, 0)), 5);
+        assert_eq!(map.own_at((2, 2)), 5);
+        assert_eq!(map.own_at((3, 3)), 0);
+        assert_eq!(map.own_strength(), 10);
+
+        // Off the map counts at the edge
+        map.add(at(900, -20), 4, false);
+        assert_eq!(map.enemy_at((3, 0)), 4);
+        assert_eq!(map.enemy_near(at(900, 0)), 4);
+        assert_eq!(map.cell_at(at(900, 0)), None);
+    }
+
+    #[test]
+    fn test_control_and_contested_regions() {
+        let mut map = InfluenceMap::new((256, 128), 64, 0);
+        map.add(at(10, 10), 10, true);
+        map.add(at(140, 10), 10, false);
+        assert_eq!(map.control((0, 0)), Control::Own);
+        assert_eq!(map.control((1, 0)), Control::Contested);
+        assert_eq!(map.control((3, 0)), Control::Enemy);
+        assert_eq!(map.control((3, 1)), Control::Enemy);
+        assert_eq!(map.contested(), vec![(1, 0), (1, 1)]);
+        assert_eq!(map.cell_center((1, 0)), at(96, 32));
+    }
+
+    #[test]
+    fn test_faction_map_counts_armed_units_in_sight() {
+        use crate::components::{CombatStats, FactionMember};
+        use crate::simulation::EntitySpawnParams;
+
+        let mut sim = Simulation::new();
+        let soldier = |x, faction| EntitySpawnParams {
+            position: Some(at(x, 100)),
+            health: Some(100),
+            faction: Some(FactionMember::new(faction, 0)),
+            combat_stats: Some(CombatStats::new(12, Fixed::from_num(60), 20)),
+            ..Default::default()
+        };
+        sim.spawn_entity(soldier(100, FactionId::Continuity));
+        sim.spawn_entity(soldier(140, FactionId::Collegium));
+        // Out of sight
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(at(480, 480)),
+            ..soldier(0, FactionId::Collegium)
+        });
+        let map = InfluenceMap::for_faction(&sim, FactionId::Continuity, (512, 512));
+        assert_eq!(map.own_strength(), 12);
+        assert_eq!(map.enemy_strength(), 12);
+        assert_eq!(map.control((2, 1)), Control::Contested);
+
+        let unarmed = sim.spawn_entity(EntitySpawnParams {
+            position: Some(at(300, 300)),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            ..Default::default()
+        });
+        assert_eq!(strength(sim.get_entity(unarmed).unwrap()), 0);
+    }
+}
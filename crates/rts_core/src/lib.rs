@@ -16,57 +16,114 @@
 //!
 //! ## Crate Structure
 //!
+//! - [`abilities`] - Active unit abilities with cooldowns
+//! - [`api`] - Stable embedding facade for third-party tools
+//! - `autosave` - Compressed rotating autosaves (`autosave` feature)
+//! - [`capacity`] - Entity limits and graceful degradation
 //! - [`components`] - ECS component definitions
+//! - [`difficulty`] - AI difficulty levels and handicaps
 //! - [`systems`] - Simulation systems
 //! - [`factions`] - Faction definitions and mechanics
+//! - [`fingerprint`] - Engine/data fingerprints for compatibility checks
+//! - [`fog`] - Per-faction fog of war
+//! - [`influence`] - Influence maps of each side's strength by region
 //! - [`simulation`] - Core simulation loop
+//! - [`market`] - Trading resources at market buildings
 //! - [`math`] - Fixed-point math utilities
+//! - [`notifications`] - Alerts for players, such as idle harvesters
+//! - [`obstacles`] - Destructible rocks and debris
+//! - [`outcome`] - How matches end ([`outcome::WinCondition`])
+//! - [`repair`] - Engineers repairing machines and medics healing infantry
+//! - [`research`] - Per-faction technology research at buildings
+//! - [`rng`] - Seeded random numbers for gameplay rolls
+//! - [`salvage`] - Wrecks left by dead units and their salvage
+//! - [`strategy`] - AI strategies: build orders, compositions and timings
+//! - [`transport`] - Transports loading and unloading other units
+//! - [`triggers`] - Scripted scenario triggers and timed events
 
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 
+pub mod abilities;
+pub mod api;
+#[cfg(feature = "autosave")]
+pub mod autosave;
 pub mod buildings;
+pub mod capacity;
 pub mod combat;
 pub mod components;
 pub mod data;
+pub mod difficulty;
 pub mod economy;
 pub mod error;
 pub mod factions;
+pub mod fingerprint;
+pub mod fog;
+pub mod influence;
 pub mod map_generation;
+pub mod market;
 pub mod math;
+pub mod notifications;
+pub mod obstacles;
+pub mod outcome;
 pub mod pathfinding;
 pub mod player_facade;
 pub mod production;
+pub mod repair;
 pub mod replay;
+pub mod research;
+pub mod rng;
+pub mod salvage;
 pub mod simulation;
+pub mod squad;
+pub mod stats;
+pub mod strategy;
 pub mod systems;
+pub mod transport;
+pub mod triggers;
 pub mod unit_kind;
 
 /// Re-export commonly used types
 pub mod prelude {
+    pub use crate::abilities::{Abilities, AbilityTarget, AbilityUsed, Shield};
     pub use crate::combat::{
         calculate_resistance_damage, convert_flat_armor_to_resistance, ArmorClass,
-        ExtendedDamageType, ResistanceStats, WeaponSize, WeaponStats, MAX_RESISTANCE, MIN_DAMAGE,
+        ExtendedDamageType, ResistanceStats, Splash, SplashEvent, SplashFalloff, WeaponSize,
+        WeaponStats, WeaponTargets, MAX_DAMAGE_VARIANCE, MAX_RESISTANCE, MIN_DAMAGE,
     };
     pub use crate::components::*;
     pub use crate::data::{
-        BuildingData, FactionData, TechData, TechEffect, TechEffectType, UnitData,
+        AbilityData, AbilityEffect, AbilityTargetType, BuildingData, FactionData, TechData,
+        TechEffect, TechEffectType, UnitData,
     };
     pub use crate::economy::{
-        Depot, EconomyEvent, Feedstock, Harvester, HarvesterState, PlayerEconomy, ResourceNode,
+        Depot, EconomyEvent, Feedstock, Harvester, HarvesterState, PlayerEconomy, ResourceAmounts,
+        ResourceKind, ResourceNode, Upkeep, UpkeepModel,
     };
     pub use crate::error::{GameError, Result};
-    pub use crate::factions::FactionId;
+    pub use crate::factions::{FactionId, FactionMechanic, MechanicData};
     pub use crate::map_generation::{
         generate_map, GeneratedMap, MapConfig, ResourcePlacement, SpawnPoint, SymmetryMode,
         TerrainCell,
     };
     pub use crate::math::Fixed;
+    pub use crate::obstacles::{Obstacle, ObstacleEvent, ObstacleKind};
+    pub use crate::outcome::WinCondition;
     pub use crate::production::{
         BlueprintRegistry, Building, BuildingBlueprint, BuildingTypeId, ProductionError,
         ProductionEvent, ProductionItem, ProductionQueue, TechId, UnitBlueprint, UnitTypeId,
     };
-    pub use crate::replay::{Replay, ReplayCommand, ReplayPlayer, REPLAY_VERSION};
+    pub use crate::repair::{RepairEvent, RepairKind, Repairer};
+    pub use crate::replay::{
+        IssueMode, Replay, ReplayCommand, ReplayEdit, ReplayPlayer, WorldEdit, REPLAY_VERSION,
+    };
+    pub use crate::research::{
+        ResearchError, ResearchEvent, ResearchFacility, ResearchItem, TechProfile, TechState,
+    };
+    pub use crate::rng::SimRng;
+    pub use crate::salvage::{SalvageEvent, Salvageable, Salvager, Wreck, WreckId};
     pub use crate::simulation::Simulation;
+    pub use crate::squad::{Formation, Squad, SquadCommand, SquadHealth, SquadId};
+    pub use crate::transport::Cargo;
     pub use crate::unit_kind::{UnitKindId, UnitKindInfo, UnitKindRegistry, UnitRole};
 }
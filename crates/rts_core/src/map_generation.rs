@@ -5,6 +5,10 @@
 //! - Resource nodes with fair distribution
 //! - Obstacles and chokepoints
 //! - NavGrid-compatible terrain data
+//!
+//! A [`MapPreset`] picks the map archetype (chokepoints, open field,
+//! islands, rich resources) and a [`Biome`] varies the terrain mix within
+//! it, so batches can measure balance across different kinds of map.
 
 use serde::{Deserialize, Serialize};
 
@@ -28,6 +32,12 @@ pub struct MapConfig {
     pub resource_density: f32,
     /// Random seed for deterministic generation.
     pub seed: u64,
+    /// Map archetype shaping obstacles and resources.
+    #[serde(default)]
+    pub preset: MapPreset,
+    /// Terrain variety within the archetype.
+    #[serde(default)]
+    pub biome: Biome,
 }
 
 impl Default for MapConfig {
@@ -40,6 +50,8 @@ impl Default for MapConfig {
             obstacle_density: 0.15,
             resource_density: 1.0,
             seed: 12345,
+            preset: MapPreset::Standard,
+            biome: Biome::Temperate,
         }
     }
 }
@@ -91,6 +103,154 @@ impl MapConfig {
         self.obstacle_density = density.clamp(0.0, 1.0);
         self
     }
+
+    /// Use a map archetype, taking its obstacle and resource densities.
+    #[must_use]
+    pub fn with_preset(mut self, preset: MapPreset) -> Self {
+        self.preset = preset;
+        self.obstacle_density = preset.obstacle_density();
+        self.resource_density = preset.resource_density();
+        self
+    }
+
+    /// Set the biome.
+    #[must_use]
+    pub const fn with_biome(mut self, biome: Biome) -> Self {
+        self.biome = biome;
+        self
+    }
+}
+
+/// Map archetype for procedural generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum MapPreset {
+    /// Mixed rocks, rough ground and the odd chokepoint.
+    #[default]
+    Standard,
+    /// Many long walls with narrow gaps between the bases.
+    ChokeHeavy,
+    /// Few obstacles and no walls; armies meet in the open.
+    OpenField,
+    /// Two landmasses split by an impassable channel, joined by a few land
+    /// bridges. Air and hover units cross anywhere.
+    Islands,
+    /// Standard terrain with twice the resources.
+    ResourceRich,
+}
+
+impl MapPreset {
+    /// Every preset.
+    pub const ALL: [Self; 5] = [
+        Self::Standard,
+        Self::ChokeHeavy,
+        Self::OpenField,
+        Self::Islands,
+        Self::ResourceRich,
+    ];
+
+    /// Parse a preset from its name, ignoring case, dashes and underscores
+    /// (`"choke-heavy"`, `"open_field"`, `"Islands"`).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        let key: String = name
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '_'))
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        match key.as_str() {
+            "standard" => Some(Self::Standard),
+            "chokeheavy" => Some(Self::ChokeHeavy),
+            "openfield" => Some(Self::OpenField),
+            "islands" | "island" => Some(Self::Islands),
+            "resourcerich" => Some(Self::ResourceRich),
+            _ => None,
+        }
+    }
+
+    /// Name used on the command line and in reports.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::ChokeHeavy => "choke-heavy",
+            Self::OpenField => "open-field",
+            Self::Islands => "islands",
+            Self::ResourceRich => "resource-rich",
+        }
+    }
+
+    /// Obstacle density this archetype generates with.
+    #[must_use]
+    pub const fn obstacle_density(self) -> f32 {
+        match self {
+            Self::Standard | Self::ResourceRich => 0.15,
+            Self::ChokeHeavy => 0.3,
+            Self::OpenField => 0.04,
+            Self::Islands => 0.08,
+        }
+    }
+
+    /// Resource density this archetype generates with.
+    #[must_use]
+    pub const fn resource_density(self) -> f32 {
+        match self {
+            Self::ResourceRich => 2.0,
+            _ => 1.0,
+        }
+    }
+
+    /// High-ground plateaus generated on each mirrored part of the map.
+    const fn plateaus(self) -> u32 {
+        match self {
+            Self::Standard | Self::ResourceRich => 2,
+            Self::ChokeHeavy => 3,
+            Self::OpenField | Self::Islands => 0,
+        }
+    }
+
+    /// Relative odds of rock clusters, rough patches and chokepoint walls.
+    const fn feature_weights(self) -> [u32; 3] {
+        match self {
+            Self::Standard | Self::ResourceRich => [1, 1, 1],
+            Self::ChokeHeavy => [1, 1, 4],
+            Self::OpenField | Self::Islands => [1, 2, 0],
+        }
+    }
+}
+
+/// Terrain variety within a map archetype.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum Biome {
+    /// Even mix of rock and rough ground.
+    #[default]
+    Temperate,
+    /// Dunes: rough ground is twice as common.
+    Arid,
+    /// High, rocky ground: rock clusters are twice as common and tall.
+    Tundra,
+    /// Marsh: rough ground is three times as common and spreads wider.
+    Wetland,
+}
+
+impl Biome {
+    /// Every biome.
+    pub const ALL: [Self; 4] = [Self::Temperate, Self::Arid, Self::Tundra, Self::Wetland];
+
+    /// Pick a biome from a seed, so a batch over many seeds sees them all.
+    #[must_use]
+    pub const fn from_seed(seed: u64) -> Self {
+        Self::ALL[(seed % Self::ALL.len() as u64) as usize]
+    }
+
+    /// Multipliers on the odds of rock clusters and rough patches.
+    const fn feature_bias(self) -> [u32; 2] {
+        match self {
+            Self::Temperate => [1, 1],
+            Self::Arid => [1, 2],
+            Self::Tundra => [2, 1],
+            Self::Wetland => [1, 3],
+        }
+    }
 }
 
 /// Symmetry mode for map generation.
@@ -118,6 +278,9 @@ pub struct TerrainCell {
     pub height: u8,
     /// Whether this is a spawn-safe zone (no obstacles near spawn).
     pub spawn_safe: bool,
+    /// Elevation level for pathing, sight and high-ground combat.
+    #[serde(default)]
+    pub elevation: u8,
 }
 
 impl TerrainCell {
@@ -128,6 +291,7 @@ impl TerrainCell {
             cell_type: CellType::Walkable,
             height: 0,
             spawn_safe: false,
+            elevation: 0,
         }
     }
 
@@ -138,6 +302,7 @@ impl TerrainCell {
             cell_type: CellType::Blocked,
             height: 0,
             spawn_safe: false,
+            elevation: 0,
         }
     }
 
@@ -148,6 +313,7 @@ impl TerrainCell {
             cell_type: CellType::SlowTerrain,
             height: 0,
             spawn_safe: false,
+            elevation: 0,
         }
     }
 
@@ -164,6 +330,13 @@ impl TerrainCell {
         self.height = height;
         self
     }
+
+    /// Set elevation level.
+    #[must_use]
+    pub const fn with_elevation(mut self, elevation: u8) -> Self {
+        self.elevation = elevation;
+        self
+    }
 }
 
 /// A resource node placement on the generated map.
@@ -259,6 +432,12 @@ impl GeneratedMap {
         self.terrain.iter().map(|c| c.cell_type).collect()
     }
 
+    /// Get the NavGrid-compatible elevation levels.
+    #[must_use]
+    pub fn as_elevations(&self) -> Vec<u8> {
+        self.terrain.iter().map(|c| c.elevation).collect()
+    }
+
     /// Map width in world units.
     #[must_use]
     pub const fn world_width(&self) -> u32 {
@@ -330,14 +509,19 @@ pub fn generate_map(config: MapConfig) -> GeneratedMap {
         }
     }
 
-    // Generate obstacles
+    // Generate obstacles and high ground
     generate_obstacles(&config, &mut terrain, &mut rng);
+    generate_plateaus(&config, &mut terrain, &mut rng);
+    if config.preset == MapPreset::Islands {
+        carve_channel(&config, &spawn_points, &mut terrain);
+    }
 
     // Apply symmetry to terrain
     apply_symmetry(&config, &mut terrain);
 
     // Generate resources
     let resources = generate_resources(&config, &spawn_points, &mut rng);
+    clear_around_resources(&config, &resources, &mut terrain);
 
     GeneratedMap {
         config,
@@ -471,8 +655,18 @@ fn generate_obstacles(config: &MapConfig, terrain: &mut [TerrainCell], rng: &mut
             continue;
         }
 
-        // Random feature type
-        let feature_type = rng.next() % 3;
+        // Random feature type, weighted by preset and biome
+        let [rock, rough, choke] = config.preset.feature_weights();
+        let [rock_bias, rough_bias] = config.biome.feature_bias();
+        let (rock, rough) = (rock * rock_bias, rough * rough_bias);
+        let roll = rng.next() % u64::from(rock + rough + choke);
+        let feature_type = if roll < u64::from(rock) {
+            0
+        } else if roll < u64::from(rock + rough) {
+            1
+        } else {
+            2
+        };
         match feature_type {
             0 => {
                 // Rock cluster (blocked cells)
@@ -485,7 +679,12 @@ fn generate_obstacles(config: &MapConfig, terrain: &mut [TerrainCell], rng: &mut
                             let nidx = (ny * config.width + nx) as usize;
                             if !terrain[nidx].spawn_safe {
                                 terrain[nidx].cell_type = CellType::Blocked;
-                                terrain[nidx].height = rng.next_range(1, 4) as u8;
+                                let height = rng.next_range(1, 4) as u8;
+                                terrain[nidx].height = if config.biome == Biome::Tundra {
+                                    height.max(2)
+                                } else {
+                                    height
+                                };
                             }
                         }
                     }
@@ -493,7 +692,8 @@ fn generate_obstacles(config: &MapConfig, terrain: &mut [TerrainCell], rng: &mut
             }
             1 => {
                 // Rough terrain patch
-                let size = rng.next_range(2, 5) as u32;
+                let spread = u32::from(config.biome == Biome::Wetland);
+                let size = rng.next_range(2, 5) as u32 + spread;
                 for dy in 0..size {
                     for dx in 0..size {
                         let nx = x + dx;
@@ -512,7 +712,11 @@ fn generate_obstacles(config: &MapConfig, terrain: &mut [TerrainCell], rng: &mut
             _ => {
                 // Chokepoint (horizontal or vertical wall with gap)
                 let horizontal = rng.next() % 2 == 0;
-                let length = rng.next_range(4, 8) as u32;
+                let length = if config.preset == MapPreset::ChokeHeavy {
+                    rng.next_range(8, 14) as u32
+                } else {
+                    rng.next_range(4, 8) as u32
+                };
                 let gap_pos = rng.next_range(1, length as i32 - 1) as u32;
 
                 for i in 0..length {
@@ -532,6 +736,112 @@ fn generate_obstacles(config: &MapConfig, terrain: &mut [TerrainCell], rng: &mut
     }
 }
 
+/// Raise plateaus one level above the ground, each with a two-cell ramp on
+/// the side facing the map centre.
+///
+/// Plateaus stay inside the part of the map that symmetry copies from, so
+/// every copy keeps its ramp.
+fn generate_plateaus(config: &MapConfig, terrain: &mut [TerrainCell], rng: &mut MapRng) {
+    let (w, h) = (config.width as i32, config.height as i32);
+    let (max_x, max_y) = match config.symmetry {
+        SymmetryMode::None => (w, h),
+        SymmetryMode::Horizontal => (w / 2, h),
+        SymmetryMode::Vertical | SymmetryMode::Rotational => (w, h / 2),
+        SymmetryMode::FourWay => (w / 2, h / 2),
+    };
+
+    for _ in 0..config.preset.plateaus() {
+        let size = rng.next_range(6, 11);
+        if max_x - size - 2 <= 2 || max_y - size - 2 <= 2 {
+            return;
+        }
+        let x0 = rng.next_range(2, max_x - size - 2);
+        let y0 = rng.next_range(2, max_y - size - 2);
+        let cells = |x: i32, y: i32| (y * w + x) as usize;
+        if (y0..y0 + size).any(|y| (x0..x0 + size).any(|x| terrain[cells(x, y)].spawn_safe)) {
+            continue;
+        }
+        for y in y0..y0 + size {
+            for x in x0..x0 + size {
+                terrain[cells(x, y)].elevation = 1;
+            }
+        }
+
+        // Ramp on the edge facing the centre, with open ground at its foot
+        let (cx, cy) = (x0 + size / 2, y0 + size / 2);
+        let (dx, dy) = (w / 2 - cx, h / 2 - cy);
+        let (step, ramp) = if dx.abs() >= dy.abs() {
+            let edge = if dx >= 0 { x0 + size - 1 } else { x0 };
+            ((dx.signum(), 0), [(edge, cy - 1), (edge, cy)])
+        } else {
+            let edge = if dy >= 0 { y0 + size - 1 } else { y0 };
+            ((0, dy.signum()), [(cx - 1, edge), (cx, edge)])
+        };
+        for (x, y) in ramp {
+            terrain[cells(x, y)].cell_type = CellType::Ramp;
+            let (fx, fy) = (x + step.0, y + step.1);
+            if fx >= 0 && fy >= 0 && fx < w && fy < h {
+                terrain[cells(fx, fy)].cell_type = CellType::Walkable;
+            }
+        }
+    }
+}
+
+/// Block a channel halfway between the first two spawns, leaving land
+/// bridges on the line between them and on either flank.
+fn carve_channel(config: &MapConfig, spawn_points: &[SpawnPoint], terrain: &mut [TerrainCell]) {
+    let [first, second, ..] = spawn_points else {
+        return;
+    };
+    let (ax, ay) = world_to_grid_simple(config, first.position);
+    let (bx, by) = world_to_grid_simple(config, second.position);
+    let (ax, ay, bx, by) = (ax as f32, ay as f32, bx as f32, by as f32);
+    let axis = ((bx - ax).powi(2) + (by - ay).powi(2)).sqrt().max(1.0);
+    let half_width = 2.0;
+    let flank = config.width.min(config.height) as f32 / 4.0;
+
+    for y in 0..config.height {
+        for x in 0..config.width {
+            let idx = (y * config.width + x) as usize;
+            if terrain[idx].spawn_safe {
+                continue;
+            }
+            let (fx, fy) = (x as f32, y as f32);
+            let to_a = ((fx - ax).powi(2) + (fy - ay).powi(2)).sqrt();
+            let to_b = ((fx - bx).powi(2) + (fy - by).powi(2)).sqrt();
+            // Distance from the channel's centre line, and from the spawn axis
+            let across = (to_a - to_b).abs() / 2.0;
+            let along = ((fx - ax) * (by - ay) - (fy - ay) * (bx - ax)).abs() / axis;
+            let bridge = along < 1.5 || (along - flank).abs() < 1.5;
+            if across <= half_width && !bridge {
+                terrain[idx].cell_type = CellType::Blocked;
+                terrain[idx].height = 0;
+            }
+        }
+    }
+}
+
+/// Keep the cells around each resource node open so harvesters reach it.
+fn clear_around_resources(
+    config: &MapConfig,
+    resources: &[ResourcePlacement],
+    terrain: &mut [TerrainCell],
+) {
+    const RADIUS: i32 = 2;
+    for resource in resources {
+        let (rx, ry) = world_to_grid_simple(config, resource.position);
+        for dy in -RADIUS..=RADIUS {
+            for dx in -RADIUS..=RADIUS {
+                let (nx, ny) = (rx as i32 + dx, ry as i32 + dy);
+                if nx >= 0 && ny >= 0 && (nx as u32) < config.width && (ny as u32) < config.height {
+                    let idx = (ny as u32 * config.width + nx as u32) as usize;
+                    terrain[idx].cell_type = CellType::Walkable;
+                }
+            }
+        }
+    }
+}
+
 fn apply_symmetry(config: &MapConfig, terrain: &mut [TerrainCell]) {
     let w = config.width;
     let h = config.height;
@@ -616,7 +926,7 @@ fn generate_resources(
         for offset in offsets {
             resources.push(ResourcePlacement::new(
                 spawn.position + offset,
-                5000,
+                (5000.0 * config.resource_density.max(1.0)) as i64,
                 true, // permanent
             ));
         }
@@ -872,6 +1182,96 @@ mod tests {
         assert_eq!(world_pos.y, Fixed::from_num(4));
     }
 
+    fn blocked_cells(map: &GeneratedMap) -> usize {
+        map.terrain
+            .iter()
+            .filter(|c| c.cell_type == CellType::Blocked)
+            .count()
+    }
+
+    #[test]
+    fn test_presets_shape_the_map() {
+        let map = |preset| generate_map(MapConfig::small().with_seed(7).with_preset(preset));
+        let open = map(MapPreset::OpenField);
+        let standard = map(MapPreset::Standard);
+        let chokes = map(MapPreset::ChokeHeavy);
+        assert!(blocked_cells(&open) < blocked_cells(&standard));
+        assert!(blocked_cells(&standard) < blocked_cells(&chokes));
+
+        let total = |map: &GeneratedMap| map.resources.iter().map(|r| r.amount).sum::<i64>();
+        assert!(total(&map(MapPreset::ResourceRich)) > total(&standard));
+
+        for preset in MapPreset::ALL {
+            assert_eq!(MapPreset::from_name(preset.name()), Some(preset));
+        }
+        assert_eq!(
+            MapPreset::from_name("Choke_Heavy"),
+            Some(MapPreset::ChokeHeavy)
+        );
+        assert_eq!(MapPreset::from_name("swamp"), None);
+    }
+
+    #[test]
+    fn test_islands_are_joined_only_by_bridges() {
+        use crate::pathfinding::{find_path, NavGrid};
+
+        let map = generate_map(
+            MapConfig::small()
+                .with_seed(3)
+                .with_preset(MapPreset::Islands),
+        );
+        assert!(
+            blocked_cells(&map)
+                > blocked_cells(&generate_map(
+                    MapConfig::small()
+                        .with_seed(3)
+                        .with_preset(MapPreset::OpenField)
+                ))
+        );
+
+        let mut grid = NavGrid::new(64, 64, Fixed::from_num(8));
+        for (i, cell) in map.as_cell_types().into_iter().enumerate() {
+            grid.set_cell(i as u32 % 64, i as u32 / 64, cell);
+        }
+        let (a, b) = (map.spawn_points[0].position, map.spawn_points[1].position);
+        assert!(
+            find_path(&grid, a, b).is_ok(),
+            "bridges should join the islands"
+        );
+
+        // Halfway between the spawns, off the bridges, is channel
+        let channel = map.get_cell(40, 24).unwrap();
+        assert_eq!(channel.cell_type, CellType::Blocked);
+    }
+
+    #[test]
+    fn test_biomes_vary_terrain() {
+        let map = |biome| {
+            generate_map(
+                MapConfig::small()
+                    .with_seed(11)
+                    .with_obstacle_density(0.4)
+                    .with_biome(biome),
+            )
+        };
+        let slow = |map: &GeneratedMap| {
+            map.terrain
+                .iter()
+                .filter(|c| c.cell_type == CellType::SlowTerrain)
+                .count()
+        };
+        assert!(slow(&map(Biome::Wetland)) > slow(&map(Biome::Temperate)));
+        assert!(map(Biome::Tundra)
+            .terrain
+            .iter()
+            .filter(|c| c.cell_type == CellType::Blocked && c.height > 0)
+            .all(|c| c.height >= 2));
+
+        let seen: std::collections::BTreeSet<_> =
+            (0..8).map(|seed| Biome::from_seed(seed) as u8).collect();
+        assert_eq!(seen.len(), Biome::ALL.len());
+    }
+
     #[test]
     fn test_as_cell_types() {
         let map = generate_map(MapConfig::small().with_seed(12345));
@@ -882,8 +1282,50 @@ mod tests {
         for cell in cells {
             assert!(matches!(
                 cell,
-                CellType::Walkable | CellType::Blocked | CellType::SlowTerrain
+                CellType::Walkable | CellType::Blocked | CellType::SlowTerrain | CellType::Ramp
             ));
         }
     }
+
+    #[test]
+    fn test_plateaus_are_reached_by_ramps() {
+        use crate::pathfinding::{find_path, NavGrid};
+
+        let map = generate_map(MapConfig::small().with_seed(5));
+        let raised: Vec<_> = (0..map.terrain.len())
+            .filter(|&i| {
+                map.terrain[i].elevation > 0 && map.terrain[i].cell_type != CellType::Blocked
+            })
+            .collect();
+        assert!(!raised.is_empty());
+        assert!(map.terrain.iter().any(|c| c.cell_type == CellType::Ramp));
+        assert!(map
+            .terrain
+            .iter()
+            .all(|c| !c.spawn_safe || c.elevation == 0));
+        assert!(
+            generate_map(MapConfig::small().with_preset(MapPreset::OpenField))
+                .terrain
+                .iter()
+                .all(|c| c.elevation == 0)
+        );
+
+        let mut grid = NavGrid::new(64, 64, Fixed::from_num(8));
+        for (i, (cell, level)) in map
+            .as_cell_types()
+            .into_iter()
+            .zip(map.as_elevations())
+            .enumerate()
+        {
+            grid.set_cell(i as u32 % 64, i as u32 / 64, cell);
+            grid.set_elevation(i as u32 % 64, i as u32 / 64, level);
+        }
+        let ramp = map
+            .terrain
+            .iter()
+            .position(|c| c.cell_type == CellType::Ramp)
+            .unwrap() as u32;
+        let top = grid.grid_to_world(ramp % 64, ramp / 64);
+        assert!(find_path(&grid, map.spawn_points[0].position, top).is_ok());
+    }
 }
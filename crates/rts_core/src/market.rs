@@ -0,0 +1,372 @@
+//! Resource exchange at market buildings.
+//!
+//! A player who owns a finished [`Marketplace`] can turn one resource into
+//! another. Each pair of resources trades at a base rate from the
+//! faction's [`MarketRules`], less a slippage that grows with how much of
+//! that pair the player has already traded in the current window, so
+//! dumping a stockpile at once pays worse than trading steadily.
+//!
+//! The simulation does not hold player resources, so trades are made
+//! against a [`PlayerEconomy`] by the game layer, which keeps one
+//! [`Market`] per player. All math is integer for deterministic
+//! simulation.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::economy::{PlayerEconomy, ResourceAmounts, ResourceKind};
+
+/// Marker for buildings that let their owner trade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Marketplace;
+
+/// Base rate for one direction of trade: `give` of `from` buys `get` of
+/// `to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExchangeRate {
+    /// Resource sold.
+    pub from: ResourceKind,
+    /// Resource bought.
+    pub to: ResourceKind,
+    /// Amount sold per lot.
+    pub give: u32,
+    /// Amount bought per lot.
+    pub get: u32,
+}
+
+impl ExchangeRate {
+    /// Create an exchange rate.
+    #[must_use]
+    pub const fn new(from: ResourceKind, to: ResourceKind, give: u32, get: u32) -> Self {
+        Self {
+            from,
+            to,
+            give,
+            get,
+        }
+    }
+}
+
+/// How a faction's markets trade.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketRules {
+    /// Pairs that can be traded; pairs not listed cannot.
+    #[serde(default = "default_rates")]
+    pub rates: Vec<ExchangeRate>,
+    /// Ticks before traded volume is forgotten.
+    #[serde(default = "default_window_ticks")]
+    pub window_ticks: u32,
+    /// Volume sold in a window per point of slippage step.
+    #[serde(default = "default_slippage_step")]
+    pub slippage_step: u32,
+    /// Percent of the base rate lost per `slippage_step` sold.
+    #[serde(default = "default_slippage_percent")]
+    pub slippage_percent: u32,
+    /// Most of the base rate slippage can take (percent).
+    #[serde(default = "default_max_slippage")]
+    pub max_slippage_percent: u32,
+}
+
+/// Default rates: feedstock buys exotics dearly, exotics buy cores, and
+/// both sell back for less.
+fn default_rates() -> Vec<ExchangeRate> {
+    use ResourceKind::{Cores, Exotics, Feedstock};
+    vec![
+        ExchangeRate::new(Feedstock, Exotics, 5, 1),
+        ExchangeRate::new(Exotics, Feedstock, 1, 3),
+        ExchangeRate::new(Exotics, Cores, 12, 1),
+        ExchangeRate::new(Cores, Exotics, 1, 6),
+    ]
+}
+
+/// Default window (one minute).
+const fn default_window_ticks() -> u32 {
+    1200
+}
+
+/// Default slippage step.
+const fn default_slippage_step() -> u32 {
+    100
+}
+
+/// Default slippage per step.
+const fn default_slippage_percent() -> u32 {
+    5
+}
+
+/// Default slippage cap.
+const fn default_max_slippage() -> u32 {
+    75
+}
+
+impl Default for MarketRules {
+    fn default() -> Self {
+        Self {
+            rates: default_rates(),
+            window_ticks: default_window_ticks(),
+            slippage_step: default_slippage_step(),
+            slippage_percent: default_slippage_percent(),
+            max_slippage_percent: default_max_slippage(),
+        }
+    }
+}
+
+impl MarketRules {
+    /// Base rate for selling `from` for `to`.
+    #[must_use]
+    pub fn rate(&self, from: ResourceKind, to: ResourceKind) -> Option<ExchangeRate> {
+        self.rates
+            .iter()
+            .find(|r| r.from == from && r.to == to && r.give > 0)
+            .copied()
+    }
+}
+
+/// A completed (or quoted) trade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trade {
+    /// Tick of the trade.
+    pub tick: u64,
+    /// Resource sold.
+    pub from: ResourceKind,
+    /// Resource bought.
+    pub to: ResourceKind,
+    /// Amount sold.
+    pub given: i32,
+    /// Amount bought.
+    pub received: i32,
+    /// Percent of the base rate lost to slippage.
+    pub slippage_percent: u32,
+}
+
+/// Why a trade could not be made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketError {
+    /// The player has no finished market.
+    NoMarket,
+    /// The pair cannot be traded.
+    NoRate {
+        /// Resource offered.
+        from: ResourceKind,
+        /// Resource wanted.
+        to: ResourceKind,
+    },
+    /// The trade would buy nothing.
+    TooSmall,
+    /// The player does not hold what they offered.
+    Insufficient(ResourceKind),
+}
+
+impl fmt::Display for MarketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoMarket => write!(f, "No market to trade at"),
+            Self::NoRate { from, to } => write!(f, "Cannot trade {from} for {to}"),
+            Self::TooSmall => write!(f, "Trade too small to buy anything"),
+            Self::Insufficient(kind) => write!(f, "Not enough {kind} to trade"),
+        }
+    }
+}
+
+impl std::error::Error for MarketError {}
+
+/// One player's trading record for the current window.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Market {
+    /// Tick the current window opened.
+    window_start: u64,
+    /// Amount sold this window, by resource sold then bought.
+    sold: BTreeMap<ResourceKind, BTreeMap<ResourceKind, u32>>,
+}
+
+impl Market {
+    /// Create a market record with nothing traded.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Amount of `from` sold for `to` in the window open at `tick`.
+    #[must_use]
+    pub fn volume(
+        &self,
+        rules: &MarketRules,
+        tick: u64,
+        from: ResourceKind,
+        to: ResourceKind,
+    ) -> u32 {
+        if self.window_expired(rules, tick) {
+            return 0;
+        }
+        self.sold
+            .get(&from)
+            .and_then(|to_map| to_map.get(&to))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Price selling `amount` of `from` for `to` at `tick`, without
+    /// trading.
+    ///
+    /// Slippage is charged on the volume midway through the trade, so one
+    /// large trade costs about the same as many small ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns why the trade could not be made.
+    pub fn quote(
+        &self,
+        rules: &MarketRules,
+        tick: u64,
+        from: ResourceKind,
+        to: ResourceKind,
+        amount: i32,
+    ) -> Result<Trade, MarketError> {
+        let rate = rules
+            .rate(from, to)
+            .ok_or(MarketError::NoRate { from, to })?;
+        let Ok(amount_u) = u32::try_from(amount) else {
+            return Err(MarketError::TooSmall);
+        };
+        let midway = u64::from(self.volume(rules, tick, from, to)) + u64::from(amount_u / 2);
+        let steps = midway / u64::from(rules.slippage_step.max(1));
+        let slippage = steps
+            .saturating_mul(u64::from(rules.slippage_percent))
+            .min(u64::from(rules.max_slippage_percent.min(100)));
+        let received = i64::from(amount) * i64::from(rate.get) * (100 - slippage as i64)
+            / (i64::from(rate.give) * 100);
+        if received <= 0 {
+            return Err(MarketError::TooSmall);
+        }
+        Ok(Trade {
+            tick,
+            from,
+            to,
+            given: amount,
+            received: i32::try_from(received).unwrap_or(i32::MAX),
+            slippage_percent: slippage as u32,
+        })
+    }
+
+    /// Sell `amount` of `from` for `to` out of `economy` at `tick`.
+    ///
+    /// # Errors
+    ///
+    /// Returns why the trade could not be made; nothing changes then.
+    pub fn trade(
+        &mut self,
+        rules: &MarketRules,
+        tick: u64,
+        from: ResourceKind,
+        to: ResourceKind,
+        amount: i32,
+        economy: &mut PlayerEconomy,
+    ) -> Result<Trade, MarketError> {
+        let trade = self.quote(rules, tick, from, to, amount)?;
+        if economy.amount(from) < amount {
+            return Err(MarketError::Insufficient(from));
+        }
+
+        if self.window_expired(rules, tick) {
+            self.window_start = tick;
+            self.sold.clear();
+        }
+        let sold = self.sold.entry(from).or_default().entry(to).or_default();
+        *sold = sold.saturating_add(amount as u32);
+
+        match from {
+            ResourceKind::Feedstock => economy.feedstock -= amount,
+            _ => economy.reserves.add(from, -amount),
+        }
+        // Bought feedstock may exceed the storage limit, like a refund
+        economy.refund_all(0, &ResourceAmounts::new().with(to, trade.received));
+        Ok(trade)
+    }
+
+    /// Check if the window has run out by `tick`.
+    fn window_expired(&self, rules: &MarketRules, tick: u64) -> bool {
+        tick >= self.window_start + u64::from(rules.window_ticks.max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_worsens_with_volume_and_recovers_next_window() {
+        let rules = MarketRules::default();
+        let mut market = Market::new();
+        let mut economy = PlayerEconomy::new(10_000, 20_000);
+        let sell = |market: &mut Market, economy: &mut PlayerEconomy, tick| {
+            market
+                .trade(
+                    &rules,
+                    tick,
+                    ResourceKind::Feedstock,
+                    ResourceKind::Exotics,
+                    500,
+                    economy,
+                )
+                .unwrap()
+        };
+
+        let first = sell(&mut market, &mut economy, 0);
+        let second = sell(&mut market, &mut economy, 10);
+        assert!(second.received < first.received);
+        assert!(second.slippage_percent > first.slippage_percent);
+        assert_eq!(economy.feedstock, 9_000);
+        assert_eq!(
+            economy.amount(ResourceKind::Exotics),
+            first.received + second.received
+        );
+
+        // A new window starts fresh
+        let later = sell(&mut market, &mut economy, u64::from(rules.window_ticks));
+        assert_eq!(later.received, first.received);
+    }
+
+    #[test]
+    fn test_trades_need_a_rate_and_the_goods() {
+        let rules = MarketRules::default();
+        let mut market = Market::new();
+        let mut economy = PlayerEconomy::new(100, 1000);
+
+        assert_eq!(
+            market.trade(
+                &rules,
+                0,
+                ResourceKind::Feedstock,
+                ResourceKind::Cores,
+                100,
+                &mut economy
+            ),
+            Err(MarketError::NoRate {
+                from: ResourceKind::Feedstock,
+                to: ResourceKind::Cores
+            })
+        );
+        assert_eq!(
+            market.trade(
+                &rules,
+                0,
+                ResourceKind::Exotics,
+                ResourceKind::Feedstock,
+                10,
+                &mut economy
+            ),
+            Err(MarketError::Insufficient(ResourceKind::Exotics))
+        );
+        assert_eq!(
+            market.quote(&rules, 0, ResourceKind::Feedstock, ResourceKind::Exotics, 4),
+            Err(MarketError::TooSmall)
+        );
+        assert_eq!(
+            market.volume(&rules, 0, ResourceKind::Feedstock, ResourceKind::Exotics),
+            0
+        );
+        assert_eq!(economy.feedstock, 100);
+    }
+}
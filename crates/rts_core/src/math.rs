@@ -123,7 +123,7 @@ impl Vec2Fixed {
 }
 
 /// Computes the square root of a fixed-point number using binary search.
-fn fixed_sqrt(value: Fixed) -> Fixed {
+pub(crate) fn fixed_sqrt(value: Fixed) -> Fixed {
     if value <= Fixed::ZERO {
         return Fixed::ZERO;
     }
@@ -0,0 +1,168 @@
+//! Player notifications: things worth an alert in the UI or a reaction
+//! from an AI, such as idle harvesters or units coming under attack.
+//!
+//! The simulation reports [`Notification`]s in
+//! [`TickEvents::notifications`](crate::simulation::TickEvents::notifications).
+//! Attacks and finished research are noticed by the simulation itself.
+//! Harvesting and payment live in the game layer, so it reports idle
+//! harvesters and blocked production through
+//! [`Simulation::notify_harvester_idle`](crate::simulation::Simulation::notify_harvester_idle)
+//! and
+//! [`Simulation::notify_production_blocked`](crate::simulation::Simulation::notify_production_blocked),
+//! and they come back in the next tick's events.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::EntityId;
+use crate::economy::ResourceKind;
+use crate::factions::FactionId;
+use crate::math::{Fixed, Vec2Fixed};
+use crate::production::ProductionError;
+use crate::simulation::TICK_RATE;
+
+/// Ticks before another attack near an earlier alert raises a new one.
+pub const UNDER_ATTACK_COOLDOWN: u64 = 10 * TICK_RATE as u64;
+
+/// Attacks within this distance of an earlier alert count as the same
+/// fight.
+pub const UNDER_ATTACK_RADIUS: i32 = 64;
+
+/// Something a player should be told about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Notification {
+    /// A harvester has nothing to gather or nowhere to take it.
+    HarvesterIdle {
+        /// The idle harvester.
+        entity: EntityId,
+        /// Its owner.
+        faction: FactionId,
+    },
+    /// A building could not start production.
+    ProductionBlocked {
+        /// The producing building.
+        building: EntityId,
+        /// Its owner.
+        faction: FactionId,
+        /// What the owner lacked.
+        reason: BlockReason,
+    },
+    /// An entity took damage from an enemy. Raised once per fight: attacks
+    /// near a recent alert stay quiet.
+    UnderAttack {
+        /// The entity hit.
+        entity: EntityId,
+        /// Its owner.
+        faction: FactionId,
+        /// Where it was hit.
+        position: Vec2Fixed,
+    },
+    /// A technology finished researching.
+    ResearchComplete {
+        /// Faction that gained the technology.
+        faction: FactionId,
+        /// Technology researched.
+        tech_id: String,
+    },
+}
+
+impl Notification {
+    /// The faction the notification is for.
+    #[must_use]
+    pub fn faction(&self) -> FactionId {
+        match self {
+            Self::HarvesterIdle { faction, .. }
+            | Self::ProductionBlocked { faction, .. }
+            | Self::UnderAttack { faction, .. }
+            | Self::ResearchComplete { faction, .. } => *faction,
+        }
+    }
+}
+
+/// Why production could not start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockReason {
+    /// Not enough of a resource.
+    Resources(ResourceKind),
+    /// No free supply.
+    Supply,
+}
+
+impl BlockReason {
+    /// The block behind a production error, if it is a shortage.
+    #[must_use]
+    pub const fn from_error(error: &ProductionError) -> Option<Self> {
+        match error.missing_resource() {
+            Some(kind) => Some(Self::Resources(kind)),
+            None if matches!(error, ProductionError::SupplyBlocked) => Some(Self::Supply),
+            None => None,
+        }
+    }
+}
+
+/// Recent under-attack alerts per faction, so one fight raises one alert.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttackAlerts {
+    /// Tick and place of each alert still cooling down.
+    recent: BTreeMap<FactionId, Vec<(u64, Vec2Fixed)>>,
+}
+
+impl AttackAlerts {
+    /// Whether an attack on `faction` at `position` deserves an alert,
+    /// remembering it if so.
+    pub fn should_alert(&mut self, faction: FactionId, position: Vec2Fixed, tick: u64) -> bool {
+        let recent = self.recent.entry(faction).or_default();
+        recent.retain(|(at, _)| tick < at + UNDER_ATTACK_COOLDOWN);
+        let radius = Fixed::from_num(UNDER_ATTACK_RADIUS);
+        if recent
+            .iter()
+            .any(|(_, near)| near.distance_squared(position) <= radius * radius)
+        {
+            return false;
+        }
+        recent.push((tick, position));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: i32) -> Vec2Fixed {
+        Vec2Fixed::new(Fixed::from_num(x), Fixed::ZERO)
+    }
+
+    #[test]
+    fn test_one_alert_per_fight() {
+        let mut alerts = AttackAlerts::default();
+        assert!(alerts.should_alert(FactionId::Continuity, at(0), 0));
+        // Same fight, same faction
+        assert!(!alerts.should_alert(FactionId::Continuity, at(10), 5));
+        // Another faction, or somewhere else
+        assert!(alerts.should_alert(FactionId::Collegium, at(10), 5));
+        assert!(alerts.should_alert(FactionId::Continuity, at(500), 5));
+        // The same place again once the alert has cooled down
+        assert!(alerts.should_alert(FactionId::Continuity, at(0), UNDER_ATTACK_COOLDOWN));
+    }
+
+    #[test]
+    fn test_notifications_serialize_with_their_kind() {
+        let notification = Notification::ProductionBlocked {
+            building: 4,
+            faction: FactionId::Tinkers,
+            reason: BlockReason::Resources(ResourceKind::Exotics),
+        };
+        let json = serde_json::to_string(&notification).unwrap();
+        assert!(json.contains(r#""kind":"production_blocked""#));
+        assert_eq!(notification.faction(), FactionId::Tinkers);
+        assert_eq!(
+            BlockReason::from_error(&ProductionError::InsufficientExotics),
+            Some(BlockReason::Resources(ResourceKind::Exotics))
+        );
+        assert_eq!(BlockReason::from_error(&ProductionError::QueueFull), None);
+    }
+}
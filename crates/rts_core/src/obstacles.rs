@@ -0,0 +1,95 @@
+//! Destructible terrain: rocks and debris that block ground until destroyed.
+//!
+//! An obstacle is an entity with an [`Obstacle`], health and no owner. It
+//! holds its footprint in the navigation and placement grids like a
+//! structure, so units path around it and nothing can be built on it.
+//! Obstacles are never acquired automatically; units must be ordered to
+//! attack one. Once destroyed, its ground opens up again, as clear ground
+//! for rocks or rough rubble for debris.
+//!
+//! Obstacles can be raised mid-match, e.g. by a scripted collapse. Every
+//! change is reported as an [`ObstacleEvent`] so the game layer can update
+//! the terrain it draws.
+
+use serde::{Deserialize, Serialize};
+
+use crate::buildings::BuildingFootprint;
+use crate::components::EntityId;
+use crate::pathfinding::CellType;
+
+/// What an obstacle is made of.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ObstacleKind {
+    /// Solid rock: leaves clear ground when destroyed.
+    #[default]
+    Rock,
+    /// Collapsed structures and wreckage: leaves rough rubble.
+    Debris,
+}
+
+impl ObstacleKind {
+    /// Terrain left where the obstacle stood once it is destroyed.
+    #[must_use]
+    pub const fn leaves(self) -> CellType {
+        match self {
+            Self::Rock => CellType::Walkable,
+            Self::Debris => CellType::SlowTerrain,
+        }
+    }
+}
+
+/// Marks an entity as a terrain obstacle and the ground it blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Obstacle {
+    /// What the obstacle is made of.
+    pub kind: ObstacleKind,
+    /// Corner cell of the footprint.
+    pub origin: (u32, u32),
+    /// Cells the obstacle covers.
+    pub footprint: BuildingFootprint,
+}
+
+impl Obstacle {
+    /// Grid cells the obstacle covers, row by row.
+    pub fn cells(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        let (x, y) = self.origin;
+        (0..self.footprint.height)
+            .flat_map(move |dy| (0..self.footprint.width).map(move |dx| (x + dx, y + dy)))
+    }
+}
+
+/// Terrain changed because an obstacle appeared or was destroyed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ObstacleEvent {
+    /// An obstacle now blocks its ground.
+    Raised {
+        /// The obstacle entity.
+        obstacle: EntityId,
+        /// Where it stands.
+        terrain: Obstacle,
+    },
+    /// An obstacle was destroyed, leaving [`ObstacleKind::leaves`] behind.
+    Destroyed {
+        /// The destroyed obstacle entity.
+        obstacle: EntityId,
+        /// Where it stood.
+        terrain: Obstacle,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_obstacle_cells_cover_footprint() {
+        let obstacle = Obstacle {
+            kind: ObstacleKind::Debris,
+            origin: (3, 5),
+            footprint: BuildingFootprint::new(2, 1),
+        };
+        assert_eq!(obstacle.cells().collect::<Vec<_>>(), vec![(3, 5), (4, 5)]);
+        assert_eq!(ObstacleKind::Rock.leaves(), CellType::Walkable);
+        assert_eq!(obstacle.kind.leaves(), CellType::SlowTerrain);
+    }
+}
@@ -0,0 +1,212 @@
+//! How matches end.
+//!
+//! [`WinCondition`] is the one vocabulary for match results: the simulation
+//! reports it in [`TickEvents`](crate::simulation::TickEvents) when a match
+//! is decided, headless metrics and batch output record it, the client end
+//! screen explains it and the server keeps it in match history. Its serde
+//! names are stable, so analytics can match on `kind` instead of parsing
+//! free-form strings:
+//!
+//! ```json
+//! {"kind":"elimination","eliminating_player":"Continuity","surviving_structures":4}
+//! {"kind":"time_limit"}
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::factions::FactionId;
+
+/// How a match ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WinCondition {
+    /// Every other faction lost its last depot.
+    Elimination {
+        /// The faction left standing.
+        eliminating_player: FactionId,
+        /// Structures the winner still had when the match ended.
+        surviving_structures: u32,
+    },
+    /// The last depots fell together; the match is a draw.
+    MutualDestruction,
+    /// A player conceded, disconnected or stopped responding.
+    Forfeit {
+        /// The faction that forfeited.
+        forfeiting_player: FactionId,
+    },
+    /// The tick limit was reached without a decision.
+    #[default]
+    TimeLimit,
+    /// Ended early by a headless runner once one side's win looked
+    /// certain.
+    Projected {
+        /// The faction projected to win.
+        projected_winner: FactionId,
+        /// How sure the projection was, in percent.
+        certainty_percent: u8,
+    },
+    /// Aborted: the entity count ran past the hard limit.
+    EntityOverflow,
+    /// Aborted: a single tick ran past the watchdog timeout.
+    TickTimeout,
+}
+
+impl WinCondition {
+    /// Stable snake_case name, as used for `kind` in serialized output.
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Elimination { .. } => "elimination",
+            Self::MutualDestruction => "mutual_destruction",
+            Self::Forfeit { .. } => "forfeit",
+            Self::TimeLimit => "time_limit",
+            Self::Projected { .. } => "projected",
+            Self::EntityOverflow => "entity_overflow",
+            Self::TickTimeout => "tick_timeout",
+        }
+    }
+
+    /// The winning faction, when the condition names one.
+    ///
+    /// A forfeit in a two-player match is won by the other side, which
+    /// this type does not know; callers resolve that themselves.
+    #[must_use]
+    pub const fn winner(&self) -> Option<FactionId> {
+        match self {
+            Self::Elimination {
+                eliminating_player, ..
+            } => Some(*eliminating_player),
+            Self::Projected {
+                projected_winner, ..
+            } => Some(*projected_winner),
+            _ => None,
+        }
+    }
+
+    /// Whether the match was cut short by a runner failure rather than
+    /// played out.
+    #[must_use]
+    pub const fn is_error(&self) -> bool {
+        matches!(self, Self::EntityOverflow | Self::TickTimeout)
+    }
+
+    /// Explain the result to one player, e.g. on an end screen.
+    #[must_use]
+    pub fn reason_for(&self, viewer: FactionId) -> String {
+        match *self {
+            Self::Elimination {
+                eliminating_player,
+                surviving_structures,
+            } if eliminating_player == viewer => format!(
+                "Every enemy depot destroyed, with {} of your structures still standing",
+                surviving_structures
+            ),
+            Self::Elimination {
+                eliminating_player, ..
+            } => format!(
+                "Your last depot fell to {}",
+                eliminating_player.display_name()
+            ),
+            Self::Forfeit { forfeiting_player } if forfeiting_player == viewer => {
+                "You forfeited the match".to_string()
+            }
+            Self::Forfeit { forfeiting_player } => {
+                format!("{} forfeited the match", forfeiting_player.display_name())
+            }
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for WinCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Elimination {
+                eliminating_player,
+                surviving_structures,
+            } => write!(
+                f,
+                "{} eliminated all opponents ({} structures standing)",
+                eliminating_player.short_name(),
+                surviving_structures
+            ),
+            Self::MutualDestruction => {
+                f.write_str("Mutual destruction: the last depots fell together")
+            }
+            Self::Forfeit { forfeiting_player } => {
+                write!(f, "{} forfeited", forfeiting_player.short_name())
+            }
+            Self::TimeLimit => f.write_str("Time limit reached"),
+            Self::Projected {
+                projected_winner,
+                certainty_percent,
+            } => write!(
+                f,
+                "{} projected to win ({}% certain)",
+                projected_winner.short_name(),
+                certainty_percent
+            ),
+            Self::EntityOverflow => f.write_str("Aborted: entity limit exceeded"),
+            Self::TickTimeout => f.write_str("Aborted: tick watchdog timeout"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serde_names_are_stable() {
+        let won = WinCondition::Elimination {
+            eliminating_player: FactionId::Continuity,
+            surviving_structures: 4,
+        };
+        let json = serde_json::to_string(&won).unwrap();
+        assert_eq!(
+            json,
+            r#"{"kind":"elimination","eliminating_player":"Continuity","surviving_structures":4}"#
+        );
+        assert_eq!(serde_json::from_str::<WinCondition>(&json).unwrap(), won);
+
+        for condition in [
+            WinCondition::MutualDestruction,
+            WinCondition::TimeLimit,
+            WinCondition::EntityOverflow,
+            WinCondition::TickTimeout,
+        ] {
+            assert_eq!(
+                serde_json::to_string(&condition).unwrap(),
+                format!(r#"{{"kind":"{}"}}"#, condition.kind())
+            );
+        }
+    }
+
+    #[test]
+    fn test_reasons_depend_on_viewer() {
+        let won = WinCondition::Elimination {
+            eliminating_player: FactionId::Collegium,
+            surviving_structures: 2,
+        };
+        assert_eq!(won.winner(), Some(FactionId::Collegium));
+        assert!(won
+            .reason_for(FactionId::Collegium)
+            .contains("2 of your structures"));
+        assert_eq!(
+            won.reason_for(FactionId::Continuity),
+            "Your last depot fell to The Collegium"
+        );
+        assert!(WinCondition::TickTimeout.is_error());
+
+        let projected = WinCondition::Projected {
+            projected_winner: FactionId::Continuity,
+            certainty_percent: 95,
+        };
+        assert_eq!(projected.kind(), "projected");
+        assert_eq!(projected.winner(), Some(FactionId::Continuity));
+        assert!(!projected.is_error());
+        assert_eq!(WinCondition::TimeLimit.winner(), None);
+    }
+}
@@ -1,5 +1,15 @@
 //! Grid-based pathfinding using A* algorithm.
 //!
+//! Large groups sent to one place share a [`FlowField`] instead: a single
+//! Dijkstra pass from the goal gives every cell its cost to get there, and
+//! each unit's path is read off the field by walking downhill. Paths from
+//! all around the map then converge on the same routes around terrain, at
+//! the cost of one search rather than one per unit.
+//!
+//! Cells may sit on different elevation levels. Ground units only change
+//! level through a [`CellType::Ramp`], one level at a time, so cliffs are
+//! walls to pathing while ramps are the way up.
+//!
 //! All calculations use fixed-point math for deterministic results
 //! across different platforms and clients.
 
@@ -19,6 +29,8 @@ pub enum CellType {
     Blocked,
     /// Slow terrain with 2x movement cost.
     SlowTerrain,
+    /// Walkable slope joining a cell to neighbours one level up or down.
+    Ramp,
 }
 
 impl CellType {
@@ -27,7 +39,7 @@ impl CellType {
     #[must_use]
     pub const fn movement_cost(self) -> Option<Fixed> {
         match self {
-            Self::Walkable => Some(Fixed::ONE),
+            Self::Walkable | Self::Ramp => Some(Fixed::ONE),
             Self::Blocked => None,
             Self::SlowTerrain => Some(Fixed::const_from_int(2)),
         }
@@ -52,6 +64,10 @@ pub struct NavGrid {
     /// Size of each cell in world units.
     #[serde(with = "fixed_serde")]
     cell_size: Fixed,
+    /// Elevation level per cell in row-major order; empty while the whole
+    /// grid is at level 0.
+    #[serde(default)]
+    elevation: Vec<u8>,
 }
 
 impl NavGrid {
@@ -75,6 +91,7 @@ impl NavGrid {
             height,
             cells: vec![CellType::Walkable; cell_count],
             cell_size,
+            elevation: Vec::new(),
         }
     }
 
@@ -137,6 +154,54 @@ impl NavGrid {
         self.get_cell(x, y).is_some_and(|c| c.is_walkable())
     }
 
+    /// Elevation level of a cell; 0 when out of bounds.
+    #[must_use]
+    pub fn elevation(&self, x: u32, y: u32) -> u8 {
+        if self.in_bounds(x, y) {
+            self.elevation
+                .get(self.coords_to_index(x, y))
+                .copied()
+                .unwrap_or(0)
+        } else {
+            0
+        }
+    }
+
+    /// Set the elevation level of a cell.
+    /// Returns `false` if out of bounds.
+    pub fn set_elevation(&mut self, x: u32, y: u32, level: u8) -> bool {
+        if !self.in_bounds(x, y) {
+            return false;
+        }
+        if self.elevation.is_empty() {
+            if level == 0 {
+                return true;
+            }
+            self.elevation = vec![0; self.cells.len()];
+        }
+        let index = self.coords_to_index(x, y);
+        self.elevation[index] = level;
+        true
+    }
+
+    /// Elevation level at a world position; 0 outside the grid.
+    #[must_use]
+    pub fn elevation_at(&self, pos: Vec2Fixed) -> u8 {
+        self.world_to_grid(pos)
+            .map_or(0, |(x, y)| self.elevation(x, y))
+    }
+
+    /// Whether a ground unit can step between two neighbouring cells: they
+    /// share a level, or one is a ramp and they are one level apart.
+    #[must_use]
+    pub fn can_step(&self, from: (u32, u32), to: (u32, u32)) -> bool {
+        let (a, b) = (self.elevation(from.0, from.1), self.elevation(to.0, to.1));
+        a == b
+            || (a.abs_diff(b) == 1
+                && (self.get_cell(from.0, from.1) == Some(CellType::Ramp)
+                    || self.get_cell(to.0, to.1) == Some(CellType::Ramp)))
+    }
+
     /// Convert world position to grid coordinates.
     ///
     /// Returns `None` if the position is outside the grid bounds.
@@ -239,15 +304,21 @@ fn chebyshev_heuristic(x1: u32, y1: u32, x2: u32, y2: u32) -> Fixed {
     Fixed::from_num(dx.max(dy))
 }
 
-/// Check if a diagonal move is valid (no corner cutting through blocked cells).
+/// Check if a move is valid: no corner cutting through blocked cells and
+/// no climbing a cliff.
 #[inline]
-fn is_diagonal_valid(grid: &NavGrid, x: u32, y: u32, dx: i32, dy: i32) -> bool {
+fn is_move_valid(grid: &NavGrid, x: u32, y: u32, dx: i32, dy: i32) -> bool {
+    let target = ((x as i32 + dx) as u32, (y as i32 + dy) as u32);
+    if !grid.can_step((x, y), target) {
+        return false;
+    }
     // For diagonal moves, check that adjacent cardinal cells are walkable
+    // and on reachable levels
     if dx != 0 && dy != 0 {
-        let check_x = (x as i32 + dx) as u32;
-        let check_y = (y as i32 + dy) as u32;
-        let adj1 = grid.is_walkable(check_x, y);
-        let adj2 = grid.is_walkable(x, check_y);
+        let check_x = target.0;
+        let check_y = target.1;
+        let adj1 = grid.is_walkable(check_x, y) && grid.can_step((x, y), (check_x, y));
+        let adj2 = grid.is_walkable(x, check_y) && grid.can_step((x, y), (x, check_y));
         adj1 && adj2
     } else {
         true
@@ -345,8 +416,8 @@ fn find_path_grid(
                 continue;
             };
 
-            // Check diagonal validity (no corner cutting)
-            if !is_diagonal_valid(grid, current.x, current.y, dx, dy) {
+            // Check move validity (no corner cutting or cliff climbing)
+            if !is_move_valid(grid, current.x, current.y, dx, dy) {
                 continue;
             }
 
@@ -411,6 +482,145 @@ fn reconstruct_path(
     path
 }
 
+/// Units sharing a move destination at which one [`FlowField`] is cheaper
+/// than an A* search per unit.
+pub const FLOW_FIELD_GROUP_SIZE: usize = 8;
+
+/// Cost to reach one goal cell from every cell of a [`NavGrid`].
+///
+/// Built with the same moves and costs as [`find_path`], so a path read
+/// from the field is as short as the A* path, though ties may break
+/// differently.
+#[derive(Debug, Clone)]
+pub struct FlowField {
+    goal: (u32, u32),
+    width: u32,
+    /// Cost per cell in row-major order; None where the goal is unreachable.
+    costs: Vec<Option<Fixed>>,
+}
+
+impl FlowField {
+    /// Build the field towards the cell containing `goal`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GameError::InvalidState` if the goal is outside the grid
+    /// or blocked.
+    pub fn new(grid: &NavGrid, goal: Vec2Fixed) -> Result<Self> {
+        let (goal_x, goal_y) = grid
+            .world_to_grid(goal)
+            .ok_or_else(|| GameError::InvalidState("Goal position outside grid".into()))?;
+        if !grid.is_walkable(goal_x, goal_y) {
+            return Err(GameError::InvalidState("Goal position is blocked".into()));
+        }
+
+        let mut costs = vec![None; grid.cells.len()];
+        costs[grid.coords_to_index(goal_x, goal_y)] = Some(Fixed::ZERO);
+        let mut open = BinaryHeap::new();
+        open.push(AStarNode {
+            x: goal_x,
+            y: goal_y,
+            f_score: Fixed::ZERO,
+            tie_breaker: coords_to_tie_breaker(goal_x, goal_y),
+        });
+
+        while let Some(current) = open.pop() {
+            let index = grid.coords_to_index(current.x, current.y);
+            if costs[index].is_some_and(|cost| cost < current.f_score) {
+                continue; // Stale entry
+            }
+            // Stepping into this cell costs its own movement cost
+            let Some(enter_cost) = grid.movement_cost(current.x, current.y) else {
+                continue;
+            };
+            let cost = current.f_score + enter_cost;
+            for (nx, ny) in walkable_neighbors(grid, current.x, current.y) {
+                let neighbor = grid.coords_to_index(nx, ny);
+                if costs[neighbor].map_or(true, |known| cost < known) {
+                    costs[neighbor] = Some(cost);
+                    open.push(AStarNode {
+                        x: nx,
+                        y: ny,
+                        f_score: cost,
+                        tie_breaker: coords_to_tie_breaker(nx, ny),
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            goal: (goal_x, goal_y),
+            width: grid.width,
+            costs,
+        })
+    }
+
+    /// Grid coordinates of the goal cell.
+    #[must_use]
+    pub const fn goal(&self) -> (u32, u32) {
+        self.goal
+    }
+
+    /// Cost to reach the goal from a cell, or None if it cannot.
+    #[must_use]
+    pub fn cost(&self, x: u32, y: u32) -> Option<Fixed> {
+        if x >= self.width {
+            return None;
+        }
+        let index = (y as usize) * (self.width as usize) + (x as usize);
+        self.costs.get(index).copied().flatten()
+    }
+
+    /// Path from `start` to the goal, as [`find_path`] would return it:
+    /// the centre of every cell on the way, start and goal included.
+    ///
+    /// `grid` must be the grid the field was built from.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GameError::InvalidState` if the start is outside the grid
+    /// or cannot reach the goal.
+    pub fn path_from(&self, grid: &NavGrid, start: Vec2Fixed) -> Result<Vec<Vec2Fixed>> {
+        let (mut x, mut y) = grid
+            .world_to_grid(start)
+            .ok_or_else(|| GameError::InvalidState("Start position outside grid".into()))?;
+        if self.cost(x, y).is_none() || !grid.is_walkable(x, y) {
+            return Err(GameError::InvalidState(format!(
+                "No path from ({x}, {y}) to ({}, {})",
+                self.goal.0, self.goal.1
+            )));
+        }
+
+        let mut path = vec![grid.grid_to_world(x, y)];
+        while (x, y) != self.goal {
+            // Walk downhill: the neighbour whose cost plus the cost of
+            // stepping into it is lowest, first direction winning ties
+            let Some(next) = walkable_neighbors(grid, x, y)
+                .filter_map(|(nx, ny)| {
+                    let total = self.cost(nx, ny)? + grid.movement_cost(nx, ny)?;
+                    Some((total, (nx, ny)))
+                })
+                .min_by_key(|&(total, _)| total)
+            else {
+                break;
+            };
+            (x, y) = next.1;
+            path.push(grid.grid_to_world(x, y));
+        }
+        Ok(path)
+    }
+}
+
+/// Cells one move away from `(x, y)` that can be stepped into, in
+/// [`DIRECTIONS`] order, without cutting corners.
+fn walkable_neighbors(grid: &NavGrid, x: u32, y: u32) -> impl Iterator<Item = (u32, u32)> + '_ {
+    DIRECTIONS.iter().filter_map(move |&(dx, dy)| {
+        let nx = u32::try_from(x as i32 + dx).ok()?;
+        let ny = u32::try_from(y as i32 + dy).ok()?;
+        (grid.is_walkable(nx, ny) && is_move_valid(grid, x, y, dx, dy)).then_some((nx, ny))
+    })
+}
+
 /// Smooth a path by removing unnecessary waypoints.
 ///
 /// Uses line-of-sight checks to skip intermediate waypoints while
@@ -463,12 +673,14 @@ fn has_line_of_sight(grid: &NavGrid, start: Vec2Fixed, end: Vec2Fixed) -> bool {
 
     let mut x = x0 as i32;
     let mut y = y0 as i32;
+    let mut previous = (x0, y0);
 
     loop {
-        // Check if current cell is walkable
-        if !grid.is_walkable(x as u32, y as u32) {
+        // Check if current cell is walkable and reachable from the last one
+        if !grid.is_walkable(x as u32, y as u32) || !grid.can_step(previous, (x as u32, y as u32)) {
             return false;
         }
+        previous = (x as u32, y as u32);
 
         if x == x1 as i32 && y == y1 as i32 {
             break;
@@ -522,6 +734,7 @@ mod tests {
             CellType::SlowTerrain.movement_cost(),
             Some(Fixed::from_num(2))
         );
+        assert_eq!(CellType::Ramp.movement_cost(), Some(Fixed::ONE));
     }
 
     #[test]
@@ -738,6 +951,92 @@ mod tests {
         assert!(!path.is_empty());
     }
 
+    /// A wall across the grid at x = 10 with a single gap at y = 3,
+    /// and slow terrain beside the gap.
+    fn walled_grid() -> NavGrid {
+        let mut grid = NavGrid::new(20, 20, fixed(1));
+        for y in (0..20).filter(|&y| y != 3) {
+            grid.set_cell(10, y, CellType::Blocked);
+        }
+        grid.set_cell(11, 2, CellType::SlowTerrain);
+        grid
+    }
+
+    #[test]
+    fn test_flow_field_paths_match_astar_and_converge() {
+        let grid = walled_grid();
+        let goal = vec2(16, 16);
+        let field = FlowField::new(&grid, goal).unwrap();
+        assert_eq!(field.goal(), (16, 16));
+        assert_eq!(field.cost(16, 16), Some(Fixed::ZERO));
+        assert_eq!(field.cost(10, 0), None);
+
+        let cost = |path: &[Vec2Fixed]| -> Fixed {
+            path.iter()
+                .skip(1)
+                .map(|p| {
+                    let (x, y) = grid.world_to_grid(*p).unwrap();
+                    grid.movement_cost(x, y).unwrap()
+                })
+                .sum()
+        };
+        let gap = grid.grid_to_world(10, 3);
+        for y in (0..20).step_by(3) {
+            for x in [0, 4, 8] {
+                let start = vec2(x, y);
+                let path = field.path_from(&grid, start).unwrap();
+                assert_eq!(path.last(), Some(&grid.grid_to_world(16, 16)));
+                assert!(path.contains(&gap), "every route goes through the gap");
+                let astar = find_path(&grid, start, goal).unwrap();
+                assert_eq!(cost(&path), cost(&astar), "from ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn test_flow_field_unreachable_and_blocked() {
+        let mut grid = walled_grid();
+        grid.set_cell(10, 3, CellType::Blocked);
+        let field = FlowField::new(&grid, vec2(16, 16)).unwrap();
+        assert!(field.path_from(&grid, vec2(2, 2)).is_err());
+        assert_eq!(
+            field.path_from(&grid, vec2(16, 16)).unwrap(),
+            vec![grid.grid_to_world(16, 16)]
+        );
+        assert!(FlowField::new(&grid, vec2(10, 5)).is_err());
+        assert!(FlowField::new(&grid, vec2(40, 5)).is_err());
+    }
+
+    #[test]
+    fn test_cliffs_are_climbed_only_by_ramps() {
+        // East half is a plateau one level up
+        let mut grid = NavGrid::new(10, 10, fixed(1));
+        for y in 0..10 {
+            for x in 5..10 {
+                grid.set_elevation(x, y, 1);
+            }
+        }
+        let (start, goal) = (vec2(2, 2), vec2(8, 2));
+        assert_eq!(grid.elevation_at(goal), 1);
+        assert!(find_path(&grid, start, goal).is_err());
+
+        grid.set_cell(5, 8, CellType::Ramp);
+        let path = find_path(&grid, start, goal).unwrap();
+        assert!(path.contains(&grid.grid_to_world(5, 8)));
+        let smoothed = smooth_path(&grid, path.clone());
+        assert!(smoothed.contains(&grid.grid_to_world(5, 8)));
+
+        let field = FlowField::new(&grid, goal).unwrap();
+        let flowed = field.path_from(&grid, start).unwrap();
+        assert!(flowed.contains(&grid.grid_to_world(5, 8)));
+        assert_eq!(flowed.len(), path.len());
+
+        // Ramps climb one level at a time
+        grid.set_elevation(6, 8, 3);
+        assert!(!grid.can_step((5, 8), (6, 8)));
+        assert!(grid.can_step((4, 8), (5, 8)));
+    }
+
     #[test]
     fn test_chebyshev_heuristic() {
         assert_eq!(chebyshev_heuristic(0, 0, 5, 5), fixed(5));
@@ -12,7 +12,8 @@ use crate::components::{Command, EntityId};
 use crate::economy::PlayerEconomy;
 use crate::error::Result;
 use crate::factions::FactionId;
-use crate::math::{Fixed, Vec2Fixed};
+use crate::fog::{sight_range, CellVisibility};
+use crate::math::Vec2Fixed;
 use crate::simulation::Simulation;
 
 /// Basic information about a unit that can be queried through the facade.
@@ -52,8 +53,9 @@ pub struct VisibleEnemy {
 /// # Visibility Rules
 ///
 /// All queries that return enemy information are filtered by visibility.
-/// An entity is visible if it's within the vision range of any friendly unit.
-/// This prevents AI from "cheating" by seeing the entire map.
+/// An entity is visible if it stands in a cell of the player's fog of war
+/// (see [`crate::fog`]) that a friendly unit can see. This prevents AI from
+/// "cheating" by seeing the entire map.
 ///
 /// # Command Flow
 ///
@@ -85,7 +87,7 @@ pub trait PlayerFacade {
 
     /// Get all enemy entities currently visible to this player.
     ///
-    /// Only returns enemies within vision range of any friendly unit.
+    /// Only returns enemies in cells the player's fog of war shows as visible.
     fn get_visible_enemies(&self) -> Vec<VisibleEnemy>;
 
     /// Query information about a specific entity.
@@ -108,7 +110,8 @@ impl Simulation {
     /// Check if a target entity is visible to a faction.
     ///
     /// An entity is visible if it's within the vision range of any entity
-    /// belonging to the viewing faction.
+    /// belonging to the viewing faction. A stealthed entity must also be
+    /// within range of one of the faction's detectors.
     ///
     /// # Arguments
     /// * `viewer_faction` - The faction trying to see
@@ -124,6 +127,9 @@ impl Simulation {
         let Some(target_pos) = target.position.as_ref() else {
             return false;
         };
+        let stealthed = target.stealth.is_some();
+        let mut seen = false;
+        let mut detected = false;
 
         // Check all entities belonging to the viewing faction
         for (_, entity) in self.entities().iter() {
@@ -137,20 +143,15 @@ impl Simulation {
                 continue;
             };
 
-            // Calculate vision range: use explicit vision_range, or 2× attack range, or default
-            let vision_range = entity
-                .vision_range
-                .or_else(|| {
-                    entity
-                        .combat_stats
-                        .map(|s| s.range * Fixed::from_num(DEFAULT_VISION_MULTIPLIER))
-                })
-                .unwrap_or(Fixed::from_num(100)); // Default vision for non-combat units
-
+            let vision_range = sight_range(entity);
             let dist_sq = own_pos.value.distance_squared(target_pos.value);
             let vision_range_sq = vision_range * vision_range;
 
-            if dist_sq <= vision_range_sq {
+            seen |= dist_sq <= vision_range_sq;
+            detected |= entity
+                .detector
+                .is_some_and(|d| dist_sq <= d.range * d.range);
+            if seen && (detected || !stealthed) {
                 return true;
             }
         }
@@ -160,7 +161,10 @@ impl Simulation {
 
     /// Get all enemies visible to a faction.
     ///
-    /// Returns position and basic info for each visible enemy unit.
+    /// Returns position and basic info for each enemy standing in a cell the
+    /// faction's fog of war shows as visible. Stealthed enemies must also be
+    /// within range of one of the faction's detectors. Enemies outside the
+    /// fog grid fall back to [`is_visible_to`](Self::is_visible_to).
     #[must_use]
     pub fn get_visible_enemies_for(&self, faction: FactionId) -> Vec<VisibleEnemy> {
         let mut visible = Vec::new();
@@ -184,7 +188,14 @@ impl Simulation {
             }
 
             // Check visibility
-            if !self.is_visible_to(faction, entity_id) {
+            let seen = match self.fog().visibility(faction, pos.value) {
+                Some(cell) => {
+                    cell == CellVisibility::Visible
+                        && (entity.stealth.is_none() || self.is_detected_by(faction, pos.value))
+                }
+                None => self.is_visible_to(faction, entity_id),
+            };
+            if !seen {
                 continue;
             }
 
@@ -198,6 +209,19 @@ impl Simulation {
         visible
     }
 
+    /// Whether any of a faction's detectors covers a position.
+    fn is_detected_by(&self, faction: FactionId, pos: Vec2Fixed) -> bool {
+        self.entities().iter().any(|(_, entity)| {
+            entity.faction.is_some_and(|f| f.faction == faction)
+                && match (entity.position, entity.detector) {
+                    (Some(own), Some(detector)) => {
+                        own.value.distance_squared(pos) <= detector.range * detector.range
+                    }
+                    _ => false,
+                }
+        })
+    }
+
     /// Get all entities owned by a faction.
     #[must_use]
     pub fn get_faction_entities(&self, faction: FactionId) -> Vec<EntityId> {
@@ -308,7 +332,7 @@ impl<'a> PlayerFacade for SimulationPlayerFacade<'a> {
     }
 
     fn get_resources(&self) -> PlayerEconomy {
-        self.economy
+        self.economy.clone()
     }
 
     fn faction(&self) -> FactionId {
@@ -320,6 +344,7 @@ impl<'a> PlayerFacade for SimulationPlayerFacade<'a> {
 mod tests {
     use super::*;
     use crate::components::{CombatStats, FactionMember};
+    use crate::math::Fixed;
     use crate::simulation::EntitySpawnParams;
 
     fn spawn_unit_for_faction(
@@ -385,6 +410,41 @@ mod tests {
         assert!(!sim.is_visible_to(FactionId::Continuity, enemy));
     }
 
+    #[test]
+    fn test_stealthed_enemy_needs_detector_in_range() {
+        let mut sim = Simulation::new();
+        let _friendly = spawn_unit_for_faction(
+            &mut sim,
+            FactionId::Continuity,
+            Vec2Fixed::ZERO,
+            Fixed::from_num(50),
+        );
+        let cloaked = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(80), Fixed::from_num(0))),
+            faction: Some(FactionMember::new(FactionId::Collegium, 0)),
+            is_stealthed: true,
+            ..Default::default()
+        });
+        assert!(!sim.is_visible_to(FactionId::Continuity, cloaked));
+
+        // A detector that is too far away does not help
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(-200), Fixed::from_num(0))),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            detection_range: Some(Fixed::from_num(100)),
+            ..Default::default()
+        });
+        assert!(!sim.is_visible_to(FactionId::Continuity, cloaked));
+
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(20), Fixed::from_num(0))),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            detection_range: Some(Fixed::from_num(100)),
+            ..Default::default()
+        });
+        assert!(sim.is_visible_to(FactionId::Continuity, cloaked));
+    }
+
     #[test]
     fn test_facade_only_sees_visible_enemies() {
         let mut sim = Simulation::new();
@@ -417,6 +477,53 @@ mod tests {
         assert_eq!(visible.len(), 1);
     }
 
+    #[test]
+    fn test_visible_enemies_follow_fog_updates() {
+        let mut sim = Simulation::new();
+        let at = |x: i32, y: i32| Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y));
+        let friendly = spawn_unit_for_faction(
+            &mut sim,
+            FactionId::Continuity,
+            at(100, 100),
+            Fixed::from_num(50),
+        );
+        let enemy = spawn_unit_for_faction(
+            &mut sim,
+            FactionId::Collegium,
+            at(180, 100),
+            Fixed::from_num(50),
+        );
+        assert_eq!(sim.get_visible_enemies_for(FactionId::Continuity).len(), 1);
+
+        sim.apply_command(enemy, Command::MoveTo(at(900, 100)))
+            .unwrap();
+        for _ in 0..400 {
+            sim.tick();
+        }
+        assert!(sim
+            .get_visible_enemies_for(FactionId::Continuity)
+            .is_empty());
+        let fog = sim.fog();
+        assert_eq!(
+            fog.visibility(FactionId::Continuity, at(180, 100)),
+            Some(CellVisibility::Visible)
+        );
+        assert_eq!(
+            fog.visibility(FactionId::Continuity, at(600, 100)),
+            Some(CellVisibility::Unexplored)
+        );
+
+        sim.apply_command(friendly, Command::MoveTo(at(100, 900)))
+            .unwrap();
+        for _ in 0..400 {
+            sim.tick();
+        }
+        assert_eq!(
+            sim.fog().visibility(FactionId::Continuity, at(100, 100)),
+            Some(CellVisibility::Explored)
+        );
+    }
+
     #[test]
     fn test_facade_cannot_command_enemy_units() {
         let mut sim = Simulation::new();
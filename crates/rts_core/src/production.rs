@@ -10,6 +10,7 @@ use std::collections::{HashMap, VecDeque};
 use serde::{Deserialize, Serialize};
 
 use crate::components::{EntityId, Position};
+use crate::economy::{PlayerEconomy, ResourceAmounts, ResourceKind, LIGHT_UNIT_SUPPLY};
 use crate::math::{fixed_serde, Fixed, Vec2Fixed};
 
 /// Unique identifier for unit types.
@@ -60,6 +61,9 @@ pub struct UnitBlueprint {
     pub name: String,
     /// Feedstock cost to produce this unit.
     pub cost: i32,
+    /// Cost in resources other than feedstock.
+    #[serde(default, skip_serializing_if = "ResourceAmounts::is_empty")]
+    pub extra_cost: ResourceAmounts,
     /// Time in ticks to build this unit.
     pub build_time: u32,
     /// Maximum health points.
@@ -76,6 +80,14 @@ pub struct UnitBlueprint {
         with = "option_fixed_serde"
     )]
     pub attack_range: Option<Fixed>,
+    /// Supply taken while the unit is queued or alive.
+    #[serde(default = "default_supply")]
+    pub supply: u32,
+}
+
+/// Default supply cost for blueprints without one.
+const fn default_supply() -> u32 {
+    LIGHT_UNIT_SUPPLY
 }
 
 /// Serde support for optional fixed-point numbers.
@@ -119,14 +131,30 @@ impl UnitBlueprint {
             id,
             name: name.into(),
             cost,
+            extra_cost: ResourceAmounts::new(),
             build_time,
             health,
             speed,
             attack_damage: None,
             attack_range: None,
+            supply: LIGHT_UNIT_SUPPLY,
         }
     }
 
+    /// Set the supply cost.
+    #[must_use]
+    pub const fn with_supply(mut self, supply: u32) -> Self {
+        self.supply = supply;
+        self
+    }
+
+    /// Add a cost in a resource other than feedstock.
+    #[must_use]
+    pub fn with_extra_cost(mut self, kind: ResourceKind, amount: i32) -> Self {
+        self.extra_cost.add(kind, amount);
+        self
+    }
+
     /// Create a unit blueprint with combat stats.
     #[must_use]
     pub fn with_combat(mut self, damage: i32, range: Fixed) -> Self {
@@ -148,6 +176,9 @@ pub struct BuildingBlueprint {
     pub name: String,
     /// Feedstock cost to construct this building.
     pub cost: i32,
+    /// Cost in resources other than feedstock.
+    #[serde(default, skip_serializing_if = "ResourceAmounts::is_empty")]
+    pub extra_cost: ResourceAmounts,
     /// Time in ticks to construct this building.
     pub build_time: u32,
     /// Maximum health points.
@@ -172,6 +203,7 @@ impl BuildingBlueprint {
             id,
             name: name.into(),
             cost,
+            extra_cost: ResourceAmounts::new(),
             build_time,
             health,
             produces: Vec::new(),
@@ -186,6 +218,13 @@ impl BuildingBlueprint {
         self
     }
 
+    /// Add a cost in a resource other than feedstock.
+    #[must_use]
+    pub fn with_extra_cost(mut self, kind: ResourceKind, amount: i32) -> Self {
+        self.extra_cost.add(kind, amount);
+        self
+    }
+
     /// Add technology requirements.
     #[must_use]
     pub fn with_tech_required(mut self, techs: Vec<TechId>) -> Self {
@@ -231,11 +270,9 @@ impl ProductionItem {
     /// Get progress as a percentage (0-100).
     #[must_use]
     pub fn percentage(&self) -> u32 {
-        if self.total_time == 0 {
-            100
-        } else {
-            (self.progress * 100) / self.total_time
-        }
+        (self.progress * 100)
+            .checked_div(self.total_time)
+            .unwrap_or(100)
     }
 
     /// Advance production by one tick.
@@ -455,14 +492,45 @@ impl Building {
 pub enum ProductionError {
     /// The production queue is full.
     QueueFull,
-    /// Cannot afford the unit cost.
+    /// Cannot afford the unit's feedstock cost.
     InsufficientResources,
+    /// Cannot afford the unit's exotics cost.
+    InsufficientExotics,
+    /// Cannot afford the unit's cores cost.
+    InsufficientCores,
     /// The building cannot produce this unit type.
     CannotProduceUnit,
     /// The building is not yet constructed.
     BuildingNotConstructed,
     /// The requested unit or building type was not found.
     BlueprintNotFound,
+    /// Not enough free supply for the unit.
+    SupplyBlocked,
+    /// A building or technology the unit requires is missing.
+    PrerequisiteMissing,
+}
+
+impl ProductionError {
+    /// The error for being short of `kind`.
+    #[must_use]
+    pub const fn insufficient(kind: ResourceKind) -> Self {
+        match kind {
+            ResourceKind::Feedstock => Self::InsufficientResources,
+            ResourceKind::Exotics => Self::InsufficientExotics,
+            ResourceKind::Cores => Self::InsufficientCores,
+        }
+    }
+
+    /// The resource this error is short of, if it is a shortfall.
+    #[must_use]
+    pub const fn missing_resource(&self) -> Option<ResourceKind> {
+        match self {
+            Self::InsufficientResources => Some(ResourceKind::Feedstock),
+            Self::InsufficientExotics => Some(ResourceKind::Exotics),
+            Self::InsufficientCores => Some(ResourceKind::Cores),
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Display for ProductionError {
@@ -470,9 +538,13 @@ impl std::fmt::Display for ProductionError {
         match self {
             Self::QueueFull => write!(f, "Production queue is full"),
             Self::InsufficientResources => write!(f, "Insufficient resources"),
+            Self::InsufficientExotics => write!(f, "Insufficient exotics"),
+            Self::InsufficientCores => write!(f, "Insufficient cores"),
             Self::CannotProduceUnit => write!(f, "Building cannot produce this unit type"),
             Self::BuildingNotConstructed => write!(f, "Building is not yet constructed"),
             Self::BlueprintNotFound => write!(f, "Blueprint not found"),
+            Self::SupplyBlocked => write!(f, "Not enough supply"),
+            Self::PrerequisiteMissing => write!(f, "Missing a required building or technology"),
         }
     }
 }
@@ -663,7 +735,9 @@ pub fn production_system(
 
 /// Queue a unit for production at a building.
 ///
-/// Validates that the building can produce the unit and that resources are available.
+/// Validates that the building can produce the unit and that resources and
+/// supply are available. Supply is reserved when the unit is queued, not
+/// when it spawns.
 ///
 /// # Arguments
 ///
@@ -671,7 +745,7 @@ pub fn production_system(
 /// * `building` - The building component
 /// * `unit_type` - The type of unit to produce
 /// * `blueprints` - Registry of blueprints
-/// * `player_feedstock` - Player's current feedstock (will be deducted on success)
+/// * `economy` - Player's economy (cost is deducted and supply reserved on success)
 ///
 /// # Returns
 ///
@@ -681,7 +755,7 @@ pub fn queue_production(
     building: &Building,
     unit_type: UnitTypeId,
     blueprints: &BlueprintRegistry,
-    player_feedstock: &mut i32,
+    economy: &mut PlayerEconomy,
 ) -> Result<(), ProductionError> {
     // Check building is constructed
     if !building.is_constructed {
@@ -703,39 +777,48 @@ pub fn queue_production(
         .ok_or(ProductionError::BlueprintNotFound)?;
 
     // Check resources
-    if *player_feedstock < unit_blueprint.cost {
-        return Err(ProductionError::InsufficientResources);
+    if let Some(kind) = economy.shortfall(unit_blueprint.cost, &unit_blueprint.extra_cost) {
+        return Err(ProductionError::insufficient(kind));
+    }
+
+    // Check supply
+    if !economy.has_supply(unit_blueprint.supply) {
+        return Err(ProductionError::SupplyBlocked);
     }
 
     // Try to add to queue
     queue.add(unit_type, unit_blueprint.build_time)?;
 
-    // Deduct cost
-    *player_feedstock -= unit_blueprint.cost;
+    // Deduct cost and reserve supply
+    economy
+        .spend_all(unit_blueprint.cost, &unit_blueprint.extra_cost)
+        .map_err(ProductionError::insufficient)?;
+    economy.reserve_supply(unit_blueprint.supply);
 
     Ok(())
 }
 
 /// Cancel production of a unit at a specific queue index.
 ///
-/// Refunds a portion of the cost based on progress.
+/// Refunds a portion of the cost, in every resource it was paid in, based
+/// on progress and releases the unit's supply.
 ///
 /// # Arguments
 ///
 /// * `queue` - The building's production queue
 /// * `index` - Index of the item to cancel
 /// * `blueprints` - Registry of blueprints
-/// * `player_feedstock` - Player's feedstock (refund will be added)
+/// * `economy` - Player's economy (refund is added and supply released)
 /// * `refund_percentage` - Percentage of cost to refund (0-100)
 ///
 /// # Returns
 ///
-/// The cancelled `ProductionItem` and refund amount if successful.
+/// The cancelled `ProductionItem` and feedstock refunded if successful.
 pub fn cancel_production(
     queue: &mut ProductionQueue,
     index: usize,
     blueprints: &BlueprintRegistry,
-    player_feedstock: &mut i32,
+    economy: &mut PlayerEconomy,
     refund_percentage: i32,
 ) -> Option<(ProductionItem, i32)> {
     let item = queue.cancel(index)?;
@@ -745,14 +828,17 @@ pub fn cancel_production(
     let base_refund = (unit_blueprint.cost * refund_percentage) / 100;
 
     // If production has started, reduce refund based on progress
-    let progress_factor = if item.total_time > 0 {
-        100 - ((item.progress * 100) / item.total_time) as i32
-    } else {
-        100
-    };
+    let progress_factor = (item.progress * 100)
+        .checked_div(item.total_time)
+        .map_or(100, |pct| 100 - pct as i32);
     let refund = (base_refund * progress_factor) / 100;
+    let extra_refund = unit_blueprint
+        .extra_cost
+        .percent(refund_percentage)
+        .percent(progress_factor);
 
-    *player_feedstock += refund;
+    economy.refund_all(refund, &extra_refund);
+    economy.release_supply(unit_blueprint.supply);
 
     Some((item, refund))
 }
@@ -777,7 +863,8 @@ mod tests {
         // Register a combat unit
         registry.register_unit(
             UnitBlueprint::new(UnitTypeId(2), "Tank", 300, 120, 200, Fixed::from_num(2))
-                .with_combat(50, Fixed::from_num(5)),
+                .with_combat(50, Fixed::from_num(5))
+                .with_supply(2),
         );
 
         // Register a barracks
@@ -1108,7 +1195,7 @@ mod tests {
 
         let mut queue = ProductionQueue::new();
         let building = Building::constructed(BuildingTypeId(2)); // Factory can build both
-        let mut feedstock = 500;
+        let mut economy = PlayerEconomy::new(500, 1000).with_supply_provided(10);
 
         // Queue infantry (costs 100)
         let result = queue_production(
@@ -1116,10 +1203,10 @@ mod tests {
             &building,
             UnitTypeId(1),
             &blueprints,
-            &mut feedstock,
+            &mut economy,
         );
         assert!(result.is_ok());
-        assert_eq!(feedstock, 400);
+        assert_eq!(economy.feedstock, 400);
         assert_eq!(queue.len(), 1);
 
         // Queue tank (costs 300)
@@ -1128,53 +1215,141 @@ mod tests {
             &building,
             UnitTypeId(2),
             &blueprints,
-            &mut feedstock,
+            &mut economy,
         );
         assert!(result.is_ok());
-        assert_eq!(feedstock, 100);
+        assert_eq!(economy.feedstock, 100);
+        assert_eq!(economy.supply_used, 3);
         assert_eq!(queue.len(), 2);
     }
 
+    #[test]
+    fn test_queue_production_supply_blocked() {
+        let blueprints = create_test_blueprints();
+
+        let mut queue = ProductionQueue::new();
+        let building = Building::constructed(BuildingTypeId(2));
+        let mut economy = PlayerEconomy::new(1000, 1000).with_supply_provided(3);
+
+        for _ in 0..3 {
+            queue_production(
+                &mut queue,
+                &building,
+                UnitTypeId(1),
+                &blueprints,
+                &mut economy,
+            )
+            .unwrap();
+        }
+        let result = queue_production(
+            &mut queue,
+            &building,
+            UnitTypeId(1),
+            &blueprints,
+            &mut economy,
+        );
+        assert!(matches!(result, Err(ProductionError::SupplyBlocked)));
+        assert_eq!(economy.feedstock, 700); // Unchanged by the rejected unit
+        assert_eq!(queue.len(), 3);
+
+        // Cancelling frees supply for the next unit
+        cancel_production(&mut queue, 2, &blueprints, &mut economy, 100).unwrap();
+        assert_eq!(economy.supply_used, 2);
+        assert!(queue_production(
+            &mut queue,
+            &building,
+            UnitTypeId(1),
+            &blueprints,
+            &mut economy,
+        )
+        .is_ok());
+    }
+
     #[test]
     fn test_queue_production_insufficient_resources() {
         let blueprints = create_test_blueprints();
 
         let mut queue = ProductionQueue::new();
         let building = Building::constructed(BuildingTypeId(2));
-        let mut feedstock = 50; // Not enough for infantry (100)
+        let mut economy = PlayerEconomy::new(50, 1000).with_supply_provided(10); // Not enough for infantry (100)
 
         let result = queue_production(
             &mut queue,
             &building,
             UnitTypeId(1),
             &blueprints,
-            &mut feedstock,
+            &mut economy,
         );
         assert!(matches!(
             result,
             Err(ProductionError::InsufficientResources)
         ));
-        assert_eq!(feedstock, 50); // Unchanged
+        assert_eq!(economy.feedstock, 50); // Unchanged
         assert!(queue.is_empty());
     }
 
+    #[test]
+    fn test_queue_production_needs_every_resource_in_the_cost() {
+        let mut blueprints = create_test_blueprints();
+        blueprints.register_unit(
+            UnitBlueprint::new(UnitTypeId(3), "Lancer", 100, 60, 80, Fixed::from_num(1))
+                .with_extra_cost(ResourceKind::Exotics, 40)
+                .with_extra_cost(ResourceKind::Cores, 1),
+        );
+        blueprints.register_building(
+            BuildingBlueprint::new(BuildingTypeId(3), "Foundry", 300, 90, 600)
+                .with_produces(vec![UnitTypeId(3)]),
+        );
+        let mut queue = ProductionQueue::new();
+        let building = Building::constructed(BuildingTypeId(3));
+        let reserves = ResourceAmounts::new().with(ResourceKind::Exotics, 30);
+        let mut economy = PlayerEconomy::new(500, 1000)
+            .with_supply_provided(10)
+            .with_reserves(reserves);
+
+        let mut queue_lancer = |economy: &mut PlayerEconomy| {
+            queue_production(&mut queue, &building, UnitTypeId(3), &blueprints, economy)
+        };
+        assert_eq!(
+            queue_lancer(&mut economy),
+            Err(ProductionError::InsufficientExotics)
+        );
+        economy.reserves.add(ResourceKind::Exotics, 10);
+        assert_eq!(
+            queue_lancer(&mut economy),
+            Err(ProductionError::InsufficientCores)
+        );
+        assert_eq!(economy.feedstock, 500, "nothing is paid on failure");
+
+        economy.reserves.add(ResourceKind::Cores, 1);
+        assert!(queue_lancer(&mut economy).is_ok());
+        assert_eq!(economy.feedstock, 400);
+        assert!(economy.reserves.is_empty());
+
+        // An untouched cancel gives everything back
+        cancel_production(&mut queue, 0, &blueprints, &mut economy, 100).unwrap();
+        assert_eq!(economy.feedstock, 500);
+        assert_eq!(economy.amount(ResourceKind::Exotics), 40);
+        assert_eq!(economy.amount(ResourceKind::Cores), 1);
+    }
+
     #[test]
     fn test_queue_production_cannot_produce() {
         let blueprints = create_test_blueprints();
 
         let mut queue = ProductionQueue::new();
         let building = Building::constructed(BuildingTypeId(1)); // Barracks can't build tanks
-        let mut feedstock = 500;
+        let mut economy = PlayerEconomy::new(500, 1000).with_supply_provided(10);
 
         let result = queue_production(
             &mut queue,
             &building,
             UnitTypeId(2),
             &blueprints,
-            &mut feedstock,
+            &mut economy,
         );
         assert!(matches!(result, Err(ProductionError::CannotProduceUnit)));
-        assert_eq!(feedstock, 500); // Unchanged
+        assert_eq!(economy.feedstock, 500); // Unchanged
     }
 
     #[test]
@@ -1183,14 +1358,14 @@ mod tests {
 
         let mut queue = ProductionQueue::new();
         let building = Building::new(BuildingTypeId(1), 90); // Not constructed
-        let mut feedstock = 500;
+        let mut economy = PlayerEconomy::new(500, 1000).with_supply_provided(10);
 
         let result = queue_production(
             &mut queue,
             &building,
             UnitTypeId(1),
             &blueprints,
-            &mut feedstock,
+            &mut economy,
         );
         assert!(matches!(
             result,
@@ -1204,15 +1379,15 @@ mod tests {
 
         let mut queue = ProductionQueue::new();
         queue.add(UnitTypeId(1), 60).unwrap(); // Infantry, no progress yet
-        let mut feedstock = 0;
+        let mut economy = PlayerEconomy::new(0, 1000).with_supply_provided(10);
 
-        let result = cancel_production(&mut queue, 0, &blueprints, &mut feedstock, 100);
+        let result = cancel_production(&mut queue, 0, &blueprints, &mut economy, 100);
         assert!(result.is_some());
 
         let (item, refund) = result.unwrap();
         assert_eq!(item.unit_type, UnitTypeId(1));
         assert_eq!(refund, 100); // Full refund (100% of 100 cost)
-        assert_eq!(feedstock, 100);
+        assert_eq!(economy.feedstock, 100);
         assert!(queue.is_empty());
     }
 
@@ -1226,15 +1401,15 @@ mod tests {
         // Simulate 50% progress
         queue.current_mut().unwrap().progress = 50;
 
-        let mut feedstock = 0;
+        let mut economy = PlayerEconomy::new(0, 1000).with_supply_provided(10);
 
         // 100% refund rate, but 50% complete = 50% actual refund
-        let result = cancel_production(&mut queue, 0, &blueprints, &mut feedstock, 100);
+        let result = cancel_production(&mut queue, 0, &blueprints, &mut economy, 100);
         assert!(result.is_some());
 
         let (_, refund) = result.unwrap();
         assert_eq!(refund, 50); // 50% of 100 cost
-        assert_eq!(feedstock, 50);
+        assert_eq!(economy.feedstock, 50);
     }
 
     #[test]
@@ -1243,15 +1418,15 @@ mod tests {
 
         let mut queue = ProductionQueue::new();
         queue.add(UnitTypeId(1), 100).unwrap(); // No progress
-        let mut feedstock = 0;
+        let mut economy = PlayerEconomy::new(0, 1000).with_supply_provided(10);
 
         // 50% refund rate
-        let result = cancel_production(&mut queue, 0, &blueprints, &mut feedstock, 50);
+        let result = cancel_production(&mut queue, 0, &blueprints, &mut economy, 50);
         assert!(result.is_some());
 
         let (_, refund) = result.unwrap();
         assert_eq!(refund, 50); // 50% of 100 cost
-        assert_eq!(feedstock, 50);
+        assert_eq!(economy.feedstock, 50);
     }
 
     #[test]
@@ -1309,5 +1484,13 @@ mod tests {
             ProductionError::BlueprintNotFound.to_string(),
             "Blueprint not found"
         );
+        assert_eq!(
+            ProductionError::SupplyBlocked.to_string(),
+            "Not enough supply"
+        );
+        assert_eq!(
+            ProductionError::PrerequisiteMissing.to_string(),
+            "Missing a required building or technology"
+        );
     }
 }
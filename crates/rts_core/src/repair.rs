@@ -0,0 +1,107 @@
+//! Repair and healing: units restoring the health of their allies.
+//!
+//! A unit with a [`Repairer`] ordered to
+//! [`Command::Repair`](crate::components::Command::Repair) a damaged ally
+//! closes to [`REPAIR_RANGE`] and restores health every few ticks until the
+//! ally is whole. Engineers ([`RepairKind::Mechanical`]) mend structures and
+//! vehicles; medics ([`RepairKind::Biological`]) tend infantry.
+//!
+//! The simulation does not hold player resources: each repair is reported
+//! as a [`RepairEvent`] carrying its cost, which the game layer pays.
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::EntityId;
+use crate::factions::FactionId;
+use crate::math::Fixed;
+use crate::research::TechProfile;
+
+/// How close a repairer must be to its patient to work on it.
+pub const REPAIR_RANGE: Fixed = Fixed::const_from_int(24);
+
+/// Tags that mark a unit as flesh and blood rather than machinery.
+pub const BIOLOGICAL_TAGS: [&str; 2] = ["infantry", "biological"];
+
+/// What a repairer can mend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RepairKind {
+    /// Structures and vehicles: anything that is not biological.
+    #[default]
+    Mechanical,
+    /// Infantry and other biological units.
+    Biological,
+}
+
+impl RepairKind {
+    /// Whether this kind of repair works on an entity with `profile`.
+    ///
+    /// Structures (anything that cannot move) are always mechanical, since
+    /// buildings carry the tags of what they produce.
+    #[must_use]
+    pub fn treats(self, is_structure: bool, profile: Option<&TechProfile>) -> bool {
+        let biological = !is_structure
+            && profile
+                .is_some_and(|p| p.tags.iter().any(|t| BIOLOGICAL_TAGS.contains(&t.as_str())));
+        biological == (self == Self::Biological)
+    }
+}
+
+/// Lets a unit restore its allies' health.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Repairer {
+    /// What the unit can mend.
+    pub kind: RepairKind,
+    /// Health restored per repair.
+    pub amount: u32,
+    /// Ticks between repairs.
+    pub interval: u32,
+    /// Feedstock each repair costs.
+    pub cost: u32,
+}
+
+impl Repairer {
+    /// Create a repairer.
+    #[must_use]
+    pub const fn new(kind: RepairKind, amount: u32, interval: u32, cost: u32) -> Self {
+        Self {
+            kind,
+            amount,
+            interval: if interval == 0 { 1 } else { interval },
+            cost,
+        }
+    }
+}
+
+/// A repairer restored some of an ally's health.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepairEvent {
+    /// The unit doing the repair.
+    pub repairer: EntityId,
+    /// The entity repaired.
+    pub target: EntityId,
+    /// Faction of the repairer, who pays the cost.
+    pub faction: Option<FactionId>,
+    /// Health restored.
+    pub amount: u32,
+    /// Feedstock the repair costs.
+    pub cost: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engineers_mend_machines_and_medics_tend_infantry() {
+        let trooper = TechProfile::new("security_team", vec!["infantry".to_string()]);
+        let barracks = TechProfile::new("barracks", vec!["infantry".to_string()]);
+        let tank = TechProfile::new("hover_tank", vec!["vehicle".to_string()]);
+
+        assert!(RepairKind::Biological.treats(false, Some(&trooper)));
+        assert!(!RepairKind::Mechanical.treats(false, Some(&trooper)));
+        assert!(RepairKind::Mechanical.treats(false, Some(&tank)));
+        assert!(RepairKind::Mechanical.treats(true, Some(&barracks)));
+        assert!(!RepairKind::Biological.treats(true, Some(&barracks)));
+        assert!(RepairKind::Mechanical.treats(false, None));
+    }
+}
@@ -2,13 +2,37 @@
 //!
 //! Replays store the initial scenario state and the stream of commands
 //! issued during the game. This allows deterministic recreation of any game.
+//!
+//! Hosts that change the world directly rather than through commands (the
+//! game client spawns entities it has built and blocks navigation cells
+//! under buildings) also record those changes as [`WorldEdit`]s, ordered
+//! against the commands, so playback makes them at the same point.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 
+use crate::buildings::BuildingFootprint;
 use crate::components::{Command, EntityId};
+use crate::data::TechData;
 use crate::error::{GameError, Result};
-use crate::simulation::Simulation;
+use crate::factions::{FactionId, MechanicData};
+use crate::fingerprint::Fingerprint;
+use crate::pathfinding::{CellType, NavGrid};
+use crate::simulation::{EntitySpawnParams, Simulation, StatChange};
+
+/// How a recorded command was handed to the simulation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IssueMode {
+    /// On its own, with [`Simulation::apply_command`].
+    #[default]
+    Apply,
+    /// With the other commands of the same batch, with
+    /// [`Simulation::apply_commands`], so group moves share flow fields.
+    Batch(u32),
+    /// Appended to the entity's queue with [`Simulation::queue_command`].
+    Queue,
+}
 
 /// A single command record for replay.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +43,8 @@ pub struct ReplayCommand {
     pub entity: EntityId,
     /// The command that was issued.
     pub command: Command,
+    /// How the command was issued.
+    pub mode: IssueMode,
 }
 
 impl ReplayCommand {
@@ -29,12 +55,157 @@ impl ReplayCommand {
             tick,
             entity,
             command,
+            mode: IssueMode::Apply,
+        }
+    }
+
+    /// Builder method to set how the command was issued.
+    #[must_use]
+    pub const fn with_mode(mut self, mode: IssueMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
+/// A change made to the simulation outside the command stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WorldEdit {
+    /// [`Simulation::spawn_entity`].
+    Spawn(Box<EntitySpawnParams>),
+    /// [`Simulation::despawn_entity`].
+    Despawn(EntityId),
+    /// Set a navigation grid cell.
+    SetCell {
+        /// Cell column.
+        x: u32,
+        /// Cell row.
+        y: u32,
+        /// New cell type.
+        cell: CellType,
+    },
+    /// [`Simulation::set_attack_target`].
+    SetAttackTarget {
+        /// Attacking entity.
+        attacker: EntityId,
+        /// Entity to attack.
+        target: EntityId,
+    },
+    /// [`Simulation::clear_attack_target`].
+    ClearAttackTarget(EntityId),
+    /// [`Simulation::set_tech_tree`].
+    SetTechTree {
+        /// Faction given the technologies.
+        faction: FactionId,
+        /// Technologies it can research.
+        techs: Vec<TechData>,
+    },
+    /// [`Simulation::set_faction_mechanics`].
+    SetFactionMechanics {
+        /// Faction given the mechanics.
+        faction: FactionId,
+        /// Its faction-unique rules.
+        mechanics: Vec<MechanicData>,
+    },
+    /// [`Simulation::queue_research`].
+    QueueResearch {
+        /// Researching facility.
+        building: EntityId,
+        /// Technology to research.
+        tech_id: String,
+    },
+    /// [`Simulation::cancel_research`].
+    CancelResearch(EntityId),
+    /// [`Simulation::order_construction`].
+    OrderConstruction {
+        /// Worker sent to build.
+        worker: EntityId,
+        /// The structure to put up.
+        params: Box<EntitySpawnParams>,
+        /// Cells it covers.
+        footprint: BuildingFootprint,
+        /// Ticks of work it takes.
+        build_time: u32,
+        /// Feedstock paid for it.
+        cost: u32,
+    },
+    /// [`Simulation::cancel_construction`].
+    CancelConstruction(EntityId),
+    /// [`Simulation::change_stats`].
+    ChangeStats {
+        /// Entity whose stats change.
+        entity: EntityId,
+        /// The new stats.
+        change: StatChange,
+    },
+}
+
+impl WorldEdit {
+    /// Make the same change to a simulation.
+    ///
+    /// # Errors
+    /// Returns the error of the underlying simulation call.
+    pub fn apply(&self, sim: &mut Simulation) -> Result<()> {
+        match self {
+            Self::Spawn(params) => {
+                sim.spawn_entity(params.as_ref().clone());
+                Ok(())
+            }
+            Self::Despawn(id) => sim.despawn_entity(*id),
+            Self::SetCell { x, y, cell } => {
+                if sim.nav_grid_mut().set_cell(*x, *y, *cell) {
+                    Ok(())
+                } else {
+                    Err(GameError::InvalidState(format!(
+                        "Nav cell ({x}, {y}) is out of bounds"
+                    )))
+                }
+            }
+            Self::SetAttackTarget { attacker, target } => sim.set_attack_target(*attacker, *target),
+            Self::ClearAttackTarget(id) => sim.clear_attack_target(*id),
+            Self::SetTechTree { faction, techs } => {
+                sim.set_tech_tree(*faction, techs.iter().cloned());
+                Ok(())
+            }
+            Self::SetFactionMechanics { faction, mechanics } => {
+                sim.set_faction_mechanics(*faction, mechanics.clone());
+                Ok(())
+            }
+            Self::QueueResearch { building, tech_id } => sim.queue_research(*building, tech_id),
+            Self::CancelResearch(building) => sim.cancel_research(*building).map(|_| ()),
+            Self::OrderConstruction {
+                worker,
+                params,
+                footprint,
+                build_time,
+                cost,
+            } => sim
+                .order_construction(
+                    *worker,
+                    params.as_ref().clone(),
+                    *footprint,
+                    *build_time,
+                    *cost,
+                )
+                .map(|_| ()),
+            Self::CancelConstruction(structure) => sim.cancel_construction(*structure).map(|_| ()),
+            Self::ChangeStats { entity, change } => sim.change_stats(*entity, change),
         }
     }
 }
 
+/// A world edit record for replay.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayEdit {
+    /// Simulation tick when the edit was made.
+    pub tick: u64,
+    /// Number of commands recorded before the edit.
+    pub after_commands: usize,
+    /// The edit.
+    pub edit: WorldEdit,
+}
+
 /// Replay file format version for compatibility.
-pub const REPLAY_VERSION: u32 = 1;
+pub const REPLAY_VERSION: u32 = 4;
 
 /// Complete replay data structure.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,14 +216,23 @@ pub struct Replay {
     pub scenario_id: String,
     /// Random seed used for the game.
     pub seed: u64,
+    /// Engine, data and map the game was recorded with.
+    pub fingerprint: Fingerprint,
     /// Serialized initial simulation state.
     pub initial_state: Vec<u8>,
+    /// Initial navigation grid, which the serialized state leaves out.
+    pub nav_grid: NavGrid,
     /// Stream of commands in tick order.
     pub commands: Vec<ReplayCommand>,
+    /// World edits in tick order.
+    pub edits: Vec<ReplayEdit>,
     /// Final tick when the game ended.
     pub final_tick: u64,
     /// Final state hash for verification.
     pub final_hash: u64,
+    /// Persistent IDs of the players, by name, for hosts that identify
+    /// them. Empty otherwise.
+    pub players: BTreeMap<String, u64>,
 }
 
 impl Replay {
@@ -67,19 +247,78 @@ impl Replay {
             version: REPLAY_VERSION,
             scenario_id: scenario_id.into(),
             seed,
+            fingerprint: Fingerprint::engine(),
             initial_state: state_bytes,
+            nav_grid: initial_state.nav_grid().clone(),
             commands: Vec::new(),
+            edits: Vec::new(),
             final_tick: 0,
             final_hash: 0,
+            players: BTreeMap::new(),
         })
     }
 
+    /// Builder method to record the full session fingerprint.
+    #[must_use]
+    pub fn with_fingerprint(mut self, fingerprint: Fingerprint) -> Self {
+        self.fingerprint = fingerprint;
+        self
+    }
+
+    /// Builder method to record who played, as persistent IDs by name.
+    #[must_use]
+    pub fn with_players(mut self, players: impl IntoIterator<Item = (String, u64)>) -> Self {
+        self.players = players.into_iter().collect();
+        self
+    }
+
+    /// Check the replay was recorded with the local data and map.
+    ///
+    /// The engine is already checked by [`load`](Self::load).
+    ///
+    /// # Errors
+    /// Returns an error naming each component that differs.
+    pub fn check_fingerprint(&self, local: &Fingerprint) -> Result<()> {
+        local.check(&self.fingerprint)
+    }
+
     /// Record a command for replay.
     pub fn record_command(&mut self, tick: u64, entity: EntityId, command: Command) {
         self.commands
             .push(ReplayCommand::new(tick, entity, command));
     }
 
+    /// Record a command appended to the entity's queue.
+    pub fn record_queued(&mut self, tick: u64, entity: EntityId, command: Command) {
+        self.commands
+            .push(ReplayCommand::new(tick, entity, command).with_mode(IssueMode::Queue));
+    }
+
+    /// Record commands applied together as one batch.
+    pub fn record_batch(&mut self, tick: u64, batch: &[(EntityId, Command)]) {
+        let id = self
+            .commands
+            .iter()
+            .rev()
+            .find_map(|cmd| match cmd.mode {
+                IssueMode::Batch(id) => Some(id + 1),
+                _ => None,
+            })
+            .unwrap_or(0);
+        self.commands.extend(batch.iter().map(|(entity, command)| {
+            ReplayCommand::new(tick, *entity, command.clone()).with_mode(IssueMode::Batch(id))
+        }));
+    }
+
+    /// Record a world edit, ordered after every command recorded so far.
+    pub fn record_edit(&mut self, tick: u64, edit: WorldEdit) {
+        self.edits.push(ReplayEdit {
+            tick,
+            after_commands: self.commands.len(),
+            edit,
+        });
+    }
+
     /// Finalize the replay with end-game state.
     pub fn finalize(&mut self, final_tick: u64, final_hash: u64) {
         self.final_tick = final_tick;
@@ -101,7 +340,8 @@ impl Replay {
     /// Load a replay from a file.
     ///
     /// # Errors
-    /// Returns an error if file reading or deserialization fails.
+    /// Returns an error if file reading or deserialization fails, or the
+    /// replay was recorded by a different engine version.
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
         let bytes = std::fs::read(path.as_ref())
             .map_err(|e| GameError::InvalidState(format!("Failed to read replay file: {}", e)))?;
@@ -115,6 +355,7 @@ impl Replay {
                 REPLAY_VERSION, replay.version
             )));
         }
+        Fingerprint::engine().check(&replay.fingerprint)?;
 
         Ok(replay)
     }
@@ -124,7 +365,9 @@ impl Replay {
     /// # Errors
     /// Returns an error if state deserialization fails.
     pub fn restore_initial_state(&self) -> Result<Simulation> {
-        Simulation::deserialize(&self.initial_state)
+        let mut sim = Simulation::deserialize(&self.initial_state)?;
+        *sim.nav_grid_mut() = self.nav_grid.clone();
+        Ok(sim)
     }
 
     /// Get commands for a specific tick.
@@ -149,6 +392,25 @@ impl Replay {
     }
 }
 
+/// Ticks between the snapshots a [`ReplayPlayer`] keeps for seeking, until
+/// it holds [`MAX_SEEK_SNAPSHOTS`].
+pub const SEEK_SNAPSHOT_INTERVAL: u64 = 200;
+
+/// Most seek snapshots a [`ReplayPlayer`] holds. Past this it drops every
+/// other one and snapshots half as often, so memory stays bounded however
+/// long the replay and the snapshots stay evenly spread over it.
+pub const MAX_SEEK_SNAPSHOTS: usize = 32;
+
+/// Playback state saved on the way through a replay, so seeking backwards
+/// re-simulates from the nearest earlier snapshot instead of tick 0.
+#[derive(Debug, Clone)]
+struct PlaybackSnapshot {
+    tick: u64,
+    simulation: Simulation,
+    command_index: usize,
+    edit_index: usize,
+}
+
 /// Replay playback controller.
 #[derive(Debug)]
 pub struct ReplayPlayer {
@@ -160,6 +422,13 @@ pub struct ReplayPlayer {
     current_tick: u64,
     /// Index into the command stream.
     command_index: usize,
+    /// Index into the world edits.
+    edit_index: usize,
+    /// Snapshots taken every `snapshot_interval` ticks, in tick order.
+    snapshots: Vec<PlaybackSnapshot>,
+    /// Ticks between snapshots: [`SEEK_SNAPSHOT_INTERVAL`], doubled each
+    /// time the snapshots are thinned out.
+    snapshot_interval: u64,
     /// Playback speed multiplier (1.0 = normal, 2.0 = 2x, 0.5 = half).
     pub playback_speed: f64,
     /// Whether playback is paused.
@@ -175,9 +444,12 @@ impl ReplayPlayer {
         let simulation = replay.restore_initial_state()?;
         Ok(Self {
             replay,
+            current_tick: simulation.get_tick(),
             simulation,
-            current_tick: 0,
             command_index: 0,
+            edit_index: 0,
+            snapshots: Vec::new(),
+            snapshot_interval: SEEK_SNAPSHOT_INTERVAL,
             playback_speed: 1.0,
             paused: false,
         })
@@ -191,56 +463,117 @@ impl ReplayPlayer {
             return self.current_tick < self.replay.final_tick;
         }
 
-        // Apply all commands for the current tick
-        while self.command_index < self.replay.commands.len() {
-            let cmd = &self.replay.commands[self.command_index];
-            if cmd.tick > self.current_tick {
-                break;
-            }
-            // Apply the command to the simulation
-            let _ = self
-                .simulation
-                .apply_command(cmd.entity, cmd.command.clone());
-            self.command_index += 1;
-        }
-
-        // Tick the simulation
-        self.simulation.tick();
-        self.current_tick += 1;
+        self.step();
 
         self.current_tick < self.replay.final_tick
     }
 
     /// Seek to a specific tick.
     ///
+    /// Seeking forward plays on from the current tick; seeking backwards
+    /// restores the nearest snapshot at or before `target_tick` (or the
+    /// initial state) and re-simulates from there.
+    ///
     /// # Errors
     /// Returns an error if state restoration fails.
     pub fn seek(&mut self, target_tick: u64) -> Result<()> {
-        // Reset to initial state
-        self.simulation = self.replay.restore_initial_state()?;
-        self.current_tick = 0;
-        self.command_index = 0;
+        if target_tick < self.current_tick {
+            let snapshot = self
+                .snapshots
+                .iter()
+                .rev()
+                .find(|snapshot| snapshot.tick <= target_tick);
+            if let Some(snapshot) = snapshot {
+                self.simulation = snapshot.simulation.clone();
+                self.current_tick = snapshot.tick;
+                self.command_index = snapshot.command_index;
+                self.edit_index = snapshot.edit_index;
+            } else {
+                self.simulation = self.replay.restore_initial_state()?;
+                self.current_tick = self.simulation.get_tick();
+                self.command_index = 0;
+                self.edit_index = 0;
+            }
+        }
 
         // Advance to target tick
         while self.current_tick < target_tick && self.current_tick < self.replay.final_tick {
-            // Apply commands for current tick
-            while self.command_index < self.replay.commands.len() {
-                let cmd = &self.replay.commands[self.command_index];
-                if cmd.tick > self.current_tick {
-                    break;
-                }
-                let _ = self
-                    .simulation
-                    .apply_command(cmd.entity, cmd.command.clone());
-                self.command_index += 1;
-            }
-            self.simulation.tick();
-            self.current_tick += 1;
+            self.step();
         }
 
         Ok(())
     }
 
+    /// Play one tick, snapshotting the result on interval boundaries not
+    /// yet covered, and thinning the snapshots out once there are more than
+    /// [`MAX_SEEK_SNAPSHOTS`].
+    fn step(&mut self) {
+        self.apply_inputs();
+        self.simulation.tick();
+        self.current_tick += 1;
+
+        let covered = self
+            .snapshots
+            .last()
+            .is_some_and(|snapshot| snapshot.tick >= self.current_tick);
+        if self.current_tick % self.snapshot_interval == 0 && !covered {
+            self.snapshots.push(PlaybackSnapshot {
+                tick: self.current_tick,
+                simulation: self.simulation.clone(),
+                command_index: self.command_index,
+                edit_index: self.edit_index,
+            });
+            if self.snapshots.len() > MAX_SEEK_SNAPSHOTS {
+                self.snapshot_interval *= 2;
+                let interval = self.snapshot_interval;
+                self.snapshots
+                    .retain(|snapshot| snapshot.tick % interval == 0);
+            }
+        }
+    }
+
+    /// Apply the edits and commands recorded up to the current tick, in
+    /// the order they were recorded.
+    fn apply_inputs(&mut self) {
+        let (replay, sim) = (&self.replay, &mut self.simulation);
+        loop {
+            if let Some(edit) = replay.edits.get(self.edit_index).filter(|edit| {
+                edit.tick <= self.current_tick && edit.after_commands <= self.command_index
+            }) {
+                let _ = edit.edit.apply(sim);
+                self.edit_index += 1;
+                continue;
+            }
+
+            let Some(cmd) = replay
+                .commands
+                .get(self.command_index)
+                .filter(|cmd| cmd.tick <= self.current_tick)
+            else {
+                break;
+            };
+            match cmd.mode {
+                IssueMode::Apply => {
+                    let _ = sim.apply_command(cmd.entity, cmd.command.clone());
+                    self.command_index += 1;
+                }
+                IssueMode::Queue => {
+                    let _ = sim.queue_command(cmd.entity, cmd.command.clone());
+                    self.command_index += 1;
+                }
+                IssueMode::Batch(id) => {
+                    let batch: Vec<_> = replay.commands[self.command_index..]
+                        .iter()
+                        .take_while(|next| next.mode == IssueMode::Batch(id))
+                        .map(|next| (next.entity, next.command.clone()))
+                        .collect();
+                    self.command_index += batch.len();
+                    let _ = sim.apply_commands(&batch);
+                }
+            }
+        }
+    }
+
     /// Get the current tick.
     #[must_use]
     pub const fn current_tick(&self) -> u64 {
@@ -280,6 +613,12 @@ impl ReplayPlayer {
         self.paused = !self.paused;
     }
 
+    /// Number of seek snapshots taken so far.
+    #[must_use]
+    pub fn snapshot_count(&self) -> usize {
+        self.snapshots.len()
+    }
+
     /// Set playback speed.
     pub fn set_speed(&mut self, speed: f64) {
         self.playback_speed = speed.clamp(0.1, 10.0);
@@ -333,6 +672,7 @@ mod tests {
         assert_eq!(replay.version, REPLAY_VERSION);
         assert_eq!(replay.scenario_id, "test_scenario");
         assert_eq!(replay.seed, 12345);
+        assert_eq!(replay.fingerprint, Fingerprint::engine());
         assert!(replay.commands.is_empty());
     }
 
@@ -373,7 +713,9 @@ mod tests {
     #[test]
     fn test_replay_save_load() {
         let sim = create_test_simulation();
-        let mut replay = Replay::new("test_scenario", 12345, &sim).unwrap();
+        let mut replay = Replay::new("test_scenario", 12345, &sim)
+            .unwrap()
+            .with_players([("alice".to_string(), 7)]);
 
         replay.record_command(0, 1, Command::Stop);
         replay.finalize(100, 0x12345678);
@@ -392,6 +734,7 @@ mod tests {
         assert_eq!(loaded.command_count(), 1);
         assert_eq!(loaded.duration(), 100);
         assert_eq!(loaded.final_hash, 0x12345678);
+        assert_eq!(loaded.players["alice"], 7);
 
         // Cleanup
         let _ = std::fs::remove_file(temp_path);
@@ -460,6 +803,74 @@ mod tests {
         assert_eq!(player.current_tick(), 10);
     }
 
+    #[test]
+    fn test_replay_player_seek_back_matches_straight_playback() {
+        let sim = create_test_simulation();
+        let mut replay = Replay::new("test_scenario", 12345, &sim).unwrap();
+        let target = Vec2Fixed::new(
+            crate::math::Fixed::from_num(900),
+            crate::math::Fixed::from_num(400),
+        );
+        replay.record_command(3, 1, Command::MoveTo(target));
+        replay.record_command(450, 1, Command::Stop);
+        replay.finalize(3 * SEEK_SNAPSHOT_INTERVAL, 0);
+
+        let mut straight = ReplayPlayer::new(replay.clone()).unwrap();
+        straight.seek(SEEK_SNAPSHOT_INTERVAL + 50).unwrap();
+        let expected = straight.simulation().state_hash();
+
+        let mut player = ReplayPlayer::new(replay).unwrap();
+        player.seek(3 * SEEK_SNAPSHOT_INTERVAL).unwrap();
+        assert_eq!(player.snapshot_count(), 3);
+
+        // Restores the tick 200 snapshot, then replays 50 ticks
+        player.seek(SEEK_SNAPSHOT_INTERVAL + 50).unwrap();
+        assert_eq!(player.current_tick(), SEEK_SNAPSHOT_INTERVAL + 50);
+        assert_eq!(player.simulation().state_hash(), expected);
+        assert_eq!(player.snapshot_count(), 3);
+
+        // Before the first snapshot falls back to the initial state
+        player.seek(10).unwrap();
+        let mut fresh = ReplayPlayer::new(player.replay().clone()).unwrap();
+        fresh.seek(10).unwrap();
+        assert_eq!(player.current_tick(), 10);
+        assert_eq!(
+            player.simulation().state_hash(),
+            fresh.simulation().state_hash()
+        );
+    }
+
+    #[test]
+    fn test_replay_player_thins_out_snapshots() {
+        let sim = create_test_simulation();
+        let mut replay = Replay::new("test_scenario", 12345, &sim).unwrap();
+        replay.record_command(
+            3,
+            1,
+            Command::MoveTo(Vec2Fixed::new(
+                crate::math::Fixed::from_num(900),
+                crate::math::Fixed::from_num(400),
+            )),
+        );
+        let length = 3 * MAX_SEEK_SNAPSHOTS as u64 * SEEK_SNAPSHOT_INTERVAL;
+        replay.finalize(length, 0);
+
+        let mut player = ReplayPlayer::new(replay).unwrap();
+        player.seek(length).unwrap();
+        assert!(player.snapshot_count() <= MAX_SEEK_SNAPSHOTS);
+        assert!(player.snapshot_count() >= MAX_SEEK_SNAPSHOTS / 2);
+
+        // Seeking back still lands on the same state as playing straight
+        let target = length / 2 + 7;
+        player.seek(target).unwrap();
+        let mut straight = ReplayPlayer::new(player.replay().clone()).unwrap();
+        straight.seek(target).unwrap();
+        assert_eq!(
+            player.simulation().state_hash(),
+            straight.simulation().state_hash()
+        );
+    }
+
     #[test]
     fn test_replay_player_pause() {
         let sim = create_test_simulation();
@@ -494,4 +905,67 @@ mod tests {
         player.seek(100).unwrap();
         assert!((player.progress_percent() - 100.0).abs() < 0.01);
     }
+
+    #[test]
+    fn test_replay_reproduces_edits_batches_and_queues() {
+        use crate::math::Fixed;
+
+        let at = |x: i32, y: i32| Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y));
+        let mut live = create_test_simulation();
+        let mut replay = Replay::new("client", 0, &live).unwrap();
+
+        // Tick 0: block a cell and spawn a second unit, then order both
+        let block = WorldEdit::SetCell {
+            x: 5,
+            y: 3,
+            cell: CellType::Blocked,
+        };
+        block.apply(&mut live).unwrap();
+        replay.record_edit(live.get_tick(), block);
+        let spawn = WorldEdit::Spawn(Box::new(EntitySpawnParams {
+            position: Some(at(40, 100)),
+            movement: Some(Fixed::from_num(4)),
+            collision_radius: Some(Fixed::from_num(5)),
+            ..Default::default()
+        }));
+        spawn.apply(&mut live).unwrap();
+        replay.record_edit(live.get_tick(), spawn);
+
+        let batch = [
+            (1, Command::MoveTo(at(300, 100))),
+            (2, Command::MoveTo(at(300, 120))),
+        ];
+        live.apply_commands(&batch);
+        replay.record_batch(live.get_tick(), &batch);
+        live.queue_command(2, Command::MoveTo(at(40, 40))).unwrap();
+        replay.record_queued(live.get_tick(), 2, Command::MoveTo(at(40, 40)));
+
+        for _ in 0..30 {
+            live.tick();
+        }
+        let despawn = WorldEdit::Despawn(1);
+        despawn.apply(&mut live).unwrap();
+        replay.record_edit(live.get_tick(), despawn);
+        for _ in 0..30 {
+            live.tick();
+        }
+        replay.finalize(live.get_tick(), live.state_hash());
+
+        assert_eq!(replay.commands[2].mode, IssueMode::Queue);
+        assert_eq!(replay.edits[2].after_commands, 3);
+
+        let path =
+            std::env::temp_dir().join(format!("test_replay_edits_{}.bin", std::process::id()));
+        replay.save(&path).unwrap();
+        let loaded = Replay::load(&path).unwrap();
+        let _ = std::fs::remove_file(path);
+
+        let mut player = ReplayPlayer::new(loaded).unwrap();
+        assert!(player.verify().unwrap());
+        assert!(player.simulation().get_entity(1).is_none());
+        assert_eq!(
+            player.simulation().nav_grid().get_cell(5, 3),
+            Some(CellType::Blocked)
+        );
+    }
 }
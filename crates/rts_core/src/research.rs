@@ -0,0 +1,369 @@
+//! Research and technology.
+//!
+//! Each faction has a [`TechState`]: the technologies it may research and
+//! the ones it has finished. Research happens at entities with a
+//! [`ResearchFacility`], one technology at a time per facility in queue
+//! order. Each tick the [`Simulation`](crate::simulation::Simulation):
+//! - advances the front of every constructed facility's queue,
+//! - marks finished technologies researched and applies their stat
+//!   modifiers to the faction's entities whose [`TechProfile`] matches,
+//! - reports starts, completions and cancellations as [`ResearchEvent`]s.
+//!
+//! Entities spawned later get the modifiers of everything already
+//! researched. The simulation does not hold player resources: the game
+//! layer pays a technology's cost before queueing it and refunds
+//! cancellations.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::EntityId;
+use crate::data::{TechData, TechEffect};
+use crate::factions::FactionId;
+
+/// Default number of technologies a facility can have queued.
+pub const DEFAULT_RESEARCH_QUEUE_SIZE: usize = 5;
+
+/// What technology effects see of an entity: its data ID and tags.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TechProfile {
+    /// Unit or building data ID (e.g. `"security_team"`).
+    pub kind: String,
+    /// Tags such as `"infantry"` or `"vehicle"`.
+    pub tags: Vec<String>,
+}
+
+impl TechProfile {
+    /// Create a profile.
+    #[must_use]
+    pub fn new(kind: impl Into<String>, tags: Vec<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            tags,
+        }
+    }
+
+    /// Whether `effect` applies to this entity.
+    ///
+    /// Effects without targets or tags apply to everything.
+    #[must_use]
+    pub fn matches(&self, effect: &TechEffect) -> bool {
+        if effect.applies_to.is_empty() && effect.applies_to_tags.is_empty() {
+            return true;
+        }
+        effect.applies_to.contains(&self.kind)
+            || effect
+                .applies_to_tags
+                .iter()
+                .any(|tag| self.tags.contains(tag))
+    }
+}
+
+/// A technology being researched or waiting in a facility's queue.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResearchItem {
+    /// Technology being researched.
+    pub tech_id: String,
+    /// Ticks of research done.
+    pub progress: u32,
+    /// Ticks of research needed.
+    pub total: u32,
+    /// Feedstock paid for the research, refunded on cancel.
+    pub cost: i32,
+}
+
+impl ResearchItem {
+    /// Research progress as a percentage (0-100).
+    #[must_use]
+    pub fn percentage(&self) -> u32 {
+        (self.progress * 100).checked_div(self.total).unwrap_or(100)
+    }
+
+    /// Whether the research is done.
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.progress >= self.total
+    }
+}
+
+/// Building that researches technologies.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResearchFacility {
+    /// Building data ID matched against [`TechData::researched_at`].
+    /// `None` researches any technology.
+    pub building: Option<String>,
+    /// Queued research, front first.
+    pub queue: VecDeque<ResearchItem>,
+    /// Maximum number of queued technologies.
+    pub max_queue_size: usize,
+}
+
+impl ResearchFacility {
+    /// Create a facility with an empty queue.
+    #[must_use]
+    pub fn new(building: Option<String>) -> Self {
+        Self {
+            building,
+            queue: VecDeque::new(),
+            max_queue_size: DEFAULT_RESEARCH_QUEUE_SIZE,
+        }
+    }
+
+    /// Whether this facility can research `tech`.
+    #[must_use]
+    pub fn hosts(&self, tech: &TechData) -> bool {
+        match (&self.building, &tech.researched_at) {
+            (Some(building), Some(at)) => building == at,
+            _ => true,
+        }
+    }
+
+    /// Whether `tech_id` is queued here.
+    #[must_use]
+    pub fn is_queued(&self, tech_id: &str) -> bool {
+        self.queue.iter().any(|item| item.tech_id == tech_id)
+    }
+
+    /// Whether the queue is full.
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.queue.len() >= self.max_queue_size
+    }
+
+    /// Research in progress, if any.
+    #[must_use]
+    pub fn current(&self) -> Option<&ResearchItem> {
+        self.queue.front()
+    }
+}
+
+/// A faction's technologies and research progress.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TechState {
+    /// Technologies the faction can research, by ID.
+    pub techs: BTreeMap<String, TechData>,
+    /// Technologies researched so far.
+    pub researched: BTreeSet<String>,
+}
+
+impl TechState {
+    /// Create a tech state from a faction's technologies.
+    #[must_use]
+    pub fn new(techs: impl IntoIterator<Item = TechData>) -> Self {
+        Self {
+            techs: techs.into_iter().map(|t| (t.id.clone(), t)).collect(),
+            researched: BTreeSet::new(),
+        }
+    }
+
+    /// Look up a technology.
+    #[must_use]
+    pub fn get(&self, tech_id: &str) -> Option<&TechData> {
+        self.techs.get(tech_id)
+    }
+
+    /// Whether `tech_id` has been researched.
+    #[must_use]
+    pub fn is_researched(&self, tech_id: &str) -> bool {
+        self.researched.contains(tech_id)
+    }
+
+    /// Check that `tech_id` may be researched now.
+    ///
+    /// Prerequisites that are not technologies are building requirements,
+    /// met when `has_building` returns true for them.
+    ///
+    /// # Errors
+    /// Returns why the technology cannot be researched.
+    pub fn check(
+        &self,
+        tech_id: &str,
+        has_building: impl Fn(&str) -> bool,
+    ) -> Result<&TechData, ResearchError> {
+        let tech = self.get(tech_id).ok_or(ResearchError::UnknownTech)?;
+        if self.is_researched(tech_id) {
+            return Err(ResearchError::AlreadyResearched);
+        }
+        let met = |id: &String| {
+            if self.techs.contains_key(id) {
+                self.is_researched(id)
+            } else {
+                has_building(id)
+            }
+        };
+        if !tech.prerequisites.iter().all(met) {
+            return Err(ResearchError::MissingPrerequisite);
+        }
+        let excluded = tech.exclusive_with.iter().any(|id| self.is_researched(id))
+            || self
+                .researched
+                .iter()
+                .filter_map(|id| self.get(id))
+                .any(|done| done.excludes(tech_id));
+        if excluded {
+            return Err(ResearchError::Excluded);
+        }
+        Ok(tech)
+    }
+}
+
+/// Errors that can occur when queueing or cancelling research.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResearchError {
+    /// The faction has no such technology.
+    UnknownTech,
+    /// The technology is already researched.
+    AlreadyResearched,
+    /// The technology is already queued at one of the faction's facilities.
+    AlreadyQueued,
+    /// A prerequisite technology or building is missing.
+    MissingPrerequisite,
+    /// A researched technology rules this one out.
+    Excluded,
+    /// The entity is not a research facility.
+    NotAFacility,
+    /// The technology is researched at a different building.
+    WrongFacility,
+    /// The facility's queue is full.
+    QueueFull,
+    /// The facility has nothing queued.
+    NothingQueued,
+}
+
+impl std::fmt::Display for ResearchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownTech => write!(f, "Unknown technology"),
+            Self::AlreadyResearched => write!(f, "Technology already researched"),
+            Self::AlreadyQueued => write!(f, "Technology already queued"),
+            Self::MissingPrerequisite => write!(f, "Prerequisites not met"),
+            Self::Excluded => write!(f, "Technology excluded by an earlier choice"),
+            Self::NotAFacility => write!(f, "Entity cannot research"),
+            Self::WrongFacility => write!(f, "Technology is researched elsewhere"),
+            Self::QueueFull => write!(f, "Research queue is full"),
+            Self::NothingQueued => write!(f, "No research queued"),
+        }
+    }
+}
+
+impl std::error::Error for ResearchError {}
+
+/// Events generated by the research system.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResearchEvent {
+    /// A facility started work on a technology.
+    ResearchStarted {
+        /// The researching facility.
+        building: EntityId,
+        /// Faction doing the research.
+        faction: FactionId,
+        /// Technology being researched.
+        tech_id: String,
+    },
+    /// A technology was researched and its effects applied.
+    ResearchComplete {
+        /// The researching facility.
+        building: EntityId,
+        /// Faction that gained the technology.
+        faction: FactionId,
+        /// Technology researched.
+        tech_id: String,
+    },
+    /// Queued research was cancelled.
+    ResearchCancelled {
+        /// The facility it was queued at.
+        building: EntityId,
+        /// Faction that queued it.
+        faction: FactionId,
+        /// Technology cancelled.
+        tech_id: String,
+        /// Feedstock to refund.
+        refund: i32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::TechEffectType;
+
+    fn tech(id: &str, prerequisites: &[&str], exclusive_with: &[&str]) -> TechData {
+        TechData {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            cost: 100,
+            research_time: 10,
+            effects: vec![],
+            prerequisites: prerequisites.iter().map(|s| s.to_string()).collect(),
+            tier: 1,
+            exclusive_with: exclusive_with.iter().map(|s| s.to_string()).collect(),
+            researched_at: Some("lab".to_string()),
+            is_doctrine: false,
+            branch: None,
+            icon: None,
+        }
+    }
+
+    #[test]
+    fn test_prerequisites_and_exclusions() {
+        let mut state = TechState::new([
+            tech("basic", &[], &[]),
+            tech("advanced", &["basic", "foundry"], &[]),
+            tech("left", &[], &["right"]),
+            tech("right", &[], &[]),
+        ]);
+        let no_buildings = |_: &str| false;
+
+        assert_eq!(
+            state.check("missing", no_buildings).err(),
+            Some(ResearchError::UnknownTech)
+        );
+        assert_eq!(
+            state.check("advanced", |b| b == "foundry").err(),
+            Some(ResearchError::MissingPrerequisite)
+        );
+
+        state.researched.insert("basic".to_string());
+        assert_eq!(
+            state.check("advanced", no_buildings).err(),
+            Some(ResearchError::MissingPrerequisite)
+        );
+        assert!(state.check("advanced", |b| b == "foundry").is_ok());
+        assert_eq!(
+            state.check("basic", no_buildings).err(),
+            Some(ResearchError::AlreadyResearched)
+        );
+
+        // Exclusion holds whichever side declares it
+        state.researched.insert("left".to_string());
+        assert_eq!(
+            state.check("right", no_buildings).err(),
+            Some(ResearchError::Excluded)
+        );
+    }
+
+    #[test]
+    fn test_profiles_match_targets_and_tags() {
+        let effect = |applies_to: &[&str], tags: &[&str]| TechEffect {
+            effect_type: TechEffectType::StatModifierPercent {
+                stat: "damage".to_string(),
+                percent: 10,
+            },
+            applies_to: applies_to.iter().map(|s| s.to_string()).collect(),
+            applies_to_tags: tags.iter().map(|s| s.to_string()).collect(),
+        };
+        let profile = TechProfile::new("security_team", vec!["infantry".to_string()]);
+
+        assert!(profile.matches(&effect(&[], &[])));
+        assert!(profile.matches(&effect(&["security_team"], &[])));
+        assert!(profile.matches(&effect(&[], &["infantry"])));
+        assert!(!profile.matches(&effect(&["tank"], &["vehicle"])));
+
+        let facility = ResearchFacility::new(Some("lab".to_string()));
+        assert!(facility.hosts(&tech("basic", &[], &[])));
+        assert!(!ResearchFacility::new(Some("forge".to_string())).hosts(&tech("basic", &[], &[])));
+        assert!(ResearchFacility::new(None).hosts(&tech("basic", &[], &[])));
+    }
+}
@@ -0,0 +1,205 @@
+//! Wrecks and salvage.
+//!
+//! Entities with a [`Salvageable`] value leave a [`Wreck`] where they die.
+//! Each tick the [`Simulation`](crate::simulation::Simulation):
+//! - removes wrecks older than the [`SalvageRules::lifetime`],
+//! - lets every [`Salvager`] collect from the closest wreck within
+//!   [`SalvageRules::radius`], at half rate while it has an attack target,
+//! - clears wrecks that have been picked clean.
+//!
+//! Anyone may salvage any wreck, including their own side's. The simulation
+//! does not hold player resources, so gains are reported as
+//! [`SalvageEvent`]s for the game layer to pay out.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::{DebugName, EntityId, FactionMember};
+use crate::factions::FactionId;
+use crate::math::{fixed_serde, Fixed, Vec2Fixed};
+
+/// Share of a unit's cost its wreck is worth (percent).
+pub const SALVAGE_PERCENT: u32 = 25;
+
+/// Default radius within which salvagers collect from wrecks.
+pub const DEFAULT_SALVAGE_RADIUS: i32 = 100;
+
+/// Default time a wreck lasts before it decays (ticks).
+pub const DEFAULT_WRECK_LIFETIME: u64 = 600;
+
+/// Salvage value of a unit costing `cost`.
+#[must_use]
+pub const fn salvage_value(cost: u32) -> u32 {
+    cost * SALVAGE_PERCENT / 100
+}
+
+/// Salvage collected per tick by a unit of the given tier.
+///
+/// Tier 1 collects 1 per tick, tier 2 collects 2 and tier 3 collects 4.
+#[must_use]
+pub const fn salvage_rate_for_tier(tier: u32) -> u32 {
+    match tier {
+        2 => 2,
+        3 => 4,
+        _ => 1,
+    }
+}
+
+/// Salvage an entity leaves behind when it dies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Salvageable {
+    /// Value of the wreck.
+    pub value: u32,
+}
+
+impl Salvageable {
+    /// Create a salvageable component.
+    #[must_use]
+    pub const fn new(value: u32) -> Self {
+        Self { value }
+    }
+}
+
+/// Collects salvage from nearby wrecks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Salvager {
+    /// Salvage collected per tick.
+    pub rate: u32,
+}
+
+impl Salvager {
+    /// Create a salvager component.
+    #[must_use]
+    pub const fn new(rate: u32) -> Self {
+        Self { rate }
+    }
+
+    /// Salvage collected this tick, halved (but at least 1) in combat.
+    #[must_use]
+    pub const fn rate_for(&self, in_combat: bool) -> u32 {
+        if in_combat && self.rate > 1 {
+            self.rate / 2
+        } else {
+            self.rate
+        }
+    }
+}
+
+/// Unique identifier for a wreck.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub struct WreckId(pub u32);
+
+impl fmt::Display for WreckId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "wreck#{}", self.0)
+    }
+}
+
+/// Remains of a dead entity that can be salvaged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Wreck {
+    /// Where the entity died.
+    pub position: Vec2Fixed,
+    /// Salvage left to collect.
+    pub remaining: u32,
+    /// Tick the wreck appeared on.
+    pub spawned_at: u64,
+    /// Owner of the entity that died.
+    pub owner: Option<FactionMember>,
+    /// Debug name of the entity that died.
+    pub name: Option<DebugName>,
+}
+
+impl Wreck {
+    /// Whether the wreck has decayed by `tick` under `rules`.
+    #[must_use]
+    pub const fn is_expired(&self, tick: u64, rules: &SalvageRules) -> bool {
+        tick.saturating_sub(self.spawned_at) >= rules.lifetime
+    }
+}
+
+/// How wrecks are collected and how long they last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SalvageRules {
+    /// Distance within which salvagers collect from a wreck.
+    #[serde(with = "fixed_serde")]
+    pub radius: Fixed,
+    /// Ticks before a wreck decays.
+    pub lifetime: u64,
+}
+
+impl Default for SalvageRules {
+    fn default() -> Self {
+        Self {
+            radius: Fixed::from_num(DEFAULT_SALVAGE_RADIUS),
+            lifetime: DEFAULT_WRECK_LIFETIME,
+        }
+    }
+}
+
+/// Events generated by the salvage system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SalvageEvent {
+    /// A dying entity left a wreck.
+    WreckSpawned {
+        /// The new wreck.
+        wreck: WreckId,
+        /// The entity that died.
+        entity: EntityId,
+        /// Salvage the wreck holds.
+        value: u32,
+    },
+    /// A salvager collected from a wreck.
+    Collected {
+        /// The wreck collected from.
+        wreck: WreckId,
+        /// The collecting entity.
+        collector: EntityId,
+        /// Faction of the collector, who should be paid.
+        faction: Option<FactionId>,
+        /// Amount collected.
+        amount: u32,
+    },
+    /// A wreck was picked clean or decayed.
+    WreckCleared {
+        /// The wreck removed.
+        wreck: WreckId,
+        /// Salvage left uncollected.
+        remaining: u32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_salvage_value_and_rates() {
+        assert_eq!(salvage_value(100), 25);
+        assert_eq!(salvage_value(3), 0);
+        assert_eq!(salvage_rate_for_tier(1), 1);
+        assert_eq!(salvage_rate_for_tier(3), 4);
+        assert_eq!(salvage_rate_for_tier(9), 1);
+
+        assert_eq!(Salvager::new(4).rate_for(true), 2);
+        assert_eq!(Salvager::new(1).rate_for(true), 1);
+        assert_eq!(Salvager::new(4).rate_for(false), 4);
+    }
+
+    #[test]
+    fn test_wreck_expiry() {
+        let rules = SalvageRules::default();
+        let wreck = Wreck {
+            position: Vec2Fixed::ZERO,
+            remaining: 10,
+            spawned_at: 100,
+            owner: None,
+            name: None,
+        };
+        assert!(!wreck.is_expired(100 + DEFAULT_WRECK_LIFETIME - 1, &rules));
+        assert!(wreck.is_expired(100 + DEFAULT_WRECK_LIFETIME, &rules));
+    }
+}
@@ -39,32 +39,61 @@
 //! ```
 
 use std::collections::hash_map::DefaultHasher;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::hash::{Hash, Hasher};
 
 use serde::{Deserialize, Serialize};
 
+use crate::abilities::{Abilities, AbilityTarget, AbilityUsed, Shield};
+use crate::buildings::{
+    can_place_building, footprint_center, footprint_origin, is_builder, place_building,
+    remove_building, BuildingFootprint, ConstructionEvent, ConstructionSite, PlacementGrid,
+    SITE_SEARCH_RADIUS,
+};
+use crate::capacity::{CapacityPressure, EntityCapacity};
+use crate::combat::{
+    calculate_resistance_damage, ExtendedDamageType, HighGroundRules, SplashEvent, WeaponStats,
+    WeaponTargets, MAX_RESISTANCE,
+};
 use crate::components::{
-    AttackTarget, CombatStats, Command, CommandQueue, EntityId, FactionMember, Health, Movement,
-    PatrolState, Position, Projectile, Velocity,
+    Ammunition, Armament, AttackTarget, Collider, CombatStats, Command, CommandQueue, Cosmetic,
+    DebugName, Detector, EntityId, FactionMember, Health, IdleBehavior, IdleMode, Movement,
+    MovementDomain, PatrolState, Position, Projectile, Regeneration, Resupply, Stealth, Velocity,
+    Weapon, IDLE_FACINGS,
 };
+use crate::data::{AbilityData, AbilityEffect, AbilityTargetType, TechData, TechEffectType};
 use crate::economy::Depot;
 use crate::error::{GameError, Result};
-use crate::factions::FactionId;
+use crate::factions::{FactionId, FactionMechanics, MechanicData};
+use crate::fog::{sight_range, FogOfWar};
+use crate::market::Marketplace;
 use crate::math::{Fixed, Vec2Fixed};
-use crate::pathfinding::{find_path, NavGrid};
+use crate::notifications::{AttackAlerts, BlockReason, Notification};
+use crate::obstacles::{Obstacle, ObstacleEvent, ObstacleKind};
+use crate::outcome::WinCondition;
+use crate::pathfinding::{find_path, FlowField, NavGrid, FLOW_FIELD_GROUP_SIZE};
 use crate::production::{
-    production_system, Building as ProductionBuilding, ProductionEvent, ProductionQueue,
+    production_system, Building as ProductionBuilding, BuildingTypeId, ProductionEvent,
+    ProductionQueue,
 };
+use crate::repair::{RepairEvent, Repairer, REPAIR_RANGE};
+use crate::research::{
+    ResearchError, ResearchEvent, ResearchFacility, ResearchItem, TechProfile, TechState,
+};
+use crate::rng::SimRng;
+use crate::salvage::{SalvageEvent, SalvageRules, Salvageable, Salvager, Wreck, WreckId};
+use crate::squad::{centroid, Squad, SquadCommand, SquadHealth, SquadId};
 use crate::systems::{
-    command_processing_system, health_system, movement_system, CombatEvent, DamageEvent,
-    PositionLookup,
+    avoidance_system, command_processing_system, health_system, movement_system, AvoidanceBody,
+    CombatEvent, DamageEvent, PositionLookup,
 };
+use crate::transport::{Cargo, BOARDING_RANGE};
+use crate::triggers::{ScenarioScript, TriggerFired};
 
 /// Serde support for `Option<Fixed>`.
 mod option_fixed_serde {
     use crate::math::Fixed;
-    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::{Deserialize, Deserializer, Serializer};
 
     /// Serialize an optional fixed-point number.
     pub fn serialize<S>(value: &Option<Fixed>, serializer: S) -> Result<S::Ok, S::Error>
@@ -72,7 +101,7 @@ mod option_fixed_serde {
         S: Serializer,
     {
         match value {
-            Some(v) => v.to_bits().serialize(serializer),
+            Some(v) => serializer.serialize_some(&v.to_bits()),
             None => serializer.serialize_none(),
         }
     }
@@ -93,11 +122,18 @@ pub const TICK_RATE: u32 = 20;
 /// Duration of one tick in milliseconds.
 pub const TICK_DURATION_MS: u32 = 1000 / TICK_RATE;
 
+/// Idle wandering moves at this fraction of a unit's speed.
+const IDLE_SPEED_DIVISOR: i32 = 4;
+
 /// An entity with optional components.
 ///
 /// Entities are composed of optional components. Only components that are
 /// `Some` are active for this entity. This allows flexible entity composition
 /// without a full ECS framework.
+///
+/// The components most systems read every tick sit on the entity itself and
+/// get a column each in [`EntityStorage`]. The rest travel together in
+/// [`row`](Self::row), which the entity derefs to.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Entity {
     /// Unique identifier for this entity.
@@ -108,14 +144,27 @@ pub struct Entity {
     pub velocity: Option<Velocity>,
     /// Health for damageable entities.
     pub health: Option<Health>,
-    /// Command queue for controllable units.
-    pub command_queue: Option<CommandQueue>,
     /// Movement capabilities.
     pub movement: Option<Movement>,
-    /// Attack target tracking.
-    pub attack_target: Option<AttackTarget>,
     /// Combat statistics.
     pub combat_stats: Option<CombatStats>,
+    /// Faction membership for ownership.
+    pub faction: Option<FactionMember>,
+    /// Every other component.
+    pub row: EntityRow,
+}
+
+/// The components of an entity that [`EntityStorage`] keeps together in
+/// one row rather than in columns of their own.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityRow {
+    /// Command queue for controllable units.
+    pub command_queue: Option<CommandQueue>,
+    /// Attack target tracking.
+    pub attack_target: Option<AttackTarget>,
+    /// Every weapon, on units that carry more than one.
+    #[serde(default)]
+    pub armament: Option<Armament>,
     /// Production queue for buildings.
     pub production_queue: Option<ProductionQueue>,
     /// Patrol state for units executing patrol commands.
@@ -124,8 +173,6 @@ pub struct Entity {
     pub building: Option<ProductionBuilding>,
     /// Projectile data for projectile entities.
     pub projectile: Option<Projectile>,
-    /// Faction membership for ownership.
-    pub faction: Option<FactionMember>,
     /// Marker for depot buildings.
     pub depot: Option<Depot>,
     /// Waypoints for path-following movement.
@@ -133,6 +180,77 @@ pub struct Entity {
     /// Vision range for visibility calculations. If None, uses 2× attack range.
     #[serde(default, with = "option_fixed_serde")]
     pub vision_range: Option<Fixed>,
+    /// Ammunition for units under logistics rules. If None, fire is unlimited.
+    #[serde(default)]
+    pub ammunition: Option<Ammunition>,
+    /// Resupply source that refills nearby friendly ammunition.
+    #[serde(default)]
+    pub resupply: Option<Resupply>,
+    /// Out-of-combat health regeneration.
+    #[serde(default)]
+    pub regeneration: Option<Regeneration>,
+    /// Marks a purely visual entity that may be culled under capacity pressure.
+    #[serde(default)]
+    pub cosmetic: Option<Cosmetic>,
+    /// Decorative behavior while the unit has no orders.
+    #[serde(default)]
+    pub idle_behavior: Option<IdleBehavior>,
+    /// Hidden from enemies that have no detector in range.
+    #[serde(default)]
+    pub stealth: Option<Stealth>,
+    /// Reveals stealthed enemies nearby.
+    #[serde(default)]
+    pub detector: Option<Detector>,
+    /// Body size for local avoidance between moving units.
+    #[serde(default)]
+    pub collider: Option<Collider>,
+    /// Salvage left behind as a wreck on death.
+    #[serde(default)]
+    pub salvageable: Option<Salvageable>,
+    /// Collects salvage from nearby wrecks.
+    #[serde(default)]
+    pub salvager: Option<Salvager>,
+    /// Data ID and tags that technology effects match against.
+    #[serde(default)]
+    pub tech_profile: Option<TechProfile>,
+    /// Research queue for buildings that research technologies.
+    #[serde(default)]
+    pub research: Option<ResearchFacility>,
+    /// Active abilities and their cooldowns.
+    #[serde(default)]
+    pub abilities: Option<Abilities>,
+    /// Damage-absorbing shield from an ability.
+    #[serde(default)]
+    pub shield: Option<Shield>,
+    /// Hold for passengers, on transports.
+    #[serde(default)]
+    pub cargo: Option<Cargo>,
+    /// Transport the unit is riding in, if any.
+    #[serde(default)]
+    pub embarked: Option<EntityId>,
+    /// Restores the health of damaged allies.
+    #[serde(default)]
+    pub repairer: Option<Repairer>,
+    /// Ground held by a structure put up by workers.
+    #[serde(default)]
+    pub site: Option<ConstructionSite>,
+    /// Marker for market buildings, where the owner can trade.
+    #[serde(default)]
+    pub market: Option<Marketplace>,
+    /// Ground blocked by a destructible terrain obstacle.
+    #[serde(default)]
+    pub obstacle: Option<Obstacle>,
+    /// Debug label for logs. Not hashed.
+    #[serde(default)]
+    pub debug_name: Option<DebugName>,
+}
+
+impl EntityRow {
+    /// The order the entity is carrying out, if any.
+    #[must_use]
+    pub fn current_command(&self) -> Option<&Command> {
+        self.command_queue.as_ref().and_then(CommandQueue::current)
+    }
 }
 
 impl Entity {
@@ -144,27 +262,191 @@ impl Entity {
             position: None,
             velocity: None,
             health: None,
-            command_queue: None,
             movement: None,
-            attack_target: None,
             combat_stats: None,
-            production_queue: None,
-            patrol_state: None,
-            building: None,
-            projectile: None,
             faction: None,
-            depot: None,
-            path_waypoints: None,
-            vision_range: None,
+            row: EntityRow::default(),
+        }
+    }
+
+    /// Borrow the entity as storage hands it out.
+    #[must_use]
+    pub fn view(&self) -> EntityRef<'_> {
+        EntityRef {
+            id: self.id,
+            position: self.position,
+            velocity: self.velocity,
+            health: self.health,
+            movement: self.movement,
+            combat_stats: self.combat_stats,
+            faction: self.faction,
+            row: &self.row,
+        }
+    }
+
+    /// Borrow the entity mutably, as storage hands it out.
+    #[must_use]
+    pub fn view_mut(&mut self) -> EntityMut<'_> {
+        EntityMut {
+            id: self.id,
+            position: &mut self.position,
+            velocity: &mut self.velocity,
+            health: &mut self.health,
+            movement: &mut self.movement,
+            combat_stats: &mut self.combat_stats,
+            faction: &mut self.faction,
+            row: &mut self.row,
+        }
+    }
+
+    /// The layer the entity moves in; anything without movement stands on
+    /// the ground.
+    #[must_use]
+    pub fn domain(&self) -> MovementDomain {
+        self.view().domain()
+    }
+}
+
+impl std::ops::Deref for Entity {
+    type Target = EntityRow;
+
+    fn deref(&self) -> &EntityRow {
+        &self.row
+    }
+}
+
+impl std::ops::DerefMut for Entity {
+    fn deref_mut(&mut self) -> &mut EntityRow {
+        &mut self.row
+    }
+}
+
+/// A stored entity, as [`EntityStorage::get`] hands it out: its column
+/// components copied out and its [`EntityRow`] borrowed, which the view
+/// derefs to.
+///
+/// Serializes like the [`Entity`] it views.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct EntityRef<'a> {
+    /// Unique identifier for this entity.
+    pub id: EntityId,
+    /// World position.
+    pub position: Option<Position>,
+    /// Velocity for movement.
+    pub velocity: Option<Velocity>,
+    /// Health for damageable entities.
+    pub health: Option<Health>,
+    /// Movement capabilities.
+    pub movement: Option<Movement>,
+    /// Combat statistics.
+    pub combat_stats: Option<CombatStats>,
+    /// Faction membership for ownership.
+    pub faction: Option<FactionMember>,
+    /// Every other component, borrowed for as long as the storage is.
+    pub row: &'a EntityRow,
+}
+
+impl EntityRef<'_> {
+    /// The layer the entity moves in; anything without movement stands on
+    /// the ground.
+    #[must_use]
+    pub fn domain(&self) -> MovementDomain {
+        self.movement.map_or(MovementDomain::Ground, |m| m.domain)
+    }
+
+    /// Copy the entity out of storage.
+    #[must_use]
+    pub fn to_entity(&self) -> Entity {
+        Entity {
+            id: self.id,
+            position: self.position,
+            velocity: self.velocity,
+            health: self.health,
+            movement: self.movement,
+            combat_stats: self.combat_stats,
+            faction: self.faction,
+            row: self.row.clone(),
+        }
+    }
+}
+
+impl std::ops::Deref for EntityRef<'_> {
+    type Target = EntityRow;
+
+    fn deref(&self) -> &EntityRow {
+        self.row
+    }
+}
+
+/// A stored entity borrowed mutably, as [`EntityStorage::get_mut`] hands it
+/// out: each column component in place and its [`EntityRow`], which the
+/// view derefs to.
+///
+/// Deref borrows the whole view, so code that holds a column component
+/// and a row component at once reaches the row through [`row`](Self::row).
+#[derive(Debug)]
+pub struct EntityMut<'a> {
+    /// Unique identifier for this entity.
+    pub id: EntityId,
+    /// World position.
+    pub position: &'a mut Option<Position>,
+    /// Velocity for movement.
+    pub velocity: &'a mut Option<Velocity>,
+    /// Health for damageable entities.
+    pub health: &'a mut Option<Health>,
+    /// Movement capabilities.
+    pub movement: &'a mut Option<Movement>,
+    /// Combat statistics.
+    pub combat_stats: &'a mut Option<CombatStats>,
+    /// Faction membership for ownership.
+    pub faction: &'a mut Option<FactionMember>,
+    /// Every other component.
+    pub row: &'a mut EntityRow,
+}
+
+impl EntityMut<'_> {
+    /// Borrow the entity immutably.
+    #[must_use]
+    pub fn view(&self) -> EntityRef<'_> {
+        EntityRef {
+            id: self.id,
+            position: *self.position,
+            velocity: *self.velocity,
+            health: *self.health,
+            movement: *self.movement,
+            combat_stats: *self.combat_stats,
+            faction: *self.faction,
+            row: self.row,
         }
     }
+
+    /// The layer the entity moves in; anything without movement stands on
+    /// the ground.
+    #[must_use]
+    pub fn domain(&self) -> MovementDomain {
+        self.view().domain()
+    }
+}
+
+impl std::ops::Deref for EntityMut<'_> {
+    type Target = EntityRow;
+
+    fn deref(&self) -> &EntityRow {
+        self.row
+    }
+}
+
+impl std::ops::DerefMut for EntityMut<'_> {
+    fn deref_mut(&mut self) -> &mut EntityRow {
+        self.row
+    }
 }
 
 /// Parameters for spawning a new entity.
 ///
 /// Use this struct to specify which components the new entity should have.
 /// All fields are optional - only provide the components you need.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EntitySpawnParams {
     /// Initial position in world space.
     pub position: Option<Vec2Fixed>,
@@ -173,38 +455,114 @@ pub struct EntitySpawnParams {
     /// Maximum health (entity starts at full health).
     pub health: Option<u32>,
     /// Movement speed (units per tick).
+    #[serde(with = "option_fixed_serde")]
     pub movement: Option<Fixed>,
+    /// Layer a moving entity travels in.
+    #[serde(default)]
+    pub domain: MovementDomain,
     /// Combat statistics.
     pub combat_stats: Option<CombatStats>,
-    /// Whether this entity has a production queue.
+    /// Weapons mounted beside the main one in `combat_stats`.
+    #[serde(default)]
+    pub weapons: Vec<Weapon>,
+    /// Whether this entity has a production queue. Such entities are
+    /// finished buildings that can be given a rally point.
     pub has_production_queue: bool,
     /// Faction membership for the entity.
     pub faction: Option<FactionMember>,
     /// Whether this entity is a depot.
     pub is_depot: bool,
+    /// Whether this entity is a market.
+    pub is_market: bool,
     /// Vision range for visibility calculations.
+    #[serde(with = "option_fixed_serde")]
     pub vision_range: Option<Fixed>,
+    /// Ammunition capacity (entity starts with a full magazine).
+    pub ammunition: Option<u32>,
+    /// Resupply source for nearby friendly units.
+    pub resupply: Option<Resupply>,
+    /// Out-of-combat health regeneration.
+    pub regeneration: Option<Regeneration>,
+    /// Cosmetic marker for purely visual entities.
+    pub cosmetic: Option<Cosmetic>,
+    /// Decorative behavior while the unit has no orders.
+    pub idle_behavior: Option<IdleBehavior>,
+    /// Whether this entity is stealthed.
+    pub is_stealthed: bool,
+    /// Range within which this entity reveals stealthed enemies.
+    #[serde(with = "option_fixed_serde")]
+    pub detection_range: Option<Fixed>,
+    /// Radius of the entity's body for local avoidance.
+    #[serde(with = "option_fixed_serde")]
+    pub collision_radius: Option<Fixed>,
+    /// Value of the wreck the entity leaves when it dies.
+    pub salvage_value: Option<u32>,
+    /// Salvage the entity collects per tick from nearby wrecks.
+    pub salvage_rate: Option<u32>,
+    /// Data ID and tags that technology effects match against.
+    pub tech_profile: Option<TechProfile>,
+    /// Research facility, for buildings that research technologies.
+    pub research: Option<ResearchFacility>,
+    /// Active abilities (empty for none).
+    pub abilities: Vec<AbilityData>,
+    /// Hold for passengers, on transports.
+    pub cargo: Option<Cargo>,
+    /// Repair or healing the entity gives its allies.
+    pub repairer: Option<Repairer>,
+    /// Kind used to generate the entity's [`DebugName`] (e.g. `"security_team"`).
+    pub debug_kind: Option<String>,
 }
 
 /// Storage for all entities in the simulation.
 ///
-/// Uses a `HashMap` for O(1) entity lookup by ID, with deterministic
-/// iteration via sorted keys when processing systems.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// Entities are stored in ID order, one row each, split into parallel
+/// columns: position, velocity, health, movement, combat stats and faction
+/// each get a column, and the remaining components of each entity share an
+/// [`EntityRow`] column. Systems scan just the [`Columns`] they need, front
+/// to back, so they visit entities in deterministic order without sorting,
+/// hashing or allocating.
+///
+/// An index from ID to row gives O(1) lookups. A removed entity leaves a
+/// gap in its row that is compacted away once gaps outnumber the live
+/// entities; compaction also rebuilds the index to start at the oldest live
+/// ID, so it spans the IDs still in play rather than every ID ever handed
+/// out.
+///
+/// Serializes as a map from ID to entity, in ID order.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(from = "StoredEntities")]
 pub struct EntityStorage {
-    /// Map of entity ID to entity data.
-    entities: HashMap<EntityId, Entity>,
+    /// ID of each row, ascending.
+    ids: Vec<EntityId>,
+    positions: Vec<Option<Position>>,
+    velocities: Vec<Option<Velocity>>,
+    health: Vec<Option<Health>>,
+    movement: Vec<Option<Movement>>,
+    combat_stats: Vec<Option<CombatStats>>,
+    factions: Vec<Option<FactionMember>>,
+    /// Remaining components of each row; None once removed.
+    rows: Vec<Option<EntityRow>>,
+    /// Row of each ID from `base` up, indexed by offset from `base`.
+    index: Vec<Option<u32>>,
+    /// First ID covered by `index`.
+    base: EntityId,
+    /// Number of live entities.
+    len: usize,
     /// Next entity ID to assign.
     next_id: EntityId,
 }
 
+/// Gaps tolerated before [`EntityStorage`] compacts its rows regardless of
+/// how many entities are live.
+const MIN_COMPACT_GAPS: usize = 64;
+
 impl EntityStorage {
     /// Create empty entity storage.
     #[must_use]
     pub fn new() -> Self {
         Self {
-            entities: HashMap::new(),
             next_id: 1,
+            ..Default::default()
         }
     }
 
@@ -213,60 +571,382 @@ impl EntityStorage {
         let id = self.next_id;
         self.next_id += 1;
         entity.id = id;
-        self.entities.insert(id, entity);
+        self.push(entity);
         id
     }
 
+    /// Append an entity whose ID is above every stored one.
+    fn push(&mut self, entity: Entity) {
+        debug_assert!(self.ids.last().map_or(true, |&last| last < entity.id));
+        if self.index.is_empty() {
+            self.base = entity.id;
+        }
+        let slot = self.slot(entity.id).expect("entity IDs fit in memory");
+        if self.index.len() <= slot {
+            self.index.resize(slot + 1, None);
+        }
+        let row = u32::try_from(self.rows.len()).expect("fewer than u32::MAX rows");
+        self.index[slot] = Some(row);
+        self.ids.push(entity.id);
+        self.positions.push(entity.position);
+        self.velocities.push(entity.velocity);
+        self.health.push(entity.health);
+        self.movement.push(entity.movement);
+        self.combat_stats.push(entity.combat_stats);
+        self.factions.push(entity.faction);
+        self.rows.push(Some(entity.row));
+        self.len += 1;
+    }
+
     /// Remove an entity by ID.
     pub fn remove(&mut self, id: EntityId) -> Option<Entity> {
-        self.entities.remove(&id)
+        let row = self.row(id)?;
+        let entity = Entity {
+            row: self.rows[row].take()?,
+            id,
+            position: self.positions[row].take(),
+            velocity: self.velocities[row].take(),
+            health: self.health[row].take(),
+            movement: self.movement[row].take(),
+            combat_stats: self.combat_stats[row].take(),
+            faction: self.factions[row].take(),
+        };
+        if let Some(slot) = self.slot(id) {
+            self.index[slot] = None;
+        }
+        self.len -= 1;
+        let gaps = self.rows.len() - self.len;
+        if gaps > self.len.max(MIN_COMPACT_GAPS) {
+            self.compact();
+        }
+        Some(entity)
+    }
+
+    /// Drop the gaps left by removed entities from every column and
+    /// rebuild the index from the oldest live ID up.
+    fn compact(&mut self) {
+        let live: Vec<bool> = self.rows.iter().map(Option::is_some).collect();
+        retain_live(&mut self.ids, &live);
+        retain_live(&mut self.positions, &live);
+        retain_live(&mut self.velocities, &live);
+        retain_live(&mut self.health, &live);
+        retain_live(&mut self.movement, &live);
+        retain_live(&mut self.combat_stats, &live);
+        retain_live(&mut self.factions, &live);
+        retain_live(&mut self.rows, &live);
+
+        self.index.clear();
+        self.base = self.ids.first().copied().unwrap_or(self.next_id);
+        let span = self.ids.last().map_or(0, |&last| last - self.base + 1);
+        self.index.resize(
+            usize::try_from(span).expect("entity IDs fit in memory"),
+            None,
+        );
+        for (row, &id) in self.ids.iter().enumerate() {
+            let slot = usize::try_from(id - self.base).expect("entity IDs fit in memory");
+            self.index[slot] = u32::try_from(row).ok();
+        }
+        self.index.shrink_to_fit();
+    }
+
+    /// Index slot of an ID.
+    fn slot(&self, id: EntityId) -> Option<usize> {
+        usize::try_from(id.checked_sub(self.base)?).ok()
+    }
+
+    /// Row holding an ID, if it is stored.
+    fn row(&self, id: EntityId) -> Option<usize> {
+        let row = (*self.index.get(self.slot(id)?)?)?;
+        usize::try_from(row).ok()
     }
 
     /// Get an entity by ID.
     #[must_use]
-    pub fn get(&self, id: EntityId) -> Option<&Entity> {
-        self.entities.get(&id)
+    pub fn get(&self, id: EntityId) -> Option<EntityRef<'_>> {
+        self.columns().entity(self.row(id)?)
     }
 
     /// Get a mutable reference to an entity by ID.
-    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut Entity> {
-        self.entities.get_mut(&id)
+    pub fn get_mut(&mut self, id: EntityId) -> Option<EntityMut<'_>> {
+        let row = self.row(id)?;
+        Some(EntityMut {
+            id,
+            row: self.rows[row].as_mut()?,
+            position: &mut self.positions[row],
+            velocity: &mut self.velocities[row],
+            health: &mut self.health[row],
+            movement: &mut self.movement[row],
+            combat_stats: &mut self.combat_stats[row],
+            faction: &mut self.factions[row],
+        })
     }
 
     /// Check if an entity exists.
     #[must_use]
     pub fn contains(&self, id: EntityId) -> bool {
-        self.entities.contains_key(&id)
+        self.row(id).is_some()
     }
 
     /// Get the number of entities.
     #[must_use]
     pub fn len(&self) -> usize {
-        self.entities.len()
+        self.len
     }
 
     /// Check if storage is empty.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.entities.is_empty()
+        self.len == 0
     }
 
     /// Get sorted entity IDs for deterministic iteration.
     #[must_use]
     pub fn sorted_ids(&self) -> Vec<EntityId> {
-        let mut ids: Vec<_> = self.entities.keys().copied().collect();
-        ids.sort_unstable();
-        ids
+        self.ids_through(EntityId::MAX, |_| true)
+    }
+
+    /// Every row, column by column.
+    #[must_use]
+    pub fn columns(&self) -> Columns<'_> {
+        self.columns_to(self.ids.len())
+    }
+
+    /// The rows of the entities with IDs up to `last`, column by column.
+    ///
+    /// Systems use this to skip entities spawned earlier in the same tick.
+    #[must_use]
+    pub fn columns_through(&self, last: EntityId) -> Columns<'_> {
+        self.columns_to(self.ids.partition_point(|&id| id <= last))
+    }
+
+    /// The first `end` rows, column by column.
+    fn columns_to(&self, end: usize) -> Columns<'_> {
+        Columns {
+            ids: &self.ids[..end],
+            positions: &self.positions[..end],
+            velocities: &self.velocities[..end],
+            health: &self.health[..end],
+            movement: &self.movement[..end],
+            combat_stats: &self.combat_stats[..end],
+            factions: &self.factions[..end],
+            rows: &self.rows[..end],
+        }
+    }
+
+    /// The rows of the entities with IDs up to `last`, column by column,
+    /// borrowed mutably.
+    pub fn columns_mut_through(&mut self, last: EntityId) -> ColumnsMut<'_> {
+        let end = self.ids.partition_point(|&id| id <= last);
+        ColumnsMut {
+            ids: &self.ids[..end],
+            positions: &mut self.positions[..end],
+            velocities: &mut self.velocities[..end],
+            health: &mut self.health[..end],
+            movement: &mut self.movement[..end],
+            combat_stats: &mut self.combat_stats[..end],
+            factions: &mut self.factions[..end],
+            rows: &mut self.rows[..end],
+        }
+    }
+
+    /// IDs of the entities up to `last` whose row passes `keep`, in ID
+    /// order.
+    pub fn ids_through(&self, last: EntityId, keep: impl Fn(&EntityRow) -> bool) -> Vec<EntityId> {
+        let columns = self.columns_through(last);
+        columns
+            .ids
+            .iter()
+            .zip(columns.rows)
+            .filter(|(_, row)| row.as_ref().is_some_and(&keep))
+            .map(|(&id, _)| id)
+            .collect()
+    }
+
+    /// Iterate over all entities in ID order.
+    pub fn iter(&self) -> impl Iterator<Item = (&EntityId, EntityRef<'_>)> {
+        self.columns().entities()
+    }
+
+    /// Iterate mutably over all entities in ID order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&EntityId, EntityMut<'_>)> {
+        self.columns_mut_through(EntityId::MAX).into_entities()
+    }
+
+    /// Iterate over the entities with IDs up to `last`, in ID order.
+    ///
+    /// Systems use this to skip entities spawned earlier in the same tick.
+    pub fn iter_through(&self, last: EntityId) -> impl Iterator<Item = (&EntityId, EntityRef<'_>)> {
+        self.columns_through(last).entities()
+    }
+
+    /// Iterate mutably over the entities with IDs up to `last`, in ID order.
+    pub fn iter_mut_through(
+        &mut self,
+        last: EntityId,
+    ) -> impl Iterator<Item = (&EntityId, EntityMut<'_>)> {
+        self.columns_mut_through(last).into_entities()
+    }
+}
+
+/// Keep the entries of `column` whose row is live.
+fn retain_live<T>(column: &mut Vec<T>, live: &[bool]) {
+    let mut live = live.iter();
+    column.retain(|_| live.next().copied().unwrap_or(false));
+}
+
+impl Serialize for EntityStorage {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::{SerializeMap, SerializeStruct};
+
+        /// The live entities, as an ID-ordered map.
+        struct Rows<'a>(&'a EntityStorage);
+
+        impl Serialize for Rows<'_> {
+            fn serialize<S: serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> std::result::Result<S::Ok, S::Error> {
+                let mut map = serializer.serialize_map(Some(self.0.len()))?;
+                for (id, entity) in self.0.iter() {
+                    map.serialize_entry(id, &entity)?;
+                }
+                map.end()
+            }
+        }
+
+        let mut state = serializer.serialize_struct("EntityStorage", 2)?;
+        state.serialize_field("entities", &Rows(self))?;
+        state.serialize_field("next_id", &self.next_id)?;
+        state.end()
+    }
+}
+
+/// Serialized form of [`EntityStorage`].
+#[derive(Deserialize)]
+struct StoredEntities {
+    entities: BTreeMap<EntityId, Entity>,
+    next_id: EntityId,
+}
+
+impl From<StoredEntities> for EntityStorage {
+    fn from(stored: StoredEntities) -> Self {
+        let mut storage = Self {
+            next_id: stored.next_id,
+            ..Default::default()
+        };
+        for (id, mut entity) in stored.entities {
+            entity.id = id;
+            storage.push(entity);
+        }
+        storage
+    }
+}
+
+/// Rows of an [`EntityStorage`], column by column.
+///
+/// The slices line up row for row. A removed entity leaves its row `None`
+/// in every column.
+#[derive(Debug, Clone, Copy)]
+pub struct Columns<'a> {
+    /// ID of each row, ascending.
+    pub ids: &'a [EntityId],
+    /// Positions.
+    pub positions: &'a [Option<Position>],
+    /// Velocities.
+    pub velocities: &'a [Option<Velocity>],
+    /// Health.
+    pub health: &'a [Option<Health>],
+    /// Movement capabilities.
+    pub movement: &'a [Option<Movement>],
+    /// Combat statistics.
+    pub combat_stats: &'a [Option<CombatStats>],
+    /// Faction membership.
+    pub factions: &'a [Option<FactionMember>],
+    /// Every other component.
+    pub rows: &'a [Option<EntityRow>],
+}
+
+impl<'a> Columns<'a> {
+    /// The entity in row `row`, unless the row is a gap.
+    #[must_use]
+    pub fn entity(&self, row: usize) -> Option<EntityRef<'a>> {
+        Some(EntityRef {
+            id: self.ids[row],
+            position: self.positions[row],
+            velocity: self.velocities[row],
+            health: self.health[row],
+            movement: self.movement[row],
+            combat_stats: self.combat_stats[row],
+            faction: self.factions[row],
+            row: self.rows[row].as_ref()?,
+        })
     }
 
-    /// Iterate over all entities (not in deterministic order).
-    pub fn iter(&self) -> impl Iterator<Item = (&EntityId, &Entity)> {
-        self.entities.iter()
+    /// The entities in these rows, in ID order.
+    pub fn entities(self) -> impl Iterator<Item = (&'a EntityId, EntityRef<'a>)> {
+        self.ids
+            .iter()
+            .enumerate()
+            .filter_map(move |(row, id)| Some((id, self.entity(row)?)))
     }
+}
+
+/// [`Columns`] borrowed mutably.
+#[derive(Debug)]
+pub struct ColumnsMut<'a> {
+    /// ID of each row, ascending.
+    pub ids: &'a [EntityId],
+    /// Positions.
+    pub positions: &'a mut [Option<Position>],
+    /// Velocities.
+    pub velocities: &'a mut [Option<Velocity>],
+    /// Health.
+    pub health: &'a mut [Option<Health>],
+    /// Movement capabilities.
+    pub movement: &'a mut [Option<Movement>],
+    /// Combat statistics.
+    pub combat_stats: &'a mut [Option<CombatStats>],
+    /// Faction membership.
+    pub factions: &'a mut [Option<FactionMember>],
+    /// Every other component.
+    pub rows: &'a mut [Option<EntityRow>],
+}
 
-    /// Iterate mutably over all entities (not in deterministic order).
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&EntityId, &mut Entity)> {
-        self.entities.iter_mut()
+impl<'a> ColumnsMut<'a> {
+    /// The entities in these rows, in ID order.
+    pub fn into_entities(self) -> impl Iterator<Item = (&'a EntityId, EntityMut<'a>)> {
+        self.ids
+            .iter()
+            .zip(self.positions)
+            .zip(self.velocities)
+            .zip(self.health)
+            .zip(self.movement)
+            .zip(self.combat_stats)
+            .zip(self.factions)
+            .zip(self.rows)
+            .filter_map(
+                |(
+                    ((((((id, position), velocity), health), movement), combat_stats), faction),
+                    row,
+                )| {
+                    Some((
+                        id,
+                        EntityMut {
+                            id: *id,
+                            position,
+                            velocity,
+                            health,
+                            movement,
+                            combat_stats,
+                            faction,
+                            row: row.as_mut()?,
+                        },
+                    ))
+                },
+            )
     }
 }
 
@@ -278,14 +958,122 @@ impl EntityStorage {
 pub struct TickEvents {
     /// Damage events from combat.
     pub damage_events: Vec<DamageEvent>,
+    /// Splash weapon shots that went off this tick, with everything they hit.
+    pub splashes: Vec<SplashEvent>,
     /// Entities that died this tick.
     pub deaths: Vec<EntityId>,
+    /// Debug names of the entities in `deaths` that had one.
+    pub death_names: Vec<(EntityId, DebugName)>,
     /// Production events this tick.
     pub production_events: Vec<ProductionEvent>,
     /// Entities spawned this tick.
     pub spawned: Vec<EntityId>,
+    /// Health regenerated this tick, per entity.
+    pub regenerated: Vec<(EntityId, u32)>,
+    /// Set when the tick started near the entity capacity.
+    pub capacity_pressure: Option<CapacityPressure>,
     /// Winning faction if the match ended.
     pub game_end: Option<FactionId>,
+    /// How the match ended, set on the same tick as `game_end`.
+    pub win_condition: Option<WinCondition>,
+    /// Ownership transfers made since the previous tick.
+    pub ownership_changes: Vec<OwnershipChange>,
+    /// Wrecks left, salvage collected and wrecks cleared this tick.
+    pub salvage: Vec<SalvageEvent>,
+    /// Research started, completed and cancelled since the previous tick.
+    pub research: Vec<ResearchEvent>,
+    /// Abilities used this tick.
+    pub abilities: Vec<AbilityUsed>,
+    /// Repairs and heals given this tick.
+    pub repairs: Vec<RepairEvent>,
+    /// Structures rebuilt by faction mechanics this tick, as
+    /// (destroyed, rebuilt) IDs.
+    pub rebuilt: Vec<(EntityId, EntityId)>,
+    /// Construction sites finished by workers this tick.
+    pub construction: Vec<ConstructionEvent>,
+    /// Scenario triggers fired this tick, for the game layer to carry out.
+    pub triggers: Vec<TriggerFired>,
+    /// Obstacles raised since the previous tick and destroyed this tick.
+    pub obstacles: Vec<ObstacleEvent>,
+    /// Alerts for players: attacks and research from this tick, and idle
+    /// harvesters and blocked production reported since the previous one.
+    pub notifications: Vec<Notification>,
+}
+
+impl TickEvents {
+    /// Label a dead entity for logs, like [`Simulation::entity_label`] but
+    /// usable after the entity has been removed.
+    #[must_use]
+    pub fn death_label(&self, id: EntityId) -> String {
+        match self.death_names.iter().find(|(dead, _)| *dead == id) {
+            Some((_, name)) => name.to_string(),
+            None => format!("#{id}"),
+        }
+    }
+}
+
+/// One way two simulations disagree, found by [`Simulation::diff`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateDifference {
+    /// Entity that differs, or None for world state (tick, squads, RNG).
+    pub entity: Option<EntityId>,
+    /// Component or world field that differs; `entity` when the entity
+    /// exists on only one side.
+    pub field: String,
+    /// The value in the simulation diffed from, None if absent there.
+    pub local: Option<String>,
+    /// The value in the other simulation, None if absent there.
+    pub remote: Option<String>,
+}
+
+/// A destroyed structure waiting for a faction mechanic to rebuild it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PendingRebuild {
+    /// Tick the structure returns on.
+    due: u64,
+    /// ID of the destroyed structure.
+    replaces: EntityId,
+    /// The structure as it will return.
+    entity: Entity,
+}
+
+/// An entity changing hands, e.g. through capture or conversion.
+///
+/// Made by [`Simulation::transfer_ownership`] and reported in the next
+/// tick's [`TickEvents::ownership_changes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnershipChange {
+    /// The entity that changed hands.
+    pub entity: EntityId,
+    /// Owner before the transfer.
+    pub previous: FactionMember,
+    /// Owner after the transfer.
+    pub owner: FactionMember,
+}
+
+/// New stats for a live entity, e.g. after its unit data was edited.
+///
+/// Applied by [`Simulation::change_stats`]; stats left as `None` keep
+/// their current value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatChange {
+    /// Maximum health. Current health keeps its share of the maximum.
+    pub max_health: Option<u32>,
+    /// Damage per attack.
+    pub damage: Option<u32>,
+    /// Attack range.
+    #[serde(with = "option_fixed_serde")]
+    pub range: Option<Fixed>,
+    /// Ticks between attacks.
+    pub attack_cooldown: Option<u32>,
+}
+
+impl StatChange {
+    /// Whether the change leaves every stat as it is.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 /// The core game simulation.
@@ -312,6 +1100,68 @@ pub struct Simulation {
     /// Navigation grid for pathfinding.
     #[serde(skip)]
     nav_grid: NavGrid,
+    /// Next debug-name number per `faction/kind`.
+    #[serde(default)]
+    debug_name_counters: BTreeMap<String, u32>,
+    /// Persistent unit squads.
+    #[serde(default)]
+    squads: BTreeMap<SquadId, Squad>,
+    /// Next squad ID to hand out.
+    #[serde(default)]
+    next_squad_id: u32,
+    /// Generator for gameplay rolls such as damage variance.
+    #[serde(default)]
+    rng: SimRng,
+    /// Entity limit and degradation policy, if any.
+    #[serde(default)]
+    capacity: Option<EntityCapacity>,
+    /// Ownership transfers not yet reported in [`TickEvents`].
+    #[serde(default)]
+    pending_ownership_changes: Vec<OwnershipChange>,
+    /// Obstacles raised but not yet reported in [`TickEvents`].
+    #[serde(default)]
+    pending_obstacle_events: Vec<ObstacleEvent>,
+    /// What each faction has explored and can currently see.
+    #[serde(default)]
+    fog: FogOfWar,
+    /// Wrecks waiting to be salvaged.
+    #[serde(default)]
+    wrecks: BTreeMap<WreckId, Wreck>,
+    /// Next wreck ID to hand out.
+    #[serde(default)]
+    next_wreck_id: u32,
+    /// Salvage radius and wreck lifetime.
+    #[serde(default)]
+    salvage_rules: SalvageRules,
+    /// How elevation changes shots between levels.
+    #[serde(default)]
+    high_ground: HighGroundRules,
+    /// Technologies and research progress per faction.
+    #[serde(default)]
+    tech: BTreeMap<FactionId, TechState>,
+    /// Research events not yet reported in [`TickEvents`].
+    #[serde(default)]
+    pending_research_events: Vec<ResearchEvent>,
+    /// Faction-unique rules per faction.
+    #[serde(default)]
+    mechanics: FactionMechanics,
+    /// Kinds of entity each faction has fielded, for deploy-once rules.
+    #[serde(default)]
+    fielded: BTreeMap<FactionId, BTreeSet<String>>,
+    /// Destroyed structures waiting to be rebuilt.
+    #[serde(default)]
+    rebuilds: Vec<PendingRebuild>,
+    /// Ground claimed by structures put up by workers.
+    placement: PlacementGrid,
+    /// Scenario triggers and how often each has fired.
+    #[serde(default)]
+    script: ScenarioScript,
+    /// Notifications from the game layer not yet reported in [`TickEvents`].
+    #[serde(default)]
+    pending_notifications: Vec<Notification>,
+    /// Recent under-attack alerts, so one fight raises one alert.
+    #[serde(default)]
+    attack_alerts: AttackAlerts,
 }
 
 impl Simulation {
@@ -332,10 +1182,33 @@ impl Simulation {
         // Create a default nav grid (2000x2000 world units, 32 unit cells)
         // All cells start as walkable; buildings will block cells when placed
         let nav_grid = NavGrid::new(64, 64, Fixed::from_num(32));
+        let fog = FogOfWar::new(64, 64, Fixed::from_num(32));
+        let placement = PlacementGrid::new(64, 64, Fixed::from_num(32));
         Self {
             tick: 0,
             entities: EntityStorage::new(),
             nav_grid,
+            debug_name_counters: BTreeMap::new(),
+            squads: BTreeMap::new(),
+            next_squad_id: 0,
+            rng: SimRng::default(),
+            capacity: None,
+            pending_ownership_changes: Vec::new(),
+            pending_obstacle_events: Vec::new(),
+            fog,
+            wrecks: BTreeMap::new(),
+            next_wreck_id: 0,
+            salvage_rules: SalvageRules::default(),
+            high_ground: HighGroundRules::default(),
+            tech: BTreeMap::new(),
+            pending_research_events: Vec::new(),
+            mechanics: FactionMechanics::default(),
+            fielded: BTreeMap::new(),
+            rebuilds: Vec::new(),
+            placement,
+            script: ScenarioScript::default(),
+            pending_notifications: Vec::new(),
+            attack_alerts: AttackAlerts::default(),
         }
     }
 
@@ -348,13 +1221,88 @@ impl Simulation {
     #[must_use]
     pub fn with_nav_grid(grid_width: u32, grid_height: u32, cell_size: Fixed) -> Self {
         let nav_grid = NavGrid::new(grid_width, grid_height, cell_size);
+        let fog = FogOfWar::new(grid_width, grid_height, cell_size);
+        let placement = PlacementGrid::new(grid_width, grid_height, cell_size);
         Self {
             tick: 0,
             entities: EntityStorage::new(),
             nav_grid,
+            debug_name_counters: BTreeMap::new(),
+            squads: BTreeMap::new(),
+            next_squad_id: 0,
+            rng: SimRng::default(),
+            capacity: None,
+            pending_ownership_changes: Vec::new(),
+            pending_obstacle_events: Vec::new(),
+            fog,
+            wrecks: BTreeMap::new(),
+            next_wreck_id: 0,
+            salvage_rules: SalvageRules::default(),
+            high_ground: HighGroundRules::default(),
+            tech: BTreeMap::new(),
+            pending_research_events: Vec::new(),
+            mechanics: FactionMechanics::default(),
+            fielded: BTreeMap::new(),
+            rebuilds: Vec::new(),
+            placement,
+            script: ScenarioScript::default(),
+            pending_notifications: Vec::new(),
+            attack_alerts: AttackAlerts::default(),
         }
     }
 
+    /// Reseed the gameplay random number generator.
+    ///
+    /// Call once at game start with the match seed so every peer and replay
+    /// rolls the same numbers.
+    pub fn seed_rng(&mut self, seed: u64) {
+        self.rng = SimRng::new(seed);
+    }
+
+    /// Set or clear the entity capacity policy.
+    pub fn set_entity_capacity(&mut self, capacity: Option<EntityCapacity>) {
+        self.capacity = capacity;
+    }
+
+    /// The entity capacity policy, if any.
+    #[must_use]
+    pub fn entity_capacity(&self) -> Option<&EntityCapacity> {
+        self.capacity.as_ref()
+    }
+
+    /// Set the scenario's scripted triggers, replacing any already set.
+    pub fn set_script(&mut self, script: ScenarioScript) {
+        self.script = script;
+    }
+
+    /// The scenario's scripted triggers.
+    #[must_use]
+    pub fn script(&self) -> &ScenarioScript {
+        &self.script
+    }
+
+    /// Set the salvage radius and wreck lifetime.
+    pub fn set_salvage_rules(&mut self, rules: SalvageRules) {
+        self.salvage_rules = rules;
+    }
+
+    /// The salvage radius and wreck lifetime.
+    #[must_use]
+    pub fn salvage_rules(&self) -> &SalvageRules {
+        &self.salvage_rules
+    }
+
+    /// Set the high-ground damage bonus and uphill miss chance.
+    pub fn set_high_ground_rules(&mut self, rules: HighGroundRules) {
+        self.high_ground = rules;
+    }
+
+    /// The high-ground damage bonus and uphill miss chance.
+    #[must_use]
+    pub fn high_ground_rules(&self) -> &HighGroundRules {
+        &self.high_ground
+    }
+
     /// Get a reference to the navigation grid.
     #[must_use]
     pub fn nav_grid(&self) -> &NavGrid {
@@ -366,6 +1314,12 @@ impl Simulation {
         &mut self.nav_grid
     }
 
+    /// Fog of war for every faction.
+    #[must_use]
+    pub fn fog(&self) -> &FogOfWar {
+        &self.fog
+    }
+
     /// Get the current tick number.
     ///
     /// The tick counter starts at 0 and increments by 1 each time
@@ -381,6 +1335,17 @@ impl Simulation {
         &self.entities
     }
 
+    /// Wrecks waiting to be salvaged, in ID order.
+    pub fn wrecks(&self) -> impl Iterator<Item = (WreckId, &Wreck)> {
+        self.wrecks.iter().map(|(&id, wreck)| (id, wreck))
+    }
+
+    /// Look up a wreck by ID.
+    #[must_use]
+    pub fn wreck(&self, id: WreckId) -> Option<&Wreck> {
+        self.wrecks.get(&id)
+    }
+
     /// Advance the simulation by one tick.
     ///
     /// Runs all systems in deterministic order and increments the tick counter.
@@ -388,11 +1353,25 @@ impl Simulation {
     ///
     /// # System Order
     ///
-    /// 1. Command processing (converts commands to velocities)
-    /// 2. Movement (applies velocities to positions)
-    /// 3. Combat (processes attacks)
-    /// 4. Health (removes dead entities)
-    /// 5. Production (advances build queues)
+    /// 0. Capacity (culls cosmetic entities near the entity limit)
+    /// 1. Squad orders (idle members resume their squad's order)
+    /// 2. Command processing (converts commands to velocities), then
+    ///    patrol, attack chase, squad cohesion, idle behavior and
+    ///    transports (boarding and unloading)
+    /// 3. Movement (applies velocities to positions)
+    /// 4. Combat (processes attacks)
+    /// 5. Resupply (refills ammunition near depots and supply units), then
+    ///    regeneration (heals units out of combat), then abilities (expires
+    ///    shields and fires ordered abilities), then repairs (engineers and
+    ///    medics restore their allies' health), then construction (workers
+    ///    raise the buildings they were ordered to put up)
+    /// 6. Health (removes dead entities, leaving wrecks, along with the
+    ///    passengers of dead transports), then salvage
+    ///    (decays wrecks and lets salvagers collect)
+    /// 7. Production (advances build queues), then research (advances
+    ///    research queues and applies finished technologies), then faction
+    ///    mechanics (returns rebuilt structures), then scenario triggers
+    ///    (fires timed events and reactions to this tick's losses)
     ///
     /// # Example
     ///
@@ -404,11 +1383,22 @@ impl Simulation {
     /// assert_eq!(sim.get_tick(), 1);
     /// ```
     pub fn tick(&mut self) -> TickEvents {
-        let mut events = TickEvents::default();
+        // 0. Capacity System
+        let mut events = TickEvents {
+            capacity_pressure: self.run_capacity_system(),
+            ownership_changes: std::mem::take(&mut self.pending_ownership_changes),
+            research: std::mem::take(&mut self.pending_research_events),
+            obstacles: std::mem::take(&mut self.pending_obstacle_events),
+            notifications: std::mem::take(&mut self.pending_notifications),
+            ..Default::default()
+        };
 
         // Get sorted entity IDs for deterministic processing
         let entity_ids = self.entities.sorted_ids();
 
+        // 0.5 Squad Order System
+        self.run_squad_order_system();
+
         // 1. Command Processing System
         self.run_command_processing_system(&entity_ids);
 
@@ -418,40 +1408,131 @@ impl Simulation {
         // 1.6 Attack Chase System
         self.run_attack_chase_system(&entity_ids);
 
+        // 1.7 Squad Cohesion System
+        self.run_squad_cohesion_system();
+
+        // 1.8 Idle System
+        self.run_idle_system(&entity_ids);
+
+        // 1.9 Transport System
+        self.run_transport_system(&entity_ids);
+
         // 2. Movement System
         self.run_movement_system(&entity_ids);
 
+        // 2.5 Avoidance System
+        self.run_avoidance_system(&entity_ids);
+
         // 3. Combat System
-        events.damage_events = self.run_combat_system(&entity_ids);
+        (events.damage_events, events.splashes) = self.run_combat_system(&entity_ids);
 
         // 3.5 Projectile System
-        let mut projectile_damage = self.run_projectile_system(&entity_ids);
+        let (mut projectile_damage, mut projectile_splashes) =
+            self.run_projectile_system(&entity_ids);
         events.damage_events.append(&mut projectile_damage);
+        events.splashes.append(&mut projectile_splashes);
+
+        // 3.6 Resupply System
+        self.run_resupply_system(&entity_ids);
+
+        // 3.7 Regeneration System
+        events.regenerated = self.run_regeneration_system(&entity_ids);
+
+        // 3.8 Ability System
+        let (abilities, mut ability_damage) = self.run_ability_system(&entity_ids);
+        events.abilities = abilities;
+        events.damage_events.append(&mut ability_damage);
+
+        // 3.9 Repair System
+        events.repairs = self.run_repair_system(&entity_ids);
+
+        // 3.95 Construction System
+        events.construction = self.run_construction_system(&entity_ids);
+
+        // 3.99 Attack alerts, while the victims are still around
+        let mut alerts = self.attack_notifications(&events.damage_events);
+        events.notifications.append(&mut alerts);
 
         // 4. Health System - identify and remove dead entities
         events.deaths = self.run_health_system(&entity_ids);
-        for dead_id in &events.deaths {
-            self.entities.remove(*dead_id);
+        let mut lost_passengers = self.passengers_of(&events.deaths);
+        events.deaths.append(&mut lost_passengers);
+        let mut destroyed = Vec::new();
+        for &dead_id in &events.deaths {
+            let Some(dead) = self.entities.remove(dead_id) else {
+                continue;
+            };
+            if let (Some(member), Some(profile)) = (dead.faction, dead.tech_profile.as_ref()) {
+                destroyed.push((member.faction, profile.kind.clone()));
+            }
+            self.release_ground(&dead);
+            if let Some(obstacle) = self.clear_obstacle(&dead) {
+                events.obstacles.push(obstacle);
+            }
+            self.schedule_rebuild(&dead);
+            if let Some(wreck) = self.leave_wreck(&dead) {
+                events.salvage.push(wreck);
+            }
+            if let Some(name) = dead.row.debug_name {
+                tracing::debug!(tick = self.tick, entity = %name, "Entity died");
+                events.death_names.push((dead_id, name));
+            }
         }
 
-        events.game_end = self.determine_winner();
+        // 4.5 Salvage System
+        let mut salvage = self.run_salvage_system(&entity_ids);
+        events.salvage.append(&mut salvage);
+
+        events.win_condition = self.determine_winner();
+        events.game_end = events.win_condition.and_then(|c| c.winner());
 
         // 5. Production System
         events.production_events = self.run_production_system(&entity_ids);
 
-        // Increment tick counter
-        self.tick += 1;
+        // 5.5 Research System
+        let mut research = self.run_research_system(&entity_ids);
+        events.research.append(&mut research);
+        for event in &events.research {
+            if let ResearchEvent::ResearchComplete {
+                faction, tech_id, ..
+            } = event
+            {
+                events.notifications.push(Notification::ResearchComplete {
+                    faction: *faction,
+                    tech_id: tech_id.clone(),
+                });
+            }
+        }
 
-        #[cfg(debug_assertions)]
-        {
-            let hash = self.state_hash();
+        // 5.6 Faction Mechanics System
+        events.rebuilt = self.run_rebuild_system();
+
+        // 5.7 Trigger System
+        let destroyed: Vec<(FactionId, &str)> = destroyed
+            .iter()
+            .map(|(faction, kind)| (*faction, kind.as_str()))
+            .collect();
+        events.triggers = self.script.evaluate(self.tick, &destroyed);
+        for fired in &events.triggers {
+            tracing::debug!(tick = self.tick, trigger = %fired.trigger, "Trigger fired");
+        }
+
+        // 6. Fog of War System
+        self.run_fog_system();
+
+        // Increment tick counter
+        self.tick += 1;
+
+        #[cfg(debug_assertions)]
+        {
+            let hash = self.state_hash();
             tracing::debug!(tick = self.tick, state_hash = hash, "Simulation state hash");
         }
 
         events
     }
 
-    fn determine_winner(&self) -> Option<FactionId> {
+    fn determine_winner(&self) -> Option<WinCondition> {
         let mut factions = std::collections::HashSet::new();
 
         for (_, entity) in self.entities.iter() {
@@ -464,52 +1545,89 @@ impl Simulation {
             }
         }
 
-        if factions.len() == 1 {
-            factions.iter().copied().next()
-        } else {
-            None
+        if factions.len() != 1 {
+            return None;
         }
+        let winner = factions.iter().copied().next()?;
+        let surviving_structures = self
+            .entities
+            .iter()
+            // Structures are whatever stands still and can be destroyed
+            .filter(|(_, e)| e.health.is_some() && e.movement.is_none())
+            .filter(|(_, e)| e.faction.is_some_and(|f| f.faction == winner))
+            .count();
+        Some(WinCondition::Elimination {
+            eliminating_player: winner,
+            surviving_structures: u32::try_from(surviving_structures).unwrap_or(u32::MAX),
+        })
     }
 
     /// Run the command processing system on all applicable entities.
     fn run_command_processing_system(&mut self, entity_ids: &[EntityId]) {
-        // Process each entity with required components
-        for &id in entity_ids {
-            if let Some(entity) = self.entities.get_mut(id) {
-                // Check if entity has all required components
-                let has_all = entity.command_queue.is_some()
-                    && entity.position.is_some()
-                    && entity.velocity.is_some()
-                    && entity.movement.is_some();
-
-                if has_all {
-                    // Process this entity individually
-                    let command_queue = entity.command_queue.as_mut().unwrap();
-                    let position = entity.position.as_ref().unwrap();
-                    let velocity = entity.velocity.as_mut().unwrap();
-                    let movement = entity.movement.as_ref().unwrap();
-                    let path_waypoints = &mut entity.path_waypoints;
-
-                    let mut single = vec![(
+        let Some(&last) = entity_ids.last() else {
+            return;
+        };
+        // Every entity with all the required components, with its queue
+        // length beforehand to spot those that finished a command
+        let columns = self.entities.columns_mut_through(last);
+        let (mut commanded, queued): (Vec<_>, Vec<_>) = columns
+            .ids
+            .iter()
+            .zip(columns.rows.iter_mut())
+            .zip(columns.positions.iter())
+            .zip(columns.velocities.iter_mut())
+            .zip(columns.movement.iter())
+            .filter_map(|((((&id, row), position), velocity), movement)| {
+                let EntityRow {
+                    command_queue,
+                    path_waypoints,
+                    ..
+                } = row.as_mut()?;
+                let command_queue = command_queue.as_mut()?;
+                let queued = command_queue.len();
+                Some((
+                    (
                         id,
                         command_queue,
-                        position,
-                        velocity,
-                        movement,
+                        position.as_ref()?,
+                        velocity.as_mut()?,
+                        movement.as_ref()?,
                         path_waypoints,
-                    )];
-                    command_processing_system(&mut single);
-                }
-            }
+                    ),
+                    queued,
+                ))
+            })
+            .unzip();
+        command_processing_system(&mut commanded);
+        let advanced: Vec<EntityId> = commanded
+            .iter()
+            .zip(queued)
+            .filter(|((_, queue, ..), queued)| queue.len() < *queued)
+            .map(|((id, ..), _)| *id)
+            .collect();
+        for id in advanced {
+            self.plan_current_command(id);
         }
     }
 
     /// Run patrol movement logic for entities with patrol commands.
     fn run_patrol_system(&mut self, entity_ids: &[EntityId]) {
+        let Some(&last) = entity_ids.last() else {
+            return;
+        };
         let arrival_threshold_sq = Fixed::from_num(1);
 
-        for &id in entity_ids {
-            let Some(entity) = self.entities.get_mut(id) else {
+        let columns = self.entities.columns_mut_through(last);
+        let units = columns
+            .rows
+            .iter_mut()
+            .zip(columns.positions.iter())
+            .zip(columns.velocities.iter_mut())
+            .zip(columns.movement.iter());
+        for (((row, position), velocity), movement) in units {
+            let (Some(entity), Some(position), Some(velocity), Some(movement)) =
+                (row, position, velocity, movement)
+            else {
                 continue;
             };
 
@@ -517,18 +1635,6 @@ impl Simulation {
                 continue;
             };
 
-            let Some(position) = entity.position.as_ref() else {
-                continue;
-            };
-
-            let Some(velocity) = entity.velocity.as_mut() else {
-                continue;
-            };
-
-            let Some(movement) = entity.movement.as_ref() else {
-                continue;
-            };
-
             match command_queue.current() {
                 Some(Command::Patrol(target)) => {
                     let target = *target;
@@ -578,57 +1684,70 @@ impl Simulation {
     fn run_attack_chase_system(&mut self, entity_ids: &[EntityId]) {
         let arrival_threshold_sq = Fixed::from_num(1);
 
-        for &id in entity_ids {
-            let Some(Command::Attack(target_id)) = self
+        for id in self.ids_with(entity_ids, |row| {
+            matches!(
+                row.current_command(),
+                Some(Command::Attack(_) | Command::ForceAttack(_) | Command::AttackGround(_))
+            )
+        }) {
+            let Some(command) = self
                 .entities
                 .get(id)
-                .and_then(|entity| entity.command_queue.as_ref())
+                .and_then(|entity| entity.row.command_queue.as_ref())
                 .and_then(|queue| queue.current().cloned())
             else {
                 continue;
             };
+            let target_id = match command {
+                Command::Attack(target_id) | Command::ForceAttack(target_id) => target_id,
+                Command::AttackGround(point) => {
+                    self.approach_ground_target(id, point);
+                    continue;
+                }
+                _ => continue,
+            };
 
             let Some(target_pos) = self
                 .entities
                 .get(target_id)
                 .and_then(|target| target.position.map(|pos| pos.value))
             else {
-                if let Some(entity) = self.entities.get_mut(id) {
-                    if let Some(command_queue) = entity.command_queue.as_mut() {
-                        command_queue.pop();
-                    }
-                    if let Some(velocity) = entity.velocity.as_mut() {
-                        velocity.value = Vec2Fixed::ZERO;
-                    }
-                }
+                self.finish_command(id);
                 continue;
             };
 
-            let Some(entity) = self.entities.get_mut(id) else {
+            let Some(EntityMut {
+                position,
+                velocity,
+                movement,
+                row,
+                ..
+            }) = self.entities.get_mut(id)
+            else {
                 continue;
             };
 
-            let Some(command_queue) = entity.command_queue.as_mut() else {
+            let Some(command_queue) = row.command_queue.as_mut() else {
                 continue;
             };
 
-            let Some(position) = entity.position.as_ref() else {
+            let Some(position) = position.as_ref() else {
                 continue;
             };
 
-            let Some(velocity) = entity.velocity.as_mut() else {
+            let Some(velocity) = velocity.as_mut() else {
                 continue;
             };
 
-            let Some(movement) = entity.movement.as_ref() else {
+            let Some(movement) = movement.as_ref() else {
                 continue;
             };
 
-            if let Some(attack_target) = entity.attack_target.as_mut() {
+            if let Some(attack_target) = row.attack_target.as_mut() {
                 attack_target.target = Some(target_id);
             }
 
-            if command_queue.current().cloned() != Some(Command::Attack(target_id)) {
+            if command_queue.current() != Some(&command) {
                 velocity.value = Vec2Fixed::ZERO;
                 continue;
             };
@@ -645,37 +1764,512 @@ impl Simulation {
         }
     }
 
+    /// Drop dead squad members and send idle, stray members back to their
+    /// squad's positional order.
+    ///
+    /// A member counts as stray when it is further than the cohesion radius
+    /// from its formation slot, e.g. after chasing down a target.
+    fn run_squad_order_system(&mut self) {
+        let entities = &self.entities;
+        self.squads.retain(|_, squad| {
+            squad.members.retain(|&id| entities.get(id).is_some());
+            !squad.members.is_empty()
+        });
+
+        let mut resumed = Vec::new();
+        for squad in self.squads.values() {
+            let Some(order) = squad.order else {
+                continue;
+            };
+            let Some(destination) = order.destination() else {
+                continue;
+            };
+            let radius_sq = squad.cohesion_radius * squad.cohesion_radius;
+            let offsets = squad.formation.offsets(squad.members.len());
+            for (&id, &offset) in squad.members.iter().zip(&offsets) {
+                let Some(entity) = self.entities.get(id) else {
+                    continue;
+                };
+                let idle = entity.command_queue.as_ref().is_some_and(|q| q.is_empty())
+                    && !entity.attack_target.is_some_and(|t| t.target.is_some());
+                let slot = destination + offset;
+                let stray = entity
+                    .position
+                    .is_some_and(|p| p.value.distance_squared(slot) > radius_sq);
+                if idle && stray {
+                    resumed.push((id, member_command(order, slot)));
+                }
+            }
+        }
+
+        for (id, command) in resumed {
+            if let Err(e) = self.apply_command(id, command) {
+                tracing::debug!(entity = id, error = %e, "Squad member could not resume order");
+            }
+        }
+    }
+
+    /// Hold back squad members that run ahead of the group while it moves.
+    ///
+    /// A member moving under its squad's positional order stops for the
+    /// tick if it is outside the cohesion radius and heading away from the
+    /// squad centre.
+    fn run_squad_cohesion_system(&mut self) {
+        for squad in self.squads.values() {
+            if squad.order.and_then(|o| o.destination()).is_none() {
+                continue;
+            }
+            let positions: Vec<Vec2Fixed> = squad
+                .members
+                .iter()
+                .filter_map(|&id| self.entities.get(id)?.position.map(|p| p.value))
+                .collect();
+            let Some(centre) = centroid(&positions) else {
+                continue;
+            };
+            let radius_sq = squad.cohesion_radius * squad.cohesion_radius;
+
+            for &id in &squad.members {
+                let Some(entity) = self.entities.get_mut(id) else {
+                    continue;
+                };
+                let moving_with_squad = matches!(
+                    entity.command_queue.as_ref().and_then(|q| q.current()),
+                    Some(Command::MoveTo(_) | Command::AttackMove(_))
+                );
+                let (true, Some(position), Some(velocity)) =
+                    (moving_with_squad, entity.position, entity.velocity.as_mut())
+                else {
+                    continue;
+                };
+                let away = position.value - centre;
+                if position.value.distance_squared(centre) > radius_sq
+                    && velocity.value.dot(away) > Fixed::ZERO
+                {
+                    velocity.value = Vec2Fixed::ZERO;
+                }
+            }
+        }
+    }
+
+    /// Move a ground-attacking unit until the point is within weapon range.
+    ///
+    /// The unit then holds position and keeps firing until given another
+    /// command.
+    /// Drive decorative idle behavior for units without orders.
+    ///
+    /// Runs after every order-driven system, so any command or attack target
+    /// set this tick interrupts idling before it moves the unit. Rolls come
+    /// from a generator seeded by entity ID and tick, never from the
+    /// simulation RNG, so idling cannot shift gameplay rolls.
+    fn run_idle_system(&mut self, entity_ids: &[EntityId]) {
+        let arrival_threshold_sq = Fixed::from_num(1);
+        let tick = self.tick;
+
+        for id in self.ids_with(entity_ids, |row| row.idle_behavior.is_some()) {
+            let Some(EntityMut {
+                position,
+                velocity,
+                movement,
+                row: entity,
+                ..
+            }) = self.entities.get_mut(id)
+            else {
+                continue;
+            };
+            let busy = entity
+                .command_queue
+                .as_ref()
+                .is_some_and(|queue| !queue.is_empty())
+                || entity
+                    .attack_target
+                    .as_ref()
+                    .is_some_and(|attack| attack.target.is_some());
+            let (Some(idle), Some(position)) = (entity.idle_behavior.as_mut(), *position) else {
+                continue;
+            };
+            if busy {
+                idle.interrupt();
+                continue;
+            }
+
+            let anchor = *idle.anchor.get_or_insert(position.value);
+            idle.timer += 1;
+            if idle.timer >= idle.interval {
+                idle.timer = 0;
+                let mut roll = SimRng::new(id.rotate_left(32) ^ tick);
+                idle.facing = roll.range_inclusive(0, u32::from(IDLE_FACINGS) - 1) as u8;
+                if idle.mode == IdleMode::Wander {
+                    let leash = idle.leash;
+                    let mut offset = || {
+                        let pct =
+                            Fixed::from_num(roll.range_inclusive(0, 200)) - Fixed::from_num(100);
+                        leash * pct / Fixed::from_num(100)
+                    };
+                    let (dx, dy) = (offset(), offset());
+                    idle.wander_target = Some(anchor + Vec2Fixed::new(dx, dy));
+                }
+            }
+
+            let (Some(velocity), Some(movement)) = (velocity.as_mut(), movement.as_ref()) else {
+                continue;
+            };
+            velocity.value = Vec2Fixed::ZERO;
+            let Some(target) = idle.wander_target else {
+                continue;
+            };
+            if position.value.distance_squared(target) <= arrival_threshold_sq {
+                idle.wander_target = None;
+            } else {
+                let direction = crate::systems::normalize_vec2(target - position.value);
+                let speed = movement.speed / Fixed::from_num(IDLE_SPEED_DIVISOR);
+                velocity.value = Vec2Fixed::new(direction.x * speed, direction.y * speed);
+            }
+        }
+    }
+
+    fn approach_ground_target(&mut self, id: EntityId, point: Vec2Fixed) {
+        let Some(entity) = self.entities.get_mut(id) else {
+            return;
+        };
+        let (Some(position), Some(speed), Some(stats)) = (
+            *entity.position,
+            entity.movement.as_ref().map(|m| m.speed),
+            *entity.combat_stats,
+        ) else {
+            return;
+        };
+        let Some(velocity) = entity.velocity.as_mut() else {
+            return;
+        };
+
+        let range = entity
+            .row
+            .armament
+            .as_ref()
+            .and_then(Armament::ground_range)
+            .unwrap_or(stats.range);
+        if position.value.distance_squared(point) <= range * range {
+            velocity.value = Vec2Fixed::ZERO;
+        } else {
+            let direction = crate::systems::normalize_vec2(point - position.value);
+            velocity.value = Vec2Fixed::new(direction.x * speed, direction.y * speed);
+        }
+    }
+
+    /// Board units ordered to load into a transport that are close enough,
+    /// move the rest towards it, and set passengers down from transports
+    /// that have reached their unload point.
+    ///
+    /// A load order is dropped when the transport is gone, full, belongs to
+    /// another faction or does not take the unit.
+    fn run_transport_system(&mut self, entity_ids: &[EntityId]) {
+        for id in self.ids_with(entity_ids, |row| {
+            matches!(
+                row.current_command(),
+                Some(Command::Load(_) | Command::Unload(_))
+            )
+        }) {
+            let Some(entity) = self.entities.get(id) else {
+                continue;
+            };
+            let Some(position) = entity.position.map(|p| p.value) else {
+                continue;
+            };
+            match entity
+                .command_queue
+                .as_ref()
+                .and_then(|q| q.current())
+                .cloned()
+            {
+                Some(Command::Load(transport_id)) => {
+                    let transport = self.entities.get(transport_id).filter(|t| {
+                        t.faction.map(|f| f.faction) == entity.faction.map(|f| f.faction)
+                            && t.cargo.as_ref().is_some_and(|c| {
+                                !c.is_full() && c.accepts(entity.tech_profile.as_ref())
+                            })
+                    });
+                    let Some(target) = transport.and_then(|t| t.position).map(|p| p.value) else {
+                        self.finish_command(id);
+                        continue;
+                    };
+                    if self.approach(id, position, target, BOARDING_RANGE) {
+                        self.board(id, transport_id);
+                    }
+                }
+                Some(Command::Unload(point))
+                    if self.approach(id, position, point, BOARDING_RANGE) =>
+                {
+                    self.unload(id, position);
+                    self.finish_command(id);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Head `id` from `position` towards `target`, stopping and returning
+    /// true once within `range` of it.
+    fn approach(
+        &mut self,
+        id: EntityId,
+        position: Vec2Fixed,
+        target: Vec2Fixed,
+        range: Fixed,
+    ) -> bool {
+        let Some(entity) = self.entities.get_mut(id) else {
+            return false;
+        };
+        let arrived = position.distance_squared(target) <= range * range;
+        let speed = entity.movement.as_ref().map(|m| m.speed);
+        if let Some(velocity) = entity.velocity.as_mut() {
+            velocity.value = match speed.filter(|_| !arrived) {
+                Some(speed) => {
+                    let direction = crate::systems::normalize_vec2(target - position);
+                    Vec2Fixed::new(direction.x * speed, direction.y * speed)
+                }
+                None => Vec2Fixed::ZERO,
+            };
+        }
+        arrived
+    }
+
+    /// Take `passenger` off the map and into `transport_id`'s hold,
+    /// dropping all its orders.
+    fn board(&mut self, passenger: EntityId, transport_id: EntityId) {
+        if let Some(mut entity) = self.entities.get_mut(passenger) {
+            *entity.position = None;
+            entity.path_waypoints = None;
+            entity.patrol_state = None;
+            entity.embarked = Some(transport_id);
+            if let Some(velocity) = entity.velocity.as_mut() {
+                velocity.value = Vec2Fixed::ZERO;
+            }
+            if let Some(queue) = entity.command_queue.as_mut() {
+                queue.clear();
+            }
+            if let Some(attack_target) = entity.attack_target.as_mut() {
+                attack_target.clear();
+            }
+        }
+        if let Some(cargo) = self
+            .entities
+            .get_mut(transport_id)
+            .and_then(|EntityMut { row, .. }| row.cargo.as_mut())
+        {
+            cargo.passengers.push(passenger);
+        }
+    }
+
+    /// Set every passenger of `transport_id` down around `center`.
+    fn unload(&mut self, transport_id: EntityId, center: Vec2Fixed) {
+        let passengers = self
+            .entities
+            .get_mut(transport_id)
+            .and_then(|EntityMut { row, .. }| row.cargo.as_mut())
+            .map(|c| std::mem::take(&mut c.passengers))
+            .unwrap_or_default();
+        for (index, passenger) in passengers.into_iter().enumerate() {
+            if let Some(mut entity) = self.entities.get_mut(passenger) {
+                *entity.position = Some(Position::new(Cargo::drop_point(center, index)));
+                entity.embarked = None;
+            }
+        }
+    }
+
+    /// Passengers of the transports among `dead`, killed along with them.
+    fn passengers_of(&mut self, dead: &[EntityId]) -> Vec<EntityId> {
+        let passengers: Vec<EntityId> = dead
+            .iter()
+            .filter_map(|&id| self.entities.get(id).and_then(|e| e.row.cargo.as_ref()))
+            .flat_map(|cargo| cargo.passengers.iter().copied())
+            .collect();
+        for &id in &passengers {
+            if let Some(health) = self.entities.get_mut(id).and_then(|e| e.health.as_mut()) {
+                health.current = 0;
+            }
+        }
+        passengers
+    }
+
     /// Run the movement system on all applicable entities.
     fn run_movement_system(&mut self, entity_ids: &[EntityId]) {
-        for &id in entity_ids {
-            if let Some(entity) = self.entities.get_mut(id) {
-                if let (Some(position), Some(velocity)) =
-                    (entity.position.as_mut(), entity.velocity.as_ref())
-                {
-                    let mut single = vec![(id, position, velocity)];
-                    movement_system(&mut single);
+        let Some(&last) = entity_ids.last() else {
+            return;
+        };
+        let columns = self.entities.columns_mut_through(last);
+        movement_system(columns.positions, columns.velocities);
+    }
+
+    /// Push overlapping moving units apart.
+    ///
+    /// Only units with both a collider and movement take part, and aircraft
+    /// only jostle other aircraft. A push that would carry a ground unit
+    /// from open ground into a blocked cell is dropped.
+    fn run_avoidance_system(&mut self, entity_ids: &[EntityId]) {
+        let Some(&last) = entity_ids.last() else {
+            return;
+        };
+        let (air, ground): (Vec<_>, Vec<_>) = self
+            .entities
+            .iter_through(last)
+            .filter_map(|(&id, entity)| {
+                entity.movement.as_ref()?;
+                let queue = entity.command_queue.as_ref();
+                let waypoint = entity.path_waypoints.as_ref().and_then(|w| w.first());
+                let heading = match queue.and_then(CommandQueue::current) {
+                    Some(Command::MoveTo(target) | Command::AttackMove(target)) => {
+                        Some(*waypoint.unwrap_or(target))
+                    }
+                    _ => None,
+                };
+                let body = AvoidanceBody {
+                    id,
+                    position: entity.position?.value,
+                    radius: entity.collider?.radius,
+                    heading,
+                    final_leg: waypoint.is_none(),
+                    settled: queue.map_or(true, CommandQueue::is_empty),
+                };
+                Some((entity.domain().ignores_terrain(), body))
+            })
+            .partition(|(airborne, _)| *airborne);
+        for (airborne, layer) in [(false, ground), (true, air)] {
+            let bodies: Vec<AvoidanceBody> = layer.into_iter().map(|(_, body)| body).collect();
+            self.separate_bodies(&bodies, airborne);
+        }
+    }
+
+    /// Push apart overlapping bodies that share a movement layer.
+    fn separate_bodies(&mut self, bodies: &[AvoidanceBody], airborne: bool) {
+        if bodies.len() < 2 {
+            return;
+        }
+
+        let avoidance = avoidance_system(bodies);
+        for (id, push) in avoidance.pushes {
+            let Some(position) = self.entities.get_mut(id).and_then(|e| e.position.as_mut()) else {
+                continue;
+            };
+            let moved = position.value + push;
+            let walkable = |pos| {
+                self.nav_grid
+                    .world_to_grid(pos)
+                    .map_or(true, |(x, y)| self.nav_grid.is_walkable(x, y))
+            };
+            // Nor is anyone pushed up or off a cliff
+            let climbs = match (
+                self.nav_grid.world_to_grid(position.value),
+                self.nav_grid.world_to_grid(moved),
+            ) {
+                (Some(from), Some(to)) => !self.nav_grid.can_step(from, to),
+                _ => false,
+            };
+            if airborne || (walkable(moved) && !climbs) || !walkable(position.value) {
+                position.value = moved;
+            }
+        }
+        for id in avoidance.arrived {
+            if let Some(mut entity) = self.entities.get_mut(id) {
+                entity.path_waypoints = None;
+            }
+            self.finish_command(id);
+        }
+        for id in avoidance.passed {
+            if let Some(waypoints) = self
+                .entities
+                .get_mut(id)
+                .and_then(|EntityMut { row, .. }| row.path_waypoints.as_mut())
+            {
+                if !waypoints.is_empty() {
+                    waypoints.remove(0);
                 }
             }
         }
     }
 
-    /// Run the combat system on all applicable entities.
-    fn run_combat_system(&mut self, entity_ids: &[EntityId]) -> Vec<DamageEvent> {
-        // Build position lookup
-        let positions: Vec<(EntityId, Position)> = entity_ids
+    /// Recompute each faction's visible cells from its units' sight.
+    fn run_fog_system(&mut self) {
+        let columns = self.entities.columns();
+        let viewers: Vec<_> = columns
+            .positions
             .iter()
-            .filter_map(|&id| {
-                self.entities
-                    .get(id)
-                    .and_then(|e| e.position.map(|p| (id, p)))
+            .zip(columns.factions)
+            .enumerate()
+            .filter_map(|(row, (position, faction))| {
+                let (position, faction) = ((*position)?.value, (*faction)?.faction);
+                let entity = columns.entity(row)?;
+                let level = (!entity.domain().ignores_terrain())
+                    .then(|| self.nav_grid.elevation_at(position));
+                Some((faction, position, sight_range(entity), level))
             })
             .collect();
+        self.fog.update(&viewers, &self.nav_grid);
+    }
+
+    /// The entities present at the start of the tick whose row passes
+    /// `keep`, picked out with one scan rather than a lookup per ID.
+    fn ids_with(
+        &self,
+        entity_ids: &[EntityId],
+        keep: impl Fn(&EntityRow) -> bool,
+    ) -> Vec<EntityId> {
+        match entity_ids.last() {
+            Some(&last) => self.entities.ids_through(last, keep),
+            None => Vec::new(),
+        }
+    }
+
+    /// Positions of the entities present at the start of the tick, in ID
+    /// order for [`PositionLookup`].
+    fn positions_through(&self, entity_ids: &[EntityId]) -> Vec<(EntityId, Position)> {
+        let Some(&last) = entity_ids.last() else {
+            return Vec::new();
+        };
+        let columns = self.entities.columns_through(last);
+        columns
+            .ids
+            .iter()
+            .zip(columns.positions)
+            .filter_map(|(&id, position)| Some((id, (*position)?)))
+            .collect()
+    }
+
+    /// Run the combat system on all applicable entities.
+    fn run_combat_system(
+        &mut self,
+        entity_ids: &[EntityId],
+    ) -> (Vec<DamageEvent>, Vec<SplashEvent>) {
+        let Some(&last) = entity_ids.last() else {
+            return (Vec::new(), Vec::new());
+        };
+        // Build position lookup
+        let positions = self.positions_through(entity_ids);
         let pos_lookup = PositionLookup::new(&positions);
 
         let mut all_damage_events = Vec::new();
+        let mut splashes = Vec::new();
+        // Shots beyond the projectile pool land instantly
+        let mut projectile_slots = self.projectile_slots();
+
+        // Only rows with a position, combat stats and a target can fire
+        let columns = self.entities.columns_through(last);
+        let attackers: Vec<EntityId> = columns
+            .ids
+            .iter()
+            .zip(columns.positions)
+            .zip(columns.combat_stats)
+            .zip(columns.rows)
+            .filter(|(((_, position), stats), row)| {
+                position.is_some()
+                    && stats.is_some()
+                    && row.as_ref().is_some_and(|r| r.attack_target.is_some())
+            })
+            .map(|(((&id, _), _), _)| id)
+            .collect();
 
         // Process attackers one at a time to avoid borrow issues
-        for &attacker_id in entity_ids {
+        for attacker_id in attackers {
             let attacker_data = {
                 let entity = match self.entities.get(attacker_id) {
                     Some(e) => e,
@@ -695,64 +2289,187 @@ impl Simulation {
                     Some(s) => *s,
                     None => continue,
                 };
+                // Unfinished structures cannot fight yet
+                if entity
+                    .building
+                    .as_ref()
+                    .is_some_and(|b| !b.is_construction_complete())
+                {
+                    continue;
+                }
+
+                let ground_target = match entity.command_queue.as_ref().and_then(|q| q.current()) {
+                    Some(Command::AttackGround(point)) => Some(*point),
+                    _ => None,
+                };
 
-                (position, attack_target, combat_stats)
+                (
+                    position,
+                    attack_target,
+                    combat_stats,
+                    entity.ammunition,
+                    ground_target,
+                    self.firing_level(entity),
+                    entity.armament.clone(),
+                )
             };
 
-            let (position, mut attack_target, mut combat_stats) = attacker_data;
+            let (
+                position,
+                mut attack_target,
+                mut combat_stats,
+                mut ammunition,
+                ground_target,
+                firing_level,
+                mut armament,
+            ) = attacker_data;
+            let has_ammo = !matches!(ammunition, Some(a) if a.is_empty());
+
+            // Units with several weapons fire the best one for the target
+            let armed = armament.as_ref().map(|armament| {
+                self.choose_weapon(
+                    armament,
+                    &combat_stats,
+                    position.value,
+                    ground_target,
+                    attack_target.target,
+                    &pos_lookup,
+                )
+            });
+            if let (Some(armament), Some(armed)) = (armament.as_mut(), armed) {
+                combat_stats = armament.weapons[armed].arm(combat_stats);
+                if ground_target.is_some() || attack_target.target.is_some() {
+                    armament.tick_idle_cooldowns(armed);
+                }
+                // Splash from the shot only reaches what the armed weapon can hit
+                if let Some(entity) = self.entities.get_mut(attacker_id) {
+                    *entity.combat_stats = Some(combat_stats);
+                }
+            }
+
+            if let Some(point) = ground_target {
+                // Ground fire takes precedence over any unit target
+                attack_target.clear();
+                if combat_stats.cooldown_remaining > 0 {
+                    combat_stats.cooldown_remaining -= 1;
+                }
 
-            // Find target and deal damage
-            if let Some(target_id) = attack_target.target {
+                let range_sq = combat_stats.range * combat_stats.range;
+                if position.value.distance_squared(point) <= range_sq
+                    && combat_stats.cooldown_remaining == 0
+                    && has_ammo
+                {
+                    let weapon_stats = combat_stats
+                        .to_weapon_stats()
+                        .with_rolled_damage(&mut self.rng);
+                    let target_level = self.nav_grid.elevation_at(point);
+                    if let Some(weapon_stats) =
+                        self.high_ground_shot(weapon_stats, firing_level, Some(target_level))
+                    {
+                        let (damage, splash) =
+                            self.detonate(attacker_id, point, &weapon_stats, None, true, last);
+                        all_damage_events.extend(damage);
+                        splashes.push(splash);
+                    }
+                    combat_stats.cooldown_remaining = combat_stats.attack_cooldown;
+                    if let Some(ammo) = ammunition.as_mut() {
+                        ammo.spend();
+                    }
+                }
+            } else if let Some(target_id) = attack_target.target {
                 // Tick down cooldown
                 if combat_stats.cooldown_remaining > 0 {
                     combat_stats.cooldown_remaining -= 1;
                 }
 
-                // Get target position
-                if let Some(target_pos) = pos_lookup.get(target_id) {
+                let out_of_reach = self
+                    .entities
+                    .get(target_id)
+                    .is_some_and(|target| !combat_stats.targets.can_hit(target.domain()));
+                if out_of_reach {
+                    // The weapon cannot reach the layer the target moves in
+                    attack_target.clear();
+                } else if let Some(target_pos) = pos_lookup.get(target_id) {
                     // Check range
                     let range_sq = combat_stats.range * combat_stats.range;
                     let dist_sq = position.value.distance_squared(target_pos.value);
 
-                    if dist_sq <= range_sq && combat_stats.cooldown_remaining == 0 {
-                        if combat_stats.uses_projectiles() {
-                            let projectile = Projectile::new(
-                                attacker_id,
-                                target_id,
-                                combat_stats.damage,
-                                combat_stats.damage_type,
-                                combat_stats.projectile_speed,
-                            );
-                            self.spawn_projectile(position.value, projectile);
-                            combat_stats.cooldown_remaining = combat_stats.attack_cooldown;
-                        } else if let Some(target_entity) = self.entities.get_mut(target_id) {
-                            if let Some(ref mut health) = target_entity.health.as_mut() {
-                                // Use resistance-based damage calculation
-                                let weapon_stats = combat_stats.to_weapon_stats();
-                                let target_stats = target_entity
-                                    .combat_stats
-                                    .map(|s| s.to_resistance_stats())
-                                    .unwrap_or_default();
-
-                                let damage = crate::combat::calculate_resistance_damage(
-                                    &weapon_stats,
-                                    &target_stats,
-                                );
-                                health.apply_damage(damage);
-
-                                all_damage_events.push(DamageEvent {
-                                    attacker: attacker_id,
-                                    target: target_id,
-                                    damage,
-                                });
-
-                                // Reset cooldown
+                    if dist_sq <= range_sq && combat_stats.cooldown_remaining == 0 && has_ammo {
+                        let weapon_stats = combat_stats
+                            .to_weapon_stats()
+                            .with_rolled_damage(&mut self.rng);
+                        let target_level = self
+                            .entities
+                            .get(target_id)
+                            .and_then(|target| self.firing_level(target));
+                        let shot = self.high_ground_shot(weapon_stats, firing_level, target_level);
+                        let pooled = !matches!(projectile_slots, Some(0));
+                        if let Some(weapon_stats) = shot {
+                            if combat_stats.uses_projectiles() && pooled {
+                                if let Some(slots) = projectile_slots.as_mut() {
+                                    *slots -= 1;
+                                }
+                                let projectile = Projectile::new(
+                                    attacker_id,
+                                    target_id,
+                                    weapon_stats.damage,
+                                    combat_stats.damage_type,
+                                    combat_stats.projectile_speed,
+                                )
+                                .with_splash(weapon_stats.splash);
+                                self.spawn_projectile(position.value, projectile);
                                 combat_stats.cooldown_remaining = combat_stats.attack_cooldown;
-
-                                // Clear target if dead
-                                if health.is_dead() {
-                                    attack_target.clear();
+                                if let Some(ammo) = ammunition.as_mut() {
+                                    ammo.spend();
                                 }
+                            } else if let Some(target_entity) = self.entities.get_mut(target_id) {
+                                if let Some(ref mut health) = target_entity.health.as_mut() {
+                                    // Use resistance-based damage calculation
+                                    let target_stats = target_entity
+                                        .combat_stats
+                                        .map(|s| s.to_resistance_stats())
+                                        .unwrap_or_default();
+
+                                    let damage =
+                                        calculate_resistance_damage(&weapon_stats, &target_stats);
+                                    health.apply_damage(damage);
+
+                                    all_damage_events.push(DamageEvent {
+                                        attacker: attacker_id,
+                                        target: target_id,
+                                        damage,
+                                    });
+
+                                    // Reset cooldown
+                                    combat_stats.cooldown_remaining = combat_stats.attack_cooldown;
+                                    if let Some(ammo) = ammunition.as_mut() {
+                                        ammo.spend();
+                                    }
+
+                                    // Clear target if dead
+                                    if health.is_dead() {
+                                        attack_target.clear();
+                                    }
+
+                                    if weapon_stats.splash.is_area() {
+                                        let (damage, splash) = self.detonate(
+                                            attacker_id,
+                                            target_pos.value,
+                                            &weapon_stats,
+                                            Some(target_id),
+                                            false,
+                                            last,
+                                        );
+                                        all_damage_events.extend(damage);
+                                        splashes.push(splash);
+                                    }
+                                }
+                            }
+                        } else {
+                            // Fired uphill and missed
+                            combat_stats.cooldown_remaining = combat_stats.attack_cooldown;
+                            if let Some(ammo) = ammunition.as_mut() {
+                                ammo.spend();
                             }
                         }
                     }
@@ -762,49 +2479,425 @@ impl Simulation {
                 }
             }
 
+            if let (Some(armament), Some(armed)) = (armament.as_mut(), armed) {
+                armament.weapons[armed].cooldown_remaining = combat_stats.cooldown_remaining;
+                combat_stats = armament.summarize(combat_stats);
+            }
+
             // Update attacker's components
-            if let Some(entity) = self.entities.get_mut(attacker_id) {
+            if let Some(mut entity) = self.entities.get_mut(attacker_id) {
                 entity.attack_target = Some(attack_target);
-                entity.combat_stats = Some(combat_stats);
+                *entity.combat_stats = Some(combat_stats);
+                entity.ammunition = ammunition;
+                if armament.is_some() {
+                    entity.armament = armament;
+                }
             }
         }
 
-        all_damage_events
+        (all_damage_events, splashes)
     }
 
-    /// Run the projectile system on all active projectiles.
-    fn run_projectile_system(&mut self, entity_ids: &[EntityId]) -> Vec<DamageEvent> {
-        let positions: Vec<(EntityId, Position)> = entity_ids
-            .iter()
-            .filter_map(|&id| {
-                self.entities
-                    .get(id)
-                    .and_then(|e| e.position.map(|p| (id, p)))
-            })
-            .collect();
-        let pos_lookup = PositionLookup::new(&positions);
+    /// Index of the weapon a unit with an [`Armament`] fires at the ground
+    /// it is shelling or the unit it is attacking.
+    fn choose_weapon(
+        &self,
+        armament: &Armament,
+        holder: &CombatStats,
+        position: Vec2Fixed,
+        ground_target: Option<Vec2Fixed>,
+        target: Option<EntityId>,
+        positions: &PositionLookup,
+    ) -> usize {
+        if let Some(point) = ground_target {
+            return armament.choose(holder, None, position.distance_squared(point));
+        }
+        let Some((target, target_pos)) =
+            target.and_then(|id| Some((self.entities.get(id)?, positions.get(id)?)))
+        else {
+            return 0;
+        };
+        let resistance = target
+            .combat_stats
+            .map(|s| s.to_resistance_stats())
+            .unwrap_or_default();
+        armament.choose(
+            holder,
+            Some((target.domain(), resistance)),
+            position.distance_squared(target_pos.value),
+        )
+    }
 
-        let mut projectile_data: Vec<(EntityId, Position, Projectile)> = entity_ids
-            .iter()
-            .filter_map(|&id| {
-                self.entities
-                    .get(id)
-                    .and_then(|entity| Some((id, entity.position?, entity.projectile?)))
-            })
-            .collect();
+    /// Elevation level an entity fires from and is fired at, or None for
+    /// aircraft, which elevation does not affect.
+    fn firing_level(&self, entity: EntityRef<'_>) -> Option<u8> {
+        if entity.domain().ignores_terrain() {
+            return None;
+        }
+        entity.position.map(|p| self.nav_grid.elevation_at(p.value))
+    }
 
-        if projectile_data.is_empty() {
-            return Vec::new();
+    /// Apply the high-ground rules to a shot between two levels, or pass it
+    /// through unchanged when either end is airborne.
+    fn high_ground_shot(
+        &mut self,
+        weapon: WeaponStats,
+        from: Option<u8>,
+        to: Option<u8>,
+    ) -> Option<WeaponStats> {
+        match (from, to) {
+            (Some(from), Some(to)) => self.high_ground.apply(weapon, from, to, &mut self.rng),
+            _ => Some(weapon),
         }
+    }
 
-        let mut target_data: Vec<(EntityId, Health, CombatStats)> = entity_ids
-            .iter()
-            .filter_map(|&id| {
-                self.entities
-                    .get(id)
-                    .and_then(|entity| Some((id, entity.health?, entity.combat_stats?)))
-            })
-            .collect();
+    /// Spread a splash weapon's shot around `center`.
+    ///
+    /// Everything with health inside the blast takes the weapon's damage,
+    /// scaled by its falloff; the firer itself is spared, as is
+    /// `direct_hit`, which the shot already damaged. Allies of the firer are
+    /// spared too unless the weapon allows friendly fire or the shot was
+    /// `forced` at the ground, and so are units in layers the firer's
+    /// weapon cannot hit. Only entities up to `last`, those present at the
+    /// start of the tick, are caught in the blast.
+    fn detonate(
+        &mut self,
+        attacker_id: EntityId,
+        center: Vec2Fixed,
+        weapon_stats: &WeaponStats,
+        direct_hit: Option<EntityId>,
+        forced: bool,
+        last: EntityId,
+    ) -> (Vec<DamageEvent>, SplashEvent) {
+        let attacker = self.entities.get(attacker_id);
+        let spared_faction = attacker
+            .and_then(|e| e.faction)
+            .map(|member| member.faction)
+            .filter(|_| !forced && !weapon_stats.splash.friendly_fire);
+        let reach = attacker
+            .and_then(|e| e.combat_stats)
+            .map_or(WeaponTargets::Any, |s| s.targets);
+        let mut events = Vec::new();
+        let mut affected: Vec<EntityId> = direct_hit.into_iter().collect();
+
+        for (&target_id, target) in self.entities.iter_mut_through(last) {
+            if target_id == attacker_id || Some(target_id) == direct_hit {
+                continue;
+            }
+            if target.projectile.is_some() {
+                continue;
+            }
+            if spared_faction.is_some() && target.faction.map(|f| f.faction) == spared_faction {
+                continue;
+            }
+            if !reach.can_hit(target.domain()) {
+                continue;
+            }
+            let Some(position) = target.position else {
+                continue;
+            };
+            let distance_sq = position.value.distance_squared(center);
+            let Some(damage) = weapon_stats
+                .splash
+                .damage_at(weapon_stats.damage, distance_sq)
+            else {
+                continue;
+            };
+            let target_stats = target
+                .combat_stats
+                .map(|s| s.to_resistance_stats())
+                .unwrap_or_default();
+            let Some(health) = target.health.as_mut() else {
+                continue;
+            };
+            if health.is_dead() {
+                continue;
+            }
+
+            let blast = WeaponStats {
+                damage,
+                ..*weapon_stats
+            };
+            let damage = calculate_resistance_damage(&blast, &target_stats);
+            health.apply_damage(damage);
+            events.push(DamageEvent {
+                attacker: attacker_id,
+                target: target_id,
+                damage,
+            });
+            affected.push(target_id);
+        }
+
+        affected.sort_unstable();
+        let splash = SplashEvent {
+            attacker: attacker_id,
+            center,
+            affected,
+        };
+        (events, splash)
+    }
+
+    /// Refill ammunition of units near a friendly resupply source.
+    ///
+    /// Each unit takes the best rate among the sources in range, so stacking
+    /// depots and supply units does not multiply the refill.
+    fn run_resupply_system(&mut self, entity_ids: &[EntityId]) {
+        let sources: Vec<(Position, FactionId, Resupply)> = self
+            .ids_with(entity_ids, |row| row.resupply.is_some())
+            .into_iter()
+            .filter_map(|id| {
+                let e = self.entities.get(id)?;
+                if e.health.is_some_and(|h| h.is_dead()) {
+                    return None;
+                }
+                Some((e.position?, e.faction?.faction, e.resupply?))
+            })
+            .collect();
+
+        if sources.is_empty() {
+            return;
+        }
+
+        for id in self.ids_with(entity_ids, |row| row.ammunition.is_some()) {
+            let Some(entity) = self.entities.get_mut(id) else {
+                continue;
+            };
+            let (Some(position), Some(faction), Some(ammo)) = (
+                *entity.position,
+                entity.faction.map(|f| f.faction),
+                entity.row.ammunition.as_mut(),
+            ) else {
+                continue;
+            };
+            if ammo.is_full() {
+                continue;
+            }
+
+            let rate = sources
+                .iter()
+                .filter(|(pos, owner, supply)| {
+                    *owner == faction
+                        && position.value.distance_squared(pos.value)
+                            <= supply.radius * supply.radius
+                })
+                .map(|(_, _, supply)| supply.rate)
+                .max();
+
+            if let Some(rate) = rate {
+                ammo.refill(rate);
+            }
+        }
+    }
+
+    /// Heal entities that have been out of combat long enough.
+    ///
+    /// Runs after all damage for the tick, so a hit this tick always resets
+    /// the out-of-combat delay before any healing happens.
+    fn run_regeneration_system(&mut self, entity_ids: &[EntityId]) -> Vec<(EntityId, u32)> {
+        let mut regenerated = Vec::new();
+        for id in self.ids_with(entity_ids, |row| row.regeneration.is_some()) {
+            let Some(entity) = self.entities.get_mut(id) else {
+                continue;
+            };
+            let faction = entity.faction.map(|f| f.faction);
+            let (Some(regen), Some(health)) =
+                (entity.row.regeneration.as_mut(), entity.health.as_mut())
+            else {
+                continue;
+            };
+            let healed = regen.tick(health, faction);
+            if healed > 0 {
+                regenerated.push((id, healed));
+            }
+        }
+        regenerated
+    }
+
+    /// Leave a wreck where a salvageable entity died.
+    fn leave_wreck(&mut self, dead: &Entity) -> Option<SalvageEvent> {
+        let value = dead.salvageable?.value;
+        let position = dead.position?.value;
+        if value == 0 {
+            return None;
+        }
+        let id = WreckId(self.next_wreck_id);
+        self.next_wreck_id += 1;
+        self.wrecks.insert(
+            id,
+            Wreck {
+                position,
+                remaining: value,
+                spawned_at: self.tick,
+                owner: dead.faction,
+                name: dead.debug_name.clone(),
+            },
+        );
+        Some(SalvageEvent::WreckSpawned {
+            wreck: id,
+            entity: dead.id,
+            value,
+        })
+    }
+
+    /// Decay old wrecks, then let salvagers collect from the closest wreck
+    /// in range and clear the wrecks they empty.
+    ///
+    /// Salvagers with an attack target collect at half rate.
+    fn run_salvage_system(&mut self, entity_ids: &[EntityId]) -> Vec<SalvageEvent> {
+        let mut events = Vec::new();
+        let (tick, rules) = (self.tick, self.salvage_rules);
+        self.wrecks.retain(|&wreck, w| {
+            let keep = !w.is_expired(tick, &rules);
+            if !keep {
+                events.push(SalvageEvent::WreckCleared {
+                    wreck,
+                    remaining: w.remaining,
+                });
+            }
+            keep
+        });
+        if self.wrecks.is_empty() {
+            return events;
+        }
+
+        let radius_sq = rules.radius * rules.radius;
+        for id in self.ids_with(entity_ids, |row| row.salvager.is_some()) {
+            let Some(entity) = self.entities.get(id) else {
+                continue;
+            };
+            let (Some(salvager), Some(position)) = (entity.salvager, entity.position) else {
+                continue;
+            };
+            let in_combat = entity
+                .attack_target
+                .as_ref()
+                .is_some_and(|t| t.target.is_some());
+
+            let mut closest: Option<(WreckId, Fixed)> = None;
+            for (&wreck_id, wreck) in &self.wrecks {
+                let dist_sq = wreck.position.distance_squared(position.value);
+                if dist_sq < radius_sq && closest.map_or(true, |(_, best)| dist_sq < best) {
+                    closest = Some((wreck_id, dist_sq));
+                }
+            }
+            let Some((wreck_id, _)) = closest else {
+                continue;
+            };
+            let Some(wreck) = self.wrecks.get_mut(&wreck_id) else {
+                continue;
+            };
+
+            let amount = salvager.rate_for(in_combat).min(wreck.remaining);
+            wreck.remaining -= amount;
+            events.push(SalvageEvent::Collected {
+                wreck: wreck_id,
+                collector: id,
+                faction: entity.faction.map(|f| f.faction),
+                amount,
+            });
+            if wreck.remaining == 0 {
+                self.wrecks.remove(&wreck_id);
+                events.push(SalvageEvent::WreckCleared {
+                    wreck: wreck_id,
+                    remaining: 0,
+                });
+            }
+        }
+        events
+    }
+
+    /// Relieve capacity pressure by culling cosmetic entities.
+    ///
+    /// Expired cosmetics go first, then the oldest (lowest ID), until the
+    /// count is back under the pressure threshold. Returns `None` when no
+    /// capacity is set or the count is below the threshold.
+    fn run_capacity_system(&mut self) -> Option<CapacityPressure> {
+        let capacity = self.capacity?;
+        let threshold = capacity.pressure_threshold();
+        let entities = self.entities.len();
+        if entities < threshold {
+            return None;
+        }
+
+        let tick = self.tick;
+        let mut candidates: Vec<(bool, EntityId)> = self
+            .entities
+            .iter()
+            .filter_map(|(&id, entity)| {
+                entity
+                    .cosmetic
+                    .map(|cosmetic| (!cosmetic.is_expired(tick), id))
+            })
+            .collect();
+        candidates.sort_unstable();
+
+        let excess = entities + 1 - threshold;
+        let culled: Vec<EntityId> = candidates
+            .into_iter()
+            .take(excess)
+            .map(|(_, id)| id)
+            .collect();
+        for &id in &culled {
+            self.entities.remove(id);
+        }
+
+        let pressure = CapacityPressure {
+            entities,
+            max_entities: capacity.max_entities,
+            culled,
+        };
+        tracing::debug!(
+            tick,
+            entities,
+            max = capacity.max_entities,
+            culled = pressure.culled.len(),
+            "Entity capacity pressure"
+        );
+        Some(pressure)
+    }
+
+    /// Projectile entities that may still be spawned this tick.
+    ///
+    /// Unlimited (`None`) unless the simulation is under capacity pressure.
+    fn projectile_slots(&self) -> Option<usize> {
+        let capacity = self.capacity?;
+        if self.entities.len() < capacity.pressure_threshold() {
+            return None;
+        }
+        let in_flight = self
+            .entities
+            .iter()
+            .filter(|(_, entity)| entity.projectile.is_some())
+            .count();
+        Some(capacity.projectile_pool.saturating_sub(in_flight))
+    }
+
+    /// Run the projectile system on all active projectiles.
+    fn run_projectile_system(
+        &mut self,
+        entity_ids: &[EntityId],
+    ) -> (Vec<DamageEvent>, Vec<SplashEvent>) {
+        let positions = self.positions_through(entity_ids);
+        let pos_lookup = PositionLookup::new(&positions);
+
+        let Some(&last) = entity_ids.last() else {
+            return (Vec::new(), Vec::new());
+        };
+        let mut projectile_data: Vec<(EntityId, Position, Projectile)> = self
+            .entities
+            .iter_through(last)
+            .filter_map(|(&id, entity)| Some((id, entity.position?, entity.projectile?)))
+            .collect();
+
+        if projectile_data.is_empty() {
+            return (Vec::new(), Vec::new());
+        }
+
+        let mut target_data: Vec<(EntityId, Health, CombatStats)> = self
+            .entities
+            .iter_through(last)
+            .filter_map(|(&id, entity)| Some((id, entity.health?, entity.combat_stats?)))
+            .collect();
 
         let mut projectile_refs: Vec<(EntityId, &mut Position, &Projectile)> = projectile_data
             .iter_mut()
@@ -824,16 +2917,23 @@ impl Simulation {
             .map(|(id, position, _)| (*id, *position))
             .collect();
 
+        let projectiles: HashMap<EntityId, Projectile> = projectile_data
+            .iter()
+            .map(|(id, _, projectile)| (*id, *projectile))
+            .collect();
         let mut damage_events = Vec::new();
+        let mut splashes = Vec::new();
 
         for update in updates {
             if update.hit {
+                let mut direct_hit = None;
                 if let Some(CombatEvent::ProjectileHit {
                     source,
                     target,
                     damage,
                 }) = update.event
                 {
+                    direct_hit = Some(target);
                     damage_events.push(DamageEvent {
                         attacker: source,
                         target,
@@ -845,29 +2945,45 @@ impl Simulation {
                         }
                     }
                 }
+                // Splash shells burst where the target stands; shots at
+                // vanished targets fizzle
+                let projectile = projectiles[&update.projectile_id];
+                let center = pos_lookup.get(projectile.target).map(|p| p.value);
+                if let (true, Some(center)) = (projectile.splash.is_area(), center) {
+                    let weapon_stats = WeaponStats::new(
+                        projectile.damage,
+                        ExtendedDamageType::from_damage_type(projectile.damage_type),
+                    )
+                    .with_splash(projectile.splash);
+                    let (damage, splash) = self.detonate(
+                        projectile.source,
+                        center,
+                        &weapon_stats,
+                        direct_hit,
+                        false,
+                        last,
+                    );
+                    damage_events.extend(damage);
+                    splashes.push(splash);
+                }
                 self.entities.remove(update.projectile_id);
             } else if let Some(new_pos) = position_map.remove(&update.projectile_id) {
                 if let Some(entity) = self.entities.get_mut(update.projectile_id) {
-                    entity.position = Some(new_pos);
+                    *entity.position = Some(new_pos);
                 }
             }
         }
 
-        damage_events
+        (damage_events, splashes)
     }
 
     /// Run the health system and return dead entity IDs.
     fn run_health_system(&self, entity_ids: &[EntityId]) -> Vec<EntityId> {
-        let health_data: Vec<(EntityId, &Health)> = entity_ids
-            .iter()
-            .filter_map(|&id| {
-                self.entities
-                    .get(id)
-                    .and_then(|e| e.health.as_ref().map(|h| (id, h)))
-            })
-            .collect();
-
-        health_system(&health_data)
+        let Some(&last) = entity_ids.last() else {
+            return Vec::new();
+        };
+        let columns = self.entities.columns_through(last);
+        health_system(columns.ids, columns.health)
     }
 
     /// Run the production system and return production events.
@@ -878,7 +2994,7 @@ impl Simulation {
         let mut buildings_data: Vec<(EntityId, ProductionQueue, ProductionBuilding, Position)> =
             Vec::new();
 
-        for &id in entity_ids {
+        for id in self.ids_with(entity_ids, |row| row.production_queue.is_some()) {
             if let Some(entity) = self.entities.get(id) {
                 if let (Some(position), Some(queue), Some(building)) = (
                     entity.position.as_ref(),
@@ -907,7 +3023,7 @@ impl Simulation {
 
         // Write back updated queues
         for (id, queue, _, _) in buildings_data {
-            if let Some(entity) = self.entities.get_mut(id) {
+            if let Some(mut entity) = self.entities.get_mut(id) {
                 entity.production_queue = Some(queue);
             }
         }
@@ -915,580 +3031,4669 @@ impl Simulation {
         events
     }
 
-    /// Spawn a projectile entity at the given position.
-    fn spawn_projectile(&mut self, position: Vec2Fixed, projectile: Projectile) -> EntityId {
-        let mut entity = Entity::new(0);
-        entity.position = Some(Position::new(position));
-        entity.projectile = Some(projectile);
-        self.entities.insert(entity)
-    }
-
-    /// Spawn a new entity with the given parameters.
-    ///
-    /// Returns the unique ID assigned to the new entity.
-    ///
-    /// # Arguments
-    ///
-    /// * `params` - Configuration for the new entity's components
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use rts_core::simulation::{Simulation, EntitySpawnParams};
-    /// use rts_core::math::{Fixed, Vec2Fixed};
-    ///
-    /// let mut sim = Simulation::new();
-    /// let unit = sim.spawn_entity(EntitySpawnParams {
-    ///     position: Some(Vec2Fixed::new(Fixed::from_num(100), Fixed::from_num(50))),
-    ///     health: Some(100),
-    ///     movement: Some(Fixed::from_num(2)),
-    ///     ..Default::default()
-    /// });
-    /// ```
-    pub fn spawn_entity(&mut self, params: EntitySpawnParams) -> EntityId {
-        let mut entity = Entity::new(0); // ID will be assigned by storage
-
-        if let Some(pos) = params.position {
-            entity.position = Some(Position::new(pos));
+    /// Advance the front of every finished facility's research queue, then
+    /// apply the technologies that complete.
+    fn run_research_system(&mut self, entity_ids: &[EntityId]) -> Vec<ResearchEvent> {
+        let mut events = Vec::new();
+        let mut completed = Vec::new();
+        for id in self.ids_with(entity_ids, |row| row.research.is_some()) {
+            let Some(mut entity) = self.entities.get_mut(id) else {
+                continue;
+            };
+            let Some(faction) = entity.faction.map(|f| f.faction) else {
+                continue;
+            };
+            if entity
+                .building
+                .as_ref()
+                .is_some_and(|b| !b.is_construction_complete())
+            {
+                continue;
+            }
+            let Some(facility) = entity.research.as_mut() else {
+                continue;
+            };
+            let Some(item) = facility.queue.front_mut() else {
+                continue;
+            };
+            if item.progress == 0 {
+                events.push(ResearchEvent::ResearchStarted {
+                    building: id,
+                    faction,
+                    tech_id: item.tech_id.clone(),
+                });
+            }
+            item.progress += 1;
+            if item.is_complete() {
+                if let Some(item) = facility.queue.pop_front() {
+                    completed.push((id, faction, item.tech_id));
+                }
+            }
         }
 
-        if let Some(vel) = params.velocity {
-            entity.velocity = Some(vel);
-        } else if params.position.is_some() {
-            // Default to zero velocity for positioned entities
-            entity.velocity = Some(Velocity::ZERO);
+        for (building, faction, tech_id) in completed {
+            self.complete_research(faction, &tech_id);
+            events.push(ResearchEvent::ResearchComplete {
+                building,
+                faction,
+                tech_id,
+            });
         }
+        events
+    }
 
-        if let Some(max_health) = params.health {
-            entity.health = Some(Health::new(max_health));
+    /// Mark a technology researched and apply its effects to the faction's
+    /// entities.
+    fn complete_research(&mut self, faction: FactionId, tech_id: &str) {
+        let Some(state) = self.tech.get_mut(&faction) else {
+            return;
+        };
+        if !state.researched.insert(tech_id.to_string()) {
+            return;
         }
-
-        if let Some(speed) = params.movement {
-            entity.movement = Some(Movement {
-                speed,
-                target: None,
-            });
-            // Units with movement get a command queue
-            entity.command_queue = Some(CommandQueue::new());
+        let Some(tech) = state.get(tech_id).cloned() else {
+            return;
+        };
+        for (_, mut entity) in self.entities.iter_mut() {
+            if !entity.faction.is_some_and(|f| f.faction == faction) {
+                continue;
+            }
+            if let Some(profile) = entity.tech_profile.clone() {
+                apply_tech_effects(&mut entity, &profile, &tech);
+            }
         }
+    }
 
-        if let Some(stats) = params.combat_stats {
-            entity.combat_stats = Some(stats);
+    /// Spawn a projectile entity at the given position.
+    /// Queue `dead` for rebuilding if one of its faction's mechanics
+    /// brings it back.
+    fn schedule_rebuild(&mut self, dead: &Entity) {
+        let Some(faction) = dead.faction else {
+            return;
+        };
+        let Some(plan) = self
+            .mechanics
+            .for_faction(faction.faction)
+            .find_map(|m| m.rebuild(dead))
+        else {
+            return;
+        };
+
+        let mut entity = dead.clone();
+        if let Some(health) = entity.health.as_mut() {
+            health.current = (health.max * plan.health_percent / 100).clamp(1, health.max);
+            if let Some(regen) = entity.row.regeneration.as_mut() {
+                regen.last_health = health.current;
+                regen.idle_ticks = 0;
+            }
+        }
+        if entity.attack_target.is_some() {
             entity.attack_target = Some(AttackTarget::new());
         }
-
-        if params.has_production_queue {
+        if entity.production_queue.is_some() {
             entity.production_queue = Some(ProductionQueue::new());
         }
-
-        if let Some(faction) = params.faction {
-            entity.faction = Some(faction);
+        if let Some(research) = entity.research.as_mut() {
+            research.queue.clear();
         }
+        self.rebuilds.push(PendingRebuild {
+            due: self.tick + u64::from(plan.delay),
+            replaces: dead.id,
+            entity,
+        });
+    }
 
-        if params.is_depot {
-            entity.depot = Some(Depot);
+    /// Return rebuilt structures whose time has come, under new IDs.
+    fn run_rebuild_system(&mut self) -> Vec<(EntityId, EntityId)> {
+        let tick = self.tick;
+        let (due, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.rebuilds)
+            .into_iter()
+            .partition(|r| r.due <= tick);
+        self.rebuilds = waiting;
+
+        due.into_iter()
+            .map(|rebuild| {
+                if let (Some(faction), Some(position)) =
+                    (rebuild.entity.faction, rebuild.entity.position)
+                {
+                    self.fog.reveal(
+                        faction.faction,
+                        position.value,
+                        sight_range(rebuild.entity.view()),
+                    );
+                }
+                let site = rebuild.entity.site;
+                let rebuilt = self.entities.insert(rebuild.entity);
+                if let Some(site) = site {
+                    self.claim_ground(rebuilt, &site);
+                }
+                (rebuild.replaces, rebuilt)
+            })
+            .collect()
+    }
+
+    /// Expire shields, then fire every ordered ability that is in range and
+    /// off cooldown, moving casters that are out of range towards their
+    /// target.
+    fn run_ability_system(
+        &mut self,
+        entity_ids: &[EntityId],
+    ) -> (Vec<AbilityUsed>, Vec<DamageEvent>) {
+        let tick = self.tick;
+        for id in self.ids_with(entity_ids, |row| row.shield.is_some()) {
+            let Some(mut entity) = self.entities.get_mut(id) else {
+                continue;
+            };
+            if let (Some(shield), Some(health)) = (entity.shield, entity.health.as_mut()) {
+                if shield.expires_at <= tick {
+                    shield.lower(health);
+                    entity.shield = None;
+                }
+            }
         }
 
-        entity.vision_range = params.vision_range;
+        let mut used = Vec::new();
+        let mut damage_events = Vec::new();
+        for id in self.ids_with(entity_ids, |row| {
+            matches!(row.current_command(), Some(Command::UseAbility { .. }))
+        }) {
+            let Some(entity) = self.entities.get(id) else {
+                continue;
+            };
+            let Some(Command::UseAbility { ability_id, target }) = entity
+                .command_queue
+                .as_ref()
+                .and_then(|queue| queue.current())
+                .cloned()
+            else {
+                continue;
+            };
+            let Some(position) = entity.position.map(|p| p.value) else {
+                continue;
+            };
+            let slot = entity
+                .abilities
+                .as_ref()
+                .and_then(|a| a.get(&ability_id))
+                .cloned();
+            let point = match target {
+                AbilityTarget::Point(point) => Some(point),
+                AbilityTarget::Entity(target) => self
+                    .entities
+                    .get(target)
+                    .and_then(|e| e.position)
+                    .map(|p| p.value),
+                AbilityTarget::Caster => Some(position),
+            };
+            let (Some(slot), Some(point)) = (slot, point) else {
+                // Lost the ability or the target
+                self.finish_command(id);
+                continue;
+            };
 
-        self.entities.insert(entity)
-    }
+            let range = slot.ability.range;
+            let in_range = matches!(target, AbilityTarget::Caster)
+                || position.distance_squared(point) <= range * range;
+            let speed = entity.movement.as_ref().map(|m| m.speed);
+            if !in_range && speed.is_none() {
+                self.finish_command(id);
+                continue;
+            }
+            if let Some(velocity) = self.entities.get_mut(id).and_then(|e| e.velocity.as_mut()) {
+                velocity.value = match speed.filter(|_| !in_range) {
+                    Some(speed) => {
+                        let direction = crate::systems::normalize_vec2(point - position);
+                        Vec2Fixed::new(direction.x * speed, direction.y * speed)
+                    }
+                    None => Vec2Fixed::ZERO,
+                };
+            }
+            if !in_range || slot.ready_at > tick {
+                continue;
+            }
 
-    /// Remove an entity from the simulation.
-    ///
-    /// Returns `Ok(())` if the entity was removed, or an error if it didn't exist.
-    ///
-    /// # Arguments
-    ///
-    /// * `id` - The entity ID to remove
-    ///
-    /// # Errors
-    ///
-    /// Returns [`GameError::EntityNotFound`] if the entity doesn't exist.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use rts_core::simulation::{Simulation, EntitySpawnParams};
-    ///
-    /// let mut sim = Simulation::new();
-    /// let id = sim.spawn_entity(EntitySpawnParams::default());
-    /// sim.despawn_entity(id).unwrap();
-    /// ```
-    pub fn despawn_entity(&mut self, id: EntityId) -> Result<()> {
-        if self.entities.remove(id).is_some() {
-            Ok(())
-        } else {
-            Err(GameError::EntityNotFound(id))
+            let affected = self.ability_targets(id, &slot.ability, target, point);
+            for &target_id in &affected {
+                let Some(target) = self.entities.get_mut(target_id) else {
+                    continue;
+                };
+                let Some(health) = target.health.as_mut() else {
+                    continue;
+                };
+                match slot.ability.effect {
+                    AbilityEffect::Heal { amount } => {
+                        health.heal(amount);
+                    }
+                    AbilityEffect::Damage { amount } => {
+                        let damage = health.apply_damage(amount);
+                        damage_events.push(DamageEvent {
+                            attacker: id,
+                            target: target_id,
+                            damage,
+                        });
+                    }
+                    AbilityEffect::Shield { amount, duration } => {
+                        // A fresh shield replaces the old one
+                        if let Some(old) = target.row.shield.take() {
+                            old.lower(health);
+                        }
+                        let expires_at = tick + u64::from(duration);
+                        target.row.shield = Some(Shield::raise(health, amount, expires_at));
+                    }
+                }
+            }
+
+            let Some(mut caster) = self.entities.get_mut(id) else {
+                continue;
+            };
+            if let Some(slot) = caster
+                .abilities
+                .as_mut()
+                .and_then(|a| a.get_mut(&ability_id))
+            {
+                slot.ready_at = tick + u64::from(slot.ability.cooldown);
+            }
+            used.push(AbilityUsed {
+                caster: id,
+                faction: caster.faction.map(|f| f.faction),
+                ability_id,
+                cost: slot.ability.cost,
+                affected,
+            });
+            self.finish_command(id);
         }
+        (used, damage_events)
     }
 
-    /// Queue a command for an entity.
+    /// Entities an ability used by `caster` at `point` lands on, in ID
+    /// order: allies for heals and shields, enemies for damage.
+    fn ability_targets(
+        &self,
+        caster: EntityId,
+        ability: &AbilityData,
+        target: AbilityTarget,
+        point: Vec2Fixed,
+    ) -> Vec<EntityId> {
+        let Some(own) = self.entities.get(caster).and_then(|e| e.faction) else {
+            return Vec::new();
+        };
+        let friendly = ability.is_friendly();
+        let eligible = |e: EntityRef<'_>| {
+            e.health.is_some()
+                && e.faction
+                    .is_some_and(|f| f.is_allied_with(&own) == friendly)
+        };
+
+        if ability.radius == Fixed::ZERO {
+            let single = match target {
+                AbilityTarget::Entity(id) => id,
+                AbilityTarget::Caster => caster,
+                AbilityTarget::Point(_) => return Vec::new(),
+            };
+            return self
+                .entities
+                .get(single)
+                .filter(|e| eligible(*e))
+                .map(|_| vec![single])
+                .unwrap_or_default();
+        }
+
+        let radius_sq = ability.radius * ability.radius;
+        self.entities
+            .iter()
+            .filter(|(_, e)| {
+                eligible(*e)
+                    && e.position
+                        .is_some_and(|p| p.value.distance_squared(point) <= radius_sq)
+            })
+            .map(|(&id, _)| id)
+            .collect()
+    }
+
+    /// Drop a unit's current order and stop it.
+    fn finish_command(&mut self, id: EntityId) {
+        let Some(mut entity) = self.entities.get_mut(id) else {
+            return;
+        };
+        if let Some(queue) = entity.command_queue.as_mut() {
+            queue.pop();
+        }
+        if let Some(velocity) = entity.velocity.as_mut() {
+            velocity.value = Vec2Fixed::ZERO;
+        }
+        self.plan_current_command(id);
+    }
+
+    /// Plan the path for the command now at the front of an entity's
+    /// queue, as [`apply_command`](Self::apply_command) does for a new
+    /// order, so shift-queued moves follow waypoints around obstacles too.
+    fn plan_current_command(&mut self, id: EntityId) {
+        let Some(ent) = self.entities.get(id) else {
+            return;
+        };
+        let Some(command) = ent.command_queue.as_ref().and_then(CommandQueue::current) else {
+            return;
+        };
+        if let Some(path) = self.plan_path(ent, command, &BTreeMap::new()) {
+            if let Some(mut ent) = self.entities.get_mut(id) {
+                ent.path_waypoints = path;
+            }
+        }
+    }
+
+    /// Restore the health of allies that units are ordered to repair,
+    /// moving repairers that are out of range towards their patient.
     ///
-    /// The command is added to the entity's command queue and will be
-    /// processed on subsequent ticks.
+    /// An order is finished once the patient is whole, and dropped when it
+    /// is gone, has changed sides or is not the kind the repairer mends.
+    fn run_repair_system(&mut self, entity_ids: &[EntityId]) -> Vec<RepairEvent> {
+        let tick = self.tick;
+        let mut repairs = Vec::new();
+        for id in self.ids_with(entity_ids, |row| {
+            matches!(row.current_command(), Some(Command::Repair(_)))
+        }) {
+            let Some(entity) = self.entities.get(id) else {
+                continue;
+            };
+            let Some(Command::Repair(target_id)) = entity
+                .command_queue
+                .as_ref()
+                .and_then(|queue| queue.current())
+                .cloned()
+            else {
+                continue;
+            };
+            let (Some(position), Some(repairer)) =
+                (entity.position.map(|p| p.value), entity.repairer)
+            else {
+                continue;
+            };
+            let faction = entity.faction.map(|f| f.faction);
+            let patient = self.entities.get(target_id).filter(|t| {
+                t.faction.map(|f| f.faction) == faction
+                    && repairer
+                        .kind
+                        .treats(t.movement.is_none(), t.tech_profile.as_ref())
+                    && t.health.is_some_and(|h| !h.is_dead() && h.current < h.max)
+            });
+            let Some(target) = patient.and_then(|t| t.position).map(|p| p.value) else {
+                self.finish_command(id);
+                continue;
+            };
+            if !self.approach(id, position, target, REPAIR_RANGE)
+                || tick % u64::from(repairer.interval) != 0
+            {
+                continue;
+            }
+            let Some(health) = self
+                .entities
+                .get_mut(target_id)
+                .and_then(|t| t.health.as_mut())
+            else {
+                continue;
+            };
+            repairs.push(RepairEvent {
+                repairer: id,
+                target: target_id,
+                faction,
+                amount: health.heal(repairer.amount),
+                cost: repairer.cost,
+            });
+        }
+        repairs
+    }
+
+    /// Advance every construction site workers are building, by one tick
+    /// for each worker within reach, moving workers that are out of reach
+    /// towards their site.
     ///
-    /// # Arguments
+    /// An order is finished once the site is complete, and dropped when
+    /// the site is gone or has changed sides.
+    fn run_construction_system(&mut self, entity_ids: &[EntityId]) -> Vec<ConstructionEvent> {
+        let cell_size = self.placement.cell_size();
+        let mut events = Vec::new();
+        for id in self.ids_with(entity_ids, |row| {
+            matches!(row.current_command(), Some(Command::Construct(_)))
+        }) {
+            let Some(entity) = self.entities.get(id) else {
+                continue;
+            };
+            let Some(Command::Construct(site_id)) = entity
+                .command_queue
+                .as_ref()
+                .and_then(|queue| queue.current())
+                .cloned()
+            else {
+                continue;
+            };
+            let Some(position) = entity.position.map(|p| p.value) else {
+                continue;
+            };
+            let faction = entity.faction.map(|f| f.faction);
+            let site = self.entities.get(site_id).filter(|s| {
+                s.faction.map(|f| f.faction) == faction
+                    && s.building
+                        .as_ref()
+                        .is_some_and(|b| !b.is_construction_complete())
+            });
+            let Some((target, reach)) =
+                site.and_then(|s| Some((s.position?.value, s.site?.reach(cell_size))))
+            else {
+                self.finish_command(id);
+                continue;
+            };
+            if !self.approach(id, position, target, reach) {
+                continue;
+            }
+            let Some(site) = self.entities.get_mut(site_id) else {
+                continue;
+            };
+            let Some(building) = site.row.building.as_mut() else {
+                continue;
+            };
+            // The structure gains health as it goes up
+            if let Some(health) = site.health.as_mut() {
+                let gain =
+                    (health.max - health.max / 10).div_ceil(building.construction_total.max(1));
+                health.heal(gain);
+            }
+            if building.tick_construction() {
+                events.push(ConstructionEvent::ConstructionComplete { building: site_id });
+                self.finish_command(id);
+            }
+        }
+        events
+    }
+
+    /// Claim the ground under a structure put up by workers, in both the
+    /// placement and navigation grids.
+    fn claim_ground(&mut self, id: EntityId, site: &ConstructionSite) {
+        let corner = self.placement.grid_to_world(site.origin.0, site.origin.1);
+        place_building(
+            &mut self.placement,
+            &mut self.nav_grid,
+            corner,
+            &site.footprint,
+            id,
+        );
+    }
+
+    /// Free the ground a structure put up by workers stood on.
+    fn release_ground(&mut self, entity: &Entity) {
+        if let Some(site) = entity.site {
+            let corner = self.placement.grid_to_world(site.origin.0, site.origin.1);
+            remove_building(
+                &mut self.placement,
+                &mut self.nav_grid,
+                corner,
+                &site.footprint,
+            );
+        }
+    }
+
+    /// Open up the ground a destroyed obstacle blocked, leaving what its
+    /// kind leaves behind.
+    fn clear_obstacle(&mut self, entity: &Entity) -> Option<ObstacleEvent> {
+        let terrain = entity.obstacle?;
+        let corner = self
+            .placement
+            .grid_to_world(terrain.origin.0, terrain.origin.1);
+        remove_building(
+            &mut self.placement,
+            &mut self.nav_grid,
+            corner,
+            &terrain.footprint,
+        );
+        for (x, y) in terrain.cells() {
+            self.nav_grid.set_cell(x, y, terrain.kind.leaves());
+        }
+        Some(ObstacleEvent::Destroyed {
+            obstacle: entity.id,
+            terrain,
+        })
+    }
+
+    fn spawn_projectile(&mut self, position: Vec2Fixed, projectile: Projectile) -> EntityId {
+        let mut entity = Entity::new(0);
+        entity.position = Some(Position::new(position));
+        entity.projectile = Some(projectile);
+        self.entities.insert(entity)
+    }
+
+    /// Spawn a new entity with the given parameters.
     ///
-    /// * `entity` - The entity to command
-    /// * `command` - The command to execute
+    /// Returns the unique ID assigned to the new entity.
     ///
-    /// # Errors
+    /// # Arguments
     ///
-    /// Returns [`GameError::EntityNotFound`] if the entity doesn't exist,
-    /// or [`GameError::InvalidState`] if the entity has no command queue.
+    /// * `params` - Configuration for the new entity's components
     ///
     /// # Example
     ///
     /// ```
     /// use rts_core::simulation::{Simulation, EntitySpawnParams};
-    /// use rts_core::components::Command;
     /// use rts_core::math::{Fixed, Vec2Fixed};
     ///
     /// let mut sim = Simulation::new();
     /// let unit = sim.spawn_entity(EntitySpawnParams {
-    ///     position: Some(Vec2Fixed::ZERO),
+    ///     position: Some(Vec2Fixed::new(Fixed::from_num(100), Fixed::from_num(50))),
+    ///     health: Some(100),
     ///     movement: Some(Fixed::from_num(2)),
     ///     ..Default::default()
     /// });
-    ///
-    /// sim.apply_command(unit, Command::MoveTo(Vec2Fixed::new(
-    ///     Fixed::from_num(50),
-    ///     Fixed::from_num(50),
-    /// ))).unwrap();
     /// ```
-    pub fn apply_command(&mut self, entity: EntityId, command: Command) -> Result<()> {
-        // For MoveTo commands, calculate path and store waypoints
-        if let Command::MoveTo(target) = &command {
-            if let Some(ent) = self.entities.get(entity) {
-                if let Some(pos) = &ent.position {
-                    // Try to find a path; if pathfinding fails, fall back to direct movement
-                    if let Ok(path) = find_path(&self.nav_grid, pos.value, *target) {
-                        // Store path (skip first waypoint if it's the start position)
-                        let waypoints: Vec<Vec2Fixed> = if path.len() > 1 {
-                            path.into_iter().skip(1).collect()
-                        } else {
-                            path
-                        };
+    pub fn spawn_entity(&mut self, params: EntitySpawnParams) -> EntityId {
+        let mut entity = Entity::new(0); // ID will be assigned by storage
 
-                        if let Some(ent_mut) = self.entities.get_mut(entity) {
-                            ent_mut.path_waypoints = Some(waypoints);
-                        }
-                    } else {
-                        // Clear waypoints if path fails - will move directly
-                        if let Some(ent_mut) = self.entities.get_mut(entity) {
-                            ent_mut.path_waypoints = None;
-                        }
-                    }
-                }
+        if let Some(pos) = params.position {
+            entity.position = Some(Position::new(pos));
+        }
+
+        if let Some(vel) = params.velocity {
+            entity.velocity = Some(vel);
+        } else if params.position.is_some() {
+            // Default to zero velocity for positioned entities
+            entity.velocity = Some(Velocity::ZERO);
+        }
+
+        if let Some(max_health) = params.health {
+            entity.health = Some(Health::new(max_health));
+        }
+
+        if let Some(speed) = params.movement {
+            entity.movement = Some(Movement {
+                speed,
+                target: None,
+                domain: params.domain,
+            });
+            // Units with movement get a command queue
+            entity.command_queue = Some(CommandQueue::new());
+        }
+
+        if let Some(stats) = params.combat_stats {
+            entity.combat_stats = Some(stats);
+            entity.attack_target = Some(AttackTarget::new());
+            if !params.weapons.is_empty() {
+                let armament = Armament::new(&stats, params.weapons);
+                entity.combat_stats = Some(armament.summarize(stats));
+                entity.armament = Some(armament);
             }
-        } else {
-            // For non-MoveTo commands, clear any existing path
-            if let Some(ent) = self.entities.get_mut(entity) {
-                ent.path_waypoints = None;
+        }
+
+        if params.has_production_queue {
+            entity.production_queue = Some(ProductionQueue::new());
+            // Producers stand finished, with somewhere to rally their units
+            entity.building = Some(ProductionBuilding::constructed(BuildingTypeId::new(0)));
+        }
+
+        if let Some(faction) = params.faction {
+            entity.faction = Some(faction);
+        }
+
+        if params.is_depot {
+            entity.depot = Some(Depot);
+        }
+        if params.is_market {
+            entity.market = Some(Marketplace);
+        }
+
+        entity.vision_range = params.vision_range;
+        entity.ammunition = params.ammunition.map(Ammunition::new);
+        entity.resupply = params.resupply;
+        entity.regeneration = params.regeneration;
+        entity.cosmetic = params.cosmetic;
+        entity.idle_behavior = params.idle_behavior;
+        entity.stealth = params.is_stealthed.then_some(Stealth);
+        entity.detector = params.detection_range.map(Detector::new);
+        entity.collider = params.collision_radius.map(Collider::new);
+        entity.salvageable = params.salvage_value.map(Salvageable::new);
+        entity.salvager = params.salvage_rate.map(Salvager::new);
+        entity.tech_profile = params.tech_profile;
+        entity.research = params.research;
+        if !params.abilities.is_empty() {
+            entity.abilities = Some(Abilities::new(params.abilities));
+        }
+        entity.cargo = params.cargo;
+        entity.repairer = params.repairer;
+
+        // Faction mechanics first, so researched modifiers build on them
+        if let Some(faction) = params.faction {
+            for mechanic in self.mechanics.for_faction(faction.faction) {
+                mechanic.on_spawn(&mut entity);
+            }
+            if let Some(profile) = &entity.tech_profile {
+                self.fielded
+                    .entry(faction.faction)
+                    .or_default()
+                    .insert(profile.kind.clone());
             }
         }
 
-        let ent = self
-            .entities
-            .get_mut(entity)
-            .ok_or(GameError::EntityNotFound(entity))?;
+        // Technologies already researched apply to new entities too
+        if let (Some(faction), Some(profile)) = (params.faction, entity.tech_profile.clone()) {
+            if let Some(state) = self.tech.get(&faction.faction) {
+                for tech in state.researched.iter().filter_map(|id| state.get(id)) {
+                    apply_tech_effects(&mut entity.view_mut(), &profile, tech);
+                }
+            }
+        }
 
-        let queue = ent.command_queue.as_mut().ok_or_else(|| {
-            GameError::InvalidState(format!("Entity {} has no command queue", entity))
-        })?;
+        if let Some(kind) = params.debug_kind {
+            let owner = params.faction.map_or_else(
+                || "neutral".to_string(),
+                |f| f.faction.short_name().to_lowercase(),
+            );
+            let key = format!("{owner}/{kind}");
+            let counter = self.debug_name_counters.entry(key.clone()).or_insert(0);
+            *counter += 1;
+            entity.debug_name = Some(DebugName(format!("{key}#{counter}")));
+        }
 
-        queue.set(command);
-        Ok(())
+        // Units see their surroundings from the moment they appear
+        if let (Some(faction), Some(position)) = (entity.faction, entity.position) {
+            self.fog
+                .reveal(faction.faction, position.value, sight_range(entity.view()));
+        }
+
+        self.entities.insert(entity)
     }
 
-    /// Queue a command without clearing existing commands.
+    /// Remove an entity from the simulation.
     ///
-    /// Unlike [`apply_command`](Self::apply_command), this adds the command
-    /// to the back of the queue rather than replacing all existing commands.
+    /// Returns `Ok(())` if the entity was removed, or an error if it didn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The entity ID to remove
     ///
     /// # Errors
     ///
-    /// Same as [`apply_command`](Self::apply_command).
-    pub fn queue_command(&mut self, entity: EntityId, command: Command) -> Result<()> {
-        let ent = self
+    /// Returns [`GameError::EntityNotFound`] if the entity doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rts_core::simulation::{Simulation, EntitySpawnParams};
+    ///
+    /// let mut sim = Simulation::new();
+    /// let id = sim.spawn_entity(EntitySpawnParams::default());
+    /// sim.despawn_entity(id).unwrap();
+    /// ```
+    pub fn despawn_entity(&mut self, id: EntityId) -> Result<()> {
+        let entity = self
             .entities
-            .get_mut(entity)
-            .ok_or(GameError::EntityNotFound(entity))?;
-
-        let queue = ent.command_queue.as_mut().ok_or_else(|| {
-            GameError::InvalidState(format!("Entity {} has no command queue", entity))
-        })?;
-
-        queue.push(command);
+            .remove(id)
+            .ok_or(GameError::EntityNotFound(id))?;
+        self.release_ground(&entity);
+        if let Some(obstacle) = self.clear_obstacle(&entity) {
+            self.pending_obstacle_events.push(obstacle);
+        }
         Ok(())
     }
 
-    /// Set an entity's attack target.
+    /// Hand an entity to another owner, as a capture or conversion would.
+    ///
+    /// The entity keeps its health, position and components but drops its
+    /// orders, target and squad, which came from the old owner. Entities of
+    /// the new owner that were attacking it stand down, unless they were
+    /// ordered to force-attack it. The change is returned and also reported
+    /// in the next tick's [`TickEvents::ownership_changes`].
     ///
     /// # Errors
     ///
-    /// Returns an error if the entity doesn't exist or has no combat capability.
-    pub fn set_attack_target(&mut self, entity: EntityId, target: EntityId) -> Result<()> {
-        let ent = self
+    /// Returns [`GameError::EntityNotFound`] if the entity doesn't exist, or
+    /// [`GameError::InvalidState`] if it has no owner or already belongs to
+    /// `new_owner`.
+    pub fn transfer_ownership(
+        &mut self,
+        entity: EntityId,
+        new_owner: FactionMember,
+    ) -> Result<OwnershipChange> {
+        let mut ent = self
             .entities
             .get_mut(entity)
             .ok_or(GameError::EntityNotFound(entity))?;
+        let previous = ent.faction.ok_or_else(|| {
+            GameError::InvalidState(format!("Entity {} has no owner to transfer", entity))
+        })?;
+        if previous == new_owner {
+            return Err(GameError::InvalidState(format!(
+                "Entity {} already belongs to {:?} player {}",
+                entity, new_owner.faction, new_owner.player_index
+            )));
+        }
 
-        let attack_target = ent
-            .attack_target
-            .as_mut()
-            .ok_or_else(|| GameError::InvalidState(format!("Entity {} cannot attack", entity)))?;
+        *ent.faction = Some(new_owner);
+        if let Some(queue) = ent.command_queue.as_mut() {
+            queue.clear();
+        }
+        if let Some(attack_target) = ent.attack_target.as_mut() {
+            attack_target.clear();
+        }
+        if let Some(velocity) = ent.velocity.as_mut() {
+            velocity.value = Vec2Fixed::ZERO;
+        }
+        ent.patrol_state = None;
+        ent.path_waypoints = None;
+        self.remove_from_squad(entity);
 
-        attack_target.target = Some(target);
-        Ok(())
+        for (&id, mut other) in self.entities.iter_mut() {
+            if id == entity || !other.faction.is_some_and(|f| f.is_allied_with(&new_owner)) {
+                continue;
+            }
+            if let Some(queue) = other.command_queue.as_mut() {
+                queue.commands.retain(|c| *c != Command::Attack(entity));
+            }
+            let forced = other
+                .command_queue
+                .as_ref()
+                .and_then(CommandQueue::current)
+                .is_some_and(|c| *c == Command::ForceAttack(entity));
+            if let Some(attack_target) = other.attack_target.as_mut() {
+                if attack_target.target == Some(entity) && !forced {
+                    attack_target.clear();
+                }
+            }
+        }
+
+        let change = OwnershipChange {
+            entity,
+            previous,
+            owner: new_owner,
+        };
+        self.pending_ownership_changes.push(change);
+        Ok(change)
     }
 
-    /// Clear an entity's attack target.
+    /// Change a live entity's stats in place.
+    ///
+    /// A unit that is hurt stays as hurt relative to its new maximum, and a
+    /// weapon mid-reload finishes no later than its new cooldown allows.
+    /// Stats the entity has no component for are ignored.
     ///
     /// # Errors
     ///
-    /// Returns an error if the entity doesn't exist or has no combat capability.
-    pub fn clear_attack_target(&mut self, entity: EntityId) -> Result<()> {
+    /// Returns [`GameError::EntityNotFound`] if the entity doesn't exist.
+    pub fn change_stats(&mut self, entity: EntityId, change: &StatChange) -> Result<()> {
         let ent = self
             .entities
             .get_mut(entity)
             .ok_or(GameError::EntityNotFound(entity))?;
 
-        let attack_target = ent
-            .attack_target
-            .as_mut()
-            .ok_or_else(|| GameError::InvalidState(format!("Entity {} cannot attack", entity)))?;
-
-        attack_target.clear();
+        if let (Some(max), Some(health)) = (change.max_health, ent.health.as_mut()) {
+            let max = max.max(1);
+            let current = u64::from(health.current) * u64::from(max) / u64::from(health.max.max(1));
+            health.current = u32::try_from(current).unwrap_or(max).clamp(1, max);
+            health.max = max;
+        }
+        if let Some(stats) = ent.combat_stats.as_mut() {
+            // With several weapons the change retunes the main one
+            let main = ent.row.armament.as_ref().and_then(|a| a.weapons.first());
+            if let Some(main) = main {
+                *stats = main.arm(*stats);
+            }
+            if let Some(damage) = change.damage {
+                stats.damage = damage;
+            }
+            if let Some(range) = change.range {
+                stats.range = range;
+            }
+            if let Some(cooldown) = change.attack_cooldown {
+                stats.attack_cooldown = cooldown.max(1);
+                stats.cooldown_remaining = stats.cooldown_remaining.min(stats.attack_cooldown);
+            }
+            if let Some(armament) = ent.row.armament.as_mut() {
+                if let Some(main) = armament.weapons.first_mut() {
+                    *main = Weapon::from_stats(stats);
+                }
+                *stats = armament.summarize(*stats);
+            }
+        }
         Ok(())
     }
 
-    /// Get an entity by ID.
-    #[must_use]
-    pub fn get_entity(&self, id: EntityId) -> Option<&Entity> {
-        self.entities.get(id)
-    }
-
-    /// Calculate a hash of the current simulation state.
+    /// Queue a command for an entity.
     ///
-    /// Used for desync detection in multiplayer. Two simulations
-    /// with identical state will produce identical hashes.
-    #[must_use]
-    pub fn state_hash(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
+    /// The command is added to the entity's command queue and will be
+    /// processed on subsequent ticks.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity` - The entity to command
+    /// * `command` - The command to execute
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GameError::EntityNotFound`] if the entity doesn't exist,
+    /// or [`GameError::InvalidState`] if the entity has no command queue or
+    /// cannot carry out the command (see [`Self::validate_command`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rts_core::simulation::{Simulation, EntitySpawnParams};
+    /// use rts_core::components::Command;
+    /// use rts_core::math::{Fixed, Vec2Fixed};
+    ///
+    /// let mut sim = Simulation::new();
+    /// let unit = sim.spawn_entity(EntitySpawnParams {
+    ///     position: Some(Vec2Fixed::ZERO),
+    ///     movement: Some(Fixed::from_num(2)),
+    ///     ..Default::default()
+    /// });
+    ///
+    /// sim.apply_command(unit, Command::MoveTo(Vec2Fixed::new(
+    ///     Fixed::from_num(50),
+    ///     Fixed::from_num(50),
+    /// ))).unwrap();
+    /// ```
+    pub fn apply_command(&mut self, entity: EntityId, command: Command) -> Result<()> {
+        self.validate_command(entity, &command)?;
+        let path = self
+            .entities
+            .get(entity)
+            .and_then(|ent| self.plan_path(ent, &command, &BTreeMap::new()));
+        self.set_command(entity, command, path)
+    }
 
-        // Hash tick
-        self.tick.hash(&mut hasher);
+    /// Apply a batch of commands, as [`apply_command`](Self::apply_command)
+    /// would one by one, returning one result per command in batch order.
+    ///
+    /// Meant for AI layers that order hundreds of units per decision: each
+    /// entity is looked up once for validation and path planning and once
+    /// to store the command, and the factions of attack targets shared by
+    /// many orders are looked up only once. Moves by at least
+    /// [`FLOW_FIELD_GROUP_SIZE`] units towards the same grid cell share one
+    /// [`FlowField`] instead of running A* per unit. A failed command does
+    /// not stop the rest of the batch.
+    pub fn apply_commands(&mut self, batch: &[(EntityId, Command)]) -> Vec<Result<()>> {
+        let flows = self.group_flow_fields(batch);
+        let mut results = Vec::with_capacity(batch.len());
+        let mut targets: BTreeMap<EntityId, (Option<FactionId>, MovementDomain)> = BTreeMap::new();
+        for (entity, command) in batch {
+            let checked = self
+                .entities
+                .get(*entity)
+                .ok_or(GameError::EntityNotFound(*entity))
+                .and_then(|ent| {
+                    Self::check_command(*entity, ent, command, |target| {
+                        *targets
+                            .entry(target)
+                            .or_insert_with(|| self.target_info(target))
+                    })?;
+                    self.check_friendly_target(*entity, ent, command)?;
+                    Ok(self.plan_path(ent, command, &flows))
+                });
+            results.push(checked.and_then(|path| self.set_command(*entity, command.clone(), path)));
+        }
+        results
+    }
 
-        // Hash entities in deterministic order
-        let ids = self.entities.sorted_ids();
-        ids.len().hash(&mut hasher);
+    /// Flow fields for the move destinations in `batch` shared by at least
+    /// [`FLOW_FIELD_GROUP_SIZE`] orders, keyed by goal cell.
+    fn group_flow_fields(&self, batch: &[(EntityId, Command)]) -> BTreeMap<(u32, u32), FlowField> {
+        let mut groups: BTreeMap<(u32, u32), (Vec2Fixed, usize)> = BTreeMap::new();
+        for (_, command) in batch {
+            if let Command::MoveTo(target) | Command::AttackMove(target) = command {
+                if let Some(cell) = self.nav_grid.world_to_grid(*target) {
+                    groups.entry(cell).or_insert((*target, 0)).1 += 1;
+                }
+            }
+        }
+        groups
+            .into_iter()
+            .filter(|(_, (_, count))| *count >= FLOW_FIELD_GROUP_SIZE)
+            .filter_map(|(cell, (target, _))| {
+                FlowField::new(&self.nav_grid, target)
+                    .ok()
+                    .map(|flow| (cell, flow))
+            })
+            .collect()
+    }
 
-        for id in ids {
-            if let Some(entity) = self.entities.get(id) {
-                id.hash(&mut hasher);
+    /// Waypoints to store for `command`: `Some(path)` for a move or
+    /// attack-move by a positioned entity (`Some(None)` when no path is
+    /// found and it will move directly), `Some(None)` to clear the path for
+    /// any other command, and `None` to leave it untouched.
+    ///
+    /// Paths towards a cell with an entry in `flows` are read from that
+    /// field rather than searched.
+    fn plan_path(
+        &self,
+        ent: EntityRef<'_>,
+        command: &Command,
+        flows: &BTreeMap<(u32, u32), FlowField>,
+    ) -> Option<Option<Vec<Vec2Fixed>>> {
+        let (Command::MoveTo(target) | Command::AttackMove(target)) = command else {
+            return Some(None);
+        };
+        let pos = ent.position.as_ref()?;
+        // Aircraft fly straight over obstacles
+        if ent.domain().ignores_terrain() {
+            return Some(None);
+        }
+        let flow = self
+            .nav_grid
+            .world_to_grid(*target)
+            .and_then(|cell| flows.get(&cell));
+        // If pathfinding fails, fall back to direct movement
+        let path = match flow {
+            Some(flow) => flow.path_from(&self.nav_grid, pos.value),
+            None => find_path(&self.nav_grid, pos.value, *target),
+        }
+        .ok();
+        // Skip the first waypoint if it's the start position
+        Some(path.map(|path| {
+            if path.len() > 1 {
+                path.into_iter().skip(1).collect()
+            } else {
+                path
+            }
+        }))
+    }
 
-                // Hash position
-                if let Some(ref pos) = entity.position {
-                    pos.value.x.to_bits().hash(&mut hasher);
-                    pos.value.y.to_bits().hash(&mut hasher);
-                }
+    /// Replace an entity's commands with `command`, storing planned waypoints.
+    fn set_command(
+        &mut self,
+        entity: EntityId,
+        command: Command,
+        path: Option<Option<Vec<Vec2Fixed>>>,
+    ) -> Result<()> {
+        let mut ent = self
+            .entities
+            .get_mut(entity)
+            .ok_or(GameError::EntityNotFound(entity))?;
+        if let Command::SetRallyPoint(point) = command {
+            if let Some(building) = ent.building.as_mut() {
+                building.set_rally_point(point);
+            }
+            return Ok(());
+        }
+        if let Some(waypoints) = path {
+            ent.path_waypoints = waypoints;
+        }
 
-                // Hash health
-                if let Some(ref health) = entity.health {
-                    health.current.hash(&mut hasher);
-                    health.max.hash(&mut hasher);
-                }
+        let queue = ent.command_queue.as_mut().ok_or_else(|| {
+            GameError::InvalidState(format!("Entity {} has no command queue", entity))
+        })?;
 
-                // Hash velocity
-                if let Some(ref vel) = entity.velocity {
-                    vel.value.x.to_bits().hash(&mut hasher);
-                    vel.value.y.to_bits().hash(&mut hasher);
-                }
+        queue.set(command);
+        Ok(())
+    }
 
-                // Hash projectile
-                if let Some(ref projectile) = entity.projectile {
-                    projectile.source.hash(&mut hasher);
-                    projectile.target.hash(&mut hasher);
-                    projectile.damage.hash(&mut hasher);
-                    projectile.damage_type.hash(&mut hasher);
-                    projectile.speed.to_bits().hash(&mut hasher);
-                }
+    /// Queue a command without clearing existing commands.
+    ///
+    /// Unlike [`apply_command`](Self::apply_command), this adds the command
+    /// to the back of the queue rather than replacing all existing commands.
+    /// Queued moves are pathed when they reach the front of the queue, so a
+    /// chain of them makes a waypoint route. [`Command::SetRallyPoint`]
+    /// takes effect at once.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`apply_command`](Self::apply_command).
+    pub fn queue_command(&mut self, entity: EntityId, command: Command) -> Result<()> {
+        self.validate_command(entity, &command)?;
+        if let Command::SetRallyPoint(_) = command {
+            return self.set_command(entity, command, None);
+        }
+
+        let mut ent = self
+            .entities
+            .get_mut(entity)
+            .ok_or(GameError::EntityNotFound(entity))?;
+
+        let queue = ent.command_queue.as_mut().ok_or_else(|| {
+            GameError::InvalidState(format!("Entity {} has no command queue", entity))
+        })?;
+
+        let idle = queue.is_empty();
+        queue.push(command);
+        if idle {
+            self.plan_current_command(entity);
+        }
+        Ok(())
+    }
+
+    /// Check that an entity can carry out a command.
+    ///
+    /// Plain [`Command::Attack`] is refused against entities of the same
+    /// faction, which need an explicit [`Command::ForceAttack`], either is
+    /// refused against units the weapon cannot hit (see
+    /// [`WeaponTargets`](crate::combat::WeaponTargets)), and
+    /// [`Command::AttackGround`] needs a splash weapon. Units aboard a
+    /// transport take no orders, [`Command::Load`] needs a friendly
+    /// transport with room for the unit and [`Command::Unload`] a hold.
+    /// [`Command::Repair`] needs a repairer and a friendly entity of the
+    /// kind it mends (see [`RepairKind`](crate::repair::RepairKind)).
+    /// [`Command::SetRallyPoint`] needs a production building.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GameError::EntityNotFound`] if the entity doesn't exist,
+    /// or [`GameError::InvalidState`] if the command is not allowed.
+    pub fn validate_command(&self, entity: EntityId, command: &Command) -> Result<()> {
+        let ent = self
+            .entities
+            .get(entity)
+            .ok_or(GameError::EntityNotFound(entity))?;
+        Self::check_command(entity, ent, command, |target| self.target_info(target))?;
+        self.check_friendly_target(entity, ent, command)
+    }
+
+    /// Check that a [`Command::Load`] names a friendly transport with room
+    /// for the unit, a [`Command::Repair`] a friendly entity of the kind the
+    /// unit mends, and a [`Command::Construct`] a friendly site the unit can
+    /// work on.
+    fn check_friendly_target(
+        &self,
+        entity: EntityId,
+        ent: EntityRef<'_>,
+        command: &Command,
+    ) -> Result<()> {
+        match *command {
+            Command::Load(transport_id) => self.check_boarding(entity, ent, transport_id),
+            Command::Repair(target) => self.check_repair(entity, ent, target),
+            Command::Construct(site) => self.check_construction(entity, ent, site),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that the unit is a worker and `site` a friendly structure
+    /// still under construction.
+    fn check_construction(
+        &self,
+        entity: EntityId,
+        ent: EntityRef<'_>,
+        site: EntityId,
+    ) -> Result<()> {
+        if !is_builder(ent.tech_profile.as_ref()) {
+            return Err(GameError::InvalidState(format!(
+                "Entity {} cannot construct",
+                entity
+            )));
+        }
+        let structure = self
+            .entities
+            .get(site)
+            .ok_or(GameError::EntityNotFound(site))?;
+        if structure.faction.map(|f| f.faction) != ent.faction.map(|f| f.faction) {
+            return Err(GameError::InvalidState(format!(
+                "Entity {} cannot build enemy structure {}",
+                entity, site
+            )));
+        }
+        if !structure
+            .building
+            .as_ref()
+            .is_some_and(|b| !b.is_construction_complete())
+        {
+            return Err(GameError::InvalidState(format!(
+                "Entity {} is not under construction",
+                site
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that `transport_id` is a friendly transport with room for the
+    /// unit.
+    fn check_boarding(
+        &self,
+        entity: EntityId,
+        ent: EntityRef<'_>,
+        transport_id: EntityId,
+    ) -> Result<()> {
+        let transport = self
+            .entities
+            .get(transport_id)
+            .ok_or(GameError::EntityNotFound(transport_id))?;
+        let cargo = transport.cargo.as_ref().ok_or_else(|| {
+            GameError::InvalidState(format!("Entity {} is not a transport", transport_id))
+        })?;
+        if transport.faction.map(|f| f.faction) != ent.faction.map(|f| f.faction) {
+            return Err(GameError::InvalidState(format!(
+                "Entity {} cannot board enemy transport {}",
+                entity, transport_id
+            )));
+        }
+        if !cargo.accepts(ent.tech_profile.as_ref()) {
+            return Err(GameError::InvalidState(format!(
+                "Transport {} does not carry units like {}",
+                transport_id, entity
+            )));
+        }
+        if cargo.is_full() {
+            return Err(GameError::InvalidState(format!(
+                "Transport {} is full",
+                transport_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that the unit can mend `target`: a friendly entity of the kind
+    /// it repairs.
+    fn check_repair(&self, entity: EntityId, ent: EntityRef<'_>, target: EntityId) -> Result<()> {
+        let repairer = ent
+            .repairer
+            .ok_or_else(|| GameError::InvalidState(format!("Entity {} cannot repair", entity)))?;
+        let patient = self
+            .entities
+            .get(target)
+            .ok_or(GameError::EntityNotFound(target))?;
+        if patient.faction.map(|f| f.faction) != ent.faction.map(|f| f.faction) {
+            return Err(GameError::InvalidState(format!(
+                "Entity {} cannot repair enemy entity {}",
+                entity, target
+            )));
+        }
+        if !repairer
+            .kind
+            .treats(patient.movement.is_none(), patient.tech_profile.as_ref())
+        {
+            return Err(GameError::InvalidState(format!(
+                "Entity {} cannot give {:?} repairs to {}",
+                entity, repairer.kind, target
+            )));
+        }
+        Ok(())
+    }
+
+    /// Faction owning an entity, if it has one, and the layer it moves in.
+    fn target_info(&self, entity: EntityId) -> (Option<FactionId>, MovementDomain) {
+        let target = self.entities.get(entity);
+        (
+            target.and_then(|e| e.faction).map(|f| f.faction),
+            target.map_or(MovementDomain::Ground, |e| e.domain()),
+        )
+    }
+
+    /// Check if `attacker`'s weapon can hit `target` in the layer it moves
+    /// in; false if either is missing or the attacker has no weapon.
+    #[must_use]
+    pub fn can_target(&self, attacker: EntityId, target: EntityId) -> bool {
+        let weapon = self.entities.get(attacker).and_then(|e| e.combat_stats);
+        let target = self.entities.get(target).map(|e| e.domain());
+        weapon
+            .zip(target)
+            .is_some_and(|(weapon, domain)| weapon.targets.can_hit(domain))
+    }
 
-                // Hash patrol state
-                if let Some(ref patrol) = entity.patrol_state {
-                    patrol.origin.x.to_bits().hash(&mut hasher);
-                    patrol.origin.y.to_bits().hash(&mut hasher);
-                    patrol.target.x.to_bits().hash(&mut hasher);
-                    patrol.target.y.to_bits().hash(&mut hasher);
-                    patrol.heading_to_target.hash(&mut hasher);
+    /// [`validate_command`](Self::validate_command) for an entity already
+    /// looked up, with target factions and domains supplied by the caller.
+    fn check_command(
+        entity: EntityId,
+        ent: EntityRef<'_>,
+        command: &Command,
+        target_info: impl FnOnce(EntityId) -> (Option<FactionId>, MovementDomain),
+    ) -> Result<()> {
+        if let Some(transport) = ent.embarked {
+            return Err(GameError::InvalidState(format!(
+                "Entity {} is aboard transport {}",
+                entity, transport
+            )));
+        }
+        match command {
+            Command::Attack(target) | Command::ForceAttack(target) => {
+                let (faction, domain) = target_info(*target);
+                let own = ent.faction.map(|f| f.faction);
+                if matches!(command, Command::Attack(_)) && own.is_some() && own == faction {
+                    return Err(GameError::InvalidState(format!(
+                        "Entity {} cannot attack friendly entity {} without forced fire",
+                        entity, target
+                    )));
+                }
+                if ent.combat_stats.is_some_and(|s| !s.targets.can_hit(domain)) {
+                    return Err(GameError::InvalidState(format!(
+                        "Entity {} cannot hit {:?} units like {}",
+                        entity, domain, target
+                    )));
+                }
+            }
+            Command::UseAbility { ability_id, target } => {
+                let slot = ent
+                    .abilities
+                    .as_ref()
+                    .and_then(|a| a.get(ability_id))
+                    .ok_or_else(|| {
+                        GameError::InvalidState(format!(
+                            "Entity {} has no ability {}",
+                            entity, ability_id
+                        ))
+                    })?;
+                let expected = match target {
+                    AbilityTarget::Point(_) => AbilityTargetType::Point,
+                    AbilityTarget::Entity(_) => AbilityTargetType::Entity,
+                    AbilityTarget::Caster => AbilityTargetType::Caster,
+                };
+                if slot.ability.target != expected {
+                    return Err(GameError::InvalidState(format!(
+                        "Ability {} targets {:?}, not {:?}",
+                        ability_id, slot.ability.target, expected
+                    )));
                 }
             }
+            Command::Unload(_) if ent.cargo.is_none() => {
+                return Err(GameError::InvalidState(format!(
+                    "Entity {} has no cargo hold to unload",
+                    entity
+                )));
+            }
+            Command::AttackGround(_)
+                if !ent.combat_stats.is_some_and(|s| s.can_attack_ground())
+                    && !ent
+                        .armament
+                        .as_ref()
+                        .is_some_and(|a| a.ground_range().is_some()) =>
+            {
+                return Err(GameError::InvalidState(format!(
+                    "Entity {} has no splash weapon to attack ground",
+                    entity
+                )));
+            }
+            Command::SetRallyPoint(_) if ent.building.is_none() => {
+                return Err(GameError::InvalidState(format!(
+                    "Entity {} is not a production building",
+                    entity
+                )));
+            }
+            _ => {}
         }
+        Ok(())
+    }
 
-        hasher.finish()
+    /// Set an entity's attack target.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entity doesn't exist or has no combat capability.
+    pub fn set_attack_target(&mut self, entity: EntityId, target: EntityId) -> Result<()> {
+        let mut ent = self
+            .entities
+            .get_mut(entity)
+            .ok_or(GameError::EntityNotFound(entity))?;
+
+        let attack_target = ent
+            .attack_target
+            .as_mut()
+            .ok_or_else(|| GameError::InvalidState(format!("Entity {} cannot attack", entity)))?;
+
+        attack_target.target = Some(target);
+        Ok(())
     }
 
-    /// Serialize the simulation state for replay or network sync.
+    /// Clear an entity's attack target.
     ///
     /// # Errors
     ///
-    /// Returns an error if serialization fails.
-    pub fn serialize(&self) -> Result<Vec<u8>> {
-        bincode::serialize(self)
-            .map_err(|e| GameError::InvalidState(format!("Failed to serialize simulation: {}", e)))
+    /// Returns an error if the entity doesn't exist or has no combat capability.
+    pub fn clear_attack_target(&mut self, entity: EntityId) -> Result<()> {
+        let mut ent = self
+            .entities
+            .get_mut(entity)
+            .ok_or(GameError::EntityNotFound(entity))?;
+
+        let attack_target = ent
+            .attack_target
+            .as_mut()
+            .ok_or_else(|| GameError::InvalidState(format!("Entity {} cannot attack", entity)))?;
+
+        attack_target.clear();
+        Ok(())
     }
 
-    /// Deserialize simulation state from bytes.
+    /// Give a faction the technologies it can research.
+    ///
+    /// Replaces any earlier tree, along with the faction's research.
+    pub fn set_tech_tree(&mut self, faction: FactionId, techs: impl IntoIterator<Item = TechData>) {
+        self.tech.insert(faction, TechState::new(techs));
+    }
+
+    /// A faction's technologies and research progress.
+    #[must_use]
+    pub fn tech_state(&self, faction: FactionId) -> Option<&TechState> {
+        self.tech.get(&faction)
+    }
+
+    /// Whether `faction` has researched `tech_id`.
+    #[must_use]
+    pub fn has_researched(&self, faction: FactionId, tech_id: &str) -> bool {
+        self.tech
+            .get(&faction)
+            .is_some_and(|state| state.is_researched(tech_id))
+    }
+
+    /// Check that `building` can queue research of `tech_id`, returning the
+    /// technology so the caller can charge its cost.
     ///
     /// # Errors
     ///
-    /// Returns an error if deserialization fails.
-    pub fn deserialize(data: &[u8]) -> Result<Self> {
-        bincode::deserialize(data).map_err(|e| {
-            GameError::InvalidState(format!("Failed to deserialize simulation: {}", e))
+    /// Returns [`GameError::EntityNotFound`] if the building doesn't exist,
+    /// or [`GameError::Research`] with the reason it cannot research.
+    pub fn validate_research(&self, building: EntityId, tech_id: &str) -> Result<&TechData> {
+        let entity = self
+            .entities
+            .get(building)
+            .ok_or(GameError::EntityNotFound(building))?;
+        let (Some(facility), Some(member)) = (entity.research.as_ref(), entity.faction) else {
+            return Err(ResearchError::NotAFacility.into());
+        };
+        let faction = member.faction;
+        let state = self.tech.get(&faction).ok_or(ResearchError::UnknownTech)?;
+        let tech = state.check(tech_id, |kind| self.owns_kind(faction, kind))?;
+        if !facility.hosts(tech) {
+            return Err(ResearchError::WrongFacility.into());
+        }
+        if self.is_research_queued(faction, tech_id) {
+            return Err(ResearchError::AlreadyQueued.into());
+        }
+        if facility.is_full() {
+            return Err(ResearchError::QueueFull.into());
+        }
+        Ok(tech)
+    }
+
+    /// Queue research of `tech_id` at `building`.
+    ///
+    /// The caller pays the technology's cost; it is recorded so a
+    /// cancellation can report the refund.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of [`validate_research`](Self::validate_research).
+    pub fn queue_research(&mut self, building: EntityId, tech_id: &str) -> Result<()> {
+        let tech = self.validate_research(building, tech_id)?;
+        let item = ResearchItem {
+            tech_id: tech.id.clone(),
+            progress: 0,
+            total: tech.research_time,
+            cost: tech.cost,
+        };
+        if let Some(facility) = self
+            .entities
+            .get_mut(building)
+            .and_then(|EntityMut { row, .. }| row.research.as_mut())
+        {
+            facility.queue.push_back(item);
+        }
+        Ok(())
+    }
+
+    /// Cancel the most recently queued research at `building`.
+    ///
+    /// A [`ResearchEvent::ResearchCancelled`] with the refund is reported on
+    /// the next tick.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GameError::EntityNotFound`] if the building doesn't exist,
+    /// or [`GameError::Research`] if it is not a facility or has nothing
+    /// queued.
+    pub fn cancel_research(&mut self, building: EntityId) -> Result<ResearchItem> {
+        let mut entity = self
+            .entities
+            .get_mut(building)
+            .ok_or(GameError::EntityNotFound(building))?;
+        let faction = entity.faction.map(|f| f.faction);
+        let facility = entity
+            .research
+            .as_mut()
+            .ok_or(ResearchError::NotAFacility)?;
+        let item = facility
+            .queue
+            .pop_back()
+            .ok_or(ResearchError::NothingQueued)?;
+        if let Some(faction) = faction {
+            self.pending_research_events
+                .push(ResearchEvent::ResearchCancelled {
+                    building,
+                    faction,
+                    tech_id: item.tech_id.clone(),
+                    refund: item.cost,
+                });
+        }
+        Ok(item)
+    }
+
+    /// Pick the facility `faction` should research `tech_id` at: the one
+    /// with the shortest queue that can take it, lowest ID first.
+    #[must_use]
+    pub fn research_facility_for(&self, faction: FactionId, tech_id: &str) -> Option<EntityId> {
+        self.entities
+            .sorted_ids()
+            .into_iter()
+            .filter(|&id| {
+                self.entities
+                    .get(id)
+                    .is_some_and(|e| e.faction.is_some_and(|f| f.faction == faction))
+            })
+            .filter(|&id| self.validate_research(id, tech_id).is_ok())
+            .min_by_key(|&id| {
+                self.entities
+                    .get(id)
+                    .and_then(|e| e.row.research.as_ref())
+                    .map_or(0, |r| r.queue.len())
+            })
+    }
+
+    /// Whether `faction` owns a finished entity whose tech profile is `kind`.
+    fn owns_kind(&self, faction: FactionId, kind: &str) -> bool {
+        self.entities.iter().any(|(_, e)| {
+            e.faction.is_some_and(|f| f.faction == faction)
+                && e.tech_profile.as_ref().is_some_and(|p| p.kind == kind)
+                && e.building
+                    .as_ref()
+                    .map_or(true, |b| b.is_construction_complete())
         })
     }
-}
 
-impl Default for Simulation {
-    fn default() -> Self {
-        Self::new()
+    /// Whether `faction` owns a finished market to trade at.
+    #[must_use]
+    pub fn owns_market(&self, faction: FactionId) -> bool {
+        self.entities.iter().any(|(_, e)| {
+            e.faction.is_some_and(|f| f.faction == faction)
+                && e.market.is_some()
+                && e.building
+                    .as_ref()
+                    .map_or(true, |b| b.is_construction_complete())
+        })
+    }
+
+    /// Report that a harvester has nothing to do, in the next tick's
+    /// [`TickEvents::notifications`]. Unowned entities are ignored.
+    pub fn notify_harvester_idle(&mut self, entity: EntityId) {
+        if let Some(faction) = self.owner(entity) {
+            self.pending_notifications
+                .push(Notification::HarvesterIdle { entity, faction });
+        }
+    }
+
+    /// Report that `building` could not start production, in the next
+    /// tick's [`TickEvents::notifications`]. Unowned entities are ignored.
+    pub fn notify_production_blocked(&mut self, building: EntityId, reason: BlockReason) {
+        if let Some(faction) = self.owner(building) {
+            self.pending_notifications
+                .push(Notification::ProductionBlocked {
+                    building,
+                    faction,
+                    reason,
+                });
+        }
+    }
+
+    /// Faction owning an entity, if it exists and has one.
+    fn owner(&self, entity: EntityId) -> Option<FactionId> {
+        self.entities
+            .get(entity)
+            .and_then(|e| e.faction)
+            .map(|member| member.faction)
+    }
+
+    /// Alerts for entities hit by an enemy, one per fight (see
+    /// [`AttackAlerts`]).
+    fn attack_notifications(&mut self, damage: &[DamageEvent]) -> Vec<Notification> {
+        let mut alerts = Vec::new();
+        for hit in damage {
+            let Some(target) = self.entities.get(hit.target) else {
+                continue;
+            };
+            let (Some(member), Some(position)) = (target.faction, target.position) else {
+                continue;
+            };
+            let faction = member.faction;
+            if self.owner(hit.attacker) == Some(faction) {
+                continue;
+            }
+            if self
+                .attack_alerts
+                .should_alert(faction, position.value, self.tick)
+            {
+                alerts.push(Notification::UnderAttack {
+                    entity: hit.target,
+                    faction,
+                    position: position.value,
+                });
+            }
+        }
+        alerts
+    }
+
+    /// Whether `tech_id` is queued at any of `faction`'s facilities.
+    fn is_research_queued(&self, faction: FactionId, tech_id: &str) -> bool {
+        self.entities.iter().any(|(_, e)| {
+            e.faction.is_some_and(|f| f.faction == faction)
+                && e.research.as_ref().is_some_and(|r| r.is_queued(tech_id))
+        })
+    }
+
+    /// Give a faction its mechanics, replacing any it had.
+    ///
+    /// Spawn-time rules only affect entities spawned afterwards, so set
+    /// mechanics before spawning a faction's starting entities.
+    pub fn set_faction_mechanics(&mut self, faction: FactionId, mechanics: Vec<MechanicData>) {
+        self.mechanics.set(faction, mechanics);
+    }
+
+    /// Every faction's mechanics.
+    #[must_use]
+    pub fn faction_mechanics(&self) -> &FactionMechanics {
+        &self.mechanics
+    }
+
+    /// Check that `faction`'s mechanics let it place a building of `kind`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GameError::FactionRule`] if a mechanic forbids it.
+    pub fn check_placement(&self, faction: FactionId, kind: &str) -> Result<()> {
+        let empty = BTreeSet::new();
+        let fielded = self.fielded.get(&faction).unwrap_or(&empty);
+        if self
+            .mechanics
+            .for_faction(faction)
+            .all(|m| m.permits_placement(kind, fielded))
+        {
+            Ok(())
+        } else {
+            Err(GameError::FactionRule(format!(
+                "{} cannot place another {kind}",
+                faction.short_name()
+            )))
+        }
+    }
+
+    /// Ground claimed by structures that workers put up.
+    #[must_use]
+    pub fn placement_grid(&self) -> &PlacementGrid {
+        &self.placement
+    }
+
+    /// Whether `entity` is a worker that can put up buildings.
+    #[must_use]
+    pub fn can_construct(&self, entity: EntityId) -> bool {
+        self.entities
+            .get(entity)
+            .is_some_and(|e| e.movement.is_some() && is_builder(e.tech_profile.as_ref()))
+    }
+
+    /// Check that a structure with `footprint` can stand centred on
+    /// `center`, returning the corner cell of its footprint.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GameError::InvalidState`] if the footprint runs off the
+    /// map or covers impassable ground, another site or a standing
+    /// structure.
+    pub fn check_construction_site(
+        &self,
+        center: Vec2Fixed,
+        footprint: &BuildingFootprint,
+    ) -> Result<(u32, u32)> {
+        let blocked = || {
+            GameError::InvalidState(format!(
+                "Cannot build a {}x{} structure at ({}, {})",
+                footprint.width, footprint.height, center.x, center.y
+            ))
+        };
+        let (x, y) = footprint_origin(&self.placement, center, footprint).ok_or_else(blocked)?;
+        let corner = self.placement.grid_to_world(x, y);
+        if !can_place_building(&self.placement, corner, footprint).is_valid() {
+            return Err(blocked());
+        }
+        let walkable = (0..footprint.height)
+            .all(|dy| (0..footprint.width).all(|dx| self.nav_grid.is_walkable(x + dx, y + dy)));
+        // Structures spawned outright hold no ground, so keep a cell clear
+        // of where they stand
+        let cell = self.placement.cell_size();
+        let low = corner - Vec2Fixed::new(cell, cell);
+        let high = corner
+            + Vec2Fixed::new(
+                Fixed::from_num(footprint.width + 1) * cell,
+                Fixed::from_num(footprint.height + 1) * cell,
+            );
+        let crowded = self.entities.iter().any(|(_, e)| {
+            e.movement.is_none()
+                && e.health.is_some()
+                && e.position.is_some_and(|p| {
+                    p.value.x > low.x
+                        && p.value.x < high.x
+                        && p.value.y > low.y
+                        && p.value.y < high.y
+                })
+        });
+        if walkable && !crowded {
+            Ok((x, y))
+        } else {
+            Err(blocked())
+        }
+    }
+
+    /// The free spot nearest `near` where a structure with `footprint` can
+    /// stand, searching outwards ring by ring up to
+    /// [`SITE_SEARCH_RADIUS`] cells away.
+    #[must_use]
+    pub fn find_construction_site(
+        &self,
+        near: Vec2Fixed,
+        footprint: &BuildingFootprint,
+    ) -> Option<Vec2Fixed> {
+        let cell = self.placement.cell_size();
+        let radius = SITE_SEARCH_RADIUS as i32;
+        (0..=radius).find_map(|ring| {
+            (-ring..=ring)
+                .flat_map(|dy| (-ring..=ring).map(move |dx| (dx, dy)))
+                .filter(|(dx, dy)| dx.abs().max(dy.abs()) == ring)
+                .find_map(|(dx, dy)| {
+                    let center = near
+                        + Vec2Fixed::new(Fixed::from_num(dx) * cell, Fixed::from_num(dy) * cell);
+                    let origin = self.check_construction_site(center, footprint).ok()?;
+                    Some(footprint_center(&self.placement, origin, footprint))
+                })
+        })
+    }
+
+    /// Have `worker` put up a structure: lay its foundation centred on
+    /// `params.position`, claiming the ground, and order the worker to
+    /// build it. The structure starts at a tenth of its health and is
+    /// finished after `build_time` ticks of work; more workers build it
+    /// faster.
+    ///
+    /// The simulation does not hold resources: the caller charges `cost`,
+    /// and gets the unbuilt share of it back from
+    /// [`cancel_construction`](Self::cancel_construction).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GameError::EntityNotFound`] if the worker doesn't exist,
+    /// [`GameError::FactionRule`] if a faction mechanic forbids the
+    /// structure, or [`GameError::InvalidState`] if the worker cannot
+    /// build it or the ground is taken.
+    pub fn order_construction(
+        &mut self,
+        worker: EntityId,
+        mut params: EntitySpawnParams,
+        footprint: BuildingFootprint,
+        build_time: u32,
+        cost: u32,
+    ) -> Result<EntityId> {
+        let ent = self
+            .entities
+            .get(worker)
+            .ok_or(GameError::EntityNotFound(worker))?;
+        if !self.can_construct(worker) {
+            return Err(GameError::InvalidState(format!(
+                "Entity {} cannot construct",
+                worker
+            )));
+        }
+        if let Some(transport) = ent.embarked {
+            return Err(GameError::InvalidState(format!(
+                "Entity {} is aboard transport {}",
+                worker, transport
+            )));
+        }
+        let faction = ent.faction.map(|f| f.faction);
+        if params.faction.map(|f| f.faction) != faction {
+            return Err(GameError::InvalidState(format!(
+                "Entity {} cannot build for another faction",
+                worker
+            )));
+        }
+        if let (Some(faction), Some(profile)) = (faction, params.tech_profile.as_ref()) {
+            self.check_placement(faction, &profile.kind)?;
+        }
+        let center = params.position.ok_or_else(|| {
+            GameError::InvalidState("A structure needs a position to be built".to_string())
+        })?;
+        let origin = self.check_construction_site(center, &footprint)?;
+
+        params.position = Some(footprint_center(&self.placement, origin, &footprint));
+        let site = ConstructionSite {
+            origin,
+            footprint,
+            cost,
+        };
+        let id = self.spawn_entity(params);
+        if let Some(mut entity) = self.entities.get_mut(id) {
+            entity.building = Some(ProductionBuilding::new(
+                BuildingTypeId::new(0),
+                build_time.max(1),
+            ));
+            if let Some(health) = entity.health.as_mut() {
+                health.current = (health.max / 10).max(1);
+            }
+            entity.site = Some(site);
+        }
+        self.claim_ground(id, &site);
+        self.apply_command(worker, Command::Construct(id))?;
+        Ok(id)
+    }
+
+    /// Raise a destructible obstacle with `health` centred on `center`,
+    /// blocking the ground under `footprint` until it is destroyed.
+    ///
+    /// Obstacles belong to no faction and are only attacked on order. The
+    /// ground under them may already be impassable, e.g. a rock wall that
+    /// can be blasted open.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GameError::InvalidState`] if the footprint runs off the
+    /// map or covers a structure or another obstacle.
+    pub fn spawn_obstacle(
+        &mut self,
+        kind: ObstacleKind,
+        center: Vec2Fixed,
+        footprint: BuildingFootprint,
+        health: u32,
+    ) -> Result<EntityId> {
+        let blocked = || {
+            GameError::InvalidState(format!(
+                "Cannot raise a {}x{} obstacle at ({}, {})",
+                footprint.width, footprint.height, center.x, center.y
+            ))
+        };
+        let origin = footprint_origin(&self.placement, center, &footprint).ok_or_else(blocked)?;
+        let corner = self.placement.grid_to_world(origin.0, origin.1);
+        if !can_place_building(&self.placement, corner, &footprint).is_valid() {
+            return Err(blocked());
+        }
+
+        let terrain = Obstacle {
+            kind,
+            origin,
+            footprint,
+        };
+        let id = self.spawn_entity(EntitySpawnParams {
+            position: Some(footprint_center(&self.placement, origin, &footprint)),
+            health: Some(health.max(1)),
+            ..Default::default()
+        });
+        if let Some(mut entity) = self.entities.get_mut(id) {
+            entity.obstacle = Some(terrain);
+        }
+        place_building(
+            &mut self.placement,
+            &mut self.nav_grid,
+            corner,
+            &footprint,
+            id,
+        );
+        self.pending_obstacle_events.push(ObstacleEvent::Raised {
+            obstacle: id,
+            terrain,
+        });
+        Ok(id)
+    }
+
+    /// Cancel an unfinished structure, freeing its ground, and return the
+    /// feedstock to refund: the share of its cost not yet built. Its
+    /// workers drop their orders.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GameError::EntityNotFound`] if the structure doesn't
+    /// exist, or [`GameError::InvalidState`] if it is not under
+    /// construction.
+    pub fn cancel_construction(&mut self, structure: EntityId) -> Result<u32> {
+        let entity = self
+            .entities
+            .get(structure)
+            .ok_or(GameError::EntityNotFound(structure))?;
+        let refund = match (entity.site, entity.building.as_ref()) {
+            (Some(site), Some(building)) if !building.is_construction_complete() => {
+                site.refund(building)
+            }
+            _ => {
+                return Err(GameError::InvalidState(format!(
+                    "Entity {} is not under construction",
+                    structure
+                )))
+            }
+        };
+        self.despawn_entity(structure)?;
+        Ok(refund)
+    }
+
+    /// Bind units into a new squad.
+    ///
+    /// Members are taken out of any squad they were already in.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GameError::EntityNotFound`] if a member doesn't exist, or
+    /// [`GameError::InvalidState`] if `members` is empty or a member cannot
+    /// take commands or belongs to another faction.
+    pub fn create_squad(
+        &mut self,
+        name: impl Into<String>,
+        members: &[EntityId],
+    ) -> Result<SquadId> {
+        let Some(&first) = members.first() else {
+            return Err(GameError::InvalidState(
+                "Cannot create a squad with no members".to_string(),
+            ));
+        };
+        let faction = self
+            .entities
+            .get(first)
+            .ok_or(GameError::EntityNotFound(first))?
+            .faction
+            .map(|f| f.faction)
+            .ok_or_else(|| GameError::InvalidState(format!("Entity {} has no faction", first)))?;
+        for &member in members {
+            self.check_squad_member(faction, member)?;
+        }
+
+        let id = SquadId(self.next_squad_id);
+        self.next_squad_id += 1;
+        let mut squad = Squad::new(name, faction);
+        for &member in members {
+            self.remove_from_squad(member);
+            squad.insert(member);
+        }
+        tracing::debug!(squad = %id, name = %squad.name, members = squad.members.len(), "Squad created");
+        self.squads.insert(id, squad);
+        Ok(id)
+    }
+
+    /// Disband a squad, leaving its members with their current commands.
+    pub fn disband_squad(&mut self, id: SquadId) -> Option<Squad> {
+        self.squads.remove(&id)
+    }
+
+    /// Add a unit to a squad.
+    ///
+    /// The unit leaves any other squad, and picks up the squad's current
+    /// positional order at its formation slot.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GameError::InvalidState`] if the squad doesn't exist or the
+    /// unit cannot join it, or [`GameError::EntityNotFound`] if the unit
+    /// doesn't exist.
+    pub fn add_to_squad(&mut self, id: SquadId, entity: EntityId) -> Result<()> {
+        let faction = self.squad_or_err(id)?.faction;
+        self.check_squad_member(faction, entity)?;
+        if self.squad_of(entity) == Some(id) {
+            return Ok(());
+        }
+        self.remove_from_squad(entity);
+
+        let squad = self.squads.get_mut(&id).ok_or_else(|| no_squad(id))?;
+        squad.insert(entity);
+        let order = squad.order;
+        let slot = squad.slot_offset(entity);
+        if let (Some(order), Some(slot)) = (order, slot) {
+            if let Some(destination) = order.destination() {
+                self.apply_command(entity, member_command(order, destination + slot))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Take a unit out of whatever squad it is in.
+    ///
+    /// Squads left empty are disbanded. Returns the squad the unit left.
+    pub fn remove_from_squad(&mut self, entity: EntityId) -> Option<SquadId> {
+        let id = self.squad_of(entity)?;
+        if let Some(squad) = self.squads.get_mut(&id) {
+            squad.remove(entity);
+            if squad.members.is_empty() {
+                self.squads.remove(&id);
+            }
+        }
+        Some(id)
+    }
+
+    /// Get a squad by ID.
+    #[must_use]
+    pub fn squad(&self, id: SquadId) -> Option<&Squad> {
+        self.squads.get(&id)
+    }
+
+    /// All squads, in ID order.
+    pub fn squads(&self) -> impl Iterator<Item = (SquadId, &Squad)> {
+        self.squads.iter().map(|(&id, squad)| (id, squad))
+    }
+
+    /// The squad a unit belongs to, if any.
+    #[must_use]
+    pub fn squad_of(&self, entity: EntityId) -> Option<SquadId> {
+        self.squads
+            .iter()
+            .find(|(_, squad)| squad.contains(entity))
+            .map(|(&id, _)| id)
+    }
+
+    /// Aggregate health of a squad's living members.
+    #[must_use]
+    pub fn squad_health(&self, id: SquadId) -> Option<SquadHealth> {
+        let squad = self.squads.get(&id)?;
+        let mut health = SquadHealth::default();
+        for &member in &squad.members {
+            let Some(member_health) = self.entities.get(member).and_then(|e| e.health) else {
+                continue;
+            };
+            if member_health.is_dead() {
+                continue;
+            }
+            health.current += u64::from(member_health.current);
+            health.max += u64::from(member_health.max);
+            health.alive += 1;
+        }
+        Some(health)
+    }
+
+    /// Give a squad an order, fanned out to every member.
+    ///
+    /// Positional orders place members by the squad's formation and stay in
+    /// force: idle members that stray are sent back, and units joining later
+    /// pick the order up. [`SquadCommand::Retreat`] also drops members'
+    /// current targets.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GameError::InvalidState`] if the squad doesn't exist or a
+    /// member refuses the resulting command (see [`Self::validate_command`]).
+    pub fn command_squad(&mut self, id: SquadId, command: SquadCommand) -> Result<()> {
+        let squad = self.squad_or_err(id)?;
+        let order = match command {
+            SquadCommand::SetFormation(formation) => {
+                let order = squad.order;
+                if let Some(squad) = self.squads.get_mut(&id) {
+                    squad.formation = formation;
+                }
+                match order {
+                    Some(order) if order.destination().is_some() => order,
+                    _ => return Ok(()),
+                }
+            }
+            command => command,
+        };
+
+        let squad = self.squad_or_err(id)?;
+        tracing::debug!(squad = %id, name = %squad.name, ?order, "Squad order");
+        let members = squad.members.clone();
+        let offsets = squad.formation.offsets(members.len());
+        for &member in &members {
+            self.validate_command(member, &member_command(order, Vec2Fixed::ZERO))?;
+        }
+
+        for (&member, &offset) in members.iter().zip(&offsets) {
+            let destination = order.destination().unwrap_or_default() + offset;
+            if matches!(order, SquadCommand::Retreat(_)) {
+                if let Some(attack_target) = self
+                    .entities
+                    .get_mut(member)
+                    .and_then(|EntityMut { row, .. }| row.attack_target.as_mut())
+                {
+                    attack_target.clear();
+                }
+            }
+            self.apply_command(member, member_command(order, destination))?;
+        }
+
+        if let Some(squad) = self.squads.get_mut(&id) {
+            squad.order = Some(order);
+        }
+        Ok(())
+    }
+
+    fn squad_or_err(&self, id: SquadId) -> Result<&Squad> {
+        self.squads.get(&id).ok_or_else(|| no_squad(id))
+    }
+
+    fn check_squad_member(&self, faction: FactionId, entity: EntityId) -> Result<()> {
+        let ent = self
+            .entities
+            .get(entity)
+            .ok_or(GameError::EntityNotFound(entity))?;
+        if ent.command_queue.is_none() {
+            return Err(GameError::InvalidState(format!(
+                "Entity {} cannot take squad orders",
+                entity
+            )));
+        }
+        if ent.faction.map(|f| f.faction) != Some(faction) {
+            return Err(GameError::InvalidState(format!(
+                "Entity {} is not in the squad's faction",
+                entity
+            )));
+        }
+        Ok(())
+    }
+
+    /// Get an entity by ID.
+    #[must_use]
+    pub fn get_entity(&self, id: EntityId) -> Option<EntityRef<'_>> {
+        self.entities.get(id)
+    }
+
+    /// Position used to decide what an entity can auto-acquire.
+    ///
+    /// Idle units measure from where they went idle, so decorative wandering
+    /// never pulls an enemy into range.
+    #[must_use]
+    pub fn acquisition_position(&self, id: EntityId) -> Option<Vec2Fixed> {
+        let entity = self.entities.get(id)?;
+        entity
+            .idle_behavior
+            .and_then(|idle| idle.anchor)
+            .or(entity.position.map(|pos| pos.value))
+    }
+
+    /// Label an entity for logs: its [`DebugName`] if it has one, otherwise
+    /// `#<id>`.
+    #[must_use]
+    pub fn entity_label(&self, id: EntityId) -> String {
+        match self
+            .entities
+            .get(id)
+            .and_then(|e| e.row.debug_name.as_ref())
+        {
+            Some(name) => name.to_string(),
+            None => format!("#{id}"),
+        }
+    }
+
+    /// Calculate a hash of the current simulation state.
+    ///
+    /// Used for desync detection in multiplayer. Two simulations
+    /// with identical state will produce identical hashes.
+    #[must_use]
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        // Hash tick
+        self.tick.hash(&mut hasher);
+
+        // Hash entities in deterministic order
+        self.entities.len().hash(&mut hasher);
+
+        for (id, entity) in self.entities.iter() {
+            id.hash(&mut hasher);
+
+            // Hash position
+            if let Some(ref pos) = entity.position {
+                pos.value.x.to_bits().hash(&mut hasher);
+                pos.value.y.to_bits().hash(&mut hasher);
+            }
+
+            // Hash health
+            if let Some(ref health) = entity.health {
+                health.current.hash(&mut hasher);
+                health.max.hash(&mut hasher);
+            }
+
+            // Hash ownership (it can change through transfers)
+            if let Some(ref member) = entity.faction {
+                member.faction.hash(&mut hasher);
+                member.player_index.hash(&mut hasher);
+            }
+
+            // Hash velocity
+            if let Some(ref vel) = entity.velocity {
+                vel.value.x.to_bits().hash(&mut hasher);
+                vel.value.y.to_bits().hash(&mut hasher);
+            }
+
+            // Hash projectile
+            if let Some(ref projectile) = entity.projectile {
+                projectile.source.hash(&mut hasher);
+                projectile.target.hash(&mut hasher);
+                projectile.damage.hash(&mut hasher);
+                projectile.damage_type.hash(&mut hasher);
+                projectile.speed.to_bits().hash(&mut hasher);
+                projectile.splash.radius.to_bits().hash(&mut hasher);
+            }
+
+            // Hash patrol state
+            if let Some(ref patrol) = entity.patrol_state {
+                patrol.origin.x.to_bits().hash(&mut hasher);
+                patrol.origin.y.to_bits().hash(&mut hasher);
+                patrol.target.x.to_bits().hash(&mut hasher);
+                patrol.target.y.to_bits().hash(&mut hasher);
+                patrol.heading_to_target.hash(&mut hasher);
+            }
+
+            // Hash ammunition
+            if let Some(ref ammo) = entity.ammunition {
+                ammo.current.hash(&mut hasher);
+                ammo.max.hash(&mut hasher);
+            }
+
+            // Hash regeneration state
+            if let Some(ref regen) = entity.regeneration {
+                regen.idle_ticks.hash(&mut hasher);
+                regen.last_health.hash(&mut hasher);
+            }
+
+            // Hash idle behavior (moves units, so it must stay in sync)
+            if let Some(ref idle) = entity.idle_behavior {
+                idle.anchor
+                    .map(|a| (a.x.to_bits(), a.y.to_bits()))
+                    .hash(&mut hasher);
+                idle.wander_target
+                    .map(|t| (t.x.to_bits(), t.y.to_bits()))
+                    .hash(&mut hasher);
+                idle.facing.hash(&mut hasher);
+                idle.timer.hash(&mut hasher);
+            }
+
+            // Hash cosmetic marker (affects capacity culling order)
+            if let Some(ref cosmetic) = entity.cosmetic {
+                cosmetic.kind.hash(&mut hasher);
+                cosmetic.expires_at.hash(&mut hasher);
+            }
+
+            // Hash stealth and detection (they decide what AIs can see)
+            if entity.stealth.is_some() {
+                true.hash(&mut hasher);
+            }
+            if let Some(ref detector) = entity.detector {
+                detector.range.to_bits().hash(&mut hasher);
+            }
+            if let Some(ref collider) = entity.collider {
+                collider.radius.to_bits().hash(&mut hasher);
+            }
+
+            // Hash salvage (decides wrecks and who collects them)
+            if let Some(ref salvageable) = entity.salvageable {
+                salvageable.value.hash(&mut hasher);
+            }
+            if let Some(ref salvager) = entity.salvager {
+                salvager.rate.hash(&mut hasher);
+            }
+
+            // Hash ability cooldowns and shields
+            if let Some(ref abilities) = entity.abilities {
+                for slot in &abilities.slots {
+                    slot.ability.id.hash(&mut hasher);
+                    slot.ready_at.hash(&mut hasher);
+                }
+            }
+            if let Some(shield) = entity.shield {
+                shield.amount.hash(&mut hasher);
+                shield.expires_at.hash(&mut hasher);
+            }
+
+            // Hash transport holds and who is aboard
+            if let Some(ref cargo) = entity.cargo {
+                cargo.passengers.hash(&mut hasher);
+            }
+            entity.embarked.hash(&mut hasher);
+
+            // Hash construction progress
+            if let Some(ref building) = entity.building {
+                building.construction_progress.hash(&mut hasher);
+            }
+
+            // Hash research queues
+            if let Some(ref research) = entity.research {
+                for item in &research.queue {
+                    item.tech_id.hash(&mut hasher);
+                    item.progress.hash(&mut hasher);
+                }
+            }
+        }
+
+        // Hash wrecks
+        self.wrecks.len().hash(&mut hasher);
+        for (id, wreck) in &self.wrecks {
+            id.hash(&mut hasher);
+            wreck.position.x.to_bits().hash(&mut hasher);
+            wreck.position.y.to_bits().hash(&mut hasher);
+            wreck.remaining.hash(&mut hasher);
+            wreck.spawned_at.hash(&mut hasher);
+        }
+
+        // Hash researched technologies
+        for (faction, state) in &self.tech {
+            faction.hash(&mut hasher);
+            state.researched.hash(&mut hasher);
+        }
+
+        // Hash fielded kinds and pending rebuilds
+        self.fielded.hash(&mut hasher);
+        self.rebuilds.len().hash(&mut hasher);
+        for rebuild in &self.rebuilds {
+            rebuild.due.hash(&mut hasher);
+            rebuild.replaces.hash(&mut hasher);
+        }
+
+        // Hash how often each scenario trigger has fired
+        self.script.fired().hash(&mut hasher);
+
+        // Hash squads
+        self.squads.len().hash(&mut hasher);
+        for (id, squad) in &self.squads {
+            id.hash(&mut hasher);
+            squad.members.hash(&mut hasher);
+            squad.formation.hash(&mut hasher);
+            squad.order.hash(&mut hasher);
+        }
+
+        self.rng.state().hash(&mut hasher);
+
+        hasher.finish()
+    }
+
+    /// List everything that differs between this simulation and `other`.
+    ///
+    /// Used to explain a desync: each difference names the entity and
+    /// component, with both values in their debug form. Debug names are
+    /// ignored, as they are by [`state_hash`](Self::state_hash).
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> Vec<StateDifference> {
+        let mut diffs = Vec::new();
+        let mut world = |field: &str, local: String, remote: String| {
+            if local != remote {
+                diffs.push(StateDifference {
+                    entity: None,
+                    field: field.to_string(),
+                    local: Some(local),
+                    remote: Some(remote),
+                });
+            }
+        };
+        world("tick", self.tick.to_string(), other.tick.to_string());
+        world(
+            "squads",
+            format!("{:?}", self.squads),
+            format!("{:?}", other.squads),
+        );
+        world(
+            "rng",
+            self.rng.state().to_string(),
+            other.rng.state().to_string(),
+        );
+        world(
+            "wrecks",
+            format!("{:?}", self.wrecks),
+            format!("{:?}", other.wrecks),
+        );
+        let researched = |sim: &Self| -> BTreeMap<FactionId, BTreeSet<String>> {
+            sim.tech
+                .iter()
+                .map(|(faction, state)| (*faction, state.researched.clone()))
+                .collect()
+        };
+        world(
+            "researched",
+            format!("{:?}", researched(self)),
+            format!("{:?}", researched(other)),
+        );
+        world(
+            "fielded",
+            format!("{:?}", self.fielded),
+            format!("{:?}", other.fielded),
+        );
+        let rebuilds = |sim: &Self| -> Vec<(u64, EntityId)> {
+            sim.rebuilds.iter().map(|r| (r.due, r.replaces)).collect()
+        };
+        world(
+            "rebuilds",
+            format!("{:?}", rebuilds(self)),
+            format!("{:?}", rebuilds(other)),
+        );
+        world(
+            "script",
+            format!("{:?}", self.script.fired()),
+            format!("{:?}", other.script.fired()),
+        );
+
+        let ids: BTreeSet<EntityId> = self
+            .entities
+            .sorted_ids()
+            .into_iter()
+            .chain(other.entities.sorted_ids())
+            .collect();
+        for id in ids {
+            match (self.entities.get(id), other.entities.get(id)) {
+                (Some(local), Some(remote)) => diff_entity(local, remote, &mut diffs),
+                (local, remote) => diffs.push(StateDifference {
+                    entity: Some(id),
+                    field: "entity".to_string(),
+                    local: local.map(|_| self.entity_label(id)),
+                    remote: remote.map(|_| other.entity_label(id)),
+                }),
+            }
+        }
+        diffs
+    }
+
+    /// Serialize the simulation state for replay or network sync.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        bincode::serialize(self)
+            .map_err(|e| GameError::InvalidState(format!("Failed to serialize simulation: {}", e)))
+    }
+
+    /// Deserialize simulation state from bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if deserialization fails.
+    pub fn deserialize(data: &[u8]) -> Result<Self> {
+        bincode::deserialize(data).map_err(|e| {
+            GameError::InvalidState(format!("Failed to deserialize simulation: {}", e))
+        })
+    }
+}
+
+/// Record every component that differs between two versions of an entity.
+fn diff_entity(local: EntityRef<'_>, remote: EntityRef<'_>, diffs: &mut Vec<StateDifference>) {
+    macro_rules! compare {
+        ($($field:ident),* $(,)?) => {
+            $(
+                if local.$field != remote.$field {
+                    diffs.push(StateDifference {
+                        entity: Some(local.id),
+                        field: stringify!($field).to_string(),
+                        local: Some(format!("{:?}", local.$field)),
+                        remote: Some(format!("{:?}", remote.$field)),
+                    });
+                }
+            )*
+        };
+    }
+    compare!(
+        position,
+        velocity,
+        health,
+        command_queue,
+        movement,
+        attack_target,
+        combat_stats,
+        armament,
+        production_queue,
+        patrol_state,
+        building,
+        projectile,
+        faction,
+        depot,
+        path_waypoints,
+        vision_range,
+        ammunition,
+        resupply,
+        regeneration,
+        cosmetic,
+        idle_behavior,
+        stealth,
+        detector,
+        collider,
+        salvageable,
+        salvager,
+        tech_profile,
+        research,
+        abilities,
+        shield,
+        cargo,
+        embarked,
+        repairer,
+        site,
+        market,
+    );
+}
+
+/// A technology's change to one stat.
+#[derive(Debug, Clone, Copy)]
+enum StatModifier {
+    /// Scale by a percentage (15 for +15%).
+    Percent(i32),
+    /// Add a flat amount.
+    Flat(i32),
+}
+
+impl StatModifier {
+    /// Apply to a whole-number stat, never going below zero.
+    fn apply(self, value: u32) -> u32 {
+        let value = i64::from(value);
+        let modified = match self {
+            Self::Percent(percent) => value * (100 + i64::from(percent)) / 100,
+            Self::Flat(amount) => value + i64::from(amount),
+        };
+        u32::try_from(modified.max(0)).unwrap_or(u32::MAX)
+    }
+
+    /// Apply to a fixed-point stat, never going below zero.
+    fn apply_fixed(self, value: Fixed) -> Fixed {
+        let modified = match self {
+            Self::Percent(percent) => value * Fixed::from_num(100 + percent) / Fixed::from_num(100),
+            Self::Flat(amount) => value + Fixed::from_num(amount),
+        };
+        modified.max(Fixed::ZERO)
+    }
+}
+
+/// Apply the stat and vision effects of `tech` that match `profile`.
+///
+/// Other effects (unlocks, abilities, cost and production modifiers) are
+/// left to the game layer, which can read them from the researched set.
+fn apply_tech_effects(entity: &mut EntityMut<'_>, profile: &TechProfile, tech: &TechData) {
+    for effect in tech.effects.iter().filter(|e| profile.matches(e)) {
+        match &effect.effect_type {
+            TechEffectType::StatModifierPercent { stat, percent } => {
+                modify_stat(entity, stat, StatModifier::Percent(*percent));
+            }
+            TechEffectType::StatModifierFlat { stat, amount } => {
+                modify_stat(entity, stat, StatModifier::Flat(*amount));
+            }
+            TechEffectType::VisionModifier { range } => {
+                entity.row.vision_range = Some(sight_range(entity.view()) + *range);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Change one of an entity's stats. Unknown stats and missing components
+/// are ignored.
+fn modify_stat(entity: &mut EntityMut<'_>, stat: &str, modifier: StatModifier) {
+    match stat {
+        "damage" => {
+            if let Some(stats) = entity.combat_stats.as_mut() {
+                stats.damage = modifier.apply(stats.damage);
+            }
+            if let Some(armament) = entity.row.armament.as_mut() {
+                armament.for_each(|w| w.damage = modifier.apply(w.damage));
+            }
+        }
+        "range" => {
+            if let Some(stats) = entity.combat_stats.as_mut() {
+                stats.range = modifier.apply_fixed(stats.range);
+            }
+            if let (Some(stats), Some(armament)) =
+                (entity.combat_stats.as_mut(), entity.row.armament.as_mut())
+            {
+                armament.for_each(|w| w.range = modifier.apply_fixed(w.range));
+                *stats = armament.summarize(*stats);
+            }
+        }
+        "armor" => {
+            if let Some(stats) = entity.combat_stats.as_mut() {
+                let resistance = modifier
+                    .apply(u32::from(stats.resistance))
+                    .min(u32::from(MAX_RESISTANCE));
+                stats.resistance = u8::try_from(resistance).unwrap_or(MAX_RESISTANCE);
+            }
+        }
+        "health" => {
+            if let Some(health) = entity.health.as_mut() {
+                let max = modifier.apply(health.max).max(1);
+                health.current = if max > health.max {
+                    health.current + (max - health.max)
+                } else {
+                    health.current.min(max)
+                };
+                health.max = max;
+            }
+        }
+        "speed" => {
+            if let Some(movement) = entity.movement.as_mut() {
+                movement.speed = modifier.apply_fixed(movement.speed);
+            }
+        }
+        "vision" => {
+            entity.row.vision_range = Some(modifier.apply_fixed(sight_range(entity.view())));
+        }
+        _ => {}
+    }
+}
+
+/// Per-member command for a squad order, given the member's slot.
+fn member_command(order: SquadCommand, slot: Vec2Fixed) -> Command {
+    match order {
+        SquadCommand::MoveTo(_) | SquadCommand::Retreat(_) | SquadCommand::SetFormation(_) => {
+            Command::MoveTo(slot)
+        }
+        SquadCommand::AttackMove(_) => Command::AttackMove(slot),
+        SquadCommand::Attack(target) => Command::Attack(target),
+    }
+}
+
+fn no_squad(id: SquadId) -> GameError {
+    GameError::InvalidState(format!("No such squad: {}", id))
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::CosmeticKind;
+    use crate::squad::Formation;
+
+    #[test]
+    fn test_simulation_new() {
+        let sim = Simulation::new();
+        assert_eq!(sim.get_tick(), 0);
+        assert!(sim.entities.is_empty());
+    }
+
+    #[test]
+    fn test_spawn_entity() {
+        let mut sim = Simulation::new();
+        let id = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(10), Fixed::from_num(20))),
+            health: Some(100),
+            ..Default::default()
+        });
+
+        assert_eq!(id, 1);
+        let entity = sim.get_entity(id).unwrap();
+        assert_eq!(entity.position.unwrap().value.x, Fixed::from_num(10));
+        assert_eq!(entity.health.unwrap().current, 100);
+    }
+
+    #[test]
+    fn test_despawn_entity() {
+        let mut sim = Simulation::new();
+        let id = sim.spawn_entity(EntitySpawnParams::default());
+
+        assert!(sim.despawn_entity(id).is_ok());
+        assert!(sim.get_entity(id).is_none());
+        assert!(sim.despawn_entity(id).is_err());
+    }
+
+    #[test]
+    fn test_entity_storage_keeps_id_order_through_removals() {
+        let mut storage = EntityStorage::new();
+        let ids: Vec<EntityId> = (0..200).map(|_| storage.insert(Entity::new(0))).collect();
+        // Enough removals to force compaction
+        for &id in ids.iter().filter(|&&id| id % 3 != 0) {
+            assert_eq!(storage.remove(id).map(|e| e.id), Some(id));
+        }
+        assert!(storage.remove(ids[1]).is_none());
+
+        let kept: Vec<EntityId> = ids.iter().copied().filter(|id| id % 3 == 0).collect();
+        assert_eq!(storage.len(), kept.len());
+        assert_eq!(storage.sorted_ids(), kept);
+        assert!(kept
+            .iter()
+            .all(|&id| storage.get(id).is_some_and(|e| e.id == id)));
+        assert!(!storage.contains(ids[1]));
+        assert_eq!(storage.insert(Entity::new(0)), 201);
+        assert_eq!(
+            storage
+                .iter_through(9)
+                .map(|(&id, _)| id)
+                .collect::<Vec<_>>(),
+            vec![3, 6, 9]
+        );
+
+        let json = serde_json::to_string(&storage).unwrap();
+        let restored: EntityStorage = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.sorted_ids(), storage.sorted_ids());
+        assert_eq!(restored.next_id, storage.next_id);
+    }
+
+    #[test]
+    fn test_entity_storage_columns_line_up_with_rows() {
+        let mut storage = EntityStorage::new();
+        let ids: Vec<EntityId> = (0..3)
+            .map(|i| {
+                let mut entity = Entity::new(0);
+                entity.position = Some(Position::new(Vec2Fixed::new(
+                    Fixed::from_num(i),
+                    Fixed::ZERO,
+                )));
+                entity.depot = Some(Depot);
+                storage.insert(entity)
+            })
+            .collect();
+
+        let mut entity = storage.get_mut(ids[2]).unwrap();
+        *entity.health = Some(Health::new(50));
+        entity.depot = None;
+
+        let removed = storage.remove(ids[1]).unwrap();
+        assert_eq!(removed.position.unwrap().value.x, Fixed::from_num(1));
+        assert!(removed.depot.is_some());
+
+        let columns = storage.columns();
+        assert_eq!(columns.ids, ids.as_slice());
+        assert!(columns.positions[1].is_none());
+        assert!(columns.rows[1].is_none());
+        assert!(columns.entity(1).is_none());
+        assert_eq!(columns.health[2], Some(Health::new(50)));
+        assert!(columns.rows[2].as_ref().is_some_and(|r| r.depot.is_none()));
+        assert_eq!(
+            columns.entities().map(|(&id, _)| id).collect::<Vec<_>>(),
+            vec![ids[0], ids[2]]
+        );
+        assert_eq!(storage.get(ids[0]).unwrap().to_entity().id, ids[0]);
+    }
+
+    #[test]
+    fn test_entity_storage_index_drops_dead_ids() {
+        let mut storage = EntityStorage::new();
+        let ids: Vec<EntityId> = (0..1000).map(|_| storage.insert(Entity::new(0))).collect();
+        for &id in &ids[..990] {
+            storage.remove(id);
+        }
+
+        // Compaction rebased the index past the long-dead IDs
+        assert!(storage.index.len() < 100);
+        assert!(ids[990..].iter().all(|&id| storage.contains(id)));
+        assert!(!storage.contains(ids[0]));
+        assert_eq!(storage.insert(Entity::new(0)), 1001);
+        assert!(storage.get(1001).is_some());
+    }
+
+    #[test]
+    fn test_tick_increments() {
+        let mut sim = Simulation::new();
+        assert_eq!(sim.get_tick(), 0);
+
+        sim.tick();
+        assert_eq!(sim.get_tick(), 1);
+
+        sim.tick();
+        assert_eq!(sim.get_tick(), 2);
+    }
+
+    #[test]
+    fn test_movement_integration() {
+        let mut sim = Simulation::new();
+        let id = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(0), Fixed::from_num(0))),
+            movement: Some(Fixed::from_num(5)),
+            ..Default::default()
+        });
+
+        // Issue move command
+        sim.apply_command(
+            id,
+            Command::MoveTo(Vec2Fixed::new(Fixed::from_num(100), Fixed::from_num(0))),
+        )
+        .unwrap();
+
+        // Run a tick
+        sim.tick();
+
+        // Entity should have moved
+        let entity = sim.get_entity(id).unwrap();
+        let pos = entity.position.unwrap();
+        assert!(pos.value.x > Fixed::from_num(0));
+    }
+
+    #[test]
+    fn test_patrol_toggles_heading() {
+        let mut sim = Simulation::new();
+        let id = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            movement: Some(Fixed::from_num(2)),
+            ..Default::default()
+        });
+
+        let target = Vec2Fixed::new(Fixed::from_num(10), Fixed::from_num(0));
+        sim.apply_command(id, Command::Patrol(target)).unwrap();
+
+        sim.tick();
+        let state = sim.get_entity(id).unwrap().patrol_state.unwrap();
+        assert!(state.heading_to_target);
+
+        if let Some(entity) = sim.entities.get_mut(id) {
+            *entity.position = Some(Position::new(target));
+        }
+
+        sim.tick();
+        let state = sim.get_entity(id).unwrap().patrol_state.unwrap();
+        assert!(!state.heading_to_target);
+    }
+
+    #[test]
+    fn test_attack_command_chases_target() {
+        let mut sim = Simulation::new();
+        let attacker = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            movement: Some(Fixed::from_num(3)),
+            ..Default::default()
+        });
+
+        let target = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(20), Fixed::from_num(0))),
+            ..Default::default()
+        });
+
+        sim.apply_command(attacker, Command::Attack(target))
+            .unwrap();
+        sim.tick();
+
+        let entity = sim.get_entity(attacker).unwrap();
+        let pos = entity.position.unwrap();
+        assert!(pos.value.x > Fixed::from_num(0));
+    }
+
+    #[test]
+    fn test_apply_commands_matches_one_by_one() {
+        let setup = || {
+            let mut sim = Simulation::new();
+            let mut spawn = |faction, x: i32| {
+                sim.spawn_entity(EntitySpawnParams {
+                    position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(0))),
+                    health: Some(100),
+                    movement: Some(Fixed::from_num(2)),
+                    combat_stats: Some(CombatStats::default()),
+                    faction: Some(FactionMember::new(faction, 0)),
+                    ..Default::default()
+                })
+            };
+            let ours = [
+                spawn(FactionId::Continuity, 0),
+                spawn(FactionId::Continuity, 10),
+            ];
+            let enemy = spawn(FactionId::Collegium, 100);
+            (sim, ours, enemy)
+        };
+        let (mut batched, [a, b], enemy) = setup();
+        let batch = vec![
+            (a, Command::Attack(enemy)),
+            (b, Command::Attack(enemy)),
+            (b, Command::Attack(a)),
+            (99, Command::Stop),
+            (
+                a,
+                Command::MoveTo(Vec2Fixed::new(Fixed::from_num(50), Fixed::from_num(5))),
+            ),
+        ];
+        let results = batched.apply_commands(&batch);
+
+        let (mut single, _, _) = setup();
+        let expected: Vec<bool> = batch
+            .iter()
+            .map(|(entity, command)| single.apply_command(*entity, command.clone()).is_ok())
+            .collect();
+        assert_eq!(
+            results.iter().map(Result::is_ok).collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(expected, vec![true, true, false, false, true]);
+        assert!(matches!(results[3], Err(GameError::EntityNotFound(99))));
+
+        for _ in 0..20 {
+            batched.tick();
+            single.tick();
+        }
+        assert_eq!(batched.state_hash(), single.state_hash());
+    }
+
+    #[test]
+    fn test_air_units_fly_over_walls_and_need_weapons_that_reach_them() {
+        use crate::combat::WeaponTargets;
+        use crate::pathfinding::CellType;
+
+        let mut sim = Simulation::new();
+        // A solid wall across the map at x = 10
+        for y in 0..64 {
+            sim.nav_grid_mut().set_cell(10, y, CellType::Blocked);
+        }
+        let spawn = |sim: &mut Simulation, domain, faction| {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(sim.nav_grid().grid_to_world(2, 15)),
+                health: Some(100),
+                movement: Some(Fixed::from_num(8)),
+                domain,
+                faction: Some(FactionMember::new(faction, 0)),
+                ..Default::default()
+            })
+        };
+        let walker = spawn(&mut sim, MovementDomain::Ground, FactionId::Collegium);
+        let flyer = spawn(&mut sim, MovementDomain::Air, FactionId::Collegium);
+        let hover = spawn(&mut sim, MovementDomain::Hover, FactionId::Collegium);
+
+        let target = sim.nav_grid().grid_to_world(20, 15);
+        sim.apply_command(flyer, Command::MoveTo(target)).unwrap();
+        assert!(sim.get_entity(flyer).unwrap().path_waypoints.is_none());
+        for _ in 0..100 {
+            sim.tick();
+        }
+        let landed = sim.get_entity(flyer).unwrap().position.unwrap().value;
+        assert!(landed.distance_squared(target) <= Fixed::from_num(1));
+
+        let artillery = sim.spawn_entity(EntitySpawnParams {
+            position: Some(sim.nav_grid().grid_to_world(3, 15)),
+            health: Some(100),
+            movement: Some(Fixed::from_num(2)),
+            combat_stats: Some(
+                CombatStats::new(10, Fixed::from_num(500), 10)
+                    .with_targets(WeaponTargets::GroundOnly),
+            ),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            ..Default::default()
+        });
+        assert!(sim.can_target(artillery, walker));
+        assert!(sim.can_target(artillery, hover));
+        assert!(!sim.can_target(artillery, flyer));
+        for command in [Command::Attack(flyer), Command::ForceAttack(flyer)] {
+            assert!(matches!(
+                sim.apply_command(artillery, command),
+                Err(GameError::InvalidState(_))
+            ));
+        }
+
+        // A target set directly is dropped once the weapon cannot reach it
+        sim.set_attack_target(artillery, flyer).unwrap();
+        let events = sim.tick();
+        assert!(events.damage_events.is_empty());
+        let attack = sim.get_entity(artillery).unwrap().attack_target.unwrap();
+        assert_eq!(attack.target, None);
+        sim.apply_command(artillery, Command::Attack(hover))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_units_with_several_weapons_fire_the_best_one_for_each_target() {
+        use crate::combat::{ArmorClass, WeaponSize, WeaponTargets};
+        use crate::components::{DamageType, Weapon};
+
+        let mut sim = Simulation::new();
+        let cannon = CombatStats::new(60, Fixed::from_num(10), 60)
+            .with_damage_type(DamageType::Explosive)
+            .with_weapon_size(WeaponSize::Heavy)
+            .with_targets(WeaponTargets::GroundOnly)
+            .with_resistance(ArmorClass::Heavy, 0);
+        let machine_gun = Weapon::from_stats(
+            &CombatStats::new(6, Fixed::from_num(8), 6).with_weapon_size(WeaponSize::Light),
+        );
+        let tank = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(10), Fixed::from_num(10))),
+            health: Some(500),
+            combat_stats: Some(cannon),
+            weapons: vec![machine_gun],
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            ..Default::default()
+        });
+        let target = |sim: &mut Simulation, x: i32, armor: ArmorClass, domain| {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(14))),
+                health: Some(1000),
+                movement: Some(Fixed::ZERO),
+                domain,
+                combat_stats: Some(CombatStats::new(0, Fixed::ZERO, 1).with_resistance(armor, 0)),
+                faction: Some(FactionMember::new(FactionId::Collegium, 0)),
+                ..Default::default()
+            })
+        };
+        let heavy = target(&mut sim, 8, ArmorClass::Heavy, MovementDomain::Ground);
+        let infantry = target(&mut sim, 10, ArmorClass::Light, MovementDomain::Ground);
+        let flyer = target(&mut sim, 12, ArmorClass::Air, MovementDomain::Air);
+
+        // Targeting sees the longest reach and every domain any weapon hits
+        let stats = sim.get_entity(tank).unwrap().combat_stats.unwrap();
+        assert_eq!(stats.range, Fixed::from_num(10));
+        assert_eq!(stats.targets, WeaponTargets::Any);
+        assert_eq!(stats.damage, 60);
+        assert!(sim.can_target(tank, flyer));
+
+        let shot_at = |sim: &mut Simulation, target| {
+            sim.set_attack_target(tank, target).unwrap();
+            (0..10)
+                .flat_map(|_| sim.tick().damage_events)
+                .find(|e| e.attacker == tank && e.target == target)
+                .map(|e| e.damage)
+        };
+        // The machine gun against infantry, holding the cannon back
+        assert_eq!(shot_at(&mut sim, infantry), Some(6));
+        let armament = sim.get_entity(tank).unwrap().armament.clone().unwrap();
+        assert_eq!(armament.weapons[0].cooldown_remaining, 0);
+        assert!(armament.weapons[1].cooldown_remaining > 0);
+        // The cannon against armor, and the machine gun against aircraft
+        // the cannon cannot reach
+        assert_eq!(shot_at(&mut sim, heavy), Some(75));
+        assert_eq!(shot_at(&mut sim, flyer), Some(4));
+        let attack = sim.get_entity(tank).unwrap().attack_target.unwrap();
+        assert_eq!(attack.target, Some(flyer));
+    }
+
+    #[test]
+    fn test_engineers_repair_machines_and_medics_heal_infantry() {
+        use crate::repair::RepairKind;
+
+        let mut sim = Simulation::new();
+        let origin = Vec2Fixed::new(Fixed::from_num(100), Fixed::from_num(100));
+        let spawn = |sim: &mut Simulation, x: i32, tag: &str, mobile: bool, repairer| {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(origin + Vec2Fixed::new(Fixed::from_num(x), Fixed::ZERO)),
+                health: Some(100),
+                movement: mobile.then(|| Fixed::from_num(4)),
+                faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+                tech_profile: Some(TechProfile::new(tag, vec![tag.to_string()])),
+                repairer,
+                ..Default::default()
+            })
+        };
+        let wrench = Repairer::new(RepairKind::Mechanical, 10, 5, 2);
+        let bandage = Repairer::new(RepairKind::Biological, 10, 5, 0);
+        let engineer = spawn(&mut sim, 0, "engineer", true, Some(wrench));
+        let medic = spawn(&mut sim, 0, "infantry", true, Some(bandage));
+        let turret = spawn(&mut sim, 60, "infantry", false, None);
+        let trooper = spawn(&mut sim, -60, "infantry", true, None);
+        for id in [turret, trooper] {
+            sim.entities
+                .get_mut(id)
+                .unwrap()
+                .health
+                .as_mut()
+                .unwrap()
+                .current = 50;
+        }
+
+        for (repairer, patient) in [(engineer, trooper), (medic, turret), (trooper, turret)] {
+            assert!(matches!(
+                sim.apply_command(repairer, Command::Repair(patient)),
+                Err(GameError::InvalidState(_))
+            ));
+        }
+        sim.apply_command(engineer, Command::Repair(turret))
+            .unwrap();
+        sim.apply_command(medic, Command::Repair(trooper)).unwrap();
+
+        let mut repairs = Vec::new();
+        for _ in 0..60 {
+            repairs.extend(sim.tick().repairs);
+        }
+        for id in [turret, trooper] {
+            assert_eq!(sim.get_entity(id).unwrap().health.unwrap().current, 100);
+        }
+        let engineer_cost: u32 = repairs
+            .iter()
+            .filter(|r| r.repairer == engineer)
+            .map(|r| r.cost)
+            .sum();
+        assert_eq!(engineer_cost, 10);
+        assert!(repairs.iter().all(|r| r.amount == 10));
+
+        // Orders end once the patient is whole
+        let queue = sim.get_entity(engineer).unwrap().row.command_queue.as_ref();
+        assert!(queue.unwrap().current().is_none());
+    }
+
+    #[test]
+    fn test_workers_construct_buildings_and_cancelling_refunds() {
+        let mut sim = Simulation::new();
+        let at = |x: i32, y: i32| Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y));
+        let owner = Some(FactionMember::new(FactionId::Continuity, 0));
+        let unit = |sim: &mut Simulation, x: i32, tag: &str| {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(at(x, 100)),
+                health: Some(100),
+                movement: Some(Fixed::from_num(4)),
+                faction: owner,
+                tech_profile: Some(TechProfile::new(tag, vec![tag.to_string()])),
+                ..Default::default()
+            })
+        };
+        let worker = unit(&mut sim, 100, "harvester");
+        let trooper = unit(&mut sim, 100, "infantry");
+        let barracks = |x: i32| EntitySpawnParams {
+            position: Some(at(x, 300)),
+            health: Some(500),
+            faction: owner,
+            tech_profile: Some(TechProfile::new("barracks", Vec::new())),
+            ..Default::default()
+        };
+        let footprint = BuildingFootprint::square(2);
+
+        assert!(matches!(
+            sim.order_construction(trooper, barracks(300), footprint, 100, 150),
+            Err(GameError::InvalidState(_))
+        ));
+        let site = sim
+            .order_construction(worker, barracks(300), footprint, 300, 150)
+            .unwrap();
+        // The ground is taken, for other sites and for pathing
+        assert!(sim
+            .order_construction(worker, barracks(310), footprint, 100, 150)
+            .is_err());
+        let origin = sim.get_entity(site).unwrap().site.unwrap().origin;
+        assert!(!sim.nav_grid().is_walkable(origin.0, origin.1));
+        assert_eq!(sim.get_entity(site).unwrap().health.unwrap().current, 50);
+
+        // Nothing is built until the worker arrives
+        let mut completed = Vec::new();
+        for _ in 0..200 {
+            completed.extend(sim.tick().construction);
+        }
+        let building = sim.get_entity(site).unwrap().building.clone().unwrap();
+        assert!(building.construction_progress > 0);
+        assert!(building.construction_progress < 300);
+        while completed.is_empty() {
+            completed.extend(sim.tick().construction);
+        }
+        assert_eq!(
+            completed,
+            vec![ConstructionEvent::ConstructionComplete { building: site }]
+        );
+        assert_eq!(sim.get_entity(site).unwrap().health.unwrap().current, 500);
+        assert!(sim.cancel_construction(site).is_err());
+
+        // Cancelling refunds the share not yet built and frees the ground
+        let second = sim
+            .order_construction(worker, barracks(500), footprint, 100, 150)
+            .unwrap();
+        sim.entities
+            .get_mut(second)
+            .unwrap()
+            .building
+            .as_mut()
+            .unwrap()
+            .construction_progress = 40;
+        let origin = sim.get_entity(second).unwrap().site.unwrap().origin;
+        assert_eq!(sim.cancel_construction(second).unwrap(), 90);
+        assert!(sim.get_entity(second).is_none());
+        assert!(sim.nav_grid().is_walkable(origin.0, origin.1));
+        sim.tick();
+        let queue = sim.get_entity(worker).unwrap().row.command_queue.as_ref();
+        assert!(queue.unwrap().current().is_none());
+    }
+
+    #[test]
+    fn test_queued_moves_path_as_waypoints_and_rally_points_apply_at_once() {
+        use crate::pathfinding::CellType;
+
+        let mut sim = Simulation::new();
+        // A wall at x = 10, open above y = 20
+        for y in 0..20 {
+            sim.nav_grid_mut().set_cell(10, y, CellType::Blocked);
+        }
+        let unit = sim.spawn_entity(EntitySpawnParams {
+            position: Some(sim.nav_grid().grid_to_world(2, 15)),
+            health: Some(100),
+            movement: Some(Fixed::from_num(8)),
+            ..Default::default()
+        });
+        let first = sim.nav_grid().grid_to_world(5, 15);
+        let second = sim.nav_grid().grid_to_world(20, 15);
+        sim.queue_command(unit, Command::MoveTo(first)).unwrap();
+        sim.queue_command(unit, Command::MoveTo(second)).unwrap();
+        let queue = sim.get_entity(unit).unwrap().command_queue.clone().unwrap();
+        assert_eq!(queue.waypoints().collect::<Vec<_>>(), vec![first, second]);
+
+        // The second leg is pathed around the wall once it comes up
+        let mut routed = false;
+        for _ in 0..400 {
+            sim.tick();
+            let entity = sim.get_entity(unit).unwrap();
+            if entity.command_queue.as_ref().unwrap().current() == Some(&Command::MoveTo(second)) {
+                routed |= entity
+                    .path_waypoints
+                    .as_ref()
+                    .is_some_and(|w| !w.is_empty());
+            }
+        }
+        assert!(routed);
+        let arrived = sim.get_entity(unit).unwrap().position.unwrap().value;
+        assert!(arrived.distance_squared(second) <= Fixed::from_num(1));
+
+        let barracks = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(100), Fixed::from_num(100))),
+            health: Some(500),
+            has_production_queue: true,
+            ..Default::default()
+        });
+        let rally = Vec2Fixed::new(Fixed::from_num(200), Fixed::from_num(150));
+        sim.queue_command(barracks, Command::SetRallyPoint(rally))
+            .unwrap();
+        let building = sim.get_entity(barracks).unwrap().building.clone().unwrap();
+        assert_eq!(building.rally_point, Some(rally));
+        assert!(sim
+            .apply_command(unit, Command::SetRallyPoint(rally))
+            .is_err());
+    }
+
+    #[test]
+    fn test_transport_carries_infantry_and_loses_them_when_destroyed() {
+        let mut sim = Simulation::new();
+        let origin = Vec2Fixed::new(Fixed::from_num(100), Fixed::from_num(100));
+        let spawn = |sim: &mut Simulation, x: i32, tag: &str, faction, cargo| {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(origin + Vec2Fixed::new(Fixed::from_num(x), Fixed::ZERO)),
+                health: Some(100),
+                movement: Some(Fixed::from_num(4)),
+                faction: Some(FactionMember::new(faction, 0)),
+                tech_profile: Some(TechProfile::new(tag, vec![tag.to_string()])),
+                cargo,
+                ..Default::default()
+            })
+        };
+        let hold = Cargo::new(2, vec!["infantry".to_string()]);
+        let transport = spawn(&mut sim, 0, "vehicle", FactionId::Continuity, Some(hold));
+        let trooper = spawn(&mut sim, 40, "infantry", FactionId::Continuity, None);
+        let tank = spawn(&mut sim, 40, "vehicle", FactionId::Continuity, None);
+        let enemy = spawn(&mut sim, 40, "infantry", FactionId::Collegium, None);
+
+        for refused in [tank, enemy] {
+            assert!(matches!(
+                sim.apply_command(refused, Command::Load(transport)),
+                Err(GameError::InvalidState(_))
+            ));
+        }
+        sim.apply_command(trooper, Command::Load(transport))
+            .unwrap();
+        for _ in 0..10 {
+            sim.tick();
+        }
+        let aboard = sim.get_entity(trooper).unwrap();
+        assert_eq!(aboard.position, None);
+        assert_eq!(aboard.embarked, Some(transport));
+        assert_eq!(
+            sim.get_entity(transport)
+                .unwrap()
+                .cargo
+                .as_ref()
+                .unwrap()
+                .passengers,
+            vec![trooper]
+        );
+        assert!(sim.apply_command(trooper, Command::Stop).is_err());
+
+        let drop = origin + Vec2Fixed::new(Fixed::from_num(200), Fixed::ZERO);
+        sim.apply_command(transport, Command::Unload(drop)).unwrap();
+        for _ in 0..60 {
+            sim.tick();
+        }
+        let landed = sim.get_entity(trooper).unwrap();
+        assert_eq!(landed.embarked, None);
+        let landed = landed.position.unwrap().value;
+        assert!(landed.distance_squared(drop) <= Fixed::from_num(32 * 32));
+
+        // Passengers go down with their transport
+        sim.apply_command(trooper, Command::Load(transport))
+            .unwrap();
+        for _ in 0..10 {
+            sim.tick();
+        }
+        assert_eq!(sim.get_entity(trooper).unwrap().embarked, Some(transport));
+        sim.entities
+            .get_mut(transport)
+            .unwrap()
+            .health
+            .as_mut()
+            .unwrap()
+            .current = 0;
+        let events = sim.tick();
+        assert_eq!(events.deaths, vec![transport, trooper]);
+        assert!(sim.get_entity(trooper).is_none());
+    }
+
+    #[test]
+    fn test_group_moves_share_flow_field_through_gap() {
+        use crate::pathfinding::CellType;
+
+        let mut sim = Simulation::new();
+        // A wall across the map at x = 10 with a single gap at y = 15
+        for y in 0..64 {
+            if y != 15 {
+                sim.nav_grid_mut().set_cell(10, y, CellType::Blocked);
+            }
+        }
+        let units: Vec<EntityId> = (0..50)
+            .map(|i| {
+                let cell = sim.nav_grid().grid_to_world(2 + i % 5, 10 + i / 5);
+                sim.spawn_entity(EntitySpawnParams {
+                    position: Some(cell),
+                    movement: Some(Fixed::from_num(8)),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        let target = sim.nav_grid().grid_to_world(20, 15);
+        let batch: Vec<_> = units
+            .iter()
+            .map(|&unit| (unit, Command::MoveTo(target)))
+            .collect();
+        assert!(sim.apply_commands(&batch).iter().all(Result::is_ok));
+
+        let gap = sim.nav_grid().grid_to_world(10, 15);
+        for &unit in &units {
+            let waypoints = sim.get_entity(unit).unwrap().path_waypoints.clone();
+            assert!(waypoints.unwrap().contains(&gap));
+        }
+
+        for _ in 0..400 {
+            sim.tick();
+            for &unit in &units {
+                let pos = sim.get_entity(unit).unwrap().position.unwrap().value;
+                let (x, y) = sim.nav_grid().world_to_grid(pos).unwrap();
+                assert!(sim.nav_grid().is_walkable(x, y), "unit {unit} in a wall");
+            }
+        }
+        for &unit in &units {
+            let entity = sim.get_entity(unit).unwrap();
+            assert!(entity.position.unwrap().value.distance_squared(target) <= Fixed::from_num(1));
+            assert!(entity.command_queue.as_ref().unwrap().is_empty());
+        }
+
+        // A lone attack-move is planned with A* and also routed through the gap
+        let straggler = sim.spawn_entity(EntitySpawnParams {
+            position: Some(sim.nav_grid().grid_to_world(2, 40)),
+            movement: Some(Fixed::from_num(8)),
+            ..Default::default()
+        });
+        sim.apply_command(straggler, Command::AttackMove(target))
+            .unwrap();
+        let waypoints = sim.get_entity(straggler).unwrap().path_waypoints.clone();
+        assert!(waypoints.unwrap().contains(&gap));
+    }
+
+    #[test]
+    fn test_units_sharing_a_destination_do_not_stack() {
+        let run = || {
+            let mut sim = Simulation::new();
+            let units: Vec<EntityId> = (0..12)
+                .map(|i| {
+                    sim.spawn_entity(EntitySpawnParams {
+                        position: Some(Vec2Fixed::new(
+                            Fixed::from_num(100 + (i % 4) * 40),
+                            Fixed::from_num(100 + (i / 4) * 40),
+                        )),
+                        movement: Some(Fixed::from_num(2)),
+                        collision_radius: Some(Fixed::from_num(10)),
+                        ..Default::default()
+                    })
+                })
+                .collect();
+            let target = Vec2Fixed::new(Fixed::from_num(500), Fixed::from_num(500));
+            let batch: Vec<_> = units
+                .iter()
+                .map(|&unit| (unit, Command::MoveTo(target)))
+                .collect();
+            sim.apply_commands(&batch);
+            for _ in 0..600 {
+                sim.tick();
+            }
+            (sim, units)
+        };
+        let (sim, units) = run();
+
+        let positions: Vec<Vec2Fixed> = units
+            .iter()
+            .map(|&unit| sim.get_entity(unit).unwrap().position.unwrap().value)
+            .collect();
+        for (i, a) in positions.iter().enumerate() {
+            for b in &positions[i + 1..] {
+                assert!(
+                    a.distance_squared(*b) >= Fixed::from_num(19 * 19),
+                    "{a:?} overlaps {b:?}"
+                );
+            }
+        }
+        for &unit in &units {
+            let entity = sim.get_entity(unit).unwrap();
+            assert!(entity.command_queue.as_ref().unwrap().is_empty());
+        }
+        assert_eq!(run().0.state_hash(), sim.state_hash());
+    }
+
+    #[test]
+    fn test_owns_market_needs_a_finished_market() {
+        let mut sim = Simulation::new();
+        let market = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            health: Some(100),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            is_market: true,
+            ..Default::default()
+        });
+        if let Some(mut entity) = sim.entities.get_mut(market) {
+            entity.building = Some(ProductionBuilding::new(BuildingTypeId::new(0), 100));
+        }
+        assert!(!sim.owns_market(FactionId::Continuity));
+
+        if let Some(mut entity) = sim.entities.get_mut(market) {
+            entity.building = Some(ProductionBuilding::constructed(BuildingTypeId::new(0)));
+        }
+        assert!(sim.owns_market(FactionId::Continuity));
+        assert!(!sim.owns_market(FactionId::Collegium));
+    }
+
+    #[test]
+    fn test_last_depot_standing_reports_elimination() {
+        let mut sim = Simulation::new();
+        let mut structure = |faction, is_depot| {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::ZERO),
+                health: Some(100),
+                faction: Some(FactionMember::new(faction, 0)),
+                is_depot,
+                ..Default::default()
+            })
+        };
+        structure(FactionId::Continuity, true);
+        structure(FactionId::Continuity, false);
+        let enemy_depot = structure(FactionId::Collegium, true);
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            health: Some(100),
+            movement: Some(Fixed::from_num(2)),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            ..Default::default()
+        });
+
+        let events = sim.tick();
+        assert_eq!(events.win_condition, None);
+
+        if let Some(health) = sim
+            .entities
+            .get_mut(enemy_depot)
+            .and_then(|e| e.health.as_mut())
+        {
+            health.current = 0;
+        }
+        let events = sim.tick();
+        assert_eq!(events.game_end, Some(FactionId::Continuity));
+        assert_eq!(
+            events.win_condition,
+            Some(WinCondition::Elimination {
+                eliminating_player: FactionId::Continuity,
+                surviving_structures: 2,
+            })
+        );
+    }
+
+    fn skirmish() -> (Simulation, EntityId, EntityId, EntityId) {
+        let mut sim = Simulation::new();
+        let mut spawn = |faction, x: i32| {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(0))),
+                health: Some(200),
+                movement: Some(Fixed::from_num(2)),
+                combat_stats: Some(CombatStats::new(5, Fixed::from_num(20), 10)),
+                faction: Some(FactionMember::new(faction, 0)),
+                ..Default::default()
+            })
+        };
+        let attacker = spawn(FactionId::Continuity, 0);
+        let forcer = spawn(FactionId::Continuity, 5);
+        let captive = spawn(FactionId::Collegium, 15);
+        sim.apply_command(attacker, Command::Attack(captive))
+            .unwrap();
+        sim.apply_command(forcer, Command::ForceAttack(captive))
+            .unwrap();
+        sim.apply_command(captive, Command::Attack(attacker))
+            .unwrap();
+        (sim, attacker, forcer, captive)
+    }
+
+    #[test]
+    fn test_transfer_ownership_stands_down_new_allies() {
+        let (mut sim, attacker, forcer, captive) = skirmish();
+        sim.tick();
+        assert_eq!(
+            sim.get_entity(attacker)
+                .unwrap()
+                .attack_target
+                .unwrap()
+                .target,
+            Some(captive)
+        );
+
+        let owner = FactionMember::new(FactionId::Continuity, 0);
+        let change = sim.transfer_ownership(captive, owner).unwrap();
+        assert_eq!(change.previous.faction, FactionId::Collegium);
+        assert_eq!(sim.get_entity(captive).unwrap().faction, Some(owner));
+
+        let captured = sim.get_entity(captive).unwrap();
+        assert!(captured.command_queue.as_ref().unwrap().is_empty());
+        assert_eq!(captured.attack_target.unwrap().target, None);
+        let attacking = sim.get_entity(attacker).unwrap();
+        assert_eq!(attacking.attack_target.unwrap().target, None);
+        assert!(attacking.command_queue.as_ref().unwrap().is_empty());
+        // Explicit force-attack orders are left alone
+        assert_eq!(
+            sim.get_entity(forcer)
+                .unwrap()
+                .attack_target
+                .unwrap()
+                .target,
+            Some(captive)
+        );
+
+        assert_eq!(sim.tick().ownership_changes, vec![change]);
+        assert!(sim.tick().ownership_changes.is_empty());
+        assert!(matches!(
+            sim.transfer_ownership(captive, owner),
+            Err(GameError::InvalidState(_))
+        ));
+        assert!(matches!(
+            sim.transfer_ownership(99, owner),
+            Err(GameError::EntityNotFound(99))
+        ));
+    }
+
+    #[test]
+    fn test_transfer_ownership_is_deterministic() {
+        let run = |transfer: bool| {
+            let (mut sim, _, _, captive) = skirmish();
+            let mut changes = Vec::new();
+            for tick in 0..60 {
+                if transfer && tick == 10 {
+                    sim.transfer_ownership(captive, FactionMember::new(FactionId::Continuity, 1))
+                        .unwrap();
+                    // Restored mid-flight state reports the same pending change
+                    sim = Simulation::deserialize(&sim.serialize().unwrap()).unwrap();
+                }
+                changes.extend(sim.tick().ownership_changes);
+            }
+            (sim.state_hash(), changes)
+        };
+
+        let (first, changes) = run(true);
+        let (second, replayed) = run(true);
+        assert_eq!(first, second);
+        assert_eq!(changes, replayed);
+        assert_eq!(changes.len(), 1);
+        // Ownership is part of the state hash
+        assert_ne!(first, run(false).0);
+    }
+
+    #[test]
+    fn test_change_stats_keeps_health_share_and_reload() {
+        let (mut sim, attacker, _, captive) = skirmish();
+        for _ in 0..3 {
+            sim.tick();
+        }
+        let before = sim.get_entity(captive).unwrap().health.unwrap();
+        assert!(before.current < before.max);
+
+        let change = StatChange {
+            max_health: Some(400),
+            damage: Some(9),
+            range: Some(Fixed::from_num(30)),
+            attack_cooldown: Some(4),
+        };
+        sim.change_stats(captive, &change).unwrap();
+        let after = sim.get_entity(captive).unwrap();
+        let health = after.health.unwrap();
+        assert_eq!(health.max, 400);
+        assert_eq!(health.current, before.current * 2);
+        let stats = after.combat_stats.unwrap();
+        assert_eq!(
+            (stats.damage, stats.range, stats.attack_cooldown),
+            (9, Fixed::from_num(30), 4)
+        );
+        assert!(stats.cooldown_remaining <= 4);
+
+        // Unchanged stats stay as they are
+        let untouched = sim.get_entity(attacker).unwrap().combat_stats.unwrap();
+        sim.change_stats(attacker, &StatChange::default()).unwrap();
+        assert_eq!(
+            sim.get_entity(attacker).unwrap().combat_stats.unwrap(),
+            untouched
+        );
+        assert!(matches!(
+            sim.change_stats(9999, &change),
+            Err(GameError::EntityNotFound(9999))
+        ));
+    }
+
+    #[test]
+    fn test_diff_names_diverged_entities_and_components() {
+        let (sim, attacker, _, captive) = skirmish();
+        assert!(sim.diff(&sim.clone()).is_empty());
+
+        let mut diverged = sim.clone();
+        if let Some(health) = diverged.entities.get_mut(captive).unwrap().health.as_mut() {
+            health.current = 150;
+        }
+        diverged.despawn_entity(attacker).unwrap();
+        diverged.seed_rng(7);
+        let diffs = sim.diff(&diverged);
+        let fields: Vec<_> = diffs.iter().map(|d| (d.entity, d.field.as_str())).collect();
+        assert_eq!(
+            fields,
+            vec![
+                (None, "rng"),
+                (Some(attacker), "entity"),
+                (Some(captive), "health")
+            ]
+        );
+        let gone = diffs.iter().find(|d| d.entity == Some(attacker)).unwrap();
+        assert!(gone.local.is_some() && gone.remote.is_none());
+    }
+
+    #[test]
+    fn test_deterministic_hash() {
+        let mut sim1 = Simulation::new();
+        let mut sim2 = Simulation::new();
+
+        // Same operations
+        let id1 = sim1.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(10), Fixed::from_num(20))),
+            health: Some(100),
+            movement: Some(Fixed::from_num(2)),
+            ..Default::default()
+        });
+        let id2 = sim2.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(10), Fixed::from_num(20))),
+            health: Some(100),
+            movement: Some(Fixed::from_num(2)),
+            ..Default::default()
+        });
+
+        sim1.apply_command(
+            id1,
+            Command::MoveTo(Vec2Fixed::new(Fixed::from_num(50), Fixed::from_num(50))),
+        )
+        .unwrap();
+        sim2.apply_command(
+            id2,
+            Command::MoveTo(Vec2Fixed::new(Fixed::from_num(50), Fixed::from_num(50))),
+        )
+        .unwrap();
+
+        sim1.tick();
+        sim2.tick();
+
+        // Hashes must be identical
+        assert_eq!(sim1.state_hash(), sim2.state_hash());
+    }
+
+    #[test]
+    fn test_projectile_hits_target() {
+        let mut sim = Simulation::new();
+        let attacker = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            health: Some(100),
+            movement: Some(Fixed::from_num(1)),
+            combat_stats: Some(
+                CombatStats::new(10, Fixed::from_num(10), 10)
+                    .with_projectile_speed(Fixed::from_num(10)),
+            ),
+            ..Default::default()
+        });
+
+        let target = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(5), Fixed::from_num(0))),
+            health: Some(5),
+            combat_stats: Some(CombatStats::default()),
+            ..Default::default()
+        });
+
+        sim.set_attack_target(attacker, target).unwrap();
+
+        sim.tick();
+        sim.tick();
+
+        if let Some(entity) = sim.get_entity(target) {
+            let target_health = entity.health.unwrap().current;
+            assert!(target_health < 5);
+        }
+
+        let has_projectiles = sim
+            .entities()
+            .iter()
+            .any(|(_, entity)| entity.projectile.is_some());
+        assert!(!has_projectiles);
+    }
+
+    #[test]
+    fn test_serialization_roundtrip() {
+        let mut sim = Simulation::new();
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(42), Fixed::from_num(24))),
+            health: Some(75),
+            ..Default::default()
+        });
+        sim.tick();
+
+        let bytes = sim.serialize().unwrap();
+        let restored = Simulation::deserialize(&bytes).unwrap();
+
+        assert_eq!(sim.get_tick(), restored.get_tick());
+        assert_eq!(sim.state_hash(), restored.state_hash());
+    }
+
+    #[test]
+    fn test_health_system_removes_dead() {
+        let mut sim = Simulation::new();
+        let id = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            health: Some(10),
+            ..Default::default()
+        });
+
+        // Manually set health to 0
+        if let Some(entity) = sim.entities.get_mut(id) {
+            if let Some(ref mut health) = entity.health {
+                health.current = 0;
+            }
+        }
+
+        let events = sim.tick();
+        assert!(events.deaths.contains(&id));
+        assert!(sim.get_entity(id).is_none());
+    }
+
+    fn spawn_shooter(sim: &mut Simulation, ammo: u32) -> (EntityId, EntityId) {
+        let shooter = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            health: Some(100),
+            movement: Some(Fixed::from_num(2)),
+            combat_stats: Some(CombatStats::new(5, Fixed::from_num(50), 1)),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            ammunition: Some(ammo),
+            ..Default::default()
+        });
+        let target = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(10), Fixed::ZERO)),
+            health: Some(1000),
+            faction: Some(FactionMember::new(FactionId::Collegium, 0)),
+            ..Default::default()
+        });
+        sim.apply_command(shooter, Command::Attack(target)).unwrap();
+        (shooter, target)
+    }
+
+    #[test]
+    fn test_empty_ammunition_stops_fire() {
+        let mut sim = Simulation::new();
+        let (shooter, _) = spawn_shooter(&mut sim, 2);
+
+        let shots: usize = (0..10).map(|_| sim.tick().damage_events.len()).sum();
+
+        assert_eq!(shots, 2);
+        let ammo = sim.get_entity(shooter).unwrap().ammunition.unwrap();
+        assert!(ammo.is_empty());
+    }
+
+    #[test]
+    fn test_attacks_and_reports_become_notifications() {
+        let mut sim = Simulation::new();
+        let (shooter, target) = spawn_shooter(&mut sim, 100);
+
+        let alerts: Vec<Notification> = (0..10).flat_map(|_| sim.tick().notifications).collect();
+        // One alert for the fight, for the victim's owner
+        assert_eq!(
+            alerts,
+            vec![Notification::UnderAttack {
+                entity: target,
+                faction: FactionId::Collegium,
+                position: Vec2Fixed::new(Fixed::from_num(10), Fixed::ZERO),
+            }]
+        );
+
+        // Game-layer reports arrive with the next tick
+        sim.notify_harvester_idle(shooter);
+        sim.notify_production_blocked(target, BlockReason::Supply);
+        let notifications = sim.tick().notifications;
+        assert!(notifications.contains(&Notification::HarvesterIdle {
+            entity: shooter,
+            faction: FactionId::Continuity,
+        }));
+        assert!(notifications.contains(&Notification::ProductionBlocked {
+            building: target,
+            faction: FactionId::Collegium,
+            reason: BlockReason::Supply,
+        }));
+        assert!(sim.tick().notifications.is_empty());
+    }
+
+    fn spawn_dummy(sim: &mut Simulation, x: i32, faction: FactionId) -> EntityId {
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::ZERO)),
+            health: Some(1000),
+            faction: Some(FactionMember::new(faction, 0)),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_attack_ground_damages_everything_in_blast() {
+        let mut sim = Simulation::new();
+        let artillery = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            health: Some(100),
+            movement: Some(Fixed::from_num(2)),
+            combat_stats: Some(
+                CombatStats::new(20, Fixed::from_num(100), 10)
+                    .with_splash_radius(Fixed::from_num(10)),
+            ),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            ..Default::default()
+        });
+        let enemy = spawn_dummy(&mut sim, 50, FactionId::Collegium);
+        let friend = spawn_dummy(&mut sim, 55, FactionId::Continuity);
+        let outside = spawn_dummy(&mut sim, 90, FactionId::Collegium);
+
+        sim.apply_command(
+            artillery,
+            Command::AttackGround(Vec2Fixed::new(Fixed::from_num(50), Fixed::ZERO)),
+        )
+        .unwrap();
+        let events = sim.tick();
+
+        let hit: Vec<EntityId> = events.damage_events.iter().map(|e| e.target).collect();
+        assert_eq!(hit, vec![enemy, friend]);
+        assert!(!hit.contains(&outside));
+        let position = sim.get_entity(artillery).unwrap().position.unwrap();
+        assert_eq!(
+            position.value,
+            Vec2Fixed::ZERO,
+            "in range, so holds position"
+        );
+    }
+
+    #[test]
+    fn test_splash_hits_around_the_target_and_spares_allies() {
+        use crate::combat::SplashFalloff;
+
+        let fire = |friendly_fire: bool, projectile_speed: i32| {
+            let mut sim = Simulation::new();
+            let artillery = sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::ZERO),
+                health: Some(100),
+                movement: Some(Fixed::from_num(2)),
+                combat_stats: Some(
+                    CombatStats::new(40, Fixed::from_num(100), 100)
+                        .with_projectile_speed(Fixed::from_num(projectile_speed))
+                        .with_splash_radius(Fixed::from_num(10))
+                        .with_splash_falloff(SplashFalloff::Linear)
+                        .with_friendly_fire(friendly_fire),
+                ),
+                faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+                ..Default::default()
+            });
+            let target = spawn_dummy(&mut sim, 50, FactionId::Collegium);
+            let near = spawn_dummy(&mut sim, 55, FactionId::Collegium);
+            let friend = spawn_dummy(&mut sim, 52, FactionId::Continuity);
+            spawn_dummy(&mut sim, 70, FactionId::Collegium);
+
+            sim.apply_command(artillery, Command::Attack(target))
+                .unwrap();
+            let mut splashes = Vec::new();
+            let mut damage = HashMap::new();
+            for _ in 0..30 {
+                let events = sim.tick();
+                splashes.extend(events.splashes);
+                for event in events.damage_events {
+                    *damage.entry(event.target).or_insert(0) += event.damage;
+                }
+            }
+            (splashes, damage, [target, near, friend])
+        };
+
+        let (splashes, damage, [target, near, _]) = fire(false, 0);
+        assert_eq!(splashes.len(), 1);
+        assert_eq!(splashes[0].affected, vec![target, near]);
+        assert!(damage[&near] < damage[&target], "damage falls off");
+
+        let (splashes, _, [target, near, friend]) = fire(true, 0);
+        assert_eq!(splashes[0].affected, vec![target, near, friend]);
+
+        let (splashes, damage, [target, near, _]) = fire(false, 10);
+        assert_eq!(splashes.len(), 1, "shells burst on impact");
+        assert_eq!(splashes[0].affected, vec![target, near]);
+        assert_eq!(damage.len(), 2);
+    }
+
+    #[test]
+    fn test_damage_variance_follows_seed() {
+        fn damage_rolls(seed: u64, variance: u8) -> (Vec<u32>, u64) {
+            let mut sim = Simulation::new();
+            sim.seed_rng(seed);
+            let shooter = sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::ZERO),
+                health: Some(100),
+                movement: Some(Fixed::ONE),
+                combat_stats: Some(
+                    CombatStats::new(50, Fixed::from_num(100), 1).with_damage_variance(variance),
+                ),
+                faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+                ..Default::default()
+            });
+            let target = spawn_dummy(&mut sim, 10, FactionId::Collegium);
+            sim.apply_command(shooter, Command::Attack(target)).unwrap();
+            let rolls = (0..20)
+                .flat_map(|_| sim.tick().damage_events)
+                .map(|e| e.damage)
+                .collect();
+            (rolls, sim.state_hash())
+        }
+
+        let (fixed, _) = damage_rolls(9, 0);
+        let base = fixed[0];
+        assert!(fixed.iter().all(|&d| d == base));
+
+        let (rolls, hash) = damage_rolls(9, 20);
+        assert!(rolls.iter().any(|&d| d < base) && rolls.iter().any(|&d| d > base));
+        assert!(rolls
+            .iter()
+            .all(|&d| d * 10 >= base * 7 && d * 10 <= base * 13));
+        assert_eq!(damage_rolls(9, 20), (rolls.clone(), hash));
+        assert_ne!(damage_rolls(10, 20).0, rolls);
+    }
+
+    #[test]
+    fn test_high_ground_changes_shots_between_levels() {
+        fn shots(shooter_level: u8, target_level: u8, rules: HighGroundRules) -> Vec<u32> {
+            let mut sim = Simulation::new();
+            sim.seed_rng(4);
+            sim.set_high_ground_rules(rules);
+            sim.nav_grid_mut().set_elevation(0, 0, shooter_level);
+            sim.nav_grid_mut().set_elevation(1, 0, target_level);
+            let shooter = sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::ZERO),
+                health: Some(100),
+                movement: Some(Fixed::ONE),
+                combat_stats: Some(CombatStats::new(40, Fixed::from_num(100), 1)),
+                faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+                ..Default::default()
+            });
+            let target = spawn_dummy(&mut sim, 40, FactionId::Collegium);
+            sim.apply_command(shooter, Command::Attack(target)).unwrap();
+            (0..20)
+                .flat_map(|_| sim.tick().damage_events)
+                .map(|e| e.damage)
+                .collect()
+        }
+
+        let rules = HighGroundRules::default();
+        let flat = shots(0, 0, rules);
+        assert!(!flat.is_empty());
+
+        let down = shots(1, 0, rules);
+        assert_eq!(down.len(), flat.len());
+        assert!(down.iter().all(|&d| d > flat[0]));
+
+        let up = shots(0, 1, rules);
+        assert!(!up.is_empty() && up.len() < flat.len());
+        assert!(up.iter().all(|&d| d == flat[0]));
+
+        assert_eq!(shots(0, 1, HighGroundRules::NONE), flat);
+    }
+
+    #[test]
+    fn test_destroyed_obstacles_open_their_ground() {
+        use crate::buildings::PlacementCell;
+        use crate::pathfinding::CellType;
+
+        let mut sim = Simulation::new();
+        let cell = |x: i32, y: i32| {
+            Vec2Fixed::new(Fixed::from_num(x * 32 + 16), Fixed::from_num(y * 32 + 16))
+        };
+        let rock = sim
+            .spawn_obstacle(
+                ObstacleKind::Rock,
+                cell(2, 0),
+                BuildingFootprint::square(1),
+                30,
+            )
+            .unwrap();
+        let debris = sim
+            .spawn_obstacle(
+                ObstacleKind::Debris,
+                cell(2, 2),
+                BuildingFootprint::square(1),
+                30,
+            )
+            .unwrap();
+        assert!(sim
+            .spawn_obstacle(
+                ObstacleKind::Rock,
+                cell(2, 2),
+                BuildingFootprint::square(1),
+                30
+            )
+            .is_err());
+        assert_eq!(sim.nav_grid().get_cell(2, 0), Some(CellType::Blocked));
+        assert_eq!(
+            sim.placement_grid().get_cell(2, 0),
+            Some(PlacementCell::Occupied(rock))
+        );
+
+        let raised = sim.tick().obstacles;
+        assert!(
+            matches!(raised[..], [ObstacleEvent::Raised { obstacle, .. }, _] if obstacle == rock)
+        );
+
+        let sapper = sim.spawn_entity(EntitySpawnParams {
+            position: Some(cell(0, 1)),
+            health: Some(100),
+            movement: Some(Fixed::ONE),
+            combat_stats: Some(CombatStats::new(10, Fixed::from_num(100), 1)),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            ..Default::default()
+        });
+        // Left alone, nobody shoots at the terrain
+        assert!((0..5).all(|_| sim.tick().damage_events.is_empty()));
+
+        sim.apply_command(sapper, Command::Attack(rock)).unwrap();
+        sim.queue_command(sapper, Command::Attack(debris)).unwrap();
+        let events: Vec<_> = (0..20).flat_map(|_| sim.tick().obstacles).collect();
+
+        assert!(matches!(
+            events[..],
+            [
+                ObstacleEvent::Destroyed { obstacle: a, .. },
+                ObstacleEvent::Destroyed { obstacle: b, .. }
+            ] if a == rock && b == debris
+        ));
+        assert_eq!(sim.nav_grid().get_cell(2, 0), Some(CellType::Walkable));
+        assert_eq!(sim.nav_grid().get_cell(2, 2), Some(CellType::SlowTerrain));
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     #[test]
-    fn test_simulation_new() {
-        let sim = Simulation::new();
-        assert_eq!(sim.get_tick(), 0);
-        assert!(sim.entities.is_empty());
+    fn test_attack_ground_requires_splash_weapon() {
+        let mut sim = Simulation::new();
+        let (shooter, _) = spawn_shooter(&mut sim, 10);
+
+        let result = sim.apply_command(shooter, Command::AttackGround(Vec2Fixed::ZERO));
+
+        assert!(matches!(result, Err(GameError::InvalidState(_))));
     }
 
     #[test]
-    fn test_spawn_entity() {
+    fn test_attacking_friendly_requires_force_attack() {
         let mut sim = Simulation::new();
-        let id = sim.spawn_entity(EntitySpawnParams {
-            position: Some(Vec2Fixed::new(Fixed::from_num(10), Fixed::from_num(20))),
+        let (shooter, _) = spawn_shooter(&mut sim, 10);
+        let friend = spawn_dummy(&mut sim, 10, FactionId::Continuity);
+
+        let refused = sim.apply_command(shooter, Command::Attack(friend));
+        assert!(matches!(refused, Err(GameError::InvalidState(_))));
+        assert!(matches!(
+            sim.queue_command(shooter, Command::Attack(friend)),
+            Err(GameError::InvalidState(_))
+        ));
+
+        sim.apply_command(shooter, Command::ForceAttack(friend))
+            .unwrap();
+        let events = sim.tick();
+        assert!(events.damage_events.iter().any(|e| e.target == friend));
+    }
+
+    fn spawn_trooper(sim: &mut Simulation, x: i32) -> EntityId {
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::ZERO)),
             health: Some(100),
+            movement: Some(Fixed::from_num(1)),
+            combat_stats: Some(CombatStats::new(5, Fixed::from_num(20), 10)),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
             ..Default::default()
-        });
-
-        assert_eq!(id, 1);
-        let entity = sim.get_entity(id).unwrap();
-        assert_eq!(entity.position.unwrap().value.x, Fixed::from_num(10));
-        assert_eq!(entity.health.unwrap().current, 100);
+        })
     }
 
     #[test]
-    fn test_despawn_entity() {
+    fn test_squad_moves_into_formation_and_reports_health() {
         let mut sim = Simulation::new();
-        let id = sim.spawn_entity(EntitySpawnParams::default());
+        let members: Vec<EntityId> = (0..3).map(|i| spawn_trooper(&mut sim, i * 10)).collect();
+        let squad = sim.create_squad("alpha", &members).unwrap();
+        let destination = Vec2Fixed::new(Fixed::from_num(100), Fixed::from_num(100));
 
-        assert!(sim.despawn_entity(id).is_ok());
-        assert!(sim.get_entity(id).is_none());
-        assert!(sim.despawn_entity(id).is_err());
+        sim.command_squad(squad, SquadCommand::MoveTo(destination))
+            .unwrap();
+        for _ in 0..200 {
+            sim.tick();
+        }
+
+        let offsets = Formation::Line.offsets(3);
+        for (member, offset) in members.iter().zip(offsets) {
+            let position = sim.get_entity(*member).unwrap().position.unwrap();
+            assert!(position.value.distance_squared(destination + offset) <= Fixed::from_num(1));
+        }
+        let health = sim.squad_health(squad).unwrap();
+        assert_eq!((health.current, health.max, health.alive), (300, 300, 3));
+        assert_eq!(health.percent(), 100);
     }
 
     #[test]
-    fn test_tick_increments() {
+    fn test_squad_cohesion_holds_back_runaway_members() {
         let mut sim = Simulation::new();
-        assert_eq!(sim.get_tick(), 0);
+        let straggler = spawn_trooper(&mut sim, 0);
+        let leader = spawn_trooper(&mut sim, 200);
+        let squad = sim.create_squad("alpha", &[straggler, leader]).unwrap();
 
+        let far = Vec2Fixed::new(Fixed::from_num(1000), Fixed::ZERO);
+        sim.command_squad(squad, SquadCommand::AttackMove(far))
+            .unwrap();
         sim.tick();
-        assert_eq!(sim.get_tick(), 1);
 
-        sim.tick();
-        assert_eq!(sim.get_tick(), 2);
+        let leader_x = sim.get_entity(leader).unwrap().position.unwrap().value.x;
+        let straggler_x = sim.get_entity(straggler).unwrap().position.unwrap().value.x;
+        assert_eq!(leader_x, Fixed::from_num(200), "leader waits");
+        assert!(straggler_x > Fixed::ZERO, "straggler catches up");
     }
 
     #[test]
-    fn test_movement_integration() {
+    fn test_squad_drops_dead_members_and_resumes_strays() {
         let mut sim = Simulation::new();
-        let id = sim.spawn_entity(EntitySpawnParams {
-            position: Some(Vec2Fixed::new(Fixed::from_num(0), Fixed::from_num(0))),
-            movement: Some(Fixed::from_num(5)),
-            ..Default::default()
-        });
+        let a = spawn_trooper(&mut sim, 0);
+        let b = spawn_trooper(&mut sim, 10);
+        let squad = sim.create_squad("alpha", &[a, b]).unwrap();
+        sim.command_squad(squad, SquadCommand::MoveTo(Vec2Fixed::ZERO))
+            .unwrap();
 
-        // Issue move command
+        sim.despawn_entity(b).unwrap();
+        sim.apply_command(a, Command::Stop).unwrap();
+        sim.tick();
+
+        assert_eq!(sim.squad(squad).unwrap().members(), &[a]);
         sim.apply_command(
-            id,
-            Command::MoveTo(Vec2Fixed::new(Fixed::from_num(100), Fixed::from_num(0))),
+            a,
+            Command::MoveTo(Vec2Fixed::new(Fixed::from_num(300), Fixed::ZERO)),
         )
         .unwrap();
+        for _ in 0..700 {
+            sim.tick();
+        }
+        let position = sim.get_entity(a).unwrap().position.unwrap();
+        assert!(
+            position.value.distance_squared(Vec2Fixed::ZERO) <= Fixed::from_num(1),
+            "stray member returns to its slot"
+        );
 
-        // Run a tick
+        sim.despawn_entity(a).unwrap();
         sim.tick();
-
-        // Entity should have moved
-        let entity = sim.get_entity(id).unwrap();
-        let pos = entity.position.unwrap();
-        assert!(pos.value.x > Fixed::from_num(0));
+        assert!(sim.squad(squad).is_none(), "empty squads are disbanded");
     }
 
     #[test]
-    fn test_patrol_toggles_heading() {
+    fn test_squad_membership_rules() {
         let mut sim = Simulation::new();
-        let id = sim.spawn_entity(EntitySpawnParams {
+        let a = spawn_trooper(&mut sim, 0);
+        let b = spawn_trooper(&mut sim, 10);
+        let enemy = sim.spawn_entity(EntitySpawnParams {
             position: Some(Vec2Fixed::ZERO),
-            movement: Some(Fixed::from_num(2)),
+            health: Some(100),
+            movement: Some(Fixed::from_num(4)),
+            faction: Some(FactionMember::new(FactionId::Collegium, 0)),
             ..Default::default()
         });
 
-        let target = Vec2Fixed::new(Fixed::from_num(10), Fixed::from_num(0));
-        sim.apply_command(id, Command::Patrol(target)).unwrap();
+        assert!(sim.create_squad("mixed", &[a, enemy]).is_err());
+        let first = sim.create_squad("first", &[a, b]).unwrap();
+        let second = sim.create_squad("second", &[b]).unwrap();
+        assert_eq!(sim.squad_of(b), Some(second));
+        assert_eq!(sim.squad(first).unwrap().members(), &[a]);
 
-        sim.tick();
-        let state = sim.get_entity(id).unwrap().patrol_state.unwrap();
-        assert!(state.heading_to_target);
+        assert!(sim.command_squad(first, SquadCommand::Attack(b)).is_err());
+        sim.command_squad(first, SquadCommand::Attack(enemy))
+            .unwrap();
+        assert_eq!(
+            sim.get_entity(a)
+                .and_then(|e| e.row.command_queue.as_ref())
+                .and_then(|q| q.current()),
+            Some(&Command::Attack(enemy))
+        );
+    }
 
-        if let Some(entity) = sim.entities.get_mut(id) {
-            entity.position = Some(Position::new(target));
+    #[test]
+    fn test_regeneration_waits_out_of_combat() {
+        let mut sim = Simulation::new();
+        let spawn_mender = |sim: &mut Simulation, faction| {
+            let id = sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::ZERO),
+                health: Some(100),
+                faction: Some(FactionMember::new(faction, 0)),
+                regeneration: Some(Regeneration::new(4, 10, 30)),
+                ..Default::default()
+            });
+            sim.entities
+                .get_mut(id)
+                .unwrap()
+                .health
+                .as_mut()
+                .unwrap()
+                .current = 50;
+            id
+        };
+        let human = spawn_mender(&mut sim, FactionId::Continuity);
+        let bio = spawn_mender(&mut sim, FactionId::BioSovereigns);
+        let current = |sim: &Simulation, id| sim.get_entity(id).unwrap().health.unwrap().current;
+
+        for _ in 0..30 {
+            assert!(sim.tick().regenerated.is_empty());
+        }
+        // Damage mid-recovery restarts the delay
+        sim.entities
+            .get_mut(human)
+            .unwrap()
+            .health
+            .as_mut()
+            .unwrap()
+            .current = 40;
+        for _ in 0..10 {
+            sim.tick();
         }
+        assert_eq!(current(&sim, human), 40);
+        assert_eq!(current(&sim, bio), 56);
 
-        sim.tick();
-        let state = sim.get_entity(id).unwrap().patrol_state.unwrap();
-        assert!(!state.heading_to_target);
+        for _ in 0..31 {
+            sim.tick();
+        }
+        assert_eq!(current(&sim, human), 44);
+        let regen = sim.get_entity(bio).unwrap().regeneration.unwrap();
+        assert_eq!(
+            u64::from(current(&sim, bio)),
+            50 + regen.healed_over(71, Some(FactionId::BioSovereigns))
+        );
     }
 
     #[test]
-    fn test_attack_command_chases_target() {
+    fn test_capacity_pressure_culls_expired_then_oldest_cosmetics() {
         let mut sim = Simulation::new();
+        sim.set_entity_capacity(Some(EntityCapacity::new(10).with_pressure_pct(50)));
+        let unit = spawn_dummy(&mut sim, 0, FactionId::Continuity);
+        let cosmetic = |sim: &mut Simulation, marker: Cosmetic| {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::ZERO),
+                cosmetic: Some(marker),
+                ..Default::default()
+            })
+        };
+        let old_wreck = cosmetic(&mut sim, Cosmetic::new(CosmeticKind::WreckRemnant));
+        let new_wreck = cosmetic(&mut sim, Cosmetic::new(CosmeticKind::WreckRemnant));
+        let debris = cosmetic(&mut sim, Cosmetic::new(CosmeticKind::Debris).with_expiry(0));
+
+        // 4 entities, threshold 5: no pressure yet
+        assert!(sim.tick().capacity_pressure.is_none());
+
+        cosmetic(&mut sim, Cosmetic::new(CosmeticKind::WreckRemnant));
+        let pressure = sim.tick().capacity_pressure.unwrap();
+        assert_eq!(pressure.entities, 5);
+        assert_eq!(pressure.culled, vec![debris]);
+        assert_eq!(pressure.headroom(), 6);
+
+        cosmetic(&mut sim, Cosmetic::new(CosmeticKind::Debris));
+        let pressure = sim.tick().capacity_pressure.unwrap();
+        assert_eq!(pressure.culled, vec![old_wreck]);
+        assert!(sim.get_entity(unit).is_some());
+        assert!(sim.get_entity(new_wreck).is_some());
+    }
+
+    #[test]
+    fn test_capacity_pressure_resolves_shots_beyond_projectile_pool() {
+        let mut sim = Simulation::new();
+        sim.set_entity_capacity(Some(
+            EntityCapacity::new(4)
+                .with_pressure_pct(50)
+                .with_projectile_pool(0),
+        ));
         let attacker = sim.spawn_entity(EntitySpawnParams {
             position: Some(Vec2Fixed::ZERO),
-            movement: Some(Fixed::from_num(3)),
+            health: Some(100),
+            combat_stats: Some(
+                CombatStats::new(10, Fixed::from_num(10), 10)
+                    .with_projectile_speed(Fixed::from_num(1)),
+            ),
             ..Default::default()
         });
+        let target = spawn_dummy(&mut sim, 5, FactionId::Tinkers);
+        sim.set_attack_target(attacker, target).unwrap();
 
-        let target = sim.spawn_entity(EntitySpawnParams {
-            position: Some(Vec2Fixed::new(Fixed::from_num(20), Fixed::from_num(0))),
+        let events = sim.tick();
+        assert!(events.capacity_pressure.is_some());
+        assert_eq!(events.damage_events.len(), 1);
+        assert_eq!(events.damage_events[0].target, target);
+        assert!(!sim
+            .entities()
+            .iter()
+            .any(|(_, entity)| entity.projectile.is_some()));
+    }
+
+    #[test]
+    fn test_idle_wander_stays_leashed_and_yields_to_commands() {
+        let mut sim = Simulation::new();
+        let home = Vec2Fixed::new(Fixed::from_num(50), Fixed::from_num(50));
+        let unit = sim.spawn_entity(EntitySpawnParams {
+            position: Some(home),
+            movement: Some(Fixed::from_num(2)),
+            idle_behavior: Some(IdleBehavior::wander(Fixed::from_num(5), 10)),
             ..Default::default()
         });
+        let rng_before = sim.rng.state();
+
+        let mut moved = false;
+        for _ in 0..100 {
+            sim.tick();
+            let pos = sim.get_entity(unit).unwrap().position.unwrap().value;
+            moved |= pos != home;
+            assert!((pos.x - home.x).abs() <= Fixed::from_num(6));
+            assert!((pos.y - home.y).abs() <= Fixed::from_num(6));
+            assert_eq!(sim.acquisition_position(unit), Some(home));
+        }
+        assert!(moved);
+        assert_eq!(sim.rng.state(), rng_before);
 
-        sim.apply_command(attacker, Command::Attack(target))
-            .unwrap();
+        let target = Vec2Fixed::new(Fixed::from_num(80), Fixed::from_num(50));
+        sim.apply_command(unit, Command::MoveTo(target)).unwrap();
         sim.tick();
-
-        let entity = sim.get_entity(attacker).unwrap();
-        let pos = entity.position.unwrap();
-        assert!(pos.value.x > Fixed::from_num(0));
+        let idle = sim.get_entity(unit).unwrap().idle_behavior.unwrap();
+        assert!(!idle.is_active());
+        let velocity = sim.get_entity(unit).unwrap().velocity.unwrap().value;
+        // Full speed, not the slower idle drift
+        assert!(velocity.x > Fixed::from_num(1));
     }
 
     #[test]
-    fn test_deterministic_hash() {
-        let mut sim1 = Simulation::new();
-        let mut sim2 = Simulation::new();
+    fn test_resupply_refills_friendly_ammunition() {
+        let mut sim = Simulation::new();
+        let (shooter, _) = spawn_shooter(&mut sim, 2);
+        for _ in 0..5 {
+            sim.tick();
+        }
 
-        // Same operations
-        let id1 = sim1.spawn_entity(EntitySpawnParams {
-            position: Some(Vec2Fixed::new(Fixed::from_num(10), Fixed::from_num(20))),
-            health: Some(100),
-            movement: Some(Fixed::from_num(2)),
-            ..Default::default()
-        });
-        let id2 = sim2.spawn_entity(EntitySpawnParams {
-            position: Some(Vec2Fixed::new(Fixed::from_num(10), Fixed::from_num(20))),
-            health: Some(100),
-            movement: Some(Fixed::from_num(2)),
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::ZERO, Fixed::from_num(20))),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            resupply: Some(Resupply::new(Fixed::from_num(40), 1)),
             ..Default::default()
         });
+        sim.tick();
 
-        sim1.apply_command(
-            id1,
-            Command::MoveTo(Vec2Fixed::new(Fixed::from_num(50), Fixed::from_num(50))),
-        )
-        .unwrap();
-        sim2.apply_command(
-            id2,
-            Command::MoveTo(Vec2Fixed::new(Fixed::from_num(50), Fixed::from_num(50))),
-        )
-        .unwrap();
-
-        sim1.tick();
-        sim2.tick();
-
-        // Hashes must be identical
-        assert_eq!(sim1.state_hash(), sim2.state_hash());
+        let ammo = sim.get_entity(shooter).unwrap().ammunition.unwrap();
+        assert!(ammo.current > 0);
     }
 
     #[test]
-    fn test_projectile_hits_target() {
+    fn test_dead_units_leave_wrecks_for_salvagers() {
         let mut sim = Simulation::new();
-        let attacker = sim.spawn_entity(EntitySpawnParams {
+        let victim = sim.spawn_entity(EntitySpawnParams {
             position: Some(Vec2Fixed::ZERO),
-            health: Some(100),
-            movement: Some(Fixed::from_num(1)),
-            combat_stats: Some(
-                CombatStats::new(10, Fixed::from_num(10), 10)
-                    .with_projectile_speed(Fixed::from_num(10)),
-            ),
+            health: Some(10),
+            faction: Some(FactionMember::new(FactionId::Collegium, 1)),
+            salvage_value: Some(5),
             ..Default::default()
         });
-
-        let target = sim.spawn_entity(EntitySpawnParams {
-            position: Some(Vec2Fixed::new(Fixed::from_num(5), Fixed::from_num(0))),
-            health: Some(5),
-            combat_stats: Some(CombatStats::default()),
+        let salvager = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(50), Fixed::ZERO)),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            salvage_rate: Some(2),
             ..Default::default()
         });
+        *sim.entities.get_mut(victim).unwrap().health = Some(Health {
+            current: 0,
+            max: 10,
+        });
 
-        sim.set_attack_target(attacker, target).unwrap();
+        let events = sim.tick();
+        let wreck = match events.salvage[0] {
+            SalvageEvent::WreckSpawned {
+                wreck,
+                entity,
+                value,
+            } => {
+                assert_eq!((entity, value), (victim, 5));
+                wreck
+            }
+            other => panic!("expected a wreck, got {other:?}"),
+        };
+        assert!(events.salvage.contains(&SalvageEvent::Collected {
+            wreck,
+            collector: salvager,
+            faction: Some(FactionId::Continuity),
+            amount: 2,
+        }));
+        assert_eq!(sim.wreck(wreck).unwrap().remaining, 3);
+
+        sim.tick();
+        let events = sim.tick();
+        assert!(events.salvage.contains(&SalvageEvent::WreckCleared {
+            wreck,
+            remaining: 0,
+        }));
+        assert_eq!(sim.wrecks().count(), 0);
+    }
+
+    #[test]
+    fn test_wrecks_decay_out_of_reach() {
+        let mut sim = Simulation::new();
+        sim.set_salvage_rules(SalvageRules {
+            lifetime: 3,
+            ..SalvageRules::default()
+        });
+        let victim = spawn_dummy(&mut sim, 0, FactionId::Collegium);
+        sim.entities.get_mut(victim).unwrap().salvageable = Some(Salvageable::new(40));
+        *sim.entities.get_mut(victim).unwrap().health = Some(Health {
+            current: 0,
+            max: 1000,
+        });
 
+        sim.tick();
+        assert_eq!(sim.wrecks().count(), 1);
         sim.tick();
         sim.tick();
+        let events = sim.tick();
+        assert!(matches!(
+            events.salvage[..],
+            [SalvageEvent::WreckCleared { remaining: 40, .. }]
+        ));
+    }
 
-        if let Some(entity) = sim.get_entity(target) {
-            let target_health = entity.health.unwrap().current;
-            assert!(target_health < 5);
+    fn damage_tech(id: &str, prerequisites: &[&str]) -> TechData {
+        TechData {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            cost: 100,
+            research_time: 3,
+            effects: vec![crate::data::TechEffect {
+                effect_type: TechEffectType::StatModifierPercent {
+                    stat: "damage".to_string(),
+                    percent: 50,
+                },
+                applies_to: vec![],
+                applies_to_tags: vec!["infantry".to_string()],
+            }],
+            prerequisites: prerequisites.iter().map(|s| s.to_string()).collect(),
+            tier: 1,
+            exclusive_with: vec![],
+            researched_at: Some("lab".to_string()),
+            is_doctrine: false,
+            branch: None,
+            icon: None,
         }
+    }
 
-        let has_projectiles = sim
-            .entities()
-            .iter()
-            .any(|(_, entity)| entity.projectile.is_some());
-        assert!(!has_projectiles);
+    fn spawn_infantry(sim: &mut Simulation, faction: FactionId) -> EntityId {
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            faction: Some(FactionMember::new(faction, 0)),
+            combat_stats: Some(CombatStats::new(10, Fixed::from_num(50), 10)),
+            tech_profile: Some(TechProfile::new(
+                "security_team",
+                vec!["infantry".to_string()],
+            )),
+            ..Default::default()
+        })
     }
 
     #[test]
-    fn test_serialization_roundtrip() {
+    fn test_research_completes_and_upgrades_units() {
+        let faction = FactionId::Continuity;
         let mut sim = Simulation::new();
-        sim.spawn_entity(EntitySpawnParams {
-            position: Some(Vec2Fixed::new(Fixed::from_num(42), Fixed::from_num(24))),
-            health: Some(75),
+        sim.set_tech_tree(
+            faction,
+            [damage_tech("drill", &[]), damage_tech("elite", &["drill"])],
+        );
+        let lab = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            faction: Some(FactionMember::new(faction, 0)),
+            research: Some(ResearchFacility::new(Some("lab".to_string()))),
             ..Default::default()
         });
-        sim.tick();
-
-        let bytes = sim.serialize().unwrap();
-        let restored = Simulation::deserialize(&bytes).unwrap();
+        let veteran = spawn_infantry(&mut sim, faction);
+        let rival = spawn_infantry(&mut sim, FactionId::Collegium);
+
+        assert!(matches!(
+            sim.queue_research(lab, "elite"),
+            Err(GameError::Research(ResearchError::MissingPrerequisite))
+        ));
+        sim.queue_research(lab, "drill").unwrap();
+        assert!(matches!(
+            sim.queue_research(lab, "drill"),
+            Err(GameError::Research(ResearchError::AlreadyQueued))
+        ));
+        assert_eq!(sim.research_facility_for(faction, "drill"), None);
 
-        assert_eq!(sim.get_tick(), restored.get_tick());
-        assert_eq!(sim.state_hash(), restored.state_hash());
+        let events = sim.tick();
+        assert!(matches!(
+            &events.research[..],
+            [ResearchEvent::ResearchStarted { tech_id, .. }] if tech_id == "drill"
+        ));
+        sim.tick();
+        let events = sim.tick();
+        assert!(matches!(
+            &events.research[..],
+            [ResearchEvent::ResearchComplete { building, .. }] if *building == lab
+        ));
+        assert!(sim.has_researched(faction, "drill"));
+
+        let damage =
+            |sim: &Simulation, id| sim.get_entity(id).unwrap().combat_stats.unwrap().damage;
+        assert_eq!(damage(&sim, veteran), 15);
+        assert_eq!(damage(&sim, rival), 10);
+        // Units trained afterwards start upgraded
+        let recruit = spawn_infantry(&mut sim, faction);
+        assert_eq!(damage(&sim, recruit), 15);
+        assert_eq!(sim.research_facility_for(faction, "elite"), Some(lab));
     }
 
     #[test]
-    fn test_health_system_removes_dead() {
+    fn test_cancelled_research_reports_refund() {
+        let faction = FactionId::Continuity;
         let mut sim = Simulation::new();
-        let id = sim.spawn_entity(EntitySpawnParams {
+        sim.set_tech_tree(faction, [damage_tech("drill", &[])]);
+        let lab = sim.spawn_entity(EntitySpawnParams {
             position: Some(Vec2Fixed::ZERO),
-            health: Some(10),
+            faction: Some(FactionMember::new(faction, 0)),
+            research: Some(ResearchFacility::new(None)),
             ..Default::default()
         });
+        let hash = sim.state_hash();
 
-        // Manually set health to 0
-        if let Some(entity) = sim.entities.get_mut(id) {
-            if let Some(ref mut health) = entity.health {
-                health.current = 0;
-            }
+        sim.queue_research(lab, "drill").unwrap();
+        assert_ne!(sim.state_hash(), hash);
+        assert_eq!(sim.cancel_research(lab).unwrap().cost, 100);
+        assert!(matches!(
+            sim.cancel_research(lab),
+            Err(GameError::Research(ResearchError::NothingQueued))
+        ));
+
+        let events = sim.tick();
+        assert!(matches!(
+            events.research[..],
+            [ResearchEvent::ResearchCancelled { refund: 100, .. }]
+        ));
+        assert!(!sim.has_researched(faction, "drill"));
+    }
+
+    #[test]
+    fn test_faction_mechanics_rebuild_and_limit_buildings() {
+        use crate::factions::{DeployOnce, Rebuild};
+
+        let faction = FactionId::Collegium;
+        let mut sim = Simulation::new();
+        sim.set_faction_mechanics(
+            faction,
+            vec![
+                MechanicData::DeployOnce(DeployOnce {
+                    building: "assembly_core".to_string(),
+                }),
+                MechanicData::Rebuild(Rebuild {
+                    tags: vec!["economy".to_string()],
+                    delay: 3,
+                    health_percent: 50,
+                }),
+            ],
+        );
+        let spawn = |sim: &mut Simulation, kind: &str, tag: &str| {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::ZERO),
+                health: Some(200),
+                faction: Some(FactionMember::new(faction, 0)),
+                tech_profile: Some(TechProfile::new(kind, vec![tag.to_string()])),
+                ..Default::default()
+            })
+        };
+
+        assert!(sim.check_placement(faction, "assembly_core").is_ok());
+        let core = spawn(&mut sim, "assembly_core", "headquarters");
+        let refinery = spawn(&mut sim, "micro_refinery", "economy");
+        assert!(matches!(
+            sim.check_placement(faction, "assembly_core"),
+            Err(GameError::FactionRule(_))
+        ));
+        assert!(sim.check_placement(faction, "micro_refinery").is_ok());
+        assert!(sim
+            .check_placement(FactionId::Continuity, "assembly_core")
+            .is_ok());
+
+        for id in [core, refinery] {
+            sim.entities
+                .get_mut(id)
+                .unwrap()
+                .health
+                .as_mut()
+                .unwrap()
+                .current = 0;
+        }
+        let events = sim.tick();
+        assert_eq!(events.deaths, vec![core, refinery]);
+        assert_eq!(
+            sim.diff(&Simulation::new())
+                .iter()
+                .filter(|d| d.field == "rebuilds")
+                .count(),
+            1
+        );
+
+        let rebuilt: Vec<_> = (0..3).flat_map(|_| sim.tick().rebuilt).collect();
+        let [(old, new)] = rebuilt[..] else {
+            panic!("expected one rebuild, got {rebuilt:?}");
+        };
+        assert_eq!(old, refinery);
+        let entity = sim.get_entity(new).unwrap();
+        assert_eq!(entity.health.unwrap().current, 100);
+        assert_eq!(entity.tech_profile.as_ref().unwrap().kind, "micro_refinery");
+    }
+
+    #[test]
+    fn test_abilities_close_to_range_and_respect_cooldowns() {
+        let ability = |id: &str, target, radius: i32, effect| AbilityData {
+            id: id.to_string(),
+            name: id.to_string(),
+            cooldown: 10,
+            cost: 25,
+            target,
+            range: Fixed::from_num(10),
+            radius: Fixed::from_num(radius),
+            effect,
+        };
+        let mut sim = Simulation::new();
+        let spawn = |sim: &mut Simulation, faction, x: i32, abilities| {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::ZERO)),
+                health: Some(100),
+                movement: Some(Fixed::from_num(5)),
+                faction: Some(FactionMember::new(faction, 0)),
+                abilities,
+                ..Default::default()
+            })
+        };
+        let medic = spawn(
+            &mut sim,
+            FactionId::Continuity,
+            0,
+            vec![
+                ability(
+                    "repair",
+                    AbilityTargetType::Entity,
+                    0,
+                    AbilityEffect::Heal { amount: 30 },
+                ),
+                ability(
+                    "emp",
+                    AbilityTargetType::Caster,
+                    50,
+                    AbilityEffect::Damage { amount: 20 },
+                ),
+            ],
+        );
+        let patient = spawn(&mut sim, FactionId::Continuity, 30, Vec::new());
+        let enemy = spawn(&mut sim, FactionId::Collegium, 40, Vec::new());
+        sim.entities
+            .get_mut(patient)
+            .unwrap()
+            .health
+            .as_mut()
+            .unwrap()
+            .current = 50;
+
+        let repair = |target| Command::UseAbility {
+            ability_id: "repair".to_string(),
+            target,
+        };
+        assert!(sim
+            .apply_command(medic, repair(AbilityTarget::Caster))
+            .is_err());
+        sim.apply_command(medic, repair(AbilityTarget::Entity(patient)))
+            .unwrap();
+        sim.queue_command(medic, repair(AbilityTarget::Entity(patient)))
+            .unwrap();
+
+        // Out of range at first, so the medic walks over before healing
+        let mut uses = Vec::new();
+        for _ in 0..8 {
+            uses.extend(sim.tick().abilities);
+        }
+        assert_eq!(uses.len(), 1);
+        assert_eq!(uses[0].affected, vec![patient]);
+        assert_eq!(uses[0].cost, 25);
+        assert_eq!(sim.get_entity(patient).unwrap().health.unwrap().current, 80);
+
+        // The queued repair waits out the cooldown
+        for _ in 0..10 {
+            uses.extend(sim.tick().abilities);
         }
+        assert_eq!(uses.len(), 2);
+        assert_eq!(
+            sim.get_entity(patient).unwrap().health.unwrap().current,
+            100
+        );
 
+        sim.apply_command(
+            medic,
+            Command::UseAbility {
+                ability_id: "emp".to_string(),
+                target: AbilityTarget::Caster,
+            },
+        )
+        .unwrap();
         let events = sim.tick();
-        assert!(events.deaths.contains(&id));
-        assert!(sim.get_entity(id).is_none());
+        assert_eq!(events.abilities[0].affected, vec![enemy]);
+        assert_eq!(sim.get_entity(enemy).unwrap().health.unwrap().current, 80);
+        assert_eq!(sim.get_entity(medic).unwrap().health.unwrap().current, 100);
+    }
+
+    #[test]
+    fn test_debug_names_count_per_faction_and_kind() {
+        let mut sim = Simulation::new();
+        let spawn = |sim: &mut Simulation, faction| {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::ZERO),
+                faction: Some(FactionMember::new(faction, 0)),
+                debug_kind: Some("security_team".to_string()),
+                ..Default::default()
+            })
+        };
+        let first = spawn(&mut sim, FactionId::Collegium);
+        spawn(&mut sim, FactionId::Continuity);
+        let second = spawn(&mut sim, FactionId::Collegium);
+        let unnamed = sim.spawn_entity(EntitySpawnParams::default());
+
+        assert_eq!(sim.entity_label(first), "collegium/security_team#1");
+        assert_eq!(sim.entity_label(second), "collegium/security_team#2");
+        assert_eq!(sim.entity_label(unnamed), format!("#{unnamed}"));
+    }
+
+    #[test]
+    fn test_debug_names_excluded_from_state_hash() {
+        let mut named = Simulation::new();
+        let mut plain = Simulation::new();
+        named.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            health: Some(50),
+            debug_kind: Some("scout".to_string()),
+            ..Default::default()
+        });
+        plain.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            health: Some(50),
+            ..Default::default()
+        });
+
+        assert_eq!(named.state_hash(), plain.state_hash());
     }
 }
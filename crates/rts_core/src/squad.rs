@@ -0,0 +1,252 @@
+//! Persistent unit squads.
+//!
+//! A squad binds units of one faction under a name so they can be ordered
+//! as a group. Squad orders fan out into ordinary per-unit [`Command`]s,
+//! placed by the squad's [`Formation`], so replays and lockstep still only
+//! see unit commands.
+//!
+//! Squads are kept by the [`Simulation`](crate::simulation::Simulation),
+//! which each tick:
+//! - drops dead members (and squads left empty),
+//! - sends idle members that drifted away back to the squad's order,
+//! - holds back members that run ahead of the group while it moves.
+//!
+//! [`Command`]: crate::components::Command
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::EntityId;
+use crate::factions::FactionId;
+use crate::math::{fixed_serde, Fixed, Vec2Fixed};
+
+/// Distance from the squad centre beyond which moving members wait for
+/// the rest (world units).
+pub const DEFAULT_COHESION_RADIUS: i32 = 96;
+
+/// Gap between neighbouring formation slots (world units).
+pub const FORMATION_SPACING: i32 = 24;
+
+/// Unique identifier for a squad.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub struct SquadId(pub u32);
+
+impl fmt::Display for SquadId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "squad#{}", self.0)
+    }
+}
+
+/// Arrangement of squad members around an order's target point.
+///
+/// Formations are axis-aligned: a line spreads along x, a column along y,
+/// and a wedge trails back along -y from its point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Formation {
+    /// Side by side.
+    #[default]
+    Line,
+    /// One behind another.
+    Column,
+    /// Arrowhead, first member at the tip.
+    Wedge,
+}
+
+impl Formation {
+    /// Offset of each of `count` members from the formation centre.
+    #[must_use]
+    pub fn offsets(self, count: usize) -> Vec<Vec2Fixed> {
+        let spacing = Fixed::from_num(FORMATION_SPACING);
+        let half_span = Fixed::from_num(count.saturating_sub(1) as i64) / Fixed::from_num(2);
+        (0..count)
+            .map(|index| {
+                let slot = Fixed::from_num(index as i64);
+                match self {
+                    Self::Line => Vec2Fixed::new((slot - half_span) * spacing, Fixed::ZERO),
+                    Self::Column => Vec2Fixed::new(Fixed::ZERO, (half_span - slot) * spacing),
+                    Self::Wedge => {
+                        let rank = Fixed::from_num(index.div_ceil(2) as i64);
+                        let side = if index % 2 == 1 {
+                            -Fixed::ONE
+                        } else {
+                            Fixed::ONE
+                        };
+                        Vec2Fixed::new(side * rank * spacing, -rank * spacing)
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Orders a squad accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SquadCommand {
+    /// Move into formation at a position.
+    MoveTo(Vec2Fixed),
+    /// Move into formation at a position, engaging enemies on the way.
+    AttackMove(Vec2Fixed),
+    /// Focus every member on one enemy.
+    Attack(EntityId),
+    /// Drop current targets and fall back into formation at a position.
+    Retreat(Vec2Fixed),
+    /// Change formation, re-placing members if the squad is moving.
+    SetFormation(Formation),
+}
+
+impl SquadCommand {
+    /// Target point of a positional order.
+    #[must_use]
+    pub const fn destination(&self) -> Option<Vec2Fixed> {
+        match self {
+            Self::MoveTo(point) | Self::AttackMove(point) | Self::Retreat(point) => Some(*point),
+            Self::Attack(_) | Self::SetFormation(_) => None,
+        }
+    }
+}
+
+/// A named group of units from one faction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Squad {
+    /// Display name, for logs and UI.
+    pub name: String,
+    /// Faction every member belongs to.
+    pub faction: FactionId,
+    /// How members are arranged around order targets.
+    pub formation: Formation,
+    /// Distance from the squad centre beyond which moving members wait.
+    #[serde(with = "fixed_serde")]
+    pub cohesion_radius: Fixed,
+    pub(crate) members: Vec<EntityId>,
+    pub(crate) order: Option<SquadCommand>,
+}
+
+impl Squad {
+    /// Create an empty squad.
+    #[must_use]
+    pub fn new(name: impl Into<String>, faction: FactionId) -> Self {
+        Self {
+            name: name.into(),
+            faction,
+            formation: Formation::default(),
+            cohesion_radius: Fixed::from_num(DEFAULT_COHESION_RADIUS),
+            members: Vec::new(),
+            order: None,
+        }
+    }
+
+    /// Members, in entity ID order.
+    #[must_use]
+    pub fn members(&self) -> &[EntityId] {
+        &self.members
+    }
+
+    /// Whether an entity is a member.
+    #[must_use]
+    pub fn contains(&self, entity: EntityId) -> bool {
+        self.members.binary_search(&entity).is_ok()
+    }
+
+    /// The last order given to the squad, if still in force.
+    #[must_use]
+    pub fn order(&self) -> Option<&SquadCommand> {
+        self.order.as_ref()
+    }
+
+    /// Formation slot of a member relative to the formation centre.
+    #[must_use]
+    pub fn slot_offset(&self, entity: EntityId) -> Option<Vec2Fixed> {
+        let index = self.members.binary_search(&entity).ok()?;
+        self.formation
+            .offsets(self.members.len())
+            .get(index)
+            .copied()
+    }
+
+    pub(crate) fn insert(&mut self, entity: EntityId) {
+        if let Err(index) = self.members.binary_search(&entity) {
+            self.members.insert(index, entity);
+        }
+    }
+
+    pub(crate) fn remove(&mut self, entity: EntityId) -> bool {
+        match self.members.binary_search(&entity) {
+            Ok(index) => {
+                self.members.remove(index);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// Aggregate health of a squad's living members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SquadHealth {
+    /// Summed current health.
+    pub current: u64,
+    /// Summed maximum health.
+    pub max: u64,
+    /// Members still alive.
+    pub alive: u32,
+}
+
+impl SquadHealth {
+    /// Remaining health as a whole percentage of maximum (0 when empty).
+    #[must_use]
+    pub fn percent(&self) -> u32 {
+        if self.max == 0 {
+            return 0;
+        }
+        (self.current * 100 / self.max) as u32
+    }
+}
+
+/// Mean of a set of positions, or `None` if empty.
+#[must_use]
+pub(crate) fn centroid(positions: &[Vec2Fixed]) -> Option<Vec2Fixed> {
+    if positions.is_empty() {
+        return None;
+    }
+    let count = Fixed::from_num(positions.len() as i64);
+    let sum = positions
+        .iter()
+        .fold(Vec2Fixed::ZERO, |acc, &position| acc + position);
+    Some(Vec2Fixed::new(sum.x / count, sum.y / count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_formation_is_centred() {
+        let offsets = Formation::Line.offsets(3);
+        let spacing = Fixed::from_num(FORMATION_SPACING);
+        assert_eq!(offsets[0], Vec2Fixed::new(-spacing, Fixed::ZERO));
+        assert_eq!(offsets[1], Vec2Fixed::ZERO);
+        assert_eq!(offsets[2], Vec2Fixed::new(spacing, Fixed::ZERO));
+    }
+
+    #[test]
+    fn test_wedge_alternates_sides() {
+        let offsets = Formation::Wedge.offsets(3);
+        assert_eq!(offsets[0], Vec2Fixed::ZERO);
+        assert!(offsets[1].x < Fixed::ZERO && offsets[2].x > Fixed::ZERO);
+        assert_eq!(offsets[1].y, offsets[2].y);
+    }
+
+    #[test]
+    fn test_members_stay_sorted() {
+        let mut squad = Squad::new("alpha", FactionId::Continuity);
+        squad.insert(7);
+        squad.insert(3);
+        squad.insert(7);
+        assert_eq!(squad.members(), &[3, 7]);
+        assert!(squad.remove(3));
+        assert!(!squad.contains(3));
+    }
+}
@@ -0,0 +1,79 @@
+//! Aggregate match statistics derived from simulation state.
+//!
+//! Shared definitions for tools that summarise a match in progress (batch
+//! observers, spectator overlays), so they all agree on what "supply" and
+//! "army value" mean.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::factions::FactionId;
+use crate::simulation::{EntityRef, Simulation};
+
+/// Whether an entity is a mobile, armed unit.
+#[must_use]
+pub fn is_army_unit(entity: EntityRef<'_>) -> bool {
+    entity.movement.is_some() && entity.combat_stats.is_some()
+}
+
+/// Aggregate state of one faction at a point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct FactionTotals {
+    /// Living mobile units (armed or not).
+    pub supply: u32,
+    /// Summed current health of mobile, armed units.
+    pub army_value: u64,
+}
+
+/// Compute [`FactionTotals`] for every faction with at least one unit.
+#[must_use]
+pub fn faction_totals(sim: &Simulation) -> BTreeMap<FactionId, FactionTotals> {
+    let mut totals: BTreeMap<FactionId, FactionTotals> = BTreeMap::new();
+    for (_, entity) in sim.entities().iter() {
+        let (Some(faction), Some(_)) = (entity.faction, entity.movement) else {
+            continue;
+        };
+        if entity.projectile.is_some() {
+            continue;
+        }
+        let entry = totals.entry(faction.faction).or_default();
+        entry.supply += 1;
+        if let (true, Some(health)) = (is_army_unit(entity), entity.health) {
+            entry.army_value += u64::from(health.current);
+        }
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{CombatStats, FactionMember};
+    use crate::math::{Fixed, Vec2Fixed};
+    use crate::simulation::EntitySpawnParams;
+
+    fn spawn(sim: &mut Simulation, faction: FactionId, health: u32, armed: bool) {
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(0), Fixed::from_num(0))),
+            health: Some(health),
+            movement: Some(Fixed::from_num(1)),
+            combat_stats: armed.then(|| CombatStats::new(5, Fixed::from_num(10), 20)),
+            faction: Some(FactionMember::new(faction, 0)),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_faction_totals_count_supply_and_army_value() {
+        let mut sim = Simulation::new();
+        spawn(&mut sim, FactionId::Continuity, 100, true);
+        spawn(&mut sim, FactionId::Continuity, 60, false);
+        spawn(&mut sim, FactionId::Collegium, 80, true);
+
+        let totals = faction_totals(&sim);
+        assert_eq!(totals[&FactionId::Continuity].supply, 2);
+        assert_eq!(totals[&FactionId::Continuity].army_value, 100);
+        assert_eq!(totals[&FactionId::Collegium].army_value, 80);
+    }
+}
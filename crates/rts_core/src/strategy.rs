@@ -0,0 +1,710 @@
+//! AI strategy definitions.
+//!
+//! A [`Strategy`] describes how an AI player plays: its build order, army
+//! composition, attack timings, economic targets and tech priorities. The
+//! built-in presets are defined here; designers can write their own as RON
+//! or JSON files (see `assets/strategies`), which the game client loads
+//! (see `rts_game::strategy_file`). The headless runner plays the whole
+//! strategy; the game client's skirmish AI plays its unit build order,
+//! composition and attack timings. [`Strategy::problems`] checks a loaded
+//! strategy for mistakes parsing can't catch.
+//!
+//! Timings are in simulation ticks; weights and fractions are in percent.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::difficulty::Handicap;
+
+/// A complete AI strategy configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Strategy {
+    /// Strategy name.
+    pub name: String,
+    /// Human-readable description.
+    pub description: String,
+    /// Build order to follow.
+    pub build_order: Vec<BuildOrderItem>,
+    /// Tick when first attack should happen.
+    pub attack_timing: u64,
+    /// Re-attack interval after first attack (in ticks).
+    pub attack_interval: u64,
+    /// Target army composition (unit_type -> percent of the army).
+    pub composition: BTreeMap<String, u32>,
+    /// Economic targets.
+    pub economy: EconomyTargets,
+    /// Aggression percent (0 = passive, 100 = hyper-aggressive).
+    pub aggression_percent: u32,
+    /// Priority overrides by unit, building or tech ID (see
+    /// [`Strategy::priority_of`]).
+    #[serde(default)]
+    pub priorities: BTreeMap<String, BuildPriority>,
+    /// Send engineers to repair structures below this percent of their
+    /// health (0 = never repair).
+    #[serde(default)]
+    pub repair_below_percent: u32,
+    /// How to use markets, once the faction owns one (None = never trade).
+    #[serde(default)]
+    pub trading: Option<TradePolicy>,
+    /// Technologies to research once the build order is done, most wanted
+    /// first.
+    #[serde(default)]
+    pub tech_priorities: Vec<String>,
+    /// Army size needed before attacking (None = the AI's own default).
+    #[serde(default)]
+    pub min_wave_size: Option<u32>,
+    /// How units fight once enemies are near.
+    #[serde(default)]
+    pub tactics: TacticsConfig,
+    /// Unit to favor against each enemy unit kind or tag, e.g.
+    /// `infantry -> ranger`. Only units already in the composition are
+    /// favored.
+    #[serde(default)]
+    pub counters: BTreeMap<String, String>,
+}
+
+impl Default for Strategy {
+    fn default() -> Self {
+        Self {
+            name: "Balanced".to_string(),
+            description: "Standard balanced gameplay".to_string(),
+            build_order: vec![
+                BuildOrderItem::Unit("harvester".to_string()),
+                BuildOrderItem::Building("barracks".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Unit("harvester".to_string()),
+            ],
+            attack_timing: 12000,  // 200 seconds (3:20) at 60 tps
+            attack_interval: 3600, // 60 seconds between attacks
+            composition: [
+                ("infantry".to_string(), 50),
+                ("ranger".to_string(), 30),
+                ("harvester".to_string(), 20),
+            ]
+            .into_iter()
+            .collect(),
+            economy: EconomyTargets::default(),
+            aggression_percent: 50,
+            priorities: BTreeMap::new(),
+            repair_below_percent: 0,
+            tech_priorities: Vec::new(),
+            min_wave_size: None,
+            tactics: TacticsConfig::default(),
+            trading: None,
+            counters: [
+                ("infantry".to_string(), "ranger".to_string()),
+                ("ranger".to_string(), "infantry".to_string()),
+                ("tank".to_string(), "ranger".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+}
+
+impl Strategy {
+    /// Priority of a build order item.
+    ///
+    /// Uses the [`priorities`](Self::priorities) override for the item's ID
+    /// if there is one. Otherwise research and buildings are
+    /// [`BuildPriority::High`], since tech and production structures gate
+    /// everything after them, and everything else is
+    /// [`BuildPriority::Normal`].
+    #[must_use]
+    pub fn priority_of(&self, item: &BuildOrderItem) -> BuildPriority {
+        if let Some(priority) = item.target().and_then(|id| self.priorities.get(id)) {
+            return *priority;
+        }
+        match item {
+            BuildOrderItem::Research(_) | BuildOrderItem::Building(_) => BuildPriority::High,
+            _ => BuildPriority::Normal,
+        }
+    }
+
+    /// This strategy played with `handicap`: aggression biased, and the
+    /// first attack and the waits between attacks stretched or shortened.
+    #[must_use]
+    pub fn with_handicap(mut self, handicap: &Handicap) -> Self {
+        self.aggression_percent = handicap.aggression(self.aggression_percent);
+        self.attack_timing = handicap.attack_delay(self.attack_timing);
+        self.attack_interval = handicap.attack_delay(self.attack_interval);
+        self
+    }
+
+    /// Everything wrong with this strategy that parsing can't catch, such
+    /// as weights out of range. Empty if it is fit to play.
+    #[must_use]
+    pub fn problems(&self) -> Vec<StrategyProblem> {
+        let mut problems = Vec::new();
+        let mut problem = |field: &str, message: String| {
+            problems.push(StrategyProblem {
+                field: field.to_string(),
+                message,
+            });
+        };
+
+        if self.name.trim().is_empty() {
+            problem("name", "must not be empty".to_string());
+        }
+        if self.aggression_percent > 100 {
+            problem(
+                "aggression_percent",
+                format!("{} is above 100", self.aggression_percent),
+            );
+        }
+        if self.repair_below_percent > 100 {
+            problem(
+                "repair_below_percent",
+                format!("{} is above 100", self.repair_below_percent),
+            );
+        }
+        if self.attack_interval == 0 {
+            problem("attack_interval", "must be at least one tick".to_string());
+        }
+        if self.min_wave_size == Some(0) {
+            problem("min_wave_size", "must be at least one unit".to_string());
+        }
+
+        if !self
+            .composition
+            .iter()
+            .any(|(unit, weight)| unit != "harvester" && *weight > 0)
+        {
+            problem(
+                "composition",
+                "needs a combat unit with a positive weight".to_string(),
+            );
+        }
+
+        for (i, item) in self.build_order.iter().enumerate() {
+            if item.target().is_some_and(|id| id.trim().is_empty()) {
+                problem(
+                    &format!("build_order[{i}]"),
+                    "names no unit, building or tech".to_string(),
+                );
+            }
+        }
+        for (i, tech) in self.tech_priorities.iter().enumerate() {
+            if self.tech_priorities[..i].contains(tech) {
+                problem(
+                    &format!("tech_priorities[{i}]"),
+                    format!("{tech} is listed twice"),
+                );
+            }
+        }
+
+        if self.tactics.retreat_below_percent >= 100 {
+            problem(
+                "tactics.retreat_below_percent",
+                "must be below 100, or units never fight".to_string(),
+            );
+        }
+
+        if let Some(trading) = &self.trading {
+            if trading.batch <= 0 {
+                problem("trading.batch", "must be positive".to_string());
+            }
+            if trading.interval_ticks == 0 {
+                problem(
+                    "trading.interval_ticks",
+                    "must be at least one tick".to_string(),
+                );
+            }
+        }
+        problems
+    }
+
+    /// Names of the built-in strategies, one per preset.
+    pub const PRESETS: &'static [&'static str] = &[
+        "rush",
+        "economic",
+        "balanced",
+        "turtle",
+        "harassment",
+        "fast_expand",
+        "all_in",
+        "tech_push",
+    ];
+
+    /// Built-in strategy by CLI/scenario name (e.g. `"rush"`, `"eco"`).
+    #[must_use]
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "rush" => Some(Self::rush()),
+            "economic" | "eco" => Some(Self::economic()),
+            "balanced" => Some(Self::default()),
+            "turtle" => Some(Self::turtle()),
+            "harassment" => Some(Self::harassment()),
+            "fast_expand" => Some(Self::fast_expand()),
+            "all_in" => Some(Self::all_in()),
+            "tech_push" => Some(Self::tech_push()),
+            _ => None,
+        }
+    }
+
+    /// Create a "Rush" strategy (early aggression).
+    #[must_use]
+    pub fn rush() -> Self {
+        Self {
+            name: "Rush".to_string(),
+            description: "Early aggression with cheap units".to_string(),
+            build_order: vec![
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Building("barracks".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::WaitForUnits("infantry".to_string(), 5),
+            ],
+            attack_timing: 7200,   // 120 seconds (balanced from 100s)
+            attack_interval: 2100, // 35 seconds between waves
+            composition: [("infantry".to_string(), 90), ("harvester".to_string(), 10)]
+                .into_iter()
+                .collect(),
+            economy: EconomyTargets {
+                target_harvesters: 1,
+                target_supply_depots: 0,
+                expand_at_resources: 2000,
+            },
+            aggression_percent: 90,
+            priorities: BTreeMap::new(),
+            repair_below_percent: 0,
+            tech_priorities: Vec::new(),
+            min_wave_size: None,
+            tactics: TacticsConfig::default(),
+            counters: BTreeMap::new(),
+            trading: None,
+        }
+    }
+
+    /// Create an "Economic" strategy (late game power).
+    #[must_use]
+    pub fn economic() -> Self {
+        Self {
+            name: "Economic".to_string(),
+            description: "Focus on economy, powerful late game".to_string(),
+            build_order: vec![
+                BuildOrderItem::Unit("harvester".to_string()),
+                BuildOrderItem::Building("supply_depot".to_string()),
+                BuildOrderItem::Unit("harvester".to_string()),
+                BuildOrderItem::Building("barracks".to_string()),
+                BuildOrderItem::Unit("harvester".to_string()),
+                BuildOrderItem::WaitForResources(2000),
+                BuildOrderItem::Building("tech_lab".to_string()),
+            ],
+            attack_timing: 18000,  // 300 seconds (5 minutes)
+            attack_interval: 6000, // 100 seconds between attacks
+            composition: [
+                ("infantry".to_string(), 30),
+                ("ranger".to_string(), 40),
+                ("harvester".to_string(), 30),
+            ]
+            .into_iter()
+            .collect(),
+            economy: EconomyTargets {
+                target_harvesters: 4,
+                target_supply_depots: 2,
+                expand_at_resources: 1500,
+            },
+            aggression_percent: 30,
+            priorities: BTreeMap::new(),
+            repair_below_percent: 0,
+            tech_priorities: Vec::new(),
+            min_wave_size: None,
+            tactics: TacticsConfig::default(),
+            counters: BTreeMap::new(),
+            trading: Some(TradePolicy::default()),
+        }
+    }
+
+    /// Create a "Turtle" strategy (defensive play).
+    #[must_use]
+    pub fn turtle() -> Self {
+        Self {
+            name: "Turtle".to_string(),
+            description: "Defensive play with turrets".to_string(),
+            build_order: vec![
+                BuildOrderItem::Unit("harvester".to_string()),
+                BuildOrderItem::Building("barracks".to_string()),
+                BuildOrderItem::Building("turret".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Building("turret".to_string()),
+                BuildOrderItem::Unit("engineer".to_string()),
+                BuildOrderItem::Unit("harvester".to_string()),
+                BuildOrderItem::Building("supply_depot".to_string()),
+            ],
+            attack_timing: 24000,  // 400 seconds
+            attack_interval: 9000, // 150 seconds
+            composition: [
+                ("infantry".to_string(), 40),
+                ("ranger".to_string(), 40),
+                ("harvester".to_string(), 20),
+            ]
+            .into_iter()
+            .collect(),
+            economy: EconomyTargets {
+                target_harvesters: 3,
+                target_supply_depots: 1,
+                expand_at_resources: 3000, // Only expand when very rich
+            },
+            aggression_percent: 10,
+            priorities: BTreeMap::new(),
+            repair_below_percent: 75,
+            tech_priorities: Vec::new(),
+            min_wave_size: None,
+            tactics: TacticsConfig::default(),
+            counters: BTreeMap::new(),
+            trading: None,
+        }
+    }
+
+    /// Create a "Fast Expand" strategy (two-base economy rush).
+    #[must_use]
+    pub fn fast_expand() -> Self {
+        Self {
+            name: "FastExpand".to_string(),
+            description: "Quick second base for economic advantage".to_string(),
+            build_order: vec![
+                BuildOrderItem::Unit("harvester".to_string()),
+                BuildOrderItem::Unit("harvester".to_string()),
+                BuildOrderItem::Building("supply_depot".to_string()),
+                BuildOrderItem::WaitForResources(300),
+                BuildOrderItem::Building("command_center".to_string()), // 2nd base
+                BuildOrderItem::Unit("harvester".to_string()),
+                BuildOrderItem::Building("barracks".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Unit("harvester".to_string()),
+            ],
+            attack_timing: 15000,  // 250 seconds - medium timing
+            attack_interval: 4800, // 80 seconds between attacks
+            composition: [
+                ("infantry".to_string(), 35),
+                ("ranger".to_string(), 35),
+                ("tank".to_string(), 15),
+                ("harvester".to_string(), 15),
+            ]
+            .into_iter()
+            .collect(),
+            economy: EconomyTargets {
+                target_harvesters: 6,
+                target_supply_depots: 3,
+                expand_at_resources: 1200,
+            },
+            aggression_percent: 50,
+            priorities: BTreeMap::new(),
+            repair_below_percent: 0,
+            tech_priorities: Vec::new(),
+            min_wave_size: None,
+            tactics: TacticsConfig::default(),
+            counters: BTreeMap::new(),
+            trading: None,
+        }
+    }
+
+    /// Create a "Harassment" strategy (constant raids and map control).
+    #[must_use]
+    pub fn harassment() -> Self {
+        Self {
+            name: "Harassment".to_string(),
+            description: "Fast scouts and constant pressure".to_string(),
+            build_order: vec![
+                BuildOrderItem::Unit("scout".to_string()),
+                BuildOrderItem::Unit("scout".to_string()),
+                BuildOrderItem::Building("barracks".to_string()),
+                BuildOrderItem::Unit("scout".to_string()),
+                BuildOrderItem::Unit("ranger".to_string()),
+                BuildOrderItem::Unit("scout".to_string()),
+                BuildOrderItem::WaitForUnits("scout".to_string(), 4),
+            ],
+            attack_timing: 4800,   // 80 seconds - very early harassment
+            attack_interval: 1200, // 20 seconds - constant pressure
+            composition: [
+                ("scout".to_string(), 50),
+                ("ranger".to_string(), 35),
+                ("infantry".to_string(), 15),
+            ]
+            .into_iter()
+            .collect(),
+            economy: EconomyTargets {
+                target_harvesters: 2,
+                target_supply_depots: 1,
+                expand_at_resources: 2000,
+            },
+            aggression_percent: 85,
+            priorities: BTreeMap::new(),
+            repair_below_percent: 0,
+            tech_priorities: Vec::new(),
+            min_wave_size: None,
+            tactics: TacticsConfig::default(),
+            counters: BTreeMap::new(),
+            trading: None,
+        }
+    }
+
+    /// Create an "All-In" strategy (one big attack, no economy).
+    #[must_use]
+    pub fn all_in() -> Self {
+        Self {
+            name: "AllIn".to_string(),
+            description: "Sacrifice economy for one decisive attack".to_string(),
+            build_order: vec![
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Building("barracks".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::WaitForUnits("infantry".to_string(), 7),
+            ],
+            attack_timing: 5400,  // 90 seconds - committed timing
+            attack_interval: 600, // 10 seconds - no holding back
+            composition: [("infantry".to_string(), 100)].into_iter().collect(),
+            economy: EconomyTargets {
+                target_harvesters: 0,
+                target_supply_depots: 0,
+                expand_at_resources: 99999, // Never expand
+            },
+            aggression_percent: 100,
+            priorities: BTreeMap::new(),
+            repair_below_percent: 0,
+            tech_priorities: Vec::new(),
+            min_wave_size: None,
+            tactics: TacticsConfig::default(),
+            counters: BTreeMap::new(),
+            trading: None,
+        }
+    }
+
+    /// Create a "Tech Push" strategy (research then attack with upgraded units).
+    /// This strategy builds economy first, researches key upgrades, then attacks
+    /// with a more powerful mixed-tier army.
+    #[must_use]
+    pub fn tech_push() -> Self {
+        Self {
+            name: "TechPush".to_string(),
+            description: "Research upgrades before building advanced units".to_string(),
+            build_order: vec![
+                BuildOrderItem::Unit("harvester".to_string()),
+                BuildOrderItem::Building("barracks".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Building("tech_lab".to_string()),
+                // Research tier 1 upgrade (generic name - faction-specific techs are looked up)
+                BuildOrderItem::Research("enhanced_training".to_string()),
+                BuildOrderItem::Unit("infantry".to_string()),
+                BuildOrderItem::Unit("ranger".to_string()),
+                BuildOrderItem::WaitForResources(200),
+                BuildOrderItem::Building("vehicle_depot".to_string()),
+                BuildOrderItem::Unit("tank".to_string()),
+            ],
+            attack_timing: 15000,  // 250 seconds - later timing for tech
+            attack_interval: 4800, // 80 seconds between attacks
+            composition: [
+                ("infantry".to_string(), 40),
+                ("ranger".to_string(), 30),
+                ("tank".to_string(), 20),
+                ("harvester".to_string(), 10),
+            ]
+            .into_iter()
+            .collect(),
+            economy: EconomyTargets {
+                target_harvesters: 2,
+                target_supply_depots: 1,
+                expand_at_resources: 2000,
+            },
+            aggression_percent: 60,
+            priorities: BTreeMap::new(),
+            repair_below_percent: 0,
+            tech_priorities: Vec::new(),
+            min_wave_size: None,
+            tactics: TacticsConfig::default(),
+            counters: BTreeMap::new(),
+            trading: None,
+        }
+    }
+}
+
+/// A single item in a build order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BuildOrderItem {
+    /// Produce a unit.
+    Unit(String),
+    /// Construct a building.
+    Building(String),
+    /// Research a technology.
+    Research(String),
+    /// Wait for a certain amount of resources.
+    WaitForResources(i64),
+    /// Wait for a certain number of a unit type.
+    WaitForUnits(String, u32),
+    /// Wait for a specific tick.
+    WaitForTick(u64),
+}
+
+impl BuildOrderItem {
+    /// ID of the unit, building or tech this item buys, if any.
+    #[must_use]
+    pub fn target(&self) -> Option<&str> {
+        match self {
+            Self::Unit(id) | Self::Building(id) | Self::Research(id) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+/// How urgently a purchase should be made.
+///
+/// Build order items above [`BuildPriority::Normal`] that cannot be afforded
+/// yet are kept and reserve their cost, so purchases of lower priority
+/// (such as continuous unit production, which is [`BuildPriority::Low`])
+/// cannot spend it.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub enum BuildPriority {
+    /// Filler purchases.
+    Low,
+    /// Ordinary build order items.
+    #[default]
+    Normal,
+    /// Key tech and structures.
+    High,
+    /// Must be bought before anything else.
+    Critical,
+}
+
+/// Economic targets for the AI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EconomyTargets {
+    /// Target number of harvesters.
+    pub target_harvesters: u32,
+    /// Target number of supply depots.
+    pub target_supply_depots: u32,
+    /// Expand when resources exceed this amount.
+    pub expand_at_resources: i64,
+}
+
+impl Default for EconomyTargets {
+    fn default() -> Self {
+        Self {
+            target_harvesters: 2,
+            target_supply_depots: 1,
+            expand_at_resources: 2500,
+        }
+    }
+}
+
+/// Unit-level tactics: which fighting behaviors an AI's units use.
+///
+/// The headless runner builds each unit's behavior tree from these (see
+/// `rts_headless::tactics`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TacticsConfig {
+    /// Pull units back home below this percent of their health (0 = fight
+    /// to the death).
+    pub retreat_below_percent: u32,
+    /// Ranged units step back from close enemies while reloading.
+    pub kite: bool,
+    /// Units in range of several enemies shoot the one closest to dying,
+    /// preferring those that shoot back.
+    pub focus_fire: bool,
+    /// Units with nothing in range go after enemies attacking harvesters
+    /// within this distance (0 = leave harvesters to fend for themselves).
+    pub defend_harvesters_within: u32,
+}
+
+impl Default for TacticsConfig {
+    fn default() -> Self {
+        Self {
+            retreat_below_percent: 20,
+            kite: true,
+            focus_fire: true,
+            defend_harvesters_within: 400,
+        }
+    }
+}
+
+/// When and how much the AI trades at its markets.
+///
+/// The AI buys the rarer resources a held-back purchase is waiting on,
+/// paying with whatever it holds beyond its reserve. Each trade is capped at
+/// `batch` and skipped when slippage would exceed `max_slippage_percent`, so
+/// the AI spreads its buying over time instead of taking the worst rates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradePolicy {
+    /// Feedstock never traded away.
+    pub keep_feedstock: i64,
+    /// Most sold in a single trade.
+    pub batch: i64,
+    /// Ticks between trades.
+    pub interval_ticks: u64,
+    /// Worst slippage accepted (percent of the base rate lost).
+    pub max_slippage_percent: u32,
+}
+
+impl Default for TradePolicy {
+    fn default() -> Self {
+        Self {
+            keep_feedstock: 300,
+            batch: 200,
+            interval_ticks: 120,
+            max_slippage_percent: 25,
+        }
+    }
+}
+
+/// A mistake in a strategy, found by [`Strategy::problems`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrategyProblem {
+    /// Field it is in, e.g. `composition[infantry]`.
+    pub field: String,
+    /// What is wrong.
+    pub message: String,
+}
+
+impl fmt::Display for StrategyProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_have_no_problems() {
+        for name in Strategy::PRESETS {
+            let strategy = Strategy::preset(name).unwrap();
+            assert!(strategy.problems().is_empty(), "{name}");
+        }
+        assert!(Strategy::preset("no_such_strategy").is_none());
+    }
+
+    #[test]
+    fn test_problems_name_their_field() {
+        let strategy = Strategy {
+            aggression_percent: 150,
+            composition: [("harvester".to_string(), 100)].into_iter().collect(),
+            tech_priorities: vec!["armor".to_string(), "armor".to_string()],
+            ..Default::default()
+        };
+
+        let fields: Vec<_> = strategy
+            .problems()
+            .into_iter()
+            .map(|problem| problem.field)
+            .collect();
+        assert_eq!(
+            fields,
+            vec!["aggression_percent", "composition", "tech_priorities[1]"]
+        );
+    }
+}
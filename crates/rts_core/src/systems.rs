@@ -6,6 +6,8 @@
 //! All systems are pure functions that operate on component data.
 //! They use fixed-point math for deterministic simulation.
 
+use std::collections::{BTreeMap, BTreeSet};
+
 use crate::combat::calculate_resistance_damage;
 use crate::components::{
     ArmorType, AttackTarget, CombatStats, Command, CommandQueue, DamageType, EntityId, Health,
@@ -19,31 +21,152 @@ use crate::math::{Fixed, Vec2Fixed};
 /// is updated by adding its velocity (which represents units per tick).
 ///
 /// # Arguments
-/// * `entities` - Slice of entities with mutable positions and read-only velocities
+/// * `positions` - Position column, updated in place
+/// * `velocities` - Velocity column, row for row with `positions`
+///
+/// Rows missing either component are skipped.
 ///
 /// # Example
 /// ```ignore
-/// let mut entities = vec![
-///     (1, &mut position, &velocity),
-/// ];
-/// movement_system(&mut entities);
+/// let columns = storage.columns_mut_through(last);
+/// movement_system(columns.positions, columns.velocities);
 /// ```
-pub fn movement_system(entities: &mut [(EntityId, &mut Position, &Velocity)]) {
-    for (_entity_id, position, velocity) in entities.iter_mut() {
-        position.value = position.value + velocity.value;
+pub fn movement_system(positions: &mut [Option<Position>], velocities: &[Option<Velocity>]) {
+    for (position, velocity) in positions.iter_mut().zip(velocities) {
+        if let (Some(position), Some(velocity)) = (position, velocity) {
+            position.value = position.value + velocity.value;
+        }
+    }
+}
+
+/// A unit taking part in local avoidance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AvoidanceBody {
+    /// The unit.
+    pub id: EntityId,
+    /// Position after this tick's movement.
+    pub position: Vec2Fixed,
+    /// Collider radius.
+    pub radius: Fixed,
+    /// Next path waypoint or final destination of a moving unit.
+    pub heading: Option<Vec2Fixed>,
+    /// Whether `heading` is the final destination rather than a waypoint.
+    pub final_leg: bool,
+    /// Whether the unit has no orders left.
+    pub settled: bool,
+}
+
+/// Outcome of [`avoidance_system`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Avoidance {
+    /// Offset to add to the position of each overlapping unit, by id.
+    pub pushes: Vec<(EntityId, Vec2Fixed)>,
+    /// Units that bumped into a settled unit nearer their destination and
+    /// should stop there rather than keep pushing into the crowd, by id.
+    pub arrived: Vec<EntityId>,
+    /// Units crowded out of their next waypoint that should move on to the
+    /// one after it, by id.
+    pub passed: Vec<EntityId>,
+}
+
+/// Separates overlapping units, boids-style.
+///
+/// Every overlapping pair is pushed apart along the line between their
+/// centres, each unit taking half of the overlap. Units on exactly the
+/// same spot are split along an axis chosen from their ids. Pairs are
+/// found with a sweep along the x axis, so the cost grows with the number
+/// of units close to each other rather than with the square of all units.
+///
+/// A unit on the final leg of a move that touches a settled unit closer to
+/// its destination is reported as arrived, so a group ordered to one spot
+/// gathers around it instead of jostling forever. Likewise a unit within
+/// touching distance of its next waypoint that bumps into a unit at least
+/// as close to it is reported as having passed that waypoint.
+pub fn avoidance_system(bodies: &[AvoidanceBody]) -> Avoidance {
+    /// Split directions for units on exactly the same spot.
+    const SPLIT: [(i32, i32); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+    let mut order: Vec<&AvoidanceBody> = bodies.iter().collect();
+    order.sort_by_key(|body| (body.position.x, body.id));
+    let max_radius = order
+        .iter()
+        .map(|body| body.radius)
+        .max()
+        .unwrap_or(Fixed::ZERO);
+
+    let mut pushes: BTreeMap<EntityId, Vec2Fixed> = BTreeMap::new();
+    let mut arrived = BTreeSet::new();
+    let mut passed = BTreeSet::new();
+    for (i, a) in order.iter().enumerate() {
+        for b in &order[i + 1..] {
+            if b.position.x - a.position.x >= a.radius + max_radius {
+                break;
+            }
+            let reach = a.radius + b.radius;
+            let dist_sq = a.position.distance_squared(b.position);
+            if dist_sq >= reach * reach {
+                continue;
+            }
+
+            let dist = fixed_sqrt(dist_sq);
+            let normal = if dist == Fixed::ZERO {
+                let (x, y) = SPLIT[(a.id.max(b.id) % SPLIT.len() as u64) as usize];
+                let normal = Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y));
+                if a.id < b.id {
+                    normal
+                } else {
+                    Vec2Fixed::ZERO - normal
+                }
+            } else {
+                let diff = b.position - a.position;
+                Vec2Fixed::new(diff.x / dist, diff.y / dist)
+            };
+            let half = (reach - dist) / Fixed::from_num(2);
+            let push = Vec2Fixed::new(normal.x * half, normal.y * half);
+            let push_b = pushes.entry(b.id).or_insert(Vec2Fixed::ZERO);
+            *push_b = *push_b + push;
+            let push_a = pushes.entry(a.id).or_insert(Vec2Fixed::ZERO);
+            *push_a = *push_a - push;
+
+            for (mover, other) in [(a, b), (b, a)] {
+                let Some(heading) = mover.heading else {
+                    continue;
+                };
+                let mover_dist_sq = mover.position.distance_squared(heading);
+                let other_dist_sq = other.position.distance_squared(heading);
+                if mover.final_leg {
+                    if other.settled && other_dist_sq < mover_dist_sq {
+                        arrived.insert(mover.id);
+                    }
+                } else if other_dist_sq <= mover_dist_sq && mover_dist_sq < reach * reach {
+                    passed.insert(mover.id);
+                }
+            }
+        }
+    }
+
+    Avoidance {
+        pushes: pushes
+            .into_iter()
+            .filter(|(_, push)| *push != Vec2Fixed::ZERO)
+            .collect(),
+        arrived: arrived.into_iter().collect(),
+        passed: passed.into_iter().collect(),
     }
 }
 
 /// Processes command queues and converts commands to movement velocity.
 ///
 /// Examines the current command for each entity and sets appropriate velocity:
-/// - `MoveTo`: Calculates direction toward target, sets velocity based on movement speed
+/// - `MoveTo` and `AttackMove`: Follows the planned path waypoints, then heads
+///   straight for the target, setting velocity based on movement speed
+///   (attack-move engagements are handled by the combat systems)
 /// - `Stop`: Sets velocity to zero
 /// - `HoldPosition`: Sets velocity to zero
 /// - Other commands: No velocity change (handled by other systems)
 ///
-/// When a `MoveTo` command reaches its destination (within a small threshold),
-/// the command is popped from the queue.
+/// When a `MoveTo` or `AttackMove` command reaches its destination (within a
+/// small threshold), the command is popped from the queue.
 ///
 /// # Arguments
 /// * `entities` - Slice of entities with their command queues, positions, velocities, movement stats, and path waypoints
@@ -64,7 +187,7 @@ pub fn command_processing_system(
         entities.iter_mut()
     {
         match command_queue.current() {
-            Some(Command::MoveTo(target)) => {
+            Some(Command::MoveTo(target) | Command::AttackMove(target)) => {
                 // If we have waypoints, follow them; otherwise go directly to target
                 let next_target = if let Some(waypoints) = path_waypoints.as_mut() {
                     if let Some(first) = waypoints.first() {
@@ -103,6 +226,10 @@ pub fn command_processing_system(
                     velocity.value = Vec2Fixed::ZERO;
                     command_queue.pop();
                     **path_waypoints = None;
+                } else if dist_sq <= movement.speed * movement.speed {
+                    // Close enough to land on the waypoint this tick rather
+                    // than overshoot it
+                    velocity.value = diff;
                 } else {
                     // Calculate direction and set velocity
                     let direction = normalize_vec2(diff);
@@ -120,27 +247,29 @@ pub fn command_processing_system(
                 **path_waypoints = None;
                 // HoldPosition stays active (don't pop)
             }
-            Some(Command::AttackMove(target)) => {
-                // Move toward position (attack logic handled by combat system)
-                // TODO: Implement pathfinding for AttackMove
-                let target = *target;
-                let diff = target - position.value;
-                let dist_sq = position.value.distance_squared(target);
-
-                if dist_sq <= arrival_threshold_sq {
-                    velocity.value = Vec2Fixed::ZERO;
-                    command_queue.pop();
-                } else {
-                    let direction = normalize_vec2(diff);
-                    velocity.value =
-                        Vec2Fixed::new(direction.x * movement.speed, direction.y * movement.speed);
-                }
-            }
             Some(Command::Patrol(_)) | Some(Command::Follow(_)) | Some(Command::Guard(_)) => {
                 // These require additional state tracking - placeholder for now
             }
-            Some(Command::Attack(_)) => {
-                // Attack command: movement handled by combat system based on range
+            Some(Command::Attack(_))
+            | Some(Command::ForceAttack(_))
+            | Some(Command::AttackGround(_)) => {
+                // Attack commands: movement handled by the attack chase system
+            }
+            Some(Command::UseAbility { .. }) => {
+                // Movement handled by the ability system
+            }
+            Some(Command::Load(_)) | Some(Command::Unload(_)) => {
+                // Movement handled by the transport system
+            }
+            Some(Command::Repair(_)) => {
+                // Movement handled by the repair system
+            }
+            Some(Command::Construct(_)) => {
+                // Movement handled by the construction system
+            }
+            Some(Command::SetRallyPoint(_)) => {
+                // Rally points are set when ordered and never wait in a queue
+                command_queue.pop();
             }
             None => {
                 // No command - stop moving
@@ -156,15 +285,16 @@ pub fn command_processing_system(
 /// entity IDs that should be removed (health <= 0).
 ///
 /// # Arguments
-/// * `entities` - Slice of entities with their health components
+/// * `ids` - ID column
+/// * `health` - Health column, row for row with `ids`
 ///
 /// # Returns
 /// Vector of entity IDs that are dead and should be removed from the simulation
-pub fn health_system(entities: &[(EntityId, &Health)]) -> Vec<EntityId> {
-    entities
-        .iter()
-        .filter(|(_id, health)| health.is_dead())
-        .map(|(id, _)| *id)
+pub fn health_system(ids: &[EntityId], health: &[Option<Health>]) -> Vec<EntityId> {
+    ids.iter()
+        .zip(health)
+        .filter(|(_, health)| health.is_some_and(|h| h.is_dead()))
+        .map(|(&id, _)| id)
         .collect()
 }
 
@@ -175,16 +305,22 @@ pub struct PositionLookup<'a> {
 
 impl<'a> PositionLookup<'a> {
     /// Create a new position lookup from a slice of entity positions.
+    ///
+    /// The slice must be sorted by entity ID, as entity storage iterates.
     pub fn new(positions: &'a [(EntityId, Position)]) -> Self {
+        debug_assert!(
+            positions.windows(2).all(|pair| pair[0].0 < pair[1].0),
+            "positions must be sorted by entity ID"
+        );
         Self { positions }
     }
 
     /// Get the position of an entity by ID.
     pub fn get(&self, entity_id: EntityId) -> Option<Position> {
         self.positions
-            .iter()
-            .find(|(id, _)| *id == entity_id)
-            .map(|(_, pos)| *pos)
+            .binary_search_by_key(&entity_id, |(id, _)| *id)
+            .ok()
+            .map(|i| self.positions[i].1)
     }
 }
 
@@ -699,14 +835,67 @@ mod tests {
 
     #[test]
     fn test_movement_system() {
-        let mut pos = Position::new(Vec2Fixed::new(Fixed::from_num(10), Fixed::from_num(20)));
+        let pos = Position::new(Vec2Fixed::new(Fixed::from_num(10), Fixed::from_num(20)));
         let vel = Velocity::new(Vec2Fixed::new(Fixed::from_num(1), Fixed::from_num(-2)));
 
-        let mut entities = vec![(1u64, &mut pos, &vel)];
-        movement_system(&mut entities);
+        let mut positions = [Some(pos), Some(pos), None];
+        movement_system(&mut positions, &[Some(vel), None, Some(vel)]);
 
-        assert_eq!(pos.value.x, Fixed::from_num(11));
-        assert_eq!(pos.value.y, Fixed::from_num(18));
+        let moved = positions[0].unwrap();
+        assert_eq!(moved.value.x, Fixed::from_num(11));
+        assert_eq!(moved.value.y, Fixed::from_num(18));
+        // Rows missing a component stay put
+        assert_eq!(positions[1].unwrap().value.x, Fixed::from_num(10));
+        assert_eq!(positions[2], None);
+    }
+
+    #[test]
+    fn test_avoidance_separates_overlaps_and_stops_at_crowd() {
+        let body = |id, x: i32, heading: Option<Vec2Fixed>, settled| AvoidanceBody {
+            id,
+            position: Vec2Fixed::new(Fixed::from_num(x), Fixed::ZERO),
+            radius: Fixed::from_num(5),
+            heading,
+            final_leg: true,
+            settled,
+        };
+        let goal = Some(Vec2Fixed::ZERO);
+        let bodies = [
+            body(1, 0, None, true),
+            body(2, 6, goal, false),
+            body(3, 40, None, false),
+            body(4, 40, None, false),
+            body(5, 100, None, false),
+        ];
+        let avoidance = avoidance_system(&bodies);
+
+        // 1 and 2 overlap by 4, 3 and 4 share a spot; 5 is clear of all
+        assert_eq!(avoidance.pushes.len(), 4);
+        // Push along x, allowing for rounding in the square root
+        let push_x = |id| {
+            avoidance
+                .pushes
+                .iter()
+                .find(|(pushed, _)| *pushed == id)
+                .map(|(_, push)| (push.x.round().to_num::<i32>(), push.y))
+        };
+        assert_eq!(push_x(1), Some((-2, Fixed::ZERO)));
+        assert_eq!(push_x(2), Some((2, Fixed::ZERO)));
+        assert_eq!(push_x(3), Some((-5, Fixed::ZERO)));
+        assert_eq!(push_x(4), Some((5, Fixed::ZERO)));
+        assert_eq!(push_x(5), None);
+        assert_eq!(avoidance.arrived, vec![2]);
+        assert!(avoidance.passed.is_empty());
+
+        // Crowded near a waypoint, the unit further from it moves on
+        let waypoint = Some(Vec2Fixed::new(Fixed::from_num(100), Fixed::ZERO));
+        let mut crowd = [body(6, 100, waypoint, false), body(7, 104, waypoint, false)];
+        crowd[1].final_leg = false;
+        assert_eq!(avoidance_system(&crowd).passed, vec![7]);
+
+        let mut reversed = bodies;
+        reversed.reverse();
+        assert_eq!(avoidance_system(&reversed), avoidance);
     }
 
     #[test]
@@ -721,8 +910,10 @@ mod tests {
             max: 50,
         };
 
-        let entities = vec![(1u64, &alive), (2u64, &dead), (3u64, &also_dead)];
-        let dead_list = health_system(&entities);
+        let dead_list = health_system(
+            &[1, 2, 3, 4],
+            &[Some(alive), Some(dead), Some(also_dead), None],
+        );
 
         assert_eq!(dead_list.len(), 2);
         assert!(dead_list.contains(&2u64));
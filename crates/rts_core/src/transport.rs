@@ -0,0 +1,116 @@
+//! Transports: units that carry other units.
+//!
+//! A unit ordered to [`Command::Load`](crate::components::Command::Load)
+//! into a transport with a [`Cargo`] hold closes on it and boards once within
+//! [`BOARDING_RANGE`]. Aboard, the passenger has no position, so movement,
+//! combat and fog of war pass it by, and it refuses orders until the
+//! transport carries out
+//! [`Command::Unload`](crate::components::Command::Unload), which sets every
+//! passenger down around the transport. If the transport is destroyed, its
+//! passengers die with it and are reported one by one among the tick's
+//! deaths.
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::EntityId;
+use crate::math::{Fixed, Vec2Fixed};
+use crate::research::TechProfile;
+
+/// How close a unit must be to a transport to board it, and a transport to
+/// its unload point to set passengers down.
+pub const BOARDING_RANGE: Fixed = Fixed::const_from_int(16);
+
+/// Passengers stepping out of a transport stand in a ring this far from it.
+const DROP_RADIUS: i32 = 8;
+
+/// Unit offsets around a transport for passengers stepping out, clockwise
+/// from the east.
+const DROP_OFFSETS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// Hold of a transport unit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cargo {
+    /// Most passengers carried at once.
+    pub capacity: u32,
+    /// Units carrying one of these tags may board; any unit if empty.
+    pub tags: Vec<String>,
+    /// Units aboard, in boarding order.
+    pub passengers: Vec<EntityId>,
+}
+
+impl Cargo {
+    /// Create an empty hold.
+    #[must_use]
+    pub fn new(capacity: u32, tags: Vec<String>) -> Self {
+        Self {
+            capacity,
+            tags,
+            passengers: Vec::new(),
+        }
+    }
+
+    /// Whether the hold has no room left.
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.passengers.len() >= self.capacity as usize
+    }
+
+    /// Whether a unit with `profile` may board. Units without a profile
+    /// only fit holds that take anything.
+    #[must_use]
+    pub fn accepts(&self, profile: Option<&TechProfile>) -> bool {
+        self.tags.is_empty()
+            || profile.is_some_and(|p| self.tags.iter().any(|t| p.tags.contains(t)))
+    }
+
+    /// Where the `index`th passenger steps out of a transport at `center`.
+    ///
+    /// The first eight take the spots of a ring; later ones fill further
+    /// rings outwards.
+    #[must_use]
+    pub fn drop_point(center: Vec2Fixed, index: usize) -> Vec2Fixed {
+        let (dx, dy) = DROP_OFFSETS[index % DROP_OFFSETS.len()];
+        let ring = (index / DROP_OFFSETS.len()) as i32 + 1;
+        let step = Fixed::from_num(DROP_RADIUS * ring);
+        center + Vec2Fixed::new(step * Fixed::from_num(dx), step * Fixed::from_num(dy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cargo_accepts_tagged_units_until_full() {
+        let mut hold = Cargo::new(1, vec!["infantry".to_string()]);
+        let trooper = TechProfile::new("security_team", vec!["infantry".to_string()]);
+        let tank = TechProfile::new("hover_tank", vec!["vehicle".to_string()]);
+
+        assert!(hold.accepts(Some(&trooper)));
+        assert!(!hold.accepts(Some(&tank)));
+        assert!(!hold.accepts(None));
+        assert!(Cargo::new(1, Vec::new()).accepts(None));
+
+        hold.passengers.push(7);
+        assert!(hold.is_full());
+    }
+
+    #[test]
+    fn test_drop_points_do_not_overlap() {
+        let points: Vec<Vec2Fixed> = (0..12)
+            .map(|i| Cargo::drop_point(Vec2Fixed::ZERO, i))
+            .collect();
+        for (i, a) in points.iter().enumerate() {
+            assert!(points[i + 1..].iter().all(|b| a != b));
+        }
+    }
+}
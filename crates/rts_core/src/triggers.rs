@@ -0,0 +1,203 @@
+//! Scripted scenario triggers: timed events and reactions to the battle.
+//!
+//! A scenario can carry [`Trigger`]s that fire on a tick, on a repeating
+//! timer (reinforcements) or when a faction loses an entity of some kind.
+//! The simulation evaluates them at the end of every tick, so every peer
+//! and replay fires the same triggers on the same tick.
+//!
+//! The simulation does not hold player resources or unit data: a fired
+//! trigger is reported as a [`TriggerFired`] event carrying its
+//! [`TriggerAction`]s, which the game layer carries out.
+
+use serde::{Deserialize, Serialize};
+
+use crate::factions::FactionId;
+
+/// When a trigger fires.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TriggerCondition {
+    /// Once, on the given tick.
+    AtTick(u64),
+    /// On `start` and every `interval` ticks after, `times` times in all.
+    Every {
+        /// First tick to fire on.
+        start: u64,
+        /// Ticks between firings.
+        interval: u64,
+        /// How many times to fire.
+        times: u32,
+    },
+    /// Once, when `faction` first loses an entity of `kind` (its data ID,
+    /// e.g. "command_center").
+    Destroyed {
+        /// Faction that loses the entity.
+        faction: FactionId,
+        /// Kind of entity lost.
+        kind: String,
+    },
+}
+
+/// What a trigger does when it fires.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TriggerAction {
+    /// Spawn units for a faction, such as an attack wave or reinforcements.
+    SpawnUnits {
+        /// Faction the units join.
+        faction: FactionId,
+        /// Unit kind (data ID or role, as in starting units).
+        kind: String,
+        /// Where they arrive.
+        position: (i32, i32),
+        /// How many arrive.
+        count: u32,
+    },
+    /// Give a faction feedstock.
+    GrantResources {
+        /// Faction that receives it.
+        faction: FactionId,
+        /// Feedstock granted.
+        amount: i64,
+    },
+    /// Tell the players something, e.g. a mission briefing line.
+    Message(String),
+}
+
+/// A scripted event in a scenario.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trigger {
+    /// Name for logs and events.
+    pub name: String,
+    /// When it fires.
+    pub condition: TriggerCondition,
+    /// What it does, in order.
+    pub actions: Vec<TriggerAction>,
+}
+
+/// A trigger fired this tick.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TriggerFired {
+    /// Name of the trigger.
+    pub trigger: String,
+    /// Actions for the game layer to carry out, in order.
+    pub actions: Vec<TriggerAction>,
+}
+
+/// A scenario's triggers and how often each has fired.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScenarioScript {
+    triggers: Vec<Trigger>,
+    fired: Vec<u32>,
+}
+
+impl ScenarioScript {
+    /// Create a script that has not fired yet.
+    #[must_use]
+    pub fn new(triggers: Vec<Trigger>) -> Self {
+        let fired = vec![0; triggers.len()];
+        Self { triggers, fired }
+    }
+
+    /// The script's triggers.
+    #[must_use]
+    pub fn triggers(&self) -> &[Trigger] {
+        &self.triggers
+    }
+
+    /// How many times each trigger has fired, in trigger order.
+    #[must_use]
+    pub fn fired(&self) -> &[u32] {
+        &self.fired
+    }
+
+    /// Fire every trigger due on `tick`, given the `(faction, kind)` of
+    /// each entity destroyed this tick, in trigger order.
+    ///
+    /// Timed triggers fire on the first evaluation at or after their tick,
+    /// so a script set part way through a match catches up once.
+    pub fn evaluate(&mut self, tick: u64, destroyed: &[(FactionId, &str)]) -> Vec<TriggerFired> {
+        let mut events = Vec::new();
+        for (trigger, fired) in self.triggers.iter().zip(self.fired.iter_mut()) {
+            let due = match &trigger.condition {
+                TriggerCondition::AtTick(at) => *fired == 0 && tick >= *at,
+                TriggerCondition::Every {
+                    start,
+                    interval,
+                    times,
+                } => {
+                    let next = start.saturating_add(interval.saturating_mul(u64::from(*fired)));
+                    *fired < *times && tick >= next
+                }
+                TriggerCondition::Destroyed { faction, kind } => {
+                    *fired == 0 && destroyed.iter().any(|&(f, k)| f == *faction && k == kind)
+                }
+            };
+            if due {
+                *fired += 1;
+                events.push(TriggerFired {
+                    trigger: trigger.name.clone(),
+                    actions: trigger.actions.clone(),
+                });
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(name: &str, condition: TriggerCondition) -> Trigger {
+        Trigger {
+            name: name.to_string(),
+            condition,
+            actions: vec![TriggerAction::Message(name.to_string())],
+        }
+    }
+
+    #[test]
+    fn test_triggers_fire_on_time_on_a_timer_and_on_losses() {
+        let mut script = ScenarioScript::new(vec![
+            message("briefing", TriggerCondition::AtTick(0)),
+            message(
+                "reinforcements",
+                TriggerCondition::Every {
+                    start: 10,
+                    interval: 5,
+                    times: 2,
+                },
+            ),
+            message(
+                "base_lost",
+                TriggerCondition::Destroyed {
+                    faction: FactionId::Continuity,
+                    kind: "command_center".to_string(),
+                },
+            ),
+        ]);
+
+        let mut log = Vec::new();
+        for tick in 0..30 {
+            let destroyed: &[(FactionId, &str)] = match tick {
+                12 => &[(FactionId::Collegium, "command_center")],
+                20 | 25 => &[(FactionId::Continuity, "command_center")],
+                _ => &[],
+            };
+            for fired in script.evaluate(tick, destroyed) {
+                log.push((tick, fired.trigger));
+            }
+        }
+
+        let names = |tick, name: &str| (tick, name.to_string());
+        assert_eq!(
+            log,
+            vec![
+                names(0, "briefing"),
+                names(10, "reinforcements"),
+                names(15, "reinforcements"),
+                names(20, "base_lost"),
+            ]
+        );
+        assert_eq!(script.fired(), &[1, 2, 1]);
+    }
+}
@@ -4,16 +4,29 @@
 //! - Resource gathering with harvesters
 //! - Unit production when resources allow
 //! - Wave-based attacks: units accumulate at rally points before attacking as coordinated waves
+//!
+//! Each AI faction plays with a [`Handicap`] from [`AiHandicaps`], set from
+//! the skirmish difficulty: it scales how fast the AI produces, how large
+//! and how often its waves are, and how quickly it acts.
+//!
+//! A faction given a [`Strategy`] in [`AiStrategies`] produces the units of
+//! its build order, then its harvester target and army composition, and
+//! attacks on its timings. The skirmish AI only produces units, so the
+//! strategy's buildings, research and trading are left out.
 
 use bevy::prelude::*;
+use rts_core::difficulty::{Difficulty, Handicap};
 use rts_core::factions::FactionId;
 use rts_core::math::Vec2Fixed;
+use rts_core::simulation::TICK_RATE;
+use rts_core::strategy::{BuildOrderItem, Strategy};
 use std::collections::{HashMap, HashSet};
 
 use crate::components::{
     CombatStats, GameDepot, GameFaction, GameHarvester, GameHarvesterState, GamePosition,
     GameProductionQueue, GameResourceNode, MovementTarget, PlayerFaction, UnitType,
 };
+use crate::data_loader::FactionRegistry;
 
 /// Plugin for AI-controlled factions.
 pub struct AiPlugin;
@@ -21,17 +34,90 @@ pub struct AiPlugin;
 impl Plugin for AiPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<AiState>()
+            .init_resource::<AiHandicaps>()
+            .init_resource::<AiStrategies>()
             .add_systems(Update, ai_production)
             .add_systems(Update, ai_harvester_assignment)
             .add_systems(Update, ai_attack_orders);
     }
 }
 
+/// Handicap of each AI faction in a skirmish.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct AiHandicaps {
+    /// Handicap of AI factions without one of their own.
+    pub default: Handicap,
+    /// Handicaps set for single factions.
+    pub factions: HashMap<FactionId, Handicap>,
+}
+
+impl AiHandicaps {
+    /// Every AI faction playing at `difficulty`.
+    #[must_use]
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self {
+            default: difficulty.handicap(),
+            factions: HashMap::new(),
+        }
+    }
+
+    /// Give one AI faction its own handicap.
+    #[must_use]
+    pub fn with_faction(mut self, faction: FactionId, handicap: Handicap) -> Self {
+        self.factions.insert(faction, handicap);
+        self
+    }
+
+    /// Handicap `faction` plays with.
+    #[must_use]
+    pub fn get(&self, faction: FactionId) -> Handicap {
+        self.factions.get(&faction).copied().unwrap_or(self.default)
+    }
+}
+
+/// Strategy of each AI faction in a skirmish, for factions that play one.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct AiStrategies {
+    /// Strategy of AI factions without one of their own (None = the
+    /// built-in behavior).
+    pub default: Option<Strategy>,
+    /// Strategies set for single factions.
+    pub factions: HashMap<FactionId, Strategy>,
+}
+
+impl AiStrategies {
+    /// Every AI faction playing `strategy`.
+    #[must_use]
+    pub fn new(strategy: Strategy) -> Self {
+        Self {
+            default: Some(strategy),
+            factions: HashMap::new(),
+        }
+    }
+
+    /// Give one AI faction its own strategy.
+    #[must_use]
+    pub fn with_faction(mut self, faction: FactionId, strategy: Strategy) -> Self {
+        self.factions.insert(faction, strategy);
+        self
+    }
+
+    /// Strategy `faction` plays, if any.
+    #[must_use]
+    pub fn get(&self, faction: FactionId) -> Option<&Strategy> {
+        self.factions.get(&faction).or(self.default.as_ref())
+    }
+}
+
 /// State tracking for AI decisions.
 #[derive(Resource)]
 pub struct AiState {
-    /// Timer for production decisions.
-    pub production_timer: f32,
+    /// Timers for production decisions, per faction.
+    pub production_timers: HashMap<FactionId, f32>,
+    /// Build order items each strategy-driven faction has gone through.
+    pub build_steps: HashMap<FactionId, usize>,
+    /// Units each strategy-driven faction has queued, by strategy name.
+    pub queued: HashMap<FactionId, HashMap<String, u32>>,
     /// Timer for attack decisions.
     pub attack_timer: f32,
     /// Timer for harvester assignments.
@@ -69,7 +155,9 @@ impl WaveState {
 impl Default for AiState {
     fn default() -> Self {
         Self {
-            production_timer: 0.0,
+            production_timers: HashMap::new(),
+            build_steps: HashMap::new(),
+            queued: HashMap::new(),
             attack_timer: 0.0,
             harvester_timer: 0.0,
             game_time: 0.0,
@@ -81,29 +169,128 @@ impl Default for AiState {
 /// Minimum game time before AI will attack (gives player time to prepare).
 const AI_GRACE_PERIOD: f32 = 60.0;
 
-/// Minimum units AI needs before launching a wave.
-///
-/// NOTE: This is currently a global default for all AI factions/personalities.
-/// According to the original design, this value should be provided per-strategy
-/// (as `min_wave_size` in the strategy config) so that different AI personalities
-/// can tune how "patient" they are before attacking.
-///
-/// When a per-strategy configuration system is wired in, this constant should be
-/// treated as a fallback/default and overridden by the strategy's `min_wave_size`.
+/// Minimum units AI needs before launching a wave, unless its strategy sets
+/// a `min_wave_size`.
 const MIN_WAVE_SIZE: usize = 8;
 
-/// Time between waves (seconds).
-///
-/// NOTE: This is currently a global default for all AI factions/personalities.
-/// The intended design is to make this configurable per-strategy via a
-/// `wave_interval` field in the strategy config, allowing different AI
-/// personalities to attack more or less frequently.
-///
-/// When per-strategy configuration is implemented, this constant should serve
-/// only as a fallback/default and be overridden by the strategy's
-/// `wave_interval` value.
+/// Time between waves (seconds), unless the AI's strategy sets an
+/// `attack_interval`.
 const WAVE_INTERVAL: f32 = 20.0;
 
+/// Most combat units a strategy-driven AI keeps in the field.
+const MAX_STRATEGY_ARMY: usize = 20;
+
+/// Time between production decisions (seconds) for an AI without handicap.
+const PRODUCTION_INTERVAL: f32 = 2.0;
+
+/// Seconds between production decisions for an AI with `handicap`: poorer
+/// AIs produce less often and richer ones more, within its APM throttle.
+fn production_interval(handicap: &Handicap) -> f32 {
+    let income = handicap.income_percent.max(1) as f32 / 100.0;
+    (PRODUCTION_INTERVAL / income).max(handicap.action_interval() as f32 / TICK_RATE as f32)
+}
+
+/// Seconds of game time in `ticks` simulation ticks.
+fn seconds(ticks: u64) -> f32 {
+    ticks as f32 / TICK_RATE as f32
+}
+
+/// Grace period (seconds) before an AI with `strategy` and `handicap`
+/// attacks.
+fn grace_period(strategy: Option<&Strategy>, handicap: &Handicap) -> f32 {
+    let planned = strategy.map_or(AI_GRACE_PERIOD, |s| seconds(s.attack_timing));
+    planned * handicap.attack_delay_percent as f32 / 100.0
+}
+
+/// Units an AI with `strategy` and `handicap` gathers before launching a
+/// wave: more aggressive AIs go with fewer.
+fn wave_size(strategy: Option<&Strategy>, handicap: &Handicap) -> usize {
+    let planned = strategy
+        .and_then(|s| s.min_wave_size)
+        .map_or(MIN_WAVE_SIZE, |size| size as usize);
+    let scale = (100 - handicap.aggression_bias_percent).max(0) as f32 / 100.0;
+    ((planned as f32 * scale).round() as usize).max(1)
+}
+
+/// Seconds between waves for an AI with `strategy` and `handicap`,
+/// including the time it takes to notice its next wave is ready.
+fn wave_interval(strategy: Option<&Strategy>, handicap: &Handicap) -> f32 {
+    let planned = strategy.map_or(WAVE_INTERVAL, |s| seconds(s.attack_interval));
+    planned * handicap.attack_delay_percent as f32 / 100.0
+        + seconds(u64::from(handicap.reaction_delay_ticks))
+}
+
+/// Faction unit ID for a unit named in a strategy: the generic `infantry`,
+/// `ranger` and `harvester`, or one of the faction's own unit IDs. None if
+/// the faction has no such unit.
+fn strategy_unit_id(
+    name: &str,
+    faction: FactionId,
+    registry: Option<&FactionRegistry>,
+) -> Option<String> {
+    let generic = match name {
+        "infantry" => Some(UnitType::Infantry),
+        "ranger" => Some(UnitType::Ranger),
+        "harvester" => Some(UnitType::Harvester),
+        _ => None,
+    };
+    match generic {
+        Some(unit_type) => Some(unit_type.to_unit_id(faction).to_string()),
+        None => registry
+            .and_then(|r| r.get(faction))
+            .and_then(|data| data.get_unit(name))
+            .map(|unit| unit.id.clone()),
+    }
+}
+
+/// Next unit a faction playing `strategy` should queue, by strategy name.
+///
+/// Units of the build order come first, in turn; its other items are
+/// passed over. Then harvesters up to the strategy's target, once a few
+/// combat units guard them, and combat units in the composition's
+/// proportions, least built for its weight first.
+fn next_strategy_unit(
+    strategy: &Strategy,
+    step: &mut usize,
+    queued: &HashMap<String, u32>,
+    harvesters: usize,
+    combat: usize,
+    buildable: impl Fn(&str) -> bool,
+) -> Option<String> {
+    while let Some(item) = strategy.build_order.get(*step) {
+        *step += 1;
+        if let BuildOrderItem::Unit(name) = item {
+            if buildable(name) {
+                return Some(name.clone());
+            }
+        }
+    }
+
+    let target_harvesters = strategy.economy.target_harvesters as usize;
+    if harvesters < target_harvesters && (harvesters == 0 || combat >= 3) {
+        return Some("harvester".to_string());
+    }
+    if combat >= MAX_STRATEGY_ARMY {
+        return None;
+    }
+
+    let mut candidates: Vec<(&String, f64)> = strategy
+        .composition
+        .iter()
+        .filter(|(name, weight)| *name != "harvester" && **weight > 0 && buildable(name))
+        .map(|(name, weight)| {
+            let built = f64::from(queued.get(name).copied().unwrap_or(0));
+            (name, built / f64::from(*weight))
+        })
+        .collect();
+    // Sorted by name first so ties break the same way every game
+    candidates.sort_by(|a, b| a.0.cmp(b.0));
+    candidates
+        .into_iter()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(name, _)| name.clone())
+}
+
 /// Rally point offset from depot (horizontal).
 const RALLY_OFFSET_X: f32 = 100.0;
 
@@ -132,22 +319,34 @@ impl Default for AiFaction {
 fn ai_production(
     time: Res<Time>,
     mut ai_state: ResMut<AiState>,
+    handicaps: Res<AiHandicaps>,
+    strategies: Res<AiStrategies>,
+    registry: Option<Res<FactionRegistry>>,
     player_faction: Res<PlayerFaction>,
     mut ai_depots: Query<(&GameFaction, &mut GameProductionQueue), With<GameDepot>>,
     harvesters: Query<&GameFaction, With<GameHarvester>>,
     combat_units: Query<&GameFaction, With<CombatStats>>,
 ) {
-    ai_state.production_timer += time.delta_seconds();
-
-    // Only check every 2 seconds
-    if ai_state.production_timer < 2.0 {
-        return;
+    let mut due = HashSet::new();
+    for (faction, _) in ai_depots.iter() {
+        if faction.faction == player_faction.faction || due.contains(&faction.faction) {
+            continue;
+        }
+        let timer = ai_state
+            .production_timers
+            .entry(faction.faction)
+            .or_default();
+        *timer += time.delta_seconds();
+        // Only check every couple of seconds, scaled by the handicap
+        if *timer >= production_interval(&handicaps.get(faction.faction)) {
+            *timer = 0.0;
+            due.insert(faction.faction);
+        }
     }
-    ai_state.production_timer = 0.0;
 
     for (faction, mut production) in ai_depots.iter_mut() {
-        // Skip player faction
-        if faction.faction == player_faction.faction {
+        // Skip player faction and factions not yet due
+        if !due.contains(&faction.faction) {
             continue;
         }
 
@@ -166,6 +365,40 @@ fn ai_production(
             .filter(|f| f.faction == faction.faction)
             .count();
 
+        if let Some(strategy) = strategies.get(faction.faction) {
+            let registry = registry.as_deref();
+            let buildable =
+                |name: &str| strategy_unit_id(name, faction.faction, registry).is_some();
+            let mut step = ai_state
+                .build_steps
+                .get(&faction.faction)
+                .copied()
+                .unwrap_or(0);
+            let queued = ai_state.queued.entry(faction.faction).or_default();
+            let next = next_strategy_unit(
+                strategy,
+                &mut step,
+                queued,
+                harvester_count,
+                combat_count,
+                buildable,
+            );
+            if let Some(name) = next {
+                if let Some(unit_id) = strategy_unit_id(&name, faction.faction, registry) {
+                    tracing::info!(
+                        "AI {:?} queued {} ({})",
+                        faction.faction,
+                        unit_id,
+                        strategy.name
+                    );
+                    production.enqueue(unit_id);
+                    *queued.entry(name).or_default() += 1;
+                }
+            }
+            ai_state.build_steps.insert(faction.faction, step);
+            continue;
+        }
+
         // Get faction-specific unit IDs
         let harvester_id = UnitType::Harvester.to_unit_id(faction.faction).to_string();
         let infantry_id = UnitType::Infantry.to_unit_id(faction.faction).to_string();
@@ -257,6 +490,8 @@ fn ai_harvester_assignment(
 fn ai_attack_orders(
     time: Res<Time>,
     mut ai_state: ResMut<AiState>,
+    handicaps: Res<AiHandicaps>,
+    strategies: Res<AiStrategies>,
     player_faction: Res<PlayerFaction>,
     mut commands: Commands,
     combat_units: Query<
@@ -270,11 +505,6 @@ fn ai_attack_orders(
     // Track total game time
     ai_state.game_time += time.delta_seconds();
 
-    // Don't attack during grace period
-    if ai_state.game_time < AI_GRACE_PERIOD {
-        return;
-    }
-
     ai_state.attack_timer += time.delta_seconds();
 
     // Only check every 5 seconds
@@ -316,7 +546,14 @@ fn ai_attack_orders(
     let mut faction_idle_units: HashMap<FactionId, Vec<Entity>> = HashMap::new();
 
     for (entity, faction, _, _) in combat_units.iter() {
-        if faction.faction == player_faction.faction {
+        // Don't attack during the grace period
+        if faction.faction == player_faction.faction
+            || ai_state.game_time
+                < grace_period(
+                    strategies.get(faction.faction),
+                    &handicaps.get(faction.faction),
+                )
+        {
             continue;
         }
         faction_idle_units
@@ -333,6 +570,8 @@ fn ai_attack_orders(
         let Some(wave_state) = ai_state.wave_state.get_mut(&faction_id) else {
             continue;
         };
+        let handicap = handicaps.get(faction_id);
+        let strategy = strategies.get(faction_id);
 
         // Clean up dead or despawned entities from rally_units
         wave_state.rally_units.retain(|&entity| {
@@ -358,7 +597,7 @@ fn ai_attack_orders(
         }
 
         // Check if we should launch a wave
-        let has_enough_units = wave_state.rally_units.len() >= MIN_WAVE_SIZE;
+        let has_enough_units = wave_state.rally_units.len() >= wave_size(strategy, &handicap);
         let wave_ready = current_game_time >= wave_state.next_wave_time;
 
         if has_enough_units && wave_ready {
@@ -397,18 +636,65 @@ fn ai_attack_orders(
 
                 // Update wave state
                 wave_state.wave_number += 1;
-                wave_state.next_wave_time = current_game_time + WAVE_INTERVAL;
+                wave_state.next_wave_time = current_game_time + wave_interval(strategy, &handicap);
                 wave_state.rally_units.clear();
             } else {
                 // No enemy found - update timer to retry later
                 // This prevents units from being stuck indefinitely
-                wave_state.next_wave_time = current_game_time + WAVE_INTERVAL;
+                wave_state.next_wave_time = current_game_time + wave_interval(strategy, &handicap);
                 tracing::debug!(
                     "AI {:?} wave ready but no enemy found, will retry in {}s",
                     faction_id,
-                    WAVE_INTERVAL
+                    wave_interval(strategy, &handicap)
                 );
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strategy_units_follow_build_order_then_composition() {
+        let strategy = Strategy::rush();
+        let mut step = 0;
+        let mut queued = HashMap::new();
+        let next = |step: &mut usize, queued: &HashMap<String, u32>, harvesters, combat| {
+            next_strategy_unit(&strategy, step, queued, harvesters, combat, |name| {
+                name != "tank"
+            })
+        };
+
+        // Rush opens with infantry, passing over its barracks
+        for _ in 0..5 {
+            assert_eq!(next(&mut step, &queued, 1, 0).as_deref(), Some("infantry"));
+        }
+
+        // Past its closing wait, a harvester once guarded, then the
+        // composition
+        assert_eq!(next(&mut step, &queued, 0, 5).as_deref(), Some("harvester"));
+        assert_eq!(step, strategy.build_order.len());
+        queued.insert("infantry".to_string(), 5);
+        assert_eq!(next(&mut step, &queued, 1, 5).as_deref(), Some("infantry"));
+        assert_eq!(next(&mut step, &queued, 1, MAX_STRATEGY_ARMY), None);
+    }
+
+    #[test]
+    fn strategy_timings_shape_waves() {
+        let mut strategy = Strategy::rush();
+        strategy.min_wave_size = Some(4);
+        let none = Handicap::NONE;
+        assert_eq!(wave_size(Some(&strategy), &none), 4);
+        assert_eq!(wave_size(None, &none), MIN_WAVE_SIZE);
+        assert!(
+            (wave_interval(Some(&strategy), &none) - seconds(strategy.attack_interval)).abs()
+                < f32::EPSILON
+        );
+        assert!(
+            grace_period(Some(&strategy), &Difficulty::Easy.handicap())
+                > grace_period(Some(&strategy), &none)
+        );
+    }
+}
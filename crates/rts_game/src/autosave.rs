@@ -0,0 +1,507 @@
+//! Periodic autosaves and resuming from them.
+//!
+//! The core simulation is saved whole, together with any commands still
+//! waiting in the [`CoreCommandBuffer`]. The Bevy world is not serialized;
+//! instead each entity mapped to the core is recorded as a [`SavedEntity`]
+//! and respawned from its bundle on resume, with position and health coming
+//! back from the core. Production queues, construction progress and AI
+//! plans are not saved and restart from scratch.
+
+use bevy::prelude::*;
+use rts_core::autosave::{Autosave, AutosaveConfig, AutosaveRotation};
+use rts_core::components::EntityId;
+use rts_core::factions::FactionId;
+use rts_core::simulation::TICK_RATE;
+use serde::{Deserialize, Serialize};
+
+use crate::bundles::{HarvesterBundle, ResourceNodeBundle, UnitBundle};
+use crate::components::{
+    Building, BuildingType, Collider, CoreEntityId, GameDebugName, GameDepot, GameFaction,
+    GameHarvester, GamePosition, GameResourceNode, ResourceNodeType, Stationary, UnderConstruction,
+    Unit, UnitDataId, UnitType,
+};
+use crate::construction::spawn_building;
+use crate::data_loader::{BevyUnitKindRegistry, FactionRegistry};
+use crate::economy::PlayerResources;
+use crate::simulation::{CoreCommandBuffer, CoreSimulation, CoreSimulationSet};
+
+/// Default ticks between autosaves (one minute of game time).
+pub const DEFAULT_AUTOSAVE_INTERVAL: u64 = 60 * TICK_RATE as u64;
+
+/// Default directory for client autosaves.
+pub const DEFAULT_AUTOSAVE_DIR: &str = "saves/autosave";
+
+/// Where and how often the client autosaves. `None` disables autosaving.
+#[derive(Resource, Debug, Clone)]
+pub struct AutosaveSettings {
+    /// Autosave configuration.
+    pub config: Option<AutosaveConfig>,
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        Self {
+            config: Some(AutosaveConfig::new(
+                DEFAULT_AUTOSAVE_DIR,
+                DEFAULT_AUTOSAVE_INTERVAL,
+            )),
+        }
+    }
+}
+
+/// Autosave to restore at startup instead of spawning a new match.
+#[derive(Resource, Debug)]
+pub struct ResumeFrom(pub Autosave);
+
+/// What a saved entity is, enough to pick the bundle that respawns it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SavedKind {
+    /// Combat unit, by faction data ID (`"unknown"` for generic units).
+    Unit {
+        /// Unit data ID from the faction RON files.
+        data_id: String,
+        /// Supply category, if the unit had one.
+        unit_type: Option<UnitType>,
+    },
+    /// Harvester.
+    Harvester,
+    /// Building, including depots.
+    Building {
+        /// Building type.
+        building_type: BuildingType,
+        /// Whether it was still being built (progress restarts).
+        under_construction: bool,
+    },
+    /// Resource node.
+    ResourceNode {
+        /// Permanent (degrading yield) rather than depletable.
+        permanent: bool,
+        /// Feedstock left in a temporary node.
+        remaining: i32,
+        /// Optimal harvester count of a permanent node.
+        optimal_harvesters: u8,
+    },
+    /// Impassable terrain feature.
+    Terrain {
+        /// Collider half-width.
+        half_width: f32,
+        /// Collider half-height.
+        half_height: f32,
+    },
+}
+
+/// One Bevy entity mapped to a core entity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedEntity {
+    /// Core entity ID.
+    pub core_id: EntityId,
+    /// Owning faction, if any.
+    pub faction: Option<FactionId>,
+    /// What to respawn.
+    pub kind: SavedKind,
+}
+
+/// Client state stored alongside the core simulation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientSave {
+    /// Respawnable entities.
+    pub entities: Vec<SavedEntity>,
+    /// Local player's resources.
+    pub resources: PlayerResources,
+}
+
+/// Plugin that writes rotating autosaves and restores a [`ResumeFrom`] save.
+pub struct AutosavePlugin;
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AutosaveSettings>()
+            .add_systems(
+                Startup,
+                restore_from_autosave.run_if(resource_exists::<ResumeFrom>),
+            )
+            .add_systems(
+                Update,
+                write_autosave
+                    .after(CoreSimulationSet::Tick)
+                    .before(CoreSimulationSet::SyncOut),
+            );
+    }
+}
+
+/// Components read to record a [`SavedEntity`].
+pub type SavedEntityQuery<'a> = (
+    &'a CoreEntityId,
+    Option<&'a GameFaction>,
+    Option<&'a GameDepot>,
+    Option<&'a Building>,
+    Option<&'a UnderConstruction>,
+    Option<&'a GameHarvester>,
+    Option<&'a GameResourceNode>,
+    Option<&'a UnitDataId>,
+    Option<&'a Unit>,
+    Option<&'a Collider>,
+);
+
+fn write_autosave(
+    settings: Res<AutosaveSettings>,
+    mut rotation: Local<Option<AutosaveRotation>>,
+    core: Res<CoreSimulation>,
+    core_commands: Res<CoreCommandBuffer>,
+    resources: Option<Res<PlayerResources>>,
+    entities: Query<SavedEntityQuery>,
+) {
+    let Some(config) = settings.config.as_ref() else {
+        return;
+    };
+    if rotation.as_ref().map_or(true, |r| r.config() != config) {
+        *rotation = Some(AutosaveRotation::new(config.clone()));
+    }
+    let Some(rotation) = rotation.as_mut() else {
+        return;
+    };
+    if !rotation.is_due(core.sim.get_tick()) {
+        return;
+    }
+
+    let result = capture_client_save(
+        "client",
+        &core,
+        &core_commands,
+        resources.as_deref(),
+        &entities,
+    )
+    .and_then(|save| rotation.write(&save));
+    match result {
+        Ok(path) => tracing::info!("Autosaved to {}", path.display()),
+        Err(e) => tracing::warn!("Autosave failed: {e}"),
+    }
+}
+
+/// Capture the core simulation, pending commands and every mapped entity
+/// as an [`Autosave`] tagged with `host`.
+///
+/// # Errors
+///
+/// Returns an error if the simulation or client state cannot be serialized.
+pub fn capture_client_save(
+    host: &str,
+    core: &CoreSimulation,
+    core_commands: &CoreCommandBuffer,
+    resources: Option<&PlayerResources>,
+    entities: &Query<SavedEntityQuery>,
+) -> rts_core::error::Result<Autosave> {
+    let mut saved: Vec<SavedEntity> = entities.iter().filter_map(saved_entity).collect();
+    saved.sort_by_key(|e| e.core_id);
+    let client = ClientSave {
+        entities: saved,
+        resources: resources.cloned().unwrap_or_default(),
+    };
+
+    Autosave::capture(host, &core.sim)
+        .map(|save| save.with_backlog(core_commands.pending()))
+        .and_then(|save| save.with_host_state(&client))
+}
+
+fn saved_entity(
+    (
+        core_id,
+        faction,
+        depot,
+        building,
+        under_construction,
+        harvester,
+        node,
+        data_id,
+        unit,
+        collider,
+    ): (
+        &CoreEntityId,
+        Option<&GameFaction>,
+        Option<&GameDepot>,
+        Option<&Building>,
+        Option<&UnderConstruction>,
+        Option<&GameHarvester>,
+        Option<&GameResourceNode>,
+        Option<&UnitDataId>,
+        Option<&Unit>,
+        Option<&Collider>,
+    ),
+) -> Option<SavedEntity> {
+    let kind = if depot.is_some() {
+        SavedKind::Building {
+            building_type: BuildingType::Depot,
+            under_construction: false,
+        }
+    } else if let Some(building) = building {
+        SavedKind::Building {
+            building_type: building.building_type,
+            under_construction: under_construction.is_some(),
+        }
+    } else if harvester.is_some() {
+        SavedKind::Harvester
+    } else if let Some(node) = node {
+        SavedKind::ResourceNode {
+            permanent: node.node_type == ResourceNodeType::Permanent,
+            remaining: node.remaining,
+            optimal_harvesters: node.optimal_harvesters,
+        }
+    } else if let Some(data_id) = data_id {
+        SavedKind::Unit {
+            data_id: data_id.0.clone(),
+            unit_type: unit.map(|u| u.unit_type),
+        }
+    } else if let Some(collider) = collider {
+        SavedKind::Terrain {
+            half_width: collider.half_width,
+            half_height: collider.half_height,
+        }
+    } else {
+        return None;
+    };
+
+    Some(SavedEntity {
+        core_id: core_id.0,
+        faction: faction.map(|f| f.faction),
+        kind,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn restore_from_autosave(
+    mut commands: Commands,
+    resume: Res<ResumeFrom>,
+    mut core: ResMut<CoreSimulation>,
+    mut core_commands: ResMut<CoreCommandBuffer>,
+    resources: Option<ResMut<PlayerResources>>,
+    faction_registry: Res<FactionRegistry>,
+    unit_kind_registry: Res<BevyUnitKindRegistry>,
+) {
+    let save = &resume.0;
+    match resume_client_save(
+        &mut commands,
+        save,
+        &mut core,
+        &mut core_commands,
+        resources.map(ResMut::into_inner),
+        &faction_registry,
+        &unit_kind_registry,
+    ) {
+        Ok(entities) => tracing::info!(
+            "Resumed from autosave at tick {} ({} entities)",
+            save.tick,
+            entities.len()
+        ),
+        Err(e) => tracing::error!("Cannot resume from autosave: {e}"),
+    }
+}
+
+/// Restore a save made by [`capture_client_save`]: swap in its simulation,
+/// respawn its entities from their bundles and requeue its pending
+/// commands. Returns the core ID and new Bevy entity of each respawned
+/// entity.
+///
+/// Mirrors of the simulation being replaced must be despawned by the
+/// caller; [`CoreSimulation::replace_sim`] unmaps them.
+///
+/// # Errors
+///
+/// Returns an error if the simulation or client state cannot be read, in
+/// which case nothing is changed.
+pub fn resume_client_save(
+    commands: &mut Commands,
+    save: &Autosave,
+    core: &mut CoreSimulation,
+    core_commands: &mut CoreCommandBuffer,
+    resources: Option<&mut PlayerResources>,
+    factions: &FactionRegistry,
+    unit_kinds: &BevyUnitKindRegistry,
+) -> rts_core::error::Result<Vec<(EntityId, Entity)>> {
+    let sim = save.restore_simulation()?;
+    let client = save.host_state::<ClientSave>()?;
+
+    core.replace_sim(sim);
+    if let Some(resources) = resources {
+        *resources = client.resources;
+    }
+
+    let mut respawned = Vec::with_capacity(client.entities.len());
+    for saved in &client.entities {
+        let Some(core_position) = core
+            .sim
+            .get_entity(saved.core_id)
+            .and_then(|e| e.position)
+            .map(|p| p.value)
+        else {
+            continue;
+        };
+        let position = Vec2::new(core_position.x.to_num(), core_position.y.to_num());
+        // Units and buildings always have a faction; nodes and terrain ignore it.
+        let faction = saved.faction.unwrap_or(FactionId::Continuity);
+
+        let entity = match &saved.kind {
+            SavedKind::Unit { data_id, unit_type } => {
+                let unit_data = factions
+                    .get(faction)
+                    .and_then(|data| data.get_unit(data_id));
+                let bundle = match unit_data {
+                    Some(unit_data) => {
+                        let kind_id = unit_kinds
+                            .find(faction, data_id)
+                            .unwrap_or(rts_core::unit_kind::UnitKindId::NONE);
+                        UnitBundle::from_data(position, faction, unit_data, kind_id)
+                    }
+                    // Health is overwritten from the core on the next sync.
+                    None => UnitBundle::new(position, faction, 100),
+                };
+                let mut entity = commands.spawn(bundle);
+                if let Some(unit_type) = unit_type {
+                    entity.insert(Unit::new(*unit_type));
+                }
+                entity.id()
+            }
+            SavedKind::Harvester => commands.spawn(HarvesterBundle::new(position, faction)).id(),
+            SavedKind::Building {
+                building_type,
+                under_construction,
+            } => {
+                let entity = spawn_building(commands, *building_type, position, faction);
+                if !under_construction {
+                    commands.entity(entity).remove::<UnderConstruction>();
+                }
+                entity
+            }
+            SavedKind::ResourceNode {
+                permanent,
+                remaining,
+                optimal_harvesters,
+            } => {
+                let bundle = if *permanent {
+                    ResourceNodeBundle::permanent(position, *optimal_harvesters)
+                } else {
+                    ResourceNodeBundle::temporary(position, *remaining)
+                };
+                commands.spawn(bundle).id()
+            }
+            SavedKind::Terrain {
+                half_width,
+                half_height,
+            } => {
+                let size = Vec2::new(half_width * 2.0, half_height * 2.0);
+                commands
+                    .spawn((
+                        SpriteBundle {
+                            sprite: Sprite {
+                                color: Color::srgb(0.3, 0.28, 0.25),
+                                custom_size: Some(size),
+                                ..default()
+                            },
+                            transform: Transform::from_translation(position.extend(-0.8)),
+                            ..default()
+                        },
+                        GamePosition::new(core_position),
+                        Collider::new(size.x, size.y),
+                        Stationary,
+                    ))
+                    .id()
+            }
+        };
+
+        // Pre-assigning the core ID keeps the spawn sync from creating a
+        // duplicate core entity.
+        commands.entity(entity).insert(CoreEntityId(saved.core_id));
+        if let Some(name) = core
+            .sim
+            .get_entity(saved.core_id)
+            .and_then(|e| e.row.debug_name.as_ref())
+        {
+            commands
+                .entity(entity)
+                .insert(GameDebugName(name.0.clone()));
+        }
+        core.register_entity(entity, saved.core_id);
+        respawned.push((saved.core_id, entity));
+    }
+
+    core_commands.clear();
+    for pending in &save.command_backlog {
+        if pending.queued {
+            core_commands.queue(pending.entity, pending.command.clone());
+        } else {
+            core_commands.set(pending.entity, pending.command.clone());
+        }
+    }
+
+    Ok(respawned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bundles::DepotBundle;
+    use crate::simulation::SimulationPlugin;
+
+    fn test_app(dir: &std::path::Path) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins((SimulationPlugin, AutosavePlugin));
+        app.init_resource::<FactionRegistry>()
+            .init_resource::<BevyUnitKindRegistry>();
+        app.insert_resource(AutosaveSettings {
+            config: Some(AutosaveConfig::new(dir, 1)),
+        });
+        app
+    }
+
+    #[test]
+    fn autosave_restores_entities_without_duplicates() {
+        let dir = std::env::temp_dir().join(format!("rts_client_autosave_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut app = test_app(&dir);
+        app.world_mut().spawn(DepotBundle::new(
+            Vec2::new(-100.0, 0.0),
+            FactionId::Continuity,
+        ));
+        app.world_mut().spawn(UnitBundle::new(
+            Vec2::new(40.0, 10.0),
+            FactionId::Collegium,
+            80,
+        ));
+        app.update();
+
+        // Advance the core by hand so the next update sees a due tick.
+        app.world_mut().resource_mut::<CoreSimulation>().sim.tick();
+        app.update();
+        let original = &app.world().resource::<CoreSimulation>().sim;
+        let (original_tick, original_hash) = (original.get_tick(), original.state_hash());
+
+        let path = rts_core::autosave::latest_autosave(&dir, None)
+            .unwrap()
+            .unwrap();
+        let save = Autosave::load(path).unwrap();
+        assert_eq!(save.tick, original_tick);
+
+        let mut resumed = test_app(&dir);
+        resumed.insert_resource(AutosaveSettings { config: None });
+        resumed.insert_resource(ResumeFrom(save));
+        resumed.update();
+
+        let core = resumed.world().resource::<CoreSimulation>();
+        assert_eq!(core.sim.entities().len(), 2);
+        assert_eq!(core.sim.state_hash(), original_hash);
+
+        let mut restored = resumed.world_mut().query::<(&CoreEntityId, &GameFaction)>();
+        let mut factions: Vec<_> = restored
+            .iter(resumed.world())
+            .map(|(id, faction)| (id.0, faction.faction))
+            .collect();
+        factions.sort_by_key(|(id, _)| *id);
+        assert_eq!(
+            factions,
+            vec![(1, FactionId::Continuity), (2, FactionId::Collegium)]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -57,6 +57,14 @@ impl Default for CameraSettings {
 #[derive(Component)]
 pub struct MainCamera;
 
+/// World-space rectangle the camera shows in a viewport of
+/// `viewport_size` pixels, taking zoom into account.
+#[must_use]
+pub fn camera_view_rect(camera_transform: &Transform, viewport_size: Vec2) -> Rect {
+    let half_extent = viewport_size * camera_transform.scale.truncate() / 2.0;
+    Rect::from_center_half_size(camera_transform.translation.truncate(), half_extent)
+}
+
 /// Spawns the main 2D camera.
 fn spawn_camera(mut commands: Commands) {
     commands.spawn((Camera2dBundle::default(), MainCamera));
@@ -169,3 +177,18 @@ fn camera_edge_scroll(
         transform.translation.y += delta.y;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn camera_view_rect_scales_with_zoom() {
+        let transform = Transform::from_xyz(100.0, -50.0, 0.0).with_scale(Vec3::splat(2.0));
+
+        let view = camera_view_rect(&transform, Vec2::new(800.0, 600.0));
+
+        assert_eq!(view.center(), Vec2::new(100.0, -50.0));
+        assert_eq!(view.size(), Vec2::new(1600.0, 1200.0));
+    }
+}
@@ -0,0 +1,398 @@
+//! Chat plugin for in-game messages and map pings.
+//!
+//! Shows chat from the other players as a log above the minimap that fades
+//! once it goes quiet, and marks their map pings on the minimap. Enter
+//! opens the chat box for everyone, Shift+Enter for the team; Alt+click on
+//! the minimap pings the team. Messages and pings the player sends go out
+//! as [`ChatSent`] and [`MapPingSent`] for the connection to relay; the
+//! server echoes them back to the sender along with everyone else.
+
+use std::collections::VecDeque;
+
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiSet};
+
+use crate::minimap::{MinimapPings, MinimapSettings};
+
+/// Chat lines kept in the log.
+pub const MAX_CHAT_LINES: usize = 50;
+
+/// Plugin for chat and map pings.
+///
+/// Provides:
+/// - A chat log that fades after a quiet spell
+/// - Enter / Shift+Enter to chat with everyone / the team
+/// - Received map pings on the minimap, colored by kind
+pub struct ChatPlugin;
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChatLog>()
+            .init_resource::<ChatSettings>()
+            .init_resource::<ChatInput>()
+            .init_resource::<MinimapSettings>()
+            .init_resource::<MinimapPings>()
+            .add_event::<ChatReceived>()
+            .add_event::<MapPingReceived>()
+            .add_event::<ChatSent>()
+            .add_event::<MapPingSent>()
+            .add_systems(PreUpdate, handle_chat_keys.after(InputSystem))
+            .add_systems(Update, receive_chat)
+            .add_systems(Update, ui_chat.after(EguiSet::InitContexts));
+    }
+}
+
+/// Who a message or ping is for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChatChannel {
+    /// Everyone in the game, spectators included.
+    #[default]
+    All,
+    /// The player's team only.
+    Team,
+}
+
+/// What a map ping asks for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PingKind {
+    /// Look here.
+    #[default]
+    Alert,
+    /// Attack here.
+    Attack,
+    /// Defend here.
+    Defend,
+    /// Gather here.
+    Rally,
+}
+
+impl PingKind {
+    /// Minimap marker color.
+    pub fn color(self) -> egui::Color32 {
+        match self {
+            Self::Alert => egui::Color32::from_rgb(255, 220, 60),
+            Self::Attack => egui::Color32::from_rgb(255, 60, 60),
+            Self::Defend => egui::Color32::from_rgb(80, 160, 255),
+            Self::Rally => egui::Color32::from_rgb(80, 230, 120),
+        }
+    }
+}
+
+/// A chat message from the server.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct ChatReceived {
+    /// Player or spectator who sent it.
+    pub from: String,
+    /// Who it was sent to.
+    pub channel: ChatChannel,
+    /// Message text.
+    pub text: String,
+}
+
+/// A map ping from the server.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct MapPingReceived {
+    /// Player or spectator who pinged.
+    pub from: String,
+    /// Who it was sent to.
+    pub channel: ChatChannel,
+    /// World position pinged.
+    pub position: Vec2,
+    /// What the ping asks for.
+    pub kind: PingKind,
+}
+
+/// A chat message the player typed, to send to the server.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct ChatSent {
+    /// Who it is for.
+    pub channel: ChatChannel,
+    /// Message text.
+    pub text: String,
+}
+
+/// A map ping the player made, to send to the server.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct MapPingSent {
+    /// Who it is for.
+    pub channel: ChatChannel,
+    /// World position pinged.
+    pub position: Vec2,
+    /// What the ping asks for.
+    pub kind: PingKind,
+}
+
+/// One line in the chat log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatLine {
+    /// Who said it.
+    pub from: String,
+    /// Who it was sent to.
+    pub channel: ChatChannel,
+    /// What was said, or a description of a ping.
+    pub text: String,
+}
+
+/// Recent chat, oldest first.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ChatLog {
+    /// Lines, at most [`MAX_CHAT_LINES`].
+    pub lines: VecDeque<ChatLine>,
+    /// Seconds since the last line arrived.
+    pub quiet_for: f32,
+}
+
+impl ChatLog {
+    /// Add a line, dropping the oldest past [`MAX_CHAT_LINES`].
+    pub fn push(&mut self, line: ChatLine) {
+        if self.lines.len() == MAX_CHAT_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+        self.quiet_for = 0.0;
+    }
+}
+
+/// Chat display settings.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct ChatSettings {
+    /// Lines shown while the chat box is closed.
+    pub visible_lines: usize,
+    /// Seconds of quiet before the log hides.
+    pub fade_after: f32,
+}
+
+impl Default for ChatSettings {
+    fn default() -> Self {
+        Self {
+            visible_lines: 6,
+            fade_after: 10.0,
+        }
+    }
+}
+
+/// The chat box, while the player is typing.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ChatInput {
+    /// Channel being typed to; None while the box is closed.
+    pub channel: Option<ChatChannel>,
+    /// Text typed so far.
+    pub text: String,
+}
+
+/// Logs incoming chat and marks incoming pings on the minimap.
+fn receive_chat(
+    time: Res<Time>,
+    settings: Res<MinimapSettings>,
+    mut log: ResMut<ChatLog>,
+    mut pings: ResMut<MinimapPings>,
+    mut messages: EventReader<ChatReceived>,
+    mut map_pings: EventReader<MapPingReceived>,
+) {
+    log.quiet_for += time.delta_seconds();
+    for message in messages.read() {
+        log.push(ChatLine {
+            from: message.from.clone(),
+            channel: message.channel,
+            text: message.text.clone(),
+        });
+    }
+    for ping in map_pings.read() {
+        pings.ping_with_color(
+            ping.position,
+            ping.kind.color(),
+            settings.ping_duration,
+            settings.ping_merge_radius,
+        );
+        log.push(ChatLine {
+            from: ping.from.clone(),
+            channel: ping.channel,
+            text: format!("pinged {:?}", ping.kind).to_lowercase(),
+        });
+    }
+}
+
+/// Opens, sends and closes the chat box. While it is open the keyboard is
+/// cleared before any other system reads it, so typing does not trigger
+/// hotkeys.
+fn handle_chat_keys(
+    mut input: ResMut<ChatInput>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut sent: EventWriter<ChatSent>,
+) {
+    let shift = keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight);
+    let enter = keyboard.just_pressed(KeyCode::Enter);
+    let escape = keyboard.just_pressed(KeyCode::Escape);
+
+    if input.channel.is_none() {
+        if !enter {
+            return;
+        }
+        input.channel = Some(if shift {
+            ChatChannel::Team
+        } else {
+            ChatChannel::All
+        });
+        input.text.clear();
+    } else if escape {
+        input.channel = None;
+    } else if enter {
+        if let Some(channel) = input.channel.take() {
+            let text = input.text.trim();
+            if !text.is_empty() {
+                sent.send(ChatSent {
+                    channel,
+                    text: text.to_string(),
+                });
+            }
+        }
+    }
+    keyboard.reset_all();
+}
+
+/// Renders the chat log and chat box above the minimap.
+fn ui_chat(
+    mut contexts: EguiContexts,
+    log: Res<ChatLog>,
+    settings: Res<ChatSettings>,
+    minimap: Res<MinimapSettings>,
+    mut input: ResMut<ChatInput>,
+) {
+    let typing = input.channel.is_some();
+    if !typing && (log.lines.is_empty() || log.quiet_for > settings.fade_after) {
+        return;
+    }
+    let Some(ctx) = contexts.try_ctx_mut() else {
+        return;
+    };
+
+    egui::Area::new(egui::Id::new("chat"))
+        .anchor(egui::Align2::LEFT_BOTTOM, [10.0, -(minimap.size + 40.0)])
+        .show(ctx, |ui| {
+            let shown = if typing {
+                log.lines.len()
+            } else {
+                settings.visible_lines
+            };
+            let skip = log.lines.len().saturating_sub(shown);
+            for line in log.lines.iter().skip(skip) {
+                let (prefix, color) = match line.channel {
+                    ChatChannel::All => ("", egui::Color32::WHITE),
+                    ChatChannel::Team => ("[team] ", egui::Color32::LIGHT_GREEN),
+                };
+                ui.label(
+                    egui::RichText::new(format!("{prefix}{}: {}", line.from, line.text))
+                        .color(color),
+                );
+            }
+
+            if let Some(channel) = input.channel {
+                let hint = match channel {
+                    ChatChannel::All => "To everyone",
+                    ChatChannel::Team => "To team",
+                };
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut input.text)
+                        .hint_text(hint)
+                        .desired_width(280.0),
+                );
+                response.request_focus();
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pings_reach_minimap_and_log() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ChatLog>()
+            .init_resource::<MinimapSettings>()
+            .init_resource::<MinimapPings>()
+            .add_event::<ChatReceived>()
+            .add_event::<MapPingReceived>()
+            .add_systems(Update, receive_chat);
+
+        app.world_mut().send_event(ChatReceived {
+            from: "bob".to_string(),
+            channel: ChatChannel::Team,
+            text: "expanding".to_string(),
+        });
+        app.world_mut().send_event(MapPingReceived {
+            from: "bob".to_string(),
+            channel: ChatChannel::Team,
+            position: Vec2::new(300.0, -200.0),
+            kind: PingKind::Defend,
+        });
+        app.update();
+
+        let log = app.world().resource::<ChatLog>();
+        let texts: Vec<_> = log.lines.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(texts, vec!["expanding", "pinged defend"]);
+        let pings = &app.world().resource::<MinimapPings>().pings;
+        assert_eq!(pings.len(), 1);
+        assert_eq!(pings[0].position, Vec2::new(300.0, -200.0));
+        assert_eq!(pings[0].color, PingKind::Defend.color());
+    }
+
+    #[test]
+    fn chat_box_swallows_hotkeys_and_sends_on_enter() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ChatInput>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .add_event::<ChatSent>()
+            .add_systems(Update, handle_chat_keys);
+        let press = |app: &mut App, keys: &[KeyCode]| {
+            let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+            keyboard.clear();
+            for &key in keys {
+                keyboard.press(key);
+            }
+            app.update();
+        };
+
+        press(&mut app, &[KeyCode::ShiftLeft, KeyCode::Enter]);
+        assert_eq!(
+            app.world().resource::<ChatInput>().channel,
+            Some(ChatChannel::Team)
+        );
+        press(&mut app, &[KeyCode::KeyA]);
+        let keyboard = app.world().resource::<ButtonInput<KeyCode>>();
+        assert!(!keyboard.pressed(KeyCode::KeyA), "hotkeys are swallowed");
+
+        app.world_mut().resource_mut::<ChatInput>().text = " gg ".to_string();
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!(app.world().resource::<ChatInput>().channel, None);
+        let sent: Vec<_> = app
+            .world_mut()
+            .resource_mut::<Events<ChatSent>>()
+            .drain()
+            .collect();
+        assert_eq!(
+            sent,
+            vec![ChatSent {
+                channel: ChatChannel::Team,
+                text: "gg".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn chat_log_keeps_the_newest_lines() {
+        let mut log = ChatLog::default();
+        for i in 0..=MAX_CHAT_LINES {
+            log.push(ChatLine {
+                from: "alice".to_string(),
+                channel: ChatChannel::All,
+                text: i.to_string(),
+            });
+        }
+        assert_eq!(log.lines.len(), MAX_CHAT_LINES);
+        assert_eq!(log.lines[0].text, "1");
+    }
+}
@@ -3,10 +3,11 @@
 use bevy::prelude::*;
 
 use crate::components::{
-    Armor, ArmorType, AttackTarget, CombatStats, DamageType, Dead, GameFaction, GameHealth,
-    GamePosition, MovementTarget, PlayerFaction, Regeneration, Unit,
+    Armor, ArmorType, AttackTarget, CombatStats, CoreEntityId, DamageType, Dead, GameDebugName,
+    GameFaction, GameHealth, GamePosition, MovementTarget, PlayerFaction, Regeneration, Unit,
 };
 use crate::economy::PlayerResources;
+use crate::simulation::CoreSimulation;
 
 /// Range at which units will auto-acquire targets.
 pub const AUTO_ATTACK_RANGE: f32 = 200.0;
@@ -113,10 +114,20 @@ fn regenerate_health(
 }
 
 /// Auto-acquire attack targets for idle units with combat capability.
+///
+/// Range is measured from the core's acquisition position, so idle
+/// wandering never pulls a unit into a fight.
 fn acquire_attack_targets(
     mut commands: Commands,
+    core: Option<Res<CoreSimulation>>,
     attackers: Query<
-        (Entity, &GamePosition, &GameFaction, &CombatStats),
+        (
+            Entity,
+            &GamePosition,
+            &GameFaction,
+            &CombatStats,
+            Option<&CoreEntityId>,
+        ),
         (
             Without<AttackTarget>,
             Without<MovementTarget>,
@@ -128,8 +139,15 @@ fn acquire_attack_targets(
         (Without<Dead>, Without<AttackTarget>),
     >,
 ) {
-    for (attacker_entity, attacker_pos, attacker_faction, stats) in attackers.iter() {
-        let my_pos = attacker_pos.as_vec2();
+    for (attacker_entity, attacker_pos, attacker_faction, stats, core_id) in attackers.iter() {
+        let my_pos = core
+            .as_ref()
+            .zip(core_id)
+            .and_then(|(core, id)| core.sim.acquisition_position(id.0))
+            .map_or_else(
+                || attacker_pos.as_vec2(),
+                |pos| Vec2::new(pos.x.to_num(), pos.y.to_num()),
+            );
         let mut closest_target: Option<(Entity, f32)> = None;
 
         // Find the closest enemy within auto-attack range
@@ -223,9 +241,9 @@ fn execute_attacks(
 /// Marks units with zero health as dead.
 fn process_deaths(
     mut commands: Commands,
-    dying: Query<(Entity, &GameHealth, &GamePosition), Without<Dead>>,
+    dying: Query<(Entity, &GameHealth, &GamePosition, Option<&GameDebugName>), Without<Dead>>,
 ) {
-    for (entity, health, position) in dying.iter() {
+    for (entity, health, position, debug_name) in dying.iter() {
         if health.current == 0 {
             commands.entity(entity).insert(Dead).insert(DeathTimer {
                 timer: DEATH_DESPAWN_DELAY,
@@ -244,7 +262,10 @@ fn process_deaths(
                     timer: DEATH_EFFECT_DURATION,
                 },
             ));
-            tracing::info!("Entity {:?} died", entity);
+            match debug_name {
+                Some(name) => tracing::info!("{} died", name.0),
+                None => tracing::info!("Entity {:?} died", entity),
+            }
         }
     }
 }
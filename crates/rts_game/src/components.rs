@@ -10,6 +10,7 @@ use rts_core::components::EntityId;
 use rts_core::factions::FactionId;
 use rts_core::math::Vec2Fixed;
 use rts_core::unit_kind::{UnitKindId, UnitRole};
+use serde::{Deserialize, Serialize};
 
 // ============================================================================
 // Core Component Wrappers
@@ -19,6 +20,13 @@ use rts_core::unit_kind::{UnitKindId, UnitRole};
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct CoreEntityId(pub EntityId);
 
+/// Debug label mirrored from the core entity (e.g. `"collegium/security_team#12"`).
+///
+/// Kept on the Bevy side so logs and the inspector can still name an
+/// entity after the simulation has removed it.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct GameDebugName(pub String);
+
 /// Wrapper for rts_core::Position that implements Bevy Component.
 ///
 /// This bridges the simulation's fixed-point positions to the render layer.
@@ -638,7 +646,7 @@ pub struct Dead;
 // ============================================================================
 
 /// Type of building that can be constructed.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BuildingType {
     /// Main base / HQ - produces harvesters, provides supply.
     Depot,
@@ -740,6 +748,51 @@ impl BuildingType {
     pub const fn can_produce_units(&self) -> bool {
         matches!(self, Self::Depot | Self::Barracks)
     }
+
+    /// Get the faction-specific building ID from RON data.
+    ///
+    /// Maps this enum variant to the faction building that fills the same
+    /// role, so its data (e.g. what it produces) can drive the UI.
+    #[must_use]
+    pub fn to_building_id(&self, faction: rts_core::factions::FactionId) -> &'static str {
+        use rts_core::factions::FactionId;
+        match (self, faction) {
+            // Continuity Authority buildings
+            (Self::Depot, FactionId::Continuity) => "administration_center",
+            (Self::Barracks, FactionId::Continuity) => "training_center",
+            (Self::SupplyDepot, FactionId::Continuity) => "processing_facility",
+            (Self::TechLab, FactionId::Continuity) => "research_institute",
+            (Self::Turret, FactionId::Continuity) => "defense_turret",
+
+            // Collegium buildings
+            (Self::Depot, FactionId::Collegium) => "assembly_core",
+            (Self::Barracks, FactionId::Collegium) => "fabricator_array",
+            (Self::SupplyDepot, FactionId::Collegium) => "micro_refinery",
+            (Self::TechLab, FactionId::Collegium) => "research_campus",
+            (Self::Turret, FactionId::Collegium) => "auto_turret",
+
+            // Tinkers' Union buildings
+            (Self::Depot, FactionId::Tinkers) => "workshop_hub",
+            (Self::Barracks, FactionId::Tinkers) => "mech_assembly",
+            (Self::SupplyDepot, FactionId::Tinkers) => "collection_point",
+            (Self::TechLab, FactionId::Tinkers) => "master_workshop",
+            (Self::Turret, FactionId::Tinkers) => "sentry_gun",
+
+            // Sculptors buildings
+            (Self::Depot, FactionId::BioSovereigns) => "harvester_bay",
+            (Self::Barracks, FactionId::BioSovereigns) => "studio",
+            (Self::SupplyDepot, FactionId::BioSovereigns) => "clinic",
+            (Self::TechLab, FactionId::BioSovereigns) => "archive",
+            (Self::Turret, FactionId::BioSovereigns) => "warden",
+
+            // Zephyr Guild buildings
+            (Self::Depot, FactionId::Zephyr) => "sky_platform",
+            (Self::Barracks, FactionId::Zephyr) => "landing_zone",
+            (Self::SupplyDepot, FactionId::Zephyr) => "sky_refinery",
+            (Self::TechLab, FactionId::Zephyr) => "navigation_tower",
+            (Self::Turret, FactionId::Zephyr) => "flak_balloon",
+        }
+    }
 }
 
 /// Component for buildings under construction.
@@ -776,6 +829,18 @@ impl UnderConstruction {
     }
 }
 
+/// Component for a building a worker was ordered to put up.
+///
+/// The core simulation tracks its progress: the site is handed to the
+/// core as a construction order rather than spawned outright.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstructionOrder {
+    /// The worker putting it up.
+    pub worker: EntityId,
+    /// Feedstock paid for it, part of which is refunded on cancel.
+    pub cost: u32,
+}
+
 /// Component marking what type of building this is.
 #[derive(Component, Debug, Clone, Copy)]
 pub struct Building {
@@ -808,6 +873,17 @@ impl FactionBuildings {
             .unwrap_or(false)
     }
 
+    /// Check if a faction has the building with a RON data ID, as used in
+    /// units' `tech_required` lists.
+    #[must_use]
+    pub fn has_building_id(&self, faction: FactionId, building_id: &str) -> bool {
+        self.buildings.get(&faction).is_some_and(|buildings| {
+            buildings
+                .iter()
+                .any(|b| b.to_building_id(faction) == building_id)
+        })
+    }
+
     /// Add a building to a faction's list.
     pub fn add_building(&mut self, faction: FactionId, building_type: BuildingType) {
         self.buildings
@@ -966,7 +1042,7 @@ impl Default for GameUnitKind {
 ///
 /// This enum provides backwards compatibility while we transition to
 /// fully data-driven unit spawning. Maps to faction-specific RON unit IDs.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum UnitType {
     /// Basic infantry unit.
     Infantry,
@@ -1188,6 +1264,14 @@ impl GameProductionQueue {
         self.queue.pop().map(|q| (q.unit_id, 1.0 - q.progress))
     }
 
+    /// Cancel the item at `index`, returning unit ID and partial refund progress.
+    pub fn cancel(&mut self, index: usize) -> Option<(String, f32)> {
+        (index < self.queue.len()).then(|| {
+            let q = self.queue.remove(index);
+            (q.unit_id, 1.0 - q.progress)
+        })
+    }
+
     /// Get the currently building unit (front of queue).
     #[must_use]
     pub fn current(&self) -> Option<&QueuedUnit> {
@@ -1,32 +1,51 @@
 //! Building construction system.
 //!
 //! Handles building placement, construction progress, and completion.
+//!
+//! Placed buildings are put up by a selected worker: the core simulation
+//! sends it to the site and tracks the work, and unfinished sites can be
+//! cancelled with Delete for a partial refund.
 
 use bevy::prelude::*;
 use bevy_egui::EguiContexts;
+use rts_core::buildings::BuildingFootprint;
+use rts_core::math::{Fixed, Vec2Fixed};
+use rts_core::replay::WorldEdit;
+use rts_core::simulation::{Simulation, TICK_RATE};
 
 use crate::bundles::{BarracksBundle, SupplyDepotBundle, TechLabBundle, TurretBundle};
 use crate::camera::MainCamera;
 use crate::components::{
-    Building, BuildingType, FactionBuildings, GameFaction, GameHealth, PlayerFaction,
-    UnderConstruction,
+    Building, BuildingType, ConstructionOrder, CoreEntityId, FactionBuildings, GameFaction,
+    GameHealth, PlayerFaction, Selected, UnderConstruction, Unit,
 };
 use crate::economy::PlayerResources;
+use crate::simulation::CoreSimulation;
 
 /// Plugin for building construction.
 pub struct ConstructionPlugin;
 
+/// Plugin for construction progress and building tracking only (no input
+/// or visuals), for headless runs.
+pub struct ConstructionProgressPlugin;
+
 /// Plugin for placement ghost visuals only (no gizmo dependencies).
 pub struct PlacementGhostPlugin;
 
-impl Plugin for ConstructionPlugin {
+impl Plugin for ConstructionProgressPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<FactionBuildings>()
-            .init_resource::<BuildingPlacement>()
-            .init_resource::<GhostEntity>()
             .add_systems(Update, construction_progress)
             .add_systems(Update, complete_construction.after(construction_progress))
-            .add_systems(Update, track_buildings)
+            .add_systems(Update, track_buildings);
+    }
+}
+
+impl Plugin for ConstructionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ConstructionProgressPlugin)
+            .init_resource::<BuildingPlacement>()
+            .init_resource::<GhostEntity>()
             .add_systems(Update, update_placement_preview)
             .add_systems(
                 Update,
@@ -36,7 +55,8 @@ impl Plugin for ConstructionPlugin {
                 Update,
                 update_placement_ghost_sprite.after(update_placement_preview),
             )
-            .add_systems(Update, building_hotkeys);
+            .add_systems(Update, building_hotkeys)
+            .add_systems(Update, cancel_construction);
     }
 }
 
@@ -74,14 +94,56 @@ pub struct BuildingGhost {
     pub building_type: BuildingType,
 }
 
+/// Cells a building covers and ticks of work it takes to put up.
+pub(crate) fn construction_plan(
+    sim: &Simulation,
+    building_type: BuildingType,
+) -> (BuildingFootprint, u32) {
+    let (width, height) = building_type.size();
+    let cell_size: f32 = sim.placement_grid().cell_size().to_num();
+    let cells = |size: f32| ((size / cell_size).ceil() as u32).max(1);
+    let build_time = (building_type.build_time() * TICK_RATE as f32).round() as u32;
+    (
+        BuildingFootprint::new(cells(width), cells(height)),
+        build_time.max(1),
+    )
+}
+
 /// Advances construction progress on buildings.
+///
+/// Buildings put up by workers mirror their progress from the core, which
+/// also owns their health.
 fn construction_progress(
     time: Res<Time>,
-    mut query: Query<(&mut UnderConstruction, &mut Sprite, &mut GameHealth)>,
+    core: Option<Res<CoreSimulation>>,
+    mut query: Query<(
+        &mut UnderConstruction,
+        &mut Sprite,
+        &mut GameHealth,
+        Option<&CoreEntityId>,
+        Has<ConstructionOrder>,
+    )>,
 ) {
     let dt = time.delta_seconds();
 
-    for (mut construction, mut sprite, mut health) in query.iter_mut() {
+    for (mut construction, mut sprite, mut health, core_id, ordered) in query.iter_mut() {
+        if ordered {
+            let building = core
+                .as_deref()
+                .zip(core_id)
+                .and_then(|(core, id)| core.sim.get_entity(id.0))
+                .and_then(|e| e.row.building.as_ref());
+            if let Some(building) = building {
+                construction.progress = if building.is_construction_complete() {
+                    1.0
+                } else {
+                    building.construction_progress as f32 / building.construction_total as f32
+                };
+            }
+            sprite.color = sprite.color.with_alpha(0.5 + construction.progress * 0.5);
+            continue;
+        }
+
         construction.advance(dt);
 
         // Visual feedback: buildings under construction are darker
@@ -183,6 +245,45 @@ pub fn spawn_building(
     }
 }
 
+/// Cancels the player's selected unfinished buildings on Delete, refunding
+/// the share of their cost not yet built.
+fn cancel_construction(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    player_faction: Res<PlayerFaction>,
+    mut resources: ResMut<PlayerResources>,
+    mut core: ResMut<CoreSimulation>,
+    sites: Query<
+        (Entity, &CoreEntityId, &GameFaction),
+        (
+            With<Selected>,
+            With<UnderConstruction>,
+            With<ConstructionOrder>,
+        ),
+    >,
+) {
+    if !keyboard.just_pressed(KeyCode::Delete) {
+        return;
+    }
+
+    for (entity, core_id, faction) in sites.iter() {
+        if faction.faction != player_faction.faction {
+            continue;
+        }
+        let tick = core.sim.get_tick();
+        match core.sim.cancel_construction(core_id.0) {
+            Ok(refund) => {
+                core.recorder
+                    .record_edit(tick, WorldEdit::CancelConstruction(core_id.0));
+                resources.feedstock += refund as i32;
+                commands.entity(entity).despawn_recursive();
+                tracing::info!("Cancelled construction, refunded {}", refund);
+            }
+            Err(e) => tracing::warn!("Cannot cancel construction: {}", e),
+        }
+    }
+}
+
 /// Updates the placement preview position based on mouse cursor.
 fn update_placement_preview(
     mut placement: ResMut<BuildingPlacement>,
@@ -218,6 +319,9 @@ fn update_placement_preview(
 }
 
 /// Handles mouse input for building placement.
+///
+/// A selected worker that can build is sent to put the building up.
+#[allow(clippy::too_many_arguments)]
 fn handle_placement_input(
     mut commands: Commands,
     mouse_button: Res<ButtonInput<MouseButton>>,
@@ -225,6 +329,8 @@ fn handle_placement_input(
     mut placement: ResMut<BuildingPlacement>,
     player_faction: Res<PlayerFaction>,
     mut resources: ResMut<PlayerResources>,
+    core: Res<CoreSimulation>,
+    workers: Query<&CoreEntityId, (With<Selected>, With<Unit>)>,
     mut egui_contexts: EguiContexts,
 ) {
     // Don't process clicks if egui is using the pointer (e.g., clicking UI)
@@ -257,6 +363,20 @@ fn handle_placement_input(
             placement.valid_placement,
         ) {
             let cost = building_type.cost();
+            let Some(worker) = workers
+                .iter()
+                .map(|id| id.0)
+                .find(|&id| core.sim.can_construct(id))
+            else {
+                tracing::warn!("Cannot place {:?}: no worker selected", building_type);
+                return;
+            };
+            let (footprint, _) = construction_plan(&core.sim, building_type);
+            let center = Vec2Fixed::new(Fixed::from_num(position.x), Fixed::from_num(position.y));
+            if let Err(e) = core.sim.check_construction_site(center, &footprint) {
+                tracing::warn!("Cannot place {:?}: {}", building_type, e);
+                return;
+            }
 
             // Check if we can afford it
             if resources.feedstock >= cost {
@@ -267,12 +387,19 @@ fn handle_placement_input(
                     cost
                 );
                 resources.feedstock -= cost;
-                spawn_building(
+                let entity = spawn_building(
                     &mut commands,
                     building_type,
                     position,
                     player_faction.faction,
                 );
+                commands.entity(entity).insert((
+                    UnderConstruction::new(building_type),
+                    ConstructionOrder {
+                        worker,
+                        cost: cost.max(0) as u32,
+                    },
+                ));
 
                 // Clear placement mode after placing
                 placement.placing = None;
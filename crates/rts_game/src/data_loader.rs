@@ -2,10 +2,16 @@
 //!
 //! Loads faction definitions from RON files and makes them available
 //! as Bevy resources. All validation happens at load time.
+//!
+//! While the game runs, edited faction files are revalidated and reloaded:
+//! a valid file replaces its faction's data and is reported as
+//! [`FactionDataReload::Reloaded`], an invalid one leaves the old data in
+//! place and is reported as [`FactionDataReload::Failed`].
 
 use std::collections::HashMap;
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use bevy::prelude::*;
 use rts_core::data::FactionData;
@@ -61,6 +67,12 @@ pub enum DataLoadError {
 /// Result type for data loading operations.
 pub type DataLoadResult<T> = Result<T, DataLoadError>;
 
+/// Directory the client loads faction data from.
+pub const FACTION_DATA_DIR: &str = "assets/data/factions";
+
+/// Seconds between checks for edited faction files.
+pub const RELOAD_POLL_SECONDS: f32 = 1.0;
+
 /// Registry containing all loaded faction data.
 ///
 /// This is a Bevy resource that holds all faction definitions.
@@ -93,6 +105,14 @@ impl FactionRegistry {
         Ok(())
     }
 
+    /// Register faction data, replacing any already registered for the
+    /// same faction.
+    ///
+    /// Returns the replaced data.
+    pub fn replace(&mut self, data: FactionData) -> Option<FactionData> {
+        self.factions.insert(data.id, data)
+    }
+
     /// Get faction data by ID.
     #[must_use]
     pub fn get(&self, id: FactionId) -> Option<&FactionData> {
@@ -273,24 +293,95 @@ pub fn load_factions_from_directory(dir: &Path) -> DataLoadResult<FactionRegistr
 
 /// Bevy plugin for loading faction data.
 ///
-/// Loads all faction data from `assets/data/factions/` at startup.
+/// Loads all faction data from [`FACTION_DATA_DIR`] at startup and reloads
+/// files edited while the game runs.
 pub struct FactionDataPlugin;
 
 impl Plugin for FactionDataPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<FactionRegistry>()
             .init_resource::<BevyUnitKindRegistry>()
-            .add_systems(PreStartup, load_faction_data);
+            .add_event::<FactionDataReload>()
+            .add_systems(PreStartup, load_faction_data)
+            .add_systems(PreUpdate, reload_edited_faction_data);
+    }
+}
+
+/// Outcome of reloading an edited faction file.
+#[derive(Event, Debug, Clone)]
+pub enum FactionDataReload {
+    /// The file was valid and replaced its faction's data.
+    Reloaded {
+        /// Faction whose data was replaced.
+        faction: FactionId,
+        /// The data it had before, if it was already loaded.
+        previous: Option<Box<FactionData>>,
+    },
+    /// The file failed to load; its faction keeps the data it had.
+    Failed {
+        /// Path to the file.
+        path: String,
+        /// Why it failed.
+        error: String,
+    },
+}
+
+/// Watches a faction data directory for edited RON files by polling their
+/// modification times.
+#[derive(Resource, Debug)]
+pub struct FactionDataWatcher {
+    /// Directory being watched.
+    dir: PathBuf,
+    /// Time until the next poll.
+    timer: Timer,
+    /// Last seen modification time of each file.
+    modified: HashMap<PathBuf, SystemTime>,
+}
+
+impl FactionDataWatcher {
+    /// Watch `dir`, treating the files already there as loaded.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let mut watcher = Self {
+            dir: dir.into(),
+            timer: Timer::from_seconds(RELOAD_POLL_SECONDS, TimerMode::Repeating),
+            modified: HashMap::new(),
+        };
+        watcher.changed_files();
+        watcher
+    }
+
+    /// Files created or modified since the last call, in path order.
+    pub fn changed_files(&mut self) -> Vec<PathBuf> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut changed = Vec::new();
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            if !path.extension().is_some_and(|ext| ext == "ron") {
+                continue;
+            }
+            let Ok(modified) = std::fs::metadata(&path).and_then(|meta| meta.modified()) else {
+                continue;
+            };
+            if self.modified.insert(path.clone(), modified) != Some(modified) {
+                changed.push(path);
+            }
+        }
+        changed.sort();
+        changed
     }
 }
 
 /// System that loads faction data at startup.
 fn load_faction_data(
+    mut commands: Commands,
     mut faction_registry: ResMut<FactionRegistry>,
     mut unit_kind_registry: ResMut<BevyUnitKindRegistry>,
 ) {
     // Determine the faction data directory
-    let faction_dir = Path::new("assets/data/factions");
+    let faction_dir = Path::new(FACTION_DATA_DIR);
+    commands.insert_resource(FactionDataWatcher::new(faction_dir));
 
     match load_factions_from_directory(faction_dir) {
         Ok(loaded_registry) => {
@@ -299,10 +390,7 @@ fn load_faction_data(
             // Build UnitKindRegistry from loaded factions
             // This creates the unified identity system mapping string IDs to numeric IDs
             for faction in faction_registry.all_factions() {
-                for unit in &faction.units {
-                    let role = UnitRole::from_tags(&unit.tags, unit.tier, unit.combat.is_some());
-                    unit_kind_registry.register(faction.id, &unit.id, role);
-                }
+                register_unit_kinds(&mut unit_kind_registry, faction);
             }
             tracing::info!(
                 "Registered {} unit kinds in UnitKindRegistry",
@@ -330,6 +418,58 @@ fn load_faction_data(
     }
 }
 
+/// Give every unit of `faction` a numeric kind ID. Kinds that already
+/// have one keep it.
+fn register_unit_kinds(registry: &mut UnitKindRegistry, faction: &FactionData) {
+    for unit in &faction.units {
+        let role = UnitRole::from_tags(&unit.tags, unit.tier, unit.combat.is_some());
+        registry.register(faction.id, &unit.id, role);
+    }
+}
+
+/// System that revalidates and reloads faction files edited since the
+/// last poll.
+///
+/// The registry is only touched when a file loads, so systems watching it
+/// for changes don't rerun on a failed edit.
+pub(crate) fn reload_edited_faction_data(
+    time: Res<Time>,
+    watcher: Option<ResMut<FactionDataWatcher>>,
+    mut faction_registry: ResMut<FactionRegistry>,
+    mut unit_kind_registry: ResMut<BevyUnitKindRegistry>,
+    mut reloads: EventWriter<FactionDataReload>,
+) {
+    let Some(mut watcher) = watcher else {
+        return;
+    };
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    for path in watcher.changed_files() {
+        match load_faction_from_file(&path) {
+            Ok(data) => {
+                let faction = data.id;
+                register_unit_kinds(&mut unit_kind_registry, &data);
+                let previous = faction_registry.replace(data).map(Box::new);
+                tracing::info!(
+                    "Reloaded faction {} from {}",
+                    faction.short_name(),
+                    path.display()
+                );
+                reloads.send(FactionDataReload::Reloaded { faction, previous });
+            }
+            Err(e) => {
+                tracing::error!("Failed to reload faction data: {}", e);
+                reloads.send(FactionDataReload::Failed {
+                    path: path.display().to_string(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+}
+
 /// Extension trait for accessing faction data from the Bevy world.
 pub trait FactionDataExt {
     /// Get faction data for a specific faction.
@@ -346,6 +486,8 @@ impl FactionDataExt for World {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rts_core::buildings::BuildingFootprint;
+    use rts_core::components::MovementDomain;
     use rts_core::data::{BuildingData, UnitData};
     use rts_core::math::Fixed;
 
@@ -359,20 +501,29 @@ mod tests {
                 name: "test".to_string(),
                 description: "test".to_string(),
                 cost: 50,
+                extra_cost: Default::default(),
                 build_time: 100,
                 health: 100,
                 speed: Fixed::from_num(5),
+                domain: MovementDomain::Ground,
                 combat: None,
+                weapons: vec![],
                 tech_required: vec![],
                 tier: 1,
                 produced_at: vec!["test_building".to_string()],
                 tags: vec![],
+                regen: None,
+                supply: None,
+                abilities: vec![],
+                cargo: None,
+                repair: None,
             }],
             buildings: vec![BuildingData {
                 id: "test_building".to_string(),
                 name: "test".to_string(),
                 description: "test".to_string(),
                 cost: 100,
+                extra_cost: Default::default(),
                 build_time: 200,
                 health: 500,
                 produces: vec!["test_unit".to_string()],
@@ -385,13 +536,20 @@ mod tests {
                 tags: vec![],
                 is_harvester: false,
                 is_main_base: false,
+                is_market: false,
+                supply_provided: 0,
+                footprint: BuildingFootprint::square(2),
             }],
             technologies: vec![],
             primary_color: [100, 100, 100],
             secondary_color: [200, 200, 200],
             starting_units: vec![],
             starting_buildings: vec![],
+            starting_presets: vec![],
             starting_feedstock: 500,
+            mechanics: vec![],
+            upkeep: Default::default(),
+            market: Default::default(),
         }
     }
 
@@ -447,6 +605,93 @@ mod tests {
         assert!(registry.validate_completeness().is_ok());
     }
 
+    /// Run the reload system as if a poll were due.
+    fn poll(world: &mut World) -> Vec<FactionDataReload> {
+        use bevy::ecs::system::RunSystemOnce;
+
+        world
+            .resource_mut::<Time>()
+            .advance_by(std::time::Duration::from_secs_f32(RELOAD_POLL_SECONDS));
+        world.run_system_once(reload_edited_faction_data);
+        world
+            .resource_mut::<Events<FactionDataReload>>()
+            .drain()
+            .collect()
+    }
+
+    /// Give `path` a modification time `secs` seconds after the epoch.
+    fn touch(path: &Path, secs: u64) {
+        let time = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_edited_files_reload_and_bad_edits_keep_old_data() {
+        let dir = std::env::temp_dir().join(format!("rts_client_reload_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("continuity.ron");
+        let original = std::fs::read_to_string("assets/data/factions/continuity.ron").unwrap();
+        std::fs::write(&path, &original).unwrap();
+        touch(&path, 1);
+
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Events<FactionDataReload>>();
+        world.init_resource::<BevyUnitKindRegistry>();
+        world.insert_resource(load_factions_from_directory(&dir).unwrap());
+        world.insert_resource(FactionDataWatcher::new(&dir));
+        let health = |world: &World| {
+            world
+                .resource::<FactionRegistry>()
+                .get(FactionId::Continuity)
+                .unwrap()
+                .get_unit("security_team")
+                .unwrap()
+                .health
+        };
+        let before = health(&world);
+
+        // Nothing reloads until a file changes
+        assert!(poll(&mut world).is_empty());
+
+        let edited = original.replacen(
+            &format!("health: {before},"),
+            &format!("health: {},", before + 25),
+            1,
+        );
+        assert_ne!(edited, original);
+        std::fs::write(&path, &edited).unwrap();
+        touch(&path, 2);
+        let reloads = poll(&mut world);
+        assert!(matches!(
+            reloads.as_slice(),
+            [FactionDataReload::Reloaded {
+                faction: FactionId::Continuity,
+                previous: Some(previous),
+            }] if previous.get_unit("security_team").unwrap().health == before
+        ));
+        assert_eq!(health(&world), before + 25);
+        assert!(!world.resource::<BevyUnitKindRegistry>().is_empty());
+
+        // A broken edit is reported and the reloaded data stays
+        std::fs::write(&path, "FactionData(").unwrap();
+        touch(&path, 3);
+        let reloads = poll(&mut world);
+        assert!(matches!(
+            reloads.as_slice(),
+            [FactionDataReload::Failed { .. }]
+        ));
+        assert_eq!(health(&world), before + 25);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_registry_iteration() {
         let mut registry = FactionRegistry::new();
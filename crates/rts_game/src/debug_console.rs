@@ -20,6 +20,7 @@ use crate::bundles::{
 };
 use crate::components::{GameFaction, GameHealth, GamePosition, PlayerFaction, Selected};
 use crate::economy::PlayerResources;
+use crate::simulation::GameSpeed;
 use crate::victory::GameState;
 
 /// Debug console plugin.
@@ -50,8 +51,6 @@ pub struct DebugConsoleState {
     pub output: Vec<(OutputLevel, String)>,
     /// God mode enabled.
     pub god_mode: bool,
-    /// Game speed multiplier.
-    pub game_speed: f32,
 }
 
 impl Default for DebugConsoleState {
@@ -63,7 +62,6 @@ impl Default for DebugConsoleState {
             history_index: None,
             output: Vec::new(),
             god_mode: false,
-            game_speed: 1.0, // Normal speed
         }
     }
 }
@@ -113,7 +111,7 @@ fn render_console(
     mut all_units: Query<(Entity, &mut GameHealth, &GameFaction)>,
     keyboard: Res<ButtonInput<KeyCode>>,
     mut game_state: ResMut<GameState>,
-    mut time: ResMut<Time<Virtual>>,
+    mut speed: ResMut<GameSpeed>,
 ) {
     if !state.visible {
         return;
@@ -141,7 +139,7 @@ fn render_console(
 
             // Status bar
             ui.horizontal(|ui| {
-                ui.label(format!("Speed: {:.1}x", state.game_speed));
+                ui.label(format!("Speed: {:.1}x", speed.multiplier()));
                 if state.god_mode {
                     ui.colored_label(egui::Color32::GOLD, "GOD MODE");
                 }
@@ -170,7 +168,7 @@ fn render_console(
                     &mut selected,
                     &mut all_units,
                     &mut game_state,
-                    &mut time,
+                    &mut speed,
                 );
 
                 state.input.clear();
@@ -209,7 +207,7 @@ fn render_console(
                 ui.label("teleport <x> <y> - Teleport selected units");
                 ui.label("god - Toggle god mode (invincibility)");
                 ui.label("resources <amount> - Set feedstock");
-                ui.label("speed <multiplier> - Set game speed (affects simulation)");
+                ui.label("speed <multiplier> - Set game speed, 0.5 to 8 (affects simulation)");
                 ui.label("win - Trigger victory");
                 ui.label("lose - Trigger defeat");
                 ui.label("clear - Clear console output");
@@ -228,7 +226,7 @@ fn execute_command(
     selected: &mut Query<(Entity, &mut GamePosition, &GameFaction), With<Selected>>,
     all_units: &mut Query<(Entity, &mut GameHealth, &GameFaction)>,
     game_state: &mut ResMut<GameState>,
-    time: &mut ResMut<Time<Virtual>>,
+    speed: &mut ResMut<GameSpeed>,
 ) {
     let parts: Vec<&str> = cmd.split_whitespace().collect();
     if parts.is_empty() {
@@ -398,7 +396,7 @@ fn execute_command(
 
         "speed" => {
             if args.is_empty() {
-                let current_speed = state.game_speed;
+                let current_speed = speed.multiplier();
                 state.output.push((
                     OutputLevel::Info,
                     format!("Current speed: {}x", current_speed),
@@ -406,16 +404,15 @@ fn execute_command(
                 return;
             }
 
-            let Ok(speed) = args[0].parse::<f32>() else {
+            let Ok(multiplier) = args[0].parse::<f32>() else {
                 state
                     .output
                     .push((OutputLevel::Error, "Invalid speed".to_string()));
                 return;
             };
 
-            let new_speed = speed.clamp(0.1, 10.0);
-            state.game_speed = new_speed;
-            time.set_relative_speed(new_speed);
+            speed.set_multiplier(multiplier);
+            let new_speed = speed.multiplier();
             state.output.push((
                 OutputLevel::Success,
                 format!("Game speed set to {}x", new_speed),
@@ -535,7 +532,7 @@ mod tests {
         assert!(state.history.is_empty());
         assert!(state.output.is_empty());
         assert!(!state.god_mode);
-        assert_eq!(state.game_speed, 1.0); // Normal speed
+        assert_eq!(GameSpeed::default().multiplier(), 1.0); // Normal speed
     }
 
     #[test]
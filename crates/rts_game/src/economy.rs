@@ -3,18 +3,27 @@
 //! Handles harvester AI, resource node depletion, and player resource updates.
 
 use bevy::prelude::*;
-
+use rts_core::abilities::AbilityUsed;
+use rts_core::economy::{PlayerEconomy, ResourceAmounts};
+use rts_core::repair::RepairEvent;
+use rts_core::salvage::SalvageEvent;
+use rts_core::triggers::{TriggerAction, TriggerFired};
+use serde::{Deserialize, Serialize};
+
+use crate::bundles::UnitBundle;
 use crate::components::{
     GameDepot, GameFaction, GameHarvester, GameHarvesterState, GamePosition, GameResourceNode,
-    MovementTarget, ResourceNodeType,
+    MovementTarget, PlayerFaction, ResourceNodeType,
 };
+use crate::data_loader::{BevyUnitKindRegistry, FactionRegistry};
+use crate::simulation::{CoreSimulation, CoreSimulationSet};
 
 /// Distance threshold for harvester interactions.
 pub const HARVEST_DISTANCE: f32 = 50.0;
 const HARVEST_DISTANCE_SQ: f32 = HARVEST_DISTANCE * HARVEST_DISTANCE;
 
 /// Player resources for the local player.
-#[derive(Resource, Debug, Clone)]
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerResources {
     /// Current feedstock amount.
     pub feedstock: i32,
@@ -24,6 +33,9 @@ pub struct PlayerResources {
     pub supply_used: i32,
     /// Maximum supply available.
     pub supply_cap: i32,
+    /// Exotics, cores and other resources beyond feedstock.
+    #[serde(default)]
+    pub reserves: ResourceAmounts,
 }
 
 impl Default for PlayerResources {
@@ -33,16 +45,37 @@ impl Default for PlayerResources {
             feedstock_cap: 10000, // Large cap, grows with buildings
             supply_used: 5,       // 3 infantry (1 each) + 1 harvester (2) = 5
             supply_cap: 10,       // From depot only, build Supply Depots for more
+            reserves: ResourceAmounts::new(),
         }
     }
 }
 
+impl PlayerResources {
+    /// These resources as an [`rts_core`] economy, which owns the supply rules.
+    #[must_use]
+    pub fn economy(&self) -> PlayerEconomy {
+        let mut economy = PlayerEconomy::new(self.feedstock, self.feedstock_cap)
+            .with_supply_provided(self.supply_cap.max(0) as u32)
+            .with_reserves(self.reserves.clone());
+        economy.supply_used = self.supply_used.max(0) as u32;
+        economy
+    }
+
+    /// Check if a unit taking `supply` fits under the supply cap.
+    #[must_use]
+    pub fn has_supply(&self, supply: i32) -> bool {
+        self.economy().has_supply(supply.max(0) as u32)
+    }
+}
+
 /// Plugin for the resource gathering economy.
 ///
 /// Handles:
 /// - Harvester AI (find nodes, gather, return to depot)
 /// - Resource node depletion
 /// - Player resource updates
+/// - Salvage collected from wrecks in the core simulation
+/// - Waves and grants from scenario triggers
 pub struct EconomyPlugin;
 
 impl Plugin for EconomyPlugin {
@@ -55,6 +88,16 @@ impl Plugin for EconomyPlugin {
                 harvester_gathering.after(count_harvesters_per_node),
                 update_node_visuals.after(harvester_gathering),
             ),
+        )
+        .add_systems(
+            Update,
+            (
+                collect_salvage,
+                pay_for_abilities,
+                pay_for_repairs,
+                carry_out_triggers,
+            )
+                .after(CoreSimulationSet::Tick),
         );
     }
 }
@@ -267,6 +310,132 @@ fn harvester_gathering(
     }
 }
 
+/// Pays the local player for salvage their units collected from wrecks.
+fn collect_salvage(
+    mut core: ResMut<CoreSimulation>,
+    player_faction: Option<Res<PlayerFaction>>,
+    mut resources: ResMut<PlayerResources>,
+) {
+    let events = std::mem::take(&mut core.salvage);
+    let Some(player_faction) = player_faction else {
+        return;
+    };
+    for event in events {
+        if let SalvageEvent::Collected {
+            faction: Some(faction),
+            amount,
+            ..
+        } = event
+        {
+            if faction == player_faction.faction {
+                let space = (resources.feedstock_cap - resources.feedstock).max(0);
+                resources.feedstock += (amount as i32).min(space);
+            }
+        }
+    }
+}
+
+/// Charges the local player for the abilities their units used.
+fn pay_for_abilities(
+    mut core: ResMut<CoreSimulation>,
+    player_faction: Option<Res<PlayerFaction>>,
+    mut resources: ResMut<PlayerResources>,
+) {
+    let used: Vec<AbilityUsed> = std::mem::take(&mut core.abilities);
+    let Some(player_faction) = player_faction else {
+        return;
+    };
+    let cost: i32 = used
+        .iter()
+        .filter(|u| u.faction == Some(player_faction.faction))
+        .map(|u| u.cost as i32)
+        .sum();
+    resources.feedstock = (resources.feedstock - cost).max(0);
+}
+
+/// Charges the local player for the repairs their units made.
+fn pay_for_repairs(
+    mut core: ResMut<CoreSimulation>,
+    player_faction: Option<Res<PlayerFaction>>,
+    mut resources: ResMut<PlayerResources>,
+) {
+    let repairs: Vec<RepairEvent> = std::mem::take(&mut core.repairs);
+    let Some(player_faction) = player_faction else {
+        return;
+    };
+    let cost: i32 = repairs
+        .iter()
+        .filter(|r| r.faction == Some(player_faction.faction))
+        .map(|r| r.cost as i32)
+        .sum();
+    resources.feedstock = (resources.feedstock - cost).max(0);
+}
+
+/// Carries out the actions of scenario triggers the core fired.
+///
+/// Waves are spawned here and reach the core through the usual spawn sync;
+/// only grants to the local player's faction change [`PlayerResources`].
+fn carry_out_triggers(
+    mut commands: Commands,
+    mut core: ResMut<CoreSimulation>,
+    player_faction: Option<Res<PlayerFaction>>,
+    mut resources: ResMut<PlayerResources>,
+    faction_registry: Option<Res<FactionRegistry>>,
+    unit_kind_registry: Option<Res<BevyUnitKindRegistry>>,
+) {
+    let fired: Vec<TriggerFired> = std::mem::take(&mut core.triggers);
+    for trigger in &fired {
+        tracing::info!("Scenario trigger '{}' fired", trigger.trigger);
+        for action in &trigger.actions {
+            match action {
+                TriggerAction::SpawnUnits {
+                    faction,
+                    kind,
+                    position,
+                    count,
+                } => {
+                    let unit_data = faction_registry.as_ref().and_then(|registry| {
+                        let data = registry.get(*faction)?;
+                        data.get_unit(kind).or_else(|| {
+                            data.units
+                                .iter()
+                                .filter(|u| u.has_tag(kind))
+                                .min_by_key(|u| u.tier)
+                        })
+                    });
+                    let Some(unit_data) = unit_data else {
+                        tracing::warn!("Trigger unit '{}' not found for {:?}", kind, faction);
+                        continue;
+                    };
+                    let kind_id = unit_kind_registry
+                        .as_ref()
+                        .and_then(|registry| registry.find(*faction, &unit_data.id))
+                        .unwrap_or(rts_core::unit_kind::UnitKindId::NONE);
+                    let position = Vec2::new(position.0 as f32, position.1 as f32);
+                    for _ in 0..*count {
+                        commands.spawn(UnitBundle::from_data(
+                            position, *faction, unit_data, kind_id,
+                        ));
+                    }
+                }
+                TriggerAction::GrantResources { faction, amount } => {
+                    if player_faction
+                        .as_ref()
+                        .is_some_and(|player| player.faction == *faction)
+                    {
+                        let amount = (*amount).clamp(i64::from(i32::MIN), i64::from(i32::MAX));
+                        resources.feedstock = resources
+                            .feedstock
+                            .saturating_add(amount as i32)
+                            .clamp(0, resources.feedstock_cap);
+                    }
+                }
+                TriggerAction::Message(text) => tracing::info!("{}", text),
+            }
+        }
+    }
+}
+
 /// Counts harvesters targeting each node and updates current_harvesters.
 fn count_harvesters_per_node(
     harvesters: Query<&GameHarvester>,
@@ -377,10 +546,90 @@ mod tests {
         assert!((dist - 25.0).abs() < 0.001);
     }
 
+    #[test]
+    fn salvage_pays_only_the_local_player() {
+        use rts_core::factions::FactionId;
+        use rts_core::salvage::WreckId;
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<CoreSimulation>()
+            .init_resource::<PlayerResources>()
+            .insert_resource(PlayerFaction {
+                faction: FactionId::Continuity,
+            })
+            .add_systems(Update, collect_salvage);
+
+        let collected = |faction, amount| SalvageEvent::Collected {
+            wreck: WreckId(0),
+            collector: 1,
+            faction: Some(faction),
+            amount,
+        };
+        app.world_mut().resource_mut::<CoreSimulation>().salvage = vec![
+            collected(FactionId::Continuity, 4),
+            collected(FactionId::Collegium, 7),
+            collected(FactionId::Continuity, 2),
+        ];
+        app.update();
+        app.update();
+
+        assert_eq!(app.world().resource::<PlayerResources>().feedstock, 506);
+        assert!(app.world().resource::<CoreSimulation>().salvage.is_empty());
+    }
+
+    #[test]
+    fn abilities_charge_only_the_local_player() {
+        use rts_core::factions::FactionId;
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<CoreSimulation>()
+            .init_resource::<PlayerResources>()
+            .insert_resource(PlayerFaction {
+                faction: FactionId::Tinkers,
+            })
+            .add_systems(Update, pay_for_abilities);
+
+        let used = |faction| AbilityUsed {
+            caster: 1,
+            faction: Some(faction),
+            ability_id: "emp_burst".to_string(),
+            cost: 25,
+            affected: Vec::new(),
+        };
+        app.world_mut().resource_mut::<CoreSimulation>().abilities =
+            vec![used(FactionId::Tinkers), used(FactionId::Zephyr)];
+        app.update();
+
+        assert_eq!(app.world().resource::<PlayerResources>().feedstock, 475);
+        assert!(app
+            .world()
+            .resource::<CoreSimulation>()
+            .abilities
+            .is_empty());
+    }
+
     #[test]
     fn player_resources_default_values() {
         let resources = PlayerResources::default();
         assert_eq!(resources.feedstock, 500);
         assert_eq!(resources.supply_cap, 10);
     }
+
+    #[test]
+    fn player_resources_supply_uses_core_cap() {
+        let mut resources = PlayerResources::default();
+        assert!(resources.has_supply(5));
+        assert!(!resources.has_supply(6));
+
+        // Buildings past the core limit add nothing
+        resources.supply_cap = 500;
+        assert_eq!(
+            resources.economy().supply_cap(),
+            rts_core::economy::MAX_SUPPLY
+        );
+        resources.supply_used = rts_core::economy::MAX_SUPPLY as i32;
+        assert!(!resources.has_supply(1));
+    }
 }
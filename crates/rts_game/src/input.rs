@@ -1,35 +1,57 @@
 //! Input plugin for game input handling.
 //!
-//! Provides move commands, attack-move, stop command, and harvester targeting.
+//! Provides move commands, attack-move, patrol, stop and hold position,
+//! harvester targeting, rally points and targeting for orders picked from
+//! the command card, with a cursor that shows which order a click will give.
 
 use bevy::prelude::*;
+use bevy::window::CursorIcon;
+use rts_core::abilities::AbilityTarget;
 use rts_core::components::Command as CoreCommand;
+use rts_core::data::AbilityTargetType;
 use rts_core::math::{Fixed, Vec2Fixed};
 
 use crate::camera::MainCamera;
 use crate::components::{
-    AttackTarget, CombatStats, CoreEntityId, GameCommandQueue, GameFaction, GameHarvester,
-    GameHarvesterState, GamePosition, GameResourceNode, MovementTarget, Selected,
+    AttackTarget, Building, CombatStats, CoreEntityId, GameCommandQueue, GameFaction,
+    GameHarvester, GameHarvesterState, GamePosition, GameProductionQueue, GameResourceNode,
+    MovementTarget, Selected,
 };
-use crate::render::CommandFeedbackEvent;
-use crate::simulation::{ClientCommandSet, CoreCommandBuffer, UNIT_RADIUS};
+use crate::render::{CommandFeedbackEvent, CommandFeedbackKind};
+use crate::simulation::{ClientCommandSet, CoreCommandBuffer, CoreSimulation, UNIT_RADIUS};
 
 /// Plugin for game input handling.
 ///
 /// Provides:
-/// - Right-click to issue move commands
-/// - Attack-move with A + right-click
-/// - Stop command with S key
+/// - Right-click to issue move commands (shift to queue them as waypoints)
+/// - Attack-move with A + right-click, patrol with P + right-click
+/// - Stop command with S key, hold position with H
+/// - A crosshair or move cursor while a targeted order is armed
+/// - Right-click with a production building selected to set its rally point
+/// - Right-click to aim an order picked from the command card (Escape
+///   cancels it)
 pub struct InputPlugin;
 
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<InputMode>()
             .init_resource::<KeyBindings>()
+            .init_resource::<PendingOrder>()
             .add_systems(Update, update_input_mode.before(ClientCommandSet::Gather))
+            .add_systems(Update, update_order_cursor.after(update_input_mode))
             .add_systems(Update, handle_move_command.in_set(ClientCommandSet::Gather))
+            .add_systems(
+                Update,
+                handle_ability_target
+                    .in_set(ClientCommandSet::Gather)
+                    .after(handle_move_command),
+            )
             .add_systems(Update, handle_stop_command.in_set(ClientCommandSet::Gather))
-            .add_systems(Update, handle_hold_command.in_set(ClientCommandSet::Gather));
+            .add_systems(Update, handle_hold_command.in_set(ClientCommandSet::Gather))
+            .add_systems(
+                Update,
+                handle_rally_point_command.in_set(ClientCommandSet::Gather),
+            );
     }
 }
 
@@ -44,6 +66,9 @@ pub struct KeyBindings {
     pub stop: KeyCode,
     /// Hold position command.
     pub hold_position: KeyCode,
+    /// Hold to force fire: right-click attacks friendly targets, and with
+    /// attack-move held, shells the ground under the cursor.
+    pub force_fire: KeyCode,
 }
 
 impl Default for KeyBindings {
@@ -53,6 +78,7 @@ impl Default for KeyBindings {
             patrol: KeyCode::KeyP,
             stop: KeyCode::KeyS,
             hold_position: KeyCode::KeyH,
+            force_fire: KeyCode::ControlLeft,
         }
     }
 }
@@ -69,30 +95,103 @@ pub enum InputMode {
     Patrol,
 }
 
-/// Updates the input mode based on key presses.
+impl InputMode {
+    /// Confirmation marker shown for a click in this mode.
+    pub fn feedback_kind(self) -> CommandFeedbackKind {
+        match self {
+            Self::Normal => CommandFeedbackKind::Move,
+            Self::AttackMove => CommandFeedbackKind::AttackMove,
+            Self::Patrol => CommandFeedbackKind::Patrol,
+        }
+    }
+}
+
+/// An order picked from the command card that needs a target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetedOrder {
+    /// Attack-move to the clicked point.
+    AttackMove,
+    /// Patrol to the clicked point.
+    Patrol,
+    /// Use an ability on the clicked point or entity.
+    Ability {
+        /// ID of the ability.
+        id: String,
+        /// What the ability is aimed at.
+        target: AbilityTargetType,
+    },
+}
+
+/// The command card order waiting for the player to right-click its
+/// target, if any.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub struct PendingOrder(pub Option<TargetedOrder>);
+
+/// Updates the input mode based on key presses, falling back to the mode
+/// of a pending command card order.
 fn update_input_mode(
     keyboard: Res<ButtonInput<KeyCode>>,
     bindings: Res<KeyBindings>,
     mut input_mode: ResMut<InputMode>,
+    mut pending: ResMut<PendingOrder>,
 ) {
+    if keyboard.just_pressed(KeyCode::Escape) {
+        pending.0 = None;
+    }
+
     // Hold patrol or attack-move binding
     if keyboard.pressed(bindings.patrol) {
         *input_mode = InputMode::Patrol;
     } else if keyboard.pressed(bindings.attack_move) {
         *input_mode = InputMode::AttackMove;
     } else {
-        *input_mode = InputMode::Normal;
+        *input_mode = match pending.0 {
+            Some(TargetedOrder::AttackMove) => InputMode::AttackMove,
+            Some(TargetedOrder::Patrol) => InputMode::Patrol,
+            _ => InputMode::Normal,
+        };
+    }
+}
+
+/// Cursor for the order the next right-click will give.
+pub fn order_cursor(input_mode: InputMode, pending: &PendingOrder) -> CursorIcon {
+    match (input_mode, &pending.0) {
+        (_, Some(TargetedOrder::Ability { .. })) => CursorIcon::Crosshair,
+        (InputMode::AttackMove, _) => CursorIcon::Crosshair,
+        (InputMode::Patrol, _) => CursorIcon::Move,
+        (InputMode::Normal, _) => CursorIcon::Default,
+    }
+}
+
+/// Swaps the window cursor to match the armed order.
+fn update_order_cursor(
+    input_mode: Res<InputMode>,
+    pending: Res<PendingOrder>,
+    mut windows: Query<&mut Window>,
+) {
+    let icon = order_cursor(*input_mode, &pending);
+    for mut window in windows.iter_mut() {
+        // Only write on change so the window isn't flagged every frame
+        if window.cursor.icon != icon {
+            window.cursor.icon = icon;
+        }
     }
 }
 
 /// Handles right-click to issue move/attack-move commands.
 /// Also handles right-clicking on resource nodes to direct harvesters,
 /// and right-clicking on enemies to attack them.
+/// With force fire held, friendly targets can be attacked too, and an
+/// attack-move click on open ground becomes an attack-ground order.
+/// The click uses up a pending attack-move or patrol order; abilities are
+/// left to [`handle_ability_target`].
 fn handle_move_command(
     commands: Commands,
     mouse_button: Res<ButtonInput<MouseButton>>,
     keyboard: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
     input_mode: Res<InputMode>,
+    mut pending: ResMut<PendingOrder>,
     mut core_commands: ResMut<CoreCommandBuffer>,
     windows: Query<&Window>,
     camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
@@ -115,11 +214,13 @@ fn handle_move_command(
             &GameFaction,
             Option<&Sprite>,
         ),
-        (With<CombatStats>, Without<Selected>),
+        (Or<(With<CombatStats>, With<Building>)>, Without<Selected>),
     >,
     feedback_events: EventWriter<CommandFeedbackEvent>,
 ) {
-    if !mouse_button.just_pressed(MouseButton::Right) {
+    if !mouse_button.just_pressed(MouseButton::Right)
+        || matches!(pending.0, Some(TargetedOrder::Ability { .. }))
+    {
         return;
     }
 
@@ -139,13 +240,16 @@ fn handle_move_command(
     else {
         return;
     };
+    pending.0 = None;
 
     let shift_held = keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight);
+    let force_fire = keyboard.pressed(bindings.force_fire);
 
     issue_move_commands_at(
         commands,
         *input_mode,
         shift_held,
+        force_fire,
         world_position,
         &mut core_commands,
         selected_units,
@@ -159,6 +263,7 @@ fn issue_move_commands_at(
     mut commands: Commands,
     input_mode: InputMode,
     shift_held: bool,
+    force_fire: bool,
     world_position: Vec2,
     core_commands: &mut CoreCommandBuffer,
     mut selected_units: Query<
@@ -180,7 +285,7 @@ fn issue_move_commands_at(
             &GameFaction,
             Option<&Sprite>,
         ),
-        (With<CombatStats>, Without<Selected>),
+        (Or<(With<CombatStats>, With<Building>)>, Without<Selected>),
     >,
     mut feedback_events: EventWriter<CommandFeedbackEvent>,
 ) {
@@ -194,18 +299,24 @@ fn issue_move_commands_at(
         })
         .map(|(entity, pos, _)| (entity, pos.value));
 
-    // Check if we clicked on an enemy unit
+    // Check if we clicked on an enemy unit (or any unit, when forcing fire)
     const UNIT_CLICK_RADIUS: f32 = 25.0;
-    let clicked_enemy = |my_faction: &GameFaction| -> Option<(Entity, CoreEntityId)> {
+    let clicked_target = |my_faction: &GameFaction| -> Option<(Entity, CoreEntityId, bool)> {
         potential_targets
             .iter()
-            .filter(|(_, _, _, faction, _)| faction.faction != my_faction.faction)
+            .filter(|(_, _, _, faction, _)| force_fire || faction.faction != my_faction.faction)
             .find(|(_, _, pos, _, sprite)| {
                 let unit_world = pos.as_vec2();
                 unit_world.distance(world_position) < click_radius(*sprite, UNIT_CLICK_RADIUS)
             })
-            .map(|(entity, core_id, _, _, _)| (entity, *core_id))
+            .map(|(entity, core_id, _, faction, _)| {
+                (entity, *core_id, faction.faction == my_faction.faction)
+            })
     };
+    let ground_target = Vec2Fixed::new(
+        Fixed::from_num(world_position.x),
+        Fixed::from_num(world_position.y),
+    );
 
     // Count selected units for formation spreading
     let unit_count = selected_units.iter().count();
@@ -213,6 +324,7 @@ fn issue_move_commands_at(
     // Calculate formation offsets for units
     // Uses a spiral pattern to spread units around the clicked point
     let mut issued_command = false;
+    let mut feedback_kind = input_mode.feedback_kind();
     for (index, (entity, core_id, my_faction, harvester_opt, combat_opt)) in
         selected_units.iter_mut().enumerate()
     {
@@ -231,9 +343,14 @@ fn issue_move_commands_at(
 
         // If we clicked on an enemy and this unit can attack, attack it
         if combat_opt.is_some() {
-            if let Some((target_entity, target_core)) = clicked_enemy(my_faction) {
+            if let Some((target_entity, target_core, friendly)) = clicked_target(my_faction) {
+                let command = if friendly {
+                    CoreCommand::ForceAttack(target_core.0)
+                } else {
+                    CoreCommand::Attack(target_core.0)
+                };
                 if shift_held {
-                    core_commands.queue(core_id.0, CoreCommand::Attack(target_core.0));
+                    core_commands.queue(core_id.0, command);
                 } else {
                     commands
                         .entity(entity)
@@ -241,9 +358,28 @@ fn issue_move_commands_at(
                             target: target_entity,
                         })
                         .remove::<MovementTarget>();
-                    core_commands.set(core_id.0, CoreCommand::Attack(target_core.0));
+                    core_commands.set(core_id.0, command);
                 }
                 issued_command = true;
+                feedback_kind = CommandFeedbackKind::Attack;
+                continue;
+            }
+
+            // Force-fire attack-move on open ground shells the point itself;
+            // the simulation refuses this for units without splash weapons
+            if force_fire && input_mode == InputMode::AttackMove {
+                let command = CoreCommand::AttackGround(ground_target);
+                if shift_held {
+                    core_commands.queue(core_id.0, command);
+                } else {
+                    core_commands.set(core_id.0, command);
+                    commands
+                        .entity(entity)
+                        .remove::<AttackTarget>()
+                        .remove::<MovementTarget>();
+                }
+                issued_command = true;
+                feedback_kind = CommandFeedbackKind::Attack;
                 continue;
             }
         }
@@ -280,6 +416,7 @@ fn issue_move_commands_at(
     if issued_command {
         feedback_events.send(CommandFeedbackEvent {
             position: world_position,
+            kind: feedback_kind,
         });
     }
 }
@@ -337,34 +474,176 @@ pub fn calculate_formation_offset(index: usize, total: usize) -> Vec2 {
     Vec2::new(angle.cos() * radius, angle.sin() * radius)
 }
 
-/// Handles S key to issue stop commands.
+/// Handles right-click while an ability from the command card waits for a
+/// target: the selected units that have the ability use it on the clicked
+/// point, or on the entity under the cursor for entity-targeted abilities.
+fn handle_ability_target(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut pending: ResMut<PendingOrder>,
+    mut core_commands: ResMut<CoreCommandBuffer>,
+    core: Res<CoreSimulation>,
+    windows: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    selected_units: Query<&CoreEntityId, (With<Selected>, With<GameCommandQueue>)>,
+    targets: Query<(&CoreEntityId, &GamePosition, Option<&Sprite>)>,
+    mut feedback_events: EventWriter<CommandFeedbackEvent>,
+) {
+    let Some(TargetedOrder::Ability { id, target }) = pending.0.as_ref() else {
+        return;
+    };
+    if !mouse_button.just_pressed(MouseButton::Right) {
+        return;
+    }
+
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+
+    let Some(world_position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor))
+    else {
+        return;
+    };
+
+    const UNIT_CLICK_RADIUS: f32 = 25.0;
+    let aim = match target {
+        AbilityTargetType::Point => AbilityTarget::Point(Vec2Fixed::new(
+            Fixed::from_num(world_position.x),
+            Fixed::from_num(world_position.y),
+        )),
+        AbilityTargetType::Entity => {
+            let clicked = targets.iter().find(|(_, pos, sprite)| {
+                pos.as_vec2().distance(world_position) < click_radius(*sprite, UNIT_CLICK_RADIUS)
+            });
+            // Keep waiting until the player clicks something to aim at
+            let Some((core_id, _, _)) = clicked else {
+                return;
+            };
+            AbilityTarget::Entity(core_id.0)
+        }
+        AbilityTargetType::Caster => AbilityTarget::Caster,
+    };
+
+    let shift_held = keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight);
+    for core_id in selected_units.iter() {
+        let has_ability = core
+            .sim
+            .get_entity(core_id.0)
+            .and_then(|e| e.row.abilities.as_ref())
+            .is_some_and(|abilities| abilities.get(id).is_some());
+        if !has_ability {
+            continue;
+        }
+        let command = CoreCommand::UseAbility {
+            ability_id: id.clone(),
+            target: aim,
+        };
+        if shift_held {
+            core_commands.queue(core_id.0, command);
+        } else {
+            core_commands.set(core_id.0, command);
+        }
+    }
+    pending.0 = None;
+    feedback_events.send(CommandFeedbackEvent {
+        position: world_position,
+        kind: CommandFeedbackKind::Ability,
+    });
+}
+
+/// Handles S key to issue stop commands, marking each stopped unit.
 fn handle_stop_command(
     keyboard: Res<ButtonInput<KeyCode>>,
     bindings: Res<KeyBindings>,
     mut core_commands: ResMut<CoreCommandBuffer>,
-    selected_units: Query<&CoreEntityId, (With<Selected>, With<GameCommandQueue>)>,
+    selected_units: Query<(&CoreEntityId, &GamePosition), (With<Selected>, With<GameCommandQueue>)>,
+    mut feedback_events: EventWriter<CommandFeedbackEvent>,
 ) {
     if keyboard.just_pressed(bindings.stop) {
-        for core_id in selected_units.iter() {
+        for (core_id, position) in selected_units.iter() {
             core_commands.set(core_id.0, CoreCommand::Stop);
+            feedback_events.send(CommandFeedbackEvent {
+                position: position.as_vec2(),
+                kind: CommandFeedbackKind::Stop,
+            });
         }
     }
 }
 
-/// Handles H key to issue hold position commands.
+/// Handles H key to issue hold position commands, marking each held unit.
 fn handle_hold_command(
     keyboard: Res<ButtonInput<KeyCode>>,
     bindings: Res<KeyBindings>,
     mut core_commands: ResMut<CoreCommandBuffer>,
-    selected_units: Query<&CoreEntityId, (With<Selected>, With<GameCommandQueue>)>,
+    selected_units: Query<(&CoreEntityId, &GamePosition), (With<Selected>, With<GameCommandQueue>)>,
+    mut feedback_events: EventWriter<CommandFeedbackEvent>,
 ) {
     if keyboard.just_pressed(bindings.hold_position) {
-        for core_id in selected_units.iter() {
+        for (core_id, position) in selected_units.iter() {
             core_commands.set(core_id.0, CoreCommand::HoldPosition);
+            feedback_events.send(CommandFeedbackEvent {
+                position: position.as_vec2(),
+                kind: CommandFeedbackKind::HoldPosition,
+            });
         }
     }
 }
 
+/// Handles right-click with production buildings selected to set where
+/// they send the units they make.
+fn handle_rally_point_command(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    mut core_commands: ResMut<CoreCommandBuffer>,
+    windows: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    selected_buildings: Query<
+        &CoreEntityId,
+        (
+            With<Selected>,
+            With<GameProductionQueue>,
+            Without<GameCommandQueue>,
+        ),
+    >,
+    mut feedback_events: EventWriter<CommandFeedbackEvent>,
+) {
+    if !mouse_button.just_pressed(MouseButton::Right) || selected_buildings.is_empty() {
+        return;
+    }
+
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+
+    let Some(world_position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor))
+    else {
+        return;
+    };
+
+    let rally = Vec2Fixed::new(
+        Fixed::from_num(world_position.x),
+        Fixed::from_num(world_position.y),
+    );
+    for core_id in selected_buildings.iter() {
+        core_commands.set(core_id.0, CoreCommand::SetRallyPoint(rally));
+    }
+    feedback_events.send(CommandFeedbackEvent {
+        position: world_position,
+        kind: CommandFeedbackKind::Move,
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -375,6 +654,7 @@ mod tests {
     struct PendingCommand {
         position: Vec2,
         shift_held: bool,
+        force_fire: bool,
     }
 
     fn issue_pending_command(
@@ -401,7 +681,7 @@ mod tests {
                 &GameFaction,
                 Option<&Sprite>,
             ),
-            (With<CombatStats>, Without<Selected>),
+            (Or<(With<CombatStats>, With<Building>)>, Without<Selected>),
         >,
         feedback_events: EventWriter<CommandFeedbackEvent>,
     ) {
@@ -409,6 +689,7 @@ mod tests {
             commands,
             *input_mode,
             pending.shift_held,
+            pending.force_fire,
             pending.position,
             &mut core_commands,
             selected_units,
@@ -451,12 +732,67 @@ mod tests {
         let queue = sim
             .get_entity(core_id)
             .unwrap()
+            .row
             .command_queue
             .as_ref()
             .unwrap();
         assert_eq!(queue.current(), Some(&CoreCommand::Stop));
     }
 
+    #[test]
+    fn stop_command_marks_each_stopped_unit() {
+        let mut app = setup_basic_app();
+        app.add_systems(Update, handle_stop_command.in_set(ClientCommandSet::Gather));
+        for x in [0, 40] {
+            app.world_mut().spawn((
+                Selected,
+                GameCommandQueue::new(),
+                GamePosition::new(Vec2Fixed::new(Fixed::from_num(x), Fixed::ZERO)),
+            ));
+        }
+
+        app.update();
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyS);
+        app.update();
+
+        let events = app.world().resource::<Events<CommandFeedbackEvent>>();
+        let mut markers: Vec<_> = events
+            .get_reader()
+            .read(events)
+            .map(|event| (event.position.x, event.kind))
+            .collect();
+        markers.sort_by(|a, b| a.0.total_cmp(&b.0));
+        assert_eq!(
+            markers,
+            vec![
+                (0.0, CommandFeedbackKind::Stop),
+                (40.0, CommandFeedbackKind::Stop)
+            ]
+        );
+    }
+
+    #[test]
+    fn order_cursor_follows_armed_order() {
+        let none = PendingOrder::default();
+        assert_eq!(order_cursor(InputMode::Normal, &none), CursorIcon::Default);
+        assert_eq!(
+            order_cursor(InputMode::AttackMove, &none),
+            CursorIcon::Crosshair
+        );
+        assert_eq!(order_cursor(InputMode::Patrol, &none), CursorIcon::Move);
+
+        let ability = PendingOrder(Some(TargetedOrder::Ability {
+            id: "blink".to_string(),
+            target: AbilityTargetType::Point,
+        }));
+        assert_eq!(
+            order_cursor(InputMode::Normal, &ability),
+            CursorIcon::Crosshair
+        );
+    }
+
     #[test]
     fn hold_command_sets_queue() {
         let mut app = setup_basic_app();
@@ -479,6 +815,7 @@ mod tests {
         let queue = sim
             .get_entity(core_id)
             .unwrap()
+            .row
             .command_queue
             .as_ref()
             .unwrap();
@@ -496,6 +833,7 @@ mod tests {
         app.insert_resource(PendingCommand {
             position: Vec2::new(100.0, 80.0),
             shift_held: false,
+            force_fire: false,
         });
 
         let unit = app
@@ -519,6 +857,7 @@ mod tests {
         let queue = sim
             .get_entity(core_id)
             .unwrap()
+            .row
             .command_queue
             .as_ref()
             .unwrap();
@@ -541,6 +880,7 @@ mod tests {
         app.insert_resource(PendingCommand {
             position: Vec2::new(120.0, 60.0),
             shift_held: false,
+            force_fire: false,
         });
 
         let unit = app
@@ -564,6 +904,7 @@ mod tests {
         let queue = sim
             .get_entity(core_id)
             .unwrap()
+            .row
             .command_queue
             .as_ref()
             .unwrap();
@@ -599,12 +940,14 @@ mod tests {
         app.insert_resource(PendingCommand {
             position: Vec2::new(50.0, 20.0),
             shift_held: false,
+            force_fire: false,
         });
         app.update();
 
         app.insert_resource(PendingCommand {
             position: Vec2::new(80.0, 40.0),
             shift_held: true,
+            force_fire: false,
         });
         app.update();
 
@@ -613,6 +956,7 @@ mod tests {
         let queue = sim
             .get_entity(core_id)
             .unwrap()
+            .row
             .command_queue
             .as_ref()
             .unwrap();
@@ -625,6 +969,31 @@ mod tests {
         assert_eq!(commands[1], CoreCommand::MoveTo(second));
     }
 
+    #[test]
+    fn pending_card_order_sets_input_mode_until_cancelled() {
+        let mut app = setup_basic_app();
+        app.insert_resource(InputMode::Normal);
+        app.insert_resource(PendingOrder(Some(TargetedOrder::Patrol)));
+        app.add_systems(Update, update_input_mode);
+
+        app.update();
+        assert!(*app.world().resource::<InputMode>() == InputMode::Patrol);
+
+        // Held keys still win over the pending order
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyA);
+        app.update();
+        assert!(*app.world().resource::<InputMode>() == InputMode::AttackMove);
+
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.reset_all();
+        keyboard.press(KeyCode::Escape);
+        app.update();
+        assert!(*app.world().resource::<InputMode>() == InputMode::Normal);
+        assert_eq!(app.world().resource::<PendingOrder>().0, None);
+    }
+
     #[test]
     fn custom_keybindings_drive_stop_command() {
         let mut app = setup_basic_app();
@@ -652,6 +1021,7 @@ mod tests {
         let queue = sim
             .get_entity(core_id)
             .unwrap()
+            .row
             .command_queue
             .as_ref()
             .unwrap();
@@ -714,6 +1084,7 @@ mod tests {
         app.insert_resource(PendingCommand {
             position: Vec2::new(50.0, 10.0),
             shift_held: true,
+            force_fire: false,
         });
 
         app.update();
@@ -724,4 +1095,58 @@ mod tests {
             "Shift-queued attacks should not set AttackTarget."
         );
     }
+
+    #[test]
+    fn force_fire_attacks_own_units() {
+        let mut app = setup_basic_app();
+        app.insert_resource(InputMode::default());
+        app.add_systems(
+            Update,
+            issue_pending_command.in_set(ClientCommandSet::Gather),
+        );
+
+        let own_faction = GameFaction {
+            faction: rts_core::factions::FactionId::Continuity,
+        };
+        let friend = app
+            .world_mut()
+            .spawn((
+                GamePosition::new(Vec2Fixed::new(Fixed::from_num(50.0), Fixed::ZERO)),
+                own_faction,
+                CombatStats::new(10, DamageType::Kinetic, 60.0, 0.5),
+            ))
+            .id();
+        let selected = app
+            .world_mut()
+            .spawn((
+                Selected,
+                GameCommandQueue::new(),
+                GamePosition::ORIGIN,
+                own_faction,
+                CombatStats::new(8, DamageType::Kinetic, 60.0, 0.5),
+            ))
+            .id();
+
+        app.insert_resource(PendingCommand {
+            position: Vec2::new(50.0, 0.0),
+            shift_held: false,
+            force_fire: true,
+        });
+        app.update();
+
+        let friend_core = app.world().get::<CoreEntityId>(friend).unwrap().0;
+        let core_id = app.world().get::<CoreEntityId>(selected).unwrap().0;
+        let sim = &app.world().resource::<CoreSimulation>().sim;
+        let queue = sim
+            .get_entity(core_id)
+            .unwrap()
+            .row
+            .command_queue
+            .as_ref()
+            .unwrap();
+        assert_eq!(
+            queue.current(),
+            Some(&CoreCommand::ForceAttack(friend_core))
+        );
+    }
 }
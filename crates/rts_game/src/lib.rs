@@ -8,29 +8,43 @@
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 
+use std::path::Path;
+
+use bevy::app::PluginGroupBuilder;
 use bevy::log::LogPlugin;
 use bevy::prelude::*;
+use rts_core::autosave::{resolve_resume_path, Autosave};
+use rts_core::difficulty::Difficulty;
 use rts_core::factions::FactionId;
 use rts_core::math::{Fixed, Vec2Fixed};
+use rts_core::replay::{Replay, ReplayPlayer};
+use rts_core::strategy::Strategy;
 
 use crate::components::{Collider, GamePosition, PlayerFaction, Stationary};
 
 // Core modules
 pub mod ai;
+pub mod autosave;
 pub mod bundles;
 pub mod camera;
+pub mod chat;
 pub mod combat;
 pub mod components;
 pub mod construction;
 pub mod data_loader;
 pub mod economy;
 pub mod input;
+pub mod minimap;
+pub mod notifications;
 pub mod plugins;
 pub mod production;
 pub mod render;
+pub mod replay;
+pub mod research;
 pub mod selection;
 pub mod simulation;
 pub mod sprites;
+pub mod strategy_file;
 pub mod ui;
 pub mod unit_utils;
 pub mod victory;
@@ -39,6 +53,8 @@ pub mod victory;
 #[cfg(feature = "dev-tools")]
 pub mod debug_console;
 
+use ai::{AiHandicaps, AiStrategies};
+use autosave::ResumeFrom;
 use components::UnderConstruction;
 pub use data_loader::{BevyUnitKindRegistry, FactionDataPlugin, FactionRegistry};
 pub use plugins::HeadlessGamePlugins;
@@ -52,29 +68,122 @@ use plugins::{
 ///
 /// Returns an error if the game fails to initialize.
 pub fn run() -> Result<(), Box<dyn std::error::Error>> {
+    run_skirmish(Difficulty::Normal, None)
+}
+
+/// Run a new skirmish with every AI player at `difficulty`, playing
+/// `strategy` if given or else the built-in AI behavior.
+///
+/// # Errors
+///
+/// Returns an error if the game fails to initialize.
+pub fn run_skirmish(
+    difficulty: Difficulty,
+    strategy: Option<Strategy>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    tracing::info!("AI difficulty: {difficulty}");
+    let strategies = match strategy {
+        Some(strategy) => {
+            tracing::info!("AI strategy: {}", strategy.name);
+            AiStrategies::new(strategy)
+        }
+        None => AiStrategies::default(),
+    };
+    launch(None, AiHandicaps::new(difficulty), strategies)
+}
+
+/// Run the game, resuming from an autosave instead of starting a new match.
+///
+/// `path` may be an autosave file or a directory, in which case its newest
+/// autosave is used.
+///
+/// # Errors
+///
+/// Returns an error if no autosave can be loaded or the game fails to
+/// initialize.
+pub fn run_from_autosave(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let path = resolve_resume_path(path, None)?;
+    let save = Autosave::load(&path)?;
+    tracing::info!("Resuming from {} (tick {})", path.display(), save.tick);
+    launch(Some(save), AiHandicaps::default(), AiStrategies::default())
+}
+
+/// Watch a recorded replay instead of playing.
+///
+/// # Errors
+///
+/// Returns an error if the replay cannot be loaded or the game fails to
+/// initialize.
+pub fn run_replay(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let replay = Replay::load(path)?;
+    tracing::info!(
+        "Watching replay {} ({} ticks, {} commands)",
+        path.display(),
+        replay.final_tick,
+        replay.command_count()
+    );
+    let player = ReplayPlayer::new(replay)?;
+
     let mut app = App::new();
+    app.add_plugins(window_plugins())
+        .add_plugins((
+            camera::CameraPlugin,
+            render::RenderPlugin,
+            replay::ReplayViewerPlugin,
+            bevy_egui::EguiPlugin,
+            replay::ReplayTimelinePlugin,
+        ))
+        .insert_resource(replay::ReplayPlayback::new(player))
+        .insert_resource(ClearColor(Color::srgb(0.15, 0.15, 0.18)))
+        .add_systems(Startup, spawn_ground_grid);
+    app.run();
 
-    app.add_plugins(
-        DefaultPlugins
-            .set(WindowPlugin {
-                primary_window: Some(Window {
-                    title: "Post-Scarcity RTS".into(),
-                    resolution: (1920.0, 1080.0).into(),
-                    ..default()
-                }),
+    Ok(())
+}
+
+/// Bevy's default plugins with the game window.
+fn window_plugins() -> PluginGroupBuilder {
+    DefaultPlugins
+        .set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "Post-Scarcity RTS".into(),
+                resolution: (1920.0, 1080.0).into(),
                 ..default()
-            })
-            .disable::<LogPlugin>(), // Logging already initialized in main.rs
-    );
+            }),
+            ..default()
+        })
+        .disable::<LogPlugin>() // Logging already initialized in main.rs
+}
+
+fn launch(
+    resume: Option<Autosave>,
+    handicaps: AiHandicaps,
+    strategies: AiStrategies,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut app = App::new();
+
+    app.add_plugins(window_plugins());
 
     // Add game plugins (camera, selection, input, rendering)
     app.add_plugins(GamePlugins);
+    app.insert_resource(handicaps);
+    app.insert_resource(strategies);
 
     // Set background color (dark gray ground)
     app.insert_resource(ClearColor(Color::srgb(0.15, 0.15, 0.18)));
 
-    // Add startup systems for visuals and test units
-    app.add_systems(Startup, (spawn_ground_grid, spawn_test_units));
+    // Add startup systems for visuals and test units; a resumed game
+    // respawns its units from the autosave instead
+    app.add_systems(
+        Startup,
+        (
+            spawn_ground_grid,
+            spawn_test_units.run_if(not(resource_exists::<ResumeFrom>)),
+        ),
+    );
+    if let Some(save) = resume {
+        app.insert_resource(ResumeFrom(save));
+    }
 
     #[cfg(feature = "dev-tools")]
     {
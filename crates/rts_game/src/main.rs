@@ -1,5 +1,11 @@
 //! Post-Scarcity RTS - Game Client
 
+use std::path::PathBuf;
+
+use rts_core::difficulty::Difficulty;
+use rts_core::strategy::Strategy;
+use rts_game::strategy_file::resolve_strategy;
+
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 fn main() {
@@ -11,8 +17,67 @@ fn main() {
 
     tracing::info!("Starting Post-Scarcity RTS");
 
-    if let Err(e) = rts_game::run() {
+    let result = if let Some(path) = path_arg("--replay") {
+        rts_game::run_replay(&path)
+    } else if let Some(path) = path_arg("--resume-from") {
+        rts_game::run_from_autosave(&path)
+    } else {
+        match (difficulty_arg(), strategy_arg()) {
+            (Ok(difficulty), Ok(strategy)) => rts_game::run_skirmish(difficulty, strategy),
+            (Err(e), _) | (_, Err(e)) => Err(e.into()),
+        }
+    };
+    if let Err(e) = result {
         tracing::error!("Game error: {e}");
         std::process::exit(1);
     }
 }
+
+/// Parse a path option such as `--resume-from <file|dir>` or
+/// `--replay <file>` from the command line.
+fn path_arg(flag: &str) -> Option<PathBuf> {
+    arg(flag).map(PathBuf::from)
+}
+
+/// Parse the AI difficulty from `--difficulty <easy|normal|hard|brutal>`,
+/// normal if not given.
+fn difficulty_arg() -> Result<Difficulty, String> {
+    match arg("--difficulty") {
+        Some(name) => {
+            Difficulty::from_name(&name).ok_or_else(|| format!("Unknown difficulty '{name}'"))
+        }
+        None => Ok(Difficulty::default()),
+    }
+}
+
+/// Parse the AI strategy from `--ai-strategy <preset|file>`, a built-in
+/// strategy name or a RON/JSON strategy file.
+fn strategy_arg() -> Result<Option<Strategy>, String> {
+    let Some(name) = arg("--ai-strategy") else {
+        return Ok(None);
+    };
+    let strategy = resolve_strategy(&name).map_err(|e| format!("{name}: {e}"))?;
+    let problems = strategy.problems();
+    if !problems.is_empty() {
+        let problems: Vec<String> = problems.iter().map(ToString::to_string).collect();
+        return Err(format!("{name}: {}", problems.join("; ")));
+    }
+    Ok(Some(strategy))
+}
+
+/// Value of an option given as `<flag> <value>` or `<flag>=<value>`.
+fn arg(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next();
+        }
+        if let Some(value) = arg
+            .strip_prefix(flag)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
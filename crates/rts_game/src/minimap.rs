@@ -0,0 +1,513 @@
+//! Minimap plugin for the overview map.
+//!
+//! Draws terrain, fog of war and unit blips, moves the camera with the left
+//! mouse button, issues orders with the right, and pings where the player's
+//! units take damage off-screen. Alt+click pings the map for the team.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiSet};
+use rts_core::components::Command as CoreCommand;
+use rts_core::fog::CellVisibility;
+use rts_core::math::{Fixed, Vec2Fixed};
+use rts_core::pathfinding::{CellType, NavGrid};
+
+use crate::camera::{camera_view_rect, MainCamera};
+use crate::chat::{ChatChannel, MapPingSent, PingKind};
+use crate::components::{
+    AttackTarget, Building, CoreEntityId, GameCommandQueue, GameFaction, GameHealth, GamePosition,
+    PlayerFaction, Selected,
+};
+use crate::input::{calculate_formation_offset, InputMode, PendingOrder, TargetedOrder};
+use crate::render::CommandFeedbackEvent;
+use crate::simulation::{
+    ClientCommandSet, CoreCommandBuffer, CoreSimulation, CoreSimulationSet, FogOfWarMask,
+};
+use crate::ui::faction_to_egui_color;
+
+/// Plugin for the minimap.
+///
+/// Provides:
+/// - Terrain, fog of war and faction-colored unit blips
+/// - Left-click or drag to move the camera
+/// - Right-click to move the selection (or attack-move/patrol)
+/// - Alert pings where the player's units are hurt off-screen
+/// - Alt+click to ping the team
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MinimapSettings>()
+            .init_resource::<MinimapPings>()
+            .init_resource::<InputMode>()
+            .init_resource::<PendingOrder>()
+            .init_resource::<FogOfWarMask>()
+            .add_event::<MapPingSent>()
+            .add_systems(
+                Update,
+                ping_offscreen_damage.after(CoreSimulationSet::SyncOut),
+            )
+            .add_systems(
+                Update,
+                ui_minimap
+                    .in_set(ClientCommandSet::Gather)
+                    .after(EguiSet::InitContexts),
+            );
+    }
+}
+
+/// Minimap layout and alert settings.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct MinimapSettings {
+    /// Side length of the minimap in pixels.
+    pub size: f32,
+    /// World area the minimap shows.
+    pub world_bounds: Rect,
+    /// Seconds an alert ping stays on the minimap.
+    pub ping_duration: f32,
+    /// Damage within this many world units of a showing ping refreshes it
+    /// instead of adding another.
+    pub ping_merge_radius: f32,
+}
+
+impl Default for MinimapSettings {
+    fn default() -> Self {
+        Self {
+            size: 180.0,
+            world_bounds: Rect::from_center_size(Vec2::ZERO, Vec2::splat(2000.0)),
+            ping_duration: 3.0,
+            ping_merge_radius: 200.0,
+        }
+    }
+}
+
+/// An alert marker on the minimap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinimapPing {
+    /// World position of the alert.
+    pub position: Vec2,
+    /// Seconds until the ping disappears.
+    pub remaining: f32,
+    /// Marker color.
+    pub color: egui::Color32,
+}
+
+/// Color of the alert pinged when the player's units are hurt.
+pub const DAMAGE_PING_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 60, 60);
+
+/// Alert pings currently showing on the minimap.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct MinimapPings {
+    /// Live pings, oldest first.
+    pub pings: Vec<MinimapPing>,
+}
+
+impl MinimapPings {
+    /// Show a damage alert at `position` for `duration` seconds,
+    /// refreshing an existing ping within `merge_radius` instead of
+    /// stacking another.
+    pub fn ping(&mut self, position: Vec2, duration: f32, merge_radius: f32) {
+        self.ping_with_color(position, DAMAGE_PING_COLOR, duration, merge_radius);
+    }
+
+    /// Like [`ping`](Self::ping), in `color`. A refreshed ping takes the
+    /// new color.
+    pub fn ping_with_color(
+        &mut self,
+        position: Vec2,
+        color: egui::Color32,
+        duration: f32,
+        merge_radius: f32,
+    ) {
+        if let Some(existing) = self
+            .pings
+            .iter_mut()
+            .find(|ping| ping.position.distance(position) <= merge_radius)
+        {
+            existing.remaining = duration;
+            existing.color = color;
+            return;
+        }
+        self.pings.push(MinimapPing {
+            position,
+            remaining: duration,
+            color,
+        });
+    }
+
+    /// Age every ping by `dt` seconds and drop the expired ones.
+    pub fn advance(&mut self, dt: f32) {
+        for ping in &mut self.pings {
+            ping.remaining -= dt;
+        }
+        self.pings.retain(|ping| ping.remaining > 0.0);
+    }
+}
+
+/// Pings the minimap when one of the player's entities loses health while
+/// outside the camera view.
+fn ping_offscreen_damage(
+    time: Res<Time>,
+    settings: Res<MinimapSettings>,
+    player: Res<PlayerFaction>,
+    mut pings: ResMut<MinimapPings>,
+    mut last_health: Local<HashMap<Entity, u32>>,
+    units: Query<(Entity, &GamePosition, &GameFaction, &GameHealth)>,
+    windows: Query<&Window>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+) {
+    pings.advance(time.delta_seconds());
+
+    let view = windows
+        .get_single()
+        .ok()
+        .zip(camera_query.get_single().ok())
+        .map(|(window, transform)| camera_view_rect(transform, window.size()));
+
+    for (entity, position, faction, health) in units.iter() {
+        if faction.faction != player.faction {
+            continue;
+        }
+        let previous = last_health.insert(entity, health.current);
+        if !previous.is_some_and(|previous| health.current < previous) {
+            continue;
+        }
+        let position = position.as_vec2();
+        if view.is_some_and(|view| view.contains(position)) {
+            continue;
+        }
+        pings.ping(position, settings.ping_duration, settings.ping_merge_radius);
+    }
+
+    last_health.retain(|entity, _| units.contains(*entity));
+}
+
+/// Renders the minimap in the bottom-left corner.
+fn ui_minimap(
+    mut contexts: EguiContexts,
+    settings: Res<MinimapSettings>,
+    pings: Res<MinimapPings>,
+    core: Res<CoreSimulation>,
+    fog: Res<FogOfWarMask>,
+    player: Res<PlayerFaction>,
+    units: Query<(&GamePosition, &GameFaction, Has<Building>)>,
+    windows: Query<&Window>,
+    mut camera_query: Query<&mut Transform, With<MainCamera>>,
+    mut core_commands: ResMut<CoreCommandBuffer>,
+    selected_units: Query<(Entity, &CoreEntityId), (With<Selected>, With<GameCommandQueue>)>,
+    mut commands: Commands,
+    (input_mode, mut pending): (Res<InputMode>, ResMut<PendingOrder>),
+    keyboard: Res<ButtonInput<KeyCode>>,
+    (mut feedback_events, mut ping_events): (
+        EventWriter<CommandFeedbackEvent>,
+        EventWriter<MapPingSent>,
+    ),
+) {
+    let Some(ctx) = contexts.try_ctx_mut() else {
+        return;
+    };
+    let bounds = settings.world_bounds;
+
+    egui::Window::new("Minimap")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_BOTTOM, [10.0, -10.0])
+        .fixed_size([settings.size, settings.size])
+        .show(ctx, |ui| {
+            let (response, painter) = ui.allocate_painter(
+                egui::Vec2::splat(settings.size),
+                egui::Sense::click_and_drag(),
+            );
+
+            let rect = response.rect;
+            let painter = painter.with_clip_rect(rect);
+
+            // Background
+            painter.rect_filled(rect, 0.0, egui::Color32::from_rgb(20, 30, 20));
+
+            draw_terrain(&painter, rect, bounds, core.sim.nav_grid());
+
+            // Draw units as dots; other factions only where the player can see
+            for (pos, faction, is_building) in units.iter() {
+                let world_pos = pos.as_vec2();
+                if faction.faction != player.faction
+                    && fog
+                        .visibility_at(world_pos)
+                        .is_some_and(|cell| cell != CellVisibility::Visible)
+                {
+                    continue;
+                }
+
+                let minimap_pos = world_to_minimap(world_pos, rect, bounds);
+                let color = faction_to_egui_color(faction.faction);
+                if is_building {
+                    painter.rect_filled(
+                        egui::Rect::from_center_size(minimap_pos, egui::Vec2::splat(7.0)),
+                        0.0,
+                        color,
+                    );
+                } else {
+                    painter.circle_filled(minimap_pos, 3.0, color);
+                }
+            }
+
+            draw_fog(&painter, rect, bounds, &fog);
+
+            // Alert pings grow and fade as they age
+            for ping in &pings.pings {
+                let age = 1.0 - (ping.remaining / settings.ping_duration).clamp(0.0, 1.0);
+                let alpha = ((1.0 - age) * 255.0) as u8;
+                let [r, g, b, _] = ping.color.to_array();
+                painter.circle_stroke(
+                    world_to_minimap(ping.position, rect, bounds),
+                    4.0 + age * 12.0,
+                    egui::Stroke::new(2.0, egui::Color32::from_rgba_unmultiplied(r, g, b, alpha)),
+                );
+            }
+
+            // Draw camera viewport
+            if let (Ok(window), Ok(camera_transform)) =
+                (windows.get_single(), camera_query.get_single())
+            {
+                let view = camera_view_rect(camera_transform, window.size());
+                painter.rect_stroke(
+                    egui::Rect::from_two_pos(
+                        world_to_minimap(view.min, rect, bounds),
+                        world_to_minimap(view.max, rect, bounds),
+                    ),
+                    0.0,
+                    egui::Stroke::new(1.0, egui::Color32::WHITE),
+                );
+            }
+
+            // Border
+            painter.rect_stroke(rect, 0.0, egui::Stroke::new(2.0, egui::Color32::GRAY));
+
+            let Some(pointer) = response.interact_pointer_pos() else {
+                return;
+            };
+            let world_pos = minimap_to_world(pointer, rect, bounds);
+
+            let alt_held =
+                keyboard.pressed(KeyCode::AltLeft) || keyboard.pressed(KeyCode::AltRight);
+            if response.clicked() && alt_held {
+                ping_events.send(MapPingSent {
+                    channel: ChatChannel::Team,
+                    position: world_pos,
+                    kind: PingKind::Alert,
+                });
+                return;
+            }
+
+            if response.clicked() || response.dragged_by(egui::PointerButton::Primary) {
+                if let Ok(mut camera_transform) = camera_query.get_single_mut() {
+                    camera_transform.translation.x = world_pos.x;
+                    camera_transform.translation.y = world_pos.y;
+                }
+                return;
+            }
+
+            if !response.secondary_clicked() {
+                return;
+            }
+
+            let unit_count = selected_units.iter().count();
+            if unit_count == 0 {
+                return;
+            }
+            let shift_held =
+                keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight);
+
+            for (index, (entity, core_id)) in selected_units.iter().enumerate() {
+                let offset = if unit_count > 1 {
+                    calculate_formation_offset(index, unit_count)
+                } else {
+                    Vec2::ZERO
+                };
+
+                let target = Vec2Fixed::new(
+                    Fixed::from_num(world_pos.x + offset.x),
+                    Fixed::from_num(world_pos.y + offset.y),
+                );
+
+                let command = match *input_mode {
+                    InputMode::Normal => CoreCommand::MoveTo(target),
+                    InputMode::AttackMove => CoreCommand::AttackMove(target),
+                    InputMode::Patrol => CoreCommand::Patrol(target),
+                };
+
+                if shift_held {
+                    core_commands.queue(core_id.0, command);
+                } else {
+                    core_commands.set(core_id.0, command);
+                    commands.entity(entity).remove::<AttackTarget>();
+                }
+            }
+
+            // The click used up an attack-move or patrol picked from the
+            // command card
+            if matches!(
+                pending.0,
+                Some(TargetedOrder::AttackMove | TargetedOrder::Patrol)
+            ) {
+                pending.0 = None;
+            }
+
+            feedback_events.send(CommandFeedbackEvent {
+                position: world_pos,
+                kind: input_mode.feedback_kind(),
+            });
+        });
+}
+
+/// Shades blocked, slow and raised terrain; open ground at level 0 is left
+/// as the background.
+fn draw_terrain(painter: &egui::Painter, rect: egui::Rect, bounds: Rect, grid: &NavGrid) {
+    let cell_size = grid.cell_size().to_num::<f32>();
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            let elevation = grid.elevation(x, y);
+            let color = match grid.get_cell(x, y) {
+                Some(CellType::Blocked) => egui::Color32::from_rgb(60, 60, 60),
+                Some(CellType::SlowTerrain) => egui::Color32::from_rgb(70, 60, 30),
+                Some(CellType::Ramp) => egui::Color32::from_rgb(45, 60, 40),
+                _ if elevation > 0 => {
+                    let lift = elevation.saturating_mul(15);
+                    egui::Color32::from_rgb(20 + lift, 30 + lift, 20 + lift)
+                }
+                _ => continue,
+            };
+            painter.rect_filled(cell_rect(x, y, cell_size, rect, bounds), 0.0, color);
+        }
+    }
+}
+
+/// Darkens cells the player cannot currently see: unexplored ones almost
+/// black, explored ones dimmed.
+fn draw_fog(painter: &egui::Painter, rect: egui::Rect, bounds: Rect, fog: &FogOfWarMask) {
+    for (index, cell) in fog.cells.iter().enumerate() {
+        let color = match cell {
+            CellVisibility::Unexplored => egui::Color32::from_black_alpha(230),
+            CellVisibility::Explored => egui::Color32::from_black_alpha(140),
+            CellVisibility::Visible => continue,
+        };
+        let x = index as u32 % fog.width;
+        let y = index as u32 / fog.width;
+        painter.rect_filled(cell_rect(x, y, fog.cell_size, rect, bounds), 0.0, color);
+    }
+}
+
+/// Minimap rectangle covered by grid cell (`x`, `y`).
+fn cell_rect(x: u32, y: u32, cell_size: f32, rect: egui::Rect, bounds: Rect) -> egui::Rect {
+    let min = Vec2::new(x as f32, y as f32) * cell_size;
+    egui::Rect::from_two_pos(
+        world_to_minimap(min, rect, bounds),
+        world_to_minimap(min + Vec2::splat(cell_size), rect, bounds),
+    )
+}
+
+fn world_to_minimap(world_pos: Vec2, rect: egui::Rect, bounds: Rect) -> egui::Pos2 {
+    let normalized = (world_pos - bounds.min) / bounds.size();
+    egui::Pos2::new(
+        rect.min.x + normalized.x * rect.width(),
+        rect.max.y - normalized.y * rect.height(),
+    )
+}
+
+fn minimap_to_world(minimap_pos: egui::Pos2, rect: egui::Rect, bounds: Rect) -> Vec2 {
+    let normalized = Vec2::new(
+        (minimap_pos.x - rect.min.x) / rect.width(),
+        (rect.max.y - minimap_pos.y) / rect.height(),
+    );
+    bounds.min + normalized * bounds.size()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rts_core::factions::FactionId;
+
+    #[test]
+    fn minimap_world_round_trip() {
+        let rect =
+            egui::Rect::from_min_max(egui::Pos2::new(0.0, 0.0), egui::Pos2::new(200.0, 200.0));
+        let bounds = Rect::from_center_size(Vec2::ZERO, Vec2::splat(2000.0));
+        let world_pos = Vec2::new(150.0, -250.0);
+
+        let minimap_pos = world_to_minimap(world_pos, rect, bounds);
+        let back = minimap_to_world(minimap_pos, rect, bounds);
+
+        let delta = (world_pos - back).length();
+        assert!(delta < 0.01);
+    }
+
+    #[test]
+    fn pings_merge_nearby_and_expire() {
+        let mut pings = MinimapPings::default();
+        pings.ping(Vec2::ZERO, 3.0, 100.0);
+        pings.advance(2.0);
+        pings.ping(Vec2::new(50.0, 0.0), 3.0, 100.0);
+        assert_eq!(pings.pings.len(), 1);
+        assert_eq!(pings.pings[0].remaining, 3.0);
+
+        pings.ping(Vec2::new(500.0, 0.0), 3.0, 100.0);
+        assert_eq!(pings.pings.len(), 2);
+
+        pings.advance(3.0);
+        assert!(pings.pings.is_empty());
+    }
+
+    #[test]
+    fn damage_off_screen_pings_the_minimap() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<MinimapSettings>()
+            .init_resource::<MinimapPings>()
+            .insert_resource(PlayerFaction {
+                faction: FactionId::Continuity,
+            })
+            .add_systems(Update, ping_offscreen_damage);
+
+        app.world_mut().spawn(Window {
+            resolution: (800.0, 600.0).into(),
+            ..default()
+        });
+        app.world_mut().spawn((Transform::default(), MainCamera));
+
+        let faction = GameFaction {
+            faction: FactionId::Continuity,
+        };
+        let on_screen = app
+            .world_mut()
+            .spawn((
+                GamePosition::new(Vec2Fixed::new(Fixed::from_num(10), Fixed::from_num(10))),
+                faction,
+                GameHealth::new(100),
+            ))
+            .id();
+        let off_screen = app
+            .world_mut()
+            .spawn((
+                GamePosition::new(Vec2Fixed::new(Fixed::from_num(900), Fixed::ZERO)),
+                faction,
+                GameHealth::new(100),
+            ))
+            .id();
+
+        app.update();
+        assert!(app.world().resource::<MinimapPings>().pings.is_empty());
+
+        for entity in [on_screen, off_screen] {
+            app.world_mut()
+                .get_mut::<GameHealth>(entity)
+                .unwrap()
+                .current = 80;
+        }
+        app.update();
+
+        let pings = &app.world().resource::<MinimapPings>().pings;
+        assert_eq!(pings.len(), 1);
+        assert_eq!(pings[0].position, Vec2::new(900.0, 0.0));
+    }
+}
@@ -0,0 +1,129 @@
+//! Notifications plugin that passes the core's player alerts to the game.
+//!
+//! Tells the core which harvesters have gone idle and reports every
+//! [`Notification`] the core raises as a [`GameNotification`] event, for
+//! the UI's alerts and idle-harvester button.
+
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use rts_core::notifications::Notification;
+
+use crate::components::{CoreEntityId, GameHarvester, GameHarvesterState};
+use crate::simulation::{CoreSimulation, CoreSimulationSet};
+
+/// Plugin that reports player notifications.
+pub struct NotificationPlugin;
+
+impl Plugin for NotificationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<IdleHarvesters>()
+            .add_event::<GameNotification>()
+            .add_systems(
+                Update,
+                report_idle_harvesters.in_set(CoreSimulationSet::SyncIn),
+            )
+            .add_systems(Update, report_notifications.after(CoreSimulationSet::Tick));
+    }
+}
+
+/// A player alert raised by the core simulation.
+#[derive(Event, Debug, Clone)]
+pub struct GameNotification(pub Notification);
+
+/// Harvesters with nothing to do, oldest first.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct IdleHarvesters {
+    /// The idle harvesters.
+    pub entities: Vec<Entity>,
+}
+
+/// Tell the core about harvesters that have gone idle.
+///
+/// Harvesters pass through idle on their way between jobs, so one only
+/// counts once it has stayed idle for two frames: the harvester AI found
+/// nothing for it to do.
+fn report_idle_harvesters(
+    mut core: ResMut<CoreSimulation>,
+    mut idle: ResMut<IdleHarvesters>,
+    mut idle_last_frame: Local<HashSet<Entity>>,
+    harvesters: Query<(Entity, &CoreEntityId, &GameHarvester)>,
+) {
+    let mut idle_now = HashSet::new();
+    for (entity, core_id, harvester) in harvesters.iter() {
+        if harvester.state != GameHarvesterState::Idle {
+            continue;
+        }
+        idle_now.insert(entity);
+        if idle_last_frame.contains(&entity) && !idle.entities.contains(&entity) {
+            idle.entities.push(entity);
+            core.sim.notify_harvester_idle(core_id.0);
+        }
+    }
+    idle.entities.retain(|entity| idle_now.contains(entity));
+    *idle_last_frame = idle_now;
+}
+
+/// Pass the core's notifications on to the rest of the game.
+fn report_notifications(
+    mut core: ResMut<CoreSimulation>,
+    mut notifications: EventWriter<GameNotification>,
+) {
+    for notification in std::mem::take(&mut core.notifications) {
+        notifications.send(GameNotification(notification));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rts_core::components::FactionMember;
+    use rts_core::factions::FactionId;
+    use rts_core::math::Vec2Fixed;
+    use rts_core::simulation::EntitySpawnParams;
+
+    #[test]
+    fn harvesters_left_idle_are_reported_once() {
+        let mut app = App::new();
+        app.init_resource::<CoreSimulation>()
+            .add_plugins(NotificationPlugin);
+        let core_id = app
+            .world_mut()
+            .resource_mut::<CoreSimulation>()
+            .sim
+            .spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::ZERO),
+                faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+                ..Default::default()
+            });
+        let harvester = app
+            .world_mut()
+            .spawn((CoreEntityId(core_id), GameHarvester::new(10, 1)))
+            .id();
+
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(
+            app.world().resource::<IdleHarvesters>().entities,
+            vec![harvester]
+        );
+        let mut core = app.world_mut().resource_mut::<CoreSimulation>();
+        let reported = core.sim.tick().notifications;
+        assert_eq!(
+            reported,
+            vec![Notification::HarvesterIdle {
+                entity: core_id,
+                faction: FactionId::Continuity,
+            }]
+        );
+
+        // Back at work
+        app.world_mut()
+            .get_mut::<GameHarvester>(harvester)
+            .unwrap()
+            .state = GameHarvesterState::Returning(Entity::PLACEHOLDER);
+        app.update();
+        assert!(app.world().resource::<IdleHarvesters>().entities.is_empty());
+    }
+}
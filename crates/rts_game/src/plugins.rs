@@ -8,14 +8,20 @@ use bevy::prelude::*;
 
 // Import plugins from submodules
 use crate::ai::AiPlugin;
+use crate::autosave::AutosavePlugin;
 use crate::camera::CameraPlugin;
+use crate::chat::ChatPlugin;
 use crate::combat::CombatPlugin;
-use crate::construction::ConstructionPlugin;
+use crate::construction::{ConstructionPlugin, ConstructionProgressPlugin};
 use crate::data_loader::FactionDataPlugin;
 use crate::economy::EconomyPlugin;
 use crate::input::InputPlugin;
+use crate::minimap::MinimapPlugin;
+use crate::notifications::NotificationPlugin;
 use crate::production::ProductionPlugin;
 use crate::render::RenderPlugin;
+use crate::replay::ReplayPlugin;
+use crate::research::ResearchPlugin;
 use crate::selection::SelectionPlugin;
 use crate::simulation::SimulationPlugin;
 use crate::sprites::SpriteLoaderPlugin;
@@ -29,7 +35,7 @@ pub use crate::bundles::{
 };
 pub use crate::economy::PlayerResources;
 pub use crate::input::InputMode;
-pub use crate::selection::{SelectionHighlight, SelectionState};
+pub use crate::selection::{ControlGroups, SelectionHighlight, SelectionState};
 
 // ============================================================================
 // Plugin Group
@@ -62,10 +68,16 @@ impl PluginGroup for GamePlugins {
             .add(EconomyPlugin)
             .add(CombatPlugin)
             .add(ProductionPlugin)
+            .add(ResearchPlugin)
+            .add(NotificationPlugin)
             .add(ConstructionPlugin)
             .add(GameUiPlugin)
+            .add(MinimapPlugin)
+            .add(ChatPlugin)
             .add(AiPlugin)
             .add(VictoryPlugin)
+            .add(AutosavePlugin)
+            .add(ReplayPlugin)
     }
 }
 
@@ -91,6 +103,9 @@ impl PluginGroup for HeadlessGamePlugins {
             .add(EconomyPlugin)
             .add(CombatPlugin)
             .add(ProductionPlugin)
+            .add(ResearchPlugin)
+            .add(NotificationPlugin)
+            .add(ConstructionProgressPlugin)
             .add(AiPlugin)
             .add(HeadlessVictoryPlugin)
     }
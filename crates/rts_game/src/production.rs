@@ -3,6 +3,8 @@
 //! Handles production queues and unit spawning.
 
 use bevy::prelude::*;
+use rts_core::data::{FactionData, UnitData};
+use rts_core::production::ProductionError;
 
 use crate::bundles::{HarvesterBundle, UnitBundle};
 use crate::components::{
@@ -11,7 +13,7 @@ use crate::components::{
 use crate::data_loader::BevyUnitKindRegistry;
 use crate::data_loader::FactionRegistry;
 use crate::economy::PlayerResources;
-use crate::unit_utils::is_ranged_unit;
+use crate::unit_utils::{is_ranged_unit, unit_supply};
 
 /// Ticks per second - used to convert build_time from ticks (stored in data) to seconds (used in calculations).
 /// Matches rts_core::simulation::TICK_RATE which is 20 ticks per second.
@@ -20,6 +22,10 @@ const TICKS_PER_SECOND: f32 = 20.0;
 /// Default build time in seconds when unit data is not found.
 const DEFAULT_BUILD_TIME_SECONDS: f32 = 5.0;
 
+/// Share of a cancelled unit's cost that is refunded, scaled by the work
+/// left on it.
+pub const CANCEL_REFUND_RATE: f32 = 0.75;
+
 /// Plugin that handles unit production from buildings.
 pub struct ProductionPlugin;
 
@@ -40,6 +46,89 @@ pub struct SpawnUnitEvent {
     pub faction: rts_core::factions::FactionId,
 }
 
+/// Check whether `unit` can be queued in `queue` right now.
+///
+/// `has_prerequisite` says whether one entry of the unit's `tech_required`
+/// list (a building or technology ID) is satisfied.
+///
+/// # Errors
+///
+/// Returns the first reason the unit cannot be queued.
+pub fn validate_queue(
+    unit: &UnitData,
+    queue: &GameProductionQueue,
+    resources: &PlayerResources,
+    has_prerequisite: impl Fn(&str) -> bool,
+) -> Result<(), ProductionError> {
+    if !unit.tech_required.iter().all(|id| has_prerequisite(id)) {
+        return Err(ProductionError::PrerequisiteMissing);
+    }
+    if let Some(kind) = resources
+        .economy()
+        .shortfall(unit.cost as i32, &unit.extra_cost)
+    {
+        return Err(ProductionError::insufficient(kind));
+    }
+    if !resources.has_supply(unit_supply(unit)) {
+        return Err(ProductionError::SupplyBlocked);
+    }
+    if !queue.can_queue() {
+        return Err(ProductionError::QueueFull);
+    }
+    Ok(())
+}
+
+/// Queue `unit`, paying its cost and reserving its supply up front.
+///
+/// # Errors
+///
+/// Returns why the unit cannot be queued; nothing is paid in that case.
+pub fn queue_unit(
+    unit: &UnitData,
+    queue: &mut GameProductionQueue,
+    resources: &mut PlayerResources,
+    has_prerequisite: impl Fn(&str) -> bool,
+) -> Result<(), ProductionError> {
+    validate_queue(unit, queue, resources, has_prerequisite)?;
+    resources
+        .reserves
+        .spend(&unit.extra_cost)
+        .map_err(ProductionError::insufficient)?;
+    resources.feedstock -= unit.cost as i32;
+    resources.supply_used += unit_supply(unit);
+    queue.enqueue(unit.id.clone());
+    Ok(())
+}
+
+/// Cancel the unit at `index` in a production queue, refunding its cost in
+/// every resource in proportion to the work left on it and releasing its
+/// supply.
+///
+/// Returns the cancelled unit ID and the feedstock refunded.
+pub fn cancel_production(
+    queue: &mut GameProductionQueue,
+    index: usize,
+    faction_data: Option<&FactionData>,
+    resources: &mut PlayerResources,
+) -> Option<(String, i32)> {
+    let (unit_id, remaining) = queue.cancel(index)?;
+    let Some(unit) = faction_data.and_then(|data| data.get_unit(&unit_id)) else {
+        tracing::warn!(
+            "Failed to find unit data for unit '{}' when canceling production; no refund applied",
+            unit_id
+        );
+        return Some((unit_id, 0));
+    };
+    let refund = (unit.cost as f32 * remaining * CANCEL_REFUND_RATE) as i32;
+    resources.feedstock += refund;
+    for (kind, amount) in unit.extra_cost.iter() {
+        let refund = (amount as f32 * remaining * CANCEL_REFUND_RATE) as i32;
+        resources.reserves.add(kind, refund);
+    }
+    resources.supply_used -= unit_supply(unit);
+    Some((unit_id, refund))
+}
+
 /// Advances production queues and spawns completed units.
 fn production_system(
     time: Res<Time>,
@@ -158,16 +247,8 @@ fn production_system(
                             let refund = unit_data.cost as i32;
                             resources.feedstock += refund;
 
-                            // Refund supply based on tags
-                            let supply = if unit_data.has_tag("harvester")
-                                || unit_data.has_tag("worker")
-                                || is_ranged_unit(unit_data)
-                                || unit_data.has_tag("heavy")
-                            {
-                                2
-                            } else {
-                                1
-                            };
+                            // Refund supply
+                            let supply = unit_supply(unit_data);
                             resources.supply_used -= supply;
 
                             tracing::info!(
@@ -183,3 +264,106 @@ fn production_system(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_loader::load_factions_from_directory;
+    use rts_core::economy::{ResourceAmounts, ResourceKind};
+    use rts_core::factions::FactionId;
+
+    fn continuity() -> FactionData {
+        let registry = load_factions_from_directory(std::path::Path::new("assets/data/factions"))
+            .expect("faction data loads");
+        registry.get(FactionId::Continuity).unwrap().clone()
+    }
+
+    #[test]
+    fn queue_unit_pays_up_front_and_checks_prerequisites() {
+        let data = continuity();
+        let security_team = data.get_unit("security_team").unwrap();
+        let sovereign = data.get_unit("sovereign_platform").unwrap();
+        let mut queue = GameProductionQueue::new(5);
+        let mut resources = PlayerResources {
+            feedstock: 1000,
+            supply_cap: 50,
+            ..PlayerResources::default()
+        };
+        let supply_before = resources.supply_used;
+
+        queue_unit(security_team, &mut queue, &mut resources, |_| false).unwrap();
+        assert_eq!(resources.feedstock, 1000 - security_team.cost as i32);
+        assert_eq!(
+            resources.supply_used,
+            supply_before + unit_supply(security_team)
+        );
+        assert_eq!(queue.queue.len(), 1);
+
+        assert_eq!(
+            queue_unit(sovereign, &mut queue, &mut resources, |_| false),
+            Err(ProductionError::PrerequisiteMissing)
+        );
+        assert_eq!(queue.queue.len(), 1);
+        assert!(validate_queue(sovereign, &queue, &resources, |id| {
+            id == "strategic_operations"
+        })
+        .is_ok());
+
+        resources.feedstock = 0;
+        assert_eq!(
+            validate_queue(security_team, &queue, &resources, |_| true),
+            Err(ProductionError::InsufficientResources)
+        );
+    }
+
+    #[test]
+    fn queue_unit_pays_rarer_resources_too() {
+        let data = continuity();
+        let mut lancer = data.get_unit("security_team").unwrap().clone();
+        lancer.extra_cost = ResourceAmounts::new().with(ResourceKind::Exotics, 30);
+        let mut queue = GameProductionQueue::new(5);
+        let mut resources = PlayerResources {
+            feedstock: 1000,
+            supply_cap: 50,
+            ..PlayerResources::default()
+        };
+
+        assert_eq!(
+            queue_unit(&lancer, &mut queue, &mut resources, |_| true),
+            Err(ProductionError::InsufficientExotics)
+        );
+        assert_eq!(resources.feedstock, 1000);
+
+        resources.reserves.add(ResourceKind::Exotics, 40);
+        queue_unit(&lancer, &mut queue, &mut resources, |_| true).unwrap();
+        assert_eq!(resources.reserves.get(ResourceKind::Exotics), 10);
+    }
+
+    #[test]
+    fn cancel_production_refunds_remaining_work_at_any_index() {
+        let data = continuity();
+        let security_team = data.get_unit("security_team").unwrap();
+        let mut queue = GameProductionQueue::new(5);
+        queue.enqueue("security_team".to_string());
+        queue.enqueue("crowd_management_unit".to_string());
+        queue.queue[0].progress = 0.5;
+        let mut resources = PlayerResources::default();
+        let (feedstock, supply_used) = (resources.feedstock, resources.supply_used);
+
+        let (unit_id, refund) =
+            cancel_production(&mut queue, 0, Some(&data), &mut resources).unwrap();
+
+        assert_eq!(unit_id, "security_team");
+        let expected = (security_team.cost as f32 * 0.5 * CANCEL_REFUND_RATE) as i32;
+        assert_eq!(refund, expected);
+        assert_eq!(resources.feedstock, feedstock + expected);
+        assert_eq!(
+            resources.supply_used,
+            supply_used - unit_supply(security_team)
+        );
+        assert_eq!(queue.queue.len(), 1);
+        assert_eq!(queue.queue[0].unit_id, "crowd_management_unit");
+
+        assert!(cancel_production(&mut queue, 3, Some(&data), &mut resources).is_none());
+    }
+}
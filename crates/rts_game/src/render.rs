@@ -44,6 +44,41 @@ pub struct OutlinePlugin;
 pub struct CommandFeedbackEvent {
     /// World position for feedback.
     pub position: Vec2,
+    /// Kind of order, used to pick the marker's shape and colour.
+    pub kind: CommandFeedbackKind,
+}
+
+/// Kind of order a confirmation marker is shown for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommandFeedbackKind {
+    /// Plain move, harvest or rally order.
+    #[default]
+    Move,
+    /// Attack on a unit or the ground.
+    Attack,
+    /// Attack-move to a point.
+    AttackMove,
+    /// Patrol to a point.
+    Patrol,
+    /// Stop order.
+    Stop,
+    /// Hold position order.
+    HoldPosition,
+    /// Ability cast.
+    Ability,
+}
+
+impl CommandFeedbackKind {
+    /// Marker colour for this kind of order.
+    pub fn color(self) -> Color {
+        match self {
+            Self::Move => Color::srgb(0.1, 0.8, 1.0),
+            Self::Attack | Self::AttackMove => Color::srgb(1.0, 0.25, 0.2),
+            Self::Patrol => Color::srgb(1.0, 0.85, 0.2),
+            Self::Stop | Self::HoldPosition => Color::srgb(0.9, 0.9, 0.9),
+            Self::Ability => Color::srgb(0.75, 0.4, 1.0),
+        }
+    }
 }
 
 impl Plugin for DamageFlashPlugin {
@@ -96,6 +131,7 @@ const COMMAND_PING_DURATION: f32 = 0.45;
 #[derive(Component, Debug, Clone, Copy)]
 struct CommandPing {
     position: Vec2,
+    kind: CommandFeedbackKind,
     timer: f32,
 }
 
@@ -370,6 +406,7 @@ fn spawn_command_pings(mut commands: Commands, mut events: EventReader<CommandFe
     for event in events.read() {
         commands.spawn(CommandPing {
             position: event.position,
+            kind: event.kind,
             timer: COMMAND_PING_DURATION,
         });
     }
@@ -393,11 +430,33 @@ fn render_command_pings(pings: Query<&CommandPing>, mut gizmos: Gizmos) {
     for ping in pings.iter() {
         let alpha = (ping.timer / COMMAND_PING_DURATION).clamp(0.0, 1.0);
         let radius = 18.0 + (1.0 - alpha) * 10.0;
-        gizmos.circle_2d(
-            ping.position,
-            radius,
-            Color::srgba(0.1, 0.8, 1.0, 0.6 * alpha),
-        );
+        let color = ping.kind.color().with_alpha(0.6 * alpha);
+        match ping.kind {
+            CommandFeedbackKind::Stop => {
+                let arm = radius * 0.6;
+                gizmos.line_2d(
+                    ping.position - Vec2::splat(arm),
+                    ping.position + Vec2::splat(arm),
+                    color,
+                );
+                gizmos.line_2d(
+                    ping.position + Vec2::new(-arm, arm),
+                    ping.position + Vec2::new(arm, -arm),
+                    color,
+                );
+            }
+            CommandFeedbackKind::HoldPosition => {
+                gizmos.rect_2d(ping.position, 0.0, Vec2::splat(radius * 1.4), color);
+            }
+            CommandFeedbackKind::AttackMove | CommandFeedbackKind::Patrol => {
+                // Doubled ring so targeted orders read apart from plain moves
+                gizmos.circle_2d(ping.position, radius, color);
+                gizmos.circle_2d(ping.position, radius * 0.55, color);
+            }
+            _ => {
+                gizmos.circle_2d(ping.position, radius, color);
+            }
+        }
     }
 }
 
@@ -414,6 +473,7 @@ mod tests {
 
         app.world_mut().send_event(CommandFeedbackEvent {
             position: Vec2::new(5.0, -3.0),
+            kind: CommandFeedbackKind::Patrol,
         });
 
         app.update();
@@ -421,6 +481,7 @@ mod tests {
         let mut pings = app.world_mut().query::<&CommandPing>();
         let ping = pings.single(app.world());
         assert_eq!(ping.position, Vec2::new(5.0, -3.0));
+        assert_eq!(ping.kind, CommandFeedbackKind::Patrol);
     }
 
     #[test]
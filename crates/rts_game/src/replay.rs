@@ -0,0 +1,574 @@
+//! Replay recording and the "watch replay" mode.
+//!
+//! While a match is played, the [`ReplayRecorder`] on [`CoreSimulation`]
+//! logs every command the client hands to the core, and every change it
+//! makes to the core directly (spawns, despawns, navigation cells and
+//! attack targets), with the tick it happened on. When the match ends the
+//! replay is finalized with the core's state hash and written to disk.
+//!
+//! [`run_replay`](crate::run_replay) plays a replay file back with a
+//! [`ReplayPlayer`], mirroring its simulation into plain sprites, with a
+//! timeline bar to pause, change speed and scrub to any tick.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use rts_core::components::{Command as CoreCommand, EntityId};
+use rts_core::error::{GameError, Result};
+use rts_core::replay::{Replay, ReplayPlayer, WorldEdit};
+use rts_core::simulation::{Simulation, TICK_RATE};
+
+use crate::bundles::faction_color;
+use crate::components::{CoreEntityId, GameHealth, GamePosition};
+use crate::simulation::CoreSimulation;
+use crate::victory::GameState;
+
+/// Default directory for client replays.
+pub const DEFAULT_REPLAY_DIR: &str = "replays";
+
+/// File extension for client replays.
+pub const REPLAY_EXTENSION: &str = "replay";
+
+/// Scenario ID recorded in client replays.
+const CLIENT_SCENARIO: &str = "client";
+
+/// Slowest replay playback speed.
+pub const MIN_PLAYBACK_SPEED: f64 = 1.0;
+
+/// Fastest replay playback speed.
+pub const MAX_PLAYBACK_SPEED: f64 = 8.0;
+
+/// Sprite size of mirrored entities that can move.
+const MOBILE_SPRITE_SIZE: f32 = 24.0;
+
+/// Sprite size of mirrored entities that cannot move.
+const STATIC_SPRITE_SIZE: f32 = 64.0;
+
+/// Records a match into a [`Replay`]. Does nothing until started.
+#[derive(Debug, Default)]
+pub struct ReplayRecorder {
+    replay: Option<Replay>,
+}
+
+impl ReplayRecorder {
+    /// Start recording from the simulation's current state, discarding any
+    /// replay in progress.
+    ///
+    /// # Errors
+    /// Returns an error if the simulation cannot be serialized.
+    pub fn start(&mut self, sim: &Simulation) -> Result<()> {
+        self.replay = Some(Replay::new(CLIENT_SCENARIO, 0, sim)?);
+        Ok(())
+    }
+
+    /// Whether a replay is being recorded.
+    #[must_use]
+    pub fn is_recording(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// The replay recorded so far.
+    #[must_use]
+    pub fn replay(&self) -> Option<&Replay> {
+        self.replay.as_ref()
+    }
+
+    /// Record commands applied together as one batch.
+    pub fn record_batch(&mut self, tick: u64, batch: &[(EntityId, CoreCommand)]) {
+        if let Some(replay) = self.replay.as_mut() {
+            replay.record_batch(tick, batch);
+        }
+    }
+
+    /// Record a command appended to an entity's queue.
+    pub fn record_queued(&mut self, tick: u64, entity: EntityId, command: CoreCommand) {
+        if let Some(replay) = self.replay.as_mut() {
+            replay.record_queued(tick, entity, command);
+        }
+    }
+
+    /// Record a direct change to the core.
+    pub fn record_edit(&mut self, tick: u64, edit: WorldEdit) {
+        if let Some(replay) = self.replay.as_mut() {
+            replay.record_edit(tick, edit);
+        }
+    }
+
+    /// Stop recording and return the replay, finalized with the
+    /// simulation's tick and state hash.
+    pub fn finish(&mut self, sim: &Simulation) -> Option<Replay> {
+        let mut replay = self.replay.take()?;
+        replay.finalize(sim.get_tick(), sim.state_hash());
+        Some(replay)
+    }
+}
+
+/// Where finished matches are saved. `None` disables recording.
+#[derive(Resource, Debug, Clone)]
+pub struct ReplaySettings {
+    /// Directory replays are written to.
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for ReplaySettings {
+    fn default() -> Self {
+        Self {
+            dir: Some(PathBuf::from(DEFAULT_REPLAY_DIR)),
+        }
+    }
+}
+
+/// Write a replay into `dir` under a timestamped name.
+///
+/// # Errors
+/// Returns an error if the directory cannot be created or the file written.
+pub fn save_replay(replay: &Replay, dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir).map_err(|e| {
+        GameError::InvalidState(format!("Failed to create replay directory: {}", e))
+    })?;
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let path = dir.join(format!(
+        "match_{secs}_{}.{REPLAY_EXTENSION}",
+        replay.final_tick
+    ));
+    replay.save(&path)?;
+    Ok(path)
+}
+
+/// Plugin that records the match and writes the replay when it ends.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplaySettings>()
+            .add_systems(PostStartup, start_replay_recording)
+            .add_systems(Update, write_replay_on_game_end);
+    }
+}
+
+/// Start recording once startup (and any autosave restore) has settled the
+/// core, before the first frame syncs entities into it.
+fn start_replay_recording(settings: Res<ReplaySettings>, mut core: ResMut<CoreSimulation>) {
+    if settings.dir.is_none() {
+        return;
+    }
+    let core = &mut *core;
+    if let Err(e) = core.recorder.start(&core.sim) {
+        tracing::warn!("Replay recording disabled: {e}");
+    }
+}
+
+fn write_replay_on_game_end(
+    settings: Res<ReplaySettings>,
+    game_state: Option<Res<GameState>>,
+    mut core: ResMut<CoreSimulation>,
+) {
+    if game_state.map_or(true, |state| *state == GameState::Playing) {
+        return;
+    }
+    let core = &mut *core;
+    let (Some(dir), Some(replay)) = (settings.dir.as_ref(), core.recorder.finish(&core.sim)) else {
+        return;
+    };
+    match save_replay(&replay, dir) {
+        Ok(path) => tracing::info!(
+            "Saved replay to {} ({} commands, {} ticks)",
+            path.display(),
+            replay.command_count(),
+            replay.final_tick
+        ),
+        Err(e) => tracing::warn!("Saving replay failed: {e}"),
+    }
+}
+
+/// Replay being watched.
+#[derive(Resource, Debug)]
+pub struct ReplayPlayback {
+    /// Player driving the replayed simulation.
+    pub player: ReplayPlayer,
+    /// Accumulator for fixed-step ticking.
+    accumulator: f32,
+}
+
+impl ReplayPlayback {
+    /// Watch a replay from its start.
+    #[must_use]
+    pub fn new(player: ReplayPlayer) -> Self {
+        Self {
+            player,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Set the playback speed, kept between [`MIN_PLAYBACK_SPEED`] and
+    /// [`MAX_PLAYBACK_SPEED`].
+    pub fn set_speed(&mut self, speed: f64) {
+        self.player
+            .set_speed(speed.clamp(MIN_PLAYBACK_SPEED, MAX_PLAYBACK_SPEED));
+    }
+
+    /// Jump to `tick`, re-simulating from the nearest earlier snapshot.
+    pub fn seek(&mut self, tick: u64) {
+        self.accumulator = 0.0;
+        if let Err(e) = self.player.seek(tick) {
+            tracing::warn!("Seeking replay to tick {tick} failed: {e}");
+        }
+    }
+}
+
+/// Plugin that plays back a [`ReplayPlayback`] and mirrors its entities.
+///
+/// Space pauses, `=` and `-` double and halve the playback speed.
+pub struct ReplayViewerPlugin;
+
+/// Plugin that draws the replay timeline bar. Needs `EguiPlugin`.
+pub struct ReplayTimelinePlugin;
+
+impl Plugin for ReplayTimelinePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, ui_replay_timeline.before(advance_replay_playback));
+    }
+}
+
+impl Plugin for ReplayViewerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                replay_playback_controls,
+                advance_replay_playback,
+                mirror_replay_entities,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn replay_playback_controls(
+    keyboard: Option<Res<ButtonInput<KeyCode>>>,
+    mut playback: ResMut<ReplayPlayback>,
+) {
+    let Some(keyboard) = keyboard else {
+        return;
+    };
+    if keyboard.just_pressed(KeyCode::Space) {
+        playback.player.toggle_pause();
+    }
+    if keyboard.just_pressed(KeyCode::Equal) {
+        let speed = playback.player.playback_speed * 2.0;
+        playback.set_speed(speed);
+    }
+    if keyboard.just_pressed(KeyCode::Minus) {
+        let speed = playback.player.playback_speed / 2.0;
+        playback.set_speed(speed);
+    }
+}
+
+/// Bottom bar with play/pause, speed buttons and a scrubber. Dragging the
+/// scrubber only previews the tick; the replay seeks when it is released.
+fn ui_replay_timeline(
+    mut contexts: EguiContexts,
+    mut playback: ResMut<ReplayPlayback>,
+    mut scrub: Local<Option<u64>>,
+) {
+    let final_tick = playback.player.replay().final_tick;
+    egui::TopBottomPanel::bottom("replay_timeline").show(contexts.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            let label = if playback.player.paused {
+                "Play"
+            } else {
+                "Pause"
+            };
+            if ui.button(label).clicked() {
+                playback.player.toggle_pause();
+            }
+
+            for speed in [1.0, 2.0, 4.0, 8.0] {
+                let selected = (playback.player.playback_speed - speed).abs() < f64::EPSILON;
+                if ui.selectable_label(selected, format!("{speed}x")).clicked() {
+                    playback.set_speed(speed);
+                }
+            }
+
+            let mut tick = scrub.unwrap_or(playback.player.current_tick());
+            ui.label(format!(
+                "{} / {}",
+                format_replay_time(tick),
+                format_replay_time(final_tick)
+            ));
+            ui.spacing_mut().slider_width = (ui.available_width() - 16.0).max(100.0);
+            let response = ui.add(egui::Slider::new(&mut tick, 0..=final_tick).show_value(false));
+            if response.dragged() {
+                *scrub = Some(tick);
+            } else if response.drag_stopped() || response.changed() {
+                *scrub = None;
+                playback.seek(tick);
+            }
+        });
+    });
+}
+
+/// `m:ss` for a tick count.
+fn format_replay_time(tick: u64) -> String {
+    let secs = tick / u64::from(TICK_RATE);
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+fn advance_replay_playback(time: Res<Time>, mut playback: ResMut<ReplayPlayback>) {
+    if playback.player.paused || playback.player.is_finished() {
+        playback.accumulator = 0.0;
+        return;
+    }
+    playback.accumulator += time.delta_seconds() * playback.player.playback_speed as f32;
+    let step = 1.0 / TICK_RATE as f32;
+
+    while playback.accumulator >= step {
+        playback.accumulator -= step;
+        if !playback.player.advance() {
+            playback.accumulator = 0.0;
+            tracing::info!("Replay finished at tick {}", playback.player.current_tick());
+            break;
+        }
+    }
+}
+
+/// Spawn a sprite for every replayed entity with a position, keep sprites
+/// in step with the simulation and remove those whose entity is gone.
+fn mirror_replay_entities(
+    mut commands: Commands,
+    playback: Res<ReplayPlayback>,
+    mut mirrored: Query<(
+        Entity,
+        &CoreEntityId,
+        &mut GamePosition,
+        Option<&mut GameHealth>,
+    )>,
+) {
+    let sim = playback.player.simulation();
+    let mut seen = HashSet::new();
+
+    for (entity, core_id, mut position, health) in &mut mirrored {
+        let Some(core_entity) = sim.get_entity(core_id.0) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        seen.insert(core_id.0);
+        if let Some(pos) = core_entity.position {
+            if position.value != pos.value {
+                position.value = pos.value;
+            }
+        }
+        if let (Some(mut health), Some(core_health)) = (health, core_entity.health) {
+            if health.current != core_health.current {
+                health.current = core_health.current;
+            }
+        }
+    }
+
+    for id in sim.entities().sorted_ids() {
+        let Some(core_entity) = sim.get_entity(id) else {
+            continue;
+        };
+        let Some(pos) = core_entity.position else {
+            continue;
+        };
+        if core_entity.cosmetic.is_some() || seen.contains(&core_entity.id) {
+            continue;
+        }
+        let size = if core_entity.movement.is_some() {
+            MOBILE_SPRITE_SIZE
+        } else {
+            STATIC_SPRITE_SIZE
+        };
+        let color = core_entity
+            .faction
+            .map_or(Color::srgb(0.5, 0.5, 0.5), |f| faction_color(f.faction));
+        let position = GamePosition::new(pos.value);
+        let mut sprite = commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(size)),
+                    ..default()
+                },
+                transform: Transform::from_translation(position.as_vec2().extend(0.0)),
+                ..default()
+            },
+            CoreEntityId(core_entity.id),
+            position,
+        ));
+        if let Some(health) = core_entity.health {
+            sprite.insert(GameHealth {
+                current: health.current,
+                max: health.max,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bundles::{TurretBundle, UnitBundle};
+    use crate::simulation::{CoreCommandBuffer, SimulationPlugin};
+    use rts_core::factions::FactionId;
+    use rts_core::math::{Fixed, Vec2Fixed};
+    use rts_core::pathfinding::CellType;
+
+    #[test]
+    fn finished_match_replays_to_the_same_hash() {
+        let dir = std::env::temp_dir().join(format!("rts_client_replay_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins((SimulationPlugin, ReplayPlugin));
+        app.init_resource::<GameState>();
+        app.insert_resource(ReplaySettings {
+            dir: Some(dir.clone()),
+        });
+
+        let unit = app
+            .world_mut()
+            .spawn(UnitBundle::new(
+                Vec2::new(100.0, 100.0),
+                FactionId::Continuity,
+                100,
+            ))
+            .id();
+        let doomed = app
+            .world_mut()
+            .spawn(UnitBundle::new(
+                Vec2::new(140.0, 300.0),
+                FactionId::Collegium,
+                100,
+            ))
+            .id();
+        app.world_mut().spawn(TurretBundle::new(
+            Vec2::new(400.0, 400.0),
+            FactionId::Continuity,
+        ));
+        app.update();
+        assert!(app
+            .world()
+            .resource::<CoreSimulation>()
+            .recorder
+            .is_recording());
+
+        let unit_id = app.world().get::<CoreEntityId>(unit).unwrap().0;
+        let target = Vec2Fixed::new(Fixed::from_num(600), Fixed::from_num(120));
+        {
+            let mut buffer = app.world_mut().resource_mut::<CoreCommandBuffer>();
+            buffer.set(unit_id, CoreCommand::MoveTo(target));
+            buffer.queue(unit_id, CoreCommand::Stop);
+        }
+        app.update();
+        for _ in 0..20 {
+            app.world_mut().resource_mut::<CoreSimulation>().sim.tick();
+        }
+        app.world_mut().entity_mut(doomed).despawn();
+        app.update();
+        for _ in 0..20 {
+            app.world_mut().resource_mut::<CoreSimulation>().sim.tick();
+        }
+
+        *app.world_mut().resource_mut::<GameState>() = GameState::Victory;
+        app.update();
+        let core = app.world().resource::<CoreSimulation>();
+        assert!(!core.recorder.is_recording());
+        let (final_tick, final_hash) = (core.sim.get_tick(), core.sim.state_hash());
+
+        let path = std::fs::read_dir(&dir)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        assert_eq!(
+            path.extension().and_then(|e| e.to_str()),
+            Some(REPLAY_EXTENSION)
+        );
+        let replay = Replay::load(&path).unwrap();
+        assert_eq!(replay.final_tick, final_tick);
+        assert_eq!(replay.final_hash, final_hash);
+        assert_eq!(replay.command_count(), 2);
+        assert!(replay
+            .edits
+            .iter()
+            .any(|e| matches!(e.edit, WorldEdit::Despawn(_))));
+        assert!(replay.edits.iter().any(|e| matches!(
+            e.edit,
+            WorldEdit::SetCell {
+                cell: CellType::Blocked,
+                ..
+            }
+        )));
+
+        let mut player = ReplayPlayer::new(replay).unwrap();
+        assert!(player.verify().unwrap());
+        assert_eq!(player.simulation().entities().len(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn viewer_mirrors_replayed_entities() {
+        let mut sim = Simulation::new();
+        let id = sim.spawn_entity(rts_core::simulation::EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(10), Fixed::from_num(20))),
+            health: Some(50),
+            ..Default::default()
+        });
+        let mut replay = Replay::new("test", 0, &sim).unwrap();
+        replay.record_edit(2, WorldEdit::Despawn(id));
+        replay.finalize(4, 0);
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(ReplayViewerPlugin);
+        app.insert_resource(ReplayPlayback::new(ReplayPlayer::new(replay).unwrap()));
+        app.update();
+
+        let mut mirrored = app
+            .world_mut()
+            .query::<(&CoreEntityId, &GamePosition, &GameHealth)>();
+        let (core_id, position, health) = mirrored.single(app.world());
+        assert_eq!(core_id.0, id);
+        assert_eq!(position.as_vec2(), Vec2::new(10.0, 20.0));
+        assert_eq!(health.max, 50);
+
+        for _ in 0..3 {
+            app.world_mut()
+                .resource_mut::<ReplayPlayback>()
+                .player
+                .advance();
+        }
+        app.update();
+        assert_eq!(mirrored.iter(app.world()).count(), 0);
+
+        // Scrubbing back before the despawn brings the sprite back
+        app.world_mut().resource_mut::<ReplayPlayback>().seek(1);
+        app.update();
+        assert_eq!(mirrored.iter(app.world()).count(), 1);
+    }
+
+    #[test]
+    fn playback_speed_stays_within_viewer_range() {
+        let replay = Replay::new("test", 0, &Simulation::new()).unwrap();
+        let mut playback = ReplayPlayback::new(ReplayPlayer::new(replay).unwrap());
+
+        playback.set_speed(0.25);
+        assert_eq!(playback.player.playback_speed, MIN_PLAYBACK_SPEED);
+        playback.set_speed(16.0);
+        assert_eq!(playback.player.playback_speed, MAX_PLAYBACK_SPEED);
+        playback.set_speed(4.0);
+        assert_eq!(playback.player.playback_speed, 4.0);
+
+        assert_eq!(format_replay_time(u64::from(TICK_RATE) * 75), "1:15");
+    }
+}
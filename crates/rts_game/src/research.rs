@@ -0,0 +1,288 @@
+//! Research plugin that drives the core simulation's tech system.
+//!
+//! Gives the core each faction's technologies, charges the local player
+//! for research they queue, refunds what they cancel and reports research
+//! progress as [`ResearchUpdate`] events.
+
+use bevy::prelude::*;
+use rts_core::components::EntityId;
+use rts_core::replay::WorldEdit;
+use rts_core::research::ResearchEvent;
+
+use crate::components::PlayerFaction;
+use crate::data_loader::FactionRegistry;
+use crate::economy::PlayerResources;
+use crate::simulation::{CoreSimulation, CoreSimulationSet};
+
+/// Plugin that handles technology research.
+pub struct ResearchPlugin;
+
+impl Plugin for ResearchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ResearchRequests>()
+            .add_event::<ResearchUpdate>()
+            .add_systems(
+                Update,
+                (
+                    register_tech_trees.run_if(resource_changed::<FactionRegistry>),
+                    apply_research_requests,
+                )
+                    .chain()
+                    .in_set(CoreSimulationSet::SyncIn),
+            )
+            .add_systems(Update, report_research.after(CoreSimulationSet::Tick));
+    }
+}
+
+/// A research order from the UI or a controller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResearchRequest {
+    /// Queue a technology at a facility.
+    Queue {
+        /// Core ID of the facility.
+        building: EntityId,
+        /// Technology to research.
+        tech_id: String,
+    },
+    /// Cancel the most recently queued research at a facility.
+    Cancel(EntityId),
+}
+
+impl ResearchRequest {
+    /// Core ID of the facility the request is for.
+    #[must_use]
+    pub const fn building(&self) -> EntityId {
+        match self {
+            Self::Queue { building, .. } | Self::Cancel(building) => *building,
+        }
+    }
+}
+
+/// Research orders waiting to be handed to the core simulation.
+#[derive(Resource, Debug, Default)]
+pub struct ResearchRequests {
+    pending: Vec<ResearchRequest>,
+}
+
+impl ResearchRequests {
+    /// Queue `tech_id` at `building`.
+    pub fn queue(&mut self, building: EntityId, tech_id: impl Into<String>) {
+        self.pending.push(ResearchRequest::Queue {
+            building,
+            tech_id: tech_id.into(),
+        });
+    }
+
+    /// Cancel the most recently queued research at `building`.
+    pub fn cancel(&mut self, building: EntityId) {
+        self.pending.push(ResearchRequest::Cancel(building));
+    }
+}
+
+/// Research started, completed or cancelled in the core simulation.
+#[derive(Event, Debug, Clone)]
+pub struct ResearchUpdate(pub ResearchEvent);
+
+/// Give the core every loaded faction's technologies.
+///
+/// Factions that already have a tech tree, such as those in a resumed
+/// autosave, keep it and their research.
+fn register_tech_trees(registry: Res<FactionRegistry>, mut core: ResMut<CoreSimulation>) {
+    let mut factions: Vec<_> = registry.all_factions().collect();
+    factions.sort_by_key(|data| data.id);
+    for data in factions {
+        if core.sim.tech_state(data.id).is_some() {
+            continue;
+        }
+        core.sim
+            .set_tech_tree(data.id, data.technologies.iter().cloned());
+        let tick = core.sim.get_tick();
+        core.recorder.record_edit(
+            tick,
+            WorldEdit::SetTechTree {
+                faction: data.id,
+                techs: data.technologies.clone(),
+            },
+        );
+    }
+}
+
+/// Hand research orders for the local player's facilities to the core,
+/// charging for what is queued and refunding what is cancelled.
+fn apply_research_requests(
+    mut requests: ResMut<ResearchRequests>,
+    mut core: ResMut<CoreSimulation>,
+    mut resources: ResMut<PlayerResources>,
+    player_faction: Option<Res<PlayerFaction>>,
+) {
+    let Some(player_faction) = player_faction else {
+        requests.pending.clear();
+        return;
+    };
+    for request in std::mem::take(&mut requests.pending) {
+        let building = request.building();
+        let edit = request_edit(&request);
+        let owned = core
+            .sim
+            .get_entity(building)
+            .and_then(|e| e.faction)
+            .is_some_and(|f| f.faction == player_faction.faction);
+        if !owned {
+            tracing::debug!(building, "Ignoring research at a facility we don't own");
+            continue;
+        }
+
+        let result = match request {
+            ResearchRequest::Queue { building, tech_id } => core
+                .sim
+                .validate_research(building, &tech_id)
+                .map(|tech| tech.cost)
+                .and_then(|cost| {
+                    if resources.feedstock < cost {
+                        return Err(rts_core::error::GameError::InsufficientResources {
+                            resource: "feedstock".to_string(),
+                            required: cost.max(0) as u32,
+                            available: resources.feedstock.max(0) as u32,
+                        });
+                    }
+                    core.sim.queue_research(building, &tech_id)?;
+                    resources.feedstock -= cost;
+                    Ok(())
+                }),
+            ResearchRequest::Cancel(building) => core.sim.cancel_research(building).map(|item| {
+                resources.feedstock += item.cost;
+            }),
+        };
+        match result {
+            Ok(()) => {
+                let tick = core.sim.get_tick();
+                core.recorder.record_edit(tick, edit);
+            }
+            Err(e) => tracing::debug!(error = %e, "Research request failed"),
+        }
+    }
+}
+
+/// The replay edit that repeats a research request.
+fn request_edit(request: &ResearchRequest) -> WorldEdit {
+    match request {
+        ResearchRequest::Queue { building, tech_id } => WorldEdit::QueueResearch {
+            building: *building,
+            tech_id: tech_id.clone(),
+        },
+        ResearchRequest::Cancel(building) => WorldEdit::CancelResearch(*building),
+    }
+}
+
+/// Pass the core's research events on to the rest of the game.
+fn report_research(mut core: ResMut<CoreSimulation>, mut updates: EventWriter<ResearchUpdate>) {
+    for event in std::mem::take(&mut core.research) {
+        if let ResearchEvent::ResearchComplete {
+            faction, tech_id, ..
+        } = &event
+        {
+            tracing::info!(faction = ?faction, tech = %tech_id, "Research complete");
+        }
+        updates.send(ResearchUpdate(event));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rts_core::components::FactionMember;
+    use rts_core::data::{FactionData, TechData};
+    use rts_core::factions::FactionId;
+    use rts_core::math::Vec2Fixed;
+    use rts_core::research::ResearchFacility;
+    use rts_core::simulation::EntitySpawnParams;
+
+    fn tech(id: &str) -> TechData {
+        TechData {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            cost: 100,
+            research_time: 2,
+            effects: vec![],
+            prerequisites: vec![],
+            tier: 1,
+            exclusive_with: vec![],
+            researched_at: None,
+            is_doctrine: false,
+            branch: None,
+            icon: None,
+        }
+    }
+
+    #[test]
+    fn research_is_paid_for_and_refunded() {
+        let faction = FactionId::Continuity;
+        let mut registry = FactionRegistry::new();
+        registry
+            .register(FactionData {
+                id: faction,
+                display_name: "Continuity".to_string(),
+                description: String::new(),
+                units: vec![],
+                buildings: vec![],
+                technologies: vec![tech("drill"), tech("doctrine")],
+                primary_color: [0, 0, 0],
+                secondary_color: [0, 0, 0],
+                starting_units: vec![],
+                starting_buildings: vec![],
+                starting_presets: vec![],
+                starting_feedstock: 0,
+                mechanics: vec![],
+                upkeep: Default::default(),
+                market: Default::default(),
+            })
+            .unwrap();
+
+        let mut app = App::new();
+        app.insert_resource(registry)
+            .insert_resource(PlayerFaction::new(faction))
+            .insert_resource(PlayerResources {
+                feedstock: 150,
+                ..Default::default()
+            })
+            .init_resource::<CoreSimulation>()
+            .add_plugins(ResearchPlugin);
+        let lab = app
+            .world_mut()
+            .resource_mut::<CoreSimulation>()
+            .sim
+            .spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::ZERO),
+                faction: Some(FactionMember::new(faction, 0)),
+                research: Some(ResearchFacility::new(None)),
+                ..Default::default()
+            });
+
+        {
+            let mut requests = app.world_mut().resource_mut::<ResearchRequests>();
+            requests.queue(lab, "drill");
+            // Can't afford a second technology
+            requests.queue(lab, "doctrine");
+        }
+        app.update();
+        assert_eq!(app.world().resource::<PlayerResources>().feedstock, 50);
+        let core = app.world().resource::<CoreSimulation>();
+        let queued = &core
+            .sim
+            .get_entity(lab)
+            .unwrap()
+            .row
+            .research
+            .as_ref()
+            .unwrap()
+            .queue;
+        assert_eq!(queued.len(), 1);
+
+        app.world_mut()
+            .resource_mut::<ResearchRequests>()
+            .cancel(lab);
+        app.update();
+        assert_eq!(app.world().resource::<PlayerResources>().feedstock, 150);
+    }
+}
@@ -1,6 +1,7 @@
 //! Selection plugin for unit selection mechanics.
 //!
-//! Provides click-to-select, box selection, and visual highlighting.
+//! Provides click-to-select, box selection, control groups, and visual
+//! highlighting.
 
 use bevy::gizmos::gizmos::Gizmos;
 use bevy::prelude::*;
@@ -13,8 +14,11 @@ use crate::construction::BuildingPlacement;
 /// Plugin for unit selection mechanics.
 ///
 /// Provides:
-/// - Click to select single unit
+/// - Click to select single unit, Shift+click to add to the selection
+/// - Double-click to select every visible unit of the same kind
 /// - Box select with drag
+/// - Control groups: Ctrl+1..9 assigns, 1..9 recalls, Shift+1..9 adds the
+///   group to the selection, and a double-tap centres the camera on it
 /// - Selection visual highlighting
 pub struct SelectionPlugin;
 
@@ -22,8 +26,10 @@ impl Plugin for SelectionPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<SelectionState>()
             .init_resource::<DoubleClickState>()
+            .init_resource::<ControlGroups>()
             .init_resource::<PlayerFaction>()
             .add_systems(Update, handle_selection_input)
+            .add_systems(Update, handle_control_groups)
             .add_systems(Update, update_selection_box)
             .add_systems(Update, apply_box_selection)
             .add_systems(Update, sync_selection_visuals);
@@ -68,6 +74,65 @@ fn double_click_unit_id(
     }
 }
 
+/// Number of control groups, bound to the digit keys 1..9.
+pub const CONTROL_GROUP_COUNT: usize = 9;
+
+/// Keys that assign and recall control groups, in group order.
+const CONTROL_GROUP_KEYS: [KeyCode; CONTROL_GROUP_COUNT] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+/// Saved selections the player can recall with the digit keys.
+#[derive(Resource, Debug, Default)]
+pub struct ControlGroups {
+    groups: [Vec<Entity>; CONTROL_GROUP_COUNT],
+    last_recall: Option<(usize, f32)>,
+}
+
+impl ControlGroups {
+    /// Replace a group's members. Out-of-range groups are ignored.
+    pub fn assign(&mut self, group: usize, members: impl IntoIterator<Item = Entity>) {
+        if let Some(slot) = self.groups.get_mut(group) {
+            *slot = members.into_iter().collect();
+            slot.sort();
+            slot.dedup();
+        }
+    }
+
+    /// Members of a group, in a stable order; empty for unknown groups.
+    #[must_use]
+    pub fn members(&self, group: usize) -> &[Entity] {
+        self.groups.get(group).map_or(&[], Vec::as_slice)
+    }
+
+    /// Drop an entity from every group, e.g. once it has died.
+    pub fn forget(&mut self, entity: Entity) {
+        for group in &mut self.groups {
+            group.retain(|&member| member != entity);
+        }
+    }
+
+    /// Note that `group` was recalled at `now`, returning whether this is a
+    /// double-tap: the same group recalled again within the double-click
+    /// threshold.
+    pub fn recall(&mut self, group: usize, now: f32) -> bool {
+        let double_tap = self
+            .last_recall
+            .is_some_and(|(last, at)| last == group && now - at <= DOUBLE_CLICK_THRESHOLD);
+        // A third tap starts a new pair rather than centring again
+        self.last_recall = (!double_tap).then_some((group, now));
+        double_tap
+    }
+}
+
 /// Visual component for selection highlight.
 #[derive(Component)]
 pub struct SelectionHighlight;
@@ -156,24 +221,32 @@ fn handle_selection_input(
             }
 
             // Find and select unit under cursor
-            let mut closest: Option<(Entity, f32, Option<&UnitDataId>)> = None;
+            let mut closest: Option<(Entity, f32, &GameFaction, Option<&UnitDataId>)> = None;
 
-            for (entity, transform, sprite, _faction, unit_data_id) in selectables.iter() {
+            for (entity, transform, sprite, faction, unit_data_id) in selectables.iter() {
                 let click_radius = selection_radius(sprite);
                 let distance = transform.translation.truncate().distance(world_position);
-                if distance < click_radius && closest.map_or(true, |(_, d, _)| distance < d) {
-                    closest = Some((entity, distance, unit_data_id));
+                if distance < click_radius && closest.map_or(true, |(_, d, _, _)| distance < d) {
+                    closest = Some((entity, distance, faction, unit_data_id));
                 }
             }
 
-            if let Some((entity, _, unit_data_id)) = closest {
+            if let Some((entity, _, clicked_faction, unit_data_id)) = closest {
                 let now = time.elapsed_seconds();
                 let double_click_id =
                     double_click_unit_id(&mut double_click_state, unit_data_id, now);
 
                 if let Some(unit_id) = double_click_id {
-                    for (candidate, _, _sprite, _faction, candidate_id) in selectables.iter() {
-                        if candidate_id.map(|id| id.as_str()) == Some(unit_id.as_str()) {
+                    // Type-select covers only what is on screen
+                    let view = visible_world_rect(window, camera, camera_transform);
+                    for (candidate, transform, _sprite, faction, candidate_id) in selectables.iter()
+                    {
+                        let on_screen = view
+                            .map_or(true, |view| view.contains(transform.translation.truncate()));
+                        if on_screen
+                            && faction.faction == clicked_faction.faction
+                            && candidate_id.map(|id| id.as_str()) == Some(unit_id.as_str())
+                        {
                             commands.entity(candidate).insert(Selected);
                         }
                     }
@@ -188,6 +261,93 @@ fn handle_selection_input(
     }
 }
 
+/// The part of the world the camera shows in `window`.
+fn visible_world_rect(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Rect> {
+    let corner = |screen: Vec2| camera.viewport_to_world_2d(camera_transform, screen);
+    Some(Rect::from_corners(
+        corner(Vec2::ZERO)?,
+        corner(Vec2::new(window.width(), window.height()))?,
+    ))
+}
+
+/// Assigns, recalls and extends control groups from the digit keys, and
+/// centres the camera on a group tapped twice.
+///
+/// Ctrl+N saves the selection as group N, N replaces the selection with
+/// the group and Shift+N adds the group to it. Dead members drop out of
+/// their groups when next recalled.
+fn handle_control_groups(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    placement: Res<BuildingPlacement>,
+    mut groups: ResMut<ControlGroups>,
+    mut commands: Commands,
+    selectables: Query<&Transform, With<Selectable>>,
+    selected: Query<Entity, With<Selected>>,
+    mut camera_query: Query<&mut Transform, (With<MainCamera>, Without<Selectable>)>,
+) {
+    // The build menu uses the digit keys for its own hotkeys
+    if placement.menu_open || placement.placing.is_some() {
+        return;
+    }
+    let Some(group) = CONTROL_GROUP_KEYS
+        .iter()
+        .position(|&key| keyboard.just_pressed(key))
+    else {
+        return;
+    };
+
+    let ctrl_held =
+        keyboard.pressed(KeyCode::ControlLeft) || keyboard.pressed(KeyCode::ControlRight);
+    if ctrl_held {
+        groups.assign(group, selected.iter());
+        return;
+    }
+
+    let dead: Vec<Entity> = groups
+        .members(group)
+        .iter()
+        .copied()
+        .filter(|&member| selectables.get(member).is_err())
+        .collect();
+    for member in dead {
+        groups.forget(member);
+    }
+    let members = groups.members(group);
+    if members.is_empty() {
+        return;
+    }
+
+    let shift_held = keyboard.pressed(KeyCode::ShiftLeft) || keyboard.pressed(KeyCode::ShiftRight);
+    if !shift_held {
+        for entity in selected.iter() {
+            if !members.contains(&entity) {
+                commands.entity(entity).remove::<Selected>();
+            }
+        }
+    }
+    for &member in members {
+        commands.entity(member).insert(Selected);
+    }
+
+    let centre = members
+        .iter()
+        .filter_map(|&member| selectables.get(member).ok())
+        .map(|transform| transform.translation.truncate())
+        .sum::<Vec2>()
+        / members.len() as f32;
+    if groups.recall(group, time.elapsed_seconds()) {
+        if let Ok(mut camera) = camera_query.get_single_mut() {
+            camera.translation.x = centre.x;
+            camera.translation.y = centre.y;
+        }
+    }
+}
+
 /// Updates the visual selection box during dragging.
 fn update_selection_box(
     selection_state: Res<SelectionState>,
@@ -338,6 +498,128 @@ mod tests {
         assert!(second.is_none());
     }
 
+    #[test]
+    fn control_groups_detect_double_taps() {
+        let mut groups = ControlGroups::default();
+
+        assert!(!groups.recall(0, 1.0));
+        assert!(groups.recall(0, 1.2));
+        assert!(!groups.recall(0, 1.3));
+        assert!(!groups.recall(1, 1.4));
+        assert!(!groups.recall(0, 1.5));
+        assert!(!groups.recall(0, 2.5));
+    }
+
+    fn control_group_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(ButtonInput::<KeyCode>::default());
+        app.insert_resource(BuildingPlacement::default());
+        app.init_resource::<ControlGroups>();
+        app.add_systems(Update, handle_control_groups);
+        app
+    }
+
+    fn press(app: &mut App, keys: &[KeyCode]) {
+        let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        input.reset_all();
+        for &key in keys {
+            input.press(key);
+        }
+        app.update();
+    }
+
+    fn selection(app: &mut App) -> Vec<Entity> {
+        let mut selected = app
+            .world_mut()
+            .query_filtered::<Entity, With<Selected>>()
+            .iter(app.world())
+            .collect::<Vec<_>>();
+        selected.sort();
+        selected
+    }
+
+    #[test]
+    fn control_groups_assign_recall_and_extend() {
+        let mut app = control_group_app();
+        let spawn = |app: &mut App, x: f32| {
+            app.world_mut()
+                .spawn((Selectable, Transform::from_xyz(x, 0.0, 0.0)))
+                .id()
+        };
+        let a = spawn(&mut app, 0.0);
+        let b = spawn(&mut app, 100.0);
+        let c = spawn(&mut app, 500.0);
+        app.world_mut().entity_mut(a).insert(Selected);
+        app.world_mut().entity_mut(b).insert(Selected);
+
+        press(&mut app, &[KeyCode::ControlLeft, KeyCode::Digit1]);
+        assert_eq!(app.world().resource::<ControlGroups>().members(0), [a, b]);
+
+        // Select something else, then recall the group
+        app.world_mut().entity_mut(a).remove::<Selected>();
+        app.world_mut().entity_mut(b).remove::<Selected>();
+        app.world_mut().entity_mut(c).insert(Selected);
+        press(&mut app, &[KeyCode::Digit1]);
+        assert_eq!(selection(&mut app), [a, b]);
+
+        // Shift adds the group to the selection instead of replacing it
+        app.world_mut().entity_mut(a).remove::<Selected>();
+        app.world_mut().entity_mut(c).insert(Selected);
+        press(&mut app, &[KeyCode::ShiftLeft, KeyCode::Digit1]);
+        assert_eq!(selection(&mut app), [a, b, c]);
+
+        // Dead members drop out of the group
+        app.world_mut().despawn(b);
+        press(&mut app, &[KeyCode::Digit1]);
+        assert_eq!(app.world().resource::<ControlGroups>().members(0), [a]);
+        assert_eq!(selection(&mut app), [a]);
+    }
+
+    #[test]
+    fn control_group_double_tap_centres_camera() {
+        let mut app = control_group_app();
+        let camera = app
+            .world_mut()
+            .spawn((MainCamera, Transform::default()))
+            .id();
+        let unit = app
+            .world_mut()
+            .spawn((Selectable, Selected, Transform::from_xyz(300.0, 200.0, 0.0)))
+            .id();
+        app.world_mut()
+            .resource_mut::<ControlGroups>()
+            .assign(2, [unit]);
+
+        press(&mut app, &[KeyCode::Digit3]);
+        assert_eq!(
+            app.world().get::<Transform>(camera).unwrap().translation,
+            Vec3::ZERO
+        );
+
+        press(&mut app, &[KeyCode::Digit3]);
+        let centred = app.world().get::<Transform>(camera).unwrap().translation;
+        assert_eq!(centred.truncate(), Vec2::new(300.0, 200.0));
+    }
+
+    #[test]
+    fn control_groups_wait_while_build_menu_is_open() {
+        let mut app = control_group_app();
+        app.world_mut()
+            .spawn((Selectable, Selected, Transform::default()));
+        app.world_mut()
+            .resource_mut::<BuildingPlacement>()
+            .menu_open = true;
+
+        press(&mut app, &[KeyCode::ControlLeft, KeyCode::Digit1]);
+
+        assert!(app
+            .world()
+            .resource::<ControlGroups>()
+            .members(0)
+            .is_empty());
+    }
+
     #[test]
     fn selection_radius_scales_with_sprite_size() {
         let small = Sprite {
@@ -2,23 +2,44 @@
 //!
 //! This module advances the deterministic core simulation and
 //! syncs positions back into Bevy for rendering.
+//!
+//! The simulation runs at a fixed [`TICK_RATE`] scaled by [`GameSpeed`],
+//! independent of the render framerate: pausing, stepping and speeding up
+//! only change how many whole ticks run per frame, never what a tick does,
+//! so replays play back the same at any speed.
 
 use std::collections::HashMap;
 
 use bevy::prelude::*;
+use rts_core::abilities::AbilityUsed;
+use rts_core::autosave::PendingCommand;
 use rts_core::combat::ArmorClass;
 use rts_core::components::{
     CombatStats as CoreCombatStats, Command as CoreCommand, DamageType as CoreDamageType, EntityId,
-    FactionMember,
+    FactionMember, IdleBehavior,
 };
+use rts_core::data::{UnitData, WeaponData};
+use rts_core::fog::CellVisibility;
 use rts_core::math::Fixed;
-use rts_core::pathfinding::CellType;
-use rts_core::simulation::{EntitySpawnParams, Simulation, TickEvents, TICK_RATE};
+use rts_core::notifications::Notification;
+use rts_core::pathfinding::{CellType, NavGrid};
+use rts_core::repair::{RepairEvent, Repairer};
+use rts_core::replay::WorldEdit;
+use rts_core::research::{ResearchEvent, ResearchFacility, TechProfile};
+use rts_core::salvage::{salvage_rate_for_tier, salvage_value, SalvageEvent};
+use rts_core::simulation::{EntitySpawnParams, Simulation, StatChange, TickEvents, TICK_RATE};
+use rts_core::transport::Cargo;
+use rts_core::triggers::TriggerFired;
 
 use crate::components::{
-    Armor, ArmorType, AttackTarget, Building, CombatStats, CoreEntityId, DamageType, GameDepot,
-    GameFaction, GameHealth, GamePosition, MovementTarget, Stationary,
+    Armor, ArmorType, AttackTarget, Building, BuildingType, CombatStats, ConstructionOrder,
+    CoreEntityId, DamageType, GameDebugName, GameDepot, GameFaction, GameHealth, GamePosition,
+    GameProductionQueue, MovementTarget, PlayerFaction, Stationary, Unit, UnitDataId, UnitType,
 };
+use crate::construction::construction_plan;
+use crate::data_loader::{reload_edited_faction_data, FactionDataReload, FactionRegistry};
+use crate::economy::PlayerResources;
+use crate::replay::ReplayRecorder;
 
 /// Systems that emit commands into the core simulation.
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
@@ -30,9 +51,129 @@ pub enum ClientCommandSet {
 /// Unit movement speed (units per second).
 pub const UNIT_SPEED: f32 = 150.0;
 
-/// Unit collision radius for selection/formation spacing.
+/// Unit collision radius for selection/formation spacing and local avoidance.
 pub const UNIT_RADIUS: f32 = 20.0;
 
+/// How far idle units wander from where they stopped.
+const IDLE_WANDER_LEASH: f32 = 12.0;
+
+/// Ticks between idle wander steps (3 seconds).
+const IDLE_WANDER_INTERVAL: u32 = 3 * TICK_RATE;
+
+/// Slowest game speed, as a multiple of normal.
+pub const MIN_GAME_SPEED: f32 = 0.5;
+
+/// Fastest game speed, as a multiple of normal.
+pub const MAX_GAME_SPEED: f32 = 8.0;
+
+/// Speeds offered by the speed control and stepped through by hotkeys.
+pub const GAME_SPEEDS: [f32; 5] = [0.5, 1.0, 2.0, 4.0, 8.0];
+
+/// Most ticks run in one frame. After a long stall the simulation drops
+/// the backlog rather than freezing the frame to catch up.
+const MAX_TICKS_PER_FRAME: u32 = 2 * TICK_RATE;
+
+/// How fast the core simulation runs, and whether it is paused.
+///
+/// Space toggles pause, `.` steps one tick while paused, `]` and `[`
+/// step the speed up and down through [`GAME_SPEEDS`].
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct GameSpeed {
+    /// Whether the simulation is paused.
+    pub paused: bool,
+    multiplier: f32,
+    /// Single steps requested while paused, not yet run.
+    steps: u32,
+}
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            multiplier: 1.0,
+            steps: 0,
+        }
+    }
+}
+
+impl GameSpeed {
+    /// Speed as a multiple of normal.
+    #[must_use]
+    pub fn multiplier(&self) -> f32 {
+        self.multiplier
+    }
+
+    /// Set the speed, kept between [`MIN_GAME_SPEED`] and
+    /// [`MAX_GAME_SPEED`].
+    pub fn set_multiplier(&mut self, multiplier: f32) {
+        self.multiplier = multiplier.clamp(MIN_GAME_SPEED, MAX_GAME_SPEED);
+    }
+
+    /// Step up to the next faster preset speed.
+    pub fn faster(&mut self) {
+        if let Some(&next) = GAME_SPEEDS.iter().find(|&&s| s > self.multiplier) {
+            self.multiplier = next;
+        }
+    }
+
+    /// Step down to the next slower preset speed.
+    pub fn slower(&mut self) {
+        if let Some(&next) = GAME_SPEEDS.iter().rev().find(|&&s| s < self.multiplier) {
+            self.multiplier = next;
+        }
+    }
+
+    /// Pause or resume.
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.steps = 0;
+    }
+
+    /// Pause, dropping any steps not yet run.
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.steps = 0;
+    }
+
+    /// Run exactly one tick next frame. Only works while paused.
+    pub fn step(&mut self) {
+        self.step_ticks(1);
+    }
+
+    /// Run exactly `count` more ticks, as fast as frames allow. Only works
+    /// while paused.
+    pub fn step_ticks(&mut self, count: u32) {
+        if self.paused {
+            self.steps = self.steps.saturating_add(count);
+        }
+    }
+
+    /// Whole ticks to run for a frame `delta` seconds long, carrying the
+    /// leftover time in `accumulator`.
+    ///
+    /// Paused, only requested steps run and no time builds up.
+    pub fn ticks_due(&mut self, accumulator: &mut f32, delta: f32) -> u32 {
+        if self.paused {
+            *accumulator = 0.0;
+            let ticks = self.steps.min(MAX_TICKS_PER_FRAME);
+            self.steps -= ticks;
+            return ticks;
+        }
+        let step = 1.0 / TICK_RATE as f32;
+        *accumulator += delta * self.multiplier;
+        let mut ticks = 0;
+        while *accumulator >= step {
+            *accumulator -= step;
+            ticks += 1;
+            if ticks == MAX_TICKS_PER_FRAME {
+                *accumulator = 0.0;
+                break;
+            }
+        }
+        ticks
+    }
+}
+
 /// Command issuance mode for the core simulation.
 #[derive(Debug, Clone, Copy)]
 pub enum CoreCommandMode {
@@ -94,6 +235,24 @@ impl CoreCommandBuffer {
             mode: CoreCommandMode::Queue,
         });
     }
+
+    /// Commands not yet applied to the core, in issue order.
+    #[must_use]
+    pub fn pending(&self) -> Vec<PendingCommand> {
+        self.pending
+            .iter()
+            .map(|request| PendingCommand {
+                entity: request.entity,
+                command: request.command.clone(),
+                queued: matches!(request.mode, CoreCommandMode::Queue),
+            })
+            .collect()
+    }
+
+    /// Drop every command not yet applied.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
 }
 
 /// Core simulation state and entity mapping.
@@ -107,16 +266,95 @@ pub struct CoreSimulation {
     entity_map: HashMap<Entity, EntityId>,
     /// Latest core tick events.
     pub last_events: TickEvents,
+    /// Salvage events from every tick since the economy last paid out.
+    pub salvage: Vec<SalvageEvent>,
+    /// Research events from every tick since they were last reported.
+    pub research: Vec<ResearchEvent>,
+    /// Abilities used since the economy last charged for them.
+    pub abilities: Vec<AbilityUsed>,
+    /// Repairs made since the economy last charged for them.
+    pub repairs: Vec<RepairEvent>,
+    /// Scenario triggers fired since their actions were last carried out.
+    pub triggers: Vec<TriggerFired>,
+    /// Notifications from every tick since they were last reported.
+    pub notifications: Vec<Notification>,
+    /// Replay of everything the client feeds the core.
+    pub recorder: ReplayRecorder,
 }
 
 impl CoreSimulation {
-    fn register_entity(&mut self, entity: Entity, id: EntityId) {
+    pub(crate) fn register_entity(&mut self, entity: Entity, id: EntityId) {
         self.entity_map.insert(entity, id);
     }
 
     fn unregister_entity(&mut self, entity: Entity) -> Option<EntityId> {
         self.entity_map.remove(&entity)
     }
+
+    /// Swap in a different simulation, such as one restored from a save.
+    ///
+    /// Every Bevy entity is unmapped, so despawning the old mirrors leaves
+    /// the new simulation alone, and events not yet handed out are dropped.
+    pub fn replace_sim(&mut self, sim: Simulation) {
+        self.sim = sim;
+        self.accumulator = 0.0;
+        self.entity_map.clear();
+        self.last_events = TickEvents::default();
+        self.salvage.clear();
+        self.research.clear();
+        self.abilities.clear();
+        self.repairs.clear();
+        self.triggers.clear();
+        self.notifications.clear();
+    }
+}
+
+/// The local player's fog of war, mirrored from the core after each tick
+/// for shader and overlay rendering.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct FogOfWarMask {
+    /// Core tick the mask was taken at.
+    pub tick: u64,
+    /// Width in cells.
+    pub width: u32,
+    /// Height in cells.
+    pub height: u32,
+    /// Size of each cell in world units.
+    pub cell_size: f32,
+    /// Cell states in row-major order, `width * height` long.
+    pub cells: Vec<CellVisibility>,
+}
+
+impl FogOfWarMask {
+    /// State of the cell containing a world position, or None outside the map.
+    #[must_use]
+    pub fn visibility_at(&self, world: Vec2) -> Option<CellVisibility> {
+        if world.x < 0.0 || world.y < 0.0 || self.cell_size <= 0.0 {
+            return None;
+        }
+        let x = (world.x / self.cell_size) as u32;
+        let y = (world.y / self.cell_size) as u32;
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.cells
+            .get((y as usize) * (self.width as usize) + (x as usize))
+            .copied()
+    }
+
+    /// One byte per cell, ready to upload as a texture: 0 unexplored,
+    /// 128 explored and 255 visible.
+    #[must_use]
+    pub fn alpha(&self) -> Vec<u8> {
+        self.cells
+            .iter()
+            .map(|cell| match cell {
+                CellVisibility::Unexplored => 0,
+                CellVisibility::Explored => 128,
+                CellVisibility::Visible => 255,
+            })
+            .collect()
+    }
 }
 
 /// Core simulation ordering.
@@ -136,9 +374,12 @@ pub struct SimulationPlugin;
 impl Plugin for SimulationPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<CoreSimulation>()
+            .init_resource::<GameSpeed>()
             .init_resource::<CoreCommandBuffer>()
             .init_resource::<CommandStream>()
             .init_resource::<BuildingFootprints>()
+            .init_resource::<FogOfWarMask>()
+            .add_event::<FactionDataReload>()
             .configure_sets(
                 Update,
                 (
@@ -152,13 +393,30 @@ impl Plugin for SimulationPlugin {
                 Update,
                 ClientCommandSet::Gather.before(CoreSimulationSet::SyncIn),
             )
+            .add_systems(
+                PreUpdate,
+                register_faction_mechanics
+                    .run_if(resource_exists_and_changed::<FactionRegistry>)
+                    .before(sync_spawned_entities),
+            )
+            .add_systems(
+                PreUpdate,
+                retune_live_units
+                    .after(reload_edited_faction_data)
+                    .before(sync_spawned_entities),
+            )
             .add_systems(PreUpdate, sync_spawned_entities)
             .add_systems(PreUpdate, sync_removed_entities)
             .add_systems(
                 Update,
                 apply_command_buffer.in_set(CoreSimulationSet::SyncIn),
             )
-            .add_systems(Update, tick_core_simulation.in_set(CoreSimulationSet::Tick))
+            .add_systems(
+                Update,
+                (game_speed_controls, tick_core_simulation)
+                    .chain()
+                    .in_set(CoreSimulationSet::Tick),
+            )
             .add_systems(
                 Update,
                 sync_positions_from_core.in_set(CoreSimulationSet::SyncOut),
@@ -167,6 +425,18 @@ impl Plugin for SimulationPlugin {
             Update,
             sync_health_from_core.in_set(CoreSimulationSet::SyncOut),
         );
+        app.add_systems(
+            Update,
+            sync_factions_from_core.in_set(CoreSimulationSet::SyncOut),
+        );
+        app.add_systems(
+            Update,
+            sync_rally_points_from_core.in_set(CoreSimulationSet::SyncOut),
+        );
+        app.add_systems(
+            Update,
+            sync_fog_of_war_mask.in_set(CoreSimulationSet::SyncOut),
+        );
         app.add_systems(
             Update,
             sync_attack_targets_to_core.in_set(CoreSimulationSet::SyncIn),
@@ -190,6 +460,134 @@ impl Plugin for SimulationPlugin {
     }
 }
 
+/// Give the core every loaded faction's mechanics before their entities
+/// spawn.
+fn register_faction_mechanics(registry: Res<FactionRegistry>, mut core: ResMut<CoreSimulation>) {
+    let mut factions: Vec<_> = registry.all_factions().collect();
+    factions.sort_by_key(|data| data.id);
+    for data in factions {
+        if core.sim.faction_mechanics().get(data.id) == data.mechanics.as_slice() {
+            continue;
+        }
+        core.sim
+            .set_faction_mechanics(data.id, data.mechanics.clone());
+        let tick = core.sim.get_tick();
+        core.recorder.record_edit(
+            tick,
+            WorldEdit::SetFactionMechanics {
+                faction: data.id,
+                mechanics: data.mechanics.clone(),
+            },
+        );
+    }
+}
+
+/// Bring live units in line with reloaded faction data.
+///
+/// Each stat edited in the data moves by the same amount on every live
+/// unit of that kind, so upgrades already researched are kept and hurt
+/// units stay as hurt relative to their new maximum. Units whose data
+/// gained or lost a weapon are left alone.
+fn retune_live_units(
+    mut reloads: EventReader<FactionDataReload>,
+    registry: Option<Res<FactionRegistry>>,
+    mut core: ResMut<CoreSimulation>,
+    mut units: Query<(
+        &CoreEntityId,
+        &UnitDataId,
+        &GameFaction,
+        Option<&mut CombatStats>,
+    )>,
+) {
+    for reload in reloads.read() {
+        let FactionDataReload::Reloaded {
+            faction,
+            previous: Some(previous),
+        } = reload
+        else {
+            continue;
+        };
+        let Some(current) = registry
+            .as_deref()
+            .and_then(|registry| registry.get(*faction))
+        else {
+            continue;
+        };
+
+        let mut changes = Vec::new();
+        for (core_id, data_id, game_faction, combat) in units.iter_mut() {
+            if game_faction.faction != *faction {
+                continue;
+            }
+            let (Some(old), Some(new)) = (
+                previous.get_unit(data_id.as_str()),
+                current.get_unit(data_id.as_str()),
+            ) else {
+                continue;
+            };
+            let Some(entity) = core.sim.get_entity(core_id.0) else {
+                continue;
+            };
+            let change = stat_change(old, new, entity);
+            if change.is_empty() {
+                continue;
+            }
+            if let (Some(mut stats), Some(core_stats)) = (combat, entity.combat_stats) {
+                stats.damage = change.damage.unwrap_or(core_stats.damage);
+                stats.range = change.range.unwrap_or(core_stats.range).to_num();
+                stats.attack_cooldown = change.attack_cooldown.unwrap_or(core_stats.attack_cooldown)
+                    as f32
+                    / TICK_RATE as f32;
+            }
+            changes.push((core_id.0, change));
+        }
+
+        changes.sort_by_key(|(id, _)| *id);
+        let tick = core.sim.get_tick();
+        for (entity, change) in changes {
+            if core.sim.change_stats(entity, &change).is_ok() {
+                core.recorder
+                    .record_edit(tick, WorldEdit::ChangeStats { entity, change });
+            }
+        }
+    }
+}
+
+/// Stats a live entity gets when its unit data changes from `old` to
+/// `new`: each edited stat moves by the amount it was edited by.
+fn stat_change(
+    old: &UnitData,
+    new: &UnitData,
+    entity: rts_core::simulation::EntityRef<'_>,
+) -> StatChange {
+    let shift = |value: u32, from: u32, to: u32| {
+        (i64::from(value) + i64::from(to) - i64::from(from)).clamp(1, i64::from(u32::MAX)) as u32
+    };
+
+    let mut change = StatChange::default();
+    if old.health != new.health {
+        change.max_health = entity
+            .health
+            .map(|health| shift(health.max, old.health, new.health));
+    }
+    if let (Some(old), Some(new), Some(stats)) = (&old.combat, &new.combat, entity.combat_stats) {
+        if old.damage != new.damage {
+            change.damage = Some(shift(stats.damage, old.damage, new.damage));
+        }
+        if old.range != new.range {
+            change.range = Some((stats.range + new.range - old.range).max(Fixed::ZERO));
+        }
+        if old.attack_cooldown != new.attack_cooldown {
+            change.attack_cooldown = Some(shift(
+                stats.attack_cooldown,
+                old.attack_cooldown,
+                new.attack_cooldown,
+            ));
+        }
+    }
+    change
+}
+
 fn unit_speed_per_tick() -> Fixed {
     Fixed::from_num(UNIT_SPEED / TICK_RATE as f32)
 }
@@ -207,14 +605,30 @@ fn sync_spawned_entities(
             Option<&Armor>,
             Option<&GameFaction>,
             Option<&GameDepot>,
+            (Option<&UnitDataId>, Option<&Unit>, Option<&Building>),
+            Option<&ConstructionOrder>,
+            Has<GameProductionQueue>,
         ),
         Without<CoreEntityId>,
     >,
+    registry: Option<Res<FactionRegistry>>,
+    mut resources: Option<ResMut<PlayerResources>>,
 ) {
     let speed = unit_speed_per_tick();
 
-    for (entity, position, stationary, health, combat_stats, armor, faction, depot) in
-        spawned.iter()
+    for (
+        entity,
+        position,
+        stationary,
+        health,
+        combat_stats,
+        armor,
+        faction,
+        depot,
+        kind,
+        order,
+        produces,
+    ) in spawned.iter()
     {
         let mut core_combat = None;
         if combat_stats.is_some() || armor.is_some() {
@@ -240,6 +654,24 @@ fn sync_spawned_entities(
             core_combat = Some(stats);
         }
 
+        let (salvage_value, salvage_rate) = unit_salvage(kind, faction, registry.as_deref());
+        let data = unit_data(kind, faction, registry.as_deref());
+        let abilities = data.map(|data| data.abilities.clone()).unwrap_or_default();
+        let domain = data.map(|data| data.domain).unwrap_or_default();
+        let weapons = data
+            .map(|data| data.weapons.iter().map(WeaponData::weapon).collect())
+            .unwrap_or_default();
+        let cargo = data
+            .and_then(|data| data.cargo.as_ref())
+            .map(|c| Cargo::new(c.capacity, c.tags.clone()));
+        let repairer = data
+            .and_then(|data| data.repair)
+            .map(|r| Repairer::new(r.kind, r.amount, r.interval_ticks, r.cost));
+        let tech_profile = tech_profile(kind, faction, registry.as_deref());
+        let research = kind
+            .2
+            .is_some_and(|b| b.building_type == BuildingType::TechLab)
+            .then(|| ResearchFacility::new(None));
         let params = EntitySpawnParams {
             position: Some(position.value),
             movement: if stationary.is_some() {
@@ -248,16 +680,145 @@ fn sync_spawned_entities(
                 Some(speed)
             },
             health: health.map(|health| health.max),
+            collision_radius: stationary.is_none().then(|| Fixed::from_num(UNIT_RADIUS)),
             combat_stats: core_combat,
             faction: faction.map(|faction| FactionMember::new(faction.faction, 0)),
+            has_production_queue: produces,
             is_depot: depot.is_some(),
+            debug_kind: debug_kind(kind),
+            salvage_value,
+            salvage_rate,
+            tech_profile,
+            research,
+            abilities,
+            cargo,
+            repairer,
+            domain,
+            weapons,
+            idle_behavior: (stationary.is_none() && kind.1.is_some()).then(|| {
+                IdleBehavior::wander(Fixed::from_num(IDLE_WANDER_LEASH), IDLE_WANDER_INTERVAL)
+            }),
             ..Default::default()
         };
 
-        let core_id = core.sim.spawn_entity(params);
+        let tick = core.sim.get_tick();
+        let core_id = match (order, kind.2) {
+            // Ordered buildings go up as sites their worker builds
+            (Some(order), Some(building)) => {
+                let (footprint, build_time) = construction_plan(&core.sim, building.building_type);
+                match core.sim.order_construction(
+                    order.worker,
+                    params.clone(),
+                    footprint,
+                    build_time,
+                    order.cost,
+                ) {
+                    Ok(id) => {
+                        core.recorder.record_edit(
+                            tick,
+                            WorldEdit::OrderConstruction {
+                                worker: order.worker,
+                                params: Box::new(params),
+                                footprint,
+                                build_time,
+                                cost: order.cost,
+                            },
+                        );
+                        id
+                    }
+                    Err(e) => {
+                        tracing::warn!("Construction refused: {e}");
+                        if let Some(resources) = resources.as_deref_mut() {
+                            resources.feedstock += order.cost as i32;
+                        }
+                        commands.entity(entity).despawn_recursive();
+                        continue;
+                    }
+                }
+            }
+            _ => {
+                core.recorder
+                    .record_edit(tick, WorldEdit::Spawn(Box::new(params.clone())));
+                core.sim.spawn_entity(params)
+            }
+        };
         core.register_entity(entity, core_id);
-        commands.entity(entity).insert(CoreEntityId(core_id));
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.insert(CoreEntityId(core_id));
+        if let Some(name) = core
+            .sim
+            .get_entity(core_id)
+            .and_then(|e| e.row.debug_name.as_ref())
+        {
+            entity_commands.insert(GameDebugName(name.0.clone()));
+        }
+    }
+}
+
+/// Kind used for an entity's debug name: its data ID when spawned from
+/// faction data, otherwise its unit or building type.
+fn debug_kind(
+    (data_id, unit, building): (Option<&UnitDataId>, Option<&Unit>, Option<&Building>),
+) -> Option<String> {
+    if let Some(id) = data_id {
+        return Some(id.0.clone());
     }
+    let name = match (unit, building) {
+        (Some(unit), _) => unit.unit_type.name(),
+        (None, Some(building)) => building.building_type.name(),
+        (None, None) => return None,
+    };
+    Some(name.to_lowercase().replace(' ', "_"))
+}
+
+/// Faction data for a unit spawned from data.
+fn unit_data<'a>(
+    (data_id, _, _): (Option<&UnitDataId>, Option<&Unit>, Option<&Building>),
+    faction: Option<&GameFaction>,
+    registry: Option<&'a FactionRegistry>,
+) -> Option<&'a UnitData> {
+    let (id, faction) = data_id.zip(faction)?;
+    registry?.get(faction.faction)?.get_unit(id.as_str())
+}
+
+/// What technology effects see of an entity: its data ID and tags when
+/// spawned from faction data, otherwise its legacy type and role.
+fn tech_profile(
+    kind: (Option<&UnitDataId>, Option<&Unit>, Option<&Building>),
+    faction: Option<&GameFaction>,
+    registry: Option<&FactionRegistry>,
+) -> Option<TechProfile> {
+    if let Some(data) = unit_data(kind, faction, registry) {
+        return Some(TechProfile::new(&data.id, data.tags.clone()));
+    }
+    let tags = match kind.1.map(|unit| unit.unit_type) {
+        Some(UnitType::Infantry | UnitType::Ranger) => vec!["infantry".to_string()],
+        Some(UnitType::Harvester) => vec!["harvester".to_string()],
+        None => Vec::new(),
+    };
+    debug_kind(kind).map(|id| TechProfile::new(id, tags))
+}
+
+/// Wreck value and salvage rate for a unit: from its faction data when it
+/// was spawned from data, otherwise from its legacy unit type.
+fn unit_salvage(
+    kind: (Option<&UnitDataId>, Option<&Unit>, Option<&Building>),
+    faction: Option<&GameFaction>,
+    registry: Option<&FactionRegistry>,
+) -> (Option<u32>, Option<u32>) {
+    if let Some(data) = unit_data(kind, faction, registry) {
+        return (Some(data.salvage_value()), data.salvage_rate());
+    }
+    let Some(unit_type) = kind.1.map(|unit| unit.unit_type) else {
+        return (None, None);
+    };
+    let value = salvage_value(unit_type.cost().max(0) as u32);
+    let rate = match unit_type {
+        UnitType::Harvester => None,
+        UnitType::Infantry => Some(salvage_rate_for_tier(1)),
+        UnitType::Ranger => Some(salvage_rate_for_tier(2)),
+    };
+    (Some(value), rate)
 }
 
 fn sync_removed_entities(
@@ -266,7 +827,10 @@ fn sync_removed_entities(
 ) {
     for entity in removed.read() {
         if let Some(core_id) = core.unregister_entity(entity) {
-            let _ = core.sim.despawn_entity(core_id);
+            if core.sim.despawn_entity(core_id).is_ok() {
+                let tick = core.sim.get_tick();
+                core.recorder.record_edit(tick, WorldEdit::Despawn(core_id));
+            }
         }
     }
 }
@@ -276,6 +840,10 @@ fn apply_command_buffer(
     mut core_commands: ResMut<CoreCommandBuffer>,
     mut command_stream: ResMut<CommandStream>,
 ) {
+    // Consecutive replacing orders go through as one batch so group moves
+    // can share a flow field; queued orders flush the batch first to keep
+    // the original order.
+    let mut batch = Vec::new();
     for request in core_commands.pending.drain(..) {
         command_stream.records.push(CommandRecord {
             tick: core.sim.get_tick(),
@@ -283,24 +851,75 @@ fn apply_command_buffer(
             command: request.command.clone(),
             mode: request.mode,
         });
-        let result = match request.mode {
-            CoreCommandMode::Replace => core.sim.apply_command(request.entity, request.command),
-            CoreCommandMode::Queue => core.sim.queue_command(request.entity, request.command),
-        };
+        match request.mode {
+            CoreCommandMode::Replace => batch.push((request.entity, request.command)),
+            CoreCommandMode::Queue => {
+                flush_command_batch(&mut core, &mut batch);
+                let tick = core.sim.get_tick();
+                core.recorder
+                    .record_queued(tick, request.entity, request.command.clone());
+                if core
+                    .sim
+                    .queue_command(request.entity, request.command)
+                    .is_err()
+                {
+                    tracing::debug!("Failed to apply core command");
+                }
+            }
+        }
+    }
+    flush_command_batch(&mut core, &mut batch);
+}
 
+fn flush_command_batch(core: &mut CoreSimulation, batch: &mut Vec<(EntityId, CoreCommand)>) {
+    if batch.is_empty() {
+        return;
+    }
+    core.recorder.record_batch(core.sim.get_tick(), batch);
+    for result in core.sim.apply_commands(batch) {
         if result.is_err() {
             tracing::debug!("Failed to apply core command");
         }
     }
+    batch.clear();
+}
+
+fn game_speed_controls(keyboard: Option<Res<ButtonInput<KeyCode>>>, mut speed: ResMut<GameSpeed>) {
+    let Some(keyboard) = keyboard else {
+        return;
+    };
+    if keyboard.just_pressed(KeyCode::Space) {
+        speed.toggle_pause();
+    }
+    if keyboard.just_pressed(KeyCode::Period) {
+        speed.step();
+    }
+    if keyboard.just_pressed(KeyCode::BracketRight) {
+        speed.faster();
+    }
+    if keyboard.just_pressed(KeyCode::BracketLeft) {
+        speed.slower();
+    }
 }
 
-fn tick_core_simulation(time: Res<Time>, mut core: ResMut<CoreSimulation>) {
-    core.accumulator += time.delta_seconds();
-    let step = 1.0 / TICK_RATE as f32;
+fn tick_core_simulation(
+    time: Res<Time>,
+    mut speed: ResMut<GameSpeed>,
+    mut core: ResMut<CoreSimulation>,
+) {
+    let mut accumulator = core.accumulator;
+    let ticks = speed.ticks_due(&mut accumulator, time.delta_seconds());
+    core.accumulator = accumulator;
 
-    while core.accumulator >= step {
-        core.last_events = core.sim.tick();
-        core.accumulator -= step;
+    for _ in 0..ticks {
+        let events = core.sim.tick();
+        core.salvage.extend_from_slice(&events.salvage);
+        core.research.extend_from_slice(&events.research);
+        core.abilities.extend_from_slice(&events.abilities);
+        core.repairs.extend_from_slice(&events.repairs);
+        core.triggers.extend_from_slice(&events.triggers);
+        core.notifications.extend_from_slice(&events.notifications);
+        core.last_events = events;
     }
 }
 
@@ -317,6 +936,33 @@ fn sync_positions_from_core(
     }
 }
 
+/// Copy the local player's fog grid whenever the core has ticked.
+fn sync_fog_of_war_mask(
+    core: Res<CoreSimulation>,
+    player: Option<Res<PlayerFaction>>,
+    mut mask: ResMut<FogOfWarMask>,
+) {
+    let Some(player) = player else {
+        return;
+    };
+    let tick = core.sim.get_tick();
+    if tick == mask.tick && !mask.cells.is_empty() {
+        return;
+    }
+    let fog = core.sim.fog();
+    let (width, height) = (fog.width(), fog.height());
+    *mask = FogOfWarMask {
+        tick,
+        width,
+        height,
+        cell_size: fog.cell_size().to_num(),
+        cells: fog.grid(player.faction).map_or_else(
+            || vec![CellVisibility::Unexplored; (width as usize) * (height as usize)],
+            |grid| grid.cells().to_vec(),
+        ),
+    };
+}
+
 fn sync_health_from_core(
     core: Res<CoreSimulation>,
     mut entities: Query<(&CoreEntityId, &mut GameHealth)>,
@@ -331,6 +977,42 @@ fn sync_health_from_core(
     }
 }
 
+/// Mirror production buildings' rally points from the core.
+fn sync_rally_points_from_core(
+    core: Res<CoreSimulation>,
+    mut buildings: Query<(&CoreEntityId, &mut GameProductionQueue)>,
+) {
+    for (core_id, mut production) in buildings.iter_mut() {
+        let Some(rally) = core
+            .sim
+            .get_entity(core_id.0)
+            .and_then(|e| e.row.building.as_ref())
+            .and_then(|b| b.rally_point)
+        else {
+            continue;
+        };
+        let rally = Some(Vec2::new(rally.x.to_num(), rally.y.to_num()));
+        if production.rally_point != rally {
+            production.rally_point = rally;
+        }
+    }
+}
+
+/// Mirror ownership transfers (captures, conversions) from the core.
+fn sync_factions_from_core(
+    core: Res<CoreSimulation>,
+    mut entities: Query<(&CoreEntityId, &mut GameFaction)>,
+) {
+    for (core_id, mut faction) in entities.iter_mut() {
+        let Some(owner) = core.sim.get_entity(core_id.0).and_then(|e| e.faction) else {
+            continue;
+        };
+        if faction.faction != owner.faction {
+            faction.faction = owner.faction;
+        }
+    }
+}
+
 /// Sync Bevy AttackTarget components to the core simulation.
 ///
 /// This ensures that when Bevy assigns a target (via acquire_attack_targets in combat.rs),
@@ -343,11 +1025,21 @@ fn sync_attack_targets_to_core(
     for (attacker_core_id, attack_target) in attackers.iter() {
         // Look up the target's core entity ID
         if let Ok(target_core_id) = targets.get(attack_target.target) {
-            if let Err(e) = core
+            match core
                 .sim
                 .set_attack_target(attacker_core_id.0, target_core_id.0)
             {
-                tracing::debug!("Failed to sync attack target to core: {}", e);
+                Ok(()) => {
+                    let tick = core.sim.get_tick();
+                    core.recorder.record_edit(
+                        tick,
+                        WorldEdit::SetAttackTarget {
+                            attacker: attacker_core_id.0,
+                            target: target_core_id.0,
+                        },
+                    );
+                }
+                Err(e) => tracing::debug!("Failed to sync attack target to core: {}", e),
             }
         }
     }
@@ -361,7 +1053,11 @@ fn clear_removed_attack_targets(
 ) {
     for entity in removed.read() {
         if let Ok(core_id) = entities.get(entity) {
-            let _ = core.sim.clear_attack_target(core_id.0);
+            if core.sim.clear_attack_target(core_id.0).is_ok() {
+                let tick = core.sim.get_tick();
+                core.recorder
+                    .record_edit(tick, WorldEdit::ClearAttackTarget(core_id.0));
+            }
         }
     }
 }
@@ -391,14 +1087,25 @@ pub struct BuildingFootprints {
 }
 
 /// Sync newly spawned buildings to the NavGrid by marking their cells as blocked.
+///
+/// Buildings put up by workers are left out: the core claims their ground.
 fn sync_building_to_navgrid(
     mut commands: Commands,
     mut core: ResMut<CoreSimulation>,
     mut footprints: ResMut<BuildingFootprints>,
-    buildings: Query<(Entity, &GamePosition, &Building), (Added<Building>, Without<NavGridSynced>)>,
+    buildings: Query<
+        (Entity, &GamePosition, &Building),
+        (
+            Added<Building>,
+            Without<NavGridSynced>,
+            Without<ConstructionOrder>,
+        ),
+    >,
 ) {
+    let core = &mut *core;
     for (entity, position, building) in buildings.iter() {
         let (width, height) = building.building_type.size();
+        let tick = core.sim.get_tick();
         let nav_grid = core.sim.nav_grid_mut();
         let cell_size: f32 = nav_grid.cell_size().to_num();
 
@@ -417,7 +1124,13 @@ fn sync_building_to_navgrid(
             // Mark all cells covered by the building as blocked
             for dy in 0..cells_y {
                 for dx in 0..cells_x {
-                    nav_grid.set_cell(start_x + dx, start_y + dy, CellType::Blocked);
+                    set_nav_cell(
+                        nav_grid,
+                        &mut core.recorder,
+                        tick,
+                        (start_x + dx, start_y + dy),
+                        CellType::Blocked,
+                    );
                 }
             }
 
@@ -448,8 +1161,10 @@ fn clear_building_from_navgrid_on_despawn(
     mut removed: RemovedComponents<Building>,
     mut footprints: ResMut<BuildingFootprints>,
 ) {
+    let core = &mut *core;
     for entity in removed.read() {
         if let Some((position, (cells_x, cells_y))) = footprints.footprints.remove(&entity) {
+            let tick = core.sim.get_tick();
             let nav_grid = core.sim.nav_grid_mut();
 
             if let Some((center_x, center_y)) = nav_grid.world_to_grid(position) {
@@ -460,7 +1175,13 @@ fn clear_building_from_navgrid_on_despawn(
 
                 for dy in 0..cells_y {
                     for dx in 0..cells_x {
-                        nav_grid.set_cell(start_x + dx, start_y + dy, CellType::Walkable);
+                        set_nav_cell(
+                            nav_grid,
+                            &mut core.recorder,
+                            tick,
+                            (start_x + dx, start_y + dy),
+                            CellType::Walkable,
+                        );
                     }
                 }
 
@@ -475,6 +1196,19 @@ fn clear_building_from_navgrid_on_despawn(
     }
 }
 
+/// Set a nav grid cell, recording the change if it was in bounds.
+fn set_nav_cell(
+    nav_grid: &mut NavGrid,
+    recorder: &mut ReplayRecorder,
+    tick: u64,
+    (x, y): (u32, u32),
+    cell: CellType,
+) {
+    if nav_grid.set_cell(x, y, cell) {
+        recorder.record_edit(tick, WorldEdit::SetCell { x, y, cell });
+    }
+}
+
 fn map_damage_type(damage_type: DamageType) -> CoreDamageType {
     match damage_type {
         DamageType::Kinetic => CoreDamageType::Kinetic,
@@ -496,7 +1230,100 @@ fn map_armor_type_to_class(armor_type: ArmorType) -> ArmorClass {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::components::GameCommandQueue;
+    use crate::components::{GameCommandQueue, UnitType};
+    use rts_core::factions::FactionId;
+    use rts_core::math::Vec2Fixed;
+
+    #[test]
+    fn fog_of_war_mask_follows_player_units() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(SimulationPlugin);
+        app.insert_resource(PlayerFaction {
+            faction: FactionId::Continuity,
+        });
+
+        app.world_mut().spawn((
+            GamePosition::new(Vec2Fixed::new(Fixed::from_num(100), Fixed::from_num(100))),
+            GameFaction {
+                faction: FactionId::Continuity,
+            },
+        ));
+        app.update();
+        app.update();
+
+        let mask = app.world().resource::<FogOfWarMask>();
+        assert_eq!(mask.cells.len(), (mask.width * mask.height) as usize);
+        assert_eq!(
+            mask.visibility_at(Vec2::new(100.0, 100.0)),
+            Some(CellVisibility::Visible)
+        );
+        assert_eq!(
+            mask.visibility_at(Vec2::new(1500.0, 1500.0)),
+            Some(CellVisibility::Unexplored)
+        );
+        assert_eq!(mask.visibility_at(Vec2::new(-1.0, 0.0)), None);
+        assert!(mask.alpha().contains(&255));
+    }
+
+    #[test]
+    fn game_speed_scales_paces_and_steps_ticks() {
+        let frame = 1.0 / TICK_RATE as f32;
+        let mut speed = GameSpeed::default();
+        let mut accumulator = 0.0;
+        assert_eq!(speed.ticks_due(&mut accumulator, frame * 1.5), 1);
+        assert_eq!(speed.ticks_due(&mut accumulator, frame * 0.5), 1);
+
+        speed.faster();
+        speed.faster();
+        assert_eq!(speed.multiplier(), 4.0);
+        assert_eq!(speed.ticks_due(&mut 0.0, frame), 4);
+        speed.set_multiplier(100.0);
+        assert_eq!(speed.multiplier(), MAX_GAME_SPEED);
+        speed.slower();
+        assert_eq!(speed.multiplier(), 4.0);
+
+        // A long stall runs a capped number of ticks
+        assert_eq!(speed.ticks_due(&mut 0.0, 10.0), MAX_TICKS_PER_FRAME);
+
+        // Paused, time stops and only steps run
+        speed.step();
+        assert_eq!(speed.ticks_due(&mut 0.0, frame), 4, "steps need a pause");
+        speed.toggle_pause();
+        speed.step();
+        speed.step();
+        let mut accumulator = 0.5;
+        assert_eq!(speed.ticks_due(&mut accumulator, 1.0), 2);
+        assert_eq!(accumulator, 0.0);
+        assert_eq!(speed.ticks_due(&mut accumulator, 1.0), 0);
+
+        // Long steps spread over frames; pausing again drops the rest
+        speed.step_ticks(MAX_TICKS_PER_FRAME + 5);
+        assert_eq!(speed.ticks_due(&mut 0.0, frame), MAX_TICKS_PER_FRAME);
+        assert_eq!(speed.ticks_due(&mut 0.0, frame), 5);
+        speed.step_ticks(3);
+        speed.pause();
+        assert_eq!(speed.ticks_due(&mut 0.0, frame), 0);
+    }
+
+    #[test]
+    fn paused_simulation_only_advances_when_stepped() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(SimulationPlugin);
+        app.world_mut().resource_mut::<GameSpeed>().toggle_pause();
+        for _ in 0..5 {
+            app.update();
+        }
+        let tick = |app: &App| app.world().resource::<CoreSimulation>().sim.get_tick();
+        assert_eq!(tick(&app), 0);
+
+        app.world_mut().resource_mut::<GameSpeed>().step();
+        app.update();
+        assert_eq!(tick(&app), 1);
+        app.update();
+        assert_eq!(tick(&app), 1);
+    }
 
     #[test]
     fn command_stream_records_commands() {
@@ -550,4 +1377,119 @@ mod tests {
         assert_eq!(core_health.max, 42);
         assert_eq!(core_health.current, 42);
     }
+
+    #[test]
+    fn reloaded_unit_data_retunes_live_units() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(SimulationPlugin);
+        let registry = crate::data_loader::load_factions_from_directory(std::path::Path::new(
+            "assets/data/factions",
+        ))
+        .unwrap();
+        let previous = registry.get(FactionId::Continuity).unwrap().clone();
+        app.insert_resource(registry);
+
+        let data = previous.get_unit("security_team").unwrap();
+        let entity = app
+            .world_mut()
+            .spawn(crate::bundles::UnitBundle::from_data(
+                Vec2::ZERO,
+                FactionId::Continuity,
+                data,
+                rts_core::unit_kind::UnitKindId::NONE,
+            ))
+            .id();
+        app.update();
+        let core_id = app.world().get::<CoreEntityId>(entity).unwrap().0;
+        {
+            let mut core = app.world_mut().resource_mut::<CoreSimulation>();
+            let core = &mut *core;
+            core.recorder.start(&core.sim).unwrap();
+        }
+
+        let mut edited = previous.clone();
+        let unit = edited
+            .units
+            .iter_mut()
+            .find(|unit| unit.id == "security_team")
+            .unwrap();
+        unit.health += 20;
+        unit.combat.as_mut().unwrap().damage += 3;
+        app.world_mut()
+            .resource_mut::<FactionRegistry>()
+            .replace(edited);
+        app.world_mut().send_event(FactionDataReload::Reloaded {
+            faction: FactionId::Continuity,
+            previous: Some(Box::new(previous.clone())),
+        });
+        app.update();
+
+        let core = app.world().resource::<CoreSimulation>();
+        let live = core.sim.get_entity(core_id).unwrap();
+        let old_combat = data.combat.as_ref().unwrap();
+        assert_eq!(live.health.unwrap().max, data.health + 20);
+        assert_eq!(live.combat_stats.unwrap().damage, old_combat.damage + 3);
+        assert_eq!(
+            app.world().get::<CombatStats>(entity).unwrap().damage,
+            old_combat.damage + 3
+        );
+        let edits = &core.recorder.replay().unwrap().edits;
+        assert!(edits.iter().any(|edit| matches!(
+            edit.edit,
+            WorldEdit::ChangeStats { entity, .. } if entity == core_id
+        )));
+    }
+
+    #[test]
+    fn spawned_units_get_debug_names() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(SimulationPlugin);
+
+        let faction = GameFaction {
+            faction: rts_core::factions::FactionId::Collegium,
+        };
+        let entity = app
+            .world_mut()
+            .spawn((GamePosition::ORIGIN, faction, Unit::new(UnitType::Ranger)))
+            .id();
+        app.update();
+
+        let name = app.world().get::<GameDebugName>(entity).unwrap();
+        assert_eq!(name.0, "collegium/ranger#1");
+    }
+
+    #[test]
+    fn transferred_ownership_is_mirrored() {
+        use rts_core::components::FactionMember;
+        use rts_core::factions::FactionId;
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_plugins(SimulationPlugin);
+
+        let entity = app
+            .world_mut()
+            .spawn((
+                GamePosition::ORIGIN,
+                GameFaction {
+                    faction: FactionId::Collegium,
+                },
+                Unit::new(UnitType::Ranger),
+            ))
+            .id();
+        app.update();
+
+        let core_id = app.world().get::<CoreEntityId>(entity).unwrap().0;
+        app.world_mut()
+            .resource_mut::<CoreSimulation>()
+            .sim
+            .transfer_ownership(core_id, FactionMember::new(FactionId::Continuity, 0))
+            .unwrap();
+        app.update();
+
+        let faction = app.world().get::<GameFaction>(entity).unwrap();
+        assert_eq!(faction.faction, FactionId::Continuity);
+    }
 }
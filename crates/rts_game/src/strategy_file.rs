@@ -0,0 +1,119 @@
+//! Loading AI strategies from RON or JSON files.
+//!
+//! The [`Strategy`] format lives in `rts_core`, which does no IO; reading
+//! and parsing strategy files happens here, for the skirmish AI and the
+//! headless runner alike.
+
+use std::path::Path;
+
+use rts_core::strategy::Strategy;
+use thiserror::Error;
+
+/// Error type for strategy loading.
+#[derive(Error, Debug)]
+pub enum StrategyError {
+    /// File not found.
+    #[error("Strategy file not found: {0}")]
+    FileNotFound(String),
+    /// Failed to read file.
+    #[error("Failed to read strategy file: {0}")]
+    ReadError(#[from] std::io::Error),
+    /// Failed to parse RON.
+    #[error("Failed to parse strategy: {0}")]
+    ParseError(#[from] ron::error::SpannedError),
+    /// Failed to parse JSON.
+    #[error("Failed to parse strategy: {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+/// Load a strategy from a RON file, or a JSON file if its extension is
+/// `.json`.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be read or parsed.
+pub fn load_strategy<P: AsRef<Path>>(path: P) -> Result<Strategy, StrategyError> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Err(StrategyError::FileNotFound(path.display().to_string()));
+    }
+    let contents = std::fs::read_to_string(path)?;
+    if path.extension().is_some_and(|ext| ext == "json") {
+        strategy_from_json_str(&contents)
+    } else {
+        strategy_from_ron_str(&contents)
+    }
+}
+
+/// Parse a strategy written in RON.
+///
+/// # Errors
+///
+/// Returns an error if the string isn't a valid strategy.
+pub fn strategy_from_ron_str(ron: &str) -> Result<Strategy, StrategyError> {
+    Ok(ron::from_str(ron)?)
+}
+
+/// Parse a strategy written in JSON.
+///
+/// # Errors
+///
+/// Returns an error if the string isn't a valid strategy.
+pub fn strategy_from_json_str(json: &str) -> Result<Strategy, StrategyError> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/// Built-in strategy named `name`, or else the strategy file at that path.
+///
+/// # Errors
+///
+/// Returns an error if `name` is not a preset and no strategy can be loaded
+/// from it.
+pub fn resolve_strategy(name: &str) -> Result<Strategy, StrategyError> {
+    match Strategy::preset(name) {
+        Some(strategy) => Ok(strategy),
+        None => load_strategy(name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_resolve_before_files() {
+        for name in Strategy::PRESETS {
+            assert!(resolve_strategy(name).is_ok(), "{name}");
+        }
+        assert!(matches!(
+            resolve_strategy("no_such_strategy"),
+            Err(StrategyError::FileNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_strategies_round_trip_through_ron_and_json() {
+        let mut rush = Strategy::rush();
+        rush.tech_priorities = vec!["enhanced_training".to_string()];
+        rush.min_wave_size = Some(4);
+
+        let ron = ron::to_string(&rush).unwrap();
+        let from_ron = strategy_from_ron_str(&ron).unwrap();
+        assert_eq!(from_ron.tech_priorities, rush.tech_priorities);
+        assert_eq!(from_ron.min_wave_size, Some(4));
+
+        let json = serde_json::to_string(&rush).unwrap();
+        let from_json = strategy_from_json_str(&json).unwrap();
+        assert_eq!(from_json.attack_timing, rush.attack_timing);
+        assert_eq!(from_json.composition, rush.composition);
+    }
+
+    #[test]
+    fn test_shipped_strategies_load() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/strategies");
+        for name in ["economic", "rush", "turtle"] {
+            let strategy = load_strategy(dir.join(format!("{name}.ron"))).unwrap();
+            assert!(strategy.problems().is_empty(), "{name}");
+        }
+    }
+}
@@ -1,39 +1,44 @@
 //! UI plugin for game interface using egui.
 //!
-//! Provides resource HUD, minimap, selection panel, and command panel.
+//! Provides resource HUD, selection panel, command card and production
+//! panel. The minimap lives in [`crate::minimap`].
 
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiSet};
+use rts_core::abilities::AbilityTarget;
 use rts_core::components::Command as CoreCommand;
+use rts_core::data::{AbilityData, AbilityTargetType, FactionData, UnitData};
 use rts_core::factions::FactionId;
-use rts_core::math::{Fixed, Vec2Fixed};
+use rts_core::notifications::{BlockReason, Notification};
+use rts_core::simulation::Simulation;
 
-use crate::camera::MainCamera;
+use crate::camera::{camera_view_rect, MainCamera};
 use crate::components::{
-    AttackTarget, Building, BuildingType, CoreEntityId, GameCommandQueue, GameDepot, GameFaction,
-    GameHealth, GamePosition, GameProductionQueue, PlayerFaction, Selected, UnitType,
+    Building, BuildingType, CoreEntityId, FactionBuildings, GameCommandQueue, GameDebugName,
+    GameDepot, GameFaction, GameHealth, GameProductionQueue, PlayerFaction, Selected,
+    UnderConstruction,
 };
 use crate::construction::BuildingPlacement;
-use crate::data_loader::FactionRegistry;
+use crate::data_loader::{FactionDataReload, FactionRegistry};
 use crate::economy::PlayerResources;
-use crate::input::{calculate_formation_offset, InputMode};
-use crate::render::CommandFeedbackEvent;
-use crate::simulation::{ClientCommandSet, CoreCommandBuffer};
-use crate::unit_utils::is_ranged_unit;
-
-/// Supply cost for light units (infantry).
-const LIGHT_UNIT_SUPPLY: i32 = 1;
-
-/// Supply cost for heavy/specialized units (rangers, harvesters).
-const HEAVY_UNIT_SUPPLY: i32 = 2;
+use crate::input::{InputMode, PendingOrder, TargetedOrder};
+use crate::notifications::{GameNotification, IdleHarvesters};
+use crate::production::{cancel_production, queue_unit, validate_queue};
+use crate::research::ResearchRequests;
+use crate::simulation::{
+    ClientCommandSet, CoreCommandBuffer, CoreSimulation, GameSpeed, GAME_SPEEDS,
+};
+use crate::unit_utils::{is_ranged_unit, unit_supply};
 
 /// Plugin for game UI using egui.
 ///
 /// Provides:
-/// - Resource HUD (top bar)
-/// - Minimap (bottom-left)
+/// - Resource HUD (top bar), with the game clock and speed control
 /// - Selection panel (bottom-center)
-/// - Command panel (bottom-right)
+/// - Command card (bottom-right)
+/// - Production panel (above the command card)
+/// - Faction data reload notices (top-center)
+/// - Alerts and the idle-harvester button (below the resource bar)
 pub struct GameUiPlugin;
 
 impl Plugin for GameUiPlugin {
@@ -43,17 +48,30 @@ impl Plugin for GameUiPlugin {
             .init_resource::<PlayerFaction>()
             .init_resource::<UiSettings>()
             .init_resource::<InputMode>()
+            .init_resource::<PendingOrder>()
+            .init_resource::<FactionBuildings>()
             .init_resource::<CombatLegendState>()
+            .init_resource::<ReloadNotice>()
+            .init_resource::<AlertNotice>()
+            .init_resource::<IdleHarvesters>()
+            .init_resource::<GameSpeed>()
+            .add_event::<FactionDataReload>()
+            .add_event::<GameNotification>()
             .add_systems(Update, apply_ui_accessibility.after(EguiSet::InitContexts))
+            .add_systems(Update, track_reload_notice)
+            .add_systems(Update, track_alerts)
             .add_systems(
                 Update,
                 (
                     ui_resource_bar,
-                    ui_minimap.in_set(ClientCommandSet::Gather),
                     ui_combat_legend,
                     ui_selection_panel,
                     ui_command_panel.in_set(ClientCommandSet::Gather),
+                    ui_production_panel,
                     ui_build_menu,
+                    ui_reload_notice.after(track_reload_notice),
+                    ui_alerts.after(track_alerts),
+                    ui_idle_harvesters,
                 )
                     .after(apply_ui_accessibility),
             );
@@ -220,25 +238,217 @@ fn ui_combat_legend(
         });
 }
 
-/// Helper to get supply cost from unit data.
-///
-/// TODO: Move supply costs into unit data RON files once `UnitData` exposes an explicit
-/// `supply` field, so balance can be tuned in data instead of being inferred from tags.
-/// Until that migration is done, we derive supply from tags here to stay consistent with the
-/// existing unit definitions.
-fn get_unit_supply(unit_data: &rts_core::data::UnitData) -> i32 {
-    if unit_data.has_tag("harvester")
-        || unit_data.has_tag("worker")
-        || is_ranged_unit(unit_data)
-        || unit_data.has_tag("heavy")
-    {
-        HEAVY_UNIT_SUPPLY
+/// Seconds a successful reload stays on screen.
+const RELOAD_NOTICE_SECONDS: f32 = 4.0;
+
+/// Seconds a failed reload stays on screen.
+const RELOAD_ERROR_SECONDS: f32 = 10.0;
+
+/// The latest faction data reload, while it is on screen.
+#[derive(Resource, Debug, Clone, Default)]
+struct ReloadNotice {
+    /// What happened; None once it has faded.
+    text: Option<String>,
+    /// Whether the reload failed.
+    failed: bool,
+    /// Seconds left on screen.
+    remaining: f32,
+}
+
+/// Shows the latest faction data reload, replacing the one before.
+fn track_reload_notice(
+    time: Res<Time>,
+    mut reloads: EventReader<FactionDataReload>,
+    mut notice: ResMut<ReloadNotice>,
+) {
+    for reload in reloads.read() {
+        *notice = match reload {
+            FactionDataReload::Reloaded { faction, .. } => ReloadNotice {
+                text: Some(format!("Reloaded {} data", faction_name(*faction))),
+                failed: false,
+                remaining: RELOAD_NOTICE_SECONDS,
+            },
+            FactionDataReload::Failed { error, .. } => ReloadNotice {
+                text: Some(format!("Faction data not reloaded: {error}")),
+                failed: true,
+                remaining: RELOAD_ERROR_SECONDS,
+            },
+        };
+    }
+    if notice.text.is_some() {
+        notice.remaining -= time.delta_seconds();
+        if notice.remaining <= 0.0 {
+            notice.text = None;
+        }
+    }
+}
+
+/// Renders the latest faction data reload below the resource bar.
+fn ui_reload_notice(mut contexts: EguiContexts, notice: Res<ReloadNotice>) {
+    let Some(text) = &notice.text else {
+        return;
+    };
+    let Some(ctx) = contexts.try_ctx_mut() else {
+        return;
+    };
+    let color = if notice.failed {
+        egui::Color32::from_rgb(255, 90, 90)
     } else {
-        LIGHT_UNIT_SUPPLY // Infantry and light units cost 1 supply
+        egui::Color32::from_rgb(120, 230, 120)
+    };
+
+    egui::Area::new(egui::Id::new("reload_notice"))
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_max_width(600.0);
+                ui.label(egui::RichText::new(text).color(color));
+            });
+        });
+}
+
+/// Seconds an alert stays on screen.
+const ALERT_SECONDS: f32 = 5.0;
+
+/// The local player's latest alert, while it is on screen.
+#[derive(Resource, Debug, Clone, Default)]
+struct AlertNotice {
+    /// What happened; None once it has faded.
+    text: Option<String>,
+    /// Seconds left on screen.
+    remaining: f32,
+}
+
+/// Text for an alert, or None for notifications shown elsewhere, such as
+/// idle harvesters on their button.
+fn alert_text(notification: &Notification) -> Option<String> {
+    match notification {
+        Notification::HarvesterIdle { .. } => None,
+        Notification::ProductionBlocked {
+            reason: BlockReason::Resources(kind),
+            ..
+        } => Some(format!("Not enough {kind} to produce")),
+        Notification::ProductionBlocked {
+            reason: BlockReason::Supply,
+            ..
+        } => Some("Supply blocked: build more supply".to_string()),
+        Notification::UnderAttack { .. } => Some("We are under attack".to_string()),
+        Notification::ResearchComplete { tech_id, .. } => {
+            Some(format!("Research complete: {tech_id}"))
+        }
+    }
+}
+
+/// Shows the local player's latest alert. Attacks the camera is already
+/// looking at are left out.
+fn track_alerts(
+    time: Res<Time>,
+    player: Res<PlayerFaction>,
+    mut notifications: EventReader<GameNotification>,
+    mut notice: ResMut<AlertNotice>,
+    windows: Query<&Window>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+) {
+    let view = windows
+        .get_single()
+        .ok()
+        .zip(camera_query.get_single().ok())
+        .map(|(window, transform)| camera_view_rect(transform, window.size()));
+
+    for GameNotification(notification) in notifications.read() {
+        if notification.faction() != player.faction {
+            continue;
+        }
+        if let Notification::UnderAttack { position, .. } = notification {
+            let position = Vec2::new(position.x.to_num(), position.y.to_num());
+            if view.is_some_and(|view| view.contains(position)) {
+                continue;
+            }
+        }
+        if let Some(text) = alert_text(notification) {
+            *notice = AlertNotice {
+                text: Some(text),
+                remaining: ALERT_SECONDS,
+            };
+        }
+    }
+    if notice.text.is_some() {
+        notice.remaining -= time.delta_seconds();
+        if notice.remaining <= 0.0 {
+            notice.text = None;
+        }
     }
 }
+
+/// Renders the latest alert below the resource bar.
+fn ui_alerts(mut contexts: EguiContexts, notice: Res<AlertNotice>) {
+    let Some(text) = &notice.text else {
+        return;
+    };
+    let Some(ctx) = contexts.try_ctx_mut() else {
+        return;
+    };
+
+    egui::Area::new(egui::Id::new("alert_notice"))
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 80.0])
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(egui::RichText::new(text).color(egui::Color32::from_rgb(255, 200, 80)));
+            });
+        });
+}
+
+/// Renders a button that selects the local player's idle harvesters in
+/// turn, while they have any.
+fn ui_idle_harvesters(
+    mut contexts: EguiContexts,
+    mut commands: Commands,
+    player: Res<PlayerFaction>,
+    idle: Res<IdleHarvesters>,
+    mut next: Local<usize>,
+    factions: Query<&GameFaction>,
+    selected: Query<Entity, With<Selected>>,
+) {
+    let ours: Vec<Entity> = idle
+        .entities
+        .iter()
+        .copied()
+        .filter(|entity| {
+            factions
+                .get(*entity)
+                .is_ok_and(|faction| faction.faction == player.faction)
+        })
+        .collect();
+    if ours.is_empty() {
+        return;
+    }
+    let Some(ctx) = contexts.try_ctx_mut() else {
+        return;
+    };
+
+    egui::Area::new(egui::Id::new("idle_harvesters"))
+        .anchor(egui::Align2::LEFT_TOP, [10.0, 40.0])
+        .show(ctx, |ui| {
+            let button = ui
+                .button(format!("⛏ Idle harvesters: {}", ours.len()))
+                .on_hover_text("Select the next idle harvester.");
+            if button.clicked() {
+                for entity in selected.iter() {
+                    commands.entity(entity).remove::<Selected>();
+                }
+                *next = (*next + 1) % ours.len();
+                commands.entity(ours[*next]).insert(Selected);
+            }
+        });
+}
+
 /// Renders the top resource bar showing feedstock and supply.
-fn ui_resource_bar(mut contexts: EguiContexts, resources: Res<PlayerResources>) {
+fn ui_resource_bar(
+    mut contexts: EguiContexts,
+    resources: Res<PlayerResources>,
+    core: Option<Res<CoreSimulation>>,
+    mut speed: ResMut<GameSpeed>,
+) {
     let Some(ctx) = contexts.try_ctx_mut() else {
         return;
     };
@@ -274,162 +484,75 @@ fn ui_resource_bar(mut contexts: EguiContexts, resources: Res<PlayerResources>)
                         .color(egui::Color32::from_rgb(100, 255, 100)),
                 );
                 response.on_hover_text("Supply: limits total population.");
-                let supply_color = if resources.supply_used >= resources.supply_cap {
+                let supply_cap = resources.economy().supply_cap() as i32;
+                let supply_color = if resources.supply_used >= supply_cap {
                     egui::Color32::RED
-                } else if resources.supply_used as f32 >= resources.supply_cap as f32 * 0.8 {
+                } else if resources.supply_used as f32 >= supply_cap as f32 * 0.8 {
                     egui::Color32::YELLOW
                 } else {
                     egui::Color32::WHITE
                 };
                 ui.label(
-                    egui::RichText::new(format!(
-                        "{} / {}",
-                        resources.supply_used, resources.supply_cap
-                    ))
-                    .size(16.0)
-                    .strong()
-                    .color(supply_color),
+                    egui::RichText::new(format!("{} / {}", resources.supply_used, supply_cap))
+                        .size(16.0)
+                        .strong()
+                        .color(supply_color),
                 );
             });
 
-            // Spacer to push game time to right
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                ui.label(egui::RichText::new("0:00").size(14.0).weak());
-                ui.label("Game Time:");
-            });
-        });
-    });
-}
-
-/// Renders the minimap in the bottom-left corner.
-fn ui_minimap(
-    mut contexts: EguiContexts,
-    units: Query<(&GamePosition, &GameFaction)>,
-    mut camera_query: Query<&mut Transform, With<MainCamera>>,
-    mut core_commands: ResMut<CoreCommandBuffer>,
-    selected_units: Query<(Entity, &CoreEntityId), (With<Selected>, With<GameCommandQueue>)>,
-    mut commands: Commands,
-    input_mode: Res<InputMode>,
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut feedback_events: EventWriter<CommandFeedbackEvent>,
-) {
-    let Some(ctx) = contexts.try_ctx_mut() else {
-        return;
-    };
-    const MINIMAP_SIZE: f32 = 180.0;
-    const WORLD_SIZE: f32 = 2000.0; // Assumed world bounds
-
-    egui::Window::new("Minimap")
-        .title_bar(false)
-        .resizable(false)
-        .anchor(egui::Align2::LEFT_BOTTOM, [10.0, -10.0])
-        .fixed_size([MINIMAP_SIZE, MINIMAP_SIZE])
-        .show(ctx, |ui| {
-            let (response, painter) =
-                ui.allocate_painter(egui::Vec2::splat(MINIMAP_SIZE), egui::Sense::click());
-
-            let rect = response.rect;
-
-            // Background
-            painter.rect_filled(rect, 0.0, egui::Color32::from_rgb(20, 30, 20));
-
-            // Border
-            painter.rect_stroke(rect, 0.0, egui::Stroke::new(2.0, egui::Color32::GRAY));
-
-            // Draw units as dots
-            for (pos, faction) in units.iter() {
-                let world_pos = pos.as_vec2();
-
-                let minimap_pos = world_to_minimap(world_pos, rect, WORLD_SIZE);
-                let color = faction_to_egui_color(faction.faction);
-                painter.circle_filled(minimap_pos, 3.0, color);
-            }
-
-            // Draw camera viewport
-            if let Ok(camera_transform) = camera_query.get_single() {
-                let cam_pos = camera_transform.translation.truncate();
-                let viewport_size = Vec2::new(400.0, 300.0); // Approximate viewport size
-
-                let min_pos = world_to_minimap(cam_pos - viewport_size / 2.0, rect, WORLD_SIZE);
-                let max_pos = world_to_minimap(cam_pos + viewport_size / 2.0, rect, WORLD_SIZE);
-
-                painter.rect_stroke(
-                    egui::Rect::from_min_max(
-                        egui::Pos2::new(min_pos.x, min_pos.y),
-                        egui::Pos2::new(max_pos.x, max_pos.y),
-                    ),
-                    0.0,
-                    egui::Stroke::new(1.0, egui::Color32::WHITE),
-                );
+            // Rarer resources, once the player holds any
+            for (kind, amount) in resources.reserves.iter() {
+                ui.separator();
+                ui.label(egui::RichText::new(format!("{kind}: {amount}")).size(16.0))
+                    .on_hover_text("Rare resource: pays for advanced units.");
             }
 
-            if response.clicked() {
-                if let Some(pointer) = response.interact_pointer_pos() {
-                    let world_pos = minimap_to_world(pointer, rect, WORLD_SIZE);
-                    if let Ok(mut camera_transform) = camera_query.get_single_mut() {
-                        camera_transform.translation.x = world_pos.x;
-                        camera_transform.translation.y = world_pos.y;
-                    }
-
-                    let unit_count = selected_units.iter().count();
-                    if unit_count > 0 {
-                        let shift_held = keyboard.pressed(KeyCode::ShiftLeft)
-                            || keyboard.pressed(KeyCode::ShiftRight);
-
-                        for (index, (entity, core_id)) in selected_units.iter().enumerate() {
-                            let offset = if unit_count > 1 {
-                                calculate_formation_offset(index, unit_count)
-                            } else {
-                                Vec2::ZERO
-                            };
-
-                            let target = Vec2Fixed::new(
-                                Fixed::from_num(world_pos.x + offset.x),
-                                Fixed::from_num(world_pos.y + offset.y),
-                            );
-
-                            let command = match *input_mode {
-                                InputMode::Normal => CoreCommand::MoveTo(target),
-                                InputMode::AttackMove => CoreCommand::AttackMove(target),
-                                InputMode::Patrol => CoreCommand::Patrol(target),
-                            };
-
-                            if shift_held {
-                                core_commands.queue(core_id.0, command);
-                            } else {
-                                core_commands.set(core_id.0, command);
-                                commands.entity(entity).remove::<AttackTarget>();
-                            }
-                        }
-
-                        feedback_events.send(CommandFeedbackEvent {
-                            position: world_pos,
-                        });
+            // Game time and speed control, pushed to the right
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                for &preset in GAME_SPEEDS.iter().rev() {
+                    let selected = (speed.multiplier() - preset).abs() < f32::EPSILON;
+                    if ui
+                        .selectable_label(selected, format!("{preset}x"))
+                        .on_hover_text("Game speed ([ and ] to change)")
+                        .clicked()
+                    {
+                        speed.set_multiplier(preset);
                     }
                 }
-            }
+                if ui
+                    .add_enabled(speed.paused, egui::Button::new("Step"))
+                    .on_hover_text("Run one tick (.)")
+                    .clicked()
+                {
+                    speed.step();
+                }
+                let label = if speed.paused { "Resume" } else { "Pause" };
+                if ui.button(label).on_hover_text("Pause (Space)").clicked() {
+                    speed.toggle_pause();
+                }
+                ui.separator();
+                let tick = core.map_or(0, |core| core.sim.get_tick());
+                ui.label(
+                    egui::RichText::new(format_game_time(tick))
+                        .size(14.0)
+                        .weak(),
+                );
+                ui.label("Game Time:");
+            });
         });
+    });
 }
 
-fn world_to_minimap(world_pos: Vec2, rect: egui::Rect, world_size: f32) -> egui::Pos2 {
-    let minimap_x = rect.min.x + ((world_pos.x + world_size / 2.0) / world_size) * rect.width();
-    let minimap_y = rect.max.y - ((world_pos.y + world_size / 2.0) / world_size) * rect.height();
-    egui::Pos2::new(minimap_x, minimap_y)
-}
-
-fn minimap_to_world(minimap_pos: egui::Pos2, rect: egui::Rect, world_size: f32) -> Vec2 {
-    let normalized_x = (minimap_pos.x - rect.min.x) / rect.width();
-    let normalized_y = (rect.max.y - minimap_pos.y) / rect.height();
-    Vec2::new(
-        normalized_x * world_size - world_size / 2.0,
-        normalized_y * world_size - world_size / 2.0,
-    )
+/// `m:ss` of game time for a tick count.
+fn format_game_time(tick: u64) -> String {
+    let secs = tick / u64::from(rts_core::simulation::TICK_RATE);
+    format!("{}:{:02}", secs / 60, secs % 60)
 }
 
 /// Renders the selection panel showing selected unit info.
 fn ui_selection_panel(
     mut contexts: EguiContexts,
-    selected: Query<(&GameHealth, &GameFaction), With<Selected>>,
+    selected: Query<(&GameHealth, &GameFaction, Option<&GameDebugName>), With<Selected>>,
 ) {
     let Some(ctx) = contexts.try_ctx_mut() else {
         return;
@@ -448,7 +571,7 @@ fn ui_selection_panel(
         .show(ctx, |ui| {
             if selected_units.len() == 1 {
                 // Single unit selected - show details
-                let (health, faction) = selected_units[0];
+                let (health, faction, debug_name) = selected_units[0];
 
                 ui.horizontal(|ui| {
                     // Unit portrait placeholder
@@ -463,7 +586,7 @@ fn ui_selection_panel(
                                 .size(16.0)
                                 .strong(),
                         );
-                        ui.label("Unit");
+                        ui.label(debug_name.map_or("Unit", |name| name.0.as_str()));
 
                         // Health bar
                         let health_ratio = health.ratio();
@@ -510,7 +633,7 @@ fn ui_selection_panel(
                     // Group by faction
                     let mut faction_counts: std::collections::HashMap<FactionId, usize> =
                         std::collections::HashMap::new();
-                    for (_, faction) in &selected_units {
+                    for (_, faction, _) in &selected_units {
                         *faction_counts.entry(faction.faction).or_insert(0) += 1;
                     }
 
@@ -526,23 +649,127 @@ fn ui_selection_panel(
         });
 }
 
-/// Renders the command panel with action buttons.
+/// An order on the command card of the selected units.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CardOrder {
+    /// Waits for the player to right-click a target.
+    Targeted(TargetedOrder),
+    /// Stop and clear the command queue.
+    Stop,
+    /// Hold position.
+    Hold,
+    /// Use a self-targeted ability right away.
+    Activate(String),
+}
+
+impl CardOrder {
+    /// Button label and hover text.
+    fn label(&self) -> (String, String) {
+        match self {
+            Self::Targeted(TargetedOrder::AttackMove) => (
+                "⚔ Attack".to_string(),
+                "Right-click to attack-move, engaging enemies on the way.".to_string(),
+            ),
+            Self::Targeted(TargetedOrder::Patrol) => (
+                "🔁 Patrol".to_string(),
+                "Right-click to patrol between here and the target.".to_string(),
+            ),
+            Self::Targeted(TargetedOrder::Ability { id, target }) => {
+                let aim = match target {
+                    AbilityTargetType::Entity => "a unit",
+                    _ => "a point",
+                };
+                (
+                    format!("✨ {id}"),
+                    format!("Right-click {aim} to use {id}."),
+                )
+            }
+            Self::Stop => (
+                "⏹ Stop".to_string(),
+                "Stop selected units and clear their queue.".to_string(),
+            ),
+            Self::Hold => (
+                "🛡 Hold".to_string(),
+                "Hold position and attack in range.".to_string(),
+            ),
+            Self::Activate(id) => (format!("✨ {id}"), format!("Use {id}.")),
+        }
+    }
+}
+
+/// Orders available to the selected units: the standard unit orders, then
+/// each distinct ability the units carry, in data order.
+fn unit_card_orders<'a>(abilities: impl IntoIterator<Item = &'a AbilityData>) -> Vec<CardOrder> {
+    let mut orders = vec![
+        CardOrder::Targeted(TargetedOrder::AttackMove),
+        CardOrder::Targeted(TargetedOrder::Patrol),
+        CardOrder::Stop,
+        CardOrder::Hold,
+    ];
+    for ability in abilities {
+        let order = match ability.target {
+            AbilityTargetType::Caster => CardOrder::Activate(ability.id.clone()),
+            target => CardOrder::Targeted(TargetedOrder::Ability {
+                id: ability.id.clone(),
+                target,
+            }),
+        };
+        if !orders.contains(&order) {
+            orders.push(order);
+        }
+    }
+    orders
+}
+
+/// Units a building of `building_type` can produce, from its faction data.
+fn production_options(faction_data: &FactionData, building_type: BuildingType) -> Vec<&UnitData> {
+    let Some(building) = faction_data.get_building(building_type.to_building_id(faction_data.id))
+    else {
+        return Vec::new();
+    };
+    building
+        .produces
+        .iter()
+        .filter_map(|unit_id| {
+            let unit = faction_data.get_unit(unit_id);
+            if unit.is_none() {
+                tracing::warn!(
+                    "Building '{}' produces unknown unit '{}' - production button not available",
+                    building.id,
+                    unit_id
+                );
+            }
+            unit
+        })
+        .collect()
+}
+
+/// Icon for a unit on production buttons and queues.
+fn unit_icon(unit_data: &UnitData) -> &'static str {
+    if unit_data.has_tag("harvester") || unit_data.has_tag("worker") {
+        "🔧"
+    } else if is_ranged_unit(unit_data) {
+        "🏹"
+    } else {
+        "🗡"
+    }
+}
+
+/// Renders the command card for the current selection: research for a
+/// tech lab, and unit orders and abilities for selected units, generated
+/// from their data. Production has its own panel, [`ui_production_panel`].
 fn ui_command_panel(
     mut contexts: EguiContexts,
     selected: Query<(Entity, &GameFaction), With<Selected>>,
     mut core_commands: ResMut<CoreCommandBuffer>,
     core_ids: Query<&CoreEntityId>,
-    mut depot_production: Query<
-        (&GameFaction, &mut GameProductionQueue),
-        (With<GameDepot>, Without<Building>),
-    >,
-    mut building_production: Query<
-        (&GameFaction, &Building, &mut GameProductionQueue),
-        Without<GameDepot>,
-    >,
-    mut resources: ResMut<PlayerResources>,
+    units: Query<(), With<GameCommandQueue>>,
+    resources: Res<PlayerResources>,
     player_faction: Res<PlayerFaction>,
-    faction_registry: Res<FactionRegistry>,
+    buildings: Query<&Building>,
+    core: Res<CoreSimulation>,
+    mut research: ResMut<ResearchRequests>,
+    mut pending: ResMut<PendingOrder>,
 ) {
     let Some(ctx) = contexts.try_ctx_mut() else {
         return;
@@ -550,20 +777,37 @@ fn ui_command_panel(
     let owned_entities = player_owned_entities(selected.iter(), player_faction.faction);
 
     if owned_entities.is_empty() {
+        pending.0 = None;
         return;
     }
 
-    // Check if we have a selected depot or barracks (for production UI)
-    let mut selected_depot: Option<Entity> = None;
-    let mut selected_barracks: Option<Entity> = None;
-    for entity in owned_entities.iter().copied() {
-        if depot_production.get(entity).is_ok() {
-            selected_depot = Some(entity);
-        } else if let Ok((_, building, _)) = building_production.get(entity) {
-            if building.building_type == BuildingType::Barracks {
-                selected_barracks = Some(entity);
-            }
-        }
+    // Check if we have a selected tech lab (for research UI)
+    let selected_lab = owned_entities
+        .iter()
+        .copied()
+        .filter(|entity| {
+            buildings
+                .get(*entity)
+                .is_ok_and(|b| b.building_type == BuildingType::TechLab)
+        })
+        .find_map(|entity| core_ids.get(entity).ok().copied());
+
+    let selected_units: Vec<CoreEntityId> = owned_entities
+        .iter()
+        .filter(|entity| units.get(**entity).is_ok())
+        .filter_map(|entity| core_ids.get(*entity).ok().copied())
+        .collect();
+    let tick = core.sim.get_tick();
+    let unit_abilities = || {
+        selected_units
+            .iter()
+            .filter_map(|id| core.sim.get_entity(id.0)?.row.abilities.as_ref())
+    };
+    let orders = unit_card_orders(
+        unit_abilities().flat_map(|abilities| abilities.slots.iter().map(|slot| &slot.ability)),
+    );
+    if selected_units.is_empty() && selected_lab.is_none() {
+        return;
     }
 
     egui::Window::new("Commands")
@@ -572,249 +816,311 @@ fn ui_command_panel(
         .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
         .fixed_size([280.0, 220.0])
         .show(ctx, |ui| {
-            // If depot selected, show harvester production only
-            if let Some(depot_entity) = selected_depot {
-                if let Ok((faction, mut production)) = depot_production.get_mut(depot_entity) {
-                    if faction.faction == player_faction.faction {
-                        ui.label(egui::RichText::new("Command Center").strong());
-                        ui.separator();
-
-                        // Look up faction-specific harvester unit ID
-                        let harvester_id = UnitType::Harvester.to_unit_id(faction.faction);
-
-                        // Get unit data from registry
-                        if let Some(faction_data) = faction_registry.get(faction.faction) {
-                            if let Some(unit_data) = faction_data.get_unit(harvester_id) {
-                                ui.horizontal(|ui| {
-                                    // Harvester button (only unit depot produces)
-                                    let harv_cost = unit_data.cost as i32;
-                                    let harv_supply = get_unit_supply(unit_data);
-                                    let can_afford_harv = resources.feedstock >= harv_cost
-                                        && resources.supply_used + harv_supply <= resources.supply_cap
-                                        && production.can_queue();
-                                    ui.add_enabled_ui(can_afford_harv, |ui| {
-                                        if ui
-                                            .button(format!(
-                                                "🔧 Harvester\n{} ⚡{}",
-                                                harv_cost, harv_supply
-                                            ))
-                                            .clicked()
-                                        {
-                                            resources.feedstock -= harv_cost;
-                                            resources.supply_used += harv_supply;
-                                            production.enqueue(harvester_id.to_string());
-                                        }
-                                    });
-                                });
-                            } else {
-                                tracing::warn!(
-                                    "Harvester unit '{}' not found in faction {:?} data - production button not available",
-                                    harvester_id,
-                                    faction.faction
-                                );
-                            }
-                        } else {
-                            tracing::warn!(
-                                "Faction {:?} data not loaded - Harvester production button not available",
-                                faction.faction
-                            );
-                        }
+            // If tech lab selected, show research
+            if let Some(lab) = selected_lab {
+                ui.label(egui::RichText::new("Tech Lab").strong());
+                ui.separator();
+                render_research(
+                    ui,
+                    &core.sim,
+                    lab.0,
+                    player_faction.faction,
+                    resources.feedstock,
+                    &mut research,
+                );
+                ui.separator();
+            }
 
-                        render_production_queue(ui, &mut production, &mut resources, &faction_registry, faction.faction);
-                        ui.separator();
-                    }
-                }
+            if selected_units.is_empty() {
+                return;
             }
 
-            // If barracks selected, show infantry/ranger production
-            if let Some(barracks_entity) = selected_barracks {
-                if let Ok((faction, _, mut production)) =
-                    building_production.get_mut(barracks_entity)
-                {
-                    if faction.faction == player_faction.faction {
-                        ui.label(egui::RichText::new("Barracks").strong());
-                        ui.separator();
-
-                        // Look up faction-specific unit IDs
-                        let infantry_id = UnitType::Infantry.to_unit_id(faction.faction);
-                        let ranger_id = UnitType::Ranger.to_unit_id(faction.faction);
-
-                        if let Some(faction_data) = faction_registry.get(faction.faction) {
-                            ui.horizontal(|ui| {
-                                // Infantry button
-                                if let Some(unit_data) = faction_data.get_unit(infantry_id) {
-                                    let inf_cost = unit_data.cost as i32;
-                                    let inf_supply = get_unit_supply(unit_data);
-                                    let can_afford_inf = resources.feedstock >= inf_cost
-                                        && resources.supply_used + inf_supply <= resources.supply_cap
-                                        && production.can_queue();
-                                    ui.add_enabled_ui(can_afford_inf, |ui| {
-                                        if ui
-                                            .button(format!("🗡 Infantry\n{} ⚡{}", inf_cost, inf_supply))
-                                            .clicked()
-                                        {
-                                            resources.feedstock -= inf_cost;
-                                            resources.supply_used += inf_supply;
-                                            production.enqueue(infantry_id.to_string());
-                                        }
-                                    });
+            ui.horizontal_wrapped(|ui| {
+                for order in &orders {
+                    let (label, hint) = order.label();
+                    let ready = match order {
+                        CardOrder::Targeted(TargetedOrder::Ability { id, .. })
+                        | CardOrder::Activate(id) => {
+                            unit_abilities().any(|abilities| abilities.is_ready(id, tick))
+                        }
+                        _ => true,
+                    };
+                    let active =
+                        matches!(order, CardOrder::Targeted(o) if pending.0.as_ref() == Some(o));
+                    ui.add_enabled_ui(ready, |ui| {
+                        let response = ui
+                            .selectable_label(active, egui::RichText::new(label).size(14.0))
+                            .on_hover_text(hint);
+                        if !response.clicked() {
+                            return;
+                        }
+                        match order {
+                            CardOrder::Targeted(order) => {
+                                pending.0 = (!active).then(|| order.clone());
+                            }
+                            CardOrder::Stop | CardOrder::Hold => {
+                                let command = if *order == CardOrder::Stop {
+                                    CoreCommand::Stop
                                 } else {
-                                    tracing::warn!(
-                                        "Infantry unit '{}' not found in faction {:?} data - production button not available",
-                                        infantry_id,
-                                        faction.faction
-                                    );
+                                    CoreCommand::HoldPosition
+                                };
+                                for core_id in &selected_units {
+                                    core_commands.set(core_id.0, command.clone());
                                 }
-
-                                // Ranger button
-                                if let Some(unit_data) = faction_data.get_unit(ranger_id) {
-                                    let rang_cost = unit_data.cost as i32;
-                                    let rang_supply = get_unit_supply(unit_data);
-                                    let can_afford_rang = resources.feedstock >= rang_cost
-                                        && resources.supply_used + rang_supply <= resources.supply_cap
-                                        && production.can_queue();
-                                    ui.add_enabled_ui(can_afford_rang, |ui| {
-                                        if ui
-                                            .button(format!("🏹 Ranger\n{} ⚡{}", rang_cost, rang_supply))
-                                            .clicked()
-                                        {
-                                            resources.feedstock -= rang_cost;
-                                            resources.supply_used += rang_supply;
-                                            production.enqueue(ranger_id.to_string());
-                                        }
-                                    });
-                                } else {
-                                    tracing::warn!(
-                                        "Ranger unit '{}' not found in faction {:?} data - production button not available",
-                                        ranger_id,
-                                        faction.faction
-                                    );
+                                pending.0 = None;
+                            }
+                            CardOrder::Activate(id) => {
+                                for core_id in &selected_units {
+                                    let has_ability = core
+                                        .sim
+                                        .get_entity(core_id.0)
+                                        .and_then(|e| e.row.abilities.as_ref())
+                                        .is_some_and(|abilities| abilities.get(id).is_some());
+                                    if has_ability {
+                                        core_commands.set(
+                                            core_id.0,
+                                            CoreCommand::UseAbility {
+                                                ability_id: id.clone(),
+                                                target: AbilityTarget::Caster,
+                                            },
+                                        );
+                                    }
                                 }
-                            });
-                        } else {
-                            tracing::warn!(
-                                "Faction {:?} data not loaded - Infantry/Ranger production buttons not available",
-                                faction.faction
-                            );
+                                pending.0 = None;
+                            }
                         }
-
-                        render_production_queue(ui, &mut production, &mut resources, &faction_registry, faction.faction);
-                        ui.separator();
-                    }
+                    });
                 }
-            }
+            });
 
-            // Standard unit commands
+            let hint = if pending.0.is_some() {
+                "Right-click to target, Esc to cancel"
+            } else {
+                "Right-click to move"
+            };
+            ui.label(egui::RichText::new(hint).weak().size(11.0));
+        });
+}
+
+/// Helper to render a research facility's queue and the technologies it
+/// can start.
+fn render_research(
+    ui: &mut egui::Ui,
+    sim: &Simulation,
+    lab: rts_core::components::EntityId,
+    faction_id: FactionId,
+    feedstock: i32,
+    research: &mut ResearchRequests,
+) {
+    let Some(tech_state) = sim.tech_state(faction_id) else {
+        ui.label(egui::RichText::new("No technologies").weak());
+        return;
+    };
+
+    let queue = sim
+        .get_entity(lab)
+        .and_then(|e| e.row.research.as_ref())
+        .map(|r| &r.queue);
+    if let Some(queue) = queue.filter(|q| !q.is_empty()) {
+        for (i, item) in queue.iter().enumerate() {
             ui.horizontal(|ui| {
-                // Stop button
-                let response = ui
-                    .button(egui::RichText::new("⏹ Stop").size(14.0))
-                    .on_hover_text("Stop selected units and clear their queue.");
-                if response.clicked() {
-                    for entity in owned_entities.iter().copied() {
-                        if let Ok(core_id) = core_ids.get(entity) {
-                            core_commands.set(core_id.0, CoreCommand::Stop);
-                        }
-                    }
+                ui.label(egui::RichText::new(&item.tech_id).size(12.0));
+                if i == 0 {
+                    let progress = item.percentage() as f32 / 100.0;
+                    ui.add(egui::ProgressBar::new(progress).desired_width(60.0));
                 }
+            });
+        }
+        if ui.small_button("❌ Cancel Last").clicked() {
+            research.cancel(lab);
+        }
+        ui.separator();
+    }
 
-                // Hold Position button
-                let response = ui
-                    .button(egui::RichText::new("🛡 Hold").size(14.0))
-                    .on_hover_text("Hold position and attack in range.");
-                if response.clicked() {
-                    for entity in owned_entities.iter().copied() {
-                        if let Ok(core_id) = core_ids.get(entity) {
-                            core_commands.set(core_id.0, CoreCommand::HoldPosition);
-                        }
-                    }
+    ui.horizontal_wrapped(|ui| {
+        for tech in tech_state.techs.values() {
+            if sim.validate_research(lab, &tech.id).is_err() {
+                continue;
+            }
+            ui.add_enabled_ui(feedstock >= tech.cost, |ui| {
+                if ui
+                    .button(format!("🔬 {}\n{}", tech.id, tech.cost))
+                    .clicked()
+                {
+                    research.queue(lab, tech.id.clone());
                 }
             });
+        }
+    });
+}
 
-            ui.label(egui::RichText::new("Right-click to move").weak().size(11.0));
+/// Renders the production panel for selected production buildings: a
+/// button per unit the building makes, validated against cost, supply,
+/// queue space and prerequisites, then its queue with progress.
+/// Right-clicking a queued unit cancels it for a partial refund.
+fn ui_production_panel(
+    mut contexts: EguiContexts,
+    selected: Query<(Entity, &GameFaction), With<Selected>>,
+    mut production: Query<
+        (Option<&Building>, Has<GameDepot>, &mut GameProductionQueue),
+        Without<UnderConstruction>,
+    >,
+    mut resources: ResMut<PlayerResources>,
+    player_faction: Res<PlayerFaction>,
+    faction_registry: Res<FactionRegistry>,
+    faction_buildings: Res<FactionBuildings>,
+    mut core: ResMut<CoreSimulation>,
+    core_ids: Query<&CoreEntityId>,
+) {
+    let Some(ctx) = contexts.try_ctx_mut() else {
+        return;
+    };
+    let faction = player_faction.faction;
+
+    // One section per selected building type
+    let mut producers: Vec<(BuildingType, Entity)> = Vec::new();
+    for entity in player_owned_entities(selected.iter(), faction) {
+        let Ok((building, is_depot, _)) = production.get(entity) else {
+            continue;
+        };
+        let building_type = match building {
+            Some(building) => building.building_type,
+            None if is_depot => BuildingType::Depot,
+            None => continue,
+        };
+        if !producers.iter().any(|(t, _)| *t == building_type) {
+            producers.push((building_type, entity));
+        }
+    }
+    if producers.is_empty() {
+        return;
+    }
+
+    let faction_data = faction_registry.get(faction);
+    if faction_data.is_none() {
+        tracing::warn!(
+            "Faction {:?} data not loaded - production buttons not available",
+            faction
+        );
+    }
+    let sim = &core.sim;
+    let has_prerequisite = |id: &str| {
+        faction_buildings.has_building_id(faction, id) || sim.has_researched(faction, id)
+    };
+    let mut blocked = Vec::new();
+
+    egui::Window::new("Production")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -240.0])
+        .fixed_size([280.0, 160.0])
+        .show(ctx, |ui| {
+            for (building_type, entity) in producers.iter().copied() {
+                let Ok((_, _, mut queue)) = production.get_mut(entity) else {
+                    continue;
+                };
+                ui.label(egui::RichText::new(building_type.name()).strong());
+                ui.separator();
+
+                if let Some(faction_data) = faction_data {
+                    ui.horizontal_wrapped(|ui| {
+                        for unit_data in production_options(faction_data, building_type) {
+                            let check =
+                                validate_queue(unit_data, &queue, &resources, has_prerequisite);
+                            // Shortages stay clickable so they raise an alert
+                            let shortage = check.as_ref().err().and_then(BlockReason::from_error);
+                            ui.add_enabled_ui(check.is_ok() || shortage.is_some(), |ui| {
+                                let mut response = ui.button(format!(
+                                    "{} {}\n{} ⚡{}",
+                                    unit_icon(unit_data),
+                                    unit_data.id,
+                                    unit_data.cost,
+                                    unit_supply(unit_data)
+                                ));
+                                if let Err(e) = check {
+                                    response = response
+                                        .on_hover_text(e.to_string())
+                                        .on_disabled_hover_text(e.to_string());
+                                }
+                                if response.clicked() {
+                                    if let Err(e) = queue_unit(
+                                        unit_data,
+                                        &mut queue,
+                                        &mut resources,
+                                        has_prerequisite,
+                                    ) {
+                                        tracing::warn!("Cannot queue {}: {}", unit_data.id, e);
+                                        if let Some(reason) = BlockReason::from_error(&e) {
+                                            blocked.push((entity, reason));
+                                        }
+                                    }
+                                }
+                            });
+                        }
+                    });
+                }
+
+                render_production_queue(ui, &mut queue, &mut resources, faction_data);
+                ui.separator();
+            }
         });
+
+    for (entity, reason) in blocked {
+        if let Ok(core_id) = core_ids.get(entity) {
+            core.sim.notify_production_blocked(core_id.0, reason);
+        }
+    }
 }
 
-/// Helper to render production queue UI.
+/// Helper to render a production queue: the unit in production with its
+/// progress, then the units waiting behind it. Right-click cancels one.
 fn render_production_queue(
     ui: &mut egui::Ui,
     production: &mut GameProductionQueue,
     resources: &mut PlayerResources,
-    faction_registry: &FactionRegistry,
-    faction_id: FactionId,
+    faction_data: Option<&FactionData>,
 ) {
-    if !production.queue.is_empty() {
-        ui.separator();
-        ui.label(egui::RichText::new("Production Queue").size(12.0));
+    if production.queue.is_empty() {
+        ui.label(egui::RichText::new("Queue empty").weak().size(11.0));
+        return;
+    }
 
-        ui.horizontal(|ui| {
-            for (i, queued) in production.queue.iter().enumerate() {
-                // Determine icon based on unit tags
-                let icon = if let Some(faction_data) = faction_registry.get(faction_id) {
-                    if let Some(unit_data) = faction_data.get_unit(&queued.unit_id) {
-                        if unit_data.has_tag("harvester") || unit_data.has_tag("worker") {
-                            "🔧"
-                        } else if is_ranged_unit(unit_data) {
-                            "🏹"
-                        } else {
-                            "🗡"
-                        }
-                    } else {
-                        "❓"
-                    }
-                } else {
-                    "❓"
-                };
+    let mut cancelled = None;
+    ui.horizontal_wrapped(|ui| {
+        for (i, queued) in production.queue.iter().enumerate() {
+            // Determine icon based on unit tags
+            let icon = faction_data
+                .and_then(|faction_data| faction_data.get_unit(&queued.unit_id))
+                .map_or("❓", unit_icon);
 
-                if i == 0 {
-                    let progress_pct = (queued.progress * 100.0).round() as i32;
-                    ui.group(|ui| {
-                        ui.vertical(|ui| {
-                            ui.label(egui::RichText::new(icon).size(20.0));
+            let response = ui
+                .group(|ui| {
+                    ui.vertical(|ui| {
+                        ui.label(egui::RichText::new(icon).size(20.0));
+                        if i == 0 {
+                            let progress_pct = (queued.progress * 100.0).round() as i32;
                             ui.add(egui::ProgressBar::new(queued.progress).desired_width(30.0));
                             ui.label(
                                 egui::RichText::new(format!("{}%", progress_pct))
                                     .size(10.0)
                                     .weak(),
                             );
-                        });
-                    });
-                } else {
-                    ui.group(|ui| {
-                        ui.vertical(|ui| {
-                            ui.label(egui::RichText::new(icon).size(20.0));
+                        } else {
                             ui.label(egui::RichText::new(format!("#{}", i)).size(10.0).weak());
-                        });
+                        }
                     });
-                }
-            }
-        });
-
-        if ui.small_button("❌ Cancel Last").clicked() {
-            // Check if faction data exists before mutating the production queue
-            if let Some(faction_data) = faction_registry.get(faction_id) {
-                if let Some((cancelled_id, refund_rate)) = production.cancel_last() {
-                    // Look up unit cost and supply for refund
-                    if let Some(unit_data) = faction_data.get_unit(&cancelled_id) {
-                        let refund = (unit_data.cost as f32 * refund_rate * 0.75) as i32;
-                        resources.feedstock += refund;
-                        // Also refund supply
-                        let supply = get_unit_supply(unit_data);
-                        resources.supply_used -= supply;
-                    } else {
-                        tracing::warn!(
-                            "Failed to find unit data for unit '{}' when canceling production; no refund applied",
-                            cancelled_id
-                        );
-                    }
-                }
-            } else {
-                tracing::warn!(
-                    "Failed to find faction data for faction {:?}; not canceling last production queue item",
-                    faction_id
-                );
+                })
+                .response
+                .interact(egui::Sense::click())
+                .on_hover_text(format!("{}\nRight-click to cancel", queued.unit_id));
+            if response.secondary_clicked() {
+                cancelled = Some(i);
             }
         }
+    });
+
+    if let Some(index) = cancelled {
+        cancel_production(production, index, faction_data, resources);
     }
 }
 
@@ -842,7 +1148,7 @@ fn building_tooltip(building_type: BuildingType) -> String {
 }
 
 #[cfg(test)]
-fn unit_tooltip(unit_type: UnitType) -> String {
+fn unit_tooltip(unit_type: crate::components::UnitType) -> String {
     format!(
         "{}\nCost: {} feedstock\nSupply: {}",
         unit_type.name(),
@@ -940,20 +1246,10 @@ fn ui_build_menu(
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    #[test]
-    fn minimap_world_round_trip() {
-        let rect =
-            egui::Rect::from_min_max(egui::Pos2::new(0.0, 0.0), egui::Pos2::new(200.0, 200.0));
-        let world_size = 2000.0;
-        let world_pos = Vec2::new(150.0, -250.0);
-
-        let minimap_pos = world_to_minimap(world_pos, rect, world_size);
-        let back = minimap_to_world(minimap_pos, rect, world_size);
-
-        let delta = (world_pos - back).length();
-        assert!(delta < 0.01);
-    }
+    use crate::components::UnitType;
+    use crate::data_loader::load_factions_from_directory;
+    use rts_core::data::AbilityEffect;
+    use rts_core::math::Fixed as CoreFixed;
 
     #[test]
     fn apply_ui_settings_updates_scale_and_contrast() {
@@ -969,6 +1265,33 @@ mod tests {
         assert!(ctx.style().visuals.override_text_color.is_some());
     }
 
+    #[test]
+    fn reload_notices_show_the_latest_and_fade() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ReloadNotice>()
+            .add_event::<FactionDataReload>()
+            .add_systems(Update, track_reload_notice);
+
+        app.world_mut().send_event(FactionDataReload::Reloaded {
+            faction: FactionId::Zephyr,
+            previous: None,
+        });
+        app.world_mut().send_event(FactionDataReload::Failed {
+            path: "zephyr.ron".to_string(),
+            error: "expected `(`".to_string(),
+        });
+        app.update();
+
+        let notice = app.world().resource::<ReloadNotice>();
+        assert!(notice.failed);
+        assert!(notice.text.as_ref().unwrap().contains("expected `(`"));
+
+        app.world_mut().resource_mut::<ReloadNotice>().remaining = 0.0;
+        app.update();
+        assert!(app.world().resource::<ReloadNotice>().text.is_none());
+    }
+
     #[test]
     fn combat_legend_entries_include_all_factions() {
         let entries = combat_legend_entries();
@@ -1006,4 +1329,54 @@ mod tests {
         let tooltip = building_tooltip(BuildingType::Turret);
         assert!(tooltip.contains("Cost:"));
     }
+
+    fn ability(id: &str, target: AbilityTargetType) -> AbilityData {
+        AbilityData {
+            id: id.to_string(),
+            name: format!("ability.{id}.name"),
+            cooldown: 100,
+            cost: 0,
+            target,
+            range: CoreFixed::from_num(5),
+            radius: CoreFixed::ZERO,
+            effect: AbilityEffect::Heal { amount: 10 },
+        }
+    }
+
+    #[test]
+    fn unit_card_lists_standard_orders_then_distinct_abilities() {
+        let repair = ability("field_repair", AbilityTargetType::Entity);
+        let barrier = ability("barrier", AbilityTargetType::Caster);
+        let orders = unit_card_orders([&repair, &barrier, &repair]);
+
+        assert_eq!(
+            orders,
+            vec![
+                CardOrder::Targeted(TargetedOrder::AttackMove),
+                CardOrder::Targeted(TargetedOrder::Patrol),
+                CardOrder::Stop,
+                CardOrder::Hold,
+                CardOrder::Targeted(TargetedOrder::Ability {
+                    id: "field_repair".to_string(),
+                    target: AbilityTargetType::Entity,
+                }),
+                CardOrder::Activate("barrier".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn production_options_come_from_building_data() {
+        let registry = load_factions_from_directory(std::path::Path::new("assets/data/factions"))
+            .expect("faction data loads");
+        let continuity = registry.get(FactionId::Continuity).unwrap();
+
+        let ids: Vec<_> = production_options(continuity, BuildingType::Barracks)
+            .into_iter()
+            .map(|unit| unit.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["security_team", "crowd_management_unit"]);
+
+        assert!(production_options(continuity, BuildingType::Turret).is_empty());
+    }
 }
@@ -7,3 +7,12 @@
 pub fn is_ranged_unit(unit_data: &rts_core::data::UnitData) -> bool {
     unit_data.has_tag("ranged") || unit_data.has_tag("ranger")
 }
+
+/// Supply cost of a unit, as [`PlayerResources`](crate::economy::PlayerResources)
+/// counts it.
+///
+/// Defers to [`UnitData::supply_cost`](rts_core::data::UnitData::supply_cost) so the
+/// client and the headless AI charge the same supply.
+pub fn unit_supply(unit_data: &rts_core::data::UnitData) -> i32 {
+    unit_data.supply_cost() as i32
+}
@@ -4,6 +4,7 @@
 
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
+use rts_core::outcome::WinCondition;
 
 use crate::components::PlayerFaction;
 use crate::simulation::CoreSimulation;
@@ -47,6 +48,8 @@ pub struct MatchStats {
     pub buildings_built: i32,
     /// Match duration in seconds.
     pub match_duration: f32,
+    /// How the match ended, once it has.
+    pub win_condition: Option<WinCondition>,
 }
 
 /// Checks if victory or defeat conditions have been met.
@@ -69,6 +72,7 @@ pub fn check_victory_conditions(
     stats.match_duration += time.delta_seconds();
 
     if let Some(winner) = core.last_events.game_end {
+        stats.win_condition = core.last_events.win_condition;
         if winner == player_faction.faction {
             *game_state = GameState::Victory;
             tracing::info!("VICTORY - Winning faction {:?}", winner);
@@ -83,6 +87,7 @@ pub fn check_victory_conditions(
 fn victory_ui(
     game_state: Res<GameState>,
     stats: Res<MatchStats>,
+    player_faction: Res<PlayerFaction>,
     mut egui_contexts: EguiContexts,
     mut commands: Commands,
     keyboard: Res<ButtonInput<KeyCode>>,
@@ -113,6 +118,15 @@ fn victory_ui(
                 // Title
                 ui.label(egui::RichText::new(title).size(72.0).color(color).strong());
 
+                if let Some(condition) = stats.win_condition {
+                    ui.add_space(10.0);
+                    ui.label(
+                        egui::RichText::new(condition.reason_for(player_faction.faction))
+                            .size(20.0)
+                            .color(egui::Color32::LIGHT_GRAY),
+                    );
+                }
+
                 ui.add_space(40.0);
 
                 // Match statistics
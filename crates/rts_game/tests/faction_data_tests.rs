@@ -6,7 +6,7 @@
 use bevy::prelude::*;
 use rts_core::factions::FactionId;
 use rts_game::bundles::UnitBundle;
-use rts_game::components::{GameHealth, UnitType};
+use rts_game::components::{BuildingType, GameHealth, UnitType};
 use rts_game::data_loader::{load_factions_from_directory, FactionRegistry};
 use std::path::Path;
 
@@ -109,6 +109,33 @@ fn test_unit_type_to_unit_id_mapping() {
     );
 }
 
+#[test]
+fn test_building_type_to_building_id_exists_in_data() {
+    let registry = load_test_registry();
+
+    for faction in FactionId::ALL {
+        let data = registry.get(faction).expect("faction loaded");
+        for building_type in [
+            BuildingType::Depot,
+            BuildingType::Barracks,
+            BuildingType::SupplyDepot,
+            BuildingType::TechLab,
+            BuildingType::Turret,
+        ] {
+            let id = building_type.to_building_id(faction);
+            let building = data
+                .get_building(id)
+                .unwrap_or_else(|| panic!("{faction:?} has no building '{id}'"));
+            if building_type.can_produce_units() {
+                assert!(
+                    !building.produces.is_empty(),
+                    "{faction:?} {id} should produce units for the command card"
+                );
+            }
+        }
+    }
+}
+
 // ==========================================================================
 // RON Data Correctness Tests
 // ==========================================================================
@@ -261,3 +288,21 @@ fn test_all_factions_have_starting_configuration() {
         );
     }
 }
+
+#[test]
+fn test_all_factions_define_standard_starting_presets() {
+    let registry = load_test_registry();
+
+    for faction_id in registry.faction_ids() {
+        let faction_data = registry.get(faction_id).unwrap();
+
+        for preset in ["standard", "fast-expand"] {
+            assert!(
+                faction_data.get_starting_preset(preset).is_some(),
+                "Faction {:?} should define the '{}' starting preset",
+                faction_id,
+                preset
+            );
+        }
+    }
+}
@@ -4,6 +4,7 @@
 
 use bevy::prelude::*;
 use rts_core::factions::FactionId;
+use rts_core::outcome::WinCondition;
 
 use rts_game::components::PlayerFaction;
 use rts_game::simulation::CoreSimulation;
@@ -55,6 +56,29 @@ fn defeat_when_core_reports_enemy_winner() {
     assert_eq!(*state, GameState::Defeat);
 }
 
+#[test]
+fn end_screen_reason_comes_from_core_condition() {
+    let mut app = setup_app(FactionId::Continuity);
+    let condition = WinCondition::Elimination {
+        eliminating_player: FactionId::Collegium,
+        surviving_structures: 3,
+    };
+    {
+        let mut core = app.world_mut().resource_mut::<CoreSimulation>();
+        core.last_events.game_end = condition.winner();
+        core.last_events.win_condition = Some(condition);
+    }
+
+    app.update();
+
+    let stats = app.world().resource::<MatchStats>();
+    assert_eq!(stats.win_condition, Some(condition));
+    assert_eq!(
+        condition.reason_for(FactionId::Continuity),
+        "Your last depot fell to The Collegium"
+    );
+}
+
 #[test]
 fn no_result_when_no_winner_reported() {
     let mut app = setup_app(FactionId::Continuity);
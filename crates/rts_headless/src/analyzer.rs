@@ -4,9 +4,22 @@
 //! generate suggestions for tuning.
 
 use crate::batch::BatchResults;
-use crate::metrics::GameMetrics;
+use crate::metrics::{GameMetrics, UnitKindMetrics};
+use crate::openings::{
+    extract_openings, game_time, summarize_openings, ArchetypeStats, Opening, OpeningArchetype,
+    COMPOSITION_MINUTES,
+};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+/// Entries in each top-N list of the markdown report
+pub const TOP_OFFENDERS: usize = 5;
+
+/// Most characters in a markdown timeline sparkline
+const SPARKLINE_WIDTH: usize = 40;
+
+/// Line colors for factions in HTML charts, in faction order
+const CHART_COLORS: [&str; 5] = ["#1f77b4", "#d4a017", "#d62728", "#2ca02c", "#9467bd"];
 
 /// Severity of a balance issue
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -131,19 +144,90 @@ impl BalanceSuggestion {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct BalanceAnalysis {
     /// Win rates by faction
-    pub win_rates: HashMap<String, f64>,
+    pub win_rates: BTreeMap<String, f64>,
     /// Matchup matrix (faction_a vs faction_b -> win rate for a)
     pub matchup_matrix: HashMap<(String, String), f64>,
     /// Detected outliers
     pub outliers: Vec<BalanceOutlier>,
     /// Generated suggestions
     pub suggestions: Vec<BalanceSuggestion>,
+    /// Average effective HP multiplier from regeneration by faction
+    #[serde(default)]
+    pub effective_hp_multiplier: BTreeMap<String, f64>,
+    /// Average share of harvest income withheld by army upkeep by faction
+    #[serde(default)]
+    pub upkeep_income_share: BTreeMap<String, f64>,
+    /// Average purchases held back per game for lack of a rare resource, by
+    /// faction then resource
+    #[serde(default)]
+    pub resource_shortfalls: BTreeMap<String, BTreeMap<String, f64>>,
+    /// Cost-effectiveness of each unit kind, by faction then kind
+    #[serde(default)]
+    pub unit_effectiveness: Vec<UnitEffectiveness>,
+    /// Unit-vs-unit matrix: damage the row unit dealt the column unit,
+    /// divided by the damage it took from it. Units are `faction/kind`.
+    #[serde(default)]
+    pub unit_matchups: BTreeMap<String, BTreeMap<String, f64>>,
+    /// Sampled time series by faction, averaged across games
+    #[serde(default)]
+    pub timelines: BTreeMap<String, FactionTimeline>,
+    /// Win rate and timings of each opening archetype played
+    #[serde(default)]
+    pub openings: BTreeMap<OpeningArchetype, ArchetypeStats>,
     /// Games analyzed
     pub games_analyzed: u32,
     /// Analysis metadata
     pub metadata: AnalysisMetadata,
 }
 
+/// How one faction's unit kind performed across a batch
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct UnitEffectiveness {
+    /// Faction fielding the unit
+    pub faction: String,
+    /// Unit kind
+    pub kind: String,
+    /// Units fielded across all games
+    pub fielded: u32,
+    /// Damage dealt per resource spent (None when every unit was free)
+    pub damage_per_resource: Option<f64>,
+    /// Average ticks a unit stayed alive
+    pub avg_survival_ticks: f64,
+    /// Kills per death against each opposing unit (`faction/kind`);
+    /// with no deaths this is the kill count
+    pub kd_by_opponent: BTreeMap<String, f64>,
+}
+
+impl UnitEffectiveness {
+    /// `faction/kind` label used in the matrices
+    pub fn label(&self) -> String {
+        unit_label(&self.faction, &self.kind)
+    }
+}
+
+/// One faction's time series averaged across a batch: at each sampled
+/// tick, the mean over the games still running then
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct FactionTimeline {
+    /// Sampled ticks
+    pub ticks: Vec<u64>,
+    /// Army value at each tick
+    pub army_value: Vec<f64>,
+    /// Resources gathered per minute
+    pub income_per_minute: Vec<f64>,
+    /// Share of map regions held, in percent
+    pub map_control: Vec<f64>,
+    /// Living units in tiers 1, 2 and 3
+    pub units_by_tier: Vec<[f64; 3]>,
+}
+
+impl FactionTimeline {
+    /// Living units of every tier at each tick
+    pub fn total_units(&self) -> Vec<f64> {
+        self.units_by_tier.iter().map(|t| t.iter().sum()).collect()
+    }
+}
+
 /// Metadata about the analysis
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AnalysisMetadata {
@@ -177,7 +261,7 @@ impl BalanceAnalysis {
     /// Get outliers sorted by severity
     pub fn outliers_by_severity(&self) -> Vec<&BalanceOutlier> {
         let mut sorted: Vec<_> = self.outliers.iter().collect();
-        sorted.sort_by(|a, b| b.severity.priority().cmp(&a.severity.priority()));
+        sorted.sort_by_key(|o| std::cmp::Reverse(o.severity.priority()));
         sorted
     }
 
@@ -214,6 +298,55 @@ impl BalanceAnalysis {
             md.push_str(&format!("| {} | {:.1}% |\n", faction, rate * 100.0));
         }
 
+        if !self.unit_effectiveness.is_empty() {
+            self.push_unit_tables(&mut md);
+        }
+
+        if !self.openings.is_empty() {
+            md.push_str("\n## Openings\n\n");
+            md.push_str(&format!(
+                "| Opening | Games | Win Rate | Production | Expansion | First Attack | Units by {} min |\n",
+                minutes_label()
+            ));
+            md.push_str("|---------|-------|----------|------------|-----------|--------------|------------------|\n");
+            for (archetype, stats) in &self.openings {
+                md.push_str(&format!(
+                    "| {} | {} | {:.1}% | {} | {} | {} | {} |\n",
+                    archetype,
+                    stats.games,
+                    stats.win_rate() * 100.0,
+                    game_time(stats.avg_first_production_tick),
+                    game_time(stats.avg_first_expansion_tick),
+                    game_time(stats.avg_first_attack_tick),
+                    units_by_minute(stats)
+                ));
+            }
+        }
+
+        if !self.timelines.is_empty() {
+            md.push_str("\n## Timelines\n\n");
+            md.push_str(
+                "| Faction | Army Value | Income / min | Map Control | Units (T1/T2/T3 at end) |\n",
+            );
+            md.push_str(
+                "|---------|------------|--------------|-------------|-------------------------|\n",
+            );
+            for (faction, timeline) in &self.timelines {
+                let tiers = timeline.units_by_tier.last().map_or("-".to_string(), |t| {
+                    format!("{:.0}/{:.0}/{:.0}", t[0], t[1], t[2])
+                });
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} | {} {} |\n",
+                    faction,
+                    sparkline_cell(&timeline.army_value, ""),
+                    sparkline_cell(&timeline.income_per_minute, ""),
+                    sparkline_cell(&timeline.map_control, "%"),
+                    sparkline(&timeline.total_units()),
+                    tiers
+                ));
+            }
+        }
+
         if !self.outliers.is_empty() {
             md.push_str("\n## Issues Detected\n\n");
             for outlier in self.outliers_by_severity() {
@@ -257,30 +390,497 @@ impl BalanceAnalysis {
         ));
         md
     }
+
+    /// Generate an HTML report, with the timelines drawn as line charts
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n");
+        html.push_str("<title>Balance Analysis Report</title>\n");
+        html.push_str("<style>\n");
+        html.push_str("body { font-family: sans-serif; margin: 20px; }\n");
+        html.push_str("table { border-collapse: collapse; }\n");
+        html.push_str("th, td { border: 1px solid #ddd; padding: 6px 10px; text-align: left; }\n");
+        html.push_str("th { background-color: #4CAF50; color: white; }\n");
+        html.push_str("svg { display: block; margin-bottom: 16px; }\n");
+        html.push_str("</style>\n</head>\n<body>\n");
+        html.push_str("<h1>Balance Analysis Report</h1>\n");
+
+        html.push_str("<h2>Win Rates</h2>\n<table>\n<tr><th>Faction</th><th>Win Rate</th></tr>\n");
+        for (faction, rate) in &self.win_rates {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{:.1}%</td></tr>\n",
+                faction,
+                rate * 100.0
+            ));
+        }
+        html.push_str("</table>\n");
+
+        if !self.openings.is_empty() {
+            html.push_str("<h2>Openings</h2>\n<table>\n<tr><th>Opening</th><th>Games</th>");
+            html.push_str("<th>Win Rate</th><th>Production</th><th>Expansion</th>");
+            html.push_str(&format!(
+                "<th>First Attack</th><th>Units by {} min</th></tr>\n",
+                minutes_label()
+            ));
+            for (archetype, stats) in &self.openings {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{:.1}%</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    archetype,
+                    stats.games,
+                    stats.win_rate() * 100.0,
+                    game_time(stats.avg_first_production_tick),
+                    game_time(stats.avg_first_expansion_tick),
+                    game_time(stats.avg_first_attack_tick),
+                    units_by_minute(stats)
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+
+        if !self.timelines.is_empty() {
+            html.push_str("<h2>Timelines</h2>\n");
+            let charts: [(&str, fn(&FactionTimeline) -> Vec<f64>); 4] = [
+                ("Army Value", |t| t.army_value.clone()),
+                ("Income / min", |t| t.income_per_minute.clone()),
+                ("Map Control (%)", |t| t.map_control.clone()),
+                ("Units", FactionTimeline::total_units),
+            ];
+            for (title, values) in charts {
+                let series: Vec<(&str, &[u64], Vec<f64>)> = self
+                    .timelines
+                    .iter()
+                    .map(|(faction, t)| (faction.as_str(), t.ticks.as_slice(), values(t)))
+                    .collect();
+                html.push_str(&svg_chart(title, &series));
+            }
+        }
+
+        if !self.outliers.is_empty() {
+            html.push_str("<h2>Issues Detected</h2>\n<ul>\n");
+            for outlier in self.outliers_by_severity() {
+                html.push_str(&format!(
+                    "<li><strong>[{:?}]</strong> {}/{}: {:.2} (expected {:.2}-{:.2}) {}</li>\n",
+                    outlier.severity,
+                    outlier.category,
+                    outlier.metric,
+                    outlier.value,
+                    outlier.expected_range.0,
+                    outlier.expected_range.1,
+                    outlier.context
+                ));
+            }
+            html.push_str("</ul>\n");
+        }
+
+        if !self.suggestions.is_empty() {
+            html.push_str("<h2>Suggestions</h2>\n<ol>\n");
+            for suggestion in self.suggestions_by_confidence() {
+                html.push_str(&format!(
+                    "<li><strong>{}</strong>: {} &rarr; {} ({:+.1}%, {:.0}% confidence) {}</li>\n",
+                    suggestion.target,
+                    suggestion.current,
+                    suggestion.suggested,
+                    suggestion.percent_change(),
+                    suggestion.confidence * 100.0,
+                    suggestion.reasoning
+                ));
+            }
+            html.push_str("</ol>\n");
+        }
+
+        html.push_str(&format!(
+            "<p><em>Analyzed {} games</em></p>\n</body>\n</html>",
+            self.games_analyzed
+        ));
+        html
+    }
+
+    /// Unit cost-effectiveness, K/D and matchup tables with their top-N lists
+    fn push_unit_tables(&self, md: &mut String) {
+        md.push_str("\n## Unit Cost-Effectiveness\n\n");
+        md.push_str("| Unit | Fielded | Damage / Resource | Avg Survival (ticks) |\n");
+        md.push_str("|------|---------|-------------------|----------------------|\n");
+        for unit in &self.unit_effectiveness {
+            let per_resource = unit
+                .damage_per_resource
+                .map_or("-".to_string(), |d| format!("{:.2}", d));
+            md.push_str(&format!(
+                "| {} | {} | {} | {:.0} |\n",
+                unit.label(),
+                unit.fielded,
+                per_resource,
+                unit.avg_survival_ticks
+            ));
+        }
+
+        let mut costed: Vec<_> = self
+            .unit_effectiveness
+            .iter()
+            .filter_map(|u| u.damage_per_resource.map(|d| (u.label(), d)))
+            .collect();
+        costed.sort_by(|a, b| b.1.total_cmp(&a.1));
+        // Split short lists so no unit is both best and worst
+        let most = TOP_OFFENDERS.min(costed.len().div_ceil(2));
+        let least = TOP_OFFENDERS.min(costed.len() - most);
+        if most > 0 {
+            md.push_str("\n### Most Cost-Effective\n\n");
+            for (i, (label, d)) in costed.iter().take(most).enumerate() {
+                md.push_str(&format!(
+                    "{}. **{}**: {:.2} damage per resource\n",
+                    i + 1,
+                    label,
+                    d
+                ));
+            }
+        }
+        if least > 0 {
+            md.push_str("\n### Least Cost-Effective\n\n");
+            for (i, (label, d)) in costed.iter().rev().take(least).enumerate() {
+                md.push_str(&format!(
+                    "{}. **{}**: {:.2} damage per resource\n",
+                    i + 1,
+                    label,
+                    d
+                ));
+            }
+        }
+
+        let kd: BTreeMap<String, BTreeMap<String, f64>> = self
+            .unit_effectiveness
+            .iter()
+            .filter(|u| !u.kd_by_opponent.is_empty())
+            .map(|u| (u.label(), u.kd_by_opponent.clone()))
+            .collect();
+        if !kd.is_empty() {
+            md.push_str("\n## Unit K/D by Opponent\n\n");
+            push_matrix(md, &kd);
+        }
+
+        if !self.unit_matchups.is_empty() {
+            md.push_str("\n## Unit Matchup Matrix\n\n");
+            md.push_str("Damage dealt per damage taken, row unit against column unit.\n\n");
+            push_matrix(md, &self.unit_matchups);
+
+            let mut lopsided: Vec<_> = self
+                .unit_matchups
+                .iter()
+                .flat_map(|(row, cols)| cols.iter().map(move |(col, v)| (row, col, *v)))
+                // Only fights both sides took part in; farming workers is no imbalance
+                .filter(|(row, col, v)| {
+                    *v > 1.0
+                        && self
+                            .unit_matchups
+                            .get(*col)
+                            .and_then(|cols| cols.get(*row))
+                            .is_some_and(|back| *back > 0.0)
+                })
+                .collect();
+            lopsided.sort_by(|a, b| b.2.total_cmp(&a.2));
+            if !lopsided.is_empty() {
+                md.push_str("\n### Most Lopsided Matchups\n\n");
+                for (i, (row, col, v)) in lopsided.iter().take(TOP_OFFENDERS).enumerate() {
+                    md.push_str(&format!(
+                        "{}. **{}** vs **{}**: deals {:.1}x the damage it takes\n",
+                        i + 1,
+                        row,
+                        col,
+                        v
+                    ));
+                }
+            }
+        }
+    }
 }
 
-/// Analyze batch results and generate balance report
-pub fn analyze_batch(results: &BatchResults) -> BalanceAnalysis {
-    let mut analysis = BalanceAnalysis::new();
-    analysis.games_analyzed = results.games.len() as u32;
-    analysis.metadata.source_batch = results.config.scenario.clone();
+/// Markdown table of a row → column → value matrix; missing cells are `-`
+fn push_matrix(md: &mut String, matrix: &BTreeMap<String, BTreeMap<String, f64>>) {
+    let columns: std::collections::BTreeSet<&String> =
+        matrix.values().flat_map(|cols| cols.keys()).collect();
+    md.push_str("| |");
+    for col in &columns {
+        md.push_str(&format!(" {} |", col));
+    }
+    md.push_str("\n|---|");
+    md.push_str(&"---|".repeat(columns.len()));
+    md.push('\n');
+    for (row, cols) in matrix {
+        md.push_str(&format!("| {} |", row));
+        for col in &columns {
+            match cols.get(*col) {
+                Some(v) => md.push_str(&format!(" {:.2} |", v)),
+                None => md.push_str(" - |"),
+            }
+        }
+        md.push('\n');
+    }
+}
 
-    // Calculate win rates
-    let mut wins: HashMap<String, u32> = HashMap::new();
-    let mut total_decided = 0u32;
+/// `3/5/8`: the minutes openings record composition at
+fn minutes_label() -> String {
+    COMPOSITION_MINUTES
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Average units an opening had produced by each composition minute
+fn units_by_minute(stats: &ArchetypeStats) -> String {
+    stats
+        .avg_units_by_minute
+        .iter()
+        .map(|units| units.map_or("-".to_string(), |u| format!("{:.1}", u)))
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+/// Sparkline of `values`, averaged down to at most [`SPARKLINE_WIDTH`]
+/// characters
+fn sparkline(values: &[f64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    if values.is_empty() {
+        return "-".to_string();
+    }
+    let buckets = values.len().min(SPARKLINE_WIDTH);
+    let points: Vec<f64> = (0..buckets)
+        .map(|i| {
+            let bucket = &values[i * values.len() / buckets..(i + 1) * values.len() / buckets];
+            bucket.iter().sum::<f64>() / bucket.len() as f64
+        })
+        .collect();
+    let (min, max) = points
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        });
+    points
+        .iter()
+        .map(|&v| {
+            let level = if max > min {
+                ((v - min) / (max - min) * 7.0).round() as usize
+            } else {
+                0
+            };
+            BARS[level.min(7)]
+        })
+        .collect()
+}
+
+/// Markdown table cell: a sparkline and the series' peak
+fn sparkline_cell(values: &[f64], unit: &str) -> String {
+    if values.is_empty() {
+        return "-".to_string();
+    }
+    let peak = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    format!("{} (peak {:.0}{})", sparkline(values), peak, unit)
+}
+
+/// SVG line chart with one line per (label, ticks, values) series
+fn svg_chart(title: &str, series: &[(&str, &[u64], Vec<f64>)]) -> String {
+    const WIDTH: f64 = 640.0;
+    const HEIGHT: f64 = 180.0;
+    const MARGIN: f64 = 40.0;
+
+    let max_tick = series
+        .iter()
+        .flat_map(|(_, ticks, _)| ticks.iter())
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+    let max_value = series
+        .iter()
+        .flat_map(|(_, _, values)| values.iter())
+        .copied()
+        .fold(0.0, f64::max)
+        .max(1.0);
+    let x = |tick: u64| MARGIN + tick as f64 / max_tick * (WIDTH - 2.0 * MARGIN);
+    let y = |value: f64| HEIGHT - MARGIN - value / max_value * (HEIGHT - 2.0 * MARGIN);
+
+    let mut svg = format!("<h3>{}</h3>\n", title);
+    svg.push_str(&format!(
+        "<svg width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+        WIDTH, HEIGHT
+    ));
+    svg.push_str(&format!(
+        "<polyline points=\"{0},{1} {0},{2} {3},{2}\" fill=\"none\" stroke=\"#999\"/>\n",
+        MARGIN,
+        MARGIN,
+        HEIGHT - MARGIN,
+        WIDTH - MARGIN
+    ));
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"end\">{:.0}</text>\n",
+        MARGIN - 4.0,
+        MARGIN + 4.0,
+        max_value
+    ));
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"end\">tick {}</text>\n",
+        WIDTH - MARGIN,
+        HEIGHT - MARGIN + 14.0,
+        max_tick
+    ));
+    for (i, (label, ticks, values)) in series.iter().enumerate() {
+        let color = CHART_COLORS[i % CHART_COLORS.len()];
+        let points: Vec<String> = ticks
+            .iter()
+            .zip(values)
+            .map(|(&tick, &value)| format!("{:.1},{:.1}", x(tick), y(value)))
+            .collect();
+        svg.push_str(&format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+            points.join(" "),
+            color
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" font-size=\"11\" fill=\"{}\">{}</text>\n",
+            MARGIN + 8.0 + 110.0 * i as f64,
+            MARGIN - 12.0,
+            color,
+            label
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// `faction/kind` label for a unit kind
+fn unit_label(faction: &str, kind: &str) -> String {
+    format!("{}/{}", faction, kind)
+}
+
+/// Sums at one tick of a faction's timeline, and the games they came from
+#[derive(Default)]
+struct TickTotals {
+    games: u32,
+    army_value: f64,
+    income: f64,
+    map_control: f64,
+    units_by_tier: [f64; 3],
+}
 
-    for metrics in &results.games {
-        if let Some(ref winner) = metrics.winner {
-            *wins.entry(winner.clone()).or_insert(0) += 1;
-            total_decided += 1;
+/// Running totals over a batch's games. Games are added one at a time, so
+/// a batch streamed to disk never has to be held in memory whole.
+#[derive(Default)]
+struct GameTotals {
+    games: u32,
+    /// Wins by faction
+    wins: BTreeMap<String, u32>,
+    /// Games that had a winner
+    decided: u32,
+    duration_sum: f64,
+    early_games: u32,
+    late_games: u32,
+    /// Faction -> (sum, samples) of the effective HP multiplier
+    regeneration: BTreeMap<String, (f64, u32)>,
+    /// Faction -> (sum, samples) of upkeep's share of income
+    upkeep: BTreeMap<String, (f64, u32)>,
+    /// Faction -> resource -> (shortfalls, gathered)
+    shortfalls: BTreeMap<String, BTreeMap<String, (u32, i64)>>,
+    /// (faction, kind) -> merged unit stats
+    units: BTreeMap<(String, String), UnitKindMetrics>,
+    /// Unit label -> opposing unit label -> (damage dealt, kills)
+    versus: BTreeMap<String, BTreeMap<String, (i64, u32)>>,
+    /// Faction -> tick -> sums
+    timelines: BTreeMap<String, BTreeMap<u64, TickTotals>>,
+    openings: Vec<Opening>,
+}
+
+impl GameTotals {
+    fn add(&mut self, game: &GameMetrics) {
+        self.games += 1;
+        if let Some(winner) = &game.winner {
+            *self.wins.entry(winner.clone()).or_default() += 1;
+            self.decided += 1;
+        }
+        self.duration_sum += game.duration_ticks as f64;
+        if game.duration_ticks < 10000 {
+            self.early_games += 1;
         }
+        if game.duration_ticks >= 35000 {
+            self.late_games += 1;
+        }
+
+        for faction in game.factions.values() {
+            let id = &faction.faction_id;
+            if faction.total_damage_taken > 0 {
+                let entry = self.regeneration.entry(id.clone()).or_default();
+                entry.0 += faction.effective_hp_multiplier();
+                entry.1 += 1;
+            }
+            if faction.upkeep_ticks_taxed > 0 || faction.upkeep_reserve.is_some() {
+                let entry = self.upkeep.entry(id.clone()).or_default();
+                entry.0 += faction.upkeep_income_share();
+                entry.1 += 1;
+            }
+
+            let resources = self.shortfalls.entry(id.clone()).or_default();
+            for (resource, count) in &faction.resource_shortfalls {
+                resources.entry(resource.clone()).or_default().0 += count;
+            }
+            for (resource, gathered) in &faction.rare_resources_gathered {
+                resources.entry(resource.clone()).or_default().1 += gathered;
+            }
+
+            let opponent = game.factions.keys().find(|f| *f != id);
+            for (kind, stats) in &faction.unit_stats {
+                self.units
+                    .entry((id.clone(), kind.clone()))
+                    .or_default()
+                    .merge(stats);
+                let Some(opponent) = opponent else {
+                    continue;
+                };
+                let row = self.versus.entry(unit_label(id, kind)).or_default();
+                for (victim, damage) in &stats.damage_to {
+                    row.entry(unit_label(opponent, victim)).or_default().0 += damage;
+                }
+                for (victim, kills) in &stats.kills {
+                    row.entry(unit_label(opponent, victim)).or_default().1 += kills;
+                }
+            }
+
+            let control: HashMap<u64, f64> =
+                faction.map_control_over_time.iter().copied().collect();
+            let by_tick = self.timelines.entry(id.clone()).or_default();
+            for sample in &faction.time_series {
+                let entry = by_tick.entry(sample.tick).or_default();
+                entry.games += 1;
+                entry.army_value += sample.army_value as f64;
+                entry.income += sample.income_per_minute as f64;
+                entry.map_control += control.get(&sample.tick).copied().unwrap_or(0.0);
+                for (sum, count) in entry.units_by_tier.iter_mut().zip(sample.units_by_tier) {
+                    *sum += f64::from(count);
+                }
+            }
+        }
+
+        self.openings.extend(extract_openings(game));
     }
+}
 
-    if total_decided > 0 {
-        for (faction, count) in &wins {
-            analysis
-                .win_rates
-                .insert(faction.clone(), *count as f64 / total_decided as f64);
+/// Analyze batch results and generate balance report.
+///
+/// Games streamed to disk are read back one at a time rather than loaded
+/// together.
+pub fn analyze_batch(results: &BatchResults) -> BalanceAnalysis {
+    let mut totals = GameTotals::default();
+    for game in results.all_games() {
+        totals.add(&game);
+    }
+
+    let mut analysis = BalanceAnalysis::new();
+    analysis.games_analyzed = totals.games;
+    analysis.metadata.source_batch = results.config.scenario.clone();
+
+    // Calculate win rates
+    if totals.decided > 0 {
+        for (faction, count) in &totals.wins {
+            analysis.win_rates.insert(
+                faction.clone(),
+                f64::from(*count) / f64::from(totals.decided),
+            );
         }
     }
 
@@ -310,7 +910,25 @@ pub fn analyze_batch(results: &BatchResults) -> BalanceAnalysis {
     }
 
     // Analyze game durations
-    analyze_timing(&mut analysis, &results.games);
+    analyze_timing(&mut analysis, &totals);
+
+    // Analyze how much regeneration stretches each faction's health
+    analyze_regeneration(&mut analysis, &totals);
+
+    // Analyze how much army upkeep costs each faction
+    analyze_upkeep(&mut analysis, &totals);
+
+    // Analyze purchases stalled on resources beyond feedstock
+    analyze_resource_shortfalls(&mut analysis, &totals);
+
+    // Analyze what each unit kind does for its cost and against whom
+    analyze_units(&mut analysis, &totals);
+
+    // Average each faction's sampled time series
+    analyze_timelines(&mut analysis, &totals);
+
+    // Classify each game's openings and how often each kind wins
+    analyze_openings(&mut analysis, &totals);
 
     // Generate suggestions based on outliers
     generate_suggestions(&mut analysis, results);
@@ -318,18 +936,74 @@ pub fn analyze_batch(results: &BatchResults) -> BalanceAnalysis {
     analysis
 }
 
+/// Openings an archetype needs before its win rate is judged
+const MIN_OPENING_SAMPLES: u32 = 10;
+
+/// Summarize openings by archetype, flagging ones that win far too often
+/// or too rarely
+fn analyze_openings(analysis: &mut BalanceAnalysis, totals: &GameTotals) {
+    analysis.openings = summarize_openings(&totals.openings);
+
+    for (archetype, stats) in &analysis.openings {
+        if stats.games < MIN_OPENING_SAMPLES {
+            continue;
+        }
+        let rate = stats.win_rate();
+        let direction = if rate > 0.65 {
+            "wins too often"
+        } else if rate < 0.35 {
+            "rarely wins"
+        } else {
+            continue;
+        };
+        analysis.outliers.push(
+            BalanceOutlier::new(
+                "opening",
+                archetype.name(),
+                rate,
+                (0.35, 0.65),
+                Severity::Medium,
+            )
+            .with_context(&format!(
+                "The {} opening {} ({} of {} games)",
+                archetype, direction, stats.wins, stats.games
+            )),
+        );
+    }
+}
+
+/// Average each faction's time series across games, tick by tick
+fn analyze_timelines(analysis: &mut BalanceAnalysis, totals: &GameTotals) {
+    for (faction, by_tick) in &totals.timelines {
+        if by_tick.is_empty() {
+            continue;
+        }
+        let mut timeline = FactionTimeline::default();
+        for (&tick, t) in by_tick {
+            let n = f64::from(t.games);
+            timeline.ticks.push(tick);
+            timeline.army_value.push(t.army_value / n);
+            timeline.income_per_minute.push(t.income / n);
+            timeline.map_control.push(t.map_control / n);
+            timeline
+                .units_by_tier
+                .push(t.units_by_tier.map(|sum| sum / n));
+        }
+        analysis.timelines.insert(faction.clone(), timeline);
+    }
+}
+
 /// Analyze timing patterns
-fn analyze_timing(analysis: &mut BalanceAnalysis, games: &[GameMetrics]) {
-    if games.is_empty() {
+fn analyze_timing(analysis: &mut BalanceAnalysis, totals: &GameTotals) {
+    if totals.games == 0 {
         return;
     }
+    let games = f64::from(totals.games);
 
-    let avg_duration: f64 =
-        games.iter().map(|g| g.duration_ticks as f64).sum::<f64>() / games.len() as f64;
+    let avg_duration = totals.duration_sum / games;
 
     // Check for games ending too quickly (rushes too strong)
-    let early_games = games.iter().filter(|g| g.duration_ticks < 10000).count();
-    let early_rate = early_games as f64 / games.len() as f64;
+    let early_rate = f64::from(totals.early_games) / games;
 
     if early_rate > 0.3 {
         analysis.outliers.push(
@@ -345,8 +1019,7 @@ fn analyze_timing(analysis: &mut BalanceAnalysis, games: &[GameMetrics]) {
     }
 
     // Check for games running to time limit
-    let late_games = games.iter().filter(|g| g.duration_ticks >= 35000).count();
-    let late_rate = late_games as f64 / games.len() as f64;
+    let late_rate = f64::from(totals.late_games) / games;
 
     if late_rate > 0.2 {
         analysis.outliers.push(
@@ -376,6 +1049,152 @@ fn analyze_timing(analysis: &mut BalanceAnalysis, games: &[GameMetrics]) {
     }
 }
 
+/// Analyze effective HP gained from out-of-combat regeneration
+fn analyze_regeneration(analysis: &mut BalanceAnalysis, totals: &GameTotals) {
+    for (faction, &(sum, count)) in &totals.regeneration {
+        let multiplier = sum / f64::from(count);
+        analysis
+            .effective_hp_multiplier
+            .insert(faction.clone(), multiplier);
+
+        let severity = if multiplier > 1.5 {
+            Severity::High
+        } else if multiplier > 1.25 {
+            Severity::Medium
+        } else {
+            continue;
+        };
+        analysis.outliers.push(
+            BalanceOutlier::new("regen", faction, multiplier, (1.0, 1.25), severity).with_context(
+                &format!(
+                    "{} regenerates {:.0}% of the damage it takes",
+                    faction,
+                    (1.0 - 1.0 / multiplier) * 100.0
+                ),
+            ),
+        );
+    }
+}
+
+/// Analyze the share of income army upkeep withholds
+fn analyze_upkeep(analysis: &mut BalanceAnalysis, totals: &GameTotals) {
+    for (faction, &(sum, count)) in &totals.upkeep {
+        let share = sum / f64::from(count);
+        analysis.upkeep_income_share.insert(faction.clone(), share);
+
+        let severity = if share > 0.4 {
+            Severity::High
+        } else if share > 0.25 {
+            Severity::Medium
+        } else {
+            continue;
+        };
+        analysis.outliers.push(
+            BalanceOutlier::new("upkeep", faction, share, (0.0, 0.25), severity).with_context(
+                &format!(
+                    "Upkeep withholds {:.0}% of {}'s harvest income",
+                    share * 100.0,
+                    faction
+                ),
+            ),
+        );
+    }
+}
+
+/// Analyze purchases held back for lack of rare resources. A faction that
+/// keeps needing a resource it never gathers has a roster the maps cannot
+/// pay for
+fn analyze_resource_shortfalls(analysis: &mut BalanceAnalysis, totals: &GameTotals) {
+    let games = f64::from(totals.games.max(1));
+    for (faction, resources) in &totals.shortfalls {
+        for (resource, &(shortfalls, gathered)) in resources {
+            if shortfalls == 0 {
+                continue;
+            }
+            let per_game = f64::from(shortfalls) / games;
+            analysis
+                .resource_shortfalls
+                .entry(faction.clone())
+                .or_default()
+                .insert(resource.clone(), per_game);
+            if gathered > 0 {
+                continue;
+            }
+            analysis.outliers.push(
+                BalanceOutlier::new(
+                    "resource_shortfall",
+                    faction,
+                    per_game,
+                    (0.0, 0.0),
+                    Severity::High,
+                )
+                .with_context(&format!(
+                    "{} kept needing {} but never gathered any",
+                    faction, resource
+                )),
+            );
+        }
+    }
+}
+
+/// Analyze per-unit-kind cost-effectiveness, survival and matchups
+fn analyze_units(analysis: &mut BalanceAnalysis, totals: &GameTotals) {
+    let versus = &totals.versus;
+    let against = |row: &str, col: &str| {
+        versus
+            .get(row)
+            .and_then(|cols| cols.get(col))
+            .copied()
+            .unwrap_or_default()
+    };
+    for (row, cols) in versus {
+        for (col, (dealt, _)) in cols {
+            let (taken, _) = against(col, row);
+            if *dealt > 0 || taken > 0 {
+                analysis
+                    .unit_matchups
+                    .entry(row.clone())
+                    .or_default()
+                    .insert(col.clone(), *dealt as f64 / taken.max(1) as f64);
+            }
+        }
+    }
+
+    for ((faction, kind), stats) in &totals.units {
+        let label = unit_label(faction, kind);
+        let mut kd_by_opponent = BTreeMap::new();
+        let opponents = versus.get(&label).into_iter().flat_map(|cols| cols.keys());
+        let attackers = versus
+            .iter()
+            .filter(|(_, cols)| cols.contains_key(&label))
+            .map(|(row, _)| row);
+        for opponent in opponents.chain(attackers) {
+            let (_, kills) = against(&label, opponent);
+            let (_, deaths) = against(opponent, &label);
+            if kills > 0 || deaths > 0 {
+                kd_by_opponent.insert(
+                    opponent.clone(),
+                    f64::from(kills) / f64::from(deaths.max(1)),
+                );
+            }
+        }
+
+        analysis.unit_effectiveness.push(UnitEffectiveness {
+            faction: faction.clone(),
+            kind: kind.clone(),
+            fielded: stats.fielded,
+            damage_per_resource: (stats.resources_spent > 0)
+                .then(|| stats.damage_dealt as f64 / stats.resources_spent as f64),
+            avg_survival_ticks: if stats.fielded > 0 {
+                stats.lifetime_ticks as f64 / f64::from(stats.fielded)
+            } else {
+                0.0
+            },
+            kd_by_opponent,
+        });
+    }
+}
+
 /// Generate balance suggestions from detected issues
 fn generate_suggestions(analysis: &mut BalanceAnalysis, _results: &BatchResults) {
     // Suggest fixes for win rate imbalances
@@ -419,6 +1238,23 @@ fn generate_suggestions(analysis: &mut BalanceAnalysis, _results: &BatchResults)
             }
         }
 
+        if outlier.category == "regen" && outlier.severity >= Severity::Medium {
+            analysis.suggestions.push(
+                BalanceSuggestion::new(
+                    &format!("{}.regen.amount", outlier.metric),
+                    100.0,
+                    80.0,
+                    &format!(
+                        "{} effectively has {:.2}x its health from regeneration. \
+                         Consider slowing regeneration by 20%.",
+                        outlier.metric, outlier.value
+                    ),
+                )
+                .with_confidence(0.5)
+                .with_outlier(&format!("{}/{}", outlier.category, outlier.metric)),
+            );
+        }
+
         if outlier.category == "timing" && outlier.metric == "early_game_rate" {
             analysis.suggestions.push(
                 BalanceSuggestion::new(
@@ -471,8 +1307,8 @@ pub fn compare_batches(before: &BatchResults, after: &BatchResults) -> Compariso
     let after_issues = after_analysis.outliers.len();
 
     ComparisonReport {
-        before_games: before.games.len() as u32,
-        after_games: after.games.len() as u32,
+        before_games: before_analysis.games_analyzed,
+        after_games: after_analysis.games_analyzed,
         improvements,
         regressions,
         before_issue_count: before_issues as u32,
@@ -528,6 +1364,8 @@ mod tests {
     fn test_analyze_batch() {
         use crate::batch::{BatchConfig, BatchResults};
         use crate::metrics::BatchSummary;
+        use rts_core::factions::FactionId;
+        use rts_core::outcome::WinCondition;
 
         let games: Vec<GameMetrics> = (0..100)
             .map(|i| GameMetrics {
@@ -536,19 +1374,26 @@ mod tests {
                 seed: i as u64,
                 duration_ticks: 20000,
                 winner: Some(if i < 55 { "faction_a" } else { "faction_b" }.to_string()),
-                win_condition: "elimination".to_string(),
+                win_condition: WinCondition::Elimination {
+                    eliminating_player: FactionId::Continuity,
+                    surviving_structures: 1,
+                },
                 factions: HashMap::new(),
                 events: Vec::new(),
                 final_state_hash: i as u64,
+                tuning: None,
+                observations: Default::default(),
             })
             .collect();
 
         let results = BatchResults {
             config: BatchConfig::default(),
             games,
+            games_file: None,
             summary: BatchSummary::default(),
             duration_seconds: 1.0,
             errors: Vec::new(),
+            interrupted: false,
         };
 
         let analysis = analyze_batch(&results);
@@ -562,6 +1407,241 @@ mod tests {
         assert!((a_rate - 0.55).abs() < 0.01);
     }
 
+    #[test]
+    fn test_regeneration_raises_effective_hp() {
+        use crate::batch::{BatchConfig, BatchResults};
+        use crate::metrics::{BatchSummary, FactionMetrics};
+
+        let faction = |id: &str, regenerated| FactionMetrics {
+            total_damage_taken: 1000,
+            total_health_regenerated: regenerated,
+            ..FactionMetrics::new(id)
+        };
+        let mut game = GameMetrics::new("game_0", "test", 0);
+        game.factions
+            .insert("continuity".to_string(), faction("continuity", 0));
+        game.factions
+            .insert("biosovereigns".to_string(), faction("biosovereigns", 500));
+        let results = BatchResults {
+            config: BatchConfig::default(),
+            games: vec![game],
+            games_file: None,
+            summary: BatchSummary::default(),
+            duration_seconds: 1.0,
+            errors: Vec::new(),
+            interrupted: false,
+        };
+
+        let analysis = analyze_batch(&results);
+
+        assert_eq!(analysis.effective_hp_multiplier["continuity"], 1.0);
+        assert_eq!(analysis.effective_hp_multiplier["biosovereigns"], 2.0);
+        assert!(analysis
+            .outliers
+            .iter()
+            .any(|o| o.category == "regen" && o.metric == "biosovereigns"));
+        assert!(analysis
+            .suggestions
+            .iter()
+            .any(|s| s.target == "biosovereigns.regen.amount"));
+    }
+
+    #[test]
+    fn test_timelines_average_games_tick_by_tick() {
+        use crate::batch::{BatchConfig, BatchResults};
+        use crate::metrics::{BatchSummary, FactionMetrics, MetricsSample};
+
+        let game = |id: &str, army: &[u64]| {
+            let mut faction = FactionMetrics::new("continuity");
+            for (i, &army_value) in army.iter().enumerate() {
+                let tick = 200 * (i as u64 + 1);
+                faction.record_sample(MetricsSample {
+                    tick,
+                    army_value,
+                    income_per_minute: 60,
+                    units_by_tier: [4, 1, 0],
+                });
+                faction.record_map_control(tick, 25.0);
+            }
+            let mut game = GameMetrics::new(id, "test", 0);
+            game.factions.insert("continuity".to_string(), faction);
+            game
+        };
+        let results = BatchResults {
+            config: BatchConfig::default(),
+            // The shorter game only counts toward the ticks it reached
+            games: vec![game("game_0", &[100, 300, 500]), game("game_1", &[300])],
+            games_file: None,
+            summary: BatchSummary::default(),
+            duration_seconds: 1.0,
+            errors: Vec::new(),
+            interrupted: false,
+        };
+
+        let analysis = analyze_batch(&results);
+
+        let timeline = &analysis.timelines["continuity"];
+        assert_eq!(timeline.ticks, vec![200, 400, 600]);
+        assert_eq!(timeline.army_value, vec![200.0, 300.0, 500.0]);
+        assert_eq!(timeline.map_control, vec![25.0; 3]);
+        assert_eq!(timeline.total_units(), vec![5.0; 3]);
+
+        let md = analysis.to_markdown();
+        assert!(md.contains("## Timelines"));
+        assert!(md.contains("| continuity | ▁▃█ (peak 500) |"));
+        assert!(md.contains("4/1/0 |"));
+        let html = analysis.to_html();
+        assert!(html.contains("<h3>Army Value</h3>"));
+        assert_eq!(html.matches("<svg").count(), 4);
+    }
+
+    #[test]
+    fn test_heavy_upkeep_is_an_outlier() {
+        use crate::batch::{BatchConfig, BatchResults};
+        use crate::metrics::{BatchSummary, FactionMetrics};
+
+        let faction = |id: &str, lost| FactionMetrics {
+            resources_from_harvest: 1000,
+            upkeep_income_lost: lost,
+            upkeep_ticks_taxed: u64::from(lost > 0),
+            ..FactionMetrics::new(id)
+        };
+        let mut game = GameMetrics::new("game_0", "test", 0);
+        game.factions
+            .insert("continuity".to_string(), faction("continuity", 1000));
+        game.factions
+            .insert("collegium".to_string(), faction("collegium", 0));
+        let results = BatchResults {
+            config: BatchConfig::default(),
+            games: vec![game],
+            games_file: None,
+            summary: BatchSummary::default(),
+            duration_seconds: 1.0,
+            errors: Vec::new(),
+            interrupted: false,
+        };
+
+        let analysis = analyze_batch(&results);
+
+        assert_eq!(analysis.upkeep_income_share["continuity"], 0.5);
+        assert!(!analysis.upkeep_income_share.contains_key("collegium"));
+        assert!(analysis.outliers.iter().any(|o| o.category == "upkeep"
+            && o.metric == "continuity"
+            && o.severity == Severity::High));
+    }
+
+    #[test]
+    fn test_needing_a_resource_never_gathered_is_an_outlier() {
+        use crate::batch::{BatchConfig, BatchResults};
+        use crate::metrics::{BatchSummary, FactionMetrics};
+
+        let faction = |id: &str, gathered: i64| FactionMetrics {
+            rare_resources_gathered: BTreeMap::from([("exotics".to_string(), gathered)]),
+            resource_shortfalls: BTreeMap::from([("exotics".to_string(), 6)]),
+            ..FactionMetrics::new(id)
+        };
+        let mut game = GameMetrics::new("game_0", "test", 0);
+        game.factions
+            .insert("continuity".to_string(), faction("continuity", 0));
+        game.factions
+            .insert("collegium".to_string(), faction("collegium", 300));
+        let results = BatchResults {
+            config: BatchConfig::default(),
+            games: vec![game.clone(), game],
+            games_file: None,
+            summary: BatchSummary::default(),
+            duration_seconds: 1.0,
+            errors: Vec::new(),
+            interrupted: false,
+        };
+
+        let analysis = analyze_batch(&results);
+
+        assert_eq!(analysis.resource_shortfalls["continuity"]["exotics"], 6.0);
+        assert_eq!(analysis.resource_shortfalls["collegium"]["exotics"], 6.0);
+        let starved: Vec<_> = analysis
+            .outliers
+            .iter()
+            .filter(|o| o.category == "resource_shortfall")
+            .collect();
+        assert_eq!(starved.len(), 1);
+        assert_eq!(starved[0].metric, "continuity");
+    }
+
+    #[test]
+    fn test_unit_effectiveness_and_matchups() {
+        use crate::batch::{BatchConfig, BatchResults};
+        use crate::metrics::BatchSummary;
+
+        let mut game = GameMetrics::new("game_0", "test", 0);
+        let tanks = UnitKindMetrics {
+            fielded: 2,
+            resources_spent: 400,
+            damage_dealt: 600,
+            lifetime_ticks: 3000,
+            lost: 1,
+            kills: BTreeMap::from([("infantry".to_string(), 4)]),
+            damage_to: BTreeMap::from([("infantry".to_string(), 600)]),
+            ..Default::default()
+        };
+        let infantry = UnitKindMetrics {
+            fielded: 6,
+            resources_spent: 300,
+            damage_dealt: 150,
+            lifetime_ticks: 1200,
+            lost: 4,
+            kills: BTreeMap::from([("tank".to_string(), 1)]),
+            damage_to: BTreeMap::from([("tank".to_string(), 150)]),
+            ..Default::default()
+        };
+        game.faction_mut("continuity")
+            .unit_stats
+            .insert("tank".to_string(), tanks);
+        game.faction_mut("collegium")
+            .unit_stats
+            .insert("infantry".to_string(), infantry);
+        let results = BatchResults {
+            config: BatchConfig::default(),
+            games: vec![game],
+            games_file: None,
+            summary: BatchSummary::default(),
+            duration_seconds: 1.0,
+            errors: Vec::new(),
+            interrupted: false,
+        };
+
+        let analysis = analyze_batch(&results);
+
+        let tank = analysis
+            .unit_effectiveness
+            .iter()
+            .find(|u| u.label() == "continuity/tank")
+            .unwrap();
+        assert_eq!(tank.damage_per_resource, Some(1.5));
+        assert_eq!(tank.avg_survival_ticks, 1500.0);
+        assert_eq!(tank.kd_by_opponent["collegium/infantry"], 4.0);
+        let infantry = analysis
+            .unit_effectiveness
+            .iter()
+            .find(|u| u.label() == "collegium/infantry")
+            .unwrap();
+        assert_eq!(infantry.damage_per_resource, Some(0.5));
+        assert_eq!(infantry.kd_by_opponent["continuity/tank"], 0.25);
+        assert_eq!(
+            analysis.unit_matchups["continuity/tank"]["collegium/infantry"],
+            4.0
+        );
+        assert_eq!(
+            analysis.unit_matchups["collegium/infantry"]["continuity/tank"],
+            0.25
+        );
+
+        let md = analysis.to_markdown();
+        assert!(md.contains("## Unit Matchup Matrix"));
+        assert!(md.contains("1. **continuity/tank**: 1.50 damage per resource"));
+        assert!(md.contains("**continuity/tank** vs **collegium/infantry**: deals 4.0x"));
+    }
+
     #[test]
     fn test_markdown_output() {
         let mut analysis = BalanceAnalysis::new();
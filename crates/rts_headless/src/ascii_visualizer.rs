@@ -1,12 +1,23 @@
 //! ASCII battle visualizer for screenshot analysis.
 //!
 //! Renders game state screenshots as ASCII art for quick terminal review.
+//!
+//! A [`LiveView`] observer does the same for a game as it plays, redrawing
+//! the map in the terminal every few ticks beside a panel of each side's
+//! resources, health and army.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
 use std::path::Path;
+use std::time::Duration;
 
+use rts_core::influence::{Control, InfluenceMap};
+use rts_core::math::{Fixed, Vec2Fixed};
+use rts_core::simulation::{Simulation, TickEvents};
 use serde::{Deserialize, Serialize};
 
+use crate::observer::{GameObserver, PlayerView};
+
 /// A unit captured in a screenshot.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UnitSnapshot {
@@ -45,6 +56,9 @@ pub struct ScreenshotState {
     pub effects: Vec<serde_json::Value>,
     pub map_bounds: [u32; 2],
     pub fog_of_war: Option<serde_json::Value>,
+    /// Threat map of the first player, if captured.
+    #[serde(default)]
+    pub threat_map: Option<InfluenceMap>,
 }
 
 impl ScreenshotState {
@@ -69,6 +83,8 @@ pub struct AsciiConfig {
     pub show_legend: bool,
     /// Use colored output (ANSI).
     pub use_color: bool,
+    /// Draw the threat map, if captured, under the units.
+    pub show_threat_map: bool,
 }
 
 impl Default for AsciiConfig {
@@ -79,6 +95,7 @@ impl Default for AsciiConfig {
             show_health: true,
             show_legend: true,
             use_color: true,
+            show_threat_map: false,
         }
     }
 }
@@ -135,6 +152,24 @@ fn faction_color(faction: &str) -> &'static str {
     }
 }
 
+/// Character and color for a threat map region.
+fn threat_glyph(control: Control, use_color: bool) -> (char, String) {
+    let (ch, color) = match control {
+        Control::Empty => return ('.', String::new()),
+        Control::Own => ('+', colors::GREEN),
+        Control::Enemy => ('-', colors::RED),
+        Control::Contested => ('*', colors::YELLOW),
+    };
+    (
+        ch,
+        if use_color {
+            color.to_string()
+        } else {
+            String::new()
+        },
+    )
+}
+
 fn health_color(health_percent: f32) -> &'static str {
     if health_percent > 0.66 {
         colors::GREEN
@@ -164,6 +199,22 @@ pub fn render_ascii(state: &ScreenshotState, config: &AsciiConfig) -> String {
     // Track faction counts
     let mut faction_counts: HashMap<String, (u32, u32, u32)> = HashMap::new(); // (units, damaged, dead)
 
+    // Threat map underneath everything else
+    let threat_map = state.threat_map.as_ref().filter(|_| config.show_threat_map);
+    if let Some(threat) = threat_map {
+        for (y, row) in grid.iter_mut().enumerate().take(config.height - 4) {
+            for (x, cell) in row.iter_mut().enumerate() {
+                let position = Vec2Fixed::new(
+                    Fixed::from_num(x as f32 / scale_x),
+                    Fixed::from_num(y as f32 / scale_y),
+                );
+                if let Some(region) = threat.cell_at(position) {
+                    *cell = threat_glyph(threat.control(region), config.use_color);
+                }
+            }
+        }
+    }
+
     // Place buildings first (they're larger)
     for bld in &state.buildings {
         let x = ((bld.position[0] * scale_x) as usize).min(config.width - 1);
@@ -315,6 +366,14 @@ pub fn render_ascii(state: &ScreenshotState, config: &AsciiConfig) -> String {
             output.push(' ');
         }
         output.push_str("║\n");
+
+        if let Some(threat) = threat_map {
+            output.push_str(&format!(
+                "║ Threat map: +=own -=enemy *=contested ({} contested regions)",
+                threat.contested().len()
+            ));
+            output.push_str(" ║\n");
+        }
     }
 
     // Footer
@@ -445,6 +504,279 @@ pub fn visualize_game_folder(path: &Path, config: &AsciiConfig) -> std::io::Resu
     Ok(output)
 }
 
+/// One side's entry in the live side panel.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FactionPanel {
+    /// Faction name, lowercase.
+    pub faction: String,
+    /// Feedstock on hand.
+    pub resources: i64,
+    /// Living units by kind.
+    pub army: BTreeMap<String, u32>,
+    /// Standing buildings.
+    pub buildings: u32,
+    /// Health left across units and buildings.
+    pub health: u64,
+    /// Health when all of them were whole.
+    pub max_health: u64,
+}
+
+/// A `width` character bar filled to `fraction`.
+fn health_bar(fraction: f32, width: usize, use_color: bool) -> String {
+    let fraction = fraction.clamp(0.0, 1.0);
+    let filled = (fraction * width as f32).round() as usize;
+    let bar = format!("{}{}", "█".repeat(filled), "░".repeat(width - filled));
+    if use_color {
+        format!("{}{}{}", health_color(fraction), bar, colors::RESET)
+    } else {
+        bar
+    }
+}
+
+/// Lines of the side panel, one block per faction.
+fn side_panel(panels: &[FactionPanel], use_color: bool) -> Vec<String> {
+    let (bold, reset) = if use_color {
+        (colors::BOLD, colors::RESET)
+    } else {
+        ("", "")
+    };
+    let mut lines = Vec::new();
+    for panel in panels {
+        let color = if use_color {
+            faction_color(&panel.faction)
+        } else {
+            ""
+        };
+        lines.push(format!(
+            "{}{}{}{}",
+            bold,
+            color,
+            panel.faction.to_uppercase(),
+            reset
+        ));
+        lines.push(format!(" Feedstock {:>7}", panel.resources));
+        let fraction = if panel.max_health == 0 {
+            0.0
+        } else {
+            panel.health as f32 / panel.max_health as f32
+        };
+        lines.push(format!(
+            " Health [{}] {:>3.0}%",
+            health_bar(fraction, 10, use_color),
+            fraction * 100.0
+        ));
+        let units: u32 = panel.army.values().sum();
+        lines.push(format!(
+            " Units {:>3}  Buildings {:>3}",
+            units, panel.buildings
+        ));
+        for (kind, count) in &panel.army {
+            lines.push(format!("   {:<12}{:>4}", kind, count));
+        }
+        lines.push(String::new());
+    }
+    lines
+}
+
+/// Render a map with a side panel of each faction's resources, health and
+/// army to its right.
+pub fn render_with_panel(
+    state: &ScreenshotState,
+    panels: &[FactionPanel],
+    config: &AsciiConfig,
+) -> String {
+    let map = render_ascii(state, config);
+    let mut panel = side_panel(panels, config.use_color).into_iter();
+    let mut output = String::with_capacity(map.len() * 2);
+    // The panel starts beside the map's top border
+    for (row, line) in map.lines().enumerate() {
+        output.push_str(line);
+        if row > 0 {
+            if let Some(entry) = panel.next() {
+                output.push_str("  ");
+                output.push_str(&entry);
+            }
+        }
+        output.push('\n');
+    }
+    for entry in panel {
+        output.push_str(&entry);
+        output.push('\n');
+    }
+    output
+}
+
+/// Capture a running game as a screenshot state, naming each entity by
+/// the kind its owner's AI gave it.
+#[must_use]
+pub fn live_state(
+    sim: &Simulation,
+    game_id: &str,
+    map_size: (u32, u32),
+    players: &[PlayerView<'_>],
+) -> ScreenshotState {
+    let mut state = ScreenshotState {
+        tick: sim.get_tick(),
+        game_id: game_id.to_string(),
+        trigger: serde_json::json!({ "live": null }),
+        camera: serde_json::Value::Null,
+        units: Vec::new(),
+        buildings: Vec::new(),
+        projectiles: Vec::new(),
+        effects: Vec::new(),
+        map_bounds: [map_size.0, map_size.1],
+        fog_of_war: None,
+        threat_map: None,
+    };
+    for id in sim.entities().sorted_ids() {
+        let Some(entity) = sim.get_entity(id) else {
+            continue;
+        };
+        let (Some(position), Some(member)) = (entity.position, entity.faction) else {
+            continue;
+        };
+        if entity.projectile.is_some() {
+            continue;
+        }
+        let player = players.iter().find(|p| p.faction == member.faction);
+        let faction = member.faction.short_name().to_lowercase();
+        let position = [position.value.x.to_num(), position.value.y.to_num()];
+        let health_percent = entity
+            .health
+            .map_or(1.0, |h| h.current as f32 / h.max.max(1) as f32);
+
+        if entity.movement.is_some() {
+            let kind = player
+                .and_then(|p| p.unit_kinds.get(&id))
+                .cloned()
+                .unwrap_or_else(|| "unit".to_string());
+            state.units.push(UnitSnapshot {
+                entity_id: id as u32,
+                kind,
+                faction,
+                position,
+                rotation: 0.0,
+                health_percent,
+                animation_state: "idle".to_string(),
+                animation_frame: 0,
+                is_selected: false,
+                current_action: None,
+            });
+        } else {
+            let kind = player
+                .and_then(|p| p.building_kinds.get(&id))
+                .cloned()
+                .unwrap_or_else(|| {
+                    if entity.depot.is_some() {
+                        "depot".to_string()
+                    } else {
+                        "building".to_string()
+                    }
+                });
+            state.buildings.push(BuildingSnapshot {
+                entity_id: id as u32,
+                kind,
+                faction,
+                position,
+                health_percent,
+            });
+        }
+    }
+    state
+}
+
+/// Side panel entries for each player of a running game.
+#[must_use]
+pub fn live_panels(sim: &Simulation, players: &[PlayerView<'_>]) -> Vec<FactionPanel> {
+    players
+        .iter()
+        .map(|player| {
+            let mut panel = FactionPanel {
+                faction: player.faction.short_name().to_lowercase(),
+                resources: player.resources,
+                ..FactionPanel::default()
+            };
+            for (id, kind) in player.unit_kinds {
+                *panel.army.entry(kind.clone()).or_default() += 1;
+                if let Some(health) = sim.get_entity(*id).and_then(|e| e.health) {
+                    panel.health += u64::from(health.current);
+                    panel.max_health += u64::from(health.max);
+                }
+            }
+            for id in player.building_kinds.keys() {
+                if let Some(health) = sim.get_entity(*id).and_then(|e| e.health) {
+                    panel.buildings += 1;
+                    panel.health += u64::from(health.current);
+                    panel.max_health += u64::from(health.max);
+                }
+            }
+            panel
+        })
+        .collect()
+}
+
+/// Redraws a game in the terminal as it plays.
+///
+/// Every `interval` ticks the screen is cleared and the map drawn again
+/// with a side panel, then the game pauses for the frame delay so it can
+/// be followed by eye.
+#[derive(Debug, Clone)]
+pub struct LiveView {
+    config: AsciiConfig,
+    game_id: String,
+    map_size: (u32, u32),
+    interval: u64,
+    delay: Duration,
+}
+
+impl LiveView {
+    /// Draw a game on a `map_size` map every `interval` ticks (minimum 1),
+    /// without pausing between frames.
+    #[must_use]
+    pub fn new(config: AsciiConfig, game_id: &str, map_size: (u32, u32), interval: u64) -> Self {
+        Self {
+            config,
+            game_id: game_id.to_string(),
+            map_size,
+            interval: interval.max(1),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Pause this long after drawing each frame.
+    #[must_use]
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// The frame for the game's current tick.
+    #[must_use]
+    pub fn frame(&self, sim: &Simulation, players: &[PlayerView<'_>]) -> String {
+        let state = live_state(sim, &self.game_id, self.map_size, players);
+        render_with_panel(&state, &live_panels(sim, players), &self.config)
+    }
+}
+
+impl GameObserver for LiveView {
+    fn on_tick(&mut self, _sim: &Simulation, _events: &TickEvents) {}
+
+    fn on_players(&mut self, sim: &Simulation, players: &[PlayerView<'_>]) {
+        if sim.get_tick() % self.interval != 0 {
+            return;
+        }
+        let frame = self.frame(sim, players);
+        let mut stdout = std::io::stdout().lock();
+        // Clear the screen and home the cursor before redrawing
+        let _ = write!(stdout, "\x1b[2J\x1b[H{}", frame);
+        let _ = stdout.flush();
+        drop(stdout);
+        if !self.delay.is_zero() {
+            std::thread::sleep(self.delay);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -470,6 +802,7 @@ mod tests {
             effects: vec![],
             map_bounds: [256, 256],
             fog_of_war: None,
+            threat_map: None,
         };
 
         let config = AsciiConfig {
@@ -522,6 +855,7 @@ mod tests {
             effects: vec![],
             map_bounds: [256, 256],
             fog_of_war: None,
+            threat_map: None,
         };
 
         let config = AsciiConfig {
@@ -530,6 +864,7 @@ mod tests {
             use_color: false,
             show_legend: true,
             show_health: true,
+            show_threat_map: false,
         };
 
         let output = render_ascii(&state, &config);
@@ -538,4 +873,113 @@ mod tests {
         // Should contain unit markers
         assert!(output.contains('i') || output.contains('I'));
     }
+
+    #[test]
+    fn test_render_threat_map_overlay() {
+        let mut threat = InfluenceMap::new((256, 256), 64, 0);
+        let at = |x: i32, y: i32| Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y));
+        threat.add(at(32, 32), 10, true);
+        threat.add(at(224, 224), 10, false);
+        let state = ScreenshotState {
+            tick: 40,
+            game_id: "threat".to_string(),
+            trigger: serde_json::json!({}),
+            camera: serde_json::json!({}),
+            units: vec![],
+            buildings: vec![],
+            projectiles: vec![],
+            effects: vec![],
+            map_bounds: [256, 256],
+            fog_of_war: None,
+            threat_map: Some(threat),
+        };
+        let mut config = AsciiConfig {
+            width: 40,
+            height: 20,
+            use_color: false,
+            ..Default::default()
+        };
+
+        let plain = render_ascii(&state, &config);
+        assert!(!plain.contains('+') && !plain.contains("Threat map"));
+
+        config.show_threat_map = true;
+        let overlay = render_ascii(&state, &config);
+        assert!(overlay.contains('+') && overlay.contains('-'));
+        assert!(!overlay
+            .lines()
+            .filter(|line| !line.contains("Threat map"))
+            .any(|line| line.contains('*')));
+        assert!(overlay.contains("0 contested regions"));
+    }
+
+    #[test]
+    fn test_live_frame_shows_map_and_side_panel() {
+        use rts_core::components::FactionMember;
+        use rts_core::factions::FactionId;
+        use rts_core::simulation::EntitySpawnParams;
+
+        let mut sim = Simulation::new();
+        let mut spawn = |faction, x: i32, health, moves: bool| {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(100))),
+                health: Some(health),
+                movement: moves.then(|| Fixed::from_num(1)),
+                faction: Some(FactionMember::new(faction, 0)),
+                ..Default::default()
+            })
+        };
+        let depot = spawn(FactionId::Continuity, 20, 500, false);
+        let infantry = spawn(FactionId::Continuity, 60, 50, true);
+        let ranger = spawn(FactionId::Collegium, 200, 80, true);
+        let kinds_a = HashMap::from([(infantry, "infantry".to_string())]);
+        let buildings_a = HashMap::from([(depot, "depot".to_string())]);
+        let kinds_b = HashMap::from([(ranger, "ranger".to_string())]);
+        let buildings_b = HashMap::new();
+        let players = [
+            PlayerView {
+                faction: FactionId::Continuity,
+                resources: 750,
+                unit_kinds: &kinds_a,
+                building_kinds: &buildings_a,
+            },
+            PlayerView {
+                faction: FactionId::Collegium,
+                resources: 1200,
+                unit_kinds: &kinds_b,
+                building_kinds: &buildings_b,
+            },
+        ];
+
+        let state = live_state(&sim, "live", (256, 256), &players);
+        assert_eq!(state.units.len(), 2);
+        assert_eq!(state.buildings[0].kind, "depot");
+
+        let panels = live_panels(&sim, &players);
+        assert_eq!(panels[0].resources, 750);
+        assert_eq!(panels[0].buildings, 1);
+        assert_eq!(panels[0].army.get("infantry"), Some(&1));
+        assert_eq!(panels[1].army.get("ranger"), Some(&1));
+
+        let view = LiveView::new(
+            AsciiConfig {
+                width: 40,
+                height: 20,
+                use_color: false,
+                ..Default::default()
+            },
+            "live",
+            (256, 256),
+            10,
+        );
+        let frame = view.frame(&sim, &players);
+        assert!(frame.contains("Trigger: live"));
+        assert!(frame.contains('i') && frame.contains('R') && frame.contains('#'));
+        assert!(frame.contains("CONTINUITY") && frame.contains("COLLEGIUM"));
+        assert!(frame.contains("Feedstock     750"));
+        assert!(frame.contains("[██████████] 100%"));
+        // The panel sits beside the map, not below it
+        let panel_row = frame.lines().position(|l| l.contains("CONTINUITY"));
+        assert_eq!(panel_row, Some(1));
+    }
 }
@@ -1,25 +1,58 @@
 //! Batch game runner for balance testing.
 //!
-//! Runs multiple games in parallel using rayon to collect balance
-//! metrics across many games efficiently.
+//! Runs multiple games in parallel on a bounded rayon worker pool to
+//! collect balance metrics across many games efficiently.
 //!
 //! # Defensive Coding
 //!
 //! Each game run is wrapped in panic catching to prevent one bad game
-//! from killing the entire batch. Resource limits are enforced.
+//! from killing the entire batch. Resource limits are enforced: games can
+//! be given a wall-clock timeout, after which they are abandoned and
+//! reported as failures. A batch can be cancelled through a shared flag
+//! (wired to Ctrl+C by the CLI); games in flight stop at their next tick,
+//! games not yet started are skipped, and the finished ones are returned
+//! as partial results.
+//!
+//! # Results on disk
+//!
+//! Results are saved as a small summary file ([`RESULTS_FILE`]) beside a
+//! JSON Lines file ([`GAMES_FILE`]) holding one [`GameMetrics`] per line.
+//! With [`BatchConfig::stream_games`] each game is appended as soon as it
+//! finishes and only running totals are kept in memory, so large batches
+//! never hold every game at once. Loading leaves the games on disk;
+//! [`BatchResults::all_games`] reads them back a line at a time.
+//!
+//! # Checkpoints
+//!
+//! With [`BatchConfig::checkpoint_interval`] set, games are streamed and a
+//! [`BatchCheckpoint`] is saved to [`CHECKPOINT_FILE`] every that many
+//! finished games. A batch that crashes or is cancelled partway can then be
+//! picked up with [`resume_batch_cancellable`], which plays only the games
+//! not yet finished.
 
 use crate::faction_loader::FactionRegistry;
-use crate::game_runner::{run_game, GameConfig};
-use crate::metrics::{BatchSummary, GameMetrics};
-use crate::scenario::Scenario;
+use crate::game_runner::{run_game, GameConfig, GameResult, StopReason, StopSignal};
+use crate::metrics::{BatchSummary, GameMetrics, SummaryTally};
+use crate::observer::GameObserver;
+use crate::projection::ProjectionConfig;
+use crate::scenario::{MapSize, Scenario};
 use crate::screenshot::{ScreenshotConfig, ScreenshotMode};
 use crate::strategies::Strategy;
+use crate::trace::{HashTrace, HashTracer, TickHashes, HASH_TRACE_KEY};
 use rayon::prelude::*;
+use rts_core::autosave::AutosaveConfig;
+use rts_core::difficulty::Difficulty;
+use rts_core::factions::FactionId;
+use rts_core::map_generation::MapPreset;
+use rts_core::simulation::TICK_RATE;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Write};
 use std::panic;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicU32, Ordering};
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
@@ -31,6 +64,18 @@ pub const BATCH_DEFAULT_MAX_TICKS: u64 = 36_000;
 /// 30 minutes of game time = 108,000 ticks. More realistic RTS match length.
 pub const REALTIME_DEFAULT_MAX_TICKS: u64 = 108_000;
 
+/// Name of the batch summary file in an output directory.
+pub const RESULTS_FILE: &str = "batch_results.json";
+
+/// Name of the per-game JSON Lines file beside [`RESULTS_FILE`].
+pub const GAMES_FILE: &str = "batch_results.jsonl";
+
+/// Name of the checkpoint file in an output directory.
+pub const CHECKPOINT_FILE: &str = "batch_checkpoint.json";
+
+/// Default finished games between checkpoints.
+pub const DEFAULT_CHECKPOINT_INTERVAL: u32 = 100;
+
 /// Long game duration for extended testing (in ticks).
 /// 60 minutes of game time = 216,000 ticks. For testing late-game scenarios.
 pub const EXTENDED_DEFAULT_MAX_TICKS: u64 = 216_000;
@@ -40,9 +85,9 @@ pub const EXTENDED_DEFAULT_MAX_TICKS: u64 = 216_000;
 pub struct BatchConfig {
     /// Scenario to run
     pub scenario: String,
-    /// Number of games to run
+    /// Number of games to run for each matchup
     pub game_count: u32,
-    /// Maximum parallel games (0 = use rayon default)
+    /// Worker threads playing games at once (0 = one per CPU)
     pub parallel_games: u32,
     /// Output directory for results
     pub output_dir: PathBuf,
@@ -52,14 +97,72 @@ pub struct BatchConfig {
     pub seed_start: u64,
     /// Maximum ticks per game (0 = unlimited)
     pub max_ticks: u64,
-    /// Strategy override for faction A
+    /// Strategy override for faction A: a preset name or strategy file
     pub strategy_a: Option<String>,
-    /// Strategy override for faction B
+    /// Strategy override for faction B: a preset name or strategy file
     pub strategy_b: Option<String>,
+    /// AI difficulty for faction A
+    #[serde(default)]
+    pub difficulty_a: Difficulty,
+    /// AI difficulty for faction B
+    #[serde(default)]
+    pub difficulty_b: Difficulty,
+    /// Faction pairings to play, as (faction A, faction B). Each gets
+    /// `game_count` games over the same seeds.
+    #[serde(default = "default_matchups")]
+    pub matchups: Vec<(FactionId, FactionId)>,
     /// Path to faction data directory (optional, enables data-driven units)
     pub faction_data_path: Option<PathBuf>,
+    /// Faction data to play with instead of loading `faction_data_path`,
+    /// e.g. data patched in memory.
+    #[serde(skip)]
+    pub faction_registry: Option<Arc<FactionRegistry>>,
+    /// Ticks between autosaves of each game (0 = disabled).
+    /// Saves go to `<output_dir>/autosaves`, prefixed with the game ID.
+    #[serde(default)]
+    pub autosave_interval: u64,
+    /// Per-shot damage spread applied to every weapon, ± percent (0 = off).
+    #[serde(default)]
+    pub damage_variance: u8,
+    /// Post AI personality chat lines. Off by default in batch runs.
+    #[serde(default)]
+    pub chat: bool,
+    /// Play every game on a generated map of this archetype instead of the
+    /// named scenario, with spawns and biome varying by seed.
+    #[serde(default)]
+    pub map_preset: Option<MapPreset>,
+    /// Wall-clock seconds a game may run before it is abandoned as a
+    /// failure (0 = no limit).
+    #[serde(default)]
+    pub game_timeout_secs: u64,
+    /// Print a progress line (games/sec, ETA) to stderr every second.
+    #[serde(default)]
+    pub show_progress: bool,
+    /// Append each game's metrics to `<output_dir>/`[`GAMES_FILE`] as
+    /// soon as it finishes.
+    #[serde(default)]
+    pub stream_games: bool,
+    /// End each game early with a `projected` result once its winner
+    /// looks certain (None = play every game out).
+    #[serde(default)]
+    pub projection: Option<ProjectionConfig>,
+    /// Ticks between time-series samples of each faction in each game
+    /// (0 = no sampling).
+    #[serde(default)]
+    pub sample_interval: u64,
+    /// Finished games between checkpoints saved to
+    /// `<output_dir>/`[`CHECKPOINT_FILE`] (0 = no checkpoints). Games are
+    /// streamed while checkpointing, whatever [`stream_games`](Self::stream_games) says.
+    #[serde(default)]
+    pub checkpoint_interval: u32,
+    /// Observers attached to every game; each game gets its own clone.
+    #[serde(skip)]
+    pub observers: Vec<Box<dyn GameObserver>>,
 }
 
+/// Default ticks between time-series samples: every 10 seconds.
+pub const DEFAULT_SAMPLE_INTERVAL: u64 = 10 * TICK_RATE as u64;
+
 impl Default for BatchConfig {
     fn default() -> Self {
         Self {
@@ -72,9 +175,42 @@ impl Default for BatchConfig {
             max_ticks: 36000, // 10 minutes at 60 tps
             strategy_a: None,
             strategy_b: None,
+            difficulty_a: Difficulty::Normal,
+            difficulty_b: Difficulty::Normal,
+            matchups: default_matchups(),
             faction_data_path: None,
+            faction_registry: None,
+            autosave_interval: 0,
+            damage_variance: 0,
+            chat: false,
+            map_preset: None,
+            game_timeout_secs: 0,
+            show_progress: false,
+            stream_games: false,
+            projection: None,
+            sample_interval: DEFAULT_SAMPLE_INTERVAL,
+            checkpoint_interval: 0,
+            observers: Vec::new(),
+        }
+    }
+}
+
+/// Continuity against Collegium.
+fn default_matchups() -> Vec<(FactionId, FactionId)> {
+    vec![(FactionId::Continuity, FactionId::Collegium)]
+}
+
+/// Every pairing of two different factions from `factions`, each once.
+pub fn round_robin(factions: &[FactionId]) -> Vec<(FactionId, FactionId)> {
+    let mut matchups = Vec::new();
+    for (i, &a) in factions.iter().enumerate() {
+        for &b in &factions[i + 1..] {
+            if a != b && !matchups.contains(&(a, b)) && !matchups.contains(&(b, a)) {
+                matchups.push((a, b));
+            }
         }
     }
+    matchups
 }
 
 impl BatchConfig {
@@ -105,6 +241,60 @@ impl BatchConfig {
         self.strategy_b = Some(b.to_string());
         self
     }
+
+    /// Set AI difficulties
+    pub fn with_difficulties(mut self, a: Difficulty, b: Difficulty) -> Self {
+        self.difficulty_a = a;
+        self.difficulty_b = b;
+        self
+    }
+
+    /// Play only `a` against `b`
+    pub fn with_matchup(mut self, a: FactionId, b: FactionId) -> Self {
+        self.matchups = vec![(a, b)];
+        self
+    }
+
+    /// Play every pairing of the given factions
+    pub fn with_round_robin(mut self, factions: &[FactionId]) -> Self {
+        self.matchups = round_robin(factions);
+        self
+    }
+
+    /// Total games across all matchups
+    pub fn total_games(&self) -> u32 {
+        self.game_count.saturating_mul(self.matchups.len() as u32)
+    }
+
+    /// Autosave each game every `ticks` ticks (0 disables)
+    pub fn with_autosave_interval(mut self, ticks: u64) -> Self {
+        self.autosave_interval = ticks;
+        self
+    }
+
+    /// Give every weapon ± `percent` damage variance (0 disables)
+    pub fn with_damage_variance(mut self, percent: u8) -> Self {
+        self.damage_variance = percent;
+        self
+    }
+
+    /// Play every game on a generated map of this archetype
+    pub fn with_map_preset(mut self, preset: MapPreset) -> Self {
+        self.map_preset = Some(preset);
+        self
+    }
+
+    /// Abandon games still running after `secs` wall-clock seconds (0 disables)
+    pub fn with_game_timeout(mut self, secs: u64) -> Self {
+        self.game_timeout_secs = secs;
+        self
+    }
+
+    /// Attach an observer to every game in the batch
+    pub fn with_observer(mut self, observer: impl GameObserver + 'static) -> Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
 }
 
 /// Results from a batch run
@@ -112,30 +302,182 @@ impl BatchConfig {
 pub struct BatchResults {
     /// Configuration used
     pub config: BatchConfig,
-    /// Individual game metrics
+    /// Individual game metrics held in memory. Saved to their own JSON
+    /// Lines file rather than the summary; still read from summaries of
+    /// older batches. Streamed games stay in [`games_file`](Self::games_file).
+    #[serde(default, skip_serializing)]
     pub games: Vec<GameMetrics>,
+    /// JSON Lines file holding the games not in `games`, when they were
+    /// streamed there or left there on loading. Not saved.
+    #[serde(skip)]
+    pub games_file: Option<PathBuf>,
     /// Aggregate summary
     pub summary: BatchSummary,
     /// Total runtime
     pub duration_seconds: f64,
     /// Errors encountered
     pub errors: Vec<BatchError>,
+    /// Set when the batch was cancelled before every game finished; the
+    /// results then cover only the games finished by then.
+    #[serde(default)]
+    pub interrupted: bool,
 }
 
 impl BatchResults {
-    /// Save results to JSON file
-    pub fn save(&self, path: &std::path::Path) -> std::io::Result<()> {
+    /// Save the summary to `path` and the games beside it, at `path` with
+    /// a `.jsonl` extension
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let target = games_path(path);
+        if self.games_file.as_deref() == Some(target.as_path()) {
+            // The streamed games are already there
+            let stream = GameStream::open_append(&target)?;
+            for game in &self.games {
+                stream.append(game)?;
+            }
+        } else {
+            let stream = GameStream::create(&target)?;
+            for game in self.all_games() {
+                stream.append(&game)?;
+            }
+        }
+        self.save_summary(path)
+    }
+
+    /// Save everything but the games, e.g. after they were streamed
+    pub fn save_summary(&self, path: &Path) -> std::io::Result<()> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
-        std::fs::write(path, json)
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(file, self).map_err(std::io::Error::other)
+    }
+
+    /// Load results saved by [`save`](Self::save). The games are left on
+    /// disk for [`all_games`](Self::all_games) to read. `path` may also be a
+    /// games file on its own, in which case the summary is recomputed from
+    /// the games, a line at a time, and the config is unknown.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        if path.extension().is_some_and(|ext| ext == "jsonl") {
+            let mut tally = SummaryTally::default();
+            for game in read_games(path)? {
+                tally.add(&game?);
+            }
+            return Ok(Self {
+                config: BatchConfig::default(),
+                games: Vec::new(),
+                games_file: Some(path.to_path_buf()),
+                summary: tally.finish(),
+                duration_seconds: 0.0,
+                errors: Vec::new(),
+                interrupted: false,
+            });
+        }
+
+        let file = BufReader::new(File::open(path)?);
+        let mut results: Self = serde_json::from_reader(file).map_err(std::io::Error::other)?;
+        let games = games_path(path);
+        // Older summaries carry their games inline
+        if results.games.is_empty() && games.exists() {
+            results.games_file = Some(games);
+        }
+        Ok(results)
+    }
+
+    /// Every game of the batch: those in [`games`](Self::games), then those
+    /// in [`games_file`](Self::games_file), read a line at a time. Games
+    /// that cannot be read are logged and skipped.
+    pub fn all_games(&self) -> impl Iterator<Item = GameMetrics> + '_ {
+        let streamed = self
+            .games_file
+            .as_deref()
+            .and_then(|path| match read_games(path) {
+                Ok(games) => Some(games),
+                Err(e) => {
+                    warn!(path = %path.display(), error = %e, "Failed to read games file");
+                    None
+                }
+            });
+        self.games
+            .iter()
+            .cloned()
+            .chain(
+                streamed
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|game| match game {
+                        Ok(game) => Some(game),
+                        Err(e) => {
+                            warn!(error = %e, "Skipping unreadable game");
+                            None
+                        }
+                    }),
+            )
+    }
+}
+
+/// Games file saved beside the summary at `path`.
+fn games_path(path: &Path) -> PathBuf {
+    path.with_extension("jsonl")
+}
+
+/// Read games from a JSON Lines file one line at a time. Blank lines are
+/// skipped.
+pub fn read_games(
+    path: &Path,
+) -> std::io::Result<impl Iterator<Item = std::io::Result<GameMetrics>>> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|line| serde_json::from_str(&line?).map_err(std::io::Error::other)))
+}
+
+/// Appends games to a JSON Lines file, one [`GameMetrics`] per line. Shared
+/// by the batch workers; each line is flushed as it is written.
+#[derive(Debug)]
+pub struct GameStream {
+    path: PathBuf,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl GameStream {
+    /// Create or truncate the file at `path`.
+    pub fn create(path: &Path) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: Mutex::new(BufWriter::new(File::create(path)?)),
+        })
     }
 
-    /// Load results from JSON file
-    pub fn load(path: &std::path::Path) -> std::io::Result<Self> {
-        let json = std::fs::read_to_string(path)?;
-        serde_json::from_str(&json).map_err(std::io::Error::other)
+    /// File being written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Open the file at `path` to append to, creating it if missing.
+    pub fn open_append(path: &Path) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    /// Append one game.
+    pub fn append(&self, game: &GameMetrics) -> std::io::Result<()> {
+        let line = serde_json::to_string(game).map_err(std::io::Error::other)?;
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| std::io::Error::other("game stream lock poisoned"))?;
+        writeln!(writer, "{line}")?;
+        writer.flush()
     }
 }
 
@@ -150,6 +492,130 @@ pub struct BatchError {
     pub message: String,
 }
 
+/// Progress of a batch, saved to its output directory so the batch can be
+/// resumed after a crash.
+///
+/// Each game's seed follows from its index, so the set of finished games
+/// is the whole seed cursor: every game before `cursor` is finished, as
+/// are those in `finished`. The finished games' metrics are in the
+/// streamed [`GAMES_FILE`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchCheckpoint {
+    /// Configuration of the batch
+    pub config: BatchConfig,
+    /// Lowest game index not yet finished
+    pub cursor: u32,
+    /// Finished games past the cursor
+    pub finished: BTreeSet<u32>,
+    /// Games that failed. They count as finished and are not replayed.
+    pub errors: Vec<BatchError>,
+}
+
+impl BatchCheckpoint {
+    /// Checkpoint of a batch with no games finished
+    pub fn new(config: &BatchConfig) -> Self {
+        Self {
+            config: config.clone(),
+            cursor: 0,
+            finished: BTreeSet::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Whether game `index` is finished
+    pub fn is_finished(&self, index: u32) -> bool {
+        index < self.cursor || self.finished.contains(&index)
+    }
+
+    /// Mark game `index` finished, moving the cursor past every finished game
+    pub fn mark_finished(&mut self, index: u32) {
+        if index >= self.cursor {
+            self.finished.insert(index);
+        }
+        while self.finished.remove(&self.cursor) {
+            self.cursor += 1;
+        }
+    }
+
+    /// Indices of the games still to play
+    pub fn remaining(&self) -> Vec<u32> {
+        (self.cursor..self.config.total_games())
+            .filter(|i| !self.finished.contains(i))
+            .collect()
+    }
+
+    /// Save to [`CHECKPOINT_FILE`] in `dir`. The file is written beside
+    /// the checkpoint and renamed over it, so a crash mid-save leaves the
+    /// previous checkpoint intact.
+    pub fn save(&self, dir: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(CHECKPOINT_FILE);
+        let partial = path.with_extension("json.partial");
+        let mut writer = BufWriter::new(File::create(&partial)?);
+        serde_json::to_writer_pretty(&mut writer, self).map_err(std::io::Error::other)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        std::fs::rename(partial, path)
+    }
+
+    /// Load the checkpoint in `dir`
+    pub fn load(dir: &Path) -> std::io::Result<Self> {
+        let file = BufReader::new(File::open(dir.join(CHECKPOINT_FILE))?);
+        serde_json::from_reader(file).map_err(std::io::Error::other)
+    }
+}
+
+/// Records finished games into a [`BatchCheckpoint`], saving it every
+/// `interval` games.
+struct Checkpointer {
+    dir: PathBuf,
+    interval: u32,
+    state: Mutex<(BatchCheckpoint, u32)>,
+}
+
+impl Checkpointer {
+    fn new(checkpoint: BatchCheckpoint, dir: &Path, interval: u32) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            interval,
+            state: Mutex::new((checkpoint, 0)),
+        }
+    }
+
+    /// Record the outcome of game `index`; cancelled games stay unfinished.
+    fn record(&self, index: u32, outcome: &GameOutcome) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let (checkpoint, unsaved) = &mut *state;
+        match outcome {
+            GameOutcome::Finished(_) | GameOutcome::Streamed => {}
+            GameOutcome::Failed(error) => checkpoint.errors.push(error.clone()),
+            GameOutcome::Cancelled => return,
+        }
+        checkpoint.mark_finished(index);
+        *unsaved += 1;
+        if *unsaved >= self.interval {
+            *unsaved = 0;
+            save_checkpoint(checkpoint, &self.dir);
+        }
+    }
+
+    /// Save whatever is recorded so far.
+    fn finish(self) {
+        if let Ok((checkpoint, _)) = self.state.into_inner() {
+            save_checkpoint(&checkpoint, &self.dir);
+        }
+    }
+}
+
+fn save_checkpoint(checkpoint: &BatchCheckpoint, dir: &Path) {
+    match checkpoint.save(dir) {
+        Ok(()) => debug!(cursor = checkpoint.cursor, "Saved batch checkpoint"),
+        Err(e) => warn!(error = %e, "Failed to save batch checkpoint"),
+    }
+}
+
 /// Progress tracking for batch runs
 #[derive(Debug)]
 pub struct BatchProgress {
@@ -157,6 +623,8 @@ pub struct BatchProgress {
     pub total: u32,
     /// Completed games
     pub completed: Arc<AtomicU32>,
+    /// Games that failed, panicked or timed out
+    pub failed: Arc<AtomicU32>,
     /// Start time
     pub start_time: Instant,
     /// Partial results for live stats
@@ -169,6 +637,7 @@ impl BatchProgress {
         Self {
             total,
             completed: Arc::new(AtomicU32::new(0)),
+            failed: Arc::new(AtomicU32::new(0)),
             start_time: Instant::now(),
             partial_wins: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
         }
@@ -184,29 +653,69 @@ impl BatchProgress {
         }
     }
 
+    /// Record a game that produced no metrics
+    pub fn record_failure(&self) {
+        self.failed.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Get current completion count
     pub fn current(&self) -> u32 {
         self.completed.load(Ordering::Relaxed)
     }
 
+    /// Get the number of failed games
+    pub fn failures(&self) -> u32 {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// Games done either way, completed or failed
+    pub fn finished(&self) -> u32 {
+        self.current() + self.failures()
+    }
+
     /// Get completion percentage
     pub fn percentage(&self) -> f64 {
-        self.current() as f64 / self.total.max(1) as f64 * 100.0
+        self.finished() as f64 / self.total.max(1) as f64 * 100.0
+    }
+
+    /// Games finished per second of wall-clock time so far
+    pub fn games_per_sec(&self) -> f64 {
+        self.finished() as f64 / self.start_time.elapsed().as_secs_f64().max(0.001)
     }
 
     /// Get estimated time remaining
     pub fn eta(&self) -> Duration {
-        let completed = self.current();
-        if completed == 0 {
+        let finished = self.finished();
+        if finished == 0 {
             return Duration::from_secs(0);
         }
 
         let elapsed = self.start_time.elapsed();
-        let per_game = elapsed.as_secs_f64() / completed as f64;
-        let remaining = self.total.saturating_sub(completed);
+        let per_game = elapsed.as_secs_f64() / finished as f64;
+        let remaining = self.total.saturating_sub(finished);
         Duration::from_secs_f64(per_game * remaining as f64)
     }
 
+    /// One-line progress report, e.g.
+    /// `Batch 40/100 (40.0%) | 3.2 games/s | ETA 0m 18s | 1 failed`
+    pub fn status_line(&self) -> String {
+        let eta = self.eta();
+        let mut line = format!(
+            "Batch {}/{} ({:.1}%) | {:.1} games/s | ETA {}m {}s",
+            self.finished(),
+            self.total,
+            self.percentage(),
+            self.games_per_sec(),
+            eta.as_secs() / 60,
+            eta.as_secs() % 60
+        );
+        let failures = self.failures();
+        if failures > 0 {
+            line.push_str(&format!(" | {} failed", failures));
+        }
+        line
+    }
+
     /// Get current win rates
     pub fn current_win_rates(&self) -> std::collections::HashMap<String, f64> {
         let completed = self.current();
@@ -225,14 +734,14 @@ impl BatchProgress {
 
     /// Display progress to stderr
     pub fn display(&self) {
-        let completed = self.current();
+        let finished = self.finished();
         let eta = self.eta();
         let rates = self.current_win_rates();
 
         eprintln!("╔════════════════════════════════════╗");
         eprintln!(
             "║ Batch Progress: {:>4}/{:<4} ({:>5.1}%) ║",
-            completed,
+            finished,
             self.total,
             self.percentage()
         );
@@ -251,122 +760,303 @@ impl BatchProgress {
     }
 }
 
-/// Run a single game using the real simulation engine.
+/// Run a single game of one matchup using the real simulation engine.
+///
+/// The returned metrics carry the final state hash.
 fn run_single_game(
     scenario: &str,
     seed: u64,
+    matchup: (FactionId, FactionId),
     config: &BatchConfig,
     faction_registry: Option<Arc<FactionRegistry>>,
-) -> Result<GameMetrics, String> {
+    stop: StopSignal,
+) -> Result<GameResult, String> {
     use crate::spawn_generator::{generate_dynamic_scenario, SpawnConfig};
 
-    // Load or create base scenario
-    let base_scenario = if scenario == "skirmish_1v1" {
-        Scenario::skirmish_1v1()
+    let mut scenario_data = if let Some(preset) = config.map_preset {
+        // Generated maps place their own spawns
+        Scenario::from_map_preset(seed, MapSize::Small, preset)
     } else {
-        Scenario::default()
-    };
+        // Load or create base scenario
+        let base_scenario = if scenario == "skirmish_1v1" {
+            Scenario::skirmish_1v1()
+        } else {
+            Scenario::default()
+        };
 
-    // Apply dynamic spawns based on seed
-    let spawn_config = SpawnConfig::default();
-    let scenario_data = generate_dynamic_scenario(seed, &base_scenario, &spawn_config);
-
-    // Parse or use default strategies
-    let strategy_a = config
-        .strategy_a
-        .as_ref()
-        .map(|s| match s.as_str() {
-            "rush" => Strategy::rush(),
-            "economic" | "eco" => Strategy::economic(),
-            "balanced" => Strategy::default(),
-            "turtle" => Strategy::turtle(),
-            "harassment" => Strategy::harassment(),
-            "fast_expand" => Strategy::fast_expand(),
-            "all_in" => Strategy::all_in(),
-            _ => Strategy::default(),
-        })
-        .unwrap_or_default();
+        // Apply dynamic spawns based on seed
+        let spawn_config = SpawnConfig::default();
+        generate_dynamic_scenario(seed, &base_scenario, &spawn_config)
+    };
+    if config.damage_variance > 0 {
+        scenario_data.tuning.damage_variance_pct = u32::from(config.damage_variance);
+    }
+    scenario_data
+        .set_matchup(matchup.0, matchup.1)
+        .map_err(|e| e.to_string())?;
+    // With faction data, starts come from faction presets and every starting
+    // kind must exist there rather than falling back to generic units
+    if let Some(registry) = &faction_registry {
+        scenario_data
+            .resolve_starts(registry)
+            .map_err(|e| e.to_string())?;
+    }
 
-    let strategy_b = config
-        .strategy_b
-        .as_ref()
-        .map(|s| match s.as_str() {
-            "rush" => Strategy::rush(),
-            "economic" | "eco" => Strategy::economic(),
-            "balanced" => Strategy::default(),
-            "turtle" => Strategy::turtle(),
-            "harassment" => Strategy::harassment(),
-            "fast_expand" => Strategy::fast_expand(),
-            "all_in" => Strategy::all_in(),
-            _ => Strategy::default(),
-        })
-        .unwrap_or_default();
+    // Presets or strategy files, else the default strategy
+    let strategy_a = resolve_strategy(config.strategy_a.as_deref())?;
+    let strategy_b = resolve_strategy(config.strategy_b.as_deref())?;
 
     // Screenshot config if enabled
     let screenshot_config = if config.screenshot_mode != ScreenshotMode::Disabled {
         Some(ScreenshotConfig::new(
             config.screenshot_mode,
             config.output_dir.join("screenshots"),
-            &format!("game_{}", seed),
+            &game_id(seed, matchup, config),
         ))
     } else {
         None
     };
 
+    let game_id = game_id(seed, matchup, config);
+    let autosave = (config.autosave_interval > 0).then(|| {
+        AutosaveConfig::new(
+            config.output_dir.join("autosaves"),
+            config.autosave_interval,
+        )
+        .with_prefix(game_id.clone())
+    });
+
     let game_config = GameConfig {
         seed,
         max_ticks: config.max_ticks,
         scenario: scenario_data,
         strategy_a,
         strategy_b,
+        handicap_a: config.difficulty_a.handicap(),
+        handicap_b: config.difficulty_b.handicap(),
         screenshot_config,
-        game_id: format!("game_{}", seed),
+        game_id,
         faction_registry,
+        autosave,
+        chat: config.chat,
+        observers: config.observers.clone(),
+        stop,
+        projection: config.projection,
+        sample_interval: (config.sample_interval > 0).then_some(config.sample_interval),
     };
 
-    let result = run_game(game_config);
-    let mut metrics = result.metrics;
-    metrics.final_state_hash = result.final_state_hash;
-    Ok(metrics)
+    let mut result = run_game(game_config);
+    result.metrics.final_state_hash = result.final_state_hash;
+    Ok(result)
+}
+
+/// Strategy named by a preset name or strategy file path, or the default
+/// strategy if none is named.
+///
+/// # Errors
+///
+/// Returns an error if the name is neither a preset nor a loadable file, or
+/// the strategy has problems.
+pub fn resolve_strategy(name: Option<&str>) -> Result<Strategy, String> {
+    let Some(name) = name else {
+        return Ok(Strategy::default());
+    };
+    let strategy =
+        rts_game::strategy_file::resolve_strategy(name).map_err(|e| format!("{name}: {e}"))?;
+    let problems = strategy.problems();
+    if !problems.is_empty() {
+        let problems: Vec<String> = problems.iter().map(ToString::to_string).collect();
+        return Err(format!("{name}: {}", problems.join("; ")));
+    }
+    Ok(strategy)
+}
+
+/// Matchup of game `i` of a batch.
+fn game_matchup(i: u32, config: &BatchConfig) -> (FactionId, FactionId) {
+    config.matchups[(i / config.game_count) as usize]
+}
+
+/// Seed of game `i` of a batch; each matchup plays the same seeds.
+fn game_seed(i: u32, config: &BatchConfig) -> u64 {
+    config
+        .seed_start
+        .wrapping_add(u64::from(i % config.game_count))
+}
+
+/// ID of one game: `game_<seed>`, prefixed by the matchup when the batch
+/// plays several so seeds repeat.
+fn game_id(seed: u64, matchup: (FactionId, FactionId), config: &BatchConfig) -> String {
+    if config.matchups.len() > 1 {
+        format!(
+            "game_{}_vs_{}_{}",
+            matchup.0.short_name().to_lowercase(),
+            matchup.1.short_name().to_lowercase(),
+            seed
+        )
+    } else {
+        format!("game_{}", seed)
+    }
 }
 
 /// Run a batch of games
 pub fn run_batch(config: BatchConfig) -> BatchResults {
+    run_batch_cancellable(config, Arc::new(AtomicBool::new(false)))
+}
+
+/// What became of one game of a batch.
+enum GameOutcome {
+    /// The game ended and produced metrics.
+    Finished(GameMetrics),
+    /// The game ended and its metrics were streamed to the games file.
+    Streamed,
+    /// The game failed, panicked or timed out.
+    Failed(BatchError),
+    /// The batch was cancelled before the game could finish.
+    Cancelled,
+}
+
+/// Run a batch of games until done or until `cancel` is set.
+///
+/// Games run on a pool of `parallel_games` worker threads. Once `cancel`
+/// is set, running games stop at their next tick and unstarted ones are
+/// skipped; the results hold the games finished by then and are marked
+/// [`interrupted`](BatchResults::interrupted).
+pub fn run_batch_cancellable(config: BatchConfig, cancel: Arc<AtomicBool>) -> BatchResults {
+    let checkpoint = BatchCheckpoint::new(&config);
+    run_batch_from(config, checkpoint, SummaryTally::default(), cancel)
+}
+
+/// Continue the batch checkpointed in `output_dir` until done or until
+/// `cancel` is set, playing only the games not yet finished.
+///
+/// The batch keeps the configuration it was started with. Its summary
+/// covers every game, including those played before the checkpoint, which
+/// are read back from the games file a line at a time.
+pub fn resume_batch_cancellable(
+    output_dir: &Path,
+    cancel: Arc<AtomicBool>,
+) -> std::io::Result<BatchResults> {
+    let mut checkpoint = BatchCheckpoint::load(output_dir)?;
+    checkpoint.config.output_dir = output_dir.to_path_buf();
+    let config = checkpoint.config.clone();
+
+    // Games streamed after the last checkpoint was saved are finished too
+    let indices: HashMap<String, u32> = (0..config.total_games())
+        .map(|i| {
+            (
+                game_id(game_seed(i, &config), game_matchup(i, &config), &config),
+                i,
+            )
+        })
+        .collect();
+    let mut earlier = SummaryTally::default();
+    recover_games(&output_dir.join(GAMES_FILE), |game| {
+        if let Some(&i) = indices.get(&game.game_id) {
+            checkpoint.mark_finished(i);
+        }
+        earlier.add(game);
+    })?;
+
+    info!(
+        finished = config.total_games() as usize - checkpoint.remaining().len(),
+        total = config.total_games(),
+        "Resuming batch from checkpoint"
+    );
+    Ok(run_batch_from(config, checkpoint, earlier, cancel))
+}
+
+/// Read back the games streamed to `path` one at a time, skipping lines
+/// that don't parse, such as one cut off by a crash. The file is rewritten
+/// without them so new games append cleanly.
+fn recover_games(path: &Path, mut visit: impl FnMut(&GameMetrics)) -> std::io::Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let mut skipped = 0;
+    for game in read_games(path)? {
+        match game {
+            Ok(game) => visit(&game),
+            // Bad JSON, or bad UTF-8 where a write was cut short
+            Err(e) if matches!(e.kind(), ErrorKind::Other | ErrorKind::InvalidData) => skipped += 1,
+            Err(e) => return Err(e),
+        }
+    }
+    if skipped > 0 {
+        warn!(
+            skipped,
+            path = %path.display(),
+            "Dropped unreadable game lines; those games will be replayed"
+        );
+        // Copy the readable games aside, then swap the copy in
+        let cleaned = path.with_extension("jsonl.tmp");
+        let stream = GameStream::create(&cleaned)?;
+        for game in read_games(path)?.filter_map(Result::ok) {
+            stream.append(&game)?;
+        }
+        drop(stream);
+        std::fs::rename(&cleaned, path)?;
+    }
+    Ok(())
+}
+
+/// Play the games of `config` not yet finished in `checkpoint`, adding
+/// them to the `earlier` games played before.
+///
+/// Streamed games are only tallied, in the order they finish; the others
+/// are kept and tallied in game order.
+fn run_batch_from(
+    config: BatchConfig,
+    checkpoint: BatchCheckpoint,
+    earlier: SummaryTally,
+    cancel: Arc<AtomicBool>,
+) -> BatchResults {
     use crate::faction_loader::load_factions_from_path;
 
     let start = Instant::now();
-    let progress = BatchProgress::new(config.game_count);
-    let progress_arc = Arc::new(progress);
+    let total_games = config.total_games();
+    let remaining = checkpoint.remaining();
+    let resumed = remaining.len() < total_games as usize;
+    let mut errors = checkpoint.errors.clone();
+    let progress = BatchProgress::new(remaining.len() as u32);
+
+    // Report system resources
+    let num_cpus = std::thread::available_parallelism()
+        .map(|p| p.get())
+        .unwrap_or(1);
+    let workers = match config.parallel_games {
+        0 => num_cpus,
+        n => n as usize,
+    };
 
     // Pre-batch diagnostics
     info!(
-        game_count = config.game_count,
+        game_count = total_games,
+        matchups = config.matchups.len(),
         scenario = %config.scenario,
-        parallel = config.parallel_games,
+        workers = workers,
         max_ticks = config.max_ticks,
+        game_timeout_secs = config.game_timeout_secs,
         seed_start = config.seed_start,
         "Starting batch run"
     );
-
-    // Report system resources
-    let num_cpus = std::thread::available_parallelism()
-        .map(|p| p.get())
-        .unwrap_or(1);
     info!(
         available_cpus = num_cpus,
         parallel_requested = config.parallel_games,
         "System resources"
     );
 
-    // Load faction data if path is provided
+    // Index faction data if path is provided. One registry is shared by every
+    // game; factions are parsed on first use.
     let faction_registry: Option<Arc<FactionRegistry>> =
-        if let Some(ref path) = config.faction_data_path {
+        if let Some(registry) = &config.faction_registry {
+            Some(Arc::clone(registry))
+        } else if let Some(ref path) = config.faction_data_path {
             match load_factions_from_path(path) {
                 Ok(registry) => {
                     info!(
-                        "Loaded faction data from {:?}: {} factions",
+                        "Indexed faction data from {:?}: {} faction files",
                         path,
-                        registry.faction_count()
+                        registry.source_count()
                     );
                     Some(Arc::new(registry))
                 }
@@ -382,100 +1072,132 @@ pub fn run_batch(config: BatchConfig) -> BatchResults {
             None
         };
 
-    // Configure thread pool if specified
-    if config.parallel_games > 0 {
-        rayon::ThreadPoolBuilder::new()
-            .num_threads(config.parallel_games as usize)
-            .build_global()
-            .ok(); // Ignore if already set
-    }
-
-    info!("Beginning parallel game execution...");
-
-    let results: Vec<Result<GameMetrics, BatchError>> = (0..config.game_count)
-        .into_par_iter()
-        .map(|i| {
-            let seed = config.seed_start.wrapping_add(i as u64);
-            let registry_clone = faction_registry.clone();
-            let game_start = Instant::now();
-
-            debug!(game_index = i, seed = seed, "Starting game");
-
-            // Wrap in panic catch to prevent one bad game from killing batch
-            let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
-                run_single_game(&config.scenario, seed, &config, registry_clone)
-            }));
-
-            let game_duration = game_start.elapsed();
-
-            match result {
-                Ok(Ok(metrics)) => {
-                    debug!(
-                        game_index = i,
-                        duration_ms = game_duration.as_millis(),
-                        winner = ?metrics.winner,
-                        "Game completed"
-                    );
-                    progress_arc.record_completion(metrics.winner.as_deref());
+    let stream = if config.stream_games || config.checkpoint_interval > 0 {
+        let path = config.output_dir.join(GAMES_FILE);
+        let stream = if resumed {
+            GameStream::open_append(&path)
+        } else {
+            GameStream::create(&path)
+        };
+        match stream {
+            Ok(stream) => {
+                info!(path = %stream.path().display(), "Streaming game results");
+                Some(stream)
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to open games file, keeping results in memory only");
+                None
+            }
+        }
+    } else {
+        None
+    };
 
-                    let completed = progress_arc.current();
-                    if completed % 10 == 0 {
-                        debug!("Progress: {}/{}", completed, config.game_count);
+    // Checkpoints point at streamed games, so they need the stream
+    let checkpointer = (config.checkpoint_interval > 0 && stream.is_some())
+        .then(|| Checkpointer::new(checkpoint, &config.output_dir, config.checkpoint_interval));
+
+    let earlier_games = earlier.games();
+    let tally = Mutex::new(earlier);
+    let play_all = || -> Vec<GameOutcome> {
+        remaining
+            .par_iter()
+            .map(|&i| {
+                let outcome = play_batch_game(i, &config, &faction_registry, &progress, &cancel);
+                let (GameOutcome::Finished(metrics), Some(stream)) = (&outcome, &stream) else {
+                    if let Some(checkpointer) = &checkpointer {
+                        checkpointer.record(i, &outcome);
                     }
-                    if completed % 100 == 0 {
-                        progress_arc.display();
-                    }
-
-                    Ok(metrics)
+                    return outcome;
+                };
+                if let Err(e) = stream.append(metrics) {
+                    // Kept in memory instead
+                    warn!(game_index = i, error = %e, "Failed to stream game result");
+                    return outcome;
                 }
-                Ok(Err(e)) => {
-                    warn!(game_index = i, seed = seed, error = %e, "Game failed");
-                    Err(BatchError {
-                        game_index: i,
-                        seed,
-                        message: e,
-                    })
+                if let Some(checkpointer) = &checkpointer {
+                    checkpointer.record(i, &outcome);
                 }
-                Err(panic_info) => {
-                    let msg = if let Some(s) = panic_info.downcast_ref::<&str>() {
-                        s.to_string()
-                    } else if let Some(s) = panic_info.downcast_ref::<String>() {
-                        s.clone()
-                    } else {
-                        "Unknown panic".to_string()
-                    };
-                    error!(
-                        game_index = i,
-                        seed = seed,
-                        panic_msg = %msg,
-                        "Game PANICKED - catching to continue batch"
-                    );
-                    Err(BatchError {
-                        game_index: i,
-                        seed,
-                        message: format!("PANIC: {}", msg),
-                    })
+                match tally.lock() {
+                    Ok(mut tally) => tally.add(metrics),
+                    Err(_) => warn!(game_index = i, "Summary lock poisoned, game left out"),
                 }
+                GameOutcome::Streamed
+            })
+            .collect()
+    };
+
+    info!("Beginning parallel game execution...");
+    let done = AtomicBool::new(false);
+    let outcomes = std::thread::scope(|scope| {
+        if config.show_progress {
+            scope.spawn(|| report_progress(&progress, &done));
+        }
+        // A pool of our own, so the worker count holds even when the
+        // global pool was already configured by an earlier batch
+        let outcomes = match rayon::ThreadPoolBuilder::new()
+            .num_threads(workers)
+            .thread_name(|i| format!("batch-worker-{i}"))
+            .build()
+        {
+            Ok(pool) => pool.install(play_all),
+            Err(e) => {
+                warn!(error = %e, "Failed to build worker pool, using the global pool");
+                play_all()
             }
-        })
-        .collect();
+        };
+        done.store(true, Ordering::Relaxed);
+        outcomes
+    });
+    if config.show_progress {
+        eprintln!("{}", progress.status_line());
+    }
+    if let Some(checkpointer) = checkpointer {
+        checkpointer.finish();
+    }
 
-    let (games, errors): (Vec<_>, Vec<_>) = results.into_iter().partition(Result::is_ok);
-    let games: Vec<GameMetrics> = games.into_iter().filter_map(Result::ok).collect();
-    let errors: Vec<BatchError> = errors.into_iter().filter_map(Result::err).collect();
+    let mut tally = tally
+        .into_inner()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let mut games = Vec::new();
+    let mut cancelled = 0;
+    for outcome in outcomes {
+        match outcome {
+            GameOutcome::Finished(metrics) => {
+                tally.add(&metrics);
+                games.push(metrics);
+            }
+            GameOutcome::Streamed => {}
+            GameOutcome::Failed(error) => errors.push(error),
+            GameOutcome::Cancelled => cancelled += 1,
+        }
+    }
+    let interrupted = cancel.load(Ordering::Relaxed) && cancelled > 0;
 
-    let summary = BatchSummary::from_games(&games);
+    let completed = tally.games();
+    let summary = tally.finish();
     let duration_seconds = start.elapsed().as_secs_f64();
 
     // Post-batch diagnostics
     info!(
-        completed = games.len(),
+        completed = completed,
         failed = errors.len(),
-        total = config.game_count,
+        cancelled = cancelled,
+        total = total_games,
         duration_secs = format!("{:.1}", duration_seconds),
-        games_per_sec = format!("{:.2}", games.len() as f64 / duration_seconds.max(0.001)),
+        games_per_sec = format!(
+            "{:.2}",
+            f64::from(completed - earlier_games) / duration_seconds.max(0.001)
+        ),
         "Batch complete"
     );
+    if interrupted {
+        warn!(
+            completed = completed,
+            total = total_games,
+            "Batch cancelled, keeping partial results"
+        );
+    }
 
     if !errors.is_empty() {
         warn!(
@@ -493,12 +1215,262 @@ pub fn run_batch(config: BatchConfig) -> BatchResults {
         }
     }
 
+    if let Some(registry) = &faction_registry {
+        debug!(
+            parsed = registry.faction_count(),
+            indexed = registry.source_count(),
+            "Faction data parsed during batch"
+        );
+    }
+
     BatchResults {
         config,
         games,
+        games_file: stream.map(|stream| stream.path().to_path_buf()),
         summary,
         duration_seconds,
         errors,
+        interrupted,
+    }
+}
+
+/// Play game `i` of a batch on the current worker thread.
+fn play_batch_game(
+    i: u32,
+    config: &BatchConfig,
+    faction_registry: &Option<Arc<FactionRegistry>>,
+    progress: &BatchProgress,
+    cancel: &Arc<AtomicBool>,
+) -> GameOutcome {
+    if cancel.load(Ordering::Relaxed) {
+        return GameOutcome::Cancelled;
+    }
+    let matchup = game_matchup(i, config);
+    let seed = game_seed(i, config);
+    let registry_clone = faction_registry.clone();
+    let mut stop = StopSignal::default().with_cancel(Arc::clone(cancel));
+    if config.game_timeout_secs > 0 {
+        stop = stop.with_timeout(Duration::from_secs(config.game_timeout_secs));
+    }
+    let game_start = Instant::now();
+
+    debug!(game_index = i, seed = seed, "Starting game");
+
+    // Wrap in panic catch to prevent one bad game from killing batch
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        run_single_game(
+            &config.scenario,
+            seed,
+            matchup,
+            config,
+            registry_clone,
+            stop,
+        )
+    }));
+
+    let game_duration = game_start.elapsed();
+    let failure = |message: String| {
+        progress.record_failure();
+        GameOutcome::Failed(BatchError {
+            game_index: i,
+            seed,
+            message,
+        })
+    };
+
+    match result {
+        Ok(Ok(GameResult {
+            stopped: Some(StopReason::Cancelled),
+            ..
+        })) => GameOutcome::Cancelled,
+        Ok(Ok(GameResult {
+            metrics,
+            stopped: Some(StopReason::TimedOut),
+            ..
+        })) => {
+            warn!(
+                game_index = i,
+                seed = seed,
+                tick = metrics.duration_ticks,
+                timeout_secs = config.game_timeout_secs,
+                "Game timed out"
+            );
+            failure(format!(
+                "TIMEOUT: still running after {}s, at tick {}",
+                config.game_timeout_secs, metrics.duration_ticks
+            ))
+        }
+        Ok(Ok(GameResult { metrics, .. })) => {
+            debug!(
+                game_index = i,
+                duration_ms = game_duration.as_millis(),
+                winner = ?metrics.winner,
+                "Game completed"
+            );
+            progress.record_completion(metrics.winner.as_deref());
+
+            let completed = progress.current();
+            if completed % 10 == 0 {
+                debug!("Progress: {}/{}", completed, progress.total);
+            }
+            if !config.show_progress && completed % 100 == 0 {
+                progress.display();
+            }
+
+            GameOutcome::Finished(metrics)
+        }
+        Ok(Err(e)) => {
+            warn!(game_index = i, seed = seed, error = %e, "Game failed");
+            failure(e)
+        }
+        Err(panic_info) => {
+            let msg = if let Some(s) = panic_info.downcast_ref::<&str>() {
+                s.to_string()
+            } else if let Some(s) = panic_info.downcast_ref::<String>() {
+                s.clone()
+            } else {
+                "Unknown panic".to_string()
+            };
+            error!(
+                game_index = i,
+                seed = seed,
+                panic_msg = %msg,
+                "Game PANICKED - catching to continue batch"
+            );
+            failure(format!("PANIC: {}", msg))
+        }
+    }
+}
+
+/// Print a progress line to stderr every second until `done` is set.
+fn report_progress(progress: &BatchProgress, done: &AtomicBool) {
+    const POLL: Duration = Duration::from_millis(100);
+    let mut last_report = Instant::now();
+    while !done.load(Ordering::Relaxed) {
+        std::thread::sleep(POLL);
+        if last_report.elapsed() >= Duration::from_secs(1) {
+            eprintln!("{}", progress.status_line());
+            last_report = Instant::now();
+        }
+    }
+}
+
+/// Outcome spread of one arm of a damage variance A/B run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VarianceArm {
+    /// Damage variance used, ± percent.
+    pub damage_variance: u8,
+    /// Games completed.
+    pub games: u32,
+    /// Win rates by faction.
+    pub win_rates: HashMap<String, f64>,
+    /// Games without a winner.
+    pub draws: u32,
+    /// Mean game length in ticks.
+    pub avg_duration_ticks: f64,
+    /// Standard deviation of game length in ticks.
+    pub duration_stddev_ticks: f64,
+}
+
+impl VarianceArm {
+    fn from_results(results: &BatchResults) -> Self {
+        let summary = &results.summary;
+        let mean = summary.avg_duration_ticks;
+        let stddev = if summary.total_games == 0 {
+            0.0
+        } else {
+            let sum_sq: f64 = results
+                .all_games()
+                .map(|g| (g.duration_ticks as f64 - mean).powi(2))
+                .sum();
+            (sum_sq / f64::from(summary.total_games)).sqrt()
+        };
+        Self {
+            damage_variance: results.config.damage_variance,
+            games: summary.total_games,
+            win_rates: summary.win_rates.clone(),
+            draws: summary.draws,
+            avg_duration_ticks: mean,
+            duration_stddev_ticks: stddev,
+        }
+    }
+}
+
+/// Same-seed comparison of a batch with and without damage variance.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VarianceComparison {
+    /// Batch with fixed damage.
+    pub baseline: VarianceArm,
+    /// Batch with damage variance on.
+    pub varied: VarianceArm,
+    /// Seeds that completed in both arms.
+    pub paired_games: u32,
+    /// Paired seeds whose winner changed once variance was on.
+    pub outcome_flips: u32,
+}
+
+impl VarianceComparison {
+    /// Fraction of paired games whose winner changed.
+    #[must_use]
+    pub fn flip_rate(&self) -> f64 {
+        if self.paired_games == 0 {
+            return 0.0;
+        }
+        f64::from(self.outcome_flips) / f64::from(self.paired_games)
+    }
+
+    /// Change in mean game length caused by variance, in ticks.
+    #[must_use]
+    pub fn duration_shift_ticks(&self) -> f64 {
+        self.varied.avg_duration_ticks - self.baseline.avg_duration_ticks
+    }
+
+    /// Save the comparison to a JSON file
+    pub fn save(&self, path: &std::path::Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+}
+
+/// Run the same seeds with fixed damage and with ± `variance` percent.
+///
+/// Each arm writes into its own subdirectory of the configured output
+/// directory. Games are paired by game ID (seed and matchup) to count
+/// outcome flips.
+pub fn compare_damage_variance(config: BatchConfig, variance: u8) -> VarianceComparison {
+    let output_dir = config.output_dir.clone();
+    let mut baseline_config = config.clone().with_damage_variance(0);
+    baseline_config.output_dir = output_dir.join("baseline");
+    let mut varied_config = config.with_damage_variance(variance);
+    varied_config.output_dir = output_dir.join(format!("variance_{variance}"));
+
+    info!(variance, "Running damage variance A/B comparison");
+    let baseline = run_batch(baseline_config);
+    let varied = run_batch(varied_config);
+
+    let baseline_winners: HashMap<String, Option<String>> = baseline
+        .all_games()
+        .map(|g| (g.game_id, g.winner))
+        .collect();
+    let mut paired_games = 0;
+    let mut outcome_flips = 0;
+    for game in varied.all_games() {
+        if let Some(winner) = baseline_winners.get(&game.game_id) {
+            paired_games += 1;
+            if *winner != game.winner {
+                outcome_flips += 1;
+            }
+        }
+    }
+
+    VarianceComparison {
+        baseline: VarianceArm::from_results(&baseline),
+        varied: VarianceArm::from_results(&varied),
+        paired_games,
+        outcome_flips,
     }
 }
 
@@ -506,8 +1478,17 @@ pub fn run_batch(config: BatchConfig) -> BatchResults {
 pub fn verify_determinism(scenario: &str, seed: u64, runs: u32) -> bool {
     let results: Vec<GameMetrics> = (0..runs)
         .map(|_| {
-            run_single_game(scenario, seed, &BatchConfig::default(), None)
-                .expect("Game should complete")
+            let config = BatchConfig::default();
+            run_single_game(
+                scenario,
+                seed,
+                config.matchups[0],
+                &config,
+                None,
+                StopSignal::default(),
+            )
+            .expect("Game should complete")
+            .metrics
         })
         .collect();
 
@@ -520,6 +1501,36 @@ pub fn verify_determinism(scenario: &str, seed: u64, runs: u32) -> bool {
     })
 }
 
+/// Play one game and record its state hash after every tick, for comparing
+/// against a trace made on another machine.
+///
+/// # Errors
+///
+/// Returns an error if the game cannot be set up.
+pub fn record_hash_trace(scenario: &str, seed: u64) -> Result<HashTrace, String> {
+    let config = BatchConfig {
+        observers: vec![Box::new(HashTracer::default())],
+        ..BatchConfig::default()
+    };
+    let metrics = run_single_game(
+        scenario,
+        seed,
+        config.matchups[0],
+        &config,
+        None,
+        StopSignal::default(),
+    )?
+    .metrics;
+    let ticks = metrics
+        .observations
+        .get(HASH_TRACE_KEY)
+        .map(|ticks| serde_json::from_value::<TickHashes>(ticks.clone()))
+        .transpose()
+        .map_err(|e| e.to_string())?
+        .unwrap_or_default();
+    Ok(HashTrace::new(scenario, seed, ticks))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -542,6 +1553,49 @@ mod tests {
         assert_eq!(config.seed_start, 12345);
     }
 
+    #[test]
+    fn test_round_robin_covers_every_pairing_once() {
+        let matchups = round_robin(&FactionId::ALL);
+        assert_eq!(matchups.len(), 10);
+        assert!(matchups.iter().all(|(a, b)| a != b));
+        assert!(matchups.contains(&(FactionId::Tinkers, FactionId::Zephyr)));
+        assert!(!matchups.contains(&(FactionId::Zephyr, FactionId::Tinkers)));
+
+        let config = BatchConfig::new("test", 4).with_round_robin(&FactionId::ALL);
+        assert_eq!(config.total_games(), 40);
+    }
+
+    #[test]
+    fn test_run_batch_plays_each_matchup() {
+        let config = BatchConfig::new("test", 2).with_round_robin(&[
+            FactionId::Continuity,
+            FactionId::Tinkers,
+            FactionId::Zephyr,
+        ]);
+        let results = run_batch(config);
+
+        assert!(results.errors.is_empty(), "{:?}", results.errors);
+        assert_eq!(results.games.len(), 6);
+        assert_eq!(results.summary.matchups.len(), 3);
+        let tinkers_zephyr = &results.summary.matchups["tinkers vs zephyr"];
+        assert_eq!(tinkers_zephyr.games, 2);
+        for game in &results.games {
+            assert_eq!(game.factions.len(), 2);
+        }
+        assert!(results
+            .games
+            .iter()
+            .any(|g| g.game_id == "game_continuity_vs_zephyr_1"));
+    }
+
+    #[test]
+    fn test_mirror_matchup_fails_game() {
+        let config = BatchConfig::new("test", 1).with_matchup(FactionId::Zephyr, FactionId::Zephyr);
+        let results = run_batch(config);
+        assert!(results.games.is_empty());
+        assert_eq!(results.errors.len(), 1);
+    }
+
     #[test]
     fn test_progress_tracking() {
         let progress = BatchProgress::new(100);
@@ -556,6 +1610,13 @@ mod tests {
 
         let rates = progress.current_win_rates();
         assert!((rates["faction_a"] - 0.666).abs() < 0.01);
+
+        progress.record_failure();
+        assert_eq!(progress.finished(), 4);
+        assert_eq!(progress.percentage(), 4.0);
+        let line = progress.status_line();
+        assert!(line.starts_with("Batch 4/100 (4.0%)"), "{line}");
+        assert!(line.ends_with("| 1 failed"), "{line}");
     }
 
     #[test]
@@ -568,6 +1629,78 @@ mod tests {
         assert!(results.duration_seconds > 0.0);
     }
 
+    #[test]
+    fn test_run_batch_on_map_presets() {
+        for preset in [MapPreset::ChokeHeavy, MapPreset::Islands] {
+            let mut config = BatchConfig::new("test", 2).with_map_preset(preset);
+            config.max_ticks = 3600;
+            let results = run_batch(config);
+
+            assert_eq!(results.games.len(), 2, "{}", preset.name());
+            assert!(results.errors.is_empty(), "{:?}", results.errors);
+        }
+    }
+
+    #[test]
+    fn test_batch_observers_record_per_game() {
+        use crate::observer::{ArmyValueSampler, ARMY_VALUE_KEY};
+
+        let config = BatchConfig::new("test", 3).with_observer(ArmyValueSampler::new(600));
+        let results = run_batch(config);
+
+        assert_eq!(results.games.len(), 3);
+        for game in &results.games {
+            assert!(game.observations.contains_key(ARMY_VALUE_KEY));
+        }
+    }
+
+    #[test]
+    fn test_parallel_games_bound_the_worker_pool() {
+        use rts_core::simulation::{Simulation, TickEvents};
+
+        /// Records the name of the thread that played the game.
+        #[derive(Debug, Clone)]
+        struct WorkerName;
+
+        impl GameObserver for WorkerName {
+            fn on_tick(&mut self, _sim: &Simulation, _events: &TickEvents) {}
+
+            fn on_game_end(&mut self, _sim: &Simulation, metrics: &mut GameMetrics) {
+                let name = std::thread::current().name().map(str::to_string);
+                metrics
+                    .observations
+                    .insert("worker".to_string(), serde_json::json!(name));
+            }
+        }
+
+        let mut config = BatchConfig::new("test", 6).with_observer(WorkerName);
+        config.parallel_games = 2;
+        config.max_ticks = 600;
+        let results = run_batch(config);
+
+        assert_eq!(results.games.len(), 6);
+        let seeds: Vec<u64> = results.games.iter().map(|g| g.seed).collect();
+        assert_eq!(seeds, (0..6).collect::<Vec<_>>());
+        let workers: std::collections::BTreeSet<String> = results
+            .games
+            .iter()
+            .map(|g| g.observations["worker"].as_str().unwrap().to_string())
+            .collect();
+        assert!(workers.len() <= 2, "{workers:?}");
+        assert!(workers.iter().all(|w| w.starts_with("batch-worker-")));
+    }
+
+    #[test]
+    fn test_cancelled_batch_returns_partial_results() {
+        let results =
+            run_batch_cancellable(BatchConfig::new("test", 4), Arc::new(AtomicBool::new(true)));
+
+        assert!(results.interrupted);
+        assert!(results.games.is_empty());
+        assert!(results.errors.is_empty());
+        assert!(!run_batch(BatchConfig::new("test", 1)).interrupted);
+    }
+
     #[test]
     fn test_batch_summary_calculated() {
         let config = BatchConfig::new("test", 20);
@@ -583,6 +1716,33 @@ mod tests {
         assert!(verify_determinism("test", 12345, 5));
     }
 
+    #[test]
+    fn test_hash_traces_of_one_seed_agree() {
+        let golden = record_hash_trace("test", 12345).unwrap();
+        assert!(golden.last_tick().is_some());
+        assert_eq!(
+            record_hash_trace("test", 12345).unwrap().compare(&golden),
+            Ok(())
+        );
+        assert!(record_hash_trace("test", 54321)
+            .unwrap()
+            .compare(&golden)
+            .is_err());
+    }
+
+    #[test]
+    fn test_damage_variance_comparison_pairs_seeds() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = BatchConfig::new("test", 4).with_output(dir.path().to_path_buf());
+        let comparison = compare_damage_variance(config, 10);
+
+        assert_eq!(comparison.baseline.damage_variance, 0);
+        assert_eq!(comparison.varied.damage_variance, 10);
+        assert_eq!(comparison.paired_games, 4);
+        assert!(comparison.flip_rate() <= 1.0);
+        assert!(comparison.baseline.duration_stddev_ticks >= 0.0);
+    }
+
     #[test]
     fn test_batch_results_save_load() {
         let config = BatchConfig::new("test", 5);
@@ -593,9 +1753,111 @@ mod tests {
 
         results.save(&path).unwrap();
         assert!(path.exists());
+        let summary: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(summary.get("games").is_none());
 
         let loaded = BatchResults::load(&path).unwrap();
-        assert_eq!(loaded.games.len(), 5);
+        assert_eq!(loaded.all_games().count(), 5);
         assert_eq!(loaded.config.scenario, "test");
+
+        let games_only = BatchResults::load(&dir.path().join("results.jsonl")).unwrap();
+        assert!(games_only.games.is_empty());
+        assert_eq!(games_only.all_games().count(), 5);
+        assert_eq!(games_only.summary.total_games, 5);
+    }
+
+    #[test]
+    fn test_streamed_games_load_with_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = BatchConfig::new("test", 4).with_output(dir.path().to_path_buf());
+        config.stream_games = true;
+        config.max_ticks = 600;
+        let results = run_batch(config);
+
+        // Only the running totals are kept in memory
+        assert!(results.games.is_empty());
+        assert_eq!(results.summary.total_games, 4);
+        assert_eq!(results.all_games().count(), 4);
+
+        // Games are on disk before the summary is saved
+        let streamed = read_games(&dir.path().join(GAMES_FILE))
+            .unwrap()
+            .collect::<std::io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(streamed.len(), 4);
+
+        let path = dir.path().join(RESULTS_FILE);
+        results.save_summary(&path).unwrap();
+        let loaded = BatchResults::load(&path).unwrap();
+        assert!(loaded.games.is_empty());
+        let mut seeds: Vec<u64> = loaded.all_games().map(|g| g.seed).collect();
+        seeds.sort_unstable();
+        assert_eq!(seeds, vec![0, 1, 2, 3]);
+        assert_eq!(loaded.summary.total_games, 4);
+    }
+
+    #[test]
+    fn test_checkpoint_cursor_skips_finished_games() {
+        let mut checkpoint = BatchCheckpoint::new(&BatchConfig::new("test", 5));
+        checkpoint.mark_finished(2);
+        checkpoint.mark_finished(0);
+        assert_eq!(checkpoint.cursor, 1);
+        assert_eq!(checkpoint.remaining(), vec![1, 3, 4]);
+
+        checkpoint.mark_finished(1);
+        assert_eq!(checkpoint.cursor, 3);
+        assert!(checkpoint.finished.is_empty());
+        assert!(checkpoint.is_finished(2));
+        assert!(!checkpoint.is_finished(3));
+    }
+
+    #[test]
+    fn test_resume_plays_only_unfinished_games() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = BatchConfig::new("test", 4).with_output(dir.path().to_path_buf());
+        config.max_ticks = 600;
+        config.checkpoint_interval = 1;
+        run_batch(config.clone());
+        assert_eq!(
+            BatchCheckpoint::load(dir.path()).unwrap().remaining(),
+            Vec::<u32>::new()
+        );
+
+        // Crash after three games were streamed but only one checkpointed,
+        // mid-way through writing the fourth
+        let mut checkpoint = BatchCheckpoint::new(&config);
+        checkpoint.mark_finished(0);
+        checkpoint.save(dir.path()).unwrap();
+        let games_file = dir.path().join(GAMES_FILE);
+        let lines: Vec<String> = std::fs::read_to_string(&games_file)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        let torn = format!("{}\n{}", lines[..3].join("\n"), &lines[3][..20]);
+        std::fs::write(&games_file, torn).unwrap();
+
+        let results =
+            resume_batch_cancellable(dir.path(), Arc::new(AtomicBool::new(false))).unwrap();
+        let mut seeds: Vec<u64> = results.all_games().map(|g| g.seed).collect();
+        seeds.sort_unstable();
+        assert_eq!(seeds, vec![0, 1, 2, 3]);
+        assert_eq!(results.summary.total_games, 4);
+        assert_eq!(read_games(&games_file).unwrap().count(), 4);
+        assert_eq!(BatchCheckpoint::load(dir.path()).unwrap().cursor, 4);
+    }
+
+    #[test]
+    fn test_inline_games_still_load() {
+        let results = run_batch(BatchConfig::new("test", 2));
+        let mut legacy = serde_json::to_value(&results).unwrap();
+        legacy["games"] = serde_json::to_value(&results.games).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("old_results.json");
+        std::fs::write(&path, legacy.to_string()).unwrap();
+
+        assert_eq!(BatchResults::load(&path).unwrap().games.len(), 2);
     }
 }
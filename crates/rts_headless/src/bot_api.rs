@@ -0,0 +1,404 @@
+//! Versioned protocol for external bot processes.
+//!
+//! Bots are separate programs that speak JSON lines over stdin/stdout, like
+//! the interactive [`crate::protocol`], but with a fixed handshake and a
+//! per-decision time budget so independently written AIs can compete fairly.
+//! Entities are described with the protocol's [`EntityState`] and orders are
+//! the protocol's unit order [`Command`]s (`move`, `attack`, `attack_move`,
+//! `patrol`, `attack_ground`, `hold_position` and `stop`), so a controller
+//! written for the interactive runner can be reused as a bot.
+//!
+//! # Session Flow
+//!
+//! ```text
+//! <- {"type":"hello","api_version":2,"faction":"Continuity","capabilities":["orders","queued_orders","state_hash"],"tick_budget_ms":50,"max_timeouts":5,"decision_interval":10}
+//! -> {"type":"handshake","api_version":2,"name":"rushbot","capabilities":["orders"]}
+//! <- {"type":"observation","tick":0,"own":[{"id":3,"entity_type":{"unit":{"kind":"infantry"}},...}],"visible_enemies":[...]}
+//! -> {"type":"orders","orders":[{"cmd":"attack","entity_id":3,"target_id":7}]}
+//! ...
+//! <- {"type":"game_over","winner":"Collegium","reason":"elimination"}
+//! ```
+//!
+//! A reply that misses the budget counts as a timeout and the bot's units
+//! keep their previous orders. A bot that reaches `max_timeouts` forfeits.
+
+use rts_core::api::ApiCommand;
+use rts_core::components::EntityId;
+use rts_core::factions::FactionId;
+use rts_core::math::{Fixed, Vec2Fixed};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::protocol::{Command, EntityState};
+
+/// Current bot API version. Bumped on any incompatible message change.
+pub const BOT_API_VERSION: u32 = 2;
+
+/// Optional features a bot can request during the handshake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Issue orders that replace a unit's queue. Required.
+    Orders,
+    /// Append orders to a unit's queue instead of replacing it.
+    QueuedOrders,
+    /// Receive the simulation state hash with each observation.
+    StateHash,
+}
+
+impl Capability {
+    /// Every capability this runner implements.
+    pub const ALL: [Capability; 3] = [Self::Orders, Self::QueuedOrders, Self::StateHash];
+}
+
+/// Errors negotiating or talking to a bot.
+#[derive(Error, Debug)]
+pub enum BotApiError {
+    /// The bot speaks a different protocol version.
+    #[error("bot api version {bot} is not supported (runner speaks {runner})")]
+    VersionMismatch {
+        /// Version declared by the bot.
+        bot: u32,
+        /// Version spoken by the runner.
+        runner: u32,
+    },
+    /// The bot did not request a capability it must have.
+    #[error("bot did not declare required capability {0:?}")]
+    MissingCapability(Capability),
+    /// The bot sent something other than what the protocol expects.
+    #[error("unexpected bot message: {0}")]
+    Unexpected(String),
+    /// The bot's output was not valid protocol JSON.
+    #[error("malformed bot message: {0}")]
+    Malformed(#[from] serde_json::Error),
+    /// The bot did not answer within its time budget.
+    #[error("bot exceeded its time budget")]
+    Timeout,
+    /// The bot process exited or closed its pipes.
+    #[error("bot disconnected")]
+    Disconnected,
+    /// Failed to start or talk to the bot process.
+    #[error("bot io error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The scenario or faction data to play could not be loaded.
+    #[error("tournament setup failed: {0}")]
+    Setup(String),
+}
+
+/// Messages sent from the runner to a bot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RunnerMessage {
+    /// First message of a session.
+    Hello {
+        /// Protocol version spoken by the runner.
+        api_version: u32,
+        /// Faction the bot controls.
+        faction: FactionId,
+        /// Capabilities the runner offers.
+        capabilities: Vec<Capability>,
+        /// Wall-clock budget for each reply, in milliseconds.
+        tick_budget_ms: u64,
+        /// Timeouts allowed before the bot forfeits.
+        max_timeouts: u32,
+        /// Simulation ticks between observations.
+        decision_interval: u64,
+    },
+    /// Game state as seen by the bot's faction. Expects an `orders` reply.
+    Observation {
+        /// Current tick.
+        tick: u64,
+        /// The bot's own entities.
+        own: Vec<EntityState>,
+        /// Enemy entities currently in vision.
+        visible_enemies: Vec<EntityState>,
+        /// State hash, if the `state_hash` capability was negotiated.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        state_hash: Option<u64>,
+    },
+    /// The match has ended; the bot should exit.
+    GameOver {
+        /// Winning faction, or `None` for a draw.
+        winner: Option<FactionId>,
+        /// Why the match ended.
+        reason: EndReason,
+    },
+}
+
+/// Why a match ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndReason {
+    /// One side lost all its depots.
+    Elimination,
+    /// A bot forfeited (timeouts, disconnect or protocol violation).
+    Forfeit,
+    /// The tick limit was reached.
+    TimeLimit,
+}
+
+/// Messages sent from a bot to the runner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BotMessage {
+    /// Reply to `hello`.
+    Handshake {
+        /// Protocol version the bot was written against.
+        api_version: u32,
+        /// Display name used in standings.
+        name: String,
+        /// Capabilities the bot wants.
+        #[serde(default)]
+        capabilities: Vec<Capability>,
+    },
+    /// Reply to `observation`.
+    Orders {
+        /// Unit orders to apply before the next ticks run. Orders with
+        /// `queue` set need `queued_orders`.
+        #[serde(default)]
+        orders: Vec<Command>,
+    },
+}
+
+/// Split a protocol command into the entity it orders, the order, and
+/// whether it is appended to the entity's orders.
+///
+/// Returns `None` for commands that are not unit orders (bots may not tick,
+/// spawn or cheat) and for targets outside the simulation's range.
+#[must_use]
+pub fn unit_order(command: &Command) -> Option<(EntityId, ApiCommand, bool)> {
+    let pos = |x: f64, y: f64| {
+        Some(Vec2Fixed::new(
+            Fixed::checked_from_num(x)?,
+            Fixed::checked_from_num(y)?,
+        ))
+    };
+    let (entity, order, queued) = match *command {
+        Command::Move {
+            entity_id,
+            target_x,
+            target_y,
+            queue,
+        } => (
+            entity_id,
+            ApiCommand::MoveTo(pos(target_x, target_y)?),
+            queue,
+        ),
+        Command::Attack {
+            entity_id,
+            target_id,
+            queue,
+            force,
+        } => {
+            let target = EntityId::from(target_id);
+            let order = if force {
+                ApiCommand::ForceAttack(target)
+            } else {
+                ApiCommand::Attack(target)
+            };
+            (entity_id, order, queue)
+        }
+        Command::AttackMove {
+            entity_id,
+            target_x,
+            target_y,
+            queue,
+        } => (
+            entity_id,
+            ApiCommand::AttackMove(pos(target_x, target_y)?),
+            queue,
+        ),
+        Command::Patrol {
+            entity_id,
+            target_x,
+            target_y,
+            queue,
+        } => (
+            entity_id,
+            ApiCommand::Patrol(pos(target_x, target_y)?),
+            queue,
+        ),
+        Command::AttackGround {
+            entity_id,
+            target_x,
+            target_y,
+        } => (
+            entity_id,
+            ApiCommand::AttackGround(pos(target_x, target_y)?),
+            false,
+        ),
+        Command::HoldPosition { entity_id } => (entity_id, ApiCommand::HoldPosition, false),
+        Command::Stop { entity_id } => (entity_id, ApiCommand::Stop, false),
+        _ => return None,
+    };
+    Some((EntityId::from(entity), order, queued))
+}
+
+impl RunnerMessage {
+    /// Serialize to a JSON line (with newline).
+    pub fn to_json_line(&self) -> String {
+        let mut json = serde_json::to_string(self).unwrap_or_default();
+        json.push('\n');
+        json
+    }
+}
+
+impl BotMessage {
+    /// Parse from a JSON line.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Result of a successful handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Negotiated {
+    /// Bot display name.
+    pub name: String,
+    /// Capabilities both sides agreed on.
+    pub capabilities: Vec<Capability>,
+}
+
+impl Negotiated {
+    /// Whether a capability was agreed on.
+    #[must_use]
+    pub fn has(&self, capability: Capability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+}
+
+/// Check a bot's handshake against what the runner offers.
+///
+/// # Errors
+///
+/// Returns an error if the message is not a handshake, the version differs,
+/// or the bot did not ask for [`Capability::Orders`].
+pub fn negotiate(offered: &[Capability], reply: BotMessage) -> Result<Negotiated, BotApiError> {
+    let BotMessage::Handshake {
+        api_version,
+        name,
+        capabilities,
+    } = reply
+    else {
+        return Err(BotApiError::Unexpected(
+            "expected handshake as first message".to_string(),
+        ));
+    };
+
+    if api_version != BOT_API_VERSION {
+        return Err(BotApiError::VersionMismatch {
+            bot: api_version,
+            runner: BOT_API_VERSION,
+        });
+    }
+    if !capabilities.contains(&Capability::Orders) {
+        return Err(BotApiError::MissingCapability(Capability::Orders));
+    }
+
+    let mut agreed: Vec<Capability> = capabilities
+        .into_iter()
+        .filter(|c| offered.contains(c))
+        .collect();
+    agreed.sort_unstable();
+    agreed.dedup();
+
+    Ok(Negotiated {
+        name,
+        capabilities: agreed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handshake(version: u32, caps: &[Capability]) -> BotMessage {
+        BotMessage::Handshake {
+            api_version: version,
+            name: "test".to_string(),
+            capabilities: caps.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_negotiate_intersects_capabilities() {
+        let negotiated = negotiate(
+            &[Capability::Orders, Capability::StateHash],
+            handshake(
+                BOT_API_VERSION,
+                &[Capability::QueuedOrders, Capability::Orders],
+            ),
+        )
+        .unwrap();
+        assert_eq!(negotiated.capabilities, vec![Capability::Orders]);
+        assert!(!negotiated.has(Capability::QueuedOrders));
+    }
+
+    #[test]
+    fn test_negotiate_rejects_version_mismatch() {
+        let err = negotiate(&Capability::ALL, handshake(99, &[Capability::Orders])).unwrap_err();
+        assert!(matches!(err, BotApiError::VersionMismatch { bot: 99, .. }));
+    }
+
+    #[test]
+    fn test_negotiate_requires_orders() {
+        let err = negotiate(&Capability::ALL, handshake(BOT_API_VERSION, &[])).unwrap_err();
+        assert!(matches!(
+            err,
+            BotApiError::MissingCapability(Capability::Orders)
+        ));
+    }
+
+    #[test]
+    fn test_parse_orders_message() {
+        let json = r#"{"type":"orders","orders":[{"cmd":"attack","entity_id":3,"target_id":7}]}"#;
+        let BotMessage::Orders { orders } = BotMessage::from_json(json).unwrap() else {
+            panic!("expected orders");
+        };
+        assert_eq!(orders.len(), 1);
+        let (entity, command, queued) = unit_order(&orders[0]).unwrap();
+        assert_eq!(entity, 3);
+        assert!(!queued);
+        assert!(matches!(command, ApiCommand::Attack(7)));
+    }
+
+    #[test]
+    fn test_unit_order_only_accepts_unit_orders() {
+        let queued = Command::AttackMove {
+            entity_id: 5,
+            target_x: 100.0,
+            target_y: 50.0,
+            queue: true,
+        };
+        assert!(matches!(
+            unit_order(&queued),
+            Some((5, ApiCommand::AttackMove(_), true))
+        ));
+        assert!(unit_order(&Command::Kill { entity_id: 5 }).is_none());
+        assert!(unit_order(&Command::Tick { count: 1 }).is_none());
+
+        let far = Command::Move {
+            entity_id: 5,
+            target_x: 1e30,
+            target_y: 0.0,
+            queue: false,
+        };
+        assert!(unit_order(&far).is_none());
+    }
+
+    #[test]
+    fn test_hello_serialization() {
+        let line = RunnerMessage::Hello {
+            api_version: BOT_API_VERSION,
+            faction: FactionId::Collegium,
+            capabilities: Capability::ALL.to_vec(),
+            tick_budget_ms: 50,
+            max_timeouts: 5,
+            decision_interval: 10,
+        }
+        .to_json_line();
+        assert!(line.ends_with('\n'));
+        assert!(line.contains(r#""type":"hello""#));
+        assert!(line.contains(r#""queued_orders""#));
+    }
+}
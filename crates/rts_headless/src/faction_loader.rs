@@ -3,63 +3,135 @@
 //! Loads faction definitions from RON files for use in automated testing.
 //! This allows tests to use real faction-specific units instead of generic hardcoded units.
 
-use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use rts_core::data::{BuildingData, FactionData, TechData, UnitData};
 use rts_core::factions::FactionId;
 
 /// Registry holding loaded faction data for headless testing.
-#[derive(Debug, Clone, Default)]
+///
+/// The registry is immutable once built and is meant to be shared behind an
+/// `Arc` (batch runs hand every game the same instance). Factions indexed
+/// from a directory are parsed lazily on first lookup, so factions no game
+/// uses are never parsed. Parsed data is cached for the registry's lifetime.
+#[derive(Debug, Default)]
 pub struct FactionRegistry {
-    factions: HashMap<FactionId, FactionData>,
+    sources: Vec<FactionSource>,
+}
+
+/// One faction definition, either preloaded or parsed on demand.
+#[derive(Debug)]
+struct FactionSource {
+    /// File to parse on first access (None for preloaded data).
+    path: Option<PathBuf>,
+    /// Parsed data; None inside the cell if parsing failed.
+    data: OnceLock<Option<FactionData>>,
+}
+
+impl FactionSource {
+    fn preloaded(data: FactionData) -> Self {
+        Self {
+            path: None,
+            data: OnceLock::from(Some(data)),
+        }
+    }
+
+    fn lazy(path: PathBuf) -> Self {
+        Self {
+            path: Some(path),
+            data: OnceLock::new(),
+        }
+    }
+
+    /// Parse on first access, logging (once) if the file is unusable.
+    fn data(&self) -> Option<&FactionData> {
+        self.data
+            .get_or_init(|| {
+                let path = self.path.as_deref()?;
+                match parse_faction_file(path) {
+                    Ok(data) => {
+                        tracing::debug!(faction = ?data.id, path = %path.display(), "Parsed faction data");
+                        Some(data)
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to load faction from {:?}: {}", path, e);
+                        None
+                    }
+                }
+            })
+            .as_ref()
+    }
+
+    fn parsed(&self) -> Option<&FactionData> {
+        self.data.get().and_then(Option::as_ref)
+    }
+
+    /// Whether the file name suggests this source holds `id`.
+    fn is_likely(&self, id: FactionId) -> bool {
+        self.path
+            .as_deref()
+            .and_then(Path::file_stem)
+            .is_some_and(|stem| stem.eq_ignore_ascii_case(id.short_name()))
+    }
+}
+
+/// Read and parse a faction RON file.
+fn parse_faction_file(path: &Path) -> Result<FactionData, FactionLoadError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| FactionLoadError::IoError(path.display().to_string(), e.to_string()))?;
+
+    ron::from_str(&content)
+        .map_err(|e| FactionLoadError::ParseError(path.display().to_string(), e.to_string()))
 }
 
 impl FactionRegistry {
     /// Create a new empty registry.
     pub fn new() -> Self {
         Self {
-            factions: HashMap::new(),
+            sources: Vec::new(),
         }
     }
 
+    /// Index every RON file in a directory without parsing it.
+    ///
+    /// Files are parsed the first time a lookup needs them.
+    pub fn lazy_from_directory(dir: &Path) -> Result<Self, FactionLoadError> {
+        let mut registry = Self::new();
+        for path in ron_files(dir)? {
+            registry.sources.push(FactionSource::lazy(path));
+        }
+        Ok(registry)
+    }
+
     /// Load faction data from a RON file.
     pub fn load_from_file(&mut self, path: &Path) -> Result<FactionId, FactionLoadError> {
-        let content = fs::read_to_string(path)
-            .map_err(|e| FactionLoadError::IoError(path.display().to_string(), e.to_string()))?;
-
-        let data: FactionData = ron::from_str(&content)
-            .map_err(|e| FactionLoadError::ParseError(path.display().to_string(), e.to_string()))?;
-
+        let data = parse_faction_file(path)?;
         let id = data.id;
-        self.factions.insert(id, data);
+        self.insert(data);
         Ok(id)
     }
 
-    /// Load all factions from a directory.
+    /// Add faction data, replacing any already parsed for the same faction.
+    ///
+    /// Lookups prefer it over unparsed files of the same faction.
+    pub fn insert(&mut self, data: FactionData) {
+        let id = data.id;
+        self.sources
+            .retain(|s| s.parsed().map(|d| d.id) != Some(id));
+        self.sources.push(FactionSource::preloaded(data));
+    }
+
+    /// Load all factions from a directory, parsing every file up front.
     pub fn load_from_directory(&mut self, dir: &Path) -> Result<Vec<FactionId>, FactionLoadError> {
         let mut loaded = Vec::new();
 
-        if !dir.exists() {
-            return Err(FactionLoadError::DirectoryNotFound(
-                dir.display().to_string(),
-            ));
-        }
-
-        for entry in fs::read_dir(dir)
-            .map_err(|e| FactionLoadError::IoError(dir.display().to_string(), e.to_string()))?
-        {
-            let entry = entry
-                .map_err(|e| FactionLoadError::IoError(dir.display().to_string(), e.to_string()))?;
-            let path = entry.path();
-
-            if path.extension().map(|e| e == "ron").unwrap_or(false) {
-                match self.load_from_file(&path) {
-                    Ok(id) => loaded.push(id),
-                    Err(e) => {
-                        tracing::warn!("Failed to load faction from {:?}: {}", path, e);
-                    }
+        for path in ron_files(dir)? {
+            match self.load_from_file(&path) {
+                Ok(id) => loaded.push(id),
+                Err(e) => {
+                    tracing::warn!("Failed to load faction from {:?}: {}", path, e);
                 }
             }
         }
@@ -67,60 +139,71 @@ impl FactionRegistry {
         Ok(loaded)
     }
 
-    /// Get faction data by ID.
+    /// Get faction data by ID, parsing it on first use.
+    ///
+    /// Already-parsed factions are checked first, then the file named after
+    /// the faction, then any remaining unparsed files in name order.
     pub fn get(&self, id: FactionId) -> Option<&FactionData> {
-        self.factions.get(&id)
+        let matches = |data: &&FactionData| data.id == id;
+        if let Some(data) = self
+            .sources
+            .iter()
+            .filter_map(FactionSource::parsed)
+            .find(matches)
+        {
+            return Some(data);
+        }
+        let (likely, rest): (Vec<_>, Vec<_>) = self
+            .sources
+            .iter()
+            .filter(|s| s.data.get().is_none())
+            .partition(|s| s.is_likely(id));
+        likely
+            .into_iter()
+            .chain(rest)
+            .filter_map(FactionSource::data)
+            .find(matches)
     }
 
     /// Get a unit definition from a faction.
     pub fn get_unit(&self, faction: FactionId, unit_id: &str) -> Option<&UnitData> {
-        self.factions
-            .get(&faction)
-            .and_then(|f| f.get_unit(unit_id))
+        self.get(faction).and_then(|f| f.get_unit(unit_id))
     }
 
     /// Get a building definition from a faction.
     pub fn get_building(&self, faction: FactionId, building_id: &str) -> Option<&BuildingData> {
-        self.factions
-            .get(&faction)
-            .and_then(|f| f.get_building(building_id))
+        self.get(faction).and_then(|f| f.get_building(building_id))
     }
 
     /// Get a technology definition from a faction.
     pub fn get_technology(&self, faction: FactionId, tech_id: &str) -> Option<&TechData> {
-        self.factions
-            .get(&faction)
-            .and_then(|f| f.get_technology(tech_id))
+        self.get(faction).and_then(|f| f.get_technology(tech_id))
     }
 
     /// Get all tier 1 technologies for a faction.
     pub fn tier1_technologies(&self, faction: FactionId) -> Vec<&TechData> {
-        self.factions
-            .get(&faction)
+        self.get(faction)
             .map(|f| f.technologies_at_tier(1).collect())
             .unwrap_or_default()
     }
 
     /// Get all units for a faction at a specific tier.
     pub fn units_at_tier(&self, faction: FactionId, tier: u8) -> Vec<&UnitData> {
-        self.factions
-            .get(&faction)
+        self.get(faction)
             .map(|f| f.units_at_tier(tier).collect())
             .unwrap_or_default()
     }
 
     /// Get all combatant units for a faction (units with combat stats).
     pub fn combatant_units(&self, faction: FactionId) -> Vec<&UnitData> {
-        self.factions
-            .get(&faction)
+        self.get(faction)
             .map(|f| f.units.iter().filter(|u| u.is_combatant()).collect())
             .unwrap_or_default()
     }
 
     /// Get harvester units for a faction.
     pub fn harvester_units(&self, faction: FactionId) -> Vec<&UnitData> {
-        self.factions
-            .get(&faction)
+        self.get(faction)
             .map(|f| f.units.iter().filter(|u| u.has_tag("harvester")).collect())
             .unwrap_or_default()
     }
@@ -129,7 +212,7 @@ impl FactionRegistry {
     /// This allows strategies to use generic role names that map to faction-specific units.
     /// Returns the first matching unit at the lowest tier if multiple units have the same tag.
     pub fn get_unit_by_role(&self, faction: FactionId, role: &str) -> Option<&UnitData> {
-        self.factions.get(&faction).and_then(|f| {
+        self.get(faction).and_then(|f| {
             f.units
                 .iter()
                 .filter(|u| u.has_tag(role))
@@ -137,22 +220,54 @@ impl FactionRegistry {
         })
     }
 
-    /// Check if a faction is loaded.
+    /// Check if a faction is available, parsing it if needed.
     pub fn has_faction(&self, id: FactionId) -> bool {
-        self.factions.contains_key(&id)
+        self.get(id).is_some()
     }
 
-    /// Get all loaded faction IDs.
+    /// Get the IDs of all factions parsed so far.
     pub fn loaded_factions(&self) -> Vec<FactionId> {
-        self.factions.keys().copied().collect()
+        self.sources
+            .iter()
+            .filter_map(FactionSource::parsed)
+            .map(|d| d.id)
+            .collect()
     }
 
-    /// Get the number of loaded factions.
+    /// Get the number of factions parsed so far.
     pub fn faction_count(&self) -> usize {
-        self.factions.len()
+        self.sources.iter().filter(|s| s.parsed().is_some()).count()
+    }
+
+    /// Get the number of faction files known, parsed or not.
+    pub fn source_count(&self) -> usize {
+        self.sources.len()
     }
 }
 
+/// List the RON files in a directory, sorted by path for determinism.
+fn ron_files(dir: &Path) -> Result<Vec<PathBuf>, FactionLoadError> {
+    if !dir.exists() {
+        return Err(FactionLoadError::DirectoryNotFound(
+            dir.display().to_string(),
+        ));
+    }
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)
+        .map_err(|e| FactionLoadError::IoError(dir.display().to_string(), e.to_string()))?
+    {
+        let entry = entry
+            .map_err(|e| FactionLoadError::IoError(dir.display().to_string(), e.to_string()))?;
+        let path = entry.path();
+        if path.extension().map(|e| e == "ron").unwrap_or(false) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
 /// Errors that can occur during faction loading.
 #[derive(Debug, Clone)]
 pub enum FactionLoadError {
@@ -208,23 +323,23 @@ pub fn default_faction_data_dir() -> Option<std::path::PathBuf> {
     None
 }
 
-/// Load all available factions from the default directory.
+/// Index all available factions from the default directory.
+///
+/// Factions are parsed lazily; see [`FactionRegistry::lazy_from_directory`].
 pub fn load_all_factions() -> Result<FactionRegistry, FactionLoadError> {
     let dir = default_faction_data_dir()
         .ok_or_else(|| FactionLoadError::DirectoryNotFound("faction data directory".to_string()))?;
 
-    let mut registry = FactionRegistry::new();
-    registry.load_from_directory(&dir)?;
-    Ok(registry)
+    FactionRegistry::lazy_from_directory(&dir)
 }
 
-/// Load all factions from a specified directory path.
+/// Index all factions in a specified directory path.
+///
+/// Factions are parsed lazily; see [`FactionRegistry::lazy_from_directory`].
 pub fn load_factions_from_path<P: AsRef<Path>>(
     path: P,
 ) -> Result<FactionRegistry, FactionLoadError> {
-    let mut registry = FactionRegistry::new();
-    registry.load_from_directory(path.as_ref())?;
-    Ok(registry)
+    FactionRegistry::lazy_from_directory(path.as_ref())
 }
 
 #[cfg(test)]
@@ -237,6 +352,46 @@ mod tests {
         assert!(registry.loaded_factions().is_empty());
     }
 
+    fn faction_data_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../rts_game/assets/data/factions")
+    }
+
+    #[test]
+    fn test_lazy_registry_parses_only_requested_factions() {
+        let registry = FactionRegistry::lazy_from_directory(&faction_data_dir()).unwrap();
+        assert!(registry.source_count() >= 2);
+        assert_eq!(registry.faction_count(), 0);
+
+        let continuity = registry.get(FactionId::Continuity).unwrap();
+        assert_eq!(continuity.id, FactionId::Continuity);
+        assert_eq!(registry.loaded_factions(), vec![FactionId::Continuity]);
+
+        // Cached: a second lookup parses nothing new
+        assert!(registry
+            .get_unit_by_role(FactionId::Continuity, "infantry")
+            .is_some());
+        assert_eq!(registry.faction_count(), 1);
+    }
+
+    #[test]
+    fn test_shared_registry_parses_each_faction_once() {
+        let registry =
+            std::sync::Arc::new(FactionRegistry::lazy_from_directory(&faction_data_dir()).unwrap());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let registry = std::sync::Arc::clone(&registry);
+                std::thread::spawn(move || {
+                    registry.has_faction(FactionId::Continuity)
+                        && registry.has_faction(FactionId::Collegium)
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+        assert_eq!(registry.faction_count(), 2);
+    }
+
     #[test]
     fn test_default_faction_dir_resolution() {
         // This test may pass or fail depending on working directory
@@ -11,24 +11,61 @@
 //! - Failure modes are explicit, not silent
 //! - Resource usage is tracked and reported
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info, trace, warn};
 
-use rts_core::components::{CombatStats, Command, EntityId, FactionMember};
+use rts_core::autosave::{Autosave, AutosaveConfig, AutosaveRotation};
+use rts_core::buildings::{BuildingFootprint, ConstructionEvent};
+use rts_core::capacity::EntityCapacity;
+use rts_core::components::{
+    CombatStats, Command, EntityId, FactionMember, IdleBehavior, Regeneration, Resupply,
+};
 use rts_core::data::UnitData;
+use rts_core::difficulty::{Handicap, Reaction};
+use rts_core::economy::{
+    economy_system_in_range, EconomyEvent, Harvester, HarvesterState, PlayerEconomy,
+    ResourceAmounts, ResourceKind, ResourceNode, Upkeep, UpkeepModel, HEAVY_UNIT_SUPPLY,
+    LIGHT_UNIT_SUPPLY,
+};
 use rts_core::factions::FactionId;
+use rts_core::fingerprint::Fingerprint;
+use rts_core::influence::{self, InfluenceMap};
+use rts_core::map_generation::{generate_map, GeneratedMap};
+use rts_core::market::{Market, MarketRules};
 use rts_core::math::{Fixed, Vec2Fixed};
-use rts_core::simulation::{EntitySpawnParams, Simulation};
+use rts_core::outcome::WinCondition;
+use rts_core::player_facade::VisibleEnemy;
+use rts_core::repair::{RepairKind, Repairer};
+use rts_core::research::{ResearchEvent, ResearchFacility, TechProfile};
+use rts_core::salvage::{salvage_rate_for_tier, salvage_value, SalvageEvent};
+use rts_core::simulation::{EntityRef, EntitySpawnParams, Simulation, TICK_RATE};
+use rts_core::squad::{SquadCommand, SquadId};
+use rts_core::stats::is_army_unit;
+use rts_core::systems::DamageEvent;
+use rts_core::transport::Cargo;
+use rts_core::triggers::{ScenarioScript, TriggerAction, TriggerFired};
 
 use crate::faction_loader::FactionRegistry;
-use crate::metrics::{EventType, FactionMetrics, GameMetrics, TimedEvent};
-use crate::scenario::Scenario;
+use crate::intel::{Intel, Sighting};
+use crate::metrics::{
+    EventType, FactionMetrics, GameMetrics, MetricsSample, TimedEvent, UnitKindMetrics,
+};
+use crate::observer::{GameObserver, PlayerView};
+use crate::personality::{AiPersonality, TauntTrigger};
+use crate::projection::{win_probability, ProjectionConfig, Projector, SideStrength};
+use crate::scenario::{FactionSetup, LogisticsConfig, Scenario};
 use crate::screenshot::{
-    ScreenshotConfig, ScreenshotManager, ScreenshotTrigger, UnitVisual, VisualState,
+    BuildingVisual, ScreenshotConfig, ScreenshotManager, ScreenshotTrigger, UnitVisual, VisualState,
 };
-use crate::strategies::{BuildOrderItem, Strategy, StrategyExecutor, TacticalDecision};
+use crate::strategies::{
+    BuildOrderItem, BuildPriority, ReservationEvent, Strategy, StrategyExecutor, TacticalDecision,
+};
+use crate::tactics::{BehaviorTree, Threat, UnitOrder, UnitView};
 
 /// High-level game runner for headless testing.
 ///
@@ -67,10 +104,14 @@ pub struct GameConfig {
     pub max_ticks: u64,
     /// Scenario to use.
     pub scenario: Scenario,
-    /// Strategy for faction A (Continuity).
+    /// Strategy for faction A, the scenario's first faction.
     pub strategy_a: Strategy,
-    /// Strategy for faction B (Collegium).
+    /// Strategy for faction B, the scenario's second faction.
     pub strategy_b: Strategy,
+    /// Handicap for faction A's AI (see [`Difficulty`](rts_core::difficulty::Difficulty)).
+    pub handicap_a: Handicap,
+    /// Handicap for faction B's AI.
+    pub handicap_b: Handicap,
     /// Screenshot configuration.
     pub screenshot_config: Option<ScreenshotConfig>,
     /// Game ID for tracking.
@@ -78,13 +119,83 @@ pub struct GameConfig {
     /// Optional faction registry for data-driven unit stats.
     /// If None, falls back to hardcoded generic units.
     pub faction_registry: Option<Arc<FactionRegistry>>,
+    /// Periodic autosaves for resuming long games (see [`resume_game`]).
+    pub autosave: Option<AutosaveConfig>,
+    /// Post AI personality chat lines (see [`crate::personality`]).
+    pub chat: bool,
+    /// Observers called after every tick and at game end.
+    pub observers: Vec<Box<dyn GameObserver>>,
+    /// Stops the game early on a wall-clock limit or cancellation.
+    pub stop: StopSignal,
+    /// End the game once its winner looks certain (None = play it out).
+    pub projection: Option<ProjectionConfig>,
+    /// Ticks between time-series samples of each faction's army, income,
+    /// units and map control (None = no sampling).
+    pub sample_interval: Option<u64>,
+}
+
+/// Why a game was stopped before it ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    /// The game ran past its wall-clock time limit.
+    TimedOut,
+    /// The game's cancel flag was raised, e.g. by Ctrl+C.
+    Cancelled,
+}
+
+/// Tells a running game to stop early: once a wall-clock deadline passes,
+/// or when a shared cancel flag is raised. Checked before every tick; the
+/// default never stops.
+#[derive(Debug, Clone, Default)]
+pub struct StopSignal {
+    deadline: Option<Instant>,
+    cancel: Option<Arc<AtomicBool>>,
+}
+
+impl StopSignal {
+    /// Stop once `limit` has passed from now.
+    #[must_use]
+    pub fn with_timeout(mut self, limit: Duration) -> Self {
+        self.deadline = Some(Instant::now() + limit);
+        self
+    }
+
+    /// Stop when `flag` is set.
+    #[must_use]
+    pub fn with_cancel(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(flag);
+        self
+    }
+
+    /// Why the game should stop now, if it should.
+    #[must_use]
+    pub fn check(&self) -> Option<StopReason> {
+        if self
+            .cancel
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+        {
+            Some(StopReason::Cancelled)
+        } else if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            Some(StopReason::TimedOut)
+        } else {
+            None
+        }
+    }
 }
 
 /// State for one player in the game.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct PlayerState {
     faction_id: FactionId,
     executor: StrategyExecutor,
+    /// Scenario personality layered over the strategy, if any.
+    #[serde(default)]
+    personality: Option<AiPersonality>,
     resources: i64,
     depot_entity: Option<EntityId>,
     units: Vec<EntityId>,
@@ -96,20 +207,127 @@ struct PlayerState {
     buildings_lost: HashMap<String, u32>,
     total_damage_dealt: i64,
     total_damage_taken: i64,
+    /// Health restored by out-of-combat regeneration.
+    #[serde(default)]
+    total_health_regenerated: i64,
     first_attack_tick: Option<u64>,
+    /// Tick the first base beyond the starting one was founded.
+    #[serde(default)]
+    first_expansion_tick: Option<u64>,
+    /// Bases founded beyond the starting one, keyed by depot.
+    #[serde(default)]
+    expansions: BTreeMap<EntityId, Expansion>,
     peak_army_size: u32,
-    /// Technologies that have been fully researched.
-    researched_techs: HashSet<String>,
-    /// Current research in progress: (tech_id, ticks_remaining).
-    current_research: Option<(String, u64)>,
-    /// Track unit kinds by entity ID for salvage calculation.
+    /// Tick each technology finished researching.
+    #[serde(default)]
+    tech_unlock_times: HashMap<String, u64>,
+    /// Track unit kinds by entity ID.
     unit_kinds: HashMap<EntityId, String>,
+    /// Tick each living unit was fielded.
+    #[serde(default)]
+    unit_fielded_at: HashMap<EntityId, u64>,
+    /// Kind of the enemy unit that last hit each of our units.
+    #[serde(default)]
+    last_hit_by: HashMap<EntityId, String>,
+    /// Combat record of each unit kind.
+    #[serde(default)]
+    unit_stats: BTreeMap<String, UnitKindMetrics>,
+    /// Track building kinds by entity ID for supply calculation.
+    #[serde(default)]
+    building_kinds: HashMap<EntityId, String>,
+    /// Harvester state machines, keyed by unit.
+    #[serde(default)]
+    harvesters: BTreeMap<EntityId, Harvester>,
     /// Resources gained from passive income (harvest simulation).
     resources_from_harvest: i64,
     /// Resources gained from salvaging enemy wrecks.
     resources_from_salvage: i64,
     /// Salvage value given to enemy when our units died.
     salvage_given_to_enemy: i64,
+    /// Units pulled out of the fight to refill ammunition.
+    resupplying: BTreeSet<EntityId>,
+    /// Times a unit was rotated out to resupply.
+    resupply_trips: u32,
+    /// Unit-ticks spent resupplying.
+    ticks_resupplying: u64,
+    /// What the player remembers of its enemies.
+    #[serde(default)]
+    intel: Intel,
+    /// Squad holding the main army.
+    #[serde(default)]
+    army_squad: Option<SquadId>,
+    /// Squad holding units detached to scout.
+    #[serde(default)]
+    scout_squad: Option<SquadId>,
+    /// Index of the next waypoint in the search sweep (see [`search_waypoint`]).
+    #[serde(default)]
+    search_step: u32,
+    /// Own and enemy strength by region, refreshed every
+    /// [`THREAT_MAP_INTERVAL`] ticks.
+    #[serde(default)]
+    threat_map: InfluenceMap,
+    /// What the faction's army costs to keep, from its data.
+    #[serde(default)]
+    upkeep_model: UpkeepModel,
+    /// Army upkeep charged so far.
+    #[serde(default)]
+    upkeep: Upkeep,
+    /// Resources beyond feedstock on hand, such as exotics.
+    #[serde(default)]
+    reserves: ResourceAmounts,
+    /// Resources beyond feedstock gathered so far.
+    #[serde(default)]
+    reserves_gathered: ResourceAmounts,
+    /// Rarer resources the last purchase held back was waiting on;
+    /// harvesters go after them until they are covered.
+    #[serde(default)]
+    reserves_needed: ResourceAmounts,
+    /// Purchases held back for lack of each rarer resource.
+    #[serde(default)]
+    resource_shortfalls: BTreeMap<ResourceKind, u32>,
+    /// Exchange rates at the faction's markets, from its data.
+    #[serde(default)]
+    market_rules: MarketRules,
+    /// Volume traded in the current market window.
+    #[serde(default)]
+    market: Market,
+    /// Tick of the last trade.
+    #[serde(default)]
+    last_trade_tick: Option<u64>,
+    /// Trades made at markets.
+    #[serde(default)]
+    trades: u32,
+    /// Feedstock value sold at markets.
+    #[serde(default)]
+    trade_value_sold: i64,
+    /// Feedstock value bought at markets.
+    #[serde(default)]
+    trade_value_bought: i64,
+    /// How much weaker or stronger this AI plays than designed.
+    #[serde(default)]
+    handicap: Handicap,
+    /// How long enemies have been in sight, for the reaction delay.
+    #[serde(default)]
+    reaction: Reaction,
+    /// Time series sampled so far.
+    #[serde(default)]
+    timeline: Timeline,
+    /// Events logged during the AI's turn, collected into the game's log
+    /// after it.
+    #[serde(default)]
+    pending_events: Vec<TimedEvent>,
+}
+
+/// A player's time series, sampled every [`GameConfig::sample_interval`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Timeline {
+    samples: Vec<MetricsSample>,
+    /// Map control as (tick, percent of regions held).
+    map_control: Vec<(u64, f64)>,
+    /// Mean army position as (tick, x, y), only while there is an army.
+    army_positions: Vec<(u64, f64, f64)>,
+    /// Resources gathered as of the last sample, for the income rate.
+    gathered: i64,
 }
 
 impl PlayerState {
@@ -118,6 +336,7 @@ impl PlayerState {
         Self {
             faction_id,
             executor,
+            personality: None,
             resources: 1000,
             depot_entity: None,
             units: Vec::new(),
@@ -129,17 +348,107 @@ impl PlayerState {
             buildings_lost: HashMap::new(),
             total_damage_dealt: 0,
             total_damage_taken: 0,
+            total_health_regenerated: 0,
             first_attack_tick: None,
+            first_expansion_tick: None,
+            expansions: BTreeMap::new(),
             peak_army_size: 0,
-            researched_techs: HashSet::new(),
-            current_research: None,
+            tech_unlock_times: HashMap::new(),
             unit_kinds: HashMap::new(),
+            unit_fielded_at: HashMap::new(),
+            last_hit_by: HashMap::new(),
+            unit_stats: BTreeMap::new(),
+            building_kinds: HashMap::new(),
+            harvesters: BTreeMap::new(),
             resources_from_harvest: 0,
             resources_from_salvage: 0,
             salvage_given_to_enemy: 0,
+            resupplying: BTreeSet::new(),
+            resupply_trips: 0,
+            ticks_resupplying: 0,
+            intel: Intel::default(),
+            army_squad: None,
+            scout_squad: None,
+            search_step: 0,
+            threat_map: InfluenceMap::default(),
+            upkeep_model: UpkeepModel::default(),
+            upkeep: Upkeep::default(),
+            reserves: ResourceAmounts::new(),
+            reserves_gathered: ResourceAmounts::new(),
+            reserves_needed: ResourceAmounts::new(),
+            resource_shortfalls: BTreeMap::new(),
+            market_rules: MarketRules::default(),
+            market: Market::new(),
+            last_trade_tick: None,
+            trades: 0,
+            trade_value_sold: 0,
+            trade_value_bought: 0,
+            handicap: Handicap::NONE,
+            reaction: Reaction::default(),
+            timeline: Timeline::default(),
+            pending_events: Vec::new(),
+        }
+    }
+
+    /// This player with its AI handicapped, strategy included.
+    fn with_handicap(mut self, handicap: Handicap) -> Self {
+        let strategy = self.executor.strategy().clone().with_handicap(&handicap);
+        self.executor = StrategyExecutor::new(strategy);
+        self.handicap = handicap;
+        self
+    }
+
+    /// What observers see of this player.
+    fn view(&self) -> PlayerView<'_> {
+        PlayerView {
+            faction: self.faction_id,
+            resources: self.resources,
+            unit_kinds: &self.unit_kinds,
+            building_kinds: &self.building_kinds,
         }
     }
 
+    /// Check the rarer resources a purchase needs beside feedstock. When
+    /// short, remember them so harvesters go after them, and count the
+    /// shortfall.
+    fn afford_extra(&mut self, extra: &ResourceAmounts) -> bool {
+        let Some(kind) = self.reserves.shortfall(extra) else {
+            return true;
+        };
+        self.reserves_needed = extra.clone();
+        *self.resource_shortfalls.entry(kind).or_default() += 1;
+        false
+    }
+
+    /// Pay for a purchase checked with [`Self::afford_extra`].
+    fn pay(&mut self, cost: i64, extra: &ResourceAmounts) {
+        self.resources -= cost;
+        let paid = self.reserves.spend(extra);
+        debug_assert!(paid.is_ok(), "rarer resources are checked before buying");
+    }
+
+    /// Take a newly spawned unit into the army, recording what it cost.
+    fn field_unit(&mut self, entity_id: EntityId, kind: String, tick: u64, cost: i64) {
+        self.units.push(entity_id);
+        self.unit_kinds.insert(entity_id, kind.clone());
+        self.unit_fielded_at.insert(entity_id, tick);
+        let stats = self.unit_stats.entry(kind.clone()).or_default();
+        stats.fielded += 1;
+        stats.resources_spent += cost;
+        *self.units_produced.entry(kind).or_insert(0) += 1;
+    }
+
+    /// Take a unit the AI paid for into the army and log its production.
+    fn produce_unit(&mut self, entity_id: EntityId, kind: String, tick: u64, cost: i64) {
+        self.pending_events.push(TimedEvent {
+            tick,
+            event_type: EventType::UnitProduced,
+            faction: faction_key(self.faction_id),
+            details: kind.clone(),
+        });
+        self.field_unit(entity_id, kind, tick, cost);
+    }
+
     /// Update peak army size.
     fn update_peak_army(&mut self) {
         let current = self.units.len() as u32;
@@ -154,6 +463,9 @@ impl PlayerState {
 pub struct GameResult {
     pub metrics: GameMetrics,
     pub final_state_hash: u64,
+    /// Set when the game was stopped before it ended; the metrics then
+    /// cover only the ticks played.
+    pub stopped: Option<StopReason>,
 }
 
 // =============================================================================
@@ -174,15 +486,6 @@ const MAX_EVENTS: usize = 100_000;
 /// Log every N ticks so we can see the game is making progress.
 const PROGRESS_LOG_INTERVAL: u64 = 1000;
 
-// =============================================================================
-// ECONOMY LIMITS (game balance)
-// =============================================================================
-
-/// Maximum units per player (supply cap).
-/// This is a fundamental RTS mechanic - you can't just build infinite units.
-/// 200 is standard for most RTS games (StarCraft, C&C, etc.)
-const MAX_SUPPLY_PER_PLAYER: usize = 200;
-
 // =============================================================================
 // WATCHDOG TIMEOUTS (detecting hangs, not game duration)
 // =============================================================================
@@ -197,57 +500,98 @@ const TICK_TIMEOUT_MS: u128 = 5_000;
 const SLOW_TICK_THRESHOLD_MS: u128 = 100;
 
 // =============================================================================
-// SALVAGE SYSTEM CONSTANTS
+// HARVESTING CONSTANTS
 // =============================================================================
 
-/// Radius within which units can collect salvage from wrecks.
-const SALVAGE_RADIUS: f32 = 100.0;
+/// Feedstock a harvester carries per trip.
+const HARVESTER_CAPACITY: i32 = 50;
 
-/// Percentage of unit cost that becomes salvageable.
-const SALVAGE_PERCENT: f32 = 0.25;
+/// Feedstock a harvester gathers per harvest action at a node.
+const HARVESTER_GATHER_RATE: i32 = 1;
 
-/// How long wrecks persist before despawning (ticks). 600 = 10 seconds at 60 TPS.
-const WRECK_LIFETIME: u64 = 600;
+/// Ticks between harvest actions. At 60 TPS a harvester parked on a node
+/// gathers 10 feedstock per second; travel roughly halves that.
+const HARVEST_INTERVAL: u64 = 6;
+
+/// Distance within which harvesters gather from a node or unload at a
+/// depot. Local avoidance keeps units from reaching exact centres.
+const HARVEST_RANGE: i32 = 24;
+
+/// How often (ticks) harvesters that stopped short re-issue their move.
+const HARVESTER_REPATH_INTERVAL: u64 = 60;
 
 /// Threshold below which we consider economy "tight" and prefer cheap units.
 const ECONOMY_TIGHT_THRESHOLD: i64 = 100;
 
+/// How far beyond weapon range (as a multiple) siege units will move to
+/// shell a remembered enemy structure.
+const SHELLING_REACH_FACTOR: i32 = 2;
+
 /// Threshold above which we consider economy "comfortable" for any unit.
 /// Reserved for future tier-gating logic.
 #[allow(dead_code)]
 const ECONOMY_COMFORTABLE_THRESHOLD: i64 = 300;
 
-/// Salvage collection rate multiplier based on unit tier.
-/// Tier 1 = 1 resource/tick, Tier 2 = 2/tick, Tier 3 = 4/tick
-fn salvage_rate_for_tier(tier: u32) -> i64 {
-    match tier {
-        1 => 1,
-        2 => 2,
-        3 => 4,
-        _ => 1,
-    }
+/// How often (ticks) resupplying units re-path to the nearest source and
+/// supply units move up to the army.
+const RESUPPLY_REPATH_INTERVAL: u64 = 60;
+
+/// How often (ticks) each AI recomputes its threat map.
+const THREAT_MAP_INTERVAL: u64 = 40;
+
+/// Ore a node must still hold to be worth founding a base at.
+const EXPANSION_MIN_ORE: i32 = 1000;
+
+/// Distance within which a depot serves an ore node; nodes farther than
+/// this from all of a player's depots need a base of their own.
+const DEPOT_SERVICE_RANGE: i32 = 150;
+
+/// How close a new base may be to an enemy structure the AI knows of.
+const EXPANSION_ENEMY_CLEARANCE: i32 = 150;
+
+/// Ore an expansion site is worth less per unit of distance from home.
+const EXPANSION_TRAVEL_COST: i64 = 10;
+
+/// Harvesters moved over to a new base once it is finished.
+const EXPANSION_HARVESTERS: usize = 2;
+
+/// Distance from an expansion within which enemies in sight count as
+/// attacking it.
+const EXPANSION_DEFEND_RADIUS: i32 = 250;
+
+/// A base the AI founded at an ore node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Expansion {
+    /// Index of the ore node it was founded at.
+    node: usize,
+    /// Tick construction started.
+    founded_at: u64,
+    /// Whether harvesters were sent over once it finished.
+    staffed: bool,
 }
 
-/// Represents a wreck that can be salvaged.
-#[derive(Debug, Clone)]
-struct WreckState {
-    /// Position of the wreck.
-    position: (f32, f32),
-    /// Remaining salvage value (resources).
-    salvage_remaining: i64,
-    /// Tick when the wreck was created.
-    spawn_tick: u64,
-    /// Unit kind for logging purposes.
-    unit_kind: String,
-}
-
-/// Tracks an active salvage operation by a unit.
-#[derive(Debug, Clone)]
-struct SalvageAction {
-    /// Index of the wreck being salvaged.
-    wreck_index: usize,
-    /// Ticks spent salvaging so far.
-    ticks_salvaging: u64,
+/// Runner-side state carried between ticks, alongside the simulation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RunnerState {
+    rng: SimpleRng,
+    player_a: PlayerState,
+    player_b: PlayerState,
+    events: Vec<TimedEvent>,
+    /// Ore nodes, identified by their index in the scenario.
+    #[serde(default)]
+    resource_nodes: Vec<ResourceNode>,
+}
+
+/// Host state stored in headless autosaves: enough of the [`GameConfig`]
+/// to rebuild it, plus the runner state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedGame {
+    seed: u64,
+    max_ticks: u64,
+    scenario: Scenario,
+    strategy_a: Strategy,
+    strategy_b: Strategy,
+    runner: RunnerState,
 }
 
 /// Run a complete game simulation.
@@ -267,19 +611,51 @@ pub fn run_game(config: GameConfig) -> GameResult {
         "Starting game simulation"
     );
 
-    let mut sim = Simulation::new();
-    let mut rng = SimpleRng::new(config.seed);
+    let mut sim = match &config.scenario.map {
+        Some(map) => simulation_on_map(&generate_map(map.clone())),
+        None => Simulation::new(),
+    };
+    sim.seed_rng(config.seed);
+    sim.set_entity_capacity(Some(EntityCapacity::new(MAX_ENTITIES)));
+    let rng = SimpleRng::new(config.seed);
 
     // Get faction registry reference for spawn functions
     let registry = config.faction_registry.as_deref();
 
-    // Set up initial state from scenario
-    let mut player_a = PlayerState::new(FactionId::Continuity, config.strategy_a.clone());
-    let mut player_b = PlayerState::new(FactionId::Collegium, config.strategy_b.clone());
+    // Set up initial state from scenario: player A plays its first faction,
+    // player B its second
+    let faction_at = |index: usize, fallback: FactionId| {
+        config
+            .scenario
+            .factions
+            .get(index)
+            .map_or(fallback, FactionSetup::faction)
+    };
+    let mut player_a = PlayerState::new(
+        faction_at(0, FactionId::Continuity),
+        config.strategy_a.clone(),
+    )
+    .with_handicap(config.handicap_a);
+    let mut player_b = PlayerState::new(
+        faction_at(1, FactionId::Collegium),
+        config.strategy_b.clone(),
+    )
+    .with_handicap(config.handicap_b);
+
+    for player in [&mut player_a, &mut player_b] {
+        let faction = player.faction_id;
+        register_tech_tree(&mut sim, faction, registry, &config.scenario);
+        if let Some(data) = registry.and_then(|reg| reg.get(faction)) {
+            sim.set_faction_mechanics(faction, data.mechanics.clone());
+            player.upkeep_model = data.upkeep.clone();
+            player.upkeep = Upkeep::new(&player.upkeep_model);
+            player.market_rules = data.market.clone();
+        }
+    }
 
     // Spawn initial entities for each faction from scenario
-    for faction_setup in &config.scenario.factions {
-        let player = if faction_setup.faction_id == "continuity" {
+    for (index, faction_setup) in config.scenario.factions.iter().enumerate() {
+        let player = if index == 0 {
             &mut player_a
         } else {
             &mut player_b
@@ -288,6 +664,18 @@ pub fn run_game(config: GameConfig) -> GameResult {
         // Set starting resources
         player.resources = faction_setup.starting_resources;
 
+        // Layer the scenario's personality over the configured strategy
+        if let Some(personality) = &faction_setup.personality {
+            let base = if index == 0 {
+                &config.strategy_a
+            } else {
+                &config.strategy_b
+            };
+            let shaped = personality.shape_strategy(base.clone(), config.seed);
+            player.executor = StrategyExecutor::new(shaped.with_handicap(&player.handicap));
+            player.personality = Some(personality.clone());
+        }
+
         // Spawn depot/command center
         for building in &faction_setup.starting_buildings {
             let entity_id = spawn_building_with_registry(
@@ -297,12 +685,13 @@ pub fn run_game(config: GameConfig) -> GameResult {
                 building.position.1,
                 player.faction_id,
                 registry,
+                &config.scenario,
             );
             player.buildings.push(entity_id);
-            if matches!(
-                building.kind.as_str(),
-                "command_center" | "depot" | "administration_center"
-            ) {
+            player
+                .building_kinds
+                .insert(entity_id, building.kind.clone());
+            if sim.get_entity(entity_id).is_some_and(|e| e.depot.is_some()) {
                 player.depot_entity = Some(entity_id);
             }
             *player
@@ -314,17 +703,14 @@ pub fn run_game(config: GameConfig) -> GameResult {
         // Spawn initial units
         for unit_spawn in &faction_setup.starting_units {
             for _ in 0..unit_spawn.count {
-                let (entity_id, resolved_name) = spawn_unit_with_registry(
+                spawn_player_unit(
                     &mut sim,
+                    player,
                     &unit_spawn.kind,
-                    unit_spawn.position.0,
-                    unit_spawn.position.1,
-                    player.faction_id,
+                    unit_spawn.position,
                     registry,
+                    &config.scenario,
                 );
-                player.units.push(entity_id);
-                player.unit_kinds.insert(entity_id, resolved_name.clone());
-                *player.units_produced.entry(resolved_name).or_insert(0) += 1;
             }
         }
 
@@ -332,14 +718,7 @@ pub fn run_game(config: GameConfig) -> GameResult {
         player.update_peak_army();
     }
 
-    // Track events with bounded capacity
-    let mut events: Vec<TimedEvent> = Vec::with_capacity(1024);
-    let mut screenshot_manager = config.screenshot_config.map(ScreenshotManager::new);
-
-    // Salvage system: track wrecks and active salvage operations
-    let mut wrecks: Vec<WreckState> = Vec::new();
-    let mut salvage_actions_a: HashMap<EntityId, SalvageAction> = HashMap::new();
-    let mut salvage_actions_b: HashMap<EntityId, SalvageAction> = HashMap::new();
+    sim.set_script(ScenarioScript::new(config.scenario.triggers.clone()));
 
     // Pre-game diagnostics
     let initial_entity_count = sim.entities().len();
@@ -352,14 +731,260 @@ pub fn run_game(config: GameConfig) -> GameResult {
         "Game initialized"
     );
 
+    let state = RunnerState {
+        rng,
+        player_a,
+        player_b,
+        // Track events with bounded capacity
+        events: Vec::with_capacity(1024),
+        resource_nodes: config
+            .scenario
+            .initial_resources
+            .ore_nodes
+            .iter()
+            .map(|ore| {
+                ResourceNode::new(
+                    Vec2Fixed::new(
+                        Fixed::from_num(ore.position.0),
+                        Fixed::from_num(ore.position.1),
+                    ),
+                    ore.amount.clamp(0, i64::from(i32::MAX)) as i32,
+                    HARVESTER_GATHER_RATE,
+                )
+                .with_kind(ore.kind)
+            })
+            .collect(),
+    };
+
+    play_game(config, sim, state, game_start)
+}
+
+/// Resume a game from a headless autosave written by [`run_game`].
+///
+/// The faction registry is not part of the save, so pass the one the
+/// original game used. Set `autosave` to keep autosaving from here on.
+/// Playing on from the save gives the same result as the uninterrupted game.
+///
+/// # Errors
+///
+/// Returns an error if the save was not written by the headless runner, was
+/// taken with different faction data, or its simulation fails hash
+/// verification.
+pub fn resume_game(
+    save: &Autosave,
+    faction_registry: Option<Arc<FactionRegistry>>,
+    autosave: Option<AutosaveConfig>,
+) -> rts_core::error::Result<GameResult> {
+    let saved: SavedGame = save.host_state()?;
+    save.check_fingerprint(&session_fingerprint(
+        &saved.scenario,
+        faction_registry.as_deref(),
+    )?)?;
+    let sim = save.restore_simulation()?;
+    info!(
+        game_id = %save.label,
+        tick = save.tick,
+        max_ticks = saved.max_ticks,
+        "Resuming game from autosave"
+    );
+
+    let config = GameConfig {
+        seed: saved.seed,
+        max_ticks: saved.max_ticks,
+        scenario: saved.scenario,
+        strategy_a: saved.strategy_a,
+        strategy_b: saved.strategy_b,
+        handicap_a: saved.runner.player_a.handicap,
+        handicap_b: saved.runner.player_b.handicap,
+        screenshot_config: None,
+        game_id: save.label.clone(),
+        faction_registry,
+        autosave,
+        chat: false,
+        observers: Vec::new(),
+        stop: StopSignal::default(),
+        projection: None,
+        sample_interval: None,
+    };
+    Ok(play_game(config, sim, saved.runner, Instant::now()))
+}
+
+/// Fingerprint of the engine, the faction data in play and the scenario.
+///
+/// Without a registry the data component is left unset.
+fn session_fingerprint(
+    scenario: &Scenario,
+    registry: Option<&FactionRegistry>,
+) -> rts_core::error::Result<Fingerprint> {
+    let fingerprint = Fingerprint::engine().with_map(scenario)?;
+    match registry {
+        Some(registry) => fingerprint.with_factions(
+            scenario
+                .factions
+                .iter()
+                .filter_map(|setup| registry.get(setup.faction())),
+        ),
+        None => Ok(fingerprint),
+    }
+}
+
+/// Write an autosave, logging rather than failing the game on error.
+fn write_autosave(
+    rotation: &mut AutosaveRotation,
+    config: &GameConfig,
+    fingerprint: Fingerprint,
+    sim: &Simulation,
+    runner: RunnerState,
+) {
+    let saved = SavedGame {
+        seed: config.seed,
+        max_ticks: config.max_ticks,
+        scenario: config.scenario.clone(),
+        strategy_a: config.strategy_a.clone(),
+        strategy_b: config.strategy_b.clone(),
+        runner,
+    };
+    let result = Autosave::capture(config.game_id.clone(), sim)
+        .map(|save| save.with_fingerprint(fingerprint))
+        .and_then(|save| save.with_host_state(&saved))
+        .and_then(|save| rotation.write(&save));
+    match result {
+        Ok(path) => debug!(tick = sim.get_tick(), path = %path.display(), "Autosaved"),
+        Err(e) => warn!(tick = sim.get_tick(), error = %e, "Autosave failed"),
+    }
+}
+
+/// A simulation whose navigation grid is a generated map's terrain.
+fn simulation_on_map(map: &GeneratedMap) -> Simulation {
+    let config = &map.config;
+    let mut sim = Simulation::with_nav_grid(
+        config.width,
+        config.height,
+        Fixed::from_num(config.cell_size),
+    );
+    for (i, (cell, level)) in map
+        .as_cell_types()
+        .into_iter()
+        .zip(map.as_elevations())
+        .enumerate()
+    {
+        let (x, y) = (i as u32 % config.width, i as u32 / config.width);
+        sim.nav_grid_mut().set_cell(x, y, cell);
+        sim.nav_grid_mut().set_elevation(x, y, level);
+    }
+    sim
+}
+
+/// A simulation with a scenario's terrain, starting forces and script, for
+/// hosts that drive the factions themselves (such as bot tournaments).
+///
+/// Entities are spawned as [`run_game`] spawns them; the runner's own AI
+/// bookkeeping and economy are left out.
+#[must_use]
+pub fn scenario_simulation(
+    scenario: &Scenario,
+    seed: u64,
+    registry: Option<&FactionRegistry>,
+) -> Simulation {
+    let mut sim = match &scenario.map {
+        Some(map) => simulation_on_map(&generate_map(map.clone())),
+        None => Simulation::new(),
+    };
+    sim.seed_rng(seed);
+    sim.set_entity_capacity(Some(EntityCapacity::new(MAX_ENTITIES)));
+
+    for setup in &scenario.factions {
+        let faction = setup.faction();
+        register_tech_tree(&mut sim, faction, registry, scenario);
+        if let Some(data) = registry.and_then(|reg| reg.get(faction)) {
+            sim.set_faction_mechanics(faction, data.mechanics.clone());
+        }
+        for building in &setup.starting_buildings {
+            let (x, y) = building.position;
+            spawn_building_with_registry(
+                &mut sim,
+                &building.kind,
+                x,
+                y,
+                faction,
+                registry,
+                scenario,
+            );
+        }
+        for unit in &setup.starting_units {
+            let (x, y) = unit.position;
+            for _ in 0..unit.count {
+                spawn_unit_with_registry(&mut sim, &unit.kind, x, y, faction, registry, scenario);
+            }
+        }
+    }
+
+    sim.set_script(ScenarioScript::new(scenario.triggers.clone()));
+    sim
+}
+
+/// Main game loop, from whatever tick `sim` is at until the game ends.
+fn play_game(
+    mut config: GameConfig,
+    mut sim: Simulation,
+    state: RunnerState,
+    game_start: Instant,
+) -> GameResult {
+    let RunnerState {
+        mut rng,
+        mut player_a,
+        mut player_b,
+        mut events,
+        mut resource_nodes,
+    } = state;
+    let registry = config.faction_registry.as_deref();
+    let scenario = &config.scenario;
+    let mut screenshot_manager = config.screenshot_config.clone().map(ScreenshotManager::new);
+    let mut autosaves = config.autosave.clone().map(AutosaveRotation::new);
+    let fingerprint = match session_fingerprint(scenario, registry) {
+        Ok(fingerprint) => fingerprint,
+        Err(e) => {
+            warn!(error = %e, "Failed to fingerprint session, saves record the engine only");
+            Fingerprint::engine()
+        }
+    };
+    let mut observers = std::mem::take(&mut config.observers);
+
     // Main game loop - BOUNDED by max_ticks
-    let mut tick = 0u64;
+    let mut tick = sim.get_tick();
     let mut winner: Option<String> = None;
-    let mut win_condition = "timeout".to_string();
+    let mut win_condition = WinCondition::TimeLimit;
     let mut last_progress_log = Instant::now();
+    let mut under_pressure = false;
+    let mut stopped = None;
+    let mut projector = Projector::default();
+
+    if tick == 0 {
+        for player in [&player_a, &player_b] {
+            post_taunt(
+                player,
+                TauntTrigger::GameStart,
+                tick,
+                &config,
+                &mut events,
+                &mut observers,
+            );
+        }
+    }
 
     // Invariant: tick always increases, loop will terminate at max_ticks
     while tick < config.max_ticks {
+        if let Some(reason) = config.stop.check() {
+            warn!(
+                game_id = %config.game_id,
+                tick = tick,
+                reason = ?reason,
+                elapsed_ms = game_start.elapsed().as_millis(),
+                "Game stopped early"
+            );
+            stopped = Some(reason);
+            break;
+        }
         let tick_start = Instant::now();
         // Defensive check: entity count sanity
         let entity_count = sim.entities().len();
@@ -370,25 +995,96 @@ pub fn run_game(config: GameConfig) -> GameResult {
                 max = MAX_ENTITIES,
                 "FATAL: Entity count exceeded maximum - aborting to prevent OOM"
             );
-            win_condition = "error_entity_overflow".to_string();
+            win_condition = WinCondition::EntityOverflow;
             break;
         }
 
         // Execute AI for each player
-        execute_ai_turn(&mut sim, &mut player_a, tick, &mut rng, registry);
-        execute_ai_turn(&mut sim, &mut player_b, tick, &mut rng, registry);
-
-        // Cache unit positions BEFORE tick (entities are removed during tick when they die)
-        let mut cached_positions: HashMap<EntityId, (f32, f32)> = HashMap::new();
-        for &unit_id in player_a.units.iter().chain(player_b.units.iter()) {
-            if let Some(pos) = get_entity_position(&sim, unit_id) {
-                cached_positions.insert(unit_id, (pos.x.to_num(), pos.y.to_num()));
+        let attacked = [player_a.first_attack_tick, player_b.first_attack_tick];
+        for player in [&mut player_a, &mut player_b] {
+            if player.handicap.may_act(tick) {
+                execute_ai_turn(
+                    &mut sim,
+                    player,
+                    tick,
+                    &mut rng,
+                    registry,
+                    scenario,
+                    &resource_nodes,
+                );
+            }
+        }
+        for player in [&player_a, &player_b] {
+            for expansion in player.expansions.values() {
+                if expansion.founded_at == tick {
+                    events.push(TimedEvent {
+                        tick,
+                        event_type: EventType::ExpansionStarted,
+                        faction: faction_key(player.faction_id),
+                        details: format!("Founded a base at ore node {}", expansion.node),
+                    });
+                }
+            }
+        }
+        for (player, before) in [(&player_a, attacked[0]), (&player_b, attacked[1])] {
+            if before.is_none() && player.first_attack_tick.is_some() {
+                events.push(TimedEvent {
+                    tick,
+                    event_type: EventType::FirstAttack,
+                    faction: faction_key(player.faction_id),
+                    details: "First attack".to_string(),
+                });
+                post_taunt(
+                    player,
+                    TauntTrigger::FirstAttack,
+                    tick,
+                    &config,
+                    &mut events,
+                    &mut observers,
+                );
             }
         }
+        record_reservation_trace(&mut player_a, &mut events);
+        record_reservation_trace(&mut player_b, &mut events);
+        events.append(&mut player_a.pending_events);
+        events.append(&mut player_b.pending_events);
 
         // Advance simulation
         let tick_events = sim.tick();
         tick += 1;
+        if !observers.is_empty() {
+            let players = [player_a.view(), player_b.view()];
+            for observer in &mut observers {
+                observer.on_tick(&sim, &tick_events);
+                observer.on_players(&sim, &players);
+            }
+        }
+        for fired in &tick_events.triggers {
+            apply_trigger(
+                &mut sim,
+                [&mut player_a, &mut player_b],
+                fired,
+                registry,
+                scenario,
+            );
+        }
+
+        // Warn once per pressure episode rather than every tick
+        match &tick_events.capacity_pressure {
+            Some(pressure) if !under_pressure => {
+                warn!(
+                    tick = tick,
+                    entities = pressure.entities,
+                    max = pressure.max_entities,
+                    culled = pressure.culled.len(),
+                    headroom = pressure.headroom(),
+                    "Entity capacity pressure - culling cosmetics and pooling projectiles"
+                );
+                under_pressure = true;
+            }
+            Some(_) => {}
+            None => under_pressure = false,
+        }
 
         // Watchdog: check tick duration
         let tick_duration = tick_start.elapsed();
@@ -414,7 +1110,7 @@ pub fn run_game(config: GameConfig) -> GameResult {
                 timeout_ms = TICK_TIMEOUT_MS,
                 "FATAL: Tick took too long - possible infinite loop or deadlock"
             );
-            win_condition = "error_tick_timeout".to_string();
+            win_condition = WinCondition::TickTimeout;
             break;
         }
 
@@ -441,7 +1137,7 @@ pub fn run_game(config: GameConfig) -> GameResult {
             let target_faction = get_entity_faction(&sim, damage_event.target);
 
             if let Some(af) = attacker_faction {
-                let player = if af == FactionId::Continuity {
+                let player = if af == player_a.faction_id {
                     &mut player_a
                 } else {
                     &mut player_b
@@ -449,20 +1145,140 @@ pub fn run_game(config: GameConfig) -> GameResult {
                 player.total_damage_dealt += damage_event.damage as i64;
             }
             if let Some(tf) = target_faction {
-                let player = if tf == FactionId::Continuity {
+                let player = if tf == player_a.faction_id {
                     &mut player_a
                 } else {
                     &mut player_b
                 };
                 player.total_damage_taken += damage_event.damage as i64;
             }
+            if let (Some(af), Some(tf)) = (attacker_faction, target_faction) {
+                if af != tf {
+                    if af == player_a.faction_id {
+                        record_unit_hit(&mut player_a, &mut player_b, damage_event);
+                    } else {
+                        record_unit_hit(&mut player_b, &mut player_a, damage_event);
+                    }
+                }
+            }
+        }
+        for &(entity, healed) in &tick_events.regenerated {
+            match get_entity_faction(&sim, entity) {
+                Some(f) if f == player_a.faction_id => {
+                    player_a.total_health_regenerated += healed as i64;
+                }
+                Some(_) => player_b.total_health_regenerated += healed as i64,
+                None => {}
+            }
         }
 
-        // Process deaths - spawn wrecks for salvage
-        for dead_id in &tick_events.deaths {
-            // Get cached position (entity is already removed from sim by this point)
-            let cached_pos = cached_positions.get(dead_id).copied();
+        // Pay out salvage; wrecks count against the side that lost the unit
+        for event in &tick_events.salvage {
+            match *event {
+                SalvageEvent::WreckSpawned {
+                    wreck,
+                    entity,
+                    value,
+                } => {
+                    for player in [&mut player_a, &mut player_b] {
+                        if player.units.contains(&entity) {
+                            player.salvage_given_to_enemy += i64::from(value);
+                            trace!(
+                                faction = %faction_key(player.faction_id),
+                                wreck = %wreck,
+                                salvage = value,
+                                "Spawned wreck"
+                            );
+                        }
+                    }
+                }
+                SalvageEvent::Collected {
+                    collector, amount, ..
+                } => {
+                    for player in [&mut player_a, &mut player_b] {
+                        if player.units.contains(&collector) {
+                            player.resources += i64::from(amount);
+                            player.resources_from_salvage += i64::from(amount);
+                        }
+                    }
+                }
+                SalvageEvent::WreckCleared { .. } => {}
+            }
+        }
+
+        for event in &tick_events.research {
+            if let ResearchEvent::ResearchComplete {
+                faction, tech_id, ..
+            } = event
+            {
+                for player in [&mut player_a, &mut player_b] {
+                    if player.faction_id == *faction {
+                        player.tech_unlock_times.insert(tech_id.clone(), tick);
+                        trace!(faction = ?faction, tech = %tech_id, "Research completed");
+                    }
+                }
+            }
+        }
+
+        // Abilities are paid for when they are used
+        for used in &tick_events.abilities {
+            for player in [&mut player_a, &mut player_b] {
+                if used.faction == Some(player.faction_id) {
+                    player.resources = (player.resources - i64::from(used.cost)).max(0);
+                }
+            }
+        }
+
+        // Repairs are paid for as they are made
+        for repair in &tick_events.repairs {
+            for player in [&mut player_a, &mut player_b] {
+                if repair.faction == Some(player.faction_id) {
+                    player.resources = (player.resources - i64::from(repair.cost)).max(0);
+                }
+            }
+        }
+
+        // Buildings count as constructed once their workers finish them
+        for event in &tick_events.construction {
+            if let ConstructionEvent::ConstructionComplete { building } = event {
+                for player in [&mut player_a, &mut player_b] {
+                    if let Some(kind) = player.building_kinds.get(building) {
+                        trace!(faction = ?player.faction_id, building = %kind, "Building finished");
+                        *player
+                            .buildings_constructed
+                            .entry(kind.clone())
+                            .or_insert(0) += 1;
+                        let event_type =
+                            if is_production_building(kind, player.faction_id, registry) {
+                                EventType::ProductionBuildingCompleted
+                            } else {
+                                EventType::BuildingCompleted
+                            };
+                        events.push(TimedEvent {
+                            tick,
+                            event_type,
+                            faction: faction_key(player.faction_id),
+                            details: kind.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        // Faction mechanics bring some buildings back under new IDs
+        for &(old, new) in &tick_events.rebuilt {
+            for player in [&mut player_a, &mut player_b] {
+                if let Some(kind) = player.building_kinds.remove(&old) {
+                    player.buildings.retain(|&id| id != old);
+                    player.buildings.push(new);
+                    trace!(faction = ?player.faction_id, building = %kind, "Building rebuilt");
+                    player.building_kinds.insert(new, kind);
+                }
+            }
+        }
 
+        // Process deaths
+        for dead_id in &tick_events.deaths {
             // Skip entities not tracked as player units (might be a building)
             let in_a = player_a.units.contains(dead_id);
             let in_b = player_b.units.contains(dead_id);
@@ -474,37 +1290,14 @@ pub fn run_game(config: GameConfig) -> GameResult {
             if player_a.units.contains(dead_id) {
                 player_a.units.retain(|&id| id != *dead_id);
 
-                // Spawn wreck if we know the unit kind and have cached position
-                if let (Some(unit_kind), Some(pos)) =
-                    (player_a.unit_kinds.remove(dead_id), cached_pos)
-                {
-                    let cost =
-                        get_unit_cost_with_registry(&unit_kind, player_a.faction_id, registry);
-                    let salvage_value = (cost as f32 * SALVAGE_PERCENT) as i64;
-                    if salvage_value > 0 {
-                        // Track salvage given to enemy (player_b can salvage this)
-                        player_a.salvage_given_to_enemy += salvage_value;
-                        wrecks.push(WreckState {
-                            position: pos,
-                            salvage_remaining: salvage_value,
-                            spawn_tick: tick,
-                            unit_kind: unit_kind.clone(),
-                        });
-                        trace!(
-                            faction = "continuity",
-                            unit_kind = %unit_kind,
-                            salvage = salvage_value,
-                            "Spawned wreck"
-                        );
-                    }
-                }
+                record_unit_death(&mut player_a, &mut player_b, *dead_id, tick);
 
                 *player_a.units_lost.entry("unit".to_string()).or_insert(0) += 1;
                 events.push(TimedEvent {
                     tick,
                     event_type: EventType::UnitKilled,
-                    faction: "continuity".to_string(),
-                    details: format!("Unit {} died", dead_id),
+                    faction: faction_key(player_a.faction_id),
+                    details: format!("Unit {} died", tick_events.death_label(*dead_id)),
                 });
 
                 // Credit the kill to the other player
@@ -513,81 +1306,79 @@ pub fn run_game(config: GameConfig) -> GameResult {
             if player_b.units.contains(dead_id) {
                 player_b.units.retain(|&id| id != *dead_id);
 
-                // Spawn wreck if we know the unit kind and have cached position
-                if let (Some(unit_kind), Some(pos)) =
-                    (player_b.unit_kinds.remove(dead_id), cached_pos)
-                {
-                    let cost =
-                        get_unit_cost_with_registry(&unit_kind, player_b.faction_id, registry);
-                    let salvage_value = (cost as f32 * SALVAGE_PERCENT) as i64;
-                    if salvage_value > 0 {
-                        // Track salvage given to enemy (player_a can salvage this)
-                        player_b.salvage_given_to_enemy += salvage_value;
-                        wrecks.push(WreckState {
-                            position: pos,
-                            salvage_remaining: salvage_value,
-                            spawn_tick: tick,
-                            unit_kind: unit_kind.clone(),
-                        });
-                        trace!(
-                            faction = "collegium",
-                            unit_kind = %unit_kind,
-                            salvage = salvage_value,
-                            "Spawned wreck"
-                        );
-                    }
-                }
+                record_unit_death(&mut player_b, &mut player_a, *dead_id, tick);
 
                 *player_b.units_lost.entry("unit".to_string()).or_insert(0) += 1;
                 events.push(TimedEvent {
                     tick,
                     event_type: EventType::UnitKilled,
-                    faction: "collegium".to_string(),
-                    details: format!("Unit {} died", dead_id),
+                    faction: faction_key(player_b.faction_id),
+                    details: format!("Unit {} died", tick_events.death_label(*dead_id)),
                 });
 
                 *player_a.units_killed.entry("unit".to_string()).or_insert(0) += 1;
             }
 
             // Check for depot destruction
-            if player_a.depot_entity == Some(*dead_id) {
-                player_a.depot_entity = None;
+            for player in [&mut player_a, &mut player_b] {
+                if player.depot_entity == Some(*dead_id) {
+                    player.depot_entity = None;
+                    post_taunt(
+                        player,
+                        TauntTrigger::HqLost,
+                        tick,
+                        &config,
+                        &mut events,
+                        &mut observers,
+                    );
+                }
             }
-            if player_b.depot_entity == Some(*dead_id) {
-                player_b.depot_entity = None;
+        }
+
+        // Armies past their faction's upkeep thresholds drain its reserve
+        for player in [&mut player_a, &mut player_b] {
+            if !player.upkeep_model.is_free() {
+                let supply = player_supply(&sim, player, registry).supply_used;
+                player.upkeep.tick(&player.upkeep_model, tick, supply);
             }
         }
 
-        // Expire old wrecks
-        wrecks.retain(|w| tick - w.spawn_tick < WRECK_LIFETIME);
+        // Harvesters gather from ore nodes and unload at depots; players
+        // take turns going first so neither drains shared nodes first
+        if tick % HARVEST_INTERVAL == 0 {
+            let harvest_order = if (tick / HARVEST_INTERVAL) % 2 == 0 {
+                [&mut player_a, &mut player_b]
+            } else {
+                [&mut player_b, &mut player_a]
+            };
+            for player in harvest_order {
+                run_harvesters(&mut sim, player, &mut resource_nodes, scenario, tick);
+            }
+        }
 
-        // Process salvage collection for both players
-        // Battleline units near wrecks will auto-collect salvage (if not in combat)
-        if !wrecks.is_empty() {
-            process_salvage_for_player(
-                &sim,
-                &mut player_a,
-                &mut wrecks,
-                &mut salvage_actions_a,
-                registry,
-            );
-            process_salvage_for_player(
+        // Time series for the balance analyzer
+        if let Some(interval) = config.sample_interval.filter(|&i| tick % i.max(1) == 0) {
+            sample_timelines(
                 &sim,
-                &mut player_b,
-                &mut wrecks,
-                &mut salvage_actions_b,
+                [&mut player_a, &mut player_b],
+                scenario.map_size,
+                interval,
                 registry,
+                tick,
             );
-
-            // Remove fully salvaged wrecks
-            wrecks.retain(|w| w.salvage_remaining > 0);
         }
 
         // Check for screenshot triggers
         if let Some(ref mut manager) = screenshot_manager {
             // Major battle trigger
             if tick_events.damage_events.len() > 5 {
-                let state = create_visual_state(&config.game_id, tick, &sim);
+                let state = create_visual_state(
+                    &config.game_id,
+                    tick,
+                    &sim,
+                    scenario.map_size,
+                    [&player_a, &player_b],
+                );
                 let trigger = ScreenshotTrigger::MajorBattle {
                     unit_count: tick_events.damage_events.len() as u32,
                 };
@@ -596,7 +1387,13 @@ pub fn run_game(config: GameConfig) -> GameResult {
 
             // Timed snapshots every 2 minutes (7200 ticks at 60fps, 2400 at 20fps)
             if manager.should_capture_timed(tick) {
-                let state = create_visual_state(&config.game_id, tick, &sim);
+                let state = create_visual_state(
+                    &config.game_id,
+                    tick,
+                    &sim,
+                    scenario.map_size,
+                    [&player_a, &player_b],
+                );
                 let trigger = ScreenshotTrigger::TimedSnapshot { tick };
                 let _ = manager.capture(state, &trigger);
                 manager.record_timed_capture(tick);
@@ -604,16 +1401,10 @@ pub fn run_game(config: GameConfig) -> GameResult {
         }
 
         // Check victory conditions
-        if tick_events.game_end.is_some() {
-            if let Some(winning_faction) = tick_events.game_end {
-                winner = Some(match winning_faction {
-                    FactionId::Continuity => "continuity".to_string(),
-                    FactionId::Collegium => "collegium".to_string(),
-                    _ => "unknown".to_string(),
-                });
-                win_condition = "elimination".to_string();
-                break;
-            }
+        if let Some(condition) = tick_events.win_condition {
+            winner = condition.winner().map(faction_key);
+            win_condition = condition;
+            break;
         }
 
         // Victory condition: HQ/depot destruction
@@ -621,21 +1412,77 @@ pub fn run_game(config: GameConfig) -> GameResult {
         let b_has_depot = player_b.depot_entity.is_some();
 
         if !a_has_depot && b_has_depot {
-            winner = Some("collegium".to_string());
-            win_condition = "elimination".to_string();
+            winner = Some(faction_key(player_b.faction_id));
+            win_condition = elimination_by(&sim, &player_b);
             break;
         }
         if !b_has_depot && a_has_depot {
-            winner = Some("continuity".to_string());
-            win_condition = "elimination".to_string();
+            winner = Some(faction_key(player_a.faction_id));
+            win_condition = elimination_by(&sim, &player_a);
             break;
         }
         if !a_has_depot && !b_has_depot {
-            // Mutual destruction - draw
+            win_condition = WinCondition::MutualDestruction;
             break;
         }
+
+        // Or end early once the result is no longer in doubt
+        if let Some(projection) = config.projection.as_ref().filter(|p| p.is_due(tick)) {
+            let a_chance = win_probability(
+                &side_strength(&sim, &player_a),
+                &side_strength(&sim, &player_b),
+            );
+            if let Some(projected) = projector.record(projection, a_chance) {
+                let leader = if projected.a_wins {
+                    &player_a
+                } else {
+                    &player_b
+                };
+                winner = Some(faction_key(leader.faction_id));
+                win_condition = WinCondition::Projected {
+                    projected_winner: leader.faction_id,
+                    certainty_percent: u8::try_from(projected.certainty / 10).unwrap_or(100),
+                };
+                break;
+            }
+        }
+
+        // Autosave at the end of the tick so resuming starts a fresh one
+        if let Some(ref mut rotation) = autosaves {
+            if rotation.is_due(tick) {
+                let runner = RunnerState {
+                    rng: rng.clone(),
+                    player_a: player_a.clone(),
+                    player_b: player_b.clone(),
+                    events: events.clone(),
+                    resource_nodes: resource_nodes.clone(),
+                };
+                write_autosave(rotation, &config, fingerprint, &sim, runner);
+            }
+        }
+    }
+
+    for player in [&player_a, &player_b] {
+        if winner.as_deref() == Some(faction_key(player.faction_id).as_str()) {
+            post_taunt(
+                player,
+                TauntTrigger::Victory,
+                tick,
+                &config,
+                &mut events,
+                &mut observers,
+            );
+        }
     }
 
+    // Announce the result in the event log
+    events.push(TimedEvent {
+        tick,
+        event_type: EventType::GameEnded,
+        faction: winner.clone().unwrap_or_else(|| "none".to_string()),
+        details: win_condition.to_string(),
+    });
+
     // Post-game diagnostics
     let game_duration = game_start.elapsed();
     info!(
@@ -661,16 +1508,13 @@ pub fn run_game(config: GameConfig) -> GameResult {
     // Build metrics
     let mut factions = HashMap::new();
 
-    factions.insert(
-        "continuity".to_string(),
-        build_faction_metrics(&player_a, tick),
-    );
-    factions.insert(
-        "collegium".to_string(),
-        build_faction_metrics(&player_b, tick),
-    );
+    for (player, enemy) in [(&player_a, &player_b), (&player_b, &player_a)] {
+        let mut faction = build_faction_metrics(player, tick);
+        faction.scout_efficiency = scout_efficiency(player, enemy);
+        factions.insert(faction_key(player.faction_id), faction);
+    }
 
-    let metrics = GameMetrics {
+    let mut metrics = GameMetrics {
         game_id: config.game_id,
         scenario: config.scenario.name.clone(),
         seed: config.seed,
@@ -680,63 +1524,134 @@ pub fn run_game(config: GameConfig) -> GameResult {
         factions,
         events,
         final_state_hash: 0, // Set by caller when copying to batch results
+        tuning: (!scenario.tuning.is_baseline()).then_some(scenario.tuning),
+        observations: Default::default(),
     };
+    for observer in &mut observers {
+        observer.on_game_end(&sim, &mut metrics);
+    }
 
     GameResult {
         metrics,
         final_state_hash: sim.state_hash(),
+        stopped,
     }
 }
 
-/// Execute AI for a player's turn.
-fn execute_ai_turn(
-    sim: &mut Simulation,
-    player: &mut PlayerState,
+/// Name a faction goes by in metrics, events and results, e.g.
+/// `"continuity"` or `"bio-sovereigns"`.
+fn faction_key(faction: FactionId) -> String {
+    faction.short_name().to_lowercase()
+}
+
+/// Post `player`'s personality taunt for `trigger`, if it has one and chat
+/// is enabled.
+///
+/// Chat lines go to the log, the game's event list and every observer.
+fn post_taunt(
+    player: &PlayerState,
+    trigger: TauntTrigger,
     tick: u64,
-    rng: &mut SimpleRng,
-    registry: Option<&FactionRegistry>,
+    config: &GameConfig,
+    events: &mut Vec<TimedEvent>,
+    observers: &mut [Box<dyn GameObserver>],
 ) {
-    // =========================================================================
-    // RESEARCH: Progress any active research
-    // =========================================================================
-    if let Some((ref tech_id, ref mut ticks_remaining)) = player.current_research {
-        if *ticks_remaining > 0 {
-            *ticks_remaining -= 1;
-            if *ticks_remaining == 0 {
-                // Research complete!
-                let completed_tech = tech_id.clone();
-                player.researched_techs.insert(completed_tech.clone());
-                trace!(
-                    faction = ?player.faction_id,
-                    tech = %completed_tech,
-                    "Research completed"
-                );
-            }
-        }
+    if !config.chat {
+        return;
+    }
+    let Some(message) = player
+        .personality
+        .as_ref()
+        .and_then(|personality| personality.taunt(trigger, tick, config.seed))
+    else {
+        return;
+    };
+    info!(tick = tick, sender = %message.sender, text = %message.text, "Chat");
+    events.push(TimedEvent {
+        tick,
+        event_type: EventType::Chat,
+        faction: faction_key(player.faction_id),
+        details: format!("{}: {}", message.sender, message.text),
+    });
+    for observer in observers.iter_mut() {
+        observer.on_chat(&message);
     }
-    // Clear completed research
-    if matches!(&player.current_research, Some((_, 0))) {
-        player.current_research = None;
+}
+
+/// Move a player's resource reservation changes into the game's event log.
+fn record_reservation_trace(player: &mut PlayerState, events: &mut Vec<TimedEvent>) {
+    let faction = faction_key(player.faction_id);
+    for event in player.executor.drain_trace() {
+        let (tick, event_type, details) = match event {
+            ReservationEvent::Reserved { tick, reservation } => (
+                tick,
+                EventType::ResourcesReserved,
+                format!(
+                    "Reserved {} for {} ({:?} priority)",
+                    reservation.amount, reservation.item, reservation.priority
+                ),
+            ),
+            ReservationEvent::Released { tick, reservation } => (
+                tick,
+                EventType::ReservationReleased,
+                format!(
+                    "Bought {} after saving for {} ticks",
+                    reservation.item,
+                    tick.saturating_sub(reservation.since_tick)
+                ),
+            ),
+        };
+        trace!(faction = %faction, details = %details, "Reservation changed");
+        events.push(TimedEvent {
+            tick,
+            event_type,
+            faction: faction.clone(),
+            details,
+        });
     }
+}
+
+/// Execute AI for a player's turn.
+fn execute_ai_turn(
+    sim: &mut Simulation,
+    player: &mut PlayerState,
+    tick: u64,
+    rng: &mut SimpleRng,
+    registry: Option<&FactionRegistry>,
+    scenario: &Scenario,
+    nodes: &[ResourceNode],
+) {
+    // Buy rarer resources a held-back purchase is waiting on
+    manage_trades(sim, player, tick);
 
     // Get current unit count for strategy decisions
     let current_resources = player.resources;
     let unit_counts: HashMap<String, u32> = player.units_produced.clone();
-    let current_supply = player.units.len();
 
-    // Supply cap check - fundamental RTS mechanic
-    let can_build_units = current_supply < MAX_SUPPLY_PER_PLAYER;
+    // Supply cap check - same rules as the client (see rts_core::economy)
+    let mut supply = player_supply(sim, player, registry);
 
     // Check build order
-    if let Some(item) = player
+    let build_order_item = player
         .executor
-        .next_build_item(tick, current_resources, &unit_counts)
-    {
-        match item {
+        .next_build_item(tick, current_resources, &unit_counts);
+    let build_order_done = build_order_item.is_none();
+    if let Some(item) = build_order_item {
+        // Items may not dip into resources reserved for more important ones
+        let priority = player.executor.priority_of(&item);
+        let spendable = player.executor.spendable(player.resources, priority);
+
+        match &item {
             BuildOrderItem::Unit(unit_type) => {
                 // Only build if we have resources AND supply
-                let cost = get_unit_cost_with_registry(&unit_type, player.faction_id, registry);
-                if player.resources >= cost && can_build_units {
+                let cost = get_unit_cost_with_registry(unit_type, player.faction_id, registry);
+                let extra =
+                    get_unit_extra_cost_with_registry(unit_type, player.faction_id, registry);
+                let unit_supply =
+                    get_unit_supply_with_registry(unit_type, player.faction_id, registry);
+                if spendable < cost || !player.afford_extra(&extra) {
+                    player.executor.defer(tick, item.clone(), cost);
+                } else if supply.reserve_supply(unit_supply) {
                     // Spawn near depot
                     if let Some(depot_id) = player.depot_entity {
                         if let Some(depot_pos) = get_entity_position(sim, depot_id) {
@@ -744,171 +1659,287 @@ fn execute_ai_turn(
                             let offset_y = (rng.next() % 50) as i32 - 25;
                             let (entity_id, resolved_name) = spawn_unit_with_registry(
                                 sim,
-                                &unit_type,
+                                unit_type,
                                 depot_pos.x.to_num::<i32>() + offset_x,
                                 depot_pos.y.to_num::<i32>() + offset_y,
                                 player.faction_id,
                                 registry,
+                                scenario,
                             );
-                            player.units.push(entity_id);
-                            player.unit_kinds.insert(entity_id, resolved_name.clone());
-                            player.resources -= cost;
-                            *player.units_produced.entry(resolved_name).or_insert(0) += 1;
+                            track_harvester(player, entity_id, &resolved_name, registry);
+                            let value = cost + extra.feedstock_value();
+                            player.produce_unit(entity_id, resolved_name, tick, value);
+                            player.pay(cost, &extra);
+                            player.executor.complete(tick, &item);
                         }
                     }
                 }
             }
             BuildOrderItem::Building(building_type) => {
                 let cost =
-                    get_building_cost_with_registry(&building_type, player.faction_id, registry);
-                if player.resources >= cost {
-                    if let Some(depot_id) = player.depot_entity {
-                        if let Some(depot_pos) = get_entity_position(sim, depot_id) {
-                            let offset_x = (rng.next() % 100) as i32 - 50;
-                            let offset_y = (rng.next() % 100) as i32 - 50;
-                            let entity_id = spawn_building_with_registry(
-                                sim,
-                                &building_type,
-                                depot_pos.x.to_num::<i32>() + offset_x,
-                                depot_pos.y.to_num::<i32>() + offset_y,
-                                player.faction_id,
-                                registry,
-                            );
-                            player.buildings.push(entity_id);
-                            player.resources -= cost;
-                            *player
-                                .buildings_constructed
-                                .entry(building_type)
-                                .or_insert(0) += 1;
-                        }
+                    get_building_cost_with_registry(building_type, player.faction_id, registry);
+                let extra = get_building_extra_cost_with_registry(
+                    building_type,
+                    player.faction_id,
+                    registry,
+                );
+                if let Err(e) = sim.check_placement(player.faction_id, building_type) {
+                    // Never allowed again, so drop it rather than wait
+                    trace!(faction = ?player.faction_id, error = %e, "Skipped building");
+                    player.executor.complete(tick, &item);
+                } else if spendable < cost || !player.afford_extra(&extra) {
+                    player.executor.defer(tick, item.clone(), cost);
+                } else if let Some(worker) = free_worker(sim, player) {
+                    // A worker puts it up on free ground near the depot
+                    let plan = building_plan(building_type, player.faction_id, registry);
+                    let site = player
+                        .depot_entity
+                        .and_then(|depot| get_entity_position(sim, depot))
+                        .and_then(|near| sim.find_construction_site(near, &plan.footprint));
+                    let ordered = site.and_then(|position| {
+                        let params = building_params_with_registry(
+                            building_type,
+                            position,
+                            player.faction_id,
+                            registry,
+                            scenario,
+                        );
+                        let cost = u32::try_from(cost).unwrap_or(u32::MAX);
+                        sim.order_construction(
+                            worker,
+                            params,
+                            plan.footprint,
+                            plan.build_time,
+                            cost,
+                        )
+                        .map_err(|e| trace!(error = %e, "Construction refused"))
+                        .ok()
+                    });
+                    if let Some(entity_id) = ordered {
+                        player.buildings.push(entity_id);
+                        player
+                            .building_kinds
+                            .insert(entity_id, building_type.clone());
+                        player.pay(cost, &extra);
+                    } else {
+                        trace!(faction = ?player.faction_id, building = %building_type, "No room to build");
                     }
+                    player.executor.complete(tick, &item);
                 }
             }
             BuildOrderItem::Research(tech_id) => {
-                // Start research if not already researching and we don't have this tech
-                if player.current_research.is_none() && !player.researched_techs.contains(&tech_id)
-                {
-                    // Look up tech data for cost and duration
-                    if let Some(reg) = registry {
-                        if let Some(tech_data) = reg.get_technology(player.faction_id, &tech_id) {
-                            let cost = tech_data.cost as i64;
-                            if player.resources >= cost {
-                                // Check prerequisites
-                                let prereqs_met = tech_data
-                                    .prerequisites
-                                    .iter()
-                                    .all(|prereq| player.researched_techs.contains(prereq));
-                                if prereqs_met {
-                                    player.resources -= cost;
-                                    // Convert research time to ticks (assume time is in seconds, 60 tps)
-                                    let ticks = (tech_data.research_time as f32 * 60.0) as u64;
-                                    player.current_research = Some((tech_id.clone(), ticks));
-                                    trace!(
-                                        faction = ?player.faction_id,
-                                        tech = %tech_id,
-                                        cost = cost,
-                                        ticks = ticks,
-                                        "Started research"
-                                    );
-                                }
-                            }
-                        }
+                // Queue at whichever facility can take it; the simulation
+                // checks prerequisites and applies the effects
+                if let Some(building) = sim.research_facility_for(player.faction_id, tech_id) {
+                    let cost = sim
+                        .validate_research(building, tech_id)
+                        .map_or(0, |tech| i64::from(tech.cost));
+                    if spendable < cost {
+                        player.executor.defer(tick, item.clone(), cost);
+                    } else if sim.queue_research(building, tech_id).is_ok() {
+                        player.resources -= cost;
+                        trace!(
+                            faction = ?player.faction_id,
+                            tech = %tech_id,
+                            cost = cost,
+                            "Started research"
+                        );
+                        player.executor.complete(tick, &item);
                     }
                 }
             }
             _ => {}
         }
-    } else {
-        // Build order exhausted - continuous production based on composition
-        // With economy-aware unit selection!
-        let composition = player.executor.composition();
+    }
 
-        // Economy-aware selection: when tight, prefer cheap tier 1 units
-        let economy_is_tight = player.resources < ECONOMY_TIGHT_THRESHOLD;
+    // Research the strategy's wanted technologies once the build order is
+    // done
+    if build_order_done {
+        manage_tech(sim, player, tick);
+    }
 
-        // Find the best unit to build
-        let selected_unit = if economy_is_tight {
-            // Tight economy: find cheapest unit in composition
-            composition
-                .iter()
-                .filter(|(unit, _)| *unit != "harvester")
-                .min_by_key(|(unit, _)| {
-                    get_unit_cost_with_registry(unit, player.faction_id, registry)
-                })
-                .map(|(unit, _)| unit.as_str())
-        } else {
-            // Comfortable economy: use normal priority
-            composition
+    // Continuous production once the build order is exhausted, or with
+    // whatever is left over while saving for a reserved item
+    if build_order_done || player.executor.reservation().is_some() {
+        // Composition-based, with economy-aware unit selection!
+        let composition = player
+            .executor
+            .adapted_composition(&player.intel.composition(), player.intel.units());
+        let spendable = player
+            .executor
+            .spendable(player.resources, BuildPriority::Low);
+
+        // Economy-aware selection: when tight, prefer cheap tier 1 units
+        let economy_is_tight = spendable < ECONOMY_TIGHT_THRESHOLD;
+        let extra_cost =
+            |unit: &str| get_unit_extra_cost_with_registry(unit, player.faction_id, registry);
+
+        // Find the best unit to build, among those whose rarer resources
+        // are on hand if `on_hand`
+        let select = |on_hand: bool| {
+            let candidates = composition
                 .iter()
                 .filter(|(unit, _)| *unit != "harvester")
-                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-                .map(|(unit, _)| unit.as_str())
+                .filter(|(unit, _)| {
+                    !on_hand || player.reserves.shortfall(&extra_cost(unit)).is_none()
+                });
+            if economy_is_tight {
+                // Tight economy: find cheapest unit in composition
+                candidates
+                    .min_by_key(|(unit, _)| {
+                        get_unit_cost_with_registry(unit, player.faction_id, registry)
+                    })
+                    .map(|(unit, _)| unit.clone())
+            } else {
+                // Comfortable economy: use normal priority
+                candidates
+                    .max_by_key(|(_, weight)| **weight)
+                    .map(|(unit, _)| unit.clone())
+            }
         };
+        let preferred = select(false);
+        let affordable = select(true);
+
+        // Note what the preferred unit is waiting on, then build the best
+        // one that can be paid for now
+        if let Some(preferred) = preferred.filter(|p| Some(p) != affordable.as_ref()) {
+            let extra = extra_cost(&preferred);
+            let cost = get_unit_cost_with_registry(&preferred, player.faction_id, registry);
+            if spendable >= cost {
+                player.afford_extra(&extra);
+            }
+        }
 
-        if let Some(best_unit) = selected_unit {
-            let cost = get_unit_cost_with_registry(best_unit, player.faction_id, registry);
+        if let Some(best_unit) = affordable {
+            let cost = get_unit_cost_with_registry(&best_unit, player.faction_id, registry);
+            let extra = get_unit_extra_cost_with_registry(&best_unit, player.faction_id, registry);
+            let unit_supply =
+                get_unit_supply_with_registry(&best_unit, player.faction_id, registry);
             // Only build if we have resources AND supply
-            if player.resources >= cost && can_build_units {
+            if spendable >= cost && supply.has_supply(unit_supply) {
                 if let Some(depot_id) = player.depot_entity {
                     if let Some(depot_pos) = get_entity_position(sim, depot_id) {
                         let offset_x = (rng.next() % 50) as i32 - 25;
                         let offset_y = (rng.next() % 50) as i32 - 25;
                         let (entity_id, resolved_name) = spawn_unit_with_registry(
                             sim,
-                            best_unit,
+                            &best_unit,
                             depot_pos.x.to_num::<i32>() + offset_x,
                             depot_pos.y.to_num::<i32>() + offset_y,
                             player.faction_id,
                             registry,
+                            scenario,
                         );
-                        player.units.push(entity_id);
-                        player.unit_kinds.insert(entity_id, resolved_name.clone());
-                        player.resources -= cost;
-                        *player.units_produced.entry(resolved_name).or_insert(0) += 1;
+                        let value = cost + extra.feedstock_value();
+                        player.produce_unit(entity_id, resolved_name, tick, value);
+                        player.pay(cost, &extra);
                     }
                 }
             }
         }
     }
 
-    // ==========================================================================
-    // ECONOMY: Passive income (simulating harvesters for headless testing)
-    // ==========================================================================
-    // Target economy rates (per second of game time, at 60 tps):
-    //   - Early game: ~5-10 resources/sec (1 infantry every 5-10 seconds)
-    //   - Mid game:   ~15-25 resources/sec (with 3-5 harvesters)
-    //   - Late game:  ~30-50 resources/sec (with economy buildings)
-    //
-    // For headless testing without actual harvesters, we use passive income:
-    //   - 1 resource every 6 ticks = 10 resources/sec
-    //   - This allows ~1 infantry per 5 seconds baseline
-    //
-    // TODO: Replace with actual harvester simulation for realistic economy
-    if tick % 6 == 0 {
-        player.resources += 1;
-        player.resources_from_harvest += 1;
+    // Logistics - rotate units low on ammunition out to resupply
+    if let Some(logistics) = &scenario.logistics {
+        manage_resupply(sim, player, logistics, tick);
     }
 
+    // Engineers mend damaged structures, if the strategy cares to
+    manage_repairs(sim, player);
+
+    // Newly finished bases get harvesters
+    manage_expansions(sim, player, nodes, registry, scenario);
+
     // Target acquisition - find and attack nearby enemies
     acquire_targets_for_units(sim, player);
 
-    // Check if we can see any enemies
-    let visible_enemies = sim.get_visible_enemies_for(player.faction_id);
+    // Check if we can see any enemies, once we have had time to react
+    let mut visible_enemies = sim.get_visible_enemies_for(player.faction_id);
+    let handicap = player.handicap;
+    if !player
+        .reaction
+        .react(!visible_enemies.is_empty(), tick, &handicap)
+    {
+        visible_enemies.clear();
+    }
     let has_visible_enemies = !visible_enemies.is_empty();
 
+    // Refresh the threat map every so often
+    if player.threat_map.is_empty() || tick >= player.threat_map.tick + THREAT_MAP_INTERVAL {
+        player.threat_map = InfluenceMap::for_faction(sim, player.faction_id, scenario.map_size);
+    }
+
+    // Bases beyond the first: one with enemies close by is defended, and
+    // a new one is founded when there is a site for it
+    let threatened = threatened_expansion(sim, player, &visible_enemies);
+    let expansion = expansion_site(sim, player, nodes);
+
     // Execute tactical decisions
     let army_supply = player.units.len() as u32;
-    let decision = player.executor.decide_action(tick, army_supply, 5, false); // Estimate enemy supply
+    let decision = player.executor.decide_action(
+        tick,
+        army_supply,
+        5, // Estimate enemy supply
+        threatened.is_some(),
+        player.resources,
+        expansion.is_some(),
+    );
+    // Attack only with the strength to take on what is in sight
+    let decision = match decision {
+        TacticalDecision::Attack if !player.executor.strong_enough(&player.threat_map) => {
+            TacticalDecision::Hold
+        }
+        decision => decision,
+    };
 
-    // Enemy base location for attack/scout moves
-    let enemy_base = if player.faction_id == FactionId::Continuity {
-        Vec2Fixed::new(Fixed::from_num(464), Fixed::from_num(256)) // Collegium base
+    // Scouting feeds the player's memory of its enemies; siege units shell
+    // remembered structures they can no longer see
+    update_intel(sim, player, &visible_enemies, tick);
+    let shelling = shell_remembered_structures(sim, player);
+
+    // Units the tactical layer may command: resupplying units and supply
+    // units are steered by the logistics step instead
+    let active_units: Vec<EntityId> = player
+        .units
+        .iter()
+        .copied()
+        .filter(|&id| is_tactically_available(sim, player, id) && !shelling.contains(&id))
+        .collect();
+
+    // The tactical layer orders whole squads: the army, plus a scouting
+    // detachment while holding with nothing in sight
+    let scouting = decision == TacticalDecision::Hold && !has_visible_enemies;
+    let scout_count = if scouting && active_units.len() >= 5 {
+        active_units.len() / 3 // Send 1/3 of army, keep the rest home
     } else {
-        Vec2Fixed::new(Fixed::from_num(48), Fixed::from_num(256)) // Continuity base
+        0
     };
+    let (scouts, army) = active_units.split_at(scout_count);
+    let faction = player.faction_id.short_name();
+    let scout_squad = sync_squad(
+        sim,
+        &mut player.scout_squad,
+        &format!("{faction} scouts"),
+        scouts,
+    );
+    let army_squad = sync_squad(
+        sim,
+        &mut player.army_squad,
+        &format!("{faction} army"),
+        army,
+    );
 
-    // Map center for scouting
-    let map_center = Vec2Fixed::new(Fixed::from_num(256), Fixed::from_num(256));
+    // Attack the closest structure we know of; with nothing known, sweep
+    // the map until something turns up
+    for squad in [army_squad, scout_squad].into_iter().flatten() {
+        advance_search(sim, player, squad, scenario.map_size);
+    }
+    let search_target = search_waypoint(scenario.map_size, player.search_step);
+    let home = player
+        .depot_entity
+        .and_then(|depot| get_entity_position(sim, depot))
+        .unwrap_or(search_target);
+    let attack_target = known_structure_to_attack(player, home).unwrap_or(search_target);
 
     match decision {
         TacticalDecision::Attack => {
@@ -916,71 +1947,438 @@ fn execute_ai_turn(
                 player.first_attack_tick = Some(tick);
             }
 
-            // Send units toward enemy base using ATTACK-MOVE so they engage on the way
-            for &unit_id in &player.units {
-                // Check if unit already has an attack target
-                let has_target = sim
-                    .get_entity(unit_id)
-                    .and_then(|e| e.attack_target.as_ref())
-                    .and_then(|t| t.target)
-                    .is_some();
-
-                if !has_target {
-                    // Attack-move, not just move - engage anything on the way
-                    let _ = sim.apply_command(unit_id, Command::AttackMove(enemy_base));
-                }
-            }
+            // Attack-move, not just move - engage anything on the way
+            order_squad(sim, army_squad, SquadCommand::AttackMove(attack_target));
         }
         TacticalDecision::Defend => {
-            // Rally to base
-            if let Some(depot_id) = player.depot_entity {
-                if let Some(depot_pos) = get_entity_position(sim, depot_id) {
-                    for &unit_id in &player.units {
-                        let _ = sim.apply_command(unit_id, Command::AttackMove(depot_pos));
-                    }
-                }
+            // Rally to the base under attack
+            let base = threatened.or_else(|| {
+                player
+                    .depot_entity
+                    .and_then(|depot| get_entity_position(sim, depot))
+            });
+            if let Some(base) = base {
+                order_squad(sim, army_squad, SquadCommand::AttackMove(base));
             }
         }
         TacticalDecision::Scout => {
-            // Active scouting - send units to find enemies
-            // Scout toward map center first, then enemy base
-            for &unit_id in &player.units {
-                let has_target = sim
-                    .get_entity(unit_id)
-                    .and_then(|e| e.attack_target.as_ref())
-                    .and_then(|t| t.target)
-                    .is_some();
-
-                if !has_target {
-                    let _ = sim.apply_command(unit_id, Command::AttackMove(map_center));
-                }
-            }
+            // Active scouting - send the army to find enemies
+            order_squad(sim, army_squad, SquadCommand::AttackMove(search_target));
         }
         TacticalDecision::Hold => {
             // If we can't see enemies and we're holding, we should still scout!
             // Otherwise we just sit at home forever
-            if !has_visible_enemies && player.units.len() >= 5 {
-                // Send a few units to scout (keep some home for defense)
-                let scouts_to_send = player.units.len() / 3; // Send 1/3 of army
-                for &unit_id in player.units.iter().take(scouts_to_send) {
-                    let has_target = sim
-                        .get_entity(unit_id)
-                        .and_then(|e| e.attack_target.as_ref())
-                        .and_then(|t| t.target)
-                        .is_some();
-
-                    if !has_target {
-                        let _ = sim.apply_command(unit_id, Command::AttackMove(map_center));
-                    }
-                }
-            }
+            order_squad(sim, scout_squad, SquadCommand::AttackMove(search_target));
         }
         TacticalDecision::Expand => {
-            // For now, treat like hold - maybe build expansion later
+            // Lay down the new base, with the army covering the site
+            if let Some(node) = expansion {
+                let site = nodes[node].position;
+                found_expansion(sim, player, node, tick, site, registry, scenario);
+                order_squad(sim, army_squad, SquadCommand::AttackMove(site));
+            }
+        }
+    }
+
+    // Then each unit fights by its behavior tree, overriding its squad's
+    // order while it retreats, kites, focuses fire or defends harvesters
+    apply_tactics(
+        sim,
+        player,
+        &active_units,
+        &visible_enemies,
+        home,
+        scenario.map_size,
+    );
+}
+
+/// Kind of depot the player founds new bases with: its starting one.
+fn expansion_kind(player: &PlayerState) -> Option<&str> {
+    player
+        .depot_entity
+        .and_then(|depot| player.building_kinds.get(&depot))
+        .map(String::as_str)
+}
+
+/// Value of founding a base at an ore node: the ore it holds, less the
+/// trip from `home`.
+fn expansion_score(node: &ResourceNode, home: Vec2Fixed) -> i64 {
+    let trip = home.manhattan_distance(node.position).to_num::<i64>();
+    i64::from(node.remaining) - trip * EXPANSION_TRAVEL_COST
+}
+
+/// Ore node to found a new base at, if any.
+///
+/// Candidates hold enough ore, lie beyond the reach of the player's depots
+/// and clear of the enemy structures it knows of; the best scoring one
+/// (see [`expansion_score`]) wins, ties going to the lowest index. There
+/// is none while an earlier expansion is still going up.
+///
+/// # Bounds
+/// - Iterates over nodes * (depots + known enemy structures)
+fn expansion_site(sim: &Simulation, player: &PlayerState, nodes: &[ResourceNode]) -> Option<usize> {
+    let kind = expansion_kind(player)?;
+    let home = get_entity_position(sim, player.depot_entity?)?;
+    if player
+        .expansions
+        .keys()
+        .any(|&depot| !is_finished(sim, depot))
+        || sim.check_placement(player.faction_id, kind).is_err()
+    {
+        return None;
+    }
+
+    let depots: Vec<Vec2Fixed> = player
+        .buildings
+        .iter()
+        .filter(|&&id| sim.get_entity(id).is_some_and(|e| e.depot.is_some()))
+        .filter_map(|&id| get_entity_position(sim, id))
+        .chain([home])
+        .collect();
+    let enemies: Vec<Vec2Fixed> = player.intel.structures().map(|(_, at)| at).collect();
+    let clear_of = |positions: &[Vec2Fixed], at: Vec2Fixed, range: i32| {
+        let range = Fixed::from_num(range);
+        positions
+            .iter()
+            .all(|position| position.distance_squared(at) > range * range)
+    };
+    nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| node.remaining >= EXPANSION_MIN_ORE)
+        .filter(|(_, node)| clear_of(&depots, node.position, DEPOT_SERVICE_RANGE))
+        .filter(|(_, node)| clear_of(&enemies, node.position, EXPANSION_ENEMY_CLEARANCE))
+        .max_by_key(|&(index, node)| (expansion_score(node, home), std::cmp::Reverse(index)))
+        .map(|(index, _)| index)
+}
+
+/// Have a worker lay down a new base by ore node `node`, near `site`,
+/// paying for it.
+///
+/// Returns the new depot, or `None` if it cannot be paid for or there is
+/// no worker or room.
+fn found_expansion(
+    sim: &mut Simulation,
+    player: &mut PlayerState,
+    node: usize,
+    tick: u64,
+    site: Vec2Fixed,
+    registry: Option<&FactionRegistry>,
+    scenario: &Scenario,
+) -> Option<EntityId> {
+    let kind = expansion_kind(player)?.to_string();
+    let cost = get_building_cost_with_registry(&kind, player.faction_id, registry);
+    let extra = get_building_extra_cost_with_registry(&kind, player.faction_id, registry);
+    let spendable = player
+        .executor
+        .spendable(player.resources, BuildPriority::Normal);
+    if spendable < cost || !player.afford_extra(&extra) {
+        return None;
+    }
+    let worker = free_worker(sim, player)?;
+    let plan = building_plan(&kind, player.faction_id, registry);
+    let position = sim.find_construction_site(site, &plan.footprint)?;
+    let params =
+        building_params_with_registry(&kind, position, player.faction_id, registry, scenario);
+    let depot = sim
+        .order_construction(
+            worker,
+            params,
+            plan.footprint,
+            plan.build_time,
+            u32::try_from(cost).unwrap_or(u32::MAX),
+        )
+        .map_err(|e| trace!(error = %e, "Expansion refused"))
+        .ok()?;
+
+    trace!(faction = ?player.faction_id, node = node, depot = depot, "Founded expansion");
+    player.buildings.push(depot);
+    player.building_kinds.insert(depot, kind);
+    player.pay(cost, &extra);
+    player.expansions.insert(
+        depot,
+        Expansion {
+            node,
+            founded_at: tick,
+            staffed: false,
+        },
+    );
+    player.first_expansion_tick.get_or_insert(tick);
+    Some(depot)
+}
+
+/// Look after the player's expansions: forget destroyed ones, and staff
+/// each newly finished one with harvesters moved over from elsewhere,
+/// plus one built on the spot if it can be paid for.
+///
+/// Half the harvesters, at least, stay where they are.
+fn manage_expansions(
+    sim: &mut Simulation,
+    player: &mut PlayerState,
+    nodes: &[ResourceNode],
+    registry: Option<&FactionRegistry>,
+    scenario: &Scenario,
+) {
+    player
+        .expansions
+        .retain(|&depot, _| sim.get_entity(depot).is_some());
+    let ready: Vec<(EntityId, usize)> = player
+        .expansions
+        .iter()
+        .filter(|&(&depot, expansion)| !expansion.staffed && is_finished(sim, depot))
+        .map(|(&depot, expansion)| (depot, expansion.node))
+        .collect();
+
+    for (depot, node) in ready {
+        if let Some(expansion) = player.expansions.get_mut(&depot) {
+            expansion.staffed = true;
+        }
+        let (Some(node_pos), Some(depot_pos)) = (
+            nodes.get(node).map(|n| n.position),
+            get_entity_position(sim, depot),
+        ) else {
+            continue;
+        };
+
+        // The closest harvesters not carrying a load home move over
+        let mut candidates: Vec<(Fixed, EntityId)> = player
+            .harvesters
+            .iter()
+            .filter(|(_, h)| {
+                !matches!(
+                    h.state,
+                    HarvesterState::Returning(_) | HarvesterState::Depositing
+                )
+            })
+            .filter(|(&id, _)| !is_constructing(sim, id))
+            .filter_map(|(&id, _)| {
+                Some((get_entity_position(sim, id)?.distance_squared(node_pos), id))
+            })
+            .collect();
+        candidates.sort();
+        let moving = EXPANSION_HARVESTERS.min(player.harvesters.len() / 2);
+        let orders: Vec<(EntityId, Command)> = candidates
+            .iter()
+            .take(moving)
+            .map(|&(_, id)| (id, Command::MoveTo(node_pos)))
+            .collect();
+        for (id, _) in &orders {
+            if let Some(harvester) = player.harvesters.get_mut(id) {
+                harvester.state = HarvesterState::MovingToNode(node as EntityId);
+            }
+        }
+        sim.apply_commands(&orders);
+
+        // And one more is built there
+        let cost = get_unit_cost_with_registry("harvester", player.faction_id, registry);
+        let extra = get_unit_extra_cost_with_registry("harvester", player.faction_id, registry);
+        let unit_supply = get_unit_supply_with_registry("harvester", player.faction_id, registry);
+        let spendable = player
+            .executor
+            .spendable(player.resources, BuildPriority::Normal);
+        if spendable >= cost
+            && player.reserves.shortfall(&extra).is_none()
+            && player_supply(sim, player, registry).has_supply(unit_supply)
+        {
+            let (entity_id, resolved_name) = spawn_unit_with_registry(
+                sim,
+                "harvester",
+                depot_pos.x.to_num::<i32>(),
+                depot_pos.y.to_num::<i32>() + HARVEST_RANGE,
+                player.faction_id,
+                registry,
+                scenario,
+            );
+            track_harvester(player, entity_id, &resolved_name, registry);
+            let value = cost + extra.feedstock_value();
+            player.produce_unit(entity_id, resolved_name, sim.get_tick(), value);
+            player.pay(cost, &extra);
         }
     }
 }
 
+/// Position of an expansion with enemies in sight close by, if any.
+fn threatened_expansion(
+    sim: &Simulation,
+    player: &PlayerState,
+    visible_enemies: &[VisibleEnemy],
+) -> Option<Vec2Fixed> {
+    let radius = Fixed::from_num(EXPANSION_DEFEND_RADIUS);
+    player
+        .expansions
+        .keys()
+        .filter_map(|&depot| get_entity_position(sim, depot))
+        .find(|&base| {
+            visible_enemies
+                .iter()
+                .any(|enemy| enemy.position.distance_squared(base) <= radius * radius)
+        })
+}
+
+/// Give each active unit the order its behavior tree picks, if any.
+///
+/// Units the tree has nothing for keep their squad's order. Orders a unit
+/// already has are not repeated.
+///
+/// # Bounds
+/// - Iterates over units * visible enemies (both bounded by MAX_ENTITIES)
+fn apply_tactics(
+    sim: &mut Simulation,
+    player: &PlayerState,
+    units: &[EntityId],
+    visible_enemies: &[VisibleEnemy],
+    home: Vec2Fixed,
+    map_size: (u32, u32),
+) {
+    if visible_enemies.is_empty() {
+        return;
+    }
+    let config = &player.executor.strategy().tactics;
+    let tree = BehaviorTree::from_config(config);
+
+    let threat = |enemy: &VisibleEnemy| {
+        let entity = sim.get_entity(enemy.id);
+        Threat {
+            id: enemy.id,
+            position: enemy.position,
+            health: entity.and_then(|e| e.health).map_or(0, |h| h.current),
+            armed: entity
+                .and_then(|e| e.combat_stats)
+                .is_some_and(|c| c.damage > 0),
+        }
+    };
+    let mut enemies: Vec<&VisibleEnemy> = visible_enemies.iter().collect();
+    enemies.sort_by_key(|enemy| enemy.id);
+
+    // Enemies close to any of our harvesters
+    let defend_within = Fixed::from_num(config.defend_harvesters_within);
+    let harvester_positions: Vec<Vec2Fixed> = player
+        .harvesters
+        .keys()
+        .filter_map(|&id| get_entity_position(sim, id))
+        .collect();
+    let harvester_attackers: Vec<Threat> = enemies
+        .iter()
+        .filter(|enemy| {
+            harvester_positions.iter().any(|&position| {
+                position.distance_squared(enemy.position) <= defend_within * defend_within
+            })
+        })
+        .map(|enemy| threat(enemy))
+        .collect();
+
+    let mut orders = Vec::new();
+    for &unit_id in units {
+        let Some(unit) = sim.get_entity(unit_id) else {
+            continue;
+        };
+        let (Some(combat), Some(health), Some(position)) =
+            (unit.combat_stats.as_ref(), unit.health, unit.position)
+        else {
+            continue;
+        };
+        let view = UnitView {
+            id: unit_id,
+            position: position.value,
+            health_percent: health.current * 100 / health.max.max(1),
+            range: combat.range,
+            reloading: combat.cooldown_remaining > 0,
+            home,
+            map_size,
+            enemies: enemies
+                .iter()
+                .filter(|enemy| sim.can_target(unit_id, enemy.id))
+                .map(|enemy| threat(enemy))
+                .collect(),
+            harvester_attackers: harvester_attackers
+                .iter()
+                .copied()
+                .filter(|enemy| sim.can_target(unit_id, enemy.id))
+                .collect(),
+        };
+
+        let command = match tree.decide(&view) {
+            Some(UnitOrder::Attack(target)) => {
+                let attacking = unit.attack_target.as_ref().and_then(|t| t.target);
+                if attacking == Some(target) {
+                    continue;
+                }
+                Command::Attack(target)
+            }
+            Some(UnitOrder::MoveTo(to)) => {
+                if current_command(unit) == Some(&Command::MoveTo(to)) {
+                    continue;
+                }
+                Command::MoveTo(to)
+            }
+            None => continue,
+        };
+        orders.push((unit_id, command));
+    }
+    sim.apply_commands(&orders);
+}
+
+/// Make a squad hold exactly `units`, creating it if needed.
+///
+/// Returns the squad, or `None` once it has no units.
+fn sync_squad(
+    sim: &mut Simulation,
+    slot: &mut Option<SquadId>,
+    name: &str,
+    units: &[EntityId],
+) -> Option<SquadId> {
+    let Some(id) = slot.filter(|&id| sim.squad(id).is_some()) else {
+        *slot = if units.is_empty() {
+            None
+        } else {
+            sim.create_squad(name, units)
+                .map_err(|e| trace!(squad = name, error = %e, "Could not form squad"))
+                .ok()
+        };
+        return *slot;
+    };
+
+    for &unit in units {
+        if let Err(e) = sim.add_to_squad(id, unit) {
+            trace!(squad = name, unit, error = %e, "Could not join squad");
+        }
+    }
+    let keep: BTreeSet<EntityId> = units.iter().copied().collect();
+    let leavers: Vec<EntityId> = sim
+        .squad(id)
+        .map(|squad| {
+            squad
+                .members()
+                .iter()
+                .copied()
+                .filter(|member| !keep.contains(member))
+                .collect()
+        })
+        .unwrap_or_default();
+    for member in leavers {
+        sim.remove_from_squad(member);
+    }
+
+    *slot = sim.squad(id).map(|_| id);
+    *slot
+}
+
+/// Give a squad an order unless it already has it.
+///
+/// Squad orders persist, so repeating one every tick would only re-path
+/// every member.
+fn order_squad(sim: &mut Simulation, squad: Option<SquadId>, command: SquadCommand) {
+    let Some(id) = squad else {
+        return;
+    };
+    if sim.squad(id).and_then(|s| s.order()) == Some(&command) {
+        return;
+    }
+    if let Err(e) = sim.command_squad(id, command) {
+        trace!(squad = %id, error = %e, "Squad order refused");
+    }
+}
+
 /// Spawn a unit in the simulation using faction data if available.
 /// Returns (entity_id, resolved_unit_name) - the name is the actual faction unit ID, not the role.
 fn spawn_unit_with_registry(
@@ -990,24 +2388,31 @@ fn spawn_unit_with_registry(
     y: i32,
     faction: FactionId,
     registry: Option<&FactionRegistry>,
+    scenario: &Scenario,
 ) -> (EntityId, String) {
     // Try to get unit data from faction registry
     if let Some(reg) = registry {
         // First try exact ID match
         if let Some(unit_data) = reg.get_unit(faction, unit_type) {
             let name = unit_data.id.clone();
-            return (spawn_unit_from_data(sim, unit_data, x, y, faction), name);
+            return (
+                spawn_unit_from_data(sim, unit_data, x, y, faction, scenario),
+                name,
+            );
         }
         // Then try role-based lookup (e.g., "infantry" tag matches "security_team")
         if let Some(unit_data) = reg.get_unit_by_role(faction, unit_type) {
             let name = unit_data.id.clone();
-            return (spawn_unit_from_data(sim, unit_data, x, y, faction), name);
+            return (
+                spawn_unit_from_data(sim, unit_data, x, y, faction, scenario),
+                name,
+            );
         }
     }
 
     // Fall back to hardcoded generic units
     (
-        spawn_unit(sim, unit_type, x, y, faction),
+        spawn_unit(sim, unit_type, x, y, faction, scenario),
         unit_type.to_string(),
     )
 }
@@ -1019,23 +2424,66 @@ fn spawn_unit_from_data(
     x: i32,
     y: i32,
     faction: FactionId,
+    scenario: &Scenario,
 ) -> EntityId {
-    let combat_stats = unit_data
-        .combat
-        .as_ref()
-        .map(|c| CombatStats::new(c.damage, c.range, c.attack_cooldown));
+    let tuning = &scenario.tuning;
+    let logistics = scenario.logistics.as_ref();
+    let combat_stats = unit_data.combat.as_ref().map(|c| {
+        CombatStats::new(tuning.damage(c.damage), c.range, c.attack_cooldown)
+            .with_splash_radius(c.splash_radius)
+            .with_splash_falloff(c.splash_falloff)
+            .with_friendly_fire(c.friendly_fire)
+            .with_targets(c.targets)
+            .with_damage_variance(tuning.damage_variance(c.damage_variance))
+    });
+    let regeneration = unit_data
+        .regen
+        .map(|r| Regeneration::new(r.amount, r.interval_ticks, r.delay_ticks));
+    let is_supply = logistics
+        .is_some_and(|l| unit_data.id == l.supply_unit || unit_data.tags.contains(&l.supply_unit));
+    let (ammunition, resupply) = unit_logistics(logistics, is_supply, combat_stats.as_ref());
 
     sim.spawn_entity(EntitySpawnParams {
         position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y))),
-        health: Some(unit_data.health),
+        health: Some(tuning.health(unit_data.health)),
         movement: Some(unit_data.speed),
         combat_stats,
         faction: Some(FactionMember::new(faction, 0)),
         is_depot: false,
+        ammunition,
+        resupply,
+        regeneration,
+        idle_behavior: idle_behavior(scenario),
+        salvage_value: Some(unit_data.salvage_value()),
+        salvage_rate: unit_data.salvage_rate(),
+        tech_profile: Some(TechProfile::new(&unit_data.id, unit_data.tags.clone())),
+        abilities: unit_data.abilities.clone(),
+        cargo: unit_data
+            .cargo
+            .as_ref()
+            .map(|c| Cargo::new(c.capacity, c.tags.clone())),
+        repairer: unit_data
+            .repair
+            .map(|r| Repairer::new(r.kind, r.amount, r.interval_ticks, r.cost)),
+        domain: unit_data.domain,
+        weapons: unit_data
+            .weapons
+            .iter()
+            .map(|w| {
+                let mut weapon = w.weapon();
+                weapon.damage = tuning.damage(weapon.damage);
+                weapon.damage_variance = tuning.damage_variance(weapon.damage_variance);
+                weapon
+            })
+            .collect(),
+        debug_kind: Some(unit_data.id.clone()),
         ..Default::default()
     })
 }
 
+/// Repairs made by the legacy hardcoded engineer.
+const LEGACY_ENGINEER_REPAIR: Repairer = Repairer::new(RepairKind::Mechanical, 8, 30, 2);
+
 /// Spawn a unit in the simulation (legacy hardcoded fallback).
 fn spawn_unit(
     sim: &mut Simulation,
@@ -1043,7 +2491,10 @@ fn spawn_unit(
     x: i32,
     y: i32,
     faction: FactionId,
+    scenario: &Scenario,
 ) -> EntityId {
+    let tuning = &scenario.tuning;
+    let logistics = scenario.logistics.as_ref();
     let (health, damage, range, speed) = match unit_type {
         "scout" | "patrol_vehicle" => (100, 8, 80, 15),
         "infantry" | "security_team" => (80, 12, 50, 10),
@@ -1053,26 +2504,111 @@ fn spawn_unit(
         "harvester" | "collection_vehicle" => (150, 0, 0, 7),
         "pacification_platform" => (300, 60, 120, 4),
         "sovereign_platform" => (1200, 100, 90, 3),
+        "supply_truck" => (150, 0, 0, 8),
+        "engineer" => (80, 0, 0, 9),
         _ => (100, 12, 60, 10),
     };
 
     let combat_stats = if damage > 0 {
-        Some(CombatStats::new(damage, Fixed::from_num(range), 20))
+        Some(
+            CombatStats::new(tuning.damage(damage), Fixed::from_num(range), 20)
+                .with_damage_variance(tuning.damage_variance(0)),
+        )
     } else {
         None
     };
+    let is_supply = logistics.is_some_and(|l| unit_type == l.supply_unit);
+    let (ammunition, resupply) = unit_logistics(logistics, is_supply, combat_stats.as_ref());
+    let (salvage_value, salvage_rate) = legacy_salvage(unit_type);
 
     sim.spawn_entity(EntitySpawnParams {
         position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y))),
-        health: Some(health),
+        health: Some(tuning.health(health)),
         movement: Some(Fixed::from_num(speed)),
         combat_stats,
         faction: Some(FactionMember::new(faction, 0)),
         is_depot: false,
+        ammunition,
+        resupply,
+        idle_behavior: idle_behavior(scenario),
+        salvage_value: Some(salvage_value),
+        salvage_rate,
+        tech_profile: Some(TechProfile::new(unit_type, legacy_tags(unit_type))),
+        repairer: (unit_type == "engineer").then_some(LEGACY_ENGINEER_REPAIR),
+        debug_kind: Some(unit_type.to_string()),
         ..Default::default()
     })
 }
 
+/// Tags of a legacy hardcoded unit: harvesters and engineers can build.
+fn legacy_tags(unit_type: &str) -> Vec<String> {
+    let tag = match unit_type {
+        "harvester" | "collection_vehicle" => "harvester",
+        "engineer" => "builder",
+        _ => return Vec::new(),
+    };
+    vec![tag.to_string()]
+}
+
+/// Idle behavior for a new unit, if the scenario enables idle wander.
+fn idle_behavior(scenario: &Scenario) -> Option<IdleBehavior> {
+    scenario
+        .idle_wander
+        .map(|idle| IdleBehavior::wander(Fixed::from_num(idle.leash), idle.interval_ticks))
+}
+
+/// Ammunition and resupply components for a new unit under `logistics`.
+///
+/// Supply units become mobile resupply sources; other units carry
+/// ammunition if their weapon reaches at least the ranged threshold.
+fn unit_logistics(
+    logistics: Option<&LogisticsConfig>,
+    is_supply: bool,
+    combat_stats: Option<&CombatStats>,
+) -> (Option<u32>, Option<Resupply>) {
+    let Some(logistics) = logistics else {
+        return (None, None);
+    };
+    if is_supply {
+        let supply = Resupply::new(
+            Fixed::from_num(logistics.supply_radius),
+            logistics.supply_rate,
+        );
+        return (None, Some(supply));
+    }
+    let is_ranged =
+        combat_stats.is_some_and(|c| c.range >= Fixed::from_num(logistics.ranged_min_range));
+    (is_ranged.then_some(logistics.ammo_capacity), None)
+}
+
+/// Resupply component for a depot under `logistics`.
+fn depot_logistics(logistics: Option<&LogisticsConfig>, is_depot: bool) -> Option<Resupply> {
+    logistics
+        .filter(|_| is_depot)
+        .map(|l| Resupply::new(Fixed::from_num(l.depot_radius), l.depot_rate))
+}
+
+/// Give a faction its technologies from faction data, at the scenario's
+/// research speed.
+fn register_tech_tree(
+    sim: &mut Simulation,
+    faction: FactionId,
+    registry: Option<&FactionRegistry>,
+    scenario: &Scenario,
+) {
+    let Some(data) = registry.and_then(|reg| reg.get(faction)) else {
+        return;
+    };
+    let techs = data.technologies.iter().cloned().map(|mut tech| {
+        let ticks = scenario
+            .tuning
+            .research_ticks(u64::from(tech.research_time));
+        tech.research_time = u32::try_from(ticks).unwrap_or(u32::MAX);
+        tech
+    });
+    sim.set_tech_tree(faction, techs);
+}
+
 /// Spawn a building in the simulation using faction data if available.
 fn spawn_building_with_registry(
     sim: &mut Simulation,
@@ -1081,33 +2617,80 @@ fn spawn_building_with_registry(
     y: i32,
     faction: FactionId,
     registry: Option<&FactionRegistry>,
+    scenario: &Scenario,
 ) -> EntityId {
+    let position = Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y));
+    sim.spawn_entity(building_params_with_registry(
+        building_type,
+        position,
+        faction,
+        registry,
+        scenario,
+    ))
+}
+
+/// Spawn parameters for a building, from faction data if available.
+fn building_params_with_registry(
+    building_type: &str,
+    position: Vec2Fixed,
+    faction: FactionId,
+    registry: Option<&FactionRegistry>,
+    scenario: &Scenario,
+) -> EntitySpawnParams {
+    let logistics = scenario.logistics.as_ref();
     // Try to get building data from faction registry
     if let Some(reg) = registry {
         if let Some(building_data) = reg.get_building(faction, building_type) {
             let is_depot = building_data.is_main_base;
-            return sim.spawn_entity(EntitySpawnParams {
-                position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y))),
-                health: Some(building_data.health as u32),
+            let researches = reg.get(faction).is_some_and(|data| {
+                data.technologies
+                    .iter()
+                    .any(|tech| tech.researched_at.as_ref() == Some(&building_data.id))
+            });
+            return EntitySpawnParams {
+                position: Some(position),
+                health: Some(scenario.tuning.health(building_data.health as u32)),
                 faction: Some(FactionMember::new(faction, 0)),
                 is_depot,
+                is_market: building_data.is_market,
+                resupply: depot_logistics(logistics, is_depot),
+                tech_profile: Some(TechProfile::new(
+                    &building_data.id,
+                    building_data.tags.clone(),
+                )),
+                research: researches.then(|| ResearchFacility::new(Some(building_data.id.clone()))),
+                debug_kind: Some(building_data.id.clone()),
                 ..Default::default()
-            });
+            };
         }
     }
 
     // Fall back to hardcoded
-    spawn_building(sim, building_type, x, y, faction)
+    building_params(building_type, position, faction, scenario)
 }
 
-/// Spawn a building in the simulation (legacy hardcoded fallback).
-fn spawn_building(
-    sim: &mut Simulation,
+/// Whether a building kind trains units, not counting the main base.
+fn is_production_building(
+    kind: &str,
+    faction: FactionId,
+    registry: Option<&FactionRegistry>,
+) -> bool {
+    match registry.and_then(|reg| reg.get_building(faction, kind)) {
+        Some(building) => !building.produces.is_empty() && !building.is_main_base,
+        None => matches!(
+            kind,
+            "barracks" | "training_center" | "vehicle_depot" | "walker_facility" | "air_operations"
+        ),
+    }
+}
+
+/// Spawn parameters for a building (legacy hardcoded fallback).
+fn building_params(
     building_type: &str,
-    x: i32,
-    y: i32,
+    position: Vec2Fixed,
     faction: FactionId,
-) -> EntityId {
+    scenario: &Scenario,
+) -> EntitySpawnParams {
     let health = match building_type {
         "command_center" | "depot" | "administration_center" => 1500,
         "barracks" | "training_center" => 500,
@@ -1124,35 +2707,861 @@ fn spawn_building(
         building_type,
         "command_center" | "depot" | "administration_center"
     );
+    // Generic labs research anything
+    let research = matches!(building_type, "tech_lab" | "research_institute")
+        .then(|| ResearchFacility::new(None));
 
-    sim.spawn_entity(EntitySpawnParams {
-        position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y))),
-        health: Some(health),
+    EntitySpawnParams {
+        position: Some(position),
+        health: Some(scenario.tuning.health(health)),
         faction: Some(FactionMember::new(faction, 0)),
         is_depot,
+        resupply: depot_logistics(scenario.logistics.as_ref(), is_depot),
+        tech_profile: Some(TechProfile::new(building_type, Vec::new())),
+        research,
+        debug_kind: Some(building_type.to_string()),
         ..Default::default()
+    }
+}
+
+/// Ticks of work a legacy hardcoded building takes to put up.
+const LEGACY_BUILD_TIME: u32 = 600;
+
+/// How a building is put up: the ground it covers and the work it takes.
+struct BuildingPlan {
+    footprint: BuildingFootprint,
+    build_time: u32,
+}
+
+/// Footprint and build time of a building, from faction data if available.
+fn building_plan(
+    building_type: &str,
+    faction: FactionId,
+    registry: Option<&FactionRegistry>,
+) -> BuildingPlan {
+    match registry.and_then(|reg| reg.get_building(faction, building_type)) {
+        Some(data) => BuildingPlan {
+            footprint: data.footprint,
+            build_time: data.build_time,
+        },
+        None => BuildingPlan {
+            footprint: BuildingFootprint::square(2),
+            build_time: LEGACY_BUILD_TIME,
+        },
+    }
+}
+
+/// A worker free to put up a building: a builder before a harvester, and
+/// never one already at work on a site.
+fn free_worker(sim: &Simulation, player: &PlayerState) -> Option<EntityId> {
+    player
+        .units
+        .iter()
+        .copied()
+        .filter(|&id| sim.can_construct(id))
+        .filter(|&id| !is_constructing(sim, id))
+        .min_by_key(|id| player.harvesters.contains_key(id))
+}
+
+/// Whether a unit is working on a construction site.
+fn is_constructing(sim: &Simulation, unit_id: EntityId) -> bool {
+    sim.get_entity(unit_id)
+        .is_some_and(|e| matches!(current_command(e), Some(Command::Construct(_))))
+}
+
+/// Whether a building is standing and finished.
+fn is_finished(sim: &Simulation, building_id: EntityId) -> bool {
+    sim.get_entity(building_id).is_some_and(|e| {
+        e.building
+            .as_ref()
+            .map_or(true, |b| b.is_construction_complete())
     })
 }
 
 /// Get an entity's faction.
 fn get_entity_faction(sim: &Simulation, entity_id: EntityId) -> Option<FactionId> {
     sim.get_entity(entity_id)
-        .and_then(|e| e.faction.as_ref())
+        .and_then(|e| e.faction)
         .map(|f| f.faction)
 }
 
 /// Get an entity's position.
 fn get_entity_position(sim: &Simulation, entity_id: EntityId) -> Option<Vec2Fixed> {
     sim.get_entity(entity_id)
-        .and_then(|e| e.position.as_ref())
+        .and_then(|e| e.position)
         .map(|p| p.value)
 }
 
-/// Acquire targets for units - find nearby enemies and issue Attack commands.
-/// Prioritize depot (HQ) when in range to enable victory.
-/// Uses visibility system - AI can only target what it can see.
+/// Whether the tactical layer may command this unit.
+fn is_tactically_available(sim: &Simulation, player: &PlayerState, unit_id: EntityId) -> bool {
+    if player.resupplying.contains(&unit_id) || player.harvesters.contains_key(&unit_id) {
+        return false;
+    }
+    !sim.get_entity(unit_id).is_some_and(|e| {
+        e.resupply.is_some()
+            || matches!(
+                current_command(e),
+                Some(Command::Repair(_) | Command::Construct(_))
+            )
+    })
+}
+
+/// The command an entity is carrying out, if any.
+fn current_command(entity: EntityRef<'_>) -> Option<&Command> {
+    entity.row.command_queue.as_ref().and_then(|q| q.current())
+}
+
+/// Buy the rarer resource a held-back purchase is waiting on at the
+/// faction's market, if the strategy trades.
 ///
-/// # Bounds
+/// Pays with feedstock above the policy's reserve, or with another rarer
+/// resource the purchase does not need. Makes at most one trade per
+/// interval, and none while slippage is past what the policy accepts.
+fn manage_trades(sim: &Simulation, player: &mut PlayerState, tick: u64) {
+    let Some(policy) = player.executor.trading().cloned() else {
+        return;
+    };
+    if player
+        .last_trade_tick
+        .is_some_and(|last| tick < last + policy.interval_ticks)
+    {
+        return;
+    }
+    let Some(wanted) = player.reserves.shortfall(&player.reserves_needed) else {
+        return;
+    };
+    if !sim.owns_market(player.faction_id) {
+        return;
+    }
+
+    let spare = |kind: ResourceKind| match kind {
+        ResourceKind::Feedstock => player.resources - policy.keep_feedstock,
+        _ => i64::from(player.reserves.get(kind) - player.reserves_needed.get(kind)),
+    };
+    let offer = ResourceKind::ALL
+        .into_iter()
+        .filter(|&from| from != wanted && player.market_rules.rate(from, wanted).is_some())
+        .map(|from| (from, spare(from).min(policy.batch)))
+        .find(|&(_, amount)| amount > 0);
+    let Some((from, amount)) = offer else {
+        return;
+    };
+    let amount = i32::try_from(amount).unwrap_or(i32::MAX);
+    let rules = &player.market_rules;
+    let acceptable = player
+        .market
+        .quote(rules, tick, from, wanted, amount)
+        .is_ok_and(|quote| quote.slippage_percent <= policy.max_slippage_percent);
+    if !acceptable {
+        return;
+    }
+
+    let feedstock = i32::try_from(player.resources).unwrap_or(i32::MAX);
+    let mut economy =
+        PlayerEconomy::new(feedstock, i32::MAX).with_reserves(player.reserves.clone());
+    let Ok(trade) = player
+        .market
+        .trade(rules, tick, from, wanted, amount, &mut economy)
+    else {
+        return;
+    };
+    player.resources += i64::from(economy.feedstock - feedstock);
+    player.reserves = economy.reserves;
+    player.last_trade_tick = Some(tick);
+    player.trades += 1;
+    player.trade_value_sold += i64::from(trade.given) * from.feedstock_value();
+    player.trade_value_bought += i64::from(trade.received) * wanted.feedstock_value();
+    debug!(
+        "{:?} traded {} {} for {} {} ({}% slippage)",
+        player.faction_id, trade.given, from, trade.received, wanted, trade.slippage_percent
+    );
+}
+
+/// Queue research of the first technology in the strategy's
+/// [`tech_priorities`](Strategy::tech_priorities) that can be researched
+/// now, if there are resources to spare for it.
+///
+/// Technologies already researched or queued, or whose prerequisites are
+/// not met yet, are passed over. Only one is queued per turn, so the
+/// strategy's order is kept.
+///
+/// # Bounds
+/// - Iterates over tech_priorities (bounded by the strategy file)
+fn manage_tech(sim: &mut Simulation, player: &mut PlayerState, tick: u64) {
+    let next = player
+        .executor
+        .strategy()
+        .tech_priorities
+        .iter()
+        .find_map(|tech_id| {
+            let building = sim.research_facility_for(player.faction_id, tech_id)?;
+            let cost = sim.validate_research(building, tech_id).ok()?.cost;
+            Some((building, tech_id.clone(), i64::from(cost)))
+        });
+    let Some((building, tech_id, cost)) = next else {
+        return;
+    };
+    let spendable = player
+        .executor
+        .spendable(player.resources, BuildPriority::Low);
+    if spendable >= cost && sim.queue_research(building, &tech_id).is_ok() {
+        player.resources -= cost;
+        trace!(
+            faction = ?player.faction_id,
+            tech = %tech_id,
+            cost = cost,
+            tick = tick,
+            "Started priority research"
+        );
+    }
+}
+
+/// Send engineers to mend structures below the strategy's repair
+/// threshold, nearest free engineer first.
+///
+/// Repairers already at work, and those whose faction cannot afford a
+/// repair, are left alone.
+///
+/// # Bounds
+/// - Iterates over player.units and player.buildings (bounded by MAX_ENTITIES)
+/// - Nearest-repairer search is O(buildings * repairers), both bounded
+fn manage_repairs(sim: &mut Simulation, player: &PlayerState) {
+    let threshold = player.executor.repair_below_percent();
+    if threshold == 0 {
+        return;
+    }
+
+    let mut in_hand = BTreeSet::new();
+    let mut free: Vec<(EntityId, Vec2Fixed)> = Vec::new();
+    for &unit_id in &player.units {
+        let Some(entity) = sim.get_entity(unit_id) else {
+            continue;
+        };
+        let (Some(repairer), Some(position)) = (entity.repairer, entity.position) else {
+            continue;
+        };
+        if let Some(&Command::Repair(target)) = current_command(entity) {
+            in_hand.insert(target);
+        } else if repairer.kind.treats(true, None) && i64::from(repairer.cost) <= player.resources {
+            free.push((unit_id, position.value));
+        }
+    }
+
+    let mut orders: Vec<(EntityId, Command)> = Vec::new();
+    for &building_id in &player.buildings {
+        if free.is_empty() {
+            break;
+        }
+        let Some(entity) = sim.get_entity(building_id) else {
+            continue;
+        };
+        let (Some(health), Some(position)) = (entity.health, entity.position) else {
+            continue;
+        };
+        let damaged = !health.is_dead()
+            && u64::from(health.current) * 100 < u64::from(threshold) * u64::from(health.max);
+        if !damaged || in_hand.contains(&building_id) {
+            continue;
+        }
+        let nearest = free
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, (_, from))| from.distance_squared(position.value))
+            .map(|(index, _)| index);
+        if let Some(index) = nearest {
+            let (unit_id, _) = free.remove(index);
+            orders.push((unit_id, Command::Repair(building_id)));
+        }
+    }
+    sim.apply_commands(&orders);
+}
+
+/// Record what the player sees of its enemies into its intel.
+///
+/// Structures stay remembered after they drop out of sight, so siege units
+/// can keep shelling them; units are forgotten once stale. The headless AI
+/// has no per-location vision query, so anything known destroyed is
+/// dropped at once.
+fn update_intel(
+    sim: &Simulation,
+    player: &mut PlayerState,
+    visible_enemies: &[VisibleEnemy],
+    tick: u64,
+) {
+    for enemy in visible_enemies {
+        let Some(entity) = sim.get_entity(enemy.id) else {
+            continue;
+        };
+        let (kind, tags) = entity.tech_profile.as_ref().map_or_else(
+            || ("unknown".to_string(), Vec::new()),
+            |profile| (profile.kind.clone(), profile.tags.clone()),
+        );
+        player.intel.observe(
+            enemy.id,
+            Sighting {
+                kind,
+                tags,
+                position: enemy.position,
+                tick,
+                structure: entity.movement.is_none(),
+                depot: entity.depot.is_some(),
+            },
+        );
+    }
+    let gone: Vec<EntityId> = player
+        .intel
+        .remembered()
+        .filter(|&id| sim.get_entity(id).is_none())
+        .collect();
+    for id in gone {
+        player.intel.forget(id);
+    }
+    player.intel.forget_stale(tick);
+}
+
+/// Remembered enemy structure to attack from `from`: the least defended
+/// on the threat map, the closest of those.
+fn known_structure_to_attack(player: &PlayerState, from: Vec2Fixed) -> Option<Vec2Fixed> {
+    let mut known: Vec<Vec2Fixed> = player.intel.structures().map(|(_, at)| at).collect();
+    known.sort_by_key(|&position| from.distance_squared(position));
+    player.executor.pick_target(&player.threat_map, &known)
+}
+
+/// Cells of the search sweep on a 3x3 grid over the map, as (column, row),
+/// spiralling out from the center.
+const SEARCH_PATTERN: [(i32, i32); 9] = [
+    (1, 1),
+    (2, 1),
+    (2, 2),
+    (1, 2),
+    (0, 2),
+    (0, 1),
+    (0, 0),
+    (1, 0),
+    (2, 0),
+];
+
+/// Waypoint `step` of the search sweep: the center of a grid cell, cycling
+/// through [`SEARCH_PATTERN`].
+fn search_waypoint(map_size: (u32, u32), step: u32) -> Vec2Fixed {
+    let (column, row) = SEARCH_PATTERN[step as usize % SEARCH_PATTERN.len()];
+    let cell = |index: i32, size: u32| Fixed::from_num((2 * index + 1) * size as i32 / 6);
+    Vec2Fixed::new(cell(column, map_size.0), cell(row, map_size.1))
+}
+
+/// Move on to the next search waypoint once any member of `squad` reaches
+/// the current one.
+///
+/// A waypoint counts as reached within a sixth of the map's smaller side,
+/// i.e. half a grid cell.
+fn advance_search(
+    sim: &Simulation,
+    player: &mut PlayerState,
+    squad: SquadId,
+    map_size: (u32, u32),
+) {
+    let Some(squad) = sim.squad(squad) else {
+        return;
+    };
+    let waypoint = search_waypoint(map_size, player.search_step);
+    let radius = Fixed::from_num(map_size.0.min(map_size.1) / 6);
+    let arrived = squad.members().iter().any(|&member| {
+        get_entity_position(sim, member)
+            .is_some_and(|position| position.distance_squared(waypoint) <= radius * radius)
+    });
+    if arrived {
+        player.search_step = player.search_step.wrapping_add(1);
+    }
+}
+
+/// Order idle splash-weapon units to attack the ground at remembered enemy
+/// structures that are out of sight but within reach.
+///
+/// Returns the units now shelling, which the tactical layer leaves alone.
+/// Visible targets take precedence: units ordered to attack a unit by
+/// target acquisition are skipped.
+///
+/// # Bounds
+/// - Iterates over player.units (bounded by MAX_ENTITIES)
+/// - Ghost search is O(units * ghosts), both bounded
+fn shell_remembered_structures(sim: &mut Simulation, player: &PlayerState) -> BTreeSet<EntityId> {
+    let ghosts: Vec<Vec2Fixed> = player
+        .intel
+        .structures()
+        .filter(|&(id, _)| !sim.is_visible_to(player.faction_id, id))
+        .map(|(_, position)| position)
+        .collect();
+
+    let mut shelling = BTreeSet::new();
+    let mut orders = Vec::new();
+    for &unit_id in &player.units {
+        if !is_tactically_available(sim, player, unit_id) {
+            continue;
+        }
+        let Some(unit) = sim.get_entity(unit_id) else {
+            continue;
+        };
+        let (Some(position), Some(stats)) = (unit.position, unit.combat_stats) else {
+            continue;
+        };
+        if !stats.can_attack_ground() {
+            continue;
+        }
+        let current = unit
+            .command_queue
+            .as_ref()
+            .and_then(|q| q.current().cloned());
+        let has_target = unit.attack_target.and_then(|t| t.target).is_some();
+        if has_target || matches!(current, Some(Command::Attack(_))) {
+            continue;
+        }
+
+        let reach = stats.range * Fixed::from_num(SHELLING_REACH_FACTOR);
+        let target = ghosts
+            .iter()
+            .copied()
+            .filter(|&ghost| position.value.distance_squared(ghost) <= reach * reach)
+            .min_by_key(|&ghost| position.value.distance_squared(ghost));
+
+        match target {
+            Some(point) => {
+                if current != Some(Command::AttackGround(point)) {
+                    orders.push((unit_id, Command::AttackGround(point)));
+                }
+                shelling.insert(unit_id);
+            }
+            None => {
+                // Ghost gone or out of reach - hand back to the tactical layer
+                if matches!(current, Some(Command::AttackGround(_))) {
+                    orders.push((unit_id, Command::Stop));
+                }
+            }
+        }
+    }
+    sim.apply_commands(&orders);
+    shelling
+}
+
+/// Rotate units low on ammunition out to the nearest friendly resupply
+/// source, release them once full, and keep supply units with the army.
+///
+/// # Bounds
+/// - Iterates over player.units (bounded by MAX_ENTITIES)
+/// - Nearest-source search is O(units * sources), both bounded
+fn manage_resupply(
+    sim: &mut Simulation,
+    player: &mut PlayerState,
+    logistics: &LogisticsConfig,
+    tick: u64,
+) {
+    // Forget units that died on the way
+    player
+        .resupplying
+        .retain(|id| player.units.contains(id) && sim.get_entity(*id).is_some());
+
+    let sources: Vec<Vec2Fixed> = player
+        .buildings
+        .iter()
+        .chain(player.units.iter())
+        .filter_map(|&id| {
+            let entity = sim.get_entity(id)?;
+            entity.resupply?;
+            Some(entity.position?.value)
+        })
+        .collect();
+
+    let repath = tick % RESUPPLY_REPATH_INTERVAL == 0;
+    let mut moves: Vec<(EntityId, Vec2Fixed)> = Vec::new();
+    let mut supply_units: Vec<EntityId> = Vec::new();
+    let mut army_sum = Vec2Fixed::ZERO;
+    let mut army_count = 0u32;
+
+    for &unit_id in &player.units {
+        let Some(entity) = sim.get_entity(unit_id) else {
+            continue;
+        };
+        let Some(position) = entity.position.map(|p| p.value) else {
+            continue;
+        };
+        if entity.resupply.is_some() {
+            supply_units.push(unit_id);
+            continue;
+        }
+
+        if let Some(ammo) = entity.ammunition {
+            if player.resupplying.contains(&unit_id) {
+                if ammo.is_full() {
+                    player.resupplying.remove(&unit_id);
+                    trace!(faction = ?player.faction_id, unit = unit_id, "Resupplied");
+                } else {
+                    if repath {
+                        if let Some(dest) = nearest_position(&sources, position) {
+                            moves.push((unit_id, dest));
+                        }
+                    }
+                    continue;
+                }
+            } else if ammo.percentage() <= logistics.rotate_out_below_pct {
+                if let Some(dest) = nearest_position(&sources, position) {
+                    player.resupplying.insert(unit_id);
+                    player.resupply_trips += 1;
+                    moves.push((unit_id, dest));
+                    continue;
+                }
+            }
+        }
+
+        if entity.combat_stats.is_some() {
+            army_sum = army_sum + position;
+            army_count += 1;
+        }
+    }
+
+    if repath && army_count > 0 {
+        let count = Fixed::from_num(army_count);
+        let rally = Vec2Fixed::new(army_sum.x / count, army_sum.y / count);
+        moves.extend(supply_units.into_iter().map(|id| (id, rally)));
+    }
+
+    let orders: Vec<(EntityId, Command)> = moves
+        .into_iter()
+        .map(|(unit_id, dest)| (unit_id, Command::MoveTo(dest)))
+        .collect();
+    sim.apply_commands(&orders);
+
+    player.ticks_resupplying += player.resupplying.len() as u64;
+}
+
+/// Start the harvester state machine for a new unit if it is a harvester.
+/// Spawn a unit for a player and record it as fielded.
+fn spawn_player_unit(
+    sim: &mut Simulation,
+    player: &mut PlayerState,
+    kind: &str,
+    position: (i32, i32),
+    registry: Option<&FactionRegistry>,
+    scenario: &Scenario,
+) -> EntityId {
+    let (entity_id, resolved_name) = spawn_unit_with_registry(
+        sim,
+        kind,
+        position.0,
+        position.1,
+        player.faction_id,
+        registry,
+        scenario,
+    );
+    track_harvester(player, entity_id, &resolved_name, registry);
+    player.field_unit(entity_id, resolved_name, sim.get_tick(), 0);
+    entity_id
+}
+
+/// Carry out a fired scenario trigger's actions.
+///
+/// Actions for a faction no player is using are skipped.
+fn apply_trigger(
+    sim: &mut Simulation,
+    mut players: [&mut PlayerState; 2],
+    fired: &TriggerFired,
+    registry: Option<&FactionRegistry>,
+    scenario: &Scenario,
+) {
+    info!(tick = sim.get_tick(), trigger = %fired.trigger, "Scenario trigger fired");
+    for action in &fired.actions {
+        match action {
+            TriggerAction::SpawnUnits {
+                faction,
+                kind,
+                position,
+                count,
+            } => {
+                let Some(player) = players.iter_mut().find(|p| p.faction_id == *faction) else {
+                    continue;
+                };
+                for _ in 0..*count {
+                    spawn_player_unit(sim, player, kind, *position, registry, scenario);
+                }
+                player.update_peak_army();
+            }
+            TriggerAction::GrantResources { faction, amount } => {
+                if let Some(player) = players.iter_mut().find(|p| p.faction_id == *faction) {
+                    player.resources += amount;
+                }
+            }
+            TriggerAction::Message(text) => {
+                info!(trigger = %fired.trigger, message = %text, "Scenario message");
+            }
+        }
+    }
+}
+
+fn track_harvester(
+    player: &mut PlayerState,
+    unit_id: EntityId,
+    unit_kind: &str,
+    registry: Option<&FactionRegistry>,
+) {
+    let is_harvester = match registry.and_then(|reg| reg.get_unit(player.faction_id, unit_kind)) {
+        Some(unit_data) => unit_data.has_tag("harvester"),
+        None => matches!(unit_kind, "harvester" | "collection_vehicle"),
+    };
+    if is_harvester {
+        player.harvesters.insert(
+            unit_id,
+            Harvester::new(HARVESTER_CAPACITY, HARVESTER_GATHER_RATE),
+        );
+    }
+}
+
+/// Run one harvest action of the `rts_core` economy for a player.
+///
+/// Harvesters shuttle between ore nodes and the player's depots; whatever
+/// they unload is paid into the player's resources, scaled by the
+/// scenario's yield tuning and less any army upkeep. Players share the nodes, so whoever gathers
+/// first depletes them.
+fn run_harvesters(
+    sim: &mut Simulation,
+    player: &mut PlayerState,
+    nodes: &mut [ResourceNode],
+    scenario: &Scenario,
+    tick: u64,
+) {
+    player
+        .harvesters
+        .retain(|id, _| sim.get_entity(*id).is_some());
+    if player.harvesters.is_empty() {
+        return;
+    }
+
+    let depots: Vec<(EntityId, Vec2Fixed)> = player
+        .buildings
+        .iter()
+        .filter_map(|&id| {
+            let entity = sim.get_entity(id)?;
+            entity.depot?;
+            Some((id, entity.position?.value))
+        })
+        .collect();
+    let positions: BTreeMap<EntityId, Vec2Fixed> = player
+        .harvesters
+        .keys()
+        .filter_map(|&id| Some((id, get_entity_position(sim, id)?)))
+        .collect();
+    let node_positions: Vec<Vec2Fixed> = nodes.iter().map(|n| n.position).collect();
+
+    // Send every other harvester after a rarer resource a purchase is
+    // waiting on, while a node of it is left
+    let wanted = player
+        .reserves
+        .shortfall(&player.reserves_needed)
+        .filter(|&kind| nodes.iter().any(|n| n.kind == kind && !n.is_depleted()));
+    for (i, harvester) in player.harvesters.values_mut().enumerate() {
+        harvester.wanted = wanted.filter(|_| i % 2 == 0);
+    }
+
+    let before: BTreeMap<EntityId, HarvesterState> = player
+        .harvesters
+        .iter()
+        .map(|(&id, h)| (id, h.state))
+        .collect();
+
+    // Unlimited storage: the runner's resource pool has no cap
+    let mut economy = PlayerEconomy::new(0, i32::MAX);
+    let events = {
+        let mut harvesters: Vec<(EntityId, &mut Harvester, &Vec2Fixed)> = player
+            .harvesters
+            .iter_mut()
+            .filter_map(|(&id, h)| Some((id, h, positions.get(&id)?)))
+            .collect();
+        let mut node_refs: Vec<(EntityId, &mut ResourceNode, &Vec2Fixed)> = nodes
+            .iter_mut()
+            .zip(&node_positions)
+            .enumerate()
+            .map(|(i, (node, pos))| (i as EntityId, node, pos))
+            .collect();
+        let depot_refs: Vec<(EntityId, &Vec2Fixed)> =
+            depots.iter().map(|(id, pos)| (*id, pos)).collect();
+        economy_system_in_range(
+            &mut harvesters,
+            &mut node_refs,
+            &depot_refs,
+            &mut economy,
+            Fixed::from_num(HARVEST_RANGE),
+        )
+    };
+
+    for event in &events {
+        if let EconomyEvent::NodeDepleted { node } = event {
+            trace!(faction = ?player.faction_id, node = node, "Ore node depleted");
+        }
+    }
+    let income = scenario.tuning.resource_yield(i64::from(economy.feedstock));
+    let income = player.handicap.income(income);
+    let income = player.upkeep.collect(&player.upkeep_model, income);
+    player.resources += income;
+    player.resources_from_harvest += income;
+    player.reserves.deposit(&economy.reserves);
+    player.reserves_gathered.deposit(&economy.reserves);
+
+    // Walk harvesters to their next stop; re-issue periodically for any
+    // that stopped short of it
+    let repath = tick % HARVESTER_REPATH_INTERVAL == 0;
+    let orders: Vec<(EntityId, Command)> = player
+        .harvesters
+        .iter()
+        .filter_map(|(&id, harvester)| {
+            let dest = match harvester.state {
+                HarvesterState::MovingToNode(node) => *node_positions.get(node as usize)?,
+                HarvesterState::Returning(depot) => depots.iter().find(|(d, _)| *d == depot)?.1,
+                _ => return None,
+            };
+            if is_constructing(sim, id) {
+                return None;
+            }
+            let changed = before.get(&id) != Some(&harvester.state);
+            let stopped = sim
+                .get_entity(id)
+                .and_then(|e| e.row.command_queue.as_ref())
+                .map_or(true, |q| q.current().is_none());
+            (changed || (repath && stopped)).then_some((id, Command::MoveTo(dest)))
+        })
+        .collect();
+    sim.apply_commands(&orders);
+}
+
+/// Elimination won by `winner`, counting its structures still standing.
+fn elimination_by(sim: &Simulation, winner: &PlayerState) -> WinCondition {
+    let surviving = winner
+        .buildings
+        .iter()
+        .filter(|&&id| sim.get_entity(id).is_some())
+        .count();
+    WinCondition::Elimination {
+        eliminating_player: winner.faction_id,
+        surviving_structures: u32::try_from(surviving).unwrap_or(u32::MAX),
+    }
+}
+
+/// What a player has going for it, for win-probability estimates.
+fn side_strength(sim: &Simulation, player: &PlayerState) -> SideStrength {
+    let army = player
+        .units
+        .iter()
+        .filter_map(|&id| sim.get_entity(id))
+        .map(influence::strength)
+        .sum();
+    let base = player
+        .buildings
+        .iter()
+        .filter_map(|&id| sim.get_entity(id))
+        .filter_map(|building| building.health)
+        .map(|health| i64::from(health.current))
+        .sum();
+    SideStrength {
+        army,
+        economy: player.resources + player.resources_from_harvest + player.resources_from_salvage,
+        base,
+    }
+}
+
+/// Add a sample to both players' time series: army value, income since
+/// the last sample, living units by tier, mean army position and map
+/// control.
+///
+/// A region is held by whichever side has more fighting strength felt
+/// there, counting every armed unit regardless of fog.
+fn sample_timelines(
+    sim: &Simulation,
+    players: [&mut PlayerState; 2],
+    map_size: (u32, u32),
+    interval: u64,
+    registry: Option<&FactionRegistry>,
+    tick: u64,
+) {
+    let mut control = InfluenceMap::new(map_size, influence::INFLUENCE_CELL_SIZE, tick);
+    for (side, player) in players.iter().enumerate() {
+        for entity in player.units.iter().filter_map(|&id| sim.get_entity(id)) {
+            if let Some(position) = entity.position {
+                control.add(position.value, influence::strength(entity), side == 0);
+            }
+        }
+    }
+    let (width, height) = control.size();
+    let mut held = [0u32; 2];
+    for cell in (0..height).flat_map(|y| (0..width).map(move |x| (x, y))) {
+        match control.own_at(cell).cmp(&control.enemy_at(cell)) {
+            std::cmp::Ordering::Greater => held[0] += 1,
+            std::cmp::Ordering::Less => held[1] += 1,
+            std::cmp::Ordering::Equal => {}
+        }
+    }
+    let regions = f64::from(width * height);
+
+    for (player, held) in players.into_iter().zip(held) {
+        let mut sample = MetricsSample {
+            tick,
+            ..MetricsSample::default()
+        };
+        let (mut x, mut y, mut army) = (0.0, 0.0, 0u32);
+        for &id in &player.units {
+            let Some(entity) = sim.get_entity(id) else {
+                continue;
+            };
+            let tier = player
+                .unit_kinds
+                .get(&id)
+                .map_or(1, |kind| unit_tier(registry, player.faction_id, kind));
+            sample.units_by_tier[tier.clamp(1, 3) as usize - 1] += 1;
+            if !is_army_unit(entity) {
+                continue;
+            }
+            if let Some(health) = entity.health {
+                sample.army_value += u64::from(health.current);
+            }
+            if let Some(position) = entity.position {
+                x += position.value.x.to_num::<f64>();
+                y += position.value.y.to_num::<f64>();
+                army += 1;
+            }
+        }
+
+        let timeline = &mut player.timeline;
+        let gathered = player.resources_from_harvest + player.resources_from_salvage;
+        let ticks_per_minute = 60 * i64::from(TICK_RATE);
+        sample.income_per_minute = (gathered - timeline.gathered) * ticks_per_minute
+            / i64::try_from(interval.max(1)).unwrap_or(i64::MAX);
+        timeline.gathered = gathered;
+        timeline.samples.push(sample);
+        timeline
+            .map_control
+            .push((tick, 100.0 * f64::from(held) / regions));
+        if army > 0 {
+            let count = f64::from(army);
+            timeline.army_positions.push((tick, x / count, y / count));
+        }
+    }
+}
+
+/// Tech tier of a unit kind, from faction data when loaded.
+fn unit_tier(registry: Option<&FactionRegistry>, faction: FactionId, kind: &str) -> u32 {
+    registry
+        .and_then(|reg| reg.get_unit(faction, kind))
+        .map_or_else(|| get_unit_tier(kind), |unit| u32::from(unit.tier))
+}
+
+/// Closest of `candidates` to `from`.
+fn nearest_position(candidates: &[Vec2Fixed], from: Vec2Fixed) -> Option<Vec2Fixed> {
+    candidates
+        .iter()
+        .copied()
+        .min_by_key(|&pos| from.distance_squared(pos))
+}
+
+/// Acquire targets for units - find nearby enemies and issue Attack commands.
+/// Prioritize depot (HQ) when in range to enable victory.
+/// Uses visibility system - AI can only target what it can see.
+///
+/// # Bounds
 /// - Iterates over player.units (bounded by MAX_ENTITIES)
 /// - Iterates over visible_enemies (bounded by MAX_ENTITIES)
 /// - Total work: O(units * visible_enemies) with both bounded
@@ -1188,7 +3597,9 @@ fn acquire_targets_for_units(sim: &mut Simulation, player: &PlayerState) {
         .saturating_mul(visible_enemies.len().saturating_add(1));
 
     // For each of our units, find best target
-    // ALWAYS prioritize depot/HQ when in range - re-evaluate every tick
+    // ALWAYS prioritize depot/HQ when in range - re-evaluate every tick.
+    // Orders are issued as one batch once every unit has been considered.
+    let mut orders = Vec::with_capacity(player.units.len());
     for &unit_id in &player.units {
         iterations += 1;
         if iterations > max_iterations {
@@ -1199,7 +3610,7 @@ fn acquire_targets_for_units(sim: &mut Simulation, player: &PlayerState) {
             );
             break;
         }
-        let Some(unit_pos) = get_entity_position(sim, unit_id) else {
+        let Some(unit_pos) = sim.acquisition_position(unit_id) else {
             continue;
         };
 
@@ -1212,6 +3623,11 @@ fn acquire_targets_for_units(sim: &mut Simulation, player: &PlayerState) {
             continue;
         }
 
+        // Leave units heading back to resupply alone
+        if player.resupplying.contains(&unit_id) {
+            continue;
+        }
+
         // Check if depot is within attack range - ALWAYS switch to it
         let attack_range = unit
             .combat_stats
@@ -1221,7 +3637,13 @@ fn acquire_targets_for_units(sim: &mut Simulation, player: &PlayerState) {
         let depot_range_sq = attack_range * attack_range * Fixed::from_num(4); // 2x attack range
 
         let mut depot_in_range: Option<EntityId> = None;
-        for enemy in &visible_enemies {
+        // Only enemies in layers this unit's weapon can hit
+        let reachable: Vec<_> = visible_enemies
+            .iter()
+            .filter(|enemy| sim.can_target(unit_id, enemy.id))
+            .collect();
+
+        for enemy in &reachable {
             if enemy.is_depot {
                 let dist_sq = unit_pos.distance_squared(enemy.position);
                 if dist_sq <= depot_range_sq {
@@ -1242,7 +3664,7 @@ fn acquire_targets_for_units(sim: &mut Simulation, player: &PlayerState) {
                 .unwrap_or(false);
 
             if !currently_attacking_depot {
-                let _ = sim.apply_command(unit_id, Command::Attack(depot_id));
+                orders.push((unit_id, Command::Attack(depot_id)));
             }
             continue;
         }
@@ -1261,7 +3683,7 @@ fn acquire_targets_for_units(sim: &mut Simulation, player: &PlayerState) {
             let mut best_target: Option<EntityId> = None;
             let mut best_dist = Fixed::MAX;
 
-            for enemy in &visible_enemies {
+            for enemy in &reachable {
                 let dist_sq = unit_pos.distance_squared(enemy.position);
                 if dist_sq < best_dist {
                     best_dist = dist_sq;
@@ -1270,10 +3692,11 @@ fn acquire_targets_for_units(sim: &mut Simulation, player: &PlayerState) {
             }
 
             if let Some(target_id) = best_target {
-                let _ = sim.apply_command(unit_id, Command::Attack(target_id));
+                orders.push((unit_id, Command::Attack(target_id)));
             }
         }
     }
+    sim.apply_commands(&orders);
 }
 
 /// Get unit production cost with optional faction data lookup.
@@ -1295,6 +3718,22 @@ fn get_unit_cost_with_registry(
     get_unit_cost(unit_type)
 }
 
+/// Get the rarer resources a unit costs beside feedstock, with optional
+/// faction data lookup. Without faction data units cost feedstock only.
+fn get_unit_extra_cost_with_registry(
+    unit_type: &str,
+    faction: FactionId,
+    registry: Option<&FactionRegistry>,
+) -> ResourceAmounts {
+    registry
+        .and_then(|reg| {
+            reg.get_unit(faction, unit_type)
+                .or_else(|| reg.get_unit_by_role(faction, unit_type))
+        })
+        .map(|unit_data| unit_data.extra_cost.clone())
+        .unwrap_or_default()
+}
+
 /// Get unit production cost (legacy hardcoded fallback).
 fn get_unit_cost(unit_type: &str) -> i64 {
     match unit_type {
@@ -1302,6 +3741,7 @@ fn get_unit_cost(unit_type: &str) -> i64 {
         "infantry" | "security_team" => 50,
         "crowd_management_unit" => 75,
         "ranger" => 100,
+        "engineer" => 60,
         "tank" | "guardian_mech" => 300,
         "harvester" | "collection_vehicle" => 100,
         "pacification_platform" => 250,
@@ -1312,27 +3752,120 @@ fn get_unit_cost(unit_type: &str) -> i64 {
     }
 }
 
-/// Get building cost with optional faction data lookup.
-fn get_building_cost_with_registry(
-    building_type: &str,
+/// Get the supply a unit takes, with optional faction data lookup.
+fn get_unit_supply_with_registry(
+    unit_type: &str,
     faction: FactionId,
     registry: Option<&FactionRegistry>,
-) -> i64 {
+) -> u32 {
     if let Some(reg) = registry {
-        if let Some(building_data) = reg.get_building(faction, building_type) {
-            return building_data.cost as i64;
+        if let Some(unit_data) = reg
+            .get_unit(faction, unit_type)
+            .or_else(|| reg.get_unit_by_role(faction, unit_type))
+        {
+            return unit_data.supply_cost();
         }
     }
-    get_building_cost(building_type)
+    get_unit_supply(unit_type)
 }
 
-/// Get building construction cost (legacy hardcoded fallback).
-fn get_building_cost(building_type: &str) -> i64 {
-    match building_type {
-        "barracks" | "training_center" => 150,
-        "supply_depot" | "processing_facility" => 200,
-        "tech_lab" | "research_institute" => 200,
-        "turret" | "defense_turret" => 150,
+/// Get unit supply cost (legacy hardcoded fallback).
+fn get_unit_supply(unit_type: &str) -> u32 {
+    match unit_type {
+        "harvester" | "collection_vehicle" | "ranger" | "tank" | "guardian_mech" => {
+            HEAVY_UNIT_SUPPLY
+        }
+        _ => LIGHT_UNIT_SUPPLY,
+    }
+}
+
+/// Get the supply a building provides, with optional faction data lookup.
+fn get_building_supply_with_registry(
+    building_type: &str,
+    faction: FactionId,
+    registry: Option<&FactionRegistry>,
+) -> u32 {
+    if let Some(reg) = registry {
+        if let Some(building_data) = reg.get_building(faction, building_type) {
+            return building_data.supply_provided;
+        }
+    }
+    get_building_supply(building_type)
+}
+
+/// Get building supply provided (legacy hardcoded fallback).
+fn get_building_supply(building_type: &str) -> u32 {
+    match building_type {
+        "command_center" | "depot" | "administration_center" => 100,
+        "supply_depot" | "processing_facility" => 50,
+        _ => 0,
+    }
+}
+
+/// Supply used by a player's living units and provided by their standing
+/// buildings.
+fn player_supply(
+    sim: &Simulation,
+    player: &PlayerState,
+    registry: Option<&FactionRegistry>,
+) -> PlayerEconomy {
+    let mut economy = PlayerEconomy::default();
+    for kind in player
+        .units
+        .iter()
+        .filter_map(|id| player.unit_kinds.get(id))
+    {
+        economy.supply_used += get_unit_supply_with_registry(kind, player.faction_id, registry);
+    }
+    for kind in player
+        .buildings
+        .iter()
+        .filter(|&&id| is_finished(sim, id))
+        .filter_map(|id| player.building_kinds.get(id))
+    {
+        economy.add_supply_provided(get_building_supply_with_registry(
+            kind,
+            player.faction_id,
+            registry,
+        ));
+    }
+    economy
+}
+
+/// Get building cost with optional faction data lookup.
+fn get_building_cost_with_registry(
+    building_type: &str,
+    faction: FactionId,
+    registry: Option<&FactionRegistry>,
+) -> i64 {
+    if let Some(reg) = registry {
+        if let Some(building_data) = reg.get_building(faction, building_type) {
+            return building_data.cost as i64;
+        }
+    }
+    get_building_cost(building_type)
+}
+
+/// Get the rarer resources a building costs beside feedstock, with
+/// optional faction data lookup.
+fn get_building_extra_cost_with_registry(
+    building_type: &str,
+    faction: FactionId,
+    registry: Option<&FactionRegistry>,
+) -> ResourceAmounts {
+    registry
+        .and_then(|reg| reg.get_building(faction, building_type))
+        .map(|building_data| building_data.extra_cost.clone())
+        .unwrap_or_default()
+}
+
+/// Get building construction cost (legacy hardcoded fallback).
+fn get_building_cost(building_type: &str) -> i64 {
+    match building_type {
+        "barracks" | "training_center" => 150,
+        "supply_depot" | "processing_facility" => 200,
+        "tech_lab" | "research_institute" => 200,
+        "turret" | "defense_turret" => 150,
         "vehicle_depot" => 200,
         "walker_facility" => 350,
         "air_operations" => 400,
@@ -1346,24 +3879,9 @@ fn get_building_cost(building_type: &str) -> i64 {
 // SALVAGE SYSTEM
 // =============================================================================
 
-/// Check if a unit kind is a "battleline" unit that can collect salvage.
-/// Uses the "battleline" tag from unit data if registry is available.
-fn is_battleline_unit(
-    unit_kind: &str,
-    registry: Option<&FactionRegistry>,
-    faction: FactionId,
-) -> bool {
-    // Try to get from registry first (data-driven)
-    if let Some(reg) = registry {
-        if let Some(unit_data) = reg.get_unit(faction, unit_kind) {
-            return unit_data.tags.iter().any(|t| t == "battleline");
-        }
-        if let Some(unit_data) = reg.get_unit_by_role(faction, unit_kind) {
-            return unit_data.tags.iter().any(|t| t == "battleline");
-        }
-    }
-
-    // Fallback: hardcoded check for when registry not available
+/// Check if a legacy hardcoded unit is a "battleline" unit that can
+/// collect salvage. Units from faction data use their `battleline` tag.
+fn is_battleline_unit(unit_kind: &str) -> bool {
     let kind_lower = unit_kind.to_lowercase();
 
     // Exclude obvious non-combat units
@@ -1388,20 +3906,9 @@ fn is_battleline_unit(
     false
 }
 
-/// Get the tier of a unit based on its kind (for salvage rate calculation).
+/// Get the tier of a legacy hardcoded unit (for salvage rate calculation).
 /// Tier 1: Basic infantry, Tier 2: Mid-tier vehicles/mechs, Tier 3: Heavy units
-fn get_unit_tier(unit_kind: &str, registry: Option<&FactionRegistry>, faction: FactionId) -> u32 {
-    // Try to get tier from registry
-    if let Some(reg) = registry {
-        if let Some(unit_data) = reg.get_unit(faction, unit_kind) {
-            return unit_data.tier as u32;
-        }
-        if let Some(unit_data) = reg.get_unit_by_role(faction, unit_kind) {
-            return unit_data.tier as u32;
-        }
-    }
-
-    // Fallback based on hardcoded cost tiers
+fn get_unit_tier(unit_kind: &str) -> u32 {
     let kind_lower = unit_kind.to_lowercase();
     if kind_lower.contains("sovereign")
         || kind_lower.contains("strategic")
@@ -1419,134 +3926,64 @@ fn get_unit_tier(unit_kind: &str, registry: Option<&FactionRegistry>, faction: F
     }
 }
 
-/// Process salvage collection for a player's units.
-/// Battleline units near wrecks will collect salvage (auto-behavior).
-fn process_salvage_for_player(
+/// Wreck value and salvage rate for a legacy hardcoded unit.
+fn legacy_salvage(unit_type: &str) -> (u32, Option<u32>) {
+    let cost = u32::try_from(get_unit_cost(unit_type)).unwrap_or(0);
+    let rate =
+        is_battleline_unit(unit_type).then(|| salvage_rate_for_tier(get_unit_tier(unit_type)));
+    (salvage_value(cost), rate)
+}
+
+/// Create a visual state snapshot from the current simulation, with the
+/// first player's threat map for debugging overlays.
+///
+/// Anything that moves is a unit and anything else a building, each named
+/// by the kind its owner gave it.
+fn create_visual_state(
+    game_id: &str,
+    tick: u64,
     sim: &Simulation,
-    player: &mut PlayerState,
-    wrecks: &mut [WreckState],
-    salvage_actions: &mut HashMap<EntityId, SalvageAction>,
-    registry: Option<&FactionRegistry>,
-) {
-    // Clean up salvage actions for dead units
-    salvage_actions.retain(|unit_id, _| player.units.contains(unit_id));
+    map_size: (u32, u32),
+    players: [&PlayerState; 2],
+) -> VisualState {
+    let trigger = ScreenshotTrigger::TimedSnapshot { tick };
+    let mut state = VisualState::new(game_id, tick, trigger);
+    state.map_bounds = map_size;
+    state.threat_map = Some(players[0].threat_map.clone());
 
-    for &unit_id in &player.units {
-        // Skip units that aren't battleline
-        let Some(unit_kind) = player.unit_kinds.get(&unit_id) else {
+    for id in sim.entities().sorted_ids() {
+        let Some(entity) = sim.get_entity(id) else {
             continue;
         };
-        if !is_battleline_unit(unit_kind, registry, player.faction_id) {
-            continue;
-        }
-
-        // Check if unit is in active combat (has attack target)
-        // Units in combat collect salvage at half rate
-        let in_combat = sim
-            .get_entity(unit_id)
-            .map(|e| e.attack_target.is_some())
-            .unwrap_or(false);
-
-        // Get unit position
-        let Some(unit_pos) = get_entity_position(sim, unit_id) else {
+        let Some(pos) = &entity.position else {
             continue;
         };
-        let unit_x: f32 = unit_pos.x.to_num();
-        let unit_y: f32 = unit_pos.y.to_num();
-
-        // Find closest wreck within range
-        let mut closest_wreck_idx: Option<usize> = None;
-        let mut closest_dist_sq = SALVAGE_RADIUS * SALVAGE_RADIUS;
-
-        for (idx, wreck) in wrecks.iter().enumerate() {
-            if wreck.salvage_remaining > 0 {
-                let dx = wreck.position.0 - unit_x;
-                let dy = wreck.position.1 - unit_y;
-                let dist_sq = dx * dx + dy * dy;
-                if dist_sq < closest_dist_sq {
-                    closest_dist_sq = dist_sq;
-                    closest_wreck_idx = Some(idx);
-                }
-            }
-        }
-
-        if let Some(wreck_idx) = closest_wreck_idx {
-            // Get tier-based salvage rate
-            let tier = get_unit_tier(unit_kind, registry, player.faction_id);
-            let mut rate = salvage_rate_for_tier(tier);
-
-            // Units in combat collect at half rate
-            if in_combat {
-                rate /= 2;
-                if rate == 0 {
-                    rate = 1; // Minimum 1 per tick
-                }
-            }
-
-            // Collect salvage
-            let wreck = &mut wrecks[wreck_idx];
-            let collected = rate.min(wreck.salvage_remaining);
-            wreck.salvage_remaining -= collected;
-            player.resources += collected;
-            player.resources_from_salvage += collected;
-
-            // Track salvage action (could be used for animation in future)
-            salvage_actions
-                .entry(unit_id)
-                .and_modify(|a| {
-                    a.wreck_index = wreck_idx;
-                    a.ticks_salvaging += 1;
-                })
-                .or_insert(SalvageAction {
-                    wreck_index: wreck_idx,
-                    ticks_salvaging: 1,
-                });
-
-            trace!(
-                unit = ?unit_id,
-                wreck_idx = wreck_idx,
-                wreck_type = %wreck.unit_kind,
-                collected = collected,
-                remaining = wreck.salvage_remaining,
-                "Unit collecting salvage"
-            );
-        } else {
-            // No wreck nearby, clear salvage action
-            salvage_actions.remove(&unit_id);
+        if entity.projectile.is_some() {
+            continue;
         }
-    }
-}
-
-/// Create a visual state snapshot from the current simulation.
-fn create_visual_state(game_id: &str, tick: u64, sim: &Simulation) -> VisualState {
-    let trigger = ScreenshotTrigger::TimedSnapshot { tick };
-    let mut state = VisualState::new(game_id, tick, trigger);
-
-    // Add all entities as unit visuals (simplified)
-    for (_, entity) in sim.entities().iter() {
-        if let Some(pos) = &entity.position {
-            let faction_name = entity
-                .faction
-                .as_ref()
-                .map(|f| match f.faction {
-                    FactionId::Continuity => "continuity",
-                    FactionId::Collegium => "collegium",
-                    _ => "unknown",
-                })
-                .unwrap_or("neutral")
-                .to_string();
-
-            let health_percent = entity
-                .health
-                .as_ref()
-                .map(|h| h.current as f32 / h.max as f32)
-                .unwrap_or(1.0);
+        let owner = entity
+            .faction
+            .and_then(|f| players.into_iter().find(|p| p.faction_id == f.faction));
+        let faction_name = entity
+            .faction
+            .as_ref()
+            .map_or_else(|| "neutral".to_string(), |f| faction_key(f.faction));
+        let health_percent = entity
+            .health
+            .as_ref()
+            .map(|h| h.current as f32 / h.max.max(1) as f32)
+            .unwrap_or(1.0);
+        let position = (pos.value.x.to_num(), pos.value.y.to_num());
 
+        if entity.movement.is_some() {
             state.units.push(UnitVisual {
                 entity_id: entity.id,
-                kind: "unit".to_string(),
+                kind: owner
+                    .and_then(|p| p.unit_kinds.get(&id))
+                    .cloned()
+                    .unwrap_or_else(|| "unit".to_string()),
                 faction: faction_name,
-                position: (pos.value.x.to_num(), pos.value.y.to_num()),
+                position,
                 rotation: 0.0,
                 health_percent,
                 animation_state: "idle".to_string(),
@@ -1554,14 +3991,89 @@ fn create_visual_state(game_id: &str, tick: u64, sim: &Simulation) -> VisualStat
                 is_selected: false,
                 current_action: None,
             });
+        } else {
+            let kind = owner
+                .and_then(|p| p.building_kinds.get(&id))
+                .cloned()
+                .unwrap_or_else(|| {
+                    if entity.depot.is_some() {
+                        "depot".to_string()
+                    } else {
+                        "building".to_string()
+                    }
+                });
+            state.buildings.push(BuildingVisual {
+                entity_id: entity.id,
+                kind,
+                faction: faction_name,
+                position,
+                health_percent,
+                construction_progress: None,
+                is_producing: entity
+                    .production_queue
+                    .as_ref()
+                    .is_some_and(|q| !q.is_empty()),
+                rally_point: None,
+            });
         }
     }
 
     state
 }
 
+/// Add a hit between two players' units to each side's per-kind record.
+fn record_unit_hit(attackers: &mut PlayerState, targets: &mut PlayerState, hit: &DamageEvent) {
+    let damage = i64::from(hit.damage);
+    let victim = targets.unit_kinds.get(&hit.target);
+    if let Some(victim) = victim {
+        targets
+            .unit_stats
+            .entry(victim.clone())
+            .or_default()
+            .damage_taken += damage;
+    }
+    let Some(kind) = attackers.unit_kinds.get(&hit.attacker) else {
+        // Hit by a building; it gets the kill rather than the last unit
+        targets.last_hit_by.remove(&hit.target);
+        return;
+    };
+    let stats = attackers.unit_stats.entry(kind.clone()).or_default();
+    stats.damage_dealt += damage;
+    if let Some(victim) = victim {
+        *stats.damage_to.entry(victim.clone()).or_default() += damage;
+        targets.last_hit_by.insert(hit.target, kind.clone());
+    }
+}
+
+/// Close a dead unit's record, crediting the kill to the enemy unit kind
+/// that hit it last.
+fn record_unit_death(
+    victims: &mut PlayerState,
+    killers: &mut PlayerState,
+    entity_id: EntityId,
+    tick: u64,
+) {
+    let Some(kind) = victims.unit_kinds.remove(&entity_id) else {
+        return;
+    };
+    let stats = victims.unit_stats.entry(kind.clone()).or_default();
+    stats.lost += 1;
+    if let Some(fielded) = victims.unit_fielded_at.remove(&entity_id) {
+        stats.lifetime_ticks += tick.saturating_sub(fielded);
+    }
+    if let Some(killer) = victims.last_hit_by.remove(&entity_id) {
+        *killers
+            .unit_stats
+            .entry(killer)
+            .or_default()
+            .kills
+            .entry(kind)
+            .or_default() += 1;
+    }
+}
+
 /// Build faction metrics from player state.
-fn build_faction_metrics(player: &PlayerState, _duration: u64) -> FactionMetrics {
+fn build_faction_metrics(player: &PlayerState, duration: u64) -> FactionMetrics {
     // Calculate K/D ratio
     let total_killed: u32 = player.units_killed.values().sum();
     let total_lost: u32 = player.units_lost.values().sum();
@@ -1573,12 +4085,20 @@ fn build_faction_metrics(player: &PlayerState, _duration: u64) -> FactionMetrics
         1.0
     };
 
+    // Units still standing were alive until the end
+    let mut unit_stats = player.unit_stats.clone();
+    for (id, fielded) in &player.unit_fielded_at {
+        if let Some(stats) = player
+            .unit_kinds
+            .get(id)
+            .and_then(|kind| unit_stats.get_mut(kind))
+        {
+            stats.lifetime_ticks += duration.saturating_sub(*fielded);
+        }
+    }
+
     FactionMetrics {
-        faction_id: match player.faction_id {
-            FactionId::Continuity => "continuity".to_string(),
-            FactionId::Collegium => "collegium".to_string(),
-            _ => "unknown".to_string(),
-        },
+        faction_id: faction_key(player.faction_id),
         final_score: (player.total_damage_dealt - player.total_damage_taken + player.resources),
         total_resources_gathered: player.resources_from_harvest + player.resources_from_salvage,
         total_resources_spent: player
@@ -1600,20 +4120,60 @@ fn build_faction_metrics(player: &PlayerState, _duration: u64) -> FactionMetrics
         buildings_lost: player.buildings_lost.clone(),
         total_damage_dealt: player.total_damage_dealt,
         total_damage_taken: player.total_damage_taken,
+        total_health_regenerated: player.total_health_regenerated,
         battles_won: player.units_killed.values().sum::<u32>(),
         battles_lost: player.units_lost.values().sum::<u32>(),
         kd_ratio,
         first_attack_tick: player.first_attack_tick,
-        first_expansion_tick: None,
-        tech_unlock_times: HashMap::new(),
+        first_expansion_tick: player.first_expansion_tick,
+        tech_unlock_times: player.tech_unlock_times.clone(),
         first_combat_unit_tick: None, // Would need tracking when first military unit is produced
-        map_control_over_time: Vec::new(),
-        average_army_position: Vec::new(),
+        map_control_over_time: player.timeline.map_control.clone(),
+        average_army_position: player.timeline.army_positions.clone(),
         peak_army_size: player.peak_army_size,
+        time_series: player.timeline.samples.clone(),
+        enemies_sighted: u32::try_from(player.intel.sighted()).unwrap_or(u32::MAX),
+        scout_efficiency: 0.0, // Needs the enemy's side; see scout_efficiency
+        resupply_trips: player.resupply_trips,
+        ticks_resupplying: player.ticks_resupplying,
+        upkeep_income_lost: player.upkeep.income_lost,
+        rare_resources_gathered: player
+            .reserves_gathered
+            .iter()
+            .map(|(kind, amount)| (kind.to_string(), i64::from(amount)))
+            .collect(),
+        resource_shortfalls: player
+            .resource_shortfalls
+            .iter()
+            .map(|(kind, count)| (kind.to_string(), *count))
+            .collect(),
+        trades: player.trades,
+        trade_value_sold: player.trade_value_sold,
+        trade_value_bought: player.trade_value_bought,
+        upkeep_ticks_taxed: player.upkeep.ticks_taxed,
+        upkeep_ticks_exhausted: player.upkeep.ticks_exhausted,
+        upkeep_reserve: player
+            .upkeep_model
+            .drain
+            .as_ref()
+            .map(|_| player.upkeep.reserve),
+        unit_stats,
     }
 }
 
+/// Share of the units and buildings `enemy` fielded that `player` saw at
+/// least once, capped at 1.0.
+fn scout_efficiency(player: &PlayerState, enemy: &PlayerState) -> f64 {
+    let fielded: u32 = enemy.units_produced.values().sum::<u32>()
+        + enemy.buildings_constructed.values().sum::<u32>();
+    if fielded == 0 {
+        return 0.0;
+    }
+    (player.intel.sighted() as f64 / f64::from(fielded)).min(1.0)
+}
+
 /// Simple deterministic RNG for reproducibility.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct SimpleRng {
     state: u64,
 }
@@ -1637,6 +4197,7 @@ impl SimpleRng {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scenario::UnitPlacement;
 
     #[test]
     fn test_simulation_combat_works() {
@@ -1698,8 +4259,22 @@ mod tests {
         let mut sim = Simulation::new();
 
         // Spawn close together on same Y
-        let unit_a = spawn_unit(&mut sim, "infantry", 50, 100, FactionId::Continuity);
-        let unit_b = spawn_unit(&mut sim, "infantry", 150, 100, FactionId::Collegium);
+        let unit_a = spawn_unit(
+            &mut sim,
+            "infantry",
+            50,
+            100,
+            FactionId::Continuity,
+            &Scenario::default(),
+        );
+        let unit_b = spawn_unit(
+            &mut sim,
+            "infantry",
+            150,
+            100,
+            FactionId::Collegium,
+            &Scenario::default(),
+        );
 
         println!(
             "Initial: Unit A at {:?}, Unit B at {:?}",
@@ -1739,6 +4314,87 @@ mod tests {
         assert!(damage_total > 0, "Combat should have dealt damage");
     }
 
+    #[test]
+    fn test_sampling_records_time_series_for_both_factions() {
+        let config = GameConfig {
+            seed: 7,
+            max_ticks: 400,
+            scenario: Scenario::default(),
+            strategy_a: Strategy::default(),
+            strategy_b: Strategy::default(),
+            handicap_a: Handicap::NONE,
+            handicap_b: Handicap::NONE,
+            screenshot_config: None,
+            game_id: "sampled".to_string(),
+            faction_registry: None,
+            autosave: None,
+            chat: false,
+            observers: Vec::new(),
+            stop: StopSignal::default(),
+            projection: None,
+            sample_interval: Some(100),
+        };
+
+        let metrics = run_game(config).metrics;
+        assert_eq!(metrics.factions.len(), 2);
+        for faction in metrics.factions.values() {
+            let ticks: Vec<u64> = faction.time_series.iter().map(|s| s.tick).collect();
+            assert_eq!(ticks, vec![100, 200, 300, 400]);
+            assert_eq!(faction.map_control_over_time.len(), 4);
+            assert!(faction
+                .map_control_over_time
+                .iter()
+                .all(|&(_, control)| (0.0..=100.0).contains(&control)));
+            let last = faction.time_series.last().unwrap();
+            assert!(last.army_value > 0);
+            assert!(last.units_by_tier.iter().sum::<u32>() > 0);
+        }
+    }
+
+    #[test]
+    fn test_projection_ends_decided_games_early() {
+        let projection = ProjectionConfig {
+            interval_ticks: 50,
+            certainty: 0.5,
+            consecutive_checks: 2,
+            min_tick: 100,
+        };
+        let config = GameConfig {
+            seed: 7,
+            max_ticks: 10000,
+            scenario: Scenario::default(),
+            strategy_a: Strategy::rush(),
+            strategy_b: Strategy::turtle(),
+            handicap_a: Handicap::NONE,
+            handicap_b: Handicap::NONE,
+            screenshot_config: None,
+            game_id: "projected".to_string(),
+            faction_registry: None,
+            autosave: None,
+            chat: false,
+            observers: Vec::new(),
+            stop: StopSignal::default(),
+            projection: Some(projection),
+            sample_interval: None,
+        };
+
+        // At even odds someone always leads, so the game ends on the first
+        // check the same side leads twice running
+        let metrics = run_game(config).metrics;
+        assert_eq!(metrics.win_condition.kind(), "projected");
+        assert!(metrics.duration_ticks >= 150);
+        assert_eq!(metrics.duration_ticks % 50, 0);
+        let WinCondition::Projected {
+            projected_winner,
+            certainty_percent,
+        } = metrics.win_condition
+        else {
+            unreachable!();
+        };
+        assert_eq!(metrics.winner, Some(faction_key(projected_winner)));
+        assert!(certainty_percent >= 50);
+    }
+
     #[test]
     fn test_debug_full_game_combat() {
         // Run a game and verify we get a winner
@@ -1748,9 +4404,17 @@ mod tests {
             scenario: Scenario::default(),
             strategy_a: Strategy::rush(),
             strategy_b: Strategy::rush(),
+            handicap_a: Handicap::NONE,
+            handicap_b: Handicap::NONE,
             screenshot_config: None,
             game_id: "debug_game".to_string(),
             faction_registry: None,
+            autosave: None,
+            chat: false,
+            observers: Vec::new(),
+            stop: StopSignal::default(),
+            projection: None,
+            sample_interval: None,
         };
 
         let result = run_game(config);
@@ -1767,7 +4431,28 @@ mod tests {
                 "  {}: {} damage dealt, {} units lost",
                 faction, metrics.total_damage_dealt, units_lost_total
             );
+
+            // The per-kind record adds up to the faction totals
+            for (kind, stats) in &metrics.unit_stats {
+                assert_eq!(Some(&stats.fielded), metrics.units_produced.get(kind));
+            }
+            let lost: u32 = metrics.unit_stats.values().map(|s| s.lost).sum();
+            assert_eq!(lost, units_lost_total);
+            let unit_damage: i64 = metrics.unit_stats.values().map(|s| s.damage_dealt).sum();
+            assert!(unit_damage <= metrics.total_damage_dealt);
+
+            // Fighting means seeing the enemy
+            assert!(metrics.enemies_sighted > 0);
+            assert!(metrics.scout_efficiency > 0.0 && metrics.scout_efficiency <= 1.0);
         }
+        let kills: u32 = result
+            .metrics
+            .factions
+            .values()
+            .flat_map(|m| m.unit_stats.values())
+            .flat_map(|s| s.kills.values())
+            .sum();
+        assert!(kills > 0, "Kills should be credited to unit kinds");
 
         // We expect a winner now that buildings can be damaged
         assert!(
@@ -1776,6 +4461,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_stop_signal_ends_game_early() {
+        let play = |stop: StopSignal| {
+            run_game(GameConfig {
+                seed: 42,
+                max_ticks: 600,
+                scenario: Scenario::default(),
+                strategy_a: Strategy::rush(),
+                strategy_b: Strategy::rush(),
+                handicap_a: Handicap::NONE,
+                handicap_b: Handicap::NONE,
+                screenshot_config: None,
+                game_id: "stopped".to_string(),
+                faction_registry: None,
+                autosave: None,
+                chat: false,
+                observers: Vec::new(),
+                stop,
+                projection: None,
+                sample_interval: None,
+            })
+        };
+
+        let timed_out = play(StopSignal::default().with_timeout(Duration::ZERO));
+        assert_eq!(timed_out.stopped, Some(StopReason::TimedOut));
+        assert_eq!(timed_out.metrics.duration_ticks, 0);
+
+        let cancel = Arc::new(AtomicBool::new(true));
+        let cancelled = play(
+            StopSignal::default()
+                .with_timeout(Duration::ZERO)
+                .with_cancel(cancel),
+        );
+        assert_eq!(cancelled.stopped, Some(StopReason::Cancelled));
+
+        let finished = play(StopSignal::default());
+        assert_eq!(finished.stopped, None);
+        assert!(finished.metrics.duration_ticks > 0);
+    }
+
     #[test]
     fn test_run_game_deterministic() {
         let config1 = GameConfig {
@@ -1784,9 +4509,17 @@ mod tests {
             scenario: Scenario::default(),
             strategy_a: Strategy::default(),
             strategy_b: Strategy::default(),
+            handicap_a: Handicap::NONE,
+            handicap_b: Handicap::NONE,
             screenshot_config: None,
             game_id: "game_1".to_string(),
             faction_registry: None,
+            autosave: None,
+            chat: false,
+            observers: Vec::new(),
+            stop: StopSignal::default(),
+            projection: None,
+            sample_interval: None,
         };
 
         let config2 = GameConfig {
@@ -1795,9 +4528,17 @@ mod tests {
             scenario: Scenario::default(),
             strategy_a: Strategy::default(),
             strategy_b: Strategy::default(),
+            handicap_a: Handicap::NONE,
+            handicap_b: Handicap::NONE,
             screenshot_config: None,
             game_id: "game_2".to_string(),
             faction_registry: None,
+            autosave: None,
+            chat: false,
+            observers: Vec::new(),
+            stop: StopSignal::default(),
+            projection: None,
+            sample_interval: None,
         };
 
         let result1 = run_game(config1);
@@ -1812,6 +4553,969 @@ mod tests {
         assert_eq!(result1.final_state_hash, result2.final_state_hash);
     }
 
+    #[test]
+    fn test_resume_from_autosave_matches_uninterrupted() {
+        let dir = std::env::temp_dir().join(format!("rts_resume_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let config = GameConfig {
+            seed: 7,
+            max_ticks: 3000,
+            scenario: Scenario::default(),
+            strategy_a: Strategy::rush(),
+            strategy_b: Strategy::economic(),
+            handicap_a: Handicap::NONE,
+            handicap_b: Handicap::NONE,
+            screenshot_config: None,
+            game_id: "resume".to_string(),
+            faction_registry: None,
+            autosave: Some(
+                AutosaveConfig::new(&dir, 100)
+                    .with_prefix("resume")
+                    .with_slots(100),
+            ),
+            observers: Vec::new(),
+            stop: StopSignal::default(),
+            chat: false,
+            projection: None,
+            sample_interval: None,
+        };
+        let uninterrupted = run_game(config.clone());
+        assert!(uninterrupted.metrics.duration_ticks > 200);
+
+        // Slot 1 holds the tick-200 save; every later tick is replayed.
+        let save = Autosave::load(config.autosave.as_ref().unwrap().slot_path(1)).unwrap();
+        assert_eq!(save.tick, 200);
+        let resumed = resume_game(&save, None, None).unwrap();
+
+        assert_eq!(resumed.final_state_hash, uninterrupted.final_state_hash);
+        assert_eq!(resumed.metrics.winner, uninterrupted.metrics.winner);
+        assert_eq!(
+            resumed.metrics.duration_ticks,
+            uninterrupted.metrics.duration_ticks
+        );
+        assert_eq!(
+            resumed.metrics.events.len(),
+            uninterrupted.metrics.events.len()
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_logistics_rotates_units_out_to_resupply() {
+        let mut scenario = Scenario::default();
+        scenario.factions[0]
+            .starting_units
+            .push(UnitPlacement::new("supply_truck", 64, 240, 1));
+        scenario.logistics = Some(LogisticsConfig {
+            ammo_capacity: 5,
+            ranged_min_range: 0,
+            ..LogisticsConfig::default()
+        });
+
+        let config = GameConfig {
+            seed: 3,
+            max_ticks: 3000,
+            scenario,
+            strategy_a: Strategy::rush(),
+            strategy_b: Strategy::rush(),
+            handicap_a: Handicap::NONE,
+            handicap_b: Handicap::NONE,
+            screenshot_config: None,
+            game_id: "logistics".to_string(),
+            faction_registry: None,
+            autosave: None,
+            chat: false,
+            observers: Vec::new(),
+            stop: StopSignal::default(),
+            projection: None,
+            sample_interval: None,
+        };
+        let result = run_game(config);
+
+        let trips: u32 = result
+            .metrics
+            .factions
+            .values()
+            .map(|f| f.resupply_trips)
+            .sum();
+        let ticks: u64 = result
+            .metrics
+            .factions
+            .values()
+            .map(|f| f.ticks_resupplying)
+            .sum();
+        assert!(trips > 0, "units should rotate out when low on ammunition");
+        assert!(ticks >= u64::from(trips));
+    }
+
+    #[test]
+    fn test_scenario_triggers_send_waves_and_grant_resources() {
+        use rts_core::triggers::{Trigger, TriggerCondition};
+
+        let scenario = Scenario::default();
+        let mut sim = Simulation::new();
+        sim.set_script(ScenarioScript::new(vec![Trigger {
+            name: "first_wave".to_string(),
+            condition: TriggerCondition::AtTick(2),
+            actions: vec![
+                TriggerAction::SpawnUnits {
+                    faction: FactionId::Collegium,
+                    kind: "infantry".to_string(),
+                    position: (400, 256),
+                    count: 3,
+                },
+                TriggerAction::GrantResources {
+                    faction: FactionId::Continuity,
+                    amount: 500,
+                },
+            ],
+        }]));
+        let mut player_a = PlayerState::new(FactionId::Continuity, Strategy::default());
+        let mut player_b = PlayerState::new(FactionId::Collegium, Strategy::default());
+
+        for _ in 0..5 {
+            let events = sim.tick();
+            for fired in &events.triggers {
+                apply_trigger(
+                    &mut sim,
+                    [&mut player_a, &mut player_b],
+                    fired,
+                    None,
+                    &scenario,
+                );
+            }
+        }
+
+        assert_eq!(player_b.units.len(), 3);
+        assert_eq!(player_b.units_produced.get("infantry"), Some(&3));
+        assert!(player_b.units.iter().all(|&id| sim
+            .get_entity(id)
+            .and_then(|e| e.faction)
+            .map(|f| f.faction)
+            == Some(FactionId::Collegium)));
+        assert_eq!(player_a.resources, 1500);
+        assert!(player_a.units.is_empty());
+    }
+
+    #[test]
+    fn test_legacy_units_carry_salvage() {
+        assert_eq!(legacy_salvage("infantry"), (12, Some(1)));
+        assert_eq!(legacy_salvage("tank"), (75, Some(2)));
+        assert_eq!(legacy_salvage("harvester"), (25, None));
+
+        let mut sim = Simulation::new();
+        let scenario = Scenario::default();
+        let unit = spawn_unit(&mut sim, "infantry", 0, 0, FactionId::Continuity, &scenario);
+        let entity = sim.get_entity(unit).unwrap();
+        assert_eq!(entity.salvageable.map(|s| s.value), Some(12));
+        assert_eq!(entity.salvager.map(|s| s.rate), Some(1));
+    }
+
+    #[test]
+    fn test_handicap_scales_harvest_income() {
+        use rts_core::difficulty::Difficulty;
+
+        let scenario = Scenario::default();
+        let gather = |difficulty: Difficulty| {
+            let mut sim = Simulation::new();
+            let mut player = PlayerState::new(FactionId::Continuity, Strategy::default())
+                .with_handicap(difficulty.handicap());
+            let depot = spawn_building_with_registry(
+                &mut sim,
+                "command_center",
+                100,
+                100,
+                FactionId::Continuity,
+                None,
+                &scenario,
+            );
+            player.buildings.push(depot);
+            let harvester = spawn_unit(
+                &mut sim,
+                "harvester",
+                110,
+                100,
+                FactionId::Continuity,
+                &scenario,
+            );
+            player.units.push(harvester);
+            track_harvester(&mut player, harvester, "harvester", None);
+            let mut nodes = vec![ResourceNode::new(
+                Vec2Fixed::new(Fixed::from_num(200), Fixed::from_num(100)),
+                5000,
+                HARVESTER_GATHER_RATE,
+            )];
+            for tick in 1..=3000 {
+                sim.tick();
+                if tick % HARVEST_INTERVAL == 0 {
+                    run_harvesters(&mut sim, &mut player, &mut nodes, &scenario, tick);
+                }
+            }
+            player.resources_from_harvest
+        };
+
+        let normal = gather(Difficulty::Normal);
+        assert!(normal > 0);
+        // Rounded down per load
+        let easy = gather(Difficulty::Easy);
+        assert!(
+            easy < normal && easy * 100 >= normal * 65,
+            "{easy} of {normal}"
+        );
+        assert!(gather(Difficulty::Brutal) > normal);
+    }
+
+    #[test]
+    fn test_harvesters_gather_and_unload_at_depot() {
+        let scenario = Scenario::default();
+        let mut sim = Simulation::new();
+        let mut player = PlayerState::new(FactionId::Continuity, Strategy::default());
+        let depot = spawn_building_with_registry(
+            &mut sim,
+            "command_center",
+            100,
+            100,
+            FactionId::Continuity,
+            None,
+            &scenario,
+        );
+        player.buildings.push(depot);
+        let harvester = spawn_unit(
+            &mut sim,
+            "harvester",
+            110,
+            100,
+            FactionId::Continuity,
+            &scenario,
+        );
+        player.units.push(harvester);
+        track_harvester(&mut player, harvester, "harvester", None);
+        let mut nodes = vec![ResourceNode::new(
+            Vec2Fixed::new(Fixed::from_num(200), Fixed::from_num(100)),
+            500,
+            HARVESTER_GATHER_RATE,
+        )];
+        let resources = player.resources;
+
+        for tick in 1..=3000 {
+            sim.tick();
+            if tick % HARVEST_INTERVAL == 0 {
+                run_harvesters(&mut sim, &mut player, &mut nodes, &scenario, tick);
+            }
+        }
+
+        let gathered = player.resources - resources;
+        assert!(
+            gathered >= i64::from(HARVESTER_CAPACITY),
+            "gathered {gathered}"
+        );
+        assert_eq!(player.resources_from_harvest, gathered);
+        assert_eq!(
+            i64::from(500 - nodes[0].remaining) - gathered,
+            i64::from(player.harvesters[&harvester].current_load)
+        );
+        // Harvesters stay out of the army
+        assert!(!is_tactically_available(&sim, &player, harvester));
+    }
+
+    #[test]
+    fn test_harvesters_fetch_the_rare_resource_a_purchase_waits_on() {
+        let scenario = Scenario::default();
+        let mut sim = Simulation::new();
+        let mut player = PlayerState::new(FactionId::Continuity, Strategy::default());
+        let depot = spawn_building_with_registry(
+            &mut sim,
+            "command_center",
+            100,
+            100,
+            FactionId::Continuity,
+            None,
+            &scenario,
+        );
+        player.buildings.push(depot);
+        for y in [100, 110] {
+            let harvester = spawn_unit(
+                &mut sim,
+                "harvester",
+                110,
+                y,
+                FactionId::Continuity,
+                &scenario,
+            );
+            player.units.push(harvester);
+            player.unit_kinds.insert(harvester, "harvester".to_string());
+            track_harvester(&mut player, harvester, "harvester", None);
+        }
+        let node = |x: i32| {
+            ResourceNode::new(
+                Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(100)),
+                5000,
+                HARVESTER_GATHER_RATE,
+            )
+        };
+        let mut nodes = vec![node(150), node(250).with_kind(ResourceKind::Exotics)];
+
+        // A purchase needing exotics is held back and counted
+        let lancer = ResourceAmounts::new().with(ResourceKind::Exotics, 20);
+        assert!(!player.afford_extra(&lancer));
+        assert_eq!(player.resource_shortfalls[&ResourceKind::Exotics], 1);
+
+        let resources = player.resources;
+        for tick in 1..=3000 {
+            sim.tick();
+            if tick % HARVEST_INTERVAL == 0 {
+                run_harvesters(&mut sim, &mut player, &mut nodes, &scenario, tick);
+            }
+        }
+
+        // One harvester went for exotics while the other kept on feedstock
+        assert!(player.reserves.get(ResourceKind::Exotics) >= 20);
+        assert!(player.resources > resources);
+        assert!(player.afford_extra(&lancer));
+        player.pay(0, &lancer);
+
+        let metrics = build_faction_metrics(&player, 3000);
+        assert!(metrics.rare_resources_gathered["exotics"] >= 20);
+        assert_eq!(metrics.resource_shortfalls["exotics"], 1);
+    }
+
+    #[test]
+    fn test_economic_ai_buys_what_a_purchase_waits_on_at_its_market() {
+        let mut sim = Simulation::new();
+        let mut player = PlayerState::new(FactionId::Continuity, Strategy::economic());
+        player.resources = 1000;
+        let lancer = ResourceAmounts::new().with(ResourceKind::Exotics, 60);
+        assert!(!player.afford_extra(&lancer));
+
+        // Nothing to trade at yet
+        manage_trades(&sim, &mut player, 0);
+        assert_eq!(player.trades, 0);
+
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            health: Some(300),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            is_market: true,
+            ..Default::default()
+        });
+        manage_trades(&sim, &mut player, 0);
+        assert_eq!(player.trades, 1);
+        assert_eq!(player.resources, 800);
+        let first = player.reserves.get(ResourceKind::Exotics);
+        assert!(first > 0);
+
+        // One trade per interval, until the purchase is covered
+        manage_trades(&sim, &mut player, 10);
+        assert_eq!(player.trades, 1);
+        let mut tick = 0;
+        while !player.afford_extra(&lancer) {
+            assert!(tick < 1200, "should cover 60 exotics within a window");
+            tick += 120;
+            manage_trades(&sim, &mut player, tick);
+            assert!(player.resources >= 300, "keeps its feedstock reserve");
+        }
+        let trades = player.trades;
+        manage_trades(&sim, &mut player, tick + 120);
+        assert_eq!(player.trades, trades);
+
+        let metrics = build_faction_metrics(&player, tick);
+        assert_eq!(metrics.trades, trades);
+        assert_eq!(metrics.trade_value_sold, 1000 - player.resources);
+        assert!(metrics.trade_value_bought < metrics.trade_value_sold);
+    }
+
+    #[test]
+    fn test_upkeep_withholds_harvest_income_and_reaches_metrics() {
+        use rts_core::economy::{UpkeepDrain, UpkeepTier};
+
+        let scenario = Scenario::default();
+        let mut sim = Simulation::new();
+        let mut player = PlayerState::new(FactionId::Continuity, Strategy::default());
+        player.upkeep_model = UpkeepModel {
+            tiers: vec![UpkeepTier {
+                above_supply: 0,
+                income_cut: 50,
+            }],
+            drain: Some(UpkeepDrain {
+                resource: "legitimacy".to_string(),
+                capacity: 10,
+                above_supply: 0,
+                per_supply: 1,
+                recovery: 0,
+                interval_ticks: 60,
+                exhausted_income_cut: 0,
+            }),
+        };
+        player.upkeep = Upkeep::new(&player.upkeep_model);
+        let depot = spawn_building_with_registry(
+            &mut sim,
+            "command_center",
+            100,
+            100,
+            FactionId::Continuity,
+            None,
+            &scenario,
+        );
+        player.buildings.push(depot);
+        let harvester = spawn_unit(
+            &mut sim,
+            "harvester",
+            110,
+            100,
+            FactionId::Continuity,
+            &scenario,
+        );
+        player.units.push(harvester);
+        player.unit_kinds.insert(harvester, "harvester".to_string());
+        track_harvester(&mut player, harvester, "harvester", None);
+        let mut nodes = vec![ResourceNode::new(
+            Vec2Fixed::new(Fixed::from_num(200), Fixed::from_num(100)),
+            500,
+            HARVESTER_GATHER_RATE,
+        )];
+
+        for tick in 1..=3000 {
+            sim.tick();
+            let supply = player_supply(&sim, &player, None).supply_used;
+            player.upkeep.tick(&player.upkeep_model, tick, supply);
+            if tick % HARVEST_INTERVAL == 0 {
+                run_harvesters(&mut sim, &mut player, &mut nodes, &scenario, tick);
+            }
+        }
+
+        // Half of every unload is withheld
+        let lost = player.upkeep.income_lost;
+        assert!(lost > 0);
+        assert!(lost >= player.resources_from_harvest);
+        assert!(lost - player.resources_from_harvest <= 1);
+
+        let metrics = build_faction_metrics(&player, 3000);
+        assert_eq!(metrics.upkeep_income_lost, lost);
+        assert_eq!(metrics.upkeep_ticks_taxed, 3000);
+        assert_eq!(metrics.upkeep_reserve, Some(0));
+        assert!(metrics.upkeep_ticks_exhausted > 0);
+        assert!((metrics.upkeep_income_share() - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_ai_orders_army_as_one_squad() {
+        let mut sim = Simulation::new();
+        let mut player = PlayerState::new(FactionId::Continuity, Strategy::default());
+        let units: Vec<EntityId> = (0..3)
+            .map(|i| {
+                spawn_unit(
+                    &mut sim,
+                    "infantry",
+                    i * 10,
+                    0,
+                    FactionId::Continuity,
+                    &Scenario::default(),
+                )
+            })
+            .collect();
+        player.units = units.clone();
+
+        let army = sync_squad(&mut sim, &mut player.army_squad, "army", &units).unwrap();
+        let target = Vec2Fixed::new(Fixed::from_num(400), Fixed::from_num(256));
+        order_squad(&mut sim, Some(army), SquadCommand::AttackMove(target));
+        order_squad(&mut sim, Some(army), SquadCommand::AttackMove(target));
+        assert_eq!(sim.squad(army).unwrap().members(), units.as_slice());
+        assert!(units.iter().all(|&id| matches!(
+            sim.get_entity(id)
+                .and_then(|e| e.row.command_queue.as_ref())
+                .map(|q| q.len()),
+            Some(1)
+        )));
+
+        let reinforcement = spawn_unit(
+            &mut sim,
+            "infantry",
+            0,
+            0,
+            FactionId::Continuity,
+            &Scenario::default(),
+        );
+        let roster = [units[1], units[2], reinforcement];
+        assert_eq!(
+            sync_squad(&mut sim, &mut player.army_squad, "army", &roster),
+            Some(army)
+        );
+        assert_eq!(sim.squad(army).unwrap().members(), &roster);
+        assert!(matches!(
+            sim.get_entity(reinforcement)
+                .and_then(|e| e.row.command_queue.as_ref())
+                .and_then(|q| q.current()),
+            Some(Command::AttackMove(_))
+        ));
+        assert_eq!(sim.squad_of(units[0]), None);
+    }
+
+    #[test]
+    fn test_siege_units_shell_remembered_structures() {
+        let mut sim = Simulation::new();
+        let mut player = PlayerState::new(FactionId::Continuity, Strategy::default());
+        let artillery = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            health: Some(100),
+            movement: Some(Fixed::from_num(10)),
+            combat_stats: Some(
+                CombatStats::new(20, Fixed::from_num(100), 10)
+                    .with_splash_radius(Fixed::from_num(30)),
+            ),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            vision_range: Some(Fixed::from_num(50)),
+            ..Default::default()
+        });
+        player.units.push(artillery);
+        let spotter = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(170), Fixed::ZERO)),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            ..Default::default()
+        });
+        let structure_pos = Vec2Fixed::new(Fixed::from_num(180), Fixed::ZERO);
+        let structure = sim.spawn_entity(EntitySpawnParams {
+            position: Some(structure_pos),
+            health: Some(1000),
+            faction: Some(FactionMember::new(FactionId::Collegium, 0)),
+            ..Default::default()
+        });
+
+        let visible = sim.get_visible_enemies_for(FactionId::Continuity);
+        update_intel(&sim, &mut player, &visible, 0);
+        assert!(shell_remembered_structures(&mut sim, &player).is_empty());
+
+        sim.despawn_entity(spotter).unwrap();
+        let shelling = shell_remembered_structures(&mut sim, &player);
+        assert!(shelling.contains(&artillery));
+        for _ in 0..20 {
+            sim.tick();
+        }
+        let health = sim.get_entity(structure).unwrap().health.unwrap();
+        assert!(health.current < health.max, "ghost should be shelled");
+
+        sim.despawn_entity(structure).unwrap();
+        update_intel(&sim, &mut player, &[], 0);
+        assert!(shell_remembered_structures(&mut sim, &player).is_empty());
+        let command = sim
+            .get_entity(artillery)
+            .and_then(|e| e.row.command_queue.as_ref())
+            .and_then(|q| q.current().cloned());
+        assert!(!matches!(command, Some(Command::AttackGround(_))));
+    }
+
+    #[test]
+    fn test_search_sweep_covers_non_default_maps() {
+        let map_size = (900, 300);
+        let waypoints: BTreeSet<(i32, i32)> = (0..SEARCH_PATTERN.len() as u32)
+            .map(|step| {
+                let waypoint = search_waypoint(map_size, step);
+                (waypoint.x.to_num(), waypoint.y.to_num())
+            })
+            .collect();
+        assert_eq!(waypoints.len(), SEARCH_PATTERN.len());
+        assert!(
+            waypoints.contains(&(450, 150)),
+            "sweep starts at the center"
+        );
+        assert!(waypoints
+            .iter()
+            .all(|&(x, y)| (0..900).contains(&x) && (0..300).contains(&y)));
+        assert_eq!(
+            search_waypoint(map_size, SEARCH_PATTERN.len() as u32),
+            search_waypoint(map_size, 0)
+        );
+
+        // The sweep advances once a squad member reaches the waypoint
+        let mut sim = Simulation::new();
+        let mut player = PlayerState::new(FactionId::Continuity, Strategy::default());
+        let mut squad_at = |name: &str, position: Vec2Fixed| {
+            let unit = sim.spawn_entity(EntitySpawnParams {
+                position: Some(position),
+                movement: Some(Fixed::from_num(10)),
+                faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+                ..Default::default()
+            });
+            sim.create_squad(name, &[unit]).unwrap()
+        };
+        let home = squad_at("home", Vec2Fixed::ZERO);
+        let sweep = squad_at("sweep", search_waypoint(map_size, 0));
+        advance_search(&sim, &mut player, home, map_size);
+        assert_eq!(player.search_step, 0);
+        advance_search(&sim, &mut player, sweep, map_size);
+        assert_eq!(player.search_step, 1);
+    }
+
+    #[test]
+    fn test_turtle_engineers_repair_damaged_turrets() {
+        let mut sim = Simulation::new();
+        let scenario = Scenario::default();
+        let faction = FactionId::Continuity;
+        let mut player = PlayerState::new(faction, Strategy::turtle());
+        let turret =
+            spawn_building_with_registry(&mut sim, "turret", 200, 200, faction, None, &scenario);
+        let engineer = spawn_unit(&mut sim, "engineer", 150, 200, faction, &scenario);
+        player.buildings.push(turret);
+        player.units.push(engineer);
+
+        // Knock the turret down to half health
+        let raider = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(230), Fixed::from_num(200))),
+            health: Some(10_000),
+            combat_stats: Some(CombatStats::new(20, Fixed::from_num(100), 1)),
+            faction: Some(FactionMember::new(FactionId::Collegium, 0)),
+            ..Default::default()
+        });
+        sim.set_attack_target(raider, turret).unwrap();
+        let health = |sim: &Simulation| sim.get_entity(turret).unwrap().health.unwrap();
+        while health(&sim).percentage() > 50 {
+            sim.tick();
+        }
+        sim.despawn_entity(raider).unwrap();
+
+        manage_repairs(&mut sim, &player);
+        assert!(!is_tactically_available(&sim, &player, engineer));
+        for _ in 0..3000 {
+            sim.tick();
+        }
+        assert_eq!(health(&sim).current, health(&sim).max);
+        assert!(is_tactically_available(&sim, &player, engineer));
+    }
+
+    #[test]
+    fn test_intel_remembers_enemies_out_of_sight() {
+        let mut sim = Simulation::new();
+        let mut player = PlayerState::new(FactionId::Continuity, Strategy::default());
+        let at = |x: i32| Vec2Fixed::new(Fixed::from_num(x), Fixed::ZERO);
+        let spotter = sim.spawn_entity(EntitySpawnParams {
+            position: Some(at(0)),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            vision_range: Some(Fixed::from_num(200)),
+            ..Default::default()
+        });
+        let scenario = Scenario::default();
+        let raider = spawn_unit(
+            &mut sim,
+            "infantry",
+            100,
+            0,
+            FactionId::Collegium,
+            &scenario,
+        );
+        let base = spawn_building_with_registry(
+            &mut sim,
+            "depot",
+            150,
+            0,
+            FactionId::Collegium,
+            None,
+            &scenario,
+        );
+
+        let visible = sim.get_visible_enemies_for(FactionId::Continuity);
+        update_intel(&sim, &mut player, &visible, 10);
+        assert_eq!(player.intel.units(), 1);
+        assert_eq!(player.intel.composition()["infantry"], 1);
+        assert_eq!(player.intel.bases().len(), 1);
+
+        // Out of sight, both are still remembered where they were seen
+        sim.despawn_entity(spotter).unwrap();
+        update_intel(&sim, &mut player, &[], 20);
+        assert_eq!(player.intel.last_seen(raider).unwrap().tick, 10);
+        assert!(player.intel.last_seen(base).is_some());
+
+        // Until the unit goes stale, or is known destroyed
+        update_intel(
+            &sim,
+            &mut player,
+            &[],
+            10 + crate::intel::FORGET_UNITS_AFTER,
+        );
+        assert!(player.intel.last_seen(raider).is_none());
+        sim.despawn_entity(base).unwrap();
+        update_intel(
+            &sim,
+            &mut player,
+            &[],
+            10 + crate::intel::FORGET_UNITS_AFTER,
+        );
+        assert_eq!(player.intel.structures().count(), 0);
+        assert_eq!(player.intel.sighted(), 2);
+    }
+
+    #[test]
+    fn test_attack_targets_nearest_undefended_known_structure() {
+        let mut player = PlayerState::new(FactionId::Continuity, Strategy::default());
+        let home = Vec2Fixed::ZERO;
+        assert_eq!(known_structure_to_attack(&player, home), None);
+
+        let far = Vec2Fixed::new(Fixed::from_num(600), Fixed::from_num(40));
+        let near = Vec2Fixed::new(Fixed::from_num(200), Fixed::from_num(90));
+        let structure = |position| Sighting {
+            kind: "barracks".to_string(),
+            tags: Vec::new(),
+            position,
+            tick: 0,
+            structure: true,
+            depot: false,
+        };
+        player.intel.observe(7, structure(far));
+        player.intel.observe(9, structure(near));
+        assert_eq!(known_structure_to_attack(&player, home), Some(near));
+
+        // Unless the threat map shows it defended
+        player.threat_map = InfluenceMap::new((1024, 1024), 64, 0);
+        player.threat_map.add(near, 20, false);
+        assert_eq!(known_structure_to_attack(&player, home), Some(far));
+    }
+
+    #[test]
+    fn test_ai_saves_up_for_reserved_building() {
+        let mut sim = Simulation::new();
+        let strategy = Strategy {
+            build_order: vec![BuildOrderItem::Building("strategic_operations".to_string())],
+            ..Strategy::default()
+        };
+        let mut player = PlayerState::new(FactionId::Continuity, strategy);
+        let scenario = Scenario::default();
+        let depot = spawn_building_with_registry(
+            &mut sim,
+            "depot",
+            100,
+            100,
+            FactionId::Continuity,
+            None,
+            &scenario,
+        );
+        player.depot_entity = Some(depot);
+        let worker = spawn_unit(
+            &mut sim,
+            "harvester",
+            150,
+            100,
+            FactionId::Continuity,
+            &scenario,
+        );
+        player.units.push(worker);
+        player.resources = 300;
+        let mut rng = SimpleRng::new(1);
+        let mut events = Vec::new();
+
+        execute_ai_turn(&mut sim, &mut player, 10, &mut rng, None, &scenario, &[]);
+        record_reservation_trace(&mut player, &mut events);
+        assert_eq!(player.resources, 300, "reserved funds must not be spent");
+        assert!(player.buildings.is_empty());
+        assert!(matches!(
+            events.as_slice(),
+            [TimedEvent {
+                event_type: EventType::ResourcesReserved,
+                ..
+            }]
+        ));
+
+        player.resources = 600;
+        execute_ai_turn(&mut sim, &mut player, 40, &mut rng, None, &scenario, &[]);
+        record_reservation_trace(&mut player, &mut events);
+        let site = player.buildings[0];
+        assert_eq!(
+            player.building_kinds.get(&site).map(String::as_str),
+            Some("strategic_operations")
+        );
+        assert!(is_constructing(&sim, worker));
+        assert!(!is_finished(&sim, site));
+        assert!(player.executor.reservation().is_none());
+        assert_eq!(events[1].event_type, EventType::ReservationReleased);
+        assert_eq!(
+            events[1].details,
+            "Bought strategic_operations after saving for 30 ticks"
+        );
+    }
+
+    #[test]
+    fn test_ai_founds_and_staffs_an_expansion() {
+        let mut sim = Simulation::new();
+        let strategy = Strategy {
+            build_order: Vec::new(),
+            ..Strategy::fast_expand()
+        };
+        let mut player = PlayerState::new(FactionId::Continuity, strategy);
+        let scenario = Scenario::default();
+        let depot = spawn_building_with_registry(
+            &mut sim,
+            "command_center",
+            100,
+            100,
+            FactionId::Continuity,
+            None,
+            &scenario,
+        );
+        player.depot_entity = Some(depot);
+        player.buildings.push(depot);
+        player
+            .building_kinds
+            .insert(depot, "command_center".to_string());
+        for y in [80, 100, 120, 140] {
+            spawn_player_unit(
+                &mut sim,
+                &mut player,
+                "harvester",
+                (150, y),
+                None,
+                &scenario,
+            );
+        }
+        let node = |x: i32, amount: i32| {
+            ResourceNode::new(
+                Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(100)),
+                amount,
+                HARVESTER_GATHER_RATE,
+            )
+        };
+        // Served from home; too poor; the one to take
+        let nodes = vec![node(180, 5000), node(500, 500), node(400, 5000)];
+        player.resources = 3000;
+        let mut rng = SimpleRng::new(1);
+
+        execute_ai_turn(&mut sim, &mut player, 10, &mut rng, None, &scenario, &nodes);
+        assert_eq!(player.first_expansion_tick, Some(10));
+        let (&base, expansion) = player.expansions.iter().next().expect("expanded");
+        assert_eq!(expansion.node, 2);
+        assert!(!is_finished(&sim, base));
+        assert_eq!(expansion_site(&sim, &player, &nodes), None);
+
+        for _ in 0..5000 {
+            if is_finished(&sim, base) {
+                break;
+            }
+            sim.tick();
+        }
+        assert!(is_finished(&sim, base), "expansion never finished");
+        let harvesters = player.harvesters.len();
+        let tick = sim.get_tick();
+        execute_ai_turn(
+            &mut sim,
+            &mut player,
+            tick,
+            &mut rng,
+            None,
+            &scenario,
+            &nodes,
+        );
+        assert!(player.expansions[&base].staffed);
+        let moved = player
+            .harvesters
+            .values()
+            .filter(|h| h.state == HarvesterState::MovingToNode(2))
+            .count();
+        assert_eq!(moved, EXPANSION_HARVESTERS);
+        assert_eq!(player.harvesters.len(), harvesters + 1);
+    }
+
+    #[test]
+    fn test_tuning_overrides_scale_spawned_units() {
+        let mut scenario = Scenario::default();
+        scenario.tuning.health_pct = 150;
+        scenario.tuning.damage_pct = 50;
+
+        let mut sim = Simulation::new();
+        let baseline = spawn_unit(
+            &mut sim,
+            "infantry",
+            0,
+            0,
+            FactionId::Continuity,
+            &Scenario::default(),
+        );
+        let tuned = spawn_unit(&mut sim, "infantry", 0, 0, FactionId::Continuity, &scenario);
+
+        let base = sim.get_entity(baseline).unwrap();
+        let scaled = sim.get_entity(tuned).unwrap();
+        assert_eq!(scaled.health.unwrap().max, base.health.unwrap().max * 3 / 2);
+        assert_eq!(
+            scaled.combat_stats.unwrap().damage,
+            base.combat_stats.unwrap().damage / 2
+        );
+
+        let config = GameConfig {
+            seed: 5,
+            max_ticks: 500,
+            scenario,
+            strategy_a: Strategy::rush(),
+            strategy_b: Strategy::rush(),
+            handicap_a: Handicap::NONE,
+            handicap_b: Handicap::NONE,
+            screenshot_config: None,
+            game_id: "tuned".to_string(),
+            faction_registry: None,
+            autosave: None,
+            chat: false,
+            observers: Vec::new(),
+            stop: StopSignal::default(),
+            projection: None,
+            sample_interval: None,
+        };
+        let result = run_game(config);
+        let tuning = result.metrics.tuning.expect("tuning recorded in metrics");
+        assert_eq!(tuning.health_pct, 150);
+    }
+
+    #[test]
+    fn test_personality_taunts_only_when_chat_enabled() {
+        use crate::personality::{AiPersonality, Taunt, TauntTrigger};
+
+        let mut scenario = Scenario::default();
+        scenario.factions[0].personality = Some(AiPersonality {
+            name: "Auditor".to_string(),
+            preferred_strategies: vec!["rush".to_string()],
+            taunts: vec![Taunt {
+                trigger: TauntTrigger::GameStart,
+                line: "Your paperwork is incomplete.".to_string(),
+            }],
+            ..Default::default()
+        });
+        let play = |chat: bool| {
+            run_game(GameConfig {
+                seed: 3,
+                max_ticks: 50,
+                scenario: scenario.clone(),
+                strategy_a: Strategy::rush(),
+                strategy_b: Strategy::rush(),
+                handicap_a: Handicap::NONE,
+                handicap_b: Handicap::NONE,
+                screenshot_config: None,
+                game_id: "personality".to_string(),
+                faction_registry: None,
+                autosave: None,
+                chat,
+                observers: Vec::new(),
+                stop: StopSignal::default(),
+                projection: None,
+                sample_interval: None,
+            })
+        };
+        let chats = |result: &GameResult| -> Vec<String> {
+            result
+                .metrics
+                .events
+                .iter()
+                .filter(|e| e.event_type == EventType::Chat)
+                .map(|e| e.details.clone())
+                .collect()
+        };
+
+        let with_chat = play(true);
+        assert_eq!(
+            chats(&with_chat),
+            vec!["Auditor: Your paperwork is incomplete.".to_string()]
+        );
+
+        // Chat is flavor only: the game plays out identically without it
+        let without_chat = play(false);
+        assert!(chats(&without_chat).is_empty());
+        assert_eq!(with_chat.final_state_hash, without_chat.final_state_hash);
+    }
+
     #[test]
     fn test_different_seeds_different_results() {
         let config1 = GameConfig {
@@ -1820,9 +5524,17 @@ mod tests {
             scenario: Scenario::default(),
             strategy_a: Strategy::rush(),
             strategy_b: Strategy::economic(),
+            handicap_a: Handicap::NONE,
+            handicap_b: Handicap::NONE,
             screenshot_config: None,
             game_id: "game_1".to_string(),
             faction_registry: None,
+            autosave: None,
+            chat: false,
+            observers: Vec::new(),
+            stop: StopSignal::default(),
+            projection: None,
+            sample_interval: None,
         };
 
         let config2 = GameConfig {
@@ -1831,9 +5543,17 @@ mod tests {
             scenario: Scenario::default(),
             strategy_a: Strategy::rush(),
             strategy_b: Strategy::economic(),
+            handicap_a: Handicap::NONE,
+            handicap_b: Handicap::NONE,
             screenshot_config: None,
             game_id: "game_2".to_string(),
             faction_registry: None,
+            autosave: None,
+            chat: false,
+            observers: Vec::new(),
+            stop: StopSignal::default(),
+            projection: None,
+            sample_interval: None,
         };
 
         let result1 = run_game(config1);
@@ -1870,9 +5590,17 @@ mod tests {
                         scenario: Scenario::default(),
                         strategy_a: strat_a.clone(),
                         strategy_b: strat_b.clone(),
+                        handicap_a: Handicap::NONE,
+                        handicap_b: Handicap::NONE,
                         screenshot_config: None,
                         game_id: format!("{}_vs_{}_{}", name_a, name_b, seed),
                         faction_registry: None,
+                        autosave: None,
+                        chat: false,
+                        observers: Vec::new(),
+                        stop: StopSignal::default(),
+                        projection: None,
+                        sample_interval: None,
                     };
 
                     let result = run_game(config);
@@ -0,0 +1,182 @@
+//! What a headless AI player knows about its enemies.
+//!
+//! The simulation only tells a faction what it sees right now. [`Intel`]
+//! remembers it: where each enemy was last seen and when, what the enemy
+//! army is made of and where its bases stand. Strategies read the
+//! remembered composition to change what they build, the runner attacks
+//! remembered structures, and metrics score how much of the enemy each
+//! side got to see.
+//!
+//! Sightings are taken in entity ID order and kept in ordered maps, so the
+//! same game always builds the same picture.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use rts_core::components::EntityId;
+use rts_core::math::Vec2Fixed;
+use rts_core::simulation::TICK_RATE;
+use serde::{Deserialize, Serialize};
+
+/// Ticks after which a unit not seen again is forgotten; it has likely
+/// moved on or died. Structures are remembered until known destroyed.
+pub const FORGET_UNITS_AFTER: u64 = 120 * TICK_RATE as u64;
+
+/// The last look at one enemy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sighting {
+    /// Unit or building kind.
+    pub kind: String,
+    /// Tags of the kind, such as `"infantry"`.
+    pub tags: Vec<String>,
+    /// Where it was.
+    pub position: Vec2Fixed,
+    /// When it was seen.
+    pub tick: u64,
+    /// Whether it is a structure, which stays put.
+    pub structure: bool,
+    /// Whether it is a base harvesters unload at.
+    pub depot: bool,
+}
+
+/// One player's memory of its enemies.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Intel {
+    /// Last sighting of each remembered enemy.
+    last_seen: BTreeMap<EntityId, Sighting>,
+    /// Every enemy ever seen, remembered or not.
+    sighted: BTreeSet<EntityId>,
+}
+
+impl Intel {
+    /// Record seeing an enemy.
+    pub fn observe(&mut self, id: EntityId, sighting: Sighting) {
+        self.sighted.insert(id);
+        self.last_seen.insert(id, sighting);
+    }
+
+    /// Forget an enemy known to be gone.
+    pub fn forget(&mut self, id: EntityId) {
+        self.last_seen.remove(&id);
+    }
+
+    /// Forget units not seen for [`FORGET_UNITS_AFTER`] ticks.
+    pub fn forget_stale(&mut self, tick: u64) {
+        self.last_seen.retain(|_, sighting| {
+            sighting.structure || tick.saturating_sub(sighting.tick) < FORGET_UNITS_AFTER
+        });
+    }
+
+    /// Last sighting of an enemy, if it is remembered.
+    #[must_use]
+    pub fn last_seen(&self, id: EntityId) -> Option<&Sighting> {
+        self.last_seen.get(&id)
+    }
+
+    /// IDs of the remembered enemies, in order.
+    pub fn remembered(&self) -> impl Iterator<Item = EntityId> + '_ {
+        self.last_seen.keys().copied()
+    }
+
+    /// Remembered enemy structures and where they stand, by entity ID.
+    pub fn structures(&self) -> impl Iterator<Item = (EntityId, Vec2Fixed)> + '_ {
+        self.last_seen
+            .iter()
+            .filter(|(_, sighting)| sighting.structure)
+            .map(|(&id, sighting)| (id, sighting.position))
+    }
+
+    /// Where the remembered enemy bases stand.
+    #[must_use]
+    pub fn bases(&self) -> Vec<Vec2Fixed> {
+        self.last_seen
+            .values()
+            .filter(|sighting| sighting.depot)
+            .map(|sighting| sighting.position)
+            .collect()
+    }
+
+    /// Remembered enemy units.
+    #[must_use]
+    pub fn units(&self) -> u32 {
+        let units = self.last_seen.values().filter(|s| !s.structure).count();
+        u32::try_from(units).unwrap_or(u32::MAX)
+    }
+
+    /// Remembered enemy units by kind and by tag: a unit counts once under
+    /// its kind and once under each of its tags.
+    #[must_use]
+    pub fn composition(&self) -> BTreeMap<String, u32> {
+        let mut counts = BTreeMap::new();
+        for sighting in self.last_seen.values().filter(|s| !s.structure) {
+            for label in std::iter::once(&sighting.kind).chain(&sighting.tags) {
+                *counts.entry(label.clone()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Distinct enemies ever seen.
+    #[must_use]
+    pub fn sighted(&self) -> usize {
+        self.sighted.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rts_core::math::Fixed;
+
+    fn seen(kind: &str, tags: &[&str], tick: u64, structure: bool) -> Sighting {
+        Sighting {
+            kind: kind.to_string(),
+            tags: tags.iter().map(ToString::to_string).collect(),
+            position: Vec2Fixed::new(Fixed::from_num(tick), Fixed::ZERO),
+            tick,
+            structure,
+            depot: kind == "command_center",
+        }
+    }
+
+    #[test]
+    fn test_remembers_composition_and_bases() {
+        let mut intel = Intel::default();
+        intel.observe(1, seen("security_team", &["infantry"], 10, false));
+        intel.observe(2, seen("security_team", &["infantry"], 10, false));
+        intel.observe(3, seen("tank", &[], 10, false));
+        intel.observe(4, seen("command_center", &[], 10, true));
+
+        assert_eq!(intel.units(), 3);
+        let composition = intel.composition();
+        assert_eq!(composition["security_team"], 2);
+        assert_eq!(composition["infantry"], 2);
+        assert_eq!(composition["tank"], 1);
+        assert_eq!(intel.bases(), vec![intel.last_seen(4).unwrap().position]);
+        assert_eq!(
+            intel.structures().map(|(id, _)| id).collect::<Vec<_>>(),
+            [4]
+        );
+
+        // Seen again elsewhere
+        intel.observe(3, seen("tank", &[], 50, false));
+        assert_eq!(intel.last_seen(3).unwrap().tick, 50);
+        assert_eq!(intel.sighted(), 4);
+    }
+
+    #[test]
+    fn test_forgets_stale_units_but_not_structures() {
+        let mut intel = Intel::default();
+        intel.observe(1, seen("tank", &[], 0, false));
+        intel.observe(2, seen("tank", &[], 100, false));
+        intel.observe(3, seen("barracks", &[], 0, true));
+
+        intel.forget_stale(FORGET_UNITS_AFTER);
+        assert!(intel.last_seen(1).is_none());
+        assert!(intel.last_seen(2).is_some());
+        assert!(intel.last_seen(3).is_some());
+
+        intel.forget(3);
+        assert_eq!(intel.structures().count(), 0);
+        assert_eq!(intel.sighted(), 3, "forgotten enemies were still seen");
+    }
+}
@@ -28,30 +28,49 @@
 //!
 //! # Verify determinism
 //! cargo run -p rts_headless -- --replay replay.bin --verify
+//!
+//! # Check determinism across machines with a per-tick hash trace
+//! cargo run -p rts_headless -- verify --trace trace.json
+//! cargo run -p rts_headless -- verify --compare trace.json
 //! ```
 
 pub mod analyzer;
 pub mod ascii_visualizer;
 pub mod batch;
+pub mod bot_api;
 pub mod faction_loader;
 pub mod game_runner;
+pub mod intel;
 pub mod metrics;
+pub mod observer;
+pub mod openings;
+pub mod personality;
+pub mod projection;
 pub mod protocol;
+pub mod raster;
 pub mod runner;
 pub mod scenario;
 pub mod screenshot;
 pub mod spawn_generator;
 pub mod strategies;
+pub mod strategy_tournament;
+pub mod tactics;
+pub mod tournament;
+pub mod trace;
+pub mod tuning;
 pub mod visual_rating;
 pub mod visual_review;
 
 pub use analyzer::{analyze_batch, BalanceAnalysis, BalanceSuggestion};
 pub use ascii_visualizer::{render_ascii, visualize_game_folder, AsciiConfig};
 pub use batch::{run_batch, BatchConfig, BatchResults};
+pub use bot_api::{BotMessage, Capability, RunnerMessage, BOT_API_VERSION};
 pub use faction_loader::{default_faction_data_dir, load_all_factions, FactionRegistry};
 pub use game_runner::GameRunner;
-pub use metrics::{BatchSummary, GameMetrics, MetricsCollector};
+pub use metrics::{BatchSummary, GameMetrics, MetricsCollector, SummaryTally};
+pub use observer::{ArmyValueSampler, GameObserver, PositionLogger};
 pub use protocol::{Command, Response};
+pub use raster::{render_png, RasterConfig};
 pub use runner::HeadlessRunner;
 pub use scenario::{MapSize, Scenario};
 pub use screenshot::{
@@ -60,7 +79,12 @@ pub use screenshot::{
 };
 pub use spawn_generator::{generate_dynamic_scenario, SpawnConfig, SpawnPattern};
 pub use strategies::Strategy;
+pub use strategy_tournament::{run_strategy_tournament, StrategyTournamentConfig};
+pub use tournament::{run_tournament, TournamentConfig, TournamentResults};
+pub use trace::{HashTrace, HashTracer, TraceMismatch};
+pub use tuning::{run_tuning, TuneConfig, TuneResults};
 pub use visual_rating::{
-    analyze_screenshots_in_dir, BatchVisualScore, VisualAnalyzer, VisualScore,
+    analyze_screenshots_in_dir, review_screenshots_in_dir, BatchVisualScore, VisualAnalyzer,
+    VisualScore, DEFAULT_MIN_SCORE,
 };
 pub use visual_review::{BatchVisualReview, VisualQualityReport};
@@ -18,8 +18,39 @@
 //! # Analyze batch results
 //! cargo run -p rts_headless -- analyze --input results/batch.json --suggest
 //!
-//! # Generate visual review report
+//! # Chart army value, income and map control over time
+//! cargo run -p rts_headless -- analyze --input results/batch_results.json --output analysis.html
+//!
+//! # Capture screenshots rendered to PNG, then review them
+//! cargo run -p rts_headless -- batch --count 10 --screenshots --screenshot-png --output results/
 //! cargo run -p rts_headless -- review --screenshots results/screenshots --output report.html
+//!
+//! # Pick a crashed or cancelled batch back up from its last checkpoint
+//! cargo run -p rts_headless -- batch --output results/ --resume
+//!
+//! # Balance every faction pairing, 100 games each
+//! cargo run -p rts_headless -- batch --count 100 --round-robin --faction-data crates/rts_game/assets/data/factions
+//!
+//! # Measure balance on choke-heavy generated maps
+//! cargo run -p rts_headless -- batch --count 100 --map-preset choke-heavy
+//!
+//! # Compare outcomes with and without ±10% damage variance
+//! cargo run -p rts_headless -- batch --count 200 --damage-variance 10 --compare-variance
+//!
+//! # Resume a long game from its newest autosave
+//! cargo run -p rts_headless -- batch --extended --count 1 --autosave-every 3600
+//! cargo run -p rts_headless -- resume --resume-from results/autosaves
+//!
+//! # Round-robin tournament between external bots
+//! cargo run -p rts_headless -- tournament --bots bots/ --output results/tournament.json
+//! cargo run -p rts_headless -- tournament --bots bots/ --scenario crates/rts_game/assets/scenarios/skirmish_1v1.ron
+//!
+//! # Cross-table of built-in strategies, 20 seeds per pairing per side
+//! cargo run -p rts_headless -- tournament --strategies rush,turtle,eco --seeds 20
+//!
+//! # Search infantry damage and cost for an even matchup, writing a RON patch
+//! cargo run -p rts_headless -- tune --faction-data crates/rts_game/assets/data/factions \
+//!     --param continuity.security_team.damage --param continuity.security_team.cost
 //! ```
 //!
 //! # Protocol
@@ -30,17 +61,33 @@
 //!
 //! See the protocol module for command/response format.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use clap::{Parser, Subcommand};
+use rts_core::difficulty::Difficulty;
+use rts_core::factions::FactionId;
+use rts_core::map_generation::MapPreset;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use rts_headless::{
     analyzer::analyze_batch,
-    ascii_visualizer::{render_ascii, visualize_game_folder, AsciiConfig, ScreenshotState},
-    batch::{run_batch, BatchConfig, BatchResults},
+    ascii_visualizer::{
+        render_ascii, visualize_game_folder, AsciiConfig, LiveView, ScreenshotState,
+    },
+    batch::{
+        compare_damage_variance, resolve_strategy, resume_batch_cancellable,
+        round_robin as round_robin_matchups, run_batch_cancellable, BatchConfig, BatchResults,
+        DEFAULT_CHECKPOINT_INTERVAL, DEFAULT_SAMPLE_INTERVAL, GAMES_FILE, RESULTS_FILE,
+    },
+    projection::ProjectionConfig,
     runner::{HeadlessConfig, HeadlessRunner},
     screenshot::ScreenshotMode,
+    strategy_tournament::{run_strategy_tournament, StrategyTournamentConfig},
+    tournament::{run_tournament, TournamentConfig},
+    tuning::{run_tuning, SearchMethod, TunableParam, TuneConfig},
+    visual_rating::{review_screenshots_in_dir, DEFAULT_MIN_SCORE},
     visual_review::BatchVisualReview,
 };
 
@@ -92,10 +139,19 @@ enum Commands {
         #[arg(long, default_value = "0")]
         seed: u64,
 
+        /// Abandon a game as failed after this many wall-clock seconds
+        /// (0 = no limit)
+        #[arg(long, default_value = "0")]
+        game_timeout: u64,
+
         /// Enable screenshot capture
         #[arg(long)]
         screenshots: bool,
 
+        /// Also render each screenshot to PNG, for review reports
+        #[arg(long, requires = "screenshots")]
+        screenshot_png: bool,
+
         /// Path to faction data directory for data-driven unit stats
         #[arg(long)]
         faction_data: Option<PathBuf>,
@@ -112,11 +168,97 @@ enum Commands {
         /// Extended mode: 60-minute games for late-game testing
         #[arg(long, conflicts_with = "duration_minutes")]
         extended: bool,
+
+        /// Autosave each game every N ticks into <output>/autosaves (0 = off)
+        #[arg(long, default_value = "0")]
+        autosave_every: u64,
+
+        /// Sample army value, income, units and map control every N ticks
+        /// for the analyzer's timelines (0 = off)
+        #[arg(long, default_value_t = DEFAULT_SAMPLE_INTERVAL)]
+        sample_every: u64,
+
+        /// Per-shot damage spread for every weapon, ± percent (0 = off, max 50)
+        #[arg(long, default_value = "0", value_parser = clap::value_parser!(u8).range(0..=50))]
+        damage_variance: u8,
+
+        /// Run the batch twice, without and with --damage-variance, and
+        /// compare outcome spread and game length
+        #[arg(long, requires = "damage_variance")]
+        compare_variance: bool,
+
+        /// Factions to pit against each other, e.g. "tinkers,zephyr"
+        /// (default: continuity,collegium)
+        #[arg(long, value_delimiter = ',')]
+        factions: Vec<String>,
+
+        /// Play every pairing of --factions, or of all five factions if
+        /// none are given; --count games per pairing
+        #[arg(long)]
+        round_robin: bool,
+
+        /// Play on generated maps of this archetype instead of --scenario:
+        /// standard, choke-heavy, open-field, islands or resource-rich
+        #[arg(long)]
+        map_preset: Option<String>,
+
+        /// AI difficulty: easy, normal, hard or brutal; "easy,hard" sets
+        /// faction A and faction B separately
+        #[arg(long, value_delimiter = ',')]
+        difficulty: Vec<String>,
+
+        /// Strategy for faction A: a preset name (rush, economic, ...) or a
+        /// RON/JSON strategy file
+        #[arg(long)]
+        strategy_a: Option<String>,
+
+        /// Strategy for faction B: a preset name or a strategy file
+        #[arg(long)]
+        strategy_b: Option<String>,
+
+        /// End games early once one side's chance of winning stays at or
+        /// above this certainty (0.5-1.0), recording them as "projected"
+        #[arg(long)]
+        project_outcome: Option<f64>,
+
+        /// Save a checkpoint to the output directory every N finished
+        /// games (0 = off)
+        #[arg(long, default_value_t = DEFAULT_CHECKPOINT_INTERVAL)]
+        checkpoint_every: u32,
+
+        /// Continue the batch checkpointed in --output instead of starting
+        /// over; its other options come from the checkpoint
+        #[arg(long, conflicts_with = "compare_variance")]
+        resume: bool,
+    },
+
+    /// Resume a batch game from an autosave and play it to the end
+    Resume {
+        /// Autosave file, or a directory to pick the newest autosave from
+        #[arg(long)]
+        resume_from: PathBuf,
+
+        /// Only consider autosaves of this game ID when given a directory
+        #[arg(long)]
+        game: Option<String>,
+
+        /// Path to faction data directory (must match the original game)
+        #[arg(long)]
+        faction_data: Option<PathBuf>,
+
+        /// Keep autosaving every N ticks alongside the resumed save (0 = off)
+        #[arg(long, default_value = "0")]
+        autosave_every: u64,
+
+        /// Write the final game metrics as JSON
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 
     /// Analyze batch results and suggest balance changes
     Analyze {
-        /// Input batch results JSON file
+        /// Batch results summary (batch_results.json) or a games file
+        /// (batch_results.jsonl)
         #[arg(short, long)]
         input: PathBuf,
 
@@ -124,27 +266,33 @@ enum Commands {
         #[arg(long)]
         suggest: bool,
 
-        /// Output markdown report
+        /// Output report: markdown, or HTML with timeline charts for a
+        /// .html path
         #[arg(short, long)]
         output: Option<PathBuf>,
     },
 
     /// Generate visual review report from screenshots
     Review {
-        /// Screenshot manifest or directory
+        /// Review JSON, or a screenshots directory to scan recursively
         #[arg(short, long)]
         screenshots: PathBuf,
 
         /// Output HTML report path
         #[arg(short, long)]
         output: PathBuf,
+
+        /// Score (0-100) a scanned screenshot needs to pass
+        #[arg(long, default_value_t = DEFAULT_MIN_SCORE)]
+        min_score: u32,
     },
 
-    /// Display ASCII visualization of game screenshots
+    /// Display ASCII visualization of game screenshots, or of a game as it
+    /// plays with --live
     Visualize {
         /// Screenshot JSON file or directory containing screenshots
-        #[arg(short, long)]
-        path: PathBuf,
+        #[arg(short, long, required_unless_present = "live")]
+        path: Option<PathBuf>,
 
         /// Width of ASCII output
         #[arg(long, default_value = "80")]
@@ -157,9 +305,48 @@ enum Commands {
         /// Disable colored output
         #[arg(long)]
         no_color: bool,
+
+        /// Draw the first player's threat map under the units
+        #[arg(long)]
+        threat_map: bool,
+
+        /// Play a game and redraw it in the terminal as it runs, with a
+        /// panel of each side's resources and army
+        #[arg(long, conflicts_with = "path")]
+        live: bool,
+
+        /// Scenario file for --live (default: skirmish_1v1)
+        #[arg(long, requires = "live")]
+        scenario: Option<String>,
+
+        /// Random seed for --live
+        #[arg(long, default_value = "0", requires = "live")]
+        seed: u64,
+
+        /// Ticks between redraws in --live mode
+        #[arg(long, default_value = "20")]
+        interval: u64,
+
+        /// Wall-clock pause after each redraw in --live mode, in milliseconds
+        #[arg(long, default_value = "100")]
+        delay_ms: u64,
+
+        /// Path to faction data directory for data-driven unit stats
+        #[arg(long, requires = "live")]
+        faction_data: Option<PathBuf>,
+
+        /// Strategy for faction A in --live mode: a preset name or a
+        /// strategy file
+        #[arg(long, requires = "live")]
+        strategy_a: Option<String>,
+
+        /// Strategy for faction B in --live mode
+        #[arg(long, requires = "live")]
+        strategy_b: Option<String>,
     },
 
-    /// Verify determinism by running same seed multiple times
+    /// Verify determinism by running same seed multiple times, or against
+    /// a hash trace made on another machine with --compare
     Verify {
         /// Scenario to test
         #[arg(short, long, default_value = "skirmish_1v1")]
@@ -172,6 +359,15 @@ enum Commands {
         /// Number of verification runs
         #[arg(short, long, default_value = "5")]
         runs: u32,
+
+        /// Also write the per-tick state hash trace to this file
+        #[arg(long)]
+        trace: Option<PathBuf>,
+
+        /// Replay the game of a golden hash trace and check every tick
+        /// against it; scenario and seed come from the trace
+        #[arg(long, conflicts_with = "trace")]
+        compare: Option<PathBuf>,
     },
 
     /// Replay a recorded game
@@ -185,6 +381,106 @@ enum Commands {
         verify: bool,
     },
 
+    /// Run a round-robin tournament between external bot processes, or
+    /// between built-in strategies with --strategies
+    Tournament {
+        /// Directory containing one bot executable per competitor
+        #[arg(long, required_unless_present = "strategies")]
+        bots: Option<PathBuf>,
+
+        /// Play built-in strategies against each other instead of bots;
+        /// e.g. "rush,turtle,eco", or every preset if no names are given
+        #[arg(long, value_delimiter = ',', num_args = 0.., conflicts_with = "bots")]
+        strategies: Option<Vec<String>>,
+
+        /// Seeds per strategy pairing per side
+        #[arg(long, default_value = "10", requires = "strategies")]
+        seeds: u32,
+
+        /// Path to faction data directory
+        #[arg(long)]
+        faction_data: Option<PathBuf>,
+
+        /// Scenario file for bot games (default: the standard 1v1 skirmish)
+        #[arg(long, requires = "bots")]
+        scenario: Option<PathBuf>,
+
+        /// Per-decision time budget in milliseconds
+        #[arg(long, default_value = "50")]
+        budget_ms: u64,
+
+        /// Timeouts allowed before a bot forfeits
+        #[arg(long, default_value = "10")]
+        max_timeouts: u32,
+
+        /// Games per pairing per side
+        #[arg(long, default_value = "1")]
+        rounds: u32,
+
+        /// Tick limit per game (reaching it is a draw)
+        #[arg(long, default_value = "18000")]
+        max_ticks: u64,
+
+        /// Write full results as JSON
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Search unit stats in faction data for values that bring a matchup
+    /// to a target score, and write them as a RON patch
+    Tune {
+        /// Faction data directory holding the stats to tune
+        #[arg(long)]
+        faction_data: PathBuf,
+
+        /// Unit stat to tune as faction.unit.stat, where stat is cost,
+        /// damage or health; repeat for several
+        #[arg(long = "param", required = true)]
+        params: Vec<String>,
+
+        /// Matchup to balance, e.g. "continuity,collegium"; the target is
+        /// the first faction's score (default: continuity,collegium)
+        #[arg(long, value_delimiter = ',')]
+        factions: Vec<String>,
+
+        /// Score the first faction should reach (wins plus half the draws)
+        #[arg(long, default_value = "0.5")]
+        target: f64,
+
+        /// How far from the target a score may land and still pass
+        #[arg(long, default_value = "0.03")]
+        tolerance: f64,
+
+        /// Search method: grid or hill-climb
+        #[arg(long, default_value = "hill-climb")]
+        method: String,
+
+        /// Percent of the baseline between grid points, and the
+        /// hill-climb's first step
+        #[arg(long, default_value = "10")]
+        step: u32,
+
+        /// Most candidates to play, baseline included
+        #[arg(long, default_value = "30")]
+        max_evaluations: u32,
+
+        /// Games per candidate
+        #[arg(short, long, default_value = "40")]
+        count: u32,
+
+        /// Starting random seed; every candidate plays the same seeds
+        #[arg(long, default_value = "0")]
+        seed: u64,
+
+        /// Maximum game duration in minutes (game time, not wall clock)
+        #[arg(long, default_value = "5")]
+        duration_minutes: u32,
+
+        /// Output directory for the results and patch
+        #[arg(short, long, default_value = "results/tune")]
+        output: PathBuf,
+    },
+
     /// Run N ticks for benchmarking
     Benchmark {
         /// Number of ticks to run
@@ -231,25 +527,97 @@ fn main() {
             parallel,
             output,
             seed,
+            game_timeout,
             screenshots,
+            screenshot_png,
             faction_data,
             duration_minutes,
             quick,
             extended,
+            autosave_every,
+            sample_every,
+            damage_variance,
+            compare_variance,
+            factions,
+            round_robin,
+            map_preset,
+            difficulty,
+            strategy_a,
+            strategy_b,
+            project_outcome,
+            checkpoint_every,
+            resume,
         }) => {
+            let matchups = parse_matchups(&factions, round_robin).unwrap_or_else(|e| {
+                eprintln!("ERROR: {}", e);
+                std::process::exit(1);
+            });
+            let map_preset = map_preset.map(|name| {
+                MapPreset::from_name(&name).unwrap_or_else(|| {
+                    eprintln!("ERROR: Unknown map preset '{}'", name);
+                    std::process::exit(1);
+                })
+            });
+            let difficulties = parse_difficulties(&difficulty).unwrap_or_else(|e| {
+                eprintln!("ERROR: {}", e);
+                std::process::exit(1);
+            });
+            let projection = project_outcome.map(|certainty| {
+                if !(0.5..=1.0).contains(&certainty) {
+                    eprintln!("ERROR: --project-outcome must be between 0.5 and 1.0");
+                    std::process::exit(1);
+                }
+                ProjectionConfig {
+                    certainty,
+                    ..ProjectionConfig::default()
+                }
+            });
+            let screenshot_mode = match (screenshots, screenshot_png) {
+                (true, true) => ScreenshotMode::Png,
+                (true, false) => ScreenshotMode::StateDump,
+                (false, _) => ScreenshotMode::Disabled,
+            };
+            // Catch broken strategy files before any game starts
+            for name in [&strategy_a, &strategy_b] {
+                if let Err(e) = resolve_strategy(name.as_deref()) {
+                    eprintln!("ERROR: {}", e);
+                    std::process::exit(1);
+                }
+            }
             cmd_batch(
                 scenario,
                 count,
                 parallel,
                 output,
                 seed,
-                screenshots,
+                game_timeout,
+                screenshot_mode,
                 faction_data,
                 duration_minutes,
                 quick,
                 extended,
+                autosave_every,
+                sample_every,
+                damage_variance,
+                compare_variance,
+                matchups,
+                map_preset,
+                difficulties,
+                (strategy_a, strategy_b),
+                projection,
+                checkpoint_every,
+                resume,
             );
         }
+        Some(Commands::Resume {
+            resume_from,
+            game,
+            faction_data,
+            autosave_every,
+            output,
+        }) => {
+            cmd_resume(resume_from, game, faction_data, autosave_every, output);
+        }
         Some(Commands::Analyze {
             input,
             suggest,
@@ -260,27 +628,132 @@ fn main() {
         Some(Commands::Review {
             screenshots,
             output,
+            min_score,
         }) => {
-            cmd_review(screenshots, output);
+            cmd_review(screenshots, output, min_score);
         }
         Some(Commands::Visualize {
             path,
             width,
             height,
             no_color,
+            threat_map,
+            live,
+            scenario,
+            seed,
+            interval,
+            delay_ms,
+            faction_data,
+            strategy_a,
+            strategy_b,
         }) => {
-            cmd_visualize(path, width, height, no_color);
+            let config = AsciiConfig {
+                width,
+                height,
+                show_health: true,
+                show_legend: true,
+                use_color: !no_color,
+                show_threat_map: threat_map,
+            };
+            match path {
+                Some(path) if !live => cmd_visualize(path, config),
+                _ => cmd_visualize_live(
+                    config,
+                    scenario,
+                    seed,
+                    interval,
+                    delay_ms,
+                    faction_data,
+                    strategy_a,
+                    strategy_b,
+                ),
+            }
         }
         Some(Commands::Verify {
             scenario,
             seed,
             runs,
-        }) => {
-            cmd_verify(scenario, seed, runs);
-        }
+            trace,
+            compare,
+        }) => match compare {
+            Some(golden) => cmd_verify_trace(&golden),
+            None => cmd_verify(scenario, seed, runs, trace),
+        },
         Some(Commands::Replay { file, verify }) => {
             cmd_replay(file, verify);
         }
+        Some(Commands::Tournament {
+            bots,
+            strategies,
+            seeds,
+            faction_data,
+            scenario,
+            budget_ms,
+            max_timeouts,
+            rounds,
+            max_ticks,
+            output,
+        }) => match (bots, strategies) {
+            (Some(bots), _) => {
+                let mut config = TournamentConfig::new(bots)
+                    .with_budget_ms(budget_ms)
+                    .with_max_timeouts(max_timeouts)
+                    .with_rounds(rounds)
+                    .with_max_ticks(max_ticks);
+                if let Some(path) = scenario {
+                    config = config.with_scenario(path);
+                }
+                if let Some(path) = faction_data {
+                    config = config.with_faction_data(path);
+                }
+                cmd_tournament(config, output);
+            }
+            (None, strategies) => {
+                let mut config = StrategyTournamentConfig::new(strategies.unwrap_or_default())
+                    .with_seeds(seeds)
+                    .with_max_ticks(max_ticks);
+                if let Some(path) = faction_data {
+                    config = config.with_faction_data(path);
+                }
+                cmd_strategy_tournament(config, output);
+            }
+        },
+        Some(Commands::Tune {
+            faction_data,
+            params,
+            factions,
+            target,
+            tolerance,
+            method,
+            step,
+            max_evaluations,
+            count,
+            seed,
+            duration_minutes,
+            output,
+        }) => {
+            let exit = |e: String| -> ! {
+                eprintln!("ERROR: {}", e);
+                std::process::exit(1);
+            };
+            let params = params
+                .iter()
+                .map(|p| p.parse::<TunableParam>())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap_or_else(|e| exit(e.to_string()));
+            let (a, b) = parse_matchups(&factions, false).unwrap_or_else(|e| exit(e))[0];
+            let method = method.parse::<SearchMethod>().unwrap_or_else(|e| exit(e));
+            let config = TuneConfig::new(faction_data, params)
+                .with_matchup(a, b)
+                .with_target(target, tolerance)
+                .with_method(method)
+                .with_step(step)
+                .with_max_evaluations(max_evaluations)
+                .with_games(count)
+                .with_seed(seed)
+                .with_max_ticks(u64::from(duration_minutes) * 60 * 60);
+            cmd_tune(config, &output);
+        }
         Some(Commands::Benchmark { ticks, scenario }) => {
             cmd_benchmark(ticks, scenario);
         }
@@ -304,6 +777,59 @@ fn cmd_run(scenario: Option<String>, auto_state: bool) {
     runner.run();
 }
 
+/// Matchups named by the batch --factions and --round-robin options
+fn parse_matchups(
+    names: &[String],
+    round_robin: bool,
+) -> Result<Vec<(FactionId, FactionId)>, String> {
+    let factions = names
+        .iter()
+        .map(|name| FactionId::from_name(name).ok_or_else(|| format!("Unknown faction '{}'", name)))
+        .collect::<Result<Vec<_>, _>>()?;
+    match (factions.as_slice(), round_robin) {
+        ([], true) => Ok(round_robin_matchups(&FactionId::ALL)),
+        (_, true) => Ok(round_robin_matchups(&factions)),
+        ([], false) => Ok(vec![(FactionId::Continuity, FactionId::Collegium)]),
+        ([a, b], false) if a != b => Ok(vec![(*a, *b)]),
+        _ => Err("--factions takes two different factions, or use --round-robin".to_string()),
+    }
+}
+
+/// AI difficulties for factions A and B named by the batch --difficulty
+/// option; one name sets both
+fn parse_difficulties(names: &[String]) -> Result<(Difficulty, Difficulty), String> {
+    let difficulties = names
+        .iter()
+        .map(|name| {
+            Difficulty::from_name(name).ok_or_else(|| format!("Unknown difficulty '{}'", name))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    match difficulties.as_slice() {
+        [] => Ok((Difficulty::Normal, Difficulty::Normal)),
+        [both] => Ok((*both, *both)),
+        [a, b] => Ok((*a, *b)),
+        _ => Err("--difficulty takes one difficulty, or one per faction".to_string()),
+    }
+}
+
+/// Flag raised by the first Ctrl+C, so a batch can stop and keep what it
+/// has; a second Ctrl+C quits at once.
+fn cancel_on_ctrl_c() -> Arc<AtomicBool> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&cancel);
+    let installed = ctrlc::set_handler(move || {
+        if flag.swap(true, Ordering::Relaxed) {
+            eprintln!("\nInterrupted again, quitting without saving");
+            std::process::exit(130);
+        }
+        eprintln!("\nInterrupted: finishing up and saving partial results (Ctrl+C again to quit)");
+    });
+    if let Err(e) = installed {
+        tracing::warn!(error = %e, "Failed to install Ctrl+C handler");
+    }
+    cancel
+}
+
 /// Run batch of games for balance testing
 fn cmd_batch(
     scenario: String,
@@ -311,11 +837,23 @@ fn cmd_batch(
     parallel: u32,
     output: PathBuf,
     seed: u64,
-    screenshots: bool,
+    game_timeout: u64,
+    screenshot_mode: ScreenshotMode,
     faction_data: Option<PathBuf>,
     duration_minutes: u32,
     quick: bool,
     extended: bool,
+    autosave_every: u64,
+    sample_every: u64,
+    damage_variance: u8,
+    compare_variance: bool,
+    matchups: Vec<(FactionId, FactionId)>,
+    map_preset: Option<MapPreset>,
+    difficulties: (Difficulty, Difficulty),
+    strategies: (Option<String>, Option<String>),
+    projection: Option<ProjectionConfig>,
+    checkpoint_every: u32,
+    resume: bool,
 ) {
     use rts_headless::batch::EXTENDED_DEFAULT_MAX_TICKS;
     use std::time::Instant;
@@ -351,10 +889,14 @@ fn cmd_batch(
         seed = seed,
         output = %output.display(),
         cpus_available = num_cpus,
-        screenshots = screenshots,
+        screenshots = %screenshot_mode,
         faction_data = ?faction_data,
         max_ticks = max_ticks,
         game_duration = %game_duration_str,
+        map_preset = map_preset.map_or("none", MapPreset::name),
+        difficulty_a = %difficulties.0,
+        difficulty_b = %difficulties.1,
+        project_outcome = ?projection.map(|p| p.certainty),
         "Batch configuration"
     );
 
@@ -381,32 +923,58 @@ fn cmd_batch(
         game_count: count,
         parallel_games: parallel,
         output_dir: output.clone(),
-        screenshot_mode: if screenshots {
-            ScreenshotMode::StateDump
-        } else {
-            ScreenshotMode::Disabled
-        },
+        screenshot_mode,
         seed_start: seed,
         max_ticks,
-        strategy_a: None,
-        strategy_b: None,
+        strategy_a: strategies.0,
+        strategy_b: strategies.1,
+        difficulty_a: difficulties.0,
+        difficulty_b: difficulties.1,
+        matchups,
         faction_data_path: faction_data,
+        faction_registry: None,
+        autosave_interval: autosave_every,
+        damage_variance,
+        chat: false,
+        map_preset,
+        game_timeout_secs: game_timeout,
+        show_progress: true,
+        stream_games: true,
+        projection,
+        sample_interval: sample_every,
+        checkpoint_interval: checkpoint_every,
+        observers: Vec::new(),
     };
 
-    let results = run_batch(config);
+    let results = if resume {
+        resume_batch_cancellable(&output, cancel_on_ctrl_c()).unwrap_or_else(|e| {
+            eprintln!(
+                "FATAL: Cannot resume batch from '{}': {}",
+                output.display(),
+                e
+            );
+            std::process::exit(1);
+        })
+    } else {
+        if compare_variance {
+            report_variance_comparison(config, damage_variance, &output);
+            return;
+        }
+        run_batch_cancellable(config, cancel_on_ctrl_c())
+    };
 
     let batch_duration = batch_start.elapsed();
 
     tracing::info!(
-        games_completed = results.games.len(),
+        games_completed = results.summary.total_games,
         games_failed = results.errors.len(),
         total_duration_secs = format!("{:.1}", batch_duration.as_secs_f64()),
         "Batch execution finished"
     );
 
-    // Save results
-    let results_path = output.join("batch_results.json");
-    if let Err(e) = results.save(&results_path) {
+    // Games were streamed to disk as they finished; add the summary
+    let results_path = output.join(RESULTS_FILE);
+    if let Err(e) = results.save_summary(&results_path) {
         tracing::error!(error = %e, path = %results_path.display(), "Failed to save results");
         eprintln!("FATAL: Failed to save results: {}", e);
         std::process::exit(1);
@@ -414,21 +982,44 @@ fn cmd_batch(
 
     // Print summary
     eprintln!("\n{}", "=".repeat(50));
-    eprintln!("BATCH COMPLETE");
+    if results.interrupted {
+        eprintln!("BATCH INTERRUPTED - partial results");
+    } else {
+        eprintln!("BATCH COMPLETE");
+    }
     eprintln!("{}", "=".repeat(50));
-    eprintln!("Games played: {}", results.games.len());
+    eprintln!(
+        "Games played: {} of {}",
+        results.summary.total_games,
+        results.config.total_games()
+    );
     if !results.errors.is_empty() {
         eprintln!("Games FAILED: {} ⚠️", results.errors.len());
     }
     eprintln!("Duration: {:.1}s", results.duration_seconds);
     eprintln!(
         "Throughput: {:.1} games/sec",
-        results.games.len() as f64 / results.duration_seconds.max(0.001)
+        f64::from(results.summary.total_games) / results.duration_seconds.max(0.001)
     );
     eprintln!("\nWin Rates:");
     for (faction, rate) in &results.summary.win_rates {
         eprintln!("  {}: {:.1}%", faction, rate * 100.0);
     }
+    if results.summary.matchups.len() > 1 {
+        eprintln!("\nMatchups:");
+        for (matchup, summary) in &results.summary.matchups {
+            let wins = summary
+                .wins
+                .iter()
+                .map(|(faction, wins)| format!("{} {}", faction, wins))
+                .collect::<Vec<_>>()
+                .join(", ");
+            eprintln!(
+                "  {}: {} games ({}, {} draws)",
+                matchup, summary.games, wins, summary.draws
+            );
+        }
+    }
 
     // Report errors if any
     if !results.errors.is_empty() {
@@ -444,7 +1035,11 @@ fn cmd_batch(
         }
     }
 
-    eprintln!("\nResults saved to: {}", results_path.display());
+    eprintln!(
+        "\nResults saved to: {} (games in {})",
+        results_path.display(),
+        output.join(GAMES_FILE).display()
+    );
 
     // Run quick analysis
     let analysis = analyze_batch(&results);
@@ -457,6 +1052,123 @@ fn cmd_batch(
             );
         }
     }
+
+    if results.interrupted {
+        std::process::exit(130);
+    }
+}
+
+/// Run a damage variance A/B batch and print how outcomes shifted
+fn report_variance_comparison(config: BatchConfig, damage_variance: u8, output: &std::path::Path) {
+    let comparison = compare_damage_variance(config, damage_variance);
+
+    let path = output.join("variance_comparison.json");
+    if let Err(e) = comparison.save(&path) {
+        tracing::error!(error = %e, path = %path.display(), "Failed to save comparison");
+        eprintln!("FATAL: Failed to save comparison: {}", e);
+        std::process::exit(1);
+    }
+
+    eprintln!("\n{}", "=".repeat(50));
+    eprintln!("DAMAGE VARIANCE A/B (±{}%)", damage_variance);
+    eprintln!("{}", "=".repeat(50));
+    for (label, arm) in [
+        ("Baseline", &comparison.baseline),
+        ("Variance", &comparison.varied),
+    ] {
+        eprintln!(
+            "{}: {} games, {} draws, length {:.0} ± {:.0} ticks",
+            label, arm.games, arm.draws, arm.avg_duration_ticks, arm.duration_stddev_ticks
+        );
+        let mut rates: Vec<_> = arm.win_rates.iter().collect();
+        rates.sort_by(|a, b| a.0.cmp(b.0));
+        for (faction, rate) in rates {
+            eprintln!("  {}: {:.1}%", faction, rate * 100.0);
+        }
+    }
+    eprintln!(
+        "\nOutcome flips: {}/{} ({:.1}%)",
+        comparison.outcome_flips,
+        comparison.paired_games,
+        comparison.flip_rate() * 100.0
+    );
+    eprintln!(
+        "Mean length shift: {:+.0} ticks",
+        comparison.duration_shift_ticks()
+    );
+    eprintln!("\nComparison saved to: {}", path.display());
+}
+
+/// Resume a game from an autosave
+fn cmd_resume(
+    resume_from: PathBuf,
+    game: Option<String>,
+    faction_data: Option<PathBuf>,
+    autosave_every: u64,
+    output: Option<PathBuf>,
+) {
+    use rts_core::autosave::{resolve_resume_path, Autosave, AutosaveConfig};
+    use rts_headless::faction_loader::load_factions_from_path;
+    use rts_headless::game_runner::resume_game;
+
+    let path = match resolve_resume_path(&resume_from, game.as_deref()) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to find autosave: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let save = match Autosave::load(&path) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to load autosave: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    eprintln!("Loaded autosave: {}", path.display());
+    eprintln!("  Game: {}", save.label);
+    eprintln!("  Tick: {}", save.tick);
+
+    let registry = faction_data.map(|dir| match load_factions_from_path(&dir) {
+        Ok(r) => std::sync::Arc::new(r),
+        Err(e) => {
+            eprintln!("Failed to load faction data: {}", e);
+            std::process::exit(1);
+        }
+    });
+
+    // Continue the same rotation the save came from
+    let autosave = (autosave_every > 0).then(|| {
+        let dir = path.parent().map(PathBuf::from).unwrap_or_default();
+        AutosaveConfig::new(dir, autosave_every).with_prefix(save.label.clone())
+    });
+
+    let result = match resume_game(&save, registry, autosave) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Failed to resume game: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut metrics = result.metrics;
+    metrics.final_state_hash = result.final_state_hash;
+    eprintln!("Game complete:");
+    eprintln!("  Winner: {}", metrics.winner.as_deref().unwrap_or("draw"));
+    eprintln!("  Duration: {} ticks", metrics.duration_ticks);
+    eprintln!("  Final hash: {:016x}", metrics.final_state_hash);
+
+    if let Some(out_path) = output {
+        let written = serde_json::to_string_pretty(&metrics)
+            .map_err(std::io::Error::other)
+            .and_then(|json| std::fs::write(&out_path, json));
+        if let Err(e) = written {
+            eprintln!("Failed to write metrics: {}", e);
+            std::process::exit(1);
+        }
+        eprintln!("Metrics saved to: {}", out_path.display());
+    }
 }
 
 /// Analyze batch results
@@ -474,7 +1186,14 @@ fn cmd_analyze(input: PathBuf, suggest: bool, output: Option<PathBuf>) {
     let analysis = analyze_batch(&results);
 
     // Output report
-    let report = analysis.to_markdown();
+    let html = output
+        .as_ref()
+        .is_some_and(|path| path.extension().is_some_and(|e| e == "html"));
+    let report = if html {
+        analysis.to_html()
+    } else {
+        analysis.to_markdown()
+    };
 
     if let Some(out_path) = output {
         if let Err(e) = std::fs::write(&out_path, &report) {
@@ -502,7 +1221,7 @@ fn cmd_analyze(input: PathBuf, suggest: bool, output: Option<PathBuf>) {
 }
 
 /// Generate visual review report
-fn cmd_review(screenshots: PathBuf, output: PathBuf) {
+fn cmd_review(screenshots: PathBuf, output: PathBuf, min_score: u32) {
     tracing::info!("Generating visual review from: {}", screenshots.display());
 
     // Load or create batch review
@@ -515,11 +1234,15 @@ fn cmd_review(screenshots: PathBuf, output: PathBuf) {
             }
         }
     } else {
-        // Scan directory for manifests
-        let review = BatchVisualReview::new("visual_review");
-        eprintln!("Note: Directory scanning not yet implemented");
-        eprintln!("Please provide a review JSON file directly");
-        review
+        // Absolute paths, so images still load wherever the report is written
+        let dir = std::fs::canonicalize(&screenshots).unwrap_or(screenshots);
+        match review_screenshots_in_dir(&dir, min_score) {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("Failed to scan screenshots: {}", e);
+                std::process::exit(1);
+            }
+        }
     };
 
     // Generate HTML
@@ -530,23 +1253,16 @@ fn cmd_review(screenshots: PathBuf, output: PathBuf) {
     }
 
     eprintln!("Visual review report saved to: {}", output.display());
+    eprintln!("  Games: {}", review.games().len());
     eprintln!("  Screenshots: {}", review.reports.len());
     eprintln!("  Average score: {:.1}", review.average_score);
     eprintln!("  Pass rate: {:.1}%", review.pass_rate * 100.0);
 }
 
 /// Display ASCII visualization of game screenshots
-fn cmd_visualize(path: PathBuf, width: usize, height: usize, no_color: bool) {
+fn cmd_visualize(path: PathBuf, config: AsciiConfig) {
     tracing::info!("Visualizing: {}", path.display());
 
-    let config = AsciiConfig {
-        width,
-        height,
-        show_health: true,
-        show_legend: true,
-        use_color: !no_color,
-    };
-
     if path.is_file() {
         // Single file visualization
         match ScreenshotState::load(&path) {
@@ -576,8 +1292,88 @@ fn cmd_visualize(path: PathBuf, width: usize, height: usize, no_color: bool) {
     }
 }
 
+/// Play a game, redrawing it in the terminal as it runs
+#[allow(clippy::too_many_arguments)]
+fn cmd_visualize_live(
+    config: AsciiConfig,
+    scenario: Option<String>,
+    seed: u64,
+    interval: u64,
+    delay_ms: u64,
+    faction_data: Option<PathBuf>,
+    strategy_a: Option<String>,
+    strategy_b: Option<String>,
+) {
+    use rts_core::difficulty::Handicap;
+    use rts_headless::faction_loader::load_factions_from_path;
+    use rts_headless::game_runner::{GameConfig, GameRunner, StopSignal};
+    use rts_headless::scenario::Scenario;
+
+    let mut scenario_data = match &scenario {
+        Some(s) => match Scenario::load(s) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("Failed to load scenario: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => Scenario::skirmish_1v1(),
+    };
+    let registry = faction_data.map(|dir| match load_factions_from_path(&dir) {
+        Ok(r) => std::sync::Arc::new(r),
+        Err(e) => {
+            eprintln!("Failed to load faction data: {}", e);
+            std::process::exit(1);
+        }
+    });
+    if let Some(registry) = &registry {
+        if let Err(e) = scenario_data.resolve_starts(registry) {
+            eprintln!("Failed to resolve starting units: {}", e);
+            std::process::exit(1);
+        }
+    }
+    let (strategy_a, strategy_b) = match (
+        resolve_strategy(strategy_a.as_deref()),
+        resolve_strategy(strategy_b.as_deref()),
+    ) {
+        (Ok(a), Ok(b)) => (a, b),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let game_id = format!("live_{}", seed);
+    let view = LiveView::new(config, &game_id, scenario_data.map_size, interval)
+        .with_delay(std::time::Duration::from_millis(delay_ms));
+    let game = GameConfig {
+        seed,
+        max_ticks: 0,
+        scenario: scenario_data,
+        strategy_a,
+        strategy_b,
+        handicap_a: Handicap::NONE,
+        handicap_b: Handicap::NONE,
+        screenshot_config: None,
+        game_id,
+        faction_registry: registry,
+        autosave: None,
+        chat: false,
+        observers: vec![Box::new(view)],
+        stop: StopSignal::default(),
+        projection: None,
+        sample_interval: None,
+    };
+
+    let result = GameRunner::new().run(game);
+    let metrics = result.metrics;
+    eprintln!("Game complete:");
+    eprintln!("  Winner: {}", metrics.winner.as_deref().unwrap_or("draw"));
+    eprintln!("  Duration: {} ticks", metrics.duration_ticks);
+}
+
 /// Verify determinism
-fn cmd_verify(scenario: String, seed: u64, runs: u32) {
+fn cmd_verify(scenario: String, seed: u64, runs: u32, trace: Option<PathBuf>) {
     tracing::info!(
         "Verifying determinism: {} with seed {} ({} runs)",
         scenario,
@@ -593,6 +1389,60 @@ fn cmd_verify(scenario: String, seed: u64, runs: u32) {
         eprintln!("FAIL: Non-determinism detected!");
         std::process::exit(1);
     }
+
+    if let Some(path) = trace {
+        let written = rts_headless::batch::record_hash_trace(&scenario, seed)
+            .and_then(|trace| trace.save(&path).map(|()| trace));
+        match written {
+            Ok(trace) => eprintln!(
+                "Wrote hash trace of {} ticks ({}) to {}",
+                trace.ticks.hashes.len(),
+                trace.platform,
+                path.display()
+            ),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Verify this machine reproduces a golden hash trace tick for tick
+fn cmd_verify_trace(golden: &Path) {
+    let golden = match rts_headless::HashTrace::load(golden) {
+        Ok(trace) => trace,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    tracing::info!(
+        "Comparing {} seed {} against a trace from {}",
+        golden.scenario,
+        golden.seed,
+        golden.platform
+    );
+
+    let local = match rts_headless::batch::record_hash_trace(&golden.scenario, golden.seed) {
+        Ok(trace) => trace,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    match local.compare(&golden) {
+        Ok(()) => eprintln!(
+            "PASS: {} ticks match {} on {}",
+            local.ticks.hashes.len(),
+            golden.platform,
+            local.platform
+        ),
+        Err(e) => {
+            eprintln!("FAIL: {} vs {}: {}", local.platform, golden.platform, e);
+            std::process::exit(1);
+        }
+    }
 }
 
 /// Replay a recorded game
@@ -675,6 +1525,117 @@ fn cmd_replay(file: PathBuf, verify: bool) {
     }
 }
 
+/// Run a bot tournament
+fn cmd_tournament(config: TournamentConfig, output: Option<PathBuf>) {
+    tracing::info!(
+        "Starting tournament with bots from: {}",
+        config.bots_dir.display()
+    );
+
+    let results = match run_tournament(config) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Tournament failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("{}", results.standings_markdown());
+    eprintln!("Games played: {}", results.matches.len());
+
+    if let Some(path) = output {
+        if let Err(e) = results.save(&path) {
+            eprintln!("Failed to save results: {}", e);
+            std::process::exit(1);
+        }
+        eprintln!("Results saved to: {}", path.display());
+    }
+}
+
+/// Run a round-robin tournament between built-in strategies
+fn cmd_strategy_tournament(config: StrategyTournamentConfig, output: Option<PathBuf>) {
+    tracing::info!(
+        "Starting strategy tournament: {} seeds per pairing per side",
+        config.seeds
+    );
+
+    let results = match run_strategy_tournament(config) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Tournament failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("{}", results.cross_table_markdown());
+    if results.failed_games > 0 {
+        eprintln!("Games FAILED: {} ⚠️", results.failed_games);
+    }
+    eprintln!("Duration: {:.1}s", results.duration_seconds);
+
+    if let Some(path) = output {
+        if let Err(e) = results.save(&path) {
+            eprintln!("Failed to save results: {}", e);
+            std::process::exit(1);
+        }
+        eprintln!("Results saved to: {}", path.display());
+    }
+}
+
+/// Search unit stats for a balanced matchup and write the suggested patch
+fn cmd_tune(config: TuneConfig, output: &Path) {
+    tracing::info!(
+        "Starting tuning search: {} parameters, {} games per candidate",
+        config.params.len(),
+        config.games
+    );
+
+    let results = match run_tuning(config) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Tuning failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    println!("{}", results.to_markdown());
+    if results.failed_games > 0 {
+        eprintln!("Games FAILED: {} ⚠️", results.failed_games);
+    }
+    eprintln!(
+        "Evaluated {} candidates in {:.1}s",
+        results.evaluations.len(),
+        results.duration_seconds
+    );
+
+    let results_path = output.join("tune_results.json");
+    let patch_path = output.join("tune_patch.ron");
+    if let Err(e) = results
+        .save(&results_path)
+        .and_then(|()| results.save_patch(&patch_path))
+    {
+        eprintln!("Failed to save results: {}", e);
+        std::process::exit(1);
+    }
+    eprintln!("Results saved to: {}", results_path.display());
+    eprintln!("Suggested patch: {}", patch_path.display());
+
+    if results.patch.within_tolerance {
+        eprintln!(
+            "TARGET MET: score {:.1}% (target {:.1}%)",
+            results.patch.score * 100.0,
+            results.patch.target_score * 100.0
+        );
+    } else {
+        eprintln!(
+            "TARGET MISSED: closest score {:.1}% (target {:.1}%)",
+            results.patch.score * 100.0,
+            results.patch.target_score * 100.0
+        );
+        std::process::exit(1);
+    }
+}
+
 /// Run benchmark
 fn cmd_benchmark(ticks: u64, scenario: Option<String>) {
     use rts_headless::scenario::Scenario;
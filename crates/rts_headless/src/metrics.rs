@@ -3,10 +3,13 @@
 //! This module provides comprehensive metrics collection for analyzing
 //! game balance across multiple matches.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use rts_core::outcome::WinCondition;
 use serde::{Deserialize, Serialize};
 
+use crate::scenario::TuningOverrides;
+
 /// Complete metrics for a single game.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GameMetrics {
@@ -21,13 +24,19 @@ pub struct GameMetrics {
     /// Winning faction (None = draw).
     pub winner: Option<String>,
     /// How the game ended.
-    pub win_condition: String,
+    pub win_condition: WinCondition,
     /// Per-faction metrics.
     pub factions: HashMap<String, FactionMetrics>,
     /// Timed events log.
     pub events: Vec<TimedEvent>,
     /// Final simulation state hash (for determinism validation).
     pub final_state_hash: u64,
+    /// Tuning overrides in effect (None when the scenario ran at baseline).
+    #[serde(default)]
+    pub tuning: Option<TuningOverrides>,
+    /// Results recorded by game observers, keyed by observer.
+    #[serde(default)]
+    pub observations: BTreeMap<String, serde_json::Value>,
 }
 
 impl GameMetrics {
@@ -60,10 +69,10 @@ impl GameMetrics {
     }
 
     /// Finalize the game with outcome.
-    pub fn finalize(&mut self, duration: u64, winner: Option<String>, condition: &str) {
+    pub fn finalize(&mut self, duration: u64, winner: Option<String>, condition: WinCondition) {
         self.duration_ticks = duration;
         self.winner = winner;
-        self.win_condition = condition.to_string();
+        self.win_condition = condition;
     }
 }
 
@@ -112,6 +121,9 @@ pub struct FactionMetrics {
     pub total_damage_dealt: i64,
     /// Total damage taken.
     pub total_damage_taken: i64,
+    /// Health restored by out-of-combat regeneration.
+    #[serde(default)]
+    pub total_health_regenerated: i64,
     /// Battles won.
     pub battles_won: u32,
     /// Battles lost.
@@ -136,6 +148,69 @@ pub struct FactionMetrics {
     pub average_army_position: Vec<(u64, f64, f64)>,
     /// Maximum units at once.
     pub peak_army_size: u32,
+
+    // === Time series ===
+    /// Army, income and unit samples taken every sampling interval, at
+    /// the same ticks as the positioning series.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub time_series: Vec<MetricsSample>,
+
+    // === Scouting ===
+    /// Distinct enemy units and buildings seen during the game.
+    #[serde(default)]
+    pub enemies_sighted: u32,
+    /// Share of everything the enemy fielded that was seen at least once
+    /// (0.0-1.0).
+    #[serde(default)]
+    pub scout_efficiency: f64,
+
+    // === Logistics ===
+    /// Times a unit was rotated out of the fight to resupply.
+    #[serde(default)]
+    pub resupply_trips: u32,
+    /// Unit-ticks spent away from the fight resupplying.
+    #[serde(default)]
+    pub ticks_resupplying: u64,
+
+    // === Upkeep ===
+    /// Harvest income withheld by army upkeep.
+    #[serde(default)]
+    pub upkeep_income_lost: i64,
+    /// Ticks the army was large enough to cut income.
+    #[serde(default)]
+    pub upkeep_ticks_taxed: u64,
+    /// Ticks the faction's upkeep resource sat used up.
+    #[serde(default)]
+    pub upkeep_ticks_exhausted: u64,
+    /// Upkeep resource left at the end (None without one).
+    #[serde(default)]
+    pub upkeep_reserve: Option<u32>,
+
+    // === Rare resources ===
+    /// Exotics, cores and other resources beyond feedstock gathered, by
+    /// resource name.
+    #[serde(default)]
+    pub rare_resources_gathered: BTreeMap<String, i64>,
+    /// Times a purchase was held back for lack of a resource beyond
+    /// feedstock, by resource name.
+    #[serde(default)]
+    pub resource_shortfalls: BTreeMap<String, u32>,
+
+    // === Trading ===
+    /// Trades made at markets.
+    #[serde(default)]
+    pub trades: u32,
+    /// Feedstock value of resources sold at markets.
+    #[serde(default)]
+    pub trade_value_sold: i64,
+    /// Feedstock value of resources bought at markets.
+    #[serde(default)]
+    pub trade_value_bought: i64,
+
+    // === Per unit kind ===
+    /// Combat record of each unit kind fielded.
+    #[serde(default)]
+    pub unit_stats: BTreeMap<String, UnitKindMetrics>,
 }
 
 impl FactionMetrics {
@@ -194,6 +269,29 @@ impl FactionMetrics {
         self.total_damage_taken += amount;
     }
 
+    /// How much more damage the army soaked thanks to regeneration.
+    ///
+    /// Damage taken divided by the health actually lost; 1.0 means
+    /// regeneration made no difference.
+    #[must_use]
+    pub fn effective_hp_multiplier(&self) -> f64 {
+        if self.total_damage_taken <= 0 {
+            return 1.0;
+        }
+        let net_loss = (self.total_damage_taken - self.total_health_regenerated).max(1);
+        self.total_damage_taken as f64 / net_loss as f64
+    }
+
+    /// Share of harvest income upkeep withheld, from 0.0 to 1.0.
+    #[must_use]
+    pub fn upkeep_income_share(&self) -> f64 {
+        let gross = self.resources_from_harvest + self.upkeep_income_lost;
+        if gross <= 0 {
+            return 0.0;
+        }
+        self.upkeep_income_lost as f64 / gross as f64
+    }
+
     /// Calculate final stats.
     pub fn calculate_derived_stats(&mut self) {
         let total_killed: u32 = self.units_killed.values().sum();
@@ -216,6 +314,65 @@ impl FactionMetrics {
     pub fn record_army_position(&mut self, tick: u64, x: f64, y: f64) {
         self.average_army_position.push((tick, x, y));
     }
+
+    /// Record a time-series sample.
+    pub fn record_sample(&mut self, sample: MetricsSample) {
+        self.time_series.push(sample);
+    }
+}
+
+/// One faction's state at a sampled tick.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricsSample {
+    /// Tick the sample was taken at.
+    pub tick: u64,
+    /// Army value, as defined by [`rts_core::stats`].
+    pub army_value: u64,
+    /// Resources gathered per minute since the previous sample.
+    pub income_per_minute: i64,
+    /// Living units by tier (tiers 1, 2 and 3; higher tiers count as 3).
+    pub units_by_tier: [u32; 3],
+}
+
+/// Combat record of one unit kind in a game.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UnitKindMetrics {
+    /// Units of this kind fielded.
+    pub fielded: u32,
+    /// Resources spent producing them, in feedstock terms (see
+    /// [`ResourceKind::feedstock_value`](rts_core::economy::ResourceKind::feedstock_value)).
+    pub resources_spent: i64,
+    /// Damage they dealt.
+    pub damage_dealt: i64,
+    /// Damage they took.
+    pub damage_taken: i64,
+    /// Units of this kind that died.
+    pub lost: u32,
+    /// Ticks alive, summed over every unit fielded. Survivors count up to
+    /// the end of the game.
+    pub lifetime_ticks: u64,
+    /// Enemy units killed, by the victim's kind.
+    pub kills: BTreeMap<String, u32>,
+    /// Damage dealt to enemy units, by the victim's kind.
+    pub damage_to: BTreeMap<String, i64>,
+}
+
+impl UnitKindMetrics {
+    /// Add another record of the same kind into this one.
+    pub fn merge(&mut self, other: &UnitKindMetrics) {
+        self.fielded += other.fielded;
+        self.resources_spent += other.resources_spent;
+        self.damage_dealt += other.damage_dealt;
+        self.damage_taken += other.damage_taken;
+        self.lost += other.lost;
+        self.lifetime_ticks += other.lifetime_ticks;
+        for (kind, kills) in &other.kills {
+            *self.kills.entry(kind.clone()).or_default() += kills;
+        }
+        for (kind, damage) in &other.damage_to {
+            *self.damage_to.entry(kind.clone()).or_default() += damage;
+        }
+    }
 }
 
 /// A timed event during the game.
@@ -234,12 +391,15 @@ pub struct TimedEvent {
 /// Types of events that can be recorded.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EventType {
-    /// Unit was produced.
+    /// Unit was produced (details carry its kind).
     UnitProduced,
     /// Unit was killed.
     UnitKilled,
-    /// Building completed construction.
+    /// Building completed construction (details carry its kind).
     BuildingCompleted,
+    /// A building that trains units completed construction (details carry
+    /// its kind).
+    ProductionBuildingCompleted,
     /// Building was destroyed.
     BuildingDestroyed,
     /// Battle started (multiple units engaged).
@@ -256,6 +416,180 @@ pub enum EventType {
     ResourcesDepleted,
     /// First attack on enemy.
     FirstAttack,
+    /// AI set resources aside for a high-priority purchase.
+    ResourcesReserved,
+    /// AI bought a reserved item, releasing its reservation.
+    ReservationReleased,
+    /// A chat message was posted (e.g. an AI personality's taunt).
+    Chat,
+    /// The match was decided (details carry the win condition).
+    GameEnded,
+}
+
+/// Results of one pairing of factions across a batch.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MatchupSummary {
+    /// Games played.
+    pub games: u32,
+    /// Games won by each faction.
+    pub wins: BTreeMap<String, u32>,
+    /// Games without a winner.
+    pub draws: u32,
+}
+
+/// Running totals behind a [`BatchSummary`], so a batch can be summarized
+/// one game at a time without keeping its games.
+#[derive(Debug, Clone, Default)]
+pub struct SummaryTally {
+    /// Counts kept as they are summarized.
+    summary: BatchSummary,
+    duration_sum: u64,
+    /// Games each faction took part in, won or not.
+    games_played: HashMap<String, u32>,
+    factions: HashMap<String, FactionTally>,
+}
+
+/// Per-faction sums behind the averages of a [`BatchSummary`].
+#[derive(Debug, Clone, Default)]
+struct FactionTally {
+    games: u32,
+    units_produced: u64,
+    resources_gathered: i64,
+    /// Sum and count of finite K/D ratios.
+    kd_sum: f64,
+    kd_count: u32,
+    /// Sum and count of first attack ticks, over games with one.
+    first_attack_sum: u64,
+    first_attacks: u32,
+    ticks_resupplying: u64,
+    upkeep_income_lost: i64,
+}
+
+impl SummaryTally {
+    /// Add one game.
+    pub fn add(&mut self, game: &GameMetrics) {
+        let summary = &mut self.summary;
+        summary.total_games += 1;
+        self.duration_sum += game.duration_ticks;
+        summary.min_duration_ticks = if summary.total_games == 1 {
+            game.duration_ticks
+        } else {
+            summary.min_duration_ticks.min(game.duration_ticks)
+        };
+        summary.max_duration_ticks = summary.max_duration_ticks.max(game.duration_ticks);
+
+        if game.tuning.is_some() {
+            summary.tuned_games += 1;
+        }
+        *summary
+            .win_conditions
+            .entry(game.win_condition.kind().to_string())
+            .or_default() += 1;
+
+        // Win tracking
+        if let Some(winner) = &game.winner {
+            *summary.wins_by_faction.entry(winner.clone()).or_default() += 1;
+        } else {
+            summary.draws += 1;
+        }
+
+        let mut players: Vec<&String> = game.factions.keys().collect();
+        if let Some(winner) = game.winner.as_ref().filter(|w| !players.contains(w)) {
+            players.push(winner);
+        }
+        for player in &players {
+            *self.games_played.entry((*player).clone()).or_default() += 1;
+        }
+        if players.len() > 1 {
+            players.sort();
+            let key = players
+                .iter()
+                .map(|p| p.as_str())
+                .collect::<Vec<_>>()
+                .join(" vs ");
+            let matchup = summary.matchups.entry(key).or_default();
+            matchup.games += 1;
+            match &game.winner {
+                Some(winner) => *matchup.wins.entry(winner.clone()).or_default() += 1,
+                None => matchup.draws += 1,
+            }
+        }
+
+        // Per-faction aggregation
+        for (faction_id, faction) in &game.factions {
+            let tally = self.factions.entry(faction_id.clone()).or_default();
+            tally.games += 1;
+            tally.units_produced += faction
+                .units_produced
+                .values()
+                .map(|&n| u64::from(n))
+                .sum::<u64>();
+            tally.resources_gathered += faction.total_resources_gathered;
+            if faction.kd_ratio.is_finite() {
+                tally.kd_sum += faction.kd_ratio;
+                tally.kd_count += 1;
+            }
+            if let Some(tick) = faction.first_attack_tick {
+                tally.first_attack_sum += tick;
+                tally.first_attacks += 1;
+            }
+            tally.ticks_resupplying += faction.ticks_resupplying;
+            tally.upkeep_income_lost += faction.upkeep_income_lost;
+        }
+    }
+
+    /// Games added so far.
+    #[must_use]
+    pub fn games(&self) -> u32 {
+        self.summary.total_games
+    }
+
+    /// The summary of every game added.
+    #[must_use]
+    pub fn finish(self) -> BatchSummary {
+        let mut summary = self.summary;
+        if summary.total_games == 0 {
+            return summary;
+        }
+        summary.avg_duration_ticks = self.duration_sum as f64 / f64::from(summary.total_games);
+
+        // Win rates
+        for (faction, wins) in &summary.wins_by_faction {
+            let played = self.games_played.get(faction).copied().unwrap_or(1).max(1);
+            summary
+                .win_rates
+                .insert(faction.clone(), f64::from(*wins) / f64::from(played));
+        }
+
+        // Average stats
+        for (faction, tally) in self.factions {
+            let games = f64::from(tally.games);
+            summary
+                .avg_units_produced
+                .insert(faction.clone(), tally.units_produced as f64 / games);
+            summary
+                .avg_resources_gathered
+                .insert(faction.clone(), tally.resources_gathered as f64 / games);
+            summary.avg_kd_ratio.insert(
+                faction.clone(),
+                tally.kd_sum / f64::from(tally.kd_count.max(1)),
+            );
+            if tally.first_attacks > 0 {
+                summary.avg_first_attack_tick.insert(
+                    faction.clone(),
+                    tally.first_attack_sum as f64 / f64::from(tally.first_attacks),
+                );
+            }
+            summary
+                .avg_ticks_resupplying
+                .insert(faction.clone(), tally.ticks_resupplying as f64 / games);
+            summary
+                .avg_upkeep_income_lost
+                .insert(faction, tally.upkeep_income_lost as f64 / games);
+        }
+
+        summary
+    }
 }
 
 /// Summary statistics across multiple games.
@@ -265,8 +599,12 @@ pub struct BatchSummary {
     pub total_games: u32,
     /// Games won by each faction.
     pub wins_by_faction: HashMap<String, u32>,
-    /// Win rates by faction.
+    /// Win rates by faction, over the games each faction played.
     pub win_rates: HashMap<String, f64>,
+    /// Results per pairing of factions, keyed like `"collegium vs continuity"`
+    /// with names sorted.
+    #[serde(default)]
+    pub matchups: BTreeMap<String, MatchupSummary>,
     /// Average game duration in ticks.
     pub avg_duration_ticks: f64,
     /// Shortest game.
@@ -275,6 +613,12 @@ pub struct BatchSummary {
     pub max_duration_ticks: u64,
     /// Draws count.
     pub draws: u32,
+    /// Games per win condition, keyed by [`WinCondition::kind`].
+    #[serde(default)]
+    pub win_conditions: BTreeMap<String, u32>,
+    /// Games played with non-baseline tuning overrides.
+    #[serde(default)]
+    pub tuned_games: u32,
 
     // === Aggregated Stats ===
     /// Average units produced per game by faction.
@@ -285,106 +629,23 @@ pub struct BatchSummary {
     pub avg_kd_ratio: HashMap<String, f64>,
     /// First attack timing distribution (faction -> avg tick).
     pub avg_first_attack_tick: HashMap<String, f64>,
+    /// Average unit-ticks spent resupplying per game by faction.
+    #[serde(default)]
+    pub avg_ticks_resupplying: HashMap<String, f64>,
+    /// Average harvest income withheld by upkeep per game by faction.
+    #[serde(default)]
+    pub avg_upkeep_income_lost: HashMap<String, f64>,
 }
 
 impl BatchSummary {
     /// Calculate summary from a list of game metrics.
     #[must_use]
     pub fn from_games(games: &[GameMetrics]) -> Self {
-        if games.is_empty() {
-            return Self::default();
-        }
-
-        let mut summary = Self {
-            total_games: games.len() as u32,
-            ..Default::default()
-        };
-
-        let mut duration_sum = 0u64;
-        let mut min_duration = u64::MAX;
-        let mut max_duration = 0u64;
-
-        // Aggregate stats
-        let mut faction_units_produced: HashMap<String, Vec<u32>> = HashMap::new();
-        let mut faction_resources: HashMap<String, Vec<i64>> = HashMap::new();
-        let mut faction_kd: HashMap<String, Vec<f64>> = HashMap::new();
-        let mut faction_first_attack: HashMap<String, Vec<u64>> = HashMap::new();
-
+        let mut tally = SummaryTally::default();
         for game in games {
-            // Duration stats
-            duration_sum += game.duration_ticks;
-            min_duration = min_duration.min(game.duration_ticks);
-            max_duration = max_duration.max(game.duration_ticks);
-
-            // Win tracking
-            if let Some(winner) = &game.winner {
-                *summary.wins_by_faction.entry(winner.clone()).or_default() += 1;
-            } else {
-                summary.draws += 1;
-            }
-
-            // Per-faction aggregation
-            for (faction_id, faction) in &game.factions {
-                let total_units: u32 = faction.units_produced.values().sum();
-                faction_units_produced
-                    .entry(faction_id.clone())
-                    .or_default()
-                    .push(total_units);
-
-                faction_resources
-                    .entry(faction_id.clone())
-                    .or_default()
-                    .push(faction.total_resources_gathered);
-
-                faction_kd
-                    .entry(faction_id.clone())
-                    .or_default()
-                    .push(faction.kd_ratio);
-
-                if let Some(tick) = faction.first_attack_tick {
-                    faction_first_attack
-                        .entry(faction_id.clone())
-                        .or_default()
-                        .push(tick);
-                }
-            }
-        }
-
-        // Calculate averages
-        summary.avg_duration_ticks = duration_sum as f64 / games.len() as f64;
-        summary.min_duration_ticks = min_duration;
-        summary.max_duration_ticks = max_duration;
-
-        // Win rates
-        for (faction, wins) in &summary.wins_by_faction {
-            summary
-                .win_rates
-                .insert(faction.clone(), *wins as f64 / summary.total_games as f64);
-        }
-
-        // Average stats
-        for (faction, values) in faction_units_produced {
-            let avg = values.iter().sum::<u32>() as f64 / values.len() as f64;
-            summary.avg_units_produced.insert(faction, avg);
+            tally.add(game);
         }
-
-        for (faction, values) in faction_resources {
-            let avg = values.iter().sum::<i64>() as f64 / values.len() as f64;
-            summary.avg_resources_gathered.insert(faction, avg);
-        }
-
-        for (faction, values) in faction_kd {
-            let avg = values.iter().filter(|v| v.is_finite()).sum::<f64>()
-                / values.iter().filter(|v| v.is_finite()).count().max(1) as f64;
-            summary.avg_kd_ratio.insert(faction, avg);
-        }
-
-        for (faction, values) in faction_first_attack {
-            let avg = values.iter().sum::<u64>() as f64 / values.len() as f64;
-            summary.avg_first_attack_tick.insert(faction, avg);
-        }
-
-        summary
+        tally.finish()
     }
 
     /// Check if faction balance is within acceptable range.
@@ -510,7 +771,7 @@ impl MetricsCollector {
 
     /// Finalize and return the metrics.
     #[must_use]
-    pub fn finalize(mut self, winner: Option<String>, condition: &str) -> GameMetrics {
+    pub fn finalize(mut self, winner: Option<String>, condition: WinCondition) -> GameMetrics {
         self.metrics.finalize(self.current_tick, winner, condition);
 
         // Calculate derived stats for each faction
@@ -577,6 +838,36 @@ mod tests {
         assert_eq!(summary.wins_by_faction.get("continuity"), Some(&1));
         assert_eq!(summary.wins_by_faction.get("collegium"), Some(&1));
         assert!((summary.avg_duration_ticks - 1500.0).abs() < 0.001);
+        assert_eq!(summary.win_conditions.get("time_limit"), Some(&2));
+    }
+
+    #[test]
+    fn test_batch_summary_by_matchup() {
+        let game = |seed: u64, a: &str, b: &str, winner: Option<&str>| {
+            let mut game = GameMetrics::new(format!("g{seed}"), "test", seed);
+            game.faction_mut(a);
+            game.faction_mut(b);
+            game.winner = winner.map(String::from);
+            game
+        };
+        let games = [
+            game(1, "continuity", "tinkers", Some("tinkers")),
+            game(2, "tinkers", "continuity", Some("tinkers")),
+            game(3, "continuity", "zephyr", None),
+            game(4, "zephyr", "continuity", Some("continuity")),
+        ];
+
+        let summary = BatchSummary::from_games(&games);
+
+        assert_eq!(summary.matchups.len(), 2);
+        let tinkers = &summary.matchups["continuity vs tinkers"];
+        assert_eq!(tinkers.games, 2);
+        assert_eq!(tinkers.wins.get("tinkers"), Some(&2));
+        let zephyr = &summary.matchups["continuity vs zephyr"];
+        assert_eq!(zephyr.draws, 1);
+        // Rates are over the games each faction played
+        assert!((summary.win_rates["tinkers"] - 1.0).abs() < 0.001);
+        assert!((summary.win_rates["continuity"] - 0.25).abs() < 0.001);
     }
 
     #[test]
@@ -596,7 +887,13 @@ mod tests {
         collector.on_unit_produced("continuity", "infantry");
         collector.on_resources_gathered("continuity", 500);
 
-        let metrics = collector.finalize(Some("continuity".to_string()), "elimination");
+        let metrics = collector.finalize(
+            Some("continuity".to_string()),
+            WinCondition::Elimination {
+                eliminating_player: rts_core::factions::FactionId::Continuity,
+                surviving_structures: 1,
+            },
+        );
 
         assert_eq!(metrics.winner, Some("continuity".to_string()));
         assert_eq!(
@@ -0,0 +1,257 @@
+//! Per-tick observers for instrumenting headless games.
+//!
+//! Observers let batch runs compute custom metrics without forking the game
+//! runner. Each [`GameConfig`](crate::game_runner::GameConfig) carries a list
+//! of observers that see the simulation after every tick and get a final
+//! call when the game ends, where they can record results into
+//! [`GameMetrics::observations`].
+//!
+//! Observers only get shared access to the simulation, so they cannot
+//! affect determinism. Batch runs clone the configured observers for each
+//! game, so every game starts from a fresh observer.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use rts_core::components::EntityId;
+use rts_core::factions::FactionId;
+use rts_core::simulation::{Simulation, TickEvents};
+use rts_core::stats::faction_totals;
+
+use crate::metrics::GameMetrics;
+use crate::personality::ChatMessage;
+
+/// Observations key used by [`ArmyValueSampler`].
+pub const ARMY_VALUE_KEY: &str = "army_value";
+
+/// Hooks called by the game runner while a game is played.
+///
+/// Implementors must be [`Clone`]; cloning is provided to trait objects
+/// through [`ObserverClone`]. Observers are shared across batch worker
+/// threads as prototypes, hence `Sync`.
+pub trait GameObserver: ObserverClone + Send + Sync + fmt::Debug {
+    /// Called after every simulation tick.
+    fn on_tick(&mut self, sim: &Simulation, events: &TickEvents);
+
+    /// Called after [`on_tick`](Self::on_tick) with what each player's AI
+    /// knows of its own side.
+    fn on_players(&mut self, _sim: &Simulation, _players: &[PlayerView<'_>]) {}
+
+    /// Called for every chat message posted while chat is enabled.
+    fn on_chat(&mut self, _message: &ChatMessage) {}
+
+    /// Called once when the game ends, before its metrics are returned.
+    fn on_game_end(&mut self, _sim: &Simulation, _metrics: &mut GameMetrics) {}
+}
+
+/// A player's own side as its AI tracks it: what the simulation alone
+/// does not know, such as resources on hand and what each entity is.
+#[derive(Debug, Clone, Copy)]
+pub struct PlayerView<'a> {
+    /// The player's faction.
+    pub faction: FactionId,
+    /// Feedstock on hand.
+    pub resources: i64,
+    /// Kind of each living unit.
+    pub unit_kinds: &'a HashMap<EntityId, String>,
+    /// Kind of each standing building.
+    pub building_kinds: &'a HashMap<EntityId, String>,
+}
+
+/// Object-safe cloning for boxed observers.
+///
+/// Implemented automatically for every `Clone` observer.
+pub trait ObserverClone {
+    /// Clone this observer into a new box.
+    fn clone_box(&self) -> Box<dyn GameObserver>;
+}
+
+impl<T> ObserverClone for T
+where
+    T: GameObserver + Clone + 'static,
+{
+    fn clone_box(&self) -> Box<dyn GameObserver> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn GameObserver> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Metrics key for a faction, matching [`GameMetrics::factions`].
+fn faction_key(faction: FactionId) -> String {
+    faction.short_name().to_lowercase()
+}
+
+/// One army value sample.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArmyValueSample {
+    /// Tick the sample was taken at.
+    pub tick: u64,
+    /// Army value by faction.
+    pub value: BTreeMap<String, u64>,
+}
+
+/// Samples each faction's army value at a fixed tick interval.
+///
+/// Army value is as defined by [`rts_core::stats`]. Samples are recorded
+/// under [`ARMY_VALUE_KEY`] at game end.
+#[derive(Debug, Clone)]
+pub struct ArmyValueSampler {
+    interval: u64,
+    samples: Vec<ArmyValueSample>,
+}
+
+impl ArmyValueSampler {
+    /// Create a sampler that records every `interval` ticks (minimum 1).
+    #[must_use]
+    pub fn new(interval: u64) -> Self {
+        Self {
+            interval: interval.max(1),
+            samples: Vec::new(),
+        }
+    }
+
+    /// Samples taken so far.
+    #[must_use]
+    pub fn samples(&self) -> &[ArmyValueSample] {
+        &self.samples
+    }
+
+    fn sample(&mut self, sim: &Simulation) {
+        let value = faction_totals(sim)
+            .into_iter()
+            .map(|(faction, totals)| (faction_key(faction), totals.army_value))
+            .collect();
+        self.samples.push(ArmyValueSample {
+            tick: sim.get_tick(),
+            value,
+        });
+    }
+}
+
+impl GameObserver for ArmyValueSampler {
+    fn on_tick(&mut self, sim: &Simulation, _events: &TickEvents) {
+        if sim.get_tick() % self.interval == 0 {
+            self.sample(sim);
+        }
+    }
+
+    fn on_game_end(&mut self, sim: &Simulation, metrics: &mut GameMetrics) {
+        if self.samples.last().map(|s| s.tick) != Some(sim.get_tick()) {
+            self.sample(sim);
+        }
+        if let Ok(samples) = serde_json::to_value(&self.samples) {
+            metrics
+                .observations
+                .insert(ARMY_VALUE_KEY.to_string(), samples);
+        }
+    }
+}
+
+/// Logs the position of every unit at a fixed tick interval.
+///
+/// Output goes to `tracing` at debug level, one event per unit, in entity
+/// ID order.
+#[derive(Debug, Clone)]
+pub struct PositionLogger {
+    interval: u64,
+}
+
+impl PositionLogger {
+    /// Create a logger that logs every `interval` ticks (minimum 1).
+    #[must_use]
+    pub fn new(interval: u64) -> Self {
+        Self {
+            interval: interval.max(1),
+        }
+    }
+}
+
+impl GameObserver for PositionLogger {
+    fn on_tick(&mut self, sim: &Simulation, _events: &TickEvents) {
+        let tick = sim.get_tick();
+        if tick % self.interval != 0 {
+            return;
+        }
+        for id in sim.entities().sorted_ids() {
+            let Some(entity) = sim.get_entity(id) else {
+                continue;
+            };
+            let (Some(position), Some(_)) = (entity.position, entity.movement) else {
+                continue;
+            };
+            debug!(
+                tick,
+                entity = %sim.entity_label(id),
+                x = position.value.x.to_num::<f32>(),
+                y = position.value.y.to_num::<f32>(),
+                "Unit position"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rts_core::components::{CombatStats, FactionMember};
+    use rts_core::math::{Fixed, Vec2Fixed};
+    use rts_core::simulation::EntitySpawnParams;
+
+    fn spawn_soldier(sim: &mut Simulation, faction: FactionId, x: i32, health: u32) {
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(0))),
+            health: Some(health),
+            movement: Some(Fixed::from_num(1)),
+            combat_stats: Some(CombatStats::new(5, Fixed::from_num(10), 20)),
+            faction: Some(FactionMember::new(faction, 0)),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_army_value_sampler_records_interval_and_final_sample() {
+        let mut sim = Simulation::new();
+        spawn_soldier(&mut sim, FactionId::Continuity, 0, 100);
+        spawn_soldier(&mut sim, FactionId::Continuity, 0, 50);
+        spawn_soldier(&mut sim, FactionId::Collegium, 1000, 80);
+
+        let mut sampler = ArmyValueSampler::new(2);
+        for _ in 0..3 {
+            let events = sim.tick();
+            sampler.on_tick(&sim, &events);
+        }
+        assert_eq!(sampler.samples().len(), 1);
+        assert_eq!(sampler.samples()[0].value["continuity"], 150);
+        assert_eq!(sampler.samples()[0].value["collegium"], 80);
+
+        let mut metrics = GameMetrics::default();
+        sampler.on_game_end(&sim, &mut metrics);
+        let recorded: Vec<ArmyValueSample> =
+            serde_json::from_value(metrics.observations[ARMY_VALUE_KEY].clone()).unwrap();
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[1].tick, 3);
+    }
+
+    #[test]
+    fn test_boxed_observers_clone_fresh_state() {
+        let prototype: Box<dyn GameObserver> = Box::new(ArmyValueSampler::new(1));
+        let mut sim = Simulation::new();
+        let mut first = prototype.clone();
+        let events = sim.tick();
+        first.on_tick(&sim, &events);
+
+        let mut metrics = GameMetrics::default();
+        prototype.clone().on_game_end(&sim, &mut metrics);
+        let recorded: Vec<ArmyValueSample> =
+            serde_json::from_value(metrics.observations[ARMY_VALUE_KEY].clone()).unwrap();
+        assert_eq!(recorded.len(), 1, "clone must not share samples");
+    }
+}
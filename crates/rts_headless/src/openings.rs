@@ -0,0 +1,382 @@
+//! Build-order openings, reconstructed from game event logs.
+//!
+//! [`extract_openings`] replays a game's events to find when each faction
+//! first finished a production building, expanded and attacked, and what
+//! it had built by [`COMPOSITION_MINUTES`]. Each opening is then sorted
+//! into an [`OpeningArchetype`], so a batch can report how often each kind
+//! of opening wins.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use rts_core::simulation::TICK_RATE;
+use serde::{Deserialize, Serialize};
+
+use crate::metrics::{EventType, GameMetrics};
+
+/// Ticks in a minute of game time.
+pub const TICKS_PER_MINUTE: u64 = 60 * TICK_RATE as u64;
+/// Minutes into the game at which openings record what was built.
+pub const COMPOSITION_MINUTES: [u64; 3] = [3, 5, 8];
+/// Attacking by this minute makes an opening a rush.
+pub const RUSH_MINUTES: u64 = 3;
+/// Expanding by this minute, before attacking, makes an opening a fast
+/// expand.
+pub const FAST_EXPAND_MINUTES: u64 = 5;
+/// Lasting this many minutes without attacking makes an opening a turtle.
+pub const TURTLE_MINUTES: u64 = 8;
+
+/// Broad kind of opening.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpeningArchetype {
+    /// Attacked within [`RUSH_MINUTES`].
+    Rush,
+    /// Expanded within [`FAST_EXPAND_MINUTES`], before any attack.
+    FastExpand,
+    /// Played [`TURTLE_MINUTES`] or more without attacking.
+    Turtle,
+    /// None of the above.
+    Standard,
+}
+
+impl OpeningArchetype {
+    /// Classify an opening by its timings, in a game `duration_ticks` long.
+    #[must_use]
+    pub fn classify(
+        first_expansion_tick: Option<u64>,
+        first_attack_tick: Option<u64>,
+        duration_ticks: u64,
+    ) -> Self {
+        let by =
+            |minutes: u64, tick: Option<u64>| tick.is_some_and(|t| t <= minutes * TICKS_PER_MINUTE);
+        let attack = first_attack_tick.unwrap_or(u64::MAX);
+        if by(RUSH_MINUTES, first_attack_tick) {
+            Self::Rush
+        } else if by(FAST_EXPAND_MINUTES, first_expansion_tick)
+            && first_expansion_tick.is_some_and(|t| t < attack)
+        {
+            Self::FastExpand
+        } else if duration_ticks >= TURTLE_MINUTES * TICKS_PER_MINUTE
+            && !by(TURTLE_MINUTES, first_attack_tick)
+        {
+            Self::Turtle
+        } else {
+            Self::Standard
+        }
+    }
+
+    /// Short name for reports.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rush => "rush",
+            Self::FastExpand => "fast_expand",
+            Self::Turtle => "turtle",
+            Self::Standard => "standard",
+        }
+    }
+}
+
+impl fmt::Display for OpeningArchetype {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How one faction opened one game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Opening {
+    /// Game the opening was played in.
+    pub game_id: String,
+    /// Faction that played it.
+    pub faction: String,
+    /// Tick its first production building was finished.
+    pub first_production_tick: Option<u64>,
+    /// Tick it started its first expansion.
+    pub first_expansion_tick: Option<u64>,
+    /// Tick it first sent its army to attack.
+    pub first_attack_tick: Option<u64>,
+    /// Units produced by each of [`COMPOSITION_MINUTES`], by kind; None
+    /// where the game was already over.
+    pub composition: Vec<Option<BTreeMap<String, u32>>>,
+    /// Kind of opening.
+    pub archetype: OpeningArchetype,
+    /// Whether the faction went on to win.
+    pub won: bool,
+}
+
+impl Opening {
+    /// Units produced by each of [`COMPOSITION_MINUTES`].
+    #[must_use]
+    pub fn units_by_minute(&self) -> Vec<Option<u32>> {
+        self.composition
+            .iter()
+            .map(|units| units.as_ref().map(|u| u.values().sum()))
+            .collect()
+    }
+}
+
+/// Reconstruct each faction's opening from a game's event log.
+///
+/// Games recorded without events yield no openings.
+#[must_use]
+pub fn extract_openings(game: &GameMetrics) -> Vec<Opening> {
+    // Without an event log there is no build order to reconstruct
+    if game.events.is_empty() {
+        return Vec::new();
+    }
+
+    let mut factions: Vec<&String> = game.factions.keys().collect();
+    factions.sort();
+
+    factions
+        .into_iter()
+        .map(|faction| {
+            let events: Vec<_> = game
+                .events
+                .iter()
+                .filter(|e| &e.faction == faction)
+                .collect();
+            let first = |event_type: EventType| {
+                events
+                    .iter()
+                    .filter(|e| e.event_type == event_type)
+                    .map(|e| e.tick)
+                    .min()
+            };
+            let first_production_tick = first(EventType::ProductionBuildingCompleted);
+            let first_expansion_tick = first(EventType::ExpansionStarted);
+            let first_attack_tick = first(EventType::FirstAttack);
+
+            let composition = COMPOSITION_MINUTES
+                .iter()
+                .map(|&minutes| {
+                    let tick = minutes * TICKS_PER_MINUTE;
+                    (game.duration_ticks >= tick).then(|| {
+                        let mut units = BTreeMap::new();
+                        for event in events
+                            .iter()
+                            .filter(|e| e.event_type == EventType::UnitProduced && e.tick <= tick)
+                        {
+                            *units.entry(event.details.clone()).or_insert(0) += 1;
+                        }
+                        units
+                    })
+                })
+                .collect();
+
+            Opening {
+                game_id: game.game_id.clone(),
+                faction: faction.clone(),
+                first_production_tick,
+                first_expansion_tick,
+                first_attack_tick,
+                composition,
+                archetype: OpeningArchetype::classify(
+                    first_expansion_tick,
+                    first_attack_tick,
+                    game.duration_ticks,
+                ),
+                won: game.winner.as_ref() == Some(faction),
+            }
+        })
+        .collect()
+}
+
+/// How an opening archetype fared across a batch.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArchetypeStats {
+    /// Openings of this kind played.
+    pub games: u32,
+    /// Of those, games won.
+    pub wins: u32,
+    /// Average tick of the first production building, where there was one.
+    pub avg_first_production_tick: Option<f64>,
+    /// Average tick of the first expansion, where there was one.
+    pub avg_first_expansion_tick: Option<f64>,
+    /// Average tick of the first attack, where there was one.
+    pub avg_first_attack_tick: Option<f64>,
+    /// Average units produced by each of [`COMPOSITION_MINUTES`], over the
+    /// games that lasted that long.
+    pub avg_units_by_minute: Vec<Option<f64>>,
+}
+
+impl ArchetypeStats {
+    /// Share of games won; draws count as not won.
+    #[must_use]
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            f64::from(self.wins) / f64::from(self.games)
+        }
+    }
+}
+
+/// Win rate and typical timings of each opening archetype played.
+#[must_use]
+pub fn summarize_openings(openings: &[Opening]) -> BTreeMap<OpeningArchetype, ArchetypeStats> {
+    let average = |values: Vec<f64>| {
+        (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+    };
+    let mut by_archetype: BTreeMap<OpeningArchetype, Vec<&Opening>> = BTreeMap::new();
+    for opening in openings {
+        by_archetype
+            .entry(opening.archetype)
+            .or_default()
+            .push(opening);
+    }
+
+    by_archetype
+        .into_iter()
+        .map(|(archetype, openings)| {
+            let ticks = |tick: fn(&Opening) -> Option<u64>| {
+                average(
+                    openings
+                        .iter()
+                        .filter_map(|o| tick(o))
+                        .map(|t| t as f64)
+                        .collect(),
+                )
+            };
+            let avg_units_by_minute = (0..COMPOSITION_MINUTES.len())
+                .map(|i| {
+                    average(
+                        openings
+                            .iter()
+                            .filter_map(|o| o.units_by_minute().get(i).copied().flatten())
+                            .map(f64::from)
+                            .collect(),
+                    )
+                })
+                .collect();
+            let stats = ArchetypeStats {
+                games: openings.len() as u32,
+                wins: openings.iter().filter(|o| o.won).count() as u32,
+                avg_first_production_tick: ticks(|o| o.first_production_tick),
+                avg_first_expansion_tick: ticks(|o| o.first_expansion_tick),
+                avg_first_attack_tick: ticks(|o| o.first_attack_tick),
+                avg_units_by_minute,
+            };
+            (archetype, stats)
+        })
+        .collect()
+}
+
+/// A tick as minutes and seconds of game time, `-` for none.
+#[must_use]
+pub fn game_time(tick: Option<f64>) -> String {
+    match tick {
+        Some(tick) => {
+            let seconds = (tick / f64::from(TICK_RATE)).round() as u64;
+            format!("{}:{:02}", seconds / 60, seconds % 60)
+        }
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::FactionMetrics;
+
+    fn minutes(m: u64) -> u64 {
+        m * TICKS_PER_MINUTE
+    }
+
+    #[test]
+    fn test_classify_by_attack_and_expansion_timing() {
+        use OpeningArchetype::*;
+        let classify = |expansion: Option<u64>, attack: Option<u64>| {
+            OpeningArchetype::classify(expansion, attack, minutes(20))
+        };
+        assert_eq!(classify(None, Some(minutes(2))), Rush);
+        // Expanding first doesn't stop an early attack being a rush
+        assert_eq!(classify(Some(minutes(1)), Some(minutes(2))), Rush);
+        assert_eq!(classify(Some(minutes(4)), Some(minutes(6))), FastExpand);
+        assert_eq!(classify(Some(minutes(4)), None), FastExpand);
+        assert_eq!(classify(None, None), Turtle);
+        assert_eq!(classify(None, Some(minutes(9))), Turtle);
+        assert_eq!(classify(None, Some(minutes(6))), Standard);
+        // Expanding after the attack is not a fast expand
+        assert_eq!(classify(Some(minutes(5)), Some(minutes(4))), Standard);
+        // Too short a game to call a turtle
+        assert_eq!(OpeningArchetype::classify(None, None, minutes(4)), Standard);
+    }
+
+    #[test]
+    fn test_extract_openings_from_events() {
+        let mut game = GameMetrics::new("g0", "test", 0);
+        game.duration_ticks = minutes(6);
+        game.winner = Some("continuity".to_string());
+        for faction in ["continuity", "collegium"] {
+            game.factions
+                .insert(faction.to_string(), FactionMetrics::new(faction));
+        }
+        game.record_event(
+            minutes(1),
+            EventType::BuildingCompleted,
+            "continuity",
+            "depot",
+        );
+        game.record_event(
+            minutes(1) + 20,
+            EventType::ProductionBuildingCompleted,
+            "continuity",
+            "barracks",
+        );
+        game.record_event(
+            minutes(2),
+            EventType::UnitProduced,
+            "continuity",
+            "infantry",
+        );
+        game.record_event(
+            minutes(2),
+            EventType::UnitProduced,
+            "continuity",
+            "infantry",
+        );
+        game.record_event(
+            minutes(2),
+            EventType::FirstAttack,
+            "continuity",
+            "First attack",
+        );
+        game.record_event(minutes(4), EventType::UnitProduced, "continuity", "tank");
+        game.record_event(
+            minutes(4),
+            EventType::ExpansionStarted,
+            "collegium",
+            "node 3",
+        );
+
+        let openings = extract_openings(&game);
+        assert_eq!(openings.len(), 2);
+        let (collegium, continuity) = (&openings[0], &openings[1]);
+
+        assert_eq!(continuity.first_production_tick, Some(minutes(1) + 20));
+        assert_eq!(continuity.first_attack_tick, Some(minutes(2)));
+        assert_eq!(continuity.archetype, OpeningArchetype::Rush);
+        assert!(continuity.won);
+        assert_eq!(continuity.units_by_minute(), vec![Some(2), Some(3), None]);
+        assert_eq!(continuity.composition[1].as_ref().unwrap()["tank"], 1);
+
+        assert_eq!(collegium.archetype, OpeningArchetype::FastExpand);
+        assert!(!collegium.won);
+        assert_eq!(collegium.units_by_minute(), vec![Some(0), Some(0), None]);
+
+        let summary = summarize_openings(&openings);
+        assert_eq!(summary[&OpeningArchetype::Rush].win_rate(), 1.0);
+        assert_eq!(summary[&OpeningArchetype::FastExpand].win_rate(), 0.0);
+        assert_eq!(
+            summary[&OpeningArchetype::Rush].avg_units_by_minute,
+            vec![Some(2.0), Some(3.0), None]
+        );
+        assert_eq!(
+            game_time(summary[&OpeningArchetype::Rush].avg_first_attack_tick),
+            "2:00"
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Scenario-defined AI personalities.
+//!
+//! A personality is a flavor layer over a [`Strategy`]: it narrows the
+//! strategy to the ones the character prefers, nudges its aggression, leans
+//! the army composition towards signature units, and posts taunt lines to
+//! chat at scripted moments.
+//!
+//! Chat is off by default in batch runs (see
+//! [`BatchConfig::chat`](crate::batch::BatchConfig::chat)), so personalities
+//! only change balance through their strategy shaping.
+
+use serde::{Deserialize, Serialize};
+
+use crate::scenario::ScenarioError;
+use crate::strategies::Strategy;
+
+/// Composition percent added for each signature unit before renormalizing.
+pub const SIGNATURE_UNIT_WEIGHT_PERCENT: u32 = 20;
+
+/// Scripted moment at which a personality may taunt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TauntTrigger {
+    /// The game has just started.
+    GameStart,
+    /// The AI launches its first attack.
+    FirstAttack,
+    /// The AI's own HQ was destroyed.
+    HqLost,
+    /// The AI won the game.
+    Victory,
+}
+
+/// A chat line posted when its trigger fires.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Taunt {
+    /// When to post the line.
+    pub trigger: TauntTrigger,
+    /// Text of the line.
+    pub line: String,
+}
+
+/// A chat message posted during a game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    /// Tick the message was posted at.
+    pub tick: u64,
+    /// Display name of the sender.
+    pub sender: String,
+    /// Message text.
+    pub text: String,
+}
+
+/// Character for an AI player, attached to a faction by a scenario.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AiPersonality {
+    /// Display name used as the chat sender.
+    pub name: String,
+    /// Strategy presets the personality will play (see
+    /// [`Strategy::preset`]). Empty means any strategy.
+    #[serde(default)]
+    pub preferred_strategies: Vec<String>,
+    /// Percentage points added to the strategy's aggression, which stays
+    /// within 0-100.
+    #[serde(default)]
+    pub aggression_bias_percent: i32,
+    /// Unit kinds the personality favors in its army.
+    #[serde(default)]
+    pub signature_units: Vec<String>,
+    /// Chat lines for scripted moments.
+    #[serde(default)]
+    pub taunts: Vec<Taunt>,
+}
+
+impl AiPersonality {
+    /// Check that every preferred strategy names a known preset.
+    pub fn validate(&self) -> Result<(), ScenarioError> {
+        match self
+            .preferred_strategies
+            .iter()
+            .find(|name| Strategy::preset(name).is_none())
+        {
+            Some(name) => Err(ScenarioError::UnknownStrategy {
+                personality: self.name.clone(),
+                strategy: name.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Shape `base` to this personality.
+    ///
+    /// If `base` is not one of the preferred strategies, a preferred one is
+    /// picked by `seed` instead. Aggression is then biased and signature
+    /// units weighted up in the composition.
+    #[must_use]
+    pub fn shape_strategy(&self, base: Strategy, seed: u64) -> Strategy {
+        let presets: Vec<Strategy> = self
+            .preferred_strategies
+            .iter()
+            .filter_map(|name| Strategy::preset(name))
+            .collect();
+        let mut strategy = if presets.is_empty() || presets.iter().any(|s| s.name == base.name) {
+            base
+        } else {
+            presets[(seed % presets.len() as u64) as usize].clone()
+        };
+
+        strategy.aggression_percent = (i64::from(strategy.aggression_percent)
+            + i64::from(self.aggression_bias_percent))
+        .clamp(0, 100) as u32;
+
+        if !self.signature_units.is_empty() {
+            for unit in &self.signature_units {
+                *strategy.composition.entry(unit.clone()).or_insert(0) +=
+                    SIGNATURE_UNIT_WEIGHT_PERCENT;
+            }
+            // Back to percent, rounding each share down
+            let total: u32 = strategy.composition.values().sum();
+            for weight in strategy.composition.values_mut() {
+                *weight = *weight * 100 / total;
+            }
+        }
+
+        strategy
+    }
+
+    /// Chat message for `trigger`, if the personality has a line for it.
+    ///
+    /// With several lines for one trigger, `seed` picks between them.
+    #[must_use]
+    pub fn taunt(&self, trigger: TauntTrigger, tick: u64, seed: u64) -> Option<ChatMessage> {
+        let lines: Vec<&Taunt> = self
+            .taunts
+            .iter()
+            .filter(|taunt| taunt.trigger == trigger)
+            .collect();
+        if lines.is_empty() {
+            return None;
+        }
+        let taunt = lines[(seed % lines.len() as u64) as usize];
+        Some(ChatMessage {
+            tick,
+            sender: self.name.clone(),
+            text: taunt.line.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warlord() -> AiPersonality {
+        AiPersonality {
+            name: "Warlord".to_string(),
+            preferred_strategies: vec!["rush".to_string(), "all_in".to_string()],
+            aggression_bias_percent: 30,
+            signature_units: vec!["tank".to_string()],
+            taunts: vec![
+                Taunt {
+                    trigger: TauntTrigger::FirstAttack,
+                    line: "Here we come!".to_string(),
+                },
+                Taunt {
+                    trigger: TauntTrigger::FirstAttack,
+                    line: "Brace yourselves.".to_string(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_shape_strategy_swaps_to_preferred_and_biases() {
+        let shaped = warlord().shape_strategy(Strategy::turtle(), 1);
+        assert_eq!(shaped.name, Strategy::all_in().name);
+        assert_eq!(shaped.aggression_percent, 100);
+        assert!(shaped.composition.contains_key("tank"));
+        let total: u32 = shaped.composition.values().sum();
+        assert!(total <= 100 && total + shaped.composition.len() as u32 > 100);
+
+        let kept = warlord().shape_strategy(Strategy::rush(), 1);
+        assert_eq!(kept.name, Strategy::rush().name);
+        assert_eq!(kept.aggression_percent, 100);
+
+        let timid = AiPersonality {
+            aggression_bias_percent: -30,
+            ..AiPersonality::default()
+        };
+        assert_eq!(
+            timid.shape_strategy(Strategy::rush(), 0).aggression_percent,
+            60
+        );
+        assert_eq!(
+            timid
+                .shape_strategy(Strategy::turtle(), 0)
+                .aggression_percent,
+            0
+        );
+    }
+
+    #[test]
+    fn test_shape_strategy_normalises_identically_in_any_map_order() {
+        let weights = [
+            ("infantry", 10),
+            ("ranger", 70),
+            ("harvester", 20),
+            ("tank", 30),
+            ("artillery", 1),
+            ("scout", 5),
+        ];
+        let shape = |order: &mut dyn Iterator<Item = &(&str, u32)>| {
+            let mut strategy = Strategy::rush();
+            strategy.composition = std::collections::BTreeMap::new();
+            for (unit, weight) in order {
+                strategy.composition.insert(unit.to_string(), *weight);
+            }
+            warlord().shape_strategy(strategy, 1)
+        };
+        let forward = shape(&mut weights.iter());
+        let backward = shape(&mut weights.iter().rev());
+        assert_eq!(forward.composition, backward.composition);
+    }
+
+    #[test]
+    fn test_taunt_picks_line_by_seed() {
+        let personality = warlord();
+        let first = personality.taunt(TauntTrigger::FirstAttack, 10, 0).unwrap();
+        let second = personality.taunt(TauntTrigger::FirstAttack, 10, 1).unwrap();
+        assert_eq!(first.sender, "Warlord");
+        assert_ne!(first.text, second.text);
+        assert!(personality.taunt(TauntTrigger::HqLost, 10, 0).is_none());
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_strategy() {
+        let mut personality = warlord();
+        assert!(personality.validate().is_ok());
+        personality.preferred_strategies.push("cheese".to_string());
+        assert!(personality.validate().is_err());
+    }
+}
@@ -0,0 +1,201 @@
+//! Win-probability estimates, for ending decided batch games early.
+//!
+//! Most batch games are decided long before the tick limit: one army has
+//! been wiped out, one economy has collapsed, but the game plays on until a
+//! depot finally falls. [`win_probability`] scores each side on army
+//! strength, economy and how much of its base still stands, and a
+//! [`Projector`] watches the estimates: once one side stays at or above
+//! the configured certainty for enough checks in a row, the runner ends
+//! the game with [`WinCondition::Projected`](rts_core::outcome::WinCondition::Projected).
+//!
+//! Estimates are integer per-mille, so the same game always projects the
+//! same way.
+
+use rts_core::simulation::TICK_RATE;
+use serde::{Deserialize, Serialize};
+
+/// Share of the estimate from army strength, in percent.
+pub const ARMY_WEIGHT: i64 = 50;
+/// Share of the estimate from economy, in percent.
+pub const ECONOMY_WEIGHT: i64 = 25;
+/// Share of the estimate from base health, in percent.
+pub const BASE_WEIGHT: i64 = 25;
+
+/// When and how surely a game may be ended on a projection.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProjectionConfig {
+    /// Ticks between estimates.
+    pub interval_ticks: u64,
+    /// Chance of winning a side needs to be projected the winner
+    /// (0.5-1.0).
+    pub certainty: f64,
+    /// Estimates in a row a side must hold that certainty for.
+    pub consecutive_checks: u32,
+    /// No projections before this tick; openings are too even to call.
+    pub min_tick: u64,
+}
+
+impl Default for ProjectionConfig {
+    fn default() -> Self {
+        Self {
+            interval_ticks: 10 * TICK_RATE as u64,
+            certainty: 0.9,
+            consecutive_checks: 3,
+            min_tick: 120 * TICK_RATE as u64,
+        }
+    }
+}
+
+impl ProjectionConfig {
+    /// Whether an estimate is due at `tick`.
+    #[must_use]
+    pub fn is_due(&self, tick: u64) -> bool {
+        tick >= self.min_tick && tick % self.interval_ticks.max(1) == 0
+    }
+}
+
+/// What one side has going for it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SideStrength {
+    /// Fighting strength of its army.
+    pub army: i64,
+    /// Resources banked and gathered so far.
+    pub economy: i64,
+    /// Health left across its structures.
+    pub base: i64,
+}
+
+/// Chance that side `a` beats side `b`, in per-mille.
+///
+/// Each of army, economy and base is scored by `a`'s share of the two
+/// sides' total, an even split when neither has any, then weighted by
+/// [`ARMY_WEIGHT`], [`ECONOMY_WEIGHT`] and [`BASE_WEIGHT`].
+#[must_use]
+pub fn win_probability(a: &SideStrength, b: &SideStrength) -> u32 {
+    let share = |ours: i64, theirs: i64| {
+        let (ours, theirs) = (ours.max(0), theirs.max(0));
+        if ours + theirs == 0 {
+            500
+        } else {
+            1000 * ours / (ours + theirs)
+        }
+    };
+    let weighted = ARMY_WEIGHT * share(a.army, b.army)
+        + ECONOMY_WEIGHT * share(a.economy, b.economy)
+        + BASE_WEIGHT * share(a.base, b.base);
+    u32::try_from(weighted / 100).unwrap_or(0)
+}
+
+/// A projected result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Projection {
+    /// Whether side A is projected to win; otherwise side B is.
+    pub a_wins: bool,
+    /// The winner's chance in the last estimate, in per-mille.
+    pub certainty: u32,
+}
+
+/// Tracks estimates until one side's win is certain enough.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Projector {
+    /// Side currently over the threshold (true = A), if either.
+    leader: Option<bool>,
+    /// Estimates in a row it has stayed there.
+    streak: u32,
+}
+
+impl Projector {
+    /// Record an estimate of side A's chance, in per-mille. Returns the
+    /// projection once a side has held `config.certainty` for
+    /// `config.consecutive_checks` estimates in a row.
+    pub fn record(&mut self, config: &ProjectionConfig, a_chance: u32) -> Option<Projection> {
+        let a_chance = a_chance.min(1000);
+        let leading = if f64::from(a_chance) / 1000.0 >= config.certainty {
+            Some(true)
+        } else if f64::from(1000 - a_chance) / 1000.0 >= config.certainty {
+            Some(false)
+        } else {
+            None
+        };
+        if leading.is_some() && leading == self.leader {
+            self.streak += 1;
+        } else {
+            self.leader = leading;
+            self.streak = u32::from(leading.is_some());
+        }
+        let a_wins = self.leader?;
+        (self.streak >= config.consecutive_checks.max(1)).then_some(Projection {
+            a_wins,
+            certainty: if a_wins { a_chance } else { 1000 - a_chance },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_win_probability_weighs_army_economy_and_base() {
+        let even = SideStrength {
+            army: 100,
+            economy: 1000,
+            base: 5000,
+        };
+        assert_eq!(win_probability(&even, &even), 500);
+        assert_eq!(
+            win_probability(&SideStrength::default(), &SideStrength::default()),
+            500
+        );
+
+        // Army wiped out, the rest even
+        let broken = SideStrength { army: 0, ..even };
+        assert_eq!(win_probability(&even, &broken), 750);
+        assert_eq!(win_probability(&broken, &even), 250);
+
+        // Nothing left at all
+        assert_eq!(win_probability(&even, &SideStrength::default()), 1000);
+    }
+
+    #[test]
+    fn test_projects_after_consecutive_certain_checks() {
+        let config = ProjectionConfig {
+            certainty: 0.9,
+            consecutive_checks: 2,
+            ..ProjectionConfig::default()
+        };
+        let mut projector = Projector::default();
+        assert_eq!(projector.record(&config, 950), None);
+        // A wobble resets the streak
+        assert_eq!(projector.record(&config, 700), None);
+        assert_eq!(projector.record(&config, 950), None);
+        assert_eq!(
+            projector.record(&config, 960),
+            Some(Projection {
+                a_wins: true,
+                certainty: 960
+            })
+        );
+
+        // Side B, and a switch of leader
+        let mut projector = Projector::default();
+        assert_eq!(projector.record(&config, 980), None);
+        assert_eq!(projector.record(&config, 50), None);
+        assert_eq!(
+            projector.record(&config, 80),
+            Some(Projection {
+                a_wins: false,
+                certainty: 920
+            })
+        );
+    }
+
+    #[test]
+    fn test_estimates_are_due_on_the_interval_after_the_opening() {
+        let config = ProjectionConfig::default();
+        assert!(!config.is_due(0));
+        assert!(!config.is_due(config.min_tick + 1));
+        assert!(config.is_due(config.min_tick));
+        assert!(config.is_due(config.min_tick + config.interval_ticks));
+    }
+}
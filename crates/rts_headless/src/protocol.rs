@@ -22,10 +22,54 @@
 //! <- {"type":"state","tick":60,"entities":[...],"resources":{"feedstock":1000}}
 //! -> {"cmd":"move","entity_id":5,"target_x":200,"target_y":200}
 //! <- {"type":"ack","cmd":"move"}
+//! -> {"cmd":"attack_move","entity_id":5,"target_x":900,"target_y":200,"queue":true}
+//! <- {"type":"ack","cmd":"attack_move"}
+//! -> {"cmd":"train","building_id":1,"unit_id":"security_team"}
+//! <- {"type":"ack","cmd":"train"}
+//! -> {"cmd":"set_rally_point","building_id":1,"x":300,"y":150}
+//! <- {"type":"ack","cmd":"set_rally_point"}
+//! -> {"cmd":"trade","from":"Feedstock","to":"Exotics","amount":200}
+//! <- {"type":"ack","cmd":"trade"}
 //! -> {"cmd":"query"}
 //! <- {"type":"state","tick":60,...}
 //! ```
+//!
+//! Alerts arrive unasked, so controllers can react instead of polling:
+//!
+//! ```text
+//! <- {"type":"alert","tick":75,"alert":"harvester_idle","entity_id":5}
+//! <- {"type":"alert","tick":90,"alert":"production_blocked","entity_id":1,"reason":"supply"}
+//! <- {"type":"alert","tick":130,"alert":"under_attack","entity_id":7}
+//! ```
+//!
+//! Controllers that only need part of the world can ask for it instead of
+//! parsing a full state dump every tick:
+//!
+//! ```text
+//! -> {"cmd":"query_entity","id":5}
+//! <- {"type":"entity","tick":60,"entity":{"id":5,...}}
+//! -> {"cmd":"query_player","faction":"continuity"}
+//! <- {"type":"player","tick":60,"player":{"faction":0,"units":3,...}}
+//! -> {"cmd":"query_region","rect":[0,0,200,200]}
+//! <- {"type":"region","tick":60,"rect":[0.0,0.0,200.0,200.0],"entities":[...]}
+//! ```
+//!
+//! To explore "what happens if" branches, pause and step the game, take
+//! snapshots and rewind to them:
+//!
+//! ```text
+//! -> {"cmd":"snapshot"}
+//! <- {"type":"snapshot","id":0,"tick":60}
+//! -> {"cmd":"step","count":20}
+//! <- {"type":"ack","cmd":"step"}
+//! <- {"type":"state","tick":80,...}
+//! -> {"cmd":"restore","id":0}
+//! <- {"type":"restored","id":0,"tick":60}
+//! ```
 
+use rts_core::economy::{ResourceAmounts, ResourceKind};
+use rts_core::factions::FactionId;
+use rts_core::simulation::EntityRef;
 use serde::{Deserialize, Serialize};
 
 // ============================================================================
@@ -42,9 +86,33 @@ pub enum Command {
         count: u32,
     },
 
+    /// Pause the game and run exactly N ticks (default: 1), then report
+    /// the state.
+    Step {
+        #[serde(default = "default_tick_count")]
+        count: u32,
+    },
+
+    /// Capture the whole game so it can be restored later.
+    Snapshot,
+
+    /// Rewind the game to a snapshot taken earlier. The snapshot is kept,
+    /// so the same point can be restored again.
+    Restore { id: u32 },
+
     /// Query current game state without advancing time.
     Query,
 
+    /// Query a single entity by ID.
+    QueryEntity { id: u32 },
+
+    /// Query one faction's economy and forces, by name (`"continuity"`)
+    /// or by the number used in entity states (`"0"`).
+    QueryPlayer { faction: String },
+
+    /// Query the entities inside `[min_x, min_y, max_x, max_y]`.
+    QueryRegion { rect: [f64; 4] },
+
     /// Spawn a unit at position.
     Spawn {
         unit_type: String,
@@ -63,15 +131,93 @@ pub enum Command {
         faction: Option<u8>,
     },
 
-    /// Issue move command to entity.
+    /// Issue move command to entity. Orders with `queue` set are appended
+    /// to the entity's orders instead of replacing them.
     Move {
         entity_id: u32,
         target_x: f64,
         target_y: f64,
+        #[serde(default)]
+        queue: bool,
     },
 
-    /// Issue attack command to entity.
-    Attack { entity_id: u32, target_id: u32 },
+    /// Issue attack command to entity. Attacking a friendly entity needs
+    /// `force`.
+    Attack {
+        entity_id: u32,
+        target_id: u32,
+        #[serde(default)]
+        queue: bool,
+        #[serde(default)]
+        force: bool,
+    },
+
+    /// Move to a position, engaging enemies on the way.
+    AttackMove {
+        entity_id: u32,
+        target_x: f64,
+        target_y: f64,
+        #[serde(default)]
+        queue: bool,
+    },
+
+    /// Patrol between the entity's position and a target.
+    Patrol {
+        entity_id: u32,
+        target_x: f64,
+        target_y: f64,
+        #[serde(default)]
+        queue: bool,
+    },
+
+    /// Shell a location with a splash weapon.
+    AttackGround {
+        entity_id: u32,
+        target_x: f64,
+        target_y: f64,
+    },
+
+    /// Hold position, engaging enemies in range.
+    HoldPosition { entity_id: u32 },
+
+    /// Follow another entity.
+    Follow { entity_id: u32, target_id: u32 },
+
+    /// Guard another entity, attacking anything that attacks it.
+    Guard { entity_id: u32, target_id: u32 },
+
+    /// Queue a unit (by faction data ID) in one of the local player's
+    /// production buildings, paying its cost and supply.
+    Train { building_id: u32, unit_id: String },
+
+    /// Set where a production building sends the units it makes.
+    SetRallyPoint { building_id: u32, x: f64, y: f64 },
+
+    /// Place a building for the local player, paying its cost. It starts
+    /// under construction.
+    Build {
+        building_type: String,
+        x: f64,
+        y: f64,
+    },
+
+    /// Start researching a technology for the local player.
+    Research { tech_id: String },
+
+    /// Cancel a building still under construction, or the last unit queued
+    /// in a production building, refunding part of the cost.
+    Cancel { entity_id: u32 },
+
+    /// Cancel the local player's research in progress.
+    CancelResearch,
+
+    /// Sell `amount` of one resource for another at the local player's
+    /// market. Rates worsen the more of a pair is traded in a window.
+    Trade {
+        from: ResourceKind,
+        to: ResourceKind,
+        amount: u32,
+    },
 
     /// Issue stop command to entity.
     Stop { entity_id: u32 },
@@ -85,7 +231,7 @@ pub enum Command {
     /// Kill an entity.
     Kill { entity_id: u32 },
 
-    /// Set game speed multiplier.
+    /// Set game speed multiplier, resuming a paused game.
     Speed { multiplier: f64 },
 
     /// Force victory condition.
@@ -140,9 +286,36 @@ pub enum Response {
         hash: u64,
     },
 
+    /// A single entity, answering `query_entity`.
+    Entity { tick: u64, entity: EntityState },
+
+    /// One faction's economy and forces, answering `query_player`.
+    Player { tick: u64, player: PlayerState },
+
+    /// Entities inside a rectangle, answering `query_region`.
+    Region {
+        tick: u64,
+        rect: [f64; 4],
+        entities: Vec<EntityState>,
+    },
+
     /// Entity was spawned.
     Spawned { entity_id: u32, unit_type: String },
 
+    /// Research finished.
+    ResearchComplete { tech_id: String, tick: u64 },
+
+    /// Something needs the controller's attention: `alert` is
+    /// `harvester_idle`, `production_blocked` or `under_attack`, about
+    /// `entity_id`. Blocked production gives its `reason`.
+    Alert {
+        tick: u64,
+        alert: String,
+        entity_id: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+
     /// Game has ended.
     GameOver {
         result: GameResult,
@@ -153,6 +326,12 @@ pub enum Response {
     /// State hash for determinism verification.
     StateHash { tick: u64, hash: u64 },
 
+    /// Snapshot taken, answering `snapshot`.
+    Snapshot { id: u32, tick: u64 },
+
+    /// Game rewound to a snapshot, answering `restore`.
+    Restored { id: u32, tick: u64 },
+
     /// Goodbye message before shutdown.
     Bye,
 }
@@ -177,6 +356,8 @@ pub struct EntityState {
     pub target: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub state: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
 }
 
 /// Type of entity.
@@ -202,6 +383,40 @@ pub struct ResourceState {
     pub feedstock: u32,
 }
 
+/// Economy and forces of one faction.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayerState {
+    pub faction: u8,
+    /// Stockpile and supply; only known for the local player.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<PlayerResourceState>,
+    pub units: u32,
+    pub buildings: u32,
+    pub harvesters: u32,
+    /// Feedstock carried by the faction's harvesters.
+    pub cargo: u32,
+    /// IDs of the faction's entities.
+    pub entity_ids: Vec<u32>,
+    /// Technologies researched; only known for the local player.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub researched: Vec<String>,
+    /// Technology being researched; only known for the local player.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub research: Option<String>,
+}
+
+/// Stockpile and supply of the local player.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerResourceState {
+    pub feedstock: u32,
+    pub feedstock_cap: u32,
+    pub supply_used: u32,
+    pub supply_cap: u32,
+    /// Exotics, cores and other resources beyond feedstock.
+    #[serde(default, skip_serializing_if = "ResourceAmounts::is_empty")]
+    pub reserves: ResourceAmounts,
+}
+
 /// Current game status.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -271,6 +486,63 @@ impl Response {
     }
 }
 
+impl EntityState {
+    /// Describe a core simulation entity, using its simulation ID as the
+    /// entity ID. Returns `None` for entities without a position or whose ID
+    /// does not fit the protocol.
+    pub fn from_entity(entity: EntityRef<'_>) -> Option<Self> {
+        let position = entity.position?.value;
+        let kind = entity
+            .tech_profile
+            .as_ref()
+            .map_or_else(|| "unknown".to_string(), |p| p.kind.clone());
+        let entity_type = if entity.projectile.is_some() {
+            EntityType::Projectile
+        } else if entity.movement.is_none() {
+            EntityType::Building { kind }
+        } else {
+            EntityType::Unit { kind }
+        };
+
+        Some(Self {
+            id: u32::try_from(entity.id).ok()?,
+            entity_type,
+            x: position.x.to_num::<f64>(),
+            y: position.y.to_num::<f64>(),
+            faction: entity.faction.map_or(0, |f| f.faction as u8),
+            health: entity.health.map(|h| HealthState {
+                current: h.current,
+                max: h.max,
+            }),
+            cargo: None,
+            target: entity
+                .attack_target
+                .as_ref()
+                .and_then(|t| t.target)
+                .and_then(|t| u32::try_from(t).ok()),
+            state: None,
+            name: entity.debug_name.as_ref().map(|n| n.0.clone()),
+        })
+    }
+}
+
+/// Parse a faction given by name (`"continuity"`, `"bio_sovereigns"`) or by
+/// its number in entity states (`"0"` to `"4"`).
+pub fn parse_faction(name: &str) -> Option<FactionId> {
+    FactionId::from_name(name).or_else(|| {
+        name.parse::<usize>()
+            .ok()
+            .and_then(|i| FactionId::ALL.get(i).copied())
+    })
+}
+
+/// Whether a point lies inside `[min_x, min_y, max_x, max_y]`, edges
+/// included. Corners given in either order are accepted.
+pub fn rect_contains(rect: [f64; 4], x: f64, y: f64) -> bool {
+    let [x0, y0, x1, y1] = rect;
+    (x0.min(x1)..=x0.max(x1)).contains(&x) && (y0.min(y1)..=y0.max(y1)).contains(&y)
+}
+
 impl Command {
     /// Parse from a JSON line.
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
@@ -281,11 +553,30 @@ impl Command {
     pub fn name(&self) -> &'static str {
         match self {
             Self::Tick { .. } => "tick",
+            Self::Step { .. } => "step",
+            Self::Snapshot => "snapshot",
+            Self::Restore { .. } => "restore",
             Self::Query => "query",
+            Self::QueryEntity { .. } => "query_entity",
+            Self::QueryPlayer { .. } => "query_player",
+            Self::QueryRegion { .. } => "query_region",
             Self::Spawn { .. } => "spawn",
             Self::SpawnBuilding { .. } => "spawn_building",
             Self::Move { .. } => "move",
             Self::Attack { .. } => "attack",
+            Self::AttackMove { .. } => "attack_move",
+            Self::Patrol { .. } => "patrol",
+            Self::AttackGround { .. } => "attack_ground",
+            Self::HoldPosition { .. } => "hold_position",
+            Self::Follow { .. } => "follow",
+            Self::Guard { .. } => "guard",
+            Self::Train { .. } => "train",
+            Self::SetRallyPoint { .. } => "set_rally_point",
+            Self::Build { .. } => "build",
+            Self::Research { .. } => "research",
+            Self::Cancel { .. } => "cancel",
+            Self::CancelResearch => "cancel_research",
+            Self::Trade { .. } => "trade",
             Self::Stop { .. } => "stop",
             Self::SetResources { .. } => "set_resources",
             Self::Teleport { .. } => "teleport",
@@ -347,4 +638,151 @@ mod tests {
         let cmd = Command::from_json(json).unwrap();
         assert!(matches!(cmd, Command::Tick { count: 1 }));
     }
+
+    #[test]
+    fn test_parse_rewind_commands() {
+        let cmd = Command::from_json(r#"{"cmd":"step"}"#).unwrap();
+        assert!(matches!(cmd, Command::Step { count: 1 }));
+        let cmd = Command::from_json(r#"{"cmd":"snapshot"}"#).unwrap();
+        assert_eq!(cmd.name(), "snapshot");
+        let cmd = Command::from_json(r#"{"cmd":"restore","id":3}"#).unwrap();
+        assert!(matches!(cmd, Command::Restore { id: 3 }));
+
+        let json = Response::Restored { id: 3, tick: 60 }.to_json_line();
+        assert!(json.contains(r#""type":"restored","id":3,"tick":60"#));
+    }
+
+    #[test]
+    fn test_parse_query_commands() {
+        let cmd = Command::from_json(r#"{"cmd":"query_entity","id":7}"#).unwrap();
+        assert!(matches!(cmd, Command::QueryEntity { id: 7 }));
+
+        let cmd = Command::from_json(r#"{"cmd":"query_player","faction":"continuity"}"#).unwrap();
+        assert!(matches!(cmd, Command::QueryPlayer { ref faction } if faction == "continuity"));
+        assert_eq!(cmd.name(), "query_player");
+
+        let cmd = Command::from_json(r#"{"cmd":"query_region","rect":[0,0,200,100.5]}"#).unwrap();
+        assert!(matches!(
+            cmd,
+            Command::QueryRegion { rect } if rect == [0.0, 0.0, 200.0, 100.5]
+        ));
+        assert!(Command::from_json(r#"{"cmd":"query_region","rect":[0,0]}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_faction() {
+        assert_eq!(parse_faction("Continuity"), Some(FactionId::Continuity));
+        assert_eq!(
+            parse_faction("bio-sovereigns"),
+            Some(FactionId::BioSovereigns)
+        );
+        assert_eq!(
+            parse_faction(&(FactionId::Zephyr as u8).to_string()),
+            Some(FactionId::Zephyr)
+        );
+        assert_eq!(parse_faction("pirates"), None);
+    }
+
+    #[test]
+    fn test_rect_contains() {
+        let rect = [100.0, 50.0, 0.0, 0.0];
+        assert!(rect_contains(rect, 0.0, 0.0));
+        assert!(rect_contains(rect, 100.0, 25.0));
+        assert!(!rect_contains(rect, 100.5, 25.0));
+        assert!(!rect_contains(rect, 50.0, -1.0));
+    }
+
+    #[test]
+    fn test_serialize_player_response() {
+        let resp = Response::Player {
+            tick: 5,
+            player: PlayerState {
+                faction: 1,
+                units: 2,
+                entity_ids: vec![3, 4],
+                ..Default::default()
+            },
+        };
+        let json = resp.to_json_line();
+        assert!(json.contains(r#""type":"player""#));
+        assert!(json.contains(r#""entity_ids":[3,4]"#));
+        assert!(!json.contains("resources"));
+    }
+
+    #[test]
+    fn test_serialize_alert_response() {
+        let resp = Response::Alert {
+            tick: 9,
+            alert: "harvester_idle".to_string(),
+            entity_id: Some(5),
+            reason: None,
+        };
+        let json = resp.to_json_line();
+        assert!(json.contains(r#""type":"alert""#));
+        assert!(json.contains(r#""alert":"harvester_idle""#));
+        assert!(!json.contains("reason"));
+    }
+
+    #[test]
+    fn test_parse_order_commands() {
+        let cmd = Command::from_json(
+            r#"{"cmd":"attack_move","entity_id":3,"target_x":10,"target_y":20,"queue":true}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            cmd,
+            Command::AttackMove {
+                entity_id: 3,
+                queue: true,
+                ..
+            }
+        ));
+
+        // Orders replace by default
+        let cmd = Command::from_json(r#"{"cmd":"move","entity_id":3,"target_x":1,"target_y":2}"#)
+            .unwrap();
+        assert!(matches!(cmd, Command::Move { queue: false, .. }));
+
+        let cmd = Command::from_json(r#"{"cmd":"guard","entity_id":3,"target_id":4}"#).unwrap();
+        assert_eq!(cmd.name(), "guard");
+        let cmd = Command::from_json(r#"{"cmd":"cancel_research"}"#).unwrap();
+        assert!(matches!(cmd, Command::CancelResearch));
+        let cmd =
+            Command::from_json(r#"{"cmd":"set_rally_point","building_id":1,"x":300,"y":150}"#)
+                .unwrap();
+        assert!(matches!(cmd, Command::SetRallyPoint { building_id: 1, .. }));
+        let cmd =
+            Command::from_json(r#"{"cmd":"trade","from":"Feedstock","to":"Exotics","amount":200}"#)
+                .unwrap();
+        assert!(matches!(
+            cmd,
+            Command::Trade {
+                from: ResourceKind::Feedstock,
+                to: ResourceKind::Exotics,
+                amount: 200
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_economy_commands() {
+        let cmd =
+            Command::from_json(r#"{"cmd":"train","building_id":1,"unit_id":"security_team"}"#)
+                .unwrap();
+        assert!(
+            matches!(cmd, Command::Train { building_id: 1, ref unit_id } if unit_id == "security_team")
+        );
+
+        let cmd =
+            Command::from_json(r#"{"cmd":"build","building_type":"barracks","x":50,"y":-20}"#)
+                .unwrap();
+        assert!(matches!(
+            cmd,
+            Command::Build { ref building_type, x, y } if building_type == "barracks" && x == 50.0 && y == -20.0
+        ));
+
+        let cmd = Command::from_json(r#"{"cmd":"research","tech_id":"hardened_plating"}"#).unwrap();
+        assert_eq!(cmd.name(), "research");
+        assert!(Command::from_json(r#"{"cmd":"cancel"}"#).is_err());
+    }
 }
@@ -0,0 +1,266 @@
+//! PNG rendering of screenshot state dumps, without Bevy or a GPU.
+//!
+//! Draws the map, buildings and units of a [`VisualState`] as flat shapes
+//! in faction colors, so headless batch runs can produce images a
+//! reviewer can look at directly. Buildings are squares, units are
+//! circles, and anything damaged gets a health bar above it.
+
+use std::path::Path;
+
+use image::{Rgb, RgbImage};
+
+use crate::screenshot::VisualState;
+
+/// Map background.
+const BACKGROUND: Rgb<u8> = Rgb([34, 40, 34]);
+/// Grid lines drawn every [`GRID_SPACING`] world units.
+const GRID: Rgb<u8> = Rgb([46, 54, 46]);
+/// World units between grid lines.
+const GRID_SPACING: f32 = 128.0;
+/// Health bar background.
+const HEALTH_EMPTY: Rgb<u8> = Rgb([90, 20, 20]);
+
+/// Image size and shape sizes for [`render_png`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RasterConfig {
+    /// Image width in pixels; the height follows the map's aspect ratio.
+    pub width: u32,
+    /// Unit radius in pixels.
+    pub unit_radius: u32,
+    /// Building half-size in pixels; depots are drawn half again as big.
+    pub building_size: u32,
+    /// Draw health bars over damaged units and buildings.
+    pub show_health: bool,
+}
+
+impl Default for RasterConfig {
+    fn default() -> Self {
+        Self {
+            width: 512,
+            unit_radius: 3,
+            building_size: 6,
+            show_health: true,
+        }
+    }
+}
+
+/// Color of a faction, matching the ASCII visualizer's palette.
+fn faction_rgb(faction: &str) -> Rgb<u8> {
+    let faction = faction.to_lowercase();
+    if faction.starts_with("cont") {
+        Rgb([70, 130, 230])
+    } else if faction.starts_with("coll") {
+        Rgb([235, 200, 60])
+    } else if faction.starts_with("tink") {
+        Rgb([220, 110, 50])
+    } else if faction.starts_with("bio") {
+        Rgb([90, 200, 90])
+    } else if faction.starts_with("zeph") {
+        Rgb([180, 120, 230])
+    } else {
+        Rgb([150, 150, 150])
+    }
+}
+
+/// Green through yellow to red as health falls.
+fn health_rgb(health_percent: f32) -> Rgb<u8> {
+    if health_percent > 0.66 {
+        Rgb([60, 200, 60])
+    } else if health_percent > 0.33 {
+        Rgb([230, 200, 40])
+    } else {
+        Rgb([220, 50, 40])
+    }
+}
+
+/// Fill the rectangle from `(x0, y0)` to `(x1, y1)` inclusive, clipped to
+/// the image.
+fn fill_rect(image: &mut RgbImage, (x0, y0): (i64, i64), (x1, y1): (i64, i64), color: Rgb<u8>) {
+    let (w, h) = (i64::from(image.width()), i64::from(image.height()));
+    for y in y0.max(0)..=y1.min(h - 1) {
+        for x in x0.max(0)..=x1.min(w - 1) {
+            image.put_pixel(x as u32, y as u32, color);
+        }
+    }
+}
+
+/// Fill a circle, clipped to the image.
+fn fill_circle(image: &mut RgbImage, (cx, cy): (i64, i64), radius: i64, color: Rgb<u8>) {
+    let (w, h) = (i64::from(image.width()), i64::from(image.height()));
+    for y in (cy - radius).max(0)..=(cy + radius).min(h - 1) {
+        for x in (cx - radius).max(0)..=(cx + radius).min(w - 1) {
+            let (dx, dy) = (x - cx, y - cy);
+            if dx * dx + dy * dy <= radius * radius {
+                image.put_pixel(x as u32, y as u32, color);
+            }
+        }
+    }
+}
+
+/// A health bar `half_width` either side of `x`, just above `top`.
+fn health_bar(image: &mut RgbImage, x: i64, top: i64, half_width: i64, health_percent: f32) {
+    let health_percent = health_percent.clamp(0.0, 1.0);
+    let y = top - 3;
+    fill_rect(
+        image,
+        (x - half_width, y),
+        (x + half_width, y + 1),
+        HEALTH_EMPTY,
+    );
+    let filled = ((2 * half_width + 1) as f32 * health_percent).round() as i64;
+    if filled > 0 {
+        fill_rect(
+            image,
+            (x - half_width, y),
+            (x - half_width + filled - 1, y + 1),
+            health_rgb(health_percent),
+        );
+    }
+}
+
+/// Render a state dump as an image.
+#[must_use]
+pub fn render_png(state: &VisualState, config: &RasterConfig) -> RgbImage {
+    let map_w = state.map_bounds.0.max(1) as f32;
+    let map_h = state.map_bounds.1.max(1) as f32;
+    let width = config.width.max(1);
+    let scale = width as f32 / map_w;
+    let height = ((map_h * scale).round() as u32).max(1);
+    let mut image = RgbImage::from_pixel(width, height, BACKGROUND);
+    let to_pixel = |(x, y): (f32, f32)| ((x * scale) as i64, (y * scale) as i64);
+
+    // Map grid
+    let spacing = (GRID_SPACING * scale).max(1.0);
+    let mut line = spacing;
+    while line < width as f32 {
+        fill_rect(
+            &mut image,
+            (line as i64, 0),
+            (line as i64, i64::from(height)),
+            GRID,
+        );
+        line += spacing;
+    }
+    let mut line = spacing;
+    while line < height as f32 {
+        fill_rect(
+            &mut image,
+            (0, line as i64),
+            (i64::from(width), line as i64),
+            GRID,
+        );
+        line += spacing;
+    }
+
+    // Buildings under units
+    for building in &state.buildings {
+        let (x, y) = to_pixel(building.position);
+        let size = if building.kind.contains("depot") {
+            i64::from(config.building_size) * 3 / 2
+        } else {
+            i64::from(config.building_size)
+        };
+        fill_rect(
+            &mut image,
+            (x - size, y - size),
+            (x + size, y + size),
+            faction_rgb(&building.faction),
+        );
+        if config.show_health && building.health_percent < 1.0 {
+            health_bar(&mut image, x, y - size, size, building.health_percent);
+        }
+    }
+
+    let radius = i64::from(config.unit_radius.max(1));
+    for unit in &state.units {
+        let (x, y) = to_pixel(unit.position);
+        fill_circle(&mut image, (x, y), radius, faction_rgb(&unit.faction));
+        if config.show_health && unit.health_percent < 1.0 {
+            health_bar(&mut image, x, y - radius, radius + 1, unit.health_percent);
+        }
+    }
+
+    image
+}
+
+/// Render a state dump and write it as a PNG.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub fn save_png(state: &VisualState, path: &Path, config: &RasterConfig) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    render_png(state, config)
+        .save_with_format(path, image::ImageFormat::Png)
+        .map_err(std::io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::screenshot::{BuildingVisual, ScreenshotTrigger, UnitVisual};
+
+    fn unit(faction: &str, position: (f32, f32), health_percent: f32) -> UnitVisual {
+        UnitVisual {
+            entity_id: 1,
+            kind: "infantry".to_string(),
+            faction: faction.to_string(),
+            position,
+            rotation: 0.0,
+            health_percent,
+            animation_state: "idle".to_string(),
+            animation_frame: 0,
+            is_selected: false,
+            current_action: None,
+        }
+    }
+
+    #[test]
+    fn test_render_draws_units_and_buildings_in_faction_colors() {
+        let mut state = VisualState::new(
+            "raster",
+            100,
+            ScreenshotTrigger::Manual {
+                name: "test".to_string(),
+            },
+        );
+        state.map_bounds = (1024, 512);
+        state.units.push(unit("continuity", (256.0, 256.0), 1.0));
+        state.units.push(unit("collegium", (768.0, 256.0), 0.2));
+        state.buildings.push(BuildingVisual {
+            entity_id: 2,
+            kind: "depot".to_string(),
+            faction: "collegium".to_string(),
+            position: (900.0, 100.0),
+            health_percent: 1.0,
+            construction_progress: None,
+            is_producing: false,
+            rally_point: None,
+        });
+
+        let image = render_png(&state, &RasterConfig::default());
+        // Height follows the map's aspect ratio
+        assert_eq!(image.dimensions(), (512, 256));
+        assert_eq!(*image.get_pixel(128, 128), faction_rgb("continuity"));
+        assert_eq!(*image.get_pixel(384, 128), faction_rgb("collegium"));
+        assert_eq!(*image.get_pixel(450, 50), faction_rgb("collegium"));
+        assert_eq!(*image.get_pixel(10, 10), BACKGROUND);
+        // A badly hurt unit gets a red health bar
+        let bar = (377..=391).map(|x| *image.get_pixel(x, 122));
+        assert!(bar.clone().any(|p| p == health_rgb(0.2)));
+        assert!(bar.into_iter().any(|p| p == HEALTH_EMPTY));
+    }
+
+    #[test]
+    fn test_save_png_writes_a_readable_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shots").join("frame.png");
+        let state = VisualState::new("raster", 0, ScreenshotTrigger::TimedSnapshot { tick: 0 });
+        save_png(&state, &path, &RasterConfig::default()).unwrap();
+
+        let loaded = image::open(&path).unwrap();
+        assert_eq!((loaded.width(), loaded.height()), (512, 512));
+    }
+}
@@ -3,14 +3,27 @@
 use std::collections::HashMap;
 use std::io::{self, BufRead, Write};
 
+use bevy::ecs::query::ROQueryItem;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
-use rts_core::components::Command as CoreCommand;
+use rts_core::autosave::Autosave;
+use rts_core::components::{Command as CoreCommand, EntityId};
+use rts_core::economy::ResourceKind;
 use rts_core::factions::FactionId;
+use rts_core::market::{Market, MarketError};
 use rts_core::math::{Fixed, Vec2Fixed};
+use rts_core::notifications::{BlockReason, Notification};
+use rts_core::production::ProductionError;
+use rts_core::research::ResearchEvent;
+use rts_core::simulation::Simulation;
+use rts_game::autosave::{capture_client_save, resume_client_save, SavedEntityQuery};
+use rts_game::components::{BuildingType, CoreEntityId, UnderConstruction};
+use rts_game::production::cancel_production;
+use rts_game::unit_utils::unit_supply;
 
 use crate::protocol::{
-    Command, EntityState, EntityType, GameResult, GameStatus, HealthState, MatchStatsOutput,
-    ResourceState, Response,
+    parse_faction, rect_contains, Command, EntityState, EntityType, GameResult, GameStatus,
+    HealthState, MatchStatsOutput, PlayerResourceState, PlayerState, ResourceState, Response,
 };
 use crate::scenario::Scenario;
 
@@ -47,6 +60,14 @@ impl EntityIdMap {
             self.external_to_bevy.remove(&id);
         }
     }
+
+    /// Point an external ID at a different entity, keeping IDs stable
+    /// across a restore.
+    fn bind(&mut self, external_id: u32, entity: Entity) {
+        self.bevy_to_external.insert(entity, external_id);
+        self.external_to_bevy.insert(external_id, entity);
+        self.next_id = self.next_id.max(external_id + 1);
+    }
 }
 
 /// Command queue for processing input.
@@ -56,6 +77,20 @@ struct CommandQueue {
     should_quit: bool,
 }
 
+/// A captured game that `restore` can rewind to.
+struct Snapshot {
+    save: Autosave,
+    /// External IDs handed out so far, by core entity.
+    ids: Vec<(u32, EntityId)>,
+}
+
+/// Snapshots taken so far, and where a running `step` stops.
+#[derive(Resource, Default)]
+struct Rewind {
+    snapshots: Vec<Snapshot>,
+    step_until: Option<u64>,
+}
+
 /// Response queue for output.
 #[derive(Resource, Default)]
 struct ResponseQueue {
@@ -117,8 +152,18 @@ impl HeadlessRunner {
             .init_resource::<EntityIdMap>()
             .init_resource::<CommandQueue>()
             .init_resource::<ResponseQueue>()
+            .init_resource::<Rewind>()
             .add_systems(First, read_stdin_commands)
-            .add_systems(Last, (process_commands, flush_responses).chain());
+            .add_systems(
+                Last,
+                (
+                    advance_research,
+                    report_alerts,
+                    process_commands,
+                    flush_responses,
+                )
+                    .chain(),
+            );
 
         // Output ready message
         let ready = Response::ready(0);
@@ -190,32 +235,59 @@ fn try_read_line() -> Option<String> {
     receiver.recv_timeout(Duration::from_millis(10)).ok()
 }
 
+/// Components reported to the controller for each entity.
+type UnitData = (
+    Entity,
+    &'static rts_game::components::GamePosition,
+    &'static rts_game::components::GameFaction,
+    Option<&'static rts_game::components::GameHealth>,
+    Option<&'static rts_game::components::GameHarvester>,
+    Option<&'static rts_game::components::AttackTarget>,
+    Option<&'static rts_game::components::GameUnitKind>,
+    Option<&'static rts_game::components::Building>,
+    Option<&'static rts_game::components::CoreEntityId>,
+    Option<&'static rts_game::components::GameDebugName>,
+);
+
+/// Entities reported to the controller.
+type UnitQuery<'w, 's> = Query<'w, 's, UnitData>;
+
 /// System to process queued commands.
 #[allow(clippy::too_many_arguments)]
-#[allow(clippy::type_complexity)]
 fn process_commands(
     mut queue: ResMut<CommandQueue>,
     mut responses: ResMut<ResponseQueue>,
     mut entity_map: ResMut<EntityIdMap>,
     mut bevy_commands: Commands,
     mut app_exit: EventWriter<AppExit>,
-    core_sim: Option<ResMut<rts_game::simulation::CoreSimulation>>,
+    mut core_sim: Option<ResMut<rts_game::simulation::CoreSimulation>>,
     mut core_commands: Option<ResMut<rts_game::simulation::CoreCommandBuffer>>,
     mut player_resources: Option<ResMut<rts_game::economy::PlayerResources>>,
-    units: Query<(
-        Entity,
-        &rts_game::components::GamePosition,
-        &rts_game::components::GameFaction,
-        Option<&rts_game::components::GameHealth>,
-        Option<&rts_game::components::GameHarvester>,
-        Option<&rts_game::components::AttackTarget>,
-        Option<&rts_game::components::GameUnitKind>,
-        Option<&rts_game::components::Building>,
-        Option<&rts_game::components::CoreEntityId>,
-    )>,
-    resources: Option<Res<rts_game::economy::PlayerResources>>,
+    units: UnitQuery,
+    player_faction: Option<Res<rts_game::components::PlayerFaction>>,
     game_state: Option<Res<rts_game::victory::GameState>>,
+    mut economy: EconomyAccess,
+    mut rewind: RewindAccess,
 ) {
+    // Report a finished step before anything queued after it
+    if let (Some(core), Some(res), Some(until)) = (
+        core_sim.as_ref(),
+        player_resources.as_ref(),
+        rewind.rewind.step_until,
+    ) {
+        if core.sim.get_tick() >= until {
+            rewind.rewind.step_until = None;
+            responses.send(build_state_response(
+                core.sim.get_tick(),
+                &units,
+                &entity_map,
+                res.feedstock as u32,
+                game_state.as_ref(),
+                core.sim.state_hash(),
+            ));
+        }
+    }
+
     // Process all queued commands
     for cmd in queue.commands.drain(..) {
         let cmd_name = cmd.name();
@@ -228,7 +300,7 @@ fn process_commands(
                 responses.send(Response::ack(cmd_name));
 
                 // Output state after ticks
-                if let (Some(core), Some(res)) = (core_sim.as_ref(), resources.as_ref()) {
+                if let (Some(core), Some(res)) = (core_sim.as_ref(), player_resources.as_ref()) {
                     let state = build_state_response(
                         core.sim.get_tick(),
                         &units,
@@ -241,8 +313,52 @@ fn process_commands(
                 }
             }
 
+            Command::Step { count } => match (core_sim.as_ref(), rewind.speed.as_mut()) {
+                (Some(core), Some(speed)) => {
+                    speed.pause();
+                    speed.step_ticks(count);
+                    rewind.rewind.step_until = Some(core.sim.get_tick() + u64::from(count));
+                    responses.send(Response::ack(cmd_name));
+                }
+                _ => responses.send(Response::error(
+                    "Simulation not initialized",
+                    Some(cmd_name),
+                )),
+            },
+
+            Command::Snapshot => {
+                let response = match (core_sim.as_ref(), core_commands.as_ref()) {
+                    (Some(core), Some(pending)) => take_snapshot(
+                        core,
+                        pending,
+                        player_resources.as_deref(),
+                        &entity_map,
+                        &mut rewind,
+                    ),
+                    _ => Err("Simulation not initialized".to_string()),
+                };
+                responses.send(response.unwrap_or_else(|e| Response::error(e, Some(cmd_name))));
+            }
+
+            Command::Restore { id } => {
+                let response = match (core_sim.as_mut(), core_commands.as_mut()) {
+                    (Some(core), Some(pending)) => restore_snapshot(
+                        id,
+                        &mut bevy_commands,
+                        core,
+                        pending,
+                        player_resources.as_deref_mut(),
+                        &mut entity_map,
+                        economy.registry.as_deref(),
+                        &mut rewind,
+                    ),
+                    _ => Err("Simulation not initialized".to_string()),
+                };
+                responses.send(response.unwrap_or_else(|e| Response::error(e, Some(cmd_name))));
+            }
+
             Command::Query => {
-                if let (Some(core), Some(res)) = (core_sim.as_ref(), resources.as_ref()) {
+                if let (Some(core), Some(res)) = (core_sim.as_ref(), player_resources.as_ref()) {
                     let state = build_state_response(
                         core.sim.get_tick(),
                         &units,
@@ -260,6 +376,66 @@ fn process_commands(
                 }
             }
 
+            Command::QueryEntity { id } => {
+                let tick = core_sim.as_ref().map_or(0, |c| c.sim.get_tick());
+                match entity_map
+                    .lookup(id)
+                    .and_then(|entity| units.get(entity).ok())
+                {
+                    Some(item) => responses.send(Response::Entity {
+                        tick,
+                        entity: entity_state(item, &entity_map),
+                    }),
+                    None => responses.send(Response::error(
+                        format!("Entity {} not found", id),
+                        Some(cmd_name),
+                    )),
+                }
+            }
+
+            Command::QueryPlayer { faction } => {
+                let Some(faction_id) = parse_faction(&faction) else {
+                    responses.send(Response::error(
+                        format!("Unknown faction {}", faction),
+                        Some(cmd_name),
+                    ));
+                    continue;
+                };
+                let tick = core_sim.as_ref().map_or(0, |c| c.sim.get_tick());
+                let local = player_faction
+                    .as_ref()
+                    .is_some_and(|p| p.faction == faction_id);
+                let mut player = player_state(
+                    faction_id,
+                    &units,
+                    &entity_map,
+                    player_resources.as_deref().filter(|_| local),
+                );
+                if let Some(core) = core_sim.as_ref().filter(|_| local) {
+                    player.researched = core
+                        .sim
+                        .tech_state(faction_id)
+                        .map(|state| state.researched.iter().cloned().collect())
+                        .unwrap_or_default();
+                    player.research = current_research(&core.sim, faction_id);
+                }
+                responses.send(Response::Player { tick, player });
+            }
+
+            Command::QueryRegion { rect } => {
+                let tick = core_sim.as_ref().map_or(0, |c| c.sim.get_tick());
+                let entities = units
+                    .iter()
+                    .map(|item| entity_state(item, &entity_map))
+                    .filter(|state| rect_contains(rect, state.x, state.y))
+                    .collect();
+                responses.send(Response::Region {
+                    tick,
+                    rect,
+                    entities,
+                });
+            }
+
             Command::Hash => {
                 if let Some(core) = core_sim.as_ref() {
                     responses.send(Response::StateHash {
@@ -302,107 +478,281 @@ fn process_commands(
                 entity_id,
                 target_x,
                 target_y,
+                queue,
             } => {
-                if let Some(entity) = entity_map.lookup(entity_id) {
-                    // Find the CoreEntityId for this entity
-                    if let Ok((_, _, _, _, _, _, _, _, Some(core_id))) = units.get(entity) {
-                        if let Some(ref mut cmds) = core_commands {
-                            let target = Vec2Fixed::new(
-                                Fixed::from_num(target_x),
-                                Fixed::from_num(target_y),
-                            );
-                            cmds.set(core_id.0, CoreCommand::MoveTo(target));
-                            responses.send(Response::ack(cmd_name));
-                        } else {
-                            responses.send(Response::error(
-                                "Core simulation not available",
-                                Some(cmd_name),
-                            ));
-                        }
+                let order = CoreCommand::MoveTo(world_pos(target_x, target_y));
+                let result = core_id(entity_id, &entity_map, &units).and_then(|id| {
+                    issue_order(
+                        id,
+                        order,
+                        queue,
+                        core_sim.as_deref(),
+                        core_commands.as_deref_mut(),
+                    )
+                });
+                responses.send(ack_or_error(result, cmd_name));
+            }
+
+            Command::Attack {
+                entity_id,
+                target_id,
+                queue,
+                force,
+            } => {
+                let result = core_id(entity_id, &entity_map, &units).and_then(|id| {
+                    let target = core_id(target_id, &entity_map, &units)
+                        .map_err(|_| format!("Target {} not found", target_id))?;
+                    let order = if force {
+                        CoreCommand::ForceAttack(target)
                     } else {
-                        responses.send(Response::error(
-                            format!("Entity {} not registered with core", entity_id),
-                            Some(cmd_name),
-                        ));
-                    }
+                        CoreCommand::Attack(target)
+                    };
+                    issue_order(
+                        id,
+                        order,
+                        queue,
+                        core_sim.as_deref(),
+                        core_commands.as_deref_mut(),
+                    )
+                });
+                responses.send(ack_or_error(result, cmd_name));
+            }
+
+            Command::AttackMove {
+                entity_id,
+                target_x,
+                target_y,
+                queue,
+            } => {
+                let order = CoreCommand::AttackMove(world_pos(target_x, target_y));
+                let result = core_id(entity_id, &entity_map, &units).and_then(|id| {
+                    issue_order(
+                        id,
+                        order,
+                        queue,
+                        core_sim.as_deref(),
+                        core_commands.as_deref_mut(),
+                    )
+                });
+                responses.send(ack_or_error(result, cmd_name));
+            }
+
+            Command::Patrol {
+                entity_id,
+                target_x,
+                target_y,
+                queue,
+            } => {
+                let order = CoreCommand::Patrol(world_pos(target_x, target_y));
+                let result = core_id(entity_id, &entity_map, &units).and_then(|id| {
+                    issue_order(
+                        id,
+                        order,
+                        queue,
+                        core_sim.as_deref(),
+                        core_commands.as_deref_mut(),
+                    )
+                });
+                responses.send(ack_or_error(result, cmd_name));
+            }
+
+            Command::AttackGround {
+                entity_id,
+                target_x,
+                target_y,
+            } => {
+                let order = CoreCommand::AttackGround(world_pos(target_x, target_y));
+                let result = core_id(entity_id, &entity_map, &units).and_then(|id| {
+                    issue_order(
+                        id,
+                        order,
+                        false,
+                        core_sim.as_deref(),
+                        core_commands.as_deref_mut(),
+                    )
+                });
+                responses.send(ack_or_error(result, cmd_name));
+            }
+
+            Command::Stop { entity_id } | Command::HoldPosition { entity_id } => {
+                let order = if matches!(cmd, Command::Stop { .. }) {
+                    CoreCommand::Stop
                 } else {
-                    responses.send(Response::error(
-                        format!("Entity {} not found", entity_id),
-                        Some(cmd_name),
-                    ));
-                }
+                    CoreCommand::HoldPosition
+                };
+                let result = core_id(entity_id, &entity_map, &units).and_then(|id| {
+                    issue_order(
+                        id,
+                        order,
+                        false,
+                        core_sim.as_deref(),
+                        core_commands.as_deref_mut(),
+                    )
+                });
+                responses.send(ack_or_error(result, cmd_name));
             }
 
-            Command::Attack {
+            Command::SetRallyPoint { building_id, x, y } => {
+                let order = CoreCommand::SetRallyPoint(world_pos(x, y));
+                let result = core_id(building_id, &entity_map, &units).and_then(|id| {
+                    issue_order(
+                        id,
+                        order,
+                        false,
+                        core_sim.as_deref(),
+                        core_commands.as_deref_mut(),
+                    )
+                });
+                responses.send(ack_or_error(result, cmd_name));
+            }
+
+            Command::Follow {
+                entity_id,
+                target_id,
+            }
+            | Command::Guard {
                 entity_id,
                 target_id,
             } => {
-                if let (Some(entity), Some(target_entity)) =
-                    (entity_map.lookup(entity_id), entity_map.lookup(target_id))
-                {
-                    // Get core IDs for both entities
-                    let attacker_core = units.get(entity).ok().and_then(|q| q.8.map(|c| c.0));
-                    let target_core = units.get(target_entity).ok().and_then(|q| q.8.map(|c| c.0));
-
-                    if let (Some(attacker_id), Some(target_id)) = (attacker_core, target_core) {
-                        if let Some(ref mut cmds) = core_commands {
-                            cmds.set(attacker_id, CoreCommand::Attack(target_id));
-                            responses.send(Response::ack(cmd_name));
-                        } else {
-                            responses.send(Response::error(
-                                "Core simulation not available",
-                                Some(cmd_name),
-                            ));
-                        }
+                let result = core_id(entity_id, &entity_map, &units).and_then(|id| {
+                    let target = core_id(target_id, &entity_map, &units)
+                        .map_err(|_| format!("Target {} not found", target_id))?;
+                    let order = if matches!(cmd, Command::Follow { .. }) {
+                        CoreCommand::Follow(target)
                     } else {
-                        responses.send(Response::error(
-                            "Entities not registered with core",
-                            Some(cmd_name),
-                        ));
+                        CoreCommand::Guard(target)
+                    };
+                    issue_order(
+                        id,
+                        order,
+                        false,
+                        core_sim.as_deref(),
+                        core_commands.as_deref_mut(),
+                    )
+                });
+                responses.send(ack_or_error(result, cmd_name));
+            }
+
+            Command::Train {
+                building_id,
+                unit_id,
+            } => {
+                let result = train_unit(
+                    building_id,
+                    &unit_id,
+                    &entity_map,
+                    &units,
+                    &mut economy,
+                    player_faction.as_deref(),
+                    player_resources.as_deref_mut(),
+                );
+                responses.send(ack_or_error(result, cmd_name));
+            }
+
+            Command::Build {
+                building_type,
+                x,
+                y,
+            } => {
+                let result = place_building(
+                    &building_type,
+                    Vec2::new(x as f32, y as f32),
+                    &economy,
+                    core_sim.as_deref(),
+                    player_faction.as_deref(),
+                    player_resources.as_deref_mut(),
+                );
+                match result {
+                    Ok((kind, faction)) => {
+                        let entity = rts_game::construction::spawn_building(
+                            &mut bevy_commands,
+                            kind,
+                            Vec2::new(x as f32, y as f32),
+                            faction,
+                        );
+                        responses.send(Response::Spawned {
+                            entity_id: entity_map.register(entity),
+                            unit_type: building_type,
+                        });
                     }
-                } else if entity_map.lookup(entity_id).is_none() {
-                    responses.send(Response::error(
-                        format!("Entity {} not found", entity_id),
-                        Some(cmd_name),
-                    ));
-                } else {
-                    responses.send(Response::error(
-                        format!("Target {} not found", target_id),
-                        Some(cmd_name),
-                    ));
+                    Err(e) => responses.send(Response::error(e, Some(cmd_name))),
                 }
             }
 
-            Command::Stop { entity_id } => {
-                if let Some(entity) = entity_map.lookup(entity_id) {
-                    if let Ok((_, _, _, _, _, _, _, _, Some(core_id))) = units.get(entity) {
-                        if let Some(ref mut cmds) = core_commands {
-                            cmds.set(core_id.0, CoreCommand::Stop);
-                            responses.send(Response::ack(cmd_name));
-                        } else {
-                            responses.send(Response::error(
-                                "Core simulation not available",
-                                Some(cmd_name),
-                            ));
-                        }
-                    } else {
-                        responses.send(Response::error(
-                            format!("Entity {} not registered with core", entity_id),
-                            Some(cmd_name),
-                        ));
+            Command::Research { tech_id } => {
+                let result = match (
+                    core_sim.as_ref(),
+                    player_faction.as_deref(),
+                    player_resources.as_deref(),
+                ) {
+                    (Some(core), Some(player), Some(res)) => {
+                        research_facility(&core.sim, player.faction, &tech_id).and_then(
+                            |building| {
+                                let cost = core
+                                    .sim
+                                    .validate_research(building, &tech_id)
+                                    .map_or(0, |tech| tech.cost);
+                                if res.feedstock < cost {
+                                    return Err(format!("Cannot afford {}", tech_id));
+                                }
+                                // Charged when the research plugin queues it
+                                economy.research.queue(building, tech_id.clone());
+                                Ok(())
+                            },
+                        )
+                    }
+                    _ => Err("Player resources not available".to_string()),
+                };
+                responses.send(ack_or_error(result, cmd_name));
+            }
+
+            Command::CancelResearch => {
+                let building = core_sim
+                    .as_ref()
+                    .zip(player_faction.as_deref())
+                    .and_then(|(core, player)| researching_facility(&core.sim, player.faction));
+                match building {
+                    Some(building) => {
+                        // Refunded when the research plugin cancels it
+                        economy.research.cancel(building);
+                        responses.send(Response::ack(cmd_name));
+                    }
+                    None => {
+                        responses.send(Response::error("No research in progress", Some(cmd_name)))
                     }
-                } else {
-                    responses.send(Response::error(
-                        format!("Entity {} not found", entity_id),
-                        Some(cmd_name),
-                    ));
                 }
             }
 
+            Command::Trade { from, to, amount } => {
+                let result = trade(
+                    from,
+                    to,
+                    amount,
+                    core_sim.as_deref(),
+                    &mut economy,
+                    player_faction.as_deref(),
+                    player_resources.as_deref_mut(),
+                );
+                responses.send(ack_or_error(result, cmd_name));
+            }
+
+            Command::Cancel { entity_id } => {
+                let result = cancel(
+                    entity_id,
+                    &mut entity_map,
+                    &mut bevy_commands,
+                    &mut economy,
+                    &units,
+                    player_faction.as_deref(),
+                    player_resources.as_deref_mut(),
+                );
+                responses.send(ack_or_error(result, cmd_name));
+            }
+
             Command::Teleport { entity_id, x, y } => {
                 if let Some(entity) = entity_map.lookup(entity_id) {
                     // Update the GamePosition component directly
-                    let target = Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y));
+                    let target = world_pos(x, y);
                     bevy_commands
                         .entity(entity)
                         .insert(rts_game::components::GamePosition::new(target));
@@ -440,10 +790,18 @@ fn process_commands(
                 }
             }
 
-            Command::Speed { multiplier: _ } => {
-                // Speed control would need Time resource modification
-                // For now, just acknowledge
-                responses.send(Response::ack(cmd_name));
+            Command::Speed { multiplier } => {
+                if let Some(speed) = rewind.speed.as_mut() {
+                    speed.set_multiplier(multiplier as f32);
+                    speed.paused = false;
+                    rewind.rewind.step_until = None;
+                    responses.send(Response::ack(cmd_name));
+                } else {
+                    responses.send(Response::error(
+                        "Simulation not initialized",
+                        Some(cmd_name),
+                    ));
+                }
             }
 
             Command::SpawnBuilding {
@@ -460,30 +818,14 @@ fn process_commands(
 
                 let pos = Vec2::new(x as f32, y as f32);
 
-                // Spawn appropriate building bundle based on type
-                let entity = match building_type.as_str() {
-                    "command_center" | "depot" => bevy_commands
-                        .spawn(rts_game::bundles::DepotBundle::new(pos, faction_id))
-                        .id(),
-                    "barracks" => bevy_commands
-                        .spawn(rts_game::bundles::BarracksBundle::new(pos, faction_id))
-                        .id(),
-                    "supply_depot" | "supply" => bevy_commands
-                        .spawn(rts_game::bundles::SupplyDepotBundle::new(pos, faction_id))
-                        .id(),
-                    "tech_lab" | "techlab" => bevy_commands
-                        .spawn(rts_game::bundles::TechLabBundle::new(pos, faction_id))
-                        .id(),
-                    "turret" | "defense" => bevy_commands
-                        .spawn(rts_game::bundles::TurretBundle::new(pos, faction_id))
-                        .id(),
-                    _ => {
-                        // Default to depot for unknown building types
-                        bevy_commands
-                            .spawn(rts_game::bundles::DepotBundle::new(pos, faction_id))
-                            .id()
-                    }
-                };
+                // Default to depot for unknown building types
+                let kind = parse_building_type(&building_type).unwrap_or(BuildingType::Depot);
+                let entity = rts_game::construction::spawn_building(
+                    &mut bevy_commands,
+                    kind,
+                    pos,
+                    faction_id,
+                );
 
                 let external_id = entity_map.register(entity);
                 responses.send(Response::Spawned {
@@ -546,63 +888,471 @@ fn process_commands(
     }
 }
 
-/// Build state response from current game state.
-#[allow(clippy::type_complexity)]
-fn build_state_response(
-    tick: u64,
-    units: &Query<(
-        Entity,
-        &rts_game::components::GamePosition,
-        &rts_game::components::GameFaction,
-        Option<&rts_game::components::GameHealth>,
-        Option<&rts_game::components::GameHarvester>,
-        Option<&rts_game::components::AttackTarget>,
-        Option<&rts_game::components::GameUnitKind>,
-        Option<&rts_game::components::Building>,
-        Option<&rts_game::components::CoreEntityId>,
-    )>,
+/// The local player's research facilities, in ID order.
+fn research_facilities(
+    sim: &Simulation,
+    faction: FactionId,
+) -> impl Iterator<Item = EntityId> + '_ {
+    sim.entities().sorted_ids().into_iter().filter(move |&id| {
+        sim.get_entity(id).is_some_and(|e| {
+            e.research.is_some() && e.faction.is_some_and(|f| f.faction == faction)
+        })
+    })
+}
+
+/// Pick the facility to research `tech_id` at, or explain why none can.
+fn research_facility(
+    sim: &Simulation,
+    faction: FactionId,
+    tech_id: &str,
+) -> Result<EntityId, String> {
+    if let Some(building) = sim.research_facility_for(faction, tech_id) {
+        return Ok(building);
+    }
+    let reason = research_facilities(sim, faction)
+        .find_map(|id| sim.validate_research(id, tech_id).err())
+        .map_or_else(|| "no research facility".to_string(), |e| e.to_string());
+    Err(format!("Cannot research {}: {}", tech_id, reason))
+}
+
+/// The first facility with research queued.
+fn researching_facility(sim: &Simulation, faction: FactionId) -> Option<EntityId> {
+    research_facilities(sim, faction).find(|&id| {
+        sim.get_entity(id)
+            .and_then(|e| e.row.research.as_ref())
+            .is_some_and(|r| r.current().is_some())
+    })
+}
+
+/// Technology in progress at the first busy facility.
+fn current_research(sim: &Simulation, faction: FactionId) -> Option<String> {
+    let building = researching_facility(sim, faction)?;
+    let research = sim.get_entity(building)?.row.research.as_ref()?;
+    research.current().map(|item| item.tech_id.clone())
+}
+
+/// Faction data, production queues and research used by economy commands.
+#[derive(SystemParam)]
+struct EconomyAccess<'w, 's> {
+    registry: Option<Res<'w, rts_game::data_loader::FactionRegistry>>,
+    buildings: Option<Res<'w, rts_game::components::FactionBuildings>>,
+    research: ResMut<'w, rts_game::research::ResearchRequests>,
+    queues: Query<'w, 's, &'static mut rts_game::components::GameProductionQueue>,
+    constructing: Query<'w, 's, &'static UnderConstruction>,
+    market: Local<'s, Market>,
+}
+
+/// Snapshots, game speed and what is needed to respawn entities on a
+/// restore.
+#[derive(SystemParam)]
+struct RewindAccess<'w, 's> {
+    rewind: ResMut<'w, Rewind>,
+    speed: Option<ResMut<'w, rts_game::simulation::GameSpeed>>,
+    unit_kinds: Option<Res<'w, rts_game::data_loader::BevyUnitKindRegistry>>,
+    mirrors: Query<'w, 's, (Entity, &'static CoreEntityId)>,
+    saved: Query<'w, 's, SavedEntityQuery<'static>>,
+}
+
+/// Capture the game, answering `snapshot`.
+fn take_snapshot(
+    core: &rts_game::simulation::CoreSimulation,
+    core_commands: &rts_game::simulation::CoreCommandBuffer,
+    resources: Option<&rts_game::economy::PlayerResources>,
     entity_map: &EntityIdMap,
-    feedstock: u32,
-    game_state: Option<&Res<rts_game::victory::GameState>>,
-    hash: u64,
-) -> Response {
-    let mut entities = Vec::new();
+    rewind: &mut RewindAccess,
+) -> Result<Response, String> {
+    let save = capture_client_save("headless", core, core_commands, resources, &rewind.saved)
+        .map_err(|e| e.to_string())?;
+    let mut ids: Vec<(u32, EntityId)> = rewind
+        .mirrors
+        .iter()
+        .filter_map(|(entity, core_id)| Some((entity_map.lookup_external(entity)?, core_id.0)))
+        .collect();
+    ids.sort_unstable();
+
+    let id = rewind.rewind.snapshots.len() as u32;
+    let tick = save.tick;
+    rewind.rewind.snapshots.push(Snapshot { save, ids });
+    Ok(Response::Snapshot { id, tick })
+}
+
+/// Rewind the game to snapshot `id`, answering `restore`.
+///
+/// Every mirrored entity is despawned and the snapshot's entities
+/// respawned in their place, keeping the external IDs they had when the
+/// snapshot was taken. The game is left paused.
+#[allow(clippy::too_many_arguments)]
+fn restore_snapshot(
+    id: u32,
+    bevy_commands: &mut Commands,
+    core: &mut rts_game::simulation::CoreSimulation,
+    core_commands: &mut rts_game::simulation::CoreCommandBuffer,
+    resources: Option<&mut rts_game::economy::PlayerResources>,
+    entity_map: &mut EntityIdMap,
+    registry: Option<&rts_game::data_loader::FactionRegistry>,
+    rewind: &mut RewindAccess,
+) -> Result<Response, String> {
+    let snapshot = rewind
+        .rewind
+        .snapshots
+        .get(id as usize)
+        .ok_or_else(|| format!("No snapshot {}", id))?;
+    let (Some(registry), Some(unit_kinds)) = (registry, rewind.unit_kinds.as_deref()) else {
+        return Err("Faction data not loaded".to_string());
+    };
+
+    // The old mirrors are unmapped by the swap, so despawning them leaves
+    // the restored simulation alone.
+    let mirrors: Vec<Entity> = rewind.mirrors.iter().map(|(entity, _)| entity).collect();
+    let respawned = resume_client_save(
+        bevy_commands,
+        &snapshot.save,
+        core,
+        core_commands,
+        resources,
+        registry,
+        unit_kinds,
+    )
+    .map_err(|e| e.to_string())?;
+    for entity in mirrors {
+        bevy_commands.entity(entity).despawn_recursive();
+        entity_map.remove(entity);
+    }
+
+    let by_core: HashMap<EntityId, Entity> = respawned.into_iter().collect();
+    for &(external_id, core_id) in &snapshot.ids {
+        if let Some(&entity) = by_core.get(&core_id) {
+            entity_map.bind(external_id, entity);
+        }
+    }
+
+    if let Some(speed) = rewind.speed.as_mut() {
+        speed.pause();
+    }
+    rewind.rewind.step_until = None;
+    Ok(Response::Restored {
+        id,
+        tick: core.sim.get_tick(),
+    })
+}
+
+/// Convert protocol coordinates to a simulation position.
+fn world_pos(x: f64, y: f64) -> Vec2Fixed {
+    Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y))
+}
+
+/// Acknowledge a command, or report why it failed.
+fn ack_or_error(result: Result<(), String>, cmd_name: &str) -> Response {
+    match result {
+        Ok(()) => Response::ack(cmd_name),
+        Err(e) => Response::error(e, Some(cmd_name)),
+    }
+}
+
+/// Sell `amount` of `from` for `to` at the local player's market.
+fn trade(
+    from: ResourceKind,
+    to: ResourceKind,
+    amount: u32,
+    core: Option<&rts_game::simulation::CoreSimulation>,
+    economy: &mut EconomyAccess,
+    player: Option<&rts_game::components::PlayerFaction>,
+    resources: Option<&mut rts_game::economy::PlayerResources>,
+) -> Result<(), String> {
+    let (Some(core), Some(player), Some(resources)) = (core, player, resources) else {
+        return Err("Player resources not available".to_string());
+    };
+    if !core.sim.owns_market(player.faction) {
+        return Err(MarketError::NoMarket.to_string());
+    }
+    let rules = economy
+        .registry
+        .as_ref()
+        .and_then(|r| r.get(player.faction))
+        .map(|data| data.market.clone())
+        .unwrap_or_default();
+    let amount = i32::try_from(amount).map_err(|_| MarketError::Insufficient(from).to_string())?;
+    let mut stock = resources.economy();
+    economy
+        .market
+        .trade(&rules, core.sim.get_tick(), from, to, amount, &mut stock)
+        .map_err(|e| e.to_string())?;
+    resources.feedstock = stock.feedstock;
+    resources.reserves = stock.reserves;
+    Ok(())
+}
+
+/// Parse a building type name, accepting the aliases `spawn_building` has
+/// always taken.
+fn parse_building_type(name: &str) -> Option<BuildingType> {
+    match name {
+        "command_center" | "depot" => Some(BuildingType::Depot),
+        "barracks" => Some(BuildingType::Barracks),
+        "supply_depot" | "supply" => Some(BuildingType::SupplyDepot),
+        "tech_lab" | "techlab" => Some(BuildingType::TechLab),
+        "turret" | "defense" => Some(BuildingType::Turret),
+        _ => None,
+    }
+}
 
-    for (entity, pos, faction, health, harvester, attack_target, unit_kind, building, _core_id) in
-        units.iter()
+/// Core simulation ID of a registered entity.
+fn core_id(
+    entity_id: u32,
+    entity_map: &EntityIdMap,
+    units: &UnitQuery,
+) -> Result<EntityId, String> {
+    let entity = entity_map
+        .lookup(entity_id)
+        .ok_or_else(|| format!("Entity {} not found", entity_id))?;
+    units
+        .get(entity)
+        .ok()
+        .and_then(|item| item.8.map(|c| c.0))
+        .ok_or_else(|| format!("Entity {} not registered with core", entity_id))
+}
+
+/// Check an order against the core simulation and buffer it, replacing or
+/// queueing behind the entity's current orders.
+fn issue_order(
+    entity: EntityId,
+    order: CoreCommand,
+    queue: bool,
+    core_sim: Option<&rts_game::simulation::CoreSimulation>,
+    core_commands: Option<&mut rts_game::simulation::CoreCommandBuffer>,
+) -> Result<(), String> {
+    let (Some(core), Some(cmds)) = (core_sim, core_commands) else {
+        return Err("Core simulation not available".to_string());
+    };
+    core.sim
+        .validate_command(entity, &order)
+        .map_err(|e| e.to_string())?;
+    if queue {
+        cmds.queue(entity, order);
+    } else {
+        cmds.set(entity, order);
+    }
+    Ok(())
+}
+
+/// Queue a unit in one of the local player's production buildings, paying
+/// its cost and supply up front as the production panel does.
+fn train_unit(
+    building_id: u32,
+    unit_id: &str,
+    entity_map: &EntityIdMap,
+    units: &UnitQuery,
+    economy: &mut EconomyAccess,
+    player: Option<&rts_game::components::PlayerFaction>,
+    resources: Option<&mut rts_game::economy::PlayerResources>,
+) -> Result<(), String> {
+    let (Some(player), Some(resources)) = (player, resources) else {
+        return Err("Player resources not available".to_string());
+    };
+    let entity = entity_map
+        .lookup(building_id)
+        .ok_or_else(|| format!("Entity {} not found", building_id))?;
+    if units
+        .get(entity)
+        .map_or(true, |item| item.2.faction != player.faction)
+    {
+        return Err(format!("Building {} is not yours", building_id));
+    }
+    if economy.constructing.contains(entity) {
+        return Err(format!("Building {} is under construction", building_id));
+    }
+    let unit = economy
+        .registry
+        .as_ref()
+        .and_then(|r| r.get(player.faction))
+        .and_then(|data| data.get_unit(unit_id))
+        .ok_or_else(|| format!("Unknown unit {}", unit_id))?;
+    let mut production = economy
+        .queues
+        .get_mut(entity)
+        .map_err(|_| format!("Building {} cannot produce units", building_id))?;
+
+    let cost = unit.cost as i32;
+    let supply = unit_supply(unit);
+    if resources.feedstock < cost {
+        return Err(format!("Cannot afford {}", unit_id));
+    }
+    if !resources.has_supply(supply) {
+        return Err(ProductionError::SupplyBlocked.to_string());
+    }
+    if !production.can_queue() {
+        return Err(format!("Production queue of {} is full", building_id));
+    }
+    resources.feedstock -= cost;
+    resources.supply_used += supply;
+    production.enqueue(unit_id.to_string());
+    Ok(())
+}
+
+/// Check and pay for a building placement by the local player.
+fn place_building(
+    name: &str,
+    pos: Vec2,
+    economy: &EconomyAccess,
+    core_sim: Option<&rts_game::simulation::CoreSimulation>,
+    player: Option<&rts_game::components::PlayerFaction>,
+    resources: Option<&mut rts_game::economy::PlayerResources>,
+) -> Result<(BuildingType, FactionId), String> {
+    let (Some(player), Some(resources)) = (player, resources) else {
+        return Err("Player resources not available".to_string());
+    };
+    let kind = parse_building_type(name).ok_or_else(|| format!("Unknown building {}", name))?;
+    if let Some(buildings) = &economy.buildings {
+        if !buildings.can_build(player.faction, kind) {
+            return Err(format!("{} requires another building first", kind.name()));
+        }
+    }
+    if let Some(core) = core_sim {
+        let grid = core.sim.nav_grid();
+        let cell = grid.world_to_grid(world_pos(f64::from(pos.x), f64::from(pos.y)));
+        if !cell.map_or(true, |(x, y)| grid.is_walkable(x, y)) {
+            return Err(format!("Cannot build {} on blocked terrain", kind.name()));
+        }
+    }
+    if resources.feedstock < kind.cost() {
+        return Err(format!("Cannot afford {}", kind.name()));
+    }
+    resources.feedstock -= kind.cost();
+    Ok((kind, player.faction))
+}
+
+/// Cancel a building under construction, refunding three quarters of its
+/// cost, or the last unit queued in a production building, refunding as the
+/// production panel does.
+fn cancel(
+    entity_id: u32,
+    entity_map: &mut EntityIdMap,
+    bevy_commands: &mut Commands,
+    economy: &mut EconomyAccess,
+    units: &UnitQuery,
+    player: Option<&rts_game::components::PlayerFaction>,
+    resources: Option<&mut rts_game::economy::PlayerResources>,
+) -> Result<(), String> {
+    let (Some(player), Some(resources)) = (player, resources) else {
+        return Err("Player resources not available".to_string());
+    };
+    let entity = entity_map
+        .lookup(entity_id)
+        .ok_or_else(|| format!("Entity {} not found", entity_id))?;
+    if units
+        .get(entity)
+        .map_or(true, |item| item.2.faction != player.faction)
     {
-        let external_id = entity_map.lookup_external(entity).unwrap_or(0);
+        return Err(format!("Building {} is not yours", entity_id));
+    }
+
+    if let Ok(construction) = economy.constructing.get(entity) {
+        resources.feedstock += construction.building_type.cost() * 3 / 4;
+        bevy_commands.entity(entity).despawn_recursive();
+        entity_map.remove(entity);
+        return Ok(());
+    }
+
+    let mut production = economy
+        .queues
+        .get_mut(entity)
+        .map_err(|_| format!("Nothing to cancel at {}", entity_id))?;
+    let last = production
+        .queue
+        .len()
+        .checked_sub(1)
+        .ok_or_else(|| format!("Nothing to cancel at {}", entity_id))?;
+    let faction_data = economy
+        .registry
+        .as_ref()
+        .and_then(|r| r.get(player.faction));
+    cancel_production(&mut production, last, faction_data, resources);
+    Ok(())
+}
 
-        let entity_type = if building.is_some() {
-            EntityType::Building {
-                kind: "unknown".to_string(),
+/// System to tell the controller about the local player's finished research.
+fn advance_research(
+    mut updates: EventReader<rts_game::research::ResearchUpdate>,
+    mut responses: ResMut<ResponseQueue>,
+    player_faction: Option<Res<rts_game::components::PlayerFaction>>,
+    core_sim: Option<Res<rts_game::simulation::CoreSimulation>>,
+) {
+    let tick = core_sim.map_or(0, |c| c.sim.get_tick());
+    for update in updates.read() {
+        if let ResearchEvent::ResearchComplete {
+            faction, tech_id, ..
+        } = &update.0
+        {
+            if player_faction
+                .as_ref()
+                .is_some_and(|p| p.faction == *faction)
+            {
+                responses.send(Response::ResearchComplete {
+                    tech_id: tech_id.clone(),
+                    tick,
+                });
             }
-        } else {
-            EntityType::Unit {
-                kind: unit_kind.map_or("unknown".to_string(), |_| "unit".to_string()),
+        }
+    }
+}
+
+/// Forward the local player's notifications, other than research, as
+/// alerts.
+fn report_alerts(
+    mut notifications: EventReader<rts_game::notifications::GameNotification>,
+    mut responses: ResMut<ResponseQueue>,
+    mut entity_map: ResMut<EntityIdMap>,
+    player_faction: Option<Res<rts_game::components::PlayerFaction>>,
+    core_sim: Option<Res<rts_game::simulation::CoreSimulation>>,
+    core_ids: Query<(Entity, &rts_game::components::CoreEntityId)>,
+) {
+    let tick = core_sim.map_or(0, |c| c.sim.get_tick());
+    for rts_game::notifications::GameNotification(notification) in notifications.read() {
+        if player_faction
+            .as_ref()
+            .map_or(true, |p| p.faction != notification.faction())
+        {
+            continue;
+        }
+        let (alert, core_id, reason) = match notification {
+            Notification::HarvesterIdle { entity, .. } => ("harvester_idle", *entity, None),
+            Notification::ProductionBlocked {
+                building, reason, ..
+            } => {
+                let reason = match reason {
+                    BlockReason::Resources(kind) => kind.name().to_string(),
+                    BlockReason::Supply => "supply".to_string(),
+                };
+                ("production_blocked", *building, Some(reason))
             }
+            Notification::UnderAttack { entity, .. } => ("under_attack", *entity, None),
+            Notification::ResearchComplete { .. } => continue,
         };
-
-        let health_state = health.map(|h| HealthState {
-            current: h.current,
-            max: h.max,
-        });
-
-        let cargo = harvester.map(|h| h.current_load as u32);
-        let target = attack_target.and_then(|at| entity_map.lookup_external(at.target));
-
-        entities.push(EntityState {
-            id: external_id,
-            entity_type,
-            x: pos.value.x.to_num::<f64>(),
-            y: pos.value.y.to_num::<f64>(),
-            faction: faction.faction as u8,
-            health: health_state,
-            cargo,
-            target,
-            state: None,
+        let entity_id = core_ids
+            .iter()
+            .find(|(_, id)| id.0 == core_id)
+            .map(|(entity, _)| entity_map.register(entity));
+        responses.send(Response::Alert {
+            tick,
+            alert: alert.to_string(),
+            entity_id,
+            reason,
         });
     }
+}
+
+/// Build state response from current game state.
+fn build_state_response(
+    tick: u64,
+    units: &UnitQuery,
+    entity_map: &EntityIdMap,
+    feedstock: u32,
+    game_state: Option<&Res<rts_game::victory::GameState>>,
+    hash: u64,
+) -> Response {
+    let entities = units
+        .iter()
+        .map(|item| entity_state(item, entity_map))
+        .collect();
 
     let game_status = match game_state {
         Some(gs) => match **gs {
@@ -622,6 +1372,95 @@ fn build_state_response(
     }
 }
 
+/// Describe one entity for the controller.
+fn entity_state(
+    (
+        entity,
+        pos,
+        faction,
+        health,
+        harvester,
+        attack_target,
+        unit_kind,
+        building,
+        _core_id,
+        debug_name,
+    ): ROQueryItem<'_, UnitData>,
+    entity_map: &EntityIdMap,
+) -> EntityState {
+    let external_id = entity_map.lookup_external(entity).unwrap_or(0);
+
+    let entity_type = if building.is_some() {
+        EntityType::Building {
+            kind: "unknown".to_string(),
+        }
+    } else {
+        EntityType::Unit {
+            kind: unit_kind.map_or("unknown".to_string(), |_| "unit".to_string()),
+        }
+    };
+
+    let health_state = health.map(|h| HealthState {
+        current: h.current,
+        max: h.max,
+    });
+
+    let cargo = harvester.map(|h| h.current_load as u32);
+    let target = attack_target.and_then(|at| entity_map.lookup_external(at.target));
+
+    EntityState {
+        id: external_id,
+        entity_type,
+        x: pos.value.x.to_num::<f64>(),
+        y: pos.value.y.to_num::<f64>(),
+        faction: faction.faction as u8,
+        health: health_state,
+        cargo,
+        target,
+        state: None,
+        name: debug_name.map(|n| n.0.clone()),
+    }
+}
+
+/// Summarize one faction's economy and forces.
+fn player_state(
+    faction: FactionId,
+    units: &UnitQuery,
+    entity_map: &EntityIdMap,
+    resources: Option<&rts_game::economy::PlayerResources>,
+) -> PlayerState {
+    let mut state = PlayerState {
+        faction: faction as u8,
+        resources: resources.map(|r| PlayerResourceState {
+            feedstock: r.feedstock.max(0) as u32,
+            feedstock_cap: r.feedstock_cap.max(0) as u32,
+            supply_used: r.supply_used.max(0) as u32,
+            supply_cap: r.economy().supply_cap(),
+            reserves: r.reserves.clone(),
+        }),
+        ..Default::default()
+    };
+    for (entity, _, owner, _, harvester, _, _, building, _, _) in units.iter() {
+        if owner.faction != faction {
+            continue;
+        }
+        if building.is_some() {
+            state.buildings += 1;
+        } else {
+            state.units += 1;
+        }
+        if let Some(harvester) = harvester {
+            state.harvesters += 1;
+            state.cargo += harvester.current_load.max(0) as u32;
+        }
+        if let Some(id) = entity_map.lookup_external(entity) {
+            state.entity_ids.push(id);
+        }
+    }
+    state.entity_ids.sort_unstable();
+    state
+}
+
 /// System to flush response queue to stdout.
 fn flush_responses(mut responses: ResMut<ResponseQueue>) {
     for response in responses.responses.drain(..) {
@@ -641,4 +1480,140 @@ mod tests {
         // Can't easily create Entity in unit test, but we can test the structure
         assert_eq!(map.next_id, 0);
     }
+
+    #[test]
+    fn test_research_facility_explains_refusals() {
+        use rts_core::components::FactionMember;
+        use rts_core::data::TechData;
+        use rts_core::research::ResearchFacility;
+        use rts_core::simulation::EntitySpawnParams;
+
+        let faction = FactionId::Continuity;
+        let mut sim = Simulation::new();
+        sim.set_tech_tree(
+            faction,
+            [TechData {
+                id: "armor".to_string(),
+                name: "armor".to_string(),
+                description: String::new(),
+                cost: 100,
+                research_time: 50,
+                effects: Vec::new(),
+                prerequisites: vec!["basic".to_string()],
+                tier: 1,
+                exclusive_with: Vec::new(),
+                researched_at: None,
+                is_doctrine: false,
+                branch: None,
+                icon: None,
+            }],
+        );
+        assert_eq!(
+            research_facility(&sim, faction, "armor"),
+            Err("Cannot research armor: no research facility".to_string())
+        );
+
+        sim.spawn_entity(EntitySpawnParams {
+            faction: Some(FactionMember::new(faction, 0)),
+            research: Some(ResearchFacility::new(None)),
+            ..Default::default()
+        });
+        assert_eq!(
+            research_facility(&sim, faction, "armor"),
+            Err("Cannot research armor: Research failed: Prerequisites not met".to_string())
+        );
+        assert_eq!(current_research(&sim, faction), None);
+    }
+
+    #[test]
+    fn test_parse_building_type() {
+        assert_eq!(
+            parse_building_type("barracks"),
+            Some(BuildingType::Barracks)
+        );
+        assert_eq!(parse_building_type("techlab"), Some(BuildingType::TechLab));
+        assert_eq!(
+            parse_building_type("command_center"),
+            Some(BuildingType::Depot)
+        );
+        assert_eq!(parse_building_type("castle"), None);
+    }
+
+    #[test]
+    fn test_step_snapshot_and_restore() {
+        use rts_game::bundles::UnitBundle;
+        use rts_game::simulation::{CoreSimulation, GameSpeed, SimulationPlugin};
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, SimulationPlugin))
+            .init_resource::<rts_game::data_loader::FactionRegistry>()
+            .init_resource::<rts_game::data_loader::BevyUnitKindRegistry>()
+            .init_resource::<rts_game::research::ResearchRequests>()
+            .init_resource::<rts_game::economy::PlayerResources>()
+            .init_resource::<EntityIdMap>()
+            .init_resource::<CommandQueue>()
+            .init_resource::<ResponseQueue>()
+            .init_resource::<Rewind>()
+            .add_systems(Last, process_commands);
+        app.world_mut().resource_mut::<GameSpeed>().pause();
+        app.world_mut().spawn(UnitBundle::new(
+            Vec2::new(40.0, 10.0),
+            FactionId::Collegium,
+            80,
+        ));
+        app.update();
+
+        let send = |app: &mut App, cmd: Command| {
+            app.world_mut()
+                .resource_mut::<CommandQueue>()
+                .commands
+                .push(cmd);
+            app.update();
+            std::mem::take(&mut app.world_mut().resource_mut::<ResponseQueue>().responses)
+        };
+        let core = |app: &App| {
+            let sim = &app.world().resource::<CoreSimulation>().sim;
+            (sim.get_tick(), sim.state_hash(), sim.entities().len())
+        };
+        let snapshotted = core(&app);
+        assert!(matches!(
+            send(&mut app, Command::Snapshot)[..],
+            [Response::Snapshot { id: 0, tick }] if tick == snapshotted.0
+        ));
+
+        // A step runs exactly its ticks, then reports the state
+        let responses = send(&mut app, Command::Step { count: 5 });
+        assert!(matches!(responses[..], [Response::Ack { .. }]));
+        app.update();
+        let responses =
+            std::mem::take(&mut app.world_mut().resource_mut::<ResponseQueue>().responses);
+        assert!(matches!(
+            responses[..],
+            [Response::State { tick, .. }] if tick == snapshotted.0 + 5
+        ));
+        assert_eq!(core(&app).0, snapshotted.0 + 5);
+
+        // Lose the unit, then rewind to before it died
+        let mut mirrors = app
+            .world_mut()
+            .query_filtered::<Entity, With<CoreEntityId>>();
+        let unit = mirrors.single(app.world());
+        app.world_mut().entity_mut(unit).despawn();
+        app.update();
+        assert_eq!(core(&app).2, 0);
+
+        let responses = send(&mut app, Command::Restore { id: 0 });
+        assert!(matches!(
+            responses[..],
+            [Response::Restored { id: 0, tick }] if tick == snapshotted.0
+        ));
+        app.update();
+        assert_eq!(core(&app), snapshotted);
+        assert_eq!(mirrors.iter(app.world()).count(), 1);
+
+        assert!(matches!(
+            send(&mut app, Command::Restore { id: 7 })[..],
+            [Response::Error { .. }]
+        ));
+    }
 }
@@ -5,9 +5,16 @@
 
 use std::path::Path;
 
+use rts_core::economy::ResourceKind;
+use rts_core::factions::FactionId;
+use rts_core::map_generation::{generate_map, Biome, GeneratedMap, MapConfig, MapPreset};
+use rts_core::triggers::{Trigger, TriggerAction};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::faction_loader::FactionRegistry;
+use crate::personality::AiPersonality;
+
 /// Error type for scenario operations.
 #[derive(Error, Debug)]
 pub enum ScenarioError {
@@ -20,6 +27,59 @@ pub enum ScenarioError {
     /// Failed to parse RON.
     #[error("Failed to parse scenario: {0}")]
     ParseError(#[from] ron::error::SpannedError),
+    /// A tuning override is outside its allowed range.
+    #[error("Tuning override {field} = {value} is outside {min}..={max}")]
+    TuningOutOfRange {
+        /// Override field name.
+        field: &'static str,
+        /// Requested value.
+        value: u32,
+        /// Smallest allowed value.
+        min: u32,
+        /// Largest allowed value.
+        max: u32,
+    },
+    /// An AI personality prefers a strategy that isn't a known preset.
+    #[error("Personality '{personality}' prefers unknown strategy '{strategy}'")]
+    UnknownStrategy {
+        /// Personality name.
+        personality: String,
+        /// Unknown strategy name.
+        strategy: String,
+    },
+    /// No faction data is loaded for a scenario faction.
+    #[error("No faction data loaded for '{0}'")]
+    MissingFactionData(String),
+    /// A faction setup names a starting preset its faction data lacks.
+    #[error("Faction '{faction}' has no starting preset '{preset}'")]
+    UnknownPreset {
+        /// Faction identifier.
+        faction: String,
+        /// Unknown preset name.
+        preset: String,
+    },
+    /// A starting unit kind matches no unit ID or role in the faction data.
+    #[error("Faction '{faction}' has no unit kind '{kind}'")]
+    UnknownUnitKind {
+        /// Faction identifier.
+        faction: String,
+        /// Unknown unit kind.
+        kind: String,
+    },
+    /// A starting building kind matches no building in the faction data.
+    #[error("Faction '{faction}' has no building kind '{kind}'")]
+    UnknownBuildingKind {
+        /// Faction identifier.
+        faction: String,
+        /// Unknown building kind.
+        kind: String,
+    },
+    /// A matchup needs two factions but the scenario has fewer.
+    #[error("Scenario '{0}' needs two factions for a matchup")]
+    TooFewFactions(String),
+    /// Both sides of a matchup are the same faction.
+    #[error("Mirror matchup {0:?} vs {0:?} is not supported")]
+    MirrorMatchup(FactionId),
 }
 
 /// Map size presets for procedural generation.
@@ -49,6 +109,22 @@ pub struct Scenario {
     pub victory_conditions: VictoryConditions,
     /// Initial resource setup.
     pub initial_resources: ResourceSetup,
+    /// Ammunition and resupply rules. `None` means unlimited ammunition.
+    #[serde(default)]
+    pub logistics: Option<LogisticsConfig>,
+    /// Stat overrides for this scenario or map, applied over faction data.
+    #[serde(default)]
+    pub tuning: TuningOverrides,
+    /// Decorative idle wander for units without orders. `None` keeps idle
+    /// units still.
+    #[serde(default)]
+    pub idle_wander: Option<IdleWanderConfig>,
+    /// Scripted events such as timed waves, reinforcements and messages.
+    #[serde(default)]
+    pub triggers: Vec<Trigger>,
+    /// Generated terrain to play on. `None` plays on open ground.
+    #[serde(default)]
+    pub map: Option<MapConfig>,
 }
 
 impl Default for Scenario {
@@ -63,6 +139,11 @@ impl Default for Scenario {
             ],
             victory_conditions: VictoryConditions::default(),
             initial_resources: ResourceSetup::default(),
+            logistics: None,
+            tuning: TuningOverrides::default(),
+            idle_wander: None,
+            triggers: Vec::new(),
+            map: None,
         }
     }
 }
@@ -75,16 +156,113 @@ impl Scenario {
             return Err(ScenarioError::FileNotFound(path.display().to_string()));
         }
         let contents = std::fs::read_to_string(path)?;
-        let scenario: Scenario = ron::from_str(&contents)?;
-        Ok(scenario)
+        Self::from_ron_str(&contents)
     }
 
     /// Load from a RON string (useful for embedded scenarios).
     pub fn from_ron_str(ron: &str) -> Result<Self, ScenarioError> {
         let scenario: Scenario = ron::from_str(ron)?;
+        scenario.tuning.validate()?;
+        for personality in scenario
+            .factions
+            .iter()
+            .filter_map(|f| f.personality.as_ref())
+        {
+            personality.validate()?;
+        }
         Ok(scenario)
     }
 
+    /// Play `a` against `b`: the first faction setup becomes `a` and the
+    /// second `b`, keeping their spawns and starting forces.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `a` and `b` are the same faction, which the
+    /// simulation could not tell apart, or the scenario has fewer than two
+    /// factions.
+    pub fn set_matchup(&mut self, a: FactionId, b: FactionId) -> Result<(), ScenarioError> {
+        if a == b {
+            return Err(ScenarioError::MirrorMatchup(a));
+        }
+        let [first, second, ..] = self.factions.as_mut_slice() else {
+            return Err(ScenarioError::TooFewFactions(self.name.clone()));
+        };
+        first.faction_id = a.short_name().to_lowercase();
+        second.faction_id = b.short_name().to_lowercase();
+        Ok(())
+    }
+
+    /// Resolve every faction's start against loaded faction data.
+    ///
+    /// Factions with a [`preset`](FactionSetup::preset) get their starting
+    /// units and buildings replaced by that preset, placed relative to their
+    /// spawn position. Every starting kind must then exist in the faction
+    /// data; unit kinds may also name a role tag (e.g. "harvester").
+    pub fn resolve_starts(&mut self, registry: &FactionRegistry) -> Result<(), ScenarioError> {
+        for setup in &mut self.factions {
+            let faction = setup.faction();
+            let data = registry
+                .get(faction)
+                .ok_or_else(|| ScenarioError::MissingFactionData(setup.faction_id.clone()))?;
+
+            if let Some(name) = &setup.preset {
+                let preset =
+                    data.get_starting_preset(name)
+                        .ok_or_else(|| ScenarioError::UnknownPreset {
+                            faction: setup.faction_id.clone(),
+                            preset: name.clone(),
+                        })?;
+                let (x, y) = setup.spawn_position;
+                setup.starting_buildings = preset
+                    .starting_buildings
+                    .iter()
+                    .map(|b| BuildingPlacement::new(&b.type_id, x + b.offset_x, y + b.offset_y))
+                    .collect();
+                setup.starting_units = preset
+                    .starting_units
+                    .iter()
+                    .map(|u| UnitPlacement::new(&u.type_id, x + u.offset_x, y + u.offset_y, 1))
+                    .collect();
+            }
+
+            if let Some(unit) = setup.starting_units.iter().find(|u| {
+                registry.get_unit(faction, &u.kind).is_none()
+                    && registry.get_unit_by_role(faction, &u.kind).is_none()
+            }) {
+                return Err(ScenarioError::UnknownUnitKind {
+                    faction: setup.faction_id.clone(),
+                    kind: unit.kind.clone(),
+                });
+            }
+            if let Some(building) = setup
+                .starting_buildings
+                .iter()
+                .find(|b| data.get_building(&b.kind).is_none())
+            {
+                return Err(ScenarioError::UnknownBuildingKind {
+                    faction: setup.faction_id.clone(),
+                    kind: building.kind.clone(),
+                });
+            }
+        }
+
+        // Trigger waves must name units their faction can field
+        for action in self.triggers.iter().flat_map(|t| &t.actions) {
+            if let TriggerAction::SpawnUnits { faction, kind, .. } = action {
+                if registry.get_unit(*faction, kind).is_none()
+                    && registry.get_unit_by_role(*faction, kind).is_none()
+                {
+                    return Err(ScenarioError::UnknownUnitKind {
+                        faction: faction.short_name().to_lowercase(),
+                        kind: kind.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Create a standard 1v1 skirmish scenario.
     #[must_use]
     pub fn skirmish_1v1() -> Self {
@@ -103,6 +281,8 @@ impl Scenario {
                     starting_buildings: vec![BuildingPlacement::new("command_center", 48, 256)],
                     spawn_position: (48, 256),
                     starting_resources: 1000,
+                    personality: None,
+                    preset: Some("standard".to_string()),
                 },
                 FactionSetup {
                     faction_id: "collegium".to_string(),
@@ -114,6 +294,8 @@ impl Scenario {
                     starting_buildings: vec![BuildingPlacement::new("command_center", 464, 256)],
                     spawn_position: (464, 256),
                     starting_resources: 1000,
+                    personality: None,
+                    preset: Some("standard".to_string()),
                 },
             ],
             victory_conditions: VictoryConditions {
@@ -130,6 +312,11 @@ impl Scenario {
                     OreNode::new(256, 256, 10000), // Contested center
                 ],
             },
+            logistics: None,
+            tuning: TuningOverrides::default(),
+            idle_wander: None,
+            triggers: Vec::new(),
+            map: None,
         }
     }
 
@@ -138,16 +325,23 @@ impl Scenario {
     /// Uses `rts_core::map_generation` to create terrain, resources, and spawn points.
     #[must_use]
     pub fn from_procedural_map(seed: u64, map_size: MapSize) -> Self {
-        use rts_core::map_generation::{generate_map, MapConfig};
+        Self::from_map_preset(seed, map_size, MapPreset::Standard)
+    }
 
+    /// Create a scenario on a generated map of the given archetype, with
+    /// the biome picked from the seed.
+    #[must_use]
+    pub fn from_map_preset(seed: u64, map_size: MapSize, preset: MapPreset) -> Self {
         let config = match map_size {
             MapSize::Small => MapConfig::small(),
             MapSize::Medium => MapConfig::medium(),
             MapSize::Large => MapConfig::large(),
         }
-        .with_seed(seed);
+        .with_seed(seed)
+        .with_preset(preset)
+        .with_biome(Biome::from_seed(seed));
 
-        let generated = generate_map(config);
+        let generated = generate_map(config.clone());
         let world_w = generated.world_width();
         let world_h = generated.world_height();
 
@@ -173,6 +367,8 @@ impl Scenario {
                 starting_buildings: vec![BuildingPlacement::new("command_center", x, y)],
                 spawn_position: (x, y),
                 starting_resources: 1000,
+                personality: None,
+                preset: Some("standard".to_string()),
             });
         }
 
@@ -189,7 +385,13 @@ impl Scenario {
 
         Self {
             name: format!("Procedural Map (seed: {})", seed),
-            description: format!("{}x{} procedurally generated map", world_w, world_h),
+            description: format!(
+                "{}x{} procedurally generated {} map ({:?})",
+                world_w,
+                world_h,
+                preset.name(),
+                config.biome
+            ),
             map_size: (world_w, world_h),
             factions,
             victory_conditions: VictoryConditions {
@@ -198,15 +400,23 @@ impl Scenario {
                 resource_threshold: None,
             },
             initial_resources: ResourceSetup { ore_nodes },
+            logistics: None,
+            tuning: TuningOverrides::default(),
+            idle_wander: None,
+            triggers: Vec::new(),
+            map: Some(config),
         }
     }
 
-    /// Get the generated terrain data for this scenario (if using procedural map).
+    /// Get the generated terrain data for this scenario.
     ///
-    /// Returns None for non-procedural scenarios.
+    /// Scenarios with a [`map`](Self::map) give that map; others get
+    /// terrain generated from `seed` to fit their map size.
     #[must_use]
-    pub fn generate_terrain(&self, seed: u64) -> Option<rts_core::map_generation::GeneratedMap> {
-        use rts_core::map_generation::{generate_map, MapConfig};
+    pub fn generate_terrain(&self, seed: u64) -> Option<GeneratedMap> {
+        if let Some(map) = &self.map {
+            return Some(generate_map(map.clone()));
+        }
 
         let config = MapConfig {
             width: self.map_size.0 / 8,
@@ -235,9 +445,24 @@ pub struct FactionSetup {
     pub spawn_position: (i32, i32),
     /// Starting resources.
     pub starting_resources: i64,
+    /// Character layered over this faction's AI strategy.
+    #[serde(default)]
+    pub personality: Option<AiPersonality>,
+    /// Named starting preset from the faction's data (e.g. "standard",
+    /// "fast-expand"). When faction data is loaded it replaces
+    /// `starting_units` and `starting_buildings`, which remain the start
+    /// for runs with generic hardcoded units.
+    #[serde(default)]
+    pub preset: Option<String>,
 }
 
 impl FactionSetup {
+    /// Faction this setup plays. Unknown IDs play as Collegium.
+    #[must_use]
+    pub fn faction(&self) -> FactionId {
+        FactionId::from_name(&self.faction_id).unwrap_or(FactionId::Collegium)
+    }
+
     /// Create default Continuity faction setup.
     #[must_use]
     pub fn default_continuity() -> Self {
@@ -251,6 +476,8 @@ impl FactionSetup {
             starting_buildings: vec![BuildingPlacement::new("command_center", 48, 256)],
             spawn_position: (48, 256),
             starting_resources: 1000,
+            personality: None,
+            preset: Some("standard".to_string()),
         }
     }
 
@@ -267,6 +494,8 @@ impl FactionSetup {
             starting_buildings: vec![BuildingPlacement::new("command_center", 464, 256)],
             spawn_position: (464, 256),
             starting_resources: 1000,
+            personality: None,
+            preset: Some("standard".to_string()),
         }
     }
 }
@@ -363,6 +592,196 @@ impl Default for ResourceSetup {
     }
 }
 
+/// Decorative idle wander for a scenario.
+///
+/// Idle units drift around where they stopped so screenshots don't look
+/// frozen. Wandering never interrupts orders or changes target acquisition,
+/// so it has no effect on balance.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct IdleWanderConfig {
+    /// Maximum distance from where the unit went idle.
+    pub leash: u32,
+    /// Ticks between wander steps.
+    pub interval_ticks: u32,
+}
+
+impl Default for IdleWanderConfig {
+    fn default() -> Self {
+        Self {
+            leash: 12,
+            interval_ticks: 60,
+        }
+    }
+}
+
+/// Ammunition logistics for a scenario.
+///
+/// Ranged units carry limited rounds and must return to a depot or a
+/// supply unit to refill. The AI pulls units out of the fight when they
+/// run low and sends them back once full.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogisticsConfig {
+    /// Rounds carried by each ranged unit.
+    pub ammo_capacity: u32,
+    /// Units with at least this attack range use ammunition.
+    pub ranged_min_range: u32,
+    /// Resupply radius around depots.
+    pub depot_radius: u32,
+    /// Rounds per tick restored by depots.
+    pub depot_rate: u32,
+    /// Unit kind that acts as a mobile resupply source.
+    pub supply_unit: String,
+    /// Resupply radius around supply units.
+    pub supply_radius: u32,
+    /// Rounds per tick restored by supply units.
+    pub supply_rate: u32,
+    /// Rotate a unit out once its ammunition falls to this percentage.
+    pub rotate_out_below_pct: u32,
+}
+
+impl Default for LogisticsConfig {
+    fn default() -> Self {
+        Self {
+            ammo_capacity: 30,
+            ranged_min_range: 60,
+            depot_radius: 80,
+            depot_rate: 2,
+            supply_unit: "supply_truck".to_string(),
+            supply_radius: 60,
+            supply_rate: 1,
+            rotate_out_below_pct: 20,
+        }
+    }
+}
+
+/// Bounded stat overrides for a scenario or map (e.g. quick modes).
+///
+/// All values are percentages of the faction-data baseline, so the default
+/// of 100 everywhere leaves balance untouched. Overrides are applied as a
+/// layer at spawn and research time; faction data itself is never edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TuningOverrides {
+    /// Resource income rate (25..=400).
+    pub resource_yield_pct: u32,
+    /// Research speed; 200 halves research time (25..=400).
+    pub research_speed_pct: u32,
+    /// Unit and building health (50..=200).
+    pub health_pct: u32,
+    /// Weapon damage (50..=200).
+    pub damage_pct: u32,
+    /// Per-shot damage spread as ± percent, replacing faction data (0..=50).
+    /// 0 keeps each weapon's own variance, which is off by default.
+    pub damage_variance_pct: u32,
+}
+
+impl Default for TuningOverrides {
+    fn default() -> Self {
+        Self {
+            resource_yield_pct: 100,
+            research_speed_pct: 100,
+            health_pct: 100,
+            damage_pct: 100,
+            damage_variance_pct: 0,
+        }
+    }
+}
+
+impl TuningOverrides {
+    const YIELD_RANGE: (u32, u32) = (25, 400);
+    const RESEARCH_RANGE: (u32, u32) = (25, 400);
+    const STAT_RANGE: (u32, u32) = (50, 200);
+    const VARIANCE_RANGE: (u32, u32) = (0, rts_core::combat::MAX_DAMAGE_VARIANCE as u32);
+
+    /// Whether every override is at its baseline value.
+    #[must_use]
+    pub fn is_baseline(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check every override is within its bounds.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScenarioError::TuningOutOfRange`] for the first field
+    /// outside its range.
+    pub fn validate(&self) -> Result<(), ScenarioError> {
+        let fields = [
+            (
+                "resource_yield_pct",
+                self.resource_yield_pct,
+                Self::YIELD_RANGE,
+            ),
+            (
+                "research_speed_pct",
+                self.research_speed_pct,
+                Self::RESEARCH_RANGE,
+            ),
+            ("health_pct", self.health_pct, Self::STAT_RANGE),
+            ("damage_pct", self.damage_pct, Self::STAT_RANGE),
+            (
+                "damage_variance_pct",
+                self.damage_variance_pct,
+                Self::VARIANCE_RANGE,
+            ),
+        ];
+        for (field, value, (min, max)) in fields {
+            if !(min..=max).contains(&value) {
+                return Err(ScenarioError::TuningOutOfRange {
+                    field,
+                    value,
+                    min,
+                    max,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Scale feedstock a harvester unloads at a depot.
+    #[must_use]
+    pub fn resource_yield(&self, amount: i64) -> i64 {
+        let pct = bounded(self.resource_yield_pct, Self::YIELD_RANGE);
+        amount * i64::from(pct) / 100
+    }
+
+    /// Scale a research duration in ticks.
+    #[must_use]
+    pub fn research_ticks(&self, ticks: u64) -> u64 {
+        let pct = bounded(self.research_speed_pct, Self::RESEARCH_RANGE);
+        ticks * 100 / u64::from(pct)
+    }
+
+    /// Scale a maximum health value.
+    #[must_use]
+    pub fn health(&self, health: u32) -> u32 {
+        scale(health, bounded(self.health_pct, Self::STAT_RANGE))
+    }
+
+    /// Scale a weapon damage value.
+    #[must_use]
+    pub fn damage(&self, damage: u32) -> u32 {
+        scale(damage, bounded(self.damage_pct, Self::STAT_RANGE))
+    }
+
+    /// Damage variance for a weapon whose faction data gives `variance`.
+    #[must_use]
+    pub fn damage_variance(&self, variance: u8) -> u8 {
+        match bounded(self.damage_variance_pct, Self::VARIANCE_RANGE) {
+            0 => variance,
+            pct => pct as u8,
+        }
+    }
+}
+
+fn bounded(value: u32, (min, max): (u32, u32)) -> u32 {
+    value.clamp(min, max)
+}
+
+fn scale(value: u32, pct: u32) -> u32 {
+    (u64::from(value) * u64::from(pct) / 100) as u32
+}
+
 /// An ore/resource node on the map.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OreNode {
@@ -370,6 +789,9 @@ pub struct OreNode {
     pub position: (i32, i32),
     /// Total resources available.
     pub amount: i64,
+    /// Resource the node yields.
+    #[serde(default)]
+    pub kind: ResourceKind,
 }
 
 impl OreNode {
@@ -379,13 +801,22 @@ impl OreNode {
         Self {
             position: (x, y),
             amount,
+            kind: ResourceKind::Feedstock,
         }
     }
+
+    /// Set the resource the node yields.
+    #[must_use]
+    pub fn with_kind(mut self, kind: ResourceKind) -> Self {
+        self.kind = kind;
+        self
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rts_core::triggers::TriggerCondition;
 
     #[test]
     fn test_default_scenario() {
@@ -423,5 +854,271 @@ mod tests {
         "#;
         let scenario = Scenario::from_ron_str(ron).unwrap();
         assert_eq!(scenario.name, "Test");
+        assert!(scenario.logistics.is_none());
+    }
+
+    #[test]
+    fn test_parse_logistics_from_ron() {
+        let ron = r#"
+            Scenario(
+                name: "Logistics",
+                description: "Limited ammunition",
+                map_size: (100, 100),
+                factions: [],
+                victory_conditions: VictoryConditions(
+                    elimination: true,
+                    time_limit_ticks: None,
+                    resource_threshold: None,
+                ),
+                initial_resources: ResourceSetup(
+                    ore_nodes: [],
+                ),
+                logistics: Some(LogisticsConfig(
+                    ammo_capacity: 12,
+                    ranged_min_range: 80,
+                    depot_radius: 100,
+                    depot_rate: 3,
+                    supply_unit: "supply_truck",
+                    supply_radius: 50,
+                    supply_rate: 1,
+                    rotate_out_below_pct: 25,
+                )),
+            )
+        "#;
+        let scenario = Scenario::from_ron_str(ron).unwrap();
+        let logistics = scenario.logistics.unwrap();
+        assert_eq!(logistics.ammo_capacity, 12);
+        assert_eq!(logistics.rotate_out_below_pct, 25);
+        assert!(scenario.tuning.is_baseline());
+    }
+
+    #[test]
+    fn test_map_preset_scenarios_carry_their_terrain() {
+        let scenario = Scenario::from_map_preset(5, MapSize::Small, MapPreset::Islands);
+        let map = scenario.map.as_ref().unwrap();
+        assert_eq!(map.preset, MapPreset::Islands);
+        assert_eq!(map.biome, Biome::from_seed(5));
+        assert_eq!(scenario.map_size, (512, 512));
+
+        // Every start sits on open ground
+        let terrain = scenario.generate_terrain(0).unwrap();
+        for setup in &scenario.factions {
+            let (x, y) = setup.spawn_position;
+            let cell = terrain.world_to_grid(rts_core::math::Vec2Fixed::new(
+                rts_core::math::Fixed::from_num(x),
+                rts_core::math::Fixed::from_num(y),
+            ));
+            assert!(terrain.get_cell(cell.0, cell.1).unwrap().spawn_safe);
+        }
+        assert!(Scenario::default().map.is_none());
+    }
+
+    #[test]
+    fn test_parse_triggers_from_ron() {
+        let ron = r#"
+            Scenario(
+                name: "Holdout",
+                description: "Survive the waves",
+                map_size: (100, 100),
+                factions: [],
+                victory_conditions: VictoryConditions(
+                    elimination: true,
+                    time_limit_ticks: None,
+                    resource_threshold: None,
+                ),
+                initial_resources: ResourceSetup(
+                    ore_nodes: [],
+                ),
+                triggers: [
+                    Trigger(
+                        name: "briefing",
+                        condition: AtTick(0),
+                        actions: [Message("Hold the ridge.")],
+                    ),
+                    Trigger(
+                        name: "waves",
+                        condition: Every(start: 600, interval: 900, times: 4),
+                        actions: [
+                            SpawnUnits(
+                                faction: Collegium,
+                                kind: "infantry",
+                                position: (90, 50),
+                                count: 4,
+                            ),
+                        ],
+                    ),
+                    Trigger(
+                        name: "depot_lost",
+                        condition: Destroyed(faction: Continuity, kind: "supply_depot"),
+                        actions: [GrantResources(faction: Continuity, amount: 300)],
+                    ),
+                ],
+            )
+        "#;
+        let scenario = Scenario::from_ron_str(ron).unwrap();
+        assert_eq!(scenario.triggers.len(), 3);
+        assert_eq!(
+            scenario.triggers[1].condition,
+            TriggerCondition::Every {
+                start: 600,
+                interval: 900,
+                times: 4,
+            }
+        );
+        assert_eq!(
+            scenario.triggers[2].actions,
+            vec![TriggerAction::GrantResources {
+                faction: FactionId::Continuity,
+                amount: 300,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_tuning_overrides_scale_stats() {
+        let tuning = TuningOverrides {
+            resource_yield_pct: 120,
+            research_speed_pct: 200,
+            health_pct: 150,
+            ..TuningOverrides::default()
+        };
+        assert!(!tuning.is_baseline());
+        assert_eq!(tuning.resource_yield(50), 60);
+        assert_eq!(tuning.research_ticks(600), 300);
+        assert_eq!(tuning.health(80), 120);
+        assert_eq!(tuning.damage(12), 12);
+        assert_eq!(tuning.damage_variance(5), 5);
+        let varied = TuningOverrides {
+            damage_variance_pct: 10,
+            ..TuningOverrides::default()
+        };
+        assert_eq!(varied.damage_variance(5), 10);
+        assert_eq!(TuningOverrides::default().resource_yield(50), 50);
+    }
+
+    #[test]
+    fn test_tuning_out_of_range_rejected() {
+        let ron = r#"
+            Scenario(
+                name: "Quick",
+                description: "Out of bounds",
+                map_size: (100, 100),
+                factions: [],
+                victory_conditions: VictoryConditions(
+                    elimination: true,
+                    time_limit_ticks: None,
+                    resource_threshold: None,
+                ),
+                initial_resources: ResourceSetup(
+                    ore_nodes: [],
+                ),
+                tuning: TuningOverrides(
+                    research_speed_pct: 1000,
+                ),
+            )
+        "#;
+        let err = Scenario::from_ron_str(ron).unwrap_err();
+        assert!(matches!(
+            err,
+            ScenarioError::TuningOutOfRange {
+                field: "research_speed_pct",
+                ..
+            }
+        ));
+    }
+
+    fn registry() -> FactionRegistry {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../rts_game/assets/data/factions");
+        FactionRegistry::lazy_from_directory(&dir).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_starts_expands_presets() {
+        let mut scenario = Scenario::skirmish_1v1();
+        scenario.factions[1].preset = Some("fast-expand".to_string());
+        scenario.resolve_starts(&registry()).unwrap();
+
+        let continuity = &scenario.factions[0];
+        assert_eq!(
+            continuity.starting_buildings[0].kind,
+            "administration_center"
+        );
+        assert_eq!(continuity.starting_buildings[0].position, (48, 256));
+        assert!(continuity
+            .starting_units
+            .iter()
+            .any(|u| u.kind == "security_team" && u.position == (53, 256)));
+
+        let collegium = &scenario.factions[1];
+        assert_eq!(collegium.starting_buildings.len(), 2);
+    }
+
+    #[test]
+    fn test_resolve_starts_rejects_unknown_kinds() {
+        let registry = registry();
+
+        let mut scenario = Scenario::skirmish_1v1();
+        scenario.factions[0].preset = Some("cheese".to_string());
+        assert!(matches!(
+            scenario.resolve_starts(&registry),
+            Err(ScenarioError::UnknownPreset { .. })
+        ));
+
+        // Without a preset, free-form kinds must exist in the faction data
+        let mut scenario = Scenario::skirmish_1v1();
+        scenario.factions[0].preset = None;
+        assert!(matches!(
+            scenario.resolve_starts(&registry),
+            Err(ScenarioError::UnknownBuildingKind { kind, .. }) if kind == "command_center"
+        ));
+
+        let mut scenario = Scenario::skirmish_1v1();
+        scenario.factions[0].preset = None;
+        scenario.factions[0].starting_buildings.clear();
+        scenario.factions[0]
+            .starting_units
+            .push(UnitPlacement::new("gigantic_robot", 0, 0, 1));
+        assert!(matches!(
+            scenario.resolve_starts(&registry),
+            Err(ScenarioError::UnknownUnitKind { kind, .. }) if kind == "gigantic_robot"
+        ));
+
+        let mut scenario = Scenario::skirmish_1v1();
+        scenario.triggers.push(Trigger {
+            name: "wave".to_string(),
+            condition: TriggerCondition::AtTick(100),
+            actions: vec![TriggerAction::SpawnUnits {
+                faction: FactionId::Continuity,
+                kind: "gigantic_robot".to_string(),
+                position: (0, 0),
+                count: 3,
+            }],
+        });
+        assert!(matches!(
+            scenario.resolve_starts(&registry),
+            Err(ScenarioError::UnknownUnitKind { kind, .. }) if kind == "gigantic_robot"
+        ));
+    }
+
+    #[test]
+    fn test_set_matchup_resolves_any_factions() {
+        let mut scenario = Scenario::skirmish_1v1();
+        scenario
+            .set_matchup(FactionId::BioSovereigns, FactionId::Zephyr)
+            .unwrap();
+        assert_eq!(scenario.factions[0].faction(), FactionId::BioSovereigns);
+        assert_eq!(scenario.factions[1].faction(), FactionId::Zephyr);
+        scenario.resolve_starts(&registry()).unwrap();
+        assert!(!scenario.factions[1].starting_buildings.is_empty());
+
+        assert!(matches!(
+            scenario.set_matchup(FactionId::Tinkers, FactionId::Tinkers),
+            Err(ScenarioError::MirrorMatchup(FactionId::Tinkers))
+        ));
+        scenario.factions.truncate(1);
+        assert!(matches!(
+            scenario.set_matchup(FactionId::Tinkers, FactionId::Zephyr),
+            Err(ScenarioError::TooFewFactions(_))
+        ));
     }
 }
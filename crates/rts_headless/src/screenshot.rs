@@ -1,17 +1,21 @@
 //! Screenshot capture system for visual quality review.
 //!
-//! Supports two modes:
+//! Supports three modes:
 //! - **GPU mode**: Direct capture via Bevy's screenshot manager (requires display)
 //! - **State dump mode**: Serialize visual state for offline rendering (headless)
+//! - **PNG mode**: State dumps plus a PNG drawn by [`crate::raster`] (headless)
 //!
 //! Screenshots are triggered at key game moments (battles, expansions, milestones)
 //! and tracked in a manifest for review.
 
 use bevy::prelude::*;
+use rts_core::influence::InfluenceMap;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::raster::{save_png, RasterConfig};
+
 /// Screenshot capture mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum ScreenshotMode {
@@ -20,6 +24,8 @@ pub enum ScreenshotMode {
     Disabled,
     /// State dump for offline rendering (headless-compatible)
     StateDump,
+    /// State dump plus a rendered PNG of it (headless-compatible)
+    Png,
     /// Direct GPU capture (requires display)
     Gpu,
 }
@@ -29,6 +35,7 @@ impl std::fmt::Display for ScreenshotMode {
         match self {
             ScreenshotMode::Disabled => write!(f, "disabled"),
             ScreenshotMode::StateDump => write!(f, "state_dump"),
+            ScreenshotMode::Png => write!(f, "png"),
             ScreenshotMode::Gpu => write!(f, "gpu"),
         }
     }
@@ -173,6 +180,9 @@ pub struct VisualState {
     pub effects: Vec<EffectVisual>,
     pub map_bounds: (u32, u32),
     pub fog_of_war: Option<Vec<Vec<bool>>>,
+    /// Threat map of the first player, for debugging overlays.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threat_map: Option<InfluenceMap>,
 }
 
 impl VisualState {
@@ -189,6 +199,7 @@ impl VisualState {
             effects: Vec::new(),
             map_bounds: (256, 256),
             fog_of_war: None,
+            threat_map: None,
         }
     }
 
@@ -213,6 +224,9 @@ pub struct ScreenshotEntry {
     pub visible_unit_count: u32,
     pub visible_building_count: u32,
     pub review_prompts: Vec<String>,
+    /// Rendered PNG of the state dump, in PNG mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
 }
 
 /// Manifest tracking all captured screenshots for a game
@@ -234,6 +248,7 @@ impl ScreenshotManifest {
             capture_mode: match mode {
                 ScreenshotMode::Disabled => "disabled".to_string(),
                 ScreenshotMode::StateDump => "state_dump".to_string(),
+                ScreenshotMode::Png => "png".to_string(),
                 ScreenshotMode::Gpu => "gpu".to_string(),
             },
         }
@@ -366,6 +381,17 @@ impl ScreenshotManager {
         let filename = format!("{}_{}.json", trigger.name(), state.tick);
         let path = screenshot_dir.join(&filename);
         state.save(&path)?;
+        let image = if self.config.mode == ScreenshotMode::Png {
+            let image = format!("{}_{}.png", trigger.name(), state.tick);
+            save_png(
+                &state,
+                &screenshot_dir.join(&image),
+                &RasterConfig::default(),
+            )?;
+            Some(image)
+        } else {
+            None
+        };
 
         // Add entry to manifest
         let entry = ScreenshotEntry {
@@ -377,6 +403,7 @@ impl ScreenshotManager {
             visible_unit_count: state.units.len() as u32,
             visible_building_count: state.buildings.len() as u32,
             review_prompts: trigger.review_prompts(),
+            image,
         };
         self.manifest.add_entry(entry);
 
@@ -517,6 +544,7 @@ mod tests {
             visible_unit_count: 10,
             visible_building_count: 4,
             review_prompts: vec!["Check silhouettes".to_string()],
+            image: None,
         });
 
         manifest.save(&path).unwrap();
@@ -540,4 +568,21 @@ mod tests {
         assert!(path.to_string_lossy().contains("game_123"));
         assert!(path.to_string_lossy().contains("battle_001.json"));
     }
+
+    #[test]
+    fn test_png_mode_renders_an_image_beside_each_dump() {
+        let dir = tempdir().unwrap();
+        let config = ScreenshotConfig::new(ScreenshotMode::Png, dir.path().to_path_buf(), "g1");
+        let mut manager = ScreenshotManager::new(config);
+        let trigger = ScreenshotTrigger::TimedSnapshot { tick: 40 };
+        manager
+            .capture(VisualState::new("g1", 40, trigger.clone()), &trigger)
+            .unwrap();
+
+        let entry = &manager.manifest().screenshots[0];
+        assert_eq!(entry.mode, "png");
+        let image = entry.image.as_deref().unwrap();
+        assert!(dir.path().join("g1").join(image).exists());
+        assert!(dir.path().join("g1").join(&entry.filename).exists());
+    }
 }
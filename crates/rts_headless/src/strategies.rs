@@ -1,368 +1,55 @@
 //! Scripted AI strategies for headless playtesting.
 //!
 //! Strategies define build orders and tactical decisions for AI players
-//! in automated game testing.
-
-use std::collections::VecDeque;
-use std::path::Path;
-
+//! in automated game testing. The strategy format itself lives in
+//! [`rts_core::strategy`], shared with the game client; this module runs
+//! it.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use rts_core::influence::InfluenceMap;
+use rts_core::math::Vec2Fixed;
+pub use rts_core::strategy::{
+    BuildOrderItem, BuildPriority, EconomyTargets, Strategy, StrategyProblem, TacticsConfig,
+    TradePolicy,
+};
+pub use rts_game::strategy_file::StrategyError;
 use serde::{Deserialize, Serialize};
-use thiserror::Error;
-
-/// Error type for strategy operations.
-#[derive(Error, Debug)]
-pub enum StrategyError {
-    /// File not found.
-    #[error("Strategy file not found: {0}")]
-    FileNotFound(String),
-    /// Failed to read file.
-    #[error("Failed to read strategy file: {0}")]
-    ReadError(#[from] std::io::Error),
-    /// Failed to parse RON.
-    #[error("Failed to parse strategy: {0}")]
-    ParseError(#[from] ron::error::SpannedError),
-}
 
-/// A complete AI strategy configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Strategy {
-    /// Strategy name.
-    pub name: String,
-    /// Human-readable description.
-    pub description: String,
-    /// Build order to follow.
-    pub build_order: Vec<BuildOrderItem>,
-    /// Tick when first attack should happen.
-    pub attack_timing: u64,
-    /// Re-attack interval after first attack (in ticks).
-    pub attack_interval: u64,
-    /// Target army composition (unit_type -> fraction 0.0-1.0).
-    pub composition: std::collections::HashMap<String, f64>,
-    /// Economic targets.
-    pub economy: EconomyTargets,
-    /// Aggression level (0.0 = passive, 1.0 = hyper-aggressive).
-    pub aggression: f64,
+/// Percent added to a counter unit's share of the composition when the
+/// whole remembered enemy army is of the kind it counters.
+pub const COUNTER_WEIGHT_PERCENT: u32 = 50;
+
+/// Resources set aside for a pending purchase.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reservation {
+    /// ID of the unit, building or tech being saved for.
+    pub item: String,
+    /// Resources set aside.
+    pub amount: i64,
+    /// Priority of the item.
+    pub priority: BuildPriority,
+    /// Tick the reservation was made.
+    pub since_tick: u64,
 }
 
-impl Default for Strategy {
-    fn default() -> Self {
-        Self {
-            name: "Balanced".to_string(),
-            description: "Standard balanced gameplay".to_string(),
-            build_order: vec![
-                BuildOrderItem::Unit("harvester".to_string()),
-                BuildOrderItem::Building("barracks".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Unit("harvester".to_string()),
-            ],
-            attack_timing: 12000,  // 200 seconds (3:20) at 60 tps
-            attack_interval: 3600, // 60 seconds between attacks
-            composition: [
-                ("infantry".to_string(), 0.5),
-                ("ranger".to_string(), 0.3),
-                ("harvester".to_string(), 0.2),
-            ]
-            .into_iter()
-            .collect(),
-            economy: EconomyTargets::default(),
-            aggression: 0.5,
-        }
-    }
-}
-
-impl Strategy {
-    /// Load a strategy from a RON file.
-    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, StrategyError> {
-        let path = path.as_ref();
-        if !path.exists() {
-            return Err(StrategyError::FileNotFound(path.display().to_string()));
-        }
-        let contents = std::fs::read_to_string(path)?;
-        let strategy: Strategy = ron::from_str(&contents)?;
-        Ok(strategy)
-    }
-
-    /// Load from a RON string.
-    pub fn from_ron_str(ron: &str) -> Result<Self, StrategyError> {
-        let strategy: Strategy = ron::from_str(ron)?;
-        Ok(strategy)
-    }
-
-    /// Create a "Rush" strategy (early aggression).
-    #[must_use]
-    pub fn rush() -> Self {
-        Self {
-            name: "Rush".to_string(),
-            description: "Early aggression with cheap units".to_string(),
-            build_order: vec![
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Building("barracks".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::WaitForUnits("infantry".to_string(), 5),
-            ],
-            attack_timing: 7200,   // 120 seconds (balanced from 100s)
-            attack_interval: 2100, // 35 seconds between waves
-            composition: [
-                ("infantry".to_string(), 0.9),
-                ("harvester".to_string(), 0.1),
-            ]
-            .into_iter()
-            .collect(),
-            economy: EconomyTargets {
-                target_harvesters: 1,
-                target_supply_depots: 0,
-                expand_at_resources: 2000,
-            },
-            aggression: 0.9,
-        }
-    }
-
-    /// Create an "Economic" strategy (late game power).
-    #[must_use]
-    pub fn economic() -> Self {
-        Self {
-            name: "Economic".to_string(),
-            description: "Focus on economy, powerful late game".to_string(),
-            build_order: vec![
-                BuildOrderItem::Unit("harvester".to_string()),
-                BuildOrderItem::Building("supply_depot".to_string()),
-                BuildOrderItem::Unit("harvester".to_string()),
-                BuildOrderItem::Building("barracks".to_string()),
-                BuildOrderItem::Unit("harvester".to_string()),
-                BuildOrderItem::WaitForResources(2000),
-                BuildOrderItem::Building("tech_lab".to_string()),
-            ],
-            attack_timing: 18000,  // 300 seconds (5 minutes)
-            attack_interval: 6000, // 100 seconds between attacks
-            composition: [
-                ("infantry".to_string(), 0.3),
-                ("ranger".to_string(), 0.4),
-                ("harvester".to_string(), 0.3),
-            ]
-            .into_iter()
-            .collect(),
-            economy: EconomyTargets {
-                target_harvesters: 4,
-                target_supply_depots: 2,
-                expand_at_resources: 1500,
-            },
-            aggression: 0.3,
-        }
-    }
-
-    /// Create a "Turtle" strategy (defensive play).
-    #[must_use]
-    pub fn turtle() -> Self {
-        Self {
-            name: "Turtle".to_string(),
-            description: "Defensive play with turrets".to_string(),
-            build_order: vec![
-                BuildOrderItem::Unit("harvester".to_string()),
-                BuildOrderItem::Building("barracks".to_string()),
-                BuildOrderItem::Building("turret".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Building("turret".to_string()),
-                BuildOrderItem::Unit("harvester".to_string()),
-                BuildOrderItem::Building("supply_depot".to_string()),
-            ],
-            attack_timing: 24000,  // 400 seconds
-            attack_interval: 9000, // 150 seconds
-            composition: [
-                ("infantry".to_string(), 0.4),
-                ("ranger".to_string(), 0.4),
-                ("harvester".to_string(), 0.2),
-            ]
-            .into_iter()
-            .collect(),
-            economy: EconomyTargets {
-                target_harvesters: 3,
-                target_supply_depots: 1,
-                expand_at_resources: 3000, // Only expand when very rich
-            },
-            aggression: 0.1,
-        }
-    }
-
-    /// Create a "Fast Expand" strategy (two-base economy rush).
-    #[must_use]
-    pub fn fast_expand() -> Self {
-        Self {
-            name: "FastExpand".to_string(),
-            description: "Quick second base for economic advantage".to_string(),
-            build_order: vec![
-                BuildOrderItem::Unit("harvester".to_string()),
-                BuildOrderItem::Unit("harvester".to_string()),
-                BuildOrderItem::Building("supply_depot".to_string()),
-                BuildOrderItem::WaitForResources(300),
-                BuildOrderItem::Building("command_center".to_string()), // 2nd base
-                BuildOrderItem::Unit("harvester".to_string()),
-                BuildOrderItem::Building("barracks".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Unit("harvester".to_string()),
-            ],
-            attack_timing: 15000,  // 250 seconds - medium timing
-            attack_interval: 4800, // 80 seconds between attacks
-            composition: [
-                ("infantry".to_string(), 0.35),
-                ("ranger".to_string(), 0.35),
-                ("tank".to_string(), 0.15),
-                ("harvester".to_string(), 0.15),
-            ]
-            .into_iter()
-            .collect(),
-            economy: EconomyTargets {
-                target_harvesters: 6,
-                target_supply_depots: 3,
-                expand_at_resources: 1200,
-            },
-            aggression: 0.5,
-        }
-    }
-
-    /// Create a "Harassment" strategy (constant raids and map control).
-    #[must_use]
-    pub fn harassment() -> Self {
-        Self {
-            name: "Harassment".to_string(),
-            description: "Fast scouts and constant pressure".to_string(),
-            build_order: vec![
-                BuildOrderItem::Unit("scout".to_string()),
-                BuildOrderItem::Unit("scout".to_string()),
-                BuildOrderItem::Building("barracks".to_string()),
-                BuildOrderItem::Unit("scout".to_string()),
-                BuildOrderItem::Unit("ranger".to_string()),
-                BuildOrderItem::Unit("scout".to_string()),
-                BuildOrderItem::WaitForUnits("scout".to_string(), 4),
-            ],
-            attack_timing: 4800,   // 80 seconds - very early harassment
-            attack_interval: 1200, // 20 seconds - constant pressure
-            composition: [
-                ("scout".to_string(), 0.5),
-                ("ranger".to_string(), 0.35),
-                ("infantry".to_string(), 0.15),
-            ]
-            .into_iter()
-            .collect(),
-            economy: EconomyTargets {
-                target_harvesters: 2,
-                target_supply_depots: 1,
-                expand_at_resources: 2000,
-            },
-            aggression: 0.85,
-        }
-    }
-
-    /// Create an "All-In" strategy (one big attack, no economy).
-    #[must_use]
-    pub fn all_in() -> Self {
-        Self {
-            name: "AllIn".to_string(),
-            description: "Sacrifice economy for one decisive attack".to_string(),
-            build_order: vec![
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Building("barracks".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::WaitForUnits("infantry".to_string(), 7),
-            ],
-            attack_timing: 5400,  // 90 seconds - committed timing
-            attack_interval: 600, // 10 seconds - no holding back
-            composition: [("infantry".to_string(), 1.0)].into_iter().collect(),
-            economy: EconomyTargets {
-                target_harvesters: 0,
-                target_supply_depots: 0,
-                expand_at_resources: 99999, // Never expand
-            },
-            aggression: 1.0,
-        }
-    }
-
-    /// Create a "Tech Push" strategy (research then attack with upgraded units).
-    /// This strategy builds economy first, researches key upgrades, then attacks
-    /// with a more powerful mixed-tier army.
-    #[must_use]
-    pub fn tech_push() -> Self {
-        Self {
-            name: "TechPush".to_string(),
-            description: "Research upgrades before building advanced units".to_string(),
-            build_order: vec![
-                BuildOrderItem::Unit("harvester".to_string()),
-                BuildOrderItem::Building("barracks".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Building("tech_lab".to_string()),
-                // Research tier 1 upgrade (generic name - faction-specific techs are looked up)
-                BuildOrderItem::Research("enhanced_training".to_string()),
-                BuildOrderItem::Unit("infantry".to_string()),
-                BuildOrderItem::Unit("ranger".to_string()),
-                BuildOrderItem::WaitForResources(200),
-                BuildOrderItem::Building("vehicle_depot".to_string()),
-                BuildOrderItem::Unit("tank".to_string()),
-            ],
-            attack_timing: 15000,  // 250 seconds - later timing for tech
-            attack_interval: 4800, // 80 seconds between attacks
-            composition: [
-                ("infantry".to_string(), 0.4),
-                ("ranger".to_string(), 0.3),
-                ("tank".to_string(), 0.2),
-                ("harvester".to_string(), 0.1),
-            ]
-            .into_iter()
-            .collect(),
-            economy: EconomyTargets {
-                target_harvesters: 2,
-                target_supply_depots: 1,
-                expand_at_resources: 2000,
-            },
-            aggression: 0.6,
-        }
-    }
-}
-
-/// A single item in a build order.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum BuildOrderItem {
-    /// Produce a unit.
-    Unit(String),
-    /// Construct a building.
-    Building(String),
-    /// Research a technology.
-    Research(String),
-    /// Wait for a certain amount of resources.
-    WaitForResources(i64),
-    /// Wait for a certain number of a unit type.
-    WaitForUnits(String, u32),
-    /// Wait for a specific tick.
-    WaitForTick(u64),
-}
-
-/// Economic targets for the AI.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct EconomyTargets {
-    /// Target number of harvesters.
-    pub target_harvesters: u32,
-    /// Target number of supply depots.
-    pub target_supply_depots: u32,
-    /// Expand when resources exceed this amount.
-    pub expand_at_resources: i64,
-}
-
-impl Default for EconomyTargets {
-    fn default() -> Self {
-        Self {
-            target_harvesters: 2,
-            target_supply_depots: 1,
-            expand_at_resources: 2500,
-        }
-    }
+/// Change in reservation state, for decision traces.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReservationEvent {
+    /// Resources were set aside for an item.
+    Reserved {
+        /// Tick of the change.
+        tick: u64,
+        /// The new reservation.
+        reservation: Reservation,
+    },
+    /// The item was bought and its reservation released.
+    Released {
+        /// Tick of the change.
+        tick: u64,
+        /// The released reservation.
+        reservation: Reservation,
+    },
 }
 
 /// Tactical decision types for AI actions.
@@ -394,7 +81,7 @@ pub struct TacticalRule {
 }
 
 /// Runtime state for executing a strategy.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StrategyExecutor {
     /// The strategy being executed.
     strategy: Strategy,
@@ -406,6 +93,12 @@ pub struct StrategyExecutor {
     attack_triggered: bool,
     /// Last attack tick.
     last_attack_tick: u64,
+    /// Resources held back for a pending high-priority item.
+    #[serde(default)]
+    reservation: Option<Reservation>,
+    /// Reservation changes not yet collected by the runner.
+    #[serde(default)]
+    trace: Vec<ReservationEvent>,
 }
 
 impl StrategyExecutor {
@@ -419,7 +112,88 @@ impl StrategyExecutor {
             current_index: 0,
             attack_triggered: false,
             last_attack_tick: 0,
+            reservation: None,
+            trace: Vec::new(),
+        }
+    }
+
+    /// Priority of a build order item under this strategy.
+    #[must_use]
+    pub fn priority_of(&self, item: &BuildOrderItem) -> BuildPriority {
+        self.strategy.priority_of(item)
+    }
+
+    /// The current reservation, if any.
+    #[must_use]
+    pub fn reservation(&self) -> Option<&Reservation> {
+        self.reservation.as_ref()
+    }
+
+    /// Resources a purchase of `priority` may spend.
+    ///
+    /// Purchases below the reservation's priority cannot touch the reserved
+    /// amount.
+    #[must_use]
+    pub fn spendable(&self, resources: i64, priority: BuildPriority) -> i64 {
+        match &self.reservation {
+            Some(reservation) if priority < reservation.priority => {
+                (resources - reservation.amount).max(0)
+            }
+            _ => resources,
+        }
+    }
+
+    /// Hand back a build order item that could not be afforded.
+    ///
+    /// Items above [`BuildPriority::Normal`] go back to the front of the
+    /// build order and reserve their cost until bought. Other items are
+    /// dropped.
+    pub fn defer(&mut self, current_tick: u64, item: BuildOrderItem, cost: i64) {
+        let priority = self.priority_of(&item);
+        if priority <= BuildPriority::Normal {
+            return;
+        }
+        let Some(id) = item.target().map(str::to_string) else {
+            return;
+        };
+
+        if self.reservation.as_ref().map(|r| r.item.as_str()) != Some(id.as_str()) {
+            let reservation = Reservation {
+                item: id,
+                amount: cost,
+                priority,
+                since_tick: current_tick,
+            };
+            self.trace.push(ReservationEvent::Reserved {
+                tick: current_tick,
+                reservation: reservation.clone(),
+            });
+            self.reservation = Some(reservation);
         }
+        self.build_queue.push_front(item);
+        self.current_index = self.current_index.saturating_sub(1);
+    }
+
+    /// Record that a build order item was bought, releasing its reservation.
+    pub fn complete(&mut self, current_tick: u64, item: &BuildOrderItem) {
+        let reserved_for_item = self
+            .reservation
+            .as_ref()
+            .is_some_and(|r| Some(r.item.as_str()) == item.target());
+        if !reserved_for_item {
+            return;
+        }
+        if let Some(reservation) = self.reservation.take() {
+            self.trace.push(ReservationEvent::Released {
+                tick: current_tick,
+                reservation,
+            });
+        }
+    }
+
+    /// Take the reservation changes recorded since the last call.
+    pub fn drain_trace(&mut self) -> Vec<ReservationEvent> {
+        std::mem::take(&mut self.trace)
     }
 
     /// Get the next build order item if conditions are met.
@@ -485,24 +259,70 @@ impl StrategyExecutor {
         false
     }
 
+    /// Get the strategy being executed.
+    #[must_use]
+    pub fn strategy(&self) -> &Strategy {
+        &self.strategy
+    }
+
     /// Get the strategy name.
     #[must_use]
     pub fn name(&self) -> &str {
         &self.strategy.name
     }
 
-    /// Get aggression level.
+    /// Get aggression percent.
     #[must_use]
-    pub fn aggression(&self) -> f64 {
-        self.strategy.aggression
+    pub fn aggression(&self) -> u32 {
+        self.strategy.aggression_percent
     }
 
-    /// Get the target composition.
+    /// Get the target composition, in percent.
     #[must_use]
-    pub fn composition(&self) -> &std::collections::HashMap<String, f64> {
+    pub fn composition(&self) -> &BTreeMap<String, u32> {
         &self.strategy.composition
     }
 
+    /// The composition, shifted toward the strategy's counters to what the
+    /// enemy is known to field.
+    ///
+    /// `enemy` counts remembered enemy units by kind and by tag, out of
+    /// `enemy_units` units. Each counter the composition fields gains
+    /// [`COUNTER_WEIGHT_PERCENT`] times the share of the enemy army it
+    /// counters. Ordered, so ties between units always break the same way.
+    #[must_use]
+    pub fn adapted_composition(
+        &self,
+        enemy: &BTreeMap<String, u32>,
+        enemy_units: u32,
+    ) -> BTreeMap<String, u32> {
+        let mut composition = self.strategy.composition.clone();
+        if enemy_units == 0 {
+            return composition;
+        }
+        for (label, &count) in enemy {
+            let Some(counter) = self.strategy.counters.get(label) else {
+                continue;
+            };
+            if let Some(weight) = composition.get_mut(counter) {
+                *weight += COUNTER_WEIGHT_PERCENT * count / enemy_units;
+            }
+        }
+        composition
+    }
+
+    /// Health percent below which structures are sent engineers.
+    #[must_use]
+    pub fn repair_below_percent(&self) -> u32 {
+        self.strategy.repair_below_percent
+    }
+
+    /// How the strategy trades at markets, if it does.
+    #[must_use]
+    pub fn trading(&self) -> Option<&TradePolicy> {
+        self.strategy.trading.as_ref()
+    }
+
     /// Get economy targets.
     #[must_use]
     pub fn economy(&self) -> &EconomyTargets {
@@ -520,6 +340,10 @@ impl StrategyExecutor {
     }
 
     /// Decide what tactical action to take based on current game state.
+    ///
+    /// `expansion_open` says whether there is a site for a new base; the
+    /// AI expands there once it holds the strategy's
+    /// [`expand_at_resources`](EconomyTargets::expand_at_resources).
     #[must_use]
     pub fn decide_action(
         &self,
@@ -527,17 +351,34 @@ impl StrategyExecutor {
         army_supply: u32,
         enemy_army_supply: u32,
         base_under_attack: bool,
+        resources: i64,
+        expansion_open: bool,
     ) -> TacticalDecision {
         // If base is under attack, defend
         if base_under_attack {
             return TacticalDecision::Defend;
         }
 
+        // Put spare resources into a new base
+        if expansion_open && resources >= self.strategy.economy.expand_at_resources {
+            return TacticalDecision::Expand;
+        }
+
+        // Not before the army is big enough, if the strategy says how big
+        if self
+            .strategy
+            .min_wave_size
+            .is_some_and(|size| army_supply < size)
+        {
+            return TacticalDecision::Hold;
+        }
+
         // Check if we should attack based on timing
         if current_tick >= self.strategy.attack_timing {
             // Attack if we have army advantage based on aggression
-            let threshold = (1.0 - self.strategy.aggression) as u32 * enemy_army_supply;
-            if army_supply >= threshold || self.strategy.aggression > 0.7 {
+            let aggression = self.strategy.aggression_percent.min(100);
+            let threshold = (100 - aggression) / 100 * enemy_army_supply;
+            if army_supply >= threshold || aggression > 70 {
                 return TacticalDecision::Attack;
             }
         }
@@ -545,6 +386,26 @@ impl StrategyExecutor {
         TacticalDecision::Hold
     }
 
+    /// Whether the army is strong enough to attack, going by the threat
+    /// map: its strength must reach that of the enemies in sight,
+    /// discounted by the strategy's aggression.
+    #[must_use]
+    pub fn strong_enough(&self, threat: &InfluenceMap) -> bool {
+        let aggression = i64::from(self.strategy.aggression_percent.min(100));
+        threat.own_strength() * 100 >= threat.enemy_strength() * (100 - aggression)
+    }
+
+    /// Where to attack: of `targets`, the one the threat map shows least
+    /// enemy strength around. Ties go to the earliest, so pass the nearest
+    /// first.
+    #[must_use]
+    pub fn pick_target(&self, threat: &InfluenceMap, targets: &[Vec2Fixed]) -> Option<Vec2Fixed> {
+        targets
+            .iter()
+            .copied()
+            .min_by_key(|&target| threat.enemy_near(target))
+    }
+
     /// Get the next build item that should be built (convenience alias for next_item).
     pub fn next_build_item(
         &mut self,
@@ -570,7 +431,7 @@ mod tests {
     #[test]
     fn test_rush_strategy() {
         let strategy = Strategy::rush();
-        assert_eq!(strategy.aggression, 0.9);
+        assert_eq!(strategy.aggression_percent, 90);
         assert!(strategy.attack_timing < 10000);
     }
 
@@ -581,6 +442,110 @@ mod tests {
         assert!(strategy.attack_timing > 15000);
     }
 
+    #[test]
+    fn test_handicap_shifts_attacks() {
+        use rts_core::difficulty::Difficulty;
+
+        let rush = Strategy::rush();
+        let easy = rush.clone().with_handicap(&Difficulty::Easy.handicap());
+        assert!(easy.attack_timing > rush.attack_timing);
+        assert!(easy.aggression_percent < rush.aggression_percent);
+        let normal = rush.clone().with_handicap(&Difficulty::Normal.handicap());
+        assert_eq!(normal.attack_timing, rush.attack_timing);
+        assert_eq!(normal.attack_interval, rush.attack_interval);
+    }
+
+    #[test]
+    fn test_min_wave_size_holds_attacks() {
+        let mut strategy = Strategy::rush();
+        strategy.min_wave_size = Some(10);
+        let executor = StrategyExecutor::new(strategy);
+        let tick = executor.strategy().attack_timing;
+        assert_eq!(
+            executor.decide_action(tick, 9, 0, false, 0, false),
+            TacticalDecision::Hold
+        );
+        assert_eq!(
+            executor.decide_action(tick, 10, 0, false, 0, false),
+            TacticalDecision::Attack
+        );
+    }
+
+    #[test]
+    fn test_expands_when_rich_and_a_site_is_open() {
+        let executor = StrategyExecutor::new(Strategy::fast_expand());
+        let rich = executor.economy().expand_at_resources;
+        assert_eq!(
+            executor.decide_action(0, 0, 0, false, rich, true),
+            TacticalDecision::Expand
+        );
+        assert_eq!(
+            executor.decide_action(0, 0, 0, false, rich - 1, true),
+            TacticalDecision::Hold
+        );
+        assert_eq!(
+            executor.decide_action(0, 0, 0, false, rich, false),
+            TacticalDecision::Hold
+        );
+        assert_eq!(
+            executor.decide_action(0, 0, 0, true, rich, true),
+            TacticalDecision::Defend
+        );
+    }
+
+    #[test]
+    fn test_threat_map_times_attacks_and_picks_targets() {
+        use rts_core::math::Fixed;
+
+        let at = |x: i32| Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(32));
+        let mut threat = InfluenceMap::new((512, 64), 64, 0);
+        threat.add(at(100), 10, true);
+        threat.add(at(300), 15, false);
+
+        let cautious = StrategyExecutor::new(Strategy::turtle());
+        let rush = StrategyExecutor::new(Strategy::rush());
+        assert!(!cautious.strong_enough(&threat));
+        assert!(rush.strong_enough(&threat));
+
+        // The defended target is nearer; the open one wins
+        let targets = [at(300), at(480)];
+        assert_eq!(cautious.pick_target(&threat, &targets), Some(at(480)));
+        assert_eq!(
+            cautious.pick_target(&InfluenceMap::default(), &targets),
+            Some(at(300))
+        );
+    }
+
+    #[test]
+    fn test_composition_adapts_to_known_enemy_army() {
+        let executor = StrategyExecutor::new(Strategy::default());
+        let base = executor.adapted_composition(&BTreeMap::new(), 0);
+        assert_eq!(base["infantry"], 50);
+        assert_eq!(base["ranger"], 30);
+
+        // An all-infantry enemy tips production toward rangers
+        let enemy: BTreeMap<String, u32> = [("infantry".to_string(), 4)].into_iter().collect();
+        let adapted = executor.adapted_composition(&enemy, 4);
+        assert_eq!(adapted["ranger"], 30 + COUNTER_WEIGHT_PERCENT);
+        assert_eq!(adapted["infantry"], 50);
+
+        // Counters outside the composition are not added
+        let rush = StrategyExecutor::new(Strategy {
+            counters: [("infantry".to_string(), "tank".to_string())]
+                .into_iter()
+                .collect(),
+            ..Strategy::rush()
+        });
+        assert!(!rush.adapted_composition(&enemy, 4).contains_key("tank"));
+    }
+
+    #[test]
+    fn test_every_preset_name_resolves() {
+        for name in Strategy::PRESETS {
+            assert!(Strategy::preset(name).is_some(), "{name}");
+        }
+    }
+
     #[test]
     fn test_executor_next_item() {
         let strategy = Strategy::rush();
@@ -613,4 +578,50 @@ mod tests {
         // After interval
         assert!(executor.should_attack(160));
     }
+
+    #[test]
+    fn test_high_priority_items_reserve_resources() {
+        let strategy = Strategy {
+            build_order: vec![BuildOrderItem::Research("plating".to_string())],
+            ..Default::default()
+        };
+        let mut executor = StrategyExecutor::new(strategy);
+        let counts = std::collections::HashMap::new();
+
+        let item = executor.next_item(10, 100, &counts).unwrap();
+        executor.defer(10, item, 300);
+        assert_eq!(executor.reservation().unwrap().amount, 300);
+        assert_eq!(executor.spendable(400, BuildPriority::Low), 100);
+        assert_eq!(executor.spendable(400, BuildPriority::High), 400);
+
+        // Deferring again keeps the one reservation
+        let item = executor.next_item(11, 100, &counts).unwrap();
+        executor.defer(11, item, 300);
+        let item = executor.next_item(50, 300, &counts).unwrap();
+        executor.complete(50, &item);
+        assert!(executor.reservation().is_none());
+        assert_eq!(executor.spendable(400, BuildPriority::Low), 400);
+
+        let trace = executor.drain_trace();
+        assert_eq!(trace.len(), 2);
+        assert!(matches!(
+            &trace[1],
+            ReservationEvent::Released { tick: 50, reservation } if reservation.since_tick == 10
+        ));
+    }
+
+    #[test]
+    fn test_normal_priority_items_are_dropped() {
+        let mut strategy = Strategy::default();
+        strategy
+            .priorities
+            .insert("barracks".to_string(), BuildPriority::Normal);
+        let mut executor = StrategyExecutor::new(strategy);
+
+        executor.defer(0, BuildOrderItem::Building("barracks".to_string()), 200);
+        executor.defer(0, BuildOrderItem::Unit("infantry".to_string()), 50);
+
+        assert!(executor.reservation().is_none());
+        assert!(executor.drain_trace().is_empty());
+    }
 }
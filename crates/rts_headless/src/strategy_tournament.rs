@@ -0,0 +1,413 @@
+//! Round-robin tournaments between built-in AI strategies.
+//!
+//! Every pair of strategies plays `seeds` games with each strategy as
+//! faction A, on the batch runner's seeded skirmish maps. Results form a
+//! cross-table of score rates (draws count as half a win), each with a 95%
+//! Wilson score interval so that a handful of games is not mistaken for a
+//! balance signal.
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use rts_core::factions::FactionId;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::info;
+
+use crate::batch::{run_batch, BatchConfig};
+use crate::strategies::Strategy;
+
+/// z-score of a two-sided 95% confidence interval.
+const Z_95: f64 = 1.96;
+
+/// Errors setting up a strategy tournament.
+#[derive(Debug, Error)]
+pub enum StrategyTournamentError {
+    /// A named strategy is not a built-in preset.
+    #[error("Unknown strategy '{0}'")]
+    UnknownStrategy(String),
+    /// Fewer than two distinct strategies were given.
+    #[error("A tournament needs at least two strategies")]
+    TooFewStrategies,
+}
+
+/// Strategy tournament configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyTournamentConfig {
+    /// Strategy preset names (see [`Strategy::preset`]).
+    pub strategies: Vec<String>,
+    /// Seeds per pairing per side.
+    pub seeds: u32,
+    /// First seed; each pairing uses the same seeds.
+    pub seed_start: u64,
+    /// Tick limit per game; reaching it is a draw.
+    pub max_ticks: u64,
+    /// Batch scenario to play.
+    pub scenario: String,
+    /// Factions played by faction A and faction B.
+    pub matchup: (FactionId, FactionId),
+    /// Path to faction data directory (optional, enables data-driven units).
+    pub faction_data_path: Option<PathBuf>,
+    /// Maximum parallel games (0 = use rayon default).
+    pub parallel_games: u32,
+}
+
+impl Default for StrategyTournamentConfig {
+    fn default() -> Self {
+        Self {
+            strategies: Strategy::PRESETS.iter().map(|s| s.to_string()).collect(),
+            seeds: 10,
+            seed_start: 0,
+            max_ticks: 18000, // 5 minutes at 60 tps
+            scenario: "skirmish_1v1".to_string(),
+            matchup: (FactionId::Continuity, FactionId::Collegium),
+            faction_data_path: None,
+            parallel_games: 0,
+        }
+    }
+}
+
+impl StrategyTournamentConfig {
+    /// Create a config for the given strategies (all presets if empty).
+    #[must_use]
+    pub fn new(strategies: Vec<String>) -> Self {
+        let mut config = Self::default();
+        if !strategies.is_empty() {
+            config.strategies = strategies;
+        }
+        config
+    }
+
+    /// Set seeds per pairing per side.
+    #[must_use]
+    pub fn with_seeds(mut self, seeds: u32) -> Self {
+        self.seeds = seeds;
+        self
+    }
+
+    /// Set the tick limit per game.
+    #[must_use]
+    pub fn with_max_ticks(mut self, max_ticks: u64) -> Self {
+        self.max_ticks = max_ticks;
+        self
+    }
+
+    /// Load faction data from a directory.
+    #[must_use]
+    pub fn with_faction_data(mut self, path: impl Into<PathBuf>) -> Self {
+        self.faction_data_path = Some(path.into());
+        self
+    }
+}
+
+/// Score rate of `successes` out of `games` and its 95% Wilson score
+/// interval, as `(low, high)`. `(0.0, 1.0)` when no games were played.
+#[must_use]
+pub fn wilson_interval(successes: f64, games: u32) -> (f64, f64) {
+    if games == 0 {
+        return (0.0, 1.0);
+    }
+    let n = f64::from(games);
+    let p = (successes / n).clamp(0.0, 1.0);
+    let z2 = Z_95 * Z_95;
+    let centre = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let margin = Z_95 / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    ((centre - margin).max(0.0), (centre + margin).min(1.0))
+}
+
+/// One strategy's results against one opponent, over both sides.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StrategyRecord {
+    /// Strategy the row is for.
+    pub strategy: String,
+    /// Opponent strategy.
+    pub opponent: String,
+    /// Games played.
+    pub games: u32,
+    /// Games won.
+    pub wins: u32,
+    /// Games drawn.
+    pub draws: u32,
+    /// Games lost.
+    pub losses: u32,
+    /// Wins plus half the draws, over games played.
+    pub score: f64,
+    /// Lower bound of the score's 95% confidence interval.
+    pub ci_low: f64,
+    /// Upper bound of the score's 95% confidence interval.
+    pub ci_high: f64,
+}
+
+impl StrategyRecord {
+    fn new(strategy: &str, opponent: &str) -> Self {
+        Self {
+            strategy: strategy.to_string(),
+            opponent: opponent.to_string(),
+            ci_high: 1.0,
+            ..Default::default()
+        }
+    }
+
+    /// Recompute the score and interval from the game counts.
+    fn finish(&mut self) {
+        let points = f64::from(self.wins) + f64::from(self.draws) / 2.0;
+        self.score = if self.games > 0 {
+            points / f64::from(self.games)
+        } else {
+            0.0
+        };
+        (self.ci_low, self.ci_high) = wilson_interval(points, self.games);
+    }
+}
+
+/// Results of a strategy tournament.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyTournamentResults {
+    /// Configuration used.
+    pub config: StrategyTournamentConfig,
+    /// Strategies in table order.
+    pub strategies: Vec<String>,
+    /// One record per ordered pair of different strategies.
+    pub records: Vec<StrategyRecord>,
+    /// Each strategy against the whole field, in table order.
+    pub overall: Vec<StrategyRecord>,
+    /// Games that failed to run.
+    pub failed_games: u32,
+    /// Total runtime.
+    pub duration_seconds: f64,
+}
+
+impl StrategyTournamentResults {
+    /// Save results to a JSON file.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+
+    /// Record of `strategy` against `opponent`.
+    #[must_use]
+    pub fn record(&self, strategy: &str, opponent: &str) -> Option<&StrategyRecord> {
+        self.records
+            .iter()
+            .find(|r| r.strategy == strategy && r.opponent == opponent)
+    }
+
+    /// Render the cross-table as markdown: each cell is the row strategy's
+    /// score against the column strategy, with its 95% interval.
+    #[must_use]
+    pub fn cross_table_markdown(&self) -> String {
+        let cell = |r: &StrategyRecord| {
+            format!(
+                "{:.0}% ({:.0}–{:.0}%)",
+                r.score * 100.0,
+                r.ci_low * 100.0,
+                r.ci_high * 100.0
+            )
+        };
+
+        let mut out = String::from("| vs |");
+        for name in &self.strategies {
+            out.push_str(&format!(" {} |", name));
+        }
+        out.push_str(" Overall |\n|----|");
+        out.push_str(&"---|".repeat(self.strategies.len() + 1));
+        out.push('\n');
+
+        for (row, overall) in self.strategies.iter().zip(&self.overall) {
+            out.push_str(&format!("| {} |", row));
+            for column in &self.strategies {
+                match self.record(row, column) {
+                    Some(record) => out.push_str(&format!(" {} |", cell(record))),
+                    None => out.push_str(" — |"),
+                }
+            }
+            out.push_str(&format!(" {} |\n", cell(overall)));
+        }
+        out
+    }
+}
+
+/// Tally one game into the records of both strategies.
+fn tally(records: &mut [StrategyRecord], a: &str, b: &str, winner: Option<&str>) {
+    for record in records.iter_mut() {
+        let opponent = if record.strategy == a && record.opponent == b {
+            b
+        } else if record.strategy == b && record.opponent == a {
+            a
+        } else {
+            continue;
+        };
+        record.games += 1;
+        match winner {
+            None => record.draws += 1,
+            Some(w) if w == opponent => record.losses += 1,
+            Some(_) => record.wins += 1,
+        }
+    }
+}
+
+/// Run every strategy against every other.
+///
+/// # Errors
+///
+/// Returns an error if a strategy is not a built-in preset or fewer than two
+/// distinct strategies are given.
+pub fn run_strategy_tournament(
+    config: StrategyTournamentConfig,
+) -> Result<StrategyTournamentResults, StrategyTournamentError> {
+    let mut strategies: Vec<String> = Vec::new();
+    for name in &config.strategies {
+        if Strategy::preset(name).is_none() {
+            return Err(StrategyTournamentError::UnknownStrategy(name.clone()));
+        }
+        if !strategies.contains(name) {
+            strategies.push(name.clone());
+        }
+    }
+    if strategies.len() < 2 {
+        return Err(StrategyTournamentError::TooFewStrategies);
+    }
+
+    let start = Instant::now();
+    let faction_a = config.matchup.0.short_name().to_lowercase();
+    let mut records: Vec<StrategyRecord> = Vec::new();
+    for a in &strategies {
+        for b in strategies.iter().filter(|b| *b != a) {
+            records.push(StrategyRecord::new(a, b));
+        }
+    }
+    let mut failed_games = 0;
+
+    info!(
+        strategies = strategies.len(),
+        seeds = config.seeds,
+        "Starting strategy tournament"
+    );
+
+    // Each ordered pair is one side of a pairing
+    for a in &strategies {
+        for b in strategies.iter().filter(|b| *b != a) {
+            let mut batch = BatchConfig::new(&config.scenario, config.seeds)
+                .with_seed(config.seed_start)
+                .with_strategies(a, b)
+                .with_matchup(config.matchup.0, config.matchup.1);
+            batch.max_ticks = config.max_ticks;
+            batch.faction_data_path = config.faction_data_path.clone();
+            batch.parallel_games = config.parallel_games;
+
+            let results = run_batch(batch);
+            failed_games += results.errors.len() as u32;
+            for game in results.all_games() {
+                let winner = game
+                    .winner
+                    .as_ref()
+                    .map(|w| if *w == faction_a { a } else { b });
+                tally(&mut records, a, b, winner.map(String::as_str));
+            }
+            info!(a = %a, b = %b, games = results.summary.total_games, "Pairing side complete");
+        }
+    }
+
+    for record in &mut records {
+        record.finish();
+    }
+    let overall = strategies
+        .iter()
+        .map(|name| {
+            let mut total = StrategyRecord::new(name, "field");
+            for r in records.iter().filter(|r| r.strategy == *name) {
+                total.games += r.games;
+                total.wins += r.wins;
+                total.draws += r.draws;
+                total.losses += r.losses;
+            }
+            total.finish();
+            total
+        })
+        .collect();
+
+    Ok(StrategyTournamentResults {
+        config,
+        strategies,
+        records,
+        overall,
+        failed_games,
+        duration_seconds: start.elapsed().as_secs_f64(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wilson_interval() {
+        let (low, high) = wilson_interval(5.0, 10);
+        assert!((low - 0.237).abs() < 0.001);
+        assert!((high - 0.763).abs() < 0.001);
+
+        // Narrows with more games and stays inside [0, 1]
+        let (low, high) = wilson_interval(500.0, 1000);
+        assert!(high - low < 0.07);
+        let (low, high) = wilson_interval(10.0, 10);
+        assert!(low > 0.6 && high <= 1.0);
+        assert_eq!(wilson_interval(0.0, 0), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_tally_scores_both_sides() {
+        let mut records = vec![
+            StrategyRecord::new("rush", "turtle"),
+            StrategyRecord::new("turtle", "rush"),
+        ];
+        tally(&mut records, "rush", "turtle", Some("rush"));
+        tally(&mut records, "turtle", "rush", Some("rush"));
+        tally(&mut records, "turtle", "rush", None);
+        for record in &mut records {
+            record.finish();
+        }
+
+        assert_eq!(
+            (records[0].games, records[0].wins, records[0].draws),
+            (3, 2, 1)
+        );
+        assert_eq!(records[1].losses, 2);
+        assert!((records[0].score - 2.5 / 3.0).abs() < 1e-9);
+        assert!((records[0].score + records[1].score - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rejects_bad_strategy_lists() {
+        let config = StrategyTournamentConfig::new(vec!["rush".into(), "cheese".into()]);
+        assert!(matches!(
+            run_strategy_tournament(config),
+            Err(StrategyTournamentError::UnknownStrategy(name)) if name == "cheese"
+        ));
+        let config = StrategyTournamentConfig::new(vec!["rush".into(), "rush".into()]);
+        assert!(matches!(
+            run_strategy_tournament(config),
+            Err(StrategyTournamentError::TooFewStrategies)
+        ));
+    }
+
+    #[test]
+    fn test_small_tournament_cross_table() {
+        let config = StrategyTournamentConfig::new(vec!["rush".into(), "turtle".into()])
+            .with_seeds(1)
+            .with_max_ticks(600);
+        let results = run_strategy_tournament(config).unwrap();
+
+        assert_eq!(results.failed_games, 0);
+        assert_eq!(results.records.len(), 2);
+        let rush = results.record("rush", "turtle").unwrap();
+        assert_eq!(rush.games, 2);
+        assert_eq!(results.overall[0].games, 2);
+
+        let table = results.cross_table_markdown();
+        assert!(table.starts_with("| vs | rush | turtle | Overall |"));
+        assert_eq!(table.lines().count(), 4);
+    }
+}
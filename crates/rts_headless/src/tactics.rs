@@ -0,0 +1,426 @@
+//! Behavior-tree tactics for units in headless battles.
+//!
+//! The strategy decides what the army as a whole does (attack, defend,
+//! scout) and orders it by squad. This layer decides how each unit fights
+//! once enemies are near: falling back when badly hurt, kiting with ranged
+//! weapons, focusing fire and coming to the aid of harvesters.
+//!
+//! A [`BehaviorTree`] is a tree of [`Node`]s evaluated over a [`UnitView`],
+//! a plain snapshot of one unit and the enemies around it. Nodes never
+//! touch the simulation, so each can be tested on its own, and evaluation
+//! only uses fixed-point math over views sorted by entity ID, so the same
+//! view always gives the same order.
+
+use rts_core::components::EntityId;
+use rts_core::math::{Fixed, Vec2Fixed};
+use rts_core::strategy::TacticsConfig;
+
+/// An enemy as one unit sees it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Threat {
+    /// Entity ID.
+    pub id: EntityId,
+    /// Current position.
+    pub position: Vec2Fixed,
+    /// Current health.
+    pub health: u32,
+    /// Whether it can shoot back.
+    pub armed: bool,
+}
+
+/// What one unit knows when deciding how to fight.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitView {
+    /// The unit.
+    pub id: EntityId,
+    /// Its position.
+    pub position: Vec2Fixed,
+    /// Its health, in percent of its maximum.
+    pub health_percent: u32,
+    /// Its weapon range.
+    pub range: Fixed,
+    /// Whether its weapon is reloading.
+    pub reloading: bool,
+    /// Where it falls back to.
+    pub home: Vec2Fixed,
+    /// Size of the map, which kiting never steps off.
+    pub map_size: (u32, u32),
+    /// Enemies it can see and hit, by ID.
+    pub enemies: Vec<Threat>,
+    /// Enemies near friendly harvesters, by ID.
+    pub harvester_attackers: Vec<Threat>,
+}
+
+/// Weapon range from which a unit counts as ranged and may kite.
+pub const RANGED_MIN_RANGE: i32 = 100;
+
+impl UnitView {
+    /// Whether the unit fights at range.
+    #[must_use]
+    pub fn is_ranged(&self) -> bool {
+        self.range >= Fixed::from_num(RANGED_MIN_RANGE)
+    }
+
+    /// Enemies within its weapon range.
+    pub fn in_range(&self) -> impl Iterator<Item = &Threat> {
+        let range_sq = self.range * self.range;
+        self.enemies
+            .iter()
+            .filter(move |enemy| self.position.distance_squared(enemy.position) <= range_sq)
+    }
+
+    /// The closest armed enemy, first by ID on ties.
+    #[must_use]
+    pub fn closest_armed(&self) -> Option<&Threat> {
+        self.enemies
+            .iter()
+            .filter(|enemy| enemy.armed)
+            .min_by_key(|enemy| (self.position.distance_squared(enemy.position), enemy.id))
+    }
+}
+
+/// An order for one unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitOrder {
+    /// Shoot this enemy.
+    Attack(EntityId),
+    /// Move here without stopping to fight.
+    MoveTo(Vec2Fixed),
+}
+
+/// Whether a node succeeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The node's check passed or its action applies.
+    Success,
+    /// It did not.
+    Failure,
+}
+
+/// A test on a unit's view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    /// Health below this percent.
+    HealthBelow(u32),
+    /// Has a ranged weapon.
+    Ranged,
+    /// Weapon reloading.
+    Reloading,
+    /// An armed enemy within this fraction of its range (percent).
+    ArmedEnemyWithin(u32),
+    /// Any enemy within weapon range.
+    EnemyInRange,
+    /// Enemies are attacking harvesters.
+    HarvestersThreatened,
+}
+
+impl Condition {
+    /// Check the condition.
+    #[must_use]
+    pub fn check(&self, view: &UnitView) -> bool {
+        match *self {
+            Self::HealthBelow(percent) => view.health_percent < percent,
+            Self::Ranged => view.is_ranged(),
+            Self::Reloading => view.reloading,
+            Self::ArmedEnemyWithin(percent) => {
+                let reach = view.range * Fixed::from_num(percent) / Fixed::from_num(100);
+                view.closest_armed().is_some_and(|enemy| {
+                    view.position.distance_squared(enemy.position) <= reach * reach
+                })
+            }
+            Self::EnemyInRange => view.in_range().next().is_some(),
+            Self::HarvestersThreatened => !view.harvester_attackers.is_empty(),
+        }
+    }
+}
+
+/// Something a unit can do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Fall back home.
+    Retreat,
+    /// Step away from the closest armed enemy, by half its range, unless
+    /// that leaves the map.
+    Kite,
+    /// Shoot the enemy in range closest to dying, armed ones first.
+    FocusFire,
+    /// Go after the closest enemy attacking a harvester.
+    DefendHarvesters,
+}
+
+impl Action {
+    /// The order this action gives, if it applies.
+    #[must_use]
+    pub fn order(&self, view: &UnitView) -> Option<UnitOrder> {
+        match self {
+            Self::Retreat => Some(UnitOrder::MoveTo(view.home)),
+            Self::Kite => {
+                let enemy = view.closest_armed()?;
+                let away = (view.position - enemy.position).normalize();
+                if away == Vec2Fixed::ZERO {
+                    return None;
+                }
+                let step = view.range / Fixed::from_num(2);
+                let to = Vec2Fixed::new(
+                    view.position.x + away.x * step,
+                    view.position.y + away.y * step,
+                );
+                let on_map = |value: Fixed, size: u32| {
+                    value >= Fixed::ZERO && value <= Fixed::from_num(size)
+                };
+                (on_map(to.x, view.map_size.0) && on_map(to.y, view.map_size.1))
+                    .then_some(UnitOrder::MoveTo(to))
+            }
+            Self::FocusFire => view
+                .in_range()
+                .min_by_key(|enemy| (!enemy.armed, enemy.health, enemy.id))
+                .map(|enemy| UnitOrder::Attack(enemy.id)),
+            Self::DefendHarvesters => view
+                .harvester_attackers
+                .iter()
+                .min_by_key(|enemy| (view.position.distance_squared(enemy.position), enemy.id))
+                .map(|enemy| UnitOrder::Attack(enemy.id)),
+        }
+    }
+}
+
+/// A behavior tree node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    /// Succeeds with the first child that succeeds.
+    Selector(Vec<Node>),
+    /// Succeeds if every child succeeds, in order; stops at the first
+    /// failure.
+    Sequence(Vec<Node>),
+    /// Succeeds if the condition holds.
+    Check(Condition),
+    /// Succeeds if the action applies, giving its order.
+    Act(Action),
+}
+
+impl Node {
+    /// Evaluate the node, returning its status and the order it gave, if
+    /// any.
+    #[must_use]
+    pub fn tick(&self, view: &UnitView) -> (Status, Option<UnitOrder>) {
+        match self {
+            Self::Selector(children) => children
+                .iter()
+                .map(|child| child.tick(view))
+                .find(|(status, _)| *status == Status::Success)
+                .unwrap_or((Status::Failure, None)),
+            Self::Sequence(children) => {
+                let mut order = None;
+                for child in children {
+                    let (status, child_order) = child.tick(view);
+                    if status == Status::Failure {
+                        return (Status::Failure, None);
+                    }
+                    order = child_order.or(order);
+                }
+                (Status::Success, order)
+            }
+            Self::Check(condition) => {
+                if condition.check(view) {
+                    (Status::Success, None)
+                } else {
+                    (Status::Failure, None)
+                }
+            }
+            Self::Act(action) => match action.order(view) {
+                Some(order) => (Status::Success, Some(order)),
+                None => (Status::Failure, None),
+            },
+        }
+    }
+}
+
+/// How close (percent of its range) an armed enemy must be before a
+/// reloading ranged unit steps back.
+const KITE_WITHIN_PERCENT: u32 = 60;
+
+/// A unit's tactics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BehaviorTree {
+    /// The root node.
+    pub root: Node,
+}
+
+impl BehaviorTree {
+    /// The tree for `config`: retreat, then kite, then focus fire, then
+    /// defend harvesters, each only if enabled.
+    #[must_use]
+    pub fn from_config(config: &TacticsConfig) -> Self {
+        let mut branches = Vec::new();
+        if config.retreat_below_percent > 0 {
+            branches.push(Node::Sequence(vec![
+                Node::Check(Condition::HealthBelow(config.retreat_below_percent)),
+                Node::Act(Action::Retreat),
+            ]));
+        }
+        if config.kite {
+            branches.push(Node::Sequence(vec![
+                Node::Check(Condition::Ranged),
+                Node::Check(Condition::Reloading),
+                Node::Check(Condition::ArmedEnemyWithin(KITE_WITHIN_PERCENT)),
+                Node::Act(Action::Kite),
+            ]));
+        }
+        if config.focus_fire {
+            branches.push(Node::Sequence(vec![
+                Node::Check(Condition::EnemyInRange),
+                Node::Act(Action::FocusFire),
+            ]));
+        }
+        if config.defend_harvesters_within > 0 {
+            branches.push(Node::Sequence(vec![
+                Node::Check(Condition::HarvestersThreatened),
+                Node::Act(Action::DefendHarvesters),
+            ]));
+        }
+        Self {
+            root: Node::Selector(branches),
+        }
+    }
+
+    /// The order the tree gives the unit, if any; None leaves the unit to
+    /// its squad.
+    #[must_use]
+    pub fn decide(&self, view: &UnitView) -> Option<UnitOrder> {
+        self.root.tick(view).1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: i32, y: i32) -> Vec2Fixed {
+        Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(y))
+    }
+
+    fn threat(id: EntityId, x: i32, health: u32, armed: bool) -> Threat {
+        Threat {
+            id,
+            position: at(500 + x, 500),
+            health,
+            armed,
+        }
+    }
+
+    fn view(range: i32, enemies: Vec<Threat>) -> UnitView {
+        UnitView {
+            id: 1,
+            position: at(500, 500),
+            health_percent: 100,
+            range: Fixed::from_num(range),
+            reloading: false,
+            home: at(0, 500),
+            map_size: (1000, 1000),
+            enemies,
+            harvester_attackers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_focus_fire_prefers_armed_then_weakest() {
+        let unit = view(
+            150,
+            vec![
+                threat(10, 100, 10, false),
+                threat(11, 100, 80, true),
+                threat(12, 120, 40, true),
+                threat(13, 400, 5, true),
+            ],
+        );
+        assert_eq!(
+            Action::FocusFire.order(&unit),
+            Some(UnitOrder::Attack(12)),
+            "armed and weakest in range; 13 is out of range"
+        );
+        assert_eq!(Action::FocusFire.order(&view(150, Vec::new())), None);
+    }
+
+    #[test]
+    fn test_kite_steps_away_from_the_closest_armed_enemy() {
+        let unit = view(
+            200,
+            vec![threat(10, 50, 50, true), threat(11, 20, 50, false)],
+        );
+        assert!(Condition::ArmedEnemyWithin(60).check(&unit));
+        let Some(UnitOrder::MoveTo(to)) = Action::Kite.order(&unit) else {
+            panic!("kiting should move the unit");
+        };
+        assert!((to.x.to_num::<f64>() - 400.0).abs() < 1.0, "{to:?}");
+        assert_eq!(to.y, at(0, 500).y);
+
+        // Backed against the map's edge, it stands its ground
+        let cornered = UnitView {
+            position: at(50, 500),
+            ..unit.clone()
+        };
+        assert_eq!(Action::Kite.order(&cornered), None);
+    }
+
+    #[test]
+    fn test_sequence_and_selector() {
+        let hurt = UnitView {
+            health_percent: 10,
+            ..view(50, vec![threat(10, 20, 50, true)])
+        };
+        let retreat = Node::Sequence(vec![
+            Node::Check(Condition::HealthBelow(20)),
+            Node::Act(Action::Retreat),
+        ]);
+        assert_eq!(
+            retreat.tick(&hurt),
+            (Status::Success, Some(UnitOrder::MoveTo(at(0, 500))))
+        );
+        assert_eq!(retreat.tick(&view(50, Vec::new())), (Status::Failure, None));
+
+        let fight = Node::Selector(vec![retreat, Node::Act(Action::FocusFire)]);
+        assert_eq!(
+            fight.tick(&view(50, vec![threat(10, 20, 50, true)])).1,
+            Some(UnitOrder::Attack(10))
+        );
+    }
+
+    #[test]
+    fn test_tree_priorities() {
+        let tree = BehaviorTree::from_config(&TacticsConfig::default());
+        let enemies = vec![threat(10, 50, 50, true)];
+
+        // Reloading ranged units kite rather than stand and shoot
+        let ranged = UnitView {
+            reloading: true,
+            ..view(200, enemies.clone())
+        };
+        assert!(matches!(tree.decide(&ranged), Some(UnitOrder::MoveTo(_))));
+        let ready = view(200, enemies.clone());
+        assert_eq!(tree.decide(&ready), Some(UnitOrder::Attack(10)));
+
+        // Retreat beats everything
+        let hurt = UnitView {
+            health_percent: 5,
+            ..ready.clone()
+        };
+        assert_eq!(tree.decide(&hurt), Some(UnitOrder::MoveTo(at(0, 500))));
+
+        // With nothing in range, help the harvesters
+        let idle = UnitView {
+            harvester_attackers: vec![threat(20, 900, 50, true)],
+            ..view(50, Vec::new())
+        };
+        assert_eq!(tree.decide(&idle), Some(UnitOrder::Attack(20)));
+        assert_eq!(tree.decide(&view(50, Vec::new())), None);
+
+        // Everything off leaves units to their squads
+        let off = BehaviorTree::from_config(&TacticsConfig {
+            retreat_below_percent: 0,
+            kite: false,
+            focus_fire: false,
+            defend_harvesters_within: 0,
+        });
+        assert_eq!(off.decide(&hurt), None);
+    }
+}
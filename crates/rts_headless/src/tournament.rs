@@ -0,0 +1,1071 @@
+//! Round-robin tournaments between external bot processes.
+//!
+//! Every executable in the bots directory is a competitor. Each pair plays
+//! `rounds` games from each side of a scenario (the standard 1v1 skirmish
+//! unless a scenario file is given), talking the [`crate::bot_api`]
+//! protocol. Results are collected into standings (3 points for a win, 1
+//! for a draw).
+//!
+//! # Isolation
+//!
+//! Each bot runs as its own process with a cleared environment (only `PATH`
+//! is kept), its own directory as working directory, stderr discarded, and is
+//! killed when its match ends. Bots only ever see their own units and enemies
+//! in vision, and orders for entities they do not own are rejected.
+//!
+//! Games run one at a time so that bots are not competing with each other's
+//! matches for CPU while on the clock.
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rts_core::api::EmbeddedSimulation;
+use rts_core::factions::FactionId;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::bot_api::{
+    negotiate, unit_order, BotApiError, BotMessage, Capability, EndReason, Negotiated,
+    RunnerMessage, BOT_API_VERSION,
+};
+use crate::faction_loader::{load_factions_from_path, FactionRegistry};
+use crate::game_runner::scenario_simulation;
+use crate::protocol::EntityState;
+use crate::scenario::{Scenario, ScenarioError};
+
+/// Longest line a bot may send, to stop a runaway bot exhausting memory.
+const MAX_LINE_BYTES: u64 = 1 << 20;
+
+/// Handshakes get this multiple of the per-tick budget (bots may need to load).
+const HANDSHAKE_BUDGET_FACTOR: u32 = 20;
+
+/// Tournament configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentConfig {
+    /// Directory containing one executable per bot.
+    pub bots_dir: PathBuf,
+    /// Wall-clock budget per reply, in milliseconds.
+    pub tick_budget_ms: u64,
+    /// Timeouts (or malformed replies) allowed before forfeit.
+    pub max_timeouts: u32,
+    /// Simulation ticks between observations.
+    pub decision_interval: u64,
+    /// Tick limit per game; reaching it is a draw.
+    pub max_ticks: u64,
+    /// Games per pairing per side.
+    pub rounds: u32,
+    /// Scenario file to play. `None` plays the standard 1v1 skirmish.
+    #[serde(default)]
+    pub scenario: Option<PathBuf>,
+    /// Path to faction data directory. Without it, generic units are used.
+    #[serde(default)]
+    pub faction_data_path: Option<PathBuf>,
+}
+
+impl Default for TournamentConfig {
+    fn default() -> Self {
+        Self {
+            bots_dir: PathBuf::from("bots"),
+            tick_budget_ms: 50,
+            max_timeouts: 10,
+            decision_interval: 10,
+            max_ticks: 18000, // 5 minutes at 60 tps
+            rounds: 1,
+            scenario: None,
+            faction_data_path: None,
+        }
+    }
+}
+
+impl TournamentConfig {
+    /// Create a config for the given bots directory.
+    #[must_use]
+    pub fn new(bots_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            bots_dir: bots_dir.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the per-reply time budget.
+    #[must_use]
+    pub fn with_budget_ms(mut self, ms: u64) -> Self {
+        self.tick_budget_ms = ms;
+        self
+    }
+
+    /// Set the number of timeouts tolerated before forfeit.
+    #[must_use]
+    pub fn with_max_timeouts(mut self, max_timeouts: u32) -> Self {
+        self.max_timeouts = max_timeouts;
+        self
+    }
+
+    /// Set the tick limit per game.
+    #[must_use]
+    pub fn with_max_ticks(mut self, max_ticks: u64) -> Self {
+        self.max_ticks = max_ticks;
+        self
+    }
+
+    /// Set the number of games per pairing per side.
+    #[must_use]
+    pub fn with_rounds(mut self, rounds: u32) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Play every game on the scenario in a RON file.
+    #[must_use]
+    pub fn with_scenario(mut self, path: impl Into<PathBuf>) -> Self {
+        self.scenario = Some(path.into());
+        self
+    }
+
+    /// Set faction data directory.
+    #[must_use]
+    pub fn with_faction_data(mut self, path: impl Into<PathBuf>) -> Self {
+        self.faction_data_path = Some(path.into());
+        self
+    }
+
+    fn budget(&self) -> Duration {
+        Duration::from_millis(self.tick_budget_ms)
+    }
+}
+
+/// A channel to one bot. Implemented for processes, and by in-memory bots in
+/// tests.
+pub trait BotEndpoint {
+    /// Send a message to the bot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bot can no longer be reached.
+    fn send(&mut self, message: &RunnerMessage) -> Result<(), BotApiError>;
+
+    /// Wait up to `timeout` for the bot's next message.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BotApiError::Timeout`] if nothing arrives in time.
+    fn recv(&mut self, timeout: Duration) -> Result<BotMessage, BotApiError>;
+}
+
+/// A bot running as a child process.
+pub struct ProcessBot {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl ProcessBot {
+    /// Launch a bot executable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the process cannot be started.
+    pub fn spawn(path: &Path) -> Result<Self, BotApiError> {
+        let mut command = Command::new(path);
+        command
+            .env_clear()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        if let Some(path_var) = std::env::var_os("PATH") {
+            command.env("PATH", path_var);
+        }
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            command.current_dir(dir);
+        }
+
+        let mut child = command.spawn()?;
+        let stdin = child.stdin.take().ok_or(BotApiError::Disconnected)?;
+        let stdout = child.stdout.take().ok_or(BotApiError::Disconnected)?;
+
+        // Reads happen on a helper thread so replies can be waited on with a
+        // deadline. The thread ends when the bot closes stdout or is killed.
+        let (tx, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(stdout).take(MAX_LINE_BYTES);
+            loop {
+                let mut line = String::new();
+                match reader.read_line(&mut line) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {
+                        if tx.send(line).is_err() {
+                            break;
+                        }
+                        reader.set_limit(MAX_LINE_BYTES);
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            child,
+            stdin,
+            lines,
+        })
+    }
+}
+
+impl BotEndpoint for ProcessBot {
+    fn send(&mut self, message: &RunnerMessage) -> Result<(), BotApiError> {
+        // Discard replies that arrived after their deadline so they are not
+        // mistaken for the answer to this message.
+        while self.lines.try_recv().is_ok() {}
+        self.stdin
+            .write_all(message.to_json_line().as_bytes())
+            .and_then(|()| self.stdin.flush())
+            .map_err(|_| BotApiError::Disconnected)
+    }
+
+    fn recv(&mut self, timeout: Duration) -> Result<BotMessage, BotApiError> {
+        match self.lines.recv_timeout(timeout) {
+            Ok(line) => Ok(BotMessage::from_json(line.trim())?),
+            Err(RecvTimeoutError::Timeout) => Err(BotApiError::Timeout),
+            Err(RecvTimeoutError::Disconnected) => Err(BotApiError::Disconnected),
+        }
+    }
+}
+
+impl Drop for ProcessBot {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// A competitor found in the bots directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BotEntry {
+    /// Name used in standings (the file stem).
+    pub name: String,
+    /// Executable path.
+    pub path: PathBuf,
+}
+
+/// Find every executable file in `dir`, sorted by name.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be read.
+pub fn discover_bots(dir: &Path) -> std::io::Result<Vec<BotEntry>> {
+    let mut bots = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() || !is_executable(&path) {
+            continue;
+        }
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        bots.push(BotEntry { name, path });
+    }
+    bots.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(bots)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "exe" || e == "bat")
+}
+
+/// The scenario every game of a tournament is played on.
+#[derive(Debug, Clone)]
+pub struct Arena {
+    scenario: Scenario,
+    seats: [FactionId; 2],
+    registry: Option<Arc<FactionRegistry>>,
+}
+
+impl Arena {
+    /// Prepare a scenario for play. The first seat plays its first faction
+    /// and the second seat its second.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the scenario has fewer than two factions, both
+    /// are the same faction (which the simulation could not tell apart), or
+    /// its starts do not match the faction data.
+    pub fn new(
+        mut scenario: Scenario,
+        registry: Option<Arc<FactionRegistry>>,
+    ) -> Result<Self, ScenarioError> {
+        let [first, second, ..] = scenario.factions.as_slice() else {
+            return Err(ScenarioError::TooFewFactions(scenario.name.clone()));
+        };
+        let seats = [first.faction(), second.faction()];
+        scenario.set_matchup(seats[0], seats[1])?;
+        // With faction data, starts come from faction presets
+        if let Some(registry) = &registry {
+            scenario.resolve_starts(registry)?;
+        }
+        Ok(Self {
+            scenario,
+            seats,
+            registry,
+        })
+    }
+
+    /// Load the scenario and faction data a tournament config names.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BotApiError::Setup`] if either cannot be loaded or the
+    /// scenario cannot be played.
+    pub fn from_config(config: &TournamentConfig) -> Result<Self, BotApiError> {
+        let setup = |e: &dyn std::fmt::Display| BotApiError::Setup(e.to_string());
+        let scenario = match &config.scenario {
+            Some(path) => Scenario::load(path).map_err(|e| setup(&e))?,
+            None => Scenario::skirmish_1v1(),
+        };
+        let registry = match &config.faction_data_path {
+            Some(path) => Some(Arc::new(
+                load_factions_from_path(path).map_err(|e| setup(&e))?,
+            )),
+            None => None,
+        };
+        Self::new(scenario, registry).map_err(|e| setup(&e))
+    }
+
+    /// Factions played by the first and second seat.
+    #[must_use]
+    pub const fn seats(&self) -> [FactionId; 2] {
+        self.seats
+    }
+
+    fn simulation(&self) -> EmbeddedSimulation {
+        EmbeddedSimulation::from_simulation(scenario_simulation(
+            &self.scenario,
+            0,
+            self.registry.as_deref(),
+        ))
+    }
+}
+
+/// Outcome of one game.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchRecord {
+    /// Bot in the first seat.
+    pub bot_a: String,
+    /// Bot in the second seat.
+    pub bot_b: String,
+    /// Winning bot, or `None` for a draw.
+    pub winner: Option<String>,
+    /// Why the game ended.
+    pub reason: EndReason,
+    /// Ticks simulated.
+    pub ticks: u64,
+    /// Timeouts per seat.
+    pub timeouts: [u32; 2],
+    /// Orders rejected per seat (foreign or dead entities, missing capability).
+    pub rejected_orders: [u32; 2],
+    /// Whether each seat forfeited.
+    pub forfeited: [bool; 2],
+}
+
+/// One row of the standings table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Standing {
+    /// Bot name.
+    pub bot: String,
+    /// Games played.
+    pub played: u32,
+    /// Games won.
+    pub wins: u32,
+    /// Games drawn.
+    pub draws: u32,
+    /// Games lost.
+    pub losses: u32,
+    /// Games forfeited (also counted as losses).
+    pub forfeits: u32,
+    /// 3 per win, 1 per draw.
+    pub points: u32,
+}
+
+/// Results of a full tournament.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentResults {
+    /// Configuration used.
+    pub config: TournamentConfig,
+    /// Every game in play order.
+    pub matches: Vec<MatchRecord>,
+    /// Standings, best first.
+    pub standings: Vec<Standing>,
+}
+
+impl TournamentResults {
+    /// Save results to a JSON file.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+
+    /// Render the standings as a markdown table.
+    #[must_use]
+    pub fn standings_markdown(&self) -> String {
+        let mut out = String::from("| # | Bot | P | W | D | L | Forfeits | Pts |\n");
+        out.push_str("|---|-----|---|---|---|---|----------|-----|\n");
+        for (i, s) in self.standings.iter().enumerate() {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {} | {} |\n",
+                i + 1,
+                s.bot,
+                s.played,
+                s.wins,
+                s.draws,
+                s.losses,
+                s.forfeits,
+                s.points
+            ));
+        }
+        out
+    }
+}
+
+/// Per-seat bookkeeping during a game.
+struct Seat<'a> {
+    bot: &'a mut dyn BotEndpoint,
+    faction: FactionId,
+    negotiated: Option<Negotiated>,
+    timeouts: u32,
+    rejected: u32,
+    forfeited: bool,
+}
+
+impl Seat<'_> {
+    fn strike(&mut self, max_timeouts: u32) {
+        self.timeouts += 1;
+        if self.timeouts >= max_timeouts {
+            self.forfeited = true;
+        }
+    }
+}
+
+/// Play one game between two connected bots on `arena`.
+///
+/// `names` label the seats in the returned record.
+pub fn play_match(
+    names: [&str; 2],
+    bot_a: &mut dyn BotEndpoint,
+    bot_b: &mut dyn BotEndpoint,
+    arena: &Arena,
+    config: &TournamentConfig,
+) -> MatchRecord {
+    let mut seats = [
+        Seat {
+            bot: bot_a,
+            faction: arena.seats[0],
+            negotiated: None,
+            timeouts: 0,
+            rejected: 0,
+            forfeited: false,
+        },
+        Seat {
+            bot: bot_b,
+            faction: arena.seats[1],
+            negotiated: None,
+            timeouts: 0,
+            rejected: 0,
+            forfeited: false,
+        },
+    ];
+
+    handshake(&mut seats, config);
+
+    let mut game = arena.simulation();
+
+    while game.winner().is_none()
+        && game.current_tick() < config.max_ticks
+        && !seats.iter().any(|s| s.forfeited)
+    {
+        decision_point(&mut seats, &mut game, config);
+        if seats.iter().any(|s| s.forfeited) {
+            break;
+        }
+        game.run(config.decision_interval);
+    }
+
+    let (winner_seat, reason) = match (seats[0].forfeited, seats[1].forfeited) {
+        (true, true) => (None, EndReason::Forfeit),
+        (true, false) => (Some(1), EndReason::Forfeit),
+        (false, true) => (Some(0), EndReason::Forfeit),
+        (false, false) => match game.winner() {
+            Some(f) => (
+                seats.iter().position(|s| s.faction == f),
+                EndReason::Elimination,
+            ),
+            None => (None, EndReason::TimeLimit),
+        },
+    };
+
+    let winner_faction = winner_seat.map(|i| seats[i].faction);
+    for seat in &mut seats {
+        let _ = seat.bot.send(&RunnerMessage::GameOver {
+            winner: winner_faction,
+            reason,
+        });
+    }
+
+    MatchRecord {
+        bot_a: names[0].to_string(),
+        bot_b: names[1].to_string(),
+        winner: winner_seat.map(|i| names[i].to_string()),
+        reason,
+        ticks: game.current_tick(),
+        timeouts: [seats[0].timeouts, seats[1].timeouts],
+        rejected_orders: [seats[0].rejected, seats[1].rejected],
+        forfeited: [seats[0].forfeited, seats[1].forfeited],
+    }
+}
+
+fn handshake(seats: &mut [Seat<'_>; 2], config: &TournamentConfig) {
+    for seat in seats.iter_mut() {
+        let hello = RunnerMessage::Hello {
+            api_version: BOT_API_VERSION,
+            faction: seat.faction,
+            capabilities: Capability::ALL.to_vec(),
+            tick_budget_ms: config.tick_budget_ms,
+            max_timeouts: config.max_timeouts,
+            decision_interval: config.decision_interval,
+        };
+        let result = seat.bot.send(&hello).and_then(|()| {
+            let reply = seat.bot.recv(config.budget() * HANDSHAKE_BUDGET_FACTOR)?;
+            negotiate(&Capability::ALL, reply)
+        });
+        match result {
+            Ok(negotiated) => {
+                debug!(faction = ?seat.faction, name = %negotiated.name, "Bot connected");
+                seat.negotiated = Some(negotiated);
+            }
+            Err(e) => {
+                warn!(faction = ?seat.faction, error = %e, "Bot handshake failed");
+                seat.forfeited = true;
+            }
+        }
+    }
+}
+
+fn decision_point(
+    seats: &mut [Seat<'_>; 2],
+    game: &mut EmbeddedSimulation,
+    config: &TournamentConfig,
+) {
+    let sim = game.inner();
+    let state_hash = game.state_hash();
+
+    // Both bots get the same deadline so neither benefits from seat order.
+    for seat in seats.iter_mut() {
+        let hash = seat
+            .negotiated
+            .as_ref()
+            .filter(|n| n.has(Capability::StateHash))
+            .map(|_| state_hash);
+        let mut own = Vec::new();
+        let mut visible_enemies = Vec::new();
+        for (_, entity) in sim.entities().iter() {
+            let Some(member) = entity.faction.filter(|_| entity.projectile.is_none()) else {
+                continue;
+            };
+            let Some(state) = EntityState::from_entity(entity) else {
+                continue;
+            };
+            if member.faction == seat.faction {
+                own.push(state);
+            } else if sim.is_visible_to(seat.faction, entity.id) {
+                visible_enemies.push(state);
+            }
+        }
+        let observation = RunnerMessage::Observation {
+            tick: sim.get_tick(),
+            own,
+            visible_enemies,
+            state_hash: hash,
+        };
+        if seat.bot.send(&observation).is_err() {
+            seat.forfeited = true;
+        }
+    }
+    let deadline = Instant::now() + config.budget();
+
+    for seat in seats.iter_mut().filter(|s| !s.forfeited) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match seat.bot.recv(remaining) {
+            Ok(BotMessage::Orders { orders }) => {
+                let queued_allowed = seat
+                    .negotiated
+                    .as_ref()
+                    .is_some_and(|n| n.has(Capability::QueuedOrders));
+                for order in orders {
+                    let Some((entity, command, queued)) = unit_order(&order) else {
+                        seat.rejected += 1;
+                        continue;
+                    };
+                    let owned = game
+                        .inner()
+                        .get_entity(entity)
+                        .and_then(|e| e.faction)
+                        .is_some_and(|f| f.faction == seat.faction);
+                    let applied = owned
+                        && (!queued || queued_allowed)
+                        && if queued {
+                            game.queue(entity, command).is_ok()
+                        } else {
+                            game.submit(entity, command).is_ok()
+                        };
+                    if !applied {
+                        seat.rejected += 1;
+                    }
+                }
+            }
+            Ok(other) => {
+                debug!(faction = ?seat.faction, message = ?other, "Unexpected bot message");
+                seat.strike(config.max_timeouts);
+            }
+            Err(BotApiError::Disconnected) => seat.forfeited = true,
+            Err(e) => {
+                debug!(faction = ?seat.faction, error = %e, "Bot missed decision");
+                seat.strike(config.max_timeouts);
+            }
+        }
+    }
+}
+
+/// Build standings from match records, best first.
+#[must_use]
+pub fn compute_standings(bots: &[BotEntry], matches: &[MatchRecord]) -> Vec<Standing> {
+    let mut table: BTreeMap<&str, Standing> = bots
+        .iter()
+        .map(|b| {
+            (
+                b.name.as_str(),
+                Standing {
+                    bot: b.name.clone(),
+                    ..Default::default()
+                },
+            )
+        })
+        .collect();
+
+    for record in matches {
+        for (seat, name) in [&record.bot_a, &record.bot_b].into_iter().enumerate() {
+            let Some(row) = table.get_mut(name.as_str()) else {
+                continue;
+            };
+            row.played += 1;
+            match &record.winner {
+                None => {
+                    row.draws += 1;
+                    row.points += 1;
+                }
+                Some(w) if w == name => {
+                    row.wins += 1;
+                    row.points += 3;
+                }
+                Some(_) => row.losses += 1,
+            }
+            if record.forfeited[seat] {
+                row.forfeits += 1;
+            }
+        }
+    }
+
+    let mut standings: Vec<Standing> = table.into_values().collect();
+    standings.sort_by(|a, b| {
+        b.points
+            .cmp(&a.points)
+            .then(b.wins.cmp(&a.wins))
+            .then(a.bot.cmp(&b.bot))
+    });
+    standings
+}
+
+/// Run a full round-robin tournament over the bots in `config.bots_dir`.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be read or holds fewer than two
+/// bots, or the scenario cannot be loaded. Individual bots failing to start
+/// forfeit their games instead.
+pub fn run_tournament(config: TournamentConfig) -> Result<TournamentResults, BotApiError> {
+    let arena = Arena::from_config(&config)?;
+    let bots = discover_bots(&config.bots_dir)?;
+    if bots.len() < 2 {
+        return Err(BotApiError::Unexpected(format!(
+            "need at least two bots in {}, found {}",
+            config.bots_dir.display(),
+            bots.len()
+        )));
+    }
+    info!(bots = bots.len(), "Starting tournament");
+
+    let mut matches = Vec::new();
+    for i in 0..bots.len() {
+        for j in (i + 1)..bots.len() {
+            for _ in 0..config.rounds {
+                for (a, b) in [(&bots[i], &bots[j]), (&bots[j], &bots[i])] {
+                    let record = play_processes(a, b, &arena, &config);
+                    info!(
+                        a = %record.bot_a,
+                        b = %record.bot_b,
+                        winner = ?record.winner,
+                        reason = ?record.reason,
+                        ticks = record.ticks,
+                        "Game finished"
+                    );
+                    matches.push(record);
+                }
+            }
+        }
+    }
+
+    let standings = compute_standings(&bots, &matches);
+    Ok(TournamentResults {
+        config,
+        matches,
+        standings,
+    })
+}
+
+fn play_processes(
+    a: &BotEntry,
+    b: &BotEntry,
+    arena: &Arena,
+    config: &TournamentConfig,
+) -> MatchRecord {
+    match (ProcessBot::spawn(&a.path), ProcessBot::spawn(&b.path)) {
+        (Ok(mut bot_a), Ok(mut bot_b)) => {
+            play_match([&a.name, &b.name], &mut bot_a, &mut bot_b, arena, config)
+        }
+        (a_result, b_result) => {
+            let forfeited = [a_result.is_err(), b_result.is_err()];
+            warn!(a = %a.name, b = %b.name, ?forfeited, "Bot failed to start");
+            MatchRecord {
+                bot_a: a.name.clone(),
+                bot_b: b.name.clone(),
+                winner: match forfeited {
+                    [true, false] => Some(b.name.clone()),
+                    [false, true] => Some(a.name.clone()),
+                    _ => None,
+                },
+                reason: EndReason::Forfeit,
+                ticks: 0,
+                timeouts: [0, 0],
+                rejected_orders: [0, 0],
+                forfeited,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{self, EntityType};
+    use crate::scenario::{BuildingPlacement, UnitPlacement};
+    use std::collections::VecDeque;
+
+    /// In-memory bot that answers each message with a closure.
+    struct FnBot<F: FnMut(&RunnerMessage) -> Option<BotMessage>> {
+        respond: F,
+        pending: VecDeque<BotMessage>,
+    }
+
+    impl<F: FnMut(&RunnerMessage) -> Option<BotMessage>> FnBot<F> {
+        fn new(respond: F) -> Self {
+            Self {
+                respond,
+                pending: VecDeque::new(),
+            }
+        }
+    }
+
+    impl<F: FnMut(&RunnerMessage) -> Option<BotMessage>> BotEndpoint for FnBot<F> {
+        fn send(&mut self, message: &RunnerMessage) -> Result<(), BotApiError> {
+            self.pending.extend((self.respond)(message));
+            Ok(())
+        }
+
+        fn recv(&mut self, _timeout: Duration) -> Result<BotMessage, BotApiError> {
+            self.pending.pop_front().ok_or(BotApiError::Timeout)
+        }
+    }
+
+    fn greet(name: &str) -> BotMessage {
+        BotMessage::Handshake {
+            api_version: BOT_API_VERSION,
+            name: name.to_string(),
+            capabilities: vec![Capability::Orders],
+        }
+    }
+
+    fn idle_bot() -> FnBot<impl FnMut(&RunnerMessage) -> Option<BotMessage>> {
+        FnBot::new(|msg| match msg {
+            RunnerMessage::Hello { .. } => Some(greet("idle")),
+            RunnerMessage::Observation { .. } => Some(BotMessage::Orders { orders: vec![] }),
+            RunnerMessage::GameOver { .. } => None,
+        })
+    }
+
+    fn is_building(entity: &EntityState) -> bool {
+        matches!(entity.entity_type, EntityType::Building { .. })
+    }
+
+    /// Marches on the mirrored depot position, then attacks it once seen.
+    fn rush_bot() -> FnBot<impl FnMut(&RunnerMessage) -> Option<BotMessage>> {
+        FnBot::new(|msg| match msg {
+            RunnerMessage::Hello { .. } => Some(greet("rush")),
+            RunnerMessage::Observation {
+                own,
+                visible_enemies,
+                ..
+            } => {
+                let depot = visible_enemies.iter().find(|e| is_building(e));
+                let home = own.iter().find(|e| is_building(e))?;
+                let orders = own
+                    .iter()
+                    .filter(|e| !is_building(e))
+                    .map(|e| match depot {
+                        Some(depot) => protocol::Command::Attack {
+                            entity_id: e.id,
+                            target_id: depot.id,
+                            queue: false,
+                            force: false,
+                        },
+                        None => protocol::Command::Move {
+                            entity_id: e.id,
+                            target_x: 512.0 - home.x,
+                            target_y: home.y,
+                            queue: false,
+                        },
+                    })
+                    .collect();
+                Some(BotMessage::Orders { orders })
+            }
+            RunnerMessage::GameOver { .. } => None,
+        })
+    }
+
+    /// A depot and four fighters per side, facing each other.
+    fn duel_scenario() -> Scenario {
+        let mut scenario = Scenario::default();
+        for (setup, (depot_x, unit_x)) in scenario.factions.iter_mut().zip([(64, 96), (448, 416)]) {
+            setup.preset = None;
+            setup.starting_buildings = vec![BuildingPlacement::new("depot", depot_x, 256)];
+            setup.starting_units = [208, 240, 272, 304]
+                .into_iter()
+                .map(|y| UnitPlacement::new("infantry", unit_x, y, 1))
+                .collect();
+        }
+        scenario
+    }
+
+    fn arena() -> Arena {
+        Arena::new(duel_scenario(), None).unwrap()
+    }
+
+    fn config() -> TournamentConfig {
+        TournamentConfig::new("unused")
+            .with_budget_ms(1)
+            .with_max_timeouts(3)
+            .with_max_ticks(600)
+    }
+
+    #[test]
+    fn test_idle_bots_draw_at_time_limit() {
+        let record = play_match(
+            ["a", "b"],
+            &mut idle_bot(),
+            &mut idle_bot(),
+            &arena(),
+            &config(),
+        );
+        assert_eq!(record.reason, EndReason::TimeLimit);
+        assert_eq!(record.winner, None);
+        assert_eq!(record.ticks, 600);
+    }
+
+    #[test]
+    fn test_silent_bot_forfeits_after_max_timeouts() {
+        let mut silent = FnBot::new(|msg| match msg {
+            RunnerMessage::Hello { .. } => Some(greet("silent")),
+            _ => None,
+        });
+        let record = play_match(
+            ["a", "silent"],
+            &mut idle_bot(),
+            &mut silent,
+            &arena(),
+            &config(),
+        );
+        assert_eq!(record.reason, EndReason::Forfeit);
+        assert_eq!(record.winner.as_deref(), Some("a"));
+        assert_eq!(record.timeouts, [0, 3]);
+        assert_eq!(record.forfeited, [false, true]);
+    }
+
+    #[test]
+    fn test_bad_handshake_forfeits() {
+        let mut old = FnBot::new(|msg| match msg {
+            RunnerMessage::Hello { .. } => Some(BotMessage::Handshake {
+                api_version: BOT_API_VERSION + 1,
+                name: "future".to_string(),
+                capabilities: vec![Capability::Orders],
+            }),
+            _ => Some(BotMessage::Orders { orders: vec![] }),
+        });
+        let record = play_match(["old", "b"], &mut old, &mut idle_bot(), &arena(), &config());
+        assert_eq!(record.winner.as_deref(), Some("b"));
+        assert_eq!(record.ticks, 0);
+    }
+
+    #[test]
+    fn test_orders_for_enemy_units_are_rejected() {
+        let mut thief = FnBot::new(|msg| match msg {
+            RunnerMessage::Hello { .. } => Some(greet("thief")),
+            RunnerMessage::Observation {
+                visible_enemies, ..
+            } => Some(BotMessage::Orders {
+                orders: visible_enemies
+                    .iter()
+                    .map(|e| protocol::Command::Stop { entity_id: e.id })
+                    .collect(),
+            }),
+            RunnerMessage::GameOver { .. } => None,
+        });
+        let mut cfg = config();
+        cfg.max_ticks = 2000;
+        let record = play_match(
+            ["thief", "rush"],
+            &mut thief,
+            &mut rush_bot(),
+            &arena(),
+            &cfg,
+        );
+        assert!(record.rejected_orders[0] > 0);
+        assert_eq!(record.rejected_orders[1], 0);
+    }
+
+    #[test]
+    fn test_rush_beats_idle() {
+        let mut cfg = config();
+        cfg.max_ticks = 5000;
+        let record = play_match(
+            ["rush", "idle"],
+            &mut rush_bot(),
+            &mut idle_bot(),
+            &arena(),
+            &cfg,
+        );
+        assert_eq!(record.reason, EndReason::Elimination);
+        assert_eq!(record.winner.as_deref(), Some("rush"));
+    }
+
+    #[test]
+    fn test_arena_seats_the_scenario_factions() {
+        let mut scenario = duel_scenario();
+        scenario
+            .set_matchup(FactionId::Tinkers, FactionId::Continuity)
+            .unwrap();
+        let arena = Arena::new(scenario, None).unwrap();
+        assert_eq!(arena.seats(), [FactionId::Tinkers, FactionId::Continuity]);
+
+        let mut mirror = duel_scenario();
+        mirror.factions[1].faction_id = mirror.factions[0].faction_id.clone();
+        assert!(matches!(
+            Arena::new(mirror, None),
+            Err(ScenarioError::MirrorMatchup(FactionId::Continuity))
+        ));
+
+        let missing = TournamentConfig::new("unused").with_scenario("no/such/scenario.ron");
+        assert!(matches!(
+            Arena::from_config(&missing),
+            Err(BotApiError::Setup(_))
+        ));
+    }
+
+    #[test]
+    fn test_standings_order() {
+        let bots: Vec<BotEntry> = ["a", "b", "c"]
+            .iter()
+            .map(|n| BotEntry {
+                name: (*n).to_string(),
+                path: PathBuf::from(n),
+            })
+            .collect();
+        let record = |a: &str, b: &str, winner: Option<&str>| MatchRecord {
+            bot_a: a.to_string(),
+            bot_b: b.to_string(),
+            winner: winner.map(str::to_string),
+            reason: EndReason::Elimination,
+            ticks: 1,
+            timeouts: [0, 0],
+            rejected_orders: [0, 0],
+            forfeited: [false, false],
+        };
+        let standings = compute_standings(
+            &bots,
+            &[
+                record("a", "b", Some("b")),
+                record("b", "c", None),
+                record("c", "a", Some("c")),
+            ],
+        );
+        let order: Vec<_> = standings.iter().map(|s| s.bot.as_str()).collect();
+        assert_eq!(order, ["b", "c", "a"]);
+        assert_eq!(standings[0].points, 4);
+        assert_eq!(standings[2].losses, 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_bots_tournament() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let idle = r#"#!/bin/sh
+read hello
+echo '{"type":"handshake","api_version":2,"name":"sh","capabilities":["orders"]}'
+while read line; do
+  echo '{"type":"orders","orders":[]}'
+done
+"#;
+        for name in ["alpha", "beta"] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, idle).unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        std::fs::write(dir.path().join("notes.txt"), "not a bot").unwrap();
+        let scenario = dir.path().join("duel.ron");
+        std::fs::write(&scenario, ron::to_string(&duel_scenario()).unwrap()).unwrap();
+
+        let config = TournamentConfig::new(dir.path())
+            .with_scenario(&scenario)
+            .with_budget_ms(500)
+            .with_max_ticks(100);
+        let results = run_tournament(config).unwrap();
+
+        assert_eq!(results.matches.len(), 2);
+        assert!(results.matches.iter().all(|m| m.winner.is_none()));
+        assert_eq!(results.standings.len(), 2);
+        assert!(results.standings.iter().all(|s| s.draws == 2));
+        assert!(results.standings_markdown().contains("| alpha |"));
+    }
+}
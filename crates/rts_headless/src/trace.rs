@@ -0,0 +1,288 @@
+//! Per-tick state hash traces for cross-platform determinism checks.
+//!
+//! Lockstep multiplayer only works if every machine reaches the same state
+//! after every tick. A [`HashTrace`] records the simulation's state hash
+//! after each tick of one scenario and seed, together with the engine
+//! [`Fingerprint`] and the platform it was produced on. A trace written on
+//! one machine is a golden reference for another: replaying the same game
+//! and [comparing](HashTrace::compare) pinpoints the first tick whose state
+//! differs, rather than only noticing that the final result does.
+//!
+//! Traces are gathered by the [`HashTracer`] observer and saved as JSON.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use rts_core::fingerprint::Fingerprint;
+use rts_core::simulation::{Simulation, TickEvents};
+
+use crate::metrics::GameMetrics;
+use crate::observer::GameObserver;
+
+/// Observations key used by [`HashTracer`].
+pub const HASH_TRACE_KEY: &str = "hash_trace";
+
+/// Ways a trace can disagree with a golden one.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TraceMismatch {
+    /// The traces are of different games.
+    #[error("trace is of {actual_scenario} seed {actual_seed}, expected {scenario} seed {seed}")]
+    DifferentGame {
+        /// Golden scenario.
+        scenario: String,
+        /// Golden seed.
+        seed: u64,
+        /// Scenario traced.
+        actual_scenario: String,
+        /// Seed traced.
+        actual_seed: u64,
+    },
+
+    /// The traces were made with different engines or data.
+    #[error("traces were made with different builds: {0}")]
+    Fingerprint(String),
+
+    /// The state first differed after this tick.
+    #[error("state diverged at tick {tick} (expected hash {expected:#018x}, got {actual:#018x})")]
+    Diverged {
+        /// First tick whose hash differs.
+        tick: u64,
+        /// Golden hash after the tick.
+        expected: u64,
+        /// Hash traced after the tick.
+        actual: u64,
+    },
+
+    /// Every shared tick agrees, but one game ran longer.
+    #[error("trace ends at tick {actual}, expected {expected}")]
+    Length {
+        /// Last tick in the golden trace.
+        expected: u64,
+        /// Last tick traced.
+        actual: u64,
+    },
+}
+
+/// State hashes of one game, tick by tick.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HashTrace {
+    /// Scenario played.
+    pub scenario: String,
+    /// Seed played.
+    pub seed: u64,
+    /// Engine and data the game ran on.
+    pub fingerprint: Fingerprint,
+    /// Platform the trace was made on, as `<os>-<arch>`.
+    pub platform: String,
+    /// Hashes recorded by the game's [`HashTracer`].
+    #[serde(flatten)]
+    pub ticks: TickHashes,
+}
+
+impl HashTrace {
+    /// Trace of `scenario` and `seed` made on this machine.
+    #[must_use]
+    pub fn new(scenario: impl Into<String>, seed: u64, ticks: TickHashes) -> Self {
+        Self {
+            scenario: scenario.into(),
+            seed,
+            fingerprint: Fingerprint::engine(),
+            platform: current_platform(),
+            ticks,
+        }
+    }
+
+    /// Tick of the last hash, or None for an empty trace.
+    #[must_use]
+    pub fn last_tick(&self) -> Option<u64> {
+        self.ticks.last_tick()
+    }
+
+    /// Check this trace against a golden one.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first way the traces disagree: a different game or
+    /// build, the first tick whose hash differs, or a different length.
+    pub fn compare(&self, golden: &HashTrace) -> Result<(), TraceMismatch> {
+        if self.scenario != golden.scenario || self.seed != golden.seed {
+            return Err(TraceMismatch::DifferentGame {
+                scenario: golden.scenario.clone(),
+                seed: golden.seed,
+                actual_scenario: self.scenario.clone(),
+                actual_seed: self.seed,
+            });
+        }
+        golden
+            .fingerprint
+            .check(&self.fingerprint)
+            .map_err(|e| TraceMismatch::Fingerprint(e.to_string()))?;
+        if let Some((tick, expected, actual)) = golden
+            .ticks
+            .iter()
+            .zip(self.ticks.iter())
+            .find(|((tick, expected), (other, actual))| tick != other || expected != actual)
+            .map(|((tick, expected), (_, actual))| (tick, expected, actual))
+        {
+            return Err(TraceMismatch::Diverged {
+                tick,
+                expected,
+                actual,
+            });
+        }
+        if self.last_tick() != golden.last_tick() {
+            return Err(TraceMismatch::Length {
+                expected: golden.last_tick().unwrap_or(0),
+                actual: self.last_tick().unwrap_or(0),
+            });
+        }
+        Ok(())
+    }
+
+    /// Write the trace as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json)
+            .map_err(|e| format!("Failed to write trace {}: {}", path.display(), e))
+    }
+
+    /// Read a trace written by [`save`](Self::save).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a trace.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read trace {}: {}", path.display(), e))?;
+        serde_json::from_str(&json)
+            .map_err(|e| format!("Failed to parse trace {}: {}", path.display(), e))
+    }
+}
+
+/// Consecutive per-tick state hashes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TickHashes {
+    /// Tick of the first hash.
+    pub first_tick: u64,
+    /// Hash after each tick from `first_tick` on.
+    pub hashes: Vec<u64>,
+}
+
+impl TickHashes {
+    /// `(tick, hash)` pairs in tick order.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        (self.first_tick..).zip(self.hashes.iter().copied())
+    }
+
+    /// Tick of the last hash, or None if there are none.
+    #[must_use]
+    pub fn last_tick(&self) -> Option<u64> {
+        (!self.hashes.is_empty()).then(|| self.first_tick + self.hashes.len() as u64 - 1)
+    }
+}
+
+/// Records the state hash after every tick. The hashes are recorded under
+/// [`HASH_TRACE_KEY`] at game end.
+#[derive(Debug, Clone, Default)]
+pub struct HashTracer {
+    ticks: TickHashes,
+}
+
+impl HashTracer {
+    /// Hashes recorded so far.
+    #[must_use]
+    pub fn ticks(&self) -> &TickHashes {
+        &self.ticks
+    }
+}
+
+impl GameObserver for HashTracer {
+    fn on_tick(&mut self, sim: &Simulation, _events: &TickEvents) {
+        if self.ticks.hashes.is_empty() {
+            self.ticks.first_tick = sim.get_tick();
+        }
+        self.ticks.hashes.push(sim.state_hash());
+    }
+
+    fn on_game_end(&mut self, _sim: &Simulation, metrics: &mut GameMetrics) {
+        if let Ok(ticks) = serde_json::to_value(&self.ticks) {
+            metrics
+                .observations
+                .insert(HASH_TRACE_KEY.to_string(), ticks);
+        }
+    }
+}
+
+/// This machine's platform, as `<os>-<arch>`.
+#[must_use]
+pub fn current_platform() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trace(hashes: &[u64]) -> HashTrace {
+        HashTrace::new(
+            "skirmish_1v1",
+            7,
+            TickHashes {
+                first_tick: 1,
+                hashes: hashes.to_vec(),
+            },
+        )
+    }
+
+    #[test]
+    fn test_compare_finds_first_divergent_tick() {
+        let golden = trace(&[10, 20, 30, 40]);
+        assert_eq!(trace(&[10, 20, 30, 40]).compare(&golden), Ok(()));
+        assert_eq!(
+            trace(&[10, 20, 31, 41]).compare(&golden),
+            Err(TraceMismatch::Diverged {
+                tick: 3,
+                expected: 30,
+                actual: 31,
+            })
+        );
+        assert_eq!(
+            trace(&[10, 20]).compare(&golden),
+            Err(TraceMismatch::Length {
+                expected: 4,
+                actual: 2,
+            })
+        );
+
+        let mut other_seed = trace(&[10, 20, 30, 40]);
+        other_seed.seed = 8;
+        assert!(matches!(
+            other_seed.compare(&golden),
+            Err(TraceMismatch::DifferentGame { .. })
+        ));
+        let mut other_build = trace(&[10, 20, 30, 40]);
+        other_build.fingerprint.sim_version += 1;
+        assert!(matches!(
+            other_build.compare(&golden),
+            Err(TraceMismatch::Fingerprint(_))
+        ));
+    }
+
+    #[test]
+    fn test_trace_roundtrips_through_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trace.json");
+        let original = trace(&[u64::MAX, 1, 2]);
+        original.save(&path).unwrap();
+        let loaded = HashTrace::load(&path).unwrap();
+        assert_eq!(loaded, original);
+        assert_eq!(loaded.last_tick(), Some(3));
+        assert_eq!(loaded.platform, current_platform());
+    }
+}
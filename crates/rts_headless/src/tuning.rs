@@ -0,0 +1,814 @@
+//! Automated balance tuning by parameter search.
+//!
+//! A [`TuneConfig`] names a matchup, the score faction A should reach in
+//! it (wins plus half the draws, over games played) and the unit stats the
+//! search may move. Each candidate scales those stats to a percentage of
+//! their faction-data value and plays a batch over the same seeds, so
+//! candidates differ only in their stats. The search is either a grid over
+//! every combination of percentages or a hill-climb that moves one stat a
+//! step at a time, halving the step when no neighbour gets closer.
+//!
+//! The closest candidate is written out as a [`TunePatch`]: the new values
+//! to put in the faction RON files. Faction data on disk is never edited.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use rts_core::data::FactionData;
+use rts_core::factions::FactionId;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::info;
+
+use crate::batch::{run_batch, BatchConfig};
+use crate::faction_loader::{load_factions_from_path, FactionRegistry};
+use crate::strategy_tournament::wilson_interval;
+
+/// Errors setting up or running a tuning search.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TuningError {
+    /// No stats were given to tune.
+    #[error("Nothing to tune: give at least one parameter")]
+    NoParams,
+    /// A parameter is not `faction.unit.stat`.
+    #[error("Bad parameter '{0}': expected faction.unit.stat with stat cost, damage or health")]
+    BadParam(String),
+    /// The search range or step is unusable.
+    #[error("Bad search range {min}%..={max}% with step {step}%")]
+    BadRange {
+        /// Lowest percentage.
+        min: u32,
+        /// Highest percentage.
+        max: u32,
+        /// Step between percentages.
+        step: u32,
+    },
+    /// The grid has more candidates than the evaluation budget.
+    #[error("Grid has {candidates} candidates, more than the {max} evaluations allowed")]
+    GridTooLarge {
+        /// Candidates in the grid.
+        candidates: u64,
+        /// Evaluations allowed.
+        max: u32,
+    },
+    /// Faction data could not be loaded.
+    #[error("Failed to load faction data: {0}")]
+    FactionData(String),
+    /// A parameter names a faction missing from the faction data.
+    #[error("No faction data for {0:?}")]
+    UnknownFaction(FactionId),
+    /// A parameter names a unit its faction does not have.
+    #[error("{faction:?} has no unit '{unit}'")]
+    UnknownUnit {
+        /// Faction named.
+        faction: FactionId,
+        /// Unit named.
+        unit: String,
+    },
+    /// A damage parameter names a unit without a weapon.
+    #[error("{0} has no weapon to tune")]
+    NoWeapon(String),
+    /// Every game of a candidate failed.
+    #[error("No games finished for candidate {0:?}")]
+    NoGames(Vec<u32>),
+}
+
+/// Unit stat a search may move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitStat {
+    /// Resource cost.
+    Cost,
+    /// Weapon damage per shot.
+    Damage,
+    /// Maximum health.
+    Health,
+}
+
+impl UnitStat {
+    /// Name used in parameters.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cost => "cost",
+            Self::Damage => "damage",
+            Self::Health => "health",
+        }
+    }
+}
+
+/// One unit stat of one faction, written `faction.unit.stat`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TunableParam {
+    /// Faction whose data holds the unit.
+    pub faction: FactionId,
+    /// Unit ID in the faction data.
+    pub unit: String,
+    /// Stat to move.
+    pub stat: UnitStat,
+}
+
+impl FromStr for TunableParam {
+    type Err = TuningError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || TuningError::BadParam(s.to_string());
+        let mut parts = s.split('.');
+        let (Some(faction), Some(unit), Some(stat), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(bad());
+        };
+        let stat = match stat {
+            "cost" => UnitStat::Cost,
+            "damage" => UnitStat::Damage,
+            "health" => UnitStat::Health,
+            _ => return Err(bad()),
+        };
+        if unit.is_empty() {
+            return Err(bad());
+        }
+        Ok(Self {
+            faction: FactionId::from_name(faction).ok_or_else(bad)?,
+            unit: unit.to_string(),
+            stat,
+        })
+    }
+}
+
+impl fmt::Display for TunableParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}",
+            self.faction.short_name().to_lowercase(),
+            self.unit,
+            self.stat.name()
+        )
+    }
+}
+
+impl TunableParam {
+    /// The stat's value in `data`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the unit is missing or, for damage, unarmed.
+    pub fn value(&self, data: &FactionData) -> Result<u32, TuningError> {
+        let unit = data
+            .get_unit(&self.unit)
+            .ok_or_else(|| self.unknown_unit())?;
+        match self.stat {
+            UnitStat::Cost => Ok(unit.cost),
+            UnitStat::Health => Ok(unit.health),
+            UnitStat::Damage => unit
+                .combat
+                .as_ref()
+                .map(|c| c.damage)
+                .ok_or_else(|| TuningError::NoWeapon(self.to_string())),
+        }
+    }
+
+    /// Set the stat in `data`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the unit is missing or, for damage, unarmed.
+    pub fn set(&self, data: &mut FactionData, value: u32) -> Result<(), TuningError> {
+        let unknown = self.unknown_unit();
+        let unit = data
+            .units
+            .iter_mut()
+            .find(|u| u.id == self.unit)
+            .ok_or(unknown)?;
+        match self.stat {
+            UnitStat::Cost => unit.cost = value,
+            UnitStat::Health => unit.health = value,
+            UnitStat::Damage => {
+                unit.combat
+                    .as_mut()
+                    .ok_or_else(|| TuningError::NoWeapon(self.to_string()))?
+                    .damage = value;
+            }
+        }
+        Ok(())
+    }
+
+    fn unknown_unit(&self) -> TuningError {
+        TuningError::UnknownUnit {
+            faction: self.faction,
+            unit: self.unit.clone(),
+        }
+    }
+}
+
+/// `value` scaled to `pct` percent, rounded, and never below 1.
+#[must_use]
+pub fn scale(value: u32, pct: u32) -> u32 {
+    ((u64::from(value) * u64::from(pct) + 50) / 100).max(1) as u32
+}
+
+/// How candidates are chosen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMethod {
+    /// Every combination of percentages in the range.
+    Grid,
+    /// Step one stat at a time towards the target from the baseline.
+    #[default]
+    HillClimb,
+}
+
+impl FromStr for SearchMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "grid" => Ok(Self::Grid),
+            "hill-climb" | "hill_climb" => Ok(Self::HillClimb),
+            _ => Err(format!(
+                "Unknown search method '{}' (use grid or hill-climb)",
+                s
+            )),
+        }
+    }
+}
+
+/// Tuning search configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TuneConfig {
+    /// Faction A and faction B; the target is faction A's score.
+    pub matchup: (FactionId, FactionId),
+    /// Score faction A should reach (wins plus half the draws).
+    pub target_score: f64,
+    /// How far from the target a score may land and still pass.
+    pub tolerance: f64,
+    /// Stats the search may move.
+    pub params: Vec<TunableParam>,
+    /// How candidates are chosen.
+    pub method: SearchMethod,
+    /// Lowest percentage of a stat's baseline to try.
+    pub min_pct: u32,
+    /// Highest percentage of a stat's baseline to try.
+    pub max_pct: u32,
+    /// Grid spacing, and the hill-climb's first step, in percent.
+    pub step_pct: u32,
+    /// Most candidates to evaluate, baseline included.
+    pub max_evaluations: u32,
+    /// Games per candidate.
+    pub games: u32,
+    /// First seed; every candidate plays the same seeds.
+    pub seed_start: u64,
+    /// Tick limit per game.
+    pub max_ticks: u64,
+    /// Batch scenario to play.
+    pub scenario: String,
+    /// Faction data directory holding the stats to tune.
+    pub faction_data_path: PathBuf,
+    /// Maximum parallel games (0 = one per CPU).
+    pub parallel_games: u32,
+}
+
+impl TuneConfig {
+    /// Tune `params` of the faction data in `faction_data` towards an even
+    /// Continuity–Collegium matchup.
+    #[must_use]
+    pub fn new(faction_data: impl Into<PathBuf>, params: Vec<TunableParam>) -> Self {
+        Self {
+            matchup: (FactionId::Continuity, FactionId::Collegium),
+            target_score: 0.5,
+            tolerance: 0.03,
+            params,
+            method: SearchMethod::default(),
+            min_pct: 50,
+            max_pct: 200,
+            step_pct: 10,
+            max_evaluations: 30,
+            games: 40,
+            seed_start: 0,
+            max_ticks: 18000, // 5 minutes at 60 tps
+            scenario: "skirmish_1v1".to_string(),
+            faction_data_path: faction_data.into(),
+            parallel_games: 0,
+        }
+    }
+
+    /// Set the matchup; the target is faction A's score.
+    #[must_use]
+    pub fn with_matchup(mut self, a: FactionId, b: FactionId) -> Self {
+        self.matchup = (a, b);
+        self
+    }
+
+    /// Set the target score and tolerance.
+    #[must_use]
+    pub fn with_target(mut self, score: f64, tolerance: f64) -> Self {
+        self.target_score = score;
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Set the search method.
+    #[must_use]
+    pub fn with_method(mut self, method: SearchMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Set the grid spacing and first hill-climb step, in percent.
+    #[must_use]
+    pub fn with_step(mut self, step_pct: u32) -> Self {
+        self.step_pct = step_pct;
+        self
+    }
+
+    /// Set the most candidates to evaluate.
+    #[must_use]
+    pub fn with_max_evaluations(mut self, max: u32) -> Self {
+        self.max_evaluations = max;
+        self
+    }
+
+    /// Set games per candidate.
+    #[must_use]
+    pub fn with_games(mut self, games: u32) -> Self {
+        self.games = games;
+        self
+    }
+
+    /// Set the first seed.
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed_start = seed;
+        self
+    }
+
+    /// Set the tick limit per game.
+    #[must_use]
+    pub fn with_max_ticks(mut self, max_ticks: u64) -> Self {
+        self.max_ticks = max_ticks;
+        self
+    }
+
+    fn validate(&self) -> Result<(), TuningError> {
+        if self.params.is_empty() {
+            return Err(TuningError::NoParams);
+        }
+        if self.step_pct == 0 || self.min_pct == 0 || self.min_pct > 100 || self.max_pct < 100 {
+            return Err(TuningError::BadRange {
+                min: self.min_pct,
+                max: self.max_pct,
+                step: self.step_pct,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// One evaluated set of stat percentages.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Candidate {
+    /// Percentage of each parameter's baseline, in parameter order.
+    pub scales: Vec<u32>,
+    /// Games finished.
+    pub games: u32,
+    /// Games faction A won.
+    pub wins: u32,
+    /// Games without a winner.
+    pub draws: u32,
+    /// Faction A's score: wins plus half the draws, over games.
+    pub score: f64,
+    /// Lower bound of the score's 95% confidence interval.
+    pub ci_low: f64,
+    /// Upper bound of the score's 95% confidence interval.
+    pub ci_high: f64,
+}
+
+impl Candidate {
+    /// Candidate with `scales` and the given game counts.
+    #[must_use]
+    pub fn new(scales: Vec<u32>, games: u32, wins: u32, draws: u32) -> Self {
+        let points = f64::from(wins) + f64::from(draws) / 2.0;
+        let (ci_low, ci_high) = wilson_interval(points, games);
+        Self {
+            scales,
+            games,
+            wins,
+            draws,
+            score: if games > 0 {
+                points / f64::from(games)
+            } else {
+                0.0
+            },
+            ci_low,
+            ci_high,
+        }
+    }
+
+    /// Distance of the score from `target`.
+    #[must_use]
+    pub fn error(&self, target: f64) -> f64 {
+        (self.score - target).abs()
+    }
+
+    /// Total percentage points moved away from the baseline.
+    fn change(&self) -> u32 {
+        self.scales.iter().map(|&s| s.abs_diff(100)).sum()
+    }
+
+    /// Whether this candidate is closer to `target` than `other`, preferring
+    /// smaller changes when both are equally close.
+    fn beats(&self, other: &Candidate, target: f64) -> bool {
+        let (mine, theirs) = (self.error(target), other.error(target));
+        mine < theirs - f64::EPSILON
+            || ((mine - theirs).abs() <= f64::EPSILON && self.change() < other.change())
+    }
+}
+
+/// One stat's suggested new value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatChange {
+    /// Faction whose data holds the unit.
+    pub faction: FactionId,
+    /// Unit ID in the faction data.
+    pub unit: String,
+    /// Stat changed.
+    pub stat: UnitStat,
+    /// Value in the faction data now.
+    pub from: u32,
+    /// Suggested value.
+    pub to: u32,
+}
+
+/// Suggested faction data changes from a tuning search.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TunePatch {
+    /// Faction A and faction B.
+    pub matchup: (FactionId, FactionId),
+    /// Score faction A should reach.
+    pub target_score: f64,
+    /// Score faction A reached with the changes.
+    pub score: f64,
+    /// Whether the score is within the tolerance of the target.
+    pub within_tolerance: bool,
+    /// Changed stats; stats left at their baseline are omitted.
+    pub changes: Vec<StatChange>,
+}
+
+/// Results of a tuning search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TuneResults {
+    /// Configuration used.
+    pub config: TuneConfig,
+    /// Each parameter's value in the faction data, in parameter order.
+    pub baseline_values: Vec<u32>,
+    /// Every candidate evaluated, in order; the first is the baseline.
+    pub evaluations: Vec<Candidate>,
+    /// Closest candidate found.
+    pub best: Candidate,
+    /// The best candidate as faction data changes.
+    pub patch: TunePatch,
+    /// Games that failed to run.
+    pub failed_games: u32,
+    /// Total runtime.
+    pub duration_seconds: f64,
+}
+
+impl TuneResults {
+    /// Save results to a JSON file.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+
+    /// Save the suggested patch as RON.
+    pub fn save_patch(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let ron = ron::ser::to_string_pretty(&self.patch, ron::ser::PrettyConfig::default())
+            .map_err(std::io::Error::other)?;
+        std::fs::write(path, ron)
+    }
+
+    /// Render the search as a markdown table of candidates, best marked.
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("| # |");
+        for param in &self.config.params {
+            out.push_str(&format!(" {} |", param));
+        }
+        out.push_str(" Score |\n|---|");
+        out.push_str(&"---|".repeat(self.config.params.len() + 1));
+        out.push('\n');
+        for (i, candidate) in self.evaluations.iter().enumerate() {
+            out.push_str(&format!("| {} |", i));
+            for (&pct, &base) in candidate.scales.iter().zip(&self.baseline_values) {
+                out.push_str(&format!(" {} ({}%) |", scale(base, pct), pct));
+            }
+            let marker = if *candidate == self.best { " ◀" } else { "" };
+            out.push_str(&format!(
+                " {:.0}% ({:.0}–{:.0}%){} |\n",
+                candidate.score * 100.0,
+                candidate.ci_low * 100.0,
+                candidate.ci_high * 100.0,
+                marker
+            ));
+        }
+        out
+    }
+}
+
+/// Run a tuning search, playing a batch per candidate.
+///
+/// # Errors
+///
+/// Returns an error if the configuration is unusable, a parameter does
+/// not match the faction data, or a candidate finishes no games.
+pub fn run_tuning(config: TuneConfig) -> Result<TuneResults, TuningError> {
+    config.validate()?;
+    let start = Instant::now();
+    let base = load_factions_from_path(&config.faction_data_path)
+        .map_err(|e| TuningError::FactionData(e.to_string()))?;
+
+    // Factions touched by any parameter, and each parameter's baseline
+    let mut tuned: Vec<FactionData> = Vec::new();
+    for param in &config.params {
+        if !tuned.iter().any(|d| d.id == param.faction) {
+            let data = base
+                .get(param.faction)
+                .ok_or(TuningError::UnknownFaction(param.faction))?;
+            tuned.push(data.clone());
+        }
+    }
+    let baseline_values = config
+        .params
+        .iter()
+        .map(|p| {
+            let data = tuned.iter().find(|d| d.id == p.faction).expect("loaded");
+            p.value(data)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let faction_a = config.matchup.0.short_name().to_lowercase();
+    let mut failed_games = 0;
+    let evaluations = search(&config, |scales| {
+        let mut registry = FactionRegistry::lazy_from_directory(&config.faction_data_path)
+            .map_err(|e| TuningError::FactionData(e.to_string()))?;
+        for data in &tuned {
+            let mut patched = data.clone();
+            for ((param, &base), &pct) in config.params.iter().zip(&baseline_values).zip(scales) {
+                if param.faction == patched.id {
+                    param.set(&mut patched, scale(base, pct))?;
+                }
+            }
+            registry.insert(patched);
+        }
+
+        let mut batch = BatchConfig::new(&config.scenario, config.games)
+            .with_seed(config.seed_start)
+            .with_matchup(config.matchup.0, config.matchup.1);
+        batch.max_ticks = config.max_ticks;
+        batch.parallel_games = config.parallel_games;
+        batch.faction_registry = Some(Arc::new(registry));
+        let results = run_batch(batch);
+        failed_games += results.errors.len() as u32;
+
+        let summary = &results.summary;
+        let games = summary.total_games;
+        if games == 0 {
+            return Err(TuningError::NoGames(scales.to_vec()));
+        }
+        let wins = summary
+            .wins_by_faction
+            .get(faction_a.as_str())
+            .copied()
+            .unwrap_or(0);
+        let draws = summary.draws;
+        let candidate = Candidate::new(scales.to_vec(), games, wins, draws);
+        info!(
+            scales = ?candidate.scales,
+            score = candidate.score,
+            "Evaluated tuning candidate"
+        );
+        Ok(candidate)
+    })?;
+
+    let best = evaluations[best_index(&evaluations, config.target_score)].clone();
+    let patch = TunePatch {
+        matchup: config.matchup,
+        target_score: config.target_score,
+        score: best.score,
+        within_tolerance: best.error(config.target_score) <= config.tolerance,
+        changes: config
+            .params
+            .iter()
+            .zip(&baseline_values)
+            .zip(&best.scales)
+            .filter(|(_, &pct)| pct != 100)
+            .map(|((param, &from), &pct)| StatChange {
+                faction: param.faction,
+                unit: param.unit.clone(),
+                stat: param.stat,
+                from,
+                to: scale(from, pct),
+            })
+            .collect(),
+    };
+
+    Ok(TuneResults {
+        config,
+        baseline_values,
+        evaluations,
+        best,
+        patch,
+        failed_games,
+        duration_seconds: start.elapsed().as_secs_f64(),
+    })
+}
+
+/// Index of the candidate closest to `target`; the earliest wins ties.
+fn best_index(candidates: &[Candidate], target: f64) -> usize {
+    (1..candidates.len()).fold(0, |best, i| {
+        if candidates[i].beats(&candidates[best], target) {
+            i
+        } else {
+            best
+        }
+    })
+}
+
+/// Choose and evaluate candidates, returning them in evaluation order with
+/// the baseline first.
+fn search(
+    config: &TuneConfig,
+    mut evaluate: impl FnMut(&[u32]) -> Result<Candidate, TuningError>,
+) -> Result<Vec<Candidate>, TuningError> {
+    let baseline = vec![100; config.params.len()];
+    let mut evaluated: Vec<Candidate> = vec![evaluate(&baseline)?];
+    let target = config.target_score;
+    let done = |best: &Candidate| best.error(target) <= config.tolerance;
+
+    match config.method {
+        SearchMethod::Grid => {
+            let values: Vec<u32> = (config.min_pct..=config.max_pct)
+                .step_by(config.step_pct as usize)
+                .collect();
+            let candidates = (values.len() as u64).saturating_pow(config.params.len() as u32);
+            if candidates > u64::from(config.max_evaluations) {
+                return Err(TuningError::GridTooLarge {
+                    candidates,
+                    max: config.max_evaluations,
+                });
+            }
+            let mut scales = vec![values[0]; config.params.len()];
+            let mut index = vec![0; config.params.len()];
+            loop {
+                for (s, &i) in scales.iter_mut().zip(&index) {
+                    *s = values[i];
+                }
+                if scales != baseline {
+                    evaluated.push(evaluate(&scales)?);
+                }
+                // Odometer over every combination
+                let Some(digit) = index.iter().rposition(|&i| i + 1 < values.len()) else {
+                    break;
+                };
+                index[digit] += 1;
+                index[digit + 1..].iter_mut().for_each(|i| *i = 0);
+            }
+        }
+        SearchMethod::HillClimb => {
+            let mut seen: HashSet<Vec<u32>> = HashSet::from([baseline]);
+            let mut current = 0;
+            let mut step = config.step_pct;
+            while step > 0 && !done(&evaluated[current]) {
+                let budget_left =
+                    |evaluated: &Vec<Candidate>| evaluated.len() < config.max_evaluations as usize;
+                for i in 0..config.params.len() {
+                    for up in [true, false] {
+                        let mut scales = evaluated[current].scales.clone();
+                        scales[i] = if up {
+                            (scales[i] + step).min(config.max_pct)
+                        } else {
+                            scales[i].saturating_sub(step).max(config.min_pct)
+                        };
+                        if seen.contains(&scales) || !budget_left(&evaluated) {
+                            continue;
+                        }
+                        seen.insert(scales.clone());
+                        evaluated.push(evaluate(&scales)?);
+                    }
+                }
+
+                // Move to the closest candidate so far, or look closer
+                let best = best_index(&evaluated, target);
+                if best == current {
+                    step /= 2;
+                }
+                current = best;
+                if !budget_left(&evaluated) {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(evaluated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_param_parsing_roundtrips() {
+        let param: TunableParam = "continuity.security_team.damage".parse().unwrap();
+        assert_eq!(param.faction, FactionId::Continuity);
+        assert_eq!(param.unit, "security_team");
+        assert_eq!(param.stat, UnitStat::Damage);
+        assert_eq!(param.to_string(), "continuity.security_team.damage");
+
+        for bad in [
+            "continuity.security_team",
+            "continuity.security_team.speed",
+            "nobody.security_team.cost",
+            "continuity..cost",
+            "a.b.c.d",
+        ] {
+            assert!(
+                matches!(bad.parse::<TunableParam>(), Err(TuningError::BadParam(_))),
+                "{bad} should not parse"
+            );
+        }
+        assert_eq!(scale(12, 110), 13);
+        assert_eq!(scale(1, 50), 1);
+    }
+
+    /// Config for `n` parameters with no faction data, for driving
+    /// [`search`] with a stand-in evaluator.
+    fn search_config(n: usize, method: SearchMethod) -> TuneConfig {
+        let param: TunableParam = "continuity.security_team.damage".parse().unwrap();
+        TuneConfig::new("unused", vec![param; n])
+            .with_method(method)
+            .with_target(0.5, 0.02)
+    }
+
+    #[test]
+    fn test_hill_climb_walks_towards_target() {
+        // Faction A's score falls 1% for every percent of the stat
+        let config = search_config(1, SearchMethod::HillClimb).with_max_evaluations(50);
+        let evaluated = search(&config, |scales| {
+            let wins = (80 - (scales[0] as i64 - 100)).clamp(0, 100) as u32;
+            Ok(Candidate::new(scales.to_vec(), 100, wins, 0))
+        })
+        .unwrap();
+
+        assert_eq!(evaluated[0].scales, vec![100]);
+        let best = evaluated.iter().find(|c| c.error(0.5) <= 0.02).unwrap();
+        assert_eq!(best.scales, vec![130]);
+        assert!(evaluated.len() < 20);
+    }
+
+    #[test]
+    fn test_grid_covers_every_combination() {
+        let mut config = search_config(2, SearchMethod::Grid);
+        config.min_pct = 80;
+        config.max_pct = 120;
+        config.step_pct = 20;
+        let evaluated = search(&config, |scales| {
+            Ok(Candidate::new(scales.to_vec(), 10, scales[0] / 20, 0))
+        })
+        .unwrap();
+        assert_eq!(evaluated.len(), 9);
+        assert_eq!(evaluated[0].scales, vec![100, 100]);
+        assert!(evaluated.iter().any(|c| c.scales == vec![80, 120]));
+
+        config.max_evaluations = 5;
+        assert_eq!(
+            search(&config, |s| Ok(Candidate::new(s.to_vec(), 1, 0, 0))),
+            Err(TuningError::GridTooLarge {
+                candidates: 9,
+                max: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_tuning_rejects_unknown_units() {
+        let Some(dir) = crate::faction_loader::default_faction_data_dir() else {
+            return;
+        };
+        let config = TuneConfig::new(dir, vec!["continuity.mystery_unit.cost".parse().unwrap()]);
+        assert!(matches!(
+            run_tuning(config),
+            Err(TuningError::UnknownUnit { unit, .. }) if unit == "mystery_unit"
+        ));
+    }
+}
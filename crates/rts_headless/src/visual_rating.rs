@@ -8,9 +8,14 @@
 //! - Visual balance (even distribution)
 
 use crate::ascii_visualizer::ScreenshotState;
+use crate::screenshot::ScreenshotTrigger;
+use crate::visual_review::{BatchVisualReview, VisualQualityReport};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Default score a screenshot needs to pass review.
+pub const DEFAULT_MIN_SCORE: u32 = 60;
 
 /// Comprehensive visual quality score.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -585,7 +590,7 @@ impl VisualAnalyzer {
         }
 
         let mut common_issues: Vec<(String, u32)> = issue_counts.into_iter().collect();
-        common_issues.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        common_issues.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
 
         // Aggregate dimension scores
         let avg_dimensions = VisualDimensions {
@@ -763,6 +768,85 @@ pub fn analyze_screenshots_in_dir(path: &Path) -> std::io::Result<BatchVisualSco
     Ok(analyzer.analyze_batch(&screenshots))
 }
 
+/// Score every screenshot under a directory into a review, grouped by game.
+///
+/// Walks `path` recursively for state dumps, skipping any JSON that isn't
+/// one (manifests, batch results). A screenshot passes when it scores at
+/// least `min_score`; its issues become the report's notes, and a PNG
+/// rendered beside it is attached as its image.
+pub fn review_screenshots_in_dir(
+    path: &Path,
+    min_score: u32,
+) -> std::io::Result<BatchVisualReview> {
+    fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+        if path.is_dir() {
+            for entry in std::fs::read_dir(path)? {
+                collect_files(&entry?.path(), files)?;
+            }
+        } else if path.extension().is_some_and(|e| e == "json") {
+            files.push(path.to_path_buf());
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    collect_files(path, &mut files)?;
+    files.sort();
+
+    let batch_id = path.file_name().map_or_else(
+        || "visual_review".to_string(),
+        |n| n.to_string_lossy().into_owned(),
+    );
+    let mut review = BatchVisualReview::new(&batch_id);
+    let analyzer = VisualAnalyzer::new();
+    let mut issue_counts: HashMap<String, u32> = HashMap::new();
+
+    for file in files {
+        let Ok(state) = ScreenshotState::load(&file) else {
+            continue;
+        };
+        let score = analyzer.analyze(&state);
+        let file_name = file
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let trigger = serde_json::from_value::<ScreenshotTrigger>(state.trigger.clone())
+            .map_or_else(|_| state.trigger.to_string(), |t| t.name());
+        let game_id = if state.game_id.is_empty() {
+            file.parent()
+                .and_then(Path::file_name)
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        } else {
+            state.game_id.clone()
+        };
+
+        let mut report =
+            VisualQualityReport::new(&file_name, state.tick, &trigger).with_game_id(&game_id);
+        let image = file.with_extension("png");
+        if image.is_file() {
+            report = report.with_image(&image.display().to_string());
+        }
+        report.quality_score = score.overall;
+        report.overall_pass = score.overall >= min_score;
+        for issue in &score.issues {
+            report.add_note(&issue.description);
+            *issue_counts.entry(issue.category.clone()).or_insert(0) += 1;
+        }
+        review.reports.push(report);
+    }
+    review.recalculate_stats();
+
+    let mut common_issues: Vec<(String, u32)> = issue_counts.into_iter().collect();
+    common_issues.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    review.common_issues = common_issues
+        .into_iter()
+        .map(|(category, count)| format!("{} ({} screenshots)", category, count))
+        .collect();
+
+    Ok(review)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -795,6 +879,7 @@ mod tests {
             effects: vec![],
             map_bounds: [512, 512],
             fog_of_war: None,
+            threat_map: None,
         }
     }
 
@@ -887,4 +972,62 @@ mod tests {
         assert!(batch.passes_quality_bar(70));
         assert!(!batch.passes_quality_bar(80));
     }
+
+    #[test]
+    fn test_review_scans_nested_game_folders() {
+        let dir = tempfile::tempdir().unwrap();
+        let save = |game: &str, name: &str, mut state: ScreenshotState| {
+            let game_dir = dir.path().join("batch").join(game);
+            std::fs::create_dir_all(&game_dir).unwrap();
+            state.game_id = game.to_string();
+            let json = serde_json::to_string(&state).unwrap();
+            std::fs::write(game_dir.join(name), json).unwrap();
+        };
+
+        let mut spread = make_state(vec![
+            make_unit("continuity", 100.0, 100.0),
+            make_unit("continuity", 150.0, 150.0),
+            make_unit("collegium", 400.0, 400.0),
+            make_unit("collegium", 350.0, 350.0),
+        ]);
+        spread.trigger = serde_json::json!({ "MajorBattle": { "unit_count": 4 } });
+        save("game_b", "major_battle_100.json", spread);
+        let mut stacked = make_state(
+            (0..6)
+                .map(|i| make_unit("a", 100.0 + i as f32, 100.0))
+                .collect(),
+        );
+        stacked.tick = 50;
+        save("game_a", "first_contact_50.json", stacked);
+        std::fs::write(dir.path().join("batch/game_a/first_contact_50.png"), b"png").unwrap();
+        // Manifests and other JSON are skipped
+        std::fs::write(dir.path().join("batch/game_a/manifest.json"), "{}").unwrap();
+
+        let review = review_screenshots_in_dir(dir.path(), 101).unwrap();
+        assert_eq!(review.reports.len(), 2);
+        assert_eq!(review.pass_rate, 0.0);
+        let games = review.games();
+        assert_eq!(
+            games.keys().copied().collect::<Vec<_>>(),
+            vec!["game_a", "game_b"]
+        );
+
+        let stacked = games["game_a"][0];
+        assert_eq!(stacked.tick, 50);
+        assert!(stacked
+            .image
+            .as_deref()
+            .unwrap()
+            .ends_with("first_contact_50.png"));
+        assert!(!stacked.manual_notes.is_empty());
+        assert_eq!(games["game_b"][0].trigger, "major_battle");
+        assert!(games["game_b"][0].image.is_none());
+        assert!(review
+            .common_issues
+            .iter()
+            .any(|i| i.starts_with("unit_overlap")));
+
+        let lenient = review_screenshots_in_dir(dir.path(), 0).unwrap();
+        assert_eq!(lenient.pass_rate, 1.0);
+    }
 }
@@ -4,6 +4,7 @@
 //! review report generation.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 
 /// Result of a silhouette distinction test
@@ -151,8 +152,14 @@ impl BrightnessResult {
 /// Combined visual quality report for a screenshot
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VisualQualityReport {
+    /// Game the screenshot came from, grouping HTML reports into sections
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub game_id: String,
     /// Screenshot filename
     pub screenshot: String,
+    /// Rendered image of the screenshot, embedded in HTML reports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
     /// Game tick when captured
     pub tick: u64,
     /// Trigger that caused capture
@@ -175,7 +182,9 @@ impl VisualQualityReport {
     /// Create new report
     pub fn new(screenshot: &str, tick: u64, trigger: &str) -> Self {
         Self {
+            game_id: String::new(),
             screenshot: screenshot.to_string(),
+            image: None,
             tick,
             trigger: trigger.to_string(),
             silhouette: None,
@@ -217,6 +226,20 @@ impl VisualQualityReport {
         self.quality_score = score;
     }
 
+    /// Attach a rendered image of the screenshot
+    #[must_use]
+    pub fn with_image(mut self, image: &str) -> Self {
+        self.image = Some(image.to_string());
+        self
+    }
+
+    /// Set the game the screenshot came from
+    #[must_use]
+    pub fn with_game_id(mut self, game_id: &str) -> Self {
+        self.game_id = game_id.to_string();
+        self
+    }
+
     /// Add manual review note
     pub fn add_note(&mut self, note: &str) {
         self.manual_notes.push(note.to_string());
@@ -299,6 +322,7 @@ impl BatchVisualReview {
         html.push_str(".score-high { background-color: #c8e6c9; }\n");
         html.push_str(".score-mid { background-color: #fff9c4; }\n");
         html.push_str(".score-low { background-color: #ffcdd2; }\n");
+        html.push_str("img { max-width: 256px; image-rendering: pixelated; }\n");
         html.push_str("</style>\n</head>\n<body>\n");
 
         html.push_str(&format!("<h1>Visual Review: {}</h1>\n", self.batch_id));
@@ -308,36 +332,38 @@ impl BatchVisualReview {
             self.pass_rate * 100.0
         ));
 
-        html.push_str("<table>\n<tr><th>Screenshot</th><th>Tick</th><th>Trigger</th>");
-        html.push_str("<th>Score</th><th>Pass</th><th>Notes</th></tr>\n");
+        let games = self.games();
+        if games.len() == 1 && games.contains_key("") {
+            html.push_str(&report_table(&self.reports.iter().collect::<Vec<_>>()));
+        } else {
+            html.push_str("<h2>Games</h2>\n<table>\n<tr><th>Game</th><th>Screenshots</th>");
+            html.push_str("<th>Average Score</th><th>Passed</th><th>Failed</th></tr>\n");
+            for (game_id, reports) in &games {
+                let passed = reports.iter().filter(|r| r.overall_pass).count();
+                let failed = reports.len() - passed;
+                let average = reports.iter().map(|r| r.quality_score).sum::<u32>() as f64
+                    / reports.len() as f64;
+                html.push_str(&format!(
+                    "<tr><td><a href=\"#game-{0}\">{0}</a></td><td>{1}</td><td>{2:.1}</td>",
+                    game_id,
+                    reports.len(),
+                    average
+                ));
+                html.push_str(&format!(
+                    "<td class=\"pass\">{}</td><td class=\"{}\">{}</td></tr>\n",
+                    passed,
+                    if failed > 0 { "fail" } else { "pass" },
+                    failed
+                ));
+            }
+            html.push_str("</table>\n");
 
-        for report in &self.reports {
-            let score_class = if report.quality_score >= 80 {
-                "score-high"
-            } else if report.quality_score >= 50 {
-                "score-mid"
-            } else {
-                "score-low"
-            };
-            let pass_class = if report.overall_pass { "pass" } else { "fail" };
-            let pass_text = if report.overall_pass { "✓" } else { "✗" };
-
-            html.push_str(&format!(
-                "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td>",
-                score_class, report.screenshot, report.tick, report.trigger
-            ));
-            html.push_str(&format!(
-                "<td>{}</td><td class=\"{}\">{}</td>",
-                report.quality_score, pass_class, pass_text
-            ));
-            html.push_str(&format!(
-                "<td>{}</td></tr>\n",
-                report.manual_notes.join("; ")
-            ));
+            for (game_id, reports) in &games {
+                html.push_str(&format!("<h2 id=\"game-{0}\">{0}</h2>\n", game_id));
+                html.push_str(&report_table(reports));
+            }
         }
 
-        html.push_str("</table>\n");
-
         if !self.common_issues.is_empty() {
             html.push_str("<h2>Common Issues</h2>\n<ul>\n");
             for issue in &self.common_issues {
@@ -349,6 +375,60 @@ impl BatchVisualReview {
         html.push_str("</body>\n</html>");
         html
     }
+
+    /// Reports grouped by game, each game's in capture order
+    pub fn games(&self) -> BTreeMap<&str, Vec<&VisualQualityReport>> {
+        let mut games: BTreeMap<&str, Vec<&VisualQualityReport>> = BTreeMap::new();
+        for report in &self.reports {
+            games.entry(&report.game_id).or_default().push(report);
+        }
+        for reports in games.values_mut() {
+            reports.sort_by_key(|r| r.tick);
+        }
+        games
+    }
+}
+
+/// HTML table of screenshot reports
+fn report_table(reports: &[&VisualQualityReport]) -> String {
+    let mut html = String::new();
+    html.push_str("<table>\n<tr><th>Screenshot</th><th>Tick</th><th>Trigger</th>");
+    html.push_str("<th>Score</th><th>Pass</th><th>Notes</th></tr>\n");
+
+    for report in reports {
+        let score_class = if report.quality_score >= 80 {
+            "score-high"
+        } else if report.quality_score >= 50 {
+            "score-mid"
+        } else {
+            "score-low"
+        };
+        let pass_class = if report.overall_pass { "pass" } else { "fail" };
+        let pass_text = if report.overall_pass { "✓" } else { "✗" };
+
+        let screenshot = match &report.image {
+            Some(image) => format!(
+                "<a href=\"{0}\"><img src=\"{0}\" alt=\"{1}\"></a><br>{1}",
+                image, report.screenshot
+            ),
+            None => report.screenshot.clone(),
+        };
+        html.push_str(&format!(
+            "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td>",
+            score_class, screenshot, report.tick, report.trigger
+        ));
+        html.push_str(&format!(
+            "<td>{}</td><td class=\"{}\">{}</td>",
+            report.quality_score, pass_class, pass_text
+        ));
+        html.push_str(&format!(
+            "<td>{}</td></tr>\n",
+            report.manual_notes.join("; ")
+        ));
+    }
+
+    html.push_str("</table>\n");
+    html
 }
 
 #[cfg(test)]
@@ -456,5 +536,42 @@ mod tests {
         assert!(html.contains("html_test"));
         assert!(html.contains("test.png"));
         assert!(html.contains("major_battle"));
+        assert!(!html.contains("<img"));
+    }
+
+    #[test]
+    fn test_html_embeds_rendered_images() {
+        let mut review = BatchVisualReview::new("images");
+        review.add_report(
+            VisualQualityReport::new("battle_40.json", 40, "major_battle")
+                .with_image("g1/battle_40.png"),
+        );
+
+        let html = review.to_html();
+        assert!(html.contains(r#"<img src="g1/battle_40.png" alt="battle_40.json">"#));
+    }
+
+    #[test]
+    fn test_html_has_a_section_per_game() {
+        let mut review = BatchVisualReview::new("games");
+        let mut failed =
+            VisualQualityReport::new("late.json", 900, "major_battle").with_game_id("g2");
+        failed.quality_score = 30;
+        failed.overall_pass = false;
+        review.add_report(failed);
+        review.add_report(VisualQualityReport::new("late.json", 900, "victory").with_game_id("g1"));
+        review.add_report(
+            VisualQualityReport::new("early.json", 100, "first_contact").with_game_id("g1"),
+        );
+
+        let games = review.games();
+        assert_eq!(games.keys().copied().collect::<Vec<_>>(), vec!["g1", "g2"]);
+        assert_eq!(games["g1"][0].tick, 100);
+
+        let html = review.to_html();
+        assert!(html.contains(r##"<a href="#game-g1">g1</a></td><td>2</td><td>100.0</td>"##));
+        assert!(html.contains(r#"<td class="pass">0</td><td class="fail">1</td>"#));
+        assert!(html.contains(r#"<h2 id="game-g1">g1</h2>"#));
+        assert!(html.find("game-g1\">g1</h2>") < html.find("game-g2\">g2</h2>"));
     }
 }
@@ -0,0 +1,170 @@
+//! Golden tests for generated reports.
+//!
+//! The analyzer and report generators produce markdown/HTML that
+//! downstream tooling parses, so their wording, thresholds and structure
+//! are pinned with insta snapshots over fixed fixtures. Review intended
+//! changes with `cargo insta review`.
+
+use std::path::{Path, PathBuf};
+
+use rts_core::factions::FactionId;
+use rts_core::outcome::WinCondition;
+use rts_headless::analyzer::analyze_batch;
+use rts_headless::ascii_visualizer::{render_battle_progress, AsciiConfig, ScreenshotState};
+use rts_headless::batch::{BatchConfig, BatchResults};
+use rts_headless::metrics::{BatchSummary, GameMetrics, UnitKindMetrics};
+use rts_headless::visual_rating::VisualAnalyzer;
+use rts_headless::visual_review::{
+    BatchVisualReview, BrightnessResult, ColorDistinctionResult, SilhouetteResult,
+    VisualQualityReport,
+};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn screenshots() -> (ScreenshotState, ScreenshotState) {
+    (
+        ScreenshotState::load(&fixture("screenshot_early.json")).unwrap(),
+        ScreenshotState::load(&fixture("screenshot_late.json")).unwrap(),
+    )
+}
+
+/// A lopsided batch: Continuity wins most games, many end early, and
+/// Collegium out-heals a large share of the damage it takes.
+fn lopsided_batch() -> BatchResults {
+    let games = (0..20u64)
+        .map(|i| {
+            let mut game = GameMetrics::new(format!("game_{i}"), "fixture", i);
+            game.duration_ticks = 6000 + i * 1500;
+            game.winner = match i % 10 {
+                0..=6 => Some("continuity".to_string()),
+                7 | 8 => Some("collegium".to_string()),
+                _ => None,
+            };
+            game.win_condition = match i % 10 {
+                0..=6 => WinCondition::Elimination {
+                    eliminating_player: FactionId::Continuity,
+                    surviving_structures: 3,
+                },
+                7 | 8 => WinCondition::Elimination {
+                    eliminating_player: FactionId::Collegium,
+                    surviving_structures: 3,
+                },
+                _ => WinCondition::TimeLimit,
+            };
+
+            let continuity = game.faction_mut("continuity");
+            continuity.total_damage_taken = 1000;
+            continuity.total_health_regenerated = 100;
+            let collegium = game.faction_mut("collegium");
+            collegium.total_damage_taken = 1000;
+            collegium.total_health_regenerated = 450;
+
+            // Continuity's tanks outtrade collegium's cheaper drones
+            let unit = |cost: i64, dealt: i64, victims: &[(&str, u32, i64)]| UnitKindMetrics {
+                fielded: 4,
+                resources_spent: cost * 4,
+                damage_dealt: dealt,
+                lost: 2,
+                lifetime_ticks: 4 * 900,
+                kills: victims
+                    .iter()
+                    .map(|(kind, kills, _)| (kind.to_string(), *kills))
+                    .collect(),
+                damage_to: victims
+                    .iter()
+                    .map(|(kind, _, damage)| (kind.to_string(), *damage))
+                    .collect(),
+                ..Default::default()
+            };
+            let continuity = game.faction_mut("continuity");
+            continuity.unit_stats.insert(
+                "tank".to_string(),
+                unit(200, 900, &[("drone", 3, 600), ("infantry", 1, 300)]),
+            );
+            continuity
+                .unit_stats
+                .insert("infantry".to_string(), unit(50, 200, &[("drone", 1, 200)]));
+            let collegium = game.faction_mut("collegium");
+            collegium.unit_stats.insert(
+                "drone".to_string(),
+                unit(75, 300, &[("tank", 1, 200), ("infantry", 1, 100)]),
+            );
+            collegium
+                .unit_stats
+                .insert("infantry".to_string(), unit(50, 150, &[("tank", 1, 150)]));
+            game
+        })
+        .collect();
+
+    BatchResults {
+        config: BatchConfig {
+            scenario: "fixture".to_string(),
+            ..Default::default()
+        },
+        games,
+        games_file: None,
+        summary: BatchSummary::default(),
+        duration_seconds: 1.0,
+        errors: Vec::new(),
+        interrupted: false,
+    }
+}
+
+#[test]
+fn balance_analysis_markdown() {
+    let analysis = analyze_batch(&lopsided_batch());
+    insta::assert_snapshot!(analysis.to_markdown());
+}
+
+#[test]
+fn visual_review_html() {
+    let mut review = BatchVisualReview::new("fixture");
+
+    let mut clean = VisualQualityReport::new("tick_1200.png", 1200, "first_contact");
+    clean.silhouette = Some(SilhouetteResult::evaluate(7, 7, 0.02));
+    clean.color_distinction = Some(ColorDistinctionResult::evaluate(vec![
+        ("continuity".to_string(), [50.0, -20.0, -40.0]),
+        ("collegium".to_string(), [70.0, 10.0, 60.0]),
+    ]));
+    clean.brightness = Some(BrightnessResult::evaluate(vec![
+        ("continuity".to_string(), 0.5),
+        ("collegium".to_string(), 0.6),
+    ]));
+    clean.calculate_overall();
+    review.add_report(clean);
+
+    let mut cluttered = VisualQualityReport::new("tick_4800.png", 4800, "major_battle");
+    cluttered.silhouette = Some(SilhouetteResult::evaluate(4, 6, 0.3));
+    cluttered.brightness = Some(BrightnessResult::evaluate(vec![(
+        "collegium".to_string(),
+        0.1,
+    )]));
+    cluttered.calculate_overall();
+    cluttered.add_note("Units stacked at the choke");
+    review.add_report(cluttered);
+
+    review.common_issues = vec!["Overlapping silhouettes in melee".to_string()];
+
+    insta::assert_snapshot!(review.to_html());
+}
+
+#[test]
+fn visual_rating_markdown() {
+    let (early, late) = screenshots();
+    let score = VisualAnalyzer::new().analyze_batch(&[early, late]);
+    insta::assert_snapshot!(score.to_markdown());
+}
+
+#[test]
+fn battle_progress_report() {
+    let (early, late) = screenshots();
+    let config = AsciiConfig {
+        use_color: false,
+        ..Default::default()
+    };
+    insta::assert_snapshot!(render_battle_progress(&early, &late, &config));
+}
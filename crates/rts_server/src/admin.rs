@@ -0,0 +1,528 @@
+//! Operator console for controlling the server while it runs.
+//!
+//! An [`AdminServer`] speaks a line-based, RCON-style protocol over TCP, so
+//! it can be driven by hand with `nc` as easily as from scripts: one
+//! [`AdminCommand`] per line, answered with `ok` or `error: <reason>`,
+//! any further lines of output, then an empty line.
+//!
+//! When the server has an
+//! [`admin_token`](crate::ServerConfig::admin_token), a connection's first
+//! line must be `auth <token>`; any other first line closes it. Without a
+//! token the console must only listen on the loopback interface, which is
+//! how the server binary binds it.
+//!
+//! | Command | Effect |
+//! | ------- | ------ |
+//! | `list` | One line per session: ID, state, name, map, players, ticks |
+//! | `kick <session> <player> [reason]` | [`SessionManager::kick`] |
+//! | `pause <session>`, `resume <session>` | [`SessionManager::set_paused`] |
+//! | `terminate <session> [reason]` | [`SessionManager::terminate`] |
+//! | `tickrate <session> <ticks per second>` | [`SessionManager::set_tick_rate`] |
+//! | `dump <session>` | The game's unfogged [`StateSnapshot`](rts_core::api::StateSnapshot) as JSON |
+//! | `help` | This list |
+//! | `quit` | Close the connection |
+//!
+//! ```text
+//! > auth 5f0c...
+//! ok
+//!
+//! > list
+//! ok
+//! 3 running "ranked" map=dunes players=alice,bob ticks=5120
+//! 4 lobby "casual" map=- players=carol ticks=0
+//!
+//! > pause 3
+//! ok
+//!
+//! ```
+
+use std::io;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use thiserror::Error;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use crate::network::SharedSessions;
+use crate::session::{SessionError, SessionId, SessionInfo, SessionManager, SessionState};
+use crate::snapshot::Viewer;
+
+/// Longest command line read before the connection is dropped.
+const MAX_LINE_BYTES: u64 = 4096;
+
+/// What players are told when no reason is given.
+const DEFAULT_KICK_REASON: &str = "kicked by the server operator";
+const DEFAULT_TERMINATE_REASON: &str = "game terminated by the server operator";
+
+const HELP: &str = "\
+list
+kick <session> <player> [reason]
+pause <session>
+resume <session>
+terminate <session> [reason]
+tickrate <session> <ticks per second>
+dump <session>
+help
+quit";
+
+/// Errors running an admin command.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AdminError {
+    /// No command by this name.
+    #[error("unknown command '{0}'; try 'help'")]
+    UnknownCommand(String),
+
+    /// The command's arguments are missing or malformed.
+    #[error("usage: {0}")]
+    Usage(&'static str),
+
+    /// The session manager refused the command.
+    #[error(transparent)]
+    Session(#[from] SessionError),
+
+    /// A snapshot could not be encoded.
+    #[error("failed to encode snapshot: {0}")]
+    Encode(String),
+}
+
+/// One line of admin console input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdminCommand {
+    /// List every session.
+    List,
+    /// Remove a player from a session.
+    Kick {
+        /// Session the player is in.
+        session: SessionId,
+        /// Player to remove.
+        player: String,
+        /// Told to the player.
+        reason: String,
+    },
+    /// Pause a running game.
+    Pause(SessionId),
+    /// Resume a paused game.
+    Resume(SessionId),
+    /// Close a session at once.
+    Terminate {
+        /// Session to close.
+        session: SessionId,
+        /// Told to everyone in it.
+        reason: String,
+    },
+    /// Change the tick rate of a game the server ticks.
+    TickRate {
+        /// Game to change.
+        session: SessionId,
+        /// New ticks per second.
+        tick_rate: u32,
+    },
+    /// Dump a game's full state snapshot.
+    Dump(SessionId),
+    /// List the commands.
+    Help,
+    /// Close the connection.
+    Quit,
+}
+
+impl FromStr for AdminCommand {
+    type Err = AdminError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+        let (name, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let mut words = args.split_whitespace();
+        let mut session = |usage| {
+            words
+                .next()
+                .and_then(|word| word.parse().ok())
+                .ok_or(AdminError::Usage(usage))
+        };
+        let command = match name {
+            "list" => Self::List,
+            "kick" => {
+                let usage = "kick <session> <player> [reason]";
+                let session = session(usage)?;
+                let player = words.next().ok_or(AdminError::Usage(usage))?.to_string();
+                Self::Kick {
+                    session,
+                    player,
+                    reason: rest_or(words, DEFAULT_KICK_REASON),
+                }
+            }
+            "pause" => Self::Pause(session("pause <session>")?),
+            "resume" => Self::Resume(session("resume <session>")?),
+            "terminate" => Self::Terminate {
+                session: session("terminate <session> [reason]")?,
+                reason: rest_or(words, DEFAULT_TERMINATE_REASON),
+            },
+            "tickrate" => {
+                let usage = "tickrate <session> <ticks per second>";
+                let session = session(usage)?;
+                let tick_rate = words
+                    .next()
+                    .and_then(|word| word.parse().ok())
+                    .filter(|&rate| rate > 0)
+                    .ok_or(AdminError::Usage(usage))?;
+                Self::TickRate { session, tick_rate }
+            }
+            "dump" => Self::Dump(session("dump <session>")?),
+            "help" => Self::Help,
+            "quit" | "exit" => Self::Quit,
+            other => return Err(AdminError::UnknownCommand(other.to_string())),
+        };
+        Ok(command)
+    }
+}
+
+/// The remaining words joined back up, or `default` if there are none.
+fn rest_or<'a>(words: impl Iterator<Item = &'a str>, default: &str) -> String {
+    let rest = words.collect::<Vec<_>>().join(" ");
+    if rest.is_empty() {
+        default.to_string()
+    } else {
+        rest
+    }
+}
+
+impl AdminCommand {
+    /// Carry out the command, returning its output lines (possibly none).
+    ///
+    /// [`Quit`](Self::Quit) does nothing here; the connection handles it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session manager refuses the command.
+    pub fn run(&self, sessions: &mut SessionManager) -> Result<String, AdminError> {
+        match self {
+            Self::List => Ok(sessions
+                .list()
+                .iter()
+                .map(list_line)
+                .collect::<Vec<_>>()
+                .join("\n")),
+            Self::Kick {
+                session,
+                player,
+                reason,
+            } => sessions
+                .kick(*session, player, reason)
+                .map(|()| String::new()),
+            Self::Pause(session) => sessions.set_paused(*session, true).map(|()| String::new()),
+            Self::Resume(session) => sessions.set_paused(*session, false).map(|()| String::new()),
+            Self::Terminate { session, reason } => {
+                sessions.terminate(*session, reason).map(|()| String::new())
+            }
+            Self::TickRate { session, tick_rate } => sessions
+                .set_tick_rate(*session, *tick_rate)
+                .map(|()| String::new()),
+            Self::Dump(session) => {
+                let snapshot = sessions
+                    .snapshot_for(*session, &Viewer::Omniscient)?
+                    .ok_or(SessionError::NotRunning(*session))?;
+                return serde_json::to_string(&snapshot)
+                    .map_err(|e| AdminError::Encode(e.to_string()));
+            }
+            Self::Help => Ok(HELP.to_string()),
+            Self::Quit => Ok(String::new()),
+        }
+        .map_err(AdminError::from)
+    }
+}
+
+/// One `list` line.
+fn list_line(info: &SessionInfo) -> String {
+    let state = match info.state {
+        SessionState::Lobby => "lobby",
+        SessionState::Running if info.paused => "paused",
+        SessionState::Running => "running",
+        SessionState::Finished => "finished",
+    };
+    let players = if info.players.is_empty() {
+        "-".to_string()
+    } else {
+        info.players.join(",")
+    };
+    format!(
+        "{} {state} {:?} map={} players={players} ticks={}",
+        info.id,
+        info.name,
+        info.map.as_deref().unwrap_or("-"),
+        info.usage.ticks
+    )
+}
+
+/// TCP listener for the admin console.
+#[derive(Debug)]
+pub struct AdminServer {
+    listener: TcpListener,
+    sessions: SharedSessions,
+    token: Option<Arc<str>>,
+}
+
+impl AdminServer {
+    /// Bind the console, requiring `token` from every connection if given.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound.
+    pub async fn bind(
+        addr: impl ToSocketAddrs,
+        sessions: SharedSessions,
+        token: Option<String>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        Ok(Self {
+            listener,
+            sessions,
+            token: token.map(Arc::from),
+        })
+    }
+
+    /// Address the console is listening on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket address cannot be read.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accept operators until the task is dropped.
+    pub async fn serve(self) {
+        loop {
+            match self.listener.accept().await {
+                Ok((stream, peer)) => {
+                    let sessions = Arc::clone(&self.sessions);
+                    let token = self.token.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = serve_operator(stream, peer, sessions, token).await {
+                            tracing::debug!(%peer, error = %e, "Admin connection failed");
+                        }
+                    });
+                }
+                Err(e) => tracing::warn!(error = %e, "Admin accept failed"),
+            }
+        }
+    }
+}
+
+/// Run one console connection: authenticate, then commands until it
+/// closes.
+async fn serve_operator(
+    stream: TcpStream,
+    peer: SocketAddr,
+    sessions: SharedSessions,
+    token: Option<Arc<str>>,
+) -> io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    if let Some(token) = token {
+        let line = read_line(&mut reader).await?.unwrap_or_default();
+        let given = line.trim().strip_prefix("auth ").unwrap_or_default();
+        if !tokens_match(given.trim(), &token) {
+            tracing::warn!(%peer, "Refused admin connection");
+            let reply = Err(AdminError::Usage("auth <token>"));
+            return write_reply(&mut writer, &reply).await;
+        }
+        write_reply(&mut writer, &Ok(String::new())).await?;
+    }
+    tracing::info!(%peer, "Admin connected");
+
+    while let Some(line) = read_line(&mut reader).await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match line.parse::<AdminCommand>() {
+            Ok(AdminCommand::Quit) => break,
+            Ok(command) => {
+                tracing::info!(%peer, command = line.trim(), "Admin command");
+                command.run(&mut *sessions.lock().await)
+            }
+            Err(e) => Err(e),
+        };
+        write_reply(&mut writer, &reply).await?;
+    }
+    tracing::info!(%peer, "Admin disconnected");
+    Ok(())
+}
+
+/// Read one line, or None at the end of the stream.
+async fn read_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    let read = (&mut *reader)
+        .take(MAX_LINE_BYTES)
+        .read_line(&mut line)
+        .await?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if !line.ends_with('\n') && read as u64 == MAX_LINE_BYTES {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "line too long"));
+    }
+    Ok(Some(line))
+}
+
+/// Compare tokens without stopping at the first difference, so response
+/// times don't reveal how much of a guess was right.
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn write_reply(
+    writer: &mut (impl AsyncWriteExt + Unpin),
+    reply: &Result<String, AdminError>,
+) -> io::Result<()> {
+    let text = match reply {
+        Ok(output) if output.is_empty() => "ok\n\n".to_string(),
+        Ok(output) => format!("ok\n{output}\n\n"),
+        Err(e) => format!("error: {e}\n\n"),
+    };
+    writer.write_all(text.as_bytes()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ServerConfig;
+    use rts_core::api::StateSnapshot;
+    use rts_core::components::FactionMember;
+    use rts_core::factions::FactionId;
+    use rts_core::math::Vec2Fixed;
+    use rts_core::simulation::{EntitySpawnParams, Simulation};
+    use tokio::sync::Mutex;
+
+    #[test]
+    fn test_commands_parse_with_usage_errors() {
+        assert_eq!("list".parse(), Ok(AdminCommand::List));
+        assert_eq!(
+            "kick 3 alice spamming chat".parse(),
+            Ok(AdminCommand::Kick {
+                session: 3,
+                player: "alice".to_string(),
+                reason: "spamming chat".to_string(),
+            })
+        );
+        assert_eq!(
+            "terminate 4".parse(),
+            Ok(AdminCommand::Terminate {
+                session: 4,
+                reason: DEFAULT_TERMINATE_REASON.to_string(),
+            })
+        );
+        assert_eq!(
+            "tickrate 2 0".parse::<AdminCommand>(),
+            Err(AdminError::Usage("tickrate <session> <ticks per second>"))
+        );
+        assert_eq!(
+            "kick 3".parse::<AdminCommand>(),
+            Err(AdminError::Usage("kick <session> <player> [reason]"))
+        );
+        assert_eq!(
+            "reboot".parse::<AdminCommand>(),
+            Err(AdminError::UnknownCommand("reboot".to_string()))
+        );
+    }
+
+    /// Send one command and read its reply, up to the blank line.
+    async fn command(reader: &mut BufReader<TcpStream>, line: &str) -> Vec<String> {
+        reader
+            .get_mut()
+            .write_all(format!("{line}\n").as_bytes())
+            .await
+            .unwrap();
+        let mut reply = Vec::new();
+        while let Some(line) = read_line(reader).await.unwrap() {
+            let line = line.trim_end().to_string();
+            if line.is_empty() {
+                break;
+            }
+            reply.push(line);
+        }
+        reply
+    }
+
+    #[tokio::test]
+    async fn test_console_authenticates_and_controls_sessions() {
+        let mut manager = SessionManager::new(&ServerConfig {
+            max_players: 2,
+            ..Default::default()
+        });
+        let game = manager.open_lobby("ranked").unwrap();
+        manager.join(game, "alice").unwrap();
+        manager.join(game, "bob").unwrap();
+        let factions = [FactionId::Continuity, FactionId::Collegium];
+        let mut sim = Simulation::new();
+        for (i, &faction) in factions.iter().enumerate() {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::default()),
+                health: Some(1000),
+                is_depot: true,
+                faction: Some(FactionMember::new(faction, i as u8)),
+                ..Default::default()
+            });
+        }
+        manager.start_lockstep(game, sim, &factions).unwrap();
+        let lobby = manager.open_lobby("casual").unwrap();
+        manager.join(lobby, "carol").unwrap();
+
+        let sessions = Arc::new(Mutex::new(manager));
+        let server = AdminServer::bind("127.0.0.1:0", Arc::clone(&sessions), Some("s3cret".into()))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        tokio::spawn(server.serve());
+
+        let mut intruder = BufReader::new(TcpStream::connect(addr).await.unwrap());
+        assert_eq!(
+            command(&mut intruder, "auth guess").await,
+            vec!["error: usage: auth <token>"]
+        );
+        assert_eq!(read_line(&mut intruder).await.unwrap(), None);
+
+        let mut console = BufReader::new(TcpStream::connect(addr).await.unwrap());
+        assert_eq!(command(&mut console, "auth s3cret").await, vec!["ok"]);
+        assert_eq!(command(&mut console, "pause 1").await, vec!["ok"]);
+        assert_eq!(
+            command(&mut console, "list").await,
+            vec![
+                "ok",
+                "1 paused \"ranked\" map=- players=alice,bob ticks=0",
+                "2 lobby \"casual\" map=- players=carol ticks=0",
+            ]
+        );
+        assert_eq!(
+            command(&mut console, "tickrate 1 60").await,
+            vec!["error: Session 1 is paced by its players"]
+        );
+
+        let dump = command(&mut console, "dump 1").await;
+        assert_eq!(dump[0], "ok");
+        let snapshot: StateSnapshot = serde_json::from_str(&dump[1]).unwrap();
+        assert_eq!(snapshot.entities.len(), 2);
+        assert_eq!(
+            command(&mut console, "dump 2").await,
+            vec!["error: Session 2 is not running"]
+        );
+
+        assert_eq!(command(&mut console, "kick 2 carol").await, vec!["ok"]);
+        assert_eq!(command(&mut console, "terminate 1").await, vec!["ok"]);
+        assert_eq!(command(&mut console, "list").await, vec!["ok"]);
+        assert!(sessions.lock().await.is_empty());
+
+        assert_eq!(
+            command(&mut console, "shutdown").await,
+            vec!["error: unknown command 'shutdown'; try 'help'"]
+        );
+        console.get_mut().write_all(b"quit\n").await.unwrap();
+        assert_eq!(read_line(&mut console).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,289 @@
+//! Bot players hosted by the server.
+//!
+//! A bot takes a lobby seat like any player and plays through the same
+//! [`LockstepRelay`](crate::lockstep::LockstepRelay): as soon as a tick
+//! opens, the session asks each [`LockstepBot`] for its commands and
+//! submits them, and it confirms the bot's turns with the server's own
+//! state hash, since the server's game is the one the bot plays. Bots
+//! never hold up the players they share a game with; a game of bots alone
+//! only moves as far as [`MAX_INPUT_LEAD`](crate::lockstep::MAX_INPUT_LEAD)
+//! allows per request.
+//!
+//! With the `bots` feature, on by default, [`StrategyBot`] plays a
+//! [`Strategy`](rts_core::strategy::Strategy) through the headless
+//! runner's [`StrategyExecutor`](rts_headless::strategies::StrategyExecutor).
+//! Lockstep turns carry unit commands only, so it commands the units its
+//! faction has, attacking, defending and holding as its strategy decides.
+
+use std::fmt::Debug;
+
+use rts_core::factions::FactionId;
+use rts_core::simulation::Simulation;
+
+use crate::lockstep::EntityCommand;
+
+/// Strategy bots play when none is asked for.
+pub const DEFAULT_BOT_STRATEGY: &str = "balanced";
+
+/// A player the server plays itself.
+pub trait LockstepBot: Debug + Send {
+    /// Commands for the tick `sim` is about to play, for `faction`'s
+    /// entities only.
+    fn commands(&mut self, sim: &Simulation, faction: FactionId) -> Vec<EntityCommand>;
+}
+
+/// Plays no commands: holds the seat of a player kicked from a running
+/// game, so the others are not left waiting on their turns.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IdleBot;
+
+impl LockstepBot for IdleBot {
+    fn commands(&mut self, _sim: &Simulation, _faction: FactionId) -> Vec<EntityCommand> {
+        Vec::new()
+    }
+}
+
+/// A bot playing the strategy preset `name`, or None if there is no such
+/// preset or bots are not built in.
+#[must_use]
+pub fn strategy_bot(name: &str) -> Option<Box<dyn LockstepBot>> {
+    #[cfg(feature = "bots")]
+    {
+        StrategyBot::preset(name).map(|bot| Box::new(bot) as Box<dyn LockstepBot>)
+    }
+    #[cfg(not(feature = "bots"))]
+    {
+        let _ = name;
+        None
+    }
+}
+
+#[cfg(feature = "bots")]
+pub use strategy::StrategyBot;
+
+#[cfg(feature = "bots")]
+mod strategy {
+    use std::collections::BTreeSet;
+
+    use rts_core::components::{Command, EntityId};
+    use rts_core::factions::FactionId;
+    use rts_core::math::{Fixed, Vec2Fixed};
+    use rts_core::player_facade::VisibleEnemy;
+    use rts_core::simulation::{Simulation, TICK_RATE};
+    use rts_core::strategy::Strategy;
+    use rts_headless::strategies::{StrategyExecutor, TacticalDecision};
+
+    use super::LockstepBot;
+    use crate::lockstep::EntityCommand;
+
+    /// Enemies this close to the bot's depot put its base under attack.
+    const DEFEND_RADIUS: i32 = 400;
+
+    /// Plays a [`Strategy`] through a [`StrategyExecutor`].
+    ///
+    /// Every [`think_interval`](Self::with_think_interval) ticks it asks
+    /// the executor what to do and attack-moves its army accordingly: at
+    /// the nearest enemy it can see, else the nearest enemy starting
+    /// position, when attacking or scouting, and home when defending. An
+    /// order goes to each unit once, so units keep their paths until the
+    /// decision or target changes.
+    #[derive(Debug)]
+    pub struct StrategyBot {
+        executor: StrategyExecutor,
+        think_interval: u64,
+        /// Where enemy depots stood when the bot first looked: the starting
+        /// positions every player is told at launch.
+        enemy_starts: Option<Vec<Vec2Fixed>>,
+        /// Latest order, and the units it has gone to.
+        order: Option<(TacticalDecision, Vec2Fixed)>,
+        ordered: BTreeSet<EntityId>,
+    }
+
+    impl StrategyBot {
+        /// Bot playing `strategy`, thinking once a second.
+        #[must_use]
+        pub fn new(strategy: Strategy) -> Self {
+            Self {
+                executor: StrategyExecutor::new(strategy),
+                think_interval: u64::from(TICK_RATE),
+                enemy_starts: None,
+                order: None,
+                ordered: BTreeSet::new(),
+            }
+        }
+
+        /// Bot playing the strategy preset `name`, if there is one.
+        #[must_use]
+        pub fn preset(name: &str) -> Option<Self> {
+            Strategy::preset(name).map(Self::new)
+        }
+
+        /// Builder method to think every `ticks` ticks (at least 1).
+        #[must_use]
+        pub fn with_think_interval(mut self, ticks: u64) -> Self {
+            self.think_interval = ticks.max(1);
+            self
+        }
+
+        /// Name of the strategy played.
+        #[must_use]
+        pub fn name(&self) -> &str {
+            self.executor.name()
+        }
+    }
+
+    impl LockstepBot for StrategyBot {
+        fn commands(&mut self, sim: &Simulation, faction: FactionId) -> Vec<EntityCommand> {
+            let tick = sim.get_tick();
+            if tick % self.think_interval != 0 {
+                return Vec::new();
+            }
+
+            let mut own = sim.get_faction_entities(faction);
+            own.sort_unstable();
+            let home = own
+                .iter()
+                .filter_map(|&id| sim.get_entity(id))
+                .find(|entity| entity.depot.is_some())
+                .and_then(|entity| entity.position)
+                .map(|position| position.value);
+            let army: Vec<EntityId> = own
+                .iter()
+                .copied()
+                .filter(|&id| {
+                    sim.get_entity(id).is_some_and(|entity| {
+                        entity.movement.is_some() && entity.combat_stats.is_some()
+                    })
+                })
+                .collect();
+            let enemies = sim.get_visible_enemies_for(faction);
+            let starts = self
+                .enemy_starts
+                .get_or_insert_with(|| enemy_depots(sim, faction));
+
+            let radius = Fixed::from_num(DEFEND_RADIUS);
+            let under_attack = home.is_some_and(|home| {
+                enemies
+                    .iter()
+                    .any(|enemy| enemy.position.distance_squared(home) <= radius * radius)
+            });
+            let enemy_army = enemies.iter().filter(|enemy| !enemy.is_depot).count();
+            let decision = self.executor.decide_action(
+                tick,
+                army.len() as u32,
+                enemy_army as u32,
+                under_attack,
+                0,
+                false,
+            );
+            let from = home.unwrap_or_default();
+            let target = match decision {
+                TacticalDecision::Attack | TacticalDecision::Scout => {
+                    attack_target(from, &enemies, starts)
+                }
+                TacticalDecision::Defend => home,
+                TacticalDecision::Hold | TacticalDecision::Expand => None,
+            };
+
+            let Some(target) = target else {
+                self.order = None;
+                return Vec::new();
+            };
+            if self.order != Some((decision, target)) {
+                self.order = Some((decision, target));
+                self.ordered.clear();
+            }
+            army.into_iter()
+                .filter(|&entity| self.ordered.insert(entity))
+                .map(|entity| EntityCommand {
+                    entity,
+                    command: Command::AttackMove(target),
+                })
+                .collect()
+        }
+    }
+
+    /// Positions of every enemy depot, in entity ID order.
+    fn enemy_depots(sim: &Simulation, faction: FactionId) -> Vec<Vec2Fixed> {
+        let mut ids = sim.entities().sorted_ids();
+        ids.retain(|&id| {
+            sim.get_entity(id).is_some_and(|entity| {
+                entity.depot.is_some() && entity.faction.is_some_and(|f| f.faction != faction)
+            })
+        });
+        ids.into_iter()
+            .filter_map(|id| sim.get_entity(id)?.position)
+            .map(|position| position.value)
+            .collect()
+    }
+
+    /// Where to attack from `from`: the nearest visible enemy depot, else
+    /// the nearest visible enemy, else the nearest enemy start.
+    fn attack_target(
+        from: Vec2Fixed,
+        enemies: &[VisibleEnemy],
+        starts: &[Vec2Fixed],
+    ) -> Option<Vec2Fixed> {
+        let nearest = |positions: &mut dyn Iterator<Item = Vec2Fixed>| {
+            positions.min_by_key(|position| position.distance_squared(from))
+        };
+        nearest(&mut enemies.iter().filter(|e| e.is_depot).map(|e| e.position))
+            .or_else(|| nearest(&mut enemies.iter().map(|e| e.position)))
+            .or_else(|| nearest(&mut starts.iter().copied()))
+    }
+}
+
+#[cfg(all(test, feature = "bots"))]
+mod tests {
+    use super::*;
+    use rts_core::components::{Command, FactionMember};
+    use rts_core::math::{Fixed, Vec2Fixed};
+    use rts_core::simulation::EntitySpawnParams;
+    use rts_core::strategy::Strategy;
+
+    fn at(x: i32) -> Vec2Fixed {
+        Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(0))
+    }
+
+    #[test]
+    fn test_strategy_bot_attacks_enemy_start_once_per_order() {
+        let mut sim = Simulation::new();
+        let mut spawn = |faction, x, depot: bool| {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(at(x)),
+                health: Some(100),
+                movement: (!depot).then(|| Fixed::from_num(2)),
+                combat_stats: (!depot).then(Default::default),
+                is_depot: depot,
+                faction: Some(FactionMember::new(faction, 0)),
+                ..Default::default()
+            })
+        };
+        spawn(FactionId::Continuity, 0, true);
+        let unit = spawn(FactionId::Continuity, 10, false);
+        spawn(FactionId::Collegium, 3000, true);
+
+        let mut bot = StrategyBot::new(Strategy {
+            attack_timing: 0,
+            aggression_percent: 100,
+            min_wave_size: None,
+            ..Strategy::rush()
+        });
+        assert_eq!(bot.name(), "Rush");
+        let commands = bot.commands(&sim, FactionId::Continuity);
+        assert_eq!(
+            commands,
+            vec![EntityCommand {
+                entity: unit,
+                command: Command::AttackMove(at(3000)),
+            }]
+        );
+        // Already on its way
+        assert!(bot
+            .with_think_interval(1)
+            .commands(&sim, FactionId::Continuity)
+            .is_empty());
+        assert!(strategy_bot(DEFAULT_BOT_STRATEGY).is_some());
+        assert!(strategy_bot("no such strategy").is_none());
+    }
+}
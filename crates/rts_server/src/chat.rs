@@ -0,0 +1,161 @@
+//! In-game chat and map pings.
+//!
+//! Players talk to everyone in their session or only to their team, and
+//! mark spots on the map with a [`MapPing`]. The server checks each
+//! message, logs it with the game, and relays it to the session's
+//! subscribers; team messages only reach players of the sender's faction,
+//! or the other spectators when a spectator sends one.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use rts_core::math::Vec2Fixed;
+
+/// Longest chat message, in characters.
+pub const MAX_CHAT_CHARS: usize = 200;
+
+/// Errors sending chat.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ChatError {
+    /// The message has no text.
+    #[error("chat message is empty")]
+    Empty,
+
+    /// The message is longer than [`MAX_CHAT_CHARS`].
+    #[error("chat message is {len} characters (at most {max})")]
+    TooLong {
+        /// Characters sent.
+        len: usize,
+        /// Characters allowed.
+        max: usize,
+    },
+}
+
+/// Who a chat message or ping is for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatChannel {
+    /// Everyone in the session, spectators included.
+    #[default]
+    All,
+    /// The sender's team only.
+    Team,
+}
+
+/// What a map ping asks of the players who see it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PingKind {
+    /// Look here.
+    #[default]
+    Alert,
+    /// Attack here.
+    Attack,
+    /// Defend here.
+    Defend,
+    /// Gather here.
+    Rally,
+}
+
+/// A chat message as relayed to its recipients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    /// Player or spectator who sent it.
+    pub from: String,
+    /// Who it was sent to.
+    pub channel: ChatChannel,
+    /// Message text.
+    pub text: String,
+}
+
+/// A spot marked on the map, as relayed to its recipients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MapPing {
+    /// Player or spectator who pinged.
+    pub from: String,
+    /// Who it was sent to.
+    pub channel: ChatChannel,
+    /// World position pinged.
+    pub position: Vec2Fixed,
+    /// What the ping asks for.
+    pub kind: PingKind,
+}
+
+/// A chat message or ping, with who receives it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatEvent {
+    /// Names that receive it; None for everyone in the session.
+    pub to: Option<Vec<String>>,
+    /// The message or ping.
+    pub content: ChatContent,
+}
+
+impl ChatEvent {
+    /// Whether `name` receives the event.
+    #[must_use]
+    pub fn is_for(&self, name: &str) -> bool {
+        self.to
+            .as_ref()
+            .map_or(true, |to| to.iter().any(|n| n == name))
+    }
+}
+
+/// Either kind of chat traffic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatContent {
+    /// A text message.
+    Message(ChatMessage),
+    /// A map ping.
+    Ping(MapPing),
+}
+
+/// Check a chat message's text, returning it trimmed.
+///
+/// # Errors
+///
+/// Returns an error if the text is blank or longer than
+/// [`MAX_CHAT_CHARS`].
+pub fn check_text(text: &str) -> Result<&str, ChatError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(ChatError::Empty);
+    }
+    let len = text.chars().count();
+    if len > MAX_CHAT_CHARS {
+        return Err(ChatError::TooLong {
+            len,
+            max: MAX_CHAT_CHARS,
+        });
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chat_text_is_trimmed_and_bounded() {
+        assert_eq!(check_text("  gg  "), Ok("gg"));
+        assert_eq!(check_text(" \n "), Err(ChatError::Empty));
+        let long = "a".repeat(MAX_CHAT_CHARS + 1);
+        assert_eq!(
+            check_text(&long),
+            Err(ChatError::TooLong {
+                len: MAX_CHAT_CHARS + 1,
+                max: MAX_CHAT_CHARS,
+            })
+        );
+
+        let team = ChatEvent {
+            to: Some(vec!["alice".to_string()]),
+            content: ChatContent::Message(ChatMessage {
+                from: "alice".to_string(),
+                channel: ChatChannel::Team,
+                text: "push mid".to_string(),
+            }),
+        };
+        assert!(team.is_for("alice"));
+        assert!(!team.is_for("bob"));
+    }
+}
@@ -0,0 +1,350 @@
+//! Server configuration from a TOML file and environment variables.
+//!
+//! Settings are layered: built-in defaults, then an optional TOML file,
+//! then `RTS_SERVER_*` environment variables. This keeps container
+//! deployments simple: bake a file into the image or mount one, and
+//! override individual values per deployment through the environment.
+//!
+//! | Variable | Setting |
+//! | -------- | ------- |
+//! | `RTS_SERVER_CONFIG` | Path of the TOML file to load |
+//! | `RTS_SERVER_PORT` | [`ServerConfig::port`] |
+//! | `RTS_SERVER_MAX_PLAYERS` | [`ServerConfig::max_players`] |
+//! | `RTS_SERVER_MAX_SPECTATORS` | [`ServerConfig::max_spectators`] |
+//! | `RTS_SERVER_MAX_SESSIONS` | [`ServerConfig::max_sessions`] |
+//! | `RTS_SERVER_MAP_POOL` | [`ServerConfig::map_pool`], comma-separated |
+//! | `RTS_SERVER_TICK_RATE` | [`ServerConfig::tick_rate`] |
+//! | `RTS_SERVER_COUNTDOWN_SECS` | [`ServerConfig::countdown_secs`] |
+//! | `RTS_SERVER_MIN_INPUT_DELAY` | [`ServerConfig::min_input_delay`] |
+//! | `RTS_SERVER_MAX_INPUT_DELAY` | [`ServerConfig::max_input_delay`] |
+//! | `RTS_SERVER_DROP_AFTER_STALLED_TICKS` | [`ServerConfig::drop_after_stalled_ticks`] |
+//! | `RTS_SERVER_OVERLAY_PORT` | [`ServerConfig::overlay_port`] |
+//! | `RTS_SERVER_HEALTH_PORT` | [`ServerConfig::health_port`] |
+//! | `RTS_SERVER_ADMIN_PORT` | [`ServerConfig::admin_port`] |
+//! | `RTS_SERVER_ADMIN_TOKEN` | [`ServerConfig::admin_token`] |
+//! | `RTS_SERVER_IDENTITY_SECRET` | [`ServerConfig::identity_secret`] |
+//! | `RTS_SERVER_RATINGS_FILE` | [`ServerConfig::ratings_file`] |
+//! | `RTS_SERVER_REPLAY_DIR` | [`ServerConfig::replay_dir`] |
+//! | `RTS_SERVER_CHECKPOINT_DIR` | [`ServerConfig::checkpoint_dir`] |
+//! | `RTS_SERVER_SHUTDOWN_GRACE_SECS` | [`ServerConfig::shutdown_grace_secs`] |
+//! | `RTS_SERVER_LOG` | [`ServerConfig::log_filter`] |
+//! | `RTS_SERVER_LOG_COLOR` | [`ServerConfig::log_color`] |
+//!
+//! Port variables accept `off` to disable an optional listener, and
+//! `RTS_SERVER_DROP_AFTER_STALLED_TICKS` accepts `off` to never drop
+//! players.
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::delay::{DEFAULT_MAX_INPUT_DELAY, DEFAULT_MIN_INPUT_DELAY};
+
+/// Environment variable naming the config file.
+pub const CONFIG_PATH_VAR: &str = "RTS_SERVER_CONFIG";
+
+/// Prefix shared by all server environment variables.
+const ENV_PREFIX: &str = "RTS_SERVER_";
+
+/// Errors loading server configuration.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// The config file could not be read.
+    #[error("Failed to read config file {path}: {source}")]
+    Io {
+        /// File that was being read.
+        path: PathBuf,
+        /// Underlying error.
+        source: std::io::Error,
+    },
+
+    /// The config file is not valid TOML for [`ServerConfig`].
+    #[error("Failed to parse config file {path}: {message}")]
+    Parse {
+        /// File that was being parsed.
+        path: PathBuf,
+        /// Parser message.
+        message: String,
+    },
+
+    /// An environment variable holds a value of the wrong type.
+    #[error("Invalid value {value:?} for {var}")]
+    InvalidEnv {
+        /// Variable name.
+        var: String,
+        /// Offending value.
+        value: String,
+    },
+
+    /// An `RTS_SERVER_*` variable that no setting corresponds to.
+    #[error("Unknown environment variable {0}")]
+    UnknownEnv(String),
+}
+
+/// Server configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Port to listen on.
+    pub port: u16,
+    /// Maximum players per game.
+    pub max_players: u8,
+    /// Maximum spectators per game.
+    pub max_spectators: usize,
+    /// Maximum lobbies and games hosted at once.
+    pub max_sessions: usize,
+    /// Map IDs offered for competitive veto and matchmaking.
+    pub map_pool: Vec<String>,
+    /// Tick rate (should match client).
+    pub tick_rate: u32,
+    /// Seconds a lobby counts down once every player is ready.
+    pub countdown_secs: u64,
+    /// Fewest ticks ahead lockstep players schedule their commands for.
+    pub min_input_delay: u64,
+    /// Most ticks ahead lockstep players schedule their commands for,
+    /// however slow their connection.
+    pub max_input_delay: u64,
+    /// Ticks a lockstep game waits on some of its players before dropping
+    /// them (None waits for ever).
+    pub drop_after_stalled_ticks: Option<u64>,
+    /// Port for the read-only score overlay websocket (None disables it).
+    pub overlay_port: Option<u16>,
+    /// Port for the `/healthz` and `/readyz` HTTP probes (None disables it).
+    pub health_port: Option<u16>,
+    /// Port for the [admin console](crate::admin) (None disables it).
+    /// Without an [`admin_token`](Self::admin_token) it only listens on
+    /// the loopback interface.
+    pub admin_port: Option<u16>,
+    /// Secret admin console connections must authenticate with. Setting it
+    /// opens the console on every interface.
+    pub admin_token: Option<String>,
+    /// Secret player identity tokens are signed with (see
+    /// [`PlayerRegistry`](crate::lobby::PlayerRegistry)). Without one,
+    /// tokens stop working when the server restarts.
+    pub identity_secret: Option<String>,
+    /// JSON file player ratings are kept in (None keeps them in memory
+    /// until the server stops).
+    pub ratings_file: Option<PathBuf>,
+    /// Directory finished games' replays are saved to, with the players'
+    /// IDs (None records no replays).
+    pub replay_dir: Option<PathBuf>,
+    /// Directory games still running at shutdown are checkpointed to
+    /// (None abandons them).
+    pub checkpoint_dir: Option<PathBuf>,
+    /// Seconds to let running games finish after SIGTERM before
+    /// checkpointing them.
+    pub shutdown_grace_secs: u64,
+    /// Default `tracing` filter, used when `RUST_LOG` is not set.
+    pub log_filter: String,
+    /// Colour log output (None colours only when stdout is a terminal).
+    pub log_color: Option<bool>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: 7777,
+            max_players: 8,
+            max_spectators: 4,
+            max_sessions: 16,
+            map_pool: Vec::new(),
+            tick_rate: rts_core::simulation::TICK_RATE,
+            countdown_secs: 5,
+            min_input_delay: DEFAULT_MIN_INPUT_DELAY,
+            max_input_delay: DEFAULT_MAX_INPUT_DELAY,
+            // 30 seconds at the default tick rate
+            drop_after_stalled_ticks: Some(600),
+            overlay_port: None,
+            health_port: Some(8080),
+            admin_port: None,
+            admin_token: None,
+            identity_secret: None,
+            ratings_file: None,
+            replay_dir: None,
+            checkpoint_dir: None,
+            shutdown_grace_secs: 30,
+            log_filter: "info".to_string(),
+            log_color: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Load the configuration for this process.
+    ///
+    /// Reads `file`, or the file named by [`CONFIG_PATH_VAR`] if `file` is
+    /// None, then applies `RTS_SERVER_*` environment overrides.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed, or an
+    /// environment variable is invalid.
+    pub fn load(file: Option<&Path>) -> Result<Self, ConfigError> {
+        let file = file
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os(CONFIG_PATH_VAR).map(PathBuf::from));
+        let mut config = match file {
+            Some(path) => Self::from_file(&path)?,
+            None => Self::default(),
+        };
+        config.apply_env(std::env::vars())?;
+        Ok(config)
+    }
+
+    /// Read a TOML config file. Missing keys keep their defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        toml::from_str(&content).map_err(|e| ConfigError::Parse {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Override settings from `RTS_SERVER_*` variables.
+    ///
+    /// Variables without the prefix are ignored; unknown ones with it are
+    /// rejected so typos don't silently fall back to defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for unknown or unparseable variables.
+    pub fn apply_env(
+        &mut self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<(), ConfigError> {
+        for (var, value) in vars {
+            let Some(key) = var.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            match key {
+                "CONFIG" => {}
+                "PORT" => self.port = parse_env(&var, &value)?,
+                "MAX_PLAYERS" => self.max_players = parse_env(&var, &value)?,
+                "MAX_SPECTATORS" => self.max_spectators = parse_env(&var, &value)?,
+                "MAX_SESSIONS" => self.max_sessions = parse_env(&var, &value)?,
+                "MAP_POOL" => {
+                    self.map_pool = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|map| !map.is_empty())
+                        .map(String::from)
+                        .collect();
+                }
+                "TICK_RATE" => self.tick_rate = parse_env(&var, &value)?,
+                "COUNTDOWN_SECS" => self.countdown_secs = parse_env(&var, &value)?,
+                "MIN_INPUT_DELAY" => self.min_input_delay = parse_env(&var, &value)?,
+                "MAX_INPUT_DELAY" => self.max_input_delay = parse_env(&var, &value)?,
+                "DROP_AFTER_STALLED_TICKS" => {
+                    self.drop_after_stalled_ticks = parse_optional(&var, &value)?;
+                }
+                "OVERLAY_PORT" => self.overlay_port = parse_optional(&var, &value)?,
+                "HEALTH_PORT" => self.health_port = parse_optional(&var, &value)?,
+                "ADMIN_PORT" => self.admin_port = parse_optional(&var, &value)?,
+                "ADMIN_TOKEN" => self.admin_token = (!value.is_empty()).then_some(value),
+                "IDENTITY_SECRET" => {
+                    self.identity_secret = (!value.is_empty()).then_some(value);
+                }
+                "RATINGS_FILE" => {
+                    self.ratings_file = (!value.is_empty()).then(|| PathBuf::from(&value));
+                }
+                "REPLAY_DIR" => {
+                    self.replay_dir = (!value.is_empty()).then(|| PathBuf::from(&value));
+                }
+                "CHECKPOINT_DIR" => {
+                    self.checkpoint_dir = (!value.is_empty()).then(|| PathBuf::from(&value));
+                }
+                "SHUTDOWN_GRACE_SECS" => self.shutdown_grace_secs = parse_env(&var, &value)?,
+                "LOG" => self.log_filter = value,
+                "LOG_COLOR" => self.log_color = Some(parse_env(&var, &value)?),
+                _ => return Err(ConfigError::UnknownEnv(var)),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_env<T: FromStr>(var: &str, value: &str) -> Result<T, ConfigError> {
+    value.trim().parse().map_err(|_| ConfigError::InvalidEnv {
+        var: var.to_string(),
+        value: value.to_string(),
+    })
+}
+
+fn parse_optional<T: FromStr>(var: &str, value: &str) -> Result<Option<T>, ConfigError> {
+    match value.trim() {
+        "" | "off" => Ok(None),
+        value => parse_env(var, value).map(Some),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_file_then_env_layering() {
+        let mut config: ServerConfig = toml::from_str(
+            r#"
+            port = 9000
+            overlay_port = 9001
+            checkpoint_dir = "/var/lib/rts"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.port, 9000);
+        assert_eq!(config.max_players, ServerConfig::default().max_players);
+
+        config
+            .apply_env(env(&[
+                ("RTS_SERVER_PORT", "9100"),
+                ("RTS_SERVER_OVERLAY_PORT", "off"),
+                ("RTS_SERVER_LOG_COLOR", "false"),
+                ("RTS_SERVER_MAP_POOL", "dunes, delta,,crater"),
+                ("RTS_SERVER_COUNTDOWN_SECS", "0"),
+                ("RTS_SERVER_ADMIN_PORT", "7780"),
+                ("RTS_SERVER_DROP_AFTER_STALLED_TICKS", "off"),
+                ("RTS_SERVER_ADMIN_TOKEN", "hunter2"),
+                ("HOME", "/root"),
+            ]))
+            .unwrap();
+        assert_eq!(config.port, 9100);
+        assert_eq!(config.overlay_port, None);
+        assert_eq!(config.log_color, Some(false));
+        assert_eq!(config.map_pool, vec!["dunes", "delta", "crater"]);
+        assert_eq!(config.countdown_secs, 0);
+        assert_eq!(config.admin_port, Some(7780));
+        assert_eq!(config.drop_after_stalled_ticks, None);
+        assert_eq!(config.admin_token.as_deref(), Some("hunter2"));
+        assert_eq!(config.checkpoint_dir, Some(PathBuf::from("/var/lib/rts")));
+    }
+
+    #[test]
+    fn test_rejects_bad_values_and_unknown_keys() {
+        let mut config = ServerConfig::default();
+        assert!(matches!(
+            config.apply_env(env(&[("RTS_SERVER_PORT", "seventy")])),
+            Err(ConfigError::InvalidEnv { .. })
+        ));
+        assert!(matches!(
+            config.apply_env(env(&[("RTS_SERVER_PROT", "7000")])),
+            Err(ConfigError::UnknownEnv(_))
+        ));
+        assert!(toml::from_str::<ServerConfig>("prot = 7000").is_err());
+    }
+}
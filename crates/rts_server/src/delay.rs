@@ -0,0 +1,151 @@
+//! Adaptive input delay for lockstep games.
+//!
+//! Players schedule their commands a few ticks ahead of the tick they are
+//! playing, so the commands can reach the server and come back in a
+//! [`Turn`](crate::lockstep::Turn) before anyone needs it. Too short a
+//! delay and the game stalls on every slow round trip; too long and every
+//! order feels sluggish.
+//!
+//! An [`InputDelay`] picks one player's delay from how long they take to
+//! confirm turns, the way TCP sets its retransmission timeout: a smoothed
+//! round trip plus four times its variation, here converted to ticks and
+//! held within [`DelayBounds`]. It rises as soon as a connection slows
+//! down, and falls only once the target is more than a tick below it, so
+//! jitter doesn't make it flap.
+
+use std::time::Duration;
+
+use crate::lockstep::MAX_INPUT_LEAD;
+
+/// Shortest input delay players are given by default, in ticks.
+pub const DEFAULT_MIN_INPUT_DELAY: u64 = 2;
+
+/// Longest input delay players are given by default, in ticks.
+pub const DEFAULT_MAX_INPUT_DELAY: u64 = 16;
+
+/// The range an [`InputDelay`] stays within, in ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DelayBounds {
+    min: u64,
+    max: u64,
+}
+
+impl DelayBounds {
+    /// Bounds from `min` to `max` ticks. The maximum is capped at
+    /// [`MAX_INPUT_LEAD`], since commands further ahead are refused, and
+    /// the minimum at the maximum.
+    #[must_use]
+    pub fn new(min: u64, max: u64) -> Self {
+        let max = max.min(MAX_INPUT_LEAD);
+        Self {
+            min: min.min(max),
+            max,
+        }
+    }
+
+    /// Shortest delay, which every player starts at.
+    #[must_use]
+    pub fn min(&self) -> u64 {
+        self.min
+    }
+
+    /// Longest delay.
+    #[must_use]
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+}
+
+impl Default for DelayBounds {
+    fn default() -> Self {
+        Self::new(DEFAULT_MIN_INPUT_DELAY, DEFAULT_MAX_INPUT_DELAY)
+    }
+}
+
+/// One player's input delay, adapted to their confirmation latency.
+#[derive(Debug, Clone)]
+pub struct InputDelay {
+    bounds: DelayBounds,
+    tick_secs: f64,
+    /// Smoothed latency and its mean deviation, in seconds, once sampled.
+    smoothed: Option<(f64, f64)>,
+    ticks: u64,
+}
+
+impl InputDelay {
+    /// Delay for a game ticking `tick_rate` times a second, starting at
+    /// the bounds' minimum.
+    #[must_use]
+    pub fn new(bounds: DelayBounds, tick_rate: u32) -> Self {
+        Self {
+            bounds,
+            tick_secs: 1.0 / f64::from(tick_rate.max(1)),
+            smoothed: None,
+            ticks: bounds.min(),
+        }
+    }
+
+    /// Ticks ahead of the tick being played to schedule commands for.
+    #[must_use]
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Smoothed confirmation latency, once one has been recorded.
+    #[must_use]
+    pub fn latency(&self) -> Option<Duration> {
+        self.smoothed
+            .map(|(latency, _)| Duration::from_secs_f64(latency))
+    }
+
+    /// Record how long the player took to confirm a turn, returning the
+    /// new delay if it changed.
+    pub fn record(&mut self, latency: Duration) -> Option<u64> {
+        let sample = latency.as_secs_f64();
+        let (smoothed, deviation) = match self.smoothed {
+            None => (sample, sample / 2.0),
+            Some((smoothed, deviation)) => (
+                smoothed * 7.0 / 8.0 + sample / 8.0,
+                deviation * 3.0 / 4.0 + (smoothed - sample).abs() / 4.0,
+            ),
+        };
+        self.smoothed = Some((smoothed, deviation));
+
+        let target = ((smoothed + 4.0 * deviation) / self.tick_secs).ceil() as u64;
+        let target = target.clamp(self.bounds.min(), self.bounds.max());
+        // A tick above the target is close enough
+        if (target..=target + 1).contains(&self.ticks) {
+            return None;
+        }
+        self.ticks = target;
+        Some(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_follows_latency_within_bounds() {
+        // 20 ticks a second: 50ms a tick
+        let mut delay = InputDelay::new(DelayBounds::new(2, 8), 20);
+        assert_eq!(delay.ticks(), 2);
+        assert_eq!(delay.latency(), None);
+
+        // A steady 90ms round trip settles just above its 1.8 ticks
+        assert_eq!(delay.record(Duration::from_millis(90)), Some(6));
+        for _ in 0..40 {
+            delay.record(Duration::from_millis(90));
+        }
+        assert_eq!(delay.ticks(), 2);
+        let latency = delay.latency().unwrap();
+        assert!(latency.abs_diff(Duration::from_millis(90)) < Duration::from_millis(1));
+
+        // Spikes raise it at once, up to the bound
+        assert_eq!(delay.record(Duration::from_secs(2)), Some(8));
+        assert_eq!(delay.record(Duration::from_millis(100)), None);
+
+        assert_eq!(DelayBounds::new(40, 100), DelayBounds::new(32, 32));
+    }
+}
@@ -0,0 +1,260 @@
+//! Games hosted by the server.
+//!
+//! A [`HostedGame`] owns one [`Simulation`] and remembers when it has been
+//! decided. Games that are still running when the server shuts down can be
+//! written out as an [`Autosave`] and resumed by a later process. Saves carry
+//! the game's [`Fingerprint`], so a build with a different engine refuses to
+//! resume them.
+//!
+//! A game can also [record a replay](HostedGame::record_replay) of every
+//! turn it plays, which the server saves once the game is decided.
+//!
+//! Lobbies launch into a [`skirmish`]: a procedural map generated from the
+//! session's seed, with a depot for every player at their starting position.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use rts_core::autosave::{Autosave, AUTOSAVE_EXTENSION};
+use rts_core::components::{Command, EntityId, FactionMember};
+use rts_core::error::{GameError, Result};
+use rts_core::factions::FactionId;
+use rts_core::fingerprint::Fingerprint;
+use rts_core::map_generation::{generate_map, MapConfig, SymmetryMode};
+use rts_core::math::Fixed;
+use rts_core::outcome::WinCondition;
+use rts_core::replay::Replay;
+use rts_core::simulation::{EntitySpawnParams, Simulation, TickEvents};
+
+/// File extension for the replays the server saves.
+pub const REPLAY_EXTENSION: &str = "replay";
+
+/// Health of the depot each player starts a skirmish with.
+const STARTING_DEPOT_HEALTH: u32 = 1000;
+
+/// One running game.
+#[derive(Debug)]
+pub struct HostedGame {
+    id: String,
+    map: Option<String>,
+    fingerprint: Fingerprint,
+    sim: Simulation,
+    win_condition: Option<WinCondition>,
+    replay: Option<Replay>,
+}
+
+impl HostedGame {
+    /// Host a simulation under `id`.
+    #[must_use]
+    pub fn new(id: impl Into<String>, sim: Simulation) -> Self {
+        Self {
+            id: id.into(),
+            map: None,
+            fingerprint: Fingerprint::engine(),
+            sim,
+            win_condition: None,
+            replay: None,
+        }
+    }
+
+    /// Builder method to record the map being played.
+    #[must_use]
+    pub fn with_map(mut self, map: impl Into<String>) -> Self {
+        self.map = Some(map.into());
+        self
+    }
+
+    /// Builder method to record the session fingerprint.
+    #[must_use]
+    pub fn with_fingerprint(mut self, fingerprint: Fingerprint) -> Self {
+        self.fingerprint = fingerprint;
+        self
+    }
+
+    /// Start recording a replay from the game's current state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the simulation cannot be serialized.
+    pub fn record_replay(&mut self) -> Result<()> {
+        let scenario = self.map.clone().unwrap_or_else(|| self.id.clone());
+        self.replay = Some(Replay::new(scenario, 0, &self.sim)?.with_fingerprint(self.fingerprint));
+        Ok(())
+    }
+
+    /// Resume a game from a checkpoint written by [`checkpoint`](Self::checkpoint).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the save does not restore.
+    pub fn resume(save: &Autosave) -> Result<Self> {
+        let mut game = Self::new(save.label.clone(), save.restore_simulation()?)
+            .with_fingerprint(save.fingerprint);
+        // The map travels as host state; saves without one have no map
+        game.map = save.host_state().ok().flatten();
+        Ok(game)
+    }
+
+    /// Game identifier.
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Map being played, if one was chosen.
+    #[must_use]
+    pub fn map(&self) -> Option<&str> {
+        self.map.as_deref()
+    }
+
+    /// Engine, data and map the game runs on.
+    #[must_use]
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint
+    }
+
+    /// The simulation.
+    #[must_use]
+    pub fn simulation(&self) -> &Simulation {
+        &self.sim
+    }
+
+    /// Winner, once the game is decided.
+    #[must_use]
+    pub fn winner(&self) -> Option<FactionId> {
+        self.win_condition.and_then(|c| c.winner())
+    }
+
+    /// How the game ended, once it is decided.
+    #[must_use]
+    pub fn win_condition(&self) -> Option<WinCondition> {
+        self.win_condition
+    }
+
+    /// Whether the game is decided.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.win_condition.is_some()
+    }
+
+    /// Advance one tick. Finished games no longer advance.
+    pub fn tick(&mut self) -> Option<TickEvents> {
+        if self.is_finished() {
+            return None;
+        }
+        let events = self.sim.tick();
+        self.win_condition = events.win_condition;
+        Some(events)
+    }
+
+    /// Apply a lockstep turn's commands, then advance one tick.
+    ///
+    /// Commands the simulation refuses are skipped, exactly as they are on
+    /// every client applying the same turn.
+    pub fn tick_with(&mut self, commands: &[(EntityId, Command)]) -> Option<TickEvents> {
+        if self.is_finished() {
+            return None;
+        }
+        if let Some(replay) = &mut self.replay {
+            replay.record_batch(self.sim.get_tick(), commands);
+        }
+        for (result, (entity, _)) in self.sim.apply_commands(commands).iter().zip(commands) {
+            if let Err(e) = result {
+                tracing::debug!(game = %self.id, entity, error = %e, "Skipped turn command");
+            }
+        }
+        self.tick()
+    }
+
+    /// Write the game to `<dir>/<id>.autosave` and return the path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created or the save
+    /// cannot be written.
+    pub fn checkpoint(&self, dir: &Path) -> Result<PathBuf> {
+        std::fs::create_dir_all(dir).map_err(|e| {
+            GameError::InvalidState(format!("Failed to create checkpoint directory: {}", e))
+        })?;
+        let path = dir.join(format!("{}.{}", self.id, AUTOSAVE_EXTENSION));
+        Autosave::capture(self.id.clone(), &self.sim)?
+            .with_fingerprint(self.fingerprint)
+            .with_host_state(&self.map)?
+            .save(&path)?;
+        Ok(path)
+    }
+
+    /// Write the replay recorded so far, finalized at the current tick
+    /// and annotated with the persistent IDs of `players` by name, to
+    /// `<dir>/<id>.replay`. Returns the path, or None if the game is not
+    /// recording.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created or the replay
+    /// cannot be written.
+    pub fn save_replay(
+        &self,
+        dir: &Path,
+        players: &BTreeMap<String, u64>,
+    ) -> Result<Option<PathBuf>> {
+        let Some(replay) = &self.replay else {
+            return Ok(None);
+        };
+        std::fs::create_dir_all(dir).map_err(|e| {
+            GameError::InvalidState(format!("Failed to create replay directory: {}", e))
+        })?;
+        let path = dir.join(format!("{}.{}", self.id, REPLAY_EXTENSION));
+        let mut replay = replay.clone().with_players(players.clone());
+        replay.finalize(self.sim.get_tick(), self.sim.state_hash());
+        replay.save(&path)?;
+        Ok(Some(path))
+    }
+}
+
+/// Build the game a lobby launches into.
+///
+/// The map is generated from `seed` with four-way symmetry, so every
+/// [starting position](crate::lobby::STARTING_POSITIONS) is equally good.
+/// Each `(faction, start)` seat gets a depot at that spawn point, with the
+/// seat's index as its player index. Starts beyond the map's spawn points
+/// are skipped.
+#[must_use]
+pub fn skirmish(seed: u64, seats: &[(FactionId, u8)]) -> Simulation {
+    let map = generate_map(MapConfig {
+        symmetry: SymmetryMode::FourWay,
+        ..MapConfig::medium().with_seed(seed)
+    });
+    let config = &map.config;
+    let mut sim = Simulation::with_nav_grid(
+        config.width,
+        config.height,
+        Fixed::from_num(config.cell_size),
+    );
+    for (i, (cell, level)) in map
+        .as_cell_types()
+        .into_iter()
+        .zip(map.as_elevations())
+        .enumerate()
+    {
+        let (x, y) = (i as u32 % config.width, i as u32 / config.width);
+        sim.nav_grid_mut().set_cell(x, y, cell);
+        sim.nav_grid_mut().set_elevation(x, y, level);
+    }
+    sim.seed_rng(seed);
+
+    for (index, &(faction, start)) in seats.iter().enumerate() {
+        let Some(spawn) = map.spawn_points.get(usize::from(start)) else {
+            continue;
+        };
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(spawn.position),
+            health: Some(STARTING_DEPOT_HEALTH),
+            is_depot: true,
+            faction: Some(FactionMember::new(faction, index as u8)),
+            debug_kind: Some("depot".to_string()),
+            ..Default::default()
+        });
+    }
+    sim
+}
@@ -0,0 +1,181 @@
+//! Liveness and readiness probes for container orchestrators.
+//!
+//! A [`HealthServer`] answers plain HTTP `GET` requests:
+//!
+//! - `/healthz` returns `200` while the process is serving at all.
+//! - `/readyz` returns `200` while the server accepts new games, and `503`
+//!   during startup, while at its session cap, and once shutdown has begun,
+//!   so load balancers stop routing players to a full or draining instance.
+//!
+//! Anything else gets a `404`. Requests are tiny and responses close the
+//! connection, so no HTTP library is needed.
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+/// Largest request head read before giving up.
+const MAX_REQUEST_BYTES: usize = 4096;
+
+/// Shared server state reported by the probes.
+#[derive(Debug, Clone, Default)]
+pub struct ServerStatus {
+    inner: Arc<StatusInner>,
+}
+
+#[derive(Debug, Default)]
+struct StatusInner {
+    ready: AtomicBool,
+    active_games: AtomicUsize,
+}
+
+impl ServerStatus {
+    /// Create a status that is not yet ready.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark the server as accepting (or no longer accepting) games.
+    pub fn set_ready(&self, ready: bool) {
+        self.inner.ready.store(ready, Ordering::SeqCst);
+    }
+
+    /// Whether the server accepts new games.
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        self.inner.ready.load(Ordering::SeqCst)
+    }
+
+    /// Record how many games are running.
+    pub fn set_active_games(&self, count: usize) {
+        self.inner.active_games.store(count, Ordering::SeqCst);
+    }
+
+    /// Number of games running.
+    #[must_use]
+    pub fn active_games(&self) -> usize {
+        self.inner.active_games.load(Ordering::SeqCst)
+    }
+}
+
+/// HTTP listener serving `/healthz` and `/readyz`.
+#[derive(Debug)]
+pub struct HealthServer {
+    listener: TcpListener,
+    status: ServerStatus,
+}
+
+impl HealthServer {
+    /// Bind the probe listener.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound.
+    pub async fn bind(addr: impl ToSocketAddrs, status: ServerStatus) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        Ok(Self { listener, status })
+    }
+
+    /// Address the server is listening on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket address cannot be read.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Answer probes until the task is dropped.
+    pub async fn serve(self) {
+        loop {
+            match self.listener.accept().await {
+                Ok((stream, peer)) => {
+                    let status = self.status.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = answer_probe(stream, &status).await {
+                            tracing::debug!(%peer, error = %e, "Health probe failed");
+                        }
+                    });
+                }
+                Err(e) => tracing::warn!(error = %e, "Health accept failed"),
+            }
+        }
+    }
+}
+
+/// Read one request head and write the matching response.
+async fn answer_probe(mut stream: TcpStream, status: &ServerStatus) -> io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 512];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+        let read = stream.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+    }
+
+    let request_line = request.split(|&b| b == b'\n').next().unwrap_or_default();
+    let mut parts = std::str::from_utf8(request_line)
+        .unwrap_or_default()
+        .split_whitespace();
+    let (status_line, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/healthz")) => ("200 OK", "ok".to_string()),
+        (Some("GET"), Some("/readyz")) if status.is_ready() => (
+            "200 OK",
+            format!("ready ({} active games)", status.active_games()),
+        ),
+        (Some("GET"), Some("/readyz")) => ("503 Service Unavailable", "not ready".to_string()),
+        _ => ("404 Not Found", "not found".to_string()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {status_line}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(format!("GET {path} HTTP/1.1\r\nHost: probe\r\n\r\n").as_bytes())
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_probes_follow_readiness() {
+        let status = ServerStatus::new();
+        let server = HealthServer::bind("127.0.0.1:0", status.clone())
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        tokio::spawn(server.serve());
+
+        assert!(get(addr, "/healthz").await.starts_with("HTTP/1.1 200"));
+        assert!(get(addr, "/readyz").await.starts_with("HTTP/1.1 503"));
+
+        status.set_ready(true);
+        assert!(get(addr, "/readyz").await.starts_with("HTTP/1.1 200"));
+        assert!(get(addr, "/metrics").await.starts_with("HTTP/1.1 404"));
+
+        // Draining: still alive, but no longer taking games
+        status.set_ready(false);
+        assert!(get(addr, "/healthz").await.starts_with("HTTP/1.1 200"));
+        assert!(get(addr, "/readyz").await.starts_with("HTTP/1.1 503"));
+    }
+}
@@ -8,26 +8,20 @@
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 
+pub mod admin;
+pub mod bots;
+pub mod chat;
+pub mod config;
+pub mod delay;
+pub mod game;
+pub mod health;
 pub mod lobby;
+pub mod lockstep;
 pub mod network;
+pub mod overlay;
+pub mod rating;
+pub mod session;
+pub mod shutdown;
+pub mod snapshot;
 
-/// Server configuration.
-#[derive(Debug, Clone)]
-pub struct ServerConfig {
-    /// Port to listen on.
-    pub port: u16,
-    /// Maximum players per game.
-    pub max_players: u8,
-    /// Tick rate (should match client).
-    pub tick_rate: u32,
-}
-
-impl Default for ServerConfig {
-    fn default() -> Self {
-        Self {
-            port: 7777,
-            max_players: 8,
-            tick_rate: rts_core::simulation::TICK_RATE,
-        }
-    }
-}
+pub use config::ServerConfig;
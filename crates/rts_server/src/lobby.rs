@@ -1,3 +1,1297 @@
 //! Lobby and matchmaking.
+//!
+//! Competitive lobbies choose their map from the server's [`MapPool`]
+//! through a [`MapVeto`]: the two captains take turns banning or picking
+//! maps in a fixed [`VetoStep`] sequence until one map is left or picked.
+//! Every finished game lands in the [`MatchHistory`], whose pick counts
+//! weight the matchmaking queue's random map choice
+//! ([`MapPool::weighted_choice`]) towards maps players actually enjoy.
+//!
+//! Players waiting in a lobby sit in its [`LobbyRoom`]: each picks a
+//! faction and optionally one of the [`STARTING_POSITIONS`], then marks
+//! themselves ready. Changing a choice clears the player's ready flag, so
+//! nobody is launched into a setup they did not agree to. Spectators wait
+//! in the room too, but take no seat: they choose nothing, never hold up
+//! the launch, and don't play.
+//!
+//! Players may present an [`Identity`]: a [`PlayerId`] that stays the same
+//! from game to game, and the name it was registered under. The server's
+//! [`PlayerRegistry`] registers new players and signs their identity into
+//! a token, which they present on later connections; the token is the
+//! identity's JSON in base64, a dot, and a keyed BLAKE3 hash of it, so the
+//! server stores no credentials and any server signing with the same
+//! secret accepts it. Identified players' seats and [`MatchRecord`]s
+//! carry their IDs. Only one player may use a name in a lobby.
+//!
+//! Identified players can wait in the [`MatchQueue`] for a rated game.
+//! It pairs players whose [ratings](crate::rating) are within
+//! [`QUEUE_RATING_GAP`] of each other, oldest first, closest rating
+//! first, and widens the gap it accepts the longer a player waits, so
+//! nobody at the far ends of the ladder waits forever.
 
-// Placeholder - will be implemented in Phase 5
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Instant;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use rts_core::factions::FactionId;
+use rts_core::outcome::WinCondition;
+use rts_core::rng::SimRng;
+
+use crate::rating::PublicRating;
+use crate::session::SessionId;
+
+/// Errors during a map veto.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum VetoError {
+    /// The server has no map pool configured.
+    #[error("No map pool configured")]
+    EmptyPool,
+
+    /// The step sequence does not end on exactly one map.
+    #[error("Veto sequence must end in a pick or leave exactly one map")]
+    InvalidSequence,
+
+    /// A captain acted out of turn.
+    #[error("It is {expected:?}'s turn")]
+    NotYourTurn {
+        /// Captain whose turn it is.
+        expected: Captain,
+    },
+
+    /// The captain tried to ban when they had to pick, or vice versa.
+    #[error("Expected a {expected:?}")]
+    WrongAction {
+        /// Action the current step calls for.
+        expected: VetoAction,
+    },
+
+    /// The map is not in the pool, or was already banned.
+    #[error("Map '{0}' is not available")]
+    Unavailable(String),
+
+    /// The veto has already produced a map.
+    #[error("Veto is already complete")]
+    Complete,
+}
+
+/// Starting positions on the map a lobby launches into.
+pub const STARTING_POSITIONS: u8 = 4;
+
+/// Players a lobby needs before it can count down.
+pub const MIN_PLAYERS: usize = 2;
+
+/// Errors changing a seat in a lobby.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SeatError {
+    /// The player is not seated in this lobby.
+    #[error("{0} is not in the lobby")]
+    UnknownPlayer(String),
+
+    /// The starting position is off the map.
+    #[error("Starting position {0} does not exist ({STARTING_POSITIONS} available)")]
+    NoSuchStart(u8),
+
+    /// Another player already chose the starting position.
+    #[error("Starting position {start} is taken by {player}")]
+    StartTaken {
+        /// Position asked for.
+        start: u8,
+        /// Player holding it.
+        player: String,
+    },
+
+    /// Players must choose a faction before readying.
+    #[error("{0} has not chosen a faction")]
+    NoFaction(String),
+
+    /// Another player already plays the faction, in a lobby that needs
+    /// every faction distinct.
+    #[error("{faction:?} is taken by {player}")]
+    FactionTaken {
+        /// Faction asked for.
+        faction: FactionId,
+        /// Player playing it.
+        player: String,
+    },
+}
+
+/// One of the two captains in a veto.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Captain {
+    /// Captain of the first team (usually the lobby host).
+    First,
+    /// Captain of the second team.
+    Second,
+}
+
+impl Captain {
+    /// The other captain.
+    #[must_use]
+    pub const fn other(self) -> Self {
+        match self {
+            Self::First => Self::Second,
+            Self::Second => Self::First,
+        }
+    }
+}
+
+/// What a captain does on their turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VetoAction {
+    /// Remove a map from consideration.
+    Ban,
+    /// Choose the map to play, ending the veto.
+    Pick,
+}
+
+/// One turn of a veto sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VetoStep {
+    /// Who acts.
+    pub captain: Captain,
+    /// What they must do.
+    pub action: VetoAction,
+}
+
+impl VetoStep {
+    /// A ban by `captain`.
+    #[must_use]
+    pub const fn ban(captain: Captain) -> Self {
+        Self {
+            captain,
+            action: VetoAction::Ban,
+        }
+    }
+
+    /// A pick by `captain`.
+    #[must_use]
+    pub const fn pick(captain: Captain) -> Self {
+        Self {
+            captain,
+            action: VetoAction::Pick,
+        }
+    }
+}
+
+/// Alternating bans, first captain first, until one map remains.
+#[must_use]
+pub fn bans_to_decider(pool_size: usize) -> Vec<VetoStep> {
+    let mut captain = Captain::First;
+    (1..pool_size)
+        .map(|_| {
+            let step = VetoStep::ban(captain);
+            captain = captain.other();
+            step
+        })
+        .collect()
+}
+
+/// Maps a server offers for competitive play.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MapPool {
+    maps: Vec<String>,
+}
+
+impl MapPool {
+    /// Create a pool, dropping duplicate IDs.
+    #[must_use]
+    pub fn new(maps: impl IntoIterator<Item = String>) -> Self {
+        let mut pool = Self::default();
+        for map in maps {
+            if !pool.contains(&map) {
+                pool.maps.push(map);
+            }
+        }
+        pool
+    }
+
+    /// Map IDs in configured order.
+    #[must_use]
+    pub fn maps(&self) -> &[String] {
+        &self.maps
+    }
+
+    /// Whether `map` is in the pool.
+    #[must_use]
+    pub fn contains(&self, map: &str) -> bool {
+        self.maps.iter().any(|m| m == map)
+    }
+
+    /// Whether the pool has no maps.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.maps.is_empty()
+    }
+
+    /// Draw a map for a matchmade game.
+    ///
+    /// Each map's weight is one plus the number of times it was played in
+    /// `history`, so popular maps come up more often while unplayed maps
+    /// keep a chance.
+    pub fn weighted_choice(&self, history: &MatchHistory, rng: &mut SimRng) -> Option<&str> {
+        let picks = history.pick_counts();
+        let weights: Vec<u64> = self
+            .maps
+            .iter()
+            .map(|map| 1 + u64::from(picks.get(map).copied().unwrap_or(0)))
+            .collect();
+        let total: u64 = weights.iter().sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut roll = rng.next_u64() % total;
+        for (map, weight) in self.maps.iter().zip(weights) {
+            if roll < weight {
+                return Some(map);
+            }
+            roll -= weight;
+        }
+        None
+    }
+}
+
+/// A ban or pick that was made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VetoEntry {
+    /// The step that was taken.
+    pub step: VetoStep,
+    /// Map it applied to.
+    pub map: String,
+}
+
+/// A veto in progress between two captains.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapVeto {
+    remaining: Vec<String>,
+    steps: Vec<VetoStep>,
+    log: Vec<VetoEntry>,
+    picked: Option<String>,
+}
+
+impl MapVeto {
+    /// Start a veto over `pool` following `steps`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pool is empty, or if the sequence would not
+    /// end on exactly one map: it must contain a pick, or ban all but one.
+    pub fn new(pool: &MapPool, steps: Vec<VetoStep>) -> Result<Self, VetoError> {
+        if pool.is_empty() {
+            return Err(VetoError::EmptyPool);
+        }
+        let bans_before_pick = steps
+            .iter()
+            .take_while(|step| step.action == VetoAction::Ban)
+            .count();
+        let has_pick = bans_before_pick < steps.len();
+        let valid = if has_pick {
+            bans_before_pick < pool.maps().len() && bans_before_pick + 1 == steps.len()
+        } else {
+            bans_before_pick + 1 == pool.maps().len()
+        };
+        if !valid {
+            return Err(VetoError::InvalidSequence);
+        }
+
+        Ok(Self {
+            remaining: pool.maps().to_vec(),
+            steps,
+            log: Vec::new(),
+            picked: None,
+        })
+    }
+
+    /// The step waiting to be taken, if the veto is still running.
+    #[must_use]
+    pub fn next_step(&self) -> Option<VetoStep> {
+        if self.chosen().is_some() {
+            return None;
+        }
+        self.steps.get(self.log.len()).copied()
+    }
+
+    /// Maps not yet banned.
+    #[must_use]
+    pub fn remaining(&self) -> &[String] {
+        &self.remaining
+    }
+
+    /// Bans and picks made so far.
+    #[must_use]
+    pub fn log(&self) -> &[VetoEntry] {
+        &self.log
+    }
+
+    /// The map to play, once decided: the pick, or the last map standing.
+    #[must_use]
+    pub fn chosen(&self) -> Option<&str> {
+        if let Some(map) = &self.picked {
+            return Some(map);
+        }
+        match self.remaining.as_slice() {
+            [decider] if self.log.len() == self.steps.len() => Some(decider),
+            _ => None,
+        }
+    }
+
+    /// Take the next step.
+    ///
+    /// Returns the chosen map if this step decided it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if it is not `captain`'s turn, the action is not
+    /// what the step calls for, or the map is unavailable.
+    pub fn act(
+        &mut self,
+        captain: Captain,
+        action: VetoAction,
+        map: &str,
+    ) -> Result<Option<&str>, VetoError> {
+        let step = self.next_step().ok_or(VetoError::Complete)?;
+        if captain != step.captain {
+            return Err(VetoError::NotYourTurn {
+                expected: step.captain,
+            });
+        }
+        if action != step.action {
+            return Err(VetoError::WrongAction {
+                expected: step.action,
+            });
+        }
+        let index = self
+            .remaining
+            .iter()
+            .position(|m| m == map)
+            .ok_or_else(|| VetoError::Unavailable(map.to_string()))?;
+
+        match action {
+            VetoAction::Ban => {
+                self.remaining.remove(index);
+            }
+            VetoAction::Pick => self.picked = Some(map.to_string()),
+        }
+        self.log.push(VetoEntry {
+            step,
+            map: map.to_string(),
+        });
+        Ok(self.chosen())
+    }
+}
+
+/// A player's place in a lobby.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LobbySeat {
+    /// Player name.
+    pub player: String,
+    /// Faction chosen, if any.
+    pub faction: Option<FactionId>,
+    /// Starting position chosen; None takes whichever is left.
+    pub start: Option<u8>,
+    /// Whether the player is ready to launch.
+    pub ready: bool,
+    /// Registered identity, if the player presented one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_id: Option<PlayerId>,
+    /// The identified player's rating.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<PublicRating>,
+}
+
+/// Players seated in a lobby, in the order they joined, and the
+/// spectators watching it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LobbyRoom {
+    seats: Vec<LobbySeat>,
+    spectators: Vec<String>,
+}
+
+impl LobbyRoom {
+    /// Create an empty room.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every seat, in join order.
+    #[must_use]
+    pub fn seats(&self) -> &[LobbySeat] {
+        &self.seats
+    }
+
+    /// Seated players, in join order.
+    pub fn players(&self) -> impl Iterator<Item = &str> {
+        self.seats.iter().map(|seat| seat.player.as_str())
+    }
+
+    /// Number of seated players.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.seats.len()
+    }
+
+    /// Whether nobody is seated.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.seats.is_empty()
+    }
+
+    /// Join order of `player`, if seated.
+    #[must_use]
+    pub fn position(&self, player: &str) -> Option<usize> {
+        self.seats.iter().position(|seat| seat.player == player)
+    }
+
+    /// Seat a player with no choices made.
+    pub fn seat(&mut self, player: impl Into<String>) {
+        self.seats.push(LobbySeat {
+            player: player.into(),
+            faction: None,
+            start: None,
+            ready: false,
+            player_id: None,
+            rating: None,
+        });
+    }
+
+    /// Record the identity `player` presented and their rating.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the player is not seated.
+    pub fn identify(
+        &mut self,
+        player: &str,
+        id: PlayerId,
+        rating: PublicRating,
+    ) -> Result<(), SeatError> {
+        let seat = self.seat_mut(player)?;
+        seat.player_id = Some(id);
+        seat.rating = Some(rating);
+        Ok(())
+    }
+
+    /// Registered identities of seated players, by name.
+    #[must_use]
+    pub fn identities(&self) -> BTreeMap<String, PlayerId> {
+        self.seats
+            .iter()
+            .filter_map(|seat| Some((seat.player.clone(), seat.player_id?)))
+            .collect()
+    }
+
+    /// Remove a player, returning the join order they had.
+    pub fn unseat(&mut self, player: &str) -> Option<usize> {
+        let index = self.position(player)?;
+        self.seats.remove(index);
+        Some(index)
+    }
+
+    /// Spectators, in the order they arrived.
+    #[must_use]
+    pub fn spectators(&self) -> &[String] {
+        &self.spectators
+    }
+
+    /// Whether `name` is spectating.
+    #[must_use]
+    pub fn is_spectator(&self, name: &str) -> bool {
+        self.spectators.iter().any(|spectator| spectator == name)
+    }
+
+    /// Add a spectator. Returns false if `name` is already seated or
+    /// spectating.
+    pub fn spectate(&mut self, name: impl Into<String>) -> bool {
+        let name = name.into();
+        if self.position(&name).is_some() || self.is_spectator(&name) {
+            return false;
+        }
+        self.spectators.push(name);
+        true
+    }
+
+    /// Remove a spectator, returning whether they were watching.
+    pub fn stop_spectating(&mut self, name: &str) -> bool {
+        let before = self.spectators.len();
+        self.spectators.retain(|spectator| spectator != name);
+        self.spectators.len() != before
+    }
+
+    /// Choose the player's faction. Clears their ready flag.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SeatError::UnknownPlayer`] if the player is not seated.
+    pub fn choose_faction(&mut self, player: &str, faction: FactionId) -> Result<(), SeatError> {
+        let seat = self.seat_mut(player)?;
+        seat.faction = Some(faction);
+        seat.ready = false;
+        Ok(())
+    }
+
+    /// Choose the player's faction, refusing one another player already
+    /// plays. Clears their ready flag.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the player is not seated, or the faction
+    /// belongs to someone else.
+    pub fn choose_unique_faction(
+        &mut self,
+        player: &str,
+        faction: FactionId,
+    ) -> Result<(), SeatError> {
+        if let Some(holder) = self
+            .seats
+            .iter()
+            .find(|seat| seat.player != player && seat.faction == Some(faction))
+        {
+            return Err(SeatError::FactionTaken {
+                faction,
+                player: holder.player.clone(),
+            });
+        }
+        self.choose_faction(player, faction)
+    }
+
+    /// Choose the player's starting position, or None to take whichever
+    /// is left at launch. Clears their ready flag.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the player is not seated, or the position does
+    /// not exist or belongs to someone else.
+    pub fn choose_start(&mut self, player: &str, start: Option<u8>) -> Result<(), SeatError> {
+        if let Some(start) = start {
+            if start >= STARTING_POSITIONS {
+                return Err(SeatError::NoSuchStart(start));
+            }
+            if let Some(holder) = self
+                .seats
+                .iter()
+                .find(|seat| seat.start == Some(start) && seat.player != player)
+            {
+                return Err(SeatError::StartTaken {
+                    start,
+                    player: holder.player.clone(),
+                });
+            }
+        }
+        let seat = self.seat_mut(player)?;
+        seat.start = start;
+        seat.ready = false;
+        Ok(())
+    }
+
+    /// Mark the player ready or not.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the player is not seated, or readies without
+    /// having chosen a faction.
+    pub fn set_ready(&mut self, player: &str, ready: bool) -> Result<(), SeatError> {
+        let seat = self.seat_mut(player)?;
+        if ready && seat.faction.is_none() {
+            return Err(SeatError::NoFaction(player.to_string()));
+        }
+        seat.ready = ready;
+        Ok(())
+    }
+
+    /// Whether enough players are seated and all of them are ready.
+    #[must_use]
+    pub fn all_ready(&self) -> bool {
+        self.seats.len() >= MIN_PLAYERS && self.seats.iter().all(|seat| seat.ready)
+    }
+
+    /// Each seat's starting position, in join order. Players who did not
+    /// choose take the lowest free positions. Returns None if there are
+    /// more players than positions.
+    #[must_use]
+    pub fn starts(&self) -> Option<Vec<u8>> {
+        let mut free = (0..STARTING_POSITIONS)
+            .filter(|start| self.seats.iter().all(|seat| seat.start != Some(*start)));
+        self.seats
+            .iter()
+            .map(|seat| seat.start.or_else(|| free.next()))
+            .collect()
+    }
+
+    fn seat_mut(&mut self, player: &str) -> Result<&mut LobbySeat, SeatError> {
+        self.seats
+            .iter_mut()
+            .find(|seat| seat.player == player)
+            .ok_or_else(|| SeatError::UnknownPlayer(player.to_string()))
+    }
+}
+
+/// Longest name a player can register, in characters.
+pub const MAX_NAME_CHARS: usize = 24;
+
+/// Key derivation context for identity token signing keys.
+const TOKEN_KEY_CONTEXT: &str = "post-scarcity-rts 2024 player identity tokens";
+
+/// A registered player's persistent ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PlayerId(pub u64);
+
+impl fmt::Display for PlayerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Who a registered player is.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Identity {
+    /// Persistent ID.
+    pub id: PlayerId,
+    /// Name registered with the ID.
+    pub name: String,
+}
+
+/// Errors registering or identifying a player.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum IdentityError {
+    /// The name is empty, too long or contains control characters.
+    #[error("Invalid player name '{0}' (1 to {MAX_NAME_CHARS} printable characters)")]
+    InvalidName(String),
+
+    /// The token is not one this server could have issued.
+    #[error("Malformed identity token")]
+    MalformedToken,
+
+    /// The token's signature does not match: forged, altered, or signed
+    /// with another secret.
+    #[error("Identity token signature does not match")]
+    BadSignature,
+
+    /// The token belongs to a different name than the player gave.
+    #[error("Identity token belongs to {registered}, not {given}")]
+    WrongName {
+        /// Name registered with the token.
+        registered: String,
+        /// Name the player gave.
+        given: String,
+    },
+
+    /// The OS random number generator failed, so no player ID could be
+    /// drawn.
+    #[error("OS random number generator failed: {0}")]
+    Entropy(getrandom::Error),
+}
+
+/// Issues and checks signed identity tokens, and keeps track of the
+/// players it has seen.
+pub struct PlayerRegistry {
+    key: [u8; 32],
+    players: BTreeMap<PlayerId, String>,
+}
+
+impl fmt::Debug for PlayerRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the signing key
+        f.debug_struct("PlayerRegistry")
+            .field("players", &self.players.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl PlayerRegistry {
+    /// Registry signing with a key derived from `secret`, so its tokens
+    /// outlive the process, or with a random key from the OS CSPRNG, so
+    /// they don't.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no secret is given and the OS random number generator
+    /// fails, rather than sign tokens with a guessable key.
+    #[must_use]
+    pub fn new(secret: Option<&str>) -> Self {
+        let key = match secret {
+            Some(secret) => blake3::derive_key(TOKEN_KEY_CONTEXT, secret.as_bytes()),
+            None => os_random().expect("OS random number generator failed"),
+        };
+        Self {
+            key,
+            players: BTreeMap::new(),
+        }
+    }
+
+    /// Register a new player under `name`, returning their identity and
+    /// the token to present from now on.
+    ///
+    /// Names are not reserved: another player may register the same one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IdentityError::InvalidName`] for an unusable name, or
+    /// [`IdentityError::Entropy`] if no ID could be drawn.
+    pub fn register(&mut self, name: &str) -> Result<(Identity, String), IdentityError> {
+        let length = name.chars().count();
+        if length == 0 || length > MAX_NAME_CHARS || name.chars().any(char::is_control) {
+            return Err(IdentityError::InvalidName(name.to_string()));
+        }
+        let id = loop {
+            let id = PlayerId(u64::from_le_bytes(
+                os_random().map_err(IdentityError::Entropy)?,
+            ));
+            if !self.players.contains_key(&id) {
+                break id;
+            }
+        };
+        let identity = Identity {
+            id,
+            name: name.to_string(),
+        };
+        let token = self.sign(&identity);
+        self.players.insert(id, identity.name.clone());
+        tracing::info!(player_id = %id, player = %name, "Registered player");
+        Ok((identity, token))
+    }
+
+    /// Check a token and return the identity it carries, remembering it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token is malformed or its signature does
+    /// not match.
+    pub fn verify(&mut self, token: &str) -> Result<Identity, IdentityError> {
+        let (payload, signature) = token.split_once('.').ok_or(IdentityError::MalformedToken)?;
+        let signature =
+            blake3::Hash::from_hex(signature).map_err(|_| IdentityError::MalformedToken)?;
+        // Hash comparison is constant-time
+        if blake3::keyed_hash(&self.key, payload.as_bytes()) != signature {
+            return Err(IdentityError::BadSignature);
+        }
+        let json = URL_SAFE_NO_PAD
+            .decode(payload)
+            .map_err(|_| IdentityError::MalformedToken)?;
+        let identity: Identity =
+            serde_json::from_slice(&json).map_err(|_| IdentityError::MalformedToken)?;
+        self.players.insert(identity.id, identity.name.clone());
+        Ok(identity)
+    }
+
+    /// Name a known player registered with.
+    #[must_use]
+    pub fn name(&self, id: PlayerId) -> Option<&str> {
+        self.players.get(&id).map(String::as_str)
+    }
+
+    /// Number of players registered or identified since the server
+    /// started.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.players.len()
+    }
+
+    /// Whether no player has registered or identified yet.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    fn sign(&self, identity: &Identity) -> String {
+        // A plain struct of a number and a string always serializes
+        let json = serde_json::to_vec(identity).unwrap_or_default();
+        let payload = URL_SAFE_NO_PAD.encode(json);
+        let signature = blake3::keyed_hash(&self.key, payload.as_bytes());
+        format!("{payload}.{}", signature.to_hex())
+    }
+}
+
+/// Random bytes from the OS CSPRNG.
+fn os_random<const N: usize>() -> Result<[u8; N], getrandom::Error> {
+    let mut bytes = [0; N];
+    getrandom::getrandom(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Widest rating gap the [`MatchQueue`] pairs players across when they
+/// have just queued.
+pub const QUEUE_RATING_GAP: i32 = 100;
+
+/// How much the accepted gap widens for every second a player waits.
+pub const QUEUE_GAP_PER_SECOND: i32 = 10;
+
+/// Widest gap the queue ever accepts.
+pub const MAX_QUEUE_RATING_GAP: i32 = 600;
+
+/// A player waiting for a rated game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedPlayer {
+    /// Player name.
+    pub player: String,
+    /// Registered identity.
+    pub player_id: PlayerId,
+    /// Rating when they queued.
+    pub rating: i32,
+    /// When they queued.
+    pub since: Instant,
+}
+
+impl QueuedPlayer {
+    /// Widest rating gap this player accepts at `now`.
+    #[must_use]
+    pub fn accepted_gap(&self, now: Instant) -> i32 {
+        let waited = now.saturating_duration_since(self.since).as_secs();
+        let widened = i32::try_from(waited)
+            .unwrap_or(i32::MAX)
+            .saturating_mul(QUEUE_GAP_PER_SECOND);
+        QUEUE_RATING_GAP
+            .saturating_add(widened)
+            .min(MAX_QUEUE_RATING_GAP)
+    }
+}
+
+/// Players waiting for a rated game, in the order they queued.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MatchQueue {
+    waiting: Vec<QueuedPlayer>,
+}
+
+impl MatchQueue {
+    /// Create an empty queue.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Players waiting, longest first.
+    #[must_use]
+    pub fn waiting(&self) -> &[QueuedPlayer] {
+        &self.waiting
+    }
+
+    /// Number of players waiting.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.waiting.len()
+    }
+
+    /// Whether nobody is waiting.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.waiting.is_empty()
+    }
+
+    /// Whether the player is waiting.
+    #[must_use]
+    pub fn contains(&self, id: PlayerId) -> bool {
+        self.waiting.iter().any(|queued| queued.player_id == id)
+    }
+
+    /// Add a player. Returns false if they are already waiting.
+    pub fn join(&mut self, player: QueuedPlayer) -> bool {
+        if self.contains(player.player_id) {
+            return false;
+        }
+        self.waiting.push(player);
+        true
+    }
+
+    /// Remove a player, returning whether they were waiting.
+    pub fn leave(&mut self, id: PlayerId) -> bool {
+        let before = self.waiting.len();
+        self.waiting.retain(|queued| queued.player_id != id);
+        self.waiting.len() != before
+    }
+
+    /// Take every pair that can play at `now` out of the queue.
+    ///
+    /// Longest-waiting players are paired first, each with the closest
+    /// rated player that queued after them and is within the gap they
+    /// accept by now.
+    pub fn pair(&mut self, now: Instant) -> Vec<(QueuedPlayer, QueuedPlayer)> {
+        let mut partner: Vec<Option<usize>> = vec![None; self.waiting.len()];
+        for first in 0..self.waiting.len() {
+            if partner[first].is_some() {
+                continue;
+            }
+            let gap = self.waiting[first].accepted_gap(now);
+            let rating = self.waiting[first].rating;
+            let closest = (first + 1..self.waiting.len())
+                .filter(|&second| partner[second].is_none())
+                .map(|second| (second, (self.waiting[second].rating - rating).abs()))
+                .filter(|&(_, difference)| difference <= gap)
+                .min_by_key(|&(_, difference)| difference);
+            if let Some((second, _)) = closest {
+                partner[first] = Some(second);
+                partner[second] = Some(first);
+            }
+        }
+
+        let mut taken: Vec<Option<QueuedPlayer>> = std::mem::take(&mut self.waiting)
+            .into_iter()
+            .map(Some)
+            .collect();
+        let mut pairs = Vec::new();
+        for (first, second) in partner.iter().enumerate() {
+            match *second {
+                Some(second) if first < second => {
+                    if let (Some(a), Some(b)) = (taken[first].take(), taken[second].take()) {
+                        pairs.push((a, b));
+                    }
+                }
+                Some(_) => {}
+                None => self.waiting.extend(taken[first].take()),
+            }
+        }
+        pairs
+    }
+}
+
+/// One finished game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchRecord {
+    /// Session the game ran in.
+    pub session: SessionId,
+    /// Map played, if one was chosen.
+    pub map: Option<String>,
+    /// Players in the lobby.
+    pub players: Vec<String>,
+    /// Registered identities of the players that had one, by name.
+    pub identities: BTreeMap<String, PlayerId>,
+    /// Whether the game was rated.
+    pub rated: bool,
+    /// Winning faction, if decided.
+    pub winner: Option<FactionId>,
+    /// How the game ended, if decided.
+    pub win_condition: Option<WinCondition>,
+    /// Ticks simulated.
+    pub ticks: u64,
+}
+
+/// Games finished on this server, oldest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MatchHistory {
+    records: Vec<MatchRecord>,
+}
+
+impl MatchHistory {
+    /// Create an empty history.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a finished game.
+    pub fn record(&mut self, record: MatchRecord) {
+        self.records.push(record);
+    }
+
+    /// All records, oldest first.
+    #[must_use]
+    pub fn records(&self) -> &[MatchRecord] {
+        &self.records
+    }
+
+    /// How many recorded games were played on each map.
+    #[must_use]
+    pub fn pick_counts(&self) -> BTreeMap<String, u32> {
+        let mut counts = BTreeMap::new();
+        for map in self.records.iter().filter_map(|r| r.map.as_ref()) {
+            *counts.entry(map.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Share of games with a map that were played on `map` (0.0 with no
+    /// history).
+    #[must_use]
+    pub fn pick_rate(&self, map: &str) -> f64 {
+        let counts = self.pick_counts();
+        let total: u32 = counts.values().sum();
+        if total == 0 {
+            return 0.0;
+        }
+        f64::from(counts.get(map).copied().unwrap_or(0)) / f64::from(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(maps: &[&str]) -> MapPool {
+        MapPool::new(maps.iter().map(|m| m.to_string()))
+    }
+
+    fn played(map: &str) -> MatchRecord {
+        MatchRecord {
+            session: 0,
+            map: Some(map.to_string()),
+            players: Vec::new(),
+            identities: BTreeMap::new(),
+            rated: false,
+            winner: None,
+            win_condition: None,
+            ticks: 0,
+        }
+    }
+
+    #[test]
+    fn test_bans_to_decider() {
+        let pool = pool(&["dunes", "delta", "crater"]);
+        let mut veto = MapVeto::new(&pool, bans_to_decider(3)).unwrap();
+        assert_eq!(veto.next_step(), Some(VetoStep::ban(Captain::First)));
+
+        assert_eq!(
+            veto.act(Captain::Second, VetoAction::Ban, "dunes"),
+            Err(VetoError::NotYourTurn {
+                expected: Captain::First
+            })
+        );
+        assert_eq!(
+            veto.act(Captain::First, VetoAction::Pick, "dunes"),
+            Err(VetoError::WrongAction {
+                expected: VetoAction::Ban
+            })
+        );
+        assert_eq!(veto.act(Captain::First, VetoAction::Ban, "dunes"), Ok(None));
+        assert_eq!(
+            veto.act(Captain::Second, VetoAction::Ban, "dunes"),
+            Err(VetoError::Unavailable("dunes".to_string()))
+        );
+        assert_eq!(
+            veto.act(Captain::Second, VetoAction::Ban, "crater"),
+            Ok(Some("delta"))
+        );
+        assert_eq!(veto.next_step(), None);
+        assert_eq!(veto.log().len(), 2);
+        assert_eq!(
+            veto.act(Captain::First, VetoAction::Ban, "delta"),
+            Err(VetoError::Complete)
+        );
+    }
+
+    #[test]
+    fn test_pick_ends_veto_and_sequences_are_checked() {
+        let pool = pool(&["dunes", "delta", "crater", "dunes"]);
+        assert_eq!(pool.maps().len(), 3);
+
+        let steps = vec![
+            VetoStep::ban(Captain::First),
+            VetoStep::pick(Captain::Second),
+        ];
+        let mut veto = MapVeto::new(&pool, steps).unwrap();
+        veto.act(Captain::First, VetoAction::Ban, "crater").unwrap();
+        assert_eq!(
+            veto.act(Captain::Second, VetoAction::Pick, "dunes"),
+            Ok(Some("dunes"))
+        );
+
+        // Two bans over three maps leaves one; one ban leaves two
+        assert!(MapVeto::new(&pool, bans_to_decider(3)).is_ok());
+        assert_eq!(
+            MapVeto::new(&pool, bans_to_decider(2)),
+            Err(VetoError::InvalidSequence)
+        );
+        assert_eq!(
+            MapVeto::new(&MapPool::default(), Vec::new()),
+            Err(VetoError::EmptyPool)
+        );
+    }
+
+    #[test]
+    fn test_weighted_choice_follows_pick_rates() {
+        let pool = pool(&["dunes", "delta"]);
+        let mut history = MatchHistory::new();
+        for _ in 0..8 {
+            history.record(played("dunes"));
+        }
+        assert!((history.pick_rate("dunes") - 1.0).abs() < f64::EPSILON);
+        assert_eq!(history.pick_rate("delta"), 0.0);
+
+        // Weights are 9:1, so dunes should dominate but delta still appears
+        let mut rng = SimRng::new(42);
+        let mut counts = BTreeMap::new();
+        for _ in 0..1000 {
+            let map = pool.weighted_choice(&history, &mut rng).unwrap();
+            *counts.entry(map.to_string()).or_insert(0) += 1;
+        }
+        assert!(counts["dunes"] > 800);
+        assert!(counts["delta"] > 0);
+        assert_eq!(MapPool::default().weighted_choice(&history, &mut rng), None);
+    }
+
+    #[test]
+    fn test_room_choices_and_readiness() {
+        let mut room = LobbyRoom::new();
+        room.seat("alice");
+        room.seat("bob");
+        assert_eq!(
+            room.set_ready("alice", true),
+            Err(SeatError::NoFaction("alice".to_string()))
+        );
+        room.choose_faction("alice", FactionId::Continuity).unwrap();
+        room.choose_faction("bob", FactionId::Collegium).unwrap();
+        room.choose_start("bob", Some(0)).unwrap();
+        assert_eq!(
+            room.choose_start("alice", Some(0)),
+            Err(SeatError::StartTaken {
+                start: 0,
+                player: "bob".to_string()
+            })
+        );
+        assert_eq!(
+            room.choose_start("alice", Some(STARTING_POSITIONS)),
+            Err(SeatError::NoSuchStart(STARTING_POSITIONS))
+        );
+        assert_eq!(room.starts(), Some(vec![1, 0]));
+
+        room.set_ready("alice", true).unwrap();
+        room.set_ready("bob", true).unwrap();
+        assert!(room.all_ready());
+
+        // Changing a choice takes back the ready
+        room.choose_start("bob", Some(3)).unwrap();
+        assert!(!room.all_ready());
+        room.set_ready("bob", true).unwrap();
+        assert_eq!(room.unseat("alice"), Some(0));
+        assert!(!room.all_ready(), "one player cannot launch");
+        assert_eq!(
+            room.set_ready("carol", true),
+            Err(SeatError::UnknownPlayer("carol".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_identity_tokens_verify_only_as_signed() {
+        let mut registry = PlayerRegistry::new(Some("secret"));
+        let (alice, token) = registry.register("alice").unwrap();
+        let (bob, _) = registry.register("alice").unwrap();
+        assert_ne!(alice.id, bob.id, "names are not reserved");
+        assert_eq!(registry.verify(&token), Ok(alice.clone()));
+
+        // Another server with the same secret knows her; one without doesn't
+        let mut restarted = PlayerRegistry::new(Some("secret"));
+        assert_eq!(restarted.name(alice.id), None);
+        assert_eq!(restarted.verify(&token), Ok(alice.clone()));
+        assert_eq!(restarted.name(alice.id), Some("alice"));
+        assert_eq!(
+            PlayerRegistry::new(None).verify(&token),
+            Err(IdentityError::BadSignature)
+        );
+        // Random keys come from the OS CSPRNG, fresh for every registry
+        assert_ne!(PlayerRegistry::new(None).key, PlayerRegistry::new(None).key);
+
+        // Renaming yourself breaks the signature
+        let (_, signature) = token.split_once('.').unwrap();
+        let renamed = Identity {
+            id: alice.id,
+            name: "mallory".to_string(),
+        };
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&renamed).unwrap());
+        assert_eq!(
+            registry.verify(&format!("{payload}.{signature}")),
+            Err(IdentityError::BadSignature)
+        );
+        assert_eq!(registry.verify("alice"), Err(IdentityError::MalformedToken));
+        for name in ["", "tab\tbed", &"x".repeat(MAX_NAME_CHARS + 1)] {
+            assert_eq!(
+                registry.register(name).map(|_| ()),
+                Err(IdentityError::InvalidName(name.to_string()))
+            );
+        }
+        assert_eq!(registry.len(), 2);
+
+        let mut room = LobbyRoom::new();
+        room.seat("alice");
+        room.seat("guest");
+        let rating = PublicRating {
+            rating: 1500,
+            deviation: 350,
+            games: 0,
+        };
+        room.identify("alice", alice.id, rating).unwrap();
+        assert_eq!(room.seats()[0].rating, Some(rating));
+        assert_eq!(
+            room.identities(),
+            BTreeMap::from([("alice".to_string(), alice.id)])
+        );
+        assert_eq!(
+            room.identify("carol", bob.id, rating),
+            Err(SeatError::UnknownPlayer("carol".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_queue_pairs_close_ratings_first() {
+        let start = Instant::now();
+        let queued = |id: u64, rating: i32| QueuedPlayer {
+            player: format!("p{id}"),
+            player_id: PlayerId(id),
+            rating,
+            since: start,
+        };
+        let mut queue = MatchQueue::new();
+        assert!(queue.join(queued(1, 1500)));
+        assert!(!queue.join(queued(1, 1500)), "already waiting");
+        queue.join(queued(2, 1700));
+        queue.join(queued(3, 1560));
+        queue.join(queued(4, 1950));
+
+        // 1 takes the closer 3; 2 and 4 are too far apart for now
+        let pairs = queue.pair(start);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(
+            (pairs[0].0.player_id, pairs[0].1.player_id),
+            (PlayerId(1), PlayerId(3))
+        );
+        assert_eq!(queue.len(), 2);
+
+        // After 15 seconds a 250 gap is close enough
+        let later = start + std::time::Duration::from_secs(15);
+        assert_eq!(queue.waiting()[0].accepted_gap(later), 250);
+        let pairs = queue.pair(later);
+        assert_eq!(
+            (pairs[0].0.player_id, pairs[0].1.player_id),
+            (PlayerId(2), PlayerId(4))
+        );
+        assert!(queue.is_empty());
+
+        queue.join(queued(5, 1000));
+        let hour = start + std::time::Duration::from_secs(3600);
+        assert_eq!(queue.waiting()[0].accepted_gap(hour), MAX_QUEUE_RATING_GAP);
+        assert!(queue.leave(PlayerId(5)));
+        assert!(!queue.leave(PlayerId(5)));
+    }
+
+    #[test]
+    fn test_spectators_take_no_seat() {
+        let mut room = LobbyRoom::new();
+        room.seat("alice");
+        assert!(room.spectate("caster"));
+        assert!(!room.spectate("caster"));
+        assert!(!room.spectate("alice"), "players cannot also spectate");
+        assert_eq!(room.len(), 1);
+        assert!(room.is_spectator("caster"));
+
+        // Spectators choose nothing and don't count towards launching
+        assert_eq!(
+            room.choose_faction("caster", FactionId::Zephyr),
+            Err(SeatError::UnknownPlayer("caster".to_string()))
+        );
+        room.seat("bob");
+        for player in ["alice", "bob"] {
+            room.choose_faction(player, FactionId::Tinkers).unwrap();
+            room.set_ready(player, true).unwrap();
+        }
+        assert!(room.all_ready());
+        assert_eq!(room.starts(), Some(vec![0, 1]));
+
+        assert!(room.stop_spectating("caster"));
+        assert!(!room.stop_spectating("caster"));
+        assert!(room.spectators().is_empty());
+    }
+}
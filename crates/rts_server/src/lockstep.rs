@@ -0,0 +1,1157 @@
+//! Lockstep command relay.
+//!
+//! In a lockstep game every client runs the full simulation and only
+//! commands cross the network. A [`LockstepRelay`] collects each player's
+//! commands for a tick, and once every seat has submitted (an empty list
+//! counts) it releases a [`Turn`]: the combined commands in seat order,
+//! which every client applies before stepping that tick. The server steps
+//! its own copy of the game with the same turn, so it always has a
+//! reference [`state_hash`](rts_core::simulation::Simulation::state_hash).
+//!
+//! Clients confirm the hash they reached after every turn marked
+//! [`confirm`](Turn::confirm): each tick by default, or every Nth with
+//! [`with_hash_interval`](LockstepRelay::with_hash_interval). The relay keeps
+//! at most [`max_unconfirmed`](LockstepRelay::with_max_unconfirmed) of those
+//! in flight, so the game advances only as fast as its slowest client. A
+//! hash that differs from the server's halts the relay with a [`Desync`]
+//! naming the players who diverged.
+//!
+//! A desynced client can upload its state to get a [`DesyncReport`] listing
+//! what differs, then load the server's authoritative state from a
+//! [`Resync`] and confirm it with
+//! [`resynced`](LockstepRelay::resynced). Once every desynced player has,
+//! the relay resumes from the server's tick.
+//!
+//! The relay also keeps what a player who lost their connection needs to
+//! get back in: a checkpoint of the game taken every
+//! [`CHECKPOINT_INTERVAL`] ticks and every turn released since. A
+//! [`Rejoin`] carries both, and the client
+//! [fast-forwards](Rejoin::fast_forward) through the turns to reach the
+//! server's tick.
+//!
+//! Each player schedules their commands [`InputDelay`] ticks ahead of the
+//! tick they are playing. The relay times how long every player takes to
+//! confirm a turn, adapts their delay to it, and announces each change as
+//! a [`LockstepEvent::InputDelay`]. It also keeps track of who the next
+//! turn is [waiting on](LockstepRelay::waiting_on) and
+//! [for how long](LockstepRelay::stalled_for), so the server can drop
+//! players who hold everyone else up.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use rts_core::autosave::Autosave;
+use rts_core::components::{Command, EntityId};
+use rts_core::error::Result as GameResult;
+use rts_core::factions::FactionId;
+use rts_core::simulation::{Simulation, StateDifference, TICK_RATE};
+
+use crate::delay::{DelayBounds, InputDelay};
+use crate::game::HostedGame;
+
+/// How far past the next turn players may submit commands.
+pub const MAX_INPUT_LEAD: u64 = 32;
+
+/// Ticks between the checkpoints rejoining players catch up from.
+pub const CHECKPOINT_INTERVAL: u64 = 600;
+
+/// Errors from lockstep requests. None of them stop the game.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LockstepError {
+    /// The player has no seat in this game.
+    #[error("{0} has no seat in this game")]
+    UnknownPlayer(String),
+
+    /// The tick has already been played.
+    #[error("tick {tick} has already been played (next is {next})")]
+    StaleTick {
+        /// Tick submitted for.
+        tick: u64,
+        /// Next tick to be played.
+        next: u64,
+    },
+
+    /// The tick is further ahead than [`MAX_INPUT_LEAD`] allows.
+    #[error("tick {tick} is too far ahead (next is {next})")]
+    TooFarAhead {
+        /// Tick submitted for.
+        tick: u64,
+        /// Next tick to be played.
+        next: u64,
+    },
+
+    /// The player already submitted commands for this tick.
+    #[error("{player} already submitted tick {tick}")]
+    AlreadySubmitted {
+        /// Player.
+        player: String,
+        /// Tick.
+        tick: u64,
+    },
+
+    /// A command names an entity the player does not own.
+    #[error("{player} does not own entity {entity}")]
+    NotOwned {
+        /// Player.
+        player: String,
+        /// Entity commanded.
+        entity: EntityId,
+    },
+
+    /// A confirmation for a tick that is not awaiting one.
+    #[error("tick {0} is not awaiting confirmation")]
+    UnexpectedConfirm(u64),
+
+    /// The relay stopped after a desync.
+    #[error("game halted by desync at tick {0}")]
+    Halted(u64),
+
+    /// A resync confirmation when the player is not awaiting one.
+    #[error("{0} is not awaiting a resync")]
+    NotDesynced(String),
+
+    /// A resynced client reached a different state than the server's.
+    #[error("resync to tick {tick} failed: expected hash {expected}, got {actual}")]
+    ResyncMismatch {
+        /// Tick resynced to.
+        tick: u64,
+        /// Server's hash at that tick.
+        expected: u64,
+        /// Hash the client reported.
+        actual: u64,
+    },
+
+    /// The game is decided; no more turns are played.
+    #[error("game is over")]
+    Finished,
+
+    /// The game could not be saved for a rejoining player.
+    #[error("no checkpoint to rejoin from")]
+    NoCheckpoint,
+}
+
+/// One command for one entity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityCommand {
+    /// Entity commanded.
+    pub entity: EntityId,
+    /// The command.
+    pub command: Command,
+}
+
+/// A command in a [`Turn`], with the player who gave it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnCommand {
+    /// Player who gave the command.
+    pub player: String,
+    /// Entity commanded.
+    pub entity: EntityId,
+    /// The command.
+    pub command: Command,
+}
+
+/// Everything every client applies before stepping one tick.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Turn {
+    /// Simulation tick the commands are applied at.
+    pub tick: u64,
+    /// Commands in seat order, then submission order, without any for an
+    /// entity its player no longer owned when the tick was played.
+    pub commands: Vec<TurnCommand>,
+    /// Whether clients confirm their state hash after this tick.
+    pub confirm: bool,
+}
+
+/// Clients disagreed with the server about the state after a tick.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Desync {
+    /// Tick whose resulting state differs.
+    pub tick: u64,
+    /// The server's state hash after the tick.
+    pub expected: u64,
+    /// Players whose hash differed.
+    pub players: Vec<String>,
+}
+
+/// What differs between a desynced client's state and the server's.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DesyncReport {
+    /// Player whose state was compared.
+    pub player: String,
+    /// Tick whose hash first diverged, if the game desynced.
+    pub desync_tick: Option<u64>,
+    /// Server tick the states were compared at.
+    pub compared_at: u64,
+    /// Every difference, server state first.
+    pub differences: Vec<StateDifference>,
+}
+
+/// The server's authoritative state, for a client to load in place of its
+/// own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Resync {
+    /// Tick the state is at; the next turn played is for this tick.
+    pub tick: u64,
+    /// The game as an [`Autosave`](rts_core::autosave::Autosave), base64
+    /// encoded on the wire.
+    #[serde(with = "state_bytes")]
+    pub state: Vec<u8>,
+}
+
+impl Resync {
+    /// The game's current state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the game cannot be saved.
+    pub fn capture(game: &HostedGame) -> GameResult<Self> {
+        let state = Autosave::capture(game.id(), game.simulation())?
+            .with_fingerprint(game.fingerprint())
+            .to_bytes()?;
+        Ok(Self {
+            tick: game.simulation().get_tick(),
+            state,
+        })
+    }
+
+    /// Client side: load the state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state does not restore.
+    pub fn restore(&self) -> GameResult<Simulation> {
+        Autosave::from_bytes(&self.state)?.restore_simulation()
+    }
+}
+
+/// What a player who lost their connection needs to rejoin lockstep.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rejoin {
+    /// The latest checkpoint of the game.
+    pub checkpoint: Resync,
+    /// Every turn released since the checkpoint, in tick order.
+    pub turns: Vec<Turn>,
+    /// Ticks ahead to schedule commands for from now on.
+    #[serde(default)]
+    pub input_delay: u64,
+}
+
+impl Rejoin {
+    /// Tick the client plays next once caught up.
+    #[must_use]
+    pub fn next_tick(&self) -> u64 {
+        self.checkpoint.tick + self.turns.len() as u64
+    }
+
+    /// Client side: load the checkpoint and play every turn after it, as
+    /// the server did, leaving the game ready for [`next_tick`](Self::next_tick).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checkpoint does not restore.
+    pub fn fast_forward(&self) -> GameResult<Simulation> {
+        let mut sim = self.checkpoint.restore()?;
+        for turn in &self.turns {
+            let orders: Vec<(EntityId, Command)> = turn
+                .commands
+                .iter()
+                .map(|c| (c.entity, c.command.clone()))
+                .collect();
+            // Refused commands are skipped on every peer alike
+            let _ = sim.apply_commands(&orders);
+            sim.tick();
+        }
+        Ok(sim)
+    }
+}
+
+/// Something every client in the game needs to hear about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockstepEvent {
+    /// A turn was released.
+    Turn(Turn),
+    /// The game desynced and has halted.
+    Desync(Desync),
+    /// Every desynced player resynced; play continues from this tick.
+    Resumed(u64),
+    /// A player's input delay changed.
+    InputDelay {
+        /// Player whose delay changed.
+        player: String,
+        /// Ticks ahead to schedule commands for from now on.
+        ticks: u64,
+    },
+}
+
+/// Serde adapter carrying saved state as base64 in JSON.
+pub(crate) mod state_bytes {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug)]
+struct Seat {
+    player: String,
+    faction: FactionId,
+    delay: InputDelay,
+}
+
+/// A released turn awaiting confirmation.
+#[derive(Debug)]
+struct Pending {
+    /// The server's hash after the tick.
+    expected: u64,
+    /// One slot per seat.
+    hashes: Vec<Option<u64>>,
+    released: Instant,
+}
+
+/// Collects commands and confirmations for one lockstep game.
+#[derive(Debug)]
+pub struct LockstepRelay {
+    seats: Vec<Seat>,
+    max_unconfirmed: u64,
+    hash_interval: u64,
+    /// Next tick to release.
+    next_tick: u64,
+    /// Commands per tick, one slot per seat.
+    submissions: BTreeMap<u64, Vec<Option<Vec<EntityCommand>>>>,
+    /// Released ticks awaiting confirmation.
+    unconfirmed: BTreeMap<u64, Pending>,
+    /// When a turn was last released, or play last restarted.
+    last_release: Instant,
+    desync: Option<Desync>,
+    /// Desynced players who have not yet confirmed a resync.
+    awaiting_resync: Vec<String>,
+    checkpoint_interval: u64,
+    /// Latest checkpoint for rejoining players, and the turns since.
+    checkpoint: Option<Resync>,
+    history: Vec<Turn>,
+}
+
+impl LockstepRelay {
+    /// Relay for `game`, with one seat per `(player, faction)` in order.
+    ///
+    /// Players may only command entities of their seat's faction.
+    #[must_use]
+    pub fn new(game: &HostedGame, seats: impl IntoIterator<Item = (String, FactionId)>) -> Self {
+        let mut relay = Self {
+            seats: seats
+                .into_iter()
+                .map(|(player, faction)| Seat {
+                    player,
+                    faction,
+                    delay: InputDelay::new(DelayBounds::default(), TICK_RATE),
+                })
+                .collect(),
+            max_unconfirmed: 1,
+            hash_interval: 1,
+            next_tick: game.simulation().get_tick(),
+            submissions: BTreeMap::new(),
+            unconfirmed: BTreeMap::new(),
+            last_release: Instant::now(),
+            desync: None,
+            awaiting_resync: Vec::new(),
+            checkpoint_interval: CHECKPOINT_INTERVAL,
+            checkpoint: None,
+            history: Vec::new(),
+        };
+        relay.take_checkpoint(game);
+        relay
+    }
+
+    /// Builder method to let up to `turns` released turns await
+    /// confirmation at once (at least 1, the default). Higher values hide
+    /// latency at the cost of noticing a desync later.
+    #[must_use]
+    pub fn with_max_unconfirmed(mut self, turns: u64) -> Self {
+        self.max_unconfirmed = turns.max(1);
+        self
+    }
+
+    /// Builder method to have clients confirm only every `ticks`th turn
+    /// (at least 1, the default): ticks 0, `ticks`, `2 * ticks`, ... A
+    /// desync is noticed up to `ticks` late, for that much less traffic.
+    #[must_use]
+    pub fn with_hash_interval(mut self, ticks: u64) -> Self {
+        self.hash_interval = ticks.max(1);
+        self
+    }
+
+    /// Builder method to checkpoint for rejoining players every `ticks`
+    /// ticks (at least 1; [`CHECKPOINT_INTERVAL`] by default). Shorter
+    /// intervals mean fewer turns to replay on rejoin, for more saving.
+    #[must_use]
+    pub fn with_checkpoint_interval(mut self, ticks: u64) -> Self {
+        self.checkpoint_interval = ticks.max(1);
+        self
+    }
+
+    /// Builder method to keep every player's input delay within `bounds`,
+    /// for a game played at `tick_rate` ticks a second (by default
+    /// [`DelayBounds::default`] at [`TICK_RATE`]).
+    #[must_use]
+    pub fn with_input_delay(mut self, bounds: DelayBounds, tick_rate: u32) -> Self {
+        for seat in &mut self.seats {
+            seat.delay = InputDelay::new(bounds, tick_rate);
+        }
+        self
+    }
+
+    /// Next tick to be released.
+    #[must_use]
+    pub fn next_tick(&self) -> u64 {
+        self.next_tick
+    }
+
+    /// Every seated player, in seat order.
+    pub fn players(&self) -> impl Iterator<Item = &str> {
+        self.seats.iter().map(|seat| seat.player.as_str())
+    }
+
+    /// Ticks ahead `player` should schedule their commands for, if they
+    /// have a seat.
+    #[must_use]
+    pub fn input_delay(&self, player: &str) -> Option<u64> {
+        self.seat(player)
+            .ok()
+            .map(|seat| self.seats[seat].delay.ticks())
+    }
+
+    /// Players the next turn is waiting on: those yet to confirm the
+    /// oldest turn when no more may await confirmation, else those yet to
+    /// submit for the next tick. Nobody while halted or once the game is
+    /// decided.
+    #[must_use]
+    pub fn waiting_on(&self, game: &HostedGame) -> Vec<String> {
+        if self.desync.is_some() || game.is_finished() {
+            return Vec::new();
+        }
+        let missing: Vec<bool> = match self.unconfirmed.first_key_value() {
+            Some((_, pending)) if self.unconfirmed.len() as u64 >= self.max_unconfirmed => {
+                pending.hashes.iter().map(Option::is_none).collect()
+            }
+            _ => match self.submissions.get(&self.next_tick) {
+                Some(slots) => slots.iter().map(Option::is_none).collect(),
+                None => vec![true; self.seats.len()],
+            },
+        };
+        missing
+            .into_iter()
+            .zip(&self.seats)
+            .filter(|&(missing, _)| missing)
+            .map(|(_, seat)| seat.player.clone())
+            .collect()
+    }
+
+    /// How long it has been, at `now`, since the relay last released a
+    /// turn or [restarted its clock](Self::restart_stall_clock).
+    #[must_use]
+    pub fn stalled_for(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_release)
+    }
+
+    /// Count the game as stalled only from now, as after a pause.
+    pub fn restart_stall_clock(&mut self) {
+        self.last_release = Instant::now();
+    }
+
+    /// Whether `player` has a seat in this game.
+    #[must_use]
+    pub fn is_seated(&self, player: &str) -> bool {
+        self.seat(player).is_ok()
+    }
+
+    /// Faction of `player`'s seat, if they have one.
+    #[must_use]
+    pub fn faction_of(&self, player: &str) -> Option<FactionId> {
+        self.seat(player).ok().map(|seat| self.seats[seat].faction)
+    }
+
+    /// Whether `player` has yet to submit commands for the next tick.
+    #[must_use]
+    pub fn awaits_commands(&self, player: &str) -> bool {
+        self.seat(player).is_ok_and(|seat| {
+            self.submissions
+                .get(&self.next_tick)
+                .map_or(true, |slots| slots[seat].is_none())
+        })
+    }
+
+    /// The desync that halted the relay, if any.
+    #[must_use]
+    pub fn desync(&self) -> Option<&Desync> {
+        self.desync.as_ref()
+    }
+
+    /// Record a player's commands for `tick` and release every turn that
+    /// is now complete.
+    ///
+    /// Ownership is checked again when the tick is played, and commands
+    /// for entities the player has lost by then are dropped from the turn.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the player has no seat, the tick is out of
+    /// range or already submitted, a command names an entity the player
+    /// does not own, or the relay has stopped.
+    pub fn submit(
+        &mut self,
+        game: &mut HostedGame,
+        player: &str,
+        tick: u64,
+        commands: Vec<EntityCommand>,
+    ) -> Result<Vec<LockstepEvent>, LockstepError> {
+        self.check_running(game)?;
+        let seat = self.seat(player)?;
+        if tick < self.next_tick {
+            return Err(LockstepError::StaleTick {
+                tick,
+                next: self.next_tick,
+            });
+        }
+        if tick > self.next_tick + MAX_INPUT_LEAD {
+            return Err(LockstepError::TooFarAhead {
+                tick,
+                next: self.next_tick,
+            });
+        }
+        let faction = self.seats[seat].faction;
+        let sim = game.simulation();
+        if let Some(foreign) = commands.iter().find(|c| !owns(sim, faction, c.entity)) {
+            return Err(LockstepError::NotOwned {
+                player: player.to_string(),
+                entity: foreign.entity,
+            });
+        }
+
+        let slots = self
+            .submissions
+            .entry(tick)
+            .or_insert_with(|| vec![None; self.seats.len()]);
+        if slots[seat].is_some() {
+            return Err(LockstepError::AlreadySubmitted {
+                player: player.to_string(),
+                tick,
+            });
+        }
+        slots[seat] = Some(commands);
+        Ok(self.release(game))
+    }
+
+    /// Record the state hash a player reached after `tick`.
+    ///
+    /// Returns the turns this unblocks, or the [`Desync`] if the hash
+    /// differs from the server's. Confirmations still in flight when the
+    /// relay halted are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the player has no seat or the tick is not
+    /// awaiting confirmation.
+    pub fn confirm(
+        &mut self,
+        game: &mut HostedGame,
+        player: &str,
+        tick: u64,
+        state_hash: u64,
+    ) -> Result<Vec<LockstepEvent>, LockstepError> {
+        let seat = self.seat(player)?;
+        if self.desync.is_some() {
+            return Ok(Vec::new());
+        }
+        let pending = self
+            .unconfirmed
+            .get_mut(&tick)
+            .ok_or(LockstepError::UnexpectedConfirm(tick))?;
+        let latency = pending.hashes[seat]
+            .is_none()
+            .then(|| pending.released.elapsed());
+        pending.hashes[seat] = Some(state_hash);
+        let expected = pending.expected;
+
+        let diverged: Vec<String> = pending
+            .hashes
+            .iter()
+            .zip(&self.seats)
+            .filter(|(hash, _)| hash.is_some_and(|h| h != expected))
+            .map(|(_, seat)| seat.player.clone())
+            .collect();
+        if !diverged.is_empty() {
+            let desync = Desync {
+                tick,
+                expected,
+                players: diverged,
+            };
+            tracing::warn!(tick, players = ?desync.players, "Lockstep desync");
+            self.awaiting_resync = desync.players.clone();
+            self.desync = Some(desync.clone());
+            return Ok(vec![LockstepEvent::Desync(desync)]);
+        }
+
+        let mut events = Vec::new();
+        if let Some(ticks) = latency.and_then(|latency| self.seats[seat].delay.record(latency)) {
+            tracing::debug!(%player, ticks, ?latency, "Input delay changed");
+            events.push(LockstepEvent::InputDelay {
+                player: player.to_string(),
+                ticks,
+            });
+        }
+        self.drop_confirmed();
+        events.extend(self.release(game));
+        Ok(events)
+    }
+
+    /// Record that a desynced player loaded the server's state at `tick`
+    /// and reached `state_hash`. Once every desynced player has, the relay
+    /// resumes: confirmations still pending are dropped and play continues
+    /// from the server's tick.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the player has no seat or is not awaiting a
+    /// resync, or the state is not the server's.
+    pub fn resynced(
+        &mut self,
+        game: &mut HostedGame,
+        player: &str,
+        tick: u64,
+        state_hash: u64,
+    ) -> Result<Vec<LockstepEvent>, LockstepError> {
+        self.seat(player)?;
+        let waiting = self
+            .awaiting_resync
+            .iter()
+            .position(|p| p == player)
+            .ok_or_else(|| LockstepError::NotDesynced(player.to_string()))?;
+        let expected = game.simulation().state_hash();
+        if tick != self.next_tick || state_hash != expected {
+            return Err(LockstepError::ResyncMismatch {
+                tick,
+                expected,
+                actual: state_hash,
+            });
+        }
+        self.awaiting_resync.remove(waiting);
+        if !self.awaiting_resync.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        tracing::info!(tick, "Lockstep resumed after resync");
+        self.desync = None;
+        self.unconfirmed.clear();
+        self.restart_stall_clock();
+        let mut events = vec![LockstepEvent::Resumed(self.next_tick)];
+        events.extend(self.release(game));
+        Ok(events)
+    }
+
+    /// Confirm every turn awaiting `player` with the server's own hash,
+    /// for a seat played on the server's game, such as a
+    /// [bot](crate::bots). Returns the turns this unblocks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the player has no seat.
+    pub fn confirm_as_server(
+        &mut self,
+        game: &mut HostedGame,
+        player: &str,
+    ) -> Result<Vec<LockstepEvent>, LockstepError> {
+        let seat = self.seat(player)?;
+        if self.desync.is_some() {
+            return Ok(Vec::new());
+        }
+        for pending in self.unconfirmed.values_mut() {
+            pending.hashes[seat].get_or_insert(pending.expected);
+        }
+        self.drop_confirmed();
+        Ok(self.release(game))
+    }
+
+    /// Let a seated player back in after losing their connection.
+    ///
+    /// Commands they submitted for ticks not yet released are dropped, so
+    /// they submit again from [`Rejoin::next_tick`], and the turns still
+    /// awaiting their confirmation count as confirmed: the client rebuilds
+    /// its game from the server's. Returns the turns this unblocks too;
+    /// the [`Rejoin`] already includes them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the player has no seat or no checkpoint could be
+    /// taken.
+    pub fn rejoin(
+        &mut self,
+        game: &mut HostedGame,
+        player: &str,
+    ) -> Result<(Rejoin, Vec<LockstepEvent>), LockstepError> {
+        let seat = self.seat(player)?;
+        for slots in self.submissions.values_mut() {
+            slots[seat] = None;
+        }
+        let mut events = Vec::new();
+        if self.desync.is_none() {
+            for pending in self.unconfirmed.values_mut() {
+                pending.hashes[seat].get_or_insert(pending.expected);
+            }
+            self.drop_confirmed();
+            events = self.release(game);
+        }
+        let checkpoint = self.checkpoint.clone().ok_or(LockstepError::NoCheckpoint)?;
+        tracing::info!(%player, tick = self.next_tick, from = checkpoint.tick, "Player rejoined");
+        let rejoin = Rejoin {
+            checkpoint,
+            turns: self.history.clone(),
+            input_delay: self.seats[seat].delay.ticks(),
+        };
+        Ok((rejoin, events))
+    }
+
+    fn seat(&self, player: &str) -> Result<usize, LockstepError> {
+        self.seats
+            .iter()
+            .position(|seat| seat.player == player)
+            .ok_or_else(|| LockstepError::UnknownPlayer(player.to_string()))
+    }
+
+    fn check_running(&self, game: &HostedGame) -> Result<(), LockstepError> {
+        if let Some(desync) = &self.desync {
+            return Err(LockstepError::Halted(desync.tick));
+        }
+        if game.is_finished() {
+            return Err(LockstepError::Finished);
+        }
+        Ok(())
+    }
+
+    /// Drop every fully confirmed tick from the front.
+    fn drop_confirmed(&mut self) {
+        while let Some(entry) = self.unconfirmed.first_entry() {
+            if entry.get().hashes.iter().all(Option::is_some) {
+                entry.remove();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Save the game for rejoining players. On failure the previous
+    /// checkpoint stays, with the turns since it.
+    fn take_checkpoint(&mut self, game: &HostedGame) {
+        match Resync::capture(game) {
+            Ok(checkpoint) => {
+                self.checkpoint = Some(checkpoint);
+                self.history.clear();
+            }
+            Err(e) => tracing::warn!(tick = self.next_tick, error = %e, "Checkpoint failed"),
+        }
+    }
+
+    /// Step the game through every complete turn the confirmation window
+    /// allows.
+    fn release(&mut self, game: &mut HostedGame) -> Vec<LockstepEvent> {
+        let mut events = Vec::new();
+        while (self.unconfirmed.len() as u64) < self.max_unconfirmed && !game.is_finished() {
+            let complete = self
+                .submissions
+                .get(&self.next_tick)
+                .is_some_and(|slots| slots.iter().all(Option::is_some));
+            if !complete {
+                break;
+            }
+            let tick = self.next_tick;
+            let slots = self.submissions.remove(&tick).unwrap_or_default();
+            // Commands were checked when submitted, up to MAX_INPUT_LEAD
+            // ticks ago; the entity may since have died, changed hands or
+            // had its ID reused. The turn only carries those still valid.
+            let sim = game.simulation();
+            let commands: Vec<TurnCommand> = slots
+                .into_iter()
+                .zip(&self.seats)
+                .flat_map(|(slot, seat)| {
+                    slot.unwrap_or_default()
+                        .into_iter()
+                        .filter(|c| {
+                            let owned = owns(sim, seat.faction, c.entity);
+                            if !owned {
+                                tracing::debug!(
+                                    tick,
+                                    player = %seat.player,
+                                    entity = c.entity,
+                                    "Dropped command for an entity no longer owned"
+                                );
+                            }
+                            owned
+                        })
+                        .map(|c| TurnCommand {
+                            player: seat.player.clone(),
+                            entity: c.entity,
+                            command: c.command,
+                        })
+                })
+                .collect();
+
+            let orders: Vec<(EntityId, Command)> = commands
+                .iter()
+                .map(|c| (c.entity, c.command.clone()))
+                .collect();
+            game.tick_with(&orders);
+            let confirm = tick % self.hash_interval == 0;
+            let now = Instant::now();
+            if confirm {
+                let pending = Pending {
+                    expected: game.simulation().state_hash(),
+                    hashes: vec![None; self.seats.len()],
+                    released: now,
+                };
+                self.unconfirmed.insert(tick, pending);
+            }
+            self.last_release = now;
+            self.next_tick += 1;
+            let turn = Turn {
+                tick,
+                commands,
+                confirm,
+            };
+            self.history.push(turn.clone());
+            if self.next_tick % self.checkpoint_interval == 0 {
+                self.take_checkpoint(game);
+            }
+            events.push(LockstepEvent::Turn(turn));
+        }
+        events
+    }
+}
+
+/// Whether `entity` exists and belongs to `faction`.
+fn owns(sim: &Simulation, faction: FactionId, entity: EntityId) -> bool {
+    sim.get_entity(entity)
+        .and_then(|e| e.faction)
+        .is_some_and(|f| f.faction == faction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rts_core::components::{CombatStats, FactionMember};
+    use rts_core::math::{Fixed, Vec2Fixed};
+    use rts_core::simulation::{EntitySpawnParams, Simulation};
+
+    /// One mobile unit per faction, far apart.
+    fn game() -> (HostedGame, EntityId, EntityId) {
+        let mut sim = Simulation::new();
+        let mut unit = |faction, x: i32| {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(0))),
+                health: Some(100),
+                movement: Some(Fixed::from_num(2)),
+                faction: Some(FactionMember::new(faction, 0)),
+                ..Default::default()
+            })
+        };
+        let ours = unit(FactionId::Continuity, 0);
+        let theirs = unit(FactionId::Collegium, 1000);
+        (HostedGame::new("lockstep", sim), ours, theirs)
+    }
+
+    fn seats() -> Vec<(String, FactionId)> {
+        vec![
+            ("alice".to_string(), FactionId::Continuity),
+            ("bob".to_string(), FactionId::Collegium),
+        ]
+    }
+
+    fn move_order(entity: EntityId) -> Vec<EntityCommand> {
+        vec![EntityCommand {
+            entity,
+            command: Command::MoveTo(Vec2Fixed::new(Fixed::from_num(500), Fixed::from_num(0))),
+        }]
+    }
+
+    fn turns(events: Vec<LockstepEvent>) -> Vec<Turn> {
+        events
+            .into_iter()
+            .filter_map(|event| match event {
+                LockstepEvent::Turn(turn) => Some(turn),
+                LockstepEvent::Desync(_)
+                | LockstepEvent::Resumed(_)
+                | LockstepEvent::InputDelay { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_turns_wait_for_every_seat_and_confirmation() {
+        let (mut game, ours, theirs) = game();
+        let mut client = game.simulation().clone();
+        let mut relay = LockstepRelay::new(&game, seats());
+
+        // Bob's turn for tick 1 is early; tick 0 still waits for alice
+        assert!(relay
+            .submit(&mut game, "bob", 0, move_order(theirs))
+            .unwrap()
+            .is_empty());
+        assert!(relay
+            .submit(&mut game, "bob", 1, Vec::new())
+            .unwrap()
+            .is_empty());
+        let released = turns(
+            relay
+                .submit(&mut game, "alice", 0, move_order(ours))
+                .unwrap(),
+        );
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].tick, 0);
+        assert_eq!(released[0].commands[0].player, "alice");
+        assert_eq!(released[0].commands[1].player, "bob");
+
+        // Tick 1 is complete but held until tick 0 is confirmed by all
+        assert!(relay
+            .submit(&mut game, "alice", 1, Vec::new())
+            .unwrap()
+            .is_empty());
+        for command in &released[0].commands {
+            client
+                .apply_command(command.entity, command.command.clone())
+                .unwrap();
+        }
+        client.tick();
+        let hash = client.state_hash();
+        assert_eq!(hash, game.simulation().state_hash());
+        assert!(relay
+            .confirm(&mut game, "alice", 0, hash)
+            .unwrap()
+            .is_empty());
+        let released = turns(relay.confirm(&mut game, "bob", 0, hash).unwrap());
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].tick, 1);
+        assert_eq!(relay.next_tick(), 2);
+
+        assert_eq!(
+            relay.submit(&mut game, "alice", 1, Vec::new()),
+            Err(LockstepError::StaleTick { tick: 1, next: 2 })
+        );
+        assert_eq!(
+            relay.submit(&mut game, "carol", 2, Vec::new()),
+            Err(LockstepError::UnknownPlayer("carol".to_string()))
+        );
+        assert_eq!(
+            relay.submit(&mut game, "alice", 2, move_order(theirs)),
+            Err(LockstepError::NotOwned {
+                player: "alice".to_string(),
+                entity: theirs,
+            })
+        );
+    }
+
+    #[test]
+    fn test_commands_for_lost_entities_are_dropped() {
+        // A Collegium gun in range of a Continuity unit that dies to one shot
+        let mut sim = Simulation::new();
+        let ours = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(0), Fixed::from_num(0))),
+            health: Some(1),
+            movement: Some(Fixed::from_num(2)),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            ..Default::default()
+        });
+        let gun = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(10), Fixed::from_num(0))),
+            health: Some(100),
+            movement: Some(Fixed::from_num(2)),
+            combat_stats: Some(CombatStats::new(50, Fixed::from_num(50), 1)),
+            faction: Some(FactionMember::new(FactionId::Collegium, 0)),
+            ..Default::default()
+        });
+        let mut game = HostedGame::new("lockstep", sim);
+        let mut relay = LockstepRelay::new(&game, seats()).with_max_unconfirmed(16);
+
+        // Alice orders her unit ahead, while it is still hers, and bob's
+        // gun shoots it before that tick comes
+        relay
+            .submit(&mut game, "alice", 10, move_order(ours))
+            .unwrap();
+        let attack = vec![EntityCommand {
+            entity: gun,
+            command: Command::Attack(ours),
+        }];
+        relay.submit(&mut game, "bob", 0, attack).unwrap();
+        for tick in 0..10 {
+            relay.submit(&mut game, "alice", tick, Vec::new()).unwrap();
+            if tick > 0 {
+                relay.submit(&mut game, "bob", tick, Vec::new()).unwrap();
+            }
+        }
+        assert!(game.simulation().get_entity(ours).is_none());
+
+        let released = turns(relay.submit(&mut game, "bob", 10, Vec::new()).unwrap());
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].tick, 10);
+        assert!(released[0].commands.is_empty());
+    }
+
+    #[test]
+    fn test_diverging_hash_halts_with_desync() {
+        let (mut game, _, _) = game();
+        let mut relay = LockstepRelay::new(&game, seats()).with_max_unconfirmed(4);
+        for tick in 0..3 {
+            relay.submit(&mut game, "alice", tick, Vec::new()).unwrap();
+            relay.submit(&mut game, "bob", tick, Vec::new()).unwrap();
+        }
+        // Three turns in flight without any confirmation
+        assert_eq!(relay.next_tick(), 3);
+
+        let expected = game.simulation().state_hash();
+        let events = relay.confirm(&mut game, "bob", 2, expected ^ 1).unwrap();
+        let desync = Desync {
+            tick: 2,
+            expected,
+            players: vec!["bob".to_string()],
+        };
+        assert_eq!(events, vec![LockstepEvent::Desync(desync.clone())]);
+        assert_eq!(relay.desync(), Some(&desync));
+        assert_eq!(
+            relay.submit(&mut game, "alice", 3, Vec::new()),
+            Err(LockstepError::Halted(2))
+        );
+    }
+
+    #[test]
+    fn test_hash_interval_and_resync_resume() {
+        let (mut game, _, _) = game();
+        let mut relay = LockstepRelay::new(&game, seats()).with_hash_interval(3);
+        for tick in 0..5 {
+            relay.submit(&mut game, "alice", tick, Vec::new()).unwrap();
+            relay.submit(&mut game, "bob", tick, Vec::new()).unwrap();
+        }
+        assert_eq!(relay.next_tick(), 1, "tick 0 awaits confirmation");
+
+        let hash = game.simulation().state_hash();
+        relay.confirm(&mut game, "alice", 0, hash).unwrap();
+        let released = turns(relay.confirm(&mut game, "bob", 0, hash).unwrap());
+        let confirms: Vec<_> = released.iter().map(|t| (t.tick, t.confirm)).collect();
+        assert_eq!(confirms, vec![(1, false), (2, false), (3, true)]);
+        assert_eq!(
+            relay.confirm(&mut game, "bob", 2, hash),
+            Err(LockstepError::UnexpectedConfirm(2))
+        );
+
+        let hash = game.simulation().state_hash();
+        relay.confirm(&mut game, "bob", 3, hash ^ 1).unwrap();
+        assert!(relay.desync().is_some());
+        assert_eq!(
+            relay.resynced(&mut game, "alice", 4, hash),
+            Err(LockstepError::NotDesynced("alice".to_string()))
+        );
+        assert_eq!(
+            relay.resynced(&mut game, "bob", 4, hash ^ 1),
+            Err(LockstepError::ResyncMismatch {
+                tick: 4,
+                expected: hash,
+                actual: hash ^ 1,
+            })
+        );
+
+        // Bob loads the server state; tick 4 was already submitted
+        let events = relay.resynced(&mut game, "bob", 4, hash).unwrap();
+        assert_eq!(events[0], LockstepEvent::Resumed(4));
+        assert_eq!(
+            turns(events).iter().map(|t| t.tick).collect::<Vec<_>>(),
+            vec![4]
+        );
+        assert_eq!(relay.desync(), None);
+    }
+
+    #[test]
+    fn test_slow_confirmations_raise_input_delay() {
+        let (mut game, _, _) = game();
+        let mut relay =
+            LockstepRelay::new(&game, seats()).with_input_delay(DelayBounds::new(1, 8), 1000);
+        assert_eq!(relay.input_delay("alice"), Some(1));
+        assert_eq!(relay.waiting_on(&game), vec!["alice", "bob"]);
+        relay.submit(&mut game, "alice", 0, Vec::new()).unwrap();
+        assert_eq!(relay.waiting_on(&game), vec!["bob"]);
+        relay.submit(&mut game, "bob", 0, Vec::new()).unwrap();
+
+        // Tens of milliseconds to confirm is tens of ticks at 1000 a second
+        std::thread::sleep(Duration::from_millis(20));
+        let hash = game.simulation().state_hash();
+        let events = relay.confirm(&mut game, "alice", 0, hash).unwrap();
+        assert_eq!(
+            events,
+            vec![LockstepEvent::InputDelay {
+                player: "alice".to_string(),
+                ticks: 8,
+            }]
+        );
+        assert_eq!(relay.waiting_on(&game), vec!["bob"]);
+        assert!(relay.stalled_for(Instant::now()) >= Duration::from_millis(20));
+
+        relay.confirm_as_server(&mut game, "bob").unwrap();
+        assert_eq!(relay.input_delay("bob"), Some(1), "no latency measured");
+        relay.restart_stall_clock();
+        assert!(relay.stalled_for(Instant::now()) < Duration::from_millis(20));
+        let (rejoin, _) = relay.rejoin(&mut game, "alice").unwrap();
+        assert_eq!(rejoin.input_delay, 8);
+    }
+
+    #[test]
+    fn test_rejoin_fast_forwards_to_the_server_tick() {
+        let (mut game, ours, theirs) = game();
+        let mut relay = LockstepRelay::new(&game, seats())
+            .with_max_unconfirmed(2)
+            .with_checkpoint_interval(4);
+        for tick in 0..10 {
+            let orders = if tick == 5 {
+                (move_order(ours), move_order(theirs))
+            } else {
+                (Vec::new(), Vec::new())
+            };
+            relay.submit(&mut game, "alice", tick, orders.0).unwrap();
+            relay.submit(&mut game, "bob", tick, orders.1).unwrap();
+            let hash = game.simulation().state_hash();
+            relay.confirm(&mut game, "bob", tick, hash).unwrap();
+            if tick < 8 {
+                relay.confirm(&mut game, "alice", tick, hash).unwrap();
+            }
+        }
+        // Alice dropped with ticks 8 and 9 unconfirmed, holding back tick 10
+        relay.submit(&mut game, "alice", 10, Vec::new()).unwrap();
+        relay.submit(&mut game, "bob", 10, Vec::new()).unwrap();
+        assert_eq!(relay.next_tick(), 10);
+
+        let (rejoin, events) = relay.rejoin(&mut game, "alice").unwrap();
+        assert!(events.is_empty(), "tick 10 awaits alice again");
+        assert_eq!(rejoin.checkpoint.tick, 8);
+        assert_eq!(
+            rejoin.turns.iter().map(|t| t.tick).collect::<Vec<_>>(),
+            vec![8, 9]
+        );
+        assert_eq!(rejoin.next_tick(), 10);
+        let client = rejoin.fast_forward().unwrap();
+        assert_eq!(client.state_hash(), game.simulation().state_hash());
+
+        let released = turns(relay.submit(&mut game, "alice", 10, Vec::new()).unwrap());
+        assert_eq!(released.len(), 1, "alice's unconfirmed turns were settled");
+        assert_eq!(
+            relay.rejoin(&mut game, "carol").map(|_| ()),
+            Err(LockstepError::UnknownPlayer("carol".to_string()))
+        );
+    }
+}
@@ -1,18 +1,181 @@
 //! Post-Scarcity RTS - Dedicated Server
+//!
+//! Usage: `rts_server [--config <file.toml>]`. See [`rts_server::config`]
+//! for the file format and `RTS_SERVER_*` environment overrides.
 
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
-fn main() {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
-        .with(tracing_subscriber::EnvFilter::from_default_env())
-        .init();
+use rts_core::rng::SimRng;
+
+use rts_server::admin::AdminServer;
+use rts_server::health::{HealthServer, ServerStatus};
+use rts_server::network::NetworkServer;
+use rts_server::rating::RatingStore;
+use rts_server::session::SessionManager;
+use rts_server::shutdown::{drain_games, shutdown_signal};
+use rts_server::ServerConfig;
+use tokio::sync::Mutex;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+#[tokio::main]
+async fn main() {
+    let config_path = parse_args();
+    let config = match ServerConfig::load(config_path.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(2);
+        }
+    };
+    init_logging(&config);
 
     tracing::info!("Starting Post-Scarcity RTS Dedicated Server");
+    tracing::debug!(?config, "Loaded configuration");
 
-    let config = rts_server::ServerConfig::default();
-    tracing::info!("Listening on port {}", config.port);
+    let status = ServerStatus::new();
+    if let Some(port) = config.health_port {
+        match HealthServer::bind(("0.0.0.0", port), status.clone()).await {
+            Ok(server) => {
+                tracing::info!("Health probes on port {}", port);
+                tokio::spawn(server.serve());
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to bind health port {}", port);
+                std::process::exit(1);
+            }
+        }
+    }
 
-    // TODO: Start server loop
+    let ratings = match &config.ratings_file {
+        Some(path) => match RatingStore::load(path) {
+            Ok(ratings) => {
+                tracing::info!(players = ratings.len(), path = %path.display(), "Loaded ratings");
+                ratings
+            }
+            Err(e) => {
+                // Starting empty would overwrite everyone's rating
+                tracing::error!(error = %e, "Failed to load ratings from {}", path.display());
+                std::process::exit(1);
+            }
+        },
+        None => RatingStore::new(),
+    };
+    let sessions = Arc::new(Mutex::new(
+        SessionManager::new(&config).with_ratings(ratings),
+    ));
+    match NetworkServer::bind(("0.0.0.0", config.port), Arc::clone(&sessions)).await {
+        Ok(server) => {
+            tracing::info!(
+                max_sessions = config.max_sessions,
+                "Listening on port {}",
+                config.port
+            );
+            tokio::spawn(server.serve());
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to bind game port {}", config.port);
+            std::process::exit(1);
+        }
+    }
+    if let Some(port) = config.admin_port {
+        // Without a token anyone who can connect is an operator
+        let host = if config.admin_token.is_some() {
+            "0.0.0.0"
+        } else {
+            "127.0.0.1"
+        };
+        let token = config.admin_token.clone();
+        match AdminServer::bind((host, port), Arc::clone(&sessions), token).await {
+            Ok(server) => {
+                tracing::info!("Admin console on {}:{}", host, port);
+                tokio::spawn(server.serve());
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to bind admin port {}", port);
+                std::process::exit(1);
+            }
+        }
+    }
+    status.set_ready(true);
+
+    let mut housekeeping = tokio::time::interval(Duration::from_secs(1));
+    // Only matchmaking map draws use this; they need not be reproducible
+    let seed = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let mut rng = SimRng::new(seed);
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            () = &mut shutdown => break,
+            _ = housekeeping.tick() => {
+                let mut sessions = sessions.lock().await;
+                let now = Instant::now();
+                sessions.launch_due(now);
+                sessions.expire_matches(now);
+                sessions.match_queued(now, &mut rng);
+                for (session, player) in sessions.drop_stalled(now) {
+                    tracing::info!(session, %player, "Seat left idle until they rejoin");
+                }
+                sessions.reap();
+                status.set_active_games(sessions.running());
+                // Full servers drop out of the load balancer until a slot frees
+                status.set_ready(sessions.len() < config.max_sessions);
+            }
+        }
+    }
+
+    status.set_ready(false);
+    let games = sessions.lock().await.stop_all().await;
+    tracing::info!(active_games = games.len(), "Shutting down");
+    let report = drain_games(
+        games,
+        config.tick_rate,
+        Duration::from_secs(config.shutdown_grace_secs),
+        config.checkpoint_dir.as_deref(),
+    )
+    .await;
+    tracing::info!(
+        finished = report.finished.len(),
+        checkpointed = report.checkpointed.len(),
+        abandoned = report.abandoned.len(),
+        "Shutdown complete"
+    );
+}
+
+/// Read `--config <path>` from the command line.
+fn parse_args() -> Option<PathBuf> {
+    let mut args = std::env::args().skip(1);
+    let mut config = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" | "-c" => config = args.next().map(PathBuf::from),
+            other => {
+                eprintln!("error: unexpected argument '{other}'");
+                eprintln!("usage: rts_server [--config <file.toml>]");
+                std::process::exit(2);
+            }
+        }
+    }
+    config
+}
+
+/// Log to stdout, uncoloured unless attached to a terminal, so container
+/// log collectors get plain lines. `RUST_LOG` overrides the configured
+/// filter.
+fn init_logging(config: &ServerConfig) {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.log_filter));
+    let ansi = config
+        .log_color
+        .unwrap_or_else(|| std::io::stdout().is_terminal());
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_ansi(ansi))
+        .with(filter)
+        .init();
 }
@@ -1,3 +1,1852 @@
 //! Network protocol and handling.
+//!
+//! Clients talk to the server over TCP in length-prefixed JSON frames: a
+//! 4-byte big-endian length followed by that many bytes of JSON, at most
+//! [`MAX_FRAME_BYTES`]. The [`NetworkServer`] accepts connections and runs
+//! each on its own task.
+//!
+//! A connection opens with a [`ClientHello`] carrying the client's protocol
+//! version and [`Fingerprint`]. The server checks the version and engine
+//! straight away, rejecting old clients before they reach a lobby, and
+//! answers with the open lobbies. From then on the client sends
+//! [`ClientRequest`]s, each answered by one [`ServerMessage`], which the
+//! server routes to the [`SessionManager`]. Joining a lobby runs the full
+//! handshake against it: the answer is a [`ServerReply`], either a welcome
+//! carrying the session's own fingerprint or a rejection naming what
+//! differs. Clients check the welcome with [`confirm_welcome`], so a
+//! mismatch is caught on both ends before any lockstep traffic flows.
+//! Dropping the connection leaves the lobby.
+//!
+//! A client can [`Register`](ClientRequest::Register) its name for a
+//! persistent player ID and is sent a signed token with it
+//! ([`Registered`](ServerMessage::Registered)); putting the token in the
+//! hello's `identity` on later connections identifies the player again.
+//! A hello with a token that fails to verify, or that was registered to
+//! another name, is rejected. Players without one still play, unidentified.
+//!
+//! Identified clients can [`Queue`](ClientRequest::Queue) for a rated game
+//! and are answered [`Queued`](ServerMessage::Queued) with the rating they
+//! are matched by, which they can also ask for with
+//! [`Rating`](ClientRequest::Rating). Once paired with a similarly rated
+//! player they are pushed [`Matched`](ServerMessage::Matched) with a lobby
+//! reserved for the two of them, which they join as usual. Lobby states
+//! show every identified player's rating, and when a rated game ends
+//! everyone in it is sent the players' new ratings in
+//! [`Rated`](ServerMessage::Rated).
+//!
+//! Inside a lobby, clients [choose a faction](ClientRequest::ChooseFaction)
+//! and [starting position](ClientRequest::ChooseStart) and mark themselves
+//! [ready](ClientRequest::Ready). These are only answered on error: the
+//! server pushes a [`Lobby`](ServerMessage::Lobby) state to everyone in the
+//! lobby after every change, and to a client when it joins. When the
+//! countdown runs out everyone receives
+//! [`Launched`](ServerMessage::Launched) with the seed and seats the game
+//! was built from.
+//!
+//! A player with an empty seat beside them can [`AddBot`](ClientRequest::AddBot)
+//! to play against one of the server's [bots](crate::bots); it joins the
+//! lobby ready, and its seat shows in the next lobby state.
+//!
+//! Once a lobby's game starts in lockstep, clients send their
+//! [`ClientRequest::Commands`] for each tick and
+//! [`ClientRequest::Confirm`] the state hash they reach; those are only
+//! answered on error. The server pushes every released
+//! [`Turn`](ServerMessage::Turn) and any [`Desync`](ServerMessage::Desync)
+//! to everyone in the session (see [`crate::lockstep`]). Clients confirm
+//! only the turns marked `confirm`.
+//!
+//! Clients schedule their commands the launch's `input_delay` ticks ahead
+//! of the tick they are playing. As the server measures how quickly each
+//! client confirms, it sends them an [`InputDelay`](ServerMessage::InputDelay)
+//! whenever their delay should change; a client whose delay grows submits
+//! empty commands for the ticks it skips. A client that holds up the game
+//! too long is dropped with [`Kicked`](ServerMessage::Kicked) and may
+//! [rejoin](ClientRequest::Rejoin).
+//!
+//! After a desync, each diverged client may
+//! [`ReportState`](ClientRequest::ReportState) to get a
+//! [`DesyncReport`](ServerMessage::DesyncReport) of what differs, then asks
+//! to [`Resync`](ClientRequest::Resync), loads the state it is sent, and
+//! confirms it with [`Resynced`](ClientRequest::Resynced). When every
+//! diverged client has, everyone is told play has
+//! [`Resumed`](ServerMessage::Resumed). Saved state travels as base64.
+//!
+//! A player whose connection drops during a game keeps their seat. Their
+//! welcome carried a session token; on a new connection they
+//! [`Rejoin`](ClientRequest::Rejoin) with it and are sent a
+//! [`Rejoined`](ServerMessage::Rejoined) checkpoint plus the turns played
+//! since, which they [fast-forward](Rejoin::fast_forward) through before
+//! submitting commands again.
+//!
+//! Anyone in a session can [`Chat`](ClientRequest::Chat) and
+//! [`Ping`](ClientRequest::Ping) the map, to everyone or to their team.
+//! These are only answered on error; recipients, the sender included, are
+//! pushed the [`Chat`](ServerMessage::Chat) or [`Ping`](ServerMessage::Ping).
+//!
+//! Spectators [list the running games](ClientRequest::ListGames) and
+//! [`Spectate`](ClientRequest::Spectate) one, or a lobby, after the same
+//! fingerprint checks as players. They are sent every lobby change and
+//! turn the players get, and on joining a running game a
+//! [`Snapshot`](ServerMessage::Snapshot) of it with nothing fogged. Their
+//! seat, ready and lockstep requests are refused.
+//!
+//! Operators on the [admin console](crate::admin) can pause a game, which
+//! everyone in it hears as [`Paused`](ServerMessage::Paused), and kick
+//! players or terminate sessions. A kicked client is sent
+//! [`Kicked`](ServerMessage::Kicked) with the reason and disconnected.
+//!
+//! ```text
+//! -> {"type":"hello","protocol_version":1,"player":"alice","fingerprint":{...}}
+//! <- {"type":"lobbies","lobbies":[{"session":3,"name":"ranked","players":["bob"],"map":null}]}
+//! -> {"type":"join","session":3}
+//! <- {"type":"welcome","session":3,"fingerprint":{...},"token":"5f0c..."}
+//! <- {"type":"lobby","session":3,"name":"ranked","map":null,"seats":[...],"countdown_ms":null}
+//! <- {"type":"rejected","reason":"Incompatible game version: engine (local v2, remote v3) differ"}
+//! ```
 
-// Placeholder - will be implemented in Phase 5
+use std::collections::BTreeMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio::task::JoinHandle;
+
+use rts_core::api::StateSnapshot;
+use rts_core::factions::FactionId;
+use rts_core::fingerprint::Fingerprint;
+use rts_core::math::Vec2Fixed;
+
+use crate::bots::DEFAULT_BOT_STRATEGY;
+use crate::chat::{ChatChannel, ChatContent, ChatMessage, MapPing, PingKind};
+use crate::lobby::{PlayerId, VetoAction};
+use crate::lockstep::{
+    state_bytes, Desync, DesyncReport, EntityCommand, LockstepEvent, Rejoin, Resync, Turn,
+};
+use crate::rating::PublicRating;
+use crate::session::{
+    Launch, LobbyState, QueueMatch, SessionError, SessionEvent, SessionId, SessionManager,
+    SessionState,
+};
+
+/// Largest frame either side may send; enough for a whole game's
+/// compressed state during a resync.
+pub const MAX_FRAME_BYTES: usize = 1024 * 1024;
+
+/// How long a new connection has to send its hello.
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Current wire protocol version. Bumped on any incompatible message change.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Errors during the connection handshake.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum HandshakeError {
+    /// The peer speaks a different protocol version.
+    #[error("protocol version {remote} is not supported (local speaks {local})")]
+    Protocol {
+        /// Version spoken here.
+        local: u32,
+        /// Version the peer spoke.
+        remote: u32,
+    },
+
+    /// The peer runs a different engine, faction data or map.
+    #[error("{0}")]
+    Incompatible(String),
+
+    /// The server refused the connection.
+    #[error("rejected by server: {0}")]
+    Rejected(String),
+}
+
+/// First message from a connecting client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "hello")]
+pub struct ClientHello {
+    /// Protocol version the client speaks.
+    pub protocol_version: u32,
+    /// Player name.
+    pub player: String,
+    /// Engine, data and (if known) map the client runs.
+    pub fingerprint: Fingerprint,
+    /// Identity token from registering, for a registered player.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+}
+
+impl ClientHello {
+    /// Hello for this build.
+    #[must_use]
+    pub fn new(player: impl Into<String>, fingerprint: Fingerprint) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            player: player.into(),
+            fingerprint,
+            identity: None,
+        }
+    }
+
+    /// Builder method to present a registered identity's token.
+    #[must_use]
+    pub fn with_identity(mut self, token: impl Into<String>) -> Self {
+        self.identity = Some(token.into());
+        self
+    }
+}
+
+/// The server's answer to a [`ClientHello`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerReply {
+    /// The client joined the session.
+    Welcome {
+        /// Session joined.
+        session: SessionId,
+        /// The session's fingerprint.
+        fingerprint: Fingerprint,
+        /// Secret to rejoin the game with after a dropped connection;
+        /// players get one, spectators do not.
+        #[serde(default)]
+        token: Option<String>,
+    },
+    /// The client was turned away.
+    Rejected {
+        /// Human-readable reason.
+        reason: String,
+    },
+}
+
+impl From<&HandshakeError> for ServerReply {
+    fn from(error: &HandshakeError) -> Self {
+        Self::Rejected {
+            reason: error.to_string(),
+        }
+    }
+}
+
+/// A request from a client that has sent its hello.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientRequest {
+    /// List the lobbies open for joining.
+    ListLobbies,
+    /// Register the hello's name as a new player, for an identity that
+    /// lasts across games.
+    Register,
+    /// Open a new lobby. The client still has to join it.
+    OpenLobby {
+        /// Display name.
+        name: String,
+    },
+    /// Join a lobby, running the handshake against it.
+    Join {
+        /// Lobby to join.
+        session: SessionId,
+    },
+    /// List the games in progress, for spectating.
+    ListGames,
+    /// Watch a lobby or running game without playing.
+    Spectate {
+        /// Session to watch.
+        session: SessionId,
+    },
+    /// Wait for a rated game against a similarly rated player. Only
+    /// registered players can queue.
+    Queue,
+    /// Stop waiting for a rated game.
+    LeaveQueue,
+    /// Ask for the client's own rating.
+    Rating,
+    /// Return to a running game after a dropped connection.
+    Rejoin {
+        /// Session the player is seated in.
+        session: SessionId,
+        /// Token from the player's welcome.
+        token: String,
+    },
+    /// Take a captain's turn in the lobby's map veto.
+    Veto {
+        /// Ban or pick.
+        action: VetoAction,
+        /// Map banned or picked.
+        map: String,
+    },
+    /// Choose a faction for the coming game.
+    ChooseFaction {
+        /// Faction to play.
+        faction: FactionId,
+    },
+    /// Choose a starting position, or null to take whichever is left.
+    ChooseStart {
+        /// Starting position.
+        start: Option<u8>,
+    },
+    /// Mark the player ready to launch, or take it back.
+    Ready {
+        /// Whether the player is ready.
+        ready: bool,
+    },
+    /// Seat a server bot in the lobby.
+    AddBot {
+        /// Faction the bot plays.
+        faction: FactionId,
+        /// Strategy preset, e.g. "rush"; the default strategy if omitted.
+        #[serde(default)]
+        strategy: Option<String>,
+    },
+    /// Leave the lobby.
+    Leave,
+    /// Say something to the session.
+    Chat {
+        /// Everyone, or the sender's team.
+        #[serde(default)]
+        channel: ChatChannel,
+        /// Message text.
+        text: String,
+    },
+    /// Mark a spot on the map.
+    Ping {
+        /// Everyone, or the sender's team.
+        #[serde(default)]
+        channel: ChatChannel,
+        /// World position.
+        position: Vec2Fixed,
+        /// What the ping asks for.
+        #[serde(default)]
+        kind: PingKind,
+    },
+    /// The player's commands for a lockstep tick; send an empty list when
+    /// there are none.
+    Commands {
+        /// Tick the commands apply at.
+        tick: u64,
+        /// Commands, in the order they were given.
+        commands: Vec<EntityCommand>,
+    },
+    /// The state hash reached after playing a lockstep turn.
+    Confirm {
+        /// Tick played.
+        tick: u64,
+        /// [`Simulation::state_hash`](rts_core::simulation::Simulation::state_hash)
+        /// after the tick.
+        state_hash: u64,
+    },
+    /// Upload the client's game to compare with the server's.
+    ReportState {
+        /// The game as [`Autosave`](rts_core::autosave::Autosave) bytes,
+        /// after every turn received.
+        #[serde(with = "state_bytes")]
+        state: Vec<u8>,
+    },
+    /// Ask for the server's state of the game.
+    Resync,
+    /// The client loaded the resync state.
+    Resynced {
+        /// Tick of the state loaded.
+        tick: u64,
+        /// State hash of the loaded game.
+        state_hash: u64,
+    },
+}
+
+/// A lobby open for joining.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LobbyListing {
+    /// Session ID.
+    pub session: SessionId,
+    /// Display name.
+    pub name: String,
+    /// Players waiting.
+    pub players: Vec<String>,
+    /// Map chosen so far, if any.
+    pub map: Option<String>,
+}
+
+/// Any message the server sends.
+///
+/// `welcome` and `rejected` are the [`ServerReply`] messages, with the same
+/// JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// The client joined a lobby.
+    Welcome {
+        /// Session joined.
+        session: SessionId,
+        /// The session's fingerprint.
+        fingerprint: Fingerprint,
+        /// Secret to rejoin the game with, for players.
+        #[serde(default)]
+        token: Option<String>,
+    },
+    /// The hello or a join was turned away.
+    Rejected {
+        /// Human-readable reason.
+        reason: String,
+    },
+    /// Lobbies open for joining.
+    Lobbies {
+        /// Lobbies, in ID order.
+        lobbies: Vec<LobbyListing>,
+    },
+    /// Games in progress.
+    Games {
+        /// Games, in ID order.
+        games: Vec<LobbyListing>,
+    },
+    /// Everything in a running game, for a spectator to start watching
+    /// from.
+    Snapshot(StateSnapshot),
+    /// The client registered; its later joins on this connection carry
+    /// the identity.
+    Registered {
+        /// The player's persistent ID.
+        player_id: PlayerId,
+        /// Token to put in the hello of future connections.
+        token: String,
+    },
+    /// A lobby was opened.
+    LobbyOpened {
+        /// The new lobby.
+        session: SessionId,
+    },
+    /// A veto turn was taken.
+    Veto {
+        /// The map, once the veto has decided it.
+        chosen: Option<String>,
+    },
+    /// The client left its lobby.
+    Left {
+        /// Lobby left.
+        session: SessionId,
+    },
+    /// The lobby as it now stands.
+    Lobby(LobbyState),
+    /// The lobby's game started.
+    Launched(Launch),
+    /// Someone in the session chatted.
+    Chat(ChatMessage),
+    /// Someone in the session pinged the map.
+    Ping(MapPing),
+    /// A lockstep turn every client applies before stepping its tick.
+    Turn(Turn),
+    /// A client's state diverged; the game has halted.
+    Desync(Desync),
+    /// What differs between the client's uploaded state and the server's.
+    DesyncReport(DesyncReport),
+    /// The server's state, to load in place of the client's.
+    Resync(Resync),
+    /// A checkpoint and the turns since, for a rejoining client to catch
+    /// up from.
+    Rejoined(Rejoin),
+    /// Every desynced client resynced; play continues.
+    Resumed {
+        /// Next tick to be played.
+        tick: u64,
+    },
+    /// The client's input delay changed.
+    InputDelay {
+        /// Ticks ahead to schedule commands for from now on.
+        ticks: u64,
+    },
+    /// An operator paused or resumed the game. Turns sent while paused
+    /// are refused.
+    Paused {
+        /// Whether the game is now paused.
+        paused: bool,
+    },
+    /// An operator removed the client from its session, or closed the
+    /// session. The server then closes the connection.
+    Kicked {
+        /// Human-readable reason.
+        reason: String,
+    },
+    /// The client is waiting for a rated game.
+    Queued {
+        /// The client's rating, which it is matched by.
+        rating: PublicRating,
+    },
+    /// The client stopped waiting for a rated game.
+    LeftQueue,
+    /// The client was matched into a rated lobby, to join as usual.
+    Matched {
+        /// The lobby, reserved for the matched players.
+        session: SessionId,
+        /// Players matched, the client included.
+        players: Vec<String>,
+    },
+    /// The client's rating.
+    Rating {
+        /// Current rating.
+        rating: PublicRating,
+    },
+    /// A rated game ended.
+    Rated {
+        /// Every player's new rating, by name.
+        ratings: BTreeMap<String, PublicRating>,
+    },
+    /// A request failed; the connection stays open.
+    Error {
+        /// Human-readable reason.
+        message: String,
+    },
+}
+
+impl From<ServerReply> for ServerMessage {
+    fn from(reply: ServerReply) -> Self {
+        match reply {
+            ServerReply::Welcome {
+                session,
+                fingerprint,
+                token,
+            } => Self::Welcome {
+                session,
+                fingerprint,
+                token,
+            },
+            ServerReply::Rejected { reason } => Self::Rejected { reason },
+        }
+    }
+}
+
+/// Check a fingerprint received from a peer against the local one.
+///
+/// # Errors
+///
+/// Returns [`HandshakeError::Incompatible`] listing each differing component.
+pub fn check_fingerprint(local: &Fingerprint, remote: &Fingerprint) -> Result<(), HandshakeError> {
+    local
+        .check(remote)
+        .map_err(|e| HandshakeError::Incompatible(e.to_string()))
+}
+
+/// Server side: validate a client's hello against the session fingerprint.
+///
+/// # Errors
+///
+/// Returns an error if the protocol version or fingerprint differs.
+pub fn check_hello(session: &Fingerprint, hello: &ClientHello) -> Result<(), HandshakeError> {
+    if hello.protocol_version != PROTOCOL_VERSION {
+        return Err(HandshakeError::Protocol {
+            local: PROTOCOL_VERSION,
+            remote: hello.protocol_version,
+        });
+    }
+    check_fingerprint(session, &hello.fingerprint)
+}
+
+/// Client side: accept the server's reply, returning the session joined.
+///
+/// # Errors
+///
+/// Returns an error if the server rejected the hello or its fingerprint
+/// differs from the client's.
+pub fn confirm_welcome(
+    local: &Fingerprint,
+    reply: &ServerReply,
+) -> Result<SessionId, HandshakeError> {
+    match reply {
+        ServerReply::Welcome {
+            session,
+            fingerprint,
+            ..
+        } => {
+            check_fingerprint(local, fingerprint)?;
+            Ok(*session)
+        }
+        ServerReply::Rejected { reason } => Err(HandshakeError::Rejected(reason.clone())),
+    }
+}
+
+/// Write one message as a frame.
+///
+/// # Errors
+///
+/// Returns an error if the message does not serialize, exceeds
+/// [`MAX_FRAME_BYTES`], or the write fails.
+pub async fn write_frame<W, T>(writer: &mut W, message: &T) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize + ?Sized,
+{
+    let bytes = serde_json::to_vec(message)?;
+    let len = u32::try_from(bytes.len())
+        .ok()
+        .filter(|_| bytes.len() <= MAX_FRAME_BYTES)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("frame of {} bytes exceeds the limit", bytes.len()),
+            )
+        })?;
+    writer.write_all(&len.to_be_bytes()).await?;
+    writer.write_all(&bytes).await?;
+    writer.flush().await
+}
+
+/// Read one frame and decode it. Returns `None` once the peer has closed
+/// the connection.
+///
+/// # Errors
+///
+/// Returns an error if the read fails, the frame exceeds
+/// [`MAX_FRAME_BYTES`], or it does not decode as `T`.
+pub async fn read_frame<R, T>(reader: &mut R) -> io::Result<Option<T>>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    match read_frame_bytes(reader).await? {
+        Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        None => Ok(None),
+    }
+}
+
+/// Read one frame without decoding it, so a malformed message can be
+/// answered without dropping the connection.
+async fn read_frame_bytes<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    match reader.read_exact(&mut len).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {len} bytes exceeds the limit"),
+        ));
+    }
+    let mut bytes = vec![0; len];
+    reader.read_exact(&mut bytes).await?;
+    Ok(Some(bytes))
+}
+
+/// Session manager shared between the network tasks and the server loop.
+pub type SharedSessions = Arc<Mutex<SessionManager>>;
+
+/// TCP listener routing client connections to the [`SessionManager`].
+#[derive(Debug)]
+pub struct NetworkServer {
+    listener: TcpListener,
+    sessions: SharedSessions,
+}
+
+impl NetworkServer {
+    /// Bind the game listener.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound.
+    pub async fn bind(addr: impl ToSocketAddrs, sessions: SharedSessions) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        Ok(Self { listener, sessions })
+    }
+
+    /// Address the server is listening on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket address cannot be read.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accept clients until the task is dropped.
+    pub async fn serve(self) {
+        loop {
+            match self.listener.accept().await {
+                Ok((stream, peer)) => {
+                    let sessions = Arc::clone(&self.sessions);
+                    tokio::spawn(async move {
+                        if let Err(e) = serve_client(stream, sessions).await {
+                            tracing::debug!(%peer, error = %e, "Client connection failed");
+                        }
+                    });
+                }
+                Err(e) => tracing::warn!(error = %e, "Game accept failed"),
+            }
+        }
+    }
+}
+
+/// Run one client connection: hello, then requests until it closes.
+async fn serve_client(stream: TcpStream, sessions: SharedSessions) -> io::Result<()> {
+    let (mut reader, mut writer) = stream.into_split();
+    let hello = match tokio::time::timeout(HANDSHAKE_TIMEOUT, read_frame(&mut reader)).await {
+        Ok(Ok(Some(hello))) => hello,
+        Ok(Ok(None)) => return Ok(()),
+        Ok(Err(e)) => {
+            let reason = format!("expected hello: {e}");
+            write_frame(&mut writer, &ServerMessage::Rejected { reason }).await?;
+            return Err(e);
+        }
+        Err(_) => {
+            let reason = "timed out waiting for hello".to_string();
+            return write_frame(&mut writer, &ServerMessage::Rejected { reason }).await;
+        }
+    };
+    let mut client = Client {
+        hello,
+        session: None,
+        spectating: false,
+        player_id: None,
+        queued: false,
+        matches: None,
+    };
+    if let Err(e) = check_hello(&Fingerprint::engine(), &client.hello) {
+        tracing::info!(player = %client.hello.player, error = %e, "Refused client");
+        return write_frame(&mut writer, &ServerMessage::from(ServerReply::from(&e))).await;
+    }
+    if client.hello.identity.is_some() {
+        match sessions.lock().await.identify(&client.hello) {
+            Ok(identity) => client.player_id = identity.map(|identity| identity.id),
+            Err(e) => {
+                tracing::info!(player = %client.hello.player, error = %e, "Refused identity");
+                let reason = e.to_string();
+                return write_frame(&mut writer, &ServerMessage::Rejected { reason }).await;
+            }
+        }
+    }
+    tracing::info!(
+        player = %client.hello.player,
+        player_id = client.player_id.map(|id| id.to_string()),
+        "Client connected"
+    );
+
+    // Replies and pushed lockstep events share one writer task
+    let (outbox, mut outgoing) = mpsc::unbounded_channel();
+    let writer_task = tokio::spawn(async move {
+        while let Some(message) = outgoing.recv().await {
+            write_frame(&mut writer, &message).await?;
+            if matches!(message, ServerMessage::Kicked { .. }) {
+                return writer.shutdown().await;
+            }
+        }
+        io::Result::Ok(())
+    });
+
+    let mut forwarder: Option<JoinHandle<()>> = None;
+    let mut matcher: Option<JoinHandle<()>> = None;
+    let mut request = Some(ClientRequest::ListLobbies);
+    let result = loop {
+        if let Some(request) = request.take() {
+            let mut sessions = sessions.lock().await;
+            let before = client.session;
+            let mut replies: Vec<_> = client.handle(request, &mut sessions).into_iter().collect();
+            if client.session != before {
+                // Follow the events of the session joined, starting from
+                // its current lobby state
+                if let Some(forwarder) = forwarder.take() {
+                    forwarder.abort();
+                }
+                if let Some(id) = client.session {
+                    if let Ok(events) = sessions.subscribe(id) {
+                        forwarder = Some(tokio::spawn(forward_events(
+                            events,
+                            client.hello.player.clone(),
+                            outbox.clone(),
+                        )));
+                    }
+                    replies.extend(sessions.lobby_state(id).ok().map(ServerMessage::Lobby));
+                    if client.spectating {
+                        let snapshot = sessions.spectator_snapshot(id, &client.hello.player);
+                        replies.extend(snapshot.ok().flatten().map(ServerMessage::Snapshot));
+                    }
+                }
+            }
+            if let Some(matches) = client.matches.take() {
+                if let Some(matcher) = matcher.take() {
+                    matcher.abort();
+                }
+                if let Some(player_id) = client.player_id {
+                    matcher = Some(tokio::spawn(forward_match(
+                        matches,
+                        player_id,
+                        outbox.clone(),
+                    )));
+                }
+            }
+            if !client.queued {
+                if let Some(matcher) = matcher.take() {
+                    matcher.abort();
+                }
+            }
+            // Sent under the lock, so no pushed event can overtake them
+            if replies.into_iter().any(|reply| outbox.send(reply).is_err()) {
+                break Ok(());
+            }
+        }
+
+        match read_frame_bytes(&mut reader).await {
+            Ok(Some(bytes)) => match serde_json::from_slice(&bytes) {
+                Ok(next) => request = Some(next),
+                Err(e) => {
+                    let message = format!("invalid request: {e}");
+                    if outbox.send(ServerMessage::Error { message }).is_err() {
+                        break Ok(());
+                    }
+                }
+            },
+            Ok(None) => break Ok(()),
+            Err(e) => break Err(e),
+        }
+    };
+
+    for task in [forwarder, matcher].into_iter().flatten() {
+        task.abort();
+    }
+    drop(outbox);
+    client.disconnect(&mut *sessions.lock().await);
+    tracing::info!(player = %client.hello.player, "Client disconnected");
+    let written = writer_task.await.unwrap_or(Ok(()));
+    result.and(written)
+}
+
+/// Pass a session's events on to one client, skipping chat meant for
+/// others.
+async fn forward_events(
+    mut events: broadcast::Receiver<SessionEvent>,
+    name: String,
+    outbox: mpsc::UnboundedSender<ServerMessage>,
+) {
+    loop {
+        let message = match events.recv().await {
+            Ok(SessionEvent::Chat(chat)) if !chat.is_for(&name) => continue,
+            Ok(SessionEvent::Chat(chat)) => match chat.content {
+                ChatContent::Message(message) => ServerMessage::Chat(message),
+                ChatContent::Ping(ping) => ServerMessage::Ping(ping),
+            },
+            Ok(SessionEvent::Lobby(state)) => ServerMessage::Lobby(state),
+            Ok(SessionEvent::Launched(launch)) => ServerMessage::Launched(launch),
+            Ok(SessionEvent::Lockstep(LockstepEvent::Turn(turn))) => ServerMessage::Turn(turn),
+            Ok(SessionEvent::Lockstep(LockstepEvent::Desync(desync))) => {
+                ServerMessage::Desync(desync)
+            }
+            Ok(SessionEvent::Lockstep(LockstepEvent::Resumed(tick))) => {
+                ServerMessage::Resumed { tick }
+            }
+            Ok(SessionEvent::Lockstep(LockstepEvent::InputDelay { player, ticks })) => {
+                if player != name {
+                    continue;
+                }
+                ServerMessage::InputDelay { ticks }
+            }
+            Ok(SessionEvent::Paused(paused)) => ServerMessage::Paused { paused },
+            Ok(SessionEvent::Rated(ratings)) => ServerMessage::Rated { ratings },
+            Ok(SessionEvent::Kicked { player, reason }) => {
+                if player.is_some_and(|player| player != name) {
+                    continue;
+                }
+                // Nothing else is sent after this
+                let _ = outbox.send(ServerMessage::Kicked { reason });
+                break;
+            }
+            Err(broadcast::error::RecvError::Lagged(missed)) => ServerMessage::Error {
+                message: format!("missed {missed} session events"),
+            },
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        if outbox.send(message).is_err() {
+            break;
+        }
+    }
+}
+
+/// Tell a queued client about the match it was put in, if it is.
+async fn forward_match(
+    mut matches: broadcast::Receiver<QueueMatch>,
+    player_id: PlayerId,
+    outbox: mpsc::UnboundedSender<ServerMessage>,
+) {
+    loop {
+        match matches.recv().await {
+            Ok(matched) => {
+                if !matched.players.iter().any(|p| p.player_id == player_id) {
+                    continue;
+                }
+                let _ = outbox.send(ServerMessage::Matched {
+                    session: matched.session,
+                    players: matched.players.into_iter().map(|p| p.player).collect(),
+                });
+                break;
+            }
+            // A match missed here goes unjoined and expires; the player
+            // can queue again
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// One connected client and the lobby it is in.
+#[derive(Debug)]
+struct Client {
+    hello: ClientHello,
+    session: Option<SessionId>,
+    /// Whether the client joined its session as a spectator.
+    spectating: bool,
+    /// Registered identity, once presented or registered.
+    player_id: Option<PlayerId>,
+    /// Whether the client asked to wait for a rated game.
+    queued: bool,
+    /// Matches made from the queue, from when the client last joined it,
+    /// until the connection starts watching them.
+    matches: Option<broadcast::Receiver<QueueMatch>>,
+}
+
+impl Client {
+    /// Route one request to the session manager. Seat changes and
+    /// lockstep requests are only answered when they fail.
+    fn handle(
+        &mut self,
+        request: ClientRequest,
+        sessions: &mut SessionManager,
+    ) -> Option<ServerMessage> {
+        let result = match request {
+            ClientRequest::ListLobbies => Ok(Some(ServerMessage::Lobbies {
+                lobbies: sessions
+                    .list()
+                    .into_iter()
+                    .filter(|info| info.state == SessionState::Lobby)
+                    .map(|info| LobbyListing {
+                        session: info.id,
+                        name: info.name,
+                        players: info.players,
+                        map: info.map,
+                    })
+                    .collect(),
+            })),
+            ClientRequest::ListGames => Ok(Some(ServerMessage::Games {
+                games: sessions
+                    .list()
+                    .into_iter()
+                    .filter(|info| info.state == SessionState::Running)
+                    .map(|info| LobbyListing {
+                        session: info.id,
+                        name: info.name,
+                        players: info.players,
+                        map: info.map,
+                    })
+                    .collect(),
+            })),
+            ClientRequest::Register => {
+                if self.player_id.is_some() {
+                    return Some(ServerMessage::Error {
+                        message: "already registered".to_string(),
+                    });
+                }
+                sessions
+                    .register(&self.hello.player)
+                    .map(|(identity, token)| {
+                        self.player_id = Some(identity.id);
+                        self.hello.identity = Some(token.clone());
+                        Some(ServerMessage::Registered {
+                            player_id: identity.id,
+                            token,
+                        })
+                    })
+            }
+            ClientRequest::OpenLobby { name } => sessions
+                .open_lobby(name)
+                .map(|session| Some(ServerMessage::LobbyOpened { session })),
+            ClientRequest::Join { session } => {
+                if let Some(current) = self.session {
+                    return Some(ServerMessage::Error {
+                        message: format!("already in session {current}"),
+                    });
+                }
+                return Some(match sessions.handshake(session, &self.hello) {
+                    Ok(reply) => {
+                        self.session = Some(session);
+                        reply.into()
+                    }
+                    Err(e) => ServerMessage::Rejected {
+                        reason: e.to_string(),
+                    },
+                });
+            }
+            ClientRequest::Spectate { session } => {
+                if let Some(current) = self.session {
+                    return Some(ServerMessage::Error {
+                        message: format!("already in session {current}"),
+                    });
+                }
+                return Some(match sessions.spectate_handshake(session, &self.hello) {
+                    Ok(reply) => {
+                        self.session = Some(session);
+                        self.spectating = true;
+                        reply.into()
+                    }
+                    Err(e) => ServerMessage::Rejected {
+                        reason: e.to_string(),
+                    },
+                });
+            }
+            ClientRequest::Queue => match (self.player_id, self.session) {
+                (_, Some(current)) => {
+                    return Some(ServerMessage::Error {
+                        message: format!("already in session {current}"),
+                    })
+                }
+                (None, None) => {
+                    return Some(ServerMessage::Error {
+                        message: "register to play rated games".to_string(),
+                    })
+                }
+                (Some(player_id), None) => sessions
+                    .enqueue(&self.hello.player, player_id, Instant::now())
+                    .map(|(rating, matches)| {
+                        self.queued = true;
+                        self.matches = Some(matches);
+                        Some(ServerMessage::Queued { rating })
+                    }),
+            },
+            ClientRequest::LeaveQueue => {
+                if let Some(player_id) = self.player_id {
+                    sessions.dequeue(player_id);
+                }
+                self.queued = false;
+                Ok(Some(ServerMessage::LeftQueue))
+            }
+            ClientRequest::Rating => match self.player_id {
+                Some(player_id) => Ok(Some(ServerMessage::Rating {
+                    rating: sessions.ratings().public(player_id),
+                })),
+                None => {
+                    return Some(ServerMessage::Error {
+                        message: "not registered".to_string(),
+                    })
+                }
+            },
+            ClientRequest::Rejoin { session, token } => {
+                if let Some(current) = self.session {
+                    return Some(ServerMessage::Error {
+                        message: format!("already in session {current}"),
+                    });
+                }
+                sessions.rejoin(session, &self.hello, &token).map(|rejoin| {
+                    self.session = Some(session);
+                    Some(ServerMessage::Rejoined(rejoin))
+                })
+            }
+            ClientRequest::Veto { action, map } => match self.session {
+                Some(session) => sessions
+                    .veto(session, &self.hello.player, action, &map)
+                    .map(|chosen| Some(ServerMessage::Veto { chosen })),
+                None => return Some(not_in_lobby()),
+            },
+            ClientRequest::ChooseFaction { faction } => match self.session {
+                Some(session) => sessions
+                    .choose_faction(session, &self.hello.player, faction)
+                    .map(|()| None),
+                None => return Some(not_in_lobby()),
+            },
+            ClientRequest::ChooseStart { start } => match self.session {
+                Some(session) => sessions
+                    .choose_start(session, &self.hello.player, start)
+                    .map(|()| None),
+                None => return Some(not_in_lobby()),
+            },
+            ClientRequest::Ready { ready } => match self.session {
+                Some(session) => sessions
+                    .set_ready(session, &self.hello.player, ready)
+                    .map(|()| None),
+                None => return Some(not_in_lobby()),
+            },
+            ClientRequest::AddBot { faction, strategy } => match self.session {
+                Some(_) if self.spectating => {
+                    Err(SessionError::Spectator(self.hello.player.clone()))
+                }
+                Some(session) => sessions
+                    .add_strategy_bot(
+                        session,
+                        strategy.as_deref().unwrap_or(DEFAULT_BOT_STRATEGY),
+                        faction,
+                    )
+                    .map(|_| None),
+                None => return Some(not_in_lobby()),
+            },
+            ClientRequest::Leave => match self.session.take() {
+                Some(session) => {
+                    self.spectating = false;
+                    sessions
+                        .leave(session, &self.hello.player)
+                        .map(|()| Some(ServerMessage::Left { session }))
+                }
+                None => return Some(not_in_lobby()),
+            },
+            ClientRequest::Chat { channel, text } => match self.session {
+                Some(session) => sessions
+                    .chat(session, &self.hello.player, channel, &text)
+                    .map(|()| None),
+                None => return Some(not_in_lobby()),
+            },
+            ClientRequest::Ping {
+                channel,
+                position,
+                kind,
+            } => match self.session {
+                Some(session) => sessions
+                    .ping(session, &self.hello.player, channel, position, kind)
+                    .map(|()| None),
+                None => return Some(not_in_lobby()),
+            },
+            ClientRequest::Commands { tick, commands } => match self.session {
+                Some(session) => sessions
+                    .submit_commands(session, &self.hello.player, tick, commands)
+                    .map(|()| None),
+                None => return Some(not_in_lobby()),
+            },
+            ClientRequest::Confirm { tick, state_hash } => match self.session {
+                Some(session) => sessions
+                    .confirm_tick(session, &self.hello.player, tick, state_hash)
+                    .map(|()| None),
+                None => return Some(not_in_lobby()),
+            },
+            ClientRequest::ReportState { state } => match self.session {
+                Some(session) => sessions
+                    .report_state(session, &self.hello.player, &state)
+                    .map(|report| Some(ServerMessage::DesyncReport(report))),
+                None => return Some(not_in_lobby()),
+            },
+            ClientRequest::Resync => match self.session {
+                Some(session) => sessions
+                    .resync(session, &self.hello.player)
+                    .map(|resync| Some(ServerMessage::Resync(resync))),
+                None => return Some(not_in_lobby()),
+            },
+            ClientRequest::Resynced { tick, state_hash } => match self.session {
+                Some(session) => sessions
+                    .resynced(session, &self.hello.player, tick, state_hash)
+                    .map(|()| None),
+                None => return Some(not_in_lobby()),
+            },
+        };
+        result.unwrap_or_else(|e: SessionError| {
+            Some(ServerMessage::Error {
+                message: e.to_string(),
+            })
+        })
+    }
+
+    /// Leave the lobby when the connection drops. Players of running
+    /// games stay in them.
+    fn disconnect(&mut self, sessions: &mut SessionManager) {
+        if let Some(player_id) = self.player_id.filter(|_| self.queued) {
+            sessions.dequeue(player_id);
+        }
+        if let Some(session) = self.session.take() {
+            if let Err(e) = sessions.leave(session, &self.hello.player) {
+                tracing::debug!(session, error = %e, "Disconnected player stays in session");
+            }
+        }
+    }
+}
+
+fn not_in_lobby() -> ServerMessage {
+    ServerMessage::Error {
+        message: "not in a lobby".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lockstep::TurnCommand;
+    use crate::ServerConfig;
+    use rts_core::autosave::Autosave;
+    use rts_core::components::Command;
+    use rts_core::fingerprint::SIM_VERSION;
+
+    #[test]
+    fn test_hello_roundtrips_as_json() {
+        let hello = ClientHello::new("alice", Fingerprint::engine().with_map_name("delta"));
+        let json = serde_json::to_string(&hello).unwrap();
+        assert!(
+            json.starts_with(r#"{"type":"hello","protocol_version":1"#),
+            "{json}"
+        );
+        assert_eq!(serde_json::from_str::<ClientHello>(&json).unwrap(), hello);
+    }
+
+    #[test]
+    fn test_mismatches_are_refused_on_both_ends() {
+        let server = Fingerprint::engine().with_map_name("delta");
+        let old_client = Fingerprint {
+            sim_version: SIM_VERSION + 1,
+            ..Fingerprint::engine()
+        };
+
+        let err = check_hello(&server, &ClientHello::new("bob", old_client)).unwrap_err();
+        assert!(err.to_string().contains("engine"), "{err}");
+        let mut stale = ClientHello::new("bob", Fingerprint::engine());
+        stale.protocol_version = 0;
+        assert_eq!(
+            check_hello(&server, &stale),
+            Err(HandshakeError::Protocol {
+                local: PROTOCOL_VERSION,
+                remote: 0
+            })
+        );
+
+        let welcome = ServerReply::Welcome {
+            session: 3,
+            fingerprint: server,
+            token: None,
+        };
+        assert_eq!(confirm_welcome(&Fingerprint::engine(), &welcome), Ok(3));
+        let other_map = Fingerprint::engine().with_map_name("dunes");
+        assert!(matches!(
+            confirm_welcome(&other_map, &welcome),
+            Err(HandshakeError::Incompatible(_))
+        ));
+        assert!(matches!(
+            confirm_welcome(&other_map, &ServerReply::from(&err)),
+            Err(HandshakeError::Rejected(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_frames_roundtrip_and_enforce_limit() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        let request = ClientRequest::Join { session: 7 };
+        write_frame(&mut client, &request).await.unwrap();
+        assert_eq!(read_frame(&mut server).await.unwrap(), Some(request));
+
+        client
+            .write_all(&((MAX_FRAME_BYTES as u32) + 1).to_be_bytes())
+            .await
+            .unwrap();
+        let err = read_frame::<_, ClientRequest>(&mut server)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        drop(client);
+        assert_eq!(
+            read_frame::<_, ClientRequest>(&mut server).await.unwrap(),
+            None
+        );
+    }
+
+    async fn connect(addr: SocketAddr, hello: &ClientHello) -> (TcpStream, ServerMessage) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        write_frame(&mut stream, hello).await.unwrap();
+        let reply = read_frame(&mut stream).await.unwrap().unwrap();
+        (stream, reply)
+    }
+
+    async fn request(stream: &mut TcpStream, request: &ClientRequest) -> ServerMessage {
+        write_frame(stream, request).await.unwrap();
+        read_frame(stream).await.unwrap().unwrap()
+    }
+
+    /// Next message that is not a lobby update.
+    /// Next message other than lobby updates and input delay changes,
+    /// which depend on how fast the test runs.
+    async fn skip_lobby(stream: &mut TcpStream) -> ServerMessage {
+        loop {
+            match read_frame(stream).await.unwrap().unwrap() {
+                ServerMessage::Lobby(_) | ServerMessage::InputDelay { .. } => {}
+                message => return message,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_server_routes_clients_to_lobbies() {
+        let config = ServerConfig {
+            max_sessions: 4,
+            ..Default::default()
+        };
+        let sessions = Arc::new(Mutex::new(SessionManager::new(&config)));
+        let server = NetworkServer::bind("127.0.0.1:0", Arc::clone(&sessions))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        tokio::spawn(server.serve());
+
+        let ours = Fingerprint {
+            data: Some(1),
+            ..Fingerprint::engine()
+        };
+        let (mut alice, listing) = connect(addr, &ClientHello::new("alice", ours)).await;
+        assert_eq!(listing, ServerMessage::Lobbies { lobbies: vec![] });
+        let opened = request(
+            &mut alice,
+            &ClientRequest::OpenLobby {
+                name: "ranked".to_string(),
+            },
+        )
+        .await;
+        assert_eq!(opened, ServerMessage::LobbyOpened { session: 1 });
+        let ServerMessage::Welcome {
+            session,
+            fingerprint,
+            token,
+        } = request(&mut alice, &ClientRequest::Join { session: 1 }).await
+        else {
+            panic!("alice should be welcomed");
+        };
+        assert!(token.is_some(), "players are given a session token");
+        assert_eq!(
+            confirm_welcome(
+                &ours,
+                &ServerReply::Welcome {
+                    session,
+                    fingerprint,
+                    token,
+                }
+            ),
+            Ok(1)
+        );
+
+        // Different faction data is refused at the lobby, not the door
+        let theirs = Fingerprint {
+            data: Some(2),
+            ..Fingerprint::engine()
+        };
+        let (mut bob, listing) = connect(addr, &ClientHello::new("bob", theirs)).await;
+        let ServerMessage::Lobbies { lobbies } = listing else {
+            panic!("expected lobby listing, got {listing:?}");
+        };
+        assert_eq!(lobbies[0].players, vec!["alice"]);
+        let refused = request(&mut bob, &ClientRequest::Join { session: 1 }).await;
+        assert!(
+            matches!(&refused, ServerMessage::Rejected { reason } if reason.contains("faction data")),
+            "{refused:?}"
+        );
+
+        // Malformed requests are answered without dropping the connection
+        bob.write_all(&4u32.to_be_bytes()).await.unwrap();
+        bob.write_all(b"oops").await.unwrap();
+        let reply: ServerMessage = read_frame(&mut bob).await.unwrap().unwrap();
+        assert!(matches!(reply, ServerMessage::Error { .. }), "{reply:?}");
+
+        // Old clients are turned away before reaching a lobby
+        let mut old = ClientHello::new("carol", Fingerprint::engine());
+        old.protocol_version = 0;
+        let (mut carol, refused) = connect(addr, &old).await;
+        assert!(matches!(refused, ServerMessage::Rejected { .. }));
+        assert_eq!(
+            read_frame::<_, ServerMessage>(&mut carol).await.unwrap(),
+            None
+        );
+
+        // Registered players come back with their token; forgers don't
+        let ServerMessage::Registered { player_id, token } =
+            request(&mut bob, &ClientRequest::Register).await
+        else {
+            panic!("bob should be registered");
+        };
+        let hello = ClientHello::new("bob", Fingerprint::engine()).with_identity(&token);
+        let (mut bob_again, listing) = connect(addr, &hello).await;
+        assert!(
+            matches!(listing, ServerMessage::Lobbies { .. }),
+            "{listing:?}"
+        );
+        assert_eq!(sessions.lock().await.players().name(player_id), Some("bob"));
+
+        // Only registered players queue for rated games
+        let ServerMessage::Queued { rating } = request(&mut bob_again, &ClientRequest::Queue).await
+        else {
+            panic!("bob should be queued");
+        };
+        assert_eq!(
+            request(&mut bob_again, &ClientRequest::Rating).await,
+            ServerMessage::Rating { rating }
+        );
+        assert_eq!(
+            request(&mut bob_again, &ClientRequest::LeaveQueue).await,
+            ServerMessage::LeftQueue
+        );
+        assert!(sessions.lock().await.queue().is_empty());
+        let (mut dave, _) = connect(addr, &ClientHello::new("dave", Fingerprint::engine())).await;
+        assert!(matches!(
+            request(&mut dave, &ClientRequest::Queue).await,
+            ServerMessage::Error { .. }
+        ));
+        let forged = ClientHello::new("bob", Fingerprint::engine()).with_identity("bob.00");
+        let (_, refused) = connect(addr, &forged).await;
+        assert!(matches!(refused, ServerMessage::Rejected { .. }));
+
+        // Dropping the connection leaves the lobby, closing it
+        drop(alice);
+        for _ in 0..100 {
+            if sessions.lock().await.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(
+            request(&mut bob, &ClientRequest::ListLobbies).await,
+            ServerMessage::Lobbies { lobbies: vec![] }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lockstep_turns_reach_every_client() {
+        use rts_core::components::FactionMember;
+        use rts_core::factions::FactionId;
+        use rts_core::math::{Fixed, Vec2Fixed};
+        use rts_core::simulation::{EntitySpawnParams, Simulation};
+
+        let sessions = Arc::new(Mutex::new(SessionManager::new(&ServerConfig::default())));
+        let server = NetworkServer::bind("127.0.0.1:0", Arc::clone(&sessions))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        tokio::spawn(server.serve());
+
+        let id = sessions.lock().await.open_lobby("lockstep").unwrap();
+        let mut clients = Vec::new();
+        for player in ["alice", "bob"] {
+            let (mut stream, _) =
+                connect(addr, &ClientHello::new(player, Fingerprint::engine())).await;
+            let joined = request(&mut stream, &ClientRequest::Join { session: id }).await;
+            assert!(
+                matches!(joined, ServerMessage::Welcome { .. }),
+                "{joined:?}"
+            );
+            clients.push(stream);
+        }
+
+        let mut sim = Simulation::new();
+        let unit = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            movement: Some(Fixed::from_num(2)),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            ..Default::default()
+        });
+        let mut local = sim.clone();
+        let stale = sim.clone();
+        sessions
+            .lock()
+            .await
+            .start_lockstep(id, sim, &[FactionId::Continuity, FactionId::Collegium])
+            .unwrap();
+
+        let order = EntityCommand {
+            entity: unit,
+            command: Command::MoveTo(Vec2Fixed::new(Fixed::from_num(40), Fixed::ZERO)),
+        };
+        let submissions = [vec![order.clone()], Vec::new()];
+        for (stream, commands) in clients.iter_mut().zip(submissions) {
+            let submit = ClientRequest::Commands { tick: 0, commands };
+            write_frame(stream, &submit).await.unwrap();
+        }
+        let expected = Turn {
+            tick: 0,
+            commands: vec![TurnCommand {
+                player: "alice".to_string(),
+                entity: unit,
+                command: order.command.clone(),
+            }],
+            confirm: true,
+        };
+        for stream in &mut clients {
+            let pushed = skip_lobby(stream).await;
+            assert_eq!(pushed, ServerMessage::Turn(expected.clone()));
+        }
+
+        // Each client plays the turn locally and confirms; bob diverges
+        local.apply_command(unit, order.command).unwrap();
+        local.tick();
+        let hashes = [local.state_hash(), local.state_hash() ^ 1];
+        for (stream, state_hash) in clients.iter_mut().zip(hashes) {
+            let confirm = ClientRequest::Confirm {
+                tick: 0,
+                state_hash,
+            };
+            write_frame(stream, &confirm).await.unwrap();
+        }
+        for stream in &mut clients {
+            let pushed: ServerMessage = read_frame(stream).await.unwrap().unwrap();
+            assert!(
+                matches!(&pushed, ServerMessage::Desync(d) if d.players == ["bob"]),
+                "{pushed:?}"
+            );
+        }
+
+        // Bob's state never played the turn; the report says where it is off
+        let state = Autosave::capture("bob", &stale)
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        let report = request(&mut clients[1], &ClientRequest::ReportState { state }).await;
+        let ServerMessage::DesyncReport(report) = report else {
+            panic!("expected desync report, got {report:?}");
+        };
+        assert_eq!(report.desync_tick, Some(0));
+        assert!(report
+            .differences
+            .iter()
+            .any(|d| d.entity == Some(unit) && d.field == "position"));
+
+        let ServerMessage::Resync(resync) = request(&mut clients[1], &ClientRequest::Resync).await
+        else {
+            panic!("expected resync");
+        };
+        let restored = Autosave::from_bytes(&resync.state)
+            .unwrap()
+            .restore_simulation()
+            .unwrap();
+        assert_eq!(restored.state_hash(), local.state_hash());
+        let resynced = ClientRequest::Resynced {
+            tick: resync.tick,
+            state_hash: restored.state_hash(),
+        };
+        write_frame(&mut clients[1], &resynced).await.unwrap();
+        for stream in &mut clients {
+            let pushed: ServerMessage = read_frame(stream).await.unwrap().unwrap();
+            assert_eq!(pushed, ServerMessage::Resumed { tick: 1 });
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spectators_watch_live_games() {
+        use rts_core::components::FactionMember;
+        use rts_core::factions::FactionId;
+        use rts_core::math::Vec2Fixed;
+        use rts_core::simulation::{EntitySpawnParams, Simulation};
+
+        let sessions = Arc::new(Mutex::new(SessionManager::new(&ServerConfig::default())));
+        let server = NetworkServer::bind("127.0.0.1:0", Arc::clone(&sessions))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        tokio::spawn(server.serve());
+
+        let mut sim = Simulation::new();
+        for faction in [FactionId::Continuity, FactionId::Collegium] {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::ZERO),
+                faction: Some(FactionMember::new(faction, 0)),
+                ..Default::default()
+            });
+        }
+        let id = {
+            let mut sessions = sessions.lock().await;
+            let id = sessions.open_lobby("final").unwrap();
+            sessions.join(id, "alice").unwrap();
+            sessions.join(id, "bob").unwrap();
+            sessions
+                .start_lockstep(id, sim, &[FactionId::Continuity, FactionId::Collegium])
+                .unwrap();
+            id
+        };
+
+        let (mut caster, _) =
+            connect(addr, &ClientHello::new("caster", Fingerprint::engine())).await;
+        let ServerMessage::Games { games } = request(&mut caster, &ClientRequest::ListGames).await
+        else {
+            panic!("expected game listing");
+        };
+        assert_eq!(games[0].players, vec!["alice", "bob"]);
+        let welcome = request(&mut caster, &ClientRequest::Spectate { session: id }).await;
+        assert!(
+            matches!(welcome, ServerMessage::Welcome { .. }),
+            "{welcome:?}"
+        );
+        let ServerMessage::Lobby(state) = read_frame(&mut caster).await.unwrap().unwrap() else {
+            panic!("expected lobby state");
+        };
+        assert_eq!(state.spectators, vec!["caster"]);
+        let snapshot = skip_lobby(&mut caster).await;
+        assert!(
+            matches!(&snapshot, ServerMessage::Snapshot(s) if s.entities.len() == 2),
+            "{snapshot:?}"
+        );
+
+        // Spectators cannot play, but receive the players' turns
+        let refused = request(
+            &mut caster,
+            &ClientRequest::Commands {
+                tick: 0,
+                commands: Vec::new(),
+            },
+        )
+        .await;
+        assert!(
+            matches!(refused, ServerMessage::Error { .. }),
+            "{refused:?}"
+        );
+        for player in ["alice", "bob"] {
+            sessions
+                .lock()
+                .await
+                .submit_commands(id, player, 0, Vec::new())
+                .unwrap();
+        }
+        let pushed = skip_lobby(&mut caster).await;
+        assert!(
+            matches!(&pushed, ServerMessage::Turn(turn) if turn.tick == 0),
+            "{pushed:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dropped_players_rejoin_with_their_token() {
+        use rts_core::components::FactionMember;
+        use rts_core::factions::FactionId;
+        use rts_core::math::{Fixed, Vec2Fixed};
+        use rts_core::simulation::{EntitySpawnParams, Simulation};
+
+        let sessions = Arc::new(Mutex::new(SessionManager::new(&ServerConfig::default())));
+        let server = NetworkServer::bind("127.0.0.1:0", Arc::clone(&sessions))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        tokio::spawn(server.serve());
+
+        let id = sessions.lock().await.open_lobby("rejoin").unwrap();
+        let mut clients = Vec::new();
+        let mut tokens = Vec::new();
+        for player in ["alice", "bob"] {
+            let (mut stream, _) =
+                connect(addr, &ClientHello::new(player, Fingerprint::engine())).await;
+            let ServerMessage::Welcome {
+                token: Some(token), ..
+            } = request(&mut stream, &ClientRequest::Join { session: id }).await
+            else {
+                panic!("{player} should be welcomed with a token");
+            };
+            clients.push(stream);
+            tokens.push(token);
+        }
+        assert_ne!(tokens[0], tokens[1]);
+
+        let mut sim = Simulation::new();
+        let unit = sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::ZERO),
+            movement: Some(Fixed::from_num(2)),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            ..Default::default()
+        });
+        let mut local = sim.clone();
+        sessions
+            .lock()
+            .await
+            .start_lockstep(id, sim, &[FactionId::Continuity, FactionId::Collegium])
+            .unwrap();
+
+        let order = EntityCommand {
+            entity: unit,
+            command: Command::MoveTo(Vec2Fixed::new(Fixed::from_num(40), Fixed::ZERO)),
+        };
+        for (stream, commands) in clients.iter_mut().zip([vec![order.clone()], Vec::new()]) {
+            write_frame(stream, &ClientRequest::Commands { tick: 0, commands })
+                .await
+                .unwrap();
+        }
+        for stream in &mut clients {
+            let pushed = skip_lobby(stream).await;
+            assert!(matches!(pushed, ServerMessage::Turn(_)), "{pushed:?}");
+        }
+        local.apply_command(unit, order.command).unwrap();
+        local.tick();
+        for stream in &mut clients {
+            let confirm = ClientRequest::Confirm {
+                tick: 0,
+                state_hash: local.state_hash(),
+            };
+            write_frame(stream, &confirm).await.unwrap();
+        }
+
+        // Alice drops; bob's tick 1 waits for her
+        let mut bob = clients.pop().unwrap();
+        drop(clients);
+        let submit = ClientRequest::Commands {
+            tick: 1,
+            commands: Vec::new(),
+        };
+        write_frame(&mut bob, &submit).await.unwrap();
+
+        let (mut alice, _) = connect(addr, &ClientHello::new("alice", Fingerprint::engine())).await;
+        let refused = request(
+            &mut alice,
+            &ClientRequest::Rejoin {
+                session: id,
+                token: tokens[1].clone(),
+            },
+        )
+        .await;
+        assert!(
+            matches!(&refused, ServerMessage::Error { message } if message.contains("token")),
+            "{refused:?}"
+        );
+        let rejoined = request(
+            &mut alice,
+            &ClientRequest::Rejoin {
+                session: id,
+                token: tokens[0].clone(),
+            },
+        )
+        .await;
+        let ServerMessage::Rejoined(rejoin) = rejoined else {
+            panic!("expected rejoin, got {rejoined:?}");
+        };
+        assert_eq!(rejoin.next_tick(), 1);
+        let caught_up = rejoin.fast_forward().unwrap();
+        assert_eq!(caught_up.state_hash(), local.state_hash());
+
+        write_frame(&mut alice, &submit).await.unwrap();
+        for stream in [&mut alice, &mut bob] {
+            let pushed = skip_lobby(stream).await;
+            assert!(
+                matches!(&pushed, ServerMessage::Turn(turn) if turn.tick == 1),
+                "{pushed:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_and_pings_reach_their_recipients() {
+        use rts_core::factions::FactionId;
+
+        let sessions = Arc::new(Mutex::new(SessionManager::new(&ServerConfig::default())));
+        let server = NetworkServer::bind("127.0.0.1:0", Arc::clone(&sessions))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        tokio::spawn(server.serve());
+
+        let id = sessions.lock().await.open_lobby("chat").unwrap();
+        let mut clients = Vec::new();
+        for (player, faction) in [
+            ("alice", FactionId::Continuity),
+            ("bob", FactionId::Collegium),
+        ] {
+            let (mut stream, _) =
+                connect(addr, &ClientHello::new(player, Fingerprint::engine())).await;
+            request(&mut stream, &ClientRequest::Join { session: id }).await;
+            write_frame(&mut stream, &ClientRequest::ChooseFaction { faction })
+                .await
+                .unwrap();
+            clients.push(stream);
+        }
+
+        let team = ClientRequest::Chat {
+            channel: ChatChannel::Team,
+            text: "rush at 3".to_string(),
+        };
+        write_frame(&mut clients[0], &team).await.unwrap();
+        let heard = skip_lobby(&mut clients[0]).await;
+        assert!(
+            matches!(&heard, ServerMessage::Chat(m) if m.from == "alice" && m.text == "rush at 3"),
+            "{heard:?}"
+        );
+
+        // Bob never hears the team message; both see his ping
+        let ping = ClientRequest::Ping {
+            channel: ChatChannel::All,
+            position: Vec2Fixed::ZERO,
+            kind: PingKind::Defend,
+        };
+        write_frame(&mut clients[1], &ping).await.unwrap();
+        for stream in &mut clients {
+            let pushed = skip_lobby(stream).await;
+            assert!(
+                matches!(&pushed, ServerMessage::Ping(p) if p.from == "bob" && p.kind == PingKind::Defend),
+                "{pushed:?}"
+            );
+        }
+
+        let refused = request(
+            &mut clients[1],
+            &ClientRequest::Chat {
+                channel: ChatChannel::All,
+                text: String::new(),
+            },
+        )
+        .await;
+        assert!(
+            matches!(refused, ServerMessage::Error { .. }),
+            "{refused:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lobby_state_is_pushed_until_launch() {
+        use rts_core::factions::FactionId;
+        use std::time::Instant;
+
+        let config = ServerConfig {
+            countdown_secs: 0,
+            ..Default::default()
+        };
+        let sessions = Arc::new(Mutex::new(SessionManager::new(&config)));
+        let server = NetworkServer::bind("127.0.0.1:0", Arc::clone(&sessions))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        tokio::spawn(server.serve());
+
+        let id = sessions.lock().await.open_lobby("skirmish").unwrap();
+        let mut clients = Vec::new();
+        for player in ["alice", "bob"] {
+            let (mut stream, _) =
+                connect(addr, &ClientHello::new(player, Fingerprint::engine())).await;
+            let joined = request(&mut stream, &ClientRequest::Join { session: id }).await;
+            assert!(matches!(joined, ServerMessage::Welcome { .. }));
+            let ServerMessage::Lobby(state) = read_frame(&mut stream).await.unwrap().unwrap()
+            else {
+                panic!("joining should push the lobby state");
+            };
+            assert_eq!(state.seats.last().unwrap().player, player);
+            clients.push(stream);
+        }
+        // Alice hears about bob joining
+        let ServerMessage::Lobby(state) = read_frame(&mut clients[0]).await.unwrap().unwrap()
+        else {
+            panic!("expected lobby update");
+        };
+        assert_eq!(state.seats.len(), 2);
+
+        let refused = request(&mut clients[0], &ClientRequest::Ready { ready: true }).await;
+        assert!(
+            matches!(&refused, ServerMessage::Error { message } if message.contains("faction")),
+            "{refused:?}"
+        );
+
+        let factions = [FactionId::Continuity, FactionId::Collegium];
+        for (stream, faction) in clients.iter_mut().zip(factions) {
+            write_frame(stream, &ClientRequest::ChooseFaction { faction })
+                .await
+                .unwrap();
+            write_frame(stream, &ClientRequest::Ready { ready: true })
+                .await
+                .unwrap();
+        }
+        // Every client sees the countdown start before the launch
+        for stream in &mut clients {
+            loop {
+                let ServerMessage::Lobby(state) = read_frame(stream).await.unwrap().unwrap() else {
+                    panic!("expected lobby update");
+                };
+                if state.countdown_ms.is_some() {
+                    break;
+                }
+            }
+        }
+
+        assert_eq!(sessions.lock().await.launch_due(Instant::now()), vec![id]);
+        for stream in &mut clients {
+            let ServerMessage::Launched(launch) = skip_lobby(stream).await else {
+                panic!("expected launch");
+            };
+            assert_eq!(launch.session, id);
+            let chosen: Vec<_> = launch.seats.iter().map(|seat| seat.faction).collect();
+            assert_eq!(chosen, vec![Some(factions[0]), Some(factions[1])]);
+        }
+    }
+}
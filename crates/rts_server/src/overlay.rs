@@ -0,0 +1,345 @@
+//! Read-only score stream for live broadcast overlays.
+//!
+//! A [`ScoreTracker`] folds simulation ticks into one [`ScoreFrame`] per
+//! second of game time, using the shared [`rts_core::stats`] definitions of
+//! supply and army value. An [`OverlayServer`] pushes the latest frame as
+//! JSON to any number of websocket clients, such as a browser source in
+//! streaming software.
+//!
+//! The stream is one-way: clients cannot issue commands, and anything they
+//! send other than a close frame is ignored. Slow clients skip frames rather
+//! than queueing them, so a stalled overlay never holds back the match.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::Message;
+
+use rts_core::factions::FactionId;
+use rts_core::simulation::{Simulation, TickEvents, TICK_RATE};
+use rts_core::stats::faction_totals;
+
+/// Side length of the grid cells damage is bucketed into when grouping
+/// combat into fights (world units).
+pub const FIGHT_CELL_SIZE: i32 = 256;
+
+/// How many seconds of fights a frame reports.
+pub const RECENT_FIGHT_SECONDS: u64 = 10;
+
+/// Aggregate state of one faction for a frame.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FactionScore {
+    /// Faction this score belongs to.
+    pub faction: FactionId,
+    /// Living mobile units.
+    pub supply: u32,
+    /// Resources gained over the last second, as reported by the host.
+    pub income: i64,
+    /// Summed current health of mobile, armed units.
+    pub army_value: u64,
+}
+
+/// Combat in one map cell during one second.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fight {
+    /// Second of game time the fight happened in.
+    pub second: u64,
+    /// Centre of the cell the fight happened in.
+    pub x: i32,
+    /// Centre of the cell the fight happened in.
+    pub y: i32,
+    /// Factions that dealt damage.
+    pub factions: BTreeSet<FactionId>,
+    /// Total damage dealt.
+    pub damage: u64,
+    /// Units and structures killed.
+    pub deaths: u32,
+}
+
+/// One second of match state, as sent to overlay clients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreFrame {
+    /// Simulation tick the frame was taken at.
+    pub tick: u64,
+    /// Whole seconds of game time elapsed.
+    pub second: u64,
+    /// Per-faction aggregates, in faction order.
+    pub factions: Vec<FactionScore>,
+    /// Fights from the last [`RECENT_FIGHT_SECONDS`], oldest first.
+    pub recent_fights: Vec<Fight>,
+}
+
+/// Folds simulation ticks into per-second [`ScoreFrame`]s.
+#[derive(Debug, Clone, Default)]
+pub struct ScoreTracker {
+    income: BTreeMap<FactionId, i64>,
+    open_fights: BTreeMap<(i32, i32), Fight>,
+    fights: VecDeque<Fight>,
+}
+
+impl ScoreTracker {
+    /// Create an empty tracker.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report resources a faction gained this tick.
+    ///
+    /// The simulation does not own player economies, so the host feeds
+    /// income in as it credits it.
+    pub fn record_income(&mut self, faction: FactionId, amount: i64) {
+        *self.income.entry(faction).or_default() += amount;
+    }
+
+    /// Fold in one simulation tick, returning a frame on each second
+    /// boundary.
+    pub fn on_tick(&mut self, sim: &Simulation, events: &TickEvents) -> Option<ScoreFrame> {
+        let tick = sim.get_tick();
+        let second = tick / u64::from(TICK_RATE);
+        self.record_fights(sim, events, second);
+
+        if tick % u64::from(TICK_RATE) != 0 {
+            return None;
+        }
+
+        self.fights
+            .extend(std::mem::take(&mut self.open_fights).into_values());
+        let oldest = second.saturating_sub(RECENT_FIGHT_SECONDS);
+        while self.fights.front().is_some_and(|f| f.second < oldest) {
+            self.fights.pop_front();
+        }
+
+        let mut totals = faction_totals(sim);
+        for faction in self.income.keys() {
+            totals.entry(*faction).or_default();
+        }
+        let factions = totals
+            .into_iter()
+            .map(|(faction, totals)| FactionScore {
+                faction,
+                supply: totals.supply,
+                income: self.income.get(&faction).copied().unwrap_or(0),
+                army_value: totals.army_value,
+            })
+            .collect();
+        self.income.clear();
+
+        Some(ScoreFrame {
+            tick,
+            second,
+            factions,
+            recent_fights: self.fights.iter().cloned().collect(),
+        })
+    }
+
+    fn record_fights(&mut self, sim: &Simulation, events: &TickEvents, second: u64) {
+        for event in &events.damage_events {
+            // Dead targets are already gone, so locate fights by attacker
+            let Some(attacker) = sim.get_entity(event.attacker) else {
+                continue;
+            };
+            let Some(position) = attacker.position else {
+                continue;
+            };
+            let cell = (
+                position.value.x.to_num::<i32>().div_euclid(FIGHT_CELL_SIZE),
+                position.value.y.to_num::<i32>().div_euclid(FIGHT_CELL_SIZE),
+            );
+            let fight = self.open_fights.entry(cell).or_insert_with(|| Fight {
+                second,
+                x: cell.0 * FIGHT_CELL_SIZE + FIGHT_CELL_SIZE / 2,
+                y: cell.1 * FIGHT_CELL_SIZE + FIGHT_CELL_SIZE / 2,
+                factions: BTreeSet::new(),
+                damage: 0,
+                deaths: 0,
+            });
+            if let Some(faction) = attacker.faction {
+                fight.factions.insert(faction.faction);
+            }
+            fight.damage += u64::from(event.damage);
+            if events.deaths.contains(&event.target) {
+                fight.deaths += 1;
+            }
+        }
+    }
+}
+
+/// Handle for publishing frames to every connected overlay client.
+#[derive(Debug, Clone)]
+pub struct OverlayPublisher {
+    frames: Arc<watch::Sender<Option<Arc<str>>>>,
+}
+
+impl OverlayPublisher {
+    /// Publish a frame, replacing the previous one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frame cannot be serialized.
+    pub fn publish(&self, frame: &ScoreFrame) -> serde_json::Result<()> {
+        let json: Arc<str> = serde_json::to_string(frame)?.into();
+        self.frames.send_replace(Some(json));
+        Ok(())
+    }
+}
+
+/// Websocket server streaming [`ScoreFrame`]s to overlay clients.
+#[derive(Debug)]
+pub struct OverlayServer {
+    listener: TcpListener,
+    frames: Arc<watch::Sender<Option<Arc<str>>>>,
+}
+
+impl OverlayServer {
+    /// Bind the overlay listener.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound.
+    pub async fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let (frames, _) = watch::channel(None);
+        Ok(Self {
+            listener,
+            frames: Arc::new(frames),
+        })
+    }
+
+    /// Address the server is listening on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket address cannot be read.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Get a handle for publishing frames.
+    #[must_use]
+    pub fn publisher(&self) -> OverlayPublisher {
+        OverlayPublisher {
+            frames: Arc::clone(&self.frames),
+        }
+    }
+
+    /// Accept clients until the task is dropped.
+    pub async fn serve(self) {
+        loop {
+            match self.listener.accept().await {
+                Ok((stream, peer)) => {
+                    let frames = self.frames.subscribe();
+                    tokio::spawn(async move {
+                        if let Err(e) = serve_client(stream, frames).await {
+                            tracing::debug!(%peer, error = %e, "Overlay client disconnected");
+                        }
+                    });
+                }
+                Err(e) => tracing::warn!(error = %e, "Overlay accept failed"),
+            }
+        }
+    }
+}
+
+/// Stream frames to one client until it closes or the publisher is gone.
+async fn serve_client(
+    stream: TcpStream,
+    mut frames: watch::Receiver<Option<Arc<str>>>,
+) -> Result<(), tokio_tungstenite::tungstenite::Error> {
+    let socket = tokio_tungstenite::accept_async(stream).await?;
+    let (mut sink, mut incoming) = socket.split();
+    tracing::debug!("Overlay client connected");
+
+    // Send whatever is current straight away, then every change
+    frames.mark_changed();
+    loop {
+        tokio::select! {
+            changed = frames.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+                let frame = frames.borrow_and_update().clone();
+                if let Some(json) = frame {
+                    sink.send(Message::Text(json.to_string())).await?;
+                }
+            }
+            message = incoming.next() => match message {
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e),
+            },
+        }
+    }
+    sink.close().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rts_core::components::{CombatStats, FactionMember};
+    use rts_core::math::{Fixed, Vec2Fixed};
+    use rts_core::simulation::EntitySpawnParams;
+
+    fn spawn_soldier(sim: &mut Simulation, faction: FactionId, x: i32) {
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(0))),
+            health: Some(100),
+            movement: Some(Fixed::from_num(1)),
+            combat_stats: Some(CombatStats::new(5, Fixed::from_num(10), 20)),
+            faction: Some(FactionMember::new(faction, 0)),
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_tracker_emits_one_frame_per_second() {
+        let mut sim = Simulation::new();
+        spawn_soldier(&mut sim, FactionId::Continuity, 0);
+        spawn_soldier(&mut sim, FactionId::Collegium, 2000);
+        let mut tracker = ScoreTracker::new();
+
+        let mut frames = Vec::new();
+        for _ in 0..TICK_RATE * 2 {
+            tracker.record_income(FactionId::Continuity, 1);
+            let events = sim.tick();
+            frames.extend(tracker.on_tick(&sim, &events));
+        }
+
+        assert_eq!(frames.len(), 2);
+        let continuity = &frames[1].factions[0];
+        assert_eq!(continuity.faction, FactionId::Continuity);
+        assert_eq!(continuity.supply, 1);
+        assert_eq!(continuity.army_value, 100);
+        assert_eq!(continuity.income, i64::from(TICK_RATE));
+        assert_eq!(frames[1].factions[1].income, 0);
+    }
+
+    #[tokio::test]
+    async fn test_overlay_streams_latest_frame_to_clients() {
+        let server = OverlayServer::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap();
+        let publisher = server.publisher();
+        tokio::spawn(server.serve());
+
+        let frame = ScoreFrame {
+            tick: 20,
+            second: 1,
+            factions: Vec::new(),
+            recent_fights: Vec::new(),
+        };
+        publisher.publish(&frame).unwrap();
+
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{addr}"))
+            .await
+            .unwrap();
+        let message = client.next().await.unwrap().unwrap();
+        let received: ScoreFrame = serde_json::from_str(message.to_text().unwrap()).unwrap();
+        assert_eq!(received, frame);
+    }
+}
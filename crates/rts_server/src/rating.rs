@@ -0,0 +1,440 @@
+//! Player ratings.
+//!
+//! Rated games update each identified player's [Glicko-2] rating: a
+//! rating, a deviation saying how sure the server is of it, and a
+//! volatility saying how erratic the player's results have been. New
+//! players start unsure, so their first games move them a long way;
+//! players with a long record move little on one upset.
+//!
+//! Every rated game is its own rating period. Each player is scored
+//! against every other player in it: the winner beats everyone, whoever
+//! forfeited loses to everyone, and anyone else draws. A game that ended
+//! in a draw counts as one for everybody, and games aborted by the server
+//! are not rated at all.
+//!
+//! The [`RatingStore`] keeps every rated player by [`PlayerId`]. Given a
+//! file, the server saves it after every rated game, so ratings outlive
+//! the process.
+//!
+//! [Glicko-2]: http://www.glicko.net/glicko/glicko2.pdf
+
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use rts_core::factions::FactionId;
+use rts_core::outcome::WinCondition;
+
+use crate::lobby::PlayerId;
+
+/// Rating new players start at.
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// Deviation new players start at, and the most a deviation grows to.
+pub const INITIAL_DEVIATION: f64 = 350.0;
+
+/// Volatility new players start at.
+pub const INITIAL_VOLATILITY: f64 = 0.06;
+
+/// How far volatility may change in one period. Smaller values suit games
+/// where upsets are rare.
+pub const VOLATILITY_CHANGE: f64 = 0.5;
+
+/// Glicko-2 works on a scale this many rating points to the unit.
+const SCALE: f64 = 173.7178;
+
+/// Precision the volatility iteration stops at.
+const CONVERGENCE: f64 = 0.000_001;
+
+/// One player's Glicko-2 rating.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rating {
+    /// Rating, 1500 for an average player.
+    pub rating: f64,
+    /// Rating deviation: the true rating is within twice this of
+    /// [`rating`](Self::rating) with 95% confidence.
+    pub deviation: f64,
+    /// How erratic the player's results are.
+    pub volatility: f64,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            rating: INITIAL_RATING,
+            deviation: INITIAL_DEVIATION,
+            volatility: INITIAL_VOLATILITY,
+        }
+    }
+}
+
+impl Rating {
+    /// The rating after one period against `results`: each opponent's
+    /// rating before the period and the score against them (1 for a win,
+    /// 0.5 for a draw, 0 for a loss). With no results only the deviation
+    /// grows.
+    #[must_use]
+    pub fn updated(&self, results: &[(Rating, f64)]) -> Self {
+        let mu = (self.rating - INITIAL_RATING) / SCALE;
+        let phi = self.deviation / SCALE;
+        if results.is_empty() {
+            let phi = phi.hypot(self.volatility);
+            return Self {
+                deviation: (phi * SCALE).min(INITIAL_DEVIATION),
+                ..*self
+            };
+        }
+
+        // Estimated variance of the rating from the results alone, and the
+        // improvement they suggest
+        let mut information = 0.0;
+        let mut surprise = 0.0;
+        for (opponent, score) in results {
+            let opponent_mu = (opponent.rating - INITIAL_RATING) / SCALE;
+            let g = weight(opponent.deviation / SCALE);
+            let expected = 1.0 / (1.0 + (-g * (mu - opponent_mu)).exp());
+            information += g * g * expected * (1.0 - expected);
+            surprise += g * (score - expected);
+        }
+        let variance = 1.0 / information;
+        let delta = variance * surprise;
+
+        let volatility = new_volatility(phi, self.volatility, variance, delta);
+        let phi_star = phi.hypot(volatility);
+        let phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / variance).sqrt();
+        let mu = mu + phi * phi * surprise;
+        Self {
+            rating: mu * SCALE + INITIAL_RATING,
+            deviation: (phi * SCALE).min(INITIAL_DEVIATION),
+            volatility,
+        }
+    }
+
+    /// Rounded, for showing to players.
+    #[must_use]
+    pub fn public(&self, games: u32) -> PublicRating {
+        PublicRating {
+            rating: self.rating.round() as i32,
+            deviation: self.deviation.round() as i32,
+            games,
+        }
+    }
+}
+
+/// How much a result against an opponent this unsure of counts.
+fn weight(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt()
+}
+
+/// The volatility after a period, found with the Illinois algorithm as
+/// the Glicko-2 paper describes.
+fn new_volatility(phi: f64, sigma: f64, variance: f64, delta: f64) -> f64 {
+    let a = (sigma * sigma).ln();
+    let tau = VOLATILITY_CHANGE;
+    let f = |x: f64| {
+        let ex = x.exp();
+        let denominator = phi * phi + variance + ex;
+        ex * (delta * delta - phi * phi - variance - ex) / (2.0 * denominator * denominator)
+            - (x - a) / (tau * tau)
+    };
+
+    let mut low = a;
+    let mut high = if delta * delta > phi * phi + variance {
+        (delta * delta - phi * phi - variance).ln()
+    } else {
+        let mut k = 1.0;
+        while f(a - k * tau) < 0.0 {
+            k += 1.0;
+        }
+        a - k * tau
+    };
+    let mut f_low = f(low);
+    let mut f_high = f(high);
+    while (high - low).abs() > CONVERGENCE {
+        let next = low + (low - high) * f_low / (f_high - f_low);
+        let f_next = f(next);
+        if f_next * f_high <= 0.0 {
+            low = high;
+            f_low = f_high;
+        } else {
+            f_low /= 2.0;
+        }
+        high = next;
+        f_high = f_next;
+    }
+    (low / 2.0).exp()
+}
+
+/// A rating as players see it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicRating {
+    /// Rating, rounded.
+    pub rating: i32,
+    /// Rating deviation, rounded.
+    pub deviation: i32,
+    /// Rated games played.
+    pub games: u32,
+}
+
+/// Score of a player on `faction` against one on `opponent`, or None if
+/// the game should not be rated.
+///
+/// Outcomes name factions, not players, so a mirror match (both on the
+/// same faction) cannot be scored and is not rated.
+#[must_use]
+pub fn score(outcome: &WinCondition, faction: FactionId, opponent: FactionId) -> Option<f64> {
+    if outcome.is_error() || faction == opponent {
+        return None;
+    }
+    let (better, worse) = match *outcome {
+        WinCondition::Forfeit { forfeiting_player } => {
+            (opponent == forfeiting_player, faction == forfeiting_player)
+        }
+        _ => match outcome.winner() {
+            Some(winner) => (faction == winner, opponent == winner),
+            None => (false, false),
+        },
+    };
+    Some(match (better, worse) {
+        (true, false) => 1.0,
+        (false, true) => 0.0,
+        _ => 0.5,
+    })
+}
+
+/// A rated player.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerRating {
+    /// Persistent ID.
+    pub id: PlayerId,
+    /// Name the player last played under.
+    pub name: String,
+    /// Current rating.
+    pub rating: Rating,
+    /// Rated games played.
+    pub games: u32,
+}
+
+impl PlayerRating {
+    /// The rating as players see it.
+    #[must_use]
+    pub fn public(&self) -> PublicRating {
+        self.rating.public(self.games)
+    }
+}
+
+/// Every rated player, optionally saved to a file.
+#[derive(Debug, Clone, Default)]
+pub struct RatingStore {
+    path: Option<PathBuf>,
+    players: BTreeMap<PlayerId, PlayerRating>,
+}
+
+impl RatingStore {
+    /// A store kept in memory only.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the store saved at `path`, or start an empty one there if the
+    /// file does not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let players = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice::<Vec<PlayerRating>>(&bytes)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            path: Some(path),
+            players: players.into_iter().map(|p| (p.id, p)).collect(),
+        })
+    }
+
+    /// File the store saves to, if any.
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// A player's record, if they have played a rated game.
+    #[must_use]
+    pub fn get(&self, id: PlayerId) -> Option<&PlayerRating> {
+        self.players.get(&id)
+    }
+
+    /// A player's rating as players see it, new players' included.
+    #[must_use]
+    pub fn public(&self, id: PlayerId) -> PublicRating {
+        self.get(id)
+            .map_or_else(|| Rating::default().public(0), PlayerRating::public)
+    }
+
+    /// Number of rated players.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.players.len()
+    }
+
+    /// Whether nobody has played a rated game.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    /// Rate a finished game between `players`, each with their name and
+    /// the faction they played, returning their new records. Returns
+    /// nothing if the outcome is not rated.
+    pub fn rate_game(
+        &mut self,
+        players: &[(PlayerId, String, FactionId)],
+        outcome: &WinCondition,
+    ) -> Vec<PlayerRating> {
+        let before: Vec<Rating> = players
+            .iter()
+            .map(|(id, _, _)| self.get(*id).map(|p| p.rating).unwrap_or_default())
+            .collect();
+        let mut rated = Vec::new();
+        for (i, (id, name, faction)) in players.iter().enumerate() {
+            let results: Option<Vec<(Rating, f64)>> = players
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(j, (_, _, opponent))| {
+                    Some((before[j], score(outcome, *faction, *opponent)?))
+                })
+                .collect();
+            let Some(results) = results.filter(|results| !results.is_empty()) else {
+                return Vec::new();
+            };
+            let games = self.get(*id).map_or(0, |p| p.games) + 1;
+            rated.push(PlayerRating {
+                id: *id,
+                name: name.clone(),
+                rating: before[i].updated(&results),
+                games,
+            });
+        }
+        for player in &rated {
+            self.players.insert(player.id, player.clone());
+        }
+        rated
+    }
+
+    /// Write the store to its file, if it has one, replacing the file
+    /// only once the new one is complete.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let players: Vec<&PlayerRating> = self.players.values().collect();
+        let json = serde_json::to_vec_pretty(&players)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let partial = path.with_extension("tmp");
+        fs::write(&partial, json)?;
+        fs::rename(&partial, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rating(rating: f64, deviation: f64) -> Rating {
+        Rating {
+            rating,
+            deviation,
+            volatility: INITIAL_VOLATILITY,
+        }
+    }
+
+    #[test]
+    fn test_glicko2_matches_the_worked_example() {
+        // The example from Glickman's paper
+        let player = rating(1500.0, 200.0);
+        let updated = player.updated(&[
+            (rating(1400.0, 30.0), 1.0),
+            (rating(1550.0, 100.0), 0.0),
+            (rating(1700.0, 300.0), 0.0),
+        ]);
+        assert!((updated.rating - 1464.06).abs() < 0.01, "{updated:?}");
+        assert!((updated.deviation - 151.52).abs() < 0.01, "{updated:?}");
+        assert!(
+            (updated.volatility - 0.05999).abs() < 0.00001,
+            "{updated:?}"
+        );
+
+        // Sitting out only makes the rating less certain
+        let idle = player.updated(&[]);
+        assert_eq!(idle.rating, player.rating);
+        assert!(idle.deviation > player.deviation);
+        assert_eq!(Rating::default().updated(&[]).deviation, INITIAL_DEVIATION);
+    }
+
+    #[test]
+    fn test_store_rates_games_and_survives_restarts() {
+        let dir = std::env::temp_dir().join(format!("rts_ratings_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ratings.json");
+        let _ = fs::remove_file(&path);
+
+        let mut store = RatingStore::load(&path).unwrap();
+        assert!(store.is_empty());
+        let (alice, bob) = (PlayerId(1), PlayerId(2));
+        let players = [
+            (alice, "alice".to_string(), FactionId::Continuity),
+            (bob, "bob".to_string(), FactionId::Collegium),
+        ];
+        let win = WinCondition::Elimination {
+            eliminating_player: FactionId::Continuity,
+            surviving_structures: 1,
+        };
+        let rated = store.rate_game(&players, &win);
+        assert_eq!(rated.len(), 2);
+        assert!(rated[0].rating.rating > INITIAL_RATING);
+        assert!(rated[1].rating.rating < INITIAL_RATING);
+        assert_eq!(store.public(alice).games, 1);
+        assert_eq!(store.public(PlayerId(3)), Rating::default().public(0));
+
+        // Forfeits lose, draws and aborts move nothing much or nothing
+        let forfeit = WinCondition::Forfeit {
+            forfeiting_player: FactionId::Collegium,
+        };
+        assert_eq!(
+            score(&forfeit, FactionId::Continuity, FactionId::Collegium),
+            Some(1.0)
+        );
+        assert_eq!(
+            score(
+                &WinCondition::TimeLimit,
+                FactionId::Continuity,
+                FactionId::Collegium
+            ),
+            Some(0.5)
+        );
+        assert!(store
+            .rate_game(&players, &WinCondition::TickTimeout)
+            .is_empty());
+        assert_eq!(store.public(alice).games, 1);
+
+        store.save().unwrap();
+        let reloaded = RatingStore::load(&path).unwrap();
+        assert_eq!(reloaded.get(alice), store.get(alice));
+        assert_eq!(reloaded.len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,3067 @@
+//! Hosting many lobbies and games in one process.
+//!
+//! The [`SessionManager`] owns every session on the server. A session
+//! starts as a lobby that players join, and becomes a [`HostedGame`] once
+//! started. Each running game ticks on its own task on the shared tokio
+//! runtime, so one slow match delays only itself, and records what it
+//! costs in [`SessionUsage`] so operators can see which matches are heavy.
+//!
+//! The number of open sessions is capped by
+//! [`ServerConfig::max_sessions`](crate::ServerConfig::max_sessions);
+//! finished games stop counting once [`SessionManager::reap`] collects them
+//! into the [`MatchHistory`].
+//!
+//! Competitive lobbies can run a [`MapVeto`] over the server's map pool
+//! before starting; the chosen map is carried into the hosted game and its
+//! match record.
+//!
+//! Clients join through [`SessionManager::handshake`], which refuses anyone
+//! whose engine or faction data differs from the session or the players
+//! already in it. The session's [`Fingerprint`] is fixed when the game
+//! starts and written into its checkpoints.
+//!
+//! Games started with [`SessionManager::start_lockstep`] have no tick task:
+//! a [`LockstepRelay`] steps them as players' commands and confirmations
+//! arrive, and every released turn goes out to the session's subscribers.
+//!
+//! When a lockstep game desyncs, the diverged players can
+//! [report their state](SessionManager::report_state) for a
+//! [`DesyncReport`], which is also logged, and
+//! [resync](SessionManager::resync) to the server's state.
+//!
+//! Players in a lobby choose a faction and starting position and mark
+//! themselves ready (see [`LobbyRoom`]). Once every player is ready the
+//! lobby counts down for
+//! [`ServerConfig::countdown_secs`](crate::ServerConfig::countdown_secs);
+//! any change cancels it. [`SessionManager::launch_due`] launches lobbies
+//! whose countdown has run out into a lockstep [`skirmish`] owned by the
+//! server. Every change is published to subscribers as a [`LobbyState`].
+//!
+//! Spectators join a lobby or a running game through
+//! [`SessionManager::spectate_handshake`], up to
+//! [`ServerConfig::max_spectators`](crate::ServerConfig::max_spectators).
+//! They subscribe to the same lockstep turns as the players, catch up from
+//! an unfogged [`SessionManager::spectator_snapshot`], and are refused if
+//! they try to submit or confirm turns.
+//!
+//! Every player admitted by a handshake is given a secret session token.
+//! A player whose connection drops keeps their seat in a running game and
+//! can [rejoin](SessionManager::rejoin) it with the token, catching up from
+//! the relay's latest checkpoint and the turns played since.
+//!
+//! Players can also [register](SessionManager::register) for an identity
+//! that outlasts the session (see [`PlayerRegistry`]). A hello presenting
+//! its token seats the player under their [`PlayerId`], which the lobby
+//! state, the launch, the match record and the saved replay (with
+//! [`ServerConfig::replay_dir`](crate::ServerConfig::replay_dir)) all
+//! carry, and which is logged with their session's events. No two players
+//! in a session share a name.
+//!
+//! Identified players can also [queue](SessionManager::enqueue) for a
+//! rated game. [`SessionManager::match_queued`] pairs them by rating (see
+//! [`MatchQueue`]) and opens a rated lobby for each pair, with seats
+//! reserved for the two players; a lobby its players don't join within
+//! [`MATCH_JOIN_TIMEOUT`] is [closed](SessionManager::expire_matches).
+//! When a rated game is reaped, every player's rating is updated (see
+//! [`crate::rating`]), saved, and published as [`SessionEvent::Rated`].
+//!
+//! Anyone in a session can [chat](SessionManager::chat) and
+//! [ping the map](SessionManager::ping), to everyone or to their team (see
+//! [`crate::chat`]). Chat is logged with the session and published to
+//! subscribers as a [`ChatEvent`] naming its recipients.
+//!
+//! Empty seats can be taken by [bots](crate::bots) the server plays
+//! itself, added one at a time with [`SessionManager::add_bot`] or to fill
+//! a lobby with [`SessionManager::fill_with_bots`]. Bots are ready at once
+//! and play their turns whenever the relay steps; a lobby with only bots
+//! left in it closes.
+//!
+//! Operators control sessions through the [admin console](crate::admin):
+//! running games can be [paused](SessionManager::set_paused),
+//! [terminated](SessionManager::terminate) or, when the server ticks them,
+//! [sped up or slowed down](SessionManager::set_tick_rate), and players
+//! [kicked](SessionManager::kick). A player kicked from a running game
+//! loses their token and their seat stands idle for the rest of it.
+//!
+//! Lockstep games adapt each player's input delay to their connection (see
+//! [`crate::delay`]) within the configured bounds. Players who hold up a
+//! game for
+//! [`drop_after_stalled_ticks`](crate::ServerConfig::drop_after_stalled_ticks)
+//! are [dropped](SessionManager::drop_stalled): their seat stands idle
+//! until they rejoin.
+
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::hash::BuildHasher;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+use rts_core::api::StateSnapshot;
+use rts_core::autosave::Autosave;
+use rts_core::factions::FactionId;
+use rts_core::fingerprint::Fingerprint;
+use rts_core::math::Vec2Fixed;
+use rts_core::outcome::WinCondition;
+use rts_core::rng::SimRng;
+use rts_core::simulation::Simulation;
+
+use crate::bots::{strategy_bot, IdleBot, LockstepBot};
+use crate::chat::{
+    check_text, ChatChannel, ChatContent, ChatError, ChatEvent, ChatMessage, MapPing, PingKind,
+};
+use crate::delay::DelayBounds;
+use crate::game::{skirmish, HostedGame};
+use crate::lobby::{
+    Captain, Identity, IdentityError, LobbyRoom, LobbySeat, MapPool, MapVeto, MatchHistory,
+    MatchQueue, MatchRecord, PlayerId, PlayerRegistry, QueuedPlayer, SeatError, VetoAction,
+    VetoError, VetoStep, STARTING_POSITIONS,
+};
+use crate::lockstep::{
+    DesyncReport, EntityCommand, LockstepError, LockstepEvent, LockstepRelay, Rejoin, Resync,
+    MAX_INPUT_LEAD,
+};
+use crate::network::{check_fingerprint, check_hello, ClientHello, HandshakeError, ServerReply};
+use crate::rating::{PublicRating, RatingStore};
+use crate::snapshot::{filtered_snapshot, Viewer};
+use crate::ServerConfig;
+
+/// Identifier of a session on this server.
+pub type SessionId = u64;
+
+/// Session events buffered per subscriber before it counts as lagging.
+const EVENT_BUFFER: usize = 1024;
+
+/// How long matched players have to join their rated lobby.
+pub const MATCH_JOIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Errors managing sessions.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SessionError {
+    /// The server already hosts as many sessions as it is allowed.
+    #[error("Server is at capacity ({0} sessions)")]
+    AtCapacity(usize),
+
+    /// No session with this ID.
+    #[error("Session not found: {0}")]
+    NotFound(SessionId),
+
+    /// The lobby has as many players as a game allows.
+    #[error("Session {id} is full ({max} players)")]
+    LobbyFull {
+        /// Session ID.
+        id: SessionId,
+        /// Player cap.
+        max: u8,
+    },
+
+    /// The session is no longer a lobby.
+    #[error("Session {0} has already started")]
+    AlreadyStarted(SessionId),
+
+    /// A veto needs two captains: the first two players to join.
+    #[error("Session {0} needs two players to run a map veto")]
+    NeedCaptains(SessionId),
+
+    /// Only captains take part in the veto.
+    #[error("{0} is not a captain")]
+    NotCaptain(String),
+
+    /// The game cannot start until the veto has produced a map.
+    #[error("Session {0} is still vetoing maps")]
+    VetoPending(SessionId),
+
+    /// The session has no veto running.
+    #[error("Session {0} has no map veto")]
+    NoVeto(SessionId),
+
+    /// A veto rule was broken.
+    #[error(transparent)]
+    Veto(#[from] VetoError),
+
+    /// A seat change was refused.
+    #[error(transparent)]
+    Seat(#[from] SeatError),
+
+    /// A client's handshake was refused.
+    #[error(transparent)]
+    Handshake(#[from] HandshakeError),
+
+    /// A lockstep game needs one faction per player.
+    #[error("{players} players but {factions} factions")]
+    SeatMismatch {
+        /// Players in the lobby.
+        players: usize,
+        /// Factions given.
+        factions: usize,
+    },
+
+    /// The session is not a running lockstep game.
+    #[error("Session {0} is not a lockstep game")]
+    NotLockstep(SessionId),
+
+    /// A lockstep request was refused.
+    #[error(transparent)]
+    Lockstep(#[from] LockstepError),
+
+    /// Game state could not be saved or restored.
+    #[error("Bad game state: {0}")]
+    BadState(String),
+
+    /// The session has as many spectators as it allows.
+    #[error("Session {id} has no spectator slots left ({max} spectators)")]
+    SpectatorsFull {
+        /// Session ID.
+        id: SessionId,
+        /// Spectator cap.
+        max: usize,
+    },
+
+    /// The name is already playing or spectating in the session.
+    #[error("{0} is already in the session")]
+    AlreadyJoined(String),
+
+    /// Spectators only watch.
+    #[error("{0} is spectating and cannot play")]
+    Spectator(String),
+
+    /// The session token does not belong to the player.
+    #[error("Invalid session token for {0}")]
+    BadToken(String),
+
+    /// A chat message was refused.
+    #[error(transparent)]
+    Chat(#[from] ChatError),
+
+    /// No bot plays a strategy by this name.
+    #[error("No bot plays strategy '{0}'")]
+    UnknownStrategy(String),
+
+    /// The session is not a game in progress.
+    #[error("Session {0} is not running")]
+    NotRunning(SessionId),
+
+    /// The game is paused by an operator.
+    #[error("Session {0} is paused")]
+    Paused(SessionId),
+
+    /// Lockstep games advance as their players' turns arrive, not on a
+    /// server clock.
+    #[error("Session {0} is paced by its players")]
+    PacedByPlayers(SessionId),
+
+    /// The name is not playing or spectating in the session.
+    #[error("{0} is not in the session")]
+    NotInSession(String),
+
+    /// A player's identity was refused.
+    #[error(transparent)]
+    Identity(#[from] IdentityError),
+
+    /// The player is already waiting for a rated game.
+    #[error("{0} is already queued")]
+    AlreadyQueued(String),
+
+    /// The lobby's seats are reserved for the players matched into it.
+    #[error("Session {id} is reserved for its matched players, not {player}")]
+    Reserved {
+        /// Session ID.
+        id: SessionId,
+        /// Player turned away.
+        player: String,
+    },
+}
+
+/// Where a session is in its life.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionState {
+    /// Waiting for players.
+    Lobby,
+    /// Game in progress.
+    Running,
+    /// Game decided; waiting to be reaped.
+    Finished,
+}
+
+/// Resources a session has used.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionUsage {
+    /// Ticks simulated.
+    pub ticks: u64,
+    /// Entities alive after the latest tick.
+    pub entities: usize,
+    /// Most entities alive at once.
+    pub peak_entities: usize,
+    /// Wall time spent simulating.
+    pub busy: Duration,
+    /// Slowest single tick.
+    pub slowest_tick: Duration,
+}
+
+impl SessionUsage {
+    /// Average wall time per tick.
+    #[must_use]
+    pub fn mean_tick(&self) -> Duration {
+        match u32::try_from(self.ticks) {
+            Ok(0) => Duration::ZERO,
+            Ok(ticks) => self.busy / ticks,
+            Err(_) => Duration::from_secs_f64(self.busy.as_secs_f64() / self.ticks as f64),
+        }
+    }
+
+    fn record_tick(&mut self, elapsed: Duration, entities: usize) {
+        self.ticks += 1;
+        self.busy += elapsed;
+        self.slowest_tick = self.slowest_tick.max(elapsed);
+        self.entities = entities;
+        self.peak_entities = self.peak_entities.max(entities);
+    }
+}
+
+/// What players in a lobby see: who is seated, their choices, and how
+/// long until launch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LobbyState {
+    /// Session ID.
+    pub session: SessionId,
+    /// Display name.
+    pub name: String,
+    /// Map chosen so far, if any.
+    pub map: Option<String>,
+    /// Seats, in join order.
+    pub seats: Vec<LobbySeat>,
+    /// Spectators, in the order they arrived.
+    #[serde(default)]
+    pub spectators: Vec<String>,
+    /// Milliseconds until launch, while counting down.
+    pub countdown_ms: Option<u64>,
+    /// Whether the game will be rated.
+    #[serde(default)]
+    pub rated: bool,
+}
+
+/// How a lobby's game was launched, so clients can build the same
+/// [`skirmish`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Launch {
+    /// Session ID.
+    pub session: SessionId,
+    /// Seed the map and simulation were generated from.
+    pub seed: u64,
+    /// Seats in player-index order, each with its starting position
+    /// filled in.
+    pub seats: Vec<LobbySeat>,
+    /// Ticks ahead players schedule their commands for, until told
+    /// otherwise.
+    #[serde(default)]
+    pub input_delay: u64,
+}
+
+/// Something everyone in a session needs to hear about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEvent {
+    /// The lobby changed.
+    Lobby(LobbyState),
+    /// The countdown ran out and the game started.
+    Launched(Launch),
+    /// A lockstep turn was released or the game desynced.
+    Lockstep(LockstepEvent),
+    /// Someone chatted or pinged the map.
+    Chat(ChatEvent),
+    /// An operator paused or resumed the game.
+    Paused(bool),
+    /// An operator removed `player` from the session, or everyone if None
+    /// because the session was terminated.
+    Kicked {
+        /// Player removed.
+        player: Option<String>,
+        /// Why, for the client to show.
+        reason: String,
+    },
+    /// A rated game ended; the players' new ratings, by name.
+    Rated(BTreeMap<String, PublicRating>),
+}
+
+/// Two queued players matched into a rated lobby.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueueMatch {
+    /// The lobby opened for them.
+    pub session: SessionId,
+    /// The players, in queue order.
+    pub players: Vec<QueuedPlayer>,
+}
+
+/// A snapshot of one session, for listings and monitoring.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    /// Session ID.
+    pub id: SessionId,
+    /// Display name.
+    pub name: String,
+    /// Lifecycle state.
+    pub state: SessionState,
+    /// Players in the session.
+    pub players: Vec<String>,
+    /// Spectators watching the session.
+    pub spectators: Vec<String>,
+    /// Map chosen for the game, if any.
+    pub map: Option<String>,
+    /// Whether an operator has paused the game.
+    pub paused: bool,
+    /// Resources used so far.
+    pub usage: SessionUsage,
+}
+
+#[derive(Debug)]
+struct Session {
+    name: String,
+    room: LobbyRoom,
+    /// When the lobby launches, once every player is ready.
+    countdown: Option<Instant>,
+    /// Fingerprints sent by players who joined through a handshake.
+    fingerprints: BTreeMap<String, Fingerprint>,
+    /// Session tokens of players who joined through a handshake.
+    tokens: BTreeMap<String, String>,
+    map: Option<String>,
+    veto: Option<MapVeto>,
+    game: Option<HostedGame>,
+    lockstep: Option<LockstepRelay>,
+    /// Seats the server plays itself, by player name.
+    bots: BTreeMap<String, Box<dyn LockstepBot>>,
+    /// Ticks per second, for games the server ticks itself.
+    tick_rate: u32,
+    paused: bool,
+    /// Whether the game counts towards ratings.
+    rated: bool,
+    /// Whether the game records a replay.
+    record_replay: bool,
+    /// Players matched into a rated lobby, the only ones who may join it.
+    reserved: BTreeMap<String, PlayerId>,
+    opened: Instant,
+    events: broadcast::Sender<SessionEvent>,
+    usage: SessionUsage,
+}
+
+impl Session {
+    /// The running game's fingerprint, or for a lobby the engine, the map
+    /// once chosen, and the faction data the first client brought.
+    fn fingerprint(&self) -> Fingerprint {
+        if let Some(game) = &self.game {
+            return game.fingerprint();
+        }
+        let mut fingerprint = Fingerprint::engine();
+        if let Some(map) = &self.map {
+            fingerprint = fingerprint.with_map_name(map);
+        }
+        fingerprint.data = self.fingerprints.values().find_map(|f| f.data);
+        fingerprint
+    }
+
+    fn state(&self) -> SessionState {
+        match &self.game {
+            None => SessionState::Lobby,
+            Some(game) if game.is_finished() => SessionState::Finished,
+            Some(_) => SessionState::Running,
+        }
+    }
+
+    fn players(&self) -> Vec<String> {
+        self.room.players().map(String::from).collect()
+    }
+
+    fn lobby_state(&self, id: SessionId) -> LobbyState {
+        LobbyState {
+            session: id,
+            name: self.name.clone(),
+            map: self.map.clone(),
+            seats: self.room.seats().to_vec(),
+            spectators: self.room.spectators().to_vec(),
+            countdown_ms: self.countdown.map(|launch| {
+                let left = launch.saturating_duration_since(Instant::now());
+                u64::try_from(left.as_millis()).unwrap_or(u64::MAX)
+            }),
+            rated: self.rated,
+        }
+    }
+
+    /// Start the countdown once everyone is ready and the map is settled,
+    /// cancel it otherwise, then tell the lobby.
+    fn lobby_changed(&mut self, id: SessionId, countdown: Duration) {
+        let ready = self.room.all_ready() && !(self.veto.is_some() && self.map.is_none());
+        match (ready, self.countdown) {
+            (true, None) => {
+                tracing::info!(session = id, "Everyone ready; counting down");
+                self.countdown = Some(Instant::now() + countdown);
+            }
+            (false, Some(_)) => {
+                tracing::info!(session = id, "Countdown cancelled");
+                self.countdown = None;
+            }
+            _ => {}
+        }
+        self.publish(SessionEvent::Lobby(self.lobby_state(id)));
+    }
+
+    /// Tell the session a spectator came or went. Lobbies run the usual
+    /// countdown check; running games just republish the state.
+    fn spectators_changed(&mut self, id: SessionId, countdown: Duration) {
+        if self.game.is_none() {
+            self.lobby_changed(id, countdown);
+        } else {
+            self.publish(SessionEvent::Lobby(self.lobby_state(id)));
+        }
+    }
+
+    /// The team `player` chats with: their faction, from the running game's
+    /// seats or else their lobby seat.
+    fn team_of(&self, player: &str) -> Option<FactionId> {
+        match &self.lockstep {
+            Some(relay) => relay.faction_of(player),
+            None => self
+                .room
+                .seats()
+                .iter()
+                .find(|seat| seat.player == player)
+                .and_then(|seat| seat.faction),
+        }
+    }
+
+    /// Who receives `from`'s message on `channel`: everyone, or for team
+    /// chat the players of their faction, or the spectators if they are
+    /// one. A player without a faction yet only hears themselves.
+    fn recipients(&self, from: &str, channel: ChatChannel) -> Option<Vec<String>> {
+        if channel == ChatChannel::All {
+            return None;
+        }
+        if self.room.is_spectator(from) {
+            return Some(self.room.spectators().to_vec());
+        }
+        let team = self.team_of(from);
+        Some(
+            self.players()
+                .into_iter()
+                .filter(|player| player == from || (team.is_some() && self.team_of(player) == team))
+                .collect(),
+        )
+    }
+
+    /// Have the server play `player`'s seat with an [`IdleBot`], so the
+    /// game goes on without them.
+    fn idle_seat(&mut self, id: SessionId, player: &str) {
+        self.bots.insert(player.to_string(), Box::new(IdleBot));
+        if !self.paused {
+            for event in drive_bots(id, self) {
+                self.publish(SessionEvent::Lockstep(event));
+            }
+        }
+    }
+
+    fn publish(&self, event: SessionEvent) {
+        // Nobody listening is fine; clients may all have dropped
+        let _ = self.events.send(event);
+    }
+}
+
+type SharedSession = Arc<Mutex<Session>>;
+
+/// Lock a session, recovering the data if a tick task panicked.
+fn lock(session: &SharedSession) -> MutexGuard<'_, Session> {
+    session
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Owns every lobby and game on the server.
+#[derive(Debug)]
+pub struct SessionManager {
+    max_sessions: usize,
+    max_players: u8,
+    max_spectators: usize,
+    tick_rate: u32,
+    input_delay: DelayBounds,
+    drop_after_stalled: Option<u64>,
+    countdown: Duration,
+    replay_dir: Option<PathBuf>,
+    map_pool: MapPool,
+    history: MatchHistory,
+    players: PlayerRegistry,
+    ratings: RatingStore,
+    queue: MatchQueue,
+    matches: broadcast::Sender<QueueMatch>,
+    next_id: SessionId,
+    sessions: BTreeMap<SessionId, SharedSession>,
+    tasks: BTreeMap<SessionId, JoinHandle<()>>,
+    stop: watch::Sender<bool>,
+}
+
+impl SessionManager {
+    /// Create a manager with the limits from `config`.
+    #[must_use]
+    pub fn new(config: &ServerConfig) -> Self {
+        let (stop, _) = watch::channel(false);
+        Self {
+            max_sessions: config.max_sessions,
+            max_players: config.max_players,
+            max_spectators: config.max_spectators,
+            tick_rate: config.tick_rate,
+            input_delay: DelayBounds::new(config.min_input_delay, config.max_input_delay),
+            drop_after_stalled: config.drop_after_stalled_ticks,
+            countdown: Duration::from_secs(config.countdown_secs),
+            replay_dir: config.replay_dir.clone(),
+            map_pool: MapPool::new(config.map_pool.iter().cloned()),
+            history: MatchHistory::new(),
+            players: PlayerRegistry::new(config.identity_secret.as_deref()),
+            ratings: RatingStore::new(),
+            queue: MatchQueue::new(),
+            matches: broadcast::channel(EVENT_BUFFER).0,
+            next_id: 1,
+            sessions: BTreeMap::new(),
+            tasks: BTreeMap::new(),
+            stop,
+        }
+    }
+
+    /// Open a new lobby.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SessionError::AtCapacity`] if the server is full.
+    pub fn open_lobby(&mut self, name: impl Into<String>) -> Result<SessionId, SessionError> {
+        if self.sessions.len() >= self.max_sessions {
+            return Err(SessionError::AtCapacity(self.max_sessions));
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        let name = name.into();
+        tracing::info!(session = id, %name, "Opened lobby");
+        self.sessions.insert(
+            id,
+            Arc::new(Mutex::new(Session {
+                name,
+                room: LobbyRoom::new(),
+                countdown: None,
+                fingerprints: BTreeMap::new(),
+                tokens: BTreeMap::new(),
+                map: None,
+                veto: None,
+                game: None,
+                lockstep: None,
+                bots: BTreeMap::new(),
+                tick_rate: self.tick_rate,
+                paused: false,
+                rated: false,
+                record_replay: self.replay_dir.is_some(),
+                reserved: BTreeMap::new(),
+                opened: Instant::now(),
+                events: broadcast::channel(EVENT_BUFFER).0,
+                usage: SessionUsage::default(),
+            })),
+        );
+        Ok(id)
+    }
+
+    /// Add a player to a lobby.
+    ///
+    /// A lobby holds at most
+    /// [`max_players`](crate::ServerConfig::max_players), and no more than
+    /// its map has [`STARTING_POSITIONS`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist, has started, is
+    /// full, or someone in it already goes by `player`.
+    pub fn join(&mut self, id: SessionId, player: impl Into<String>) -> Result<(), SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let mut session = lock(session);
+        if session.game.is_some() {
+            return Err(SessionError::AlreadyStarted(id));
+        }
+        let max = self.max_players.min(STARTING_POSITIONS);
+        if session.room.len() >= usize::from(max) {
+            return Err(SessionError::LobbyFull { id, max });
+        }
+        let player = player.into();
+        if session.room.position(&player).is_some() || session.room.is_spectator(&player) {
+            return Err(SessionError::AlreadyJoined(player));
+        }
+        if !session.reserved.is_empty() && !session.reserved.contains_key(&player) {
+            return Err(SessionError::Reserved { id, player });
+        }
+        session.room.seat(player);
+        session.lobby_changed(id, self.countdown);
+        Ok(())
+    }
+
+    /// Register a new player under `name`, returning their identity and
+    /// the token to put in future hellos.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SessionError::Identity`] if the name is unusable.
+    pub fn register(&mut self, name: &str) -> Result<(Identity, String), SessionError> {
+        Ok(self.players.register(name)?)
+    }
+
+    /// The identity a hello presents, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SessionError::Identity`] if the token does not verify or
+    /// was registered to a different name.
+    pub fn identify(&mut self, hello: &ClientHello) -> Result<Option<Identity>, SessionError> {
+        let Some(token) = &hello.identity else {
+            return Ok(None);
+        };
+        let identity = self.players.verify(token)?;
+        if identity.name != hello.player {
+            return Err(IdentityError::WrongName {
+                registered: identity.name,
+                given: hello.player.clone(),
+            }
+            .into());
+        }
+        Ok(Some(identity))
+    }
+
+    /// Players registered or identified since the server started.
+    #[must_use]
+    pub fn players(&self) -> &PlayerRegistry {
+        &self.players
+    }
+
+    /// Builder method to rate games into `ratings`, e.g. one loaded from
+    /// [`ServerConfig::ratings_file`](crate::ServerConfig::ratings_file).
+    #[must_use]
+    pub fn with_ratings(mut self, ratings: RatingStore) -> Self {
+        self.ratings = ratings;
+        self
+    }
+
+    /// Every rated player's rating.
+    #[must_use]
+    pub fn ratings(&self) -> &RatingStore {
+        &self.ratings
+    }
+
+    /// Players waiting for a rated game.
+    #[must_use]
+    pub fn queue(&self) -> &MatchQueue {
+        &self.queue
+    }
+
+    /// Queue an identified player for a rated game, returning their
+    /// rating and a receiver that hears every match made from the queue.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SessionError::AlreadyQueued`] if the player is already
+    /// waiting.
+    pub fn enqueue(
+        &mut self,
+        player: &str,
+        player_id: PlayerId,
+        now: Instant,
+    ) -> Result<(PublicRating, broadcast::Receiver<QueueMatch>), SessionError> {
+        let rating = self.ratings.public(player_id);
+        let queued = self.queue.join(QueuedPlayer {
+            player: player.to_string(),
+            player_id,
+            rating: rating.rating,
+            since: now,
+        });
+        if !queued {
+            return Err(SessionError::AlreadyQueued(player.to_string()));
+        }
+        tracing::info!(%player, %player_id, rating = rating.rating, "Queued for a rated game");
+        Ok((rating, self.matches.subscribe()))
+    }
+
+    /// Take a player out of the queue, returning whether they were in it.
+    pub fn dequeue(&mut self, player_id: PlayerId) -> bool {
+        self.queue.leave(player_id)
+    }
+
+    /// Pair queued players by rating, opening a rated lobby reserved for
+    /// each pair and announcing it to the queue's receivers. Returns the
+    /// lobbies opened.
+    ///
+    /// Matched lobbies draw a map from the pool when it has one. Pairs
+    /// that find the server full go back to the front of the queue.
+    pub fn match_queued(&mut self, now: Instant, rng: &mut SimRng) -> Vec<SessionId> {
+        let mut opened = Vec::new();
+        let mut unplaced = Vec::new();
+        for (first, second) in self.queue.pair(now) {
+            let name = format!("ranked: {} vs {}", first.player, second.player);
+            let Ok(id) = self.open_lobby(name) else {
+                unplaced.extend([first, second]);
+                continue;
+            };
+            let map = self.random_map(rng);
+            if let Some(session) = self.sessions.get(&id) {
+                let mut session = lock(session);
+                session.rated = true;
+                session.map = map;
+                for queued in [&first, &second] {
+                    session
+                        .reserved
+                        .insert(queued.player.clone(), queued.player_id);
+                }
+            }
+            tracing::info!(
+                session = id,
+                players = ?[&first.player, &second.player],
+                ratings = ?[first.rating, second.rating],
+                "Matched rated game"
+            );
+            // Nobody waiting on the queue any more is fine
+            let _ = self.matches.send(QueueMatch {
+                session: id,
+                players: vec![first, second],
+            });
+            opened.push(id);
+        }
+        if !unplaced.is_empty() {
+            let mut queue = MatchQueue::new();
+            for queued in unplaced.into_iter().chain(self.queue.waiting().to_vec()) {
+                queue.join(queued);
+            }
+            self.queue = queue;
+        }
+        opened
+    }
+
+    /// Close matched lobbies whose players did not all join within
+    /// [`MATCH_JOIN_TIMEOUT`] of the match, returning their IDs.
+    ///
+    /// Players who did join are told why and can queue again.
+    pub fn expire_matches(&mut self, now: Instant) -> Vec<SessionId> {
+        let expired: Vec<SessionId> = self
+            .sessions
+            .iter()
+            .filter(|(_, session)| {
+                let session = lock(session);
+                !session.reserved.is_empty()
+                    && session.game.is_none()
+                    && session.room.len() < session.reserved.len()
+                    && now.saturating_duration_since(session.opened) >= MATCH_JOIN_TIMEOUT
+            })
+            .map(|(&id, _)| id)
+            .collect();
+        for &id in &expired {
+            let _ = self.terminate(id, "a matched player did not join");
+        }
+        expired
+    }
+
+    /// Admit a connecting client to a lobby.
+    ///
+    /// The hello must speak this protocol and match the session's
+    /// fingerprint and every player already admitted; the welcome carries
+    /// the session fingerprint back so the client can check it too, and
+    /// the token to [`rejoin`](Self::rejoin) with. A hello presenting an
+    /// identity seats the player under its [`PlayerId`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SessionError::Handshake`] on a mismatch, an error if the
+    /// identity is refused, or any error [`join`](Self::join) returns.
+    pub fn handshake(
+        &mut self,
+        id: SessionId,
+        hello: &ClientHello,
+    ) -> Result<ServerReply, SessionError> {
+        let fingerprint = {
+            let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+            let session = lock(session);
+            let fingerprint = session.fingerprint();
+            check_hello(&fingerprint, hello)?;
+            for peer in session.fingerprints.values() {
+                check_fingerprint(peer, &hello.fingerprint)?;
+            }
+            fingerprint
+        };
+        let player_id = self.identify(hello)?.map(|identity| identity.id);
+        if let Some(session) = self.sessions.get(&id) {
+            // Matched seats take the matched identity, not just the name
+            let session = lock(session);
+            let reserved = session.reserved.get(&hello.player);
+            if !session.reserved.is_empty() && reserved != player_id.as_ref() {
+                return Err(SessionError::Reserved {
+                    id,
+                    player: hello.player.clone(),
+                });
+            }
+        }
+        self.join(id, hello.player.clone())?;
+        let token = session_token(id, &hello.player);
+        if let Some(session) = self.sessions.get(&id) {
+            let mut session = lock(session);
+            session
+                .fingerprints
+                .insert(hello.player.clone(), hello.fingerprint);
+            session.tokens.insert(hello.player.clone(), token.clone());
+            if let Some(player_id) = player_id {
+                let rating = self.ratings.public(player_id);
+                session.room.identify(&hello.player, player_id, rating)?;
+                session.lobby_changed(id, self.countdown);
+            }
+        }
+        tracing::info!(
+            session = id,
+            player = %hello.player,
+            player_id = player_id.map(|id| id.to_string()),
+            %fingerprint,
+            "Handshake accepted"
+        );
+        Ok(ServerReply::Welcome {
+            session: id,
+            fingerprint,
+            token: Some(token),
+        })
+    }
+
+    /// Remove a player from a lobby, e.g. when their connection drops.
+    ///
+    /// A running veto is abandoned if a captain leaves, and a lobby left
+    /// empty, or with only bots, is closed, freeing its slot. Spectators can leave at any time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist, or has started and
+    /// `player` is playing in it.
+    pub fn leave(&mut self, id: SessionId, player: &str) -> Result<(), SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let empty = {
+            let mut session = lock(session);
+            if session.room.stop_spectating(player) {
+                session.fingerprints.remove(player);
+                session.spectators_changed(id, self.countdown);
+                return Ok(());
+            }
+            if session.game.is_some() {
+                return Err(SessionError::AlreadyStarted(id));
+            }
+            if let Some(index) = session.room.unseat(player) {
+                if index < 2 && session.veto.take().is_some() {
+                    tracing::info!(session = id, %player, "Captain left; veto abandoned");
+                }
+            }
+            session.fingerprints.remove(player);
+            session.tokens.remove(player);
+            session.bots.remove(player);
+            session.lobby_changed(id, self.countdown);
+            let bots = &session.bots;
+            let only_bots = session
+                .room
+                .players()
+                .all(|player| bots.contains_key(player));
+            only_bots
+        };
+        if empty {
+            self.sessions.remove(&id);
+            tracing::info!(session = id, "Closed empty lobby");
+        }
+        Ok(())
+    }
+
+    /// Add a spectator to a lobby or running game.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist, has no spectator
+    /// slots left, or `name` is already in it.
+    pub fn spectate(&mut self, id: SessionId, name: impl Into<String>) -> Result<(), SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let mut session = lock(session);
+        if session.room.spectators().len() >= self.max_spectators {
+            return Err(SessionError::SpectatorsFull {
+                id,
+                max: self.max_spectators,
+            });
+        }
+        let name = name.into();
+        if !session.room.spectate(name.clone()) {
+            return Err(SessionError::AlreadyJoined(name));
+        }
+        tracing::info!(session = id, spectator = %name, "Spectator joined");
+        session.spectators_changed(id, self.countdown);
+        Ok(())
+    }
+
+    /// Admit a connecting client as a spectator.
+    ///
+    /// The hello is checked against the session's fingerprint as for
+    /// players, since a spectator replays the same turns.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SessionError::Handshake`] on a mismatch, or any error
+    /// [`spectate`](Self::spectate) returns.
+    pub fn spectate_handshake(
+        &mut self,
+        id: SessionId,
+        hello: &ClientHello,
+    ) -> Result<ServerReply, SessionError> {
+        let fingerprint = self.fingerprint(id)?;
+        check_hello(&fingerprint, hello)?;
+        self.spectate(id, hello.player.clone())?;
+        Ok(ServerReply::Welcome {
+            session: id,
+            fingerprint,
+            token: None,
+        })
+    }
+
+    /// Unfogged snapshot of a running game for one of its spectators to
+    /// catch up from. Returns None for lobbies.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist or `name` is not
+    /// spectating it.
+    pub fn spectator_snapshot(
+        &self,
+        id: SessionId,
+        name: &str,
+    ) -> Result<Option<StateSnapshot>, SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        if !lock(session).room.is_spectator(name) {
+            return Err(SeatError::UnknownPlayer(name.to_string()).into());
+        }
+        self.snapshot_for(id, &Viewer::Omniscient)
+    }
+
+    /// The session's fingerprint, as sent to connecting clients.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SessionError::NotFound`] if the session does not exist.
+    pub fn fingerprint(&self, id: SessionId) -> Result<Fingerprint, SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let fingerprint = lock(session).fingerprint();
+        Ok(fingerprint)
+    }
+
+    /// Begin a map veto between the lobby's first two players.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist, has started, has
+    /// fewer than two players, or `steps` does not suit the map pool.
+    pub fn begin_veto(&mut self, id: SessionId, steps: Vec<VetoStep>) -> Result<(), SessionError> {
+        let veto = MapVeto::new(&self.map_pool, steps)?;
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let mut session = lock(session);
+        if session.game.is_some() {
+            return Err(SessionError::AlreadyStarted(id));
+        }
+        if session.room.len() < 2 {
+            return Err(SessionError::NeedCaptains(id));
+        }
+        session.map = veto.chosen().map(String::from);
+        session.veto = Some(veto);
+        session.lobby_changed(id, self.countdown);
+        Ok(())
+    }
+
+    /// Take a captain's veto turn, returning the map once it is decided.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session has no veto, `player` is not a
+    /// captain, or the turn breaks the veto rules.
+    pub fn veto(
+        &mut self,
+        id: SessionId,
+        player: &str,
+        action: VetoAction,
+        map: &str,
+    ) -> Result<Option<String>, SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let mut session = lock(session);
+        let captain = match session.room.position(player) {
+            Some(0) => Captain::First,
+            Some(1) => Captain::Second,
+            _ => return Err(SessionError::NotCaptain(player.to_string())),
+        };
+        let veto = session.veto.as_mut().ok_or(SessionError::NoVeto(id))?;
+        let chosen = veto.act(captain, action, map)?.map(String::from);
+        if let Some(map) = &chosen {
+            tracing::info!(session = id, %map, "Map chosen by veto");
+            session.map = Some(map.clone());
+        }
+        session.lobby_changed(id, self.countdown);
+        Ok(chosen)
+    }
+
+    /// Set the map for a lobby directly, e.g. from the matchmaking queue.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist or has started.
+    pub fn set_map(&mut self, id: SessionId, map: impl Into<String>) -> Result<(), SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let mut session = lock(session);
+        if session.game.is_some() {
+            return Err(SessionError::AlreadyStarted(id));
+        }
+        session.map = Some(map.into());
+        session.lobby_changed(id, self.countdown);
+        Ok(())
+    }
+
+    /// Seat a bot named `name` in a lobby, playing `faction`, and mark it
+    /// ready.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SessionError::AlreadyJoined`] if the name is taken, or
+    /// any error [`join`](Self::join) returns.
+    pub fn add_bot(
+        &mut self,
+        id: SessionId,
+        name: &str,
+        faction: FactionId,
+        bot: Box<dyn LockstepBot>,
+    ) -> Result<(), SessionError> {
+        {
+            let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+            let session = lock(session);
+            if session.room.position(name).is_some() || session.room.is_spectator(name) {
+                return Err(SessionError::AlreadyJoined(name.to_string()));
+            }
+        }
+        self.join(id, name)?;
+        self.update_room(id, |room| {
+            room.choose_faction(name, faction)?;
+            room.set_ready(name, true)
+        })?;
+        if let Some(session) = self.sessions.get(&id) {
+            lock(session).bots.insert(name.to_string(), bot);
+        }
+        tracing::info!(session = id, bot = %name, ?faction, "Bot seated");
+        Ok(())
+    }
+
+    /// Seat a bot playing the strategy preset `strategy` (e.g. `"rush"`),
+    /// returning the name it was given.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SessionError::UnknownStrategy`] if no bot plays the
+    /// strategy, or any error [`add_bot`](Self::add_bot) returns.
+    pub fn add_strategy_bot(
+        &mut self,
+        id: SessionId,
+        strategy: &str,
+        faction: FactionId,
+    ) -> Result<String, SessionError> {
+        let bot = strategy_bot(strategy)
+            .ok_or_else(|| SessionError::UnknownStrategy(strategy.to_string()))?;
+        let name = {
+            let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+            let session = lock(session);
+            let taken = |name: &str| {
+                session.room.position(name).is_some() || session.room.is_spectator(name)
+            };
+            (1..)
+                .map(|n| match n {
+                    1 => format!("{strategy} bot"),
+                    n => format!("{strategy} bot {n}"),
+                })
+                .find(|name| !taken(name))
+                .unwrap_or_default()
+        };
+        self.add_bot(id, &name, faction, bot)?;
+        Ok(name)
+    }
+
+    /// Fill a lobby's empty seats with bots playing `strategy`, returning
+    /// their names. Each takes a faction nobody else in the lobby plays,
+    /// while there are any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist or has started, or
+    /// [`SessionError::UnknownStrategy`].
+    pub fn fill_with_bots(
+        &mut self,
+        id: SessionId,
+        strategy: &str,
+    ) -> Result<Vec<String>, SessionError> {
+        let max = usize::from(self.max_players.min(STARTING_POSITIONS));
+        let mut added = Vec::new();
+        loop {
+            let taken: Vec<FactionId> = {
+                let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+                let session = lock(session);
+                if session.game.is_some() {
+                    return Err(SessionError::AlreadyStarted(id));
+                }
+                if session.room.len() >= max {
+                    return Ok(added);
+                }
+                session
+                    .room
+                    .seats()
+                    .iter()
+                    .filter_map(|s| s.faction)
+                    .collect()
+            };
+            let faction = FactionId::ALL
+                .into_iter()
+                .find(|faction| !taken.contains(faction))
+                .unwrap_or(FactionId::ALL[added.len() % FactionId::ALL.len()]);
+            added.push(self.add_strategy_bot(id, strategy, faction)?);
+        }
+    }
+
+    /// Choose a player's faction. Clears their ready flag.
+    ///
+    /// Rated games score players by the faction that won, so in a rated
+    /// lobby each faction can only be played once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist, has started, or
+    /// the player is not in it, or if the lobby is rated and another
+    /// player already plays the faction.
+    pub fn choose_faction(
+        &mut self,
+        id: SessionId,
+        player: &str,
+        faction: FactionId,
+    ) -> Result<(), SessionError> {
+        let rated = self.sessions.get(&id).is_some_and(|s| lock(s).rated);
+        self.update_room(id, |room| {
+            if rated {
+                room.choose_unique_faction(player, faction)
+            } else {
+                room.choose_faction(player, faction)
+            }
+        })
+    }
+
+    /// Choose a player's starting position, or None to take whichever is
+    /// left at launch. Clears their ready flag.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist, has started, or
+    /// the position is unavailable.
+    pub fn choose_start(
+        &mut self,
+        id: SessionId,
+        player: &str,
+        start: Option<u8>,
+    ) -> Result<(), SessionError> {
+        self.update_room(id, |room| room.choose_start(player, start))
+    }
+
+    /// Mark a player ready or not. The countdown starts when every player
+    /// is ready.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist, has started, or
+    /// the player readies without a faction.
+    pub fn set_ready(
+        &mut self,
+        id: SessionId,
+        player: &str,
+        ready: bool,
+    ) -> Result<(), SessionError> {
+        self.update_room(id, |room| room.set_ready(player, ready))
+    }
+
+    /// The lobby as its players see it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SessionError::NotFound`] if the session does not exist.
+    pub fn lobby_state(&self, id: SessionId) -> Result<LobbyState, SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let state = lock(session).lobby_state(id);
+        Ok(state)
+    }
+
+    /// Launch every lobby whose countdown ran out by `now`, returning
+    /// their IDs.
+    ///
+    /// Each becomes a lockstep [`skirmish`] seeded by its session ID. A
+    /// lobby that cannot start, e.g. because a fingerprint no longer
+    /// matches, has its countdown cancelled instead.
+    pub fn launch_due(&mut self, now: Instant) -> Vec<SessionId> {
+        let mut launched = Vec::new();
+        for (&id, session) in &self.sessions {
+            let mut session = lock(session);
+            if session.game.is_some() || session.countdown.map_or(true, |launch| launch > now) {
+                continue;
+            }
+            match launch(id, &mut session, self.input_delay) {
+                Ok(launch) => {
+                    session.publish(SessionEvent::Launched(launch));
+                    for event in drive_bots(id, &mut session) {
+                        session.publish(SessionEvent::Lockstep(event));
+                    }
+                    launched.push(id);
+                }
+                Err(e) => {
+                    tracing::warn!(session = id, error = %e, "Launch failed");
+                    session.countdown = None;
+                    session.lobby_changed(id, self.countdown);
+                }
+            }
+        }
+        launched
+    }
+
+    /// Apply a seat change to a lobby and tell its players.
+    fn update_room(
+        &mut self,
+        id: SessionId,
+        change: impl FnOnce(&mut LobbyRoom) -> Result<(), SeatError>,
+    ) -> Result<(), SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let mut session = lock(session);
+        if session.game.is_some() {
+            return Err(SessionError::AlreadyStarted(id));
+        }
+        change(&mut session.room)?;
+        session.lobby_changed(id, self.countdown);
+        Ok(())
+    }
+
+    /// Draw a map for a matchmade game, weighted by past pick rates (see
+    /// [`MapPool::weighted_choice`]).
+    pub fn random_map(&self, rng: &mut SimRng) -> Option<String> {
+        self.map_pool
+            .weighted_choice(&self.history, rng)
+            .map(String::from)
+    }
+
+    /// The server's map pool.
+    #[must_use]
+    pub fn map_pool(&self) -> &MapPool {
+        &self.map_pool
+    }
+
+    /// Games finished on this server.
+    #[must_use]
+    pub fn history(&self) -> &MatchHistory {
+        &self.history
+    }
+
+    /// Start a lobby's game and its tick loop.
+    ///
+    /// Must be called from within the tokio runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist, has started, is
+    /// still vetoing maps, or a player's fingerprint no longer matches the
+    /// chosen map.
+    pub fn start(&mut self, id: SessionId, sim: Simulation) -> Result<(), SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        begin_game(id, &mut lock(session), sim)?;
+
+        let task = tokio::spawn(run_session(id, Arc::clone(session), self.stop.subscribe()));
+        self.tasks.insert(id, task);
+        Ok(())
+    }
+
+    /// Start a lobby's game in lockstep, seating each player with the
+    /// faction at the same position in `factions`.
+    ///
+    /// The game advances only through
+    /// [`submit_commands`](Self::submit_commands) and
+    /// [`confirm_tick`](Self::confirm_tick).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SessionError::SeatMismatch`] if `factions` does not have
+    /// one entry per player, or any error [`start`](Self::start) returns.
+    pub fn start_lockstep(
+        &mut self,
+        id: SessionId,
+        sim: Simulation,
+        factions: &[FactionId],
+    ) -> Result<(), SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let mut session = lock(session);
+        begin_lockstep(id, &mut session, sim, factions, self.input_delay)?;
+        for event in drive_bots(id, &mut session) {
+            session.publish(SessionEvent::Lockstep(event));
+        }
+        Ok(())
+    }
+
+    /// Submit a player's commands for a lockstep tick.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session is not a running lockstep game or
+    /// the relay refuses the commands.
+    pub fn submit_commands(
+        &mut self,
+        id: SessionId,
+        player: &str,
+        tick: u64,
+        commands: Vec<EntityCommand>,
+    ) -> Result<(), SessionError> {
+        self.refuse_non_player(id, player)?;
+        self.step_lockstep(id, |relay, game| relay.submit(game, player, tick, commands))
+    }
+
+    /// Confirm the state hash a player reached after a lockstep tick.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session is not a running lockstep game or
+    /// the tick is not awaiting confirmation.
+    pub fn confirm_tick(
+        &mut self,
+        id: SessionId,
+        player: &str,
+        tick: u64,
+        state_hash: u64,
+    ) -> Result<(), SessionError> {
+        self.refuse_non_player(id, player)?;
+        self.step_lockstep(id, |relay, game| {
+            relay.confirm(game, player, tick, state_hash)
+        })
+    }
+
+    /// Compare a player's uploaded state with the server's and log what
+    /// differs.
+    ///
+    /// `state` is the player's game as [`Autosave`] bytes, after playing
+    /// every turn it received.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session is not a running lockstep game, the
+    /// player has no seat, or the state does not restore.
+    pub fn report_state(
+        &self,
+        id: SessionId,
+        player: &str,
+        state: &[u8],
+    ) -> Result<DesyncReport, SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let session = lock(session);
+        let (Some(relay), Some(game)) = (session.lockstep.as_ref(), session.game.as_ref()) else {
+            return Err(SessionError::NotLockstep(id));
+        };
+        if !relay.is_seated(player) {
+            return Err(LockstepError::UnknownPlayer(player.to_string()).into());
+        }
+        let remote = Autosave::from_bytes(state)
+            .and_then(|save| save.restore_simulation())
+            .map_err(|e| SessionError::BadState(e.to_string()))?;
+        let report = DesyncReport {
+            player: player.to_string(),
+            desync_tick: relay.desync().map(|desync| desync.tick),
+            compared_at: game.simulation().get_tick(),
+            differences: game.simulation().diff(&remote),
+        };
+        tracing::warn!(
+            session = id,
+            %player,
+            desync_tick = ?report.desync_tick,
+            compared_at = report.compared_at,
+            differences = report.differences.len(),
+            "Desync report"
+        );
+        for difference in &report.differences {
+            tracing::warn!(
+                session = id,
+                %player,
+                entity = ?difference.entity,
+                field = %difference.field,
+                server = ?difference.local,
+                client = ?difference.remote,
+                "State differs"
+            );
+        }
+        Ok(report)
+    }
+
+    /// The server's state of a lockstep game, for a desynced player to
+    /// load. The player then confirms it with
+    /// [`resynced`](Self::resynced).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session is not a running lockstep game, the
+    /// player has no seat, or the state cannot be saved.
+    pub fn resync(&self, id: SessionId, player: &str) -> Result<Resync, SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let session = lock(session);
+        let (Some(relay), Some(game)) = (session.lockstep.as_ref(), session.game.as_ref()) else {
+            return Err(SessionError::NotLockstep(id));
+        };
+        if !relay.is_seated(player) {
+            return Err(LockstepError::UnknownPlayer(player.to_string()).into());
+        }
+        let resync = Resync::capture(game).map_err(|e| SessionError::BadState(e.to_string()))?;
+        tracing::info!(session = id, %player, tick = resync.tick, "Resyncing player");
+        Ok(resync)
+    }
+
+    /// Confirm a player loaded the state from [`resync`](Self::resync);
+    /// play resumes once every desynced player has.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session is not a running lockstep game or
+    /// the relay refuses the confirmation.
+    pub fn resynced(
+        &mut self,
+        id: SessionId,
+        player: &str,
+        tick: u64,
+        state_hash: u64,
+    ) -> Result<(), SessionError> {
+        self.step_lockstep(id, |relay, game| {
+            relay.resynced(game, player, tick, state_hash)
+        })
+    }
+
+    /// Send a chat message from a player or spectator.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist, `from` is not in it,
+    /// or the text is blank or too long.
+    pub fn chat(
+        &mut self,
+        id: SessionId,
+        from: &str,
+        channel: ChatChannel,
+        text: &str,
+    ) -> Result<(), SessionError> {
+        let text = check_text(text)?;
+        self.relay_chat(
+            id,
+            from,
+            channel,
+            ChatContent::Message(ChatMessage {
+                from: from.to_string(),
+                channel,
+                text: text.to_string(),
+            }),
+        )
+    }
+
+    /// Ping a spot on the map for a player's team or everyone.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist or `from` is not in
+    /// it.
+    pub fn ping(
+        &mut self,
+        id: SessionId,
+        from: &str,
+        channel: ChatChannel,
+        position: Vec2Fixed,
+        kind: PingKind,
+    ) -> Result<(), SessionError> {
+        self.relay_chat(
+            id,
+            from,
+            channel,
+            ChatContent::Ping(MapPing {
+                from: from.to_string(),
+                channel,
+                position,
+                kind,
+            }),
+        )
+    }
+
+    /// Log chat traffic and publish it to its recipients.
+    fn relay_chat(
+        &mut self,
+        id: SessionId,
+        from: &str,
+        channel: ChatChannel,
+        content: ChatContent,
+    ) -> Result<(), SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let session = lock(session);
+        if !session.room.players().any(|player| player == from) && !session.room.is_spectator(from)
+        {
+            return Err(SeatError::UnknownPlayer(from.to_string()).into());
+        }
+        let tick = session
+            .game
+            .as_ref()
+            .map(|game| game.simulation().get_tick());
+        match &content {
+            ChatContent::Message(message) => {
+                tracing::info!(session = id, ?tick, %from, ?channel, text = %message.text, "Chat");
+            }
+            ChatContent::Ping(ping) => {
+                tracing::info!(session = id, ?tick, %from, ?channel, kind = ?ping.kind, position = ?ping.position, "Map ping");
+            }
+        }
+        let to = session.recipients(from, channel);
+        session.publish(SessionEvent::Chat(ChatEvent { to, content }));
+        Ok(())
+    }
+
+    /// Let a player back into their running lockstep game after their
+    /// connection dropped.
+    ///
+    /// The hello must match the session's fingerprint and `token` must be
+    /// the one the player was welcomed with. The client loads the
+    /// [`Rejoin`] checkpoint, [fast-forwards](Rejoin::fast_forward) through
+    /// its turns and submits again from [`Rejoin::next_tick`]; subscribing
+    /// before releasing the session lock means no turn is missed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SessionError::BadToken`] if the token is wrong, or an
+    /// error if the session is not a running lockstep game or the relay
+    /// refuses the player.
+    pub fn rejoin(
+        &mut self,
+        id: SessionId,
+        hello: &ClientHello,
+        token: &str,
+    ) -> Result<Rejoin, SessionError> {
+        {
+            let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+            let mut session = lock(session);
+            check_hello(&session.fingerprint(), hello)?;
+            if session.tokens.get(&hello.player).map(String::as_str) != Some(token) {
+                tracing::warn!(session = id, player = %hello.player, "Refused rejoin");
+                return Err(SessionError::BadToken(hello.player.clone()));
+            }
+            // A dropped player takes their seat back from its stand-in
+            session.bots.remove(&hello.player);
+        }
+        let mut rejoin = None;
+        self.step_lockstep(id, |relay, game| {
+            let (caught_up, events) = relay.rejoin(game, &hello.player)?;
+            rejoin = Some(caught_up);
+            Ok(events)
+        })?;
+        rejoin.ok_or(SessionError::NotLockstep(id))
+    }
+
+    /// Receive the session's lobby changes, launch, lockstep turns and
+    /// desyncs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SessionError::NotFound`] if the session does not exist.
+    pub fn subscribe(
+        &self,
+        id: SessionId,
+    ) -> Result<broadcast::Receiver<SessionEvent>, SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let events = lock(session).events.subscribe();
+        Ok(events)
+    }
+
+    /// Turn away lockstep traffic from a spectator, or for a seat the
+    /// server plays, such as a kicked player's.
+    fn refuse_non_player(&self, id: SessionId, player: &str) -> Result<(), SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let session = lock(session);
+        if session.room.is_spectator(player) {
+            return Err(SessionError::Spectator(player.to_string()));
+        }
+        if session.bots.contains_key(player) {
+            return Err(SessionError::NotInSession(player.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Run one relay request and publish the events it produced.
+    fn step_lockstep(
+        &mut self,
+        id: SessionId,
+        step: impl FnOnce(
+            &mut LockstepRelay,
+            &mut HostedGame,
+        ) -> Result<Vec<LockstepEvent>, LockstepError>,
+    ) -> Result<(), SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let mut session = lock(session);
+        let session = &mut *session;
+        let (Some(relay), Some(game)) = (session.lockstep.as_mut(), session.game.as_mut()) else {
+            return Err(SessionError::NotLockstep(id));
+        };
+        if session.paused {
+            return Err(SessionError::Paused(id));
+        }
+
+        let start = Instant::now();
+        let mut events = step(relay, game)?;
+        events.extend(drive_bots(id, session));
+        let Some(game) = session.game.as_ref() else {
+            return Err(SessionError::NotLockstep(id));
+        };
+        let elapsed = start.elapsed();
+        let entities = game.simulation().entities().len();
+        let turns = events
+            .iter()
+            .filter(|event| matches!(event, LockstepEvent::Turn(_)))
+            .count();
+        for _ in 0..turns {
+            session.usage.record_tick(elapsed / turns as u32, entities);
+        }
+        if turns > 0 && game.is_finished() {
+            tracing::info!(session = id, win_condition = ?game.win_condition(), "Game decided");
+        }
+        for event in events {
+            session.publish(SessionEvent::Lockstep(event));
+        }
+        Ok(())
+    }
+
+    /// Pause or resume a running game, telling everyone in it.
+    ///
+    /// A paused game the server ticks stands still; a paused lockstep game
+    /// refuses turns and confirmations until it is resumed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist or is not running.
+    pub fn set_paused(&mut self, id: SessionId, paused: bool) -> Result<(), SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let mut session = lock(session);
+        if session.state() != SessionState::Running {
+            return Err(SessionError::NotRunning(id));
+        }
+        if session.paused == paused {
+            return Ok(());
+        }
+        session.paused = paused;
+        if let Some(relay) = session.lockstep.as_mut() {
+            relay.restart_stall_clock();
+        }
+        tracing::info!(session = id, paused, "Game pause changed");
+        session.publish(SessionEvent::Paused(paused));
+        if !paused {
+            // Bots may have turns waiting from before the pause
+            for event in drive_bots(id, &mut session) {
+                session.publish(SessionEvent::Lockstep(event));
+            }
+        }
+        Ok(())
+    }
+
+    /// Change how many ticks per second a game the server ticks plays at.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist, is not running, or
+    /// is a lockstep game.
+    pub fn set_tick_rate(&mut self, id: SessionId, tick_rate: u32) -> Result<(), SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let mut session = lock(session);
+        if session.state() != SessionState::Running {
+            return Err(SessionError::NotRunning(id));
+        }
+        if session.lockstep.is_some() {
+            return Err(SessionError::PacedByPlayers(id));
+        }
+        session.tick_rate = tick_rate.max(1);
+        tracing::info!(
+            session = id,
+            tick_rate = session.tick_rate,
+            "Tick rate changed"
+        );
+        Ok(())
+    }
+
+    /// Remove `player` from a session, telling them `reason`.
+    ///
+    /// Spectators and players in a lobby leave as if by themselves. A
+    /// player in a running game keeps their seat, which the server plays as
+    /// an [`IdleBot`], but loses their token, so they cannot rejoin.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session does not exist or `player` is not
+    /// in it.
+    pub fn kick(&mut self, id: SessionId, player: &str, reason: &str) -> Result<(), SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        {
+            let mut session = lock(session);
+            let seated = session.room.position(player).is_some();
+            if !seated && !session.room.is_spectator(player) {
+                return Err(SessionError::NotInSession(player.to_string()));
+            }
+            session.publish(SessionEvent::Kicked {
+                player: Some(player.to_string()),
+                reason: reason.to_string(),
+            });
+            if seated && session.game.is_some() {
+                tracing::info!(session = id, %player, %reason, "Kicked player; seat left idle");
+                session.tokens.remove(player);
+                session.idle_seat(id, player);
+                return Ok(());
+            }
+        }
+        tracing::info!(session = id, %player, %reason, "Kicked player");
+        self.leave(id, player)
+    }
+
+    /// Drop every player who has held up their lockstep game for
+    /// [`drop_after_stalled_ticks`](crate::ServerConfig::drop_after_stalled_ticks)
+    /// at `now`, returning the sessions and players dropped.
+    ///
+    /// A dropped player is treated as having lost their connection: they
+    /// are sent a [`SessionEvent::Kicked`] to close it, and the server
+    /// plays their seat with an [`IdleBot`] until they
+    /// [rejoin](Self::rejoin). Paused and desynced games are left alone, as
+    /// are games waiting on every player in them, since there would be
+    /// nobody left to play on for.
+    pub fn drop_stalled(&mut self, now: Instant) -> Vec<(SessionId, String)> {
+        let Some(limit) = self.drop_after_stalled else {
+            return Vec::new();
+        };
+        let mut dropped = Vec::new();
+        for (&id, session) in &self.sessions {
+            let mut session = lock(session);
+            let (Some(relay), Some(game)) = (&session.lockstep, &session.game) else {
+                continue;
+            };
+            let stalled =
+                (relay.stalled_for(now).as_secs_f64() * f64::from(session.tick_rate)) as u64;
+            if session.paused || stalled < limit {
+                continue;
+            }
+            let bots = &session.bots;
+            let humans = relay
+                .players()
+                .filter(|player| !bots.contains_key(*player))
+                .count();
+            let mut waiting = relay.waiting_on(game);
+            waiting.retain(|player| !bots.contains_key(player));
+            if waiting.is_empty() || waiting.len() == humans {
+                continue;
+            }
+            for player in waiting {
+                tracing::warn!(session = id, %player, stalled, "Dropped stalled player");
+                session.publish(SessionEvent::Kicked {
+                    player: Some(player.clone()),
+                    reason: format!("dropped for stalling the game {stalled} ticks"),
+                });
+                session.idle_seat(id, &player);
+                dropped.push((id, player));
+            }
+        }
+        dropped
+    }
+
+    /// Close a session at once, whatever state it is in, telling everyone
+    /// in it `reason`.
+    ///
+    /// The game, if any, is dropped without a result and is not recorded
+    /// in the match history.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SessionError::NotFound`] if the session does not exist.
+    pub fn terminate(&mut self, id: SessionId, reason: &str) -> Result<(), SessionError> {
+        let session = self
+            .sessions
+            .remove(&id)
+            .ok_or(SessionError::NotFound(id))?;
+        if let Some(task) = self.tasks.remove(&id) {
+            task.abort();
+        }
+        let session = lock(&session);
+        session.publish(SessionEvent::Kicked {
+            player: None,
+            reason: reason.to_string(),
+        });
+        tracing::info!(
+            session = id,
+            tick = session.game.as_ref().map(|game| game.simulation().get_tick()),
+            %reason,
+            "Session terminated"
+        );
+        Ok(())
+    }
+
+    /// Snapshot one session.
+    #[must_use]
+    pub fn info(&self, id: SessionId) -> Option<SessionInfo> {
+        self.sessions.get(&id).map(|session| {
+            let session = lock(session);
+            SessionInfo {
+                id,
+                name: session.name.clone(),
+                state: session.state(),
+                players: session.players(),
+                spectators: session.room.spectators().to_vec(),
+                map: session.map.clone(),
+                paused: session.paused,
+                usage: session.usage,
+            }
+        })
+    }
+
+    /// State snapshot of a running game, filtered for `viewer`.
+    ///
+    /// Used to bring a reconnecting player or a joining observer up to
+    /// date without revealing anything they could not see themselves.
+    /// Returns None for lobbies.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SessionError::NotFound`] if the session does not exist.
+    pub fn snapshot_for(
+        &self,
+        id: SessionId,
+        viewer: &Viewer,
+    ) -> Result<Option<StateSnapshot>, SessionError> {
+        let session = self.sessions.get(&id).ok_or(SessionError::NotFound(id))?;
+        let session = lock(session);
+        Ok(session
+            .game
+            .as_ref()
+            .map(|game| filtered_snapshot(game.simulation(), viewer)))
+    }
+
+    /// Snapshot every session, in ID order.
+    #[must_use]
+    pub fn list(&self) -> Vec<SessionInfo> {
+        self.sessions
+            .keys()
+            .filter_map(|&id| self.info(id))
+            .collect()
+    }
+
+    /// Number of sessions counting towards the cap.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Whether no sessions are open.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Number of games in progress.
+    #[must_use]
+    pub fn running(&self) -> usize {
+        self.sessions
+            .values()
+            .filter(|session| lock(session).state() == SessionState::Running)
+            .count()
+    }
+
+    /// Remove finished games, freeing their slots, and record them in the
+    /// match history.
+    pub fn reap(&mut self) -> Vec<SessionInfo> {
+        let finished: Vec<_> = self
+            .list()
+            .into_iter()
+            .filter(|info| info.state == SessionState::Finished)
+            .collect();
+        for info in &finished {
+            let Some(session) = self.sessions.remove(&info.id) else {
+                continue;
+            };
+            self.tasks.remove(&info.id);
+            let session = lock(&session);
+            let win_condition = session.game.as_ref().and_then(HostedGame::win_condition);
+            let identities = session.room.identities();
+            let rated = session.rated && self.rate(info.id, &session, win_condition.as_ref());
+            self.save_replay(info.id, &session, &identities);
+            let player_ids: Vec<String> = identities
+                .iter()
+                .map(|(player, id)| format!("{player}={id}"))
+                .collect();
+            self.history.record(MatchRecord {
+                session: info.id,
+                map: info.map.clone(),
+                players: info.players.clone(),
+                identities,
+                rated,
+                winner: win_condition.and_then(|c| c.winner()),
+                win_condition,
+                ticks: info.usage.ticks,
+            });
+            tracing::info!(
+                session = info.id,
+                ticks = info.usage.ticks,
+                busy_ms = info.usage.busy.as_millis(),
+                player_ids = %player_ids.join(","),
+                "Session finished"
+            );
+        }
+        finished
+    }
+
+    /// Save a finished game's replay, with the IDs of its identified
+    /// players, if the server keeps replays.
+    fn save_replay(
+        &self,
+        id: SessionId,
+        session: &Session,
+        identities: &BTreeMap<String, PlayerId>,
+    ) {
+        let (Some(dir), Some(game)) = (&self.replay_dir, &session.game) else {
+            return;
+        };
+        let players = identities
+            .iter()
+            .map(|(name, player)| (name.clone(), player.0))
+            .collect();
+        match game.save_replay(dir, &players) {
+            Ok(Some(path)) => tracing::info!(session = id, path = %path.display(), "Replay saved"),
+            Ok(None) => {}
+            Err(e) => tracing::error!(session = id, error = %e, "Failed to save replay"),
+        }
+    }
+
+    /// Update the ratings of a finished rated game's players, save them
+    /// and tell the session. Returns whether the game was rated: every
+    /// seat must be identified and the outcome decided.
+    fn rate(&mut self, id: SessionId, session: &Session, outcome: Option<&WinCondition>) -> bool {
+        let (Some(outcome), Some(relay)) = (outcome, &session.lockstep) else {
+            return false;
+        };
+        let players: Option<Vec<_>> = session
+            .room
+            .seats()
+            .iter()
+            .map(|seat| {
+                let faction = relay.faction_of(&seat.player)?;
+                Some((seat.player_id?, seat.player.clone(), faction))
+            })
+            .collect();
+        let Some(players) = players else {
+            return false;
+        };
+        let rated = self.ratings.rate_game(&players, outcome);
+        if rated.is_empty() {
+            return false;
+        }
+        for player in &rated {
+            tracing::info!(
+                session = id,
+                player = %player.name,
+                player_id = %player.id,
+                rating = player.rating.rating.round(),
+                deviation = player.rating.deviation.round(),
+                "Rating updated"
+            );
+        }
+        if let Err(e) = self.ratings.save() {
+            tracing::error!(error = %e, "Failed to save ratings");
+        }
+        session.publish(SessionEvent::Rated(
+            rated
+                .iter()
+                .map(|player| (player.name.clone(), player.public()))
+                .collect(),
+        ));
+        true
+    }
+
+    /// Stop every tick loop and hand back the games still in progress.
+    ///
+    /// Lobbies are closed. The returned games are frozen at their current
+    /// tick, ready for [`drain_games`](crate::shutdown::drain_games).
+    pub async fn stop_all(&mut self) -> Vec<HostedGame> {
+        self.stop.send_replace(true);
+        for (id, task) in std::mem::take(&mut self.tasks) {
+            if let Err(e) = task.await {
+                tracing::error!(session = id, error = %e, "Session task failed");
+            }
+        }
+        std::mem::take(&mut self.sessions)
+            .into_values()
+            .filter_map(|session| lock(&session).game.take())
+            .filter(|game| !game.is_finished())
+            .collect()
+    }
+}
+
+/// Fresh secret for a player to rejoin a session with.
+fn session_token(id: SessionId, player: &str) -> String {
+    // Each RandomState is keyed from the OS entropy source
+    let salt = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    let words: [u64; 2] =
+        std::array::from_fn(|half| RandomState::new().hash_one((id, player, salt, half)));
+    format!("{:016x}{:016x}", words[0], words[1])
+}
+
+/// Turn a lobby into a game, checking it is ready to start.
+fn begin_game(id: SessionId, session: &mut Session, sim: Simulation) -> Result<(), SessionError> {
+    if session.game.is_some() {
+        return Err(SessionError::AlreadyStarted(id));
+    }
+    if session.veto.is_some() && session.map.is_none() {
+        return Err(SessionError::VetoPending(id));
+    }
+    let fingerprint = session.fingerprint();
+    for peer in session.fingerprints.values() {
+        check_fingerprint(&fingerprint, peer)?;
+    }
+    let mut game = HostedGame::new(format!("session-{id}"), sim).with_fingerprint(fingerprint);
+    if let Some(map) = &session.map {
+        game = game.with_map(map.clone());
+    }
+    if session.record_replay {
+        if let Err(e) = game.record_replay() {
+            tracing::error!(session = id, error = %e, "Failed to start replay");
+        }
+    }
+    session.game = Some(game);
+    session.countdown = None;
+    tracing::info!(session = id, players = session.room.len(), "Game started");
+    Ok(())
+}
+
+/// Start a lobby's game in lockstep, one seat per player in join order.
+fn begin_lockstep(
+    id: SessionId,
+    session: &mut Session,
+    sim: Simulation,
+    factions: &[FactionId],
+    input_delay: DelayBounds,
+) -> Result<(), SessionError> {
+    if session.room.len() != factions.len() {
+        return Err(SessionError::SeatMismatch {
+            players: session.room.len(),
+            factions: factions.len(),
+        });
+    }
+    if session.rated {
+        // The outcome names a winning faction, which cannot tell apart
+        // two players on the same one
+        let players = session.players();
+        for (i, &faction) in factions.iter().enumerate() {
+            if let Some(j) = factions[..i].iter().position(|&f| f == faction) {
+                return Err(SeatError::FactionTaken {
+                    faction,
+                    player: players[j].clone(),
+                }
+                .into());
+            }
+        }
+    }
+    begin_game(id, session, sim)?;
+    let seats: Vec<_> = session
+        .players()
+        .into_iter()
+        .zip(factions.iter().copied())
+        .collect();
+    let tick_rate = session.tick_rate;
+    session.lockstep = session
+        .game
+        .as_ref()
+        .map(|game| LockstepRelay::new(game, seats).with_input_delay(input_delay, tick_rate));
+    Ok(())
+}
+
+/// Have a lockstep game's bots confirm and submit every turn they can,
+/// returning the events that releases.
+///
+/// Each pass gives every bot at most one turn. The players' own
+/// submissions, at most [`MAX_INPUT_LEAD`] ticks ahead, bound how many
+/// passes make progress; a game of bots alone stops after that many.
+fn drive_bots(id: SessionId, session: &mut Session) -> Vec<LockstepEvent> {
+    let (Some(relay), Some(game)) = (session.lockstep.as_mut(), session.game.as_mut()) else {
+        return Vec::new();
+    };
+    let mut events = Vec::new();
+    for _ in 0..=MAX_INPUT_LEAD {
+        let mut submitted = false;
+        for (name, bot) in &mut session.bots {
+            if relay.desync().is_some() || game.is_finished() {
+                return events;
+            }
+            let Some(faction) = relay.faction_of(name) else {
+                continue;
+            };
+            let mut step = relay.confirm_as_server(game, name);
+            if relay.awaits_commands(name) {
+                let commands = bot.commands(game.simulation(), faction);
+                let tick = relay.next_tick();
+                step = step.and_then(|mut released| {
+                    released.extend(relay.submit(game, name, tick, commands)?);
+                    Ok(released)
+                });
+                submitted = true;
+            }
+            match step {
+                Ok(released) => events.extend(released),
+                Err(e) => tracing::warn!(session = id, bot = %name, error = %e, "Bot turn refused"),
+            }
+        }
+        if !submitted {
+            break;
+        }
+    }
+    events
+}
+
+/// Launch a ready lobby into a skirmish with the seats it chose.
+fn launch(
+    id: SessionId,
+    session: &mut Session,
+    input_delay: DelayBounds,
+) -> Result<Launch, SessionError> {
+    let starts = session.room.starts().ok_or(SessionError::LobbyFull {
+        id,
+        max: STARTING_POSITIONS,
+    })?;
+    let mut seats = session.room.seats().to_vec();
+    let mut layout = Vec::with_capacity(seats.len());
+    for (seat, start) in seats.iter_mut().zip(starts) {
+        let faction = seat
+            .faction
+            .ok_or_else(|| SeatError::NoFaction(seat.player.clone()))?;
+        seat.start = Some(start);
+        layout.push((faction, start));
+    }
+    let factions: Vec<_> = layout.iter().map(|&(faction, _)| faction).collect();
+    let seed = id;
+    begin_lockstep(id, session, skirmish(seed, &layout), &factions, input_delay)?;
+    Ok(Launch {
+        session: id,
+        seed,
+        seats,
+        input_delay: input_delay.min(),
+    })
+}
+
+/// Ticks of a game played at `tick_rate` per second.
+fn tick_interval(tick_rate: u32) -> tokio::time::Interval {
+    let mut interval = tokio::time::interval(Duration::from_secs(1) / tick_rate.max(1));
+    // A late tick should not trigger a burst of catch-up ticks
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    interval
+}
+
+/// Tick one game at its session's tick rate until it ends or the server
+/// stops. Paused games skip their ticks.
+async fn run_session(id: SessionId, session: SharedSession, mut stop: watch::Receiver<bool>) {
+    let mut tick_rate = lock(&session).tick_rate;
+    let mut interval = tick_interval(tick_rate);
+
+    loop {
+        tokio::select! {
+            _ = stop.changed() => break,
+            _ = interval.tick() => {}
+        }
+        if *stop.borrow() {
+            break;
+        }
+
+        let mut session = lock(&session);
+        if session.tick_rate != tick_rate {
+            tick_rate = session.tick_rate;
+            interval = tick_interval(tick_rate);
+        }
+        if session.paused {
+            continue;
+        }
+        let Some(game) = session.game.as_mut() else {
+            break;
+        };
+        let start = Instant::now();
+        game.tick();
+        let elapsed = start.elapsed();
+        let entities = game.simulation().entities().len();
+        let finished = game.is_finished();
+        let win_condition = game.win_condition();
+        session.usage.record_tick(elapsed, entities);
+
+        if finished {
+            tracing::info!(session = id, ?win_condition, "Game decided");
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lobby::bans_to_decider;
+    use rts_core::components::FactionMember;
+    use rts_core::factions::FactionId;
+    use rts_core::math::{Fixed, Vec2Fixed};
+    use rts_core::replay::Replay;
+    use rts_core::simulation::EntitySpawnParams;
+
+    fn config(max_sessions: usize) -> ServerConfig {
+        ServerConfig {
+            max_sessions,
+            max_players: 2,
+            tick_rate: 1000,
+            ..Default::default()
+        }
+    }
+
+    fn depots(factions: &[FactionId]) -> Simulation {
+        let mut sim = Simulation::new();
+        for (i, &faction) in factions.iter().enumerate() {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::new(
+                    Fixed::from_num(i as i32 * 500),
+                    Fixed::from_num(0),
+                )),
+                health: Some(1000),
+                is_depot: true,
+                faction: Some(FactionMember::new(faction, 0)),
+                ..Default::default()
+            });
+        }
+        sim
+    }
+
+    async fn wait_for(manager: &SessionManager, id: SessionId, ticks: u64) {
+        while manager.info(id).unwrap().usage.ticks < ticks {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    #[test]
+    fn test_session_cap_and_lobby_rules() {
+        let mut manager = SessionManager::new(&config(2));
+        let a = manager.open_lobby("a").unwrap();
+        manager.open_lobby("b").unwrap();
+        assert_eq!(manager.open_lobby("c"), Err(SessionError::AtCapacity(2)));
+
+        manager.join(a, "alice").unwrap();
+        manager.join(a, "bob").unwrap();
+        assert_eq!(
+            manager.join(a, "carol"),
+            Err(SessionError::LobbyFull { id: a, max: 2 })
+        );
+        assert_eq!(manager.join(99, "dave"), Err(SessionError::NotFound(99)));
+        assert_eq!(manager.info(a).unwrap().state, SessionState::Lobby);
+    }
+
+    #[tokio::test]
+    async fn test_games_tick_independently_and_account_usage() {
+        let mut manager = SessionManager::new(&config(4));
+        let both = [FactionId::Continuity, FactionId::Collegium];
+        let small = manager.open_lobby("small").unwrap();
+        let large = manager.open_lobby("large").unwrap();
+        manager.start(small, depots(&both)).unwrap();
+        manager
+            .start(large, depots(&[both[0], both[1], both[0]]))
+            .unwrap();
+        assert_eq!(
+            manager.start(small, depots(&both)),
+            Err(SessionError::AlreadyStarted(small))
+        );
+
+        wait_for(&manager, small, 5).await;
+        wait_for(&manager, large, 5).await;
+        assert_eq!(manager.running(), 2);
+        assert_eq!(manager.info(small).unwrap().usage.peak_entities, 2);
+        assert_eq!(manager.info(large).unwrap().usage.peak_entities, 3);
+
+        let games = manager.stop_all().await;
+        assert_eq!(games.len(), 2);
+        assert!(games.iter().all(|game| game.simulation().get_tick() >= 5));
+        assert!(manager.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reaping_finished_games_frees_capacity() {
+        let mut manager = SessionManager::new(&config(1));
+        let id = manager.open_lobby("decided").unwrap();
+        manager.start(id, depots(&[FactionId::Continuity])).unwrap();
+        assert!(manager.open_lobby("waiting").is_err());
+
+        wait_for(&manager, id, 1).await;
+        let reaped = manager.reap();
+        assert_eq!(reaped.len(), 1);
+        assert_eq!(reaped[0].state, SessionState::Finished);
+        assert!(manager.open_lobby("next").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_vetoed_map_reaches_game_and_history() {
+        let mut manager = SessionManager::new(&ServerConfig {
+            map_pool: vec!["dunes".to_string(), "delta".to_string()],
+            ..config(2)
+        });
+        let id = manager.open_lobby("ranked").unwrap();
+        manager.join(id, "alice").unwrap();
+        assert_eq!(
+            manager.begin_veto(id, bans_to_decider(2)),
+            Err(SessionError::NeedCaptains(id))
+        );
+        manager.join(id, "bob").unwrap();
+        manager.begin_veto(id, bans_to_decider(2)).unwrap();
+        assert_eq!(
+            manager.start(id, depots(&[FactionId::Continuity])),
+            Err(SessionError::VetoPending(id))
+        );
+        assert_eq!(
+            manager.veto(id, "bob", VetoAction::Ban, "dunes"),
+            Err(SessionError::Veto(VetoError::NotYourTurn {
+                expected: Captain::First
+            }))
+        );
+        assert_eq!(
+            manager.veto(id, "alice", VetoAction::Ban, "dunes"),
+            Ok(Some("delta".to_string()))
+        );
+
+        manager.start(id, depots(&[FactionId::Continuity])).unwrap();
+        wait_for(&manager, id, 1).await;
+        manager.reap();
+        let record = &manager.history().records()[0];
+        assert_eq!(record.map.as_deref(), Some("delta"));
+        assert_eq!(record.players, vec!["alice", "bob"]);
+        assert_eq!(record.winner, Some(FactionId::Continuity));
+        assert_eq!(
+            record.win_condition,
+            Some(WinCondition::Elimination {
+                eliminating_player: FactionId::Continuity,
+                surviving_structures: 1,
+            })
+        );
+
+        let mut rng = SimRng::new(1);
+        assert!(manager.random_map(&mut rng).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_handshake_refuses_mismatched_clients() {
+        let mut manager = SessionManager::new(&config(2));
+        let id = manager.open_lobby("ranked").unwrap();
+        let data = Fingerprint {
+            data: Some(7),
+            ..Fingerprint::engine()
+        };
+
+        let reply = manager
+            .handshake(id, &ClientHello::new("alice", data))
+            .unwrap();
+        assert!(matches!(reply, ServerReply::Welcome { session, .. } if session == id));
+        assert_eq!(manager.fingerprint(id).unwrap().data, Some(7));
+
+        // Different faction data from the player already admitted
+        let modded = Fingerprint {
+            data: Some(8),
+            ..Fingerprint::engine()
+        };
+        assert!(matches!(
+            manager.handshake(id, &ClientHello::new("bob", modded)),
+            Err(SessionError::Handshake(HandshakeError::Incompatible(_)))
+        ));
+        assert_eq!(manager.info(id).unwrap().players, vec!["alice"]);
+        manager
+            .handshake(id, &ClientHello::new("bob", data))
+            .unwrap();
+
+        manager.set_map(id, "delta").unwrap();
+        manager.start(id, depots(&[FactionId::Continuity])).unwrap();
+        let fingerprint = manager.fingerprint(id).unwrap();
+        assert_eq!(
+            fingerprint,
+            Fingerprint {
+                data: Some(7),
+                ..Fingerprint::engine().with_map_name("delta")
+            }
+        );
+        manager.stop_all().await;
+    }
+
+    #[tokio::test]
+    async fn test_identified_players_carry_their_id_into_the_record() {
+        let dir = std::env::temp_dir().join(format!("rts_replay_test_{}", std::process::id()));
+        let mut manager = SessionManager::new(&ServerConfig {
+            replay_dir: Some(dir.clone()),
+            ..config(2)
+        });
+        let id = manager.open_lobby("ranked").unwrap();
+        let (alice, token) = manager.register("alice").unwrap();
+        let hello = ClientHello::new("alice", Fingerprint::engine()).with_identity(&token);
+        assert_eq!(manager.identify(&hello), Ok(Some(alice.clone())));
+        manager.handshake(id, &hello).unwrap();
+
+        // Nobody else gets the name, with or without the token
+        let impostor = ClientHello::new("alice", Fingerprint::engine());
+        assert_eq!(
+            manager.handshake(id, &impostor),
+            Err(SessionError::AlreadyJoined("alice".to_string()))
+        );
+        let borrowed = ClientHello::new("bob", Fingerprint::engine()).with_identity(&token);
+        assert_eq!(
+            manager.handshake(id, &borrowed),
+            Err(SessionError::Identity(IdentityError::WrongName {
+                registered: "alice".to_string(),
+                given: "bob".to_string(),
+            }))
+        );
+        manager
+            .handshake(id, &ClientHello::new("bob", Fingerprint::engine()))
+            .unwrap();
+        let seats = manager.lobby_state(id).unwrap().seats;
+        assert_eq!(seats[0].player_id, Some(alice.id));
+        assert_eq!(seats[1].player_id, None);
+
+        manager.start(id, depots(&[FactionId::Continuity])).unwrap();
+        wait_for(&manager, id, 1).await;
+        manager.reap();
+        let record = &manager.history().records()[0];
+        assert_eq!(
+            record.identities,
+            BTreeMap::from([("alice".to_string(), alice.id)])
+        );
+        assert_eq!(manager.players().name(alice.id), Some("alice"));
+
+        // So does the replay
+        let replay = Replay::load(dir.join(format!("session-{id}.replay"))).unwrap();
+        assert_eq!(
+            replay.players,
+            BTreeMap::from([("alice".to_string(), alice.id.0)])
+        );
+        assert!(replay.final_tick >= 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_players_rejoin_running_games_with_their_token() {
+        let mut manager = SessionManager::new(&config(2));
+        let id = manager.open_lobby("ranked").unwrap();
+        let factions = [FactionId::Continuity, FactionId::Collegium];
+        let mut tokens = Vec::new();
+        for player in ["alice", "bob"] {
+            let hello = ClientHello::new(player, Fingerprint::engine());
+            let ServerReply::Welcome {
+                token: Some(token), ..
+            } = manager.handshake(id, &hello).unwrap()
+            else {
+                panic!("{player} should get a token");
+            };
+            tokens.push(token);
+        }
+        let alice = ClientHello::new("alice", Fingerprint::engine());
+        assert!(matches!(
+            manager.rejoin(id, &alice, &tokens[0]),
+            Err(SessionError::NotLockstep(_))
+        ));
+
+        manager
+            .start_lockstep(id, depots(&factions), &factions)
+            .unwrap();
+        for player in ["alice", "bob"] {
+            manager.submit_commands(id, player, 0, Vec::new()).unwrap();
+        }
+        assert_eq!(
+            manager.rejoin(id, &alice, &tokens[1]),
+            Err(SessionError::BadToken("alice".to_string()))
+        );
+        let rejoin = manager.rejoin(id, &alice, &tokens[0]).unwrap();
+        assert_eq!(rejoin.checkpoint.tick, 0);
+        assert_eq!(rejoin.next_tick(), 1);
+        let resync = manager.resync(id, "alice").unwrap();
+        assert_eq!(
+            rejoin.fast_forward().unwrap().state_hash(),
+            resync.restore().unwrap().state_hash()
+        );
+    }
+
+    #[test]
+    fn test_bots_fill_seats_and_play_their_turns() {
+        let mut manager = SessionManager::new(&config(2));
+        let abandoned = manager.open_lobby("abandoned").unwrap();
+        manager.join(abandoned, "bob").unwrap();
+        manager
+            .add_strategy_bot(abandoned, "turtle", FactionId::Zephyr)
+            .unwrap();
+        manager.leave(abandoned, "bob").unwrap();
+        assert_eq!(manager.info(abandoned), None);
+
+        let id = manager.open_lobby("vs bot").unwrap();
+        manager.join(id, "alice").unwrap();
+        manager
+            .choose_faction(id, "alice", FactionId::Continuity)
+            .unwrap();
+        assert_eq!(
+            manager.add_strategy_bot(id, "nope", FactionId::Collegium),
+            Err(SessionError::UnknownStrategy("nope".to_string()))
+        );
+        assert_eq!(
+            manager.fill_with_bots(id, "rush").unwrap(),
+            vec!["rush bot"]
+        );
+        assert!(manager.fill_with_bots(id, "rush").unwrap().is_empty());
+        let bot = &manager.lobby_state(id).unwrap().seats[1];
+        assert_eq!(bot.faction, Some(FactionId::Collegium));
+        assert!(bot.ready);
+
+        // The bot submits as soon as each tick opens, so alice alone
+        // drives the game
+        let factions = [FactionId::Continuity, FactionId::Collegium];
+        let mut events = manager.subscribe(id).unwrap();
+        manager
+            .start_lockstep(id, depots(&factions), &factions)
+            .unwrap();
+        let mut local = depots(&factions);
+        for tick in 0..3 {
+            manager
+                .submit_commands(id, "alice", tick, Vec::new())
+                .unwrap();
+            let turn = std::iter::from_fn(|| events.try_recv().ok())
+                .find_map(|event| match event {
+                    SessionEvent::Lockstep(LockstepEvent::Turn(turn)) => Some(turn),
+                    _ => None,
+                })
+                .unwrap();
+            assert_eq!(turn.tick, tick);
+            local.tick();
+            manager
+                .confirm_tick(id, "alice", tick, local.state_hash())
+                .unwrap();
+        }
+        assert_eq!(manager.info(id).unwrap().usage.ticks, 3);
+    }
+
+    #[tokio::test]
+    async fn test_operators_pause_kick_and_terminate() {
+        let mut manager = SessionManager::new(&config(2));
+        let lobby = manager.open_lobby("lobby").unwrap();
+        manager.join(lobby, "carol").unwrap();
+        assert_eq!(
+            manager.set_paused(lobby, true),
+            Err(SessionError::NotRunning(lobby))
+        );
+        manager.kick(lobby, "carol", "afk").unwrap();
+        assert_eq!(manager.info(lobby), None);
+
+        let id = manager.open_lobby("game").unwrap();
+        manager.join(id, "alice").unwrap();
+        manager.join(id, "bob").unwrap();
+        let factions = [FactionId::Continuity, FactionId::Collegium];
+        let mut events = manager.subscribe(id).unwrap();
+        manager
+            .start_lockstep(id, depots(&factions), &factions)
+            .unwrap();
+        assert_eq!(
+            manager.set_tick_rate(id, 5),
+            Err(SessionError::PacedByPlayers(id))
+        );
+
+        manager.set_paused(id, true).unwrap();
+        assert!(manager.info(id).unwrap().paused);
+        assert_eq!(
+            manager.submit_commands(id, "alice", 0, Vec::new()),
+            Err(SessionError::Paused(id))
+        );
+        manager.set_paused(id, false).unwrap();
+
+        // Bob's seat stands idle, so alice alone drives the game
+        manager.kick(id, "bob", "afk").unwrap();
+        assert_eq!(
+            manager.kick(id, "dave", "afk"),
+            Err(SessionError::NotInSession("dave".to_string()))
+        );
+        assert_eq!(
+            manager.submit_commands(id, "bob", 0, Vec::new()),
+            Err(SessionError::NotInSession("bob".to_string()))
+        );
+        manager.submit_commands(id, "alice", 0, Vec::new()).unwrap();
+        let events: Vec<_> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+        assert!(events.contains(&SessionEvent::Paused(true)));
+        assert!(events.contains(&SessionEvent::Kicked {
+            player: Some("bob".to_string()),
+            reason: "afk".to_string(),
+        }));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            SessionEvent::Lockstep(LockstepEvent::Turn(turn)) if turn.tick == 0
+        )));
+
+        manager.terminate(id, "maintenance").unwrap();
+        assert!(manager.is_empty());
+        assert!(manager.history().records().is_empty());
+
+        // Games the server ticks can be slowed down
+        let ticked = manager.open_lobby("ticked").unwrap();
+        manager.join(ticked, "erin").unwrap();
+        manager.start(ticked, depots(&factions)).unwrap();
+        manager.set_tick_rate(ticked, 5).unwrap();
+        assert_eq!(manager.stop_all().await.len(), 1);
+    }
+
+    #[test]
+    fn test_queued_players_are_matched_and_rated() {
+        let mut manager = SessionManager::new(&config(4));
+        let mut hellos = Vec::new();
+        let mut ids = Vec::new();
+        let now = Instant::now();
+        for player in ["alice", "bob"] {
+            let (identity, token) = manager.register(player).unwrap();
+            hellos.push(ClientHello::new(player, Fingerprint::engine()).with_identity(token));
+            ids.push(identity.id);
+        }
+        let (rating, mut matches) = manager.enqueue("alice", ids[0], now).unwrap();
+        assert_eq!(rating.rating, 1500);
+        assert_eq!(
+            manager.enqueue("alice", ids[0], now).map(|_| ()),
+            Err(SessionError::AlreadyQueued("alice".to_string()))
+        );
+        assert!(manager.match_queued(now, &mut SimRng::new(1)).is_empty());
+        manager.enqueue("bob", ids[1], now).unwrap();
+        let opened = manager.match_queued(now, &mut SimRng::new(1));
+        assert_eq!(opened.len(), 1);
+        let id = opened[0];
+        let matched = matches.try_recv().unwrap();
+        assert_eq!(matched.session, id);
+        assert!(manager.queue().is_empty());
+
+        // The seats are reserved for the matched identities
+        assert_eq!(
+            manager.join(id, "carol"),
+            Err(SessionError::Reserved {
+                id,
+                player: "carol".to_string(),
+            })
+        );
+        let anonymous = ClientHello::new("alice", Fingerprint::engine());
+        assert!(matches!(
+            manager.handshake(id, &anonymous),
+            Err(SessionError::Reserved { .. })
+        ));
+        for hello in &hellos {
+            manager.handshake(id, hello).unwrap();
+        }
+        let lobby = manager.lobby_state(id).unwrap();
+        assert!(lobby.rated);
+        assert_eq!(lobby.seats[0].rating, Some(rating));
+        assert!(manager.expire_matches(now + MATCH_JOIN_TIMEOUT).is_empty());
+
+        // Alice's depot is the only one, so she wins on the first tick
+        let factions = [FactionId::Continuity, FactionId::Collegium];
+        manager
+            .start_lockstep(id, depots(&factions[..1]), &factions)
+            .unwrap();
+        let mut events = manager.subscribe(id).unwrap();
+        for player in ["alice", "bob"] {
+            manager.submit_commands(id, player, 0, Vec::new()).unwrap();
+        }
+        manager.reap();
+        assert!(manager.history().records()[0].rated);
+        let (alice, bob) = (
+            manager.ratings().public(ids[0]),
+            manager.ratings().public(ids[1]),
+        );
+        assert!(alice.rating > 1500 && bob.rating < 1500);
+        assert_eq!((alice.games, bob.games), (1, 1));
+        let rated = std::iter::from_fn(|| events.try_recv().ok())
+            .find_map(|event| match event {
+                SessionEvent::Rated(ratings) => Some(ratings),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(rated["alice"], alice);
+    }
+
+    #[test]
+    fn test_rated_lobbies_refuse_mirror_matches() {
+        let mut manager = SessionManager::new(&config(4));
+        let now = Instant::now();
+        let mut hellos = Vec::new();
+        for player in ["alice", "bob"] {
+            let (identity, token) = manager.register(player).unwrap();
+            manager.enqueue(player, identity.id, now).unwrap();
+            hellos.push(ClientHello::new(player, Fingerprint::engine()).with_identity(token));
+        }
+        let id = manager.match_queued(now, &mut SimRng::new(1))[0];
+        for hello in &hellos {
+            manager.handshake(id, hello).unwrap();
+        }
+        manager
+            .choose_faction(id, "alice", FactionId::Continuity)
+            .unwrap();
+        let taken = Err(SessionError::Seat(SeatError::FactionTaken {
+            faction: FactionId::Continuity,
+            player: "alice".to_string(),
+        }));
+        assert_eq!(
+            manager.choose_faction(id, "bob", FactionId::Continuity),
+            taken
+        );
+
+        // Nor can a mirror game be started directly, and nobody is rated
+        let factions = [FactionId::Continuity, FactionId::Continuity];
+        assert_eq!(
+            manager.start_lockstep(id, depots(&factions[..1]), &factions),
+            taken
+        );
+        let mirror = WinCondition::Elimination {
+            eliminating_player: FactionId::Continuity,
+            surviving_structures: 1,
+        };
+        assert_eq!(
+            crate::rating::score(&mirror, FactionId::Continuity, FactionId::Continuity),
+            None
+        );
+        assert!(manager.ratings().is_empty());
+
+        // Unrated lobbies still allow them
+        let casual = manager.open_lobby("casual").unwrap();
+        for player in ["carol", "dave"] {
+            manager.join(casual, player).unwrap();
+            manager
+                .choose_faction(casual, player, FactionId::Continuity)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_unjoined_matches_expire() {
+        let mut manager = SessionManager::new(&config(4));
+        let now = Instant::now();
+        let (alice, token) = manager.register("alice").unwrap();
+        let (bob, _) = manager.register("bob").unwrap();
+        manager.enqueue("alice", alice.id, now).unwrap();
+        manager.enqueue("bob", bob.id, now).unwrap();
+        let id = manager.match_queued(now, &mut SimRng::new(1))[0];
+        let hello = ClientHello::new("alice", Fingerprint::engine()).with_identity(token);
+        manager.handshake(id, &hello).unwrap();
+        let mut events = manager.subscribe(id).unwrap();
+
+        let later = Instant::now() + MATCH_JOIN_TIMEOUT;
+        assert_eq!(manager.expire_matches(later), vec![id]);
+        assert!(manager.is_empty());
+        assert!(matches!(
+            events.try_recv(),
+            Ok(SessionEvent::Kicked { player: None, .. })
+        ));
+    }
+
+    #[test]
+    fn test_stalled_players_are_dropped_until_they_rejoin() {
+        let mut manager = SessionManager::new(&ServerConfig {
+            drop_after_stalled_ticks: Some(100),
+            ..config(2)
+        });
+        let id = manager.open_lobby("laggy").unwrap();
+        let mut tokens = BTreeMap::new();
+        for player in ["alice", "bob"] {
+            let hello = ClientHello::new(player, Fingerprint::engine());
+            let ServerReply::Welcome { token, .. } = manager.handshake(id, &hello).unwrap() else {
+                panic!("{player} was refused");
+            };
+            tokens.insert(player, token.unwrap());
+        }
+        let factions = [FactionId::Continuity, FactionId::Collegium];
+        manager
+            .start_lockstep(id, depots(&factions), &factions)
+            .unwrap();
+        manager.submit_commands(id, "alice", 0, Vec::new()).unwrap();
+
+        // 100 ticks at 1000 a second
+        let soon = Instant::now() + Duration::from_millis(50);
+        assert!(manager.drop_stalled(soon).is_empty());
+        let later = Instant::now() + Duration::from_millis(150);
+        manager.set_paused(id, true).unwrap();
+        assert!(manager.drop_stalled(later).is_empty(), "paused games wait");
+        manager.set_paused(id, false).unwrap();
+        let mut events = manager.subscribe(id).unwrap();
+        assert_eq!(manager.drop_stalled(later), vec![(id, "bob".to_string())]);
+        assert!(matches!(
+            events.try_recv(),
+            Ok(SessionEvent::Kicked { player: Some(player), .. }) if player == "bob"
+        ));
+        assert!(matches!(
+            events.try_recv(),
+            Ok(SessionEvent::Lockstep(LockstepEvent::Turn(turn))) if turn.tick == 0
+        ));
+
+        // Alice alone is not dropped: there'd be nobody left to play
+        let hours = Instant::now() + Duration::from_secs(3600);
+        assert!(manager.drop_stalled(hours).is_empty());
+
+        let hello = ClientHello::new("bob", Fingerprint::engine());
+        manager.rejoin(id, &hello, &tokens["bob"]).unwrap();
+        manager.submit_commands(id, "bob", 1, Vec::new()).unwrap();
+    }
+
+    #[test]
+    fn test_team_chat_reaches_only_teammates() {
+        let mut manager = SessionManager::new(&ServerConfig {
+            max_players: 3,
+            ..config(2)
+        });
+        let id = manager.open_lobby("2v1").unwrap();
+        let teams = [
+            ("alice", FactionId::Continuity),
+            ("bob", FactionId::Continuity),
+            ("carol", FactionId::Collegium),
+        ];
+        for (player, faction) in teams {
+            manager.join(id, player).unwrap();
+            manager.choose_faction(id, player, faction).unwrap();
+        }
+        manager.spectate(id, "dave").unwrap();
+        let mut events = manager.subscribe(id).unwrap();
+        let mut next_chat = || loop {
+            match events.try_recv().unwrap() {
+                SessionEvent::Chat(chat) => return chat,
+                _ => continue,
+            }
+        };
+
+        manager
+            .chat(id, "alice", ChatChannel::Team, " push mid ")
+            .unwrap();
+        let chat = next_chat();
+        assert_eq!(chat.to, Some(vec!["alice".to_string(), "bob".to_string()]));
+        assert!(
+            matches!(&chat.content, ChatContent::Message(m) if m.text == "push mid"),
+            "{chat:?}"
+        );
+        manager
+            .ping(
+                id,
+                "carol",
+                ChatChannel::All,
+                Vec2Fixed::ZERO,
+                PingKind::Attack,
+            )
+            .unwrap();
+        assert_eq!(next_chat().to, None);
+        manager.chat(id, "dave", ChatChannel::Team, "nice").unwrap();
+        assert_eq!(next_chat().to, Some(vec!["dave".to_string()]));
+
+        assert_eq!(
+            manager.chat(id, "alice", ChatChannel::All, "  "),
+            Err(SessionError::Chat(ChatError::Empty))
+        );
+        assert!(matches!(
+            manager.chat(id, "mallory", ChatChannel::All, "hi"),
+            Err(SessionError::Seat(SeatError::UnknownPlayer(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_ready_lobby_counts_down_and_launches() {
+        let mut manager = SessionManager::new(&ServerConfig {
+            countdown_secs: 60,
+            ..config(2)
+        });
+        let id = manager.open_lobby("skirmish").unwrap();
+        let mut events = manager.subscribe(id).unwrap();
+        manager.join(id, "alice").unwrap();
+        manager.join(id, "bob").unwrap();
+        manager
+            .choose_faction(id, "alice", FactionId::Continuity)
+            .unwrap();
+        manager
+            .choose_faction(id, "bob", FactionId::Collegium)
+            .unwrap();
+        manager.choose_start(id, "bob", Some(2)).unwrap();
+        assert_eq!(
+            manager.choose_start(id, "alice", Some(2)),
+            Err(SessionError::Seat(SeatError::StartTaken {
+                start: 2,
+                player: "bob".to_string()
+            }))
+        );
+        manager.set_ready(id, "alice", true).unwrap();
+        manager.set_ready(id, "bob", true).unwrap();
+        assert!(manager.lobby_state(id).unwrap().countdown_ms.is_some());
+
+        // Taking back a ready cancels the countdown
+        manager.set_ready(id, "bob", false).unwrap();
+        assert_eq!(manager.lobby_state(id).unwrap().countdown_ms, None);
+        manager.set_ready(id, "bob", true).unwrap();
+        assert!(manager.launch_due(Instant::now()).is_empty());
+        let later = Instant::now() + Duration::from_secs(61);
+        assert_eq!(manager.launch_due(later), vec![id]);
+        assert_eq!(manager.info(id).unwrap().state, SessionState::Running);
+        assert_eq!(
+            manager.set_ready(id, "alice", false),
+            Err(SessionError::AlreadyStarted(id))
+        );
+
+        let launch = loop {
+            match events.recv().await.unwrap() {
+                SessionEvent::Launched(launch) => break launch,
+                SessionEvent::Lobby(state) => assert_eq!(state.session, id),
+                other => panic!("unexpected {other:?}"),
+            }
+        };
+        let starts: Vec<_> = launch.seats.iter().map(|seat| seat.start).collect();
+        assert_eq!(starts, vec![Some(0), Some(2)]);
+        let sim = skirmish(
+            launch.seed,
+            &[(FactionId::Continuity, 0), (FactionId::Collegium, 2)],
+        );
+        assert_eq!(sim.entities().len(), 2);
+        let snapshot = manager
+            .snapshot_for(id, &Viewer::Omniscient)
+            .unwrap()
+            .unwrap();
+        assert_eq!(snapshot.entities.len(), 2);
+        manager.submit_commands(id, "alice", 0, Vec::new()).unwrap();
+    }
+
+    #[test]
+    fn test_spectators_watch_without_playing() {
+        let mut manager = SessionManager::new(&ServerConfig {
+            max_spectators: 1,
+            ..config(2)
+        });
+        let id = manager.open_lobby("cast").unwrap();
+        manager.join(id, "alice").unwrap();
+        manager.spectate(id, "caster").unwrap();
+        assert_eq!(
+            manager.spectate(id, "alice"),
+            Err(SessionError::SpectatorsFull { id, max: 1 })
+        );
+        manager.join(id, "bob").unwrap();
+        assert_eq!(manager.info(id).unwrap().players, vec!["alice", "bob"]);
+        assert_eq!(manager.lobby_state(id).unwrap().spectators, vec!["caster"]);
+        assert_eq!(manager.spectator_snapshot(id, "caster"), Ok(None));
+
+        let both = [FactionId::Continuity, FactionId::Collegium];
+        manager.start_lockstep(id, depots(&both), &both).unwrap();
+        assert_eq!(
+            manager.submit_commands(id, "caster", 0, Vec::new()),
+            Err(SessionError::Spectator("caster".to_string()))
+        );
+        assert_eq!(
+            manager.confirm_tick(id, "caster", 0, 0),
+            Err(SessionError::Spectator("caster".to_string()))
+        );
+        let snapshot = manager.spectator_snapshot(id, "caster").unwrap().unwrap();
+        assert_eq!(snapshot.entities.len(), 2);
+        assert!(matches!(
+            manager.spectator_snapshot(id, "alice"),
+            Err(SessionError::Seat(SeatError::UnknownPlayer(_)))
+        ));
+
+        // Spectators may leave a running game; players may not
+        manager.leave(id, "caster").unwrap();
+        assert!(manager.info(id).unwrap().spectators.is_empty());
+        assert_eq!(
+            manager.leave(id, "alice"),
+            Err(SessionError::AlreadyStarted(id))
+        );
+    }
+}
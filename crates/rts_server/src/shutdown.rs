@@ -0,0 +1,167 @@
+//! Graceful shutdown.
+//!
+//! On SIGTERM (or Ctrl-C) the server stops reporting ready, keeps ticking
+//! running games for a grace period so nearly-finished matches can end
+//! normally, then checkpoints whatever is still running. Container
+//! runtimes send SIGTERM and wait before SIGKILL, so the grace period
+//! should stay below the runtime's stop timeout.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::game::HostedGame;
+
+/// Resolve when the process is asked to stop.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::warn!(error = %e, "Failed to listen for Ctrl-C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => tracing::info!("Received Ctrl-C"),
+        () = terminate => tracing::info!("Received SIGTERM"),
+    }
+}
+
+/// What happened to each game during shutdown.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DrainReport {
+    /// Games that finished within the grace period.
+    pub finished: Vec<String>,
+    /// Checkpoint files written for games still running.
+    pub checkpointed: Vec<PathBuf>,
+    /// Games that were still running and could not be saved.
+    pub abandoned: Vec<String>,
+}
+
+/// Let running games play out for up to `grace`, then checkpoint the rest
+/// into `checkpoint_dir`.
+///
+/// Games keep ticking at `tick_rate` so they end exactly as they would
+/// have. Without a checkpoint directory, unfinished games are abandoned.
+pub async fn drain_games(
+    games: Vec<HostedGame>,
+    tick_rate: u32,
+    grace: Duration,
+    checkpoint_dir: Option<&Path>,
+) -> DrainReport {
+    let mut report = DrainReport::default();
+    let mut running = games;
+    let mut remaining_ticks = grace.as_millis() * u128::from(tick_rate) / 1000;
+    let mut interval = tokio::time::interval(Duration::from_secs(1) / tick_rate.max(1));
+
+    loop {
+        let (finished, still_running): (Vec<_>, Vec<_>) =
+            running.into_iter().partition(HostedGame::is_finished);
+        report
+            .finished
+            .extend(finished.iter().map(|game| game.id().to_string()));
+        running = still_running;
+        if running.is_empty() || remaining_ticks == 0 {
+            break;
+        }
+
+        interval.tick().await;
+        for game in &mut running {
+            game.tick();
+        }
+        remaining_ticks -= 1;
+    }
+
+    for game in running {
+        let saved = checkpoint_dir.map(|dir| game.checkpoint(dir));
+        match saved {
+            Some(Ok(path)) => {
+                tracing::info!(game = game.id(), path = %path.display(), "Checkpointed game");
+                report.checkpointed.push(path);
+            }
+            Some(Err(e)) => {
+                tracing::error!(game = game.id(), error = %e, "Checkpoint failed");
+                report.abandoned.push(game.id().to_string());
+            }
+            None => {
+                tracing::warn!(game = game.id(), "No checkpoint directory; abandoning game");
+                report.abandoned.push(game.id().to_string());
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rts_core::autosave::Autosave;
+    use rts_core::components::FactionMember;
+    use rts_core::factions::FactionId;
+    use rts_core::math::{Fixed, Vec2Fixed};
+    use rts_core::simulation::{EntitySpawnParams, Simulation};
+
+    /// A game with depots for both sides, so it never ends on its own.
+    fn stalemate() -> Simulation {
+        let mut sim = Simulation::new();
+        for (faction, x) in [(FactionId::Continuity, 0), (FactionId::Collegium, 500)] {
+            sim.spawn_entity(EntitySpawnParams {
+                position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(0))),
+                health: Some(1000),
+                is_depot: true,
+                faction: Some(FactionMember::new(faction, 0)),
+                ..Default::default()
+            });
+        }
+        sim
+    }
+
+    #[tokio::test]
+    async fn test_drain_finishes_decided_games_and_checkpoints_the_rest() {
+        let dir = std::env::temp_dir().join(format!("rts_drain_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // One depot left: decided on its first tick
+        let mut decided = stalemate();
+        let collegium_depot = decided.entities().sorted_ids()[1];
+        decided.despawn_entity(collegium_depot).unwrap();
+        let games = vec![
+            HostedGame::new("decided", decided),
+            HostedGame::new("running", stalemate()).with_map("delta"),
+        ];
+
+        let report = drain_games(games, 20, Duration::from_millis(200), Some(&dir)).await;
+        assert_eq!(report.finished, vec!["decided".to_string()]);
+        assert_eq!(report.checkpointed, vec![dir.join("running.autosave")]);
+        assert!(report.abandoned.is_empty());
+
+        let resumed =
+            HostedGame::resume(&Autosave::load(&report.checkpointed[0]).unwrap()).unwrap();
+        assert_eq!(resumed.id(), "running");
+        assert_eq!(resumed.map(), Some("delta"));
+        assert_eq!(resumed.simulation().get_tick(), 4);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_drain_without_checkpoint_dir_abandons_running_games() {
+        let games = vec![HostedGame::new("running", stalemate())];
+        let report = drain_games(games, 20, Duration::ZERO, None).await;
+        assert_eq!(report.abandoned, vec!["running".to_string()]);
+    }
+}
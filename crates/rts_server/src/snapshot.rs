@@ -0,0 +1,141 @@
+//! Per-viewer state snapshots that never leak hidden information.
+//!
+//! Lockstep clients normally rebuild state from commands, but reconnecting
+//! players and joining observers need a snapshot to start from. Sending the
+//! full simulation would hand them everything under the fog of war, so the
+//! server builds each snapshot for a [`Viewer`]: a player or team sees its
+//! own entities plus whatever [`Simulation::is_visible_to`] says it can see
+//! right now, which already accounts for stealth and detectors. Only a
+//! [`Viewer::Omniscient`] observer, such as a tournament caster on a delayed
+//! feed, gets everything.
+
+use rts_core::api::{EntitySnapshot, StateSnapshot};
+use rts_core::factions::FactionId;
+use rts_core::simulation::{EntityRef, Simulation};
+
+/// Who a snapshot is being built for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Viewer {
+    /// A player, seeing with their faction's vision.
+    Player(FactionId),
+    /// An observer tied to one or more factions, seeing with their
+    /// combined vision (a team's spectator seat).
+    Observer(Vec<FactionId>),
+    /// An observer with full map vision.
+    Omniscient,
+}
+
+impl Viewer {
+    fn factions(&self) -> &[FactionId] {
+        match self {
+            Self::Player(faction) => std::slice::from_ref(faction),
+            Self::Observer(factions) => factions,
+            Self::Omniscient => &[],
+        }
+    }
+
+    /// Whether this viewer may see `entity`.
+    #[must_use]
+    pub fn can_see(&self, sim: &Simulation, entity: EntityRef<'_>) -> bool {
+        if *self == Self::Omniscient {
+            return true;
+        }
+        let factions = self.factions();
+        let owned = entity
+            .faction
+            .is_some_and(|member| factions.contains(&member.faction));
+        owned
+            || factions
+                .iter()
+                .any(|&faction| sim.is_visible_to(faction, entity.id))
+    }
+}
+
+/// Build the snapshot `viewer` is allowed to receive.
+///
+/// Entities the viewer cannot currently see are left out entirely, not
+/// blanked, so their count and IDs don't leak either. The state hash still
+/// covers the full simulation so lockstep clients can verify it once they
+/// have caught up.
+#[must_use]
+pub fn filtered_snapshot(sim: &Simulation, viewer: &Viewer) -> StateSnapshot {
+    let entities = sim
+        .entities()
+        .sorted_ids()
+        .into_iter()
+        .filter_map(|id| sim.get_entity(id))
+        .filter(|entity| viewer.can_see(sim, *entity))
+        .filter_map(EntitySnapshot::from_entity)
+        .collect();
+
+    StateSnapshot::new(sim.get_tick(), sim.state_hash(), entities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rts_core::components::{CombatStats, EntityId, FactionMember};
+    use rts_core::math::{Fixed, Vec2Fixed};
+    use rts_core::simulation::EntitySpawnParams;
+
+    fn spawn(sim: &mut Simulation, faction: FactionId, x: i32, stealthed: bool) -> EntityId {
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(x), Fixed::from_num(0))),
+            health: Some(100),
+            combat_stats: Some(CombatStats::new(5, Fixed::from_num(50), 20)),
+            faction: Some(FactionMember::new(faction, 0)),
+            is_stealthed: stealthed,
+            ..Default::default()
+        })
+    }
+
+    fn ids(snapshot: &StateSnapshot) -> Vec<EntityId> {
+        snapshot.entities.iter().map(|e| e.id).collect()
+    }
+
+    #[test]
+    fn test_snapshots_respect_fog_and_stealth() {
+        let mut sim = Simulation::new();
+        let scout = spawn(&mut sim, FactionId::Continuity, 0, false);
+        let spotted = spawn(&mut sim, FactionId::Collegium, 80, false);
+        let cloaked = spawn(&mut sim, FactionId::Collegium, 90, true);
+        let fogged = spawn(&mut sim, FactionId::Collegium, 2000, false);
+
+        let player = filtered_snapshot(&sim, &Viewer::Player(FactionId::Continuity));
+        assert_eq!(ids(&player), vec![scout, spotted]);
+
+        // Collegium sees all of its own units, stealthed or not
+        let enemy = filtered_snapshot(&sim, &Viewer::Player(FactionId::Collegium));
+        assert_eq!(ids(&enemy), vec![scout, spotted, cloaked, fogged]);
+
+        let caster = filtered_snapshot(&sim, &Viewer::Omniscient);
+        assert_eq!(caster.entities.len(), 4);
+        assert_eq!(caster.state_hash, player.state_hash);
+
+        // Detection reveals the cloaked unit
+        sim.spawn_entity(EntitySpawnParams {
+            position: Some(Vec2Fixed::new(Fixed::from_num(40), Fixed::from_num(0))),
+            faction: Some(FactionMember::new(FactionId::Continuity, 0)),
+            detection_range: Some(Fixed::from_num(100)),
+            ..Default::default()
+        });
+        let player = filtered_snapshot(&sim, &Viewer::Player(FactionId::Continuity));
+        assert!(ids(&player).contains(&cloaked));
+        assert!(!ids(&player).contains(&fogged));
+    }
+
+    #[test]
+    fn test_team_observer_sees_combined_vision_only() {
+        let mut sim = Simulation::new();
+        let a = spawn(&mut sim, FactionId::Continuity, 0, false);
+        let b = spawn(&mut sim, FactionId::Tinkers, 1000, false);
+        let near_b = spawn(&mut sim, FactionId::Collegium, 1050, false);
+        let _far = spawn(&mut sim, FactionId::Collegium, 5000, false);
+
+        let team = Viewer::Observer(vec![FactionId::Continuity, FactionId::Tinkers]);
+        assert_eq!(ids(&filtered_snapshot(&sim, &team)), vec![a, b, near_b]);
+        assert!(filtered_snapshot(&sim, &Viewer::Observer(Vec::new()))
+            .entities
+            .is_empty());
+    }
+}
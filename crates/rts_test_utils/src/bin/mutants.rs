@@ -0,0 +1,65 @@
+//! Time-boxed mutation testing over the curated core systems.
+//!
+//! ```text
+//! cargo run -p rts_test_utils --bin mutants -- [--budget-mins N] [--timeout-secs N] [--output DIR]
+//! ```
+//!
+//! Writes `report.md` to the output directory (default `target/mutants`).
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs, process};
+
+use rts_test_utils::mutation::{run_mutants, MutationBudget, CURATED_TARGETS};
+
+fn main() {
+    let mut budget = MutationBudget::default();
+    let mut output = PathBuf::from("target/mutants");
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| usage(&format!("{arg} needs a value")));
+        match arg.as_str() {
+            "--budget-mins" => budget.total = Duration::from_secs(parse(&arg, &value) * 60),
+            "--timeout-secs" => budget.per_mutant = Duration::from_secs(parse(&arg, &value)),
+            "--output" => output = PathBuf::from(value),
+            _ => usage(&format!("unknown argument {arg}")),
+        }
+    }
+
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let output = root.join(output);
+    let report = match run_mutants(&root, &output, CURATED_TARGETS, &budget) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("failed to run cargo-mutants (is it installed?): {e}");
+            process::exit(1);
+        }
+    };
+
+    let path = output.join("report.md");
+    if let Err(e) = fs::write(&path, report.to_markdown()) {
+        eprintln!("failed to write {}: {e}", path.display());
+        process::exit(1);
+    }
+    println!(
+        "{} missed / {} tested, report at {}",
+        report.missed.len(),
+        report.tested(),
+        path.display()
+    );
+}
+
+fn parse(arg: &str, value: &str) -> u64 {
+    value
+        .parse()
+        .unwrap_or_else(|_| usage(&format!("{arg} expects a number, got {value}")))
+}
+
+fn usage(error: &str) -> ! {
+    eprintln!("{error}");
+    eprintln!("usage: mutants [--budget-mins N] [--timeout-secs N] [--output DIR]");
+    process::exit(2);
+}
@@ -5,6 +5,7 @@
 //! - Fixture spawning helpers
 //! - Benchmark scenarios
 //! - Property-based testing strategies
+//! - Time-boxed mutation testing
 
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
@@ -12,6 +13,7 @@
 pub mod balance;
 pub mod determinism;
 pub mod fixtures;
+pub mod mutation;
 
 /// Re-export proptest for convenience.
 pub use proptest;
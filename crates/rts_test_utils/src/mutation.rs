@@ -0,0 +1,398 @@
+//! Time-boxed mutation testing for core systems.
+//!
+//! Wraps [cargo-mutants](https://mutants.rs) so we can ask "which branches
+//! of the combat math and command handling would survive a bug?" without
+//! mutating the whole workspace. The run is scoped to a curated list of
+//! functions in `rts_core::systems`, `rts_core::combat` and
+//! `rts_core::production`, and is killed once its time budget is spent.
+//!
+//! cargo-mutants writes its results incrementally to `mutants.out/`, so a
+//! run cut short by the budget still yields a partial [`MutationReport`].
+//!
+//! # Usage
+//!
+//! ```text
+//! cargo install cargo-mutants
+//! cargo run -p rts_test_utils --bin mutants -- --budget-mins 30
+//! ```
+//!
+//! Missed mutants are the interesting output: each one is a behavior the
+//! test suite does not pin down.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Package that mutation runs are scoped to.
+pub const MUTATION_PACKAGE: &str = "rts_core";
+
+/// A function selected for mutation testing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MutationTarget {
+    /// Source file, relative to the workspace root.
+    pub file: &'static str,
+    /// Function name as cargo-mutants reports it (`Type::method` for methods).
+    pub function: &'static str,
+}
+
+impl MutationTarget {
+    const fn new(file: &'static str, function: &'static str) -> Self {
+        Self { file, function }
+    }
+}
+
+const SYSTEMS: &str = "crates/rts_core/src/systems.rs";
+const COMBAT: &str = "crates/rts_core/src/combat.rs";
+const PRODUCTION: &str = "crates/rts_core/src/production.rs";
+
+/// Curated functions with branch-heavy logic worth mutating.
+///
+/// Constructors and plain accessors are left out: mutating them mostly
+/// produces noise that any test touching the type already catches.
+pub const CURATED_TARGETS: &[MutationTarget] = &[
+    // Command transitions and combat resolution.
+    MutationTarget::new(SYSTEMS, "command_processing_system"),
+    MutationTarget::new(SYSTEMS, "movement_system"),
+    MutationTarget::new(SYSTEMS, "health_system"),
+    MutationTarget::new(SYSTEMS, "calculate_damage"),
+    MutationTarget::new(SYSTEMS, "combat_system"),
+    MutationTarget::new(SYSTEMS, "projectile_system"),
+    MutationTarget::new(SYSTEMS, "auto_attack_system"),
+    // Resistance and penetration math.
+    MutationTarget::new(COMBAT, "WeaponSize::tracking_modifier_vs"),
+    MutationTarget::new(COMBAT, "ExtendedDamageType::effectiveness_vs"),
+    MutationTarget::new(COMBAT, "ResistanceStats::effective_resistance"),
+    MutationTarget::new(COMBAT, "ResistanceStats::resistance_fraction"),
+    MutationTarget::new(COMBAT, "WeaponStats::with_rolled_damage"),
+    MutationTarget::new(COMBAT, "WeaponStats::penetration_fraction"),
+    MutationTarget::new(COMBAT, "calculate_resistance_damage"),
+    MutationTarget::new(COMBAT, "convert_flat_armor_to_resistance"),
+    // Queue bookkeeping and build progress.
+    MutationTarget::new(PRODUCTION, "ProductionItem::tick"),
+    MutationTarget::new(PRODUCTION, "ProductionItem::percentage"),
+    MutationTarget::new(PRODUCTION, "ProductionQueue::add"),
+    MutationTarget::new(PRODUCTION, "ProductionQueue::cancel"),
+    MutationTarget::new(PRODUCTION, "ProductionQueue::cancel_unit_type"),
+    MutationTarget::new(PRODUCTION, "ProductionQueue::complete"),
+    MutationTarget::new(PRODUCTION, "Building::tick_construction"),
+    MutationTarget::new(PRODUCTION, "BlueprintRegistry::can_building_produce"),
+    MutationTarget::new(PRODUCTION, "production_system"),
+    MutationTarget::new(PRODUCTION, "queue_production"),
+    MutationTarget::new(PRODUCTION, "cancel_production"),
+];
+
+/// Time limits for a mutation run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MutationBudget {
+    /// Wall-clock limit for the whole run.
+    pub total: Duration,
+    /// Test timeout for a single mutant.
+    pub per_mutant: Duration,
+}
+
+impl Default for MutationBudget {
+    fn default() -> Self {
+        Self {
+            total: Duration::from_secs(30 * 60),
+            per_mutant: Duration::from_secs(120),
+        }
+    }
+}
+
+/// Build the `cargo` arguments that mutate only `targets`.
+#[must_use]
+pub fn cargo_mutants_args(targets: &[MutationTarget], budget: &MutationBudget) -> Vec<String> {
+    let mut args = vec![
+        "mutants".to_string(),
+        "--package".to_string(),
+        MUTATION_PACKAGE.to_string(),
+        "--timeout".to_string(),
+        budget.per_mutant.as_secs().max(1).to_string(),
+        "--no-shuffle".to_string(),
+    ];
+
+    let mut files: Vec<&str> = targets.iter().map(|t| t.file).collect();
+    files.sort_unstable();
+    files.dedup();
+    for file in files {
+        args.push("--file".to_string());
+        args.push(file.to_string());
+    }
+
+    // cargo-mutants names mutants "replace <fn> -> ..." or "... in <fn>".
+    for target in targets {
+        args.push("--re".to_string());
+        args.push(format!(r"(replace|in) {}\b", target.function));
+    }
+
+    args
+}
+
+/// Outcome category of a single mutant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MutantOutcome {
+    /// A test failed, so the mutation was detected.
+    Caught,
+    /// All tests passed with the mutation in place.
+    Missed,
+    /// Tests did not finish within the per-mutant timeout.
+    Timeout,
+    /// The mutated code did not compile.
+    Unviable,
+}
+
+impl MutantOutcome {
+    const ALL: [Self; 4] = [Self::Caught, Self::Missed, Self::Timeout, Self::Unviable];
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Caught => "caught.txt",
+            Self::Missed => "missed.txt",
+            Self::Timeout => "timeout.txt",
+            Self::Unviable => "unviable.txt",
+        }
+    }
+}
+
+/// A mutant reported by cargo-mutants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mutant {
+    /// Source file the mutation was applied to.
+    pub file: String,
+    /// Line of the mutation.
+    pub line: u32,
+    /// Description, e.g. `replace < with <= in combat_system`.
+    pub description: String,
+}
+
+impl Mutant {
+    /// Parse a cargo-mutants listing line (`file:line:col: description`).
+    #[must_use]
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut parts = line.trim().splitn(4, ':');
+        let file = parts.next()?.to_string();
+        let line_no = parts.next()?.parse().ok()?;
+        let rest = parts.next()?;
+        // The column is optional in older cargo-mutants releases.
+        let description = match rest.trim().parse::<u32>() {
+            Ok(_) => parts.next()?,
+            Err(_) => rest,
+        };
+        Some(Self {
+            file,
+            line: line_no,
+            description: description.trim().to_string(),
+        })
+    }
+}
+
+/// Results of a (possibly partial) mutation run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MutationReport {
+    /// Mutants detected by the test suite.
+    pub caught: Vec<Mutant>,
+    /// Mutants no test detected: untested behaviors.
+    pub missed: Vec<Mutant>,
+    /// Mutants whose tests hung.
+    pub timeouts: Vec<Mutant>,
+    /// Mutants that failed to build.
+    pub unviable: Vec<Mutant>,
+    /// Whether the run was stopped by the time budget.
+    pub budget_exhausted: bool,
+}
+
+impl MutationReport {
+    /// Load the outcome listings from a cargo-mutants output directory.
+    ///
+    /// Missing listings are treated as empty, since cargo-mutants only
+    /// creates them once a mutant lands in that category.
+    pub fn from_output_dir(dir: &Path) -> io::Result<Self> {
+        let mut report = Self::default();
+        for outcome in MutantOutcome::ALL {
+            let path = dir.join(outcome.file_name());
+            let contents = match fs::read_to_string(&path) {
+                Ok(contents) => contents,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            let mutants = contents.lines().filter_map(Mutant::parse).collect();
+            match outcome {
+                MutantOutcome::Caught => report.caught = mutants,
+                MutantOutcome::Missed => report.missed = mutants,
+                MutantOutcome::Timeout => report.timeouts = mutants,
+                MutantOutcome::Unviable => report.unviable = mutants,
+            }
+        }
+        Ok(report)
+    }
+
+    /// Number of viable mutants tested.
+    #[must_use]
+    pub fn tested(&self) -> usize {
+        self.caught.len() + self.missed.len() + self.timeouts.len()
+    }
+
+    /// Fraction of viable mutants detected (timeouts count as detected).
+    #[must_use]
+    pub fn mutation_score(&self) -> f64 {
+        if self.tested() == 0 {
+            return 1.0;
+        }
+        (self.caught.len() + self.timeouts.len()) as f64 / self.tested() as f64
+    }
+
+    /// Render a markdown report listing missed mutants grouped by file.
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Mutation Testing Report\n\n");
+        if self.budget_exhausted {
+            out.push_str("> Time budget exhausted; results are partial.\n\n");
+        }
+        let _ = writeln!(out, "| Outcome | Count |\n|---|---|");
+        let _ = writeln!(out, "| Caught | {} |", self.caught.len());
+        let _ = writeln!(out, "| Missed | {} |", self.missed.len());
+        let _ = writeln!(out, "| Timeout | {} |", self.timeouts.len());
+        let _ = writeln!(out, "| Unviable | {} |", self.unviable.len());
+        let _ = writeln!(
+            out,
+            "\nMutation score: {:.1}%\n",
+            self.mutation_score() * 100.0
+        );
+
+        if self.missed.is_empty() {
+            out.push_str("No untested behaviors found.\n");
+            return out;
+        }
+
+        out.push_str("## Untested behaviors\n");
+        let mut missed: Vec<&Mutant> = self.missed.iter().collect();
+        missed.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+        let mut current_file = None;
+        for mutant in missed {
+            if current_file != Some(&mutant.file) {
+                let _ = writeln!(out, "\n### `{}`\n", mutant.file);
+                current_file = Some(&mutant.file);
+            }
+            let _ = writeln!(out, "- line {}: {}", mutant.line, mutant.description);
+        }
+        out
+    }
+}
+
+/// Run cargo-mutants over `targets` from `workspace_root`, stopping once
+/// the budget's total time is spent.
+///
+/// Results are written to `output_dir/mutants.out`.
+pub fn run_mutants(
+    workspace_root: &Path,
+    output_dir: &Path,
+    targets: &[MutationTarget],
+    budget: &MutationBudget,
+) -> io::Result<MutationReport> {
+    fs::create_dir_all(output_dir)?;
+    let mut args = cargo_mutants_args(targets, budget);
+    args.push("--output".to_string());
+    args.push(output_dir.display().to_string());
+
+    let mut child = Command::new("cargo")
+        .args(&args)
+        .current_dir(workspace_root)
+        .stdout(Stdio::null())
+        .spawn()?;
+
+    let deadline = Instant::now() + budget.total;
+    let mut budget_exhausted = false;
+    loop {
+        if child.try_wait()?.is_some() {
+            break;
+        }
+        if Instant::now() >= deadline {
+            tracing::warn!("Mutation budget of {:?} exhausted", budget.total);
+            child.kill()?;
+            child.wait()?;
+            budget_exhausted = true;
+            break;
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+
+    let mut report = MutationReport::from_output_dir(&mutants_out_dir(output_dir))?;
+    report.budget_exhausted = budget_exhausted;
+    Ok(report)
+}
+
+/// Directory cargo-mutants writes its listings into under `output_dir`.
+#[must_use]
+pub fn mutants_out_dir(output_dir: &Path) -> PathBuf {
+    output_dir.join("mutants.out")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../..")
+    }
+
+    #[test]
+    fn test_curated_targets_exist_in_source() {
+        for target in CURATED_TARGETS {
+            let source = fs::read_to_string(workspace_root().join(target.file))
+                .unwrap_or_else(|e| panic!("{}: {e}", target.file));
+            let name = target.function.rsplit("::").next().unwrap();
+            assert!(
+                source.contains(&format!("fn {name}")),
+                "{} not found in {}",
+                target.function,
+                target.file
+            );
+        }
+    }
+
+    #[test]
+    fn test_args_scope_package_files_and_functions() {
+        let args = cargo_mutants_args(CURATED_TARGETS, &MutationBudget::default());
+        assert_eq!(&args[..3], ["mutants", "--package", MUTATION_PACKAGE]);
+        assert_eq!(args.iter().filter(|a| *a == "--file").count(), 3);
+        assert_eq!(
+            args.iter().filter(|a| *a == "--re").count(),
+            CURATED_TARGETS.len()
+        );
+        assert!(args.contains(&r"(replace|in) calculate_damage\b".to_string()));
+    }
+
+    #[test]
+    fn test_report_from_partial_output() {
+        let dir = std::env::temp_dir().join(format!("rts_mutants_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("caught.txt"),
+            "crates/rts_core/src/systems.rs:300:9: replace calculate_damage -> Fixed with Default::default()\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("missed.txt"),
+            "crates/rts_core/src/combat.rs:410:20: replace < with <= in calculate_resistance_damage\n\
+             crates/rts_core/src/combat.rs:220: replace ResistanceStats::effective_resistance -> Fixed with Default::default()\n",
+        )
+        .unwrap();
+
+        let report = MutationReport::from_output_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report.caught.len(), 1);
+        assert_eq!(report.missed.len(), 2);
+        assert!(report.timeouts.is_empty());
+        assert_eq!(report.missed[1].line, 220);
+        assert!((report.mutation_score() - 1.0 / 3.0).abs() < 1e-9);
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("### `crates/rts_core/src/combat.rs`"));
+        assert!(markdown.contains("- line 410: replace < with <= in calculate_resistance_damage"));
+    }
+}
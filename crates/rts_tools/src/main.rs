@@ -18,28 +18,83 @@ enum Commands {
         /// Path to data directory
         #[arg(default_value = "assets/data")]
         path: String,
+
+        /// Print the report as JSON on stdout, for CI
+        #[arg(long)]
+        json: bool,
+
+        /// Also check the AI strategy files in this directory
+        #[arg(long)]
+        strategies: Option<String>,
     },
 }
 
 fn main() {
     // Initialize tracing
     tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
         .with(tracing_subscriber::EnvFilter::from_default_env())
         .init();
 
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Validate { path } => {
+        Commands::Validate {
+            path,
+            json,
+            strategies,
+        } => {
             tracing::info!("Validating data files in: {path}");
-            match rts_tools::validate::validate_data_directory(std::path::Path::new(&path)) {
-                Ok(()) => tracing::info!("Validation passed"),
-                Err(e) => {
-                    tracing::error!("Validation failed: {e}");
-                    std::process::exit(1);
+            let mut report =
+                match rts_tools::validate::check_data_directory(std::path::Path::new(&path)) {
+                    Ok(report) => report,
+                    Err(e) => {
+                        tracing::error!("Validation failed: {e}");
+                        std::process::exit(1);
+                    }
+                };
+            if let Some(dir) = strategies {
+                tracing::info!("Validating strategy files in: {dir}");
+                match rts_tools::validate::check_strategy_directory(std::path::Path::new(&dir)) {
+                    Ok(strategy_report) => {
+                        report = rts_tools::validate::ValidationReport::new(
+                            report.files + strategy_report.files,
+                            report
+                                .diagnostics
+                                .into_iter()
+                                .chain(strategy_report.diagnostics)
+                                .collect(),
+                        );
+                    }
+                    Err(e) => {
+                        tracing::error!("Validation failed: {e}");
+                        std::process::exit(1);
+                    }
+                }
+            }
+
+            if json {
+                match serde_json::to_string_pretty(&report) {
+                    Ok(text) => println!("{text}"),
+                    Err(e) => {
+                        tracing::error!("Failed to write report: {e}");
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                for diagnostic in &report.diagnostics {
+                    println!("{diagnostic}");
                 }
+                println!(
+                    "{} error(s), {} warning(s) in {} file(s)",
+                    report.errors, report.warnings, report.files
+                );
+            }
+
+            if report.has_errors() {
+                std::process::exit(1);
             }
+            tracing::info!("Validation passed");
         }
     }
 }
@@ -1,13 +1,1134 @@
 //! Data validation utilities.
+//!
+//! [`check_data_directory`] parses every faction file and checks what the
+//! data means, not just that it parses. Each problem is reported as a
+//! [`Diagnostic`] naming the file, line and field it is in:
+//!
+//! - references to units, buildings and technologies that don't exist,
+//!   such as dangling tech prerequisites
+//! - costs that are negative, or zero for anything a player can buy
+//! - IDs defined twice in a faction, or reused by another faction
+//! - tags outside [`KNOWN_TAGS`], with a suggestion when one is close
+//!
+//! [`check_strategy_directory`] does the same for AI strategy files,
+//! reporting parse errors and the problems [`Strategy::problems`] finds.
 
-use rts_core::error::Result;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use rts_core::data::FactionData;
+use rts_core::error::{GameError, Result};
+use rts_core::factions::MechanicData;
+use rts_core::strategy::Strategy;
+use serde::Serialize;
+
+/// Every tag the data may use. A tag outside this list is most likely a
+/// typo; add it here when it is a new tag on purpose.
+pub const KNOWN_TAGS: &[&str] = &[
+    "advanced",
+    "air",
+    "anti_air",
+    "anti_armor",
+    "anti_ground",
+    "aoe",
+    "area_denial",
+    "artillery",
+    "assault",
+    "aura",
+    "battleline",
+    "boarding",
+    "bomber",
+    "buff",
+    "builder",
+    "capital",
+    "capture",
+    "cheap",
+    "combat",
+    "command",
+    "commander",
+    "conversion",
+    "defense",
+    "detection",
+    "disable",
+    "drone",
+    "economy",
+    "elite",
+    "energy_weapon",
+    "engineer",
+    "expansion",
+    "fast",
+    "floating",
+    "garrison",
+    "grapple",
+    "ground",
+    "gunship",
+    "harvester",
+    "headquarters",
+    "healer",
+    "healing",
+    "heavy",
+    "hidden",
+    "hover",
+    "infantry",
+    "influence",
+    "mech",
+    "mobile",
+    "mobile_base",
+    "mobile_workshop",
+    "modular",
+    "modules",
+    "navigation",
+    "network",
+    "organic",
+    "packable",
+    "paradrop",
+    "piracy",
+    "production",
+    "ranged",
+    "refinery",
+    "regenerating",
+    "repair",
+    "research",
+    "scout",
+    "shield",
+    "siege",
+    "signature",
+    "stealth",
+    "structure",
+    "super_heavy",
+    "superweapon",
+    "support",
+    "swarm",
+    "tech",
+    "temporary",
+    "tier3",
+    "trade",
+    "transport",
+    "trap",
+    "unique",
+    "upgrades",
+    "utility",
+    "vehicle",
+    "wall",
+    "weather_control",
+    "worker",
+];
+
+/// How serious a problem is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Worth a look, but the data still loads and plays.
+    Warning,
+    /// The data is wrong and must be fixed.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+/// A problem found in a data file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// How serious it is.
+    pub severity: Severity,
+    /// File it is in.
+    pub file: String,
+    /// Line it is on, counting from 1, when it could be found.
+    pub line: Option<usize>,
+    /// Field it is in, e.g. `units[security_team].cost`; None for problems
+    /// with the file as a whole.
+    pub field: Option<String>,
+    /// What is wrong.
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+        write!(f, ": {}: ", self.severity)?;
+        if let Some(field) = &self.field {
+            write!(f, "{field}: ")?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// Everything [`check_data_directory`] or [`check_strategy_directory`]
+/// found.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ValidationReport {
+    /// Files checked.
+    pub files: usize,
+    /// Diagnostics that are errors.
+    pub errors: usize,
+    /// Diagnostics that are warnings.
+    pub warnings: usize,
+    /// Problems found, by file and line.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    /// Report on `files` files with the given problems.
+    #[must_use]
+    pub fn new(files: usize, mut diagnostics: Vec<Diagnostic>) -> Self {
+        diagnostics.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+        let errors = diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .count();
+        Self {
+            files,
+            errors,
+            warnings: diagnostics.len() - errors,
+            diagnostics,
+        }
+    }
+
+    /// Whether any problem is an error.
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        self.errors > 0
+    }
+}
 
 /// Validate all RON data files in a directory.
 ///
+/// Runs [`check_data_directory`] and logs what it finds.
+///
+/// # Errors
+///
+/// Returns an error if the directory can't be read or any data file has an
+/// error; warnings are logged but pass.
+pub fn validate_data_directory(path: &Path) -> Result<()> {
+    let report = check_data_directory(path)?;
+    for diagnostic in &report.diagnostics {
+        match diagnostic.severity {
+            Severity::Warning => tracing::warn!("{diagnostic}"),
+            Severity::Error => tracing::error!("{diagnostic}"),
+        }
+    }
+
+    if report.has_errors() {
+        Err(GameError::InvalidState(format!(
+            "{} faction data problem(s) found",
+            report.errors
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Check every faction file under `<path>/factions`.
+///
+/// A file that fails to parse is reported where the parser stopped and
+/// skipped; the rest are checked one by one and then against each other.
+///
 /// # Errors
 ///
-/// Returns an error if any data file fails validation.
-pub fn validate_data_directory(_path: &std::path::Path) -> Result<()> {
-    // TODO: Implement data validation
-    Ok(())
+/// Returns an error if the directory can't be read.
+pub fn check_data_directory(path: &Path) -> Result<ValidationReport> {
+    let factions_dir = path.join("factions");
+    let mut files: Vec<_> = std::fs::read_dir(&factions_dir)
+        .map_err(|e| GameError::FactionLoadError(format!("{}: {e}", factions_dir.display())))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "ron"))
+        .collect();
+    files.sort();
+
+    let mut diagnostics = Vec::new();
+    let mut sources = Vec::new();
+    for file in &files {
+        let name = file.display().to_string();
+        let content = std::fs::read_to_string(file).map_err(|e| GameError::DataParseError {
+            path: name.clone(),
+            message: e.to_string(),
+        })?;
+        match ron::from_str::<FactionData>(&content) {
+            Ok(data) => {
+                tracing::info!(
+                    faction = ?data.id,
+                    presets = data.starting_presets.len(),
+                    "Checked {name}"
+                );
+                sources.push((name, content, data));
+            }
+            Err(e) => diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                file: name,
+                line: Some(e.position.line),
+                field: None,
+                message: format!("failed to parse: {}", e.code),
+            }),
+        }
+    }
+
+    let mut checks: Vec<_> = sources
+        .iter()
+        .map(|(name, content, data)| FactionCheck::new(name, content, data))
+        .collect();
+    for check in &mut checks {
+        check.check();
+    }
+    check_across_factions(&mut checks);
+
+    diagnostics.extend(checks.into_iter().flat_map(|check| check.diagnostics));
+    Ok(ValidationReport::new(files.len(), diagnostics))
+}
+
+/// Check every RON or JSON strategy file in `path`.
+///
+/// Problems are errors, on the line of the field they are in when it can
+/// be found.
+///
+/// # Errors
+///
+/// Returns an error if the directory can't be read.
+pub fn check_strategy_directory(path: &Path) -> Result<ValidationReport> {
+    let mut files: Vec<_> = std::fs::read_dir(path)
+        .map_err(|e| GameError::DataParseError {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|ext| ext == "ron" || ext == "json")
+        })
+        .collect();
+    files.sort();
+
+    let mut diagnostics = Vec::new();
+    for file in &files {
+        let name = file.display().to_string();
+        let content = std::fs::read_to_string(file).map_err(|e| GameError::DataParseError {
+            path: name.clone(),
+            message: e.to_string(),
+        })?;
+        let error = |line, message| Diagnostic {
+            severity: Severity::Error,
+            file: name.clone(),
+            line,
+            field: None,
+            message,
+        };
+        let parsed = if file.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str::<Strategy>(&content)
+                .map_err(|e| error(Some(e.line()), format!("failed to parse: {e}")))
+        } else {
+            ron::from_str::<Strategy>(&content).map_err(|e| {
+                error(
+                    Some(e.position.line),
+                    format!("failed to parse: {}", e.code),
+                )
+            })
+        };
+        let strategy = match parsed {
+            Ok(strategy) => strategy,
+            Err(diagnostic) => {
+                diagnostics.push(diagnostic);
+                continue;
+            }
+        };
+
+        tracing::info!(strategy = %strategy.name, "Checked {name}");
+        for problem in strategy.problems() {
+            // The top-level field, e.g. `composition` of `composition[tank]`
+            let top = problem
+                .field
+                .split(['[', '.'])
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            let line = content
+                .lines()
+                .position(|line| {
+                    let line = line.trim_start().trim_start_matches('"');
+                    line.starts_with(&format!("{top}:")) || line.starts_with(&format!("{top}\":"))
+                })
+                .map(|i| i + 1);
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                file: name.clone(),
+                line,
+                field: Some(problem.field),
+                message: problem.message,
+            });
+        }
+    }
+    Ok(ValidationReport::new(files.len(), diagnostics))
+}
+
+/// Finds where items and fields sit in a RON file by searching its text,
+/// relying on the one-field-per-line layout the data files use.
+struct Source<'a> {
+    lines: Vec<&'a str>,
+}
+
+impl<'a> Source<'a> {
+    fn new(content: &'a str) -> Self {
+        Self {
+            lines: content.lines().collect(),
+        }
+    }
+
+    /// Lines (from 0) of every item in the `section` list whose `key` is
+    /// `value`, each with the line its fields end on.
+    fn items(&self, section: &str, key: &str, value: &str) -> Vec<(usize, usize)> {
+        let header = format!("{section}: [");
+        let Some(section_start) = self
+            .lines
+            .iter()
+            .position(|line| line.trim_start().starts_with(&header))
+        else {
+            return Vec::new();
+        };
+        let section_indent = indent(self.lines[section_start]);
+        let needle = format!("{key}: \"{value}\"");
+
+        let mut items = Vec::new();
+        for (i, line) in self.lines.iter().enumerate().skip(section_start + 1) {
+            let trimmed = line.trim();
+            if !trimmed.is_empty() && indent(line) <= section_indent {
+                break;
+            }
+            if trimmed.starts_with(&needle) {
+                let item_indent = indent(line);
+                let end = self.lines[i + 1..]
+                    .iter()
+                    .position(|l| !l.trim().is_empty() && indent(l) < item_indent)
+                    .map_or(self.lines.len(), |offset| i + 1 + offset);
+                items.push((i, end));
+            }
+        }
+        items
+    }
+
+    /// Line (from 1) of the first item in `section` whose `key` is
+    /// `value`, or of the first line in it `find` accepts.
+    fn line(
+        &self,
+        section: &str,
+        key: &str,
+        value: &str,
+        find: impl Fn(&str) -> bool,
+    ) -> Option<usize> {
+        let (start, end) = *self.items(section, key, value).first()?;
+        let found = (start..end).find(|&i| find(self.lines[i].trim_start()));
+        Some(found.unwrap_or(start) + 1)
+    }
+
+    /// Line (from 1) of `field` in the item of `section` with ID `id`.
+    fn field(&self, section: &str, id: &str, field: &str) -> Option<usize> {
+        let prefix = format!("{field}:");
+        self.line(section, "id", id, |line| line.starts_with(&prefix))
+    }
+
+    /// Line (from 1) where `value` is quoted in the item of `section` with
+    /// `key` `id`, after the key itself.
+    fn value(&self, section: &str, key: &str, id: &str, value: &str) -> Option<usize> {
+        let quoted = format!("\"{value}\"");
+        let own = format!("{key}: ");
+        self.line(section, key, id, |line| {
+            line.contains(&quoted) && !line.starts_with(&own)
+        })
+    }
+
+    /// Line (from 1) of the first line after the top-level `section`
+    /// header that quotes `value`.
+    fn in_section(&self, section: &str, value: &str) -> Option<usize> {
+        let header = format!("{section}: [");
+        let quoted = format!("\"{value}\"");
+        let start = self
+            .lines
+            .iter()
+            .position(|line| line.trim_start().starts_with(&header))?;
+        let found = self.lines[start..]
+            .iter()
+            .position(|line| line.contains(&quoted))
+            .map_or(start, |offset| start + offset);
+        Some(found + 1)
+    }
+
+    /// Line (from 1) of the faction's own ID.
+    fn faction_id(&self) -> Option<usize> {
+        self.lines
+            .iter()
+            .position(|line| line.trim_start().starts_with("id:"))
+            .map(|i| i + 1)
+    }
+}
+
+/// Leading spaces on a line.
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Semantic checks over one faction file.
+struct FactionCheck<'a> {
+    file: &'a str,
+    source: Source<'a>,
+    data: &'a FactionData,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> FactionCheck<'a> {
+    fn new(file: &'a str, content: &'a str, data: &'a FactionData) -> Self {
+        Self {
+            file,
+            source: Source::new(content),
+            data,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, severity: Severity, line: Option<usize>, field: String, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            file: self.file.to_string(),
+            line,
+            field: Some(field),
+            message,
+        });
+    }
+
+    fn error(&mut self, line: Option<usize>, field: String, message: String) {
+        self.push(Severity::Error, line, field, message);
+    }
+
+    fn check(&mut self) {
+        self.check_duplicates();
+        self.check_costs();
+        self.check_references();
+        self.check_tags();
+        self.check_weapons();
+    }
+
+    /// IDs defined more than once in this faction.
+    fn check_duplicates(&mut self) {
+        let data = self.data;
+        let sections: [(&str, &str, Vec<&str>); 4] = [
+            (
+                "units",
+                "id",
+                data.units.iter().map(|u| u.id.as_str()).collect(),
+            ),
+            (
+                "buildings",
+                "id",
+                data.buildings.iter().map(|b| b.id.as_str()).collect(),
+            ),
+            (
+                "technologies",
+                "id",
+                data.technologies.iter().map(|t| t.id.as_str()).collect(),
+            ),
+            (
+                "starting_presets",
+                "name",
+                data.starting_presets
+                    .iter()
+                    .map(|p| p.name.as_str())
+                    .collect(),
+            ),
+        ];
+        for (section, key, ids) in sections {
+            let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+            for id in ids {
+                *counts.entry(id).or_default() += 1;
+            }
+            for (id, count) in counts.into_iter().filter(|(_, count)| *count > 1) {
+                let line = self
+                    .source
+                    .items(section, key, id)
+                    .get(1)
+                    .map(|(start, _)| start + 1);
+                self.error(
+                    line,
+                    format!("{section}[{id}]"),
+                    format!("'{id}' is defined {count} times"),
+                );
+            }
+        }
+    }
+
+    /// Extra weapons on units without a main weapon, or named twice.
+    fn check_weapons(&mut self) {
+        let data = self.data;
+        for unit in data.units.iter().filter(|u| !u.weapons.is_empty()) {
+            let field = format!("units[{}].weapons", unit.id);
+            if unit.combat.is_none() {
+                let line = self.source.field("units", &unit.id, "weapons");
+                self.error(
+                    line,
+                    field.clone(),
+                    "extra weapons need combat stats for the main weapon".to_string(),
+                );
+            }
+            for (i, weapon) in unit.weapons.iter().enumerate() {
+                if unit.weapons[..i].iter().any(|w| w.id == weapon.id) {
+                    let line = self.source.value("units", "id", &unit.id, &weapon.id);
+                    self.error(
+                        line,
+                        field.clone(),
+                        format!("weapon '{}' is defined twice", weapon.id),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Costs that are negative, or zero for something a player can buy.
+    fn check_costs(&mut self) {
+        let data = self.data;
+        for unit in &data.units {
+            if unit.cost == 0 && !unit.produced_at.is_empty() {
+                let line = self.source.field("units", &unit.id, "cost");
+                self.error(
+                    line,
+                    format!("units[{}].cost", unit.id),
+                    "is 0 but the unit can be produced".to_string(),
+                );
+            }
+        }
+        for building in &data.buildings {
+            let starting = data
+                .starting_buildings
+                .iter()
+                .chain(
+                    data.starting_presets
+                        .iter()
+                        .flat_map(|p| &p.starting_buildings),
+                )
+                .any(|b| b.type_id == building.id);
+            let problem = match building.cost {
+                cost if cost < 0 => Some(format!("is negative ({cost})")),
+                0 if !starting => Some("is 0 but it is not a starting building".to_string()),
+                _ => None,
+            };
+            if let Some(message) = problem {
+                let line = self.source.field("buildings", &building.id, "cost");
+                self.error(line, format!("buildings[{}].cost", building.id), message);
+            }
+        }
+        for tech in &data.technologies {
+            if tech.cost <= 0 {
+                let line = self.source.field("technologies", &tech.id, "cost");
+                self.error(
+                    line,
+                    format!("technologies[{}].cost", tech.id),
+                    format!("must be positive, not {}", tech.cost),
+                );
+            }
+        }
+    }
+
+    /// Report `reference` in `section[id].field` unless `exists` finds it.
+    fn check_reference(
+        &mut self,
+        (section, id, field): (&str, &str, &str),
+        reference: &str,
+        what: &str,
+        exists: bool,
+    ) {
+        if exists {
+            return;
+        }
+        let key = if section == "starting_presets" {
+            "name"
+        } else {
+            "id"
+        };
+        let line = self.source.value(section, key, id, reference);
+        self.error(
+            line,
+            format!("{section}[{id}].{field}"),
+            format!("unknown {what} '{reference}'"),
+        );
+    }
+
+    /// References to units, buildings and technologies that don't exist.
+    fn check_references(&mut self) {
+        let data = self.data;
+        let is_unit = |id: &str| data.get_unit(id).is_some();
+        let is_building = |id: &str| data.get_building(id).is_some();
+        let is_tech = |id: &str| data.get_technology(id).is_some();
+        let is_prerequisite = |id: &str| is_tech(id) || is_building(id);
+
+        for building in &data.buildings {
+            for unit in &building.produces {
+                let at = ("buildings", building.id.as_str(), "produces");
+                self.check_reference(at, unit, "unit", is_unit(unit));
+            }
+            for required in &building.tech_required {
+                let at = ("buildings", building.id.as_str(), "tech_required");
+                self.check_reference(at, required, "tech or building", is_prerequisite(required));
+            }
+        }
+
+        for unit in &data.units {
+            for building in &unit.produced_at {
+                let at = ("units", unit.id.as_str(), "produced_at");
+                self.check_reference(at, building, "building", is_building(building));
+            }
+            for required in &unit.tech_required {
+                let at = ("units", unit.id.as_str(), "tech_required");
+                self.check_reference(at, required, "tech or building", is_prerequisite(required));
+            }
+        }
+
+        for tech in &data.technologies {
+            let id = tech.id.as_str();
+            for prerequisite in &tech.prerequisites {
+                self.check_reference(
+                    ("technologies", id, "prerequisites"),
+                    prerequisite,
+                    "prerequisite",
+                    is_prerequisite(prerequisite),
+                );
+            }
+            for other in &tech.exclusive_with {
+                let at = ("technologies", id, "exclusive_with");
+                self.check_reference(at, other, "tech", is_tech(other));
+            }
+            if let Some(building) = &tech.researched_at {
+                let at = ("technologies", id, "researched_at");
+                self.check_reference(at, building, "building", is_building(building));
+            }
+            for target in tech.effects.iter().flat_map(|e| &e.applies_to) {
+                self.check_reference(
+                    ("technologies", id, "effects.applies_to"),
+                    target,
+                    "unit or building",
+                    is_unit(target) || is_building(target),
+                );
+            }
+        }
+
+        for unit in &data.starting_units {
+            if !is_unit(&unit.type_id) {
+                let line = self.source.in_section("starting_units", &unit.type_id);
+                self.error(
+                    line,
+                    "starting_units".to_string(),
+                    format!("unknown unit '{}'", unit.type_id),
+                );
+            }
+        }
+        for building in &data.starting_buildings {
+            if !is_building(&building.type_id) {
+                let line = self
+                    .source
+                    .in_section("starting_buildings", &building.type_id);
+                self.error(
+                    line,
+                    "starting_buildings".to_string(),
+                    format!("unknown building '{}'", building.type_id),
+                );
+            }
+        }
+        for preset in &data.starting_presets {
+            let name = preset.name.as_str();
+            for unit in &preset.starting_units {
+                let at = ("starting_presets", name, "starting_units");
+                self.check_reference(at, &unit.type_id, "unit", is_unit(&unit.type_id));
+            }
+            for building in &preset.starting_buildings {
+                let at = ("starting_presets", name, "starting_buildings");
+                let exists = is_building(&building.type_id);
+                self.check_reference(at, &building.type_id, "building", exists);
+            }
+        }
+
+        for mechanic in &data.mechanics {
+            if let MechanicData::DeployOnce(deploy) = mechanic {
+                if !is_building(&deploy.building) {
+                    let line = self.source.in_section("mechanics", &deploy.building);
+                    self.error(
+                        line,
+                        "mechanics".to_string(),
+                        format!("deploy-once names unknown building '{}'", deploy.building),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Tags outside [`KNOWN_TAGS`].
+    fn check_tags(&mut self) {
+        let data = self.data;
+        let mut tagged: Vec<(&str, &str, &str, &[String])> = Vec::new();
+        for unit in &data.units {
+            tagged.push(("units", &unit.id, "tags", &unit.tags));
+            if let Some(cargo) = &unit.cargo {
+                tagged.push(("units", &unit.id, "cargo.tags", &cargo.tags));
+            }
+        }
+        for building in &data.buildings {
+            tagged.push(("buildings", &building.id, "tags", &building.tags));
+        }
+        for tech in &data.technologies {
+            for effect in &tech.effects {
+                let field = "effects.applies_to_tags";
+                tagged.push(("technologies", &tech.id, field, &effect.applies_to_tags));
+            }
+        }
+
+        for (section, id, field, tags) in tagged {
+            for tag in tags
+                .iter()
+                .filter(|tag| !KNOWN_TAGS.contains(&tag.as_str()))
+            {
+                let line = self.source.value(section, "id", id, tag);
+                self.unknown_tag(line, format!("{section}[{id}].{field}"), tag);
+            }
+        }
+
+        for mechanic in &data.mechanics {
+            let tags = match mechanic {
+                MechanicData::Rebuild(rebuild) => &rebuild.tags,
+                MechanicData::Mobility(mobility) => &mobility.tags,
+                MechanicData::DeployOnce(_) => continue,
+            };
+            for tag in tags
+                .iter()
+                .filter(|tag| !KNOWN_TAGS.contains(&tag.as_str()))
+            {
+                let line = self.source.in_section("mechanics", tag);
+                self.unknown_tag(line, "mechanics".to_string(), tag);
+            }
+        }
+    }
+
+    /// An error when `tag` looks like a typo of a known tag, otherwise a
+    /// warning that it is new.
+    fn unknown_tag(&mut self, line: Option<usize>, field: String, tag: &str) {
+        match closest_tag(tag) {
+            Some(known) => self.error(
+                line,
+                field,
+                format!("unknown tag '{tag}', did you mean '{known}'?"),
+            ),
+            None => self.push(
+                Severity::Warning,
+                line,
+                field,
+                format!(
+                    "tag '{tag}' is not in the known vocabulary; add it to KNOWN_TAGS if it is new"
+                ),
+            ),
+        }
+    }
+}
+
+/// Faction IDs claimed by two files, and unit, building and technology IDs
+/// reused by another faction.
+fn check_across_factions(checks: &mut [FactionCheck<'_>]) {
+    let mut owners: BTreeMap<(&str, &str), Vec<usize>> = BTreeMap::new();
+    for (i, check) in checks.iter().enumerate() {
+        let data = check.data;
+        let ids = data
+            .units
+            .iter()
+            .map(|u| ("units", u.id.as_str()))
+            .chain(data.buildings.iter().map(|b| ("buildings", b.id.as_str())))
+            .chain(
+                data.technologies
+                    .iter()
+                    .map(|t| ("technologies", t.id.as_str())),
+            );
+        for key in ids {
+            let files = owners.entry(key).or_default();
+            if !files.contains(&i) {
+                files.push(i);
+            }
+        }
+    }
+
+    for i in 0..checks.len() {
+        let faction = checks[i].data.id;
+        if let Some(first) = checks[..i].iter().find(|c| c.data.id == faction) {
+            let message = format!("faction {faction:?} is already defined in {}", first.file);
+            let line = checks[i].source.faction_id();
+            checks[i].error(line, "id".to_string(), message);
+        }
+    }
+
+    for ((section, id), files) in owners.into_iter().filter(|(_, files)| files.len() > 1) {
+        for &i in &files {
+            let others: Vec<_> = files
+                .iter()
+                .filter(|&&j| j != i)
+                .map(|&j| format!("{:?} ({})", checks[j].data.id, checks[j].file))
+                .collect();
+            let line = checks[i].source.field(section, id, "id");
+            checks[i].push(
+                Severity::Warning,
+                line,
+                format!("{section}[{id}]"),
+                format!("'{id}' is also defined by {}", others.join(", ")),
+            );
+        }
+    }
+}
+
+/// The known tag `tag` is most likely a typo of, if any is close enough.
+fn closest_tag(tag: &str) -> Option<&'static str> {
+    let allowed = if tag.len() <= 4 { 1 } else { 2 };
+    KNOWN_TAGS
+        .iter()
+        .map(|known| (edit_distance(tag, known), *known))
+        .filter(|(distance, _)| *distance <= allowed)
+        .min()
+        .map(|(_, known)| known)
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(ca != *cb);
+            current.push(substitute.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shipped_data() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../rts_game/assets/data")
+    }
+
+    /// A data directory holding the shipped Continuity file with `edit`
+    /// applied, and the edited text.
+    fn edited_continuity(
+        name: &str,
+        edit: impl Fn(String) -> String,
+    ) -> (std::path::PathBuf, String) {
+        let dir = std::env::temp_dir().join(format!("rts_validate_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("factions")).unwrap();
+        let original =
+            std::fs::read_to_string(shipped_data().join("factions/continuity.ron")).unwrap();
+        let edited = edit(original);
+        std::fs::write(dir.join("factions/continuity.ron"), &edited).unwrap();
+        (dir, edited)
+    }
+
+    /// Line (from 1) of the first line of `text` containing `needle`.
+    fn line_of(text: &str, needle: &str) -> usize {
+        text.lines().position(|l| l.contains(needle)).unwrap() + 1
+    }
+
+    #[test]
+    fn test_shipped_faction_data_is_valid() {
+        validate_data_directory(&shipped_data()).unwrap();
+        let report = check_data_directory(&shipped_data()).unwrap();
+        assert_eq!(report.files, 5);
+        assert!(!report.has_errors(), "{:#?}", report.diagnostics);
+    }
+
+    #[test]
+    fn test_strategy_files_are_checked() {
+        let shipped = Path::new(env!("CARGO_MANIFEST_DIR")).join("../rts_game/assets/strategies");
+        let report = check_strategy_directory(&shipped).unwrap();
+        assert_eq!(report.files, 3);
+        assert!(!report.has_errors(), "{:#?}", report.diagnostics);
+
+        let dir =
+            std::env::temp_dir().join(format!("rts_validate_strategies_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let rush = std::fs::read_to_string(shipped.join("rush.ron")).unwrap();
+        let edited = rush.replacen("aggression_percent: 90", "aggression_percent: 190", 1);
+        std::fs::write(dir.join("rush.ron"), &edited).unwrap();
+        let mut json = serde_json::to_value(Strategy::rush()).unwrap();
+        json["attack_interval"] = 0.into();
+        let json = serde_json::to_string_pretty(&json).unwrap();
+        std::fs::write(dir.join("rush.json"), &json).unwrap();
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+
+        let report = check_strategy_directory(&dir).unwrap();
+        let found: Vec<_> = report
+            .diagnostics
+            .iter()
+            .map(|d| {
+                (
+                    d.file.rsplit('/').next().unwrap(),
+                    d.field.as_deref(),
+                    d.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("broken.json", None, Some(1)),
+                (
+                    "rush.json",
+                    Some("attack_interval"),
+                    Some(line_of(&json, "\"attack_interval\"")),
+                ),
+                (
+                    "rush.ron",
+                    Some("aggression_percent"),
+                    Some(line_of(&edited, "aggression_percent:"))
+                ),
+            ]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_missing_directory_is_an_error() {
+        assert!(validate_data_directory(Path::new("does/not/exist")).is_err());
+    }
+
+    #[test]
+    fn test_semantic_problems_name_their_line_and_field() {
+        let (dir, edited) = edited_continuity("semantic", |text| {
+            text.replacen(
+                "tags: [\"infantry\", \"ground\", \"battleline\"]",
+                "tags: [\"infantyr\", \"ground\", \"battleline\"]",
+                1,
+            )
+            .replacen(
+                "produces: [\"security_team\"",
+                "produces: [\"ghost_team\"",
+                1,
+            )
+            .replacen("cost: 0,  // Starting building", "cost: -5,", 1)
+        });
+        let report = check_data_directory(&dir).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let find = |field: &str| {
+            report
+                .diagnostics
+                .iter()
+                .find(|d| d.field.as_deref() == Some(field))
+                .unwrap_or_else(|| panic!("no diagnostic for {field}: {:#?}", report.diagnostics))
+        };
+
+        let typo = find("units[security_team].tags");
+        assert_eq!(typo.severity, Severity::Error);
+        assert_eq!(typo.line, Some(line_of(&edited, "\"infantyr\"")));
+        assert!(typo.message.contains("did you mean 'infantry'"));
+
+        let produces = report
+            .diagnostics
+            .iter()
+            .find(|d| d.message == "unknown unit 'ghost_team'")
+            .unwrap();
+        assert_eq!(produces.line, Some(line_of(&edited, "\"ghost_team\"")));
+        assert!(produces.field.as_deref().unwrap().ends_with(".produces"));
+
+        let cost = find("buildings[administration_center].cost");
+        assert_eq!(cost.line, Some(line_of(&edited, "cost: -5,")));
+        assert_eq!(cost.message, "is negative (-5)");
+        assert!(report.has_errors());
+    }
+
+    #[test]
+    fn test_dangling_prerequisites_and_parse_errors() {
+        let (dir, edited) = edited_continuity("prerequisites", |text| {
+            let first = text.find("prerequisites: [").unwrap();
+            let mut text = text;
+            text.insert_str(first + "prerequisites: [".len(), "\"time_travel\", ");
+            text
+        });
+        let report = check_data_directory(&dir).unwrap();
+        let dangling = report
+            .diagnostics
+            .iter()
+            .find(|d| d.message == "unknown prerequisite 'time_travel'")
+            .unwrap();
+        assert_eq!(dangling.line, Some(line_of(&edited, "\"time_travel\"")));
+        assert!(dangling.to_string().contains(&format!(
+            ":{}: error: technologies[",
+            dangling.line.unwrap()
+        )));
+
+        std::fs::write(
+            dir.join("factions/broken.ron"),
+            "(\n    id: Continuity,\n    units: [oops],\n)",
+        )
+        .unwrap();
+        let report = check_data_directory(&dir).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        let parse = report
+            .diagnostics
+            .iter()
+            .find(|d| d.file.ends_with("broken.ron"))
+            .unwrap();
+        assert_eq!(parse.line, Some(3));
+        assert!(parse.message.starts_with("failed to parse"));
+    }
+
+    #[test]
+    fn test_extra_weapons_need_a_main_weapon_and_unique_ids() {
+        let (dir, edited) = edited_continuity("weapons", |text| {
+            text.replacen(
+                "combat: None,",
+                "combat: None,\n            weapons: [(id: \"mg\", damage: 4, range: 21474836480, attack_cooldown: 6), (id: \"mg\", damage: 4, range: 21474836480, attack_cooldown: 6)],",
+                1,
+            )
+        });
+        let report = check_data_directory(&dir).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let weapons: Vec<_> = report
+            .diagnostics
+            .iter()
+            .filter(|d| d.field.as_deref() == Some("units[collection_vehicle].weapons"))
+            .collect();
+        assert_eq!(weapons.len(), 2);
+        assert!(weapons.iter().all(|d| d.severity == Severity::Error));
+        assert!(weapons
+            .iter()
+            .all(|d| d.line == Some(line_of(&edited, "weapons: [(id"))));
+        assert!(weapons[1].message.contains("'mg' is defined twice"));
+    }
+
+    #[test]
+    fn test_ids_reused_across_factions_are_warnings() {
+        let report = check_data_directory(&shipped_data()).unwrap();
+        let reused: Vec<_> = report
+            .diagnostics
+            .iter()
+            .filter(|d| d.field.as_deref() == Some("technologies[emergency_protocols]"))
+            .collect();
+        assert_eq!(reused.len(), 2);
+        assert!(reused.iter().all(|d| d.severity == Severity::Warning));
+        assert!(reused.iter().all(|d| d.line.is_some()));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["errors"], 0);
+        assert_eq!(json["diagnostics"][0]["severity"], "warning");
+    }
+
+    #[test]
+    fn test_closest_tag_only_suggests_near_misses() {
+        assert_eq!(closest_tag("vehicel"), Some("vehicle"));
+        assert_eq!(closest_tag("arr"), Some("air"));
+        assert_eq!(closest_tag("teleporter"), None);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
 }